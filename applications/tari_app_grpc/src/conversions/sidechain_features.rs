@@ -22,20 +22,27 @@
 
 use std::convert::TryFrom;
 
+use tari_common_types::types::FixedHash;
 use tari_core::transactions::transaction_components::SideChainFeatures;
 
 use crate::tari_rpc as grpc;
 
 impl From<SideChainFeatures> for grpc::SideChainFeatures {
-    fn from(_value: SideChainFeatures) -> Self {
-        Self {}
+    fn from(value: SideChainFeatures) -> Self {
+        Self {
+            sidechain_id: value.sidechain_id.to_vec(),
+            committee_signature: value.committee_signature,
+        }
     }
 }
 
 impl TryFrom<grpc::SideChainFeatures> for SideChainFeatures {
     type Error = String;
 
-    fn try_from(_features: grpc::SideChainFeatures) -> Result<Self, Self::Error> {
-        Ok(Self {})
+    fn try_from(features: grpc::SideChainFeatures) -> Result<Self, Self::Error> {
+        let sidechain_id = FixedHash::try_from(features.sidechain_id).map_err(|err| err.to_string())?;
+        let features = SideChainFeatures::new(sidechain_id).with_committee_signature(features.committee_signature);
+        features.validate().map_err(|err| err.to_string())?;
+        Ok(features)
     }
 }
@@ -102,6 +102,7 @@ impl From<TransactionStatus> for grpc::TransactionStatus {
             FauxUnconfirmed => grpc::TransactionStatus::FauxUnconfirmed,
             FauxConfirmed => grpc::TransactionStatus::FauxConfirmed,
             Queued => grpc::TransactionStatus::Queued,
+            DoubleSpent => grpc::TransactionStatus::DoubleSpent,
         }
     }
 }
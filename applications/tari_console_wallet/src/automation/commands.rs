@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    collections::HashMap,
     convert::TryInto,
     fs,
     fs::File,
@@ -36,30 +37,30 @@ use futures::FutureExt;
 use log::*;
 use serde::{de::DeserializeOwned, Serialize};
 use sha2::Sha256;
-use strum_macros::{Display, EnumIter, EnumString};
 use tari_app_grpc::authentication::salted_password::create_salted_hashed_password;
 use tari_common_types::{
-    emoji::EmojiId,
     transaction::TxId,
-    types::{CommitmentFactory, FixedHash, PublicKey},
+    types::{CommitmentFactory, PublicKey},
 };
 use tari_comms::{
     connectivity::{ConnectivityEvent, ConnectivityRequester},
     multiaddr::Multiaddr,
-    types::CommsPublicKey,
 };
-use tari_comms_dht::{envelope::NodeDestination, DhtDiscoveryRequester};
 use tari_core::transactions::{
     tari_amount::{uT, MicroTari, Tari},
-    transaction_components::{OutputFeatures, TransactionOutput, UnblindedOutput},
+    transaction_components::UnblindedOutput,
 };
 use tari_utilities::{hex::Hex, ByteArray};
 use tari_wallet::{
+    command_executor::{CommandExecutor, CommandResult, PaymentKind, WalletCommand},
     connectivity_service::WalletConnectivityInterface,
     error::WalletError,
     key_manager_service::NextKeyResult,
-    output_manager_service::handle::OutputManagerHandle,
-    transaction_service::handle::{TransactionEvent, TransactionServiceHandle},
+    transaction_service::{
+        handle::{TransactionEvent, TransactionServiceHandle},
+        storage::models::CompletedTransaction,
+    },
+    CommandExecutorSqlite,
     TransactionStage,
     WalletConfig,
     WalletSqlite,
@@ -77,160 +78,15 @@ use crate::{
 
 pub const LOG_TARGET: &str = "wallet::automation::commands";
 
-/// Enum representing commands used by the wallet
-#[derive(Clone, PartialEq, Debug, Display, EnumIter, EnumString)]
-#[strum(serialize_all = "kebab_case")]
-pub enum WalletCommand {
-    GetBalance,
-    SendTari,
-    SendOneSided,
-    MakeItRain,
-    CoinSplit,
-    DiscoverPeer,
-    Whois,
-    ExportUtxos,
-    ExportSpentUtxos,
-    CountUtxos,
-    SetBaseNode,
-    SetCustomBaseNode,
-    ClearCustomBaseNode,
-    InitShaAtomicSwap,
-    FinaliseShaAtomicSwap,
-    ClaimShaAtomicSwapRefund,
-    RegisterAsset,
-    MintTokens,
-    CreateInitialCheckpoint,
-    RevalidateWalletDb,
-}
-
 #[derive(Debug)]
 pub struct SentTransaction {}
 
-/// Send a normal negotiated transaction to a recipient
-pub async fn send_tari(
-    mut wallet_transaction_service: TransactionServiceHandle,
-    fee_per_gram: u64,
-    amount: MicroTari,
-    dest_pubkey: PublicKey,
-    message: String,
-) -> Result<TxId, CommandError> {
-    wallet_transaction_service
-        .send_transaction(
-            dest_pubkey,
-            amount,
-            OutputFeatures::default(),
-            fee_per_gram * uT,
-            message,
-        )
-        .await
-        .map_err(CommandError::TransactionServiceError)
-}
-
-/// publishes a tari-SHA atomic swap HTLC transaction
-pub async fn init_sha_atomic_swap(
-    mut wallet_transaction_service: TransactionServiceHandle,
-    fee_per_gram: u64,
-    amount: MicroTari,
-    dest_pubkey: PublicKey,
-    message: String,
-) -> Result<(TxId, PublicKey, TransactionOutput), CommandError> {
-    let (tx_id, pre_image, output) = wallet_transaction_service
-        .send_sha_atomic_swap_transaction(dest_pubkey, amount, fee_per_gram * uT, message)
-        .await
-        .map_err(CommandError::TransactionServiceError)?;
-    Ok((tx_id, pre_image, output))
-}
-
-/// claims a tari-SHA atomic swap HTLC transaction
-pub async fn finalise_sha_atomic_swap(
-    mut output_service: OutputManagerHandle,
-    mut transaction_service: TransactionServiceHandle,
-    output_hash: FixedHash,
-    pre_image: PublicKey,
-    fee_per_gram: MicroTari,
-    message: String,
-) -> Result<TxId, CommandError> {
-    let (tx_id, _fee, amount, tx) = output_service
-        .create_claim_sha_atomic_swap_transaction(output_hash, pre_image, fee_per_gram)
-        .await?;
-    transaction_service
-        .submit_transaction(tx_id, tx, amount, message)
-        .await?;
-    Ok(tx_id)
-}
-
-/// claims a HTLC refund transaction
-pub async fn claim_htlc_refund(
-    mut output_service: OutputManagerHandle,
-    mut transaction_service: TransactionServiceHandle,
-    output_hash: FixedHash,
-    fee_per_gram: MicroTari,
-    message: String,
-) -> Result<TxId, CommandError> {
-    let (tx_id, _fee, amount, tx) = output_service
-        .create_htlc_refund_transaction(output_hash, fee_per_gram)
-        .await?;
-    transaction_service
-        .submit_transaction(tx_id, tx, amount, message)
-        .await?;
-    Ok(tx_id)
-}
-
-/// Send a one-sided transaction to a recipient
-pub async fn send_one_sided(
-    mut wallet_transaction_service: TransactionServiceHandle,
-    fee_per_gram: u64,
-    amount: MicroTari,
-    dest_pubkey: PublicKey,
-    message: String,
-) -> Result<TxId, CommandError> {
-    wallet_transaction_service
-        .send_one_sided_transaction(
-            dest_pubkey,
-            amount,
-            OutputFeatures::default(),
-            fee_per_gram * uT,
-            message,
-        )
-        .await
-        .map_err(CommandError::TransactionServiceError)
-}
-
-pub async fn send_one_sided_to_stealth_address(
-    mut wallet_transaction_service: TransactionServiceHandle,
-    fee_per_gram: u64,
-    amount: MicroTari,
-    dest_pubkey: PublicKey,
-    message: String,
-) -> Result<TxId, CommandError> {
-    wallet_transaction_service
-        .send_one_sided_to_stealth_address_transaction(
-            dest_pubkey,
-            amount,
-            OutputFeatures::default(),
-            fee_per_gram * uT,
-            message,
-        )
-        .await
-        .map_err(CommandError::TransactionServiceError)
-}
-
-pub async fn coin_split(
-    amount_per_split: MicroTari,
-    num_splits: usize,
-    fee_per_gram: MicroTari,
-    message: String,
-    output_service: &mut OutputManagerHandle,
-    transaction_service: &mut TransactionServiceHandle,
-) -> Result<TxId, CommandError> {
-    let (tx_id, tx, amount) = output_service
-        .create_coin_split(vec![], amount_per_split, num_splits as usize, fee_per_gram)
-        .await?;
-    transaction_service
-        .submit_transaction(tx_id, tx, amount, message)
-        .await?;
-
-    Ok(tx_id)
+/// Executes a command that sends a transaction and returns the id of the transaction
+async fn send_transaction(executor: &mut CommandExecutorSqlite, command: WalletCommand) -> Result<TxId, CommandError> {
+    match executor.execute(command).await? {
+        CommandResult::TransactionSent(tx_id) => Ok(tx_id),
+        result => Err(CommandError::General(format!("Unexpected command result {:?}", result))),
+    }
 }
 
 async fn wait_for_comms(connectivity_requester: &ConnectivityRequester) -> Result<(), CommandError> {
@@ -257,33 +113,30 @@ async fn wait_for_comms(connectivity_requester: &ConnectivityRequester) -> Resul
 }
 
 async fn set_base_node_peer(
-    mut wallet: WalletSqlite,
+    executor: &mut CommandExecutorSqlite,
     public_key: PublicKey,
     address: Multiaddr,
-) -> Result<(CommsPublicKey, Multiaddr), CommandError> {
+) -> Result<(), CommandError> {
     println!("Setting base node peer...");
     println!("{}::{}", public_key, address);
-    wallet.set_base_node_peer(public_key.clone(), address.clone()).await?;
-    Ok((public_key, address))
+    executor
+        .execute(WalletCommand::SetBaseNode { public_key, address })
+        .await?;
+    Ok(())
 }
 
 pub async fn discover_peer(
-    mut dht_service: DhtDiscoveryRequester,
+    executor: &mut CommandExecutorSqlite,
     dest_public_key: PublicKey,
 ) -> Result<(), CommandError> {
     let start = Instant::now();
     println!("🌎 Peer discovery started.");
-    match dht_service
-        .discover_peer(
-            dest_public_key.clone(),
-            NodeDestination::PublicKey(Box::new(dest_public_key)),
-        )
-        .await
-    {
-        Ok(peer) => {
+    match executor.execute(WalletCommand::DiscoverPeer(dest_public_key)).await {
+        Ok(CommandResult::PeerDiscovered(peer)) => {
             println!("⚡️ Discovery succeeded in {}ms.", start.elapsed().as_millis());
             println!("{}", peer);
         },
+        Ok(result) => return Err(CommandError::General(format!("Unexpected command result {:?}", result))),
         Err(err) => {
             println!("💀 Discovery failed: '{:?}'", err);
         },
@@ -294,7 +147,7 @@ pub async fn discover_peer(
 
 #[allow(clippy::too_many_lines)]
 pub async fn make_it_rain(
-    wallet_transaction_service: TransactionServiceHandle,
+    executor: CommandExecutorSqlite,
     fee_per_gram: u64,
     transactions_per_second: u32,
     duration: Duration,
@@ -350,7 +203,7 @@ pub async fn make_it_rain(
                     transaction_type
                 );
                 let loop_started_at = Instant::now();
-                let tx_service = wallet_transaction_service.clone();
+                let mut executor = executor.clone();
                 // Transaction details
                 let amount = start_amount + increase_amount * (i as u64);
 
@@ -369,17 +222,14 @@ pub async fn make_it_rain(
                 tokio::task::spawn(async move {
                     let spawn_start = Instant::now();
                     // Send transaction
-                    let tx_id = match transaction_type {
-                        MakeItRainTransactionType::Interactive => {
-                            send_tari(tx_service, fee, amount, pk.clone(), msg.clone()).await
-                        },
-                        MakeItRainTransactionType::OneSided => {
-                            send_one_sided(tx_service, fee, amount, pk.clone(), msg.clone()).await
-                        },
-                        MakeItRainTransactionType::StealthOneSided => {
-                            send_one_sided_to_stealth_address(tx_service, fee, amount, pk.clone(), msg.clone()).await
-                        },
-                    };
+                    let tx_id = send_transaction(&mut executor, WalletCommand::SendTari {
+                        kind: transaction_type.into(),
+                        destination: pk,
+                        amount,
+                        fee_per_gram: fee * uT,
+                        message: msg,
+                    })
+                    .await;
                     let submit_time = Instant::now();
 
                     if let Err(e) = sender_clone
@@ -492,13 +342,15 @@ pub async fn monitor_transactions(
                 TransactionEvent::TransactionMinedUnconfirmed {
                     tx_id,
                     num_confirmations,
+                    confirmations_required,
                     is_valid,
                 } if tx_ids.contains(tx_id) => {
                     debug!(
                         target: LOG_TARGET,
-                        "tx mined unconfirmed event for tx_id: {}, confirmations: {}, is_valid: {}",
+                        "tx mined unconfirmed event for tx_id: {}, confirmations: {}/{}, is_valid: {}",
                         *tx_id,
                         num_confirmations,
+                        confirmations_required,
                         is_valid
                     );
                     if wait_stage == TransactionStage::MinedUnconfirmed {
@@ -547,10 +399,10 @@ pub async fn command_runner(
 ) -> Result<(), CommandError> {
     let wait_stage = config.command_send_wait_stage;
 
-    let mut transaction_service = wallet.transaction_service.clone();
-    let mut output_service = wallet.output_manager_service.clone();
-    let dht_service = wallet.dht_service.discovery_service_requester().clone();
+    let transaction_service = wallet.transaction_service.clone();
     let connectivity_requester = wallet.comms.connectivity();
+    let mut executor = CommandExecutor::new(wallet.clone());
+    let fee_per_gram = config.fee_per_gram * uT;
     let mut online = false;
 
     let mut tx_ids = Vec::new();
@@ -564,10 +416,11 @@ pub async fn command_runner(
         println!("\n{}. {:?}\n", idx + 1, parsed);
         use crate::cli::CliCommands::*;
         match parsed {
-            GetBalance => match output_service.clone().get_balance().await {
-                Ok(balance) => {
+            GetBalance => match executor.execute(WalletCommand::GetBalance).await {
+                Ok(CommandResult::Balance(balance)) => {
                     println!("{}", balance);
                 },
+                Ok(_) => {},
                 Err(e) => eprintln!("GetBalance error! {}", e),
             },
             DiscoverPeer(args) => {
@@ -575,40 +428,40 @@ pub async fn command_runner(
                     wait_for_comms(&connectivity_requester).await?;
                     online = true;
                 }
-                discover_peer(dht_service.clone(), args.dest_public_key.into()).await?
+                discover_peer(&mut executor, args.dest_public_key.into()).await?
             },
             SendTari(args) => {
-                let tx_id = send_tari(
-                    transaction_service.clone(),
-                    config.fee_per_gram,
-                    args.amount,
-                    args.destination.into(),
-                    args.message,
-                )
+                let tx_id = send_transaction(&mut executor, WalletCommand::SendTari {
+                    kind: PaymentKind::Interactive,
+                    destination: args.destination.into(),
+                    amount: args.amount,
+                    fee_per_gram,
+                    message: args.message,
+                })
                 .await?;
                 debug!(target: LOG_TARGET, "send-tari tx_id {}", tx_id);
                 tx_ids.push(tx_id);
             },
             SendOneSided(args) => {
-                let tx_id = send_one_sided(
-                    transaction_service.clone(),
-                    config.fee_per_gram,
-                    args.amount,
-                    args.destination.into(),
-                    args.message,
-                )
+                let tx_id = send_transaction(&mut executor, WalletCommand::SendTari {
+                    kind: PaymentKind::OneSided,
+                    destination: args.destination.into(),
+                    amount: args.amount,
+                    fee_per_gram,
+                    message: args.message,
+                })
                 .await?;
                 debug!(target: LOG_TARGET, "send-one-sided tx_id {}", tx_id);
                 tx_ids.push(tx_id);
             },
             SendOneSidedToStealthAddress(args) => {
-                let tx_id = send_one_sided_to_stealth_address(
-                    transaction_service.clone(),
-                    config.fee_per_gram,
-                    args.amount,
-                    args.destination.into(),
-                    args.message,
-                )
+                let tx_id = send_transaction(&mut executor, WalletCommand::SendTari {
+                    kind: PaymentKind::OneSidedToStealthAddress,
+                    destination: args.destination.into(),
+                    amount: args.amount,
+                    fee_per_gram,
+                    message: args.message,
+                })
                 .await?;
                 debug!(target: LOG_TARGET, "send-one-sided-to-stealth-address tx_id {}", tx_id);
                 tx_ids.push(tx_id);
@@ -616,7 +469,7 @@ pub async fn command_runner(
             MakeItRain(args) => {
                 let transaction_type = args.transaction_type();
                 make_it_rain(
-                    transaction_service.clone(),
+                    executor.clone(),
                     config.fee_per_gram,
                     args.transactions_per_second,
                     args.duration,
@@ -630,84 +483,66 @@ pub async fn command_runner(
                 .await?;
             },
             CoinSplit(args) => {
-                let tx_id = coin_split(
-                    args.amount_per_split,
-                    args.num_splits,
-                    args.fee_per_gram,
-                    args.message,
-                    &mut output_service,
-                    &mut transaction_service.clone(),
-                )
+                let tx_id = send_transaction(&mut executor, WalletCommand::CoinSplit {
+                    amount_per_split: args.amount_per_split,
+                    num_splits: args.num_splits,
+                    fee_per_gram: args.fee_per_gram,
+                    message: args.message,
+                })
                 .await?;
                 tx_ids.push(tx_id);
                 println!("Coin split succeeded");
             },
             Whois(args) => {
-                let public_key = args.public_key.into();
-                let emoji_id = EmojiId::from_pubkey(&public_key);
-
-                println!("Public Key: {}", public_key.to_hex());
-                println!("Emoji ID  : {}", emoji_id);
+                if let CommandResult::Identity { public_key, emoji_id } =
+                    executor.execute(WalletCommand::Whois(args.public_key.into())).await?
+                {
+                    println!("Public Key: {}", public_key.to_hex());
+                    println!("Emoji ID  : {}", emoji_id);
+                }
             },
             ExportUtxos(args) => {
-                let utxos = output_service.get_unspent_outputs().await?;
-                let count = utxos.len();
-                let sum: MicroTari = utxos.iter().map(|utxo| utxo.value).sum();
-                if let Some(file) = args.output_file {
-                    write_utxos_to_csv_file(utxos, file)?;
-                } else {
-                    for (i, utxo) in utxos.iter().enumerate() {
-                        println!("{}. Value: {} {}", i + 1, utxo.value, utxo.features);
-                    }
+                if let CommandResult::Outputs(utxos) = executor.execute(WalletCommand::GetUnspentOutputs).await? {
+                    export_utxos(utxos, args.output_file)?;
                 }
-                println!("Total number of UTXOs: {}", count);
-                println!("Total value of UTXOs: {}", sum);
             },
             ExportSpentUtxos(args) => {
-                let utxos = output_service.get_spent_outputs().await?;
-                let count = utxos.len();
-                let sum: MicroTari = utxos.iter().map(|utxo| utxo.value).sum();
-                if let Some(file) = args.output_file {
-                    write_utxos_to_csv_file(utxos, file)?;
-                } else {
-                    for (i, utxo) in utxos.iter().enumerate() {
-                        println!("{}. Value: {} {}", i + 1, utxo.value, utxo.features);
-                    }
+                if let CommandResult::Outputs(utxos) = executor.execute(WalletCommand::GetSpentOutputs).await? {
+                    export_utxos(utxos, args.output_file)?;
                 }
-                println!("Total number of UTXOs: {}", count);
-                println!("Total value of UTXOs: {}", sum);
             },
             CountUtxos => {
-                let utxos = output_service.get_unspent_outputs().await?;
-                let count = utxos.len();
-                let values: Vec<MicroTari> = utxos.iter().map(|utxo| utxo.value).collect();
-                let sum: MicroTari = values.iter().sum();
-                println!("Total number of UTXOs: {}", count);
-                println!("Total value of UTXOs : {}", sum);
-                if let Some(min) = values.iter().min() {
-                    println!("Minimum value UTXO   : {}", min);
-                }
-                if count > 0 {
-                    let average = f64::from(sum) / count as f64;
-                    let average = Tari::from(MicroTari(average.round() as u64));
-                    println!("Average value UTXO   : {}", average);
-                }
-                if let Some(max) = values.iter().max() {
-                    println!("Maximum value UTXO   : {}", max);
+                if let CommandResult::UtxoStats(stats) = executor.execute(WalletCommand::CountUtxos).await? {
+                    println!("Total number of UTXOs: {}", stats.count);
+                    println!("Total value of UTXOs : {}", stats.total);
+                    if let Some(min) = stats.min {
+                        println!("Minimum value UTXO   : {}", min);
+                    }
+                    if let Some(average) = stats.average {
+                        println!("Average value UTXO   : {}", Tari::from(average));
+                    }
+                    if let Some(max) = stats.max {
+                        println!("Maximum value UTXO   : {}", max);
+                    }
                 }
             },
+            CoinbaseStats => match executor.execute(WalletCommand::GetCoinbaseStats).await {
+                Ok(CommandResult::CoinbaseStats(stats)) => println!("{}", stats),
+                Ok(_) => {},
+                Err(e) => eprintln!("CoinbaseStats error! {}", e),
+            },
             SetBaseNode(args) => {
-                set_base_node_peer(wallet.clone(), args.public_key.into(), args.address).await?;
+                set_base_node_peer(&mut executor, args.public_key.into(), args.address).await?;
             },
             SetCustomBaseNode(args) => {
-                let (public_key, net_address) =
-                    set_base_node_peer(wallet.clone(), args.public_key.into(), args.address).await?;
+                let public_key = args.public_key.into();
+                set_base_node_peer(&mut executor, public_key.clone(), args.address.clone()).await?;
                 wallet
                     .db
                     .set_client_key_value(CUSTOM_BASE_NODE_PUBLIC_KEY_KEY.to_string(), public_key.to_string())?;
                 wallet
                     .db
-                    .set_client_key_value(CUSTOM_BASE_NODE_ADDRESS_KEY.to_string(), net_address.to_string())?;
+                    .set_client_key_value(CUSTOM_BASE_NODE_ADDRESS_KEY.to_string(), args.address.to_string())?;
                 println!("Custom base node peer saved in wallet database.");
             },
             ClearCustomBaseNode => {
@@ -718,57 +553,49 @@ pub async fn command_runner(
                 println!("Custom base node peer cleared from wallet database.");
             },
             InitShaAtomicSwap(args) => {
-                let (tx_id, pre_image, output) = init_sha_atomic_swap(
-                    transaction_service.clone(),
-                    config.fee_per_gram,
-                    args.amount,
-                    args.destination.into(),
-                    args.message,
-                )
-                .await?;
-                debug!(target: LOG_TARGET, "tari HTLC tx_id {}", tx_id);
-                let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
-                println!("pre_image hex: {}", pre_image.to_hex());
-                println!("pre_image hash: {}", hash.to_hex());
-                println!("Output hash: {}", output.hash().to_hex());
-                tx_ids.push(tx_id);
+                let command = WalletCommand::InitShaAtomicSwap {
+                    destination: args.destination.into(),
+                    amount: args.amount,
+                    fee_per_gram,
+                    message: args.message,
+                };
+                if let CommandResult::ShaAtomicSwapInitiated {
+                    tx_id,
+                    pre_image,
+                    output_hash,
+                } = executor.execute(command).await?
+                {
+                    debug!(target: LOG_TARGET, "tari HTLC tx_id {}", tx_id);
+                    let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
+                    println!("pre_image hex: {}", pre_image.to_hex());
+                    println!("pre_image hash: {}", hash.to_hex());
+                    println!("Output hash: {}", output_hash.to_hex());
+                    tx_ids.push(tx_id);
+                }
             },
             FinaliseShaAtomicSwap(args) => {
-                let hash = args.output_hash[0].clone().try_into()?;
-                let tx_id = finalise_sha_atomic_swap(
-                    output_service.clone(),
-                    transaction_service.clone(),
-                    hash,
-                    args.pre_image.into(),
-                    config.fee_per_gram.into(),
-                    args.message,
-                )
+                let tx_id = send_transaction(&mut executor, WalletCommand::FinaliseShaAtomicSwap {
+                    output_hash: args.output_hash[0].clone().try_into()?,
+                    pre_image: args.pre_image.into(),
+                    fee_per_gram,
+                    message: args.message,
+                })
                 .await?;
                 debug!(target: LOG_TARGET, "claiming tari HTLC tx_id {}", tx_id);
                 tx_ids.push(tx_id);
             },
             ClaimShaAtomicSwapRefund(args) => {
-                let hash = args.output_hash[0].clone().try_into()?;
-                let tx_id = claim_htlc_refund(
-                    output_service.clone(),
-                    transaction_service.clone(),
-                    hash,
-                    config.fee_per_gram.into(),
-                    args.message,
-                )
+                let tx_id = send_transaction(&mut executor, WalletCommand::ClaimShaAtomicSwapRefund {
+                    output_hash: args.output_hash[0].clone().try_into()?,
+                    fee_per_gram,
+                    message: args.message,
+                })
                 .await?;
                 debug!(target: LOG_TARGET, "claiming tari HTLC tx_id {}", tx_id);
                 tx_ids.push(tx_id);
             },
             RevalidateWalletDb => {
-                output_service
-                    .revalidate_all_outputs()
-                    .await
-                    .map_err(CommandError::OutputManagerError)?;
-                transaction_service
-                    .revalidate_all_transactions()
-                    .await
-                    .map_err(CommandError::TransactionServiceError)?;
+                executor.execute(WalletCommand::RevalidateWalletDb).await?;
             },
             HashGrpcPassword(args) => {
                 let (username, password) = config
@@ -789,6 +616,34 @@ pub async fn command_runner(
                     );
                 }
             },
+            TagTransaction(args) => {
+                executor
+                    .execute(WalletCommand::TagTransaction {
+                        tx_id: args.tx_id.into(),
+                        tag: args.tag.clone(),
+                    })
+                    .await?;
+                println!("Transaction {} tagged with '{}'", args.tx_id, args.tag);
+            },
+            UntagTransaction(args) => {
+                executor
+                    .execute(WalletCommand::UntagTransaction {
+                        tx_id: args.tx_id.into(),
+                        tag: args.tag.clone(),
+                    })
+                    .await?;
+                println!("Tag '{}' removed from transaction {}", args.tag, args.tx_id);
+            },
+            ExportTransactions(args) => {
+                if let CommandResult::Transactions { transactions, tags } = executor
+                    .execute(WalletCommand::GetCompletedTransactions { tag: args.tag })
+                    .await?
+                {
+                    let count = transactions.len();
+                    write_transactions_to_csv_file(&transactions, &tags, args.output_file)?;
+                    println!("Total number of transactions exported: {}", count);
+                }
+            },
         }
     }
 
@@ -830,6 +685,21 @@ pub async fn command_runner(
     Ok(())
 }
 
+fn export_utxos(utxos: Vec<UnblindedOutput>, output_file: Option<PathBuf>) -> Result<(), CommandError> {
+    let count = utxos.len();
+    let sum: MicroTari = utxos.iter().map(|utxo| utxo.value).sum();
+    if let Some(file) = output_file {
+        write_utxos_to_csv_file(utxos, file)?;
+    } else {
+        for (i, utxo) in utxos.iter().enumerate() {
+            println!("{}. Value: {} {}", i + 1, utxo.value, utxo.features);
+        }
+    }
+    println!("Total number of UTXOs: {}", count);
+    println!("Total value of UTXOs: {}", sum);
+    Ok(())
+}
+
 fn write_utxos_to_csv_file(utxos: Vec<UnblindedOutput>, file_path: PathBuf) -> Result<(), CommandError> {
     let factory = CommitmentFactory::default();
     let file = File::create(file_path).map_err(|e| CommandError::CSVFile(e.to_string()))?;
@@ -864,6 +734,39 @@ fn write_utxos_to_csv_file(utxos: Vec<UnblindedOutput>, file_path: PathBuf) -> R
     }
     Ok(())
 }
+
+fn write_transactions_to_csv_file(
+    transactions: &[CompletedTransaction],
+    tags: &HashMap<TxId, Vec<String>>,
+    file_path: PathBuf,
+) -> Result<(), CommandError> {
+    let file = File::create(file_path).map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    let mut csv_file = LineWriter::new(file);
+    writeln!(
+        csv_file,
+        r##""tx_id","timestamp","direction","status","amount","fee","source_public_key","destination_public_key","message","tags""##
+    )
+    .map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    for tx in transactions {
+        writeln!(
+            csv_file,
+            r##""{}","{}","{}","{}","{}","{}","{}","{}","{}","{}""##,
+            tx.tx_id,
+            tx.timestamp,
+            tx.direction,
+            tx.status,
+            tx.amount.0,
+            tx.fee.0,
+            tx.source_public_key.to_hex(),
+            tx.destination_public_key.to_hex(),
+            tx.message.replace('"', "\"\""),
+            tags.get(&tx.tx_id).map(|t| t.join(";")).unwrap_or_default(),
+        )
+        .map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    }
+    Ok(())
+}
+
 #[allow(dead_code)]
 fn write_json_file<P: AsRef<Path>, T: Serialize>(path: P, data: &T) -> Result<(), CommandError> {
     fs::create_dir_all(path.as_ref().parent().unwrap()).map_err(|e| CommandError::JsonFile(e.to_string()))?;
@@ -36,6 +36,7 @@ use tari_utilities::{
     hex::{Hex, HexError},
     SafePassword,
 };
+use tari_wallet::command_executor::PaymentKind;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -124,6 +125,7 @@ pub enum CliCommands {
     ExportUtxos(ExportUtxosArgs),
     ExportSpentUtxos(ExportUtxosArgs),
     CountUtxos,
+    CoinbaseStats,
     SetBaseNode(SetBaseNodeArgs),
     SetCustomBaseNode(SetBaseNodeArgs),
     ClearCustomBaseNode,
@@ -132,6 +134,9 @@ pub enum CliCommands {
     ClaimShaAtomicSwapRefund(ClaimShaAtomicSwapRefundArgs),
     RevalidateWalletDb,
     HashGrpcPassword(HashPasswordArgs),
+    TagTransaction(TagTransactionArgs),
+    UntagTransaction(TagTransactionArgs),
+    ExportTransactions(ExportTransactionsArgs),
 }
 
 #[derive(Debug, Args, Clone)]
@@ -193,6 +198,16 @@ impl Display for MakeItRainTransactionType {
     }
 }
 
+impl From<MakeItRainTransactionType> for PaymentKind {
+    fn from(transaction_type: MakeItRainTransactionType) -> Self {
+        match transaction_type {
+            MakeItRainTransactionType::Interactive => PaymentKind::Interactive,
+            MakeItRainTransactionType::OneSided => PaymentKind::OneSided,
+            MakeItRainTransactionType::StealthOneSided => PaymentKind::OneSidedToStealthAddress,
+        }
+    }
+}
+
 fn parse_start_time(arg: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
     let mut start_time = Utc::now();
     if !arg.is_empty() && arg.to_uppercase() != "NOW" {
@@ -227,6 +242,21 @@ pub struct ExportUtxosArgs {
     pub output_file: Option<PathBuf>,
 }
 
+#[derive(Debug, Args, Clone)]
+pub struct TagTransactionArgs {
+    pub tx_id: u64,
+    pub tag: String,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct ExportTransactionsArgs {
+    #[clap(short, long)]
+    pub output_file: PathBuf,
+    /// Only export the transactions with this tag
+    #[clap(short, long)]
+    pub tag: Option<String>,
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct SetBaseNodeArgs {
     pub public_key: UniPublicKey,
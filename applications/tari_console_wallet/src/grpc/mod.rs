@@ -3,13 +3,16 @@
 
 mod wallet_grpc_server;
 
-use tari_app_grpc::tari_rpc::TransactionEvent;
+use std::fmt::Display;
+
+use prost::Message;
+use tari_app_grpc::tari_rpc::{TransactionEvent, WalletErrorDetails};
 use tari_utilities::hex::Hex;
-use tari_wallet::transaction_service::storage::models::{
-    CompletedTransaction,
-    InboundTransaction,
-    OutboundTransaction,
+use tari_wallet::{
+    error::{ClassifyError, ErrorKind},
+    transaction_service::storage::models::{CompletedTransaction, InboundTransaction, OutboundTransaction},
 };
+use tonic::{Code, Status};
 
 pub use self::wallet_grpc_server::*;
 
@@ -59,3 +62,29 @@ pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -
         },
     }
 }
+
+/// The classification of a wallet error, for clients that decide whether and when to retry a call
+pub fn wallet_error_details<E: ClassifyError>(err: &E) -> WalletErrorDetails {
+    let class = err.class();
+    WalletErrorDetails {
+        kind: class.kind as i32,
+        suggested_action: class.action as i32,
+        transient: class.is_transient(),
+    }
+}
+
+/// Converts a wallet error to a status with the code that matches its kind, and its [WalletErrorDetails] encoded in
+/// the details of the status
+pub fn wallet_error_status<E: ClassifyError + Display>(err: E) -> Status {
+    let details = wallet_error_details(&err);
+    let code = match err.class().kind {
+        ErrorKind::InvalidArgument => Code::InvalidArgument,
+        ErrorKind::NotFound => Code::NotFound,
+        ErrorKind::Conflict => Code::AlreadyExists,
+        ErrorKind::InsufficientFunds | ErrorKind::Rejected => Code::FailedPrecondition,
+        ErrorKind::Connectivity | ErrorKind::Unavailable => Code::Unavailable,
+        ErrorKind::Unauthorized => Code::Unauthenticated,
+        ErrorKind::Storage | ErrorKind::Internal => Code::Internal,
+    };
+    Status::with_details(code, err.to_string(), details.encode_to_vec().into())
+}
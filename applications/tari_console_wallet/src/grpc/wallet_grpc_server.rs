@@ -87,23 +87,27 @@ use tari_core::transactions::{
 };
 use tari_utilities::{hex::Hex, ByteArray};
 use tari_wallet::{
+    command_executor::{CommandExecutor, CommandResult, WalletCommand},
     connectivity_service::{OnlineStatus, WalletConnectivityInterface},
-    output_manager_service::handle::OutputManagerHandle,
+    output_manager_service::{handle::OutputManagerHandle, service::Balance},
     transaction_service::{
         handle::TransactionServiceHandle,
         storage::models::{self, WalletTransaction},
     },
+    CommandExecutorSqlite,
     WalletSqlite,
 };
 use tokio::{sync::broadcast, task};
 use tonic::{Request, Response, Status};
 
 use crate::{
-    grpc::{convert_to_transaction_event, TransactionWrapper},
+    grpc::{convert_to_transaction_event, wallet_error_details, wallet_error_status, TransactionWrapper},
     notifier::{CANCELLED, CONFIRMATION, MINED, NEW_BLOCK_MINED, QUEUED, RECEIVED, SENT},
 };
 
 const LOG_TARGET: &str = "wallet::ui::grpc";
+/// The caller that the requests of the gRPC server to the wallet services are limited as
+const REQUEST_CALLER: &str = "grpc";
 
 async fn send_transaction_event(
     transaction_event: TransactionEvent,
@@ -120,6 +124,14 @@ async fn send_transaction_event(
     }
 }
 
+fn convert_balance(balance: Balance) -> GetBalanceResponse {
+    GetBalanceResponse {
+        available_balance: balance.available_balance.0,
+        pending_incoming_balance: balance.pending_incoming_balance.0,
+        pending_outgoing_balance: balance.pending_outgoing_balance.0,
+    }
+}
+
 pub struct WalletGrpcServer {
     wallet: WalletSqlite,
 }
@@ -130,11 +142,19 @@ impl WalletGrpcServer {
     }
 
     fn get_transaction_service(&self) -> TransactionServiceHandle {
-        self.wallet.transaction_service.clone()
+        self.wallet
+            .transaction_service
+            .for_caller(&self.wallet.request_scheduler, REQUEST_CALLER)
     }
 
     fn get_output_manager_service(&self) -> OutputManagerHandle {
-        self.wallet.output_manager_service.clone()
+        self.wallet
+            .output_manager_service
+            .for_caller(&self.wallet.request_scheduler, REQUEST_CALLER)
+    }
+
+    fn command_executor(&self) -> CommandExecutorSqlite {
+        CommandExecutor::new(self.wallet.clone())
     }
 
     fn comms(&self) -> &CommsNode {
@@ -145,6 +165,7 @@ impl WalletGrpcServer {
 #[tonic::async_trait]
 impl wallet_server::Wallet for WalletGrpcServer {
     type GetCompletedTransactionsStream = mpsc::Receiver<Result<GetCompletedTransactionsResponse, Status>>;
+    type StreamBalanceChangesStream = mpsc::Receiver<Result<GetBalanceResponse, Status>>;
     type StreamTransactionEventsStream = mpsc::Receiver<Result<TransactionEventResponse, Status>>;
 
     async fn get_version(&self, _: Request<GetVersionRequest>) -> Result<Response<GetVersionResponse>, Status> {
@@ -210,26 +231,45 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
         println!("Setting base node peer...");
         println!("{}::{}", public_key, net_address);
-        let mut wallet = self.wallet.clone();
-        wallet
-            .set_base_node_peer(public_key.clone(), net_address.clone())
+        self.command_executor()
+            .execute(WalletCommand::SetBaseNode {
+                public_key,
+                address: net_address,
+            })
             .await
-            .map_err(|e| Status::internal(format!("{:?}", e)))?;
+            .map_err(wallet_error_status)?;
 
         Ok(Response::new(SetBaseNodeResponse {}))
     }
 
     async fn get_balance(&self, _request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
         let mut output_service = self.get_output_manager_service();
-        let balance = match output_service.get_balance().await {
-            Ok(b) => b,
-            Err(e) => return Err(Status::not_found(format!("GetBalance error! {}", e))),
-        };
-        Ok(Response::new(GetBalanceResponse {
-            available_balance: balance.available_balance.0,
-            pending_incoming_balance: balance.pending_incoming_balance.0,
-            pending_outgoing_balance: balance.pending_outgoing_balance.0,
-        }))
+        let balance = output_service.get_balance().await.map_err(wallet_error_status)?;
+        Ok(Response::new(convert_balance(balance)))
+    }
+
+    async fn stream_balance_changes(
+        &self,
+        _request: Request<GetBalanceRequest>,
+    ) -> Result<Response<Self::StreamBalanceChangesStream>, Status> {
+        let (mut sender, receiver) = mpsc::channel(10);
+        let mut balance_changes = self.get_output_manager_service().subscribe_balance_changes();
+
+        task::spawn(async move {
+            loop {
+                let balance = balance_changes.borrow().clone();
+                if let Some(balance) = balance {
+                    if sender.send(Ok(convert_balance(balance))).await.is_err() {
+                        debug!(target: LOG_TARGET, "The client closed the balance change stream");
+                        break;
+                    }
+                }
+                if balance_changes.changed().await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(receiver))
     }
 
     async fn get_unspent_amounts(
@@ -237,10 +277,10 @@ impl wallet_server::Wallet for WalletGrpcServer {
         _: Request<tari_rpc::Empty>,
     ) -> Result<Response<GetUnspentAmountsResponse>, Status> {
         let mut output_service = self.get_output_manager_service();
-        let unspent_amounts = match output_service.get_unspent_outputs().await {
-            Ok(uo) => uo,
-            Err(e) => return Err(Status::not_found(format!("GetUnspentAmounts error! {}", e))),
-        };
+        let unspent_amounts = output_service
+            .get_unspent_outputs()
+            .await
+            .map_err(wallet_error_status)?;
         Ok(Response::new(GetUnspentAmountsResponse {
             amount: unspent_amounts
                 .into_iter()
@@ -254,16 +294,10 @@ impl wallet_server::Wallet for WalletGrpcServer {
         &self,
         _request: Request<RevalidateRequest>,
     ) -> Result<Response<RevalidateResponse>, Status> {
-        let mut output_service = self.get_output_manager_service();
-        output_service
-            .revalidate_all_outputs()
-            .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
-        let mut tx_service = self.get_transaction_service();
-        tx_service
-            .revalidate_all_transactions()
+        self.command_executor()
+            .execute(WalletCommand::RevalidateWalletDb)
             .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
+            .map_err(wallet_error_status)?;
         Ok(Response::new(RevalidateResponse {}))
     }
 
@@ -275,9 +309,14 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let mut tx_service = self.get_transaction_service();
 
         let coinbase = tx_service
-            .generate_coinbase_transaction(request.reward.into(), request.fee.into(), request.height)
+            .generate_coinbase_transaction_with_extra(
+                request.reward.into(),
+                request.fee.into(),
+                request.height,
+                request.extra,
+            )
             .await
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(wallet_error_status)?;
 
         let coinbase = coinbase.try_into().map_err(Status::internal)?;
         Ok(Response::new(GetCoinbaseResponse {
@@ -371,12 +410,18 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         transaction_id: tx_id.as_u64(),
                         is_success: true,
                         failure_message: Default::default(),
+                        error_details: None,
+                        is_repeated: false,
+                        repeated_status: Default::default(),
                     },
                     Err(e) => TransferResult {
                         address: Default::default(),
                         transaction_id: Default::default(),
                         is_success: false,
                         failure_message: e.to_string(),
+                        error_details: Some(wallet_error_details(&e)),
+                        is_repeated: false,
+                        repeated_status: Default::default(),
                     },
                 }
             },
@@ -387,6 +432,9 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     transaction_id: Default::default(),
                     is_success: false,
                     failure_message: e.to_string(),
+                    error_details: Some(wallet_error_details(&e)),
+                    is_repeated: false,
+                    repeated_status: Default::default(),
                 }
             },
         };
@@ -421,12 +469,18 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         transaction_id: tx_id.as_u64(),
                         is_success: true,
                         failure_message: Default::default(),
+                        error_details: None,
+                        is_repeated: false,
+                        repeated_status: Default::default(),
                     },
                     Err(e) => TransferResult {
                         address: Default::default(),
                         transaction_id: Default::default(),
                         is_success: false,
                         failure_message: e.to_string(),
+                        error_details: Some(wallet_error_details(&e)),
+                        is_repeated: false,
+                        repeated_status: Default::default(),
                     },
                 }
             },
@@ -437,6 +491,9 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     transaction_id: Default::default(),
                     is_success: false,
                     failure_message: e.to_string(),
+                    error_details: Some(wallet_error_details(&e)),
+                    is_repeated: false,
+                    repeated_status: Default::default(),
                 }
             },
         };
@@ -462,27 +519,44 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     dest.fee_per_gram,
                     dest.message,
                     dest.payment_type,
+                    Some(dest.idempotency_key).filter(|key| !key.is_empty()),
                 ))
             })
             .collect::<Result<Vec<_>, _>>()
             .map_err(Status::invalid_argument)?;
 
         let mut transfers = Vec::new();
-        for (address, pk, amount, fee_per_gram, message, payment_type) in recipients {
+        for (address, pk, amount, fee_per_gram, message, payment_type, idempotency_key) in recipients {
             let mut transaction_service = self.get_transaction_service();
             transfers.push(async move {
                 (
                     address,
                     if payment_type == PaymentType::StandardMimblewimble as i32 {
-                        transaction_service
-                            .send_transaction(
-                                pk,
-                                amount.into(),
-                                OutputFeatures::default(),
-                                fee_per_gram.into(),
-                                message,
-                            )
-                            .await
+                        match idempotency_key {
+                            Some(idempotency_key) => {
+                                transaction_service
+                                    .send_idempotent_transaction(
+                                        pk,
+                                        amount.into(),
+                                        OutputFeatures::default(),
+                                        fee_per_gram.into(),
+                                        message,
+                                        idempotency_key,
+                                    )
+                                    .await
+                            },
+                            None => transaction_service
+                                .send_transaction(
+                                    pk,
+                                    amount.into(),
+                                    OutputFeatures::default(),
+                                    fee_per_gram.into(),
+                                    message,
+                                    None,
+                                )
+                                .await
+                                .map(|tx_id| (tx_id, None)),
+                        }
                     } else if payment_type == PaymentType::OneSided as i32 {
                         transaction_service
                             .send_one_sided_transaction(
@@ -493,6 +567,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                                 message,
                             )
                             .await
+                            .map(|tx_id| (tx_id, None))
                     } else {
                         transaction_service
                             .send_one_sided_to_stealth_address_transaction(
@@ -503,6 +578,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                                 message,
                             )
                             .await
+                            .map(|tx_id| (tx_id, None))
                     },
                 )
             });
@@ -513,11 +589,16 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let results = transfers_results
             .into_iter()
             .map(|(address, result)| match result {
-                Ok(tx_id) => TransferResult {
+                Ok((tx_id, repeated_status)) => TransferResult {
                     address,
                     transaction_id: tx_id.into(),
                     is_success: true,
                     failure_message: Default::default(),
+                    error_details: None,
+                    is_repeated: repeated_status.is_some(),
+                    repeated_status: repeated_status
+                        .map(|status| TransactionStatus::from(status) as i32)
+                        .unwrap_or_default(),
                 },
                 Err(err) => {
                     warn!(
@@ -529,6 +610,9 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         transaction_id: Default::default(),
                         is_success: false,
                         failure_message: err.to_string(),
+                        error_details: Some(wallet_error_details(&err)),
+                        is_repeated: false,
+                        repeated_status: Default::default(),
                     }
                 },
             })
@@ -590,12 +674,13 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let transactions = future::try_join_all(queries)
             .await
             .map(|tx| tx.into_iter())
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(wallet_error_status)?;
 
         let wallet_pk = self.wallet.comms.node_identity_ref().public_key();
 
         let transactions = transactions
             .map(|(tx_id, tx)| match tx {
+                Some(WalletTransaction::Completed(tx)) if tx.is_decoy => TransactionInfo::not_found(tx_id),
                 Some(tx) => convert_wallet_transaction_into_transaction_info(tx, wallet_pk),
                 None => TransactionInfo::not_found(tx_id),
             })
@@ -638,7 +723,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                                             }
                                         },
                                         ReceivedFinalizedTransaction(tx_id) => handle_completed_tx(tx_id, RECEIVED, &mut transaction_service, &mut sender).await,
-                                        TransactionMinedUnconfirmed{tx_id, num_confirmations: _, is_valid: _} | FauxTransactionUnconfirmed{tx_id, num_confirmations: _, is_valid: _}=> handle_completed_tx(tx_id, CONFIRMATION, &mut transaction_service, &mut sender).await,
+                                        TransactionMinedUnconfirmed{tx_id, num_confirmations: _, confirmations_required: _, is_valid: _} | FauxTransactionUnconfirmed{tx_id, num_confirmations: _, is_valid: _}=> handle_completed_tx(tx_id, CONFIRMATION, &mut transaction_service, &mut sender).await,
                                         TransactionMined{tx_id, is_valid: _} | FauxTransactionConfirmed{tx_id, is_valid: _} => handle_completed_tx(tx_id, MINED, &mut transaction_service, &mut sender).await,
                                         TransactionCancelled(tx_id, _) => {
                                             match transaction_service.get_any_transaction(tx_id).await{
@@ -692,10 +777,11 @@ impl wallet_server::Wallet for WalletGrpcServer {
             "Incoming GRPC request for GetAllCompletedTransactions"
         );
         let mut transaction_service = self.get_transaction_service();
-        let transactions = transaction_service
+        let mut transactions = transaction_service
             .get_completed_transactions()
             .await
-            .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
+            .map_err(wallet_error_status)?;
+        transactions.retain(|_, tx| !tx.is_decoy);
 
         let (mut sender, receiver) = mpsc::channel(transactions.len());
         task::spawn(async move {
@@ -717,6 +803,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .unwrap_or(&Signature::default())
                             .get_signature()
                             .to_vec(),
+                        coinbase_extra: txn.coinbase_extra().map(<[u8]>::to_vec).unwrap_or_default(),
                         message: txn.message,
                     }),
                 };
@@ -742,20 +829,22 @@ impl wallet_server::Wallet for WalletGrpcServer {
     async fn coin_split(&self, request: Request<CoinSplitRequest>) -> Result<Response<CoinSplitResponse>, Status> {
         let message = request.into_inner();
 
-        let mut wallet = self.wallet.clone();
-
-        let tx_id = wallet
-            .coin_split(
-                vec![], // TODO: refactor grpc to accept and use commitments
-                MicroTari::from(message.amount_per_split),
-                message.split_count as usize,
-                MicroTari::from(message.fee_per_gram),
-                message.message,
-            )
+        // TODO: refactor grpc to accept and use commitments
+        let result = self
+            .command_executor()
+            .execute(WalletCommand::CoinSplit {
+                amount_per_split: MicroTari::from(message.amount_per_split),
+                num_splits: message.split_count as usize,
+                fee_per_gram: MicroTari::from(message.fee_per_gram),
+                message: message.message,
+            })
             .await
-            .map_err(|e| Status::internal(format!("{:?}", e)))?;
+            .map_err(wallet_error_status)?;
 
-        Ok(Response::new(CoinSplitResponse { tx_id: tx_id.into() }))
+        match result {
+            CommandResult::TransactionSent(tx_id) => Ok(Response::new(CoinSplitResponse { tx_id: tx_id.into() })),
+            _ => Err(Status::internal("Unexpected result of the coin split")),
+        }
     }
 
     async fn import_utxos(
@@ -783,7 +872,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         "Imported via gRPC".to_string(),
                     )
                     .await
-                    .map_err(|e| Status::internal(format!("{:?}", e)))?
+                    .map_err(wallet_error_status)?
                     .into(),
             );
         }
@@ -942,6 +1031,7 @@ fn convert_wallet_transaction_into_transaction_info(
             excess_sig: Default::default(),
             timestamp: Some(naive_datetime_to_timestamp(tx.timestamp)),
             message: tx.message,
+            coinbase_extra: Default::default(),
         },
         PendingOutbound(tx) => TransactionInfo {
             tx_id: tx.tx_id.into(),
@@ -955,6 +1045,7 @@ fn convert_wallet_transaction_into_transaction_info(
             excess_sig: Default::default(),
             timestamp: Some(naive_datetime_to_timestamp(tx.timestamp)),
             message: tx.message,
+            coinbase_extra: Default::default(),
         },
         Completed(tx) => TransactionInfo {
             tx_id: tx.tx_id.into(),
@@ -971,6 +1062,7 @@ fn convert_wallet_transaction_into_transaction_info(
                 .first_kernel_excess_sig()
                 .map(|s| s.get_signature().to_vec())
                 .unwrap_or_default(),
+            coinbase_extra: tx.coinbase_extra().map(<[u8]>::to_vec).unwrap_or_default(),
             message: tx.message,
         },
     }
@@ -45,6 +45,7 @@ use tari_utilities::{ByteArray, SafePassword};
 use tari_wallet::{
     error::{WalletError, WalletStorageError},
     output_manager_service::storage::database::OutputManagerDatabase,
+    platform_keystore::EncryptionKeySource,
     storage::{
         database::{WalletBackend, WalletDatabase},
         sqlite_utilities::initialize_sqlite_database_backends,
@@ -52,6 +53,7 @@ use tari_wallet::{
     wallet::{derive_comms_secret_key, read_or_create_master_seed},
     Wallet,
     WalletConfig,
+    WalletExtensions,
     WalletSqlite,
 };
 
@@ -260,6 +262,13 @@ pub async fn init_wallet(
     fs::create_dir_all(&config.wallet.p2p.datastore_path)
         .map_err(|e| ExitError::new(ExitCode::WalletError, format!("Error creating peer db folder. {}", e)))?;
 
+    if config.wallet.encryption_key_source == EncryptionKeySource::PlatformKeystore {
+        return Err(ExitError::new(
+            ExitCode::ConfigError,
+            "The console wallet has no platform keystore, set wallet.encryption_key_source to \"passphrase\"",
+        ));
+    }
+
     debug!(target: LOG_TARGET, "Running Wallet database migrations");
 
     // test encryption by initializing with no passphrase...
@@ -322,11 +331,14 @@ pub async fn init_wallet(
     if let TransportType::Tor = config.wallet.p2p.transport.transport_type {
         wallet_config.p2p.transport.tor.identity = wallet_db.get_tor_id()?;
     }
+    if let TransportType::I2p = config.wallet.p2p.transport.transport_type {
+        wallet_config.p2p.transport.i2p.identity = wallet_db.get_i2p_id()?;
+    }
 
     let factories = CryptoFactories::default();
 
     let mut wallet = Wallet::start(
-        config.wallet.clone(),
+        wallet_config,
         config.peer_seeds.clone(),
         config.auto_update.clone(),
         node_identity,
@@ -339,6 +351,7 @@ pub async fn init_wallet(
         key_manager_backend,
         shutdown_signal,
         master_seed,
+        WalletExtensions::default(),
     )
     .await
     .map_err(|e| match e {
@@ -354,6 +367,12 @@ pub async fn init_wallet(
             .set_tor_identity(hs.tor_identity().clone())
             .map_err(|e| ExitError::new(ExitCode::WalletError, format!("Problem writing tor identity. {}", e)))?;
     }
+    if let Some(session) = wallet.comms.i2p_session() {
+        wallet
+            .db
+            .set_i2p_identity(session.identity().clone())
+            .map_err(|e| ExitError::new(ExitCode::WalletError, format!("Problem writing I2P identity. {}", e)))?;
+    }
 
     if !wallet_encrypted {
         debug!(target: LOG_TARGET, "Wallet is not encrypted.");
@@ -20,7 +20,7 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{env, process};
+use std::{env, iter, process};
 
 use clap::Parser;
 use cli::Cli;
@@ -34,7 +34,12 @@ use init::{
     WalletBoot,
 };
 use log::*;
-use opentelemetry::{self, global, KeyValue};
+use opentelemetry::{
+    self,
+    global,
+    sdk::{propagation::TraceContextPropagator, trace as sdktrace, Resource},
+    KeyValue,
+};
 use recovery::prompt_private_key_from_seed_words;
 use tari_app_utilities::consts;
 use tari_common::{
@@ -107,8 +112,10 @@ fn main_inner() -> Result<(), ExitError> {
         .build()
         .expect("Failed to build a runtime!");
 
-    if cli.tracing_enabled {
-        enable_tracing();
+    if cli.tracing_enabled || config.wallet.otlp_endpoint.is_some() {
+        // The exporters batch spans on the runtime
+        let _guard = runtime.enter();
+        enable_tracing(config.wallet.otlp_endpoint.as_deref());
     }
 
     info!(
@@ -166,6 +173,24 @@ fn main_inner() -> Result<(), ExitError> {
         shutdown_signal,
     ))?;
 
+    // Apply changes to the wallet settings in the config file on SIGHUP
+    #[cfg(unix)]
+    {
+        let transport = config.wallet.p2p.transport.clone();
+        let reload = runtime.block_on(async {
+            wallet.reload_config_on_sighup(move || {
+                let cfg = load_configuration(config_path.as_path(), false, &Cli::parse()).map_err(|e| e.to_string())?;
+                let mut config = ApplicationConfig::load_from(&cfg).map_err(|e| e.to_string())?;
+                // Keep the transport that was set up at startup (e.g. by libtor)
+                config.wallet.p2p.transport = transport.clone();
+                Ok::<_, String>(config.wallet)
+            })
+        });
+        if let Err(e) = reload {
+            warn!(target: LOG_TARGET, "Config reloading on SIGHUP is disabled: {}", e);
+        }
+    }
+
     // Check if there is an in progress recovery in the wallet's database
     if wallet.is_recovery_in_progress()? {
         println!("A Wallet Recovery was found to be in progress, continuing.");
@@ -239,23 +264,51 @@ fn get_recovery_seed(boot_mode: WalletBoot, cli: &Cli) -> Result<Option<CipherSe
     }
 }
 
-fn enable_tracing() {
-    // To run:
-    // docker run -d -p6831:6831/udp -p6832:6832/udp -p16686:16686 -p14268:14268 jaegertracing/all-in-one:latest
-    // To view the UI after starting the container (default):
-    // http://localhost:16686
-    global::set_text_map_propagator(opentelemetry_jaeger::Propagator::new());
-    let tracer = opentelemetry_jaeger::new_pipeline()
-        .with_service_name("tari::console_wallet")
-        .with_tags(vec![
-            KeyValue::new("pid", process::id().to_string()),
-            KeyValue::new(
-                "current_exe",
-                env::current_exe().unwrap().to_str().unwrap_or_default().to_owned(),
-            ),
-        ])
-        .install_batch(opentelemetry::runtime::Tokio)
-        .unwrap();
+fn enable_tracing(otlp_endpoint: Option<&str>) {
+    let tags = vec![
+        KeyValue::new("pid", process::id().to_string()),
+        KeyValue::new(
+            "current_exe",
+            env::current_exe().unwrap().to_str().unwrap_or_default().to_owned(),
+        ),
+    ];
+    let tracer = match otlp_endpoint {
+        // Exports to an OpenTelemetry collector, which can forward the spans to Jaeger or any other backend
+        Some(endpoint) => {
+            global::set_text_map_propagator(TraceContextPropagator::new());
+            let resource = Resource::new(iter::once(KeyValue::new("service.name", "tari::console_wallet")).chain(tags));
+            let result = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+                .with_trace_config(sdktrace::config().with_resource(resource))
+                .install_batch(opentelemetry::runtime::Tokio);
+            match result {
+                Ok(tracer) => tracer,
+                Err(e) => {
+                    // A bad endpoint should not stop the wallet, it just runs without exporting spans
+                    error!(
+                        target: LOG_TARGET,
+                        "Could not export traces to OpenTelemetry collector at '{}', tracing is disabled: {}",
+                        endpoint,
+                        e
+                    );
+                    return;
+                },
+            }
+        },
+        // To run:
+        // docker run -d -p6831:6831/udp -p6832:6832/udp -p16686:16686 -p14268:14268 jaegertracing/all-in-one:latest
+        // To view the UI after starting the container (default):
+        // http://localhost:16686
+        None => {
+            global::set_text_map_propagator(opentelemetry_jaeger::Propagator::new());
+            opentelemetry_jaeger::new_pipeline()
+                .with_service_name("tari::console_wallet")
+                .with_tags(tags)
+                .install_batch(opentelemetry::runtime::Tokio)
+                .unwrap()
+        },
+    };
     let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
     let subscriber = Registry::default().with(telemetry);
     tracing::subscriber::set_global_default(subscriber)
@@ -84,6 +84,7 @@ pub async fn wallet_recovery(
     wallet: &WalletSqlite,
     base_node_config: &PeerConfig,
     retry_limit: usize,
+    key_index_gap_limit: u64,
 ) -> Result<(), ExitError> {
     println!("\nPress Ctrl-C to stop the recovery process\n");
     // We dont care about the shutdown signal here, so we just create one
@@ -112,6 +113,7 @@ pub async fn wallet_recovery(
         .with_peers(peer_public_keys)
         // Do not make this a small number as wallet recovery needs to be resilient
         .with_retry_limit(retry_limit)
+        .with_key_index_gap_limit(key_index_gap_limit)
         .build_with_wallet(wallet, shutdown_signal);
 
     let mut event_stream = recovery_task.get_event_receiver();
@@ -178,6 +180,7 @@ pub async fn wallet_recovery(
                 num_recovered,
                 value_recovered,
                 time_taken,
+                statistics,
             }) => {
                 let rate = (final_height as f32) * 1000f32 / (time_taken.as_millis() as f32);
                 let stats = format!(
@@ -186,6 +189,22 @@ pub async fn wallet_recovery(
                 );
                 info!(target: LOG_TARGET, "{}", stats);
                 println!("{}", stats);
+                for (output_type, type_stats) in [
+                    ("standard", statistics.standard),
+                    ("coinbase", statistics.coinbase),
+                    ("one-sided", statistics.one_sided),
+                    ("stealth one-sided", statistics.stealth_one_sided),
+                    ("unrecognized", statistics.unrecognized),
+                    ("unknown", statistics.unknown),
+                    ("unique asset", statistics.unique_assets),
+                ] {
+                    let s = format!(
+                        "  {} outputs: {} worth {}",
+                        output_type, type_stats.num_recovered, type_stats.value_recovered
+                    );
+                    info!(target: LOG_TARGET, "{}", s);
+                    println!("{}", s);
+                }
             },
             Err(e @ broadcast::error::RecvError::Lagged(_)) => {
                 debug!(target: LOG_TARGET, "Error receiving Wallet recovery events: {}", e);
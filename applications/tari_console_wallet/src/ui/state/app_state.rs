@@ -29,10 +29,11 @@ use std::{
 use bitflags::bitflags;
 use chrono::{DateTime, Local, NaiveDateTime};
 use log::*;
-use qrcode::{render::unicode, QrCode};
+use qrcode::{render::unicode, EcLevel, QrCode};
 use tari_common::configuration::Network;
 use tari_common_types::{
     emoji::EmojiId,
+    qr_payload::{QrErrorCorrection, QrPayload},
     transaction::{TransactionDirection, TransactionStatus, TxId},
     types::PublicKey,
 };
@@ -58,7 +59,7 @@ use tari_wallet::{
     output_manager_service::{handle::OutputManagerEventReceiver, service::Balance},
     transaction_service::{
         handle::TransactionEventReceiver,
-        storage::models::{CompletedTransaction, TxCancellationReason},
+        storage::models::{CompletedTransaction, TxCancellationReason, WalletTransaction},
     },
     WalletConfig,
     WalletSqlite,
@@ -660,6 +661,8 @@ impl AppStateInner {
                 .collect::<Vec<CompletedTransaction>>(),
         );
 
+        // Decoy self-spends are never presented as payments
+        completed_transactions.retain(|tx| !tx.is_decoy);
         completed_transactions.sort_by(|a, b| {
             b.timestamp
                 .partial_cmp(&a.timestamp)
@@ -687,6 +690,9 @@ impl AppStateInner {
 
     pub async fn refresh_single_transaction_state(&mut self, tx_id: TxId) -> Result<(), UiError> {
         let found = self.wallet.transaction_service.get_any_transaction(tx_id).await?;
+        if matches!(&found, Some(WalletTransaction::Completed(tx)) if tx.is_decoy) {
+            return Ok(());
+        }
 
         match found {
             None => {
@@ -1081,8 +1087,14 @@ impl AppStateData {
         base_node_config: PeerConfig,
     ) -> Self {
         let eid = EmojiId::from_pubkey(node_identity.public_key()).to_string();
-        let qr_link = format!("tari://{}/pubkey/{}", network, &node_identity.public_key().to_hex());
-        let code = QrCode::new(qr_link).unwrap();
+        let qr_payload = QrPayload::new(network.to_string(), node_identity.public_key().clone());
+        let ec_level = match qr_payload.error_correction() {
+            QrErrorCorrection::Low => EcLevel::L,
+            QrErrorCorrection::Medium => EcLevel::M,
+            QrErrorCorrection::Quartile => EcLevel::Q,
+            QrErrorCorrection::High => EcLevel::H,
+        };
+        let code = QrCode::with_error_correction_level(qr_payload.to_uri(), ec_level).unwrap();
         let image = code
             .render::<unicode::Dense1x2>()
             .dark_color(unicode::Dense1x2::Dark)
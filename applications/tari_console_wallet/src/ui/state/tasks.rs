@@ -42,7 +42,7 @@ pub async fn send_transaction_task(
     let mut event_stream = transaction_service_handle.get_event_stream();
     let mut send_status = TransactionSendStatus::default();
     match transaction_service_handle
-        .send_transaction(public_key, amount, output_features, fee_per_gram, message)
+        .send_transaction(public_key, amount, output_features, fee_per_gram, message, None)
         .await
     {
         Err(e) => {
@@ -108,7 +108,7 @@ impl WalletEventMonitor {
                                         format!("Finalized Transaction Received - TxId: {}", tx_id)
                                     ).await;
                                 },
-                                TransactionEvent::TransactionMinedUnconfirmed{tx_id, num_confirmations, is_valid: _}  |
+                                TransactionEvent::TransactionMinedUnconfirmed{tx_id, num_confirmations, confirmations_required: _, is_valid: _}  |
                                 TransactionEvent::FauxTransactionUnconfirmed{tx_id, num_confirmations, is_valid: _}=> {
                                     self.trigger_confirmations_refresh(tx_id, num_confirmations).await;
                                     self.trigger_tx_state_refresh(tx_id).await;
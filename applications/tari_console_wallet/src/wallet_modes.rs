@@ -335,6 +335,7 @@ pub fn recovery_mode(
         &wallet,
         base_node_config,
         wallet_config.recovery_retry_limit,
+        wallet_config.recovery_key_index_gap_limit,
     )) {
         Ok(_) => println!("Wallet recovered!"),
         Err(e) => {
@@ -455,6 +456,7 @@ mod test {
                 CliCommands::ExportUtxos(_) => {},
                 CliCommands::ExportSpentUtxos(_) => {},
                 CliCommands::CountUtxos => {},
+                CliCommands::CoinbaseStats => {},
                 CliCommands::SetBaseNode(_) => {},
                 CliCommands::SetCustomBaseNode(_) => {},
                 CliCommands::ClearCustomBaseNode => {},
@@ -463,6 +465,9 @@ mod test {
                 CliCommands::ClaimShaAtomicSwapRefund(_) => {},
                 CliCommands::RevalidateWalletDb => {},
                 CliCommands::HashGrpcPassword(_) => {},
+                CliCommands::TagTransaction(_) => {},
+                CliCommands::UntagTransaction(_) => {},
+                CliCommands::ExportTransactions(_) => {},
             }
         }
         assert!(get_balance && send_tari && make_it_rain && coin_split && discover_peer && whois);
@@ -42,6 +42,7 @@ pub struct BlockTemplateProtocol<'a> {
     wallet_client: &'a mut grpc::wallet_client::WalletClient<
         tonic::codegen::InterceptedService<tonic::transport::Channel, ClientAuthenticationInterceptor>,
     >,
+    coinbase_extra: Vec<u8>,
 }
 
 impl<'a> BlockTemplateProtocol<'a> {
@@ -50,10 +51,12 @@ impl<'a> BlockTemplateProtocol<'a> {
         wallet_client: &'a mut grpc::wallet_client::WalletClient<
             tonic::codegen::InterceptedService<tonic::transport::Channel, ClientAuthenticationInterceptor>,
         >,
+        coinbase_extra: Vec<u8>,
     ) -> Self {
         Self {
             base_node_client,
             wallet_client,
+            coinbase_extra,
         }
     }
 }
@@ -190,6 +193,7 @@ impl BlockTemplateProtocol<'_> {
                 reward: block_reward,
                 fee: total_fees,
                 height: tari_height,
+                extra: self.coinbase_extra.clone(),
             })
             .await
             .map_err(|status| MmProxyError::GrpcRequestError {
@@ -60,6 +60,8 @@ pub struct MergeMiningProxyConfig {
     pub check_tari_difficulty_before_submit: bool,
     /// The maximum amount of VMs that RandomX will be use
     pub max_randomx_vms: usize,
+    /// Extra data, e.g. a pool tag, to add to the coinbase output of mined Tari blocks
+    pub coinbase_extra: String,
 }
 
 impl Default for MergeMiningProxyConfig {
@@ -78,6 +80,7 @@ impl Default for MergeMiningProxyConfig {
             wait_for_initial_sync_at_startup: true,
             check_tari_difficulty_before_submit: true,
             max_randomx_vms: 5,
+            coinbase_extra: String::new(),
         }
     }
 }
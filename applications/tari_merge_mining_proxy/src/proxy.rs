@@ -422,7 +422,11 @@ impl InnerService {
             }
         }
 
-        let new_block_protocol = BlockTemplateProtocol::new(&mut grpc_client, &mut grpc_wallet_client);
+        let new_block_protocol = BlockTemplateProtocol::new(
+            &mut grpc_client,
+            &mut grpc_wallet_client,
+            self.config.coinbase_extra.as_bytes().to_vec(),
+        );
 
         let seed_hash = FixedByteArray::from_hex(&monerod_resp["result"]["seed_hash"].to_string().replace('\"', ""))
             .map_err(|err| MmProxyError::InvalidMonerodResponse(format!("seed hash hex is invalid: {}", err)))?;
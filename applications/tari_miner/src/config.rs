@@ -69,6 +69,8 @@ pub struct MinerConfig {
     pub mining_wallet_address: String,
     /// Stratum Mode configuration - mining worker name
     pub mining_worker_name: String,
+    /// Extra data, e.g. a pool tag, to add to the coinbase output of mined blocks
+    pub coinbase_extra: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -96,6 +98,7 @@ impl Default for MinerConfig {
             mining_pool_address: String::new(),
             mining_wallet_address: String::new(),
             mining_worker_name: String::new(),
+            coinbase_extra: String::new(),
         }
     }
 }
@@ -24,6 +24,7 @@ pub mod chain_metadata;
 pub mod emoji;
 pub mod grpc_authentication;
 pub mod luhn;
+pub mod qr_payload;
 pub mod transaction;
 mod tx_id;
 pub mod types;
@@ -0,0 +1,314 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The payload of the QR codes that wallets show to be paid, so that any wallet can scan the code of any other.
+//!
+//! A payload is a `tari://` URI that names the network and the public key of the recipient, and optionally the amount
+//! in µT and a memo:
+//!
+//! ```text
+//! tari://<network>/transactions/send?publicKey=<hex>[&amount=<µT>][&memo=<percent-encoded text>]
+//! ```
+//!
+//! The public key may also be given as an emoji ID when parsing, and the `tari://<network>/pubkey/<hex>` links of older
+//! wallets are still understood. Unknown query parameters are ignored, so that parameters can be added later.
+
+use std::{
+    fmt::{Display, Error, Formatter},
+    str::FromStr,
+};
+
+use tari_crypto::tari_utilities::hex::Hex;
+use thiserror::Error;
+
+use crate::{emoji::EmojiId, types::PublicKey};
+
+pub const TARI_URI_SCHEME: &str = "tari";
+
+/// The number of bytes that a QR code of version 10 (57 by 57 modules) holds at each error correction level. Larger
+/// codes no longer scan reliably from a phone screen or a terminal.
+const VERSION_10_CAPACITY: [(QrErrorCorrection, usize); 3] = [
+    (QrErrorCorrection::High, 119),
+    (QrErrorCorrection::Quartile, 151),
+    (QrErrorCorrection::Medium, 213),
+];
+
+/// The error correction level to render a payload with, from the lowest to the highest redundancy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrErrorCorrection {
+    /// Recovers about 7% of the code
+    Low,
+    /// Recovers about 15% of the code
+    Medium,
+    /// Recovers about 25% of the code
+    Quartile,
+    /// Recovers about 30% of the code
+    High,
+}
+
+/// A request to be paid, as encoded in a QR code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrPayload {
+    pub network: String,
+    pub public_key: PublicKey,
+    /// The amount requested in µT
+    pub amount: Option<u64>,
+    pub memo: Option<String>,
+}
+
+impl QrPayload {
+    pub fn new<T: Into<String>>(network: T, public_key: PublicKey) -> Self {
+        Self {
+            network: network.into(),
+            public_key,
+            amount: None,
+            memo: None,
+        }
+    }
+
+    pub fn with_amount(mut self, amount: u64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn with_memo<T: Into<String>>(mut self, memo: T) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    pub fn emoji_id(&self) -> EmojiId {
+        EmojiId::from_pubkey(&self.public_key)
+    }
+
+    /// The URI to encode in the QR code
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!(
+            "{}://{}/transactions/send?publicKey={}",
+            TARI_URI_SCHEME,
+            percent_encode(&self.network),
+            self.public_key.to_hex()
+        );
+        if let Some(amount) = self.amount {
+            uri.push_str(&format!("&amount={}", amount));
+        }
+        if let Some(memo) = self.memo.as_ref().filter(|memo| !memo.is_empty()) {
+            uri.push_str(&format!("&memo={}", percent_encode(memo)));
+        }
+        uri
+    }
+
+    /// The highest error correction level at which the payload still fits a QR code that scans reliably. Long memos
+    /// get less redundancy rather than a larger code.
+    pub fn error_correction(&self) -> QrErrorCorrection {
+        let len = self.to_uri().len();
+        VERSION_10_CAPACITY
+            .iter()
+            .find(|(_, capacity)| len <= *capacity)
+            .map_or(QrErrorCorrection::Low, |(level, _)| *level)
+    }
+}
+
+impl Display for QrPayload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.write_str(&self.to_uri())
+    }
+}
+
+impl FromStr for QrPayload {
+    type Err = QrPayloadError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .trim()
+            .strip_prefix(TARI_URI_SCHEME)
+            .and_then(|rest| rest.strip_prefix("://"))
+            .ok_or(QrPayloadError::InvalidScheme)?;
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (rest, ""),
+        };
+        let mut segments = path.trim_end_matches('/').split('/');
+        let network = segments
+            .next()
+            .filter(|network| !network.is_empty())
+            .ok_or(QrPayloadError::MissingNetwork)
+            .and_then(percent_decode)?;
+
+        match (segments.next(), segments.next(), segments.next()) {
+            (Some("transactions"), Some("send"), None) => {
+                let mut public_key = None;
+                let mut payload_amount = None;
+                let mut payload_memo = None;
+                for (key, value) in query.split('&').filter_map(|param| param.split_once('=')) {
+                    match key {
+                        "publicKey" => public_key = Some(parse_public_key(&percent_decode(value)?)?),
+                        "amount" => {
+                            let amount = value
+                                .parse::<u64>()
+                                .map_err(|_| QrPayloadError::InvalidAmount(value.to_string()))?;
+                            payload_amount = Some(amount);
+                        },
+                        "memo" => payload_memo = Some(percent_decode(value)?).filter(|memo| !memo.is_empty()),
+                        _ => {},
+                    }
+                }
+                Ok(Self {
+                    network,
+                    public_key: public_key.ok_or(QrPayloadError::MissingPublicKey)?,
+                    amount: payload_amount,
+                    memo: payload_memo,
+                })
+            },
+            (Some("pubkey"), Some(public_key), None) => Ok(Self::new(network, parse_public_key(public_key)?)),
+            _ => Err(QrPayloadError::UnsupportedPath(path.to_string())),
+        }
+    }
+}
+
+/// Parses a public key in hex or as an emoji ID
+fn parse_public_key(s: &str) -> Result<PublicKey, QrPayloadError> {
+    PublicKey::from_hex(s)
+        .or_else(|_| EmojiId::str_to_pubkey(s))
+        .map_err(|_| QrPayloadError::InvalidPublicKey(s.to_string()))
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(char::from(byte)),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(s: &str) -> Result<String, QrPayloadError> {
+    let invalid = || QrPayloadError::InvalidEncoding(s.to_string());
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = s.get(i + 1..i + 3).ok_or_else(invalid)?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                i += 3;
+            },
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QrPayloadError {
+    #[error("The payload is not a tari:// URI")]
+    InvalidScheme,
+    #[error("The payload does not name a network")]
+    MissingNetwork,
+    #[error("Unsupported payload path `{0}`")]
+    UnsupportedPath(String),
+    #[error("The payload does not contain a public key")]
+    MissingPublicKey,
+    #[error("Invalid public key or emoji ID `{0}`")]
+    InvalidPublicKey(String),
+    #[error("Invalid amount `{0}`")]
+    InvalidAmount(String),
+    #[error("Invalid percent-encoding `{0}`")]
+    InvalidEncoding(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: &str = "70350e09c474809209824c6e6888707b7dd09959aa227343b5106382b856f73a";
+
+    fn public_key() -> PublicKey {
+        PublicKey::from_hex(KEY).unwrap()
+    }
+
+    #[test]
+    fn it_round_trips() {
+        let payload = QrPayload::new("dibbler", public_key())
+            .with_amount(1_250_000)
+            .with_memo("Coffee & cake 🍰");
+        let uri = payload.to_uri();
+        assert_eq!(
+            uri,
+            format!(
+                "tari://dibbler/transactions/send?publicKey={}&amount=1250000&memo=Coffee%20%26%20cake%20%F0%9F%8D%B0",
+                KEY
+            )
+        );
+        assert_eq!(uri.parse::<QrPayload>().unwrap(), payload);
+
+        let payload = QrPayload::new("dibbler", public_key());
+        assert_eq!(payload.to_string().parse::<QrPayload>().unwrap(), payload);
+    }
+
+    #[test]
+    fn it_parses_other_forms() {
+        let expected = QrPayload::new("dibbler", public_key());
+        let legacy = format!("tari://dibbler/pubkey/{}", KEY);
+        assert_eq!(legacy.parse::<QrPayload>().unwrap(), expected);
+
+        let emoji_id = percent_encode(EmojiId::from_pubkey(&public_key()).as_str());
+        let uri = format!(
+            "tari://dibbler/transactions/send?publicKey={}&label=shop&memo=",
+            emoji_id
+        );
+        assert_eq!(uri.parse::<QrPayload>().unwrap(), expected);
+    }
+
+    #[test]
+    fn it_rejects_invalid_payloads() {
+        assert_eq!(
+            format!("https://dibbler/pubkey/{}", KEY).parse::<QrPayload>(),
+            Err(QrPayloadError::InvalidScheme)
+        );
+        assert_eq!(
+            format!("tari:///pubkey/{}", KEY).parse::<QrPayload>(),
+            Err(QrPayloadError::MissingNetwork)
+        );
+        assert_eq!(
+            "tari://dibbler/transactions/send?amount=1".parse::<QrPayload>(),
+            Err(QrPayloadError::MissingPublicKey)
+        );
+        assert_eq!(
+            format!("tari://dibbler/transactions/send?publicKey={}&amount=-1", KEY).parse::<QrPayload>(),
+            Err(QrPayloadError::InvalidAmount("-1".to_string()))
+        );
+        assert_eq!(
+            format!("tari://dibbler/transactions/send?publicKey={}&memo=%F0%9F", KEY).parse::<QrPayload>(),
+            Err(QrPayloadError::InvalidEncoding("%F0%9F".to_string()))
+        );
+        assert!(matches!(
+            "tari://dibbler/pubkey/abc".parse::<QrPayload>(),
+            Err(QrPayloadError::InvalidPublicKey(_))
+        ));
+        assert!(matches!(
+            "tari://dibbler/base_nodes/add".parse::<QrPayload>(),
+            Err(QrPayloadError::UnsupportedPath(_))
+        ));
+    }
+
+    #[test]
+    fn it_lowers_the_error_correction_of_long_payloads() {
+        let payload = QrPayload::new("dibbler", public_key());
+        assert_eq!(payload.error_correction(), QrErrorCorrection::High);
+        let payload = payload.with_amount(1_000_000).with_memo("Invoice 42");
+        assert_eq!(payload.error_correction(), QrErrorCorrection::Quartile);
+        let payload = payload.with_memo("x".repeat(200));
+        assert_eq!(payload.error_correction(), QrErrorCorrection::Low);
+    }
+}
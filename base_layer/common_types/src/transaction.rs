@@ -37,6 +37,8 @@ pub enum TransactionStatus {
     FauxConfirmed,
     /// This transaction is still being queued for initial sending
     Queued,
+    /// An input of this transaction was spent on-chain by a different transaction, so it can never be mined
+    DoubleSpent,
 }
 
 impl TransactionStatus {
@@ -70,6 +72,7 @@ impl TryFrom<i32> for TransactionStatus {
             8 => Ok(TransactionStatus::FauxUnconfirmed),
             9 => Ok(TransactionStatus::FauxConfirmed),
             10 => Ok(TransactionStatus::Queued),
+            11 => Ok(TransactionStatus::DoubleSpent),
             code => Err(TransactionConversionError { code }),
         }
     }
@@ -96,6 +99,7 @@ impl Display for TransactionStatus {
             TransactionStatus::FauxUnconfirmed => write!(f, "FauxUnconfirmed"),
             TransactionStatus::FauxConfirmed => write!(f, "FauxConfirmed"),
             TransactionStatus::Queued => write!(f, "Queued"),
+            TransactionStatus::DoubleSpent => write!(f, "Double Spent"),
         }
     }
 }
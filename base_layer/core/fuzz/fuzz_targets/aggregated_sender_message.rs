@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tari_core::transactions::transaction_protocol::aggregated_sender::AggregatedSenderMessage;
+
+fuzz_target!(|data: &[u8]| {
+    // Other parties send arbitrary bytes. A message that decodes must survive a round trip through the encoding.
+    if let Ok(message) = AggregatedSenderMessage::from_bytes(data) {
+        let bytes = message.to_bytes();
+        assert_eq!(AggregatedSenderMessage::from_bytes(&bytes).unwrap(), message);
+        assert_eq!(AggregatedSenderMessage::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+    }
+});
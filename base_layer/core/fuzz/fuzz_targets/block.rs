@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tari_core::blocks::{Block, BlockHeader};
+use tari_core_fuzz::check_decode_round_trip;
+
+fuzz_target!(|data: &[u8]| {
+    check_decode_round_trip::<BlockHeader>(data);
+    check_decode_round_trip::<Block>(data);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tari_core::covenants::Covenant;
+use tari_core_fuzz::{check_decode_round_trip, check_encode_round_trip};
+
+fuzz_target!(|data: &[u8]| {
+    check_decode_round_trip::<Covenant>(data);
+    if let Ok(covenant) = Covenant::from_bytes(data) {
+        check_encode_round_trip(&covenant);
+    }
+});
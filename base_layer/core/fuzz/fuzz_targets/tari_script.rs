@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tari_core_fuzz::{check_decode_round_trip, check_encode_round_trip};
+use tari_script::TariScript;
+
+fuzz_target!(|data: &[u8]| {
+    check_decode_round_trip::<TariScript>(data);
+    // Scripts are also read from their raw bytes, e.g. by the wallet
+    if let Ok(script) = TariScript::from_bytes(data) {
+        assert_eq!(TariScript::from_bytes(&script.as_bytes()).unwrap(), script);
+        check_encode_round_trip(&script);
+    }
+});
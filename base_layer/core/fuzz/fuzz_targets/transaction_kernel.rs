@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tari_core::transactions::transaction_components::TransactionKernel;
+use tari_core_fuzz::{check_decode_round_trip, check_encode_round_trip, ArbitraryKernel};
+
+fuzz_target!(|input: (&[u8], ArbitraryKernel)| {
+    let (data, kernel) = input;
+    check_decode_round_trip::<TransactionKernel>(data);
+    check_encode_round_trip(&TransactionKernel::from(kernel));
+});
@@ -0,0 +1,38 @@
+#![no_main]
+use std::convert::TryFrom;
+
+use libfuzzer_sys::fuzz_target;
+use prost::Message;
+use tari_core::transactions::transaction_protocol::{
+    proto::protocol as proto,
+    recipient::RecipientSignedMessage,
+    sender::TransactionSenderMessage,
+};
+
+fuzz_target!(|data: &[u8]| {
+    // Other wallets send arbitrary bytes. A message that converts must encode to the same bytes after a round trip.
+    if let Ok(message) = proto::TransactionSenderMessage::decode(data) {
+        if let Ok(message) = TransactionSenderMessage::try_from(message) {
+            let bytes = proto::TransactionSenderMessage::from(message).encode_to_vec();
+            let message = proto::TransactionSenderMessage::decode(bytes.as_slice()).unwrap();
+            let message = TransactionSenderMessage::try_from(message).unwrap();
+            assert_eq!(proto::TransactionSenderMessage::from(message).encode_to_vec(), bytes);
+        }
+    }
+    if let Ok(message) = proto::RecipientSignedMessage::decode(data) {
+        if let Ok(message) = RecipientSignedMessage::try_from(message) {
+            let bytes = proto::RecipientSignedMessage::from(message.clone()).encode_to_vec();
+            let decoded = proto::RecipientSignedMessage::decode(bytes.as_slice()).unwrap();
+            assert_eq!(RecipientSignedMessage::try_from(decoded).unwrap(), message);
+        }
+    }
+    if let Ok(message) = proto::TransactionFinalizedMessage::decode(data) {
+        if let Ok((tx_id, transaction)) = message.into_transaction() {
+            let bytes = proto::TransactionFinalizedMessage::new(tx_id, transaction.clone())
+                .unwrap()
+                .encode_to_vec();
+            let decoded = proto::TransactionFinalizedMessage::decode(bytes.as_slice()).unwrap();
+            assert_eq!(decoded.into_transaction().unwrap(), (tx_id, transaction));
+        }
+    }
+});
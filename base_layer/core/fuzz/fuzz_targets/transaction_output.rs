@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tari_core::transactions::transaction_components::TransactionOutput;
+use tari_core_fuzz::{check_decode_round_trip, check_encode_round_trip, ArbitraryOutput};
+
+fuzz_target!(|input: (&[u8], ArbitraryOutput)| {
+    let (data, output) = input;
+    check_decode_round_trip::<TransactionOutput>(data);
+    check_encode_round_trip(&TransactionOutput::from(output));
+});
@@ -0,0 +1,123 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Shared invariants and structured inputs for the consensus decoder fuzz targets.
+//!
+//! Each target feeds raw bytes to a consensus decoder, since that is what the decoders receive from the network, and
+//! also builds a value from structured input so that the encoder is exercised with values that decode successfully.
+//! In both cases a value must survive an encode/decode round trip unchanged.
+
+use std::fmt::Debug;
+
+use arbitrary::Arbitrary;
+use tari_common_types::types::{ComSignature, Commitment, PrivateKey, PublicKey, RangeProof, Signature};
+use tari_core::{
+    consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized, FromConsensusBytes, ToConsensusBytes},
+    covenants::Covenant,
+    transactions::{
+        tari_amount::MicroTari,
+        transaction_components::{
+            EncryptedValue,
+            KernelFeatures,
+            OutputFeatures,
+            TransactionKernel,
+            TransactionKernelVersion,
+            TransactionOutput,
+            TransactionOutputVersion,
+        },
+    },
+};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_script::TariScript;
+use tari_utilities::ByteArray;
+
+/// Decodes `data` and, if it is a valid encoding, checks that the decoded value round trips
+pub fn check_decode_round_trip<T>(data: &[u8])
+where T: ConsensusEncoding + ConsensusEncodingSized + ConsensusDecoding + Eq + Debug {
+    let mut reader = data;
+    if let Ok(decoded) = T::consensus_decode(&mut reader) {
+        check_encode_round_trip(&decoded);
+    }
+}
+
+/// Checks that the encoding of `subject` has the size it reports, and decodes to a value equal to `subject` without
+/// leaving any bytes unread
+pub fn check_encode_round_trip<T>(subject: &T)
+where T: ConsensusEncoding + ConsensusEncodingSized + ConsensusDecoding + Eq + Debug {
+    let bytes = subject.to_consensus_bytes();
+    assert_eq!(bytes.len(), subject.consensus_encode_exact_size());
+    let decoded = T::from_consensus_bytes(&bytes).expect("the encoding of a value must decode");
+    assert_eq!(&decoded, subject);
+}
+
+/// Keys and commitments are derived from small secrets, because arbitrary bytes are rarely valid curve points
+fn keypair(secret: u64) -> (PrivateKey, PublicKey) {
+    let k = PrivateKey::from(secret);
+    let p = PublicKey::from_secret_key(&k);
+    (k, p)
+}
+
+fn commitment(secret: u64) -> Commitment {
+    Commitment::from_public_key(&keypair(secret).1)
+}
+
+#[derive(Debug, Arbitrary)]
+pub struct ArbitraryKernel {
+    features: u8,
+    fee: u64,
+    lock_height: u64,
+    excess: u64,
+    nonce: u64,
+    signature: u64,
+    burn_commitment: Option<u64>,
+}
+
+impl From<ArbitraryKernel> for TransactionKernel {
+    fn from(kernel: ArbitraryKernel) -> Self {
+        let (_, public_nonce) = keypair(kernel.nonce);
+        TransactionKernel::new(
+            TransactionKernelVersion::get_current_version(),
+            KernelFeatures::from_bits_truncate(kernel.features),
+            MicroTari::from(kernel.fee),
+            kernel.lock_height,
+            commitment(kernel.excess),
+            Signature::new(public_nonce, PrivateKey::from(kernel.signature)),
+            kernel.burn_commitment.map(commitment),
+        )
+    }
+}
+
+#[derive(Debug, Arbitrary)]
+pub struct ArbitraryOutput {
+    maturity: u64,
+    metadata: Vec<u8>,
+    commitment: u64,
+    proof: Vec<u8>,
+    script: Vec<u8>,
+    sender_offset: u64,
+    signature: (u64, u64, u64),
+    covenant: Vec<u8>,
+    minimum_value_promise: u64,
+}
+
+impl From<ArbitraryOutput> for TransactionOutput {
+    fn from(output: ArbitraryOutput) -> Self {
+        let (nonce, u, v) = output.signature;
+        TransactionOutput::new(
+            TransactionOutputVersion::get_current_version(),
+            OutputFeatures {
+                maturity: output.maturity,
+                metadata: output.metadata,
+                ..Default::default()
+            },
+            commitment(output.commitment),
+            RangeProof::from_bytes(&output.proof).expect("a range proof accepts any bytes"),
+            TariScript::from_bytes(&output.script).unwrap_or_default(),
+            keypair(output.sender_offset).1,
+            ComSignature::new(commitment(nonce), PrivateKey::from(u), PrivateKey::from(v)),
+            Covenant::from_bytes(&output.covenant).unwrap_or_default(),
+            EncryptedValue::default(),
+            MicroTari::from(output.minimum_value_promise),
+        )
+    }
+}
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    cmp::max,
     convert::{TryFrom, TryInto},
     fmt::{Display, Error, Formatter},
 };
@@ -28,7 +29,11 @@ use std::{
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::{BlockHash, Signature};
 
-use crate::proto::{base_node as proto, types};
+use crate::{
+    proof_of_work::{AlgorithmHashRate, Difficulty, NetworkHashRate},
+    proto::{base_node as proto, types},
+    transactions::{fee::Fee, tari_amount::MicroTari},
+};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TxSubmissionResponse {
@@ -45,6 +50,7 @@ pub enum TxSubmissionRejectionReason {
     Orphan,
     TimeLocked,
     ValidationFailed,
+    FeeTooLow,
 }
 
 impl Display for TxSubmissionRejectionReason {
@@ -57,6 +63,7 @@ impl Display for TxSubmissionRejectionReason {
             Orphan => "Orphan",
             TimeLocked => "Time Locked",
             ValidationFailed => "Validation Failed",
+            FeeTooLow => "Fee Too Low",
             None => "None",
         };
         fmt.write_str(response)
@@ -76,6 +83,7 @@ impl TryFrom<proto::TxSubmissionRejectionReason> for TxSubmissionRejectionReason
             Orphan => TxSubmissionRejectionReason::Orphan,
             TimeLocked => TxSubmissionRejectionReason::TimeLocked,
             ValidationFailed => TxSubmissionRejectionReason::ValidationFailed,
+            FeeTooLow => TxSubmissionRejectionReason::FeeTooLow,
         })
     }
 }
@@ -91,6 +99,7 @@ impl From<TxSubmissionRejectionReason> for proto::TxSubmissionRejectionReason {
             Orphan => proto::TxSubmissionRejectionReason::Orphan,
             TimeLocked => proto::TxSubmissionRejectionReason::TimeLocked,
             ValidationFailed => proto::TxSubmissionRejectionReason::ValidationFailed,
+            FeeTooLow => proto::TxSubmissionRejectionReason::FeeTooLow,
         }
     }
 }
@@ -277,3 +286,94 @@ impl proto::sync_utxo::Utxo {
         }
     }
 }
+
+/// The lowest fees that a base node accepts transactions with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FeeFloor {
+    /// The minimum fee per gram that the mempool of the base node relays transactions for
+    pub min_relay_fee_per_gram: MicroTari,
+    /// The minimum fee per gram of the consensus rules
+    pub min_fee_per_gram: MicroTari,
+    /// The minimum fee of a transaction of the consensus rules
+    pub min_transaction_fee: MicroTari,
+}
+
+impl FeeFloor {
+    /// The fee floor of the consensus rules, for when the base node does not report its minimum relay fee
+    pub fn consensus() -> Self {
+        Self {
+            min_relay_fee_per_gram: Fee::MINIMUM_FEE_PER_GRAM,
+            min_fee_per_gram: Fee::MINIMUM_FEE_PER_GRAM,
+            min_transaction_fee: Fee::MINIMUM_TRANSACTION_FEE,
+        }
+    }
+
+    /// The lowest fee per gram that a transaction can pay and still be broadcast by the base node
+    pub fn fee_per_gram(&self) -> MicroTari {
+        max(self.min_relay_fee_per_gram, self.min_fee_per_gram)
+    }
+}
+
+impl From<FeeFloor> for proto::FeeFloorResponse {
+    fn from(floor: FeeFloor) -> Self {
+        Self {
+            min_relay_fee_per_gram: floor.min_relay_fee_per_gram.as_u64(),
+            min_fee_per_gram: floor.min_fee_per_gram.as_u64(),
+            min_transaction_fee: floor.min_transaction_fee.as_u64(),
+        }
+    }
+}
+
+impl From<proto::FeeFloorResponse> for FeeFloor {
+    fn from(response: proto::FeeFloorResponse) -> Self {
+        Self {
+            min_relay_fee_per_gram: response.min_relay_fee_per_gram.into(),
+            min_fee_per_gram: response.min_fee_per_gram.into(),
+            min_transaction_fee: response.min_transaction_fee.into(),
+        }
+    }
+}
+
+impl From<NetworkHashRate> for proto::NetworkHashRateResponse {
+    fn from(estimate: NetworkHashRate) -> Self {
+        Self {
+            tip_height: estimate.tip_height,
+            sha3: Some(estimate.sha3.into()),
+            monero: Some(estimate.monero.into()),
+        }
+    }
+}
+
+impl From<proto::NetworkHashRateResponse> for NetworkHashRate {
+    fn from(response: proto::NetworkHashRateResponse) -> Self {
+        Self {
+            tip_height: response.tip_height,
+            sha3: response.sha3.map(Into::into).unwrap_or_default(),
+            monero: response.monero.map(Into::into).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<AlgorithmHashRate> for proto::AlgorithmHashRate {
+    fn from(estimate: AlgorithmHashRate) -> Self {
+        Self {
+            difficulty: estimate.difficulty.as_u64(),
+            estimated_hash_rate: estimate.estimated_hash_rate,
+            difficulty_change: estimate.difficulty_change,
+            average_block_time: estimate.average_block_time.unwrap_or_default(),
+            num_blocks: estimate.num_blocks as u64,
+        }
+    }
+}
+
+impl From<proto::AlgorithmHashRate> for AlgorithmHashRate {
+    fn from(estimate: proto::AlgorithmHashRate) -> Self {
+        Self {
+            difficulty: Difficulty::from(estimate.difficulty),
+            estimated_hash_rate: estimate.estimated_hash_rate,
+            difficulty_change: estimate.difficulty_change,
+            average_block_time: Some(estimate.average_block_time).filter(|t| *t > 0),
+            num_blocks: estimate.num_blocks as usize,
+        }
+    }
+}
@@ -41,16 +41,22 @@ use crate::{
     proto,
     proto::{
         base_node::{
+            BlockInclusionProof,
+            BlockInclusionProofRequest,
+            FeeFloorResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
             GetMempoolFeePerGramStatsResponse,
+            NetworkHashRateRequest,
+            NetworkHashRateResponse,
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
+            TransactionPackage,
             TxQueryBatchResponses,
             TxQueryResponse,
             TxSubmissionResponse,
@@ -119,6 +125,27 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
         &self,
         request: Request<GetMempoolFeePerGramStatsRequest>,
     ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus>;
+
+    #[rpc(method = 13)]
+    async fn get_block_inclusion_proof(
+        &self,
+        request: Request<BlockInclusionProofRequest>,
+    ) -> Result<Response<BlockInclusionProof>, RpcStatus>;
+
+    #[rpc(method = 14)]
+    async fn submit_transaction_package(
+        &self,
+        request: Request<TransactionPackage>,
+    ) -> Result<Response<TxSubmissionResponse>, RpcStatus>;
+
+    #[rpc(method = 15)]
+    async fn get_network_hash_rate(
+        &self,
+        request: Request<NetworkHashRateRequest>,
+    ) -> Result<Response<NetworkHashRateResponse>, RpcStatus>;
+
+    #[rpc(method = 16)]
+    async fn get_fee_floor(&self, request: Request<()>) -> Result<Response<FeeFloorResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
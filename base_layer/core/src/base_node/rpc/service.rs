@@ -31,25 +31,34 @@ use tokio::sync::mpsc;
 
 use crate::{
     base_node::{
+        proto::wallet_rpc::FeeFloor,
         rpc::{sync_utxos_by_block_task::SyncUtxosByBlockTask, BaseNodeWalletService},
         state_machine_service::states::StateInfo,
         StateMachineHandle,
     },
+    blocks::BlockInclusionProof,
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, PrunedOutput},
-    mempool::{service::MempoolHandle, TxStorageResponse},
+    mempool::{service::MempoolHandle, TxStorageResponse, MAX_TRANSACTION_PACKAGE_SIZE},
+    proof_of_work::{estimate_network_hash_rate, DifficultySample},
     proto,
     proto::{
         base_node::{
+            BlockInclusionProof as BlockInclusionProofProto,
+            BlockInclusionProofRequest,
+            FeeFloorResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
             GetMempoolFeePerGramStatsResponse,
+            NetworkHashRateRequest,
+            NetworkHashRateResponse,
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures as SignaturesProto,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
+            TransactionPackage,
             TxLocation,
             TxQueryBatchResponse,
             TxQueryBatchResponses,
@@ -63,9 +72,13 @@ use crate::{
         types::{Signature as SignatureProto, Transaction as TransactionProto},
     },
     transactions::transaction_components::Transaction,
+    PrunedKernelMmr,
+    PrunedWitnessMmr,
 };
 
 const LOG_TARGET: &str = "c::base_node::rpc";
+/// The maximum number of blocks the network hash rate can be estimated from, about a day of blocks
+const MAX_HASH_RATE_ESTIMATE_BLOCKS: u64 = 720;
 
 pub struct BaseNodeWalletRpcService<B> {
     db: AsyncBlockchainDb<B>,
@@ -149,6 +162,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
             TxStorageResponse::NotStoredTimeLocked |
             TxStorageResponse::NotStoredAlreadySpent |
             TxStorageResponse::NotStoredConsensus |
+            TxStorageResponse::NotStoredFeeTooLow |
             TxStorageResponse::NotStored => TxQueryResponse {
                 location: TxLocation::NotStored as i32,
                 block_hash: None,
@@ -160,32 +174,25 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
         };
         Ok(mempool_response)
     }
-}
-
-#[tari_comms::async_trait]
-impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpcService<B> {
-    async fn submit_transaction(
-        &self,
-        request: Request<TransactionProto>,
-    ) -> Result<Response<TxSubmissionResponse>, RpcStatus> {
-        let message = request.into_message();
-        let transaction =
-            Transaction::try_from(message).map_err(|_| RpcStatus::bad_request("Transaction was invalid"))?;
-        let mut mempool = self.mempool();
-        let state_machine = self.state_machine();
 
-        // Determine if we are synced
-        let status_watch = state_machine.get_status_info_watch();
+    /// Whether the base node considers itself synced
+    fn is_synced(&self) -> bool {
+        let status_watch = self.state_machine().get_status_info_watch();
         let is_synced = match (*status_watch.borrow()).state_info {
             StateInfo::Listening(li) => li.is_synced(),
             _ => false,
         };
+        is_synced
+    }
 
-        let response = match mempool
-            .submit_transaction(transaction.clone())
-            .await
-            .rpc_status_internal_error(LOG_TARGET)?
-        {
+    /// Translates the result of submitting `transaction` to the mempool into the response of the submit RPCs
+    async fn submission_response(
+        &self,
+        tx_storage: TxStorageResponse,
+        transaction: &Transaction,
+    ) -> Result<TxSubmissionResponse, RpcStatus> {
+        let is_synced = self.is_synced();
+        let response = match tx_storage {
             TxStorageResponse::UnconfirmedPool => TxSubmissionResponse {
                 accepted: true,
                 rejection_reason: TxSubmissionRejectionReason::None.into(),
@@ -207,6 +214,11 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                 rejection_reason: TxSubmissionRejectionReason::ValidationFailed.into(),
                 is_synced,
             },
+            TxStorageResponse::NotStoredFeeTooLow => TxSubmissionResponse {
+                accepted: false,
+                rejection_reason: TxSubmissionRejectionReason::FeeTooLow.into(),
+                is_synced,
+            },
             TxStorageResponse::NotStoredAlreadySpent | TxStorageResponse::ReorgPool => {
                 // Is this transaction a double spend or has this transaction been mined?
                 match transaction.first_kernel_excess_sig() {
@@ -239,6 +251,25 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                 }
             },
         };
+        Ok(response)
+    }
+}
+
+#[tari_comms::async_trait]
+impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpcService<B> {
+    async fn submit_transaction(
+        &self,
+        request: Request<TransactionProto>,
+    ) -> Result<Response<TxSubmissionResponse>, RpcStatus> {
+        let message = request.into_message();
+        let transaction =
+            Transaction::try_from(message).map_err(|_| RpcStatus::bad_request("Transaction was invalid"))?;
+        let mut mempool = self.mempool();
+        let tx_storage = mempool
+            .submit_transaction(transaction.clone())
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let response = self.submission_response(tx_storage, &transaction).await?;
         Ok(Response::new(response))
     }
 
@@ -643,4 +674,173 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
 
         Ok(Response::new(stats.into()))
     }
+
+    async fn get_block_inclusion_proof(
+        &self,
+        request: Request<BlockInclusionProofRequest>,
+    ) -> Result<Response<BlockInclusionProofProto>, RpcStatus> {
+        let block_hash: FixedHash = request
+            .into_message()
+            .block_hash
+            .try_into()
+            .map_err(|_| RpcStatus::bad_request("Malformed block hash received"))?;
+        let db = self.db();
+        let header = db
+            .fetch_header_by_block_hash(block_hash)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?
+            .ok_or_else(|| RpcStatus::not_found(&format!("Header not found for block {}", block_hash.to_hex())))?;
+
+        // The MMRs of the genesis block start out empty
+        let (kernel_set, witness_set) = if header.height == 0 {
+            Default::default()
+        } else {
+            let (kernels, _, witness, _) = db
+                .fetch_block_accumulated_data(header.prev_hash)
+                .await
+                .rpc_status_internal_error(LOG_TARGET)?
+                .dissolve();
+            (kernels, witness)
+        };
+        // Only the peaks are needed to extend the MMRs with the block's leaves
+        let kernel_set = PrunedKernelMmr::new(kernel_set)
+            .get_pruned_hash_set()
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let witness_set = PrunedWitnessMmr::new(witness_set)
+            .get_pruned_hash_set()
+            .rpc_status_internal_error(LOG_TARGET)?;
+
+        let kernels = db
+            .fetch_kernels_in_block(block_hash)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let (outputs, _) = db
+            .fetch_utxos_in_block(block_hash, None)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        // Blocks are sorted, so the inputs are added to the input MMR in the order of the outputs they spend
+        let mut inputs = db
+            .fetch_inputs_in_block(block_hash)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        inputs.sort();
+        let input_hashes = inputs
+            .iter()
+            .map(|i| i.canonical_hash())
+            .collect::<Result<Vec<_>, _>>()
+            .rpc_status_internal_error(LOG_TARGET)?;
+
+        let proof = BlockInclusionProof::new(
+            kernel_set,
+            kernels.iter().map(|k| k.hash()).collect(),
+            witness_set,
+            outputs.iter().map(|o| o.witness_hash()).collect(),
+            input_hashes,
+        );
+        Ok(Response::new(proof.into()))
+    }
+
+    async fn submit_transaction_package(
+        &self,
+        request: Request<TransactionPackage>,
+    ) -> Result<Response<TxSubmissionResponse>, RpcStatus> {
+        let message = request.into_message();
+        if message.transactions.is_empty() || message.transactions.len() > MAX_TRANSACTION_PACKAGE_SIZE {
+            return Err(RpcStatus::bad_request(&format!(
+                "A transaction package must contain between 1 and {} transactions",
+                MAX_TRANSACTION_PACKAGE_SIZE
+            )));
+        }
+        let transactions = message
+            .transactions
+            .into_iter()
+            .map(Transaction::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| RpcStatus::bad_request("Transaction was invalid"))?;
+
+        // Parents that were mined since the package was put together do not need to be submitted again
+        let db = self.db();
+        let mut unmined_transactions = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            let excess_sig = transaction
+                .first_kernel_excess_sig()
+                .ok_or_else(|| RpcStatus::bad_request("Transaction has no kernels"))?;
+            if db
+                .fetch_kernel_by_excess_sig(excess_sig.clone())
+                .await
+                .rpc_status_internal_error(LOG_TARGET)?
+                .is_none()
+            {
+                unmined_transactions.push(transaction);
+            }
+        }
+        let child = match unmined_transactions.last() {
+            Some(child) => child.clone(),
+            None => {
+                return Ok(Response::new(TxSubmissionResponse {
+                    accepted: false,
+                    rejection_reason: TxSubmissionRejectionReason::AlreadyMined.into(),
+                    is_synced: self.is_synced(),
+                }))
+            },
+        };
+
+        let mut mempool = self.mempool();
+        let tx_storage = mempool
+            .submit_transaction_package(unmined_transactions)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let response = self.submission_response(tx_storage, &child).await?;
+        Ok(Response::new(response))
+    }
+
+    async fn get_network_hash_rate(
+        &self,
+        request: Request<NetworkHashRateRequest>,
+    ) -> Result<Response<NetworkHashRateResponse>, RpcStatus> {
+        let num_blocks = request.into_message().num_blocks;
+        if num_blocks == 0 || num_blocks > MAX_HASH_RATE_ESTIMATE_BLOCKS {
+            return Err(RpcStatus::bad_request(&format!(
+                "num_blocks must be between 1 and {}",
+                MAX_HASH_RATE_ESTIMATE_BLOCKS
+            )));
+        }
+
+        let db = self.db();
+        let tip_height = db
+            .get_chain_metadata()
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?
+            .height_of_longest_chain();
+        let start_height = tip_height.saturating_sub(num_blocks - 1);
+        let samples = db
+            .fetch_chain_headers(start_height..=tip_height)
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?
+            .iter()
+            .map(|header| DifficultySample {
+                height: header.height(),
+                timestamp: header.timestamp(),
+                pow_algo: header.header().pow.pow_algo,
+                target_difficulty: header.accumulated_data().target_difficulty,
+            })
+            .collect::<Vec<_>>();
+        let estimate = estimate_network_hash_rate(&samples, db.inner().rules());
+
+        Ok(Response::new(estimate.into()))
+    }
+
+    async fn get_fee_floor(&self, _: Request<()>) -> Result<Response<FeeFloorResponse>, RpcStatus> {
+        let min_relay_fee_per_gram = self
+            .mempool()
+            .get_min_fee_per_gram()
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let floor = FeeFloor {
+            min_relay_fee_per_gram,
+            ..FeeFloor::consensus()
+        };
+
+        Ok(Response::new(floor.into()))
+    }
 }
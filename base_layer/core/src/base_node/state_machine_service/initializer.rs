@@ -107,6 +107,7 @@ where B: BlockchainBackend + 'static
                 factories,
                 config.bypass_range_proof_verification,
                 config.blockchain_sync_config.validation_concurrency,
+                config.blockchain_sync_config.range_proof_batch_size,
             );
             let max_randomx_vms = config.max_randomx_vms;
 
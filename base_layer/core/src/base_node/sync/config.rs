@@ -26,6 +26,8 @@ use serde::{Deserialize, Serialize};
 use tari_common::configuration::serializers;
 use tari_comms::peer_manager::NodeId;
 
+use crate::transactions::transaction_components::transaction_output::DEFAULT_RANGE_PROOF_BATCH_SIZE;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct BlockchainSyncConfig {
@@ -48,6 +50,9 @@ pub struct BlockchainSyncConfig {
     pub forced_sync_peers: Vec<NodeId>,
     /// Number of threads to use for validation
     pub validation_concurrency: usize,
+    /// The maximum number of range proofs that are verified in a single batch during validation, rounded down to a
+    /// power of two
+    pub range_proof_batch_size: usize,
 }
 
 impl Default for BlockchainSyncConfig {
@@ -59,6 +64,7 @@ impl Default for BlockchainSyncConfig {
             short_ban_period: Duration::from_secs(60),
             forced_sync_peers: Default::default(),
             validation_concurrency: 6,
+            range_proof_batch_size: DEFAULT_RANGE_PROOF_BATCH_SIZE,
         }
     }
 }
@@ -65,7 +65,7 @@ use crate::{
         SyncUtxosResponse,
     },
     transactions::transaction_components::{
-        transaction_output::batch_verify_range_proofs,
+        transaction_output::batch_verify_range_proofs_with_batch_size,
         TransactionKernel,
         TransactionOutput,
     },
@@ -521,6 +521,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                     height_utxo_counter += 1;
                     let output = TransactionOutput::try_from(output).map_err(HorizonSyncError::ConversionError)?;
                     helpers::check_tari_script_byte_size(&output.script, constants.get_max_script_byte_size())?;
+                    helpers::check_tari_script_opcodes(&output.script, &constants)?;
                     unpruned_outputs.push(output.clone());
 
                     output_mmr.push(output.hash().to_vec())?;
@@ -702,6 +703,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
 
     async fn validate_rangeproofs(&self, mut unpruned_outputs: Vec<TransactionOutput>) -> Result<(), HorizonSyncError> {
         let concurrency = self.config.validation_concurrency;
+        let range_proof_batch_size = self.config.range_proof_batch_size;
         let mut chunk_size = unpruned_outputs.len() / concurrency;
         if unpruned_outputs.len() % concurrency > 0 {
             chunk_size += 1;
@@ -720,7 +722,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 let prover = self.prover.clone();
                 task::spawn_blocking(move || -> Result<(), HorizonSyncError> {
                     let outputs = chunk.iter().collect::<Vec<_>>();
-                    batch_verify_range_proofs(&prover, &outputs)?;
+                    batch_verify_range_proofs_with_batch_size(&prover, &outputs, range_proof_batch_size)?;
                     Ok(())
                 })
             })
@@ -58,6 +58,7 @@ impl<B: BlockchainBackend + 'static> SyncValidators<B> {
         factories: CryptoFactories,
         bypass_range_proof_verification: bool,
         concurrency: usize,
+        range_proof_batch_size: usize,
     ) -> Self {
         Self::new(
             BlockValidator::new(
@@ -66,7 +67,8 @@ impl<B: BlockchainBackend + 'static> SyncValidators<B> {
                 factories.clone(),
                 bypass_range_proof_verification,
                 concurrency,
-            ),
+            )
+            .with_range_proof_batch_size(range_proof_batch_size),
             ChainBalanceValidator::<B>::new(rules, factories),
         )
     }
@@ -107,6 +107,7 @@ impl Block {
     /// 1. There is exactly ONE coinbase output
     /// 1. The output's maturity is correctly set
     /// 1. The amount is correct.
+    /// 1. The extra data of the output does not exceed the maximum size.
     pub fn check_coinbase_output(
         &self,
         reward: MicroTari,
@@ -119,6 +120,12 @@ impl Block {
             factories,
             self.header.height,
         )?;
+        let max = consensus_constants.coinbase_extra_max_length();
+        for extra in self.body.outputs().iter().filter_map(|o| o.features.coinbase_extra()) {
+            if extra.len() > max {
+                return Err(TransactionError::InvalidCoinbaseExtra { len: extra.len(), max }.into());
+            }
+        }
         Ok(())
     }
 
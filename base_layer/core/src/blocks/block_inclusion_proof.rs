@@ -0,0 +1,245 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::{TryFrom, TryInto};
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::FixedHash;
+use tari_mmr::{error::MerkleMountainRangeError, pruned_hashset::PrunedHashSet};
+
+use crate::{
+    blocks::{BlockError, BlockHeader},
+    proto::base_node as proto,
+    PrunedInputMmr,
+    PrunedKernelMmr,
+    PrunedWitnessMmr,
+};
+
+/// The peaks of the kernel and witness MMRs before a block, and the leaves that the block added to them. Extending the
+/// MMRs with the leaves must reproduce the MMR roots and sizes committed to in the block header, which proves which
+/// kernels and outputs were mined in the block without trusting the node that provided the proof.
+///
+/// The output MMR root also commits to the spent outputs, so outputs are proven against the witness MMR instead, which
+/// contains the same outputs in the same order.
+///
+/// The input MMR only contains the inputs of the block, so its leaves alone reproduce the input MMR root. Older base
+/// nodes do not provide them, so inputs are verified separately with [verify_inputs](Self::verify_inputs).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockInclusionProof {
+    kernel_mmr: PrunedHashSet,
+    kernel_hashes: Vec<FixedHash>,
+    witness_mmr: PrunedHashSet,
+    witness_hashes: Vec<FixedHash>,
+    input_hashes: Vec<FixedHash>,
+}
+
+impl BlockInclusionProof {
+    pub fn new(
+        kernel_mmr: PrunedHashSet,
+        kernel_hashes: Vec<FixedHash>,
+        witness_mmr: PrunedHashSet,
+        witness_hashes: Vec<FixedHash>,
+        input_hashes: Vec<FixedHash>,
+    ) -> Self {
+        Self {
+            kernel_mmr,
+            kernel_hashes,
+            witness_mmr,
+            witness_hashes,
+            input_hashes,
+        }
+    }
+
+    /// Checks that the leaves in the proof produce the kernel and witness MMRs committed to in `header`
+    pub fn verify(&self, header: &BlockHeader) -> Result<(), BlockError> {
+        let invalid = |e: MerkleMountainRangeError| BlockError::InvalidInclusionProof(e.to_string());
+
+        let mut kernel_mmr = PrunedKernelMmr::new(self.kernel_mmr.clone());
+        for hash in &self.kernel_hashes {
+            kernel_mmr.push(hash.to_vec()).map_err(invalid)?;
+        }
+        if header.kernel_mr != kernel_mmr.get_merkle_root().map_err(invalid)? ||
+            header.kernel_mmr_size != kernel_mmr.get_leaf_count().map_err(invalid)? as u64
+        {
+            return Err(BlockError::InvalidInclusionProof(format!(
+                "Kernels do not match the kernel MMR of block #{}",
+                header.height
+            )));
+        }
+
+        let mut witness_mmr = PrunedWitnessMmr::new(self.witness_mmr.clone());
+        for hash in &self.witness_hashes {
+            witness_mmr.push(hash.to_vec()).map_err(invalid)?;
+        }
+        if header.witness_mr != witness_mmr.get_merkle_root().map_err(invalid)? ||
+            header.output_mmr_size != witness_mmr.get_leaf_count().map_err(invalid)? as u64
+        {
+            return Err(BlockError::InvalidInclusionProof(format!(
+                "Outputs do not match the witness MMR of block #{}",
+                header.height
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that the canonical hashes of the inputs in the proof produce the input MMR root committed to in `header`
+    pub fn verify_inputs(&self, header: &BlockHeader) -> Result<(), BlockError> {
+        let invalid = |e: MerkleMountainRangeError| BlockError::InvalidInclusionProof(e.to_string());
+
+        let mut input_mmr = PrunedInputMmr::new(PrunedHashSet::default());
+        for hash in &self.input_hashes {
+            input_mmr.push(hash.to_vec()).map_err(invalid)?;
+        }
+        if header.input_mr != input_mmr.get_merkle_root().map_err(invalid)? {
+            return Err(BlockError::InvalidInclusionProof(format!(
+                "Inputs do not match the input MMR of block #{}",
+                header.height
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn contains_kernel(&self, kernel_hash: &FixedHash) -> bool {
+        self.kernel_hashes.contains(kernel_hash)
+    }
+
+    pub fn contains_output_witness(&self, witness_hash: &FixedHash) -> bool {
+        self.witness_hashes.contains(witness_hash)
+    }
+
+    /// Returns true if the proof contains the input with the given canonical hash, see
+    /// `TransactionInput::canonical_hash`
+    pub fn contains_input(&self, input_hash: &FixedHash) -> bool {
+        self.input_hashes.contains(input_hash)
+    }
+}
+
+impl TryFrom<proto::BlockInclusionProof> for BlockInclusionProof {
+    type Error = String;
+
+    fn try_from(proof: proto::BlockInclusionProof) -> Result<Self, Self::Error> {
+        let to_hashes = |hashes: Vec<Vec<u8>>| {
+            hashes
+                .into_iter()
+                .map(|h| h.try_into().map_err(|_| "Malformed hash".to_string()))
+                .collect::<Result<Vec<FixedHash>, _>>()
+        };
+        let base_offset = |offset: u64| usize::try_from(offset).map_err(|_| "MMR size overflows usize".to_string());
+
+        Ok(Self {
+            kernel_mmr: PrunedHashSet::from_peaks(base_offset(proof.kernel_mmr_base_offset)?, proof.kernel_mmr_peaks)
+                .map_err(|e| format!("Invalid kernel MMR peaks: {}", e))?,
+            kernel_hashes: to_hashes(proof.kernel_hashes)?,
+            witness_mmr: PrunedHashSet::from_peaks(
+                base_offset(proof.witness_mmr_base_offset)?,
+                proof.witness_mmr_peaks,
+            )
+            .map_err(|e| format!("Invalid witness MMR peaks: {}", e))?,
+            witness_hashes: to_hashes(proof.witness_hashes)?,
+            input_hashes: to_hashes(proof.input_hashes)?,
+        })
+    }
+}
+
+impl From<BlockInclusionProof> for proto::BlockInclusionProof {
+    fn from(proof: BlockInclusionProof) -> Self {
+        Self {
+            kernel_mmr_base_offset: proof.kernel_mmr.base_offset() as u64,
+            kernel_mmr_peaks: proof.kernel_mmr.peak_hashes().to_vec(),
+            kernel_hashes: proof.kernel_hashes.iter().map(|h| h.to_vec()).collect(),
+            witness_mmr_base_offset: proof.witness_mmr.base_offset() as u64,
+            witness_mmr_peaks: proof.witness_mmr.peak_hashes().to_vec(),
+            witness_hashes: proof.witness_hashes.iter().map(|h| h.to_vec()).collect(),
+            input_hashes: proof.input_hashes.iter().map(|h| h.to_vec()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hash(n: u8) -> FixedHash {
+        FixedHash::from([n; 32])
+    }
+
+    /// Creates a proof for a block adding 3 kernels and 2 outputs to MMRs of 10 leaves, and a header that matches it
+    fn create_proof() -> (BlockInclusionProof, BlockHeader) {
+        let mut kernel_mmr = PrunedKernelMmr::new(PrunedHashSet::default());
+        let mut witness_mmr = PrunedWitnessMmr::new(PrunedHashSet::default());
+        for n in 0..10 {
+            kernel_mmr.push(hash(n).to_vec()).unwrap();
+            witness_mmr.push(hash(100 + n).to_vec()).unwrap();
+        }
+        let proof = BlockInclusionProof::new(
+            kernel_mmr.get_pruned_hash_set().unwrap(),
+            (10..13).map(hash).collect(),
+            witness_mmr.get_pruned_hash_set().unwrap(),
+            (110..112).map(hash).collect(),
+            (200..202).map(hash).collect(),
+        );
+        for n in 10..13 {
+            kernel_mmr.push(hash(n).to_vec()).unwrap();
+        }
+        for n in 110..112 {
+            witness_mmr.push(hash(n).to_vec()).unwrap();
+        }
+
+        let mut header = BlockHeader::new(0);
+        header.height = 1;
+        header.kernel_mr = kernel_mmr.get_merkle_root().unwrap().try_into().unwrap();
+        header.kernel_mmr_size = 13;
+        header.witness_mr = witness_mmr.get_merkle_root().unwrap().try_into().unwrap();
+        header.output_mmr_size = 12;
+        let mut input_mmr = PrunedInputMmr::new(PrunedHashSet::default());
+        for n in 200..202 {
+            input_mmr.push(hash(n).to_vec()).unwrap();
+        }
+        header.input_mr = input_mmr.get_merkle_root().unwrap().try_into().unwrap();
+        (proof, header)
+    }
+
+    #[test]
+    fn it_verifies_a_proof_that_matches_the_header() {
+        let (proof, header) = create_proof();
+        proof.verify(&header).unwrap();
+        assert!(proof.contains_kernel(&hash(11)));
+        assert!(!proof.contains_kernel(&hash(9)));
+        assert!(proof.contains_output_witness(&hash(111)));
+        proof.verify_inputs(&header).unwrap();
+        assert!(proof.contains_input(&hash(201)));
+
+        let proof = BlockInclusionProof::try_from(proto::BlockInclusionProof::from(proof)).unwrap();
+        proof.verify(&header).unwrap();
+        proof.verify_inputs(&header).unwrap();
+    }
+
+    #[test]
+    fn it_rejects_a_proof_that_does_not_match_the_header() {
+        let (mut proof, header) = create_proof();
+        proof.kernel_hashes[1] = hash(99);
+        assert!(matches!(
+            proof.verify(&header),
+            Err(BlockError::InvalidInclusionProof(_))
+        ));
+
+        let (mut proof, header) = create_proof();
+        proof.witness_hashes.pop();
+        assert!(matches!(
+            proof.verify(&header),
+            Err(BlockError::InvalidInclusionProof(_))
+        ));
+
+        let (proof, mut header) = create_proof();
+        header.kernel_mmr_size = 14;
+        assert!(proof.verify(&header).is_err());
+
+        let (mut proof, header) = create_proof();
+        proof.input_hashes.reverse();
+        proof.verify(&header).unwrap();
+        assert!(matches!(
+            proof.verify_inputs(&header),
+            Err(BlockError::InvalidInclusionProof(_))
+        ));
+    }
+}
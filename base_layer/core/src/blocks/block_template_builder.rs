@@ -0,0 +1,226 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Assembles new blocks for solo miners from the mempool of a local base node and a coinbase provided by a wallet,
+//! e.g. by `OutputManagerHandle::get_coinbase_transaction` of the wallet, so mining tools do not need to copy this
+//! logic from the base node application.
+
+use log::*;
+use tari_utilities::hex::Hex;
+
+use crate::{
+    base_node::{comms_interface::CommsInterfaceError, LocalNodeCommsInterface},
+    blocks::{Block, NewBlockTemplate},
+    consensus::ConsensusManager,
+    proof_of_work::PowAlgorithm,
+    transactions::{
+        tari_amount::MicroTari,
+        transaction_components::{KernelFeatures, OutputType, Transaction, TransactionError},
+        CryptoFactories,
+    },
+};
+
+const LOG_TARGET: &str = "c::blocks::block_template_builder";
+
+/// Builds mineable blocks on top of the tip of a local base node. A block is built in two steps:
+/// 1. [new_template](Self::new_template) fills a template for the next block with the highest priority transactions in
+///    the mempool.
+/// 2. Once the wallet has created a coinbase that pays out [coinbase_value] at the height of the template,
+///    [build_block](Self::build_block) adds it to the template and returns a block that only needs to be mined.
+pub struct BlockTemplateBuilder {
+    node_interface: LocalNodeCommsInterface,
+    consensus_manager: ConsensusManager,
+    factories: CryptoFactories,
+    pow_algo: PowAlgorithm,
+    max_weight: u64,
+}
+
+impl BlockTemplateBuilder {
+    pub fn new(node_interface: LocalNodeCommsInterface, consensus_manager: ConsensusManager) -> Self {
+        Self {
+            node_interface,
+            consensus_manager,
+            factories: CryptoFactories::default(),
+            pow_algo: PowAlgorithm::Sha3,
+            max_weight: 0,
+        }
+    }
+
+    /// The proof of work algorithm the blocks are mined with, Sha3 by default
+    pub fn with_pow_algo(mut self, pow_algo: PowAlgorithm) -> Self {
+        self.pow_algo = pow_algo;
+        self
+    }
+
+    /// The maximum weight of the transactions in a template. Zero, the default, fills the template up to the maximum
+    /// block weight allowed by consensus.
+    pub fn with_max_weight(mut self, max_weight: u64) -> Self {
+        self.max_weight = max_weight;
+        self
+    }
+
+    /// Fetches a template for the block on top of the current tip, containing the highest priority transactions in the
+    /// mempool
+    pub async fn new_template(&mut self) -> Result<NewBlockTemplate, BlockTemplateError> {
+        let template = self
+            .node_interface
+            .get_new_block_template(self.pow_algo, self.max_weight)
+            .await?;
+        debug!(
+            target: LOG_TARGET,
+            "New block template at height {} with {} kernel(s), reward: {}, fees: {}",
+            template.header.height,
+            template.body.kernels().len(),
+            template.reward,
+            template.total_fees
+        );
+        Ok(template)
+    }
+
+    /// Adds the coinbase to the template after checking that it is the only coinbase and pays out exactly the reward
+    /// and fees of the template with the maturity required by consensus
+    pub fn add_coinbase(
+        &self,
+        mut template: NewBlockTemplate,
+        coinbase: Transaction,
+    ) -> Result<NewBlockTemplate, BlockTemplateError> {
+        if !coinbase.body.inputs().is_empty() ||
+            coinbase
+                .body
+                .outputs()
+                .iter()
+                .any(|o| o.features.output_type != OutputType::Coinbase) ||
+            coinbase
+                .body
+                .kernels()
+                .iter()
+                .any(|k| !k.features.contains(KernelFeatures::COINBASE_KERNEL))
+        {
+            return Err(BlockTemplateError::InvalidCoinbase(
+                "The coinbase transaction may only contain coinbase outputs and kernels".to_string(),
+            ));
+        }
+
+        let height = template.header.height;
+        let (_, outputs, kernels) = coinbase.body.dissolve();
+        for output in outputs {
+            template.body.add_output(output);
+        }
+        for kernel in kernels {
+            template.body.add_kernel(kernel);
+        }
+        template.body.check_coinbase_output(
+            coinbase_value(&template),
+            self.consensus_manager
+                .consensus_constants(height)
+                .coinbase_lock_height(),
+            &self.factories,
+            height,
+        )?;
+        Ok(template)
+    }
+
+    /// Adds the coinbase to the template and has the base node calculate the MMR roots of the block. The returned block
+    /// is ready to be mined.
+    pub async fn build_block(
+        &mut self,
+        template: NewBlockTemplate,
+        coinbase: Transaction,
+    ) -> Result<Block, BlockTemplateError> {
+        let template = self.add_coinbase(template, coinbase)?;
+        let block = self.node_interface.get_new_block(template).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Built block {} at height {}",
+            block.hash().to_hex(),
+            block.header.height
+        );
+        Ok(block)
+    }
+}
+
+/// The value the coinbase of a block built from `template` must pay out, i.e. the block reward and the fees of the
+/// transactions in the template
+pub fn coinbase_value(template: &NewBlockTemplate) -> MicroTari {
+    template.reward + template.total_fees
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlockTemplateError {
+    #[error("Base node request failed: {0}")]
+    CommsInterfaceError(#[from] CommsInterfaceError),
+    #[error("Invalid coinbase: {0}")]
+    InvalidCoinbase(String),
+    #[error("Transaction error: {0}")]
+    TransactionError(#[from] TransactionError),
+}
+
+#[cfg(test)]
+mod test {
+    use tari_service_framework::reply_channel;
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::{
+        proof_of_work::Difficulty,
+        test_helpers::{create_consensus_rules, create_orphan_block},
+        transactions::{tari_amount::uT, CoinbaseBuilder},
+    };
+
+    fn create_builder(consensus_manager: ConsensusManager) -> BlockTemplateBuilder {
+        let (request_sender, _) = reply_channel::unbounded();
+        let (block_sender, _) = reply_channel::unbounded();
+        let (block_event_sender, _) = broadcast::channel(1);
+        let node_interface = LocalNodeCommsInterface::new(request_sender, block_sender, block_event_sender);
+        BlockTemplateBuilder::new(node_interface, consensus_manager)
+    }
+
+    fn create_coinbase(consensus_manager: &ConsensusManager, height: u64, value: MicroTari) -> Transaction {
+        CoinbaseBuilder::new(CryptoFactories::default())
+            .with_block_height(height)
+            .with_fees(0.into())
+            .with_nonce(1.into())
+            .with_spend_key(2.into())
+            .build_with_reward(consensus_manager.consensus_constants(height), value)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn it_only_adds_coinbases_that_pay_out_the_reward_and_fees() {
+        let consensus_manager = create_consensus_rules();
+        let builder = create_builder(consensus_manager.clone());
+        let reward = consensus_manager.get_block_reward_at(1);
+        let mut template = NewBlockTemplate::from_block(
+            create_orphan_block(1, vec![], &consensus_manager),
+            Difficulty::min(),
+            reward,
+        );
+        template.total_fees = 100 * uT;
+
+        let coinbase = create_coinbase(&consensus_manager, 1, reward);
+        assert!(matches!(
+            builder.add_coinbase(template.clone(), coinbase),
+            Err(BlockTemplateError::TransactionError(TransactionError::InvalidCoinbase))
+        ));
+
+        let coinbase = create_coinbase(&consensus_manager, 1, coinbase_value(&template));
+        let mut not_coinbase = coinbase.clone();
+        not_coinbase.body.kernels_mut()[0].features = KernelFeatures::empty();
+        assert!(matches!(
+            builder.add_coinbase(template.clone(), not_coinbase),
+            Err(BlockTemplateError::InvalidCoinbase(_))
+        ));
+
+        let template = builder.add_coinbase(template, coinbase.clone()).unwrap();
+        assert_eq!(template.body.outputs().len(), 1);
+        assert_eq!(template.body.kernels().len(), 1);
+        // A block can only have a single coinbase
+        assert!(matches!(
+            builder.add_coinbase(template, coinbase),
+            Err(BlockTemplateError::TransactionError(
+                TransactionError::MoreThanOneCoinbase
+            ))
+        ));
+    }
+}
@@ -30,4 +30,8 @@ pub enum BlockError {
     HistoricalBlockContainsPrunedTxos,
     #[error("Chain block invariant error: {0}")]
     ChainBlockInvariantError(String),
+    #[error("Invalid block inclusion proof: {0}")]
+    InvalidInclusionProof(String),
+    #[error("Invalid spend proof: {0}")]
+    InvalidSpendProof(String),
 }
@@ -35,6 +35,16 @@ pub use accumulated_data::{
 };
 use tari_crypto::hash_domain;
 
+#[cfg(feature = "base_node")]
+mod block_inclusion_proof;
+#[cfg(feature = "base_node")]
+pub use block_inclusion_proof::BlockInclusionProof;
+
+#[cfg(feature = "base_node")]
+mod block_template_builder;
+#[cfg(feature = "base_node")]
+pub use block_template_builder::{coinbase_value, BlockTemplateBuilder, BlockTemplateError};
+
 mod error;
 pub use error::BlockError;
 
@@ -64,4 +74,9 @@ mod new_blockheader_template;
 #[cfg(feature = "base_node")]
 pub use new_blockheader_template::NewBlockHeaderTemplate;
 
+#[cfg(feature = "base_node")]
+mod spend_proof;
+#[cfg(feature = "base_node")]
+pub use spend_proof::SpendProof;
+
 hash_domain!(BlocksHashDomain, "com.tari.base_layer.core.blocks", 0);
@@ -0,0 +1,144 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{Commitment, FixedHash};
+
+use crate::{
+    blocks::{BlockError, BlockHeader, BlockInclusionProof},
+    transactions::transaction_components::{TransactionInput, TransactionKernel},
+};
+
+/// Proves that an output was spent in a block, e.g. for the sender of a payment to settle a dispute with the
+/// recipient. The proof contains the header of the block, the input that spent the output and the kernel of the
+/// spending transaction, together with the inclusion proof of the block that commits to both.
+///
+/// A third party that follows the header chain checks that [block_hash](Self::block_hash) is part of it and then
+/// [verifies](Self::verify) the proof, which needs no other data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendProof {
+    pub header: BlockHeader,
+    /// The input that spent the output, including the data of the spent output
+    pub input: TransactionInput,
+    pub kernel: TransactionKernel,
+    pub inclusion_proof: BlockInclusionProof,
+}
+
+impl SpendProof {
+    /// The hash of the block the output was spent in
+    pub fn block_hash(&self) -> FixedHash {
+        self.header.hash()
+    }
+
+    /// The commitment of the spent output
+    pub fn commitment(&self) -> Result<&Commitment, BlockError> {
+        self.input
+            .commitment()
+            .map_err(|e| BlockError::InvalidSpendProof(e.to_string()))
+    }
+
+    /// Checks that the input and kernel were mined in the block with the header in the proof and that the kernel is
+    /// signed
+    pub fn verify(&self) -> Result<(), BlockError> {
+        self.inclusion_proof.verify(&self.header)?;
+        self.inclusion_proof.verify_inputs(&self.header)?;
+
+        let input_hash = self
+            .input
+            .canonical_hash()
+            .map_err(|e| BlockError::InvalidSpendProof(e.to_string()))?;
+        if !self.inclusion_proof.contains_input(&input_hash) {
+            return Err(BlockError::InvalidSpendProof(format!(
+                "The input was not spent in block #{}",
+                self.header.height
+            )));
+        }
+        if !self.inclusion_proof.contains_kernel(&self.kernel.hash()) {
+            return Err(BlockError::InvalidSpendProof(format!(
+                "The kernel was not mined in block #{}",
+                self.header.height
+            )));
+        }
+        self.kernel
+            .verify_signature()
+            .map_err(|e| BlockError::InvalidSpendProof(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use tari_mmr::pruned_hashset::PrunedHashSet;
+
+    use super::*;
+    use crate::{
+        transactions::{
+            tari_amount::uT,
+            test_helpers::create_tx,
+            transaction_components::OutputFeatures,
+            CryptoFactories,
+        },
+        PrunedInputMmr,
+        PrunedKernelMmr,
+        PrunedWitnessMmr,
+    };
+
+    fn create_spend_proof() -> SpendProof {
+        let (tx, inputs, _) = create_tx(5000 * uT, 3 * uT, 1, 2, 1, 2, OutputFeatures::default());
+        let factories = CryptoFactories::default();
+        // Every conversion signs the script with a new nonce, so the inputs are only converted once
+        let inputs = inputs
+            .iter()
+            .map(|i| i.as_transaction_input(&factories.commitment).unwrap())
+            .collect::<Vec<_>>();
+        let input_hashes = inputs.iter().map(|i| i.canonical_hash().unwrap()).collect::<Vec<_>>();
+        let kernel = tx.body.kernels()[0].clone();
+
+        let mut kernel_mmr = PrunedKernelMmr::new(PrunedHashSet::default());
+        kernel_mmr.push(kernel.hash().to_vec()).unwrap();
+        let witness_mmr = PrunedWitnessMmr::new(PrunedHashSet::default());
+        let mut input_mmr = PrunedInputMmr::new(PrunedHashSet::default());
+        for hash in &input_hashes {
+            input_mmr.push(hash.to_vec()).unwrap();
+        }
+        let mut header = BlockHeader::new(0);
+        header.height = 1;
+        header.kernel_mr = kernel_mmr.get_merkle_root().unwrap().try_into().unwrap();
+        header.kernel_mmr_size = 1;
+        header.witness_mr = witness_mmr.get_merkle_root().unwrap().try_into().unwrap();
+        header.output_mmr_size = 0;
+        header.input_mr = input_mmr.get_merkle_root().unwrap().try_into().unwrap();
+
+        SpendProof {
+            header,
+            input: inputs[1].clone(),
+            kernel: kernel.clone(),
+            inclusion_proof: BlockInclusionProof::new(
+                PrunedHashSet::default(),
+                vec![kernel.hash()],
+                PrunedHashSet::default(),
+                vec![],
+                input_hashes,
+            ),
+        }
+    }
+
+    #[test]
+    fn it_verifies_that_the_output_was_spent_in_the_block() {
+        let proof = create_spend_proof();
+        proof.verify().unwrap();
+
+        let mut other_block = proof.clone();
+        other_block.header.input_mr = FixedHash::zero();
+        assert!(other_block.verify().is_err());
+
+        let mut other_input = proof.clone();
+        other_input.input = create_spend_proof().input;
+        assert!(matches!(other_input.verify(), Err(BlockError::InvalidSpendProof(_))));
+
+        let mut other_kernel = proof;
+        other_kernel.kernel = create_spend_proof().kernel;
+        assert!(matches!(other_kernel.verify(), Err(BlockError::InvalidSpendProof(_))));
+    }
+}
@@ -429,6 +429,12 @@ where B: BlockchainBackend
         db.fetch_kernels_in_block(&hash)
     }
 
+    /// Returns the compact inputs of the block with the given hash
+    pub fn fetch_inputs_in_block(&self, hash: HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_inputs_in_block(&hash)
+    }
+
     pub fn fetch_utxos_in_block(
         &self,
         hash: HashOutput,
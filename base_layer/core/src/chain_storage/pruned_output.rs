@@ -52,6 +52,16 @@ impl PrunedOutput {
         }
     }
 
+    pub fn witness_hash(&self) -> FixedHash {
+        match self {
+            PrunedOutput::Pruned {
+                output_hash: _,
+                witness_hash,
+            } => *witness_hash,
+            PrunedOutput::NotPruned { output } => output.witness_hash(),
+        }
+    }
+
     pub fn as_transaction_output(&self) -> Option<&TransactionOutput> {
         match self {
             PrunedOutput::Pruned { .. } => None,
@@ -87,6 +97,7 @@ mod test {
         drop(obj.clone());
         format!("{:?}", obj);
         obj.hash();
+        obj.witness_hash();
         obj.as_transaction_output();
         obj.into_unpruned_output();
     }
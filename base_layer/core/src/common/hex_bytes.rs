@@ -0,0 +1,134 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Serializes binary fields as hex strings in human readable formats such as JSON, e.g.
+//! `#[serde(with = "crate::common::hex_bytes")]`. Binary formats keep the serialization of the field type, so
+//! existing databases are not affected. Byte arrays, the JSON representation before fields were hex encoded, are still
+//! accepted when deserializing.
+
+use std::{convert::TryFrom, fmt, marker::PhantomData};
+
+use serde::{
+    de::{Error, SeqAccess, Visitor},
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use tari_common_types::types::FixedHash;
+use tari_utilities::hex::{from_hex, Hex};
+
+/// A field type that can be serialized with [hex_bytes](self)
+pub trait HexBytes: Sized {
+    fn hex_bytes(&self) -> &[u8];
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, String>;
+}
+
+impl HexBytes for Vec<u8> {
+    fn hex_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl HexBytes for FixedHash {
+    fn hex_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, String> {
+        FixedHash::try_from(bytes).map_err(|e| e.to_string())
+    }
+}
+
+pub fn serialize<S, T>(value: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: HexBytes + Serialize,
+{
+    if ser.is_human_readable() {
+        ser.serialize_str(&value.hex_bytes().to_hex())
+    } else {
+        value.serialize(ser)
+    }
+}
+
+pub fn deserialize<'de, D, T>(de: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: HexBytes + Deserialize<'de>,
+{
+    if de.is_human_readable() {
+        de.deserialize_any(HexBytesVisitor(PhantomData))
+    } else {
+        T::deserialize(de)
+    }
+}
+
+struct HexBytesVisitor<T>(PhantomData<T>);
+
+impl<'de, T: HexBytes> Visitor<'de> for HexBytesVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("Expecting a hex string or binary array")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where E: Error {
+        let bytes = from_hex(v).map_err(|e| E::custom(e.to_string()))?;
+        self.visit_bytes(&bytes)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where E: Error {
+        T::from_hex_bytes(v).map_err(E::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Blob {
+        #[serde(with = "super")]
+        data: Vec<u8>,
+        #[serde(with = "super")]
+        hash: FixedHash,
+    }
+
+    #[test]
+    fn it_serializes_binary_fields_as_hex_in_json() {
+        let blob = Blob {
+            data: vec![0xde, 0xad],
+            hash: FixedHash::zero(),
+        };
+        let json = serde_json::to_string(&blob).unwrap();
+        assert_eq!(json, format!(r#"{{"data":"dead","hash":"{}"}}"#, "00".repeat(32)));
+        assert_eq!(serde_json::from_str::<Blob>(&json).unwrap(), blob);
+
+        // Byte arrays are still accepted
+        let json = format!(r#"{{"data":[222,173],"hash":{:?}}}"#, [0u8; 32]);
+        assert_eq!(serde_json::from_str::<Blob>(&json).unwrap(), blob);
+        assert!(serde_json::from_str::<Blob>(r#"{"data":"dead","hash":"00"}"#).is_err());
+
+        // Binary formats are not affected
+        let bytes = bincode::serialize(&blob).unwrap();
+        assert_eq!(bytes, bincode::serialize(&(vec![0xdeu8, 0xad], [0u8; 32])).unwrap());
+        assert_eq!(bincode::deserialize::<Blob>(&bytes).unwrap(), blob);
+    }
+}
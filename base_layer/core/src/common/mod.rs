@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod byte_counter;
+pub mod hex_bytes;
 pub mod limited_reader;
 #[cfg(feature = "base_node")]
 pub mod rolling_avg;
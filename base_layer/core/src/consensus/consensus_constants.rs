@@ -27,7 +27,7 @@ use std::{
 
 use chrono::{DateTime, Duration, Utc};
 use tari_common::configuration::Network;
-use tari_script::script;
+use tari_script::{script, OpcodeVersion};
 use tari_utilities::epoch_time::EpochTime;
 
 use crate::{
@@ -86,6 +86,12 @@ pub struct ConsensusConstants {
     transaction_weight: TransactionWeight,
     /// Maximum byte size of TariScript
     max_script_byte_size: usize,
+    /// Maximum worst case execution cost of TariScript, see `TariScript::execution_cost`
+    max_script_execution_cost: u64,
+    /// Maximum byte size of the extra data, e.g. a pool tag, that a miner can add to the coinbase output
+    coinbase_extra_max_length: usize,
+    /// The latest version of TariScript opcodes that may be used in outputs
+    max_opcode_version: OpcodeVersion,
     /// Range of valid transaction input versions
     input_version_range: RangeInclusive<TransactionInputVersion>,
     /// Range of valid transaction output (and features) versions
@@ -227,6 +233,22 @@ impl ConsensusConstants {
         }
     }
 
+    /// The expected time in seconds between blocks of any PoW algorithm. Every algorithm mines blocks at its own
+    /// target time, so blocks are expected at the sum of their rates.
+    pub fn get_target_block_interval(&self) -> u64 {
+        let blocks_per_second = self
+            .proof_of_work
+            .values()
+            .filter(|v| v.target_time > 0)
+            .map(|v| 1.0 / v.target_time as f64)
+            .sum::<f64>();
+        if blocks_per_second > 0.0 {
+            (1.0 / blocks_per_second).round() as u64
+        } else {
+            0
+        }
+    }
+
     /// This is how many blocks we use to count towards the median timestamp to ensure the block chain moves forward.
     pub fn get_median_timestamp_count(&self) -> usize {
         self.median_timestamp_count
@@ -237,6 +259,21 @@ impl ConsensusConstants {
         self.max_script_byte_size
     }
 
+    /// The maximum worst case execution cost of TariScript
+    pub fn max_script_execution_cost(&self) -> u64 {
+        self.max_script_execution_cost
+    }
+
+    /// The maximum byte size of the extra data in the coinbase output
+    pub fn coinbase_extra_max_length(&self) -> usize {
+        self.coinbase_extra_max_length
+    }
+
+    /// The latest version of TariScript opcodes that is accepted
+    pub fn max_opcode_version(&self) -> OpcodeVersion {
+        self.max_opcode_version
+    }
+
     /// This is the min initial difficulty that can be requested for the pow
     pub fn min_pow_difficulty(&self, pow_algo: PowAlgorithm) -> Difficulty {
         match self.proof_of_work.get(&pow_algo) {
@@ -319,6 +356,9 @@ impl ConsensusConstants {
             faucet_value: (10 * 4000) * T,
             transaction_weight: TransactionWeight::latest(),
             max_script_byte_size: 2048,
+            max_script_execution_cost: 102_400,
+            coinbase_extra_max_length: 64,
+            max_opcode_version: OpcodeVersion::V1,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -359,6 +399,9 @@ impl ConsensusConstants {
             faucet_value: (5000 * 4000) * T,
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_script_execution_cost: 102_400,
+            coinbase_extra_max_length: 64,
+            max_opcode_version: OpcodeVersion::V0,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -402,6 +445,9 @@ impl ConsensusConstants {
             faucet_value: 0.into(),
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_script_execution_cost: 102_400,
+            coinbase_extra_max_length: 64,
+            max_opcode_version: OpcodeVersion::V0,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -452,6 +498,9 @@ impl ConsensusConstants {
                 faucet_value: (10 * 4000) * T,
                 transaction_weight: TransactionWeight::v1(),
                 max_script_byte_size: 2048,
+                max_script_execution_cost: 102_400,
+                coinbase_extra_max_length: 64,
+                max_opcode_version: OpcodeVersion::V0,
                 input_version_range: input_version_range.clone(),
                 output_version_range: output_version_range.clone(),
                 kernel_version_range: kernel_version_range.clone(),
@@ -475,6 +524,9 @@ impl ConsensusConstants {
                 faucet_value: (10 * 4000) * T,
                 transaction_weight: TransactionWeight::v1(),
                 max_script_byte_size: 2048,
+                max_script_execution_cost: 102_400,
+                coinbase_extra_max_length: 64,
+                max_opcode_version: OpcodeVersion::V0,
                 input_version_range,
                 output_version_range,
                 kernel_version_range,
@@ -523,6 +575,9 @@ impl ConsensusConstants {
             faucet_value: (10 * 4000) * T,
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_script_execution_cost: 102_400,
+            coinbase_extra_max_length: 64,
+            max_opcode_version: OpcodeVersion::V0,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -564,6 +619,9 @@ impl ConsensusConstants {
             faucet_value: MicroTari::from(0),
             transaction_weight: TransactionWeight::v1(),
             max_script_byte_size: 2048,
+            max_script_execution_cost: 102_400,
+            coinbase_extra_max_length: 64,
+            max_opcode_version: OpcodeVersion::V0,
             input_version_range,
             output_version_range,
             kernel_version_range,
@@ -616,6 +674,21 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_max_script_execution_cost(mut self, cost: u64) -> Self {
+        self.consensus.max_script_execution_cost = cost;
+        self
+    }
+
+    pub fn with_coinbase_extra_max_length(mut self, max_length: usize) -> Self {
+        self.consensus.coinbase_extra_max_length = max_length;
+        self
+    }
+
+    pub fn with_max_opcode_version(mut self, version: OpcodeVersion) -> Self {
+        self.consensus.max_opcode_version = version;
+        self
+    }
+
     pub fn with_max_block_transaction_weight(mut self, weight: u64) -> Self {
         self.consensus.max_block_transaction_weight = weight;
         self
@@ -690,4 +763,11 @@ mod test {
         let (_, reward, _) = rewards.next().unwrap();
         assert_eq!(reward, esmeralda[0].emission_tail);
     }
+
+    #[test]
+    fn target_block_interval() {
+        let esmeralda = ConsensusConstants::esmeralda();
+        // Monero blocks every 200 seconds and SHA3 blocks every 300 seconds
+        assert_eq!(esmeralda[0].get_target_block_interval(), 120);
+    }
 }
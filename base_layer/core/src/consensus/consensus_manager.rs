@@ -113,7 +113,7 @@ impl ConsensusManager {
     /// Create a new TargetDifficulty for the given proof of work using constants that are effective from the given
     /// height
     #[cfg(feature = "base_node")]
-    pub(crate) fn new_target_difficulty(&self, pow_algo: PowAlgorithm, height: u64) -> TargetDifficultyWindow {
+    pub fn new_target_difficulty(&self, pow_algo: PowAlgorithm, height: u64) -> TargetDifficultyWindow {
         use std::convert::TryFrom;
         let constants = self.consensus_constants(height);
         let block_window = constants.get_difficulty_block_window();
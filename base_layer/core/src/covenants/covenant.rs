@@ -28,11 +28,12 @@ use crate::{
     common::{byte_counter::ByteCounter, limited_reader::LimitedBytesReader},
     consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized},
     covenants::{
+        arguments::CovenantArg,
         context::CovenantContext,
         decoder::{CovenantDecodeError, CovenantTokenDecoder},
         encoder::CovenantTokenEncoder,
         error::CovenantError,
-        filters::Filter,
+        filters::{CovenantFilter, Filter},
         output_set::OutputSet,
         token::{CovenantToken, CovenantTokenCollection},
     },
@@ -107,6 +108,26 @@ impl Covenant {
         self.tokens.push(token);
     }
 
+    /// Returns the lowest block height at which the covenant can pass, as enforced by its `absolute_height` filters.
+    /// Filters can only remove outputs unless they are combined with `or`, `xor` or `not`, so a covenant that uses
+    /// those is not considered to be locked.
+    pub fn lock_height(&self) -> u64 {
+        let mut lock_height = 0;
+        let mut tokens = self.tokens.iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token.as_filter() {
+                Some(CovenantFilter::Or(_) | CovenantFilter::Xor(_) | CovenantFilter::Not(_)) => return 0,
+                Some(CovenantFilter::AbsoluteHeight(_)) => {
+                    if let Some(CovenantArg::Uint(height)) = tokens.peek().and_then(|t| t.as_arg()) {
+                        lock_height = lock_height.max(*height);
+                    }
+                },
+                _ => {},
+            }
+        }
+        lock_height
+    }
+
     #[cfg(test)]
     pub(super) fn tokens(&self) -> &[CovenantToken] {
         &self.tokens
@@ -193,6 +214,18 @@ mod test {
         assert_eq!(num_matching_outputs, 3);
     }
 
+    #[test]
+    fn it_returns_the_lock_height() {
+        assert_eq!(covenant!().lock_height(), 0);
+        let covenant = covenant!(and(
+            absolute_height(@uint(100)),
+            field_eq(@field::features_maturity, @uint(200))
+        ));
+        assert_eq!(covenant.lock_height(), 100);
+        let covenant = covenant!(or(absolute_height(@uint(100)), identity()));
+        assert_eq!(covenant.lock_height(), 0);
+    }
+
     mod consensus_encoding {
         use super::*;
 
@@ -109,7 +109,7 @@ impl OutputField {
             Features => output.features.to_consensus_bytes(),
             FeaturesOutputType => output.features.output_type.to_consensus_bytes(),
             FeaturesMaturity => output.features.maturity.to_consensus_bytes(),
-            FeaturesSideChainFeatures => output.features.sidechain_features.to_consensus_bytes(),
+            FeaturesSideChainFeatures => output.features.sidechain_features_consensus_bytes(),
             FeaturesMetadata => output.features.metadata.to_consensus_bytes(),
         }
     }
@@ -335,7 +335,7 @@ impl FromIterator<OutputField> for OutputFields {
 
 #[cfg(test)]
 mod test {
-    use tari_common_types::types::{Commitment, PublicKey};
+    use tari_common_types::types::{Commitment, FixedHash, PublicKey};
     use tari_script::script;
 
     use super::*;
@@ -359,7 +359,7 @@ mod test {
             fn it_returns_true_if_eq() {
                 let output = create_outputs(1, UtxoTestParams {
                     features: OutputFeatures {
-                        sidechain_features: Some(Box::new(SideChainFeatures {})),
+                        sidechain_features: Some(Box::new(SideChainFeatures::new(FixedHash::zero()))),
                         ..Default::default()
                     },
                     script: script![Drop Nop],
@@ -378,7 +378,7 @@ mod test {
                     .is_eq(&output, &output.features.output_type)
                     .unwrap());
                 assert!(OutputField::FeaturesSideChainFeatures
-                    .is_eq(&output, &SideChainFeatures {})
+                    .is_eq(&output, &SideChainFeatures::new(FixedHash::zero()))
                     .unwrap());
                 assert!(OutputField::FeaturesSideChainFeatures
                     .is_eq(&output, output.features.sidechain_features.as_ref().unwrap())
@@ -395,7 +395,7 @@ mod test {
             fn it_returns_false_if_not_eq() {
                 let output = create_outputs(1, UtxoTestParams {
                     features: OutputFeatures {
-                        sidechain_features: Some(Box::new(SideChainFeatures {})),
+                        sidechain_features: Some(Box::new(SideChainFeatures::new(FixedHash::zero()))),
                         ..Default::default()
                     },
                     script: script![Drop Nop],
@@ -39,6 +39,7 @@ impl Filter for AndFilter {
 #[cfg(test)]
 mod test {
 
+    use tari_common_types::types::FixedHash;
     use tari_script::script;
 
     use super::*;
@@ -56,12 +57,12 @@ mod test {
         let input = create_input();
         let (mut context, outputs) = setup_filter_test(&covenant, &input, 0, |outputs| {
             outputs[5].features.maturity = 42;
-            outputs[5].features.sidechain_features = Some(Box::new(SideChainFeatures {}));
+            outputs[5].features.sidechain_features = Some(Box::new(SideChainFeatures::new(FixedHash::zero())));
             outputs[7].features.maturity = 42;
-            outputs[7].features.sidechain_features = Some(Box::new(SideChainFeatures {}));
+            outputs[7].features.sidechain_features = Some(Box::new(SideChainFeatures::new(FixedHash::zero())));
             // Does not have maturity = 42
             outputs[8].features.maturity = 123;
-            outputs[8].features.sidechain_features = Some(Box::new(SideChainFeatures {}));
+            outputs[8].features.sidechain_features = Some(Box::new(SideChainFeatures::new(FixedHash::zero())));
         });
 
         let mut output_set = OutputSet::new(&outputs);
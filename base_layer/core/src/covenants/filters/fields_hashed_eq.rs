@@ -40,7 +40,7 @@ impl Filter for FieldsHashedEqFilter {
 
 #[cfg(test)]
 mod test {
-    use tari_common_types::types::Challenge;
+    use tari_common_types::types::{Challenge, FixedHash};
     use tari_crypto::hashing::DomainSeparation;
 
     use super::*;
@@ -60,7 +60,7 @@ mod test {
     fn it_filters_outputs_with_fields_that_hash_to_given_hash() {
         let features = OutputFeatures {
             maturity: 42,
-            sidechain_features: Some(Box::new(SideChainFeatures {})),
+            sidechain_features: Some(Box::new(SideChainFeatures::new(FixedHash::zero()))),
             ..Default::default()
         };
         let mut hasher = Challenge::new();
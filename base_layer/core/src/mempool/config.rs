@@ -23,18 +23,36 @@
 use serde::{Deserialize, Serialize};
 use tari_common::SubConfigPath;
 
-use crate::mempool::{reorg_pool::ReorgPoolConfig, unconfirmed_pool::UnconfirmedPoolConfig};
+use crate::{
+    mempool::{reorg_pool::ReorgPoolConfig, unconfirmed_pool::UnconfirmedPoolConfig},
+    transactions::{fee::Fee, tari_amount::MicroTari},
+};
 
 /// Configuration for the Mempool.
-#[derive(Clone, Deserialize, Serialize, Default, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct MempoolConfig {
     override_from: Option<String>,
+    /// The minimum fee per gram of the transactions that are submitted to the mempool, i.e. the minimum relay fee.
+    /// Wallets query it to avoid broadcasting transactions that the node rejects.
+    pub min_fee_per_gram: MicroTari,
     pub unconfirmed_pool: UnconfirmedPoolConfig,
     pub reorg_pool: ReorgPoolConfig,
     pub service: MempoolServiceConfig,
 }
 
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            override_from: None,
+            min_fee_per_gram: Fee::MINIMUM_FEE_PER_GRAM,
+            unconfirmed_pool: UnconfirmedPoolConfig::default(),
+            reorg_pool: ReorgPoolConfig::default(),
+            service: MempoolServiceConfig::default(),
+        }
+    }
+}
+
 impl SubConfigPath for MempoolConfig {
     fn main_key_prefix() -> &'static str {
         "mempool"
@@ -37,7 +37,7 @@ use crate::{
         StatsResponse,
         TxStorageResponse,
     },
-    transactions::transaction_components::Transaction,
+    transactions::{tari_amount::MicroTari, transaction_components::Transaction},
     validation::MempoolTransactionValidation,
 };
 
@@ -66,6 +66,11 @@ impl Mempool {
         self.with_write_access(|storage| storage.insert(tx)).await
     }
 
+    /// Insert a package of dependent transactions into the Mempool. Either all of them are stored or none are.
+    pub async fn insert_package(&self, txs: Vec<Arc<Transaction>>) -> Result<TxStorageResponse, MempoolError> {
+        self.with_write_access(|storage| storage.insert_package(txs)).await
+    }
+
     /// Inserts all transactions into the mempool.
     pub async fn insert_all(&self, transactions: Vec<Arc<Transaction>>) -> Result<(), MempoolError> {
         self.with_write_access(|storage| {
@@ -151,6 +156,11 @@ impl Mempool {
             .await
     }
 
+    /// Returns the minimum fee per gram that transactions must pay to be accepted into the Mempool.
+    pub async fn min_fee_per_gram(&self) -> Result<MicroTari, MempoolError> {
+        self.with_read_access(|storage| Ok(storage.min_fee_per_gram())).await
+    }
+
     async fn with_read_access<F, T>(&self, callback: F) -> Result<T, MempoolError>
     where
         F: FnOnce(&MempoolStorage) -> Result<T, MempoolError> + Send + 'static,
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use log::*;
 use tari_common_types::types::{PrivateKey, Signature};
@@ -38,8 +38,9 @@ use crate::{
         StateResponse,
         StatsResponse,
         TxStorageResponse,
+        MAX_TRANSACTION_PACKAGE_SIZE,
     },
-    transactions::{transaction_components::Transaction, weight::TransactionWeight},
+    transactions::{tari_amount::MicroTari, transaction_components::Transaction, weight::TransactionWeight},
     validation::{MempoolTransactionValidation, ValidationError},
 };
 
@@ -53,6 +54,7 @@ pub struct MempoolStorage {
     reorg_pool: ReorgPool,
     validator: Box<dyn MempoolTransactionValidation>,
     rules: ConsensusManager,
+    min_fee_per_gram: MicroTari,
 }
 
 impl MempoolStorage {
@@ -67,12 +69,38 @@ impl MempoolStorage {
             reorg_pool: ReorgPool::new(config.reorg_pool),
             validator,
             rules,
+            min_fee_per_gram: config.min_fee_per_gram,
         }
     }
 
     /// Insert an unconfirmed transaction into the Mempool. The transaction *MUST* have passed through the validation
     /// pipeline already and will thus always be internally consistent by this stage
     pub fn insert(&mut self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
+        if self.is_below_min_fee(&[tx.clone()]) {
+            debug!(
+                target: LOG_TARGET,
+                "Rejecting transaction with a fee of {} below the minimum relay fee",
+                tx.body.get_total_fee()
+            );
+            return Ok(TxStorageResponse::NotStoredFeeTooLow);
+        }
+        self.validate_and_insert(tx)
+    }
+
+    /// The minimum fee per gram of the transactions that are submitted to the mempool
+    pub fn min_fee_per_gram(&self) -> MicroTari {
+        self.min_fee_per_gram
+    }
+
+    /// Returns true if the transactions pay less than the minimum fee per gram for their combined weight
+    fn is_below_min_fee(&self, txs: &[Arc<Transaction>]) -> bool {
+        let weighting = self.get_transaction_weighting(0);
+        let weight = txs.iter().map(|tx| weighting.calculate_body(&tx.body)).sum::<u64>();
+        let fee = txs.iter().map(|tx| tx.body.get_total_fee()).sum::<MicroTari>();
+        fee < MicroTari::from(weight) * self.min_fee_per_gram
+    }
+
+    fn validate_and_insert(&mut self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
         let tx_id = tx
             .body
             .kernels()
@@ -119,14 +147,69 @@ impl MempoolStorage {
         }
     }
 
+    /// Insert a package of dependent transactions, e.g. a transaction and the unconfirmed parent whose change it
+    /// spends, into the Mempool. The transactions are validated in dependency order, so a child can spend the
+    /// outputs of its parents in the package, and either all transactions of the package are stored or none are.
+    pub fn insert_package(&mut self, txs: Vec<Arc<Transaction>>) -> Result<TxStorageResponse, MempoolError> {
+        if txs.is_empty() || txs.len() > MAX_TRANSACTION_PACKAGE_SIZE {
+            debug!(
+                target: LOG_TARGET,
+                "Rejecting package of {} transactions, packages must contain between 1 and {} transactions",
+                txs.len(),
+                MAX_TRANSACTION_PACKAGE_SIZE
+            );
+            return Ok(TxStorageResponse::NotStored);
+        }
+        let txs = match order_package(txs) {
+            Some(txs) => txs,
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Rejecting package with transactions that spend the same output or depend on each other"
+                );
+                return Ok(TxStorageResponse::NotStoredConsensus);
+            },
+        };
+        // The children of a package pay for their parents, so only the package as a whole has to pay the minimum fee
+        if self.is_below_min_fee(&txs) {
+            debug!(
+                target: LOG_TARGET,
+                "Rejecting package of {} transactions with a fee below the minimum relay fee",
+                txs.len()
+            );
+            return Ok(TxStorageResponse::NotStoredFeeTooLow);
+        }
+
+        let mut inserted = Vec::with_capacity(txs.len());
+        for tx in txs {
+            if self.has_transaction(&tx)?.is_stored() {
+                continue;
+            }
+            let tx_storage = self.validate_and_insert(tx.clone())?;
+            if tx_storage != TxStorageResponse::UnconfirmedPool {
+                debug!(
+                    target: LOG_TARGET,
+                    "Package rejected, removing {} of its transactions from the unconfirmed pool: {}",
+                    inserted.len(),
+                    tx_storage
+                );
+                self.unconfirmed_pool.remove_transactions(&inserted);
+                return Ok(tx_storage);
+            }
+            inserted.push(tx);
+        }
+        Ok(TxStorageResponse::UnconfirmedPool)
+    }
+
     fn get_transaction_weighting(&self, height: u64) -> TransactionWeight {
         *self.rules.consensus_constants(height).transaction_weight()
     }
 
-    // Insert a set of new transactions into the UTxPool.
+    // Insert a set of new transactions into the UTxPool. These were already accepted, so the minimum fee is not applied
+    // again.
     fn insert_txs(&mut self, txs: Vec<Arc<Transaction>>) -> Result<(), MempoolError> {
         for tx in txs {
-            self.insert(tx)?;
+            self.validate_and_insert(tx)?;
         }
         Ok(())
     }
@@ -319,3 +402,38 @@ impl MempoolStorage {
         Ok(stats)
     }
 }
+
+/// Orders the transactions of a package so that every transaction comes after the transactions whose outputs it spends.
+/// Returns None if two transactions spend the same output or if the transactions depend on each other in a cycle.
+fn order_package(txs: Vec<Arc<Transaction>>) -> Option<Vec<Arc<Transaction>>> {
+    let mut spent_outputs = HashSet::new();
+    if !txs
+        .iter()
+        .flat_map(|tx| tx.body.inputs())
+        .all(|input| spent_outputs.insert(input.output_hash()))
+    {
+        return None;
+    }
+
+    let mut ordered = Vec::with_capacity(txs.len());
+    let mut remaining = txs;
+    while !remaining.is_empty() {
+        let unordered_outputs = remaining
+            .iter()
+            .flat_map(|tx| tx.body.outputs())
+            .map(|output| output.hash())
+            .collect::<HashSet<_>>();
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|tx| {
+            tx.body
+                .inputs()
+                .iter()
+                .all(|input| !unordered_outputs.contains(&input.output_hash()))
+        });
+        if ready.is_empty() {
+            return None;
+        }
+        ordered.extend(ready);
+        remaining = blocked;
+    }
+    Some(ordered)
+}
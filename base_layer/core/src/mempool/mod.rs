@@ -102,6 +102,9 @@ pub struct StateResponse {
     pub reorg_pool: Vec<Signature>,
 }
 
+/// The maximum number of transactions in a package of dependent transactions submitted to the mempool together
+pub const MAX_TRANSACTION_PACKAGE_SIZE: usize = 25;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TxStorageResponse {
     UnconfirmedPool,
@@ -110,6 +113,7 @@ pub enum TxStorageResponse {
     NotStoredTimeLocked,
     NotStoredAlreadySpent,
     NotStoredConsensus,
+    NotStoredFeeTooLow,
     NotStored,
 }
 
@@ -128,6 +132,7 @@ impl Display for TxStorageResponse {
             TxStorageResponse::NotStoredTimeLocked => "Not stored time locked transaction",
             TxStorageResponse::NotStoredAlreadySpent => "Not stored output already spent",
             TxStorageResponse::NotStoredConsensus => "Not stored due to consensus rule",
+            TxStorageResponse::NotStoredFeeTooLow => "Not stored fee below the minimum relay fee",
             TxStorageResponse::NotStored => "Not stored",
         };
         fmt.write_str(storage)
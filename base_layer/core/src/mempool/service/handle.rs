@@ -32,7 +32,7 @@ use crate::{
         StatsResponse,
         TxStorageResponse,
     },
-    transactions::transaction_components::Transaction,
+    transactions::{tari_amount::MicroTari, transaction_components::Transaction},
 };
 
 #[derive(Clone)]
@@ -83,6 +83,22 @@ impl MempoolHandle {
         }
     }
 
+    /// Submits a package of dependent transactions, e.g. a transaction and the unconfirmed parent it spends from.
+    /// Either all of them are accepted or none are.
+    pub async fn submit_transaction_package(
+        &mut self,
+        transactions: Vec<Transaction>,
+    ) -> Result<TxStorageResponse, MempoolServiceError> {
+        match self
+            .inner
+            .call(MempoolRequest::SubmitTransactionPackage(transactions))
+            .await??
+        {
+            MempoolResponse::TxStorage(resp) => Ok(resp),
+            _ => panic!("Incorrect response"),
+        }
+    }
+
     pub async fn get_fee_per_gram_stats(
         &mut self,
         count: usize,
@@ -97,4 +113,12 @@ impl MempoolHandle {
             _ => panic!("Incorrect response"),
         }
     }
+
+    /// Returns the minimum fee per gram that the mempool relays transactions for
+    pub async fn get_min_fee_per_gram(&mut self) -> Result<MicroTari, MempoolServiceError> {
+        match self.inner.call(MempoolRequest::GetMinFeePerGram).await?? {
+            MempoolResponse::MinFeePerGram(min_fee_per_gram) => Ok(min_fee_per_gram),
+            _ => panic!("Incorrect response"),
+        }
+    }
 }
@@ -57,7 +57,15 @@ impl MempoolInboundHandlers {
     /// Handle inbound Mempool service requests from remote nodes and local services.
     pub async fn handle_request(&mut self, request: MempoolRequest) -> Result<MempoolResponse, MempoolServiceError> {
         debug!(target: LOG_TARGET, "Handling remote request: {}", request);
-        use MempoolRequest::{GetFeePerGramStats, GetState, GetStats, GetTxStateByExcessSig, SubmitTransaction};
+        use MempoolRequest::{
+            GetFeePerGramStats,
+            GetMinFeePerGram,
+            GetState,
+            GetStats,
+            GetTxStateByExcessSig,
+            SubmitTransaction,
+            SubmitTransactionPackage,
+        };
         match request {
             GetStats => Ok(MempoolResponse::Stats(self.mempool.stats().await?)),
             GetState => Ok(MempoolResponse::State(self.mempool.state().await?)),
@@ -72,10 +80,19 @@ impl MempoolInboundHandlers {
                 );
                 Ok(MempoolResponse::TxStorage(self.submit_transaction(tx, None).await?))
             },
+            SubmitTransactionPackage(txs) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Package of {} transaction(s) submitted using request.",
+                    txs.len()
+                );
+                Ok(MempoolResponse::TxStorage(self.submit_transaction_package(txs).await?))
+            },
             GetFeePerGramStats { count, tip_height } => {
                 let stats = self.mempool.get_fee_per_gram_stats(count, tip_height).await?;
                 Ok(MempoolResponse::FeePerGramStats { response: stats })
             },
+            GetMinFeePerGram => Ok(MempoolResponse::MinFeePerGram(self.mempool.min_fee_per_gram().await?)),
         }
     }
 
@@ -149,6 +166,36 @@ impl MempoolInboundHandlers {
         }
     }
 
+    /// Submits a package of dependent transactions to the mempool and propagates them, parents first, if the package
+    /// was accepted. Peers that receive the child before its parent will reject it as an orphan, so the transactions
+    /// are propagated one at a time in dependency order.
+    async fn submit_transaction_package(
+        &mut self,
+        txs: Vec<Transaction>,
+    ) -> Result<TxStorageResponse, MempoolServiceError> {
+        let txs = txs.into_iter().map(Arc::new).collect::<Vec<_>>();
+        let tx_storage = self.mempool.insert_package(txs.clone()).await?;
+        let counter = if tx_storage.is_stored() {
+            metrics::inbound_transactions(None)
+        } else {
+            metrics::rejected_inbound_transactions(None)
+        };
+        counter.inc_by(txs.len() as u64);
+        self.update_pool_size_metrics().await;
+        debug!(
+            target: LOG_TARGET,
+            "Package of {} transaction(s) submitted to mempool: {}",
+            txs.len(),
+            tx_storage
+        );
+        if matches!(tx_storage, TxStorageResponse::UnconfirmedPool) {
+            for tx in txs {
+                self.outbound_nmi.propagate_tx(tx, vec![]).await?;
+            }
+        }
+        Ok(tx_storage)
+    }
+
     #[allow(clippy::cast_possible_wrap)]
     async fn update_pool_size_metrics(&self) {
         if let Ok(stats) = self.mempool.stats().await {
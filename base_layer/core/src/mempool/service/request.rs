@@ -36,7 +36,9 @@ pub enum MempoolRequest {
     GetState,
     GetTxStateByExcessSig(Signature),
     SubmitTransaction(Transaction),
+    SubmitTransactionPackage(Vec<Transaction>),
     GetFeePerGramStats { count: usize, tip_height: u64 },
+    GetMinFeePerGram,
 }
 
 impl Display for MempoolRequest {
@@ -52,9 +54,13 @@ impl Display for MempoolRequest {
                 "SubmitTransaction ({})",
                 tx.body.kernels()[0].excess_sig.get_signature().to_hex()
             ),
+            MempoolRequest::SubmitTransactionPackage(txs) => {
+                write!(f, "SubmitTransactionPackage ({} transaction(s))", txs.len())
+            },
             MempoolRequest::GetFeePerGramStats { count, tip_height } => {
                 write!(f, "GetFeePerGramStats(count: {}, tip_height: {})", *count, *tip_height)
             },
+            MempoolRequest::GetMinFeePerGram => write!(f, "GetMinFeePerGram"),
         }
     }
 }
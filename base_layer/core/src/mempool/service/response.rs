@@ -24,7 +24,10 @@ use std::{fmt, fmt::Formatter};
 
 use tari_common_types::waiting_requests::RequestKey;
 
-use crate::mempool::{FeePerGramStat, StateResponse, StatsResponse, TxStorageResponse};
+use crate::{
+    mempool::{FeePerGramStat, StateResponse, StatsResponse, TxStorageResponse},
+    transactions::tari_amount::MicroTari,
+};
 
 /// API Response enum for Mempool responses.
 #[derive(Clone, Debug)]
@@ -33,16 +36,18 @@ pub enum MempoolResponse {
     State(StateResponse),
     TxStorage(TxStorageResponse),
     FeePerGramStats { response: Vec<FeePerGramStat> },
+    MinFeePerGram(MicroTari),
 }
 
 impl fmt::Display for MempoolResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        use MempoolResponse::{FeePerGramStats, State, Stats, TxStorage};
+        use MempoolResponse::{FeePerGramStats, MinFeePerGram, State, Stats, TxStorage};
         match &self {
             Stats(_) => write!(f, "Stats"),
             State(_) => write!(f, "State"),
             TxStorage(_) => write!(f, "TxStorage"),
             FeePerGramStats { response } => write!(f, "FeePerGramStats({} item(s))", response.len()),
+            MinFeePerGram(min_fee_per_gram) => write!(f, "MinFeePerGram({})", min_fee_per_gram),
         }
     }
 }
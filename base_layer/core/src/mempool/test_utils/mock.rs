@@ -29,12 +29,15 @@ use futures::StreamExt;
 use tari_service_framework::reply_channel;
 use tokio::{sync::Mutex, task};
 
-use crate::mempool::{
-    service::{MempoolHandle, MempoolRequest, MempoolResponse},
-    MempoolServiceError,
-    StateResponse,
-    StatsResponse,
-    TxStorageResponse,
+use crate::{
+    mempool::{
+        service::{MempoolHandle, MempoolRequest, MempoolResponse},
+        MempoolServiceError,
+        StateResponse,
+        StatsResponse,
+        TxStorageResponse,
+    },
+    transactions::fee::Fee,
 };
 
 pub fn create_mempool_service_mock() -> (MempoolHandle, MempoolMockState) {
@@ -124,7 +127,15 @@ impl MempoolServiceMock {
     }
 
     async fn handle_request(&self, req: MempoolRequest) -> Result<MempoolResponse, MempoolServiceError> {
-        use MempoolRequest::{GetFeePerGramStats, GetState, GetStats, GetTxStateByExcessSig, SubmitTransaction};
+        use MempoolRequest::{
+            GetFeePerGramStats,
+            GetMinFeePerGram,
+            GetState,
+            GetStats,
+            GetTxStateByExcessSig,
+            SubmitTransaction,
+            SubmitTransactionPackage,
+        };
 
         self.state.inc_call_count();
         match req {
@@ -133,12 +144,13 @@ impl MempoolServiceMock {
             GetTxStateByExcessSig(_) => Ok(MempoolResponse::TxStorage(
                 self.state.get_tx_state_by_excess_sig.lock().await.clone(),
             )),
-            SubmitTransaction(_) => Ok(MempoolResponse::TxStorage(
+            SubmitTransaction(_) | SubmitTransactionPackage(_) => Ok(MempoolResponse::TxStorage(
                 self.state.submit_transaction.lock().await.clone(),
             )),
             GetFeePerGramStats { .. } => {
                 unimplemented!()
             },
+            GetMinFeePerGram => Ok(MempoolResponse::MinFeePerGram(Fee::MINIMUM_FEE_PER_GRAM)),
         }
     }
 }
@@ -478,6 +478,22 @@ impl UnconfirmedPool {
         Some(prioritized_transaction.transaction)
     }
 
+    /// Removes the given transactions from the pool, e.g. the transactions of a package that could only be partially
+    /// inserted
+    pub fn remove_transactions(&mut self, txs: &[Arc<Transaction>]) {
+        for tx in txs {
+            let tx_keys = self
+                .tx_by_key
+                .iter()
+                .filter(|(_, ptx)| Arc::ptr_eq(&ptx.transaction, tx))
+                .map(|(k, _)| *k)
+                .collect::<Vec<_>>();
+            for tx_key in tx_keys {
+                self.remove_transaction(tx_key);
+            }
+        }
+    }
+
     /// Remove all unconfirmed transactions that have become time locked. This can happen when the chain height was
     /// reduced on some reorgs.
     pub fn remove_timelocked(&mut self, tip_height: u64) {
@@ -20,9 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+//! Estimates of the hash rate of the network from the target difficulties of recent blocks, for mining dashboards and
+//! the `get_network_difficulty` gRPC method of the base node.
+
 use std::collections::VecDeque;
 
-use tari_core::{
+use crate::{
     consensus::ConsensusManager,
     proof_of_work::{Difficulty, PowAlgorithm},
 };
@@ -100,15 +103,104 @@ impl HashRateMovingAverage {
     }
 }
 
+/// The proof of work and target difficulty of a block, which are all that is needed to estimate the hash rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultySample {
+    pub height: u64,
+    pub timestamp: u64,
+    pub pow_algo: PowAlgorithm,
+    pub target_difficulty: Difficulty,
+}
+
+/// The estimated hash rate of the network, per proof of work algorithm
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetworkHashRate {
+    /// The height of the most recent block the estimate is based on
+    pub tip_height: u64,
+    pub sha3: AlgorithmHashRate,
+    pub monero: AlgorithmHashRate,
+}
+
+impl NetworkHashRate {
+    /// The combined estimated hash rate of both algorithms, as reported by the `get_network_difficulty` gRPC method
+    pub fn estimated_hash_rate(&self) -> u64 {
+        self.sha3
+            .estimated_hash_rate
+            .saturating_add(self.monero.estimated_hash_rate)
+    }
+}
+
+/// The estimated hash rate and difficulty trend of a single proof of work algorithm
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlgorithmHashRate {
+    /// The target difficulty of the most recent block mined with the algorithm
+    pub difficulty: Difficulty,
+    /// The moving average of the hash rate, in hashes per second
+    pub estimated_hash_rate: u64,
+    /// The relative change of the target difficulty over the blocks, e.g. 0.05 if it rose by 5%
+    pub difficulty_change: f64,
+    /// The average number of seconds between the blocks, if there were at least two blocks
+    pub average_block_time: Option<u64>,
+    /// The number of blocks mined with the algorithm that the estimate is based on
+    pub num_blocks: usize,
+}
+
+/// Estimates the hash rate of the network from recent blocks, ordered by height. The hash rate is a moving average
+/// over the most recent blocks of each algorithm, while the difficulty trend and block time cover all the blocks.
+pub fn estimate_network_hash_rate(
+    samples: &[DifficultySample],
+    consensus_manager: &ConsensusManager,
+) -> NetworkHashRate {
+    NetworkHashRate {
+        tip_height: samples.last().map(|s| s.height).unwrap_or_default(),
+        sha3: estimate_algorithm_hash_rate(samples, PowAlgorithm::Sha3, consensus_manager),
+        monero: estimate_algorithm_hash_rate(samples, PowAlgorithm::Monero, consensus_manager),
+    }
+}
+
+fn estimate_algorithm_hash_rate(
+    samples: &[DifficultySample],
+    pow_algo: PowAlgorithm,
+    consensus_manager: &ConsensusManager,
+) -> AlgorithmHashRate {
+    let samples = samples.iter().filter(|s| s.pow_algo == pow_algo).collect::<Vec<_>>();
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return AlgorithmHashRate::default(),
+    };
+
+    let mut moving_average = HashRateMovingAverage::new(pow_algo, consensus_manager.clone());
+    for sample in &samples {
+        moving_average.add(sample.height, sample.target_difficulty);
+    }
+
+    let first_difficulty = first.target_difficulty.as_u64();
+    let difficulty_change = if first_difficulty == 0 {
+        0.0
+    } else {
+        (last.target_difficulty.as_u64() as f64 - first_difficulty as f64) / first_difficulty as f64
+    };
+    let average_block_time = if samples.len() > 1 {
+        Some(last.timestamp.saturating_sub(first.timestamp) / (samples.len() as u64 - 1))
+    } else {
+        None
+    };
+
+    AlgorithmHashRate {
+        difficulty: last.target_difficulty,
+        estimated_hash_rate: moving_average.average(),
+        difficulty_change,
+        average_block_time,
+        num_blocks: samples.len(),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use tari_core::{
-        consensus::{ConsensusConstants, ConsensusManagerBuilder},
-        proof_of_work::{Difficulty, PowAlgorithm},
-    };
-    use tari_p2p::Network;
+    use tari_common::configuration::Network;
 
-    use super::HashRateMovingAverage;
+    use super::*;
+    use crate::consensus::{ConsensusConstants, ConsensusManagerBuilder};
 
     #[test]
     fn window_is_empty() {
@@ -195,4 +287,49 @@ mod test {
         moving_average.add(height, Difficulty::from(difficulty));
         assert_eq!(moving_average.average(), expected_hash_rate);
     }
+
+    #[test]
+    fn it_estimates_the_hash_rate_per_algorithm() {
+        let consensus_manager = ConsensusManagerBuilder::new(Network::Esmeralda)
+            .add_consensus_constants(ConsensusConstants::esmeralda()[0].clone())
+            .build();
+        assert_eq!(
+            estimate_network_hash_rate(&[], &consensus_manager),
+            NetworkHashRate::default()
+        );
+
+        let sample = |height, timestamp, pow_algo, difficulty: u64| DifficultySample {
+            height,
+            timestamp,
+            pow_algo,
+            target_difficulty: Difficulty::from(difficulty),
+        };
+        let samples = [
+            sample(1, 1000, PowAlgorithm::Sha3, 90_000),
+            sample(2, 1100, PowAlgorithm::Monero, 50_000),
+            sample(3, 1300, PowAlgorithm::Sha3, 99_000),
+            sample(4, 1600, PowAlgorithm::Sha3, 108_000),
+        ];
+        let estimate = estimate_network_hash_rate(&samples, &consensus_manager);
+        assert_eq!(estimate.tip_height, 4);
+
+        let sha3_target_time = consensus_manager
+            .consensus_constants(4)
+            .get_diff_target_block_interval(PowAlgorithm::Sha3);
+        assert_eq!(estimate.sha3.num_blocks, 3);
+        assert_eq!(estimate.sha3.difficulty, Difficulty::from(108_000));
+        assert_eq!(
+            estimate.sha3.estimated_hash_rate,
+            (90_000 / sha3_target_time + 99_000 / sha3_target_time + 108_000 / sha3_target_time) / 3
+        );
+        assert!((estimate.sha3.difficulty_change - 0.2).abs() < f64::EPSILON);
+        assert_eq!(estimate.sha3.average_block_time, Some(300));
+
+        assert_eq!(estimate.monero.num_blocks, 1);
+        assert_eq!(estimate.monero.average_block_time, None);
+        assert_eq!(
+            estimate.estimated_hash_rate(),
+            estimate.sha3.estimated_hash_rate + estimate.monero.estimated_hash_rate
+        );
+    }
 }
@@ -30,6 +30,17 @@ mod error;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use error::{DifficultyAdjustmentError, PowError};
 
+#[cfg(any(feature = "base_node", feature = "transactions"))]
+mod hash_rate;
+#[cfg(any(feature = "base_node", feature = "transactions"))]
+pub use hash_rate::{
+    estimate_network_hash_rate,
+    AlgorithmHashRate,
+    DifficultySample,
+    HashRateMovingAverage,
+    NetworkHashRate,
+};
+
 #[cfg(feature = "base_node")]
 pub mod monero_rx;
 #[cfg(feature = "base_node")]
@@ -27,7 +27,7 @@ use std::{
     sync::Arc,
 };
 
-use tari_common_types::types::{BlindingFactor, BulletRangeProof, Commitment, PublicKey};
+use tari_common_types::types::{BlindingFactor, BulletRangeProof, Commitment, FixedHash, PublicKey};
 use tari_crypto::tari_utilities::{ByteArray, ByteArrayError};
 use tari_script::{ExecutionStack, TariScript};
 use tari_utilities::convert::try_convert_all;
@@ -330,16 +330,22 @@ impl From<OutputFeatures> for proto::types::OutputFeatures {
 
 //---------------------------------- SideChainFeatures --------------------------------------------//
 impl From<SideChainFeatures> for proto::types::SideChainFeatures {
-    fn from(_value: SideChainFeatures) -> Self {
-        Self {}
+    fn from(value: SideChainFeatures) -> Self {
+        Self {
+            sidechain_id: value.sidechain_id.to_vec(),
+            committee_signature: value.committee_signature,
+        }
     }
 }
 
 impl TryFrom<proto::types::SideChainFeatures> for SideChainFeatures {
     type Error = String;
 
-    fn try_from(_features: proto::types::SideChainFeatures) -> Result<Self, Self::Error> {
-        Ok(Self {})
+    fn try_from(features: proto::types::SideChainFeatures) -> Result<Self, Self::Error> {
+        let sidechain_id = FixedHash::try_from(features.sidechain_id).map_err(|err| err.to_string())?;
+        let features = SideChainFeatures::new(sidechain_id).with_committee_signature(features.committee_signature);
+        features.validate().map_err(|err| err.to_string())?;
+        Ok(features)
     }
 }
 
@@ -61,6 +61,7 @@ use crate::{
             TransactionInput,
             TransactionKernel,
             TransactionOutput,
+            UniqueAsset,
         },
         weight::TransactionWeight,
     },
@@ -187,6 +188,54 @@ impl AggregateBody {
         self.kernels = vec![kernel];
     }
 
+    /// Adds the inputs, outputs and kernels of `other` to this body, e.g. to merge several transactions of a wallet
+    /// into a single transaction before it is broadcast. Kernel signatures cannot be combined non-interactively, so
+    /// the kernels are kept as they are. Bodies can only be aggregated if they do not contain coinbases, do not
+    /// spend the same input and do not share outputs or kernel excesses. The body has to be sorted again
+    /// afterwards.
+    pub fn aggregate(&mut self, other: AggregateBody) -> Result<(), TransactionError> {
+        for body in [&*self, &other] {
+            if body.outputs.iter().any(|o| o.is_coinbase()) ||
+                body.kernels
+                    .iter()
+                    .any(|k| k.features.contains(KernelFeatures::COINBASE_KERNEL))
+            {
+                return Err(TransactionError::InvalidAggregation(
+                    "Coinbases cannot be aggregated".to_string(),
+                ));
+            }
+        }
+        if other.inputs.iter().any(|input| self.inputs.contains(input)) {
+            return Err(TransactionError::InvalidAggregation(
+                "Both bodies spend the same input".to_string(),
+            ));
+        }
+        if other
+            .outputs
+            .iter()
+            .any(|output| self.outputs.iter().any(|o| o.commitment == output.commitment))
+        {
+            return Err(TransactionError::InvalidAggregation(
+                "Both bodies contain the same output".to_string(),
+            ));
+        }
+        if other
+            .kernels
+            .iter()
+            .any(|kernel| self.kernels.iter().any(|k| k.excess == kernel.excess))
+        {
+            return Err(TransactionError::InvalidAggregation(
+                "Both bodies contain a kernel with the same excess".to_string(),
+            ));
+        }
+
+        let (mut inputs, mut outputs, mut kernels) = other.dissolve();
+        self.add_inputs(&mut inputs);
+        self.add_outputs(&mut outputs);
+        self.add_kernels(&mut kernels);
+        Ok(())
+    }
+
     pub fn contains_duplicated_inputs(&self) -> bool {
         // If the body is sorted, can do a linear check instead of n^2
         if self.sorted {
@@ -251,6 +300,22 @@ impl AggregateBody {
         Ok(())
     }
 
+    /// Verify that every unique asset output is either minted, i.e. signed by the parent of the asset for the output,
+    /// or transfers an asset that is spent by an input of this body. Each spent asset can be transferred to one output
+    /// only. The inputs must not be compact.
+    pub fn verify_unique_assets(&self) -> Result<(), TransactionError> {
+        let mut spent_asset_ids = Vec::new();
+        for input in self.inputs() {
+            if let Some(asset) = UniqueAsset::from_features(input.features()?)? {
+                spent_asset_ids.push(asset.asset_id());
+            }
+        }
+        for output in self.outputs() {
+            output.verify_unique_asset(&mut spent_asset_ids)?;
+        }
+        Ok(())
+    }
+
     pub fn get_total_fee(&self) -> MicroTari {
         let mut fee = MicroTari::from(0);
         for kernel in &self.kernels {
@@ -376,6 +441,18 @@ impl AggregateBody {
         Ok(())
     }
 
+    /// Checks that the kernel excesses of an aggregated body, offset by the sum of the offsets of the transactions it
+    /// was aggregated from, balance the inputs, outputs and fees, without running the more expensive checks of
+    /// [validate_internal_consistency](Self::validate_internal_consistency)
+    pub fn verify_aggregated_offset(
+        &self,
+        aggregated_offset: &BlindingFactor,
+        factory: &CommitmentFactory,
+    ) -> Result<(), TransactionError> {
+        let offset = factory.commit_value(aggregated_offset, 0);
+        self.validate_kernel_sum(offset, factory)
+    }
+
     pub fn dissolve(self) -> (Vec<TransactionInput>, Vec<TransactionOutput>, Vec<TransactionKernel>) {
         (self.inputs, self.outputs, self.kernels)
     }
@@ -78,6 +78,8 @@ pub enum CoinbaseBuildError {
     InvalidTransaction,
     #[error("Unable to produce a spender offset key from spend key hash")]
     InvalidSenderOffsetKey,
+    #[error("The coinbase extra is {len} bytes but the maximum is {max} bytes")]
+    ExtraTooLong { len: usize, max: usize },
 }
 
 pub struct CoinbaseBuilder {
@@ -90,6 +92,7 @@ pub struct CoinbaseBuilder {
     private_nonce: Option<PrivateKey>,
     rewind_data: Option<RewindData>,
     covenant: Covenant,
+    extra: Vec<u8>,
 }
 
 impl CoinbaseBuilder {
@@ -106,6 +109,7 @@ impl CoinbaseBuilder {
             private_nonce: None,
             rewind_data: None,
             covenant: Covenant::default(),
+            extra: Vec::new(),
         }
     }
 
@@ -152,6 +156,12 @@ impl CoinbaseBuilder {
         self
     }
 
+    /// Set the extra data of the coinbase output, e.g. a pool tag or merge mining data
+    pub fn with_extra(mut self, extra: Vec<u8>) -> Self {
+        self.extra = extra;
+        self
+    }
+
     /// Add the rewind data needed to make this coinbase output rewindable
     pub fn with_rewind_data(mut self, rewind_data: RewindData) -> Self {
         self.rewind_data = Some(rewind_data);
@@ -190,6 +200,12 @@ impl CoinbaseBuilder {
         block_reward: MicroTari,
     ) -> Result<(Transaction, UnblindedOutput), CoinbaseBuildError> {
         let height = self.block_height.ok_or(CoinbaseBuildError::MissingBlockHeight)?;
+        if self.extra.len() > constants.coinbase_extra_max_length() {
+            return Err(CoinbaseBuildError::ExtraTooLong {
+                len: self.extra.len(),
+                max: constants.coinbase_extra_max_length(),
+            });
+        }
         let total_reward = block_reward + self.fees.ok_or(CoinbaseBuildError::MissingFees)?;
         let nonce = self.private_nonce.ok_or(CoinbaseBuildError::MissingNonce)?;
         let public_nonce = PublicKey::from_secret_key(&nonce);
@@ -201,7 +217,8 @@ impl CoinbaseBuilder {
             .factories
             .commitment
             .commit_value(&spending_key, total_reward.as_u64());
-        let output_features = OutputFeatures::create_coinbase(height + constants.coinbase_lock_height());
+        let output_features =
+            OutputFeatures::create_coinbase_with_extra(height + constants.coinbase_lock_height(), self.extra);
         let excess = self.factories.commitment.commit_value(&spending_key, 0);
         let kernel_features = KernelFeatures::create_coinbase();
         let metadata = TransactionMetadata::new_with_features(0.into(), 0, kernel_features);
@@ -391,6 +408,39 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn coinbase_extra() {
+        let p = TestParams::new();
+        let (builder, rules, _) = get_builder();
+        let constants = rules.consensus_constants(42);
+        let (tx, _) = builder
+            .with_block_height(42)
+            .with_fees(145 * uT)
+            .with_nonce(p.nonce.clone())
+            .with_spend_key(p.spend_key.clone())
+            .with_extra(b"pool tag".to_vec())
+            .build(constants, rules.emission_schedule())
+            .unwrap();
+        assert_eq!(
+            tx.body.outputs()[0].features.coinbase_extra(),
+            Some(b"pool tag".as_slice())
+        );
+
+        let (builder, _, _) = get_builder();
+        let max = constants.coinbase_extra_max_length();
+        assert_eq!(
+            builder
+                .with_block_height(42)
+                .with_fees(145 * uT)
+                .with_nonce(p.nonce.clone())
+                .with_spend_key(p.spend_key.clone())
+                .with_extra(vec![0u8; max + 1])
+                .build(constants, rules.emission_schedule())
+                .unwrap_err(),
+            CoinbaseBuildError::ExtraTooLong { len: max + 1, max }
+        );
+    }
+
     #[test]
     fn valid_coinbase_with_rewindable_output() {
         let rewind_blinding_key = PrivateKey::random(&mut OsRng);
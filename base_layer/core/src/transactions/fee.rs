@@ -29,7 +29,10 @@ use crate::transactions::aggregated_body::AggregateBody;
 pub struct Fee(TransactionWeight);
 
 impl Fee {
-    pub(crate) const MINIMUM_TRANSACTION_FEE: MicroTari = MicroTari(101);
+    /// The smallest fee per gram, as fees are charged in whole micro Tari per gram
+    pub const MINIMUM_FEE_PER_GRAM: MicroTari = MicroTari(1);
+    /// The minimum fee of a transaction
+    pub const MINIMUM_TRANSACTION_FEE: MicroTari = MicroTari(101);
 
     pub fn new(weight: TransactionWeight) -> Self {
         Self(weight)
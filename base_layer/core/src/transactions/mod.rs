@@ -12,6 +12,9 @@ mod coinbase_builder;
 pub use coinbase_builder::{CoinbaseBuildError, CoinbaseBuilder};
 
 pub mod fee;
+mod standalone_validation;
+pub use standalone_validation::validate_transaction_standalone;
+
 pub mod tari_amount;
 pub mod transaction_components;
 
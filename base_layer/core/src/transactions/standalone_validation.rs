@@ -0,0 +1,188 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Stateless validation of a single transaction for integrators that do not run a base node, e.g. payment processors
+//! that want to sanity-check a transaction they received before relaying it.
+
+use std::collections::HashSet;
+
+use crate::{
+    consensus::{ConsensusConstants, ConsensusEncodingSized},
+    transactions::{
+        transaction_components::{KernelFeatures, Transaction, TransactionError, TransactionOutput},
+        CryptoFactories,
+    },
+};
+
+/// Performs all the checks on a transaction that do not need the blockchain:
+/// 1. The transaction does not contain coinbases, duplicate inputs or duplicate outputs
+/// 1. The weight of the transaction fits in a block
+/// 1. The versions and output types are permitted by consensus
+/// 1. The output features and scripts are within the limits enforced by consensus
+/// 1. Token registrations are signed by their issuer
+/// 1. Every burned output has a matching burn kernel
+/// 1. The kernel signatures, range proofs and metadata signatures are valid
+/// 1. The kernels balance the inputs, outputs and fees, and the script offset balances the scripts
+/// 1. The scripts of the inputs execute successfully and the covenants are honoured
+///
+/// It does NOT check that the inputs are unspent outputs on chain, that the kernels are not on chain yet or that the
+/// inputs are mature and the lock heights have passed. As the height at which the transaction is mined is not known,
+/// scripts and covenants are executed at the maximum height, which passes any height lock.
+pub fn validate_transaction_standalone(
+    tx: &Transaction,
+    consensus_constants: &ConsensusConstants,
+) -> Result<(), TransactionError> {
+    let body = tx.body();
+    if body.outputs().iter().any(|o| o.is_coinbase()) ||
+        body.kernels()
+            .iter()
+            .any(|k| k.features.contains(KernelFeatures::COINBASE_KERNEL))
+    {
+        return Err(TransactionError::ValidationError(
+            "Transactions may not contain a coinbase".to_string(),
+        ));
+    }
+    if body.contains_duplicated_inputs() || body.contains_duplicated_outputs() {
+        return Err(TransactionError::ValidationError(
+            "Transaction contains duplicate inputs or outputs".to_string(),
+        ));
+    }
+
+    let weight = tx.calculate_weight(consensus_constants.transaction_weight());
+    let max_weight = consensus_constants.get_max_block_weight_excluding_coinbase();
+    if weight > max_weight {
+        return Err(TransactionError::ValidationError(format!(
+            "Transaction weight {} exceeds the maximum of {}",
+            weight, max_weight
+        )));
+    }
+
+    for input in body.inputs() {
+        if !consensus_constants.input_version_range().contains(&input.version) {
+            return Err(TransactionError::ValidationError(format!(
+                "Input version {:?} is not permitted by consensus",
+                input.version
+            )));
+        }
+    }
+    for output in body.outputs() {
+        check_output(output, consensus_constants)?;
+    }
+    for kernel in body.kernels() {
+        if !consensus_constants.kernel_version_range().contains(&kernel.version) {
+            return Err(TransactionError::ValidationError(format!(
+                "Kernel version {:?} is not permitted by consensus",
+                kernel.version
+            )));
+        }
+    }
+    tx.body.verify_unique_assets()?;
+    check_burns(tx)?;
+
+    tx.validate_internal_consistency(false, &CryptoFactories::default(), None, None, u64::MAX)
+}
+
+fn check_output(output: &TransactionOutput, consensus_constants: &ConsensusConstants) -> Result<(), TransactionError> {
+    let version_range = consensus_constants.output_version_range();
+    if !version_range.outputs.contains(&output.version) || !version_range.features.contains(&output.features.version) {
+        return Err(TransactionError::ValidationError(format!(
+            "Output version {:?} or output features version {:?} is not permitted by consensus",
+            output.version, output.features.version
+        )));
+    }
+    if !consensus_constants
+        .permitted_output_types()
+        .contains(&output.features.output_type)
+    {
+        return Err(TransactionError::ValidationError(format!(
+            "Output type {} is not permitted by consensus",
+            output.features.output_type
+        )));
+    }
+    output.features.validate()?;
+    output.verify_token_registration()?;
+
+    let script_size = output.script.consensus_encode_exact_size();
+    if script_size > consensus_constants.get_max_script_byte_size() {
+        return Err(TransactionError::ValidationError(format!(
+            "Script size {} exceeds the maximum of {}",
+            script_size,
+            consensus_constants.get_max_script_byte_size()
+        )));
+    }
+    if output.script.opcode_version() > consensus_constants.max_opcode_version() {
+        return Err(TransactionError::ValidationError(format!(
+            "Script opcode version {:?} is not permitted by consensus",
+            output.script.opcode_version()
+        )));
+    }
+    if output.script.execution_cost() > consensus_constants.max_script_execution_cost() {
+        return Err(TransactionError::ValidationError(format!(
+            "Script execution cost {} exceeds the maximum of {}",
+            output.script.execution_cost(),
+            consensus_constants.max_script_execution_cost()
+        )));
+    }
+    Ok(())
+}
+
+#[allow(clippy::mutable_key_type)]
+fn check_burns(tx: &Transaction) -> Result<(), TransactionError> {
+    let mut burned_outputs = tx
+        .body()
+        .outputs()
+        .iter()
+        .filter(|o| o.is_burned())
+        .map(|o| o.commitment.clone())
+        .collect::<HashSet<_>>();
+    for kernel in tx.body().kernels() {
+        if kernel.is_burned() && !burned_outputs.remove(kernel.get_burn_commitment()?) {
+            return Err(TransactionError::ValidationError(
+                "Burn kernel does not match a burned output".to_string(),
+            ));
+        }
+    }
+    if !burned_outputs.is_empty() {
+        return Err(TransactionError::ValidationError(
+            "Burned output has no matching burn kernel".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+
+    use super::*;
+    use crate::{
+        consensus::ConsensusConstantsBuilder,
+        transactions::{
+            tari_amount::uT,
+            test_helpers::create_tx,
+            transaction_components::{OutputFeatures, OutputType},
+        },
+    };
+
+    #[test]
+    fn it_validates_transactions_without_a_blockchain() {
+        let constants = ConsensusConstantsBuilder::new(Network::LocalNet).build();
+        let (tx, _, _) = create_tx(5000 * uT, 3 * uT, 1, 2, 1, 2, OutputFeatures::default());
+        validate_transaction_standalone(&tx, &constants).unwrap();
+
+        let mut tampered = tx.clone();
+        tampered.body.kernels_mut()[0].fee += 1 * uT;
+        assert!(validate_transaction_standalone(&tampered, &constants).is_err());
+
+        // Leaves room for a coinbase only
+        let constants = ConsensusConstantsBuilder::new(Network::LocalNet)
+            .with_max_block_transaction_weight(constants.coinbase_weight() + 1)
+            .build();
+        assert!(validate_transaction_standalone(&tx, &constants).is_err());
+
+        let constants = ConsensusConstantsBuilder::new(Network::LocalNet)
+            .with_permitted_output_types(&[OutputType::Burn])
+            .build();
+        assert!(validate_transaction_standalone(&tx, &constants).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use chacha20poly1305::{
+    aead::{Aead, NewAead, Payload},
+    ChaCha20Poly1305,
+    Key,
+    Nonce,
+};
+use tari_common_types::types::PrivateKey;
+use tari_crypto::{hash::blake2::Blake256, hashing::DomainSeparatedHasher};
+use tari_utilities::ByteArray;
+
+use crate::transactions::{transaction_components::EncryptionError, TransactionKdfDomain};
+
+/// The maximum size in bytes of a memo
+pub const MAX_MEMO_SIZE: usize = 256;
+/// The size in bytes of the authentication tag appended to an encrypted memo
+const TAG_SIZE: usize = 16;
+
+/// A short message for the recipient of an output, carried in the metadata of the output features. Only the holder
+/// of the encryption key of the output, i.e. the sender and the recipient, can read it.
+pub struct EncryptedMemo;
+
+impl EncryptedMemo {
+    const TAG: &'static [u8] = b"TARI_AAD_MEMO";
+
+    /// The size of the encrypted form of a memo, which allows the space for it to be reserved before the encryption
+    /// key is known
+    pub fn encrypted_size(memo: &str) -> usize {
+        memo.len() + TAG_SIZE
+    }
+
+    /// Encrypts a memo. The encryption key must be unique to the output, as the nonce is fixed.
+    pub fn encrypt_memo(encryption_key: &PrivateKey, memo: &str) -> Result<Vec<u8>, EncryptionError> {
+        if memo.len() > MAX_MEMO_SIZE {
+            return Err(EncryptionError::MemoTooLong(MAX_MEMO_SIZE));
+        }
+        let aead_payload = Payload {
+            msg: memo.as_bytes(),
+            aad: Self::TAG,
+        };
+        Ok(ChaCha20Poly1305::new(&kdf_aead(encryption_key)).encrypt(&Nonce::default(), aead_payload)?)
+    }
+
+    /// Authenticates and decrypts a memo. Data that was not encrypted with the key, including metadata that is not a
+    /// memo, results in an error.
+    pub fn decrypt_memo(encryption_key: &PrivateKey, data: &[u8]) -> Result<String, EncryptionError> {
+        if data.len() < TAG_SIZE || data.len() > MAX_MEMO_SIZE + TAG_SIZE {
+            return Err(EncryptionError::InvalidMemo);
+        }
+        let aead_payload = Payload {
+            msg: data,
+            aad: Self::TAG,
+        };
+        let memo = ChaCha20Poly1305::new(&kdf_aead(encryption_key)).decrypt(&Nonce::default(), aead_payload)?;
+        String::from_utf8(memo).map_err(|_| EncryptionError::InvalidMemo)
+    }
+}
+
+// Generate a ChaCha20-Poly1305 key for a memo from the encryption key of its output using Blake2b
+fn kdf_aead(encryption_key: &PrivateKey) -> Key {
+    const AEAD_KEY_LENGTH: usize = 32; // The length in bytes of a ChaCha20-Poly1305 AEAD key
+    let output = DomainSeparatedHasher::<Blake256, TransactionKdfDomain>::new_with_label("encrypted_memo")
+        .chain(encryption_key.as_bytes())
+        .finalize();
+
+    *Key::from_slice(&output.as_ref()[..AEAD_KEY_LENGTH])
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn it_encrypts_and_decrypts_correctly() {
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let longest = "x".repeat(MAX_MEMO_SIZE);
+        for memo in ["", "Invoice 42", "∑ ünïcödé", longest.as_str()] {
+            let data = EncryptedMemo::encrypt_memo(&encryption_key, memo).unwrap();
+            assert_eq!(data.len(), EncryptedMemo::encrypted_size(memo));
+            assert_eq!(EncryptedMemo::decrypt_memo(&encryption_key, &data).unwrap(), memo);
+        }
+    }
+
+    #[test]
+    fn it_rejects_other_keys_and_invalid_memos() {
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let data = EncryptedMemo::encrypt_memo(&encryption_key, "Invoice 42").unwrap();
+        assert!(EncryptedMemo::decrypt_memo(&PrivateKey::random(&mut OsRng), &data).is_err());
+        assert!(EncryptedMemo::decrypt_memo(&encryption_key, &[]).is_err());
+        assert!(matches!(
+            EncryptedMemo::encrypt_memo(&encryption_key, &"x".repeat(MAX_MEMO_SIZE + 1)),
+            Err(EncryptionError::MemoTooLong(MAX_MEMO_SIZE))
+        ));
+    }
+}
@@ -31,36 +31,68 @@ use chacha20poly1305::{
     Key,
     Nonce,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
 use tari_common_types::types::{Commitment, PrivateKey};
 use tari_crypto::{hash::blake2::Blake256, hashing::DomainSeparatedHasher};
 use tari_utilities::{ByteArray, ByteArrayError};
 use thiserror::Error;
 
 use crate::{
-    consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized},
-    transactions::{tari_amount::MicroTari, TransactionKdfDomain},
+    common::hex_bytes,
+    consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized, MaxSizeBytes},
+    transactions::{tari_amount::MicroTari, transaction_components::TransactionOutputVersion, TransactionKdfDomain},
 };
 
 const SIZE: usize = 24;
+/// The size in bytes of the authentication tag appended to encrypted data
+const TAG_SIZE: usize = 16;
+/// The maximum size in bytes of the encrypted payload of an output, including its version and authentication tag
+pub const MAX_ENCRYPTED_PAYLOAD_SIZE: usize = 512;
+/// The maximum size in bytes of a payload before encryption
+pub const MAX_PAYLOAD_SIZE: usize = MAX_ENCRYPTED_PAYLOAD_SIZE - TAG_SIZE - 1;
 
-/// value: u64 + tag: [u8; 16]
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
-pub struct EncryptedValue(#[serde(with = "tari_utilities::serde::hex")] pub [u8; SIZE]);
+/// value: u64 + tag: [u8; 16], optionally followed by an encrypted payload: version: u8 + payload + tag: [u8; 16]
+///
+/// Only outputs from [TransactionOutputVersion::V1] commit to the payload, earlier versions may not carry one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncryptedValue(Vec<u8>);
+
+/// The version of the encryption of the payload of an [EncryptedValue]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EncryptedPayloadVersion {
+    V0 = 0,
+}
 
 impl Default for EncryptedValue {
     fn default() -> Self {
-        Self([0; SIZE])
+        Self(vec![0; SIZE])
     }
 }
 
 impl ByteArray for EncryptedValue {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
-        ByteArray::from_bytes(bytes).map(Self)
+        if bytes.len() < SIZE || bytes.len() > SIZE + MAX_ENCRYPTED_PAYLOAD_SIZE {
+            return Err(ByteArrayError::IncorrectLength);
+        }
+        Ok(Self(bytes.to_vec()))
     }
 
     fn as_bytes(&self) -> &[u8] {
-        self.0.as_bytes()
+        &self.0
+    }
+}
+
+impl Serialize for EncryptedValue {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        hex_bytes::serialize(&self.0, ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptedValue {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let bytes: Vec<u8> = hex_bytes::deserialize(de)?;
+        Self::from_bytes(&bytes).map_err(D::Error::custom)
     }
 }
 
@@ -68,6 +100,14 @@ impl ByteArray for EncryptedValue {
 pub enum EncryptionError {
     #[error("Encryption failed: {0}")]
     EncryptionFailed(Error),
+    #[error("A memo cannot be longer than {0} bytes")]
+    MemoTooLong(usize),
+    #[error("The data is not a valid memo")]
+    InvalidMemo,
+    #[error("A payload cannot be longer than {0} bytes")]
+    PayloadTooLong(usize),
+    #[error("The encrypted value does not contain a valid payload")]
+    InvalidPayload,
 }
 
 // chacha error is not StdError compatible
@@ -78,6 +118,7 @@ impl From<Error> for EncryptionError {
 }
 
 impl EncryptedValue {
+    const PAYLOAD_TAG: &'static [u8] = b"TARI_AAD_PAYLOAD";
     const TAG: &'static [u8] = b"TARI_AAD_VALUE";
 
     pub fn encrypt_value(
@@ -85,7 +126,7 @@ impl EncryptedValue {
         commitment: &Commitment,
         value: MicroTari,
     ) -> Result<EncryptedValue, EncryptionError> {
-        let aead_key = kdf_aead(encryption_key, commitment);
+        let aead_key = kdf_aead(encryption_key, commitment, "encrypted_value");
         // Encrypt the value (with fixed length) using ChaCha20-Poly1305 with a fixed zero nonce
         let aead_payload = Payload {
             msg: &value.as_u64().to_le_bytes(),
@@ -93,9 +134,7 @@ impl EncryptedValue {
         };
         // Included in the public transaction
         let buffer = ChaCha20Poly1305::new(&aead_key).encrypt(&Nonce::default(), aead_payload)?;
-        let mut data: [u8; SIZE] = [0; SIZE];
-        data.copy_from_slice(&buffer);
-        Ok(EncryptedValue(data))
+        Ok(EncryptedValue(buffer))
     }
 
     pub fn decrypt_value(
@@ -103,10 +142,10 @@ impl EncryptedValue {
         commitment: &Commitment,
         value: &EncryptedValue,
     ) -> Result<MicroTari, EncryptionError> {
-        let aead_key = kdf_aead(encryption_key, commitment);
+        let aead_key = kdf_aead(encryption_key, commitment, "encrypted_value");
         // Authenticate and decrypt the value
         let aead_payload = Payload {
-            msg: value.as_bytes(),
+            msg: value.value_bytes(),
             aad: Self::TAG,
         };
         let mut value_bytes = [0u8; 8];
@@ -114,12 +153,98 @@ impl EncryptedValue {
         value_bytes.clone_from_slice(&decrypted_bytes[..8]);
         Ok(u64::from_le_bytes(value_bytes).into())
     }
+
+    /// Encrypts `payload` with the encryption key of the output and appends it to the encrypted value, replacing any
+    /// payload it had. The encryption key must be unique to the output, as the nonce is fixed.
+    pub fn with_encrypted_payload(
+        mut self,
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        payload: &[u8],
+    ) -> Result<EncryptedValue, EncryptionError> {
+        if payload.len() > MAX_PAYLOAD_SIZE {
+            return Err(EncryptionError::PayloadTooLong(MAX_PAYLOAD_SIZE));
+        }
+        let version = EncryptedPayloadVersion::V0 as u8;
+        let aead_key = kdf_aead(encryption_key, commitment, "encrypted_payload");
+        let aad = [Self::PAYLOAD_TAG, &[version]].concat();
+        let aead_payload = Payload {
+            msg: payload,
+            aad: &aad,
+        };
+        let buffer = ChaCha20Poly1305::new(&aead_key).encrypt(&Nonce::default(), aead_payload)?;
+        self.0.truncate(SIZE);
+        self.0.push(version);
+        self.0.extend(buffer);
+        Ok(self)
+    }
+
+    /// Authenticates and decrypts the payload. Values without a payload, payloads of unknown versions and payloads that
+    /// were not encrypted with the key result in an error.
+    pub fn decrypt_payload(
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        value: &EncryptedValue,
+    ) -> Result<Vec<u8>, EncryptionError> {
+        let (version, data) = value.payload().split_first().ok_or(EncryptionError::InvalidPayload)?;
+        if *version != EncryptedPayloadVersion::V0 as u8 || data.len() < TAG_SIZE {
+            return Err(EncryptionError::InvalidPayload);
+        }
+        let aead_key = kdf_aead(encryption_key, commitment, "encrypted_payload");
+        let aad = [Self::PAYLOAD_TAG, &[*version]].concat();
+        let aead_payload = Payload { msg: data, aad: &aad };
+        Ok(ChaCha20Poly1305::new(&aead_key).decrypt(&Nonce::default(), aead_payload)?)
+    }
+
+    /// The encrypted value without the payload
+    pub fn value_bytes(&self) -> &[u8] {
+        &self.0[..SIZE]
+    }
+
+    /// The encrypted payload, including its version. Empty if there is no payload.
+    pub fn payload(&self) -> &[u8] {
+        &self.0[SIZE..]
+    }
+
+    pub fn has_payload(&self) -> bool {
+        self.0.len() > SIZE
+    }
+
+    /// Encodes the value and, if the output version commits to it, the payload. The payload is dropped for earlier
+    /// output versions, which may not carry one.
+    pub(super) fn consensus_encode_versioned<W: Write>(
+        &self,
+        version: TransactionOutputVersion,
+        writer: &mut W,
+    ) -> Result<(), io::Error> {
+        self.consensus_encode(writer)?;
+        match version {
+            TransactionOutputVersion::V0 => Ok(()),
+            TransactionOutputVersion::V1 => self.payload().consensus_encode(writer),
+        }
+    }
+
+    /// Decodes the encoding written by [consensus_encode_versioned](Self::consensus_encode_versioned)
+    pub(super) fn consensus_decode_versioned<R: Read>(
+        version: TransactionOutputVersion,
+        reader: &mut R,
+    ) -> Result<Self, io::Error> {
+        let mut encrypted_value = Self::consensus_decode(reader)?;
+        match version {
+            TransactionOutputVersion::V0 => {},
+            TransactionOutputVersion::V1 => {
+                let payload = MaxSizeBytes::<MAX_ENCRYPTED_PAYLOAD_SIZE>::consensus_decode(reader)?;
+                encrypted_value.0.extend_from_slice(&payload);
+            },
+        }
+        Ok(encrypted_value)
+    }
 }
 
 // Generate a ChaCha20-Poly1305 key from an ECDH shared secret and commitment using Blake2b
-fn kdf_aead(shared_secret: &PrivateKey, commitment: &Commitment) -> Key {
+fn kdf_aead(shared_secret: &PrivateKey, commitment: &Commitment, label: &'static str) -> Key {
     const AEAD_KEY_LENGTH: usize = 32; // The length in bytes of a ChaCha20-Poly1305 AEAD key
-    let output = DomainSeparatedHasher::<Blake256, TransactionKdfDomain>::new_with_label("encrypted_value")
+    let output = DomainSeparatedHasher::<Blake256, TransactionKdfDomain>::new_with_label(label)
         .chain(shared_secret.as_bytes())
         .chain(commitment.as_bytes())
         .finalize();
@@ -127,9 +252,11 @@ fn kdf_aead(shared_secret: &PrivateKey, commitment: &Commitment) -> Key {
     *Key::from_slice(&output.as_ref()[..AEAD_KEY_LENGTH])
 }
 
+/// Only the value is encoded, the payload is encoded by the outputs that commit to it, see
+/// [consensus_encode_versioned](EncryptedValue::consensus_encode_versioned)
 impl ConsensusEncoding for EncryptedValue {
     fn consensus_encode<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
-        self.0.consensus_encode(writer)?;
+        writer.write_all(self.value_bytes())?;
         Ok(())
     }
 }
@@ -143,7 +270,7 @@ impl ConsensusEncodingSized for EncryptedValue {
 impl ConsensusDecoding for EncryptedValue {
     fn consensus_decode<R: Read>(reader: &mut R) -> Result<Self, io::Error> {
         let data = <[u8; SIZE]>::consensus_decode(reader)?;
-        Ok(Self(data))
+        Ok(Self(data.to_vec()))
     }
 }
 
@@ -155,6 +282,7 @@ mod test {
         commitment::HomomorphicCommitmentFactory,
         keys::{PublicKey, SecretKey},
     };
+    use tari_utilities::hex::Hex;
 
     use super::*;
     use crate::consensus::{check_consensus_encoding_correctness, ToConsensusBytes};
@@ -194,7 +322,75 @@ mod test {
     #[test]
     fn consensus_encoding() {
         let value = [0u8; SIZE];
-        let encrypted_value = EncryptedValue(value);
+        let encrypted_value = EncryptedValue(value.to_vec());
         check_consensus_encoding_correctness(encrypted_value).unwrap();
     }
+
+    #[test]
+    fn it_encrypts_and_decrypts_payloads() {
+        let commitment = Commitment::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)));
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let encrypted_value = EncryptedValue::encrypt_value(&encryption_key, &commitment, 123.into()).unwrap();
+        assert!(!encrypted_value.has_payload());
+        assert!(EncryptedValue::decrypt_payload(&encryption_key, &commitment, &encrypted_value).is_err());
+
+        let longest = vec![7u8; MAX_PAYLOAD_SIZE];
+        for payload in [&b""[..], &b"payment id 42"[..], longest.as_slice()] {
+            let with_payload = encrypted_value
+                .clone()
+                .with_encrypted_payload(&encryption_key, &commitment, payload)
+                .unwrap();
+            assert_eq!(with_payload.value_bytes(), encrypted_value.value_bytes());
+            assert_eq!(with_payload.payload().len(), payload.len() + TAG_SIZE + 1);
+            assert_eq!(
+                EncryptedValue::decrypt_payload(&encryption_key, &commitment, &with_payload).unwrap(),
+                payload
+            );
+            assert_eq!(
+                EncryptedValue::decrypt_value(&encryption_key, &commitment, &with_payload).unwrap(),
+                MicroTari::from(123)
+            );
+            assert!(
+                EncryptedValue::decrypt_payload(&PrivateKey::random(&mut OsRng), &commitment, &with_payload).is_err()
+            );
+            assert_eq!(
+                EncryptedValue::from_bytes(with_payload.as_bytes()).unwrap(),
+                with_payload
+            );
+        }
+        assert!(matches!(
+            encrypted_value.with_encrypted_payload(&encryption_key, &commitment, &[0; MAX_PAYLOAD_SIZE + 1]),
+            Err(EncryptionError::PayloadTooLong(MAX_PAYLOAD_SIZE))
+        ));
+    }
+
+    #[test]
+    fn it_only_encodes_the_payload_for_v1_outputs() {
+        let commitment = Commitment::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)));
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let encrypted_value = EncryptedValue::encrypt_value(&encryption_key, &commitment, 123.into())
+            .unwrap()
+            .with_encrypted_payload(&encryption_key, &commitment, b"Invoice 42")
+            .unwrap();
+
+        let mut buf = Vec::new();
+        encrypted_value
+            .consensus_encode_versioned(TransactionOutputVersion::V0, &mut buf)
+            .unwrap();
+        assert_eq!(buf.len(), SIZE);
+        let decoded = EncryptedValue::consensus_decode_versioned(TransactionOutputVersion::V0, &mut &buf[..]).unwrap();
+        assert!(!decoded.has_payload());
+
+        let mut buf = Vec::new();
+        encrypted_value
+            .consensus_encode_versioned(TransactionOutputVersion::V1, &mut buf)
+            .unwrap();
+        let decoded = EncryptedValue::consensus_decode_versioned(TransactionOutputVersion::V1, &mut &buf[..]).unwrap();
+        assert_eq!(decoded, encrypted_value);
+
+        // JSON stays a hex string
+        let json = serde_json::to_string(&encrypted_value).unwrap();
+        assert_eq!(json, format!("\"{}\"", encrypted_value.to_hex()));
+        assert_eq!(serde_json::from_str::<EncryptedValue>(&json).unwrap(), encrypted_value);
+    }
 }
@@ -51,6 +51,8 @@ pub enum TransactionError {
     InvalidCoinbase,
     #[error("Invalid coinbase maturity in body")]
     InvalidCoinbaseMaturity,
+    #[error("Coinbase extra is {len} bytes but the maximum is {max} bytes")]
+    InvalidCoinbaseExtra { len: usize, max: usize },
     #[error("More than one coinbase in body")]
     MoreThanOneCoinbase,
     #[error("No coinbase in body")]
@@ -73,6 +75,16 @@ pub enum TransactionError {
     ConsensusEncodingError(String),
     #[error("Committee contains too many members: contains {len} members but maximum is {max}")]
     InvalidCommitteeLength { len: usize, max: usize },
+    #[error("Invalid output features: {0}")]
+    InvalidOutputFeatures(String),
+    #[error("Invalid side chain features: {0}")]
+    InvalidSideChainFeatures(String),
+    #[error("Invalid token registration: {0}")]
+    InvalidTokenRegistration(String),
+    #[error("Invalid unique asset: {0}")]
+    InvalidUniqueAsset(String),
+    #[error("Bodies cannot be aggregated: {0}")]
+    InvalidAggregation(String),
 }
 
 impl From<CovenantError> for TransactionError {
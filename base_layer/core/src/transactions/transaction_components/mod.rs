@@ -23,37 +23,50 @@
 // Portions of this file were originally copyrighted (c) 2018 The Grin Developers, issued under the Apache License,
 // Version 2.0, available at http://www.apache.org/licenses/LICENSE-2.0.
 
-pub use encrypted_value::{EncryptedValue, EncryptionError};
+pub use encrypted_memo::{EncryptedMemo, MAX_MEMO_SIZE};
+pub use encrypted_value::{
+    EncryptedPayloadVersion,
+    EncryptedValue,
+    EncryptionError,
+    MAX_ENCRYPTED_PAYLOAD_SIZE,
+    MAX_PAYLOAD_SIZE,
+};
 pub use error::TransactionError;
 pub use kernel_builder::KernelBuilder;
 pub use kernel_features::KernelFeatures;
 pub use kernel_sum::KernelSum;
-pub use output_features::OutputFeatures;
+pub use output_features::{OutputFeatures, MAX_OUTPUT_FEATURES_METADATA_SIZE};
+pub use output_features_builder::OutputFeaturesBuilder;
 pub use output_features_version::OutputFeaturesVersion;
 pub use output_type::OutputType;
 pub use side_chain::*;
 use tari_common_types::types::{Commitment, FixedHash, PublicKey};
 use tari_script::TariScript;
+pub use token_registration::{TokenRegistration, MAX_TOKEN_SYMBOL_LENGTH};
 pub use transaction::Transaction;
 pub use transaction_builder::TransactionBuilder;
 pub use transaction_input::{SpentOutput, TransactionInput};
 pub use transaction_input_version::TransactionInputVersion;
 pub use transaction_kernel::TransactionKernel;
 pub use transaction_kernel_version::TransactionKernelVersion;
-pub use transaction_output::TransactionOutput;
+pub use transaction_output::{RewoundOutput, TransactionOutput};
 pub use transaction_output_version::TransactionOutputVersion;
 pub use unblinded_output::UnblindedOutput;
 pub use unblinded_output_builder::UnblindedOutputBuilder;
+pub use unique_asset::{UniqueAsset, MAX_UNIQUE_ID_SIZE};
 
+mod encrypted_memo;
 mod encrypted_value;
 mod error;
 mod kernel_builder;
 mod kernel_features;
 mod kernel_sum;
 mod output_features;
+mod output_features_builder;
 mod output_features_version;
 mod output_type;
 mod side_chain;
+mod token_registration;
 
 mod transaction;
 mod transaction_builder;
@@ -65,6 +78,7 @@ pub mod transaction_output;
 mod transaction_output_version;
 mod unblinded_output;
 mod unblinded_output_builder;
+mod unique_asset;
 
 #[cfg(test)]
 mod test;
@@ -106,6 +120,7 @@ pub(super) fn hash_output(
         .chain(&minimum_value_promise);
 
     match version {
-        TransactionOutputVersion::V0 | TransactionOutputVersion::V1 => common_hash.finalize().into(),
+        TransactionOutputVersion::V0 => common_hash.finalize().into(),
+        TransactionOutputVersion::V1 => common_hash.chain(&encrypted_value.payload()).finalize().into(),
     }
 }
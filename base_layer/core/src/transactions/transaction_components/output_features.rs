@@ -33,9 +33,18 @@ use serde::{Deserialize, Serialize};
 use super::OutputFeaturesVersion;
 use crate::{
     consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized, MaxSizeBytes},
-    transactions::transaction_components::{side_chain::SideChainFeatures, OutputType},
+    transactions::transaction_components::{
+        side_chain::SideChainFeatures,
+        OutputType,
+        TokenRegistration,
+        TransactionError,
+        UniqueAsset,
+    },
 };
 
+/// The maximum size in bytes of the metadata of output features
+pub const MAX_OUTPUT_FEATURES_METADATA_SIZE: usize = 1024;
+
 /// Options for UTXO's
 #[derive(Debug, Clone, Hash, PartialEq, Deserialize, Serialize, Eq)]
 pub struct OutputFeatures {
@@ -45,6 +54,7 @@ pub struct OutputFeatures {
     /// the maturity of the specific UTXO. This is the min lock height at which an UTXO can be spent. Coinbase UTXO
     /// require a min maturity of the Coinbase_lock_height, this should be checked on receiving new blocks.
     pub maturity: u64,
+    #[serde(with = "crate::common::hex_bytes")]
     pub metadata: Vec<u8>,
     pub sidechain_features: Option<Box<SideChainFeatures>>,
 }
@@ -90,6 +100,16 @@ impl OutputFeatures {
         }
     }
 
+    /// Creates the features of a coinbase output that carries extra data chosen by the miner, e.g. a pool tag or
+    /// merge mining data. The size of the extra data is limited by consensus, see
+    /// [coinbase_extra_max_length](crate::consensus::ConsensusConstants::coinbase_extra_max_length).
+    pub fn create_coinbase_with_extra(maturity_height: u64, extra: Vec<u8>) -> OutputFeatures {
+        OutputFeatures {
+            metadata: extra,
+            ..Self::create_coinbase(maturity_height)
+        }
+    }
+
     /// creates output features for a burned output
     pub fn create_burn_output() -> OutputFeatures {
         OutputFeatures {
@@ -101,6 +121,62 @@ impl OutputFeatures {
     pub fn is_coinbase(&self) -> bool {
         matches!(self.output_type, OutputType::Coinbase)
     }
+
+    /// The extra data that the miner added to a coinbase output, or `None` if these are not the features of a coinbase
+    pub fn coinbase_extra(&self) -> Option<&[u8]> {
+        if self.is_coinbase() {
+            Some(&self.metadata)
+        } else {
+            None
+        }
+    }
+
+    /// Checks that the features are within the limits enforced by consensus
+    pub fn validate(&self) -> Result<(), TransactionError> {
+        if self.metadata.len() > MAX_OUTPUT_FEATURES_METADATA_SIZE {
+            return Err(TransactionError::InvalidOutputFeatures(format!(
+                "Metadata is {} bytes but the maximum is {} bytes",
+                self.metadata.len(),
+                MAX_OUTPUT_FEATURES_METADATA_SIZE
+            )));
+        }
+        if let Some(sidechain_features) = self.sidechain_features.as_ref() {
+            if self.version == OutputFeaturesVersion::V0 && !sidechain_features.is_empty() {
+                return Err(TransactionError::InvalidSideChainFeatures(
+                    "A side chain id and committee signature need output features of version 1 or later".to_string(),
+                ));
+            }
+            sidechain_features.validate()?;
+        }
+        if let Some(registration) = TokenRegistration::from_features(self)? {
+            registration.validate()?;
+        }
+        if let Some(asset) = UniqueAsset::from_features(self)? {
+            asset.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Encodes the optional side chain features in the encoding of this version of the features
+    pub fn consensus_encode_sidechain_features<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        match self.sidechain_features.as_ref() {
+            Some(sidechain_features) => {
+                writer.write_all(&[1u8])?;
+                sidechain_features.consensus_encode_versioned(self.version, writer)?;
+            },
+            None => writer.write_all(&[0u8])?,
+        }
+        Ok(())
+    }
+
+    /// The consensus encoding of the optional side chain features, see
+    /// [OutputFeatures::consensus_encode_sidechain_features]
+    pub fn sidechain_features_consensus_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.consensus_encode_sidechain_features(&mut buf)
+            .expect("Writing to a Vec<u8> cannot fail");
+        buf
+    }
 }
 
 impl ConsensusEncoding for OutputFeatures {
@@ -108,7 +184,7 @@ impl ConsensusEncoding for OutputFeatures {
         self.version.consensus_encode(writer)?;
         self.maturity.consensus_encode(writer)?;
         self.output_type.consensus_encode(writer)?;
-        self.sidechain_features.consensus_encode(writer)?;
+        self.consensus_encode_sidechain_features(writer)?;
         self.metadata.consensus_encode(writer)?;
 
         Ok(())
@@ -124,9 +200,22 @@ impl ConsensusDecoding for OutputFeatures {
         let version = OutputFeaturesVersion::consensus_decode(reader)?;
         let maturity = u64::consensus_decode(reader)?;
         let flags = OutputType::consensus_decode(reader)?;
-        let sidechain_features = <Option<Box<SideChainFeatures>> as ConsensusDecoding>::consensus_decode(reader)?;
-        const MAX_METADATA_SIZE: usize = 1024;
-        let metadata = <MaxSizeBytes<MAX_METADATA_SIZE> as ConsensusDecoding>::consensus_decode(reader)?;
+        let mut option_byte = [0u8; 1];
+        reader.read_exact(&mut option_byte)?;
+        let sidechain_features = match option_byte[0] {
+            0 => None,
+            1 => Some(Box::new(SideChainFeatures::consensus_decode_versioned(
+                version, reader,
+            )?)),
+            b => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("consensus decode: Invalid Option byte {}", b),
+                ))
+            },
+        };
+        let metadata =
+            <MaxSizeBytes<MAX_OUTPUT_FEATURES_METADATA_SIZE> as ConsensusDecoding>::consensus_decode(reader)?;
         Ok(Self {
             version,
             output_type: flags,
@@ -167,17 +256,26 @@ impl Display for OutputFeatures {
 
 #[cfg(test)]
 mod test {
+    use tari_common_types::types::FixedHash;
+
     use super::*;
-    use crate::consensus::check_consensus_encoding_correctness;
+    use crate::consensus::{check_consensus_encoding_correctness, ToConsensusBytes};
 
     #[allow(clippy::too_many_lines)]
     fn make_fully_populated_output_features(version: OutputFeaturesVersion) -> OutputFeatures {
+        // Version 0 side chain features are always empty
+        let sidechain_features = match version {
+            OutputFeaturesVersion::V0 => SideChainFeatures::new(FixedHash::zero()),
+            OutputFeaturesVersion::V1 => {
+                SideChainFeatures::new([2u8; 32].into()).with_committee_signature(vec![3u8; 64])
+            },
+        };
         OutputFeatures {
             version,
             output_type: OutputType::Standard,
             maturity: u64::MAX,
-            metadata: vec![1; 1024],
-            sidechain_features: Some(Box::new(SideChainFeatures {})),
+            metadata: vec![1; MAX_OUTPUT_FEATURES_METADATA_SIZE],
+            sidechain_features: Some(Box::new(sidechain_features)),
         }
     }
 
@@ -196,4 +294,54 @@ mod test {
         subject.sidechain_features = None;
         check_consensus_encoding_correctness(subject).unwrap();
     }
+
+    #[test]
+    fn it_keeps_the_version_0_encoding() {
+        // The encoding of version 0 features with the empty side chain features struct that preceded the side chain id
+        // and committee signature
+        let mut subject = OutputFeatures::new(
+            OutputFeaturesVersion::V0,
+            OutputType::Standard,
+            5,
+            vec![7, 8],
+            Some(SideChainFeatures::new(FixedHash::zero())),
+        );
+        let bytes = vec![0x00, 0x05, 0x00, 0x01, 0x02, 0x07, 0x08];
+        assert_eq!(subject.to_consensus_bytes(), bytes);
+        assert_eq!(
+            OutputFeatures::consensus_decode(&mut bytes.as_slice()).unwrap(),
+            subject
+        );
+        assert_eq!(subject.sidechain_features_consensus_bytes(), vec![0x01]);
+
+        subject.sidechain_features = None;
+        let bytes = vec![0x00, 0x05, 0x00, 0x00, 0x02, 0x07, 0x08];
+        assert_eq!(subject.to_consensus_bytes(), bytes);
+        assert_eq!(
+            OutputFeatures::consensus_decode(&mut bytes.as_slice()).unwrap(),
+            subject
+        );
+    }
+
+    #[test]
+    fn it_rejects_side_chain_data_in_version_0_features() {
+        let mut subject = make_fully_populated_output_features(OutputFeaturesVersion::V1);
+        subject.validate().unwrap();
+        subject.version = OutputFeaturesVersion::V0;
+        assert!(matches!(
+            subject.validate(),
+            Err(TransactionError::InvalidSideChainFeatures(_))
+        ));
+    }
+
+    #[test]
+    fn it_validates_the_metadata_size() {
+        let mut subject = make_fully_populated_output_features(OutputFeaturesVersion::V1);
+        subject.validate().unwrap();
+        subject.metadata.push(1);
+        assert!(matches!(
+            subject.validate(),
+            Err(TransactionError::InvalidOutputFeatures(_))
+        ));
+    }
 }
@@ -0,0 +1,133 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use crate::transactions::transaction_components::{
+    OutputFeatures,
+    OutputFeaturesVersion,
+    OutputType,
+    SideChainFeatures,
+    TransactionError,
+};
+
+/// Builds [OutputFeatures], checking that the features are within the limits enforced by consensus
+#[derive(Debug, Clone)]
+pub struct OutputFeaturesBuilder {
+    version: OutputFeaturesVersion,
+    output_type: OutputType,
+    maturity: u64,
+    metadata: Vec<u8>,
+    sidechain_features: Option<SideChainFeatures>,
+}
+
+impl OutputFeaturesBuilder {
+    /// Creates a builder for standard output features of the current version
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_version(mut self, version: OutputFeaturesVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn with_output_type(mut self, output_type: OutputType) -> Self {
+        self.output_type = output_type;
+        self
+    }
+
+    /// The min lock height at which the output can be spent
+    pub fn with_maturity(mut self, maturity: u64) -> Self {
+        self.maturity = maturity;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: Vec<u8>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Marks the output as belonging to a side chain. A side chain id or committee signature needs output features of
+    /// version 1 or later, so the version is raised to V1 for them.
+    pub fn with_sidechain_features(mut self, sidechain_features: SideChainFeatures) -> Self {
+        if !sidechain_features.is_empty() && self.version < OutputFeaturesVersion::V1 {
+            self.version = OutputFeaturesVersion::V1;
+        }
+        self.sidechain_features = Some(sidechain_features);
+        self
+    }
+
+    pub fn build(self) -> Result<OutputFeatures, TransactionError> {
+        let features = OutputFeatures::new(
+            self.version,
+            self.output_type,
+            self.maturity,
+            self.metadata,
+            self.sidechain_features,
+        );
+        features.validate()?;
+        Ok(features)
+    }
+}
+
+impl Default for OutputFeaturesBuilder {
+    fn default() -> Self {
+        Self {
+            version: OutputFeaturesVersion::get_current_version(),
+            output_type: OutputType::default(),
+            maturity: 0,
+            metadata: Vec::new(),
+            sidechain_features: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::FixedHash;
+
+    use super::*;
+    use crate::transactions::transaction_components::{
+        MAX_COMMITTEE_SIGNATURE_SIZE,
+        MAX_OUTPUT_FEATURES_METADATA_SIZE,
+    };
+
+    #[test]
+    fn it_builds_default_features() {
+        assert_eq!(OutputFeaturesBuilder::new().build().unwrap(), OutputFeatures::default());
+    }
+
+    #[test]
+    fn it_builds_sidechain_features() {
+        let sidechain_id = FixedHash::from([1u8; 32]);
+        let features = OutputFeaturesBuilder::new()
+            .with_maturity(10)
+            .with_metadata(vec![1, 2, 3])
+            .with_sidechain_features(SideChainFeatures::new(sidechain_id).with_committee_signature(vec![4u8; 64]))
+            .build()
+            .unwrap();
+        assert_eq!(features.version, OutputFeaturesVersion::V1);
+        assert_eq!(features.maturity, 10);
+        assert_eq!(features.metadata, vec![1, 2, 3]);
+        let sidechain_features = features.sidechain_features.unwrap();
+        assert_eq!(sidechain_features.sidechain_id, sidechain_id);
+        assert_eq!(sidechain_features.committee_signature, vec![4u8; 64]);
+    }
+
+    #[test]
+    fn it_rejects_features_that_exceed_the_limits() {
+        let err = OutputFeaturesBuilder::new()
+            .with_metadata(vec![1; MAX_OUTPUT_FEATURES_METADATA_SIZE + 1])
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, TransactionError::InvalidOutputFeatures(_)));
+
+        let committee_signature = vec![1; MAX_COMMITTEE_SIGNATURE_SIZE + 1];
+        let err = OutputFeaturesBuilder::new()
+            .with_sidechain_features(
+                SideChainFeatures::new(FixedHash::zero()).with_committee_signature(committee_signature),
+            )
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, TransactionError::InvalidSideChainFeatures(_)));
+    }
+}
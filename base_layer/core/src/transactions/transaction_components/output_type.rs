@@ -44,6 +44,12 @@ pub enum OutputType {
     Coinbase = 1,
     /// Output is a burned output and can not be spent ever.
     Burn = 2,
+    /// Output registers a fungible token, its features carry a
+    /// [TokenRegistration](crate::transactions::transaction_components::TokenRegistration).
+    TokenRegistration = 3,
+    /// Output holds a unique asset, its features carry a
+    /// [UniqueAsset](crate::transactions::transaction_components::UniqueAsset).
+    UniqueAsset = 4,
 }
 
 impl OutputType {
@@ -59,7 +65,13 @@ impl OutputType {
     }
 
     pub const fn all() -> &'static [Self] {
-        &[OutputType::Standard, OutputType::Coinbase, OutputType::Burn]
+        &[
+            OutputType::Standard,
+            OutputType::Coinbase,
+            OutputType::Burn,
+            OutputType::TokenRegistration,
+            OutputType::UniqueAsset,
+        ]
     }
 }
 
@@ -113,6 +125,8 @@ mod tests {
         assert_eq!(OutputType::from_byte(0), Some(OutputType::Standard));
         assert_eq!(OutputType::from_byte(1), Some(OutputType::Coinbase));
         assert_eq!(OutputType::from_byte(2), Some(OutputType::Burn));
+        assert_eq!(OutputType::from_byte(3), Some(OutputType::TokenRegistration));
+        assert_eq!(OutputType::from_byte(4), Some(OutputType::UniqueAsset));
         assert_eq!(OutputType::from_byte(255), None);
     }
 
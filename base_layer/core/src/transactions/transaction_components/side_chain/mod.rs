@@ -21,7 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod sidechain_features;
-pub use sidechain_features::SideChainFeatures;
+pub use sidechain_features::{SideChainFeatures, MAX_COMMITTEE_SIGNATURE_SIZE};
 // Length of FixedString
 pub const FIXED_STR_LEN: usize = 32;
 pub type FixedString = [u8; FIXED_STR_LEN];
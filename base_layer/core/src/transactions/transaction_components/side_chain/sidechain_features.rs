@@ -23,36 +23,135 @@
 use std::io::{Error, Read, Write};
 
 use serde::{Deserialize, Serialize};
+use tari_common_types::types::FixedHash;
 
-use crate::consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized};
+use crate::{
+    consensus::{ConsensusDecoding, ConsensusEncoding, MaxSizeBytes},
+    transactions::transaction_components::{OutputFeaturesVersion, TransactionError},
+};
+
+/// The maximum size in bytes of the committee signature of side chain features
+pub const MAX_COMMITTEE_SIGNATURE_SIZE: usize = 512;
 
 #[derive(Debug, Clone, Hash, PartialEq, Deserialize, Serialize, Eq)]
-pub struct SideChainFeatures {}
+pub struct SideChainFeatures {
+    /// Identifies the side chain that the output belongs to
+    #[serde(with = "crate::common::hex_bytes")]
+    pub sidechain_id: FixedHash,
+    /// The signature of the side chain committee. The base layer treats the signature as an opaque blob.
+    #[serde(with = "crate::common::hex_bytes")]
+    pub committee_signature: Vec<u8>,
+}
+
+impl SideChainFeatures {
+    pub fn new(sidechain_id: FixedHash) -> Self {
+        Self {
+            sidechain_id,
+            committee_signature: Vec::new(),
+        }
+    }
 
-impl SideChainFeatures {}
+    pub fn with_committee_signature(mut self, committee_signature: Vec<u8>) -> Self {
+        self.committee_signature = committee_signature;
+        self
+    }
+
+    /// Returns true if the features have no side chain id or committee signature, as all features of version 0 output
+    /// features do
+    pub fn is_empty(&self) -> bool {
+        self.sidechain_id == FixedHash::zero() && self.committee_signature.is_empty()
+    }
 
-impl ConsensusEncoding for SideChainFeatures {
-    fn consensus_encode<W: Write>(&self, _writer: &mut W) -> Result<(), Error> {
+    /// Checks that the side chain features are within the limits enforced by consensus
+    pub fn validate(&self) -> Result<(), TransactionError> {
+        if self.committee_signature.len() > MAX_COMMITTEE_SIGNATURE_SIZE {
+            return Err(TransactionError::InvalidSideChainFeatures(format!(
+                "Committee signature is {} bytes but the maximum is {} bytes",
+                self.committee_signature.len(),
+                MAX_COMMITTEE_SIGNATURE_SIZE
+            )));
+        }
         Ok(())
     }
 }
 
-impl ConsensusEncodingSized for SideChainFeatures {}
+impl SideChainFeatures {
+    /// Encodes the features as part of output features of `version`. The features of version 0 output features are an
+    /// empty struct that encodes to nothing, and must stay that way as the encoding is part of the output hash.
+    pub fn consensus_encode_versioned<W: Write>(
+        &self,
+        version: OutputFeaturesVersion,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        match version {
+            OutputFeaturesVersion::V0 => {},
+            OutputFeaturesVersion::V1 => {
+                self.sidechain_id.consensus_encode(writer)?;
+                self.committee_signature.consensus_encode(writer)?;
+            },
+        }
+        Ok(())
+    }
 
-impl ConsensusDecoding for SideChainFeatures {
-    fn consensus_decode<R: Read>(_reader: &mut R) -> Result<Self, Error> {
-        Ok(Self {})
+    /// Decodes features encoded by [SideChainFeatures::consensus_encode_versioned] for the same `version`
+    pub fn consensus_decode_versioned<R: Read>(version: OutputFeaturesVersion, reader: &mut R) -> Result<Self, Error> {
+        match version {
+            OutputFeaturesVersion::V0 => Ok(Self::new(FixedHash::zero())),
+            OutputFeaturesVersion::V1 => {
+                let sidechain_id = FixedHash::consensus_decode(reader)?;
+                let committee_signature =
+                    <MaxSizeBytes<MAX_COMMITTEE_SIGNATURE_SIZE> as ConsensusDecoding>::consensus_decode(reader)?;
+                Ok(Self {
+                    sidechain_id,
+                    committee_signature: committee_signature.into(),
+                })
+            },
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::consensus::check_consensus_encoding_correctness;
+
+    fn round_trip(features: &SideChainFeatures, version: OutputFeaturesVersion) -> (Vec<u8>, SideChainFeatures) {
+        let mut bytes = Vec::new();
+        features.consensus_encode_versioned(version, &mut bytes).unwrap();
+        let mut reader = bytes.as_slice();
+        let decoded = SideChainFeatures::consensus_decode_versioned(version, &mut reader).unwrap();
+        assert!(reader.is_empty());
+        (bytes, decoded)
+    }
 
     #[test]
     fn consensus_encoding() {
-        let features = SideChainFeatures {};
-        check_consensus_encoding_correctness(features).unwrap();
+        let features = SideChainFeatures::new(FixedHash::zero());
+        assert_eq!(
+            round_trip(&features, OutputFeaturesVersion::V0),
+            (vec![], features.clone())
+        );
+        assert_eq!(round_trip(&features, OutputFeaturesVersion::V1).1, features);
+
+        let features =
+            SideChainFeatures::new([1u8; 32].into()).with_committee_signature(vec![2u8; MAX_COMMITTEE_SIGNATURE_SIZE]);
+        assert_eq!(round_trip(&features, OutputFeaturesVersion::V1).1, features);
+    }
+
+    #[test]
+    fn it_rejects_oversized_committee_signatures() {
+        let features =
+            SideChainFeatures::new(FixedHash::zero())
+                .with_committee_signature(vec![2u8; MAX_COMMITTEE_SIGNATURE_SIZE + 1]);
+        assert!(matches!(
+            features.validate(),
+            Err(TransactionError::InvalidSideChainFeatures(_))
+        ));
+        let mut bytes = Vec::new();
+        features
+            .consensus_encode_versioned(OutputFeaturesVersion::V1, &mut bytes)
+            .unwrap();
+        assert!(
+            SideChainFeatures::consensus_decode_versioned(OutputFeaturesVersion::V1, &mut bytes.as_slice()).is_err()
+        );
     }
 }
@@ -34,6 +34,7 @@ use tari_test_utils::unpack_enum;
 
 use super::*;
 use crate::{
+    consensus::{check_consensus_encoding_correctness, ToConsensusBytes},
     transactions::{
         tari_amount::{uT, MicroTari, T},
         test_helpers,
@@ -377,6 +378,71 @@ fn check_cut_through() {
         .unwrap_err();
 }
 
+#[test]
+fn it_aggregates_transactions() {
+    let factories = CryptoFactories::default();
+    let (tx1, _, outputs) = test_helpers::create_tx(50000000.into(), 3.into(), 1, 2, 1, 2, Default::default());
+    // The second transaction spends an output of the first, which is allowed as long as there is no cut-through
+    let schema = txn_schema!(from: vec![outputs[1].clone()], to: vec![1 * T, 2 * T]);
+    let (tx2, _) = test_helpers::spend_utxos(schema);
+    let (tx3, _, _) = test_helpers::create_tx(50000000.into(), 3.into(), 5, 1, 1, 1, Default::default());
+
+    let aggregate = Transaction::aggregate(vec![tx1.clone(), tx2.clone(), tx3.clone()], &factories).unwrap();
+    assert!(aggregate.body.is_sorted());
+    assert_eq!(aggregate.body.inputs().len(), 4);
+    assert_eq!(aggregate.body.outputs().len(), 6);
+    assert_eq!(aggregate.body.kernels().len(), 3);
+    assert_eq!(
+        aggregate.offset,
+        tx1.offset.clone() + tx2.offset.clone() + tx3.offset.clone()
+    );
+    assert_eq!(aggregate.min_spendable_height(), 5);
+    aggregate
+        .validate_internal_consistency(false, &factories, None, None, u64::MAX)
+        .unwrap();
+
+    // The offset has to balance the kernels of all the transactions
+    assert!(aggregate
+        .body
+        .verify_aggregated_offset(&tx1.offset, &factories.commitment)
+        .is_err());
+
+    let err = Transaction::aggregate(vec![tx1.clone(), tx1.clone()], &factories).unwrap_err();
+    unpack_enum!(TransactionError::InvalidAggregation(_a) = err);
+    let err = Transaction::aggregate(Vec::new(), &factories).unwrap_err();
+    unpack_enum!(TransactionError::InvalidAggregation(_a) = err);
+}
+
+#[test]
+fn it_round_trips_hex_and_json() {
+    let (tx, _, _) = test_helpers::create_tx(5000.into(), 3.into(), 1, 2, 1, 2, OutputFeatures {
+        metadata: vec![1, 2, 3],
+        ..Default::default()
+    });
+    check_consensus_encoding_correctness(tx.clone()).unwrap();
+
+    let decoded = Transaction::from_hex(&tx.to_hex()).unwrap();
+    assert_eq!(decoded, tx);
+    assert_eq!(decoded.to_consensus_bytes(), tx.to_consensus_bytes());
+    let kernel = &tx.body.kernels()[0];
+    assert_eq!(&TransactionKernel::from_hex(&kernel.to_hex()).unwrap(), kernel);
+    let output = &tx.body.outputs()[0];
+    assert_eq!(&TransactionOutput::from_hex(&output.to_hex()).unwrap(), output);
+
+    let json = serde_json::to_string(&tx).unwrap();
+    let decoded = serde_json::from_str::<Transaction>(&json).unwrap();
+    assert_eq!(decoded.to_consensus_bytes(), tx.to_consensus_bytes());
+    let json = serde_json::to_value(output).unwrap();
+    assert_eq!(json["proof"], output.proof.0.to_hex());
+    assert_eq!(json["features"]["metadata"], "010203");
+    let json = serde_json::to_string(kernel).unwrap();
+    assert_eq!(&serde_json::from_str::<TransactionKernel>(&json).unwrap(), kernel);
+
+    // Trailing bytes are not part of the canonical encoding
+    assert!(Transaction::from_hex(&format!("{}00", tx.to_hex())).is_err());
+    assert!(TransactionKernel::from_hex("not hex").is_err());
+}
+
 #[test]
 fn check_duplicate_inputs_outputs() {
     let (tx, _, _outputs) = test_helpers::create_tx(50000000.into(), 3.into(), 1, 2, 1, 2, Default::default());
@@ -460,6 +526,32 @@ fn test_output_rewinding_bulletproofs() {
         .unwrap();
     assert_eq!(recovered_mask, test_params.spend_key);
 }
+
+#[test]
+fn it_rewinds_owned_outputs() {
+    let test_params = TestParams::new();
+    let factories = CryptoFactories::new(32);
+    let unblinded_output = test_params.create_unblinded_output_with_rewind_data(UtxoTestParams {
+        value: MicroTari::from(42),
+        ..Default::default()
+    });
+    let output = unblinded_output
+        .as_rewindable_transaction_output(&factories, &test_params.rewind_data, None)
+        .unwrap();
+
+    let rewound = output
+        .rewind(&factories.range_proof, &test_params.rewind_data)
+        .unwrap()
+        .unwrap();
+    assert_eq!(rewound.value, unblinded_output.value);
+    assert_eq!(rewound.blinding_factor, test_params.spend_key);
+
+    let other_wallet = TestParams::new();
+    assert!(output
+        .rewind(&factories.range_proof, &other_wallet.rewind_data)
+        .unwrap()
+        .is_none());
+}
 mod output_features {
     use std::io;
 
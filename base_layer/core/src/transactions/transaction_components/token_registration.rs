@@ -0,0 +1,256 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The registration of a simple native fungible token. A token is issued by a
+//! [TokenRegistration](OutputType::TokenRegistration) output that carries the symbol and total supply of the token in
+//! the metadata of its features, signed by the key of the issuer. The owner of the output owns the whole supply.
+
+use std::io::{Error, Read, Write};
+
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{Commitment, FixedHash, PrivateKey, PublicKey, Signature};
+use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+use crate::{
+    consensus::{
+        ConsensusDecoding,
+        ConsensusEncoding,
+        ConsensusEncodingSized,
+        DomainSeparatedConsensusHasher,
+        FromConsensusBytes,
+        MaxSizeBytes,
+        ToConsensusBytes,
+    },
+    transactions::{
+        transaction_components::{OutputFeatures, OutputType, TransactionError},
+        TransactionHashDomain,
+    },
+};
+
+/// The maximum length of a token symbol
+pub const MAX_TOKEN_SYMBOL_LENGTH: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenRegistration {
+    /// The ticker of the token, 1 to 12 uppercase ASCII letters or digits
+    pub symbol: String,
+    /// The number of units of the token that are issued
+    pub total_supply: u64,
+    /// The key of the issuer. Together with the symbol it identifies the token.
+    pub issuer_public_key: PublicKey,
+    /// The signature of the issuer, binding the registration to the commitment of the output
+    pub issuer_signature: Signature,
+}
+
+impl TokenRegistration {
+    /// Creates a registration for the output with the given commitment, signed by the issuer
+    pub fn new(
+        symbol: String,
+        total_supply: u64,
+        issuer_private_key: &PrivateKey,
+        commitment: &Commitment,
+    ) -> Result<Self, TransactionError> {
+        let issuer_public_key = PublicKey::from_secret_key(issuer_private_key);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let challenge = Self::build_challenge(
+            &symbol,
+            total_supply,
+            &issuer_public_key,
+            &PublicKey::from_secret_key(&nonce),
+            commitment,
+        );
+        let issuer_signature = Signature::sign(issuer_private_key.clone(), nonce, &challenge)
+            .map_err(|e| TransactionError::InvalidSignatureError(e.to_string()))?;
+        let registration = Self {
+            symbol,
+            total_supply,
+            issuer_public_key,
+            issuer_signature,
+        };
+        registration.validate()?;
+        Ok(registration)
+    }
+
+    /// Returns the registration carried by the features, or `None` if they are not the features of a registration
+    /// output
+    pub fn from_features(features: &OutputFeatures) -> Result<Option<Self>, TransactionError> {
+        if features.output_type != OutputType::TokenRegistration {
+            return Ok(None);
+        }
+        let registration = Self::from_consensus_bytes(&features.metadata)
+            .map_err(|e| TransactionError::InvalidTokenRegistration(e.to_string()))?;
+        Ok(Some(registration))
+    }
+
+    /// The features of the output that registers this token
+    pub fn to_features(&self) -> OutputFeatures {
+        OutputFeatures {
+            output_type: OutputType::TokenRegistration,
+            metadata: self.to_consensus_bytes(),
+            ..Default::default()
+        }
+    }
+
+    /// The unique identifier of the token, derived from the issuer key and the symbol, so that different issuers can
+    /// use the same symbol
+    pub fn token_id(&self) -> FixedHash {
+        DomainSeparatedConsensusHasher::<TransactionHashDomain>::new("token_id")
+            .chain(&self.issuer_public_key)
+            .chain(&self.symbol.as_bytes())
+            .finalize()
+            .into()
+    }
+
+    /// Checks that the symbol and supply are valid. This does not check the signature, see
+    /// [verify_signature](Self::verify_signature).
+    pub fn validate(&self) -> Result<(), TransactionError> {
+        if self.symbol.is_empty() || self.symbol.len() > MAX_TOKEN_SYMBOL_LENGTH {
+            return Err(TransactionError::InvalidTokenRegistration(format!(
+                "Symbol must be 1 to {} characters long",
+                MAX_TOKEN_SYMBOL_LENGTH
+            )));
+        }
+        if !self
+            .symbol
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return Err(TransactionError::InvalidTokenRegistration(format!(
+                "Symbol '{}' may only contain uppercase letters and digits",
+                self.symbol
+            )));
+        }
+        if self.total_supply == 0 {
+            return Err(TransactionError::InvalidTokenRegistration(
+                "Total supply must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verifies that the issuer signed the registration for the output with the given commitment
+    pub fn verify_signature(&self, commitment: &Commitment) -> Result<(), TransactionError> {
+        let challenge = Self::build_challenge(
+            &self.symbol,
+            self.total_supply,
+            &self.issuer_public_key,
+            self.issuer_signature.get_public_nonce(),
+            commitment,
+        );
+        if !self
+            .issuer_signature
+            .verify_challenge(&self.issuer_public_key, &challenge)
+        {
+            return Err(TransactionError::InvalidTokenRegistration(format!(
+                "Issuer signature of token {} is not valid",
+                self.symbol
+            )));
+        }
+        Ok(())
+    }
+
+    fn build_challenge(
+        symbol: &str,
+        total_supply: u64,
+        issuer_public_key: &PublicKey,
+        public_nonce: &PublicKey,
+        commitment: &Commitment,
+    ) -> [u8; 32] {
+        DomainSeparatedConsensusHasher::<TransactionHashDomain>::new("token_registration")
+            .chain(&symbol.as_bytes())
+            .chain(&total_supply)
+            .chain(issuer_public_key)
+            .chain(public_nonce)
+            .chain(commitment)
+            .finalize()
+    }
+}
+
+impl ConsensusEncoding for TokenRegistration {
+    fn consensus_encode<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.symbol.as_bytes().consensus_encode(writer)?;
+        self.total_supply.consensus_encode(writer)?;
+        self.issuer_public_key.consensus_encode(writer)?;
+        self.issuer_signature.consensus_encode(writer)?;
+        Ok(())
+    }
+}
+
+impl ConsensusEncodingSized for TokenRegistration {}
+
+impl ConsensusDecoding for TokenRegistration {
+    fn consensus_decode<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let symbol = <MaxSizeBytes<MAX_TOKEN_SYMBOL_LENGTH> as ConsensusDecoding>::consensus_decode(reader)?;
+        let symbol = String::from_utf8(symbol.into())
+            .map_err(|_| Error::new(std::io::ErrorKind::InvalidInput, "Token symbol is not valid UTF-8"))?;
+        Ok(Self {
+            symbol,
+            total_supply: u64::consensus_decode(reader)?,
+            issuer_public_key: PublicKey::consensus_decode(reader)?,
+            issuer_signature: Signature::consensus_decode(reader)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::consensus::check_consensus_encoding_correctness;
+
+    fn create_commitment() -> Commitment {
+        Commitment::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)))
+    }
+
+    #[test]
+    fn consensus_encoding() {
+        let registration = TokenRegistration::new(
+            "TKN".to_string(),
+            1_000,
+            &PrivateKey::random(&mut OsRng),
+            &create_commitment(),
+        )
+        .unwrap();
+        check_consensus_encoding_correctness(registration).unwrap();
+    }
+
+    #[test]
+    fn it_binds_the_registration_to_the_output() {
+        let issuer_key = PrivateKey::random(&mut OsRng);
+        let commitment = create_commitment();
+        let registration = TokenRegistration::new("TKN".to_string(), 1_000, &issuer_key, &commitment).unwrap();
+        registration.verify_signature(&commitment).unwrap();
+        assert!(registration.verify_signature(&create_commitment()).is_err());
+
+        let mut inflated = registration.clone();
+        inflated.total_supply += 1;
+        assert!(inflated.verify_signature(&commitment).is_err());
+
+        let features = registration.to_features();
+        assert_eq!(
+            TokenRegistration::from_features(&features).unwrap(),
+            Some(registration.clone())
+        );
+        assert_eq!(
+            TokenRegistration::from_features(&OutputFeatures::default()).unwrap(),
+            None
+        );
+
+        // The same symbol registered by another issuer is a different token
+        let other =
+            TokenRegistration::new("TKN".to_string(), 1_000, &PrivateKey::random(&mut OsRng), &commitment).unwrap();
+        assert_ne!(registration.token_id(), other.token_id());
+    }
+
+    #[test]
+    fn it_rejects_invalid_registrations() {
+        let issuer_key = PrivateKey::random(&mut OsRng);
+        let commitment = create_commitment();
+        for (symbol, supply) in [("", 1), ("TOOLONGSYMBOL", 1), ("tkn", 1), ("TK N", 1), ("TKN", 0)] {
+            assert!(matches!(
+                TokenRegistration::new(symbol.to_string(), supply, &issuer_key, &commitment),
+                Err(TransactionError::InvalidTokenRegistration(_))
+            ));
+        }
+    }
+}
@@ -26,25 +26,30 @@
 use std::{
     cmp::{max, min},
     fmt::{Display, Formatter},
+    io,
+    io::{Read, Write},
     ops::Add,
 };
 
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::{BlindingFactor, HashOutput, Signature};
-use tari_utilities::hex::Hex;
-
-use crate::transactions::{
-    aggregated_body::AggregateBody,
-    tari_amount::{uT, MicroTari},
-    transaction_components::{
-        OutputFeatures,
-        TransactionError,
-        TransactionInput,
-        TransactionKernel,
-        TransactionOutput,
+use tari_utilities::hex::{from_hex, Hex};
+
+use crate::{
+    consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized, FromConsensusBytes, ToConsensusBytes},
+    transactions::{
+        aggregated_body::AggregateBody,
+        tari_amount::{uT, MicroTari},
+        transaction_components::{
+            OutputFeatures,
+            TransactionError,
+            TransactionInput,
+            TransactionKernel,
+            TransactionOutput,
+        },
+        weight::TransactionWeight,
+        CryptoFactories,
     },
-    weight::TransactionWeight,
-    CryptoFactories,
 };
 
 /// A transaction which consists of a kernel offset and an aggregate body made up of inputs, outputs and kernels.
@@ -171,9 +176,45 @@ impl Transaction {
         self
     }
 
+    /// Merges transactions into a single transaction, e.g. to broadcast the transactions a wallet created in the same
+    /// window as one (a non-interactive coinjoin), which hides which inputs and outputs belong together. The offsets
+    /// are summed, the kernels are kept as they are and the body is sorted. See
+    /// [AggregateBody::aggregate](crate::transactions::aggregated_body::AggregateBody::aggregate) for the transactions
+    /// that cannot be merged.
+    pub fn aggregate<I: IntoIterator<Item = Transaction>>(
+        transactions: I,
+        factories: &CryptoFactories,
+    ) -> Result<Self, TransactionError> {
+        let mut transactions = transactions.into_iter();
+        let mut aggregate = transactions
+            .next()
+            .ok_or_else(|| TransactionError::InvalidAggregation("At least one transaction is required".to_string()))?;
+        for transaction in transactions {
+            aggregate.body.aggregate(transaction.body)?;
+            aggregate.offset = aggregate.offset + transaction.offset;
+            aggregate.script_offset = aggregate.script_offset + transaction.script_offset;
+        }
+        aggregate.body.sort();
+        aggregate
+            .body
+            .verify_aggregated_offset(&aggregate.offset, &factories.commitment)?;
+        Ok(aggregate)
+    }
+
     pub fn first_kernel_excess_sig(&self) -> Option<&Signature> {
         Some(&self.body.kernels().first()?.excess_sig)
     }
+
+    /// The canonical hex representation of the transaction, i.e. its consensus encoding in hex, e.g. for tooling
+    pub fn to_hex(&self) -> String {
+        self.to_consensus_bytes().to_hex()
+    }
+
+    /// Decodes a transaction from its canonical hex representation, see [to_hex](Self::to_hex)
+    pub fn from_hex(hex: &str) -> Result<Self, TransactionError> {
+        let bytes = from_hex(hex).map_err(|e| TransactionError::ConversionError(e.to_string()))?;
+        Ok(Self::from_consensus_bytes(&bytes)?)
+    }
 }
 
 impl Add for Transaction {
@@ -185,6 +226,30 @@ impl Add for Transaction {
     }
 }
 
+impl ConsensusEncoding for Transaction {
+    fn consensus_encode<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        self.offset.consensus_encode(writer)?;
+        self.body.consensus_encode(writer)?;
+        self.script_offset.consensus_encode(writer)?;
+        Ok(())
+    }
+}
+
+impl ConsensusEncodingSized for Transaction {}
+
+impl ConsensusDecoding for Transaction {
+    fn consensus_decode<R: Read>(reader: &mut R) -> Result<Self, io::Error> {
+        let offset = BlindingFactor::consensus_decode(reader)?;
+        let body = AggregateBody::consensus_decode(reader)?;
+        let script_offset = BlindingFactor::consensus_decode(reader)?;
+        Ok(Self {
+            offset,
+            body,
+            script_offset,
+        })
+    }
+}
+
 impl Display for Transaction {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
         writeln!(fmt, "-------------- Transaction --------------")?;
@@ -159,7 +159,9 @@ impl TransactionInput {
         };
     }
 
-    pub(super) fn build_script_challenge(
+    /// Builds the challenge that the script signature of an input signs, so that signers holding the keys of an output
+    /// can create the script signature without building the input
+    pub fn build_script_challenge(
         version: TransactionInputVersion,
         nonce_commitment: &Commitment,
         script: &TariScript,
@@ -481,7 +483,7 @@ impl ConsensusDecoding for TransactionInput {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 pub enum SpentOutput {
-    OutputHash(HashOutput),
+    OutputHash(#[serde(with = "crate::common::hex_bytes")] HashOutput),
     OutputData {
         version: TransactionOutputVersion,
         features: OutputFeatures,
@@ -525,7 +527,7 @@ impl ConsensusEncoding for SpentOutput {
                 script.consensus_encode(writer)?;
                 sender_offset_public_key.consensus_encode(writer)?;
                 covenant.consensus_encode(writer)?;
-                encrypted_value.consensus_encode(writer)?;
+                encrypted_value.consensus_encode_versioned(*version, writer)?;
                 minimum_value_promise.consensus_encode(writer)?;
             },
         };
@@ -549,7 +551,7 @@ impl ConsensusDecoding for SpentOutput {
                 let script = TariScript::consensus_decode(reader)?;
                 let sender_offset_public_key = PublicKey::consensus_decode(reader)?;
                 let covenant = Covenant::consensus_decode(reader)?;
-                let encrypted_value = EncryptedValue::consensus_decode(reader)?;
+                let encrypted_value = EncryptedValue::consensus_decode_versioned(version, reader)?;
                 let minimum_value_promise = MicroTari::consensus_decode(reader)?;
                 Ok(SpentOutput::OutputData {
                     version,
@@ -32,11 +32,21 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::{Commitment, FixedHash, PublicKey, Signature};
-use tari_utilities::{hex::Hex, message_format::MessageFormat};
+use tari_utilities::{
+    hex::{from_hex, Hex},
+    message_format::MessageFormat,
+};
 
 use super::TransactionKernelVersion;
 use crate::{
-    consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized, DomainSeparatedConsensusHasher},
+    consensus::{
+        ConsensusDecoding,
+        ConsensusEncoding,
+        ConsensusEncodingSized,
+        DomainSeparatedConsensusHasher,
+        FromConsensusBytes,
+        ToConsensusBytes,
+    },
     transactions::{
         tari_amount::MicroTari,
         transaction_components::{KernelFeatures, TransactionError},
@@ -196,6 +206,17 @@ impl TransactionKernel {
             .chain(burn_commitment)
             .finalize()
     }
+
+    /// The canonical hex representation of the kernel, i.e. its consensus encoding in hex, e.g. for tooling
+    pub fn to_hex(&self) -> String {
+        self.to_consensus_bytes().to_hex()
+    }
+
+    /// Decodes a kernel from its canonical hex representation, see [to_hex](Self::to_hex)
+    pub fn from_hex(hex: &str) -> Result<Self, TransactionError> {
+        let bytes = from_hex(hex).map_err(|e| TransactionError::ConversionError(e.to_string()))?;
+        Ok(Self::from_consensus_bytes(&bytes)?)
+    }
 }
 
 impl Display for TransactionKernel {
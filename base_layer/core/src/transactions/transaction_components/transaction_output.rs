@@ -50,24 +50,50 @@ use tari_crypto::{
     extended_range_proof::{ExtendedRangeProofService, Statement},
     keys::{PublicKey as PublicKeyTrait, SecretKey},
     ristretto::bulletproofs_plus::RistrettoAggregatedPublicStatement,
-    tari_utilities::{hex::Hex, ByteArray},
+    tari_utilities::{
+        hex::{from_hex, Hex},
+        ByteArray,
+    },
 };
 use tari_script::TariScript;
 
 use super::TransactionOutputVersion;
 use crate::{
-    consensus::{ConsensusDecoding, ConsensusEncoding, ConsensusEncodingSized, DomainSeparatedConsensusHasher},
+    consensus::{
+        ConsensusDecoding,
+        ConsensusEncoding,
+        ConsensusEncodingSized,
+        DomainSeparatedConsensusHasher,
+        FromConsensusBytes,
+        ToConsensusBytes,
+    },
     covenants::Covenant,
     transactions::{
         tari_amount::MicroTari,
         transaction_components,
-        transaction_components::{EncryptedValue, OutputFeatures, OutputType, TransactionError, TransactionInput},
+        transaction_components::{
+            EncryptedValue,
+            OutputFeatures,
+            OutputType,
+            TokenRegistration,
+            TransactionError,
+            TransactionInput,
+            UniqueAsset,
+        },
+        transaction_protocol::RewindData,
         TransactionHashDomain,
     },
 };
 
 pub const LOG_TARGET: &str = "c::transactions::transaction_output";
 
+/// The value and blinding factor of an output that was rewound with [TransactionOutput::rewind]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewoundOutput {
+    pub value: MicroTari,
+    pub blinding_factor: BlindingFactor,
+}
+
 /// Output for a transaction, defining the new ownership of coins that are being transferred. The commitment is a
 /// blinded value for the output while the range proof guarantees the commitment includes a positive value without
 /// overflow and the ownership of the private key.
@@ -191,8 +217,14 @@ impl TransactionOutput {
         }
     }
 
-    /// Verify that the metadata signature is valid
+    /// Verify that the metadata signature is valid. Outputs before V1 do not commit to an encrypted payload, so they
+    /// may not carry one.
     pub fn verify_metadata_signature(&self) -> Result<(), TransactionError> {
+        if self.version == TransactionOutputVersion::V0 && self.encrypted_value.has_payload() {
+            return Err(TransactionError::ValidationError(
+                "Only V1 outputs can carry an encrypted payload".to_string(),
+            ));
+        }
         let challenge = TransactionOutput::build_metadata_signature_challenge(
             self.version,
             &self.script,
@@ -216,6 +248,39 @@ impl TransactionOutput {
         Ok(())
     }
 
+    /// Verify that the issuer signed the registration of a token registration output for this output. Other outputs
+    /// are always valid.
+    pub fn verify_token_registration(&self) -> Result<(), TransactionError> {
+        match TokenRegistration::from_features(&self.features)? {
+            Some(registration) => {
+                registration.validate()?;
+                registration.verify_signature(&self.commitment)
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Verify that the parent of the asset held by a unique asset output signed the mint of the asset for this output,
+    /// unless the output transfers an asset of `spent_asset_ids`, the ids of the assets spent in the same transaction
+    /// or block. A transferred asset is removed from `spent_asset_ids`, so that it is transferred to one output only.
+    /// Other outputs are always valid.
+    pub fn verify_unique_asset(&self, spent_asset_ids: &mut Vec<FixedHash>) -> Result<(), TransactionError> {
+        match UniqueAsset::from_features(&self.features)? {
+            Some(asset) => {
+                asset.validate()?;
+                let asset_id = asset.asset_id();
+                match spent_asset_ids.iter().position(|id| *id == asset_id) {
+                    Some(index) => {
+                        spent_asset_ids.swap_remove(index);
+                        Ok(())
+                    },
+                    None => asset.verify_signature(&self.commitment),
+                }
+            },
+            None => Ok(()),
+        }
+    }
+
     /// Attempt to rewind the range proof to reveal the mask (blinding factor)
     pub fn recover_mask(
         &self,
@@ -235,6 +300,26 @@ impl TransactionOutput {
         Ok(prover.verify_mask(&self.commitment, blinding_factor, value)?)
     }
 
+    /// Attempt to rewind the output with the rewind keys of a wallet, revealing its value and blinding factor. Returns
+    /// `None` if the output does not belong to the wallet, i.e. the value cannot be decrypted or the recovered mask
+    /// does not open the commitment.
+    pub fn rewind(
+        &self,
+        prover: &RangeProofService,
+        rewind_data: &RewindData,
+    ) -> Result<Option<RewoundOutput>, TransactionError> {
+        let value =
+            match EncryptedValue::decrypt_value(&rewind_data.encryption_key, &self.commitment, &self.encrypted_value) {
+                Ok(value) => value,
+                Err(_) => return Ok(None),
+            };
+        let blinding_factor = self.recover_mask(prover, &rewind_data.rewind_blinding_key)?;
+        if !self.verify_mask(prover, &blinding_factor, value.as_u64())? {
+            return Ok(None);
+        }
+        Ok(Some(RewoundOutput { value, blinding_factor }))
+    }
+
     /// This will check if the input and the output is the same commitment by looking at the commitment and features.
     /// This will ignore the output range proof
     #[inline]
@@ -293,7 +378,8 @@ impl TransactionOutput {
             .chain(encrypted_value)
             .chain(&minimum_value_promise);
         match version {
-            TransactionOutputVersion::V0 | TransactionOutputVersion::V1 => common.finalize(),
+            TransactionOutputVersion::V0 => common.finalize(),
+            TransactionOutputVersion::V1 => common.chain(&encrypted_value.payload()).finalize(),
         }
     }
 
@@ -413,7 +499,19 @@ impl TransactionOutput {
     pub fn get_metadata_size(&self) -> usize {
         self.features.consensus_encode_exact_size() +
             self.script.consensus_encode_exact_size() +
-            self.covenant.consensus_encode_exact_size()
+            self.covenant.consensus_encode_exact_size() +
+            self.encrypted_value.payload().len()
+    }
+
+    /// The canonical hex representation of the output, i.e. its consensus encoding in hex, e.g. for tooling
+    pub fn to_hex(&self) -> String {
+        self.to_consensus_bytes().to_hex()
+    }
+
+    /// Decodes an output from its canonical hex representation, see [to_hex](Self::to_hex)
+    pub fn from_hex(hex: &str) -> Result<Self, TransactionError> {
+        let bytes = from_hex(hex).map_err(|e| TransactionError::ConversionError(e.to_string()))?;
+        Ok(Self::from_consensus_bytes(&bytes)?)
     }
 }
 
@@ -478,7 +576,7 @@ impl ConsensusEncoding for TransactionOutput {
         self.sender_offset_public_key.consensus_encode(writer)?;
         self.metadata_signature.consensus_encode(writer)?;
         self.covenant.consensus_encode(writer)?;
-        self.encrypted_value.consensus_encode(writer)?;
+        self.encrypted_value.consensus_encode_versioned(self.version, writer)?;
         self.minimum_value_promise.consensus_encode(writer)?;
         Ok(())
     }
@@ -495,7 +593,7 @@ impl ConsensusDecoding for TransactionOutput {
         let sender_offset_public_key = PublicKey::consensus_decode(reader)?;
         let metadata_signature = ComSignature::consensus_decode(reader)?;
         let covenant = Covenant::consensus_decode(reader)?;
-        let encrypted_value = EncryptedValue::consensus_decode(reader)?;
+        let encrypted_value = EncryptedValue::consensus_decode_versioned(version, reader)?;
         let minimum_value_promise = MicroTari::consensus_decode(reader)?;
         let output = TransactionOutput::new(
             version,
@@ -513,15 +611,29 @@ impl ConsensusDecoding for TransactionOutput {
     }
 }
 
-/// Performs batched range proof verification for an arbitrary number of outputs. Batched range proof verification gains
-/// above batch sizes of 2^8 = 256 gives diminishing returns, see <https://github.com/tari-project/bulletproofs-plus>,
-/// so the batch sizes are limited to 2^8.
+/// The default maximum number of range proofs that are verified in a single batch. Batched range proof verification
+/// gains above batch sizes of 2^8 = 256 gives diminishing returns, see <https://github.com/tari-project/bulletproofs-plus>.
+pub const DEFAULT_RANGE_PROOF_BATCH_SIZE: usize = 256;
+
+/// Performs batched range proof verification for an arbitrary number of outputs, in batches of at most
+/// [DEFAULT_RANGE_PROOF_BATCH_SIZE] proofs.
 pub fn batch_verify_range_proofs(
     prover: &RangeProofService,
     outputs: &[&TransactionOutput],
 ) -> Result<(), RangeProofError> {
+    batch_verify_range_proofs_with_batch_size(prover, outputs, DEFAULT_RANGE_PROOF_BATCH_SIZE)
+}
+
+/// Performs batched range proof verification for an arbitrary number of outputs, in batches of at most
+/// `max_batch_size` proofs. The batch size is rounded down to a power of two.
+pub fn batch_verify_range_proofs_with_batch_size(
+    prover: &RangeProofService,
+    outputs: &[&TransactionOutput],
+    max_batch_size: usize,
+) -> Result<(), RangeProofError> {
+    let max_power = (usize::BITS - 1 - max_batch_size.max(1).leading_zeros()) as u8;
     // We need optimized power of two chunks, for example if we have 15 outputs, then we need chunks of 8, 4, 2, 1.
-    let power_of_two_vec = power_of_two_chunk_sizes(outputs.len(), 8);
+    let power_of_two_vec = power_of_two_chunk_sizes(outputs.len(), max_power);
     debug!(
         target: LOG_TARGET,
         "Queueing range proof batch verify output(s): {:?}", &power_of_two_vec
@@ -581,13 +693,25 @@ fn power_of_two_chunk_sizes(len: usize, max_power: u8) -> Vec<usize> {
 
 #[cfg(test)]
 mod test {
-    use super::{batch_verify_range_proofs, TransactionOutput};
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PrivateKey;
+    use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::SecretKey};
+    use tari_utilities::ByteArray;
+
+    use super::{batch_verify_range_proofs, batch_verify_range_proofs_with_batch_size, TransactionOutput};
     use crate::{
         consensus::check_consensus_encoding_correctness,
         transactions::{
             tari_amount::MicroTari,
             test_helpers::{TestParams, UtxoTestParams},
-            transaction_components::transaction_output::power_of_two_chunk_sizes,
+            transaction_components::{
+                transaction_output::power_of_two_chunk_sizes,
+                EncryptedValue,
+                TokenRegistration,
+                TransactionError,
+                TransactionOutputVersion,
+                UniqueAsset,
+            },
             CryptoFactories,
         },
     };
@@ -656,6 +780,9 @@ mod test {
         ];
 
         assert!(batch_verify_range_proofs(&factories.range_proof, &outputs,).is_ok());
+        for batch_size in [0, 1, 2, 3] {
+            assert!(batch_verify_range_proofs_with_batch_size(&factories.range_proof, &outputs, batch_size).is_ok());
+        }
     }
 
     #[test]
@@ -669,6 +796,110 @@ mod test {
         ];
 
         assert!(batch_verify_range_proofs(&factories.range_proof, &outputs,).is_err());
+        assert!(batch_verify_range_proofs_with_batch_size(&factories.range_proof, &outputs, 1).is_err());
+    }
+
+    #[test]
+    fn it_only_commits_to_encrypted_payloads_in_v1_outputs() {
+        let factories = CryptoFactories::default();
+        let test_params = TestParams::new();
+        let mut output = create_valid_output(&test_params, &factories, MicroTari(10), MicroTari::zero());
+        let hash = output.hash();
+        output.encrypted_value = output
+            .encrypted_value
+            .clone()
+            .with_encrypted_payload(
+                &test_params.sender_offset_private_key,
+                &output.commitment,
+                b"Invoice 42",
+            )
+            .unwrap();
+        assert_eq!(output.hash(), hash);
+        assert!(output.verify_metadata_signature().is_err());
+
+        output.version = TransactionOutputVersion::V1;
+        let mut without_payload = output.clone();
+        without_payload.encrypted_value = EncryptedValue::from_bytes(output.encrypted_value.value_bytes()).unwrap();
+        assert_ne!(output.hash(), without_payload.hash());
+        check_consensus_encoding_correctness(output).unwrap();
+    }
+
+    #[test]
+    fn it_verifies_that_token_registrations_are_signed_for_the_output() {
+        let factories = CryptoFactories::default();
+        let test_params = TestParams::new();
+        let value = MicroTari(10);
+        let commitment = factories
+            .commitment
+            .commit_value(&test_params.spend_key, value.as_u64());
+        let registration = TokenRegistration::new(
+            "TKN".to_string(),
+            1_000_000,
+            &test_params.script_private_key,
+            &commitment,
+        )
+        .unwrap();
+        let output = test_params
+            .create_unblinded_output(UtxoTestParams {
+                value,
+                features: registration.to_features(),
+                ..Default::default()
+            })
+            .as_transaction_output(&factories)
+            .unwrap();
+        output.verify_metadata_signature().unwrap();
+        output.verify_token_registration().unwrap();
+
+        // The registration can not be copied to another output
+        let mut copied = create_valid_output(&test_params, &factories, MicroTari(11), MicroTari::zero());
+        copied.features = output.features.clone();
+        assert!(matches!(
+            copied.verify_token_registration(),
+            Err(TransactionError::InvalidTokenRegistration(_))
+        ));
+        create_valid_output(&test_params, &factories, value, MicroTari::zero())
+            .verify_token_registration()
+            .unwrap();
+    }
+
+    #[test]
+    fn it_rejects_unique_asset_mints_with_a_forged_parent_signature() {
+        let factories = CryptoFactories::default();
+        let test_params = TestParams::new();
+        let value = MicroTari(10);
+        let commitment = factories
+            .commitment
+            .commit_value(&test_params.spend_key, value.as_u64());
+        let parent_key = PrivateKey::random(&mut OsRng);
+        let asset = UniqueAsset::new(b"sword #1".to_vec(), &parent_key, &commitment).unwrap();
+        let create_output = |asset: &UniqueAsset| {
+            test_params
+                .create_unblinded_output(UtxoTestParams {
+                    value,
+                    features: asset.to_features(),
+                    ..Default::default()
+                })
+                .as_transaction_output(&factories)
+                .unwrap()
+        };
+        create_output(&asset).verify_unique_asset(&mut vec![]).unwrap();
+
+        // A mint signed by another key that claims the parent key of the collection
+        let mut forged = UniqueAsset::new(b"sword #2".to_vec(), &PrivateKey::random(&mut OsRng), &commitment).unwrap();
+        forged.parent_public_key = asset.parent_public_key.clone();
+        assert!(matches!(
+            create_output(&forged).verify_unique_asset(&mut vec![]),
+            Err(TransactionError::InvalidUniqueAsset(_))
+        ));
+
+        // A copy of the asset in another output is only valid as a transfer of the spent asset
+        let mut copied = create_valid_output(&test_params, &factories, MicroTari(11), MicroTari::zero());
+        copied.features = asset.to_features();
+        assert!(copied.verify_unique_asset(&mut vec![]).is_err());
+        let mut spent_asset_ids = vec![asset.asset_id()];
+        copied.verify_unique_asset(&mut spent_asset_ids).unwrap();
+        assert!(spent_asset_ids.is_empty());
+        assert!(copied.verify_unique_asset(&mut spent_asset_ids).is_err());
     }
 
     fn create_valid_output(
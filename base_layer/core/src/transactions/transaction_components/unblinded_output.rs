@@ -329,6 +329,21 @@ impl UnblindedOutput {
         Ok(output)
     }
 
+    /// Returns the lowest height of a block that can spend this output, taking into account the maturity, the script
+    /// lock height, the `CheckHeightVerify` opcodes of the script and the `absolute_height` filters of the covenant
+    pub fn spendable_height(&self) -> u64 {
+        self.features
+            .maturity
+            .max(self.script_lock_height)
+            .max(self.script.lock_height())
+            .max(self.covenant.lock_height())
+    }
+
+    /// Returns true if the output can be spent in a block at the given height
+    pub fn is_spendable_at(&self, block_height: u64) -> bool {
+        self.spendable_height() <= block_height
+    }
+
     pub fn metadata_byte_size(&self) -> usize {
         self.features.consensus_encode_exact_size() +
             self.script.consensus_encode_exact_size() +
@@ -391,3 +406,26 @@ impl Debug for UnblindedOutput {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use tari_script::script;
+
+    use super::*;
+    use crate::{covenant, transactions::test_helpers::TestParams};
+
+    #[test]
+    fn it_returns_the_spendable_height() {
+        let mut output = TestParams::new().create_unblinded_output(Default::default());
+        assert_eq!(output.spendable_height(), 0);
+        output.features.maturity = 10;
+        output.script = script!(CheckHeightVerify(20) Nop);
+        assert_eq!(output.spendable_height(), 20);
+        output.covenant = covenant!(absolute_height(@uint(30)));
+        assert_eq!(output.spendable_height(), 30);
+        output.script_lock_height = 40;
+        assert_eq!(output.spendable_height(), 40);
+        assert!(!output.is_spendable_at(39));
+        assert!(output.is_spendable_at(40));
+    }
+}
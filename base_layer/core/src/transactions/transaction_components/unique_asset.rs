@@ -0,0 +1,259 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Unique assets (NFTs). A unique asset is held in a [UniqueAsset](OutputType::UniqueAsset) output that carries the
+//! unique id of the asset and the public key of its parent, e.g. the collection it belongs to, in the metadata of its
+//! features. An asset is minted by its parent, which signs the asset for the commitment of the output it is minted in.
+//! The asset is transferred by spending the output to an output with the same features, which consensus only accepts
+//! in a transaction or block that spends the asset. Consensus does not check that the unique id is unique within its
+//! parent.
+
+use std::io::{Error, Read, Write};
+
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{Commitment, FixedHash, PrivateKey, PublicKey, Signature};
+use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+use crate::{
+    consensus::{
+        ConsensusDecoding,
+        ConsensusEncoding,
+        ConsensusEncodingSized,
+        DomainSeparatedConsensusHasher,
+        FromConsensusBytes,
+        MaxSizeBytes,
+        ToConsensusBytes,
+    },
+    transactions::{
+        transaction_components::{OutputFeatures, OutputType, TransactionError},
+        TransactionHashDomain,
+    },
+};
+
+/// The maximum size in bytes of the unique id of an asset
+pub const MAX_UNIQUE_ID_SIZE: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UniqueAsset {
+    /// The public key of the parent of the asset, e.g. the collection it belongs to
+    pub parent_public_key: PublicKey,
+    /// Identifies the asset among the assets of its parent
+    #[serde(with = "crate::common::hex_bytes")]
+    pub unique_id: Vec<u8>,
+    /// The signature of the parent, binding the mint of the asset to the commitment of the output it is minted in
+    pub parent_signature: Signature,
+}
+
+impl UniqueAsset {
+    /// Mints the asset `unique_id` of the parent in the output with the given commitment, signed by the parent
+    pub fn new(
+        unique_id: Vec<u8>,
+        parent_private_key: &PrivateKey,
+        commitment: &Commitment,
+    ) -> Result<Self, TransactionError> {
+        let parent_public_key = PublicKey::from_secret_key(parent_private_key);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let challenge = Self::build_challenge(
+            &parent_public_key,
+            &unique_id,
+            &PublicKey::from_secret_key(&nonce),
+            commitment,
+        );
+        let parent_signature = Signature::sign(parent_private_key.clone(), nonce, &challenge)
+            .map_err(|e| TransactionError::InvalidSignatureError(e.to_string()))?;
+        let asset = Self {
+            parent_public_key,
+            unique_id,
+            parent_signature,
+        };
+        asset.validate()?;
+        Ok(asset)
+    }
+
+    /// Returns the asset held in an output with these features, or `None` if they are not the features of a unique
+    /// asset output
+    pub fn from_features(features: &OutputFeatures) -> Result<Option<Self>, TransactionError> {
+        if features.output_type != OutputType::UniqueAsset {
+            return Ok(None);
+        }
+        let asset = Self::from_consensus_bytes(&features.metadata)
+            .map_err(|e| TransactionError::InvalidUniqueAsset(e.to_string()))?;
+        Ok(Some(asset))
+    }
+
+    /// The features of an output that holds this asset
+    pub fn to_features(&self) -> OutputFeatures {
+        OutputFeatures {
+            output_type: OutputType::UniqueAsset,
+            metadata: self.to_consensus_bytes(),
+            ..Default::default()
+        }
+    }
+
+    /// Identifies the asset across all parents
+    pub fn asset_id(&self) -> FixedHash {
+        DomainSeparatedConsensusHasher::<TransactionHashDomain>::new("unique_asset_id")
+            .chain(&self.parent_public_key)
+            .chain(&self.unique_id)
+            .finalize()
+            .into()
+    }
+
+    /// Checks that the unique id is valid. This does not check the signature, see
+    /// [verify_signature](Self::verify_signature).
+    pub fn validate(&self) -> Result<(), TransactionError> {
+        if self.unique_id.is_empty() || self.unique_id.len() > MAX_UNIQUE_ID_SIZE {
+            return Err(TransactionError::InvalidUniqueAsset(format!(
+                "Unique id must be 1 to {} bytes long",
+                MAX_UNIQUE_ID_SIZE
+            )));
+        }
+        Ok(())
+    }
+
+    /// Verifies that the parent signed the mint of the asset in the output with the given commitment
+    pub fn verify_signature(&self, commitment: &Commitment) -> Result<(), TransactionError> {
+        let challenge = Self::build_challenge(
+            &self.parent_public_key,
+            &self.unique_id,
+            self.parent_signature.get_public_nonce(),
+            commitment,
+        );
+        if !self
+            .parent_signature
+            .verify_challenge(&self.parent_public_key, &challenge)
+        {
+            return Err(TransactionError::InvalidUniqueAsset(format!(
+                "Parent signature of asset {} is not valid",
+                self.asset_id()
+            )));
+        }
+        Ok(())
+    }
+
+    fn build_challenge(
+        parent_public_key: &PublicKey,
+        unique_id: &[u8],
+        public_nonce: &PublicKey,
+        commitment: &Commitment,
+    ) -> [u8; 32] {
+        DomainSeparatedConsensusHasher::<TransactionHashDomain>::new("unique_asset")
+            .chain(parent_public_key)
+            .chain(&unique_id)
+            .chain(commitment)
+            .chain(public_nonce)
+            .finalize()
+    }
+}
+
+impl ConsensusEncoding for UniqueAsset {
+    fn consensus_encode<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.parent_public_key.consensus_encode(writer)?;
+        self.unique_id.consensus_encode(writer)?;
+        self.parent_signature.consensus_encode(writer)?;
+        Ok(())
+    }
+}
+
+impl ConsensusEncodingSized for UniqueAsset {}
+
+impl ConsensusDecoding for UniqueAsset {
+    fn consensus_decode<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let parent_public_key = PublicKey::consensus_decode(reader)?;
+        let unique_id = <MaxSizeBytes<MAX_UNIQUE_ID_SIZE> as ConsensusDecoding>::consensus_decode(reader)?;
+        Ok(Self {
+            parent_public_key,
+            unique_id: unique_id.into(),
+            parent_signature: Signature::consensus_decode(reader)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_utilities::ByteArray;
+
+    use super::*;
+    use crate::consensus::check_consensus_encoding_correctness;
+
+    fn create_commitment() -> Commitment {
+        Commitment::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)))
+    }
+
+    #[test]
+    fn consensus_encoding() {
+        let asset = UniqueAsset::new(
+            vec![1u8; MAX_UNIQUE_ID_SIZE],
+            &PrivateKey::random(&mut OsRng),
+            &create_commitment(),
+        )
+        .unwrap();
+        check_consensus_encoding_correctness(asset).unwrap();
+    }
+
+    #[test]
+    fn it_round_trips_through_output_features() {
+        let parent_key = PrivateKey::random(&mut OsRng);
+        let commitment = create_commitment();
+        let asset = UniqueAsset::new(b"sword #1".to_vec(), &parent_key, &commitment).unwrap();
+        let features = asset.to_features();
+        features.validate().unwrap();
+        assert_eq!(UniqueAsset::from_features(&features).unwrap(), Some(asset.clone()));
+        assert_eq!(UniqueAsset::from_features(&OutputFeatures::default()).unwrap(), None);
+
+        let other = UniqueAsset::new(b"sword #2".to_vec(), &parent_key, &commitment).unwrap();
+        assert_ne!(asset.asset_id(), other.asset_id());
+
+        let mut malformed = features;
+        malformed.metadata.pop();
+        assert!(matches!(
+            UniqueAsset::from_features(&malformed),
+            Err(TransactionError::InvalidUniqueAsset(_))
+        ));
+        assert!(malformed.validate().is_err());
+    }
+
+    #[test]
+    fn it_binds_the_mint_to_the_output() {
+        let parent_key = PrivateKey::random(&mut OsRng);
+        let commitment = create_commitment();
+        let asset = UniqueAsset::new(b"sword #1".to_vec(), &parent_key, &commitment).unwrap();
+        asset.verify_signature(&commitment).unwrap();
+        assert!(asset.verify_signature(&create_commitment()).is_err());
+
+        let mut renamed = asset.clone();
+        renamed.unique_id = b"sword #2".to_vec();
+        assert!(renamed.verify_signature(&commitment).is_err());
+
+        // Another key can not mint an asset of the parent
+        let mut counterfeit =
+            UniqueAsset::new(b"sword #1".to_vec(), &PrivateKey::random(&mut OsRng), &commitment).unwrap();
+        counterfeit.parent_public_key = asset.parent_public_key.clone();
+        assert_eq!(counterfeit.asset_id(), asset.asset_id());
+        assert!(counterfeit.verify_signature(&commitment).is_err());
+
+        // A signature made up for the parent key without the private key
+        let mut made_up = asset.clone();
+        made_up.unique_id = b"sword #3".to_vec();
+        let s = PrivateKey::random(&mut OsRng);
+        let challenge = UniqueAsset::build_challenge(
+            &made_up.parent_public_key,
+            &made_up.unique_id,
+            &PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            &commitment,
+        );
+        let e = PrivateKey::from_bytes(&challenge).unwrap();
+        let nonce = PublicKey::from_secret_key(&s) - &e * &made_up.parent_public_key;
+        made_up.parent_signature = Signature::new(nonce, s);
+        assert!(made_up.verify_signature(&commitment).is_err());
+    }
+
+    #[test]
+    fn it_rejects_invalid_unique_ids() {
+        let parent_key = PrivateKey::random(&mut OsRng);
+        let commitment = create_commitment();
+        assert!(UniqueAsset::new(vec![], &parent_key, &commitment).is_err());
+        assert!(UniqueAsset::new(vec![1u8; MAX_UNIQUE_ID_SIZE + 1], &parent_key, &commitment).is_err());
+    }
+}
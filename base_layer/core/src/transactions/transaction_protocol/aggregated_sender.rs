@@ -0,0 +1,515 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! One-sided payments where the sender offset key is an aggregate of the keys of several parties, e.g. the parties of
+//! an escrow that all have to agree to release it. No party knows the private key of the aggregate, so the sender part
+//! of the metadata signature is created in a [SenderSigningSession] with four rounds:
+//! 1. Every party generates a key for the payment and sends its public key to the others.
+//! 1. Every party aggregates the keys and commits to a nonce, see [SenderSigningSession::add_party_keys].
+//! 1. Once all commitments are in, every party reveals its nonce and its share of the Diffie-Hellman secret with the
+//!    recipient. The coordinator, who builds the output, derives the spending key of the recipient from the combined
+//!    secret and creates its part of the metadata signature with the combined nonce.
+//! 1. Every party checks the output and signs it. The coordinator combines the partial signatures with
+//!    [AggregatedSenderKey::finalize_metadata_signature].
+//!
+//! The session can be serialized between rounds so that a wallet can persist it while it waits for the other parties.
+//! The messages of the rounds are exchanged as an [AggregatedSenderMessage].
+
+use derivative::Derivative;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{ComSignature, CommitmentFactory, FixedHash, PrivateKey, PublicKey, Signature};
+use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+use tari_utilities::ByteArray;
+
+use crate::{
+    consensus::DomainSeparatedConsensusHasher,
+    transactions::{
+        transaction_components::{TransactionOutput, UnblindedOutput},
+        transaction_protocol::TransactionProtocolError as TPE,
+        TransactionHashDomain,
+    },
+};
+
+/// The sender offset public key of a group of parties
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregatedSenderKey {
+    /// The public keys of the parties, sorted
+    keys: Vec<PublicKey>,
+    public_key: PublicKey,
+}
+
+impl AggregatedSenderKey {
+    /// Aggregates the public keys of the parties. Every key is weighted with a coefficient that commits to all the
+    /// keys, so that a party cannot choose its key to cancel out the keys of the others.
+    pub fn new(mut keys: Vec<PublicKey>) -> Result<Self, TPE> {
+        keys.sort();
+        keys.dedup();
+        if keys.len() < 2 {
+            return Err(TPE::ValidationError(
+                "An aggregated sender key needs at least two parties".to_string(),
+            ));
+        }
+        let mut public_key = PublicKey::default();
+        for key in &keys {
+            public_key = public_key + &(key_coefficient(&keys, key)? * key);
+        }
+        Ok(Self { keys, public_key })
+    }
+
+    /// The aggregated key, used as the sender offset public key of the output
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    /// The coefficient of the key of a party in the aggregated key
+    pub fn coefficient(&self, key: &PublicKey) -> Result<PrivateKey, TPE> {
+        if !self.keys.contains(key) {
+            return Err(TPE::ValidationError(format!(
+                "{} is not a party to the aggregated key",
+                key
+            )));
+        }
+        key_coefficient(&self.keys, key)
+    }
+
+    /// Combines the nonces that the parties revealed, for the coordinator to create its part of the metadata signature
+    pub fn aggregate_public_nonce(&self, reveals: &[NonceReveal]) -> Result<PublicKey, TPE> {
+        self.check_parties(reveals.iter().map(|r| &r.party))?;
+        Ok(reveals
+            .iter()
+            .fold(PublicKey::default(), |sum, r| sum + &r.public_nonce))
+    }
+
+    /// Combines the shares of the Diffie-Hellman secret that the parties revealed. The result equals the secret that
+    /// the recipient derives from the aggregated key, see `DiffieHellmanSharedSecret::shared_secret`.
+    pub fn aggregate_shared_secret(&self, reveals: &[NonceReveal]) -> Result<PublicKey, TPE> {
+        self.check_parties(reveals.iter().map(|r| &r.party))?;
+        Ok(reveals
+            .iter()
+            .fold(PublicKey::default(), |sum, r| sum + &r.partial_shared_secret))
+    }
+
+    /// Creates the part of the metadata signature of the output that the coordinator signs with the spending key. The
+    /// sender offset public key of the output must be the aggregated key.
+    pub fn create_partial_metadata_signature(
+        &self,
+        output: &UnblindedOutput,
+        aggregate_public_nonce: &PublicKey,
+    ) -> Result<ComSignature, TPE> {
+        if output.sender_offset_public_key != self.public_key {
+            return Err(TPE::ValidationError(
+                "The sender offset public key of the output is not the aggregated key".to_string(),
+            ));
+        }
+        Ok(TransactionOutput::create_partial_metadata_signature(
+            output.version,
+            output.value,
+            &output.spending_key,
+            &output.script,
+            &output.features,
+            &self.public_key,
+            aggregate_public_nonce,
+            &output.covenant,
+            &output.encrypted_value,
+            output.minimum_value_promise,
+        )?)
+    }
+
+    /// Adds the partial signatures of the parties to the partial metadata signature of the output, see
+    /// [create_partial_metadata_signature](Self::create_partial_metadata_signature), and verifies the result
+    pub fn finalize_metadata_signature(
+        &self,
+        output: &TransactionOutput,
+        partial_signatures: &[PartialMetadataSignature],
+    ) -> Result<ComSignature, TPE> {
+        self.check_parties(partial_signatures.iter().map(|s| &s.party))?;
+        let (r_pub, u, v) = output.metadata_signature.complete_signature_tuple();
+        let mut r_pub_aggregated = r_pub.clone();
+        let mut u_aggregated = u.clone();
+        for partial in partial_signatures {
+            r_pub_aggregated = &r_pub_aggregated + &partial.public_nonce;
+            u_aggregated = &u_aggregated + &partial.signature;
+        }
+        let metadata_signature = ComSignature::new(r_pub_aggregated, u_aggregated, v.clone());
+
+        let e = TransactionOutput::build_metadata_signature_challenge(
+            output.version,
+            &output.script,
+            &output.features,
+            &self.public_key,
+            metadata_signature.public_nonce(),
+            &output.commitment,
+            &output.covenant,
+            &output.encrypted_value,
+            output.minimum_value_promise,
+        );
+        if !metadata_signature.verify_challenge(
+            &(&output.commitment + &self.public_key),
+            &e,
+            &CommitmentFactory::default(),
+        ) {
+            return Err(TPE::InvalidSignatureError(
+                "The aggregated metadata signature is not valid".to_string(),
+            ));
+        }
+        Ok(metadata_signature)
+    }
+
+    /// Combines the shares of the private key that the parties revealed with their partial signatures. The coordinator
+    /// needs the private key to balance the script offset of the transaction.
+    pub fn aggregate_private_key(&self, partial_signatures: &[PartialMetadataSignature]) -> Result<PrivateKey, TPE> {
+        self.check_parties(partial_signatures.iter().map(|s| &s.party))?;
+        let private_key = partial_signatures
+            .iter()
+            .fold(PrivateKey::default(), |sum, s| sum + &s.private_key_share);
+        if PublicKey::from_secret_key(&private_key) != self.public_key {
+            return Err(TPE::ValidationError(
+                "The private key shares do not add up to the aggregated key".to_string(),
+            ));
+        }
+        Ok(private_key)
+    }
+
+    /// Checks that every party contributed exactly once
+    fn check_parties<'a, I: Iterator<Item = &'a PublicKey>>(&self, parties: I) -> Result<(), TPE> {
+        let mut parties = parties.collect::<Vec<_>>();
+        parties.sort();
+        if parties.len() != self.keys.len() || parties.iter().zip(&self.keys).any(|(p, k)| *p != k) {
+            return Err(TPE::ValidationError(
+                "Expected exactly one contribution from every party".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn key_coefficient(keys: &[PublicKey], key: &PublicKey) -> Result<PrivateKey, TPE> {
+    let hasher = keys.iter().fold(
+        DomainSeparatedConsensusHasher::<TransactionHashDomain>::new("aggregated_sender_key"),
+        |hasher, k| hasher.chain(k),
+    );
+    PrivateKey::from_bytes(&hasher.chain(key).finalize()).map_err(|e| TPE::ConversionError(e.to_string()))
+}
+
+fn nonce_commitment(public_nonce: &PublicKey) -> FixedHash {
+    DomainSeparatedConsensusHasher::<TransactionHashDomain>::new("aggregated_sender_nonce")
+        .chain(public_nonce)
+        .finalize()
+        .into()
+}
+
+/// The commitment of a party to its nonce, sent in the first round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceCommitment {
+    pub party: PublicKey,
+    pub commitment: FixedHash,
+}
+
+/// The nonce of a party and its share of the Diffie-Hellman secret with the recipient, sent in the second round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceReveal {
+    pub party: PublicKey,
+    pub public_nonce: PublicKey,
+    pub partial_shared_secret: PublicKey,
+}
+
+/// The partial signature of a party, sent in the last round. The party also reveals its weighted share of the
+/// aggregated private key, as the coordinator needs it for the script offset. The script offset is public, so whoever
+/// knows the script keys of the inputs learns the aggregated private key once the transaction is published anyway.
+#[derive(Derivative, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derivative(Debug)]
+pub struct PartialMetadataSignature {
+    pub party: PublicKey,
+    pub public_nonce: PublicKey,
+    #[derivative(Debug = "ignore")]
+    pub signature: PrivateKey,
+    #[derivative(Debug = "ignore")]
+    pub private_key_share: PrivateKey,
+}
+
+/// A message that a party sends to the other parties. It is exchanged in the versioned protobuf encoding of
+/// [AggregatedSenderMessage::to_bytes].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AggregatedSenderMessage {
+    /// The public key of the party, sent in the first round
+    PartyKey(PublicKey),
+    NonceCommitment(NonceCommitment),
+    NonceReveal(NonceReveal),
+    PartialSignature(PartialMetadataSignature),
+}
+
+/// The round a [SenderSigningSession] is waiting for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SenderSigningRound {
+    /// Waiting for the public keys of all the parties
+    PartyKeys,
+    /// Waiting for the nonce commitments of all the parties
+    NonceCommitments,
+    /// Waiting for the nonces of all the parties and the output to sign
+    NonceReveals,
+    /// The output was signed. The nonce is discarded, so the session cannot sign again.
+    Signed,
+}
+
+/// The state of one party in the signing of the sender part of a metadata signature
+#[derive(Derivative, Clone, Serialize, Deserialize)]
+#[derivative(Debug)]
+pub struct SenderSigningSession {
+    aggregated_key: Option<AggregatedSenderKey>,
+    #[derivative(Debug = "ignore")]
+    private_key: PrivateKey,
+    public_key: PublicKey,
+    recipient: PublicKey,
+    #[derivative(Debug = "ignore")]
+    private_nonce: PrivateKey,
+    nonce_commitments: Vec<NonceCommitment>,
+    round: SenderSigningRound,
+}
+
+impl SenderSigningSession {
+    /// Starts a session for the party with the given private key, to pay the recipient with the given public key. The
+    /// key should only be used for this payment.
+    pub fn new(private_key: PrivateKey, recipient: PublicKey) -> Self {
+        Self {
+            aggregated_key: None,
+            public_key: PublicKey::from_secret_key(&private_key),
+            private_key,
+            recipient,
+            private_nonce: PrivateKey::random(&mut OsRng),
+            nonce_commitments: Vec::new(),
+            round: SenderSigningRound::PartyKeys,
+        }
+    }
+
+    /// The aggregated key, once the keys of all the parties were added
+    pub fn aggregated_key(&self) -> Option<&AggregatedSenderKey> {
+        self.aggregated_key.as_ref()
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    pub fn recipient(&self) -> &PublicKey {
+        &self.recipient
+    }
+
+    pub fn round(&self) -> SenderSigningRound {
+        self.round
+    }
+
+    /// Completes the first round with the public keys of all the parties, including this one, and returns the
+    /// commitment to the nonce of this party to send to the others
+    pub fn add_party_keys(&mut self, party_keys: Vec<PublicKey>) -> Result<NonceCommitment, TPE> {
+        if self.round != SenderSigningRound::PartyKeys {
+            return Err(TPE::InvalidStateError);
+        }
+        let aggregated_key = AggregatedSenderKey::new(party_keys)?;
+        // Fails if this party is not one of the parties
+        aggregated_key.coefficient(&self.public_key)?;
+        self.aggregated_key = Some(aggregated_key);
+        self.round = SenderSigningRound::NonceCommitments;
+        Ok(self.nonce_commitment())
+    }
+
+    /// The commitment to the nonce of this party, which can be sent again while the session waits for the commitments
+    /// of the other parties
+    pub fn nonce_commitment(&self) -> NonceCommitment {
+        NonceCommitment {
+            party: self.public_key.clone(),
+            commitment: nonce_commitment(&PublicKey::from_secret_key(&self.private_nonce)),
+        }
+    }
+
+    /// Completes the second round with the nonce commitments of all the parties, including this one, and returns the
+    /// nonce of this party to send to the others
+    pub fn add_nonce_commitments(&mut self, commitments: Vec<NonceCommitment>) -> Result<NonceReveal, TPE> {
+        if self.round != SenderSigningRound::NonceCommitments {
+            return Err(TPE::InvalidStateError);
+        }
+        self.get_aggregated_key()?
+            .check_parties(commitments.iter().map(|c| &c.party))?;
+        if !commitments.contains(&self.nonce_commitment()) {
+            return Err(TPE::ValidationError(
+                "The nonce commitment of this party is missing".to_string(),
+            ));
+        }
+        self.nonce_commitments = commitments;
+        self.round = SenderSigningRound::NonceReveals;
+        self.nonce_reveal()
+    }
+
+    /// The nonce of this party, which can be sent again while the session waits for the nonces of the other parties
+    pub fn nonce_reveal(&self) -> Result<NonceReveal, TPE> {
+        if self.round != SenderSigningRound::NonceReveals {
+            return Err(TPE::InvalidStateError);
+        }
+        let weighted_key = self.weighted_private_key()?;
+        Ok(NonceReveal {
+            party: self.public_key.clone(),
+            public_nonce: PublicKey::from_secret_key(&self.private_nonce),
+            partial_shared_secret: &weighted_key * &self.recipient,
+        })
+    }
+
+    /// Completes the last round by signing the output that the coordinator built with the combined nonces. The caller
+    /// is responsible for checking that the output pays what the parties agreed to. The nonce is discarded
+    /// afterwards, so the session must be persisted before the partial signature is sent.
+    pub fn sign(
+        &mut self,
+        reveals: &[NonceReveal],
+        output: &TransactionOutput,
+    ) -> Result<PartialMetadataSignature, TPE> {
+        if self.round != SenderSigningRound::NonceReveals {
+            return Err(TPE::InvalidStateError);
+        }
+        for reveal in reveals {
+            let committed = self
+                .nonce_commitments
+                .iter()
+                .any(|c| c.party == reveal.party && c.commitment == nonce_commitment(&reveal.public_nonce));
+            if !committed {
+                return Err(TPE::ValidationError(format!(
+                    "The nonce of party {} does not match its commitment",
+                    reveal.party
+                )));
+            }
+        }
+        let aggregated_key = self.get_aggregated_key()?;
+        if output.sender_offset_public_key != aggregated_key.public_key {
+            return Err(TPE::ValidationError(
+                "The sender offset public key of the output is not the aggregated key".to_string(),
+            ));
+        }
+
+        let aggregate_public_nonce = aggregated_key.aggregate_public_nonce(reveals)?;
+        let e = output.get_metadata_signature_challenge(Some(&aggregate_public_nonce));
+        let weighted_key = self.weighted_private_key()?;
+        let signature = Signature::sign(weighted_key.clone(), self.private_nonce.clone(), &e)?;
+
+        self.private_nonce = PrivateKey::default();
+        self.round = SenderSigningRound::Signed;
+        Ok(PartialMetadataSignature {
+            party: self.public_key.clone(),
+            public_nonce: signature.get_public_nonce().clone(),
+            signature: signature.get_signature().clone(),
+            private_key_share: weighted_key,
+        })
+    }
+
+    fn get_aggregated_key(&self) -> Result<&AggregatedSenderKey, TPE> {
+        self.aggregated_key.as_ref().ok_or(TPE::InvalidStateError)
+    }
+
+    fn weighted_private_key(&self) -> Result<PrivateKey, TPE> {
+        Ok(self.get_aggregated_key()?.coefficient(&self.public_key)? * &self.private_key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transactions::{
+        test_helpers::{TestParams, UtxoTestParams},
+        CryptoFactories,
+    };
+
+    /// Creates the sessions of the parties and completes the first round
+    fn create_sessions(num_parties: usize) -> (Vec<SenderSigningSession>, Vec<NonceCommitment>, PrivateKey) {
+        let recipient_key = PrivateKey::random(&mut OsRng);
+        let mut sessions = (0..num_parties)
+            .map(|_| {
+                SenderSigningSession::new(
+                    PrivateKey::random(&mut OsRng),
+                    PublicKey::from_secret_key(&recipient_key),
+                )
+            })
+            .collect::<Vec<_>>();
+        let party_keys = sessions.iter().map(|s| s.public_key().clone()).collect::<Vec<_>>();
+        let commitments = sessions
+            .iter_mut()
+            .map(|s| s.add_party_keys(party_keys.clone()).unwrap())
+            .collect();
+        (sessions, commitments, recipient_key)
+    }
+
+    #[test]
+    fn it_aggregates_the_metadata_signature_of_the_parties() {
+        let (sessions, commitments, recipient_key) = create_sessions(3);
+        let aggregated_key = sessions[0].aggregated_key().unwrap().clone();
+
+        // The sessions are persisted between rounds
+        let mut sessions = sessions
+            .iter()
+            .map(|s| serde_json::from_str::<SenderSigningSession>(&serde_json::to_string(s).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        let reveals = sessions
+            .iter_mut()
+            .map(|s| s.add_nonce_commitments(commitments.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        // The coordinator builds the output. The recipient derives the same secret from the aggregated key.
+        let shared_secret = aggregated_key.aggregate_shared_secret(&reveals).unwrap();
+        assert_eq!(shared_secret, &recipient_key * aggregated_key.public_key());
+        let mut output = TestParams::new().create_unblinded_output(UtxoTestParams::default());
+        output.sender_offset_public_key = aggregated_key.public_key().clone();
+        let aggregate_public_nonce = aggregated_key.aggregate_public_nonce(&reveals).unwrap();
+        output.metadata_signature = aggregated_key
+            .create_partial_metadata_signature(&output, &aggregate_public_nonce)
+            .unwrap();
+        let mut output = output.as_transaction_output(&CryptoFactories::default()).unwrap();
+        assert!(output.verify_metadata_signature().is_err());
+
+        let partial_signatures = sessions
+            .iter_mut()
+            .map(|s| s.sign(&reveals, &output).unwrap())
+            .collect::<Vec<_>>();
+        assert!(sessions[0].sign(&reveals, &output).is_err());
+        output.metadata_signature = aggregated_key
+            .finalize_metadata_signature(&output, &partial_signatures)
+            .unwrap();
+        output.verify_metadata_signature().unwrap();
+        aggregated_key.aggregate_private_key(&partial_signatures).unwrap();
+        assert!(aggregated_key
+            .finalize_metadata_signature(&output, &partial_signatures[1..])
+            .is_err());
+    }
+
+    #[test]
+    fn it_rejects_nonces_that_do_not_match_their_commitment() {
+        let (mut sessions, commitments, _) = create_sessions(2);
+        let mut reveals = sessions
+            .iter_mut()
+            .map(|s| s.add_nonce_commitments(commitments.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert!(sessions[0].add_nonce_commitments(commitments).is_err());
+
+        reveals[1].public_nonce = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let mut output = TestParams::new().create_unblinded_output(UtxoTestParams::default());
+        output.sender_offset_public_key = sessions[0].aggregated_key().unwrap().public_key().clone();
+        let output = output.as_transaction_output(&CryptoFactories::default()).unwrap();
+        assert!(matches!(
+            sessions[0].sign(&reveals, &output),
+            Err(TPE::ValidationError(_))
+        ));
+        assert_eq!(sessions[0].round(), SenderSigningRound::NonceReveals);
+    }
+
+    #[test]
+    fn it_rejects_sessions_of_outsiders() {
+        let party_keys = vec![
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        ];
+        let recipient = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let mut session = SenderSigningSession::new(PrivateKey::random(&mut OsRng), recipient);
+        assert!(session.add_party_keys(party_keys.clone()).is_err());
+        assert_eq!(session.round(), SenderSigningRound::PartyKeys);
+        assert!(AggregatedSenderKey::new(vec![party_keys[0].clone(), party_keys[0].clone()]).is_err());
+    }
+}
@@ -93,8 +93,10 @@ use thiserror::Error;
 
 use crate::transactions::{tari_amount::*, transaction_components::TransactionError};
 
+pub mod aggregated_sender;
 pub mod proto;
 pub mod recipient;
+pub mod sanitizer;
 pub mod sender;
 pub mod single_receiver;
 pub mod transaction_initializer;
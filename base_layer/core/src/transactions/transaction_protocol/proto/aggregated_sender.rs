@@ -0,0 +1,243 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::{TryFrom, TryInto};
+
+use prost::Message;
+use tari_common_types::types::{FixedHash, PrivateKey, PublicKey};
+use tari_utilities::ByteArray;
+
+use super::{protocol as proto, protocol::aggregated_sender_message::Message as ProtoAggregatedSenderMessage};
+use crate::transactions::transaction_protocol::{
+    aggregated_sender::{AggregatedSenderMessage, NonceCommitment, NonceReveal, PartialMetadataSignature},
+    TransactionProtocolError as TPE,
+};
+
+/// The version of the encoding of [AggregatedSenderMessage]. Fields can be added without a new version, as older
+/// wallets skip them, but any other change needs one.
+pub const AGGREGATED_SENDER_MESSAGE_VERSION: u32 = 1;
+
+impl AggregatedSenderMessage {
+    /// Encodes the message as a versioned protobuf message
+    pub fn to_bytes(&self) -> Vec<u8> {
+        proto::AggregatedSenderMessage::from(self.clone()).encode_to_vec()
+    }
+
+    /// Decodes a message encoded by [AggregatedSenderMessage::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TPE> {
+        let message = proto::AggregatedSenderMessage::decode(bytes).map_err(|e| TPE::ConversionError(e.to_string()))?;
+        if message.version == 0 {
+            return Err(TPE::ConversionError(
+                "Aggregated sender message version not provided".to_string(),
+            ));
+        }
+        if message.version > AGGREGATED_SENDER_MESSAGE_VERSION {
+            return Err(TPE::UnsupportedError(format!(
+                "Aggregated sender message version {}",
+                message.version
+            )));
+        }
+        Self::try_from(message).map_err(TPE::ConversionError)
+    }
+}
+
+impl TryFrom<proto::AggregatedSenderMessage> for AggregatedSenderMessage {
+    type Error = String;
+
+    fn try_from(message: proto::AggregatedSenderMessage) -> Result<Self, Self::Error> {
+        let message = match message
+            .message
+            .ok_or_else(|| "Aggregated sender message not provided".to_string())?
+        {
+            ProtoAggregatedSenderMessage::PartyKey(key) => {
+                AggregatedSenderMessage::PartyKey(PublicKey::from_bytes(&key).map_err(|err| err.to_string())?)
+            },
+            ProtoAggregatedSenderMessage::NonceCommitment(commitment) => {
+                AggregatedSenderMessage::NonceCommitment(commitment.try_into()?)
+            },
+            ProtoAggregatedSenderMessage::NonceReveal(reveal) => {
+                AggregatedSenderMessage::NonceReveal(reveal.try_into()?)
+            },
+            ProtoAggregatedSenderMessage::PartialSignature(signature) => {
+                AggregatedSenderMessage::PartialSignature(signature.try_into()?)
+            },
+        };
+        Ok(message)
+    }
+}
+
+impl From<AggregatedSenderMessage> for proto::AggregatedSenderMessage {
+    fn from(message: AggregatedSenderMessage) -> Self {
+        let message = match message {
+            AggregatedSenderMessage::PartyKey(key) => ProtoAggregatedSenderMessage::PartyKey(key.to_vec()),
+            AggregatedSenderMessage::NonceCommitment(commitment) => {
+                ProtoAggregatedSenderMessage::NonceCommitment(commitment.into())
+            },
+            AggregatedSenderMessage::NonceReveal(reveal) => ProtoAggregatedSenderMessage::NonceReveal(reveal.into()),
+            AggregatedSenderMessage::PartialSignature(signature) => {
+                ProtoAggregatedSenderMessage::PartialSignature(signature.into())
+            },
+        };
+        Self {
+            version: AGGREGATED_SENDER_MESSAGE_VERSION,
+            message: Some(message),
+        }
+    }
+}
+
+impl TryFrom<proto::AggregatedSenderNonceCommitment> for NonceCommitment {
+    type Error = String;
+
+    fn try_from(commitment: proto::AggregatedSenderNonceCommitment) -> Result<Self, Self::Error> {
+        Ok(Self {
+            party: PublicKey::from_bytes(&commitment.party).map_err(|err| err.to_string())?,
+            commitment: FixedHash::try_from(commitment.commitment).map_err(|err| err.to_string())?,
+        })
+    }
+}
+
+impl From<NonceCommitment> for proto::AggregatedSenderNonceCommitment {
+    fn from(commitment: NonceCommitment) -> Self {
+        Self {
+            party: commitment.party.to_vec(),
+            commitment: commitment.commitment.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<proto::AggregatedSenderNonceReveal> for NonceReveal {
+    type Error = String;
+
+    fn try_from(reveal: proto::AggregatedSenderNonceReveal) -> Result<Self, Self::Error> {
+        Ok(Self {
+            party: PublicKey::from_bytes(&reveal.party).map_err(|err| err.to_string())?,
+            public_nonce: PublicKey::from_bytes(&reveal.public_nonce).map_err(|err| err.to_string())?,
+            partial_shared_secret: PublicKey::from_bytes(&reveal.partial_shared_secret)
+                .map_err(|err| err.to_string())?,
+        })
+    }
+}
+
+impl From<NonceReveal> for proto::AggregatedSenderNonceReveal {
+    fn from(reveal: NonceReveal) -> Self {
+        Self {
+            party: reveal.party.to_vec(),
+            public_nonce: reveal.public_nonce.to_vec(),
+            partial_shared_secret: reveal.partial_shared_secret.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<proto::AggregatedSenderPartialSignature> for PartialMetadataSignature {
+    type Error = String;
+
+    fn try_from(signature: proto::AggregatedSenderPartialSignature) -> Result<Self, Self::Error> {
+        Ok(Self {
+            party: PublicKey::from_bytes(&signature.party).map_err(|err| err.to_string())?,
+            public_nonce: PublicKey::from_bytes(&signature.public_nonce).map_err(|err| err.to_string())?,
+            signature: PrivateKey::from_bytes(&signature.signature).map_err(|err| err.to_string())?,
+            private_key_share: PrivateKey::from_bytes(&signature.private_key_share).map_err(|err| err.to_string())?,
+        })
+    }
+}
+
+impl From<PartialMetadataSignature> for proto::AggregatedSenderPartialSignature {
+    fn from(signature: PartialMetadataSignature) -> Self {
+        Self {
+            party: signature.party.to_vec(),
+            public_nonce: signature.public_nonce.to_vec(),
+            signature: signature.signature.to_vec(),
+            private_key_share: signature.private_key_share.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+    use tari_utilities::hex::{from_hex, Hex};
+
+    use super::*;
+
+    /// The encodings of `1⋅G`, `2⋅G` and `3⋅G`
+    const KEY_1: &str = "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76";
+    const KEY_2: &str = "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919";
+    const KEY_3: &str = "94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259";
+
+    fn public_key(secret: u64) -> PublicKey {
+        PublicKey::from_secret_key(&PrivateKey::from(secret))
+    }
+
+    fn scalar_hex(secret: u8) -> String {
+        format!("{:02x}{}", secret, "00".repeat(31))
+    }
+
+    /// Messages and their encoding in the current version. These must not change, as wallets of other releases have
+    /// to decode them.
+    fn golden_vectors() -> Vec<(AggregatedSenderMessage, String)> {
+        vec![
+            (
+                AggregatedSenderMessage::PartyKey(public_key(1)),
+                format!("08011220{}", KEY_1),
+            ),
+            (
+                AggregatedSenderMessage::NonceCommitment(NonceCommitment {
+                    party: public_key(1),
+                    commitment: FixedHash::from([7u8; 32]),
+                }),
+                format!("08011a440a20{}1220{}", KEY_1, "07".repeat(32)),
+            ),
+            (
+                AggregatedSenderMessage::NonceReveal(NonceReveal {
+                    party: public_key(1),
+                    public_nonce: public_key(2),
+                    partial_shared_secret: public_key(3),
+                }),
+                format!("080122660a20{}1220{}1a20{}", KEY_1, KEY_2, KEY_3),
+            ),
+            (
+                AggregatedSenderMessage::PartialSignature(PartialMetadataSignature {
+                    party: public_key(1),
+                    public_nonce: public_key(2),
+                    signature: PrivateKey::from(4),
+                    private_key_share: PrivateKey::from(5),
+                }),
+                format!(
+                    "08012a88010a20{}1220{}1a20{}2220{}",
+                    KEY_1,
+                    KEY_2,
+                    scalar_hex(4),
+                    scalar_hex(5)
+                ),
+            ),
+        ]
+    }
+
+    #[test]
+    fn it_matches_the_golden_vectors() {
+        for (message, encoding) in golden_vectors() {
+            assert_eq!(message.to_bytes().to_hex(), encoding);
+            assert_eq!(
+                AggregatedSenderMessage::from_bytes(&from_hex(&encoding).unwrap()).unwrap(),
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_messages() {
+        let (_, encoding) = golden_vectors().remove(0);
+        let mut bytes = from_hex(&encoding).unwrap();
+        bytes[1] = 2;
+        assert!(matches!(
+            AggregatedSenderMessage::from_bytes(&bytes),
+            Err(TPE::UnsupportedError(_))
+        ));
+        bytes[1] = 1;
+        bytes[4] ^= 1;
+        assert!(AggregatedSenderMessage::from_bytes(&bytes).is_err());
+
+        assert!(AggregatedSenderMessage::from_bytes(&[0x08, 0x01]).is_err());
+        assert!(AggregatedSenderMessage::from_bytes(&[]).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Golden vectors of the messages that wallets exchange to negotiate a transaction. The encodings must not change, as
+//! wallets of other releases have to decode them.
+
+use std::convert::TryFrom;
+
+use prost::Message;
+use tari_common_types::{
+    transaction::TxId,
+    types::{ComSignature, Commitment, PrivateKey, PublicKey, RangeProof, Signature},
+};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_script::script;
+use tari_utilities::hex::{from_hex, Hex};
+
+use super::protocol as proto;
+use crate::{
+    covenants::Covenant,
+    transactions::{
+        tari_amount::MicroTari,
+        transaction_components::{
+            EncryptedValue,
+            KernelFeatures,
+            OutputFeatures,
+            Transaction,
+            TransactionKernel,
+            TransactionOutput,
+        },
+        transaction_protocol::{
+            recipient::RecipientSignedMessage,
+            sender::{SingleRoundSenderData, TransactionSenderMessage},
+            TransactionMetadata,
+        },
+    },
+};
+
+/// The encodings of `1⋅G`, `2⋅G` and `3⋅G`
+const KEY_1: &str = "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76";
+const KEY_2: &str = "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919";
+const KEY_3: &str = "94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259";
+
+fn public_key(secret: u64) -> PublicKey {
+    PublicKey::from_secret_key(&PrivateKey::from(secret))
+}
+
+fn commitment(secret: u64) -> Commitment {
+    Commitment::from_public_key(&public_key(secret))
+}
+
+fn scalar_hex(secret: u8) -> String {
+    format!("{:02x}{}", secret, "00".repeat(31))
+}
+
+fn tx_id() -> TxId {
+    TxId::from(1u64)
+}
+
+fn sender_data() -> SingleRoundSenderData {
+    SingleRoundSenderData {
+        tx_id: tx_id(),
+        amount: MicroTari(2000),
+        public_excess: public_key(1),
+        public_nonce: public_key(2),
+        metadata: TransactionMetadata::new(MicroTari(100), 0),
+        message: "Golden".to_string(),
+        features: OutputFeatures::default(),
+        script: script!(Nop),
+        sender_offset_public_key: public_key(3),
+        public_commitment_nonce: public_key(2),
+        covenant: Covenant::default(),
+        minimum_value_promise: MicroTari::zero(),
+        expires_at: None,
+    }
+}
+
+fn recipient_signed_message() -> RecipientSignedMessage {
+    RecipientSignedMessage {
+        tx_id: tx_id(),
+        output: TransactionOutput::new_current_version(
+            OutputFeatures::default(),
+            commitment(1),
+            RangeProof::default(),
+            script!(Nop),
+            public_key(2),
+            ComSignature::new(commitment(3), PrivateKey::from(4), PrivateKey::from(5)),
+            Covenant::default(),
+            EncryptedValue::default(),
+            MicroTari::zero(),
+        ),
+        public_spend_key: public_key(2),
+        partial_signature: Signature::new(public_key(3), PrivateKey::from(6)),
+        tx_metadata: TransactionMetadata::new(MicroTari(100), 0),
+    }
+}
+
+fn finalized_transaction() -> Transaction {
+    let kernel = TransactionKernel::new_current_version(
+        KernelFeatures::empty(),
+        MicroTari(100),
+        0,
+        commitment(1),
+        Signature::new(public_key(2), PrivateKey::from(9)),
+        None,
+    );
+    Transaction::new(vec![], vec![], vec![kernel], PrivateKey::from(7), PrivateKey::from(8))
+}
+
+fn decode<T: Message + Default>(encoding: &str) -> T {
+    T::decode(from_hex(encoding).unwrap().as_slice()).unwrap()
+}
+
+#[test]
+fn it_matches_the_transaction_sender_message_vectors() {
+    let encoding = "08012001";
+    let message = proto::TransactionSenderMessage::from(TransactionSenderMessage::None);
+    assert_eq!(message.encode_to_vec().to_hex(), encoding);
+    assert!(matches!(
+        TransactionSenderMessage::try_from(decode::<proto::TransactionSenderMessage>(encoding)).unwrap(),
+        TransactionSenderMessage::None
+    ));
+
+    let encoding = format!(
+        "12a101080110d00f1a20{}2220{}2a0208643206476f6c64656e3a01734220{}4a20{}52005a01002001",
+        KEY_1, KEY_2, KEY_3, KEY_2
+    );
+    let message = proto::TransactionSenderMessage::from(TransactionSenderMessage::Single(Box::new(sender_data())));
+    assert_eq!(message.encode_to_vec().to_hex(), encoding);
+    let message = TransactionSenderMessage::try_from(decode::<proto::TransactionSenderMessage>(&encoding)).unwrap();
+    assert_eq!(message.single(), Some(&sender_data()));
+}
+
+#[test]
+fn it_matches_the_recipient_signed_message_vector() {
+    let encoding = format!(
+        "080112cd010a0012220a20{}2201732a20{}32660a20{}1220{}1a20{}4a18{}1a20{}22440a20{}1220{}2a0208643001",
+        KEY_1,
+        KEY_2,
+        KEY_3,
+        scalar_hex(4),
+        scalar_hex(5),
+        "00".repeat(24),
+        KEY_2,
+        KEY_3,
+        scalar_hex(6)
+    );
+    let message = proto::RecipientSignedMessage::from(recipient_signed_message());
+    assert_eq!(message.encode_to_vec().to_hex(), encoding);
+    assert_eq!(
+        RecipientSignedMessage::try_from(decode::<proto::RecipientSignedMessage>(&encoding)).unwrap(),
+        recipient_signed_message()
+    );
+}
+
+#[test]
+fn it_matches_the_transaction_finalized_message_vector() {
+    let encoding = format!(
+        "080112b8010a220a20{}126e1a6c106432220a20{}3a440a20{}1220{}1a220a20{}1801",
+        scalar_hex(7),
+        KEY_1,
+        KEY_2,
+        scalar_hex(9),
+        scalar_hex(8)
+    );
+    let message = proto::TransactionFinalizedMessage::new(tx_id(), finalized_transaction()).unwrap();
+    assert_eq!(message.encode_to_vec().to_hex(), encoding);
+    let (tx_id, transaction) = decode::<proto::TransactionFinalizedMessage>(&encoding)
+        .into_transaction()
+        .unwrap();
+    assert_eq!(tx_id, self::tx_id());
+    assert_eq!(transaction, finalized_transaction());
+}
+
+#[test]
+fn it_decodes_messages_without_a_version() {
+    let mut message = proto::TransactionSenderMessage::from(TransactionSenderMessage::Single(Box::new(sender_data())));
+    message.version = 0;
+    let message = TransactionSenderMessage::try_from(message).unwrap();
+    assert_eq!(message.single(), Some(&sender_data()));
+
+    let mut message = proto::RecipientSignedMessage::from(recipient_signed_message());
+    message.version = 0;
+    assert_eq!(
+        RecipientSignedMessage::try_from(message).unwrap(),
+        recipient_signed_message()
+    );
+
+    let mut message = proto::TransactionFinalizedMessage::new(tx_id(), finalized_transaction()).unwrap();
+    message.version = 0;
+    assert_eq!(message.into_transaction().unwrap().1, finalized_transaction());
+}
+
+#[test]
+fn it_rejects_newer_versions() {
+    let mut message = proto::TransactionSenderMessage::from(TransactionSenderMessage::Single(Box::new(sender_data())));
+    message.version += 1;
+    assert!(TransactionSenderMessage::try_from(message).is_err());
+
+    let mut message = proto::RecipientSignedMessage::from(recipient_signed_message());
+    message.version += 1;
+    assert!(RecipientSignedMessage::try_from(message).is_err());
+
+    let mut message = proto::TransactionFinalizedMessage::new(tx_id(), finalized_transaction()).unwrap();
+    message.version += 1;
+    assert!(message.into_transaction().is_err());
+}
@@ -22,7 +22,11 @@
 
 pub use crate::proto::transaction_protocol as protocol;
 
+pub mod aggregated_sender;
+#[cfg(test)]
+mod golden_vectors;
 pub mod recipient_signed_message;
+pub mod transaction_finalized;
 pub mod transaction_metadata;
 pub mod transaction_sender;
 
@@ -28,10 +28,21 @@ use tari_utilities::ByteArray;
 use super::protocol as proto;
 use crate::transactions::transaction_protocol::recipient::RecipientSignedMessage;
 
+/// The version of the encoding of [RecipientSignedMessage]. Fields can be added without a new version, as older
+/// wallets skip them, but any other change needs one.
+pub const RECIPIENT_SIGNED_MESSAGE_VERSION: u32 = 1;
+
 impl TryFrom<proto::RecipientSignedMessage> for RecipientSignedMessage {
     type Error = String;
 
     fn try_from(message: proto::RecipientSignedMessage) -> Result<Self, Self::Error> {
+        // Messages from wallets released before the message was versioned have a version of 0 and the same fields
+        if message.version > RECIPIENT_SIGNED_MESSAGE_VERSION {
+            return Err(format!(
+                "Unsupported RecipientSignedMessage version {}",
+                message.version
+            ));
+        }
         let output = message
             .output
             .map(TryInto::try_into)
@@ -66,6 +77,7 @@ impl From<RecipientSignedMessage> for proto::RecipientSignedMessage {
             public_spend_key: message.public_spend_key.to_vec(),
             partial_signature: Some(message.partial_signature.into()),
             metadata: Some(message.tx_metadata.into()),
+            version: RECIPIENT_SIGNED_MESSAGE_VERSION,
         }
     }
 }
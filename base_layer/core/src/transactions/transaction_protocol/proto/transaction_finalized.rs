@@ -0,0 +1,40 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::TryInto;
+
+use tari_common_types::transaction::TxId;
+
+use super::protocol as proto;
+use crate::transactions::transaction_components::Transaction;
+
+/// The version of the encoding of [proto::TransactionFinalizedMessage]. Fields can be added without a new version, as
+/// older wallets skip them, but any other change needs one.
+pub const TRANSACTION_FINALIZED_MESSAGE_VERSION: u32 = 1;
+
+impl proto::TransactionFinalizedMessage {
+    /// Creates the message that the sender sends to the recipient for a finalized transaction
+    pub fn new(tx_id: TxId, transaction: Transaction) -> Result<Self, String> {
+        Ok(Self {
+            tx_id: tx_id.into(),
+            transaction: Some(transaction.try_into()?),
+            version: TRANSACTION_FINALIZED_MESSAGE_VERSION,
+        })
+    }
+
+    /// Returns the transaction id and the finalized transaction of the message
+    pub fn into_transaction(self) -> Result<(TxId, Transaction), String> {
+        // Messages from wallets released before the message was versioned have a version of 0 and the same fields
+        if self.version > TRANSACTION_FINALIZED_MESSAGE_VERSION {
+            return Err(format!(
+                "Unsupported TransactionFinalizedMessage version {}",
+                self.version
+            ));
+        }
+        let transaction = self
+            .transaction
+            .ok_or_else(|| "Finalized Transaction missing Transaction field".to_string())?
+            .try_into()?;
+        Ok((self.tx_id.into(), transaction))
+    }
+}
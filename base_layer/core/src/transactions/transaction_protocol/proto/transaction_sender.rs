@@ -34,22 +34,29 @@ use crate::{
     transactions::transaction_protocol::sender::{SingleRoundSenderData, TransactionSenderMessage},
 };
 
+/// The version of the encoding of [TransactionSenderMessage]. Fields can be added without a new version, as older
+/// wallets skip them, but any other change needs one.
+pub const TRANSACTION_SENDER_MESSAGE_VERSION: u32 = 1;
+
 impl proto::TransactionSenderMessage {
     pub fn none() -> Self {
         proto::TransactionSenderMessage {
             message: Some(ProtoTxnSenderMessage::None(true)),
+            version: TRANSACTION_SENDER_MESSAGE_VERSION,
         }
     }
 
     pub fn single(data: proto::SingleRoundSenderData) -> Self {
         proto::TransactionSenderMessage {
             message: Some(ProtoTxnSenderMessage::Single(data)),
+            version: TRANSACTION_SENDER_MESSAGE_VERSION,
         }
     }
 
     pub fn multiple() -> Self {
         proto::TransactionSenderMessage {
             message: Some(ProtoTxnSenderMessage::Multiple(true)),
+            version: TRANSACTION_SENDER_MESSAGE_VERSION,
         }
     }
 }
@@ -58,6 +65,13 @@ impl TryFrom<proto::TransactionSenderMessage> for TransactionSenderMessage {
     type Error = String;
 
     fn try_from(message: proto::TransactionSenderMessage) -> Result<Self, Self::Error> {
+        // Messages from wallets released before the message was versioned have a version of 0 and the same fields
+        if message.version > TRANSACTION_SENDER_MESSAGE_VERSION {
+            return Err(format!(
+                "Unsupported TransactionSenderMessage version {}",
+                message.version
+            ));
+        }
         let inner_message = message
             .message
             .ok_or_else(|| "TransactionSenderMessage.message not provided".to_string())?;
@@ -82,7 +96,10 @@ impl From<TransactionSenderMessage> for proto::TransactionSenderMessage {
             TransactionSenderMessage::Multiple => ProtoTransactionSenderMessage::Multiple(true),
         };
 
-        Self { message: Some(message) }
+        Self {
+            message: Some(message),
+            version: TRANSACTION_SENDER_MESSAGE_VERSION,
+        }
     }
 }
 
@@ -122,6 +139,7 @@ impl TryFrom<proto::SingleRoundSenderData> for SingleRoundSenderData {
             public_commitment_nonce,
             covenant,
             minimum_value_promise: data.minimum_value_promise.into(),
+            expires_at: Some(data.expires_at).filter(|expires_at| *expires_at > 0),
         })
     }
 }
@@ -143,6 +161,7 @@ impl From<SingleRoundSenderData> for proto::SingleRoundSenderData {
             public_commitment_nonce: sender_data.public_commitment_nonce.to_vec(),
             covenant: sender_data.covenant.to_consensus_bytes(),
             minimum_value_promise: sender_data.minimum_value_promise.into(),
+            expires_at: sender_data.expires_at.unwrap_or_default(),
         }
     }
 }
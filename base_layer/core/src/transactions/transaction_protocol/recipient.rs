@@ -249,6 +249,7 @@ mod test {
             public_commitment_nonce: p.sender_public_commitment_nonce,
             covenant: Covenant::default(),
             minimum_value_promise: MicroTari::zero(),
+            expires_at: None,
         };
         let sender_info = TransactionSenderMessage::Single(Box::new(msg.clone()));
         let pubkey = PublicKey::from_secret_key(&p.spend_key);
@@ -296,6 +297,7 @@ mod test {
             public_commitment_nonce: p.sender_public_commitment_nonce,
             covenant: Covenant::default(),
             minimum_value_promise: MicroTari::zero(),
+            expires_at: None,
         };
         let sender_info = TransactionSenderMessage::Single(Box::new(msg));
         let receiver = ReceiverTransactionProtocol::new_with_rewindable_output(
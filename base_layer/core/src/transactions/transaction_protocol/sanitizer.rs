@@ -0,0 +1,80 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Canonical policy for the transaction values that a sender chooses locally.
+//!
+//! Values such as the lock height, the exact fee and the arrangement of no-op scripts are not fixed by consensus, so
+//! each wallet implementation tends to pick them in its own recognisable way. The sender protocol refuses to build a
+//! transaction with a lock height or script that is not in the canonical form defined here, rather than changing a
+//! value that its caller chose, and rounds up the fee of a transaction that has a change output so that the fee does
+//! not reveal the exact fee per gram. Outputs need no treatment here as the body of every transaction is sorted.
+
+use tari_script::{Opcode, TariScript};
+
+use crate::transactions::tari_amount::MicroTari;
+
+/// The fee of a transaction with a change output is rounded up to a multiple of this value
+pub const FEE_ROUNDING_STEP: MicroTari = MicroTari(100);
+
+/// Rounds a fee up to the next multiple of [FEE_ROUNDING_STEP]. A fee that cannot be rounded without overflowing is
+/// returned unchanged.
+pub fn canonical_fee(fee: MicroTari) -> MicroTari {
+    let step = FEE_ROUNDING_STEP.as_u64();
+    match fee.as_u64() % step {
+        0 => fee,
+        remainder => fee.checked_add(MicroTari(step - remainder)).unwrap_or(fee),
+    }
+}
+
+/// A lock height at or below the current tip height does not restrict when the transaction can be mined, but the
+/// value chosen (e.g. the tip height as an anti fee-sniping measure) identifies the wallet that made it, so only 0 is
+/// accepted in that case. Any lock height is accepted when the tip height is not known.
+pub fn check_lock_height(lock_height: u64, tip_height: Option<u64>) -> Result<(), String> {
+    match tip_height {
+        Some(tip_height) if lock_height != 0 && lock_height <= tip_height => Err(format!(
+            "Lock height {} has already been reached at tip height {}, use a lock height of 0 instead",
+            lock_height, tip_height
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Scripts made up only of `Nop` opcodes are equivalent, so the single `Nop` script used by the default wallet is the
+/// only canonical one among them. Any other script is canonical.
+pub fn is_canonical_script(script: &TariScript) -> bool {
+    let ops = script.as_slice();
+    ops.len() <= 1 || ops.iter().any(|op| *op != Opcode::Nop)
+}
+
+#[cfg(test)]
+mod test {
+    use tari_script::script;
+
+    use super::*;
+
+    #[test]
+    fn it_rounds_fees_up() {
+        assert_eq!(canonical_fee(MicroTari(0)), MicroTari(0));
+        assert_eq!(canonical_fee(MicroTari(1)), MicroTari(100));
+        assert_eq!(canonical_fee(MicroTari(100)), MicroTari(100));
+        assert_eq!(canonical_fee(MicroTari(1_201)), MicroTari(1_300));
+        assert_eq!(canonical_fee(MicroTari(u64::MAX)), MicroTari(u64::MAX));
+    }
+
+    #[test]
+    fn it_rejects_lock_heights_that_have_passed() {
+        assert!(check_lock_height(0, Some(100)).is_ok());
+        assert!(check_lock_height(99, Some(100)).is_err());
+        assert!(check_lock_height(100, Some(100)).is_err());
+        assert!(check_lock_height(101, Some(100)).is_ok());
+        assert!(check_lock_height(99, None).is_ok());
+    }
+
+    #[test]
+    fn it_rejects_nop_only_scripts() {
+        assert!(!is_canonical_script(&script!(Nop Nop Nop)));
+        assert!(is_canonical_script(&script!(Nop)));
+        assert!(is_canonical_script(&script!(Nop Drop)));
+        assert!(is_canonical_script(&TariScript::default()));
+    }
+}
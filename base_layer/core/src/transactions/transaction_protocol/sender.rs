@@ -150,6 +150,10 @@ pub struct SingleRoundSenderData {
     pub covenant: Covenant,
     /// The minimum value of the commitment that is proven by the range proof
     pub minimum_value_promise: MicroTari,
+    /// Unix timestamp in seconds after which both parties cancel the transaction if it has not been finalized. This is
+    /// set by the wallet of the sender.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -432,6 +436,7 @@ impl SenderTransactionProtocol {
                     public_commitment_nonce: PublicKey::from_secret_key(private_commitment_nonce),
                     covenant: recipient_covenant,
                     minimum_value_promise: recipient_minimum_value_promise,
+                    expires_at: None,
                 })
             },
             _ => Err(TPE::InvalidStateError),
@@ -813,6 +818,7 @@ mod test {
             test_helpers::{create_test_input, create_unblinded_output, TestParams},
             transaction_components::{EncryptedValue, OutputFeatures, TransactionOutput, TransactionOutputVersion},
             transaction_protocol::{
+                sanitizer,
                 sender::{SenderTransactionProtocol, TransactionSenderMessage},
                 single_receiver::SingleReceiverTransactionProtocol,
                 TransactionProtocolError,
@@ -1065,9 +1071,14 @@ mod test {
         let (utxo, input) = create_test_input(MicroTari(25000), 0, &factories.commitment);
         let mut builder = SenderTransactionProtocol::builder(1, create_consensus_constants(0));
         let script = script!(Nop);
-        let expected_fee = builder
-            .fee()
-            .calculate(MicroTari(20), 1, 1, 2, a.get_size_for_default_metadata(2));
+        // The fee of a transaction with change is rounded up
+        let expected_fee = sanitizer::canonical_fee(builder.fee().calculate(
+            MicroTari(20),
+            1,
+            1,
+            2,
+            a.get_size_for_default_metadata(2),
+        ));
         builder
             .with_lock_height(0)
             .with_fee_per_gram(MicroTari(20))
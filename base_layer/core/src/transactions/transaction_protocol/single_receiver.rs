@@ -226,6 +226,7 @@ mod test {
             public_commitment_nonce,
             covenant: Default::default(),
             minimum_value_promise: MicroTari::zero(),
+            expires_at: None,
         };
         let prot = SingleReceiverTransactionProtocol::create(&info, r, k.clone(), &factories, None).unwrap();
         assert_eq!(prot.tx_id.as_u64(), 500, "tx_id is incorrect");
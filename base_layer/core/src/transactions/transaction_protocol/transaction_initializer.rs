@@ -57,6 +57,7 @@ use crate::{
         },
         transaction_protocol::{
             recipient::RecipientInfo,
+            sanitizer,
             sender::{calculate_tx_id, RawTransactionInfo, SenderState, SenderTransactionProtocol},
             KernelFeatures,
             RewindData,
@@ -271,6 +272,12 @@ impl SenderTransactionInitializer {
         self
     }
 
+    /// Provide the sender offset private key for the change output. If not provided a random key is used.
+    pub fn with_change_sender_offset_private_key(&mut self, sender_offset_private_key: PrivateKey) -> &mut Self {
+        self.change_sender_offset_private_key = Some(sender_offset_private_key);
+        self
+    }
+
     /// Provide the rewind data required for outputs (change and manually added sender outputs) to be rewindable.
     pub fn with_rewindable_outputs(&mut self, rewind_data: RewindData) -> &mut Self {
         self.rewind_data = Some(rewind_data);
@@ -383,8 +390,13 @@ impl SenderTransactionInitializer {
             )),
             Some(MicroTari(0)) => Ok((fee_without_change, MicroTari(0), None)),
             Some(v) => {
-                let change_amount = v.checked_sub(change_fee);
-                let change_sender_offset_private_key = PrivateKey::random(&mut OsRng);
+                // The change output also pays for rounding the fee up
+                let fee_with_change = sanitizer::canonical_fee(fee_without_change + change_fee);
+                let change_amount = v.checked_sub(fee_with_change - fee_without_change);
+                let change_sender_offset_private_key = self
+                    .change_sender_offset_private_key
+                    .clone()
+                    .unwrap_or_else(|| PrivateKey::random(&mut OsRng));
                 self.change_sender_offset_private_key = Some(change_sender_offset_private_key.clone());
                 match change_amount {
                     // You can't win. Just add the change to the fee (which is less than the cost of adding another
@@ -446,7 +458,7 @@ impl SenderTransactionInitializer {
                             encrypted_value,
                             minimum_value_promise,
                         );
-                        Ok((fee_without_change + change_fee, v, Some(change_unblinded_output)))
+                        Ok((fee_with_change, v, Some(change_unblinded_output)))
                     },
                 }
             },
@@ -527,6 +539,24 @@ impl SenderTransactionInitializer {
         if self.inputs.len() > MAX_TRANSACTION_INPUTS {
             return self.build_err("Too many inputs in transaction");
         }
+        // Reject the locally chosen values that could identify the wallet implementation. A height of u64::MAX means
+        // the tip height is not known.
+        let tip_height = if height == u64::MAX { None } else { Some(height) };
+        if let Err(e) = sanitizer::check_lock_height(self.lock_height.unwrap_or_default(), tip_height) {
+            return self.build_err(&e);
+        }
+        if !self.change_script.as_ref().map_or(true, sanitizer::is_canonical_script) {
+            return self.build_err("The change script is not canonical");
+        }
+        if !self
+            .recipient_scripts
+            .clone()
+            .into_vec()
+            .iter()
+            .all(sanitizer::is_canonical_script)
+        {
+            return self.build_err("A recipient script is not canonical");
+        }
         // Calculate the fee based on whether we need to add a residual change output or not
         let (total_fee, change, change_output) = match self.add_change_if_required(factories) {
             Ok((fee, change, output)) => (fee, change, output),
@@ -720,6 +750,7 @@ mod test {
             test_helpers::{create_test_input, create_unblinded_output, TestParams, UtxoTestParams},
             transaction_components::{OutputFeatures, MAX_TRANSACTION_INPUTS},
             transaction_protocol::{
+                sanitizer,
                 sender::SenderState,
                 transaction_initializer::SenderTransactionInitializer,
                 TransactionProtocolError,
@@ -772,9 +803,13 @@ mod test {
                 MicroTari::zero(),
             )
             .with_change_script(script, ExecutionStack::default(), PrivateKey::default());
-        let expected_fee = builder
-            .fee()
-            .calculate(MicroTari(20), 1, 1, 2, p.get_size_for_default_metadata(2));
+        let expected_fee = sanitizer::canonical_fee(builder.fee().calculate(
+            MicroTari(20),
+            1,
+            1,
+            2,
+            p.get_size_for_default_metadata(2),
+        ));
         // We needed a change input, so this should fail
         let err = builder.build(&factories, None, u64::MAX).unwrap_err();
         assert_eq!(err.message, "Change spending key was not provided");
@@ -1056,13 +1091,13 @@ mod test {
 
         let script = script!(Nop);
         let constants = create_consensus_constants(0);
-        let expected_fee = Fee::from(*constants.transaction_weight()).calculate(
+        let expected_fee = sanitizer::canonical_fee(Fee::from(*constants.transaction_weight()).calculate(
             fee_per_gram,
             1,
             2,
             3,
             p.get_size_for_default_metadata(3),
-        );
+        ));
         let output = create_unblinded_output(
             script.clone(),
             OutputFeatures::default(),
@@ -1107,6 +1142,76 @@ mod test {
         }
     }
 
+    #[test]
+    fn sanitizer_is_applied() {
+        let factories = CryptoFactories::default();
+        let p = TestParams::new();
+        let (utxo, input) = create_test_input(MicroTari(5000), 0, &factories.commitment);
+        let fee_per_gram = MicroTari(7);
+        let script = script!(Nop);
+        let constants = create_consensus_constants(0);
+        let mut builder = SenderTransactionInitializer::new(1, &constants);
+        builder
+            .with_lock_height(100)
+            .with_offset(p.offset.clone())
+            .with_private_nonce(p.nonce.clone())
+            .with_input(utxo, input)
+            .with_amount(0, MicroTari(2500))
+            .with_change_secret(p.change_spend_key.clone())
+            .with_fee_per_gram(fee_per_gram)
+            .with_recipient_data(
+                0,
+                script!(Nop Nop),
+                PrivateKey::random(&mut OsRng),
+                Default::default(),
+                PrivateKey::random(&mut OsRng),
+                Covenant::default(),
+                MicroTari::zero(),
+            )
+            .with_change_script(script!(Nop Nop), ExecutionStack::default(), PrivateKey::default());
+        // The lock height has already been reached at a tip height of 150
+        let err = builder.build(&factories, None, 150).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Lock height 100 has already been reached at tip height 150, use a lock height of 0 instead"
+        );
+        let mut builder = err.builder;
+        builder.with_lock_height(0);
+        let err = builder.build(&factories, None, 150).unwrap_err();
+        assert_eq!(err.message, "The change script is not canonical");
+        let mut builder = err.builder;
+        builder.with_change_script(script.clone(), ExecutionStack::default(), PrivateKey::default());
+        let err = builder.build(&factories, None, 150).unwrap_err();
+        assert_eq!(err.message, "A recipient script is not canonical");
+        let mut builder = err.builder;
+        builder.with_recipient_data(
+            0,
+            script,
+            PrivateKey::random(&mut OsRng),
+            Default::default(),
+            PrivateKey::random(&mut OsRng),
+            Covenant::default(),
+            MicroTari::zero(),
+        );
+        let expected_fee = Fee::from(*constants.transaction_weight()).calculate(
+            fee_per_gram,
+            1,
+            1,
+            2,
+            p.get_size_for_default_metadata(2),
+        );
+        let result = builder.build(&factories, None, 150).unwrap();
+        if let SenderState::SingleRoundMessageReady(info) = result.into_state() {
+            assert_eq!(info.metadata.lock_height, 0, "Lock height");
+            // The fee of a transaction with change is rounded up
+            assert_eq!(info.metadata.fee, sanitizer::canonical_fee(expected_fee), "Fee");
+            assert_eq!(info.metadata.fee.as_u64() % sanitizer::FEE_ROUNDING_STEP.as_u64(), 0);
+            assert_eq!(info.outputs.len(), 1, "There should be 1 change output");
+        } else {
+            panic!("There was a recipient, we should be ready to send a message");
+        }
+    }
+
     #[test]
     fn fail_range_proof() {
         // Create some inputs
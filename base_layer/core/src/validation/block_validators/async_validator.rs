@@ -39,7 +39,7 @@ use crate::{
     transactions::{
         aggregated_body::AggregateBody,
         transaction_components::{
-            transaction_output::batch_verify_range_proofs,
+            transaction_output::{batch_verify_range_proofs_with_batch_size, DEFAULT_RANGE_PROOF_BATCH_SIZE},
             KernelSum,
             TransactionError,
             TransactionInput,
@@ -66,6 +66,7 @@ pub struct BlockValidator<B> {
     db: AsyncBlockchainDb<B>,
     concurrency: usize,
     bypass_range_proof_verification: bool,
+    range_proof_batch_size: usize,
 }
 
 impl<B: BlockchainBackend + 'static> BlockValidator<B> {
@@ -82,9 +83,17 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
             db,
             concurrency,
             bypass_range_proof_verification,
+            range_proof_batch_size: DEFAULT_RANGE_PROOF_BATCH_SIZE,
         }
     }
 
+    /// The maximum number of range proofs that are verified in a single batch, [DEFAULT_RANGE_PROOF_BATCH_SIZE] by
+    /// default. It is rounded down to a power of two.
+    pub fn with_range_proof_batch_size(mut self, range_proof_batch_size: usize) -> Self {
+        self.range_proof_batch_size = range_proof_batch_size;
+        self
+    }
+
     async fn check_mmr_roots(&self, block: Block) -> Result<Block, ValidationError> {
         let (block, mmr_roots) = self.db.calculate_mmr_roots(block).await?;
         helpers::check_mmr_roots(&block.header, &mmr_roots)?;
@@ -141,6 +150,8 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
             AggregateBody::new_sorted_unchecked(inputs_result.inputs, outputs_result.outputs, kernels_result.kernels),
         );
 
+        // Unique asset transfers are checked against the spent assets once the compact inputs have been filled in
+        block.body.verify_unique_assets()?;
         helpers::validate_covenants(&block)?;
 
         Ok(block)
@@ -357,6 +368,7 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
         let concurrency = cmp::min(self.concurrency, num_outputs);
         let output_chunks = into_enumerated_batches(outputs, concurrency);
         let bypass_range_proof_verification = self.bypass_range_proof_verification;
+        let range_proof_batch_size = self.range_proof_batch_size;
         if bypass_range_proof_verification {
             warn!(target: LOG_TARGET, "Range proof verification will be bypassed!")
         }
@@ -404,13 +416,20 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
 
                         helpers::check_permitted_output_types(&constants, output)?;
                         helpers::check_tari_script_byte_size(&output.script, max_script_size)?;
+                        helpers::check_tari_script_opcodes(&output.script, &constants)?;
+                        output.features.validate()?;
                         output.verify_metadata_signature()?;
+                        output.verify_token_registration()?;
                         helpers::check_not_duplicate_txo(&*db, output)?;
                         commitment_sum = &commitment_sum + &output.commitment;
                     }
                     if !bypass_range_proof_verification {
                         let this_outputs = outputs.iter().map(|o| &o.1).collect::<Vec<_>>();
-                        batch_verify_range_proofs(&range_proof_prover, &this_outputs)?;
+                        batch_verify_range_proofs_with_batch_size(
+                            &range_proof_prover,
+                            &this_outputs,
+                            range_proof_batch_size,
+                        )?;
                     }
 
                     Ok((outputs, aggregate_sender_offset, commitment_sum, coinbase_index))
@@ -22,7 +22,8 @@
 use std::sync::Arc;
 
 use tari_common::configuration::Network;
-use tari_script::script;
+use tari_common_types::types::PublicKey;
+use tari_script::{script, OpcodeVersion};
 use tari_test_utils::unpack_enum;
 
 use crate::{
@@ -211,6 +212,33 @@ async fn it_limits_the_script_byte_size() {
     assert!(matches!(err, ValidationError::TariScriptExceedsMaxSize { .. }));
 }
 
+#[tokio::test]
+async fn it_rejects_opcodes_that_are_not_active() {
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(0)
+                .with_max_opcode_version(OpcodeVersion::V0)
+                .build(),
+        )
+        .build();
+    let (mut blockchain, validator) = setup_with_rules(rules);
+
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).unwrap();
+
+    let mut schema1 = txn_schema!(from: vec![coinbase_a], to: vec![50 * T, 12 * T]);
+    let keys = vec![PublicKey::default(), PublicKey::default()];
+    schema1.script = script!(CheckMultiSigVerifyAggregatePubKey(1, 2, keys, Box::new([0u8; 32])));
+    let (txs, _) = schema_to_transaction(&[schema1]);
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (block, _) = blockchain.create_next_tip(block_spec!("B", transactions: txs));
+
+    let err = validator.validate_block_body(block.block().clone()).await.unwrap_err();
+    assert!(matches!(err, ValidationError::TariScriptOpcodeNotPermitted {
+        opcode_version: OpcodeVersion::V1
+    }));
+}
+
 #[tokio::test]
 async fn it_rejects_invalid_input_metadata() {
     let rules = ConsensusManager::builder(Network::LocalNet)
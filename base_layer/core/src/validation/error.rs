@@ -22,6 +22,7 @@
 
 use tari_common_types::types::{FixedHash, FixedHashSizeError, HashOutput};
 use tari_crypto::errors::RangeProofError;
+use tari_script::OpcodeVersion;
 use thiserror::Error;
 use tokio::task;
 
@@ -111,6 +112,13 @@ pub enum ValidationError {
         max_script_size: usize,
         actual_script_size: usize,
     },
+    #[error("Script contains opcodes of version {opcode_version:?}, which is not permitted by consensus")]
+    TariScriptOpcodeNotPermitted { opcode_version: OpcodeVersion },
+    #[error("Script exceeded maximum execution cost, expected at most {max_execution_cost} but was {execution_cost}")]
+    TariScriptExceedsMaxExecutionCost {
+        max_execution_cost: u64,
+        execution_cost: u64,
+    },
     #[error("Consensus Error: {0}")]
     ConsensusError(String),
     #[error("Covenant failed to validate: {0}")]
@@ -420,8 +420,12 @@ pub fn check_input_is_utxo<B: BlockchainBackend>(db: &B, input: &TransactionInpu
 
 /// This function checks:
 /// 1. that the output type is permitted
-/// 2. the byte size of TariScript does not exceed the maximum
-/// 3. that the outputs do not already exist in the UTxO set.
+/// 2. that the output features are within the consensus limits
+/// 3. that token registrations are signed by their issuer
+/// 4. the byte size of TariScript does not exceed the maximum
+/// 5. the opcodes of TariScript are permitted and their execution cost does not exceed the maximum
+/// 6. that the outputs do not already exist in the UTxO set
+/// 7. that unique assets are minted by their parent or transferred from an input.
 pub fn check_outputs<B: BlockchainBackend>(
     db: &B,
     constants: &ConsensusConstants,
@@ -430,9 +434,13 @@ pub fn check_outputs<B: BlockchainBackend>(
     let max_script_size = constants.get_max_script_byte_size();
     for output in body.outputs() {
         check_permitted_output_types(constants, output)?;
+        output.features.validate()?;
+        output.verify_token_registration()?;
         check_tari_script_byte_size(&output.script, max_script_size)?;
+        check_tari_script_opcodes(&output.script, constants)?;
         check_not_duplicate_txo(db, output)?;
     }
+    body.verify_unique_assets()?;
     Ok(())
 }
 
@@ -448,6 +456,24 @@ pub fn check_tari_script_byte_size(script: &TariScript, max_script_size: usize)
     Ok(())
 }
 
+/// Checks that TariScript only uses opcodes of the versions permitted by consensus, and that its worst case execution
+/// cost does not exceed the maximum, otherwise returns an error.
+pub fn check_tari_script_opcodes(script: &TariScript, constants: &ConsensusConstants) -> Result<(), ValidationError> {
+    let opcode_version = script.opcode_version();
+    if opcode_version > constants.max_opcode_version() {
+        return Err(ValidationError::TariScriptOpcodeNotPermitted { opcode_version });
+    }
+    let max_execution_cost = constants.max_script_execution_cost();
+    let execution_cost = script.execution_cost();
+    if execution_cost > max_execution_cost {
+        return Err(ValidationError::TariScriptExceedsMaxExecutionCost {
+            max_execution_cost,
+            execution_cost,
+        });
+    }
+    Ok(())
+}
+
 /// This function checks that the outputs do not already exist in the TxO set.
 pub fn check_not_duplicate_txo<B: BlockchainBackend>(
     db: &B,
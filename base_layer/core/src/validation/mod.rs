@@ -31,6 +31,12 @@ mod error;
 pub use error::ValidationError;
 
 pub(crate) mod helpers;
+pub use helpers::{
+    check_blockchain_version,
+    check_header_timestamp_greater_than_median,
+    check_target_difficulty,
+    check_timestamp_ftl,
+};
 
 mod traits;
 pub use traits::{
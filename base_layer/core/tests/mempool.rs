@@ -701,6 +701,74 @@ async fn test_zero_conf() {
     assert!(retrieved_txs.contains(&Arc::new(tx34)));
 }
 
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_insert_package() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![21 * T, 11 * T, 11 * T]
+    )];
+    // "Mine" Block 1
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).await.unwrap();
+
+    let (parent, parent_out) = spend_utxos(txn_schema!(from: vec![outputs[1][0].clone()], to: vec![15 * T, 5 * T]));
+    let (child, _) = spend_utxos(txn_schema!(from: vec![parent_out[0].clone()], to: vec![10 * T]));
+    let (double_spend, _) = spend_utxos(txn_schema!(from: vec![parent_out[0].clone()], to: vec![9 * T]));
+    // The orphan spends an output of this transaction, which is never submitted
+    let (_, unsubmitted_out) = spend_utxos(txn_schema!(from: vec![outputs[1][1].clone()], to: vec![5 * T]));
+    let (orphan, _) = spend_utxos(txn_schema!(from: vec![unsubmitted_out[0].clone()], to: vec![2 * T]));
+    let (unrelated, _) = spend_utxos(txn_schema!(from: vec![outputs[1][2].clone()], to: vec![5 * T]));
+    let (parent, child, double_spend) = (Arc::new(parent), Arc::new(child), Arc::new(double_spend));
+    let (orphan, unrelated) = (Arc::new(orphan), Arc::new(unrelated));
+
+    // The child is an orphan on its own, but is accepted together with its parent, in any order
+    assert_eq!(
+        mempool.insert(child.clone()).await.unwrap(),
+        TxStorageResponse::NotStoredOrphan
+    );
+    assert_eq!(
+        mempool
+            .insert_package(vec![child.clone(), parent.clone()])
+            .await
+            .unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(mempool.stats().await.unwrap().unconfirmed_txs, 2);
+
+    // Transactions that spend the same output cannot be in the same package
+    assert_eq!(
+        mempool
+            .insert_package(vec![parent.clone(), child, double_spend])
+            .await
+            .unwrap(),
+        TxStorageResponse::NotStoredConsensus
+    );
+
+    // None of the transactions of a rejected package are stored
+    assert_eq!(
+        mempool.insert_package(vec![unrelated.clone(), orphan]).await.unwrap(),
+        TxStorageResponse::NotStoredOrphan
+    );
+    assert_eq!(
+        mempool.has_transaction(unrelated).await.unwrap(),
+        TxStorageResponse::NotStored
+    );
+    assert_eq!(mempool.stats().await.unwrap().unconfirmed_txs, 2);
+    assert_eq!(
+        mempool.insert_package(vec![]).await.unwrap(),
+        TxStorageResponse::NotStored
+    );
+}
+
 #[tokio::test]
 #[allow(clippy::identity_op)]
 async fn test_reorg() {
@@ -1346,3 +1414,35 @@ async fn block_event_and_reorg_event_handling() {
         TxStorageResponse::ReorgPool
     );
 }
+
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_min_fee_per_gram() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(
+        MempoolConfig {
+            min_fee_per_gram: 25 * uT,
+            ..Default::default()
+        },
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+    assert_eq!(mempool.min_fee_per_gram().await.unwrap(), 25 * uT);
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T], fee: 25*uT, lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+
+    let tx = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1 * T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx = Arc::new(spend_utxos(tx).0);
+    let response = mempool.insert(tx).await.unwrap();
+    assert!(matches!(response, TxStorageResponse::NotStoredFeeTooLow));
+
+    let tx = txn_schema!(from: vec![outputs[1][1].clone()], to: vec![1 * T], fee: 25*uT, lock: 0, features: OutputFeatures::default());
+    let tx = Arc::new(spend_utxos(tx).0);
+    let response = mempool.insert(tx).await.unwrap();
+    assert!(matches!(response, TxStorageResponse::UnconfirmedPool));
+}
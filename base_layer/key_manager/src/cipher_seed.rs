@@ -43,9 +43,9 @@ use tari_crypto::hash::blake2::Blake256;
 use tari_utilities::ByteArray;
 
 use crate::{
-    error::KeyManagerError,
+    error::{KeyManagerError, MnemonicError},
     mac_domain_hasher,
-    mnemonic::{from_bytes, to_bytes, to_bytes_with_language, Mnemonic, MnemonicLanguage},
+    mnemonic::{from_bytes, is_valid_word, to_bytes_with_language, Mnemonic, MnemonicLanguage},
     LABEL_ARGON_ENCODING,
     LABEL_CHACHA20_ENCODING,
     LABEL_MAC_GENERATION,
@@ -185,33 +185,13 @@ impl CipherSeed {
     }
 
     pub fn from_enciphered_bytes(enciphered_bytes: &[u8], passphrase: Option<String>) -> Result<Self, KeyManagerError> {
-        // 1 byte Version || 2 byte Birthday || 16 byte Entropy || 5 byte MAC || 5 byte salt || 4 byte CRC32
-        if enciphered_bytes.len() != 7 + CIPHER_SEED_ENTROPY_BYTES + CIPHER_SEED_SALT_BYTES + CIPHER_SEED_MAC_BYTES {
-            return Err(KeyManagerError::InvalidData);
-        }
-
-        if enciphered_bytes[0] != CIPHER_SEED_VERSION {
-            return Err(KeyManagerError::VersionMismatch);
-        }
+        Self::validate_enciphered_bytes(enciphered_bytes)?;
 
         let passphrase = passphrase.unwrap_or_else(|| DEFAULT_CIPHER_SEED_PASSPHRASE.to_string());
 
         let mut body = enciphered_bytes.to_owned();
-        // extract 32 bit checksum
-        let checksum_vec = body.split_off(body.len() - 4);
-
-        let mut crc_hasher = CrcHasher::new();
-        crc_hasher.update(body.as_slice());
-
-        let calculated_checksum = crc_hasher.finalize();
-
-        let mut checksum_bytes: [u8; 4] = [0u8; 4];
-        checksum_bytes.copy_from_slice(&checksum_vec[..4]);
-        let checksum = u32::from_le_bytes(checksum_bytes);
-
-        if calculated_checksum != checksum {
-            return Err(KeyManagerError::CrcError);
-        }
+        // strip the 32 bit checksum, which was validated above
+        body.truncate(body.len() - 4);
 
         let salt = body.split_off(body.len() - CIPHER_SEED_SALT_BYTES);
         let mut enciphered_seed = body.split_off(1);
@@ -256,6 +236,58 @@ impl CipherSeed {
         })
     }
 
+    /// Checks the length, version and CRC32 checksum of an enciphered seed. The MAC can only be checked once the seed
+    /// is deciphered with its passphrase.
+    fn validate_enciphered_bytes(enciphered_bytes: &[u8]) -> Result<(), KeyManagerError> {
+        // 1 byte Version || 2 byte Birthday || 16 byte Entropy || 5 byte MAC || 5 byte salt || 4 byte CRC32
+        if enciphered_bytes.len() != 7 + CIPHER_SEED_ENTROPY_BYTES + CIPHER_SEED_SALT_BYTES + CIPHER_SEED_MAC_BYTES {
+            return Err(KeyManagerError::InvalidData);
+        }
+
+        if enciphered_bytes[0] != CIPHER_SEED_VERSION {
+            return Err(KeyManagerError::VersionMismatch);
+        }
+
+        let (body, checksum_bytes) = enciphered_bytes.split_at(enciphered_bytes.len() - 4);
+        let mut crc_hasher = CrcHasher::new();
+        crc_hasher.update(body);
+        let calculated_checksum = crc_hasher.finalize();
+
+        let mut checksum: [u8; 4] = [0u8; 4];
+        checksum.copy_from_slice(checksum_bytes);
+        if calculated_checksum != u32::from_le_bytes(checksum) {
+            return Err(KeyManagerError::CrcError);
+        }
+        Ok(())
+    }
+
+    /// Checks the words, version and checksum of a mnemonic sequence without deciphering it, so a recovery UI can
+    /// report typos before asking for the passphrase. Returns the language of the sequence. When the words exist in
+    /// more than one language, the checksum decides between them.
+    pub fn validate_mnemonic(mnemonic_seq: &[String]) -> Result<MnemonicLanguage, KeyManagerError> {
+        let candidates = MnemonicLanguage::detect_candidates(mnemonic_seq);
+        if candidates.is_empty() {
+            // Point out the word that is not in any word list, if there is one
+            if let Some(word) = mnemonic_seq
+                .iter()
+                .find(|word| MnemonicLanguage::iterator().all(|language| !is_valid_word(word, *language)))
+            {
+                return Err(MnemonicError::WordNotFound(word.clone()).into());
+            }
+        }
+        let mut result = Err(KeyManagerError::MnemonicError(MnemonicError::UnknownLanguage));
+        for language in candidates {
+            result = to_bytes_with_language(mnemonic_seq, &language)
+                .map_err(KeyManagerError::from)
+                .and_then(|bytes| Self::validate_enciphered_bytes(&bytes))
+                .map(|_| language);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
     fn apply_stream_cipher(data: &mut Vec<u8>, passphrase: &str, salt: &[u8]) -> Result<(), KeyManagerError> {
         // encryption nonce for ChaCha20 encryption, generated as a domain separated hash of the given salt. Following
         // https://libsodium.gitbook.io/doc/advanced/stream_ciphers/chacha20, as of the IEF variant, the produced encryption
@@ -390,8 +422,8 @@ impl Mnemonic<CipherSeed> for CipherSeed {
     /// Generates a CipherSeed that represent the provided mnemonic sequence of words, the language of the mnemonic
     /// sequence is autodetected
     fn from_mnemonic(mnemonic_seq: &[String], passphrase: Option<String>) -> Result<CipherSeed, KeyManagerError> {
-        let bytes = to_bytes(mnemonic_seq)?;
-        CipherSeed::from_enciphered_bytes(&bytes, passphrase)
+        let language = CipherSeed::validate_mnemonic(mnemonic_seq)?;
+        CipherSeed::from_mnemonic_with_language(mnemonic_seq, language, passphrase)
     }
 
     /// Generates a SecretKey that represent the provided mnemonic sequence of words using the specified language
@@ -420,7 +452,7 @@ mod test {
 
     use crate::{
         cipher_seed::CipherSeed,
-        error::KeyManagerError,
+        error::{KeyManagerError, MnemonicError},
         mnemonic::{Mnemonic, MnemonicLanguage},
     };
 
@@ -580,6 +612,36 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_validate_mnemonic() {
+        let seed = CipherSeed::new();
+        let mnemonic_seq = seed
+            .to_mnemonic(MnemonicLanguage::French, Some("Passphrase".to_string()))
+            .expect("Couldn't convert CipherSeed to Mnemonic");
+        assert_eq!(
+            CipherSeed::validate_mnemonic(&mnemonic_seq),
+            Ok(MnemonicLanguage::French)
+        );
+
+        // A valid word in the wrong place fails the checksum
+        let mut swapped = mnemonic_seq.clone();
+        swapped.swap(3, 4);
+        if swapped != mnemonic_seq {
+            assert!(CipherSeed::validate_mnemonic(&swapped).is_err());
+        }
+
+        // An unknown word
+        let mut typo = mnemonic_seq;
+        typo[5] = "xyzzy".to_string();
+        assert_eq!(
+            CipherSeed::validate_mnemonic(&typo),
+            Err(KeyManagerError::MnemonicError(MnemonicError::WordNotFound(
+                "xyzzy".to_string()
+            )))
+        );
+        assert!(CipherSeed::validate_mnemonic(&typo[..12]).is_err());
+    }
+
     #[test]
     fn cipher_seed_to_and_from_mnemonic_with_passphrase() {
         let seed = CipherSeed::new();
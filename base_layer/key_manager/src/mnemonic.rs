@@ -72,15 +72,20 @@ impl MnemonicLanguage {
 
     /// Returns the mnemonic word list count for the specified language
     pub fn word_count(language: &MnemonicLanguage) -> usize {
-        match language {
-            MnemonicLanguage::ChineseSimplified => MNEMONIC_CHINESE_SIMPLIFIED_WORDS.len(),
-            MnemonicLanguage::English => MNEMONIC_ENGLISH_WORDS.len(),
-            MnemonicLanguage::French => MNEMONIC_FRENCH_WORDS.len(),
-            MnemonicLanguage::Italian => MNEMONIC_ITALIAN_WORDS.len(),
-            MnemonicLanguage::Japanese => MNEMONIC_JAPANESE_WORDS.len(),
-            MnemonicLanguage::Korean => MNEMONIC_KOREAN_WORDS.len(),
-            MnemonicLanguage::Spanish => MNEMONIC_SPANISH_WORDS.len(),
-        }
+        word_list(*language).len()
+    }
+
+    /// Returns every language whose word list contains all of the words. A recovery UI can use this to narrow down the
+    /// language while the words are still being entered, before [MnemonicLanguage::detect_language] can decide.
+    pub fn detect_candidates(words: &[String]) -> Vec<MnemonicLanguage> {
+        MnemonicLanguage::iterator()
+            .copied()
+            .filter(|language| {
+                words
+                    .iter()
+                    .all(|word| find_mnemonic_index_from_word(word, *language).is_ok())
+            })
+            .collect()
     }
 
     /// Detects the language of a list of words
@@ -129,45 +134,66 @@ impl MnemonicLanguage {
     }
 }
 
-/// Finds and returns the index of a specific word in a mnemonic word list defined by the specified language
-fn find_mnemonic_index_from_word(word: &str, language: MnemonicLanguage) -> Result<usize, MnemonicError> {
+/// Returns the word list of the specified language
+fn word_list(language: MnemonicLanguage) -> &'static [&'static str] {
+    match language {
+        MnemonicLanguage::ChineseSimplified => &MNEMONIC_CHINESE_SIMPLIFIED_WORDS,
+        MnemonicLanguage::English => &MNEMONIC_ENGLISH_WORDS,
+        MnemonicLanguage::French => &MNEMONIC_FRENCH_WORDS,
+        MnemonicLanguage::Italian => &MNEMONIC_ITALIAN_WORDS,
+        MnemonicLanguage::Japanese => &MNEMONIC_JAPANESE_WORDS,
+        MnemonicLanguage::Korean => &MNEMONIC_KOREAN_WORDS,
+        MnemonicLanguage::Spanish => &MNEMONIC_SPANISH_WORDS,
+    }
+}
+
+/// Converts a word into the form used by the word list of the specified language, which is lowercase and, for latin
+/// scripts, without diacritics
+fn normalize_word(word: &str, language: MnemonicLanguage) -> String {
     let lowercase_word = word.to_lowercase();
-    let search_result = match language {
-        // Search through languages are ordered according to the predominance (number of speakers in the world) of that
-        // language
-        MnemonicLanguage::ChineseSimplified => {
-            MNEMONIC_CHINESE_SIMPLIFIED_WORDS.binary_search(&lowercase_word.as_str())
-        },
-        MnemonicLanguage::English => MNEMONIC_ENGLISH_WORDS.binary_search(&remove_diacritics(&lowercase_word).as_str()),
-        MnemonicLanguage::French => MNEMONIC_FRENCH_WORDS.binary_search(&remove_diacritics(&lowercase_word).as_str()),
-        MnemonicLanguage::Italian => MNEMONIC_ITALIAN_WORDS.binary_search(&remove_diacritics(&lowercase_word).as_str()),
-        MnemonicLanguage::Japanese => MNEMONIC_JAPANESE_WORDS.binary_search(&lowercase_word.as_str()),
-        MnemonicLanguage::Korean => MNEMONIC_KOREAN_WORDS.binary_search(&lowercase_word.as_str()),
-        MnemonicLanguage::Spanish => MNEMONIC_SPANISH_WORDS.binary_search(&remove_diacritics(&lowercase_word).as_str()),
-    };
-    match search_result {
-        Ok(v) => Ok(v),
-        Err(_err) => Err(MnemonicError::WordNotFound(word.to_string())),
+    match language {
+        MnemonicLanguage::English |
+        MnemonicLanguage::French |
+        MnemonicLanguage::Italian |
+        MnemonicLanguage::Spanish => remove_diacritics(&lowercase_word),
+        MnemonicLanguage::ChineseSimplified | MnemonicLanguage::Japanese | MnemonicLanguage::Korean => lowercase_word,
     }
 }
 
+/// Finds and returns the index of a specific word in a mnemonic word list defined by the specified language
+fn find_mnemonic_index_from_word(word: &str, language: MnemonicLanguage) -> Result<usize, MnemonicError> {
+    word_list(language)
+        .binary_search(&normalize_word(word, language).as_str())
+        .map_err(|_| MnemonicError::WordNotFound(word.to_string()))
+}
+
 /// Finds and returns the word for a specific index in a mnemonic word list defined by the specified language
 fn find_mnemonic_word_from_index(index: usize, language: MnemonicLanguage) -> Result<String, MnemonicError> {
-    if index < MNEMONIC_ENGLISH_WORDS.len() {
-        Ok(match language {
-            // Select word according to specified language
-            MnemonicLanguage::ChineseSimplified => MNEMONIC_CHINESE_SIMPLIFIED_WORDS[index],
-            MnemonicLanguage::English => MNEMONIC_ENGLISH_WORDS[index],
-            MnemonicLanguage::French => MNEMONIC_FRENCH_WORDS[index],
-            MnemonicLanguage::Italian => MNEMONIC_ITALIAN_WORDS[index],
-            MnemonicLanguage::Japanese => MNEMONIC_JAPANESE_WORDS[index],
-            MnemonicLanguage::Korean => MNEMONIC_KOREAN_WORDS[index],
-            MnemonicLanguage::Spanish => MNEMONIC_SPANISH_WORDS[index],
-        }
-        .to_string())
-    } else {
-        Err(MnemonicError::IndexOutOfBounds)
+    word_list(language)
+        .get(index)
+        .map(|word| (*word).to_string())
+        .ok_or(MnemonicError::IndexOutOfBounds)
+}
+
+/// Returns true if the word is in the word list of the specified language
+pub fn is_valid_word(word: &str, language: MnemonicLanguage) -> bool {
+    find_mnemonic_index_from_word(word, language).is_ok()
+}
+
+/// Returns the words of the specified language that start with `prefix`, in word list order. As with word lookups,
+/// case and, for latin scripts, diacritics are ignored. An empty prefix has no completions.
+pub fn word_completions(prefix: &str, language: MnemonicLanguage) -> Vec<String> {
+    let prefix = normalize_word(prefix.trim(), language);
+    if prefix.is_empty() {
+        return Vec::new();
     }
+    let words = word_list(language);
+    let start = words.partition_point(|word| *word < prefix.as_str());
+    words[start..]
+        .iter()
+        .take_while(|word| word.starts_with(prefix.as_str()))
+        .map(|word| (*word).to_string())
+        .collect()
 }
 
 /// Converts a vector of bytes to a sequence of mnemonic words using the specified language
@@ -435,6 +461,41 @@ mod test {
         assert_eq!(desired_word, word);
     }
 
+    #[test]
+    fn test_language_candidates() {
+        let words = vec!["animal".to_string(), "badge".to_string()];
+        let candidates = MnemonicLanguage::detect_candidates(&words);
+        assert!(candidates.contains(&MnemonicLanguage::English));
+        assert!(candidates.contains(&MnemonicLanguage::French));
+        assert!(!candidates.contains(&MnemonicLanguage::Italian));
+
+        let words = vec!["animal".to_string(), "trick".to_string()];
+        assert_eq!(MnemonicLanguage::detect_candidates(&words), vec![
+            MnemonicLanguage::English
+        ]);
+        assert!(MnemonicLanguage::detect_candidates(&["retro".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_word_completions() {
+        assert_eq!(word_completions("trick", MnemonicLanguage::English), vec!["trick"]);
+        assert_eq!(word_completions("Zo", MnemonicLanguage::English), vec!["zone", "zoo"]);
+        assert!(word_completions("zz", MnemonicLanguage::English).is_empty());
+        assert!(word_completions(" ", MnemonicLanguage::English).is_empty());
+        // Diacritics are ignored, like in word lookups
+        assert_eq!(
+            word_completions("éléph", MnemonicLanguage::French),
+            word_completions("eleph", MnemonicLanguage::French)
+        );
+        assert!(!word_completions("ab", MnemonicLanguage::French).is_empty());
+        for word in word_completions("ab", MnemonicLanguage::Spanish) {
+            assert!(word.starts_with("ab"));
+            assert!(is_valid_word(&word, MnemonicLanguage::Spanish));
+        }
+        assert!(is_valid_word("Trick", MnemonicLanguage::English));
+        assert!(!is_valid_word("tri", MnemonicLanguage::English));
+    }
+
     #[test]
     fn test_mnemonic_from_bytes_and_to_bytes() {
         let secretkey_bytes = RistrettoSecretKey::random(&mut OsRng).to_vec();
@@ -49,6 +49,36 @@ pub struct PrunedHashSet {
     hashes: Vec<Hash>,
 }
 
+impl PrunedHashSet {
+    /// Creates the pruned hash set of an MMR of size `base_offset` from the hashes of its peaks, e.g. as received from
+    /// a peer that holds the MMR
+    pub fn from_peaks(base_offset: usize, peak_hashes: Vec<Hash>) -> Result<Self, MerkleMountainRangeError> {
+        let peak_indices = find_peaks(base_offset);
+        if base_offset > 0 && peak_indices.is_empty() {
+            return Err(MerkleMountainRangeError::InvalidConfig);
+        }
+        if peak_hashes.len() != peak_indices.len() {
+            return Err(MerkleMountainRangeError::InvalidConfig);
+        }
+        Ok(PrunedHashSet {
+            base_offset,
+            peak_indices,
+            peak_hashes,
+            hashes: Vec::new(),
+        })
+    }
+
+    /// The size of the base MMR
+    pub fn base_offset(&self) -> usize {
+        self.base_offset
+    }
+
+    /// The hashes at the peaks of the base MMR
+    pub fn peak_hashes(&self) -> &[Hash] {
+        &self.peak_hashes
+    }
+}
+
 impl<D, B> TryFrom<&MerkleMountainRange<D, B>> for PrunedHashSet
 where
     D: Digest + DomainDigest,
@@ -31,7 +31,8 @@ use rand::{
 };
 use support::{create_mmr, create_mutable_mmr, int_to_hash};
 use tari_mmr::{
-    functions::{calculate_mmr_root, calculate_pruned_mmr_root, prune_mmr},
+    functions::{calculate_mmr_root, calculate_pruned_mmr_root, prune_mmr, PrunedMmr},
+    pruned_hashset::PrunedHashSet,
     Hash,
 };
 
@@ -65,6 +66,26 @@ fn pruned_mmrs() {
     }
 }
 
+#[test]
+fn pruned_mmr_from_peaks() {
+    for size in &[0, 1, 6, 14, 63, 64, 65, 127] {
+        let mmr = create_mmr(*size);
+        let mmr2 = create_mmr(size + 2);
+        let pruned_set = mmr.get_pruned_hash_set().unwrap();
+
+        let from_peaks =
+            PrunedHashSet::from_peaks(pruned_set.base_offset(), pruned_set.peak_hashes().to_vec()).unwrap();
+        let mut pruned = PrunedMmr::<support::MmrTestHasherBlake256>::new(from_peaks);
+        assert_eq!(pruned.get_merkle_root(), mmr.get_merkle_root());
+        assert!(pruned.push(int_to_hash(*size)).is_ok());
+        assert!(pruned.push(int_to_hash(*size + 1)).is_ok());
+        assert_eq!(pruned.get_merkle_root(), mmr2.get_merkle_root());
+    }
+    // An MMR of 2 nodes is not valid, and a valid size needs a hash for every peak
+    assert!(PrunedHashSet::from_peaks(2, vec![int_to_hash(0)]).is_err());
+    assert!(PrunedHashSet::from_peaks(4, vec![int_to_hash(0)]).is_err());
+}
+
 fn get_changes() -> (usize, Vec<Hash>, Vec<u32>) {
     let mut rng = rand::thread_rng();
     let src_size: usize = rng.gen_range(25..150);
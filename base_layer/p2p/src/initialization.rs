@@ -42,6 +42,7 @@ use tari_common::{
 };
 use tari_comms::{
     backoff::ConstantBackoff,
+    i2p,
     peer_manager::{NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManagerError},
     pipeline,
     protocol::{
@@ -74,7 +75,7 @@ use crate::{
     comms_connector::{InboundDomainConnector, PubsubDomainConnector},
     config::{P2pConfig, PeerSeedsConfig},
     peer_seeds::{DnsSeedResolver, SeedPeer},
-    transport::{TorTransportConfig, TransportType},
+    transport::{I2pTransportConfig, TorTransportConfig, TransportType},
     TransportConfig,
     MAJOR_NETWORK_VERSION,
     MINOR_NETWORK_VERSION,
@@ -101,6 +102,8 @@ pub enum CommsInitializationError {
     InvalidTorForwardAddress(std::io::Error),
     #[error("IO Error: `{0}`")]
     IoError(#[from] std::io::Error),
+    #[error("Failed to initialize I2P session: {0}")]
+    I2pError(#[from] i2p::I2pError),
 }
 
 impl CommsInitializationError {
@@ -217,10 +220,16 @@ pub async fn spawn_comms_using_transport(
     let comms = match transport_config.transport_type {
         TransportType::Memory => {
             debug!(target: LOG_TARGET, "Building in-memory comms stack");
-            comms
+            let comms = comms
                 .with_listener_address(transport_config.memory.listener_address.clone())
                 .spawn_with_transport(MemoryTransport)
-                .await?
+                .await?;
+            // Memory peers can only be reached at the address the node is listening on, which is assigned when
+            // listening on `/memory/0`
+            comms
+                .node_identity()
+                .set_public_address(comms.listening_address().clone());
+            comms
         },
         TransportType::Tcp => {
             let config = transport_config.tcp;
@@ -259,6 +268,21 @@ pub async fn spawn_comms_using_transport(
                 .spawn_with_transport(transport)
                 .await?
         },
+        TransportType::I2p => {
+            let i2p_config = transport_config.i2p;
+            debug!(target: LOG_TARGET, "Building I2P comms stack ({:?})", i2p_config);
+            let allow_outbound_tcp = i2p_config.allow_outbound_tcp;
+            let session = initialize_i2p_session(i2p_config).await?;
+            let mut transport = i2p::I2pTransport::new(&session);
+            if allow_outbound_tcp {
+                transport = transport.with_tcp_fallback();
+            }
+            comms
+                .with_listener_address(session.public_address().clone())
+                .with_i2p_session(session)
+                .spawn_with_transport(transport)
+                .await?
+        },
         TransportType::Socks5 => {
             debug!(target: LOG_TARGET, "Building SOCKS5 comms stack");
             let transport = SocksTransport::new(transport_config.socks.into());
@@ -296,6 +320,19 @@ async fn initialize_hidden_service(
     Ok(hidden_svc_ctl)
 }
 
+async fn initialize_i2p_session(mut config: I2pTransportConfig) -> Result<i2p::I2pSession, CommsInitializationError> {
+    let mut builder = i2p::I2pSessionBuilder::new()
+        .with_sam_address(config.sam_address)
+        .with_port(config.port.get());
+
+    if let Some(identity) = config.identity.take() {
+        builder = builder.with_identity(identity);
+    }
+
+    let session = builder.build().await?;
+    Ok(session)
+}
+
 async fn configure_comms_and_dht(
     builder: CommsBuilder,
     config: &P2pConfig,
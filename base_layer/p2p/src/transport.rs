@@ -23,6 +23,7 @@ use std::{num::NonZeroU16, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 use tari_comms::{
+    i2p::I2pIdentity,
     multiaddr::Multiaddr,
     socks,
     tor,
@@ -42,6 +43,7 @@ pub struct TransportConfig {
     pub tor: TorTransportConfig,
     pub socks: Socks5TransportConfig,
     pub memory: MemoryTransportConfig,
+    pub i2p: I2pTransportConfig,
 }
 
 impl TransportConfig {
@@ -69,6 +71,14 @@ impl TransportConfig {
         }
     }
 
+    pub fn new_i2p(config: I2pTransportConfig) -> Self {
+        Self {
+            transport_type: TransportType::I2p,
+            i2p: config,
+            ..Default::default()
+        }
+    }
+
     pub fn new_socks5(forward_address: Multiaddr, config: Socks5TransportConfig) -> Self {
         Self {
             transport_type: TransportType::Socks5,
@@ -84,6 +94,10 @@ impl TransportConfig {
     pub fn is_tor(&self) -> bool {
         matches!(self.transport_type, TransportType::Tor)
     }
+
+    pub fn is_i2p(&self) -> bool {
+        matches!(self.transport_type, TransportType::I2p)
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -99,6 +113,9 @@ pub enum TransportType {
     Tor,
     /// Use a SOCKS5 proxy transport. This transport allows any addresses supported by the proxy.
     Socks5,
+    /// Run the node as an I2P destination using the SAM bridge of a local I2P router. This transport can connect to
+    /// I2P addresses and, if configured to, TCP addresses.
+    I2p,
 }
 
 impl Default for TransportType {
@@ -200,6 +217,32 @@ impl Default for TorTransportConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct I2pTransportConfig {
+    /// The address of the SAM bridge of the I2P router
+    pub sam_address: Multiaddr,
+    /// The port included in the I2P address of the node
+    pub port: NonZeroU16,
+    /// When set to true, peers with non-I2P addresses are dialed directly over TCP, which reveals the IP address of
+    /// the node to them. Defaults to false.
+    pub allow_outbound_tcp: bool,
+    /// The I2P identity to create the session with. If None, a new one will be generated.
+    #[serde(skip)]
+    pub identity: Option<I2pIdentity>,
+}
+
+impl Default for I2pTransportConfig {
+    fn default() -> Self {
+        Self {
+            sam_address: "/ip4/127.0.0.1/tcp/7656".parse().unwrap(),
+            port: NonZeroU16::new(18141).unwrap(),
+            allow_outbound_tcp: false,
+            identity: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Socks5TransportConfig {
@@ -0,0 +1,7 @@
+CREATE TABLE transaction_protocol_states (
+    tx_id                   BIGINT PRIMARY KEY NOT NULL,
+    stage                   INTEGER            NOT NULL,
+    counterparty_public_key BLOB               NOT NULL,
+    protocol_state          TEXT               NOT NULL,
+    last_updated            DATETIME           NOT NULL
+);
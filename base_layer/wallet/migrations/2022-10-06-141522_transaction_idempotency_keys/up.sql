@@ -0,0 +1,7 @@
+CREATE TABLE transaction_idempotency_keys (
+    idempotency_key        TEXT PRIMARY KEY NOT NULL,
+    tx_id                  BIGINT           NOT NULL,
+    destination_public_key BLOB             NOT NULL,
+    amount                 BIGINT           NOT NULL,
+    timestamp              DATETIME         NOT NULL
+);
@@ -0,0 +1,11 @@
+CREATE TABLE pending_approval_transactions (
+    tx_id                  BIGINT PRIMARY KEY NOT NULL,
+    payment_type           INTEGER            NOT NULL,
+    destination_public_key BLOB               NOT NULL,
+    amount                 BIGINT             NOT NULL,
+    fee_per_gram           BIGINT             NOT NULL,
+    output_features        TEXT               NOT NULL,
+    message                TEXT               NOT NULL,
+    timestamp              DATETIME           NOT NULL,
+    expiry_timestamp       DATETIME           NOT NULL
+);
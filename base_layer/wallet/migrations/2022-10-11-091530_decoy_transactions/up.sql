@@ -0,0 +1 @@
+ALTER TABLE completed_transactions ADD COLUMN is_decoy INTEGER NOT NULL DEFAULT 0;
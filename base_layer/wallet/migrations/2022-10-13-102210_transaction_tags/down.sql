@@ -0,0 +1,2 @@
+DROP INDEX idx_transaction_tags_tag;
+DROP TABLE transaction_tags;
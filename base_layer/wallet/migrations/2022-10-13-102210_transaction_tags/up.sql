@@ -0,0 +1,7 @@
+CREATE TABLE transaction_tags (
+    tx_id BIGINT NOT NULL,
+    tag   TEXT   NOT NULL,
+    PRIMARY KEY (tx_id, tag)
+);
+
+CREATE INDEX idx_transaction_tags_tag ON transaction_tags (tag);
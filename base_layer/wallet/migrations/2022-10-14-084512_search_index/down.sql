@@ -0,0 +1,15 @@
+DROP TRIGGER search_index_tag_delete;
+DROP TRIGGER search_index_tag_insert;
+DROP TRIGGER search_index_contact_delete;
+DROP TRIGGER search_index_contact_update;
+DROP TRIGGER search_index_contact_insert;
+DROP TRIGGER search_index_outbound_delete;
+DROP TRIGGER search_index_outbound_update;
+DROP TRIGGER search_index_outbound_insert;
+DROP TRIGGER search_index_inbound_delete;
+DROP TRIGGER search_index_inbound_update;
+DROP TRIGGER search_index_inbound_insert;
+DROP TRIGGER search_index_completed_delete;
+DROP TRIGGER search_index_completed_update;
+DROP TRIGGER search_index_completed_insert;
+DROP TABLE search_index;
@@ -0,0 +1,107 @@
+-- Full-text index over transaction messages, contact aliases and transaction tags. The index only holds references to
+-- the indexed rows and is kept up to date by the triggers below.
+CREATE VIRTUAL TABLE search_index USING fts5(
+    content,
+    kind UNINDEXED,
+    tx_id UNINDEXED,
+    public_key UNINDEXED,
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+-- The insert triggers remove the existing entry first, because `REPLACE` does not fire the delete triggers
+CREATE TRIGGER search_index_completed_insert AFTER INSERT ON completed_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'completed' AND tx_id = NEW.tx_id;
+    INSERT INTO search_index (content, kind, tx_id)
+        SELECT NEW.message, 'completed', NEW.tx_id WHERE NEW.message != '' AND NEW.is_decoy = 0;
+END;
+
+CREATE TRIGGER search_index_completed_update AFTER UPDATE OF message, is_decoy ON completed_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'completed' AND tx_id = OLD.tx_id;
+    INSERT INTO search_index (content, kind, tx_id)
+        SELECT NEW.message, 'completed', NEW.tx_id WHERE NEW.message != '' AND NEW.is_decoy = 0;
+END;
+
+CREATE TRIGGER search_index_completed_delete AFTER DELETE ON completed_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'completed' AND tx_id = OLD.tx_id;
+END;
+
+CREATE TRIGGER search_index_inbound_insert AFTER INSERT ON inbound_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'inbound' AND tx_id = NEW.tx_id;
+    INSERT INTO search_index (content, kind, tx_id)
+        SELECT NEW.message, 'inbound', NEW.tx_id WHERE NEW.message != '';
+END;
+
+CREATE TRIGGER search_index_inbound_update AFTER UPDATE OF message ON inbound_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'inbound' AND tx_id = OLD.tx_id;
+    INSERT INTO search_index (content, kind, tx_id)
+        SELECT NEW.message, 'inbound', NEW.tx_id WHERE NEW.message != '';
+END;
+
+CREATE TRIGGER search_index_inbound_delete AFTER DELETE ON inbound_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'inbound' AND tx_id = OLD.tx_id;
+END;
+
+CREATE TRIGGER search_index_outbound_insert AFTER INSERT ON outbound_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'outbound' AND tx_id = NEW.tx_id;
+    INSERT INTO search_index (content, kind, tx_id)
+        SELECT NEW.message, 'outbound', NEW.tx_id WHERE NEW.message != '';
+END;
+
+CREATE TRIGGER search_index_outbound_update AFTER UPDATE OF message ON outbound_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'outbound' AND tx_id = OLD.tx_id;
+    INSERT INTO search_index (content, kind, tx_id)
+        SELECT NEW.message, 'outbound', NEW.tx_id WHERE NEW.message != '';
+END;
+
+CREATE TRIGGER search_index_outbound_delete AFTER DELETE ON outbound_transactions
+BEGIN
+    DELETE FROM search_index WHERE kind = 'outbound' AND tx_id = OLD.tx_id;
+END;
+
+CREATE TRIGGER search_index_contact_insert AFTER INSERT ON contacts
+BEGIN
+    DELETE FROM search_index WHERE kind = 'contact' AND public_key = hex(NEW.public_key);
+    INSERT INTO search_index (content, kind, public_key) VALUES (NEW.alias, 'contact', hex(NEW.public_key));
+END;
+
+CREATE TRIGGER search_index_contact_update AFTER UPDATE OF alias ON contacts
+BEGIN
+    DELETE FROM search_index WHERE kind = 'contact' AND public_key = hex(OLD.public_key);
+    INSERT INTO search_index (content, kind, public_key) VALUES (NEW.alias, 'contact', hex(NEW.public_key));
+END;
+
+CREATE TRIGGER search_index_contact_delete AFTER DELETE ON contacts
+BEGIN
+    DELETE FROM search_index WHERE kind = 'contact' AND public_key = hex(OLD.public_key);
+END;
+
+CREATE TRIGGER search_index_tag_insert AFTER INSERT ON transaction_tags
+BEGIN
+    DELETE FROM search_index WHERE kind = 'tag' AND tx_id = NEW.tx_id AND content = NEW.tag;
+    INSERT INTO search_index (content, kind, tx_id) VALUES (NEW.tag, 'tag', NEW.tx_id);
+END;
+
+CREATE TRIGGER search_index_tag_delete AFTER DELETE ON transaction_tags
+BEGIN
+    DELETE FROM search_index WHERE kind = 'tag' AND tx_id = OLD.tx_id AND content = OLD.tag;
+END;
+
+INSERT INTO search_index (content, kind, tx_id)
+    SELECT message, 'completed', tx_id FROM completed_transactions WHERE message != '' AND is_decoy = 0;
+INSERT INTO search_index (content, kind, tx_id)
+    SELECT message, 'inbound', tx_id FROM inbound_transactions WHERE message != '';
+INSERT INTO search_index (content, kind, tx_id)
+    SELECT message, 'outbound', tx_id FROM outbound_transactions WHERE message != '';
+INSERT INTO search_index (content, kind, public_key)
+    SELECT alias, 'contact', hex(public_key) FROM contacts;
+INSERT INTO search_index (content, kind, tx_id)
+    SELECT tag, 'tag', tx_id FROM transaction_tags;
@@ -0,0 +1,6 @@
+DROP INDEX idx_outputs_script_lock_height;
+DROP INDEX idx_outputs_status_maturity;
+DROP TRIGGER output_balances_delete;
+DROP TRIGGER output_balances_update;
+DROP TRIGGER output_balances_insert;
+DROP TABLE output_balances;
@@ -0,0 +1,33 @@
+-- Running totals of the output values per status and source, so that the balance does not have to be summed over every
+-- output. The totals only hold sums of the plaintext `value` column and are kept up to date by the triggers below.
+CREATE TABLE output_balances (
+    status INTEGER NOT NULL,
+    source INTEGER NOT NULL,
+    amount BIGINT  NOT NULL,
+    PRIMARY KEY (status, source)
+);
+
+CREATE TRIGGER output_balances_insert AFTER INSERT ON outputs
+BEGIN
+    INSERT INTO output_balances (status, source, amount) VALUES (NEW.status, NEW.source, NEW.value)
+        ON CONFLICT (status, source) DO UPDATE SET amount = amount + excluded.amount;
+END;
+
+CREATE TRIGGER output_balances_update AFTER UPDATE OF status, source, value ON outputs
+BEGIN
+    UPDATE output_balances SET amount = amount - OLD.value WHERE status = OLD.status AND source = OLD.source;
+    INSERT INTO output_balances (status, source, amount) VALUES (NEW.status, NEW.source, NEW.value)
+        ON CONFLICT (status, source) DO UPDATE SET amount = amount + excluded.amount;
+END;
+
+CREATE TRIGGER output_balances_delete AFTER DELETE ON outputs
+BEGIN
+    UPDATE output_balances SET amount = amount - OLD.value WHERE status = OLD.status AND source = OLD.source;
+END;
+
+INSERT INTO output_balances (status, source, amount)
+    SELECT status, source, sum(value) FROM outputs GROUP BY status, source;
+
+-- The time locked balance depends on the chain tip, so it is summed over the locked outputs found with these indexes
+CREATE INDEX idx_outputs_status_maturity ON outputs (status, maturity);
+CREATE INDEX idx_outputs_script_lock_height ON outputs (script_lock_height);
@@ -0,0 +1,7 @@
+-- The key epochs of the key manager. Keys are derived from the branches of the latest epoch, and an epoch is marked
+-- as compromised when the keys are rotated away from it. A wallet without rows is still in epoch 0.
+CREATE TABLE key_manager_epochs (
+    epoch          BIGINT PRIMARY KEY NOT NULL,
+    created_at     DATETIME           NOT NULL,
+    compromised_at DATETIME           NULL
+);
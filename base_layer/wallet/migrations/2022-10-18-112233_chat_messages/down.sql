@@ -0,0 +1,2 @@
+DROP INDEX idx_chat_messages_address;
+DROP TABLE chat_messages;
@@ -0,0 +1,13 @@
+-- Text messages exchanged with contacts. `address` is the public key of the contact and `direction` is 0 for
+-- inbound and 1 for outbound messages. The delivery of an outbound message is confirmed by a receipt of the contact.
+CREATE TABLE chat_messages (
+    message_id            BLOB     PRIMARY KEY NOT NULL,
+    address               BLOB     NOT NULL,
+    body                  TEXT     NOT NULL,
+    direction             INTEGER  NOT NULL,
+    sent_at               DATETIME NOT NULL,
+    stored_at             DATETIME NOT NULL,
+    delivery_confirmed_at DATETIME NULL
+);
+
+CREATE INDEX idx_chat_messages_address ON chat_messages (address, sent_at);
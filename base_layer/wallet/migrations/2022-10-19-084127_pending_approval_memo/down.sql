@@ -0,0 +1 @@
+ALTER TABLE pending_approval_transactions DROP COLUMN memo;
@@ -0,0 +1 @@
+ALTER TABLE pending_approval_transactions ADD COLUMN memo TEXT NULL;
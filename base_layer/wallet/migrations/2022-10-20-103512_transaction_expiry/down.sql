@@ -0,0 +1,3 @@
+ALTER TABLE pending_approval_transactions DROP COLUMN expires_at;
+ALTER TABLE outbound_transactions DROP COLUMN expires_at;
+ALTER TABLE inbound_transactions DROP COLUMN expires_at;
@@ -0,0 +1,3 @@
+ALTER TABLE inbound_transactions ADD COLUMN expires_at DATETIME NULL;
+ALTER TABLE outbound_transactions ADD COLUMN expires_at DATETIME NULL;
+ALTER TABLE pending_approval_transactions ADD COLUMN expires_at DATETIME NULL;
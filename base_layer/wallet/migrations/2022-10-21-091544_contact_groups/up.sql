@@ -0,0 +1,8 @@
+-- Contacts made up of several members that sign together. `aggregate_public_key` is the sum of the public keys of the
+-- members and identifies the group, `members` holds the public keys of the members concatenated in ascending order.
+CREATE TABLE contact_groups (
+    aggregate_public_key BLOB    PRIMARY KEY NOT NULL,
+    alias                TEXT    NOT NULL,
+    threshold            INTEGER NOT NULL,
+    members              BLOB    NOT NULL
+);
@@ -0,0 +1 @@
+DROP TABLE aggregated_sender_sessions;
@@ -0,0 +1,7 @@
+-- The signing sessions of this wallet as a party to an aggregated sender key, persisted between the rounds of the
+-- session. `public_key` is the key of the party in the session and `session` holds the serialized session.
+CREATE TABLE aggregated_sender_sessions (
+    public_key   BLOB     PRIMARY KEY NOT NULL,
+    session      TEXT     NOT NULL,
+    last_updated DATETIME NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Small files, e.g. the receipt or the invoice of a payment, attached to a transaction. `data` is encrypted with the
+-- rest of the transaction data when the wallet database is encrypted.
+CREATE TABLE transaction_attachments (
+    tx_id        BIGINT   NOT NULL,
+    name         TEXT     NOT NULL,
+    content_type TEXT     NOT NULL,
+    data         BLOB     NOT NULL,
+    timestamp    DATETIME NOT NULL,
+    PRIMARY KEY (tx_id, name)
+);
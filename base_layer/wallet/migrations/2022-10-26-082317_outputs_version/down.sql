@@ -0,0 +1,4 @@
+DROP TRIGGER outputs_version_delete;
+DROP TRIGGER outputs_version_update;
+DROP TRIGGER outputs_version_insert;
+DROP TABLE outputs_version;
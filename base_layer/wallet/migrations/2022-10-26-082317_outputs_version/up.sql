@@ -0,0 +1,23 @@
+-- A counter of the changes to the outputs, so that the in-memory cache of the unspent outputs can tell whether it still
+-- reflects the outputs, including after changes that were made by another process
+CREATE TABLE outputs_version (
+    id      INTEGER NOT NULL PRIMARY KEY CHECK (id = 0),
+    version BIGINT  NOT NULL
+);
+
+INSERT INTO outputs_version (id, version) VALUES (0, 0);
+
+CREATE TRIGGER outputs_version_insert AFTER INSERT ON outputs
+BEGIN
+    UPDATE outputs_version SET version = version + 1;
+END;
+
+CREATE TRIGGER outputs_version_update AFTER UPDATE ON outputs
+BEGIN
+    UPDATE outputs_version SET version = version + 1;
+END;
+
+CREATE TRIGGER outputs_version_delete AFTER DELETE ON outputs
+BEGIN
+    UPDATE outputs_version SET version = version + 1;
+END;
@@ -0,0 +1,3 @@
+DROP INDEX deposits_key_index;
+DROP TABLE deposits;
+DROP TABLE deposit_identities;
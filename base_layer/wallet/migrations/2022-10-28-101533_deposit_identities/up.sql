@@ -0,0 +1,18 @@
+-- Receive-only identities that the wallet derives for the users of a custodian. Payments to an identity are spent
+-- together with the rest of the outputs of the wallet, but are attributed to the identity in `deposits`.
+CREATE TABLE deposit_identities (
+    key_index  BIGINT PRIMARY KEY NOT NULL,
+    label      TEXT UNIQUE        NOT NULL,
+    public_key BLOB UNIQUE        NOT NULL,
+    created_at DATETIME           NOT NULL
+);
+
+CREATE TABLE deposits (
+    commitment  BLOB PRIMARY KEY NOT NULL,
+    key_index   BIGINT           NOT NULL REFERENCES deposit_identities (key_index),
+    value       BIGINT           NOT NULL,
+    tx_id       BIGINT           NOT NULL,
+    received_at DATETIME         NOT NULL
+);
+
+CREATE INDEX deposits_key_index ON deposits (key_index);
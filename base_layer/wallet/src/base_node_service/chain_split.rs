@@ -0,0 +1,207 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Cross-checks the tips reported by all the configured base nodes. The wallet only follows a single base node, so a
+//! chain split, e.g. during a contentious reorg, would otherwise go unnoticed until that base node reorgs.
+
+use std::{convert::TryFrom, sync::Arc};
+
+use futures::future;
+use log::*;
+use tari_common_types::{chain_metadata::ChainMetadata, types::BlockHash};
+use tari_comms::{
+    connectivity::{ConnectivityError, ConnectivityRequester},
+    peer_manager::{Peer, PeerManager},
+    protocol::rpc::RpcError,
+    types::CommsPublicKey,
+};
+use tari_core::base_node::rpc::BaseNodeWalletRpcClient;
+use tari_shutdown::ShutdownSignal;
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::base_node_service::{
+    clock_skew::ClockSkewMonitor,
+    config::BaseNodeServiceConfig,
+    handle::{BaseNodeEvent, BaseNodeEventSender},
+};
+
+const LOG_TARGET: &str = "wallet::base_node_service::chain_split";
+
+/// The tip reported by one of the monitored base nodes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BaseNodeTip {
+    pub public_key: CommsPublicKey,
+    pub height: u64,
+    pub accumulated_difficulty: u128,
+    pub best_block: BlockHash,
+}
+
+pub(super) struct ChainSplitMonitor {
+    config: BaseNodeServiceConfig,
+    base_nodes: Vec<Peer>,
+    connectivity: ConnectivityRequester,
+    peer_manager: Arc<PeerManager>,
+    event_publisher: BaseNodeEventSender,
+    clock_skew: ClockSkewMonitor,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl ChainSplitMonitor {
+    pub fn new(
+        config: BaseNodeServiceConfig,
+        base_nodes: Vec<Peer>,
+        connectivity: ConnectivityRequester,
+        peer_manager: Arc<PeerManager>,
+        event_publisher: BaseNodeEventSender,
+        clock_skew: ClockSkewMonitor,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            base_nodes,
+            connectivity,
+            peer_manager,
+            event_publisher,
+            clock_skew,
+            shutdown_signal,
+        }
+    }
+
+    /// Publishes `ChainSplitSuspected` when the tips of the base nodes start to diverge and `ChainSplitResolved` when
+    /// they agree again
+    pub async fn run(mut self) {
+        for peer in &self.base_nodes {
+            if !self.peer_manager.exists(&peer.public_key).await {
+                if let Err(e) = self.peer_manager.add_peer(peer.clone()).await {
+                    warn!(target: LOG_TARGET, "Could not add base node {}: {}", peer.node_id, e);
+                }
+            }
+        }
+
+        let mut interval = time::interval(self.config.chain_split_check_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut split_suspected = false;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = self.shutdown_signal.wait() => break,
+            }
+
+            let tips = self.fetch_tips().await;
+            // A single base node cannot disagree with anyone
+            if tips.len() < 2 {
+                continue;
+            }
+            let is_split = is_chain_split(&tips, self.config.chain_split_height_threshold);
+            if is_split == split_suspected {
+                continue;
+            }
+            split_suspected = is_split;
+            let event = if is_split {
+                warn!(
+                    target: LOG_TARGET,
+                    "The base nodes report diverging tips, a chain split is suspected: {:?}", tips
+                );
+                BaseNodeEvent::ChainSplitSuspected(tips)
+            } else {
+                info!(target: LOG_TARGET, "The base nodes agree on the tip again");
+                BaseNodeEvent::ChainSplitResolved
+            };
+            let _size = self.event_publisher.send(Arc::new(event));
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Chain split monitor shutting down because it received the shutdown signal"
+        );
+    }
+
+    /// The tips of the base nodes that replied
+    async fn fetch_tips(&self) -> Vec<BaseNodeTip> {
+        let results = future::join_all(self.base_nodes.iter().map(|peer| self.fetch_tip(peer))).await;
+        self.base_nodes
+            .iter()
+            .zip(results)
+            .filter_map(|(peer, result)| match result {
+                Ok(tip) => Some(tip),
+                Err(e) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Could not fetch the tip of base node {}: {}", peer.node_id, e
+                    );
+                    None
+                },
+            })
+            .collect()
+    }
+
+    async fn fetch_tip(&self, peer: &Peer) -> Result<BaseNodeTip, ChainSplitMonitorError> {
+        let mut connection = self.connectivity.dial_peer(peer.node_id.clone()).await?;
+        let mut client = connection
+            .connect_rpc_using_builder(
+                BaseNodeWalletRpcClient::builder().with_deadline(self.config.base_node_rpc_request_timeout),
+            )
+            .await?;
+        let metadata = client
+            .get_tip_info()
+            .await?
+            .metadata
+            .ok_or_else(|| ChainSplitMonitorError::InvalidBaseNodeResponse("Tip info no metadata".to_string()))?;
+        let metadata = ChainMetadata::try_from(metadata).map_err(ChainSplitMonitorError::InvalidBaseNodeResponse)?;
+        self.clock_skew.record_tip(&peer.node_id, &metadata);
+        Ok(BaseNodeTip {
+            public_key: peer.public_key.clone(),
+            height: metadata.height_of_longest_chain(),
+            accumulated_difficulty: metadata.accumulated_difficulty(),
+            best_block: *metadata.best_block(),
+        })
+    }
+}
+
+/// The tips diverge if one base node is more than `height_threshold` blocks ahead of another, or if a base node that
+/// is ahead reports less accumulated difficulty than one that is behind, i.e. they follow different chains
+fn is_chain_split(tips: &[BaseNodeTip], height_threshold: u64) -> bool {
+    tips.iter().any(|ahead| {
+        tips.iter().any(|behind| {
+            ahead.height > behind.height.saturating_add(height_threshold) ||
+                (ahead.height > behind.height && ahead.accumulated_difficulty < behind.accumulated_difficulty)
+        })
+    })
+}
+
+#[derive(thiserror::Error, Debug)]
+enum ChainSplitMonitorError {
+    #[error("Connectivity error: {0}")]
+    ConnectivityError(#[from] ConnectivityError),
+    #[error("Rpc error: {0}")]
+    RpcFailed(#[from] RpcError),
+    #[error("Invalid base node response: {0}")]
+    InvalidBaseNodeResponse(String),
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey;
+
+    use super::*;
+
+    fn tip(height: u64, accumulated_difficulty: u128) -> BaseNodeTip {
+        BaseNodeTip {
+            public_key: CommsPublicKey::random_keypair(&mut OsRng).1,
+            height,
+            accumulated_difficulty,
+            best_block: BlockHash::default(),
+        }
+    }
+
+    #[test]
+    fn it_detects_diverging_tips() {
+        assert!(!is_chain_split(&[], 2));
+        assert!(!is_chain_split(&[tip(10, 100), tip(10, 100)], 2));
+        // Base nodes that are a few blocks apart on the same chain
+        assert!(!is_chain_split(&[tip(10, 100), tip(12, 120), tip(11, 110)], 2));
+        assert!(is_chain_split(&[tip(10, 100), tip(13, 130)], 2));
+        // The base node that is ahead follows a lighter chain
+        assert!(is_chain_split(&[tip(10, 100), tip(11, 90)], 2));
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Estimates the skew of the local clock from the timestamps of the new tips that the base nodes report. A block is
+//! only a sample when it is first seen, which is shortly after it was mined, so a skew that stays above the threshold
+//! is published as an event and corrected for in the time that the wallet computes expiries with.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use log::*;
+use tari_common_types::{chain_metadata::ChainMetadata, types::BlockHash};
+use tari_comms::peer_manager::NodeId;
+
+use crate::{
+    base_node_service::{
+        config::BaseNodeServiceConfig,
+        handle::{BaseNodeEvent, BaseNodeEventSender},
+    },
+    util::clock::set_clock_correction,
+};
+
+const LOG_TARGET: &str = "wallet::base_node_service::clock_skew";
+
+/// The number of samples needed before the skew is estimated
+const MIN_SAMPLES: usize = 3;
+/// The number of most recent samples that the skew is estimated from
+const MAX_SAMPLES: usize = 15;
+
+struct ClockSkewState {
+    /// The last tip seen from each base node
+    tips: HashMap<NodeId, BlockHash>,
+    /// The timestamps of new tips minus the local time at which they were seen, in milliseconds
+    samples: VecDeque<i64>,
+    skew_detected: bool,
+}
+
+/// The clock skew estimate, shared by the base node monitors that report the tips
+#[derive(Clone)]
+pub(super) struct ClockSkewMonitor {
+    state: Arc<Mutex<ClockSkewState>>,
+    threshold: Duration,
+    correct_clock_skew: bool,
+    event_publisher: BaseNodeEventSender,
+}
+
+impl ClockSkewMonitor {
+    pub fn new(config: &BaseNodeServiceConfig, event_publisher: BaseNodeEventSender) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ClockSkewState {
+                tips: HashMap::new(),
+                samples: VecDeque::with_capacity(MAX_SAMPLES),
+                skew_detected: false,
+            })),
+            threshold: Duration::from_std(config.clock_skew_threshold).unwrap_or_else(|_| Duration::max_value()),
+            correct_clock_skew: config.correct_clock_skew,
+            event_publisher,
+        }
+    }
+
+    /// Records the tip that a base node reported just now
+    pub fn record_tip(&self, base_node: &NodeId, tip: &ChainMetadata) {
+        if let Some(event) = self.record_tip_at(base_node, tip, Utc::now().naive_utc()) {
+            let _size = self.event_publisher.send(Arc::new(event));
+        }
+    }
+
+    /// How far the local clock is ahead of the chain, or `None` if there are not enough samples yet. The estimate is
+    /// always a little ahead, because blocks are seen some time after their timestamp.
+    pub fn estimated_skew(&self) -> Option<Duration> {
+        let state = self.state.lock().expect("The clock skew lock is poisoned");
+        if state.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        median(&state.samples).map(|offset| Duration::milliseconds(-offset))
+    }
+
+    /// Records the tip that a base node reported at the local time `now` and returns the event to publish if the skew
+    /// crossed the threshold
+    fn record_tip_at(&self, base_node: &NodeId, tip: &ChainMetadata, now: NaiveDateTime) -> Option<BaseNodeEvent> {
+        let mut state = self.state.lock().expect("The clock skew lock is poisoned");
+        let previous_tip = state.tips.insert(base_node.clone(), *tip.best_block());
+        // The first tip seen from a base node may have been mined a long time ago
+        if previous_tip.map_or(true, |previous| previous == *tip.best_block()) {
+            return None;
+        }
+        let timestamp = i64::try_from(tip.timestamp()).ok().filter(|t| *t > 0)?;
+        let offset = timestamp.saturating_mul(1000).saturating_sub(now.timestamp_millis());
+        if state.samples.len() == MAX_SAMPLES {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(offset);
+
+        if state.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let offset = Duration::milliseconds(median(&state.samples)?);
+        let skew_detected = offset.num_milliseconds().abs() > self.threshold.num_milliseconds();
+        if skew_detected && self.correct_clock_skew {
+            set_clock_correction(offset);
+        }
+        if skew_detected == state.skew_detected {
+            return None;
+        }
+        state.skew_detected = skew_detected;
+        if skew_detected {
+            warn!(
+                target: LOG_TARGET,
+                "The local clock is off by {} seconds from the timestamps of the chain{}",
+                -offset.num_seconds(),
+                if self.correct_clock_skew {
+                    ", expiries are corrected for it"
+                } else {
+                    ""
+                }
+            );
+            Some(BaseNodeEvent::ClockSkewDetected(-offset.num_seconds()))
+        } else {
+            info!(target: LOG_TARGET, "The local clock agrees with the chain again");
+            if self.correct_clock_skew {
+                set_clock_correction(Duration::zero());
+            }
+            Some(BaseNodeEvent::ClockSkewResolved)
+        }
+    }
+}
+
+fn median(samples: &VecDeque<i64>) -> Option<i64> {
+    let mut sorted = samples.iter().copied().collect::<Vec<_>>();
+    sorted.sort_unstable();
+    sorted.get(sorted.len() / 2).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::sync::broadcast;
+
+    use super::*;
+
+    fn tip(hash: u8, timestamp: i64) -> ChainMetadata {
+        ChainMetadata::new(
+            1,
+            BlockHash::from([hash; 32]),
+            0,
+            0,
+            1,
+            u64::try_from(timestamp).unwrap(),
+        )
+    }
+
+    #[test]
+    fn it_detects_a_skewed_clock() {
+        let config = BaseNodeServiceConfig {
+            correct_clock_skew: false,
+            ..Default::default()
+        };
+        let monitor = ClockSkewMonitor::new(&config, broadcast::channel(1).0);
+        let base_node = NodeId::default();
+        let now = NaiveDateTime::from_timestamp(1_000_000, 0);
+        // The local clock is an hour ahead
+        let block_time = now.timestamp() - 3600;
+
+        assert_eq!(monitor.record_tip_at(&base_node, &tip(0, block_time), now), None);
+        for hash in 1..MIN_SAMPLES {
+            assert_eq!(
+                monitor.record_tip_at(&base_node, &tip(hash as u8, block_time), now),
+                None
+            );
+        }
+        // The same tip is not sampled twice
+        assert_eq!(
+            monitor.record_tip_at(&base_node, &tip(MIN_SAMPLES as u8 - 1, block_time), now),
+            None
+        );
+        assert_eq!(monitor.estimated_skew(), None);
+        assert_eq!(
+            monitor.record_tip_at(&base_node, &tip(MIN_SAMPLES as u8, block_time), now),
+            Some(BaseNodeEvent::ClockSkewDetected(3600))
+        );
+        assert_eq!(monitor.estimated_skew(), Some(Duration::hours(1)));
+
+        let mut event = None;
+        for hash in 0..MAX_SAMPLES {
+            let next = monitor.record_tip_at(&base_node, &tip(100 + hash as u8, now.timestamp()), now);
+            event = event.or(next);
+        }
+        assert_eq!(event, Some(BaseNodeEvent::ClockSkewResolved));
+        assert_eq!(monitor.estimated_skew(), Some(Duration::zero()));
+    }
+}
@@ -33,8 +33,26 @@ pub struct BaseNodeServiceConfig {
     pub base_node_monitor_refresh_interval: Duration,
     /// The RPC client pool size
     pub base_node_rpc_pool_size: usize,
+    /// The number of sync RPC sessions, used for header sync and UTXO scanning
+    pub base_node_sync_rpc_pool_size: usize,
+    /// How long a single RPC request to the base node may take before it times out
+    #[serde(with = "serializers::seconds")]
+    pub base_node_rpc_request_timeout: Duration,
+    /// The maximum number of RPC clients handed out to wallet services per second, or 0 for no limit
+    pub base_node_rpc_rate_limit: u32,
     /// This is the size of the event channel used to communicate base node events to the wallet
     pub event_channel_size: usize,
+    /// How often the tips of the configured base nodes are cross-checked, if more than one is configured
+    #[serde(with = "serializers::seconds")]
+    pub chain_split_check_interval: Duration,
+    /// The number of blocks that the tips of the configured base nodes may differ by before a chain split is suspected
+    pub chain_split_height_threshold: u64,
+    /// How far the local clock may be off from the timestamps of the chain before a `ClockSkewDetected` event is
+    /// published
+    #[serde(with = "serializers::seconds")]
+    pub clock_skew_threshold: Duration,
+    /// If true, expiries are computed with the time of the chain instead of the local clock while the clock is skewed
+    pub correct_clock_skew: bool,
 }
 
 impl Default for BaseNodeServiceConfig {
@@ -42,7 +60,14 @@ impl Default for BaseNodeServiceConfig {
         Self {
             base_node_monitor_refresh_interval: Duration::from_secs(3),
             base_node_rpc_pool_size: 10,
+            base_node_sync_rpc_pool_size: 2,
+            base_node_rpc_request_timeout: Duration::from_secs(120),
+            base_node_rpc_rate_limit: 50,
             event_channel_size: 250,
+            chain_split_check_interval: Duration::from_secs(60),
+            chain_split_height_threshold: 5,
+            clock_skew_threshold: Duration::from_secs(600),
+            correct_clock_skew: true,
         }
     }
 }
@@ -25,7 +25,10 @@ use tari_comms_dht::outbound::DhtOutboundError;
 use tari_service_framework::reply_channel::TransportChannelError;
 use thiserror::Error;
 
-use crate::{connectivity_service::WalletConnectivityError, error::WalletStorageError};
+use crate::{
+    connectivity_service::WalletConnectivityError,
+    error::{ClassifyError, ErrorClass, ErrorKind, SuggestedAction, WalletStorageError},
+};
 
 #[derive(Debug, Error)]
 pub enum BaseNodeServiceError {
@@ -50,3 +53,22 @@ pub enum BaseNodeServiceError {
     #[error("Wallet connectivity error: `{0}`")]
     WalletConnectivityError(#[from] WalletConnectivityError),
 }
+
+impl ClassifyError for BaseNodeServiceError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            BaseNodeServiceError::WalletStorageError(e) => e.class(),
+            BaseNodeServiceError::NoBaseNodePeer => {
+                ErrorClass::new(ErrorKind::Connectivity, SuggestedAction::FixRequest)
+            },
+            BaseNodeServiceError::BaseNodeConnectivityError(_) |
+            BaseNodeServiceError::RpcError(_) |
+            BaseNodeServiceError::NoChainMetadata |
+            BaseNodeServiceError::OutboundError(_) |
+            BaseNodeServiceError::InvalidBaseNodeResponse(_) |
+            BaseNodeServiceError::WalletConnectivityError(_) => ErrorClass::CONNECTIVITY,
+            BaseNodeServiceError::TransportChannelError(_) => ErrorClass::UNAVAILABLE,
+            BaseNodeServiceError::UnexpectedApiResponse => ErrorClass::INTERNAL,
+        }
+    }
+}
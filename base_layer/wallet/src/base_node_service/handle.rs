@@ -22,31 +22,54 @@
 
 use std::{fmt, fmt::Formatter, sync::Arc, time::Duration};
 
-use tari_common_types::chain_metadata::ChainMetadata;
+use tari_common_types::{chain_metadata::ChainMetadata, types::BlockHash};
+use tari_core::{base_node::proto::wallet_rpc::FeeFloor, proof_of_work::NetworkHashRate};
 use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
 
-use super::{error::BaseNodeServiceError, service::BaseNodeState};
+use super::{chain_split::BaseNodeTip, error::BaseNodeServiceError, service::BaseNodeState};
+use crate::connectivity_service::BaseNodeIdentityMismatch;
 
 pub type BaseNodeEventSender = broadcast::Sender<Arc<BaseNodeEvent>>;
 pub type BaseNodeEventReceiver = broadcast::Receiver<Arc<BaseNodeEvent>>;
+pub type ChainEventSender = broadcast::Sender<Arc<ChainEvent>>;
+pub type ChainEventReceiver = broadcast::Receiver<Arc<ChainEvent>>;
 /// API Request enum
 #[derive(Debug)]
 pub enum BaseNodeServiceRequest {
     GetChainMetadata,
     GetBaseNodeLatency,
+    GetFeeFloor,
+    /// Estimate the network hash rate from the given number of most recent blocks
+    GetNetworkHashRate(u64),
+    GetClockSkew,
 }
 /// API Response enum
 #[derive(Debug)]
 pub enum BaseNodeServiceResponse {
     ChainMetadata(Option<ChainMetadata>),
     Latency(Option<Duration>),
+    FeeFloor(Option<FeeFloor>),
+    NetworkHashRate(NetworkHashRate),
+    ClockSkew(Option<chrono::Duration>),
 }
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum BaseNodeEvent {
     BaseNodeStateChanged(BaseNodeState),
     NewBlockDetected(u64),
+    /// The base node presented another identity than the one that is pinned for it. The new identity is only used
+    /// once it is trusted with `Wallet::trust_new_base_node_identity`.
+    BaseNodeIdentityChanged(BaseNodeIdentityMismatch),
+    /// The tips reported by the configured base nodes diverge, e.g. during a contentious reorg
+    ChainSplitSuspected(Vec<BaseNodeTip>),
+    /// The configured base nodes agree on the tip again after a suspected chain split
+    ChainSplitResolved,
+    /// The local clock is off from the timestamps of the chain by more than the threshold. The skew is the number of
+    /// seconds that the local clock is ahead, or behind if negative.
+    ClockSkewDetected(i64),
+    /// The local clock agrees with the timestamps of the chain again
+    ClockSkewResolved,
 }
 
 impl fmt::Display for BaseNodeEvent {
@@ -58,6 +81,42 @@ impl fmt::Display for BaseNodeEvent {
             BaseNodeEvent::NewBlockDetected(s) => {
                 write!(f, "NewBlockDetected: {}", s)
             },
+            BaseNodeEvent::BaseNodeIdentityChanged(mismatch) => {
+                write!(
+                    f,
+                    "BaseNodeIdentityChanged: expected {}, presented {}",
+                    mismatch.expected_public_key, mismatch.presented_public_key
+                )
+            },
+            BaseNodeEvent::ChainSplitSuspected(tips) => {
+                let heights = tips.iter().map(|tip| tip.height.to_string()).collect::<Vec<_>>();
+                write!(f, "ChainSplitSuspected: tips at heights {}", heights.join(", "))
+            },
+            BaseNodeEvent::ChainSplitResolved => write!(f, "ChainSplitResolved"),
+            BaseNodeEvent::ClockSkewDetected(skew) => write!(f, "ClockSkewDetected: {} s", skew),
+            BaseNodeEvent::ClockSkewResolved => write!(f, "ClockSkewResolved"),
+        }
+    }
+}
+
+/// Changes to the chain of the connected base node, published as soon as the base node monitor observes them
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ChainEvent {
+    /// The base node has a new best block at the given height
+    NewBlock(u64, BlockHash),
+    /// The previous best block is no longer part of the base node's chain. The tip moved from height `from` to height
+    /// `to` on a different chain.
+    Reorged { from: u64, to: u64 },
+    /// The base node started (`true`) or stopped (`false`) being in sync with the network
+    SyncStateChanged(bool),
+}
+
+impl fmt::Display for ChainEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainEvent::NewBlock(height, hash) => write!(f, "NewBlock: {} ({})", height, hash),
+            ChainEvent::Reorged { from, to } => write!(f, "Reorged: from {} to {}", from, to),
+            ChainEvent::SyncStateChanged(is_synced) => write!(f, "SyncStateChanged: Synced:{}", is_synced),
         }
     }
 }
@@ -68,16 +127,19 @@ impl fmt::Display for BaseNodeEvent {
 pub struct BaseNodeServiceHandle {
     handle: SenderService<BaseNodeServiceRequest, Result<BaseNodeServiceResponse, BaseNodeServiceError>>,
     event_stream_sender: BaseNodeEventSender,
+    chain_event_sender: ChainEventSender,
 }
 
 impl BaseNodeServiceHandle {
     pub fn new(
         handle: SenderService<BaseNodeServiceRequest, Result<BaseNodeServiceResponse, BaseNodeServiceError>>,
         event_stream_sender: BaseNodeEventSender,
+        chain_event_sender: ChainEventSender,
     ) -> Self {
         Self {
             handle,
             event_stream_sender,
+            chain_event_sender,
         }
     }
 
@@ -85,6 +147,11 @@ impl BaseNodeServiceHandle {
         self.event_stream_sender.subscribe()
     }
 
+    /// Subscribes to new block, reorg and sync state events of the connected base node
+    pub fn subscribe_chain_events(&self) -> ChainEventReceiver {
+        self.chain_event_sender.subscribe()
+    }
+
     pub async fn get_chain_metadata(&mut self) -> Result<Option<ChainMetadata>, BaseNodeServiceError> {
         match self.handle.call(BaseNodeServiceRequest::GetChainMetadata).await?? {
             BaseNodeServiceResponse::ChainMetadata(metadata) => Ok(metadata),
@@ -98,4 +165,35 @@ impl BaseNodeServiceHandle {
             _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// The lowest fees that the connected base node accepts transactions with. This is `None` until the base node
+    /// responded after connecting.
+    pub async fn get_fee_floor(&mut self) -> Result<Option<FeeFloor>, BaseNodeServiceError> {
+        match self.handle.call(BaseNodeServiceRequest::GetFeeFloor).await?? {
+            BaseNodeServiceResponse::FeeFloor(fee_floor) => Ok(fee_floor),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Has the base node estimate the hash rate and difficulty trend of the network from its `num_blocks` most recent
+    /// blocks, e.g. for mining dashboards
+    pub async fn get_network_hash_rate(&mut self, num_blocks: u64) -> Result<NetworkHashRate, BaseNodeServiceError> {
+        match self
+            .handle
+            .call(BaseNodeServiceRequest::GetNetworkHashRate(num_blocks))
+            .await??
+        {
+            BaseNodeServiceResponse::NetworkHashRate(estimate) => Ok(estimate),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// How far the local clock is ahead of the timestamps of the chain, or behind if negative. This is `None` until
+    /// enough new blocks were seen to estimate it.
+    pub async fn get_clock_skew(&mut self) -> Result<Option<chrono::Duration>, BaseNodeServiceError> {
+        match self.handle.call(BaseNodeServiceRequest::GetClockSkew).await?? {
+            BaseNodeServiceResponse::ClockSkew(skew) => Ok(skew),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
 }
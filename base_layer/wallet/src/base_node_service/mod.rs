@@ -20,14 +20,22 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod chain_split;
 pub mod config;
 pub mod error;
 pub mod handle;
 pub mod service;
 
+mod clock_skew;
 mod monitor;
 
+use std::sync::Arc;
+
 use log::*;
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{Peer, PeerManager},
+};
 use tari_service_framework::{
     async_trait,
     reply_channel,
@@ -38,7 +46,12 @@ use tari_service_framework::{
 use tokio::sync::broadcast;
 
 use crate::{
-    base_node_service::{config::BaseNodeServiceConfig, handle::BaseNodeServiceHandle, service::BaseNodeService},
+    base_node_service::{
+        chain_split::ChainSplitMonitor,
+        config::BaseNodeServiceConfig,
+        handle::BaseNodeServiceHandle,
+        service::BaseNodeService,
+    },
     connectivity_service::WalletConnectivityHandle,
     storage::database::{WalletBackend, WalletDatabase},
 };
@@ -50,13 +63,25 @@ where T: WalletBackend + 'static
 {
     config: BaseNodeServiceConfig,
     db: WalletDatabase<T>,
+    monitored_base_nodes: Vec<Peer>,
 }
 
 impl<T> BaseNodeServiceInitializer<T>
 where T: WalletBackend + 'static
 {
     pub fn new(config: BaseNodeServiceConfig, db: WalletDatabase<T>) -> Self {
-        Self { config, db }
+        Self {
+            config,
+            db,
+            monitored_base_nodes: Vec::new(),
+        }
+    }
+
+    /// The base nodes whose tips are cross-checked to detect chain splits. Nothing is monitored if fewer than two are
+    /// given.
+    pub fn with_monitored_base_nodes(mut self, base_nodes: Vec<Peer>) -> Self {
+        self.monitored_base_nodes = base_nodes;
+        self
     }
 }
 
@@ -71,27 +96,45 @@ where T: WalletBackend + 'static
 
         let (event_publisher, _) = broadcast::channel(self.config.event_channel_size);
 
-        let basenode_service_handle = BaseNodeServiceHandle::new(sender, event_publisher.clone());
+        let (chain_event_publisher, _) = broadcast::channel(self.config.event_channel_size);
+
+        let basenode_service_handle =
+            BaseNodeServiceHandle::new(sender, event_publisher.clone(), chain_event_publisher.clone());
 
         // Register handle before waiting for handles to be ready
         context.register_handle(basenode_service_handle);
 
         let config = self.config.clone();
         let db = self.db.clone();
+        let monitored_base_nodes = self.monitored_base_nodes.clone();
 
         context.spawn_when_ready(move |handles| async move {
             let wallet_connectivity = handles.expect_handle::<WalletConnectivityHandle>();
 
-            let result = BaseNodeService::new(
-                config,
+            let service = BaseNodeService::new(
+                config.clone(),
                 request_stream,
                 wallet_connectivity,
-                event_publisher,
+                event_publisher.clone(),
+                chain_event_publisher,
                 handles.get_shutdown_signal(),
                 db,
-            )
-            .start()
-            .await;
+            );
+
+            if monitored_base_nodes.len() > 1 {
+                let monitor = ChainSplitMonitor::new(
+                    config,
+                    monitored_base_nodes,
+                    handles.expect_handle::<ConnectivityRequester>(),
+                    handles.expect_handle::<Arc<PeerManager>>(),
+                    event_publisher,
+                    service.clock_skew_monitor(),
+                    handles.get_shutdown_signal(),
+                );
+                tokio::spawn(monitor.run());
+            }
+
+            let result = service.start().await;
 
             info!(
                 target: LOG_TARGET,
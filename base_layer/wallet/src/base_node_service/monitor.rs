@@ -32,11 +32,13 @@ use futures::{future, future::Either};
 use log::*;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::protocol::rpc::RpcError;
+use tari_core::{base_node::proto::wallet_rpc::FeeFloor, blocks::BlockHeader};
 use tokio::{sync::RwLock, time};
 
 use crate::{
     base_node_service::{
-        handle::{BaseNodeEvent, BaseNodeEventSender},
+        clock_skew::ClockSkewMonitor,
+        handle::{BaseNodeEvent, BaseNodeEventSender, ChainEvent, ChainEventSender},
         service::BaseNodeState,
     },
     connectivity_service::WalletConnectivityInterface,
@@ -52,6 +54,8 @@ pub struct BaseNodeMonitor<TBackend, TWalletConnectivity> {
     db: WalletDatabase<TBackend>,
     wallet_connectivity: TWalletConnectivity,
     event_publisher: BaseNodeEventSender,
+    chain_event_publisher: ChainEventSender,
+    clock_skew: ClockSkewMonitor,
 }
 
 impl<TBackend, TWalletConnectivity> BaseNodeMonitor<TBackend, TWalletConnectivity>
@@ -65,6 +69,8 @@ where
         db: WalletDatabase<TBackend>,
         wallet_connectivity: TWalletConnectivity,
         event_publisher: BaseNodeEventSender,
+        chain_event_publisher: ChainEventSender,
+        clock_skew: ClockSkewMonitor,
     ) -> Self {
         Self {
             interval,
@@ -72,6 +78,8 @@ where
             db,
             wallet_connectivity,
             event_publisher,
+            chain_event_publisher,
+            clock_skew,
         }
     }
 
@@ -95,6 +103,7 @@ where
                         is_synced: None,
                         updated: None,
                         latency: None,
+                        fee_floor: None,
                     })
                     .await;
                     continue;
@@ -163,19 +172,57 @@ where
                 timer.elapsed().as_millis()
             );
 
+            // The previous best block is only still part of the chain if the header at its height has the same hash
+            let previous_state = self.state.read().await.clone();
+            let previous_tip_on_chain = match previous_state.chain_metadata.as_ref() {
+                Some(previous) if chain_metadata.height_of_longest_chain() > previous.height_of_longest_chain() => {
+                    let header = client.get_header_by_height(previous.height_of_longest_chain()).await?;
+                    let header =
+                        BlockHeader::try_from(header).map_err(BaseNodeMonitorError::InvalidBaseNodeResponse)?;
+                    header.hash() == *previous.best_block()
+                },
+                Some(previous) => previous.best_block() == chain_metadata.best_block(),
+                None => true,
+            };
+
+            let fee_floor = match previous_state.fee_floor {
+                Some(fee_floor) => fee_floor,
+                None => match client.get_fee_floor().await {
+                    Ok(response) => FeeFloor::from(response),
+                    Err(e) => {
+                        // Base nodes of older releases do not report their minimum relay fee
+                        debug!(
+                            target: LOG_TARGET,
+                            "Base node {} did not report its fee floor ({}), using the consensus floor",
+                            base_node_id,
+                            e
+                        );
+                        FeeFloor::consensus()
+                    },
+                },
+            };
+
             self.db.set_chain_metadata(chain_metadata.clone())?;
+            self.clock_skew.record_tip(&base_node_id, &chain_metadata);
 
             let is_synced = tip_info.is_synced;
             let height_of_longest_chain = chain_metadata.height_of_longest_chain();
+            let chain_events = chain_events(&previous_state, &chain_metadata, is_synced, previous_tip_on_chain);
 
             self.update_state(BaseNodeState {
                 chain_metadata: Some(chain_metadata),
                 is_synced: Some(is_synced),
                 updated: Some(Utc::now().naive_utc()),
                 latency: Some(latency),
+                fee_floor: Some(fee_floor),
             })
             .await;
 
+            for event in chain_events {
+                debug!(target: LOG_TARGET, "Base node chain event: {}", event);
+                let _size = self.chain_event_publisher.send(Arc::new(event));
+            }
+
             debug!(
                 target: LOG_TARGET,
                 "Base node {} Tip: {} ({}) Latency: {} ms",
@@ -221,6 +268,33 @@ where
     }
 }
 
+/// The chain events for a base node moving from the `previous` state to `chain_metadata`
+fn chain_events(
+    previous: &BaseNodeState,
+    chain_metadata: &ChainMetadata,
+    is_synced: bool,
+    previous_tip_on_chain: bool,
+) -> Vec<ChainEvent> {
+    let mut events = Vec::new();
+    if previous.is_synced != Some(is_synced) {
+        events.push(ChainEvent::SyncStateChanged(is_synced));
+    }
+    let height = chain_metadata.height_of_longest_chain();
+    let best_block = *chain_metadata.best_block();
+    match previous.chain_metadata.as_ref() {
+        Some(previous) if !previous_tip_on_chain => {
+            events.push(ChainEvent::Reorged {
+                from: previous.height_of_longest_chain(),
+                to: height,
+            });
+            events.push(ChainEvent::NewBlock(height, best_block));
+        },
+        Some(previous) if *previous.best_block() == best_block => {},
+        _ => events.push(ChainEvent::NewBlock(height, best_block)),
+    }
+    events
+}
+
 #[derive(thiserror::Error, Debug)]
 enum BaseNodeMonitorError {
     #[error("Node is shutting down")]
@@ -245,3 +319,39 @@ where
         Either::Right((v, _)) => Some(v),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::BlockHash;
+
+    use super::*;
+
+    fn metadata(height: u64, hash: u8) -> ChainMetadata {
+        ChainMetadata::new(height, BlockHash::from([hash; 32]), 0, 0, 1, 0)
+    }
+
+    fn state(height: u64, hash: u8) -> BaseNodeState {
+        BaseNodeState {
+            chain_metadata: Some(metadata(height, hash)),
+            is_synced: Some(true),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_emits_chain_events() {
+        let hash = BlockHash::from([2u8; 32]);
+        assert_eq!(chain_events(&state(1, 1), &metadata(1, 1), true, true), vec![]);
+        assert_eq!(chain_events(&state(1, 1), &metadata(2, 2), true, true), vec![
+            ChainEvent::NewBlock(2, hash)
+        ]);
+        assert_eq!(chain_events(&state(5, 1), &metadata(4, 2), true, false), vec![
+            ChainEvent::Reorged { from: 5, to: 4 },
+            ChainEvent::NewBlock(4, hash)
+        ]);
+        assert_eq!(
+            chain_events(&BaseNodeState::default(), &metadata(2, 2), false, true),
+            vec![ChainEvent::SyncStateChanged(false), ChainEvent::NewBlock(2, hash)]
+        );
+    }
+}
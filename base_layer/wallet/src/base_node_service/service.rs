@@ -26,6 +26,11 @@ use chrono::NaiveDateTime;
 use futures::{future, StreamExt};
 use log::*;
 use tari_common_types::chain_metadata::ChainMetadata;
+use tari_core::{
+    base_node::proto::wallet_rpc::FeeFloor,
+    proof_of_work::NetworkHashRate,
+    proto::base_node::NetworkHashRateRequest,
+};
 use tari_service_framework::reply_channel::Receiver;
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::RwLock;
@@ -33,11 +38,11 @@ use tokio::sync::RwLock;
 use super::{
     config::BaseNodeServiceConfig,
     error::BaseNodeServiceError,
-    handle::{BaseNodeEventSender, BaseNodeServiceRequest, BaseNodeServiceResponse},
+    handle::{BaseNodeEvent, BaseNodeEventSender, BaseNodeServiceRequest, BaseNodeServiceResponse, ChainEventSender},
 };
 use crate::{
-    base_node_service::monitor::BaseNodeMonitor,
-    connectivity_service::WalletConnectivityHandle,
+    base_node_service::{clock_skew::ClockSkewMonitor, monitor::BaseNodeMonitor},
+    connectivity_service::{WalletConnectivityHandle, WalletConnectivityInterface},
     storage::database::{WalletBackend, WalletDatabase},
 };
 
@@ -50,6 +55,8 @@ pub struct BaseNodeState {
     pub is_synced: Option<bool>,
     pub updated: Option<NaiveDateTime>,
     pub latency: Option<Duration>,
+    /// The lowest fees that the base node accepts, fetched once per connection
+    pub fee_floor: Option<FeeFloor>,
 }
 
 /// The base node service is responsible for handling requests to be sent to the connected base node.
@@ -60,8 +67,10 @@ where T: WalletBackend + 'static
     request_stream: Option<Receiver<BaseNodeServiceRequest, Result<BaseNodeServiceResponse, BaseNodeServiceError>>>,
     wallet_connectivity: WalletConnectivityHandle,
     event_publisher: BaseNodeEventSender,
+    chain_event_publisher: ChainEventSender,
     shutdown_signal: ShutdownSignal,
     state: Arc<RwLock<BaseNodeState>>,
+    clock_skew: ClockSkewMonitor,
     db: WalletDatabase<T>,
 }
 
@@ -73,20 +82,29 @@ where T: WalletBackend + 'static
         request_stream: Receiver<BaseNodeServiceRequest, Result<BaseNodeServiceResponse, BaseNodeServiceError>>,
         wallet_connectivity: WalletConnectivityHandle,
         event_publisher: BaseNodeEventSender,
+        chain_event_publisher: ChainEventSender,
         shutdown_signal: ShutdownSignal,
         db: WalletDatabase<T>,
     ) -> Self {
+        let clock_skew = ClockSkewMonitor::new(&config, event_publisher.clone());
         Self {
             config,
             request_stream: Some(request_stream),
             wallet_connectivity,
             event_publisher,
+            chain_event_publisher,
             shutdown_signal,
             state: Default::default(),
+            clock_skew,
             db,
         }
     }
 
+    /// The clock skew estimate, which the other monitors of the service add the tips of their base nodes to
+    pub(super) fn clock_skew_monitor(&self) -> ClockSkewMonitor {
+        self.clock_skew.clone()
+    }
+
     /// Returns the last known state of the connected base node.
     pub async fn get_state(&self) -> BaseNodeState {
         self.state.read().await.clone()
@@ -95,6 +113,7 @@ where T: WalletBackend + 'static
     /// Starts the service.
     pub async fn start(mut self) -> Result<(), BaseNodeServiceError> {
         self.spawn_monitor();
+        self.spawn_identity_mismatch_publisher();
 
         let mut request_stream = self
             .request_stream
@@ -106,6 +125,17 @@ where T: WalletBackend + 'static
         while let Some(request_context) = request_stream.next().await {
             // Incoming requests
             let (request, reply_tx) = request_context.split();
+            if let BaseNodeServiceRequest::GetNetworkHashRate(num_blocks) = request {
+                // Connecting to the base node can take a while, which should not hold up the other requests
+                let wallet_connectivity = self.wallet_connectivity.clone();
+                tokio::spawn(async move {
+                    let response = get_network_hash_rate(wallet_connectivity, num_blocks)
+                        .await
+                        .map(BaseNodeServiceResponse::NetworkHashRate);
+                    let _result = reply_tx.send(response);
+                });
+                continue;
+            }
             let response = self.handle_request(request).await.map_err(|e| {
                 error!(target: LOG_TARGET, "Error handling request: {:?}", e);
                 e
@@ -130,6 +160,8 @@ where T: WalletBackend + 'static
             self.db.clone(),
             self.wallet_connectivity.clone(),
             self.event_publisher.clone(),
+            self.chain_event_publisher.clone(),
+            self.clock_skew.clone(),
         );
 
         let shutdown_signal = self.shutdown_signal.clone();
@@ -140,6 +172,30 @@ where T: WalletBackend + 'static
         });
     }
 
+    /// Publishes a `BaseNodeIdentityChanged` event every time the base node presents an identity it was not set with
+    fn spawn_identity_mismatch_publisher(&self) {
+        let mut mismatch_watch = self.wallet_connectivity.get_base_node_identity_mismatch_watch();
+        let event_publisher = self.event_publisher.clone();
+        let mut shutdown_signal = self.shutdown_signal.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    result = mismatch_watch.changed() => {
+                        if result.is_err() {
+                            break;
+                        }
+                        let mismatch = mismatch_watch.borrow().clone();
+                        if let Some(mismatch) = mismatch {
+                            let event = BaseNodeEvent::BaseNodeIdentityChanged(mismatch);
+                            let _size = event_publisher.send(Arc::new(event));
+                        }
+                    },
+                    _ = shutdown_signal.wait() => break,
+                }
+            }
+        });
+    }
+
     /// This handler is called when requests arrive from the various streams
     async fn handle_request(
         &mut self,
@@ -161,6 +217,31 @@ where T: WalletBackend + 'static
             BaseNodeServiceRequest::GetBaseNodeLatency => {
                 Ok(BaseNodeServiceResponse::Latency(self.state.read().await.latency))
             },
+            BaseNodeServiceRequest::GetFeeFloor => {
+                Ok(BaseNodeServiceResponse::FeeFloor(self.state.read().await.fee_floor))
+            },
+            BaseNodeServiceRequest::GetNetworkHashRate(num_blocks) => {
+                get_network_hash_rate(self.wallet_connectivity.clone(), num_blocks)
+                    .await
+                    .map(BaseNodeServiceResponse::NetworkHashRate)
+            },
+            BaseNodeServiceRequest::GetClockSkew => {
+                Ok(BaseNodeServiceResponse::ClockSkew(self.clock_skew.estimated_skew()))
+            },
         }
     }
 }
+
+async fn get_network_hash_rate(
+    mut wallet_connectivity: WalletConnectivityHandle,
+    num_blocks: u64,
+) -> Result<NetworkHashRate, BaseNodeServiceError> {
+    let mut client = wallet_connectivity
+        .obtain_base_node_wallet_rpc_client()
+        .await
+        .ok_or(BaseNodeServiceError::NoBaseNodePeer)?;
+    let response = client
+        .get_network_hash_rate(NetworkHashRateRequest { num_blocks })
+        .await?;
+    Ok(response.into())
+}
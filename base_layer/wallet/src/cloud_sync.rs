@@ -0,0 +1,352 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! End-to-end encrypted synchronisation of the non-sensitive state of a wallet between the devices of its owner. The
+//! contacts, transaction tags and read state of a wallet are combined into a [SyncSnapshot], encrypted with a key
+//! derived from the master seed and stored in a [SyncBlobStore]. Every device that was restored from the same seed
+//! derives the same key and blob id, so it can merge the snapshot into its own state and upload the result, see
+//! [Wallet::cloud_sync](crate::Wallet::cloud_sync).
+//!
+//! Merging only ever adds entries, so devices converge no matter in which order they sync. Removing a contact or tag
+//! on one device does not remove it from the others, and a contact that is known to both devices keeps the alias of
+//! the device that synced last. Keys, outputs and transactions are never part of a snapshot.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use serde::{Deserialize, Serialize};
+use tari_key_manager::cipher_seed::CipherSeed;
+use tari_utilities::{hex::Hex, ByteArray};
+
+use crate::{
+    error::WalletError,
+    portable_dump::PortableContact,
+    util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce},
+    WalletSecretKeysDomainHasher,
+};
+
+/// The version of the snapshot format
+const SYNC_SNAPSHOT_VERSION: u32 = 1;
+/// The domain that snapshots are encrypted under
+const SYNC_SNAPSHOT_DOMAIN: &[u8] = b"CLOUD_SYNC_SNAPSHOT";
+
+/// Stores the encrypted snapshots of wallets. Blobs are opaque to the store, and the blob id of a wallet does not
+/// reveal anything about the wallet.
+#[async_trait]
+pub trait SyncBlobStore: Send + Sync {
+    /// Fetches the blob stored under `id`, or `None` if nothing was stored yet
+    async fn fetch(&self, id: &str) -> Result<Option<Vec<u8>>, WalletError>;
+    /// Stores the blob under `id`, replacing the previous blob
+    async fn store(&self, id: &str, blob: Vec<u8>) -> Result<(), WalletError>;
+}
+
+/// A [SyncBlobStore] that keeps the blobs in memory. Clones share the same blobs, which makes it useful to sync
+/// wallets that run in the same process, e.g. in tests.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBlobStore {
+    blobs: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+#[async_trait]
+impl SyncBlobStore for MemoryBlobStore {
+    async fn fetch(&self, id: &str) -> Result<Option<Vec<u8>>, WalletError> {
+        let blobs = self.blobs.lock().map_err(|_| poisoned())?;
+        Ok(blobs.get(id).cloned())
+    }
+
+    async fn store(&self, id: &str, blob: Vec<u8>) -> Result<(), WalletError> {
+        let mut blobs = self.blobs.lock().map_err(|_| poisoned())?;
+        blobs.insert(id.to_string(), blob);
+        Ok(())
+    }
+}
+
+/// A [SyncBlobStore] backed by a generic HTTP object store. Blobs are fetched with `GET {base_url}/{id}` and stored
+/// with `PUT {base_url}/{id}`, and a missing blob is expected to return `404 Not Found`.
+#[cfg(feature = "http_sync")]
+#[derive(Debug, Clone)]
+pub struct HttpBlobStore {
+    client: reqwest::Client,
+    base_url: String,
+    auth_token: Option<String>,
+}
+
+#[cfg(feature = "http_sync")]
+impl HttpBlobStore {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            auth_token: None,
+        }
+    }
+
+    /// Sends the token as a bearer token with every request
+    pub fn with_auth_token(mut self, auth_token: String) -> Self {
+        self.auth_token = Some(auth_token);
+        self
+    }
+
+    fn request(&self, method: reqwest::Method, id: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, format!("{}/{}", self.base_url, id));
+        match &self.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[cfg(feature = "http_sync")]
+#[async_trait]
+impl SyncBlobStore for HttpBlobStore {
+    async fn fetch(&self, id: &str) -> Result<Option<Vec<u8>>, WalletError> {
+        let response = self
+            .request(reqwest::Method::GET, id)
+            .send()
+            .await
+            .map_err(http_error)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let blob = response
+            .error_for_status()
+            .map_err(http_error)?
+            .bytes()
+            .await
+            .map_err(http_error)?;
+        Ok(Some(blob.to_vec()))
+    }
+
+    async fn store(&self, id: &str, blob: Vec<u8>) -> Result<(), WalletError> {
+        self.request(reqwest::Method::PUT, id)
+            .body(blob)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(http_error)?;
+        Ok(())
+    }
+}
+
+/// The key that the snapshots of a wallet are encrypted with, and the id they are stored under. Both are derived from
+/// the master seed, so every device of the owner derives the same key.
+#[derive(Clone)]
+pub struct SyncKey {
+    cipher: XChaCha20Poly1305,
+    blob_id: String,
+}
+
+impl SyncKey {
+    pub fn from_seed(master_seed: &CipherSeed) -> Self {
+        let key = WalletSecretKeysDomainHasher::new_with_label("cloud_sync_key")
+            .chain(master_seed.entropy())
+            .finalize();
+        let blob_id = WalletSecretKeysDomainHasher::new_with_label("cloud_sync_blob_id")
+            .chain(master_seed.entropy())
+            .finalize();
+        Self {
+            cipher: XChaCha20Poly1305::new(Key::from_slice(key.as_ref())),
+            blob_id: blob_id.as_ref().to_hex(),
+        }
+    }
+
+    /// The id that the snapshots are stored under
+    pub fn blob_id(&self) -> &str {
+        &self.blob_id
+    }
+}
+
+/// The non-sensitive state of a wallet that is synced between devices
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncSnapshot {
+    version: u32,
+    /// The contacts, sorted by public key
+    pub contacts: Vec<PortableContact>,
+    /// The tags of every tagged transaction, keyed by transaction id
+    pub transaction_tags: BTreeMap<u64, BTreeSet<String>>,
+    /// The ids of the transactions that the owner has seen
+    pub read_transactions: BTreeSet<u64>,
+}
+
+impl SyncSnapshot {
+    pub fn new(
+        mut contacts: Vec<PortableContact>,
+        transaction_tags: BTreeMap<u64, BTreeSet<String>>,
+        read_transactions: BTreeSet<u64>,
+    ) -> Self {
+        contacts.sort_by(|a, b| a.public_key.as_bytes().cmp(b.public_key.as_bytes()));
+        Self {
+            version: SYNC_SNAPSHOT_VERSION,
+            contacts,
+            transaction_tags,
+            read_transactions,
+        }
+    }
+
+    /// Adds the entries of `other` that this snapshot does not have yet. Contacts that are in both snapshots keep the
+    /// alias they have in this snapshot.
+    pub fn merge(&mut self, other: &SyncSnapshot) {
+        for contact in &other.contacts {
+            if let Err(pos) = self
+                .contacts
+                .binary_search_by(|c| c.public_key.as_bytes().cmp(contact.public_key.as_bytes()))
+            {
+                self.contacts.insert(pos, contact.clone());
+            }
+        }
+        for (tx_id, tags) in &other.transaction_tags {
+            self.transaction_tags
+                .entry(*tx_id)
+                .or_default()
+                .extend(tags.iter().cloned());
+        }
+        self.read_transactions.extend(other.read_transactions.iter().copied());
+    }
+
+    pub fn encrypt(&self, key: &SyncKey) -> Result<Vec<u8>, WalletError> {
+        let json = serde_json::to_vec(self).map_err(|e| WalletError::CloudSyncError(e.to_string()))?;
+        encrypt_bytes_integral_nonce(&key.cipher, SYNC_SNAPSHOT_DOMAIN.to_vec(), json)
+            .map_err(WalletError::CloudSyncError)
+    }
+
+    pub fn decrypt(key: &SyncKey, blob: Vec<u8>) -> Result<Self, WalletError> {
+        let json = decrypt_bytes_integral_nonce(&key.cipher, SYNC_SNAPSHOT_DOMAIN.to_vec(), blob)
+            .map_err(WalletError::CloudSyncError)?;
+        let snapshot = serde_json::from_slice::<Self>(&json).map_err(|e| WalletError::CloudSyncError(e.to_string()))?;
+        if snapshot.version != SYNC_SNAPSHOT_VERSION {
+            return Err(WalletError::CloudSyncError(format!(
+                "Unsupported snapshot version {}",
+                snapshot.version
+            )));
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Syncs snapshots of a wallet through a [SyncBlobStore]
+pub struct CloudSyncEngine<S> {
+    store: S,
+    key: SyncKey,
+}
+
+impl<S: SyncBlobStore> CloudSyncEngine<S> {
+    pub fn new(store: S, key: SyncKey) -> Self {
+        Self { store, key }
+    }
+
+    /// Fetches and decrypts the snapshot of the wallet, or returns `None` if no device has synced yet
+    pub async fn pull(&self) -> Result<Option<SyncSnapshot>, WalletError> {
+        match self.store.fetch(self.key.blob_id()).await? {
+            Some(blob) => SyncSnapshot::decrypt(&self.key, blob).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Encrypts and stores the snapshot, replacing the snapshot in the store
+    pub async fn push(&self, snapshot: &SyncSnapshot) -> Result<(), WalletError> {
+        let blob = snapshot.encrypt(&self.key)?;
+        self.store.store(self.key.blob_id(), blob).await
+    }
+}
+
+/// The number of entries that [Wallet::cloud_sync](crate::Wallet::cloud_sync) added to the wallet from other devices
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub contacts: usize,
+    pub transaction_tags: usize,
+    pub read_transactions: usize,
+    /// Whether the merged snapshot was uploaded, which is skipped if the store already had it
+    pub uploaded: bool,
+}
+
+fn poisoned() -> WalletError {
+    WalletError::CloudSyncError("The blob store lock is poisoned".to_string())
+}
+
+#[cfg(feature = "http_sync")]
+fn http_error(err: reqwest::Error) -> WalletError {
+    WalletError::CloudSyncError(err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_comms::types::CommsPublicKey;
+    use tari_crypto::keys::PublicKey;
+
+    use super::*;
+
+    fn contact(alias: &str) -> PortableContact {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        PortableContact {
+            alias: alias.to_string(),
+            public_key,
+            last_seen: None,
+        }
+    }
+
+    fn tags(tx_id: u64, tags: &[&str]) -> BTreeMap<u64, BTreeSet<String>> {
+        let mut map = BTreeMap::new();
+        map.insert(tx_id, tags.iter().map(|t| t.to_string()).collect());
+        map
+    }
+
+    #[test]
+    fn it_merges_snapshots_in_any_order() {
+        let alice = contact("alice");
+        let bob = contact("bob");
+        let mut renamed_bob = bob.clone();
+        renamed_bob.alias = "Bobby".to_string();
+
+        let a = SyncSnapshot::new(vec![alice, bob], tags(1, &["rent"]), vec![1, 2].into_iter().collect());
+        let b = SyncSnapshot::new(
+            vec![renamed_bob, contact("carol")],
+            tags(1, &["home"]),
+            vec![2, 3].into_iter().collect(),
+        );
+
+        let mut ab = a.clone();
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+        assert_eq!(ab.contacts.len(), 3);
+        assert_eq!(ab.transaction_tags, tags(1, &["home", "rent"]));
+        assert_eq!(ab.read_transactions, vec![1, 2, 3].into_iter().collect());
+        assert_eq!(ab.transaction_tags, ba.transaction_tags);
+        assert_eq!(ab.read_transactions, ba.read_transactions);
+        // A contact that is known to both keeps the local alias
+        assert!(ab.contacts.iter().any(|c| c.alias == "bob"));
+        assert!(ba.contacts.iter().any(|c| c.alias == "Bobby"));
+
+        // Merging again changes nothing
+        let mut again = ab.clone();
+        again.merge(&b);
+        assert_eq!(again, ab);
+    }
+
+    #[test]
+    fn it_only_decrypts_with_the_key_of_the_wallet() {
+        let seed = CipherSeed::new();
+        let key = SyncKey::from_seed(&seed);
+        assert_eq!(key.blob_id(), SyncKey::from_seed(&seed).blob_id());
+
+        let snapshot = SyncSnapshot::new(vec![contact("alice")], tags(7, &["rent"]), BTreeSet::new());
+        let blob = snapshot.encrypt(&key).unwrap();
+        assert_eq!(SyncSnapshot::decrypt(&key, blob.clone()).unwrap(), snapshot);
+
+        let other_key = SyncKey::from_seed(&CipherSeed::new());
+        assert_ne!(key.blob_id(), other_key.blob_id());
+        assert!(matches!(
+            SyncSnapshot::decrypt(&other_key, blob),
+            Err(WalletError::CloudSyncError(_))
+        ));
+
+        let mut unsupported = snapshot;
+        unsupported.version = SYNC_SNAPSHOT_VERSION + 1;
+        assert!(SyncSnapshot::decrypt(&key, unsupported.encrypt(&key).unwrap()).is_err());
+    }
+}
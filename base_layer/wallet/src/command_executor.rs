@@ -0,0 +1,358 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The commands that automation frontends run against a wallet, e.g. the command mode of the console wallet, scripts
+//! and the gRPC server. A [WalletCommand] is executed by a [CommandExecutor] and results in a typed [CommandResult], so
+//! the frontends share the orchestration of the wallet services and only differ in how they present the result.
+
+use std::collections::HashMap;
+
+use log::*;
+use tari_common_types::{
+    emoji::EmojiId,
+    transaction::TxId,
+    types::{FixedHash, PublicKey},
+};
+use tari_comms::{multiaddr::Multiaddr, peer_manager::Peer};
+use tari_comms_dht::envelope::NodeDestination;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction_components::{OutputFeatures, UnblindedOutput},
+};
+
+use crate::{
+    contacts_service::storage::database::ContactsBackend,
+    error::WalletError,
+    key_manager_service::storage::database::KeyManagerBackend,
+    output_manager_service::{
+        service::{Balance, CoinbaseStats},
+        storage::database::OutputManagerBackend,
+    },
+    storage::database::WalletBackend,
+    transaction_service::storage::{database::TransactionBackend, models::CompletedTransaction},
+    Wallet,
+};
+
+const LOG_TARGET: &str = "wallet::command_executor";
+
+/// How a payment is made to its recipient
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentKind {
+    /// Negotiated with the recipient, who must come online to receive it
+    Interactive,
+    /// Sent to the public key of the recipient without any interaction
+    OneSided,
+    /// Sent one-sided to a stealth address derived from the public key of the recipient
+    OneSidedToStealthAddress,
+}
+
+#[derive(Debug, Clone)]
+pub enum WalletCommand {
+    GetBalance,
+    SendTari {
+        kind: PaymentKind,
+        destination: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    CoinSplit {
+        amount_per_split: MicroTari,
+        num_splits: usize,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    DiscoverPeer(PublicKey),
+    Whois(PublicKey),
+    GetUnspentOutputs,
+    GetSpentOutputs,
+    CountUtxos,
+    GetCoinbaseStats,
+    SetBaseNode {
+        public_key: PublicKey,
+        address: Multiaddr,
+    },
+    InitShaAtomicSwap {
+        destination: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    FinaliseShaAtomicSwap {
+        output_hash: FixedHash,
+        pre_image: PublicKey,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    ClaimShaAtomicSwapRefund {
+        output_hash: FixedHash,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    RevalidateWalletDb,
+    TagTransaction {
+        tx_id: TxId,
+        tag: String,
+    },
+    UntagTransaction {
+        tx_id: TxId,
+        tag: String,
+    },
+    /// The completed transactions in chronological order, optionally only the ones with a tag
+    GetCompletedTransactions {
+        tag: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum CommandResult {
+    /// The command has no result beyond having succeeded
+    Done,
+    Balance(Balance),
+    /// A transaction was created and is on its way to the recipient or the mempool
+    TransactionSent(TxId),
+    ShaAtomicSwapInitiated {
+        tx_id: TxId,
+        pre_image: PublicKey,
+        output_hash: FixedHash,
+    },
+    PeerDiscovered(Box<Peer>),
+    Identity {
+        public_key: PublicKey,
+        emoji_id: EmojiId,
+    },
+    Outputs(Vec<UnblindedOutput>),
+    UtxoStats(UtxoStats),
+    CoinbaseStats(CoinbaseStats),
+    Transactions {
+        transactions: Vec<CompletedTransaction>,
+        /// The tags of the transactions that have any
+        tags: HashMap<TxId, Vec<String>>,
+    },
+}
+
+/// The number and the values of the unspent outputs of the wallet
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UtxoStats {
+    pub count: usize,
+    pub total: MicroTari,
+    pub min: Option<MicroTari>,
+    pub max: Option<MicroTari>,
+    /// The mean value, rounded to the nearest µT
+    pub average: Option<MicroTari>,
+}
+
+impl UtxoStats {
+    pub fn from_values<I: IntoIterator<Item = MicroTari>>(values: I) -> Self {
+        let mut stats = Self::default();
+        for value in values {
+            stats.count += 1;
+            stats.total += value;
+            stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+            stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+        }
+        if stats.count > 0 {
+            let average = stats.total.as_u64() as f64 / stats.count as f64;
+            stats.average = Some(MicroTari(average.round() as u64));
+        }
+        stats
+    }
+}
+
+/// Executes [WalletCommand]s against a wallet. The executor holds a clone of the wallet, so it can be cloned and
+/// moved into tasks, e.g. to run commands concurrently.
+#[derive(Clone)]
+pub struct CommandExecutor<T, U, V, W, X> {
+    wallet: Wallet<T, U, V, W, X>,
+}
+
+impl<T, U, V, W, X> CommandExecutor<T, U, V, W, X>
+where
+    T: WalletBackend + 'static,
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+    W: ContactsBackend + 'static,
+    X: KeyManagerBackend + 'static,
+{
+    pub fn new(wallet: Wallet<T, U, V, W, X>) -> Self {
+        Self { wallet }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub async fn execute(&mut self, command: WalletCommand) -> Result<CommandResult, WalletError> {
+        debug!(target: LOG_TARGET, "Executing {:?}", command);
+        let wallet = &mut self.wallet;
+        let result = match command {
+            WalletCommand::GetBalance => CommandResult::Balance(wallet.output_manager_service.get_balance().await?),
+            WalletCommand::SendTari {
+                kind,
+                destination,
+                amount,
+                fee_per_gram,
+                message,
+            } => {
+                let transaction_service = &mut wallet.transaction_service;
+                let features = OutputFeatures::default();
+                let tx_id = match kind {
+                    PaymentKind::Interactive => {
+                        transaction_service
+                            .send_transaction(destination, amount, features, fee_per_gram, message, None)
+                            .await?
+                    },
+                    PaymentKind::OneSided => {
+                        transaction_service
+                            .send_one_sided_transaction(destination, amount, features, fee_per_gram, message)
+                            .await?
+                    },
+                    PaymentKind::OneSidedToStealthAddress => {
+                        transaction_service
+                            .send_one_sided_to_stealth_address_transaction(
+                                destination,
+                                amount,
+                                features,
+                                fee_per_gram,
+                                message,
+                            )
+                            .await?
+                    },
+                };
+                CommandResult::TransactionSent(tx_id)
+            },
+            WalletCommand::CoinSplit {
+                amount_per_split,
+                num_splits,
+                fee_per_gram,
+                message,
+            } => CommandResult::TransactionSent(
+                wallet
+                    .coin_split(vec![], amount_per_split, num_splits, fee_per_gram, message)
+                    .await?,
+            ),
+            WalletCommand::DiscoverPeer(public_key) => {
+                let peer = wallet
+                    .dht_service
+                    .discovery_service_requester()
+                    .discover_peer(public_key.clone(), NodeDestination::PublicKey(Box::new(public_key)))
+                    .await?;
+                CommandResult::PeerDiscovered(Box::new(peer))
+            },
+            WalletCommand::Whois(public_key) => CommandResult::Identity {
+                emoji_id: EmojiId::from_pubkey(&public_key),
+                public_key,
+            },
+            WalletCommand::GetUnspentOutputs => {
+                CommandResult::Outputs(wallet.output_manager_service.get_unspent_outputs().await?)
+            },
+            WalletCommand::GetSpentOutputs => {
+                CommandResult::Outputs(wallet.output_manager_service.get_spent_outputs().await?)
+            },
+            WalletCommand::CountUtxos => {
+                let outputs = wallet.output_manager_service.get_unspent_outputs().await?;
+                CommandResult::UtxoStats(UtxoStats::from_values(outputs.iter().map(|output| output.value)))
+            },
+            WalletCommand::GetCoinbaseStats => {
+                CommandResult::CoinbaseStats(wallet.output_manager_service.get_coinbase_stats().await?)
+            },
+            WalletCommand::SetBaseNode { public_key, address } => {
+                wallet.set_base_node_peer(public_key, address).await?;
+                CommandResult::Done
+            },
+            WalletCommand::InitShaAtomicSwap {
+                destination,
+                amount,
+                fee_per_gram,
+                message,
+            } => {
+                let (tx_id, pre_image, output) = wallet
+                    .transaction_service
+                    .send_sha_atomic_swap_transaction(destination, amount, fee_per_gram, message)
+                    .await?;
+                CommandResult::ShaAtomicSwapInitiated {
+                    tx_id,
+                    pre_image,
+                    output_hash: output.hash(),
+                }
+            },
+            WalletCommand::FinaliseShaAtomicSwap {
+                output_hash,
+                pre_image,
+                fee_per_gram,
+                message,
+            } => {
+                let (tx_id, _fee, amount, tx) = wallet
+                    .output_manager_service
+                    .create_claim_sha_atomic_swap_transaction(output_hash, pre_image, fee_per_gram)
+                    .await?;
+                wallet
+                    .transaction_service
+                    .submit_transaction(tx_id, tx, amount, message)
+                    .await?;
+                CommandResult::TransactionSent(tx_id)
+            },
+            WalletCommand::ClaimShaAtomicSwapRefund {
+                output_hash,
+                fee_per_gram,
+                message,
+            } => {
+                let (tx_id, _fee, amount, tx) = wallet
+                    .output_manager_service
+                    .create_htlc_refund_transaction(output_hash, fee_per_gram)
+                    .await?;
+                wallet
+                    .transaction_service
+                    .submit_transaction(tx_id, tx, amount, message)
+                    .await?;
+                CommandResult::TransactionSent(tx_id)
+            },
+            WalletCommand::RevalidateWalletDb => {
+                wallet.output_manager_service.revalidate_all_outputs().await?;
+                wallet.transaction_service.revalidate_all_transactions().await?;
+                CommandResult::Done
+            },
+            WalletCommand::TagTransaction { tx_id, tag } => {
+                wallet.transaction_service.tag_transaction(tx_id, tag).await?;
+                CommandResult::Done
+            },
+            WalletCommand::UntagTransaction { tx_id, tag } => {
+                wallet.transaction_service.untag_transaction(tx_id, tag).await?;
+                CommandResult::Done
+            },
+            WalletCommand::GetCompletedTransactions { tag } => {
+                let tags = wallet.transaction_service.get_all_transaction_tags().await?;
+                let mut transactions = wallet
+                    .transaction_service
+                    .get_completed_transactions()
+                    .await?
+                    .into_values()
+                    .filter(|tx| !tx.is_decoy)
+                    .collect::<Vec<_>>();
+                if let Some(tag) = tag {
+                    let tag = tag.trim();
+                    transactions.retain(|tx| tags.get(&tx.tx_id).map_or(false, |t| t.iter().any(|t| t == tag)));
+                }
+                transactions.sort_by_key(|tx| tx.timestamp);
+                CommandResult::Transactions { transactions, tags }
+            },
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_computes_utxo_stats() {
+        assert_eq!(UtxoStats::from_values(vec![]), UtxoStats::default());
+
+        let stats = UtxoStats::from_values(vec![MicroTari(10), MicroTari(3), MicroTari(4)]);
+        assert_eq!(stats, UtxoStats {
+            count: 3,
+            total: MicroTari(17),
+            min: Some(MicroTari(3)),
+            max: Some(MicroTari(10)),
+            average: Some(MicroTari(6)),
+        });
+    }
+}
@@ -34,12 +34,17 @@ use tari_common::{
 };
 use tari_common_types::grpc_authentication::GrpcAuthentication;
 use tari_comms::multiaddr::Multiaddr;
-use tari_p2p::P2pConfig;
+use tari_p2p::{transport::MemoryTransportConfig, P2pConfig, TransportConfig};
 use tari_utilities::SafePassword;
 
 use crate::{
     base_node_service::config::BaseNodeServiceConfig,
+    decoy_service::config::DecoyServiceConfig,
+    digest_service::config::DigestServiceConfig,
+    header_sync::config::HeaderSyncServiceConfig,
+    inheritance_service::config::InheritanceServiceConfig,
     output_manager_service::config::OutputManagerServiceConfig,
+    platform_keystore::EncryptionKeySource,
     transaction_service::config::TransactionServiceConfig,
 };
 
@@ -73,6 +78,18 @@ pub struct WalletConfig {
     /// The base_node_service_config config settings
     #[serde(rename = "base_node")]
     pub base_node_service_config: BaseNodeServiceConfig,
+    /// The digest_service_config config settings
+    #[serde(rename = "digest")]
+    pub digest_service_config: DigestServiceConfig,
+    /// The decoy_service_config config settings
+    #[serde(rename = "decoy")]
+    pub decoy_service_config: DecoyServiceConfig,
+    /// The inheritance_service_config config settings
+    #[serde(rename = "inheritance")]
+    pub inheritance_service_config: InheritanceServiceConfig,
+    /// The header_sync_service_config config settings
+    #[serde(rename = "header_sync")]
+    pub header_sync_service_config: HeaderSyncServiceConfig,
     /// The relative path to store persistent data
     pub data_dir: PathBuf,
     /// The main wallet db file
@@ -82,6 +99,8 @@ pub struct WalletConfig {
     /// The main wallet password
     #[serde(deserialize_with = "deserialize_safe_password_option")]
     pub password: Option<SafePassword>,
+    /// Where the key that encrypts the wallet database comes from
+    pub encryption_key_source: EncryptionKeySource,
     /// The auto ping interval to use for contacts liveness data
     #[serde(with = "serializers::seconds")]
     pub contacts_auto_ping_interval: Duration,
@@ -106,8 +125,20 @@ pub struct WalletConfig {
     pub custom_base_node: Option<String>,
     /// A list of base node peers that the wallet should use for service requests and tracking chain state
     pub base_node_service_peers: StringList,
+    /// Hex public keys of peers that are never banned by the wallet
+    pub allow_listed_peers: StringList,
+    /// If true, the wallet disconnects from any peer that is neither allow listed nor its current base node
+    pub allow_list_only: bool,
+    /// If true, the wallet runs as a pure client that does not join the DHT or discover the network and only keeps
+    /// the connections it needs, i.e. to its base node and the wallets it transacts with directly. Messages for
+    /// offline counterparties are handed to connected peers for store-and-forward. See
+    /// [apply_light_mode](WalletConfig::apply_light_mode).
+    pub light_mode: bool,
     /// The amount of times wallet recovery will be retried before being abandoned
     pub recovery_retry_limit: usize,
+    /// The number of consecutive unused keys probed past the last used key of each key manager branch when restoring
+    /// key indices during wallet recovery
+    pub recovery_key_index_gap_limit: u64,
     /// The default uT fee per gram to use for transaction fees
     pub fee_per_gram: u64,
     /// Number of required transaction confirmations used for UI purposes
@@ -117,6 +148,21 @@ pub struct WalletConfig {
     pub use_libtor: bool,
     /// A path to the file that stores the base node identity and secret key
     pub identity_file: Option<PathBuf>,
+    /// A service that takes longer than this to reply to a health check is reported as lagging
+    #[serde(with = "serializers::seconds")]
+    pub health_check_lag_threshold: Duration,
+    /// A service that does not reply to a health check within this time is reported as failed
+    #[serde(with = "serializers::seconds")]
+    pub health_check_timeout: Duration,
+    /// The directory the application writes its log files to. The most recent log lines are included in diagnostics
+    /// bundles.
+    pub log_dir: Option<PathBuf>,
+    /// The OTLP (gRPC) endpoint of an OpenTelemetry collector that the spans of the wallet services are exported to,
+    /// e.g. `http://localhost:4317`. Spans are not exported if this is not set.
+    pub otlp_endpoint: Option<String>,
+    /// The maximum number of requests that a caller of the wallet services, e.g. the gRPC server, can have in flight.
+    /// Further requests of the caller wait until one of its requests completes.
+    pub max_requests_per_caller: usize,
 }
 
 impl Default for WalletConfig {
@@ -134,10 +180,15 @@ impl Default for WalletConfig {
             buffer_rate_limit: 1_000,
             network: Default::default(),
             base_node_service_config: Default::default(),
+            digest_service_config: Default::default(),
+            decoy_service_config: Default::default(),
+            inheritance_service_config: Default::default(),
+            header_sync_service_config: Default::default(),
             data_dir: PathBuf::from_str("data/wallet").unwrap(),
             db_file: PathBuf::from_str("db/console_wallet.db").unwrap(),
             db_connection_pool_size: 16, // Note: Do not reduce this default number
             password: None,
+            encryption_key_source: EncryptionKeySource::default(),
             contacts_auto_ping_interval: Duration::from_secs(30),
             contacts_online_ping_window: 30,
             command_send_wait_stage: TransactionStage::Broadcast,
@@ -148,11 +199,20 @@ impl Default for WalletConfig {
             grpc_authentication: GrpcAuthentication::default(),
             custom_base_node: None,
             base_node_service_peers: StringList::default(),
+            allow_listed_peers: StringList::default(),
+            allow_list_only: false,
+            light_mode: false,
             recovery_retry_limit: 3,
+            recovery_key_index_gap_limit: 1000,
             fee_per_gram: 5,
             num_required_confirmations: 3,
             use_libtor: false,
             identity_file: None,
+            health_check_lag_threshold: Duration::from_secs(1),
+            health_check_timeout: Duration::from_secs(5),
+            log_dir: None,
+            otlp_endpoint: None,
+            max_requests_per_caller: 4,
         }
     }
 }
@@ -171,8 +231,48 @@ impl WalletConfig {
         if !self.db_file.is_absolute() {
             self.db_file = self.data_dir.join(self.db_file.as_path());
         }
+        if let Some(log_dir) = self.log_dir.as_mut() {
+            if !log_dir.is_absolute() {
+                *log_dir = base_path.as_ref().join(log_dir.as_path());
+            }
+        }
+        if let Some(recording_file) = self.transaction_service_config.protocol_recording_file.as_mut() {
+            if !recording_file.is_absolute() {
+                *recording_file = self.data_dir.join(recording_file.as_path());
+            }
+        }
+        if let Some(report_dir) = self.digest_service_config.report_dir.as_mut() {
+            if !report_dir.is_absolute() {
+                *report_dir = self.data_dir.join(report_dir.as_path());
+            }
+        }
         self.p2p.set_base_path(base_path);
     }
+
+    /// Configures the wallet to communicate over the in-process memory transport on an address assigned when comms
+    /// starts. Such a wallet can only reach peers running in the same process, which lets several wallets exchange
+    /// messages in a single test binary without opening any sockets.
+    pub fn set_memory_transport(&mut self) {
+        self.p2p.transport = TransportConfig::new_memory(MemoryTransportConfig::default());
+        self.p2p.public_address = None;
+        self.p2p.allow_test_addresses = true;
+    }
+
+    /// Overrides the settings that make the wallet participate in the DHT if `light_mode` is set. The wallet does not
+    /// announce itself to the network, discover new peers or keep random connections, and store-and-forward messages
+    /// are only sent to peers it is already connected to. A wallet identity does not advertise store-and-forward, so
+    /// it never stores messages for others.
+    pub fn apply_light_mode(&mut self) {
+        if !self.light_mode {
+            return;
+        }
+        let dht = &mut self.p2p.dht;
+        dht.auto_join = false;
+        dht.num_neighbouring_nodes = 1;
+        dht.num_random_nodes = 0;
+        dht.network_discovery.enabled = false;
+        self.transaction_service_config.store_and_forward_connected_peers_only = true;
+    }
 }
 
 #[derive(Debug, EnumString, PartialEq, Clone, Copy, Serialize, Deserialize)]
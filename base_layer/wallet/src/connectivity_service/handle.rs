@@ -28,7 +28,7 @@ use tari_comms::{
 use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
 use tokio::sync::{mpsc, oneshot, watch};
 
-use super::service::OnlineStatus;
+use super::service::{BaseNodeIdentityMismatch, OnlineStatus};
 use crate::{connectivity_service::WalletConnectivityInterface, util::watch::Watch};
 
 pub enum WalletConnectivityRequest {
@@ -41,6 +41,7 @@ pub struct WalletConnectivityHandle {
     sender: mpsc::Sender<WalletConnectivityRequest>,
     base_node_watch: Watch<Option<Peer>>,
     online_status_rx: watch::Receiver<OnlineStatus>,
+    identity_mismatch_rx: watch::Receiver<Option<BaseNodeIdentityMismatch>>,
 }
 
 impl WalletConnectivityHandle {
@@ -48,13 +49,20 @@ impl WalletConnectivityHandle {
         sender: mpsc::Sender<WalletConnectivityRequest>,
         base_node_watch: Watch<Option<Peer>>,
         online_status_rx: watch::Receiver<OnlineStatus>,
+        identity_mismatch_rx: watch::Receiver<Option<BaseNodeIdentityMismatch>>,
     ) -> Self {
         Self {
             sender,
             base_node_watch,
             online_status_rx,
+            identity_mismatch_rx,
         }
     }
+
+    /// Watches for the base node presenting another identity than the one it was set with
+    pub fn get_base_node_identity_mismatch_watch(&self) -> watch::Receiver<Option<BaseNodeIdentityMismatch>> {
+        self.identity_mismatch_rx.clone()
+    }
 }
 
 #[async_trait::async_trait]
@@ -54,10 +54,12 @@ impl ServiceInitializer for WalletConnectivityInitializer {
         let (sender, receiver) = mpsc::channel(5);
         let base_node_watch = Watch::new(None);
         let online_status_watch = Watch::new(OnlineStatus::Offline);
+        let identity_mismatch_watch = Watch::new(None);
         context.register_handle(WalletConnectivityHandle::new(
             sender,
             base_node_watch.clone(),
             online_status_watch.get_receiver(),
+            identity_mismatch_watch.get_receiver(),
         ));
 
         let config = self.config.clone();
@@ -69,6 +71,7 @@ impl ServiceInitializer for WalletConnectivityInitializer {
                 receiver,
                 base_node_watch.get_receiver(),
                 online_status_watch,
+                identity_mismatch_watch,
                 connectivity,
             );
             service.start()
@@ -29,8 +29,10 @@ pub use handle::WalletConnectivityHandle;
 mod initializer;
 pub use initializer::WalletConnectivityInitializer;
 
+mod pool;
+
 mod service;
-pub use service::OnlineStatus;
+pub use service::{BaseNodeIdentityMismatch, OnlineStatus};
 
 #[cfg(test)]
 mod test;
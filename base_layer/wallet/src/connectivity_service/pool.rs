@@ -0,0 +1,116 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{sync::Arc, time::Duration};
+
+use tari_comms::{
+    protocol::rpc::{RpcClientBuilder, RpcClientLease, RpcClientPool, RpcClientPoolError},
+    PeerConnection,
+};
+use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
+use tokio::{
+    sync::Mutex,
+    time::{self, Instant},
+};
+
+use crate::base_node_service::config::BaseNodeServiceConfig;
+
+/// The RPC sessions to the base node that are shared by all wallet services. Each session is leased to the service that
+/// is using it the least, so that concurrent requests are spread over the sessions instead of queueing behind one
+/// another.
+#[derive(Clone)]
+pub(super) struct BaseNodeRpcPool {
+    wallet_rpc: RpcClientPool<BaseNodeWalletRpcClient>,
+    sync_rpc: RpcClientPool<BaseNodeSyncRpcClient>,
+    rate_limiter: RateLimiter,
+}
+
+impl BaseNodeRpcPool {
+    pub fn new(conn: &PeerConnection, config: &BaseNodeServiceConfig, rate_limiter: RateLimiter) -> Self {
+        Self {
+            wallet_rpc: conn.create_rpc_client_pool(
+                config.base_node_rpc_pool_size,
+                RpcClientBuilder::new().with_deadline(config.base_node_rpc_request_timeout),
+            ),
+            sync_rpc: conn.create_rpc_client_pool(
+                config.base_node_sync_rpc_pool_size,
+                RpcClientBuilder::new().with_deadline(config.base_node_rpc_request_timeout),
+            ),
+            rate_limiter,
+        }
+    }
+
+    pub async fn get_wallet_rpc_client(&self) -> Result<RpcClientLease<BaseNodeWalletRpcClient>, RpcClientPoolError> {
+        self.rate_limiter.wait().await;
+        self.wallet_rpc.get().await
+    }
+
+    pub async fn get_sync_rpc_client(&self) -> Result<RpcClientLease<BaseNodeSyncRpcClient>, RpcClientPoolError> {
+        self.rate_limiter.wait().await;
+        self.sync_rpc.get().await
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        self.wallet_rpc.is_connected().await
+    }
+}
+
+/// Spaces out the clients handed out by the pool so that the wallet does not exceed a number of leases per second.
+/// The limit carries over when the pool is recreated for a new connection.
+#[derive(Clone)]
+pub(super) struct RateLimiter {
+    interval: Duration,
+    next_slot: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    /// A limit of 0 disables rate limiting
+    pub fn new(max_per_second: u32) -> Self {
+        let interval = if max_per_second == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / max_per_second
+        };
+        Self {
+            interval,
+            next_slot: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Waits until the next slot is available
+    pub async fn wait(&self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_spaces_out_leases() {
+        let rate_limiter = RateLimiter::new(100);
+        let start = Instant::now();
+        for _ in 0..5 {
+            rate_limiter.wait().await;
+        }
+        // The first slot is immediate and each following slot is 10ms after the previous one
+        assert!(start.elapsed() >= Duration::from_millis(40));
+
+        let unlimited = RateLimiter::new(0);
+        let start = Instant::now();
+        for _ in 0..1000 {
+            unlimited.wait().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(40));
+    }
+}
@@ -24,12 +24,16 @@ use std::{mem, time::Duration};
 
 use log::*;
 use tari_comms::{
+    connection_manager::ConnectionManagerError,
     connectivity::{ConnectivityError, ConnectivityRequester},
+    multiaddr::Multiaddr,
     peer_manager::{NodeId, Peer},
-    protocol::rpc::{RpcClientLease, RpcClientPool},
+    protocol::rpc::RpcClientLease,
+    types::CommsPublicKey,
     PeerConnection,
 };
 use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
+use tari_utilities::hex::Hex;
 use tokio::{
     sync::{mpsc, oneshot, watch},
     time,
@@ -38,7 +42,11 @@ use tokio::{
 
 use crate::{
     base_node_service::config::BaseNodeServiceConfig,
-    connectivity_service::{error::WalletConnectivityError, handle::WalletConnectivityRequest},
+    connectivity_service::{
+        error::WalletConnectivityError,
+        handle::WalletConnectivityRequest,
+        pool::{BaseNodeRpcPool, RateLimiter},
+    },
     util::watch::Watch,
 };
 
@@ -52,19 +60,29 @@ pub enum OnlineStatus {
     Offline,
 }
 
+/// The base node authenticated with another public key than the one it was dialed with, e.g. because its address now
+/// resolves to another node
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BaseNodeIdentityMismatch {
+    pub addresses: Vec<Multiaddr>,
+    pub expected_public_key: CommsPublicKey,
+    pub presented_public_key: CommsPublicKey,
+}
+
 pub struct WalletConnectivityService {
     config: BaseNodeServiceConfig,
     request_receiver: mpsc::Receiver<WalletConnectivityRequest>,
     connectivity: ConnectivityRequester,
     base_node_watch: watch::Receiver<Option<Peer>>,
-    pools: Option<ClientPoolContainer>,
+    pools: Option<BaseNodeRpcPool>,
+    /// Incremented every time the pool is recreated, so that failures of a previous pool can be ignored
+    pool_generation: u64,
+    rate_limiter: RateLimiter,
     online_status_watch: Watch<OnlineStatus>,
+    identity_mismatch_watch: Watch<Option<BaseNodeIdentityMismatch>>,
     pending_requests: Vec<ReplyOneshot>,
-}
-
-struct ClientPoolContainer {
-    pub base_node_wallet_rpc_client: RpcClientPool<BaseNodeWalletRpcClient>,
-    pub base_node_sync_rpc_client: RpcClientPool<BaseNodeSyncRpcClient>,
+    failed_requests_tx: mpsc::UnboundedSender<(u64, ReplyOneshot)>,
+    failed_requests_rx: mpsc::UnboundedReceiver<(u64, ReplyOneshot)>,
 }
 
 impl WalletConnectivityService {
@@ -73,16 +91,23 @@ impl WalletConnectivityService {
         request_receiver: mpsc::Receiver<WalletConnectivityRequest>,
         base_node_watch: watch::Receiver<Option<Peer>>,
         online_status_watch: Watch<OnlineStatus>,
+        identity_mismatch_watch: Watch<Option<BaseNodeIdentityMismatch>>,
         connectivity: ConnectivityRequester,
     ) -> Self {
+        let (failed_requests_tx, failed_requests_rx) = mpsc::unbounded_channel();
         Self {
+            rate_limiter: RateLimiter::new(config.base_node_rpc_rate_limit),
             config,
             request_receiver,
             connectivity,
             base_node_watch,
             pools: None,
+            pool_generation: 0,
             pending_requests: Vec::new(),
             online_status_watch,
+            identity_mismatch_watch,
+            failed_requests_tx,
+            failed_requests_rx,
         }
     }
 
@@ -104,6 +129,10 @@ impl WalletConnectivityService {
                     }
                 },
 
+                Some((generation, reply)) = self.failed_requests_rx.recv() => {
+                    self.handle_failed_request(generation, reply).await;
+                },
+
                 Some(req) = self.request_receiver.recv() => {
                     self.handle_request(req).await;
                 },
@@ -118,7 +147,7 @@ impl WalletConnectivityService {
     async fn check_connection(&mut self) {
         match self.pools.as_ref() {
             Some(pool) => {
-                if !pool.base_node_wallet_rpc_client.is_connected().await {
+                if !pool.is_connected().await {
                     debug!(target: LOG_TARGET, "Peer connection lost. Attempting to reconnect...");
                     self.set_online_status(OnlineStatus::Offline);
                     self.setup_base_node_connection().await;
@@ -144,36 +173,13 @@ impl WalletConnectivityService {
         }
     }
 
+    /// Leases a client from the pool on a separate task, so that requests are not held up by one another while a
+    /// session is being established or the rate limit is reached
     async fn handle_pool_request(&mut self, reply: ReplyOneshot) {
-        use ReplyOneshot::{SyncRpc, WalletRpc};
-        match reply {
-            WalletRpc(tx) => self.handle_get_base_node_wallet_rpc_client(tx).await,
-            SyncRpc(tx) => self.handle_get_base_node_sync_rpc_client(tx).await,
-        }
-    }
-
-    async fn handle_get_base_node_wallet_rpc_client(
-        &mut self,
-        reply: oneshot::Sender<RpcClientLease<BaseNodeWalletRpcClient>>,
-    ) {
-        match self.pools {
-            Some(ref pools) => match pools.base_node_wallet_rpc_client.get().await {
-                Ok(client) => {
-                    let _result = reply.send(client);
-                },
-                Err(e) => {
-                    warn!(
-                        target: LOG_TARGET,
-                        "Base node connection failed: {}. Reconnecting...", e
-                    );
-                    if let Some(node_id) = self.current_base_node() {
-                        self.disconnect_base_node(node_id).await;
-                    };
-                    self.pending_requests.push(reply.into());
-                },
-            },
+        let pool = match self.pools {
+            Some(ref pool) => pool.clone(),
             None => {
-                self.pending_requests.push(reply.into());
+                self.pending_requests.push(reply);
                 if self.base_node_watch.borrow().is_none() {
                     warn!(
                         target: LOG_TARGET,
@@ -181,41 +187,54 @@ impl WalletConnectivityService {
                         self.pending_requests.len()
                     );
                 }
+                return;
             },
-        }
-    }
-
-    async fn handle_get_base_node_sync_rpc_client(
-        &mut self,
-        reply: oneshot::Sender<RpcClientLease<BaseNodeSyncRpcClient>>,
-    ) {
-        match self.pools {
-            Some(ref pools) => match pools.base_node_sync_rpc_client.get().await {
-                Ok(client) => {
-                    let _result = reply.send(client);
+        };
+        let generation = self.pool_generation;
+        let failed_requests_tx = self.failed_requests_tx.clone();
+        tokio::spawn(async move {
+            use ReplyOneshot::{SyncRpc, WalletRpc};
+            let result = match reply {
+                WalletRpc(tx) => match pool.get_wallet_rpc_client().await {
+                    Ok(client) => {
+                        let _result = tx.send(client);
+                        Ok(())
+                    },
+                    Err(e) => Err((e, WalletRpc(tx))),
                 },
-                Err(e) => {
-                    warn!(
-                        target: LOG_TARGET,
-                        "Base node connection failed: {}. Reconnecting...", e
-                    );
-                    if let Some(node_id) = self.current_base_node() {
-                        self.disconnect_base_node(node_id).await;
-                    };
-                    self.pending_requests.push(reply.into());
+                SyncRpc(tx) => match pool.get_sync_rpc_client().await {
+                    Ok(client) => {
+                        let _result = tx.send(client);
+                        Ok(())
+                    },
+                    Err(e) => Err((e, SyncRpc(tx))),
                 },
-            },
-            None => {
-                self.pending_requests.push(reply.into());
-                if self.base_node_watch.borrow().is_none() {
-                    warn!(
-                        target: LOG_TARGET,
-                        "{} requests are waiting for base node to be set",
-                        self.pending_requests.len()
-                    );
-                }
-            },
+            };
+            if let Err((e, reply)) = result {
+                warn!(
+                    target: LOG_TARGET,
+                    "Base node connection failed: {}. Reconnecting...", e
+                );
+                let _result = failed_requests_tx.send((generation, reply));
+            }
+        });
+    }
+
+    /// Requeues a request that could not be served, and disconnects the base node if the pool that failed is the
+    /// current one. The connection is re-established on the next connection check.
+    async fn handle_failed_request(&mut self, generation: u64, reply: ReplyOneshot) {
+        if reply.is_canceled() {
+            return;
         }
+        if generation != self.pool_generation {
+            // The pool has already been replaced, so try again with the new one
+            self.handle_pool_request(reply).await;
+            return;
+        }
+        if let Some(node_id) = self.current_base_node() {
+            self.disconnect_base_node(node_id).await;
+        }
+        self.pending_requests.push(reply);
     }
 
     fn current_base_node(&self) -> Option<NodeId> {
@@ -249,6 +268,9 @@ impl WalletConnectivityService {
             self.set_online_status(OnlineStatus::Connecting);
             match self.try_setup_rpc_pool(node_id.clone()).await {
                 Ok(true) => {
+                    if self.identity_mismatch_watch.borrow().is_some() {
+                        self.identity_mismatch_watch.send(None);
+                    }
                     self.set_online_status(OnlineStatus::Online);
                     debug!(
                         target: LOG_TARGET,
@@ -275,6 +297,7 @@ impl WalletConnectivityService {
                 },
                 Err(e) => {
                     warn!(target: LOG_TARGET, "{}", e);
+                    self.check_identity_mismatch(&e);
                     if self.current_base_node().as_ref() == Some(&node_id) {
                         self.disconnect_base_node(node_id).await;
                         self.set_online_status(OnlineStatus::Offline);
@@ -290,6 +313,39 @@ impl WalletConnectivityService {
         self.online_status_watch.send(status);
     }
 
+    /// Reports a base node that presented another identity than the one it was dialed with
+    fn check_identity_mismatch(&self, error: &WalletConnectivityError) {
+        let authenticated_pk = match error {
+            WalletConnectivityError::ConnectivityError(ConnectivityError::ConnectionFailed(
+                ConnectionManagerError::DialedPublicKeyMismatch { authenticated_pk, .. },
+            )) => authenticated_pk,
+            _ => return,
+        };
+        let presented_public_key = match CommsPublicKey::from_hex(authenticated_pk) {
+            Ok(public_key) => public_key,
+            Err(_) => return,
+        };
+        let peer = match self.base_node_watch.borrow().clone() {
+            Some(peer) => peer,
+            None => return,
+        };
+        let mismatch = BaseNodeIdentityMismatch {
+            addresses: peer.addresses.iter().cloned().collect(),
+            expected_public_key: peer.public_key,
+            presented_public_key,
+        };
+        if self.identity_mismatch_watch.borrow().as_ref() != Some(&mismatch) {
+            warn!(
+                target: LOG_TARGET,
+                "Base node {} presented the public key {} instead of {}",
+                peer.node_id,
+                mismatch.presented_public_key,
+                mismatch.expected_public_key
+            );
+            self.identity_mismatch_watch.send(Some(mismatch));
+        }
+    }
+
     async fn try_setup_rpc_pool(&mut self, peer: NodeId) -> Result<bool, WalletConnectivityError> {
         let conn = match self.try_dial_peer(peer.clone()).await? {
             Some(c) => c,
@@ -303,11 +359,8 @@ impl WalletConnectivityService {
             "Successfully established peer connection to base node {}",
             conn.peer_node_id()
         );
-        self.pools = Some(ClientPoolContainer {
-            base_node_sync_rpc_client: conn.create_rpc_client_pool(1, Default::default()),
-            base_node_wallet_rpc_client: conn
-                .create_rpc_client_pool(self.config.base_node_rpc_pool_size, Default::default()),
-        });
+        self.pools = Some(BaseNodeRpcPool::new(&conn, &self.config, self.rate_limiter.clone()));
+        self.pool_generation += 1;
         self.notify_pending_requests().await?;
         debug!(target: LOG_TARGET, "Successfully established RPC connection {}", peer);
         Ok(true)
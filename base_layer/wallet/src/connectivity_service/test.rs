@@ -25,6 +25,7 @@ use std::{iter, sync::Arc};
 
 use futures::future;
 use tari_comms::{
+    connection_manager::ConnectionManagerError,
     peer_manager::PeerFeatures,
     protocol::rpc::{
         mock::{MockRpcImpl, MockRpcServer},
@@ -37,6 +38,7 @@ use tari_comms::{
 };
 use tari_shutdown::Shutdown;
 use tari_test_utils::runtime::spawn_until_shutdown;
+use tari_utilities::hex::Hex;
 use tokio::{
     sync::{mpsc, Barrier},
     task,
@@ -57,7 +59,13 @@ async fn setup() -> (
     let (tx, rx) = mpsc::channel(1);
     let base_node_watch = Watch::new(None);
     let online_status_watch = Watch::new(OnlineStatus::Offline);
-    let handle = WalletConnectivityHandle::new(tx, base_node_watch.clone(), online_status_watch.get_receiver());
+    let identity_mismatch_watch = Watch::new(None);
+    let handle = WalletConnectivityHandle::new(
+        tx,
+        base_node_watch.clone(),
+        online_status_watch.get_receiver(),
+        identity_mismatch_watch.get_receiver(),
+    );
     let (connectivity, mock) = create_connectivity_mock();
     let mock_state = mock.spawn();
     // let peer_manager = create_peer_manager(tempdir().unwrap());
@@ -66,6 +74,7 @@ async fn setup() -> (
         rx,
         base_node_watch.get_receiver(),
         online_status_watch,
+        identity_mismatch_watch,
         connectivity,
     );
     let shutdown = spawn_until_shutdown(service.start());
@@ -99,6 +108,33 @@ async fn it_dials_peer_when_base_node_is_set() {
     assert!(rpc_client.is_connected());
 }
 
+#[tokio::test]
+async fn it_reports_a_base_node_that_presents_another_identity() {
+    let (mut handle, _mock_server, mock_state, _shutdown) = setup().await;
+    let base_node_peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let impostor = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let mut mismatch_watch = handle.get_base_node_identity_mismatch_watch();
+
+    mock_state.set_pending_connection(base_node_peer.node_id()).await;
+    handle.set_base_node(base_node_peer.to_peer());
+    mock_state.await_call_count(1).await;
+    mock_state
+        .fail_pending_connection(
+            base_node_peer.node_id(),
+            ConnectionManagerError::DialedPublicKeyMismatch {
+                authenticated_pk: impostor.public_key().to_hex(),
+                expected_pk: base_node_peer.public_key().to_hex(),
+            },
+        )
+        .await;
+
+    mismatch_watch.changed().await.unwrap();
+    let mismatch = mismatch_watch.borrow().clone().unwrap();
+    assert_eq!(&mismatch.expected_public_key, base_node_peer.public_key());
+    assert_eq!(&mismatch.presented_public_key, impostor.public_key());
+    assert_eq!(mismatch.addresses, vec![base_node_peer.public_address()]);
+}
+
 #[tokio::test]
 async fn it_resolves_many_pending_rpc_session_requests() {
     let (mut handle, mock_server, mock_state, _shutdown) = setup().await;
@@ -239,3 +275,24 @@ async fn it_gracefully_handles_multiple_connection_failures() {
     // Still able to get a base node rpc client
     pending_request.await.unwrap();
 }
+
+#[tokio::test]
+async fn it_leases_wallet_and_sync_sessions_concurrently() {
+    let (mut handle, mock_server, mock_state, _shutdown) = setup().await;
+    let base_node_peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let conn = mock_server.create_mockimpl_connection(base_node_peer.to_peer()).await;
+    mock_state.add_active_connection(conn).await;
+    handle.set_base_node(base_node_peer.to_peer());
+
+    let mut sync_handle = handle.clone();
+    let mut other_sync_handle = handle.clone();
+    let (wallet_client, sync_client, other_sync_client) = future::join3(
+        handle.obtain_base_node_wallet_rpc_client(),
+        sync_handle.obtain_base_node_sync_rpc_client(),
+        other_sync_handle.obtain_base_node_sync_rpc_client(),
+    )
+    .await;
+    assert!(wallet_client.unwrap().is_connected());
+    assert!(sync_client.unwrap().is_connected());
+    assert!(other_sync_client.unwrap().is_connected());
+}
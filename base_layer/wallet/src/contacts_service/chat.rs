@@ -0,0 +1,102 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Text messages exchanged between the wallets of contacts. Messages and receipts are encrypted for the contact and
+//! sent both directly and to the neighbours of the contact for store-and-forward, so they also reach contacts that
+//! are offline.
+
+use chrono::{NaiveDateTime, Utc};
+use rand::{rngs::OsRng, RngCore};
+use tari_comms::types::CommsPublicKey;
+
+use crate::contacts_service::{
+    error::ContactsServiceError,
+    storage::database::{ChatDirection, ChatMessage},
+};
+
+/// The number of random bytes in the id of a chat message
+pub const CHAT_MESSAGE_ID_BYTES: usize = 32;
+/// The maximum size of the body of a chat message in bytes
+pub const MAX_CHAT_MESSAGE_BYTES: usize = 4096;
+
+/// The wire format of a chat message, sent as a `TariMessageType::Text` message
+#[derive(Clone, prost::Message)]
+pub struct ChatMessageProto {
+    #[prost(bytes, tag = "1")]
+    pub message_id: Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub body: String,
+    /// Unix timestamp in seconds
+    #[prost(uint64, tag = "3")]
+    pub sent_at: u64,
+}
+
+/// The wire format of a delivery receipt, sent as a `TariMessageType::TextAck` message
+#[derive(Clone, prost::Message)]
+pub struct ChatReceiptProto {
+    #[prost(bytes, tag = "1")]
+    pub message_id: Vec<u8>,
+}
+
+impl ChatMessage {
+    /// Creates a message to be sent to the contact identified by `address`
+    pub fn new_outbound(address: CommsPublicKey, body: String) -> Result<Self, ContactsServiceError> {
+        validate_body(&body)?;
+        let mut message_id = vec![0u8; CHAT_MESSAGE_ID_BYTES];
+        OsRng.fill_bytes(&mut message_id);
+        let now = Utc::now().naive_utc();
+        Ok(Self {
+            message_id,
+            address,
+            body,
+            direction: ChatDirection::Outbound,
+            sent_at: now,
+            stored_at: now,
+            delivery_confirmed_at: None,
+        })
+    }
+
+    /// Creates a message received from the contact identified by `address`
+    pub fn from_inbound(address: CommsPublicKey, message: ChatMessageProto) -> Result<Self, ContactsServiceError> {
+        if message.message_id.len() != CHAT_MESSAGE_ID_BYTES {
+            return Err(ContactsServiceError::InvalidChatMessage(format!(
+                "Message id is {} bytes long",
+                message.message_id.len()
+            )));
+        }
+        validate_body(&message.body)?;
+        #[allow(clippy::cast_possible_wrap)]
+        let sent_at = NaiveDateTime::from_timestamp_opt(message.sent_at as i64, 0)
+            .ok_or_else(|| ContactsServiceError::InvalidChatMessage("Invalid timestamp".to_string()))?;
+        Ok(Self {
+            message_id: message.message_id,
+            address,
+            body: message.body,
+            direction: ChatDirection::Inbound,
+            sent_at,
+            stored_at: Utc::now().naive_utc(),
+            delivery_confirmed_at: None,
+        })
+    }
+}
+
+impl From<&ChatMessage> for ChatMessageProto {
+    #[allow(clippy::cast_sign_loss)]
+    fn from(message: &ChatMessage) -> Self {
+        Self {
+            message_id: message.message_id.clone(),
+            body: message.body.clone(),
+            sent_at: message.sent_at.timestamp() as u64,
+        }
+    }
+}
+
+fn validate_body(body: &str) -> Result<(), ContactsServiceError> {
+    if body.is_empty() {
+        return Err(ContactsServiceError::InvalidChatMessage("Message is empty".to_string()));
+    }
+    if body.len() > MAX_CHAT_MESSAGE_BYTES {
+        return Err(ContactsServiceError::ChatMessageTooLong(MAX_CHAT_MESSAGE_BYTES));
+    }
+    Ok(())
+}
@@ -22,11 +22,16 @@
 
 use diesel::result::Error as DieselError;
 use tari_comms::connectivity::ConnectivityError;
+use tari_comms_dht::outbound::DhtOutboundError;
 use tari_p2p::services::liveness::error::LivenessError;
 use tari_service_framework::reply_channel::TransportChannelError;
 use thiserror::Error;
 
-use crate::{contacts_service::storage::database::DbKey, error::WalletStorageError};
+use crate::{
+    contacts_service::storage::database::DbKey,
+    error::{diesel_error_class, ClassifyError, ErrorClass, ErrorKind, SuggestedAction, WalletStorageError},
+    transaction_service::error::TransactionServiceError,
+};
 
 #[derive(Debug, Error)]
 #[allow(clippy::large_enum_variant)]
@@ -43,6 +48,18 @@ pub enum ContactsServiceError {
     LivenessError(#[from] LivenessError),
     #[error("ConnectivityError error: `{0}`")]
     ConnectivityError(#[from] ConnectivityError),
+    #[error("Transaction service error: `{0}`")]
+    TransactionServiceError(#[from] TransactionServiceError),
+    #[error("Contact stats require the transaction service, which is not running")]
+    TransactionServiceUnavailable,
+    #[error("Invalid chat message: `{0}`")]
+    InvalidChatMessage(String),
+    #[error("Chat messages cannot be longer than {0} bytes")]
+    ChatMessageTooLong(usize),
+    #[error("Invalid contact group: `{0}`")]
+    InvalidContactGroup(String),
+    #[error("DHT outbound error: `{0}`")]
+    DhtOutboundError(#[from] DhtOutboundError),
 }
 
 #[derive(Debug, Error)]
@@ -68,3 +85,40 @@ pub enum ContactsServiceStorageError {
     #[error("Blocking task spawn error: `{0}`")]
     BlockingTaskSpawnError(String),
 }
+
+impl ClassifyError for ContactsServiceError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            ContactsServiceError::ContactsServiceStorageError(e) => e.class(),
+            ContactsServiceError::TransactionServiceError(e) => e.class(),
+            ContactsServiceError::ContactNotFound => ErrorClass::NOT_FOUND,
+            ContactsServiceError::InvalidChatMessage(_) |
+            ContactsServiceError::ChatMessageTooLong(_) |
+            ContactsServiceError::InvalidContactGroup(_) => ErrorClass::INVALID_ARGUMENT,
+            ContactsServiceError::LivenessError(_) |
+            ContactsServiceError::ConnectivityError(_) |
+            ContactsServiceError::DhtOutboundError(_) => ErrorClass::CONNECTIVITY,
+            ContactsServiceError::TransportChannelError(_) => ErrorClass::UNAVAILABLE,
+            ContactsServiceError::TransactionServiceUnavailable => {
+                ErrorClass::new(ErrorKind::Unavailable, SuggestedAction::Restart)
+            },
+            ContactsServiceError::UnexpectedApiResponse => ErrorClass::INTERNAL,
+        }
+    }
+}
+
+impl ClassifyError for ContactsServiceStorageError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            ContactsServiceStorageError::DieselR2d2Error(e) => e.class(),
+            ContactsServiceStorageError::DieselError(e) => diesel_error_class(e),
+            ContactsServiceStorageError::BlockingTaskSpawnError(_) => {
+                ErrorClass::new(ErrorKind::Storage, SuggestedAction::Retry)
+            },
+            ContactsServiceStorageError::ValuesNotFound | ContactsServiceStorageError::ValueNotFound(_) => {
+                ErrorClass::NOT_FOUND
+            },
+            _ => ErrorClass::STORAGE,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Contacts made up of several members with a signing threshold, e.g. the cosigners of a multi-signature payment.
+//! A group is identified by the sum of the public keys of its members, so the same members always form the same group
+//! regardless of the order they were given in.
+
+use tari_comms::types::CommsPublicKey;
+use tari_utilities::ByteArray;
+
+use crate::contacts_service::{error::ContactsServiceError, storage::database::ContactGroup};
+
+/// The maximum number of members of a contact group
+pub const MAX_CONTACT_GROUP_MEMBERS: usize = 32;
+
+impl ContactGroup {
+    pub fn new(alias: String, mut members: Vec<CommsPublicKey>, threshold: u8) -> Result<Self, ContactsServiceError> {
+        members.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        let num_members = members.len();
+        members.dedup();
+        if members.len() != num_members {
+            return Err(ContactsServiceError::InvalidContactGroup(
+                "Members must be unique".to_string(),
+            ));
+        }
+        if members.len() < 2 || members.len() > MAX_CONTACT_GROUP_MEMBERS {
+            return Err(ContactsServiceError::InvalidContactGroup(format!(
+                "A group must have between 2 and {} members",
+                MAX_CONTACT_GROUP_MEMBERS
+            )));
+        }
+        if threshold == 0 || usize::from(threshold) > members.len() {
+            return Err(ContactsServiceError::InvalidContactGroup(format!(
+                "The threshold must be between 1 and the number of members, {}",
+                members.len()
+            )));
+        }
+        Ok(Self {
+            alias,
+            aggregate_public_key: aggregate_public_key(&members),
+            members,
+            threshold,
+        })
+    }
+
+    /// Returns true if `public_key` is one of the members of the group
+    pub fn is_member(&self, public_key: &CommsPublicKey) -> bool {
+        self.members.contains(public_key)
+    }
+}
+
+/// The sum of the public keys of the members of a group
+pub fn aggregate_public_key(members: &[CommsPublicKey]) -> CommsPublicKey {
+    members
+        .iter()
+        .fold(CommsPublicKey::default(), |aggregate, member| &aggregate + member)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PrivateKey;
+    use tari_crypto::keys::{PublicKey, SecretKey};
+
+    use super::*;
+
+    fn random_public_key() -> CommsPublicKey {
+        CommsPublicKey::from_secret_key(&PrivateKey::random(&mut OsRng))
+    }
+
+    #[test]
+    fn it_identifies_groups_by_their_members() {
+        let members = (0..3).map(|_| random_public_key()).collect::<Vec<_>>();
+        let group = ContactGroup::new("Board".to_string(), members.clone(), 2).unwrap();
+        let reversed = ContactGroup::new("Board".to_string(), members.iter().rev().cloned().collect(), 2).unwrap();
+        assert_eq!(group, reversed);
+        assert_eq!(group.aggregate_public_key, &(&members[0] + &members[1]) + &members[2]);
+        assert!(members.iter().all(|member| group.is_member(member)));
+        assert!(!group.is_member(&random_public_key()));
+    }
+
+    #[test]
+    fn it_rejects_invalid_groups() {
+        let member = random_public_key();
+        let other = random_public_key();
+        let new_group = |members: Vec<CommsPublicKey>, threshold| ContactGroup::new(String::new(), members, threshold);
+        assert!(new_group(vec![member.clone()], 1).is_err());
+        assert!(new_group(vec![member.clone(), member.clone()], 1).is_err());
+        assert!(new_group(vec![member.clone(), other.clone()], 0).is_err());
+        assert!(new_group(vec![member.clone(), other.clone()], 3).is_err());
+        assert!(new_group(
+            (0..=MAX_CONTACT_GROUP_MEMBERS).map(|_| random_public_key()).collect(),
+            1
+        )
+        .is_err());
+        assert!(new_group(vec![member, other], 2).is_ok());
+    }
+}
@@ -31,10 +31,13 @@ use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
 
-use crate::contacts_service::{
-    error::ContactsServiceError,
-    service::{ContactMessageType, ContactOnlineStatus},
-    storage::database::Contact,
+use crate::{
+    contacts_service::{
+        error::ContactsServiceError,
+        service::{ContactMessageType, ContactOnlineStatus},
+        storage::database::{ChatMessage, Contact, ContactGroup},
+    },
+    transaction_service::storage::models::CounterpartyStats,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -122,6 +125,13 @@ pub enum ContactsLivenessEvent {
     NetworkSilence,
 }
 
+#[derive(Debug)]
+pub enum ChatEvent {
+    MessageReceived(Box<ChatMessage>),
+    /// The contact confirmed that it received an outbound message
+    DeliveryConfirmed(Box<ChatMessage>),
+}
+
 #[derive(Debug)]
 pub enum ContactsServiceRequest {
     GetContact(CommsPublicKey),
@@ -129,6 +139,13 @@ pub enum ContactsServiceRequest {
     RemoveContact(CommsPublicKey),
     GetContacts,
     GetContactOnlineStatus(Contact),
+    GetContactStats(CommsPublicKey),
+    SendChatMessage(CommsPublicKey, String),
+    GetChatMessages(CommsPublicKey),
+    GetContactGroup(CommsPublicKey),
+    GetContactGroups,
+    UpsertContactGroup(ContactGroup),
+    RemoveContactGroup(CommsPublicKey),
 }
 
 #[derive(Debug)]
@@ -138,6 +155,13 @@ pub enum ContactsServiceResponse {
     Contact(Contact),
     Contacts(Vec<Contact>),
     OnlineStatus(ContactOnlineStatus),
+    ContactStats(Box<CounterpartyStats>),
+    ChatMessageSent(Box<ChatMessage>),
+    ChatMessages(Vec<ChatMessage>),
+    ContactGroup(ContactGroup),
+    ContactGroups(Vec<ContactGroup>),
+    ContactGroupSaved,
+    ContactGroupRemoved(ContactGroup),
 }
 
 #[derive(Clone)]
@@ -145,6 +169,7 @@ pub struct ContactsServiceHandle {
     request_response_service:
         SenderService<ContactsServiceRequest, Result<ContactsServiceResponse, ContactsServiceError>>,
     liveness_events: broadcast::Sender<Arc<ContactsLivenessEvent>>,
+    chat_events: broadcast::Sender<Arc<ChatEvent>>,
 }
 
 impl ContactsServiceHandle {
@@ -154,10 +179,12 @@ impl ContactsServiceHandle {
             Result<ContactsServiceResponse, ContactsServiceError>,
         >,
         liveness_events: broadcast::Sender<Arc<ContactsLivenessEvent>>,
+        chat_events: broadcast::Sender<Arc<ChatEvent>>,
     ) -> Self {
         Self {
             request_response_service,
             liveness_events,
+            chat_events,
         }
     }
 
@@ -209,6 +236,10 @@ impl ContactsServiceHandle {
         self.liveness_events.subscribe()
     }
 
+    pub fn get_chat_event_stream(&self) -> broadcast::Receiver<Arc<ChatEvent>> {
+        self.chat_events.subscribe()
+    }
+
     /// Determines the contact's online status based on their last seen time
     pub async fn get_contact_online_status(
         &mut self,
@@ -223,4 +254,105 @@ impl ContactsServiceHandle {
             _ => Err(ContactsServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns the totals of the completed transactions exchanged with a contact, including the time of the last
+    /// payment
+    pub async fn get_contact_stats(
+        &mut self,
+        pub_key: CommsPublicKey,
+    ) -> Result<CounterpartyStats, ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::GetContactStats(pub_key))
+            .await??
+        {
+            ContactsServiceResponse::ContactStats(stats) => Ok(*stats),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends an encrypted text message to a contact. The message is stored and returned before it is delivered, its
+    /// delivery is confirmed with a [ChatEvent::DeliveryConfirmed] event.
+    pub async fn send_chat_message(
+        &mut self,
+        pub_key: CommsPublicKey,
+        body: String,
+    ) -> Result<ChatMessage, ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::SendChatMessage(pub_key, body))
+            .await??
+        {
+            ContactsServiceResponse::ChatMessageSent(message) => Ok(*message),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the messages exchanged with a contact, oldest first
+    pub async fn get_chat_messages(
+        &mut self,
+        pub_key: CommsPublicKey,
+    ) -> Result<Vec<ChatMessage>, ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::GetChatMessages(pub_key))
+            .await??
+        {
+            ContactsServiceResponse::ChatMessages(messages) => Ok(messages),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the group identified by the aggregate public key of its members
+    pub async fn get_contact_group(
+        &mut self,
+        aggregate_public_key: CommsPublicKey,
+    ) -> Result<ContactGroup, ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::GetContactGroup(aggregate_public_key))
+            .await??
+        {
+            ContactsServiceResponse::ContactGroup(group) => Ok(group),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns all groups ordered by alias
+    pub async fn get_contact_groups(&mut self) -> Result<Vec<ContactGroup>, ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::GetContactGroups)
+            .await??
+        {
+            ContactsServiceResponse::ContactGroups(groups) => Ok(groups),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Saves a group, replacing the alias and threshold of an existing group with the same members
+    pub async fn upsert_contact_group(&mut self, group: ContactGroup) -> Result<(), ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::UpsertContactGroup(group))
+            .await??
+        {
+            ContactsServiceResponse::ContactGroupSaved => Ok(()),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn remove_contact_group(
+        &mut self,
+        aggregate_public_key: CommsPublicKey,
+    ) -> Result<ContactGroup, ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::RemoveContactGroup(aggregate_public_key))
+            .await??
+        {
+            ContactsServiceResponse::ContactGroupRemoved(group) => Ok(group),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
 }
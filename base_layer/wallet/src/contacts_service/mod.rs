@@ -20,17 +20,28 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod chat;
 pub mod error;
+pub mod groups;
 pub mod handle;
 pub mod service;
 pub mod storage;
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use futures::future;
+use futures::{future, Stream, StreamExt};
 use log::*;
 use tari_comms::connectivity::ConnectivityRequester;
-use tari_p2p::services::liveness::LivenessHandle;
+use tari_comms_dht::Dht;
+use tari_p2p::{
+    comms_connector::SubscriptionFactory,
+    domain_message::DomainMessage,
+    services::{
+        liveness::LivenessHandle,
+        utils::{map_decode, ok_or_skip_result},
+    },
+    tari_message::TariMessageType,
+};
 use tari_service_framework::{
     async_trait,
     reply_channel,
@@ -40,18 +51,24 @@ use tari_service_framework::{
 };
 use tokio::sync::broadcast;
 
-use crate::contacts_service::{
-    handle::ContactsServiceHandle,
-    service::ContactsService,
-    storage::database::{ContactsBackend, ContactsDatabase},
+use crate::{
+    contacts_service::{
+        chat::{ChatMessageProto, ChatReceiptProto},
+        handle::ContactsServiceHandle,
+        service::ContactsService,
+        storage::database::{ContactsBackend, ContactsDatabase},
+    },
+    transaction_service::handle::TransactionServiceHandle,
 };
 
 const LOG_TARGET: &str = "wallet::contacts_service::initializer";
+const SUBSCRIPTION_LABEL: &str = "Contacts Service";
 
 pub struct ContactsServiceInitializer<T>
 where T: ContactsBackend
 {
     backend: Option<T>,
+    subscription_factory: Arc<SubscriptionFactory>,
     contacts_auto_ping_interval: Duration,
     contacts_online_ping_window: usize,
 }
@@ -59,9 +76,15 @@ where T: ContactsBackend
 impl<T> ContactsServiceInitializer<T>
 where T: ContactsBackend
 {
-    pub fn new(backend: T, contacts_auto_ping_interval: Duration, online_ping_window: usize) -> Self {
+    pub fn new(
+        backend: T,
+        subscription_factory: Arc<SubscriptionFactory>,
+        contacts_auto_ping_interval: Duration,
+        online_ping_window: usize,
+    ) -> Self {
         Self {
             backend: Some(backend),
+            subscription_factory,
             contacts_auto_ping_interval,
             contacts_online_ping_window: online_ping_window,
         }
@@ -75,8 +98,9 @@ where T: ContactsBackend + 'static
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         let (sender, receiver) = reply_channel::unbounded();
         let (publisher, _) = broadcast::channel(250);
+        let (chat_publisher, _) = broadcast::channel(250);
 
-        let contacts_handle = ContactsServiceHandle::new(sender, publisher.clone());
+        let contacts_handle = ContactsServiceHandle::new(sender, publisher.clone(), chat_publisher.clone());
 
         // Register handle before waiting for handles to be ready
         context.register_handle(contacts_handle);
@@ -86,6 +110,9 @@ where T: ContactsBackend + 'static
             .take()
             .expect("Cannot start Contacts Service without setting a storage backend");
 
+        let chat_message_stream = chat_message_stream(&self.subscription_factory);
+        let chat_receipt_stream = chat_receipt_stream(&self.subscription_factory);
+
         let shutdown_signal = context.get_shutdown_signal();
 
         let contacts_auto_ping_interval = self.contacts_auto_ping_interval;
@@ -93,6 +120,9 @@ where T: ContactsBackend + 'static
         context.spawn_when_ready(move |handles| async move {
             let liveness = handles.expect_handle::<LivenessHandle>();
             let connectivity = handles.expect_handle::<ConnectivityRequester>();
+            // Contact stats are only available when the contacts service runs alongside the transaction service
+            let transaction_service = handles.get_handle::<TransactionServiceHandle>();
+            let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
 
             let service = ContactsService::new(
                 ContactsDatabase::new(backend),
@@ -100,7 +130,12 @@ where T: ContactsBackend + 'static
                 handles.get_shutdown_signal(),
                 liveness,
                 connectivity,
+                transaction_service,
+                outbound_message_service,
+                chat_message_stream,
+                chat_receipt_stream,
                 publisher,
+                chat_publisher,
                 contacts_auto_ping_interval,
                 contacts_online_ping_window,
             )
@@ -112,3 +147,33 @@ where T: ContactsBackend + 'static
         Ok(())
     }
 }
+
+fn chat_message_stream(
+    subscription_factory: &SubscriptionFactory,
+) -> impl Stream<Item = DomainMessage<ChatMessageProto>> {
+    trace!(
+        target: LOG_TARGET,
+        "Subscription '{}' for topic '{:?}' created.",
+        SUBSCRIPTION_LABEL,
+        TariMessageType::Text
+    );
+    subscription_factory
+        .get_subscription(TariMessageType::Text, SUBSCRIPTION_LABEL)
+        .map(map_decode::<ChatMessageProto>)
+        .filter_map(ok_or_skip_result)
+}
+
+fn chat_receipt_stream(
+    subscription_factory: &SubscriptionFactory,
+) -> impl Stream<Item = DomainMessage<ChatReceiptProto>> {
+    trace!(
+        target: LOG_TARGET,
+        "Subscription '{}' for topic '{:?}' created.",
+        SUBSCRIPTION_LABEL,
+        TariMessageType::TextAck
+    );
+    subscription_factory
+        .get_subscription(TariMessageType::TextAck, SUBSCRIPTION_LABEL)
+        .map(map_decode::<ChatReceiptProto>)
+        .filter_map(ok_or_skip_result)
+}
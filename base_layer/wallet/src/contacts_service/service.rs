@@ -28,18 +28,40 @@ use std::{
 };
 
 use chrono::{NaiveDateTime, Utc};
-use futures::{pin_mut, StreamExt};
+use futures::{pin_mut, Stream, StreamExt};
 use log::*;
-use tari_comms::connectivity::{ConnectivityEvent, ConnectivityRequester};
-use tari_p2p::services::liveness::{LivenessEvent, LivenessHandle, MetadataKey, PingPongEvent};
+use tari_comms::{
+    connectivity::{ConnectivityEvent, ConnectivityRequester},
+    types::CommsPublicKey,
+};
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
+};
+use tari_p2p::{
+    domain_message::DomainMessage,
+    services::liveness::{LivenessEvent, LivenessHandle, MetadataKey, PingPongEvent},
+    tari_message::TariMessageType,
+};
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
+use tari_utilities::hex::to_hex;
 use tokio::sync::broadcast;
 
-use crate::contacts_service::{
-    error::ContactsServiceError,
-    handle::{ContactsLivenessData, ContactsLivenessEvent, ContactsServiceRequest, ContactsServiceResponse},
-    storage::database::{Contact, ContactsBackend, ContactsDatabase},
+use crate::{
+    contacts_service::{
+        chat::{ChatMessageProto, ChatReceiptProto},
+        error::{ContactsServiceError, ContactsServiceStorageError},
+        handle::{
+            ChatEvent,
+            ContactsLivenessData,
+            ContactsLivenessEvent,
+            ContactsServiceRequest,
+            ContactsServiceResponse,
+        },
+        storage::database::{ChatMessage, Contact, ContactGroup, ContactsBackend, ContactsDatabase},
+    },
+    transaction_service::handle::TransactionServiceHandle,
 };
 
 const LOG_TARGET: &str = "wallet:contacts_service";
@@ -81,7 +103,7 @@ impl Display for ContactOnlineStatus {
     }
 }
 
-pub struct ContactsService<T>
+pub struct ContactsService<T, TChatStream, TReceiptStream>
 where T: ContactsBackend + 'static
 {
     db: ContactsDatabase<T>,
@@ -91,14 +113,22 @@ where T: ContactsBackend + 'static
     liveness: LivenessHandle,
     liveness_data: Vec<ContactsLivenessData>,
     connectivity: ConnectivityRequester,
+    transaction_service: Option<TransactionServiceHandle>,
+    outbound_message_service: OutboundMessageRequester,
+    chat_message_stream: Option<TChatStream>,
+    chat_receipt_stream: Option<TReceiptStream>,
     event_publisher: broadcast::Sender<Arc<ContactsLivenessEvent>>,
+    chat_event_publisher: broadcast::Sender<Arc<ChatEvent>>,
     number_of_rounds_no_pings: u16,
     contacts_auto_ping_interval: Duration,
     contacts_online_ping_window: usize,
 }
 
-impl<T> ContactsService<T>
-where T: ContactsBackend + 'static
+impl<T, TChatStream, TReceiptStream> ContactsService<T, TChatStream, TReceiptStream>
+where
+    T: ContactsBackend + 'static,
+    TChatStream: Stream<Item = DomainMessage<ChatMessageProto>>,
+    TReceiptStream: Stream<Item = DomainMessage<ChatReceiptProto>>,
 {
     pub fn new(
         db: ContactsDatabase<T>,
@@ -109,7 +139,12 @@ where T: ContactsBackend + 'static
         shutdown_signal: ShutdownSignal,
         liveness: LivenessHandle,
         connectivity: ConnectivityRequester,
+        transaction_service: Option<TransactionServiceHandle>,
+        outbound_message_service: OutboundMessageRequester,
+        chat_message_stream: TChatStream,
+        chat_receipt_stream: TReceiptStream,
         event_publisher: broadcast::Sender<Arc<ContactsLivenessEvent>>,
+        chat_event_publisher: broadcast::Sender<Arc<ChatEvent>>,
         contacts_auto_ping_interval: Duration,
         contacts_online_ping_window: usize,
     ) -> Self {
@@ -120,7 +155,12 @@ where T: ContactsBackend + 'static
             liveness,
             liveness_data: Vec::new(),
             connectivity,
+            transaction_service,
+            outbound_message_service,
+            chat_message_stream: Some(chat_message_stream),
+            chat_receipt_stream: Some(chat_receipt_stream),
             event_publisher,
+            chat_event_publisher,
             number_of_rounds_no_pings: 0,
             contacts_auto_ping_interval,
             contacts_online_ping_window,
@@ -141,6 +181,19 @@ where T: ContactsBackend + 'static
         let connectivity_events = self.connectivity.get_event_subscription();
         pin_mut!(connectivity_events);
 
+        let chat_message_stream = self
+            .chat_message_stream
+            .take()
+            .expect("Contacts Service initialized without chat_message_stream")
+            .fuse();
+        pin_mut!(chat_message_stream);
+        let chat_receipt_stream = self
+            .chat_receipt_stream
+            .take()
+            .expect("Contacts Service initialized without chat_receipt_stream")
+            .fuse();
+        pin_mut!(chat_receipt_stream);
+
         let shutdown = self
             .shutdown_signal
             .take()
@@ -179,6 +232,20 @@ where T: ContactsBackend + 'static
                     self.handle_connectivity_event(event);
                 }
 
+                Some(message) = chat_message_stream.next() => {
+                    let _result = self.handle_chat_message(message).await.map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to handle inbound chat message: {:?}", e);
+                        e
+                    });
+                },
+
+                Some(receipt) = chat_receipt_stream.next() => {
+                    let _result = self.handle_chat_receipt(receipt).map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to handle chat delivery receipt: {:?}", e);
+                        e
+                    });
+                },
+
                 _ = shutdown.wait() => {
                     info!(target: LOG_TARGET, "Contacts service shutting down because it received the shutdown signal");
                     break;
@@ -232,7 +299,181 @@ where T: ContactsBackend + 'static
                 let result = self.get_online_status(&contact).await;
                 Ok(result.map(ContactsServiceResponse::OnlineStatus)?)
             },
+            ContactsServiceRequest::GetContactStats(pk) => {
+                let stats = self
+                    .transaction_service
+                    .as_mut()
+                    .ok_or(ContactsServiceError::TransactionServiceUnavailable)?
+                    .get_counterparty_stats(pk)
+                    .await?;
+                Ok(ContactsServiceResponse::ContactStats(Box::new(stats)))
+            },
+            ContactsServiceRequest::SendChatMessage(pk, body) => {
+                let message = self.send_chat_message(pk, body).await?;
+                Ok(ContactsServiceResponse::ChatMessageSent(Box::new(message)))
+            },
+            ContactsServiceRequest::GetChatMessages(pk) => Ok(self
+                .db
+                .get_chat_messages(pk)
+                .map(ContactsServiceResponse::ChatMessages)?),
+            ContactsServiceRequest::GetContactGroup(pk) => Ok(self
+                .db
+                .get_contact_group(pk)
+                .map(ContactsServiceResponse::ContactGroup)?),
+            ContactsServiceRequest::GetContactGroups => Ok(self
+                .db
+                .get_contact_groups()
+                .map(ContactsServiceResponse::ContactGroups)?),
+            ContactsServiceRequest::UpsertContactGroup(group) => {
+                // Groups are validated again, as the fields of a group can be set without its constructor
+                let group = ContactGroup::new(group.alias, group.members, group.threshold)?;
+                info!(
+                    target: LOG_TARGET,
+                    "Contact Group Saved: \nAlias: {}\nAggregate PubKey: {}\nThreshold: {} of {}",
+                    group.alias,
+                    group.aggregate_public_key,
+                    group.threshold,
+                    group.members.len()
+                );
+                self.db.upsert_contact_group(group)?;
+                Ok(ContactsServiceResponse::ContactGroupSaved)
+            },
+            ContactsServiceRequest::RemoveContactGroup(pk) => {
+                let group = self.db.remove_contact_group(pk)?;
+                info!(
+                    target: LOG_TARGET,
+                    "Contact Group Removed: \nAlias: {}\nAggregate PubKey: {}", group.alias, group.aggregate_public_key
+                );
+                Ok(ContactsServiceResponse::ContactGroupRemoved(group))
+            },
+        }
+    }
+
+    async fn send_chat_message(
+        &mut self,
+        address: CommsPublicKey,
+        body: String,
+    ) -> Result<ChatMessage, ContactsServiceError> {
+        match self.db.get_contact(address.clone()) {
+            Ok(_) => {},
+            Err(ContactsServiceStorageError::ValueNotFound(_)) => return Err(ContactsServiceError::ContactNotFound),
+            Err(e) => return Err(e.into()),
+        }
+        let message = ChatMessage::new_outbound(address.clone(), body)?;
+        self.db.save_chat_message(message.clone())?;
+        self.send_encrypted(address, TariMessageType::Text, ChatMessageProto::from(&message))
+            .await?;
+        debug!(
+            target: LOG_TARGET,
+            "Chat message {} sent to {}",
+            to_hex(&message.message_id),
+            message.address
+        );
+        Ok(message)
+    }
+
+    /// Stores a message from a contact and acknowledges it with a receipt. Messages that were not encrypted by their
+    /// origin, or that come from someone who is not a contact, are dropped.
+    async fn handle_chat_message(
+        &mut self,
+        message: DomainMessage<ChatMessageProto>,
+    ) -> Result<(), ContactsServiceError> {
+        let (address, message) = match message.authenticated_origin {
+            Some(origin) => (origin, message.inner),
+            None => {
+                return Err(ContactsServiceError::InvalidChatMessage(
+                    "Message has no authenticated origin".to_string(),
+                ))
+            },
+        };
+        if let Err(ContactsServiceStorageError::ValueNotFound(_)) = self.db.get_contact(address.clone()) {
+            debug!(
+                target: LOG_TARGET,
+                "Dropping chat message from unknown peer {}", address
+            );
+            return Ok(());
+        }
+
+        let message = ChatMessage::from_inbound(address.clone(), message)?;
+        // The same message arrives directly and via store-and-forward, only the first copy is acknowledged
+        if !self.db.save_chat_message(message.clone())? {
+            trace!(
+                target: LOG_TARGET,
+                "Chat message {} was already received",
+                to_hex(&message.message_id)
+            );
+            return Ok(());
+        }
+        let receipt = ChatReceiptProto {
+            message_id: message.message_id.clone(),
+        };
+        self.send_encrypted(address, TariMessageType::TextAck, receipt).await?;
+
+        // Send only fails if there are no subscribers.
+        let _size = self
+            .chat_event_publisher
+            .send(Arc::new(ChatEvent::MessageReceived(Box::new(message))));
+        Ok(())
+    }
+
+    fn handle_chat_receipt(&mut self, receipt: DomainMessage<ChatReceiptProto>) -> Result<(), ContactsServiceError> {
+        let address = match receipt.authenticated_origin {
+            Some(origin) => origin,
+            None => {
+                return Err(ContactsServiceError::InvalidChatMessage(
+                    "Receipt has no authenticated origin".to_string(),
+                ))
+            },
+        };
+        let confirmed = self
+            .db
+            .confirm_chat_delivery(receipt.inner.message_id, address, Utc::now().naive_utc())?;
+        if let Some(message) = confirmed {
+            debug!(
+                target: LOG_TARGET,
+                "Delivery of chat message {} confirmed",
+                to_hex(&message.message_id)
+            );
+            // Send only fails if there are no subscribers.
+            let _size = self
+                .chat_event_publisher
+                .send(Arc::new(ChatEvent::DeliveryConfirmed(Box::new(message))));
         }
+        Ok(())
+    }
+
+    /// Sends a message encrypted for the destination, both directly and to the neighbours of the destination for
+    /// store-and-forward in case it is offline
+    async fn send_encrypted<M>(
+        &mut self,
+        destination: CommsPublicKey,
+        message_type: TariMessageType,
+        message: M,
+    ) -> Result<(), ContactsServiceError>
+    where
+        M: prost::Message + Clone,
+    {
+        let _send_message_response = self
+            .outbound_message_service
+            .send_message(
+                SendMessageParams::new()
+                    .direct_public_key(destination.clone())
+                    .with_encryption(OutboundEncryption::encrypt_for(destination.clone()))
+                    .with_discovery(true)
+                    .finish(),
+                OutboundDomainMessage::new(&message_type, message.clone()),
+            )
+            .await?;
+        let _message_send_states = self
+            .outbound_message_service
+            .closest_broadcast(
+                destination.clone(),
+                OutboundEncryption::encrypt_for(destination),
+                vec![],
+                OutboundDomainMessage::new(&message_type, message),
+            )
+            .await?;
+        Ok(())
     }
 
     async fn add_contacts_to_liveness_service(&mut self, contacts: &[Contact]) -> Result<(), ContactsServiceError> {
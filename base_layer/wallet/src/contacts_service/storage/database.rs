@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    convert::TryFrom,
     fmt::{Display, Error, Formatter},
     sync::Arc,
 };
@@ -59,6 +60,62 @@ impl Contact {
     }
 }
 
+/// Whether a chat message was received from or sent to a contact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatDirection {
+    Inbound = 0,
+    Outbound = 1,
+}
+
+impl TryFrom<i32> for ChatDirection {
+    type Error = ContactsServiceStorageError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ChatDirection::Inbound),
+            1 => Ok(ChatDirection::Outbound),
+            _ => Err(ContactsServiceStorageError::ConversionError),
+        }
+    }
+}
+
+impl Display for ChatDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ChatDirection::Inbound => write!(f, "Inbound"),
+            ChatDirection::Outbound => write!(f, "Outbound"),
+        }
+    }
+}
+
+/// A text message exchanged with the contact identified by `address`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub message_id: Vec<u8>,
+    pub address: CommsPublicKey,
+    pub body: String,
+    pub direction: ChatDirection,
+    /// The time the sender created the message
+    pub sent_at: NaiveDateTime,
+    /// The time the message was stored by this wallet
+    pub stored_at: NaiveDateTime,
+    /// The time the receipt of the contact was received, only set for outbound messages
+    pub delivery_confirmed_at: Option<NaiveDateTime>,
+}
+
+/// A contact made up of several members, of which `threshold` have to sign together. Groups are created with
+/// `ContactGroup::new`, which validates the threshold and orders the members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactGroup {
+    pub alias: String,
+    /// The sum of the public keys of the members, which identifies the group. It is not protected against rogue key
+    /// attacks, so it must not be used as a signing key.
+    pub aggregate_public_key: CommsPublicKey,
+    /// The public keys of the members in ascending order
+    pub members: Vec<CommsPublicKey>,
+    pub threshold: u8,
+}
+
 /// This trait defines the functionality that a database backend need to provide for the Contacts Service
 pub trait ContactsBackend: Send + Sync + Clone {
     /// Retrieve the record associated with the provided DbKey
@@ -72,23 +129,36 @@ pub enum DbKey {
     Contact(CommsPublicKey),
     ContactId(NodeId),
     Contacts,
+    ChatMessages(CommsPublicKey),
+    ContactGroup(CommsPublicKey),
+    ContactGroups,
 }
 
 pub enum DbValue {
     Contact(Box<Contact>),
     Contacts(Vec<Contact>),
     PublicKey(Box<CommsPublicKey>),
+    ChatMessage(Box<ChatMessage>),
+    ChatMessages(Vec<ChatMessage>),
+    ContactGroup(Box<ContactGroup>),
+    ContactGroups(Vec<ContactGroup>),
 }
 
 #[allow(clippy::large_enum_variant)]
 pub enum DbKeyValuePair {
     Contact(CommsPublicKey, Contact),
     LastSeen(NodeId, NaiveDateTime, Option<i32>),
+    /// Stores a chat message unless a message with the same id was stored before
+    ChatMessage(ChatMessage),
+    /// The message id, the address of the contact that confirmed the delivery and the time of confirmation
+    ChatDelivery(Vec<u8>, CommsPublicKey, NaiveDateTime),
+    ContactGroup(ContactGroup),
 }
 
 pub enum WriteOperation {
     Upsert(Box<DbKeyValuePair>),
     UpdateLastSeen(Box<DbKeyValuePair>),
+    ConfirmChatDelivery(Box<DbKeyValuePair>),
     Remove(DbKey),
 }
 
@@ -173,11 +243,87 @@ where T: ContactsBackend + 'static
             .ok_or_else(|| ContactsServiceStorageError::ValueNotFound(DbKey::Contact(pub_key.clone())))?;
         match result {
             DbValue::Contact(c) => Ok(*c),
-            DbValue::Contacts(_) | DbValue::PublicKey(_) => Err(ContactsServiceStorageError::UnexpectedResult(
+            _ => Err(ContactsServiceStorageError::UnexpectedResult(
+                "Incorrect response from backend.".to_string(),
+            )),
+        }
+    }
+
+    /// Stores a chat message, returning false if a message with the same id was already stored. Messages are sent
+    /// both directly and via store-and-forward, so the same message is usually received more than once.
+    pub fn save_chat_message(&self, message: ChatMessage) -> Result<bool, ContactsServiceStorageError> {
+        let result = self
+            .db
+            .write(WriteOperation::Upsert(Box::new(DbKeyValuePair::ChatMessage(message))))?;
+        Ok(result.is_some())
+    }
+
+    /// Returns the messages exchanged with a contact, oldest first
+    pub fn get_chat_messages(&self, address: CommsPublicKey) -> Result<Vec<ChatMessage>, ContactsServiceStorageError> {
+        let key = DbKey::ChatMessages(address);
+        match self.db.fetch(&key) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::ChatMessages(messages))) => Ok(messages),
+            Ok(Some(other)) => unexpected_result(key, other),
+            Err(e) => log_error(key, e),
+        }
+    }
+
+    /// Marks an outbound message as delivered. Only the contact the message was sent to can confirm its delivery.
+    /// Returns the updated message, or None if it does not exist or its delivery was already confirmed.
+    pub fn confirm_chat_delivery(
+        &self,
+        message_id: Vec<u8>,
+        address: CommsPublicKey,
+        confirmed_at: NaiveDateTime,
+    ) -> Result<Option<ChatMessage>, ContactsServiceStorageError> {
+        let result = self.db.write(WriteOperation::ConfirmChatDelivery(Box::new(
+            DbKeyValuePair::ChatDelivery(message_id, address, confirmed_at),
+        )))?;
+        match result {
+            None => Ok(None),
+            Some(DbValue::ChatMessage(m)) => Ok(Some(*m)),
+            Some(_) => Err(ContactsServiceStorageError::UnexpectedResult(
                 "Incorrect response from backend.".to_string(),
             )),
         }
     }
+
+    pub fn get_contact_group(
+        &self,
+        aggregate_public_key: CommsPublicKey,
+    ) -> Result<ContactGroup, ContactsServiceStorageError> {
+        let db_clone = self.db.clone();
+        fetch!(db_clone, aggregate_public_key, ContactGroup)
+    }
+
+    pub fn get_contact_groups(&self) -> Result<Vec<ContactGroup>, ContactsServiceStorageError> {
+        match self.db.fetch(&DbKey::ContactGroups) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::ContactGroups(groups))) => Ok(groups),
+            Ok(Some(other)) => unexpected_result(DbKey::ContactGroups, other),
+            Err(e) => log_error(DbKey::ContactGroups, e),
+        }
+    }
+
+    /// Stores a group, or updates the alias and threshold of the group with the same members
+    pub fn upsert_contact_group(&self, group: ContactGroup) -> Result<(), ContactsServiceStorageError> {
+        self.db
+            .write(WriteOperation::Upsert(Box::new(DbKeyValuePair::ContactGroup(group))))?;
+        Ok(())
+    }
+
+    pub fn remove_contact_group(
+        &self,
+        aggregate_public_key: CommsPublicKey,
+    ) -> Result<ContactGroup, ContactsServiceStorageError> {
+        let key = DbKey::ContactGroup(aggregate_public_key);
+        match self.db.write(WriteOperation::Remove(key.clone()))? {
+            None => Err(ContactsServiceStorageError::ValueNotFound(key)),
+            Some(DbValue::ContactGroup(g)) => Ok(*g),
+            Some(other) => unexpected_result(key, other),
+        }
+    }
 }
 
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, ContactsServiceStorageError> {
@@ -192,6 +338,9 @@ impl Display for DbKey {
             DbKey::Contact(c) => f.write_str(&format!("Contact: {:?}", c)),
             DbKey::ContactId(id) => f.write_str(&format!("Contact: {:?}", id)),
             DbKey::Contacts => f.write_str("Contacts"),
+            DbKey::ChatMessages(pk) => f.write_str(&format!("Chat messages: {:?}", pk)),
+            DbKey::ContactGroup(pk) => f.write_str(&format!("Contact group: {:?}", pk)),
+            DbKey::ContactGroups => f.write_str("Contact groups"),
         }
     }
 }
@@ -202,6 +351,10 @@ impl Display for DbValue {
             DbValue::Contact(_) => f.write_str("Contact"),
             DbValue::Contacts(_) => f.write_str("Contacts"),
             DbValue::PublicKey(_) => f.write_str("PublicKey"),
+            DbValue::ChatMessage(_) => f.write_str("ChatMessage"),
+            DbValue::ChatMessages(_) => f.write_str("ChatMessages"),
+            DbValue::ContactGroup(_) => f.write_str("ContactGroup"),
+            DbValue::ContactGroups(_) => f.write_str("ContactGroups"),
         }
     }
 }
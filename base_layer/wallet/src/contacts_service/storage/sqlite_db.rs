@@ -26,14 +26,25 @@ use chrono::NaiveDateTime;
 use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
 use tari_common_types::types::PublicKey;
 use tari_comms::peer_manager::NodeId;
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
 use tari_utilities::ByteArray;
 
 use crate::{
     contacts_service::{
         error::ContactsServiceStorageError,
-        storage::database::{Contact, ContactsBackend, DbKey, DbKeyValuePair, DbValue, WriteOperation},
+        storage::database::{
+            ChatDirection,
+            ChatMessage,
+            Contact,
+            ContactGroup,
+            ContactsBackend,
+            DbKey,
+            DbKeyValuePair,
+            DbValue,
+            WriteOperation,
+        },
     },
-    schema::contacts,
+    schema::{chat_messages, contact_groups, contacts},
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
     util::diesel_ext::ExpectedRowsExtension,
 };
@@ -70,6 +81,23 @@ impl ContactsBackend for ContactsServiceSqliteDatabase {
                     .map(|c| Contact::try_from(c.clone()))
                     .collect::<Result<Vec<_>, _>>()?,
             )),
+            DbKey::ChatMessages(pk) => Some(DbValue::ChatMessages(
+                ChatMessageSql::find_by_address(&pk.to_vec(), &conn)?
+                    .into_iter()
+                    .map(ChatMessage::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            DbKey::ContactGroup(pk) => match ContactGroupSql::find(&pk.to_vec(), &conn) {
+                Ok(g) => Some(DbValue::ContactGroup(Box::new(ContactGroup::try_from(g)?))),
+                Err(ContactsServiceStorageError::DieselError(DieselError::NotFound)) => None,
+                Err(e) => return Err(e),
+            },
+            DbKey::ContactGroups => Some(DbValue::ContactGroups(
+                ContactGroupSql::index(&conn)?
+                    .into_iter()
+                    .map(ContactGroup::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
         };
 
         Ok(result)
@@ -95,7 +123,17 @@ impl ContactsBackend for ContactsServiceSqliteDatabase {
                         ContactSql::from(c).commit(&conn)?;
                     },
                 },
-                DbKeyValuePair::LastSeen(..) => return Err(ContactsServiceStorageError::OperationNotSupported),
+                DbKeyValuePair::ChatMessage(m) => {
+                    if ChatMessageSql::from(m.clone()).commit_if_new(&conn)? {
+                        return Ok(Some(DbValue::ChatMessage(Box::new(m))));
+                    }
+                },
+                DbKeyValuePair::ContactGroup(g) => {
+                    ContactGroupSql::from(g).upsert(&conn)?;
+                },
+                DbKeyValuePair::LastSeen(..) | DbKeyValuePair::ChatDelivery(..) => {
+                    return Err(ContactsServiceStorageError::OperationNotSupported)
+                },
             },
             WriteOperation::UpdateLastSeen(kvp) => match *kvp {
                 DbKeyValuePair::LastSeen(node_id, date_time, latency) => {
@@ -117,7 +155,22 @@ impl ContactsBackend for ContactsServiceSqliteDatabase {
                         Err(e) => return Err(e),
                     }
                 },
-                DbKeyValuePair::Contact(..) => return Err(ContactsServiceStorageError::OperationNotSupported),
+                DbKeyValuePair::Contact(..) |
+                DbKeyValuePair::ChatMessage(..) |
+                DbKeyValuePair::ChatDelivery(..) |
+                DbKeyValuePair::ContactGroup(..) => return Err(ContactsServiceStorageError::OperationNotSupported),
+            },
+            WriteOperation::ConfirmChatDelivery(kvp) => match *kvp {
+                DbKeyValuePair::ChatDelivery(message_id, address, confirmed_at) => {
+                    if ChatMessageSql::confirm_delivery(&message_id, &address.to_vec(), confirmed_at, &conn)? {
+                        let message = ChatMessageSql::find(&message_id, &conn)?;
+                        return Ok(Some(DbValue::ChatMessage(Box::new(ChatMessage::try_from(message)?))));
+                    }
+                },
+                DbKeyValuePair::Contact(..) |
+                DbKeyValuePair::LastSeen(..) |
+                DbKeyValuePair::ChatMessage(..) |
+                DbKeyValuePair::ContactGroup(..) => return Err(ContactsServiceStorageError::OperationNotSupported),
             },
             WriteOperation::Remove(k) => match k {
                 DbKey::Contact(k) => match ContactSql::find_by_public_key(&k.to_vec(), &conn) {
@@ -136,7 +189,17 @@ impl ContactsBackend for ContactsServiceSqliteDatabase {
                     Err(ContactsServiceStorageError::DieselError(DieselError::NotFound)) => (),
                     Err(e) => return Err(e),
                 },
-                DbKey::Contacts => return Err(ContactsServiceStorageError::OperationNotSupported),
+                DbKey::ContactGroup(k) => match ContactGroupSql::find(&k.to_vec(), &conn) {
+                    Ok(g) => {
+                        g.delete(&conn)?;
+                        return Ok(Some(DbValue::ContactGroup(Box::new(ContactGroup::try_from(g)?))));
+                    },
+                    Err(ContactsServiceStorageError::DieselError(DieselError::NotFound)) => (),
+                    Err(e) => return Err(e),
+                },
+                DbKey::Contacts | DbKey::ChatMessages(_) | DbKey::ContactGroups => {
+                    return Err(ContactsServiceStorageError::OperationNotSupported)
+                },
             },
         }
 
@@ -253,10 +316,174 @@ pub struct UpdateContact {
     latency: Option<Option<i32>>,
 }
 
+/// A Sql version of the ChatMessage struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq, Eq)]
+#[table_name = "chat_messages"]
+struct ChatMessageSql {
+    message_id: Vec<u8>,
+    address: Vec<u8>,
+    body: String,
+    direction: i32,
+    sent_at: NaiveDateTime,
+    stored_at: NaiveDateTime,
+    delivery_confirmed_at: Option<NaiveDateTime>,
+}
+
+impl ChatMessageSql {
+    /// Write this struct to the database unless a message with the same id exists, returning true if it was written
+    pub fn commit_if_new(&self, conn: &SqliteConnection) -> Result<bool, ContactsServiceStorageError> {
+        let num_inserted = diesel::insert_or_ignore_into(chat_messages::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(num_inserted > 0)
+    }
+
+    pub fn find(message_id: &[u8], conn: &SqliteConnection) -> Result<ChatMessageSql, ContactsServiceStorageError> {
+        Ok(chat_messages::table
+            .filter(chat_messages::message_id.eq(message_id))
+            .first::<ChatMessageSql>(conn)?)
+    }
+
+    /// Return the messages exchanged with a contact in the order they were sent
+    pub fn find_by_address(
+        address: &[u8],
+        conn: &SqliteConnection,
+    ) -> Result<Vec<ChatMessageSql>, ContactsServiceStorageError> {
+        Ok(chat_messages::table
+            .filter(chat_messages::address.eq(address))
+            .order((chat_messages::sent_at.asc(), chat_messages::stored_at.asc()))
+            .load::<ChatMessageSql>(conn)?)
+    }
+
+    /// Set the delivery time of an unconfirmed outbound message sent to `address`, returning true if it was set
+    pub fn confirm_delivery(
+        message_id: &[u8],
+        address: &[u8],
+        confirmed_at: NaiveDateTime,
+        conn: &SqliteConnection,
+    ) -> Result<bool, ContactsServiceStorageError> {
+        let num_updated = diesel::update(
+            chat_messages::table
+                .filter(chat_messages::message_id.eq(message_id))
+                .filter(chat_messages::address.eq(address))
+                .filter(chat_messages::direction.eq(ChatDirection::Outbound as i32))
+                .filter(chat_messages::delivery_confirmed_at.is_null()),
+        )
+        .set(chat_messages::delivery_confirmed_at.eq(Some(confirmed_at)))
+        .execute(conn)?;
+        Ok(num_updated > 0)
+    }
+}
+
+impl TryFrom<ChatMessageSql> for ChatMessage {
+    type Error = ContactsServiceStorageError;
+
+    fn try_from(o: ChatMessageSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            message_id: o.message_id,
+            address: PublicKey::from_vec(&o.address).map_err(|_| ContactsServiceStorageError::ConversionError)?,
+            body: o.body,
+            direction: ChatDirection::try_from(o.direction)?,
+            sent_at: o.sent_at,
+            stored_at: o.stored_at,
+            delivery_confirmed_at: o.delivery_confirmed_at,
+        })
+    }
+}
+
+impl From<ChatMessage> for ChatMessageSql {
+    fn from(o: ChatMessage) -> Self {
+        Self {
+            message_id: o.message_id,
+            address: o.address.to_vec(),
+            body: o.body,
+            direction: o.direction as i32,
+            sent_at: o.sent_at,
+            stored_at: o.stored_at,
+            delivery_confirmed_at: o.delivery_confirmed_at,
+        }
+    }
+}
+
+/// A Sql version of the ContactGroup struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq, Eq)]
+#[table_name = "contact_groups"]
+struct ContactGroupSql {
+    aggregate_public_key: Vec<u8>,
+    alias: String,
+    threshold: i32,
+    members: Vec<u8>,
+}
+
+impl ContactGroupSql {
+    /// Write this struct to the database, replacing the group with the same members if it exists
+    pub fn upsert(&self, conn: &SqliteConnection) -> Result<(), ContactsServiceStorageError> {
+        diesel::replace_into(contact_groups::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Return all groups
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<ContactGroupSql>, ContactsServiceStorageError> {
+        Ok(contact_groups::table
+            .order(contact_groups::alias.asc())
+            .load::<ContactGroupSql>(conn)?)
+    }
+
+    pub fn find(
+        aggregate_public_key: &[u8],
+        conn: &SqliteConnection,
+    ) -> Result<ContactGroupSql, ContactsServiceStorageError> {
+        Ok(contact_groups::table
+            .filter(contact_groups::aggregate_public_key.eq(aggregate_public_key))
+            .first::<ContactGroupSql>(conn)?)
+    }
+
+    pub fn delete(&self, conn: &SqliteConnection) -> Result<(), ContactsServiceStorageError> {
+        diesel::delete(
+            contact_groups::table.filter(contact_groups::aggregate_public_key.eq(&self.aggregate_public_key)),
+        )
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+impl TryFrom<ContactGroupSql> for ContactGroup {
+    type Error = ContactsServiceStorageError;
+
+    fn try_from(o: ContactGroupSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            alias: o.alias,
+            aggregate_public_key: PublicKey::from_vec(&o.aggregate_public_key)
+                .map_err(|_| ContactsServiceStorageError::ConversionError)?,
+            members: o
+                .members
+                .chunks(PublicKey::key_length())
+                .map(|member| PublicKey::from_bytes(member).map_err(|_| ContactsServiceStorageError::ConversionError))
+                .collect::<Result<Vec<_>, _>>()?,
+            threshold: u8::try_from(o.threshold).map_err(|_| ContactsServiceStorageError::ConversionError)?,
+        })
+    }
+}
+
+impl From<ContactGroup> for ContactGroupSql {
+    fn from(o: ContactGroup) -> Self {
+        Self {
+            aggregate_public_key: o.aggregate_public_key.to_vec(),
+            alias: o.alias,
+            threshold: i32::from(o.threshold),
+            members: o.members.iter().flat_map(|member| member.as_bytes().to_vec()).collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::convert::TryFrom;
 
+    use chrono::{Duration, NaiveDateTime, Utc};
     use diesel::{Connection, SqliteConnection};
     use rand::rngs::OsRng;
     use tari_common_types::types::{PrivateKey, PublicKey};
@@ -267,8 +494,8 @@ mod test {
     use tari_test_utils::{paths::with_temp_dir, random::string};
 
     use crate::contacts_service::storage::{
-        database::Contact,
-        sqlite_db::{ContactSql, UpdateContact},
+        database::{ChatDirection, ChatMessage, Contact, ContactGroup},
+        sqlite_db::{ChatMessageSql, ContactGroupSql, ContactSql, UpdateContact},
     };
 
     #[test]
@@ -330,4 +557,92 @@ mod test {
             assert_eq!(c_updated.alias, "Fred".to_string());
         });
     }
+
+    #[test]
+    fn test_chat_messages() {
+        with_temp_dir(|dir_path| {
+            let db_path = format!("{}/{}.sqlite3", dir_path.to_str().unwrap(), string(8).as_str());
+
+            embed_migrations!("./migrations");
+            let conn =
+                SqliteConnection::establish(&db_path).unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+            embedded_migrations::run(&conn).expect("Migration failed");
+
+            let alice = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+            let bob = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+            let now = Utc::now().naive_utc();
+            let message = |id: u8, address: &PublicKey, direction: ChatDirection, sent_at: NaiveDateTime| ChatMessage {
+                message_id: vec![id; 32],
+                address: address.clone(),
+                body: format!("Message {}", id),
+                direction,
+                sent_at,
+                stored_at: now,
+                delivery_confirmed_at: None,
+            };
+
+            let later = message(1, &alice, ChatDirection::Inbound, now);
+            let earlier = message(2, &alice, ChatDirection::Outbound, now - Duration::seconds(10));
+            let other = message(3, &bob, ChatDirection::Outbound, now);
+            for m in [&later, &earlier, &other] {
+                assert!(ChatMessageSql::from(m.clone()).commit_if_new(&conn).unwrap());
+            }
+            // A message received a second time is ignored
+            assert!(!ChatMessageSql::from(later.clone()).commit_if_new(&conn).unwrap());
+
+            let messages = ChatMessageSql::find_by_address(&alice.to_vec(), &conn)
+                .unwrap()
+                .into_iter()
+                .map(|m| ChatMessage::try_from(m).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(messages, vec![earlier.clone(), later.clone()]);
+
+            // Inbound messages and messages sent to another contact cannot be confirmed
+            assert!(!ChatMessageSql::confirm_delivery(&later.message_id, &alice.to_vec(), now, &conn).unwrap());
+            assert!(!ChatMessageSql::confirm_delivery(&other.message_id, &alice.to_vec(), now, &conn).unwrap());
+
+            assert!(ChatMessageSql::confirm_delivery(&earlier.message_id, &alice.to_vec(), now, &conn).unwrap());
+            assert!(!ChatMessageSql::confirm_delivery(&earlier.message_id, &alice.to_vec(), now, &conn).unwrap());
+            let confirmed = ChatMessageSql::find(&earlier.message_id, &conn).unwrap();
+            assert_eq!(confirmed.delivery_confirmed_at, Some(now));
+        });
+    }
+
+    #[test]
+    fn test_contact_groups() {
+        with_temp_dir(|dir_path| {
+            let db_path = format!("{}/{}.sqlite3", dir_path.to_str().unwrap(), string(8).as_str());
+
+            embed_migrations!("./migrations");
+            let conn =
+                SqliteConnection::establish(&db_path).unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+            embedded_migrations::run(&conn).expect("Migration failed");
+
+            let members = (0..3)
+                .map(|_| PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)))
+                .collect::<Vec<_>>();
+            let board = ContactGroup::new("Board".to_string(), members.clone(), 2).unwrap();
+            let pair = ContactGroup::new("Pair".to_string(), members[..2].to_vec(), 1).unwrap();
+            for group in [&board, &pair] {
+                ContactGroupSql::from(group.clone()).upsert(&conn).unwrap();
+            }
+
+            let groups = ContactGroupSql::index(&conn)
+                .unwrap()
+                .into_iter()
+                .map(|g| ContactGroup::try_from(g).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(groups, vec![board.clone(), pair.clone()]);
+
+            // The same members form the same group, so saving them again updates it
+            let renamed = ContactGroup::new("Directors".to_string(), members, 3).unwrap();
+            ContactGroupSql::from(renamed.clone()).upsert(&conn).unwrap();
+            let found = ContactGroupSql::find(&board.aggregate_public_key.to_vec(), &conn).unwrap();
+            assert_eq!(ContactGroup::try_from(found.clone()).unwrap(), renamed);
+
+            found.delete(&conn).unwrap();
+            assert!(found.delete(&conn).is_err());
+            assert_eq!(ContactGroupSql::index(&conn).unwrap().len(), 1);
+        });
+    }
 }
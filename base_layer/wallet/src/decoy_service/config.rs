@@ -0,0 +1,46 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+use tari_core::transactions::tari_amount::MicroTari;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DecoyServiceConfig {
+    /// If true, low value self-spend transactions are created at random intervals to obscure the spending pattern of
+    /// the wallet
+    pub enabled: bool,
+    /// The minimum time between two decoy transactions
+    #[serde(with = "serializers::seconds")]
+    pub min_interval: Duration,
+    /// The maximum time between two decoy transactions
+    #[serde(with = "serializers::seconds")]
+    pub max_interval: Duration,
+    /// The value of each decoy transaction is chosen at random between `min_amount` and `max_amount`
+    pub min_amount: MicroTari,
+    pub max_amount: MicroTari,
+    /// The fee per gram used for decoy transactions
+    pub fee_per_gram: MicroTari,
+    /// The maximum total fees that decoy transactions may spend in any 30 day period
+    pub monthly_fee_budget: MicroTari,
+    /// This is the size of the event channel used to communicate decoy events to the wallet
+    pub event_channel_size: usize,
+}
+
+impl Default for DecoyServiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_interval: Duration::from_secs(12 * 60 * 60),
+            max_interval: Duration::from_secs(72 * 60 * 60),
+            min_amount: MicroTari::from(1_000),
+            max_amount: MicroTari::from(100_000),
+            fee_per_gram: MicroTari::from(5),
+            monthly_fee_budget: MicroTari::from(50_000),
+            event_channel_size: 10,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use thiserror::Error;
+
+use crate::{output_manager_service::error::OutputManagerError, transaction_service::error::TransactionServiceError};
+
+#[derive(Debug, Error)]
+pub enum DecoyServiceError {
+    #[error("Transaction service error: `{0}`")]
+    TransactionServiceError(#[from] TransactionServiceError),
+    #[error("Output manager service error: `{0}`")]
+    OutputManagerError(#[from] OutputManagerError),
+}
@@ -0,0 +1,54 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{fmt, fmt::Formatter, sync::Arc};
+
+use tari_common_types::transaction::TxId;
+use tari_core::transactions::tari_amount::MicroTari;
+use tokio::sync::broadcast;
+
+pub type DecoyEventSender = broadcast::Sender<Arc<DecoyEvent>>;
+pub type DecoyEventReceiver = broadcast::Receiver<Arc<DecoyEvent>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecoyEvent {
+    DecoyTransactionSent {
+        tx_id: TxId,
+        amount: MicroTari,
+    },
+    /// A decoy transaction was skipped because its fee would exceed the remaining monthly fee budget
+    FeeBudgetExhausted {
+        spent: MicroTari,
+        budget: MicroTari,
+    },
+}
+
+impl fmt::Display for DecoyEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DecoyEvent::DecoyTransactionSent { tx_id, amount } => {
+                write!(f, "DecoyTransactionSent: TxId {} of {}", tx_id, amount)
+            },
+            DecoyEvent::FeeBudgetExhausted { spent, budget } => {
+                write!(f, "FeeBudgetExhausted: {} of {} spent", spent, budget)
+            },
+        }
+    }
+}
+
+/// The Decoy Service Handle is a struct that contains the interfaces used to communicate with a running
+/// Decoy Service
+#[derive(Clone)]
+pub struct DecoyServiceHandle {
+    event_stream_sender: DecoyEventSender,
+}
+
+impl DecoyServiceHandle {
+    pub fn new(event_stream_sender: DecoyEventSender) -> Self {
+        Self { event_stream_sender }
+    }
+
+    pub fn get_event_stream(&self) -> DecoyEventReceiver {
+        self.event_stream_sender.subscribe()
+    }
+}
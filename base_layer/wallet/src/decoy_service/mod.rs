@@ -0,0 +1,65 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+pub mod config;
+pub mod error;
+pub mod handle;
+pub mod service;
+
+use log::*;
+use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
+use tokio::sync::broadcast;
+
+use crate::{
+    decoy_service::{config::DecoyServiceConfig, handle::DecoyServiceHandle, service::DecoyService},
+    output_manager_service::handle::OutputManagerHandle,
+    transaction_service::handle::TransactionServiceHandle,
+};
+
+const LOG_TARGET: &str = "wallet::decoy_service";
+
+pub struct DecoyServiceInitializer {
+    config: DecoyServiceConfig,
+}
+
+impl DecoyServiceInitializer {
+    pub fn new(config: DecoyServiceConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl ServiceInitializer for DecoyServiceInitializer {
+    async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
+        info!(target: LOG_TARGET, "Wallet decoy service initializing.");
+
+        let (event_publisher, _) = broadcast::channel(self.config.event_channel_size);
+
+        // Register handle before waiting for handles to be ready
+        context.register_handle(DecoyServiceHandle::new(event_publisher.clone()));
+
+        let config = self.config.clone();
+
+        context.spawn_when_ready(move |handles| async move {
+            let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
+            let output_manager_service = handles.expect_handle::<OutputManagerHandle>();
+
+            let result = DecoyService::new(
+                config,
+                transaction_service,
+                output_manager_service,
+                event_publisher,
+                handles.get_shutdown_signal(),
+            )
+            .start()
+            .await;
+
+            info!(
+                target: LOG_TARGET,
+                "Wallet Decoy Service shutdown with result {:?}", result
+            );
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{NaiveDateTime, Utc};
+use log::*;
+use rand::{rngs::OsRng, Rng};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_shutdown::ShutdownSignal;
+use tokio::time;
+
+use super::{
+    config::DecoyServiceConfig,
+    error::DecoyServiceError,
+    handle::{DecoyEvent, DecoyEventSender},
+};
+use crate::{
+    output_manager_service::handle::OutputManagerHandle,
+    transaction_service::{handle::TransactionServiceHandle, storage::models::CompletedTransaction},
+};
+
+const LOG_TARGET: &str = "wallet::decoy_service::service";
+
+/// The period over which the decoy fee budget applies
+const FEE_BUDGET_PERIOD_DAYS: i64 = 30;
+
+/// The decoy service creates low value self-spend transactions at random intervals so that an observer of the chain
+/// cannot infer the wallet's real spending pattern from the timing of its transactions. Decoys are flagged in the
+/// transaction history and their fees are limited by a monthly budget.
+pub struct DecoyService {
+    config: DecoyServiceConfig,
+    transaction_service: TransactionServiceHandle,
+    output_manager_service: OutputManagerHandle,
+    event_publisher: DecoyEventSender,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl DecoyService {
+    pub fn new(
+        config: DecoyServiceConfig,
+        transaction_service: TransactionServiceHandle,
+        output_manager_service: OutputManagerHandle,
+        event_publisher: DecoyEventSender,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            transaction_service,
+            output_manager_service,
+            event_publisher,
+            shutdown_signal,
+        }
+    }
+
+    /// Starts the service.
+    pub async fn start(mut self) -> Result<(), DecoyServiceError> {
+        if !self.config.enabled {
+            debug!(target: LOG_TARGET, "Wallet Decoy Service is disabled");
+            return Ok(());
+        }
+        let mut shutdown = self.shutdown_signal.clone();
+
+        debug!(target: LOG_TARGET, "Wallet Decoy Service started");
+        loop {
+            let delay = random_delay(self.config.min_interval, self.config.max_interval);
+            trace!(target: LOG_TARGET, "Next decoy transaction in {:.0?}", delay);
+            tokio::select! {
+                _ = time::sleep(delay) => {
+                    if let Err(e) = self.send_decoy_transaction().await {
+                        warn!(target: LOG_TARGET, "Could not create decoy transaction: {}", e);
+                    }
+                },
+                _ = shutdown.wait() => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Wallet Decoy Service shutting down because the shutdown signal was received"
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a decoy transaction of a random value, unless its estimated fee would exceed the remaining fee budget
+    async fn send_decoy_transaction(&mut self) -> Result<(), DecoyServiceError> {
+        let amount = random_amount(self.config.min_amount, self.config.max_amount);
+        let budget = self.config.monthly_fee_budget;
+        let since = Utc::now().naive_utc() - chrono::Duration::days(FEE_BUDGET_PERIOD_DAYS);
+        let transactions = self.transaction_service.get_completed_transactions().await?;
+        let spent = decoy_fees_since(transactions.values(), since);
+        // A pay-to-self transaction has a recipient and a change output
+        let fee_estimate = self
+            .output_manager_service
+            .fee_estimate(amount, self.config.fee_per_gram, 1, 2)
+            .await?;
+        if spent + fee_estimate > budget {
+            debug!(
+                target: LOG_TARGET,
+                "Skipping decoy transaction, {} of the {} fee budget has been spent", spent, budget
+            );
+            let _size = self
+                .event_publisher
+                .send(Arc::new(DecoyEvent::FeeBudgetExhausted { spent, budget }));
+            return Ok(());
+        }
+
+        let tx_id = self
+            .transaction_service
+            .send_decoy_transaction(amount, self.config.fee_per_gram)
+            .await?;
+        let _size = self
+            .event_publisher
+            .send(Arc::new(DecoyEvent::DecoyTransactionSent { tx_id, amount }));
+        Ok(())
+    }
+}
+
+/// The total fees of the decoy transactions created since `since` that have not been cancelled
+fn decoy_fees_since<'a, I: Iterator<Item = &'a CompletedTransaction>>(
+    transactions: I,
+    since: NaiveDateTime,
+) -> MicroTari {
+    transactions
+        .filter(|tx| tx.is_decoy && tx.cancelled.is_none() && tx.timestamp >= since)
+        .map(|tx| tx.fee)
+        .sum()
+}
+
+fn random_delay(min_interval: Duration, max_interval: Duration) -> Duration {
+    let min = min_interval.min(max_interval).as_secs();
+    let max = min_interval.max(max_interval).as_secs();
+    Duration::from_secs(OsRng.gen_range(min..=max))
+}
+
+fn random_amount(min_amount: MicroTari, max_amount: MicroTari) -> MicroTari {
+    let min = min_amount.min(max_amount).as_u64();
+    let max = min_amount.max(max_amount).as_u64();
+    MicroTari::from(OsRng.gen_range(min..=max))
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::{
+        transaction::{TransactionDirection, TransactionStatus, TxId},
+        types::PrivateKey,
+    };
+    use tari_comms::types::CommsPublicKey;
+    use tari_core::transactions::transaction_components::Transaction;
+    use tari_crypto::keys::PublicKey;
+
+    use super::*;
+    use crate::transaction_service::storage::models::TxCancellationReason;
+
+    fn make_transaction(fee: u64, timestamp: NaiveDateTime, is_decoy: bool) -> CompletedTransaction {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let mut tx = CompletedTransaction::new(
+            TxId::new_random(),
+            public_key.clone(),
+            public_key,
+            MicroTari::from(1000),
+            MicroTari::from(fee),
+            Transaction::new(vec![], vec![], vec![], PrivateKey::default(), PrivateKey::default()),
+            TransactionStatus::Completed,
+            "".to_string(),
+            timestamp,
+            TransactionDirection::Inbound,
+            None,
+            None,
+            None,
+        );
+        tx.is_decoy = is_decoy;
+        tx
+    }
+
+    #[test]
+    fn it_sums_the_fees_of_recent_decoys() {
+        let now = Utc::now().naive_utc();
+        let since = now - chrono::Duration::days(FEE_BUDGET_PERIOD_DAYS);
+        let mut cancelled = make_transaction(1000, now, true);
+        cancelled.cancelled = Some(TxCancellationReason::UserCancelled);
+        let transactions = vec![
+            make_transaction(10, now, true),
+            make_transaction(20, since + chrono::Duration::hours(1), true),
+            make_transaction(40, since - chrono::Duration::hours(1), true),
+            make_transaction(80, now, false),
+            cancelled,
+        ];
+
+        assert_eq!(decoy_fees_since(transactions.iter(), since), MicroTari::from(30));
+    }
+
+    #[test]
+    fn it_stays_within_the_configured_bounds() {
+        for _ in 0..100 {
+            let delay = random_delay(Duration::from_secs(10), Duration::from_secs(20));
+            assert!(delay >= Duration::from_secs(10) && delay <= Duration::from_secs(20));
+            let amount = random_amount(MicroTari::from(200), MicroTari::from(100));
+            assert!(amount >= MicroTari::from(100) && amount <= MicroTari::from(200));
+        }
+    }
+}
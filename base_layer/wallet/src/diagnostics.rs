@@ -0,0 +1,261 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The diagnostics bundle written by [Wallet::generate_diagnostics](crate::Wallet::generate_diagnostics), which users
+//! can attach to bug reports. Settings that could hold secrets are redacted from the config, long hex strings that
+//! could be keys are redacted from the logs, and the finished bundle is checked against the actual secrets of the
+//! wallet before it is written.
+
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::WalletError, network_state::set_owner_only_permissions};
+
+/// The version of the diagnostics bundle format
+const DIAGNOSTICS_VERSION: u32 = 1;
+/// The number of most recent log lines included in a bundle
+const MAX_LOG_LINES: usize = 1000;
+/// The number of most recent warnings and errors included in a bundle
+const MAX_ERROR_LINES: usize = 100;
+/// Hex strings of at least this length are redacted from the logs, as they could be keys
+const MIN_REDACTED_HEX_LENGTH: usize = 64;
+const REDACTED: &str = "<redacted>";
+/// Config settings whose names contain any of these are redacted
+const SECRET_SETTINGS: &[&str] = &[
+    "password",
+    "passphrase",
+    "secret",
+    "private",
+    "seed",
+    "token",
+    "auth",
+    "cookie",
+    "identity",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    version: u32,
+    pub generated_at: String,
+    pub software_version: String,
+    pub network: String,
+    /// The wallet config with its secret settings redacted
+    pub config: Value,
+    pub services: Vec<ServiceDiagnostics>,
+    pub database: DatabaseStatistics,
+    pub connectivity: ConnectivityDiagnostics,
+    /// The most recent warnings and errors in the logs
+    pub recent_errors: Vec<String>,
+    /// The most recent lines of the logs
+    pub logs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceDiagnostics {
+    pub service: String,
+    pub status: String,
+    pub response_time_ms: Option<u128>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DatabaseStatistics {
+    pub db_file_size: Option<u64>,
+    pub unspent_outputs: Option<usize>,
+    pub spent_outputs: Option<usize>,
+    pub completed_transactions: Option<usize>,
+    pub cancelled_transactions: Option<usize>,
+    pub pending_inbound_transactions: Option<usize>,
+    pub pending_outbound_transactions: Option<usize>,
+    pub contacts: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConnectivityDiagnostics {
+    pub comms_status: String,
+    pub base_node_status: String,
+    pub base_node: Option<String>,
+    pub known_peers: usize,
+    pub connected_peers: Vec<String>,
+}
+
+impl DiagnosticsBundle {
+    pub fn new(software_version: String, network: String, config: Value) -> Self {
+        Self {
+            version: DIAGNOSTICS_VERSION,
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            software_version,
+            network,
+            config: redact_config(config),
+            services: Vec::new(),
+            database: DatabaseStatistics::default(),
+            connectivity: ConnectivityDiagnostics::default(),
+            recent_errors: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    /// Adds the most recent lines of the `*.log` files in `log_dir`. Long hex strings and the given secrets are
+    /// redacted from every line.
+    pub fn add_logs(&mut self, log_dir: &Path, secrets: &[String]) -> Result<(), WalletError> {
+        let mut log_files = fs::read_dir(log_dir)
+            .map_err(io_error)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "log"))
+            .collect::<Vec<PathBuf>>();
+        log_files.sort();
+
+        let mut lines = Vec::new();
+        for log_file in log_files {
+            let contents = fs::read_to_string(&log_file).map_err(io_error)?;
+            lines.extend(contents.lines().map(|line| sanitize_log_line(line, secrets)));
+        }
+        self.recent_errors = last(
+            lines.iter().filter(|line| is_error_line(line)).cloned().collect(),
+            MAX_ERROR_LINES,
+        );
+        self.logs = last(lines, MAX_LOG_LINES);
+        Ok(())
+    }
+
+    /// Checks that none of the secrets appear anywhere in the bundle and writes it to `path` as JSON. On unix, the file
+    /// is only readable by its owner.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P, secrets: &[String]) -> Result<(), WalletError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| WalletError::DiagnosticsError(e.to_string()))?;
+        if secrets
+            .iter()
+            .any(|secret| !secret.is_empty() && json.contains(secret.as_str()))
+        {
+            return Err(WalletError::DiagnosticsError(
+                "The diagnostics bundle contains a secret of the wallet and was not written".to_string(),
+            ));
+        }
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(path.as_ref(), json).map_err(io_error)?;
+        set_owner_only_permissions(path.as_ref()).map_err(io_error)?;
+        Ok(())
+    }
+}
+
+/// Replaces the values of the settings whose names suggest they hold secrets, at any depth
+fn redact_config(config: Value) -> Value {
+    match config {
+        Value::Object(settings) => Value::Object(
+            settings
+                .into_iter()
+                .map(|(name, value)| {
+                    let lowercase = name.to_lowercase();
+                    if SECRET_SETTINGS.iter().any(|secret| lowercase.contains(secret)) {
+                        (name, Value::String(REDACTED.to_string()))
+                    } else {
+                        (name, redact_config(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(redact_config).collect()),
+        value => value,
+    }
+}
+
+/// Redacts the secrets and any hex string long enough to be a key from a log line
+fn sanitize_log_line(line: &str, secrets: &[String]) -> String {
+    let mut sanitized = String::with_capacity(line.len());
+    let mut hex_run = String::new();
+    for c in line.chars() {
+        if c.is_ascii_hexdigit() {
+            hex_run.push(c);
+            continue;
+        }
+        flush_hex_run(&mut sanitized, &mut hex_run);
+        sanitized.push(c);
+    }
+    flush_hex_run(&mut sanitized, &mut hex_run);
+
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(sanitized, |line, secret| line.replace(secret.as_str(), REDACTED))
+}
+
+fn flush_hex_run(sanitized: &mut String, hex_run: &mut String) {
+    if hex_run.len() >= MIN_REDACTED_HEX_LENGTH {
+        sanitized.push_str(REDACTED);
+    } else {
+        sanitized.push_str(hex_run);
+    }
+    hex_run.clear();
+}
+
+fn is_error_line(line: &str) -> bool {
+    line.contains(" ERROR ") || line.contains(" WARN ")
+}
+
+fn last(mut lines: Vec<String>, n: usize) -> Vec<String> {
+    let excess = lines.len().saturating_sub(n);
+    lines.drain(..excess);
+    lines
+}
+
+fn io_error(err: io::Error) -> WalletError {
+    WalletError::DiagnosticsError(err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn it_redacts_secrets() {
+        let config = redact_config(json!({
+            "password": "hunter2",
+            "grpc_authentication": { "basic": { "username": "admin", "password": "hunter2" } },
+            "p2p": { "tor_identity": "abc", "datastore_path": "peer_db" },
+            "fee_per_gram": 5,
+        }));
+        assert_eq!(config["password"], REDACTED);
+        assert_eq!(config["grpc_authentication"], REDACTED);
+        assert_eq!(config["p2p"]["tor_identity"], REDACTED);
+        assert_eq!(config["p2p"]["datastore_path"], "peer_db");
+        assert_eq!(config["fee_per_gram"], 5);
+
+        let key = "ab".repeat(32);
+        let line = format!("2022-10-01 INFO Sent {} to {} with hunter2", "deadbeef", key);
+        assert_eq!(
+            sanitize_log_line(&line, &["hunter2".to_string()]),
+            "2022-10-01 INFO Sent deadbeef to <redacted> with <redacted>"
+        );
+    }
+
+    #[test]
+    fn it_refuses_to_write_bundles_with_secrets() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("wallet.log"),
+            "2022-10-01 INFO Started\n2022-10-01 WARN Base node offline\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "Not a log").unwrap();
+
+        let mut bundle = DiagnosticsBundle::new("0.38.0".to_string(), "esmeralda".to_string(), json!({}));
+        bundle.add_logs(dir.path(), &[]).unwrap();
+        assert_eq!(bundle.logs.len(), 2);
+        assert_eq!(bundle.recent_errors, vec!["2022-10-01 WARN Base node offline"]);
+
+        let path = dir.path().join("diagnostics.json");
+        assert!(bundle.write_to_file(&path, &["Base node".to_string()]).is_err());
+        assert!(!path.exists());
+        bundle.write_to_file(&path, &["hunter2".to_string()]).unwrap();
+        assert!(path.exists());
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DigestServiceConfig {
+    /// If true, a summary of the wallet activity is published once every `digest_interval`
+    pub enabled: bool,
+    /// The period that each digest covers
+    #[serde(with = "serializers::seconds")]
+    pub digest_interval: Duration,
+    /// If set, each digest is also written to a JSON report file in this directory
+    pub report_dir: Option<PathBuf>,
+    /// This is the size of the event channel used to communicate digest events to the wallet
+    pub event_channel_size: usize,
+}
+
+impl Default for DigestServiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            digest_interval: Duration::from_secs(24 * 60 * 60),
+            report_dir: None,
+            event_channel_size: 10,
+        }
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use tari_service_framework::reply_channel::TransportChannelError;
+use thiserror::Error;
+
+use crate::{contacts_service::error::ContactsServiceError, transaction_service::error::TransactionServiceError};
+
+#[derive(Debug, Error)]
+pub enum DigestServiceError {
+    #[error("Transport channel error: `{0}`")]
+    TransportChannelError(#[from] TransportChannelError),
+    #[error("Transaction service error: `{0}`")]
+    TransactionServiceError(#[from] TransactionServiceError),
+    #[error("Contacts service error: `{0}`")]
+    ContactsServiceError(#[from] ContactsServiceError),
+    #[error("Could not write the digest report: `{0}`")]
+    ReportIoError(#[from] std::io::Error),
+    #[error("Could not serialize the digest report: `{0}`")]
+    ReportSerializationError(#[from] serde_json::Error),
+}
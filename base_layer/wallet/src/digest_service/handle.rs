@@ -0,0 +1,93 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{fmt, fmt::Formatter, sync::Arc};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_service_framework::reply_channel::SenderService;
+use tokio::sync::broadcast;
+use tower::Service;
+
+use super::error::DigestServiceError;
+
+pub type DigestEventSender = broadcast::Sender<Arc<DigestEvent>>;
+pub type DigestEventReceiver = broadcast::Receiver<Arc<DigestEvent>>;
+
+/// API Request enum
+#[derive(Debug)]
+pub enum DigestServiceRequest {
+    GetCurrentDigest,
+}
+
+/// API Response enum
+#[derive(Debug)]
+pub enum DigestServiceResponse {
+    Digest(Box<WalletDigest>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DigestEvent {
+    DigestCompleted(Box<WalletDigest>),
+}
+
+impl fmt::Display for DigestEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DigestEvent::DigestCompleted(digest) => {
+                write!(f, "DigestCompleted: {} to {}", digest.period_start, digest.period_end)
+            },
+        }
+    }
+}
+
+/// A summary of the wallet activity in a period
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletDigest {
+    pub period_start: NaiveDateTime,
+    pub period_end: NaiveDateTime,
+    /// The number and total value of the transactions received in the period
+    pub received_count: usize,
+    pub received_amount: MicroTari,
+    /// The number, total value and total fees of the transactions sent in the period
+    pub sent_count: usize,
+    pub sent_amount: MicroTari,
+    pub fees_paid: MicroTari,
+    /// The number of transactions that were mined in the period and have since been confirmed
+    pub confirmed_count: usize,
+    /// The contacts that were added in the period
+    pub new_contacts: Vec<CommsPublicKey>,
+}
+
+/// The Digest Service Handle is a struct that contains the interfaces used to communicate with a running
+/// Digest Service
+#[derive(Clone)]
+pub struct DigestServiceHandle {
+    handle: SenderService<DigestServiceRequest, Result<DigestServiceResponse, DigestServiceError>>,
+    event_stream_sender: DigestEventSender,
+}
+
+impl DigestServiceHandle {
+    pub fn new(
+        handle: SenderService<DigestServiceRequest, Result<DigestServiceResponse, DigestServiceError>>,
+        event_stream_sender: DigestEventSender,
+    ) -> Self {
+        Self {
+            handle,
+            event_stream_sender,
+        }
+    }
+
+    pub fn get_event_stream(&self) -> DigestEventReceiver {
+        self.event_stream_sender.subscribe()
+    }
+
+    /// Returns a summary of the wallet activity since the last digest was published, without ending the period
+    pub async fn get_current_digest(&mut self) -> Result<WalletDigest, DigestServiceError> {
+        match self.handle.call(DigestServiceRequest::GetCurrentDigest).await?? {
+            DigestServiceResponse::Digest(digest) => Ok(*digest),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+pub mod config;
+pub mod error;
+pub mod handle;
+pub mod service;
+
+use log::*;
+use tari_service_framework::{
+    async_trait,
+    reply_channel,
+    ServiceInitializationError,
+    ServiceInitializer,
+    ServiceInitializerContext,
+};
+use tokio::sync::broadcast;
+
+use crate::{
+    contacts_service::handle::ContactsServiceHandle,
+    digest_service::{config::DigestServiceConfig, handle::DigestServiceHandle, service::DigestService},
+    transaction_service::handle::TransactionServiceHandle,
+};
+
+const LOG_TARGET: &str = "wallet::digest_service";
+
+pub struct DigestServiceInitializer {
+    config: DigestServiceConfig,
+}
+
+impl DigestServiceInitializer {
+    pub fn new(config: DigestServiceConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl ServiceInitializer for DigestServiceInitializer {
+    async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
+        info!(target: LOG_TARGET, "Wallet digest service initializing.");
+
+        let (sender, request_stream) = reply_channel::unbounded();
+
+        let (event_publisher, _) = broadcast::channel(self.config.event_channel_size);
+
+        let digest_service_handle = DigestServiceHandle::new(sender, event_publisher.clone());
+
+        // Register handle before waiting for handles to be ready
+        context.register_handle(digest_service_handle);
+
+        let config = self.config.clone();
+
+        context.spawn_when_ready(move |handles| async move {
+            let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
+            let contacts_service = handles.expect_handle::<ContactsServiceHandle>();
+
+            let result = DigestService::new(
+                config,
+                request_stream,
+                transaction_service,
+                contacts_service,
+                event_publisher,
+                handles.get_shutdown_signal(),
+            )
+            .start()
+            .await;
+
+            info!(
+                target: LOG_TARGET,
+                "Wallet Digest Service shutdown with result {:?}", result
+            );
+        });
+
+        Ok(())
+    }
+}
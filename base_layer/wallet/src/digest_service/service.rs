@@ -0,0 +1,312 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{collections::HashSet, fs, fs::File, sync::Arc};
+
+use chrono::{NaiveDateTime, Utc};
+use futures::StreamExt;
+use log::*;
+use tari_common_types::transaction::{TransactionDirection, TransactionStatus};
+use tari_comms::types::CommsPublicKey;
+use tari_service_framework::reply_channel::Receiver;
+use tari_shutdown::ShutdownSignal;
+use tokio::time::{self, Instant, MissedTickBehavior};
+
+use super::{
+    config::DigestServiceConfig,
+    error::DigestServiceError,
+    handle::{DigestEvent, DigestEventSender, DigestServiceRequest, DigestServiceResponse, WalletDigest},
+};
+use crate::{
+    contacts_service::handle::ContactsServiceHandle,
+    transaction_service::{handle::TransactionServiceHandle, storage::models::CompletedTransaction},
+};
+
+const LOG_TARGET: &str = "wallet::digest_service::service";
+
+/// The digest service periodically aggregates the wallet activity into a single summary event, and optionally a JSON
+/// report file, for consumers that do not want to process every individual transaction event.
+pub struct DigestService {
+    config: DigestServiceConfig,
+    request_stream: Option<Receiver<DigestServiceRequest, Result<DigestServiceResponse, DigestServiceError>>>,
+    transaction_service: TransactionServiceHandle,
+    contacts_service: ContactsServiceHandle,
+    event_publisher: DigestEventSender,
+    shutdown_signal: ShutdownSignal,
+    period_start: NaiveDateTime,
+    known_contacts: HashSet<CommsPublicKey>,
+}
+
+impl DigestService {
+    pub fn new(
+        config: DigestServiceConfig,
+        request_stream: Receiver<DigestServiceRequest, Result<DigestServiceResponse, DigestServiceError>>,
+        transaction_service: TransactionServiceHandle,
+        contacts_service: ContactsServiceHandle,
+        event_publisher: DigestEventSender,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            request_stream: Some(request_stream),
+            transaction_service,
+            contacts_service,
+            event_publisher,
+            shutdown_signal,
+            period_start: Utc::now().naive_utc(),
+            known_contacts: HashSet::new(),
+        }
+    }
+
+    /// Starts the service.
+    pub async fn start(mut self) -> Result<(), DigestServiceError> {
+        let mut request_stream = self
+            .request_stream
+            .take()
+            .expect("Wallet Digest Service initialized without request_stream");
+        let mut shutdown = self.shutdown_signal.clone();
+
+        self.known_contacts = self.fetch_contacts().await?;
+
+        let interval = self.config.digest_interval;
+        let mut digest_interval = time::interval_at(Instant::now() + interval, interval);
+        digest_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        debug!(target: LOG_TARGET, "Wallet Digest Service started");
+        loop {
+            tokio::select! {
+                Some(request_context) = request_stream.next() => {
+                    let (request, reply_tx) = request_context.split();
+                    let response = self.handle_request(request).await.map_err(|e| {
+                        error!(target: LOG_TARGET, "Error handling request: {:?}", e);
+                        e
+                    });
+                    let _result = reply_tx.send(response).map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send reply");
+                        e
+                    });
+                },
+                _ = digest_interval.tick(), if self.config.enabled => {
+                    if let Err(e) = self.publish_digest().await {
+                        error!(target: LOG_TARGET, "Error publishing wallet digest: {:?}", e);
+                    }
+                },
+                _ = shutdown.wait() => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Wallet Digest Service shutting down because the shutdown signal was received"
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_request(
+        &mut self,
+        request: DigestServiceRequest,
+    ) -> Result<DigestServiceResponse, DigestServiceError> {
+        debug!(target: LOG_TARGET, "Handling Service Request: {:?}", request);
+        match request {
+            DigestServiceRequest::GetCurrentDigest => {
+                let (digest, _) = self.build_digest(Utc::now().naive_utc()).await?;
+                Ok(DigestServiceResponse::Digest(Box::new(digest)))
+            },
+        }
+    }
+
+    /// Closes the current period, publishing its digest and writing the report file if one is configured
+    async fn publish_digest(&mut self) -> Result<(), DigestServiceError> {
+        let period_end = Utc::now().naive_utc();
+        let (digest, contacts) = self.build_digest(period_end).await?;
+        self.period_start = period_end;
+        self.known_contacts = contacts;
+
+        if let Some(report_dir) = self.config.report_dir.as_ref() {
+            fs::create_dir_all(report_dir)?;
+            let report_path = report_dir.join(format!("wallet_digest_{}.json", period_end.format("%Y%m%d_%H%M%S")));
+            serde_json::to_writer_pretty(File::create(&report_path)?, &digest)?;
+            debug!(
+                target: LOG_TARGET,
+                "Wallet digest report written to {}",
+                report_path.display()
+            );
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Wallet digest from {} to {}: {} received ({}), {} sent ({}), {} confirmed, {} new contacts",
+            digest.period_start,
+            digest.period_end,
+            digest.received_count,
+            digest.received_amount,
+            digest.sent_count,
+            digest.sent_amount,
+            digest.confirmed_count,
+            digest.new_contacts.len()
+        );
+        let _size = self
+            .event_publisher
+            .send(Arc::new(DigestEvent::DigestCompleted(Box::new(digest))));
+        Ok(())
+    }
+
+    async fn build_digest(
+        &mut self,
+        period_end: NaiveDateTime,
+    ) -> Result<(WalletDigest, HashSet<CommsPublicKey>), DigestServiceError> {
+        let transactions = self.transaction_service.get_completed_transactions().await?;
+        let contacts = self.fetch_contacts().await?;
+        let mut new_contacts = contacts.difference(&self.known_contacts).cloned().collect::<Vec<_>>();
+        new_contacts.sort();
+        let digest = summarize(self.period_start, period_end, transactions.values(), new_contacts);
+        Ok((digest, contacts))
+    }
+
+    async fn fetch_contacts(&mut self) -> Result<HashSet<CommsPublicKey>, DigestServiceError> {
+        Ok(self
+            .contacts_service
+            .get_contacts()
+            .await?
+            .into_iter()
+            .map(|c| c.public_key)
+            .collect())
+    }
+}
+
+/// Aggregates the transactions that fall in the period `[period_start, period_end)` into a digest. Cancelled and decoy
+/// transactions are ignored.
+fn summarize<'a, I: Iterator<Item = &'a CompletedTransaction>>(
+    period_start: NaiveDateTime,
+    period_end: NaiveDateTime,
+    transactions: I,
+    new_contacts: Vec<CommsPublicKey>,
+) -> WalletDigest {
+    let in_period = |timestamp: NaiveDateTime| timestamp >= period_start && timestamp < period_end;
+    let mut digest = WalletDigest {
+        period_start,
+        period_end,
+        received_count: 0,
+        received_amount: 0.into(),
+        sent_count: 0,
+        sent_amount: 0.into(),
+        fees_paid: 0.into(),
+        confirmed_count: 0,
+        new_contacts,
+    };
+    for tx in transactions.filter(|tx| tx.cancelled.is_none() && !tx.is_decoy) {
+        if in_period(tx.timestamp) {
+            match tx.direction {
+                TransactionDirection::Inbound => {
+                    digest.received_count += 1;
+                    digest.received_amount += tx.amount;
+                },
+                TransactionDirection::Outbound => {
+                    digest.sent_count += 1;
+                    digest.sent_amount += tx.amount;
+                    digest.fees_paid += tx.fee;
+                },
+                TransactionDirection::Unknown => {},
+            }
+        }
+        if tx.status == TransactionStatus::MinedConfirmed && tx.mined_timestamp.map_or(false, in_period) {
+            digest.confirmed_count += 1;
+        }
+    }
+    digest
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::{transaction::TxId, types::PrivateKey};
+    use tari_core::transactions::{tari_amount::MicroTari, transaction_components::Transaction};
+    use tari_crypto::keys::PublicKey;
+
+    use super::*;
+    use crate::transaction_service::storage::models::TxCancellationReason;
+
+    fn make_transaction(
+        direction: TransactionDirection,
+        amount: u64,
+        status: TransactionStatus,
+        timestamp: NaiveDateTime,
+    ) -> CompletedTransaction {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        CompletedTransaction::new(
+            TxId::new_random(),
+            public_key.clone(),
+            public_key,
+            MicroTari::from(amount),
+            MicroTari::from(10),
+            Transaction::new(vec![], vec![], vec![], PrivateKey::default(), PrivateKey::default()),
+            status,
+            "".to_string(),
+            timestamp,
+            direction,
+            None,
+            None,
+            Some(timestamp),
+        )
+    }
+
+    #[test]
+    fn it_summarizes_the_period() {
+        let period_end = Utc::now().naive_utc();
+        let period_start = period_end - chrono::Duration::days(1);
+        let before_period = period_start - chrono::Duration::hours(1);
+        let in_period = period_start + chrono::Duration::hours(1);
+
+        let mut cancelled = make_transaction(
+            TransactionDirection::Inbound,
+            1000,
+            TransactionStatus::Completed,
+            in_period,
+        );
+        cancelled.cancelled = Some(TxCancellationReason::UserCancelled);
+        let mut decoy = make_transaction(
+            TransactionDirection::Inbound,
+            400,
+            TransactionStatus::MinedConfirmed,
+            in_period,
+        );
+        decoy.is_decoy = true;
+        let transactions = vec![
+            make_transaction(
+                TransactionDirection::Inbound,
+                100,
+                TransactionStatus::MinedConfirmed,
+                in_period,
+            ),
+            make_transaction(
+                TransactionDirection::Inbound,
+                200,
+                TransactionStatus::Broadcast,
+                in_period,
+            ),
+            make_transaction(
+                TransactionDirection::Outbound,
+                50,
+                TransactionStatus::Completed,
+                in_period,
+            ),
+            make_transaction(
+                TransactionDirection::Outbound,
+                500,
+                TransactionStatus::MinedConfirmed,
+                before_period,
+            ),
+            cancelled,
+            decoy,
+        ];
+
+        let digest = summarize(period_start, period_end, transactions.iter(), vec![]);
+        assert_eq!(digest.received_count, 2);
+        assert_eq!(digest.received_amount, MicroTari::from(300));
+        assert_eq!(digest.sent_count, 1);
+        assert_eq!(digest.sent_amount, MicroTari::from(50));
+        assert_eq!(digest.fees_paid, MicroTari::from(10));
+        assert_eq!(digest.confirmed_count, 1);
+    }
+}
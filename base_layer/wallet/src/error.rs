@@ -20,17 +20,23 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use diesel::result::Error as DieselError;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use log::SetLoggerError;
 use serde_json::Error as SerdeJsonError;
-use tari_common::exit_codes::{ExitCode, ExitError};
+use strum_macros::Display;
+use tari_common::{
+    configuration::Network,
+    exit_codes::{ExitCode, ExitError},
+};
 use tari_common_sqlite::error::SqliteStorageError;
 use tari_comms::{
     connectivity::ConnectivityError,
     multiaddr,
+    multiaddr::Multiaddr,
     peer_manager::{node_id::NodeIdError, PeerManagerError},
+    types::CommsPublicKey,
 };
-use tari_comms_dht::store_forward::StoreAndForwardError;
+use tari_comms_dht::{store_forward::StoreAndForwardError, DhtDiscoveryError};
 use tari_core::transactions::transaction_components::TransactionError;
 use tari_key_manager::error::KeyManagerError;
 use tari_p2p::{initialization::CommsInitializationError, services::liveness::error::LivenessError};
@@ -43,9 +49,13 @@ use crate::{
     contacts_service::error::ContactsServiceError,
     key_manager_service::KeyManagerServiceError,
     output_manager_service::error::OutputManagerError,
+    platform_keystore::PlatformKeystoreError,
+    proof_of_reserves::ReserveAttestationError,
     storage::database::DbKey,
     transaction_service::error::TransactionServiceError,
+    util::supervisor::RestartError,
     utxo_scanner_service::error::UtxoScannerError,
+    wallet::ServiceKind,
 };
 
 #[derive(Debug, Error)]
@@ -76,6 +86,8 @@ pub enum WalletError {
     LivenessServiceError(#[from] LivenessError),
     #[error("Store and forward error: `{0}`")]
     StoreAndForwardError(#[from] StoreAndForwardError),
+    #[error("Peer discovery error: `{0}`")]
+    DhtDiscoveryError(#[from] DhtDiscoveryError),
     #[error("Connectivity error: `{0}`")]
     ConnectivityError(#[from] ConnectivityError),
     #[error("Failed to initialize services: {0}")]
@@ -102,6 +114,45 @@ pub enum WalletError {
     TransportChannelError(#[from] TransportChannelError),
     #[error("Unexpected API Response while calling method `{method}` on `{api}`")]
     UnexpectedApiResponse { method: String, api: String },
+    #[error("A wallet named `{0}` is already running")]
+    WalletAlreadyRunning(String),
+    #[error("No wallet named `{0}` is running")]
+    WalletNotRunning(String),
+    #[error("Wallet `{name}` conflicts with running wallet `{other}`: {reason}")]
+    ConflictingWallet {
+        name: String,
+        other: String,
+        reason: String,
+    },
+    #[error("Wallet runtime error: {0}")]
+    RuntimeError(String),
+    #[error("Could not restart the {0}: {1}")]
+    ServiceRestartFailed(ServiceKind, RestartError),
+    #[error("Could not reload the wallet config: {0}")]
+    ConfigReloadError(String),
+    #[error("Network state error: {0}")]
+    NetworkStateError(String),
+    #[error("Portable wallet dump error: {0}")]
+    PortableDumpError(String),
+    #[error("Cloud sync error: {0}")]
+    CloudSyncError(String),
+    #[error("Diagnostics error: {0}")]
+    DiagnosticsError(String),
+    #[error("Reserve attestation error: {0}")]
+    ReserveAttestationError(#[from] ReserveAttestationError),
+    #[error("The key branch `{0}` cannot be registered, as it is used by the wallet")]
+    InvalidKeyBranch(String),
+    #[error("The wallet database belongs to the {database} network, but the wallet is configured for {configured}")]
+    NetworkMismatch { database: Network, configured: Network },
+    #[error(
+        "The base node at {address} presented the public key {presented_public_key}, but {pinned_public_key} is \
+         pinned for it"
+    )]
+    BaseNodeIdentityChanged {
+        address: Multiaddr,
+        pinned_public_key: CommsPublicKey,
+        presented_public_key: CommsPublicKey,
+    },
 }
 
 pub const LOG_TARGET: &str = "tari::application";
@@ -173,6 +224,8 @@ pub enum WalletStorageError {
     KeyManagerError(#[from] KeyManagerError),
     #[error("Recovery Seed Error: {0}")]
     RecoverySeedError(String),
+    #[error("Platform keystore error: `{0}`")]
+    PlatformKeystoreError(#[from] PlatformKeystoreError),
 }
 
 impl From<WalletStorageError> for ExitError {
@@ -184,3 +237,216 @@ impl From<WalletStorageError> for ExitError {
         }
     }
 }
+
+/// The broad category of an error, so that integrators can handle errors without matching every variant. The values
+/// are stable, as they are passed across the FFI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ErrorKind {
+    /// The request is malformed or refers to something that is not valid
+    InvalidArgument = 1,
+    /// The item that the request refers to does not exist
+    NotFound = 2,
+    /// The request conflicts with the state of the wallet, e.g. the item already exists
+    Conflict = 3,
+    /// The wallet does not hold enough spendable funds
+    InsufficientFunds = 4,
+    /// The base node or another peer could not be reached
+    Connectivity = 5,
+    /// The wallet database could not be read or written
+    Storage = 6,
+    /// The passphrase, seed or keys do not match the wallet
+    Unauthorized = 7,
+    /// A service is busy, recovering or shutting down
+    Unavailable = 8,
+    /// The transaction was rejected by the network, the mempool or a spending policy
+    Rejected = 9,
+    /// A bug or a state that the wallet does not expect
+    Internal = 10,
+}
+
+/// What the caller can do about an error. The values are stable, as they are passed across the FFI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum SuggestedAction {
+    /// Retry the same request after a backoff
+    Retry = 1,
+    /// Retry the same request once the wallet is connected to a synced base node
+    RetryWhenConnected = 2,
+    /// Retry the same request once pending funds are confirmed
+    WaitForFunds = 3,
+    /// The request is still being processed, wait for its events instead of retrying, which would repeat it
+    AwaitEvents = 4,
+    /// Correct the request, as retrying it unchanged fails again
+    FixRequest = 5,
+    /// Unlock the wallet with the correct passphrase
+    ProvidePassphrase = 6,
+    /// Restart the wallet
+    Restart = 7,
+    /// Nothing the caller does makes the request succeed, the error should be reported
+    Report = 8,
+}
+
+/// The classification of an error, see [ClassifyError]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorClass {
+    pub kind: ErrorKind,
+    pub action: SuggestedAction,
+}
+
+impl ErrorClass {
+    pub const CONFLICT: Self = Self::new(ErrorKind::Conflict, SuggestedAction::FixRequest);
+    pub const CONNECTIVITY: Self = Self::new(ErrorKind::Connectivity, SuggestedAction::RetryWhenConnected);
+    pub const INTERNAL: Self = Self::new(ErrorKind::Internal, SuggestedAction::Report);
+    pub const INVALID_ARGUMENT: Self = Self::new(ErrorKind::InvalidArgument, SuggestedAction::FixRequest);
+    pub const NOT_FOUND: Self = Self::new(ErrorKind::NotFound, SuggestedAction::FixRequest);
+    pub const SHUTDOWN: Self = Self::new(ErrorKind::Unavailable, SuggestedAction::Restart);
+    pub const STORAGE: Self = Self::new(ErrorKind::Storage, SuggestedAction::Report);
+    pub const UNAUTHORIZED: Self = Self::new(ErrorKind::Unauthorized, SuggestedAction::ProvidePassphrase);
+    pub const UNAVAILABLE: Self = Self::new(ErrorKind::Unavailable, SuggestedAction::Retry);
+
+    pub const fn new(kind: ErrorKind, action: SuggestedAction) -> Self {
+        Self { kind, action }
+    }
+
+    /// Returns true if the same request may succeed later, i.e. it is safe and useful to retry it
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.action,
+            SuggestedAction::Retry | SuggestedAction::RetryWhenConnected | SuggestedAction::WaitForFunds
+        )
+    }
+}
+
+/// Classifies an error by kind and by what the caller can do about it. Errors that wrap the error of another layer
+/// delegate to it, so the classification of e.g. a locked database is the same whichever service reports it.
+pub trait ClassifyError {
+    fn class(&self) -> ErrorClass;
+
+    fn is_transient(&self) -> bool {
+        self.class().is_transient()
+    }
+}
+
+impl ClassifyError for WalletError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            WalletError::OutputManagerError(e) => e.class(),
+            WalletError::TransactionServiceError(e) => e.class(),
+            WalletError::WalletStorageError(e) => e.class(),
+            WalletError::ContactsServiceError(e) => e.class(),
+            WalletError::BaseNodeServiceError(e) => e.class(),
+            WalletError::KeyManagerServiceError(e) => e.class(),
+            WalletError::ArgumentError { .. } |
+            WalletError::MultiaddrError(_) |
+            WalletError::NodeIdError(_) |
+            WalletError::ByteArrayError(_) |
+            WalletError::InvalidKeyBranch(_) |
+            WalletError::ConfigReloadError(_) |
+            WalletError::NetworkMismatch { .. } => ErrorClass::INVALID_ARGUMENT,
+            WalletError::KeyManagerError(KeyManagerError::DecryptionFailed) => ErrorClass::UNAUTHORIZED,
+            WalletError::KeyManagerError(_) => ErrorClass::INVALID_ARGUMENT,
+            WalletError::CommsInitializationError(_) |
+            WalletError::LivenessServiceError(_) |
+            WalletError::StoreAndForwardError(_) |
+            WalletError::DhtDiscoveryError(_) |
+            WalletError::ConnectivityError(_) => ErrorClass::CONNECTIVITY,
+            WalletError::BaseNodeIdentityChanged { .. } => {
+                ErrorClass::new(ErrorKind::Connectivity, SuggestedAction::FixRequest)
+            },
+            WalletError::TransportChannelError(_) | WalletError::ServiceRestartFailed(..) => ErrorClass::UNAVAILABLE,
+            WalletError::Shutdown => ErrorClass::SHUTDOWN,
+            WalletError::WalletAlreadyRunning(_) | WalletError::ConflictingWallet { .. } => ErrorClass::CONFLICT,
+            WalletError::WalletNotRunning(_) => ErrorClass::NOT_FOUND,
+            _ => ErrorClass::INTERNAL,
+        }
+    }
+}
+
+impl ClassifyError for WalletStorageError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            WalletStorageError::DieselError(e) => diesel_error_class(e),
+            WalletStorageError::DieselR2d2Error(_) | WalletStorageError::BlockingTaskSpawnError(_) => {
+                ErrorClass::new(ErrorKind::Storage, SuggestedAction::Retry)
+            },
+            WalletStorageError::CannotAcquireFileLock => ErrorClass::UNAVAILABLE,
+            WalletStorageError::ValuesNotFound |
+            WalletStorageError::ValueNotFound(_) |
+            WalletStorageError::DbPathDoesNotExist => ErrorClass::NOT_FOUND,
+            WalletStorageError::DuplicateContact | WalletStorageError::AlreadyEncrypted => ErrorClass::CONFLICT,
+            WalletStorageError::InvalidEncryptionCipher |
+            WalletStorageError::InvalidPassphrase |
+            WalletStorageError::NoPasswordError |
+            WalletStorageError::AeadError(_) |
+            WalletStorageError::PlatformKeystoreError(_) => ErrorClass::UNAUTHORIZED,
+            WalletStorageError::InvalidUnicodePath |
+            WalletStorageError::DatabasePathIsRootPath |
+            WalletStorageError::RecoverySeedError(_) => ErrorClass::INVALID_ARGUMENT,
+            _ => ErrorClass::STORAGE,
+        }
+    }
+}
+
+/// Classifies the diesel errors of the wallet database. A locked or busy database is transient, as sqlite reports it
+/// when another connection holds the write lock for longer than the busy timeout.
+pub(crate) fn diesel_error_class(error: &DieselError) -> ErrorClass {
+    match error {
+        DieselError::NotFound => ErrorClass::NOT_FOUND,
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => ErrorClass::CONFLICT,
+        DieselError::DatabaseError(_, info) if is_sqlite_busy(info.message()) => {
+            ErrorClass::new(ErrorKind::Storage, SuggestedAction::Retry)
+        },
+        _ => ErrorClass::STORAGE,
+    }
+}
+
+fn is_sqlite_busy(message: &str) -> bool {
+    message.contains("database is locked") || message.contains("database is busy")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        output_manager_service::error::OutputManagerStorageError,
+        transaction_service::error::TransactionStorageError,
+    };
+
+    #[test]
+    fn it_classifies_the_wrapped_error() {
+        let err = WalletError::OutputManagerError(OutputManagerError::FundsPending);
+        assert_eq!(
+            err.class(),
+            ErrorClass::new(ErrorKind::InsufficientFunds, SuggestedAction::WaitForFunds)
+        );
+        assert!(err.is_transient());
+
+        let err = WalletError::OutputManagerError(OutputManagerError::NotEnoughFunds);
+        assert_eq!(err.class().kind, ErrorKind::InsufficientFunds);
+        assert!(!err.is_transient());
+
+        assert_eq!(WalletError::Shutdown.class(), ErrorClass::SHUTDOWN);
+    }
+
+    #[test]
+    fn it_classifies_a_locked_database_as_transient() {
+        let locked =
+            || DieselError::DatabaseError(DatabaseErrorKind::__Unknown, Box::new("database is locked".to_string()));
+        let errors = [
+            WalletError::WalletStorageError(WalletStorageError::DieselError(locked())),
+            WalletError::OutputManagerError(OutputManagerError::OutputManagerStorageError(
+                OutputManagerStorageError::DieselError(locked()),
+            )),
+            WalletError::TransactionServiceError(TransactionServiceError::TransactionStorageError(
+                TransactionStorageError::DieselError(locked()),
+            )),
+        ];
+        for err in errors {
+            assert_eq!(err.class(), ErrorClass::new(ErrorKind::Storage, SuggestedAction::Retry));
+            assert!(err.is_transient());
+        }
+
+        let err = WalletStorageError::DieselError(DieselError::NotFound);
+        assert_eq!(err.class(), ErrorClass::NOT_FOUND);
+        assert!(!err.is_transient());
+    }
+}
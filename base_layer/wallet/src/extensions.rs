@@ -0,0 +1,99 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Extension points for applications that embed the wallet, such as second layer applications that derive their own
+//! keys from the seed of the wallet.
+
+use std::{collections::HashMap, sync::Arc};
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    config::KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY,
+    error::WalletError,
+    key_manager_service::{KeyUsage, KeyUsageCallback},
+    output_manager_service::resources::OutputManagerKeyManagerBranch,
+};
+
+/// The extensions an embedding application registers when it starts the wallet with
+/// [Wallet::start](crate::Wallet::start)
+#[derive(Clone, Default)]
+pub struct WalletExtensions {
+    key_branches: Vec<String>,
+    key_usage_callbacks: HashMap<String, KeyUsageCallback>,
+}
+
+impl WalletExtensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a key manager branch that the application derives its keys from. The branch is added to the key
+    /// manager when the wallet starts, so its keys are available through the key manager handle of the wallet.
+    pub fn with_key_branch<T: Into<String>>(mut self, branch: T) -> Self {
+        let branch = branch.into();
+        if !self.key_branches.contains(&branch) {
+            self.key_branches.push(branch);
+        }
+        self
+    }
+
+    /// Registers a key manager branch and a callback that is called with every key derived from it with
+    /// `get_next_key`, e.g. to record which keys an application has handed out
+    pub fn with_key_branch_callback<T, F>(self, branch: T, callback: F) -> Self
+    where
+        T: Into<String>,
+        F: Fn(&KeyUsage) + Send + Sync + 'static,
+    {
+        let branch = branch.into();
+        let mut extensions = self.with_key_branch(branch.clone());
+        extensions.key_usage_callbacks.insert(branch, Arc::new(callback));
+        extensions
+    }
+
+    pub fn key_branches(&self) -> &[String] {
+        &self.key_branches
+    }
+
+    pub(crate) fn key_usage_callbacks(&self) -> HashMap<String, KeyUsageCallback> {
+        self.key_usage_callbacks.clone()
+    }
+
+    /// Checks that no registered branch shares its keys with a branch of the wallet. The keys of a branch in a later
+    /// key epoch are derived from the branch name with an `#` suffix, so names containing `#` are not allowed either.
+    pub fn validate(&self) -> Result<(), WalletError> {
+        for branch in &self.key_branches {
+            if branch.contains('#') ||
+                branch.as_str() == KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY ||
+                OutputManagerKeyManagerBranch::iter().any(|wallet_branch| &wallet_branch.get_branch_key() == branch)
+            {
+                return Err(WalletError::InvalidKeyBranch(branch.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_rejects_branches_of_the_wallet() {
+        assert!(WalletExtensions::new().validate().is_ok());
+        let extensions = WalletExtensions::new()
+            .with_key_branch("dan_templates")
+            .with_key_branch_callback("identity", |_| {})
+            .with_key_branch("dan_templates");
+        assert_eq!(extensions.key_branches(), ["dan_templates", "identity"]);
+        assert!(extensions.validate().is_ok());
+        assert_eq!(extensions.key_usage_callbacks().len(), 1);
+
+        for branch in ["", "script", "comms", "identity#epoch1"] {
+            assert!(matches!(
+                WalletExtensions::new().with_key_branch(branch).validate(),
+                Err(WalletError::InvalidKeyBranch(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderSyncServiceConfig {
+    /// If true, the wallet keeps its own proof of work verified copy of the base node's header chain. This requires
+    /// the wallet to be built with the `header_sync` feature. The chain is verified from the genesis block each time
+    /// the wallet starts.
+    pub enabled: bool,
+    /// The number of most recent verified headers kept in memory. This must cover the difficulty windows of both
+    /// proof of work algorithms and the deepest reorg the wallet should be able to follow.
+    pub max_retained_headers: usize,
+    /// The number of headers requested from the base node at a time (at most 1000)
+    pub headers_per_request: u64,
+    /// This is the size of the event channel used to communicate header sync events to the wallet
+    pub event_channel_size: usize,
+}
+
+impl Default for HeaderSyncServiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_retained_headers: 10_000,
+            headers_per_request: 1000,
+            event_channel_size: 10,
+        }
+    }
+}
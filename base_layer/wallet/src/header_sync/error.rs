@@ -0,0 +1,40 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use tari_comms::protocol::rpc::RpcError;
+use tari_core::blocks::BlockError;
+use tari_service_framework::reply_channel::TransportChannelError;
+use thiserror::Error;
+use tokio::task::JoinError;
+
+#[derive(Debug, Error)]
+pub enum HeaderSyncError {
+    #[error("Header #{height} does not connect to the verified chain tip #{tip_height}")]
+    DoesNotConnect { height: u64, tip_height: u64 },
+    #[error("Header #{height} is invalid: {details}")]
+    InvalidHeader { height: u64, details: String },
+    #[error("Not enough verified headers are retained to calculate the target difficulty of header #{0}")]
+    InsufficientHistory(u64),
+    #[error("The base node's chain does not link to any of the retained verified headers")]
+    ChainSplitNotFound,
+    #[error("The base node reorged to a chain with less accumulated difficulty than the verified chain")]
+    WeakerChain,
+    #[error("Header #{0} has not been verified")]
+    HeaderNotVerified(u64),
+    #[error("Block #{0} is not part of the verified header chain")]
+    BlockNotVerified(u64),
+    #[error("Invalid inclusion proof: {0}")]
+    InvalidInclusionProof(#[from] BlockError),
+    #[error("Received invalid base node response: {0}")]
+    InvalidBaseNodeResponse(String),
+    #[error("Node is shutting down")]
+    NodeShuttingDown,
+    #[error("RPC Error: `{0}`")]
+    RpcError(#[from] RpcError),
+    #[error("Header verification task failed: `{0}`")]
+    JoinError(#[from] JoinError),
+    #[error("Unexpected API Response")]
+    UnexpectedApiResponse,
+    #[error("Transport channel error: `{0}`")]
+    TransportChannelError(#[from] TransportChannelError),
+}
@@ -0,0 +1,112 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{fmt, fmt::Formatter, sync::Arc};
+
+use tari_common_types::types::BlockHash;
+use tari_core::blocks::{BlockInclusionProof, ChainHeader};
+use tari_service_framework::reply_channel::SenderService;
+use tokio::sync::broadcast;
+use tower::Service;
+
+use super::error::HeaderSyncError;
+
+pub type HeaderSyncEventSender = broadcast::Sender<Arc<HeaderSyncEvent>>;
+pub type HeaderSyncEventReceiver = broadcast::Receiver<Arc<HeaderSyncEvent>>;
+
+/// API Request enum
+#[derive(Debug)]
+pub enum HeaderSyncRequest {
+    GetVerifiedHeader(u64),
+    GetVerifiedTip,
+    GetVerifiedInclusionProof { height: u64, block_hash: BlockHash },
+}
+
+/// API Response enum
+#[derive(Debug)]
+pub enum HeaderSyncResponse {
+    VerifiedHeader(Option<Box<ChainHeader>>),
+    VerifiedTip(Box<ChainHeader>),
+    VerifiedInclusionProof(Box<BlockInclusionProof>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderSyncEvent {
+    /// The verified header chain caught up with the base node
+    Synced { height: u64, accumulated_difficulty: u128 },
+    /// The base node sent headers that could not be verified
+    SyncFailed(String),
+}
+
+impl fmt::Display for HeaderSyncEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderSyncEvent::Synced {
+                height,
+                accumulated_difficulty,
+            } => write!(
+                f,
+                "Synced: height {} with accumulated difficulty {}",
+                height, accumulated_difficulty
+            ),
+            HeaderSyncEvent::SyncFailed(reason) => write!(f, "SyncFailed: {}", reason),
+        }
+    }
+}
+
+/// The Header Sync Service Handle gives access to the wallet's verified copy of the base node's header chain
+#[derive(Clone)]
+pub struct HeaderSyncHandle {
+    handle: SenderService<HeaderSyncRequest, Result<HeaderSyncResponse, HeaderSyncError>>,
+    event_stream_sender: HeaderSyncEventSender,
+}
+
+impl HeaderSyncHandle {
+    pub fn new(
+        handle: SenderService<HeaderSyncRequest, Result<HeaderSyncResponse, HeaderSyncError>>,
+        event_stream_sender: HeaderSyncEventSender,
+    ) -> Self {
+        Self {
+            handle,
+            event_stream_sender,
+        }
+    }
+
+    pub fn get_event_stream(&self) -> HeaderSyncEventReceiver {
+        self.event_stream_sender.subscribe()
+    }
+
+    /// Returns the verified header at `height`, or None if the header has not been verified yet or is no longer
+    /// retained
+    pub async fn get_verified_header(&mut self, height: u64) -> Result<Option<ChainHeader>, HeaderSyncError> {
+        match self.handle.call(HeaderSyncRequest::GetVerifiedHeader(height)).await?? {
+            HeaderSyncResponse::VerifiedHeader(header) => Ok(header.map(|h| *h)),
+            _ => Err(HeaderSyncError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the tip of the verified header chain, including its accumulated difficulty
+    pub async fn get_verified_tip(&mut self) -> Result<ChainHeader, HeaderSyncError> {
+        match self.handle.call(HeaderSyncRequest::GetVerifiedTip).await?? {
+            HeaderSyncResponse::VerifiedTip(header) => Ok(*header),
+            _ => Err(HeaderSyncError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fetches the inclusion proof of block `block_hash` at `height` from the base node, and verifies it against the
+    /// verified header at that height. Fails if the block is not part of the verified header chain.
+    pub async fn get_verified_inclusion_proof(
+        &mut self,
+        height: u64,
+        block_hash: BlockHash,
+    ) -> Result<BlockInclusionProof, HeaderSyncError> {
+        match self
+            .handle
+            .call(HeaderSyncRequest::GetVerifiedInclusionProof { height, block_hash })
+            .await??
+        {
+            HeaderSyncResponse::VerifiedInclusionProof(proof) => Ok(*proof),
+            _ => Err(HeaderSyncError::UnexpectedApiResponse),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{collections::VecDeque, convert::TryFrom};
+
+use tari_common_types::types::HashOutput;
+use tari_core::{
+    blocks::{BlockHeader, BlockHeaderAccumulatedData, ChainHeader},
+    consensus::ConsensusManager,
+    proof_of_work::{randomx_factory::RandomXFactory, Difficulty},
+    validation::{
+        check_blockchain_version,
+        check_header_timestamp_greater_than_median,
+        check_target_difficulty,
+        check_timestamp_ftl,
+    },
+};
+
+use super::error::HeaderSyncError;
+
+/// A proof of work verified header chain, starting at the genesis block. Only the most recent `max_headers` headers
+/// are retained.
+///
+/// Each header must connect to the tip, have a valid version and timestamp, and achieve the target difficulty
+/// calculated from the headers before it, as the base node does. The RandomX seed age is not checked, because that
+/// requires the full block history.
+#[derive(Clone)]
+pub struct HeaderChain {
+    rules: ConsensusManager,
+    randomx_factory: RandomXFactory,
+    headers: VecDeque<ChainHeader>,
+    max_headers: usize,
+}
+
+impl HeaderChain {
+    pub fn new(rules: ConsensusManager, randomx_factory: RandomXFactory, max_headers: usize) -> Self {
+        let mut headers = VecDeque::new();
+        headers.push_back(rules.get_genesis_block().to_chain_header());
+        Self {
+            rules,
+            randomx_factory,
+            headers,
+            max_headers: max_headers.max(1),
+        }
+    }
+
+    pub fn tip(&self) -> &ChainHeader {
+        self.headers
+            .back()
+            .expect("The header chain always contains at least one header")
+    }
+
+    /// Returns the verified header at `height` if it is retained
+    pub fn get_header(&self, height: u64) -> Option<&ChainHeader> {
+        let first_height = self.headers.front()?.height();
+        let index = usize::try_from(height.checked_sub(first_height)?).ok()?;
+        self.headers.get(index)
+    }
+
+    /// The hashes of the most recent headers in descending height order, used to find where the base node's chain
+    /// forks from the verified chain
+    pub fn block_hashes(&self, max_hashes: usize) -> Vec<HashOutput> {
+        self.headers.iter().rev().take(max_hashes).map(|h| *h.hash()).collect()
+    }
+
+    /// Removes the `count` most recent headers
+    pub fn rewind(&mut self, count: usize) -> Result<(), HeaderSyncError> {
+        if count >= self.headers.len() {
+            return Err(HeaderSyncError::ChainSplitNotFound);
+        }
+        self.headers.truncate(self.headers.len() - count);
+        Ok(())
+    }
+
+    /// Verifies `header` against the tip of the chain and appends it
+    pub fn add_header(&mut self, header: BlockHeader) -> Result<(), HeaderSyncError> {
+        let tip = self.tip();
+        if header.height != tip.height() + 1 || header.prev_hash != *tip.hash() {
+            return Err(HeaderSyncError::DoesNotConnect {
+                height: header.height,
+                tip_height: tip.height(),
+            });
+        }
+        let height = header.height;
+        let invalid = |details: String| HeaderSyncError::InvalidHeader { height, details };
+
+        let constants = self.rules.consensus_constants(height);
+        check_blockchain_version(constants, header.version).map_err(|e| invalid(e.to_string()))?;
+        check_timestamp_ftl(&header, &self.rules).map_err(|e| invalid(e.to_string()))?;
+        let mut timestamps = self
+            .headers
+            .iter()
+            .rev()
+            .take(constants.get_median_timestamp_count())
+            .map(|h| h.header().timestamp)
+            .collect::<Vec<_>>();
+        timestamps.reverse();
+        check_header_timestamp_greater_than_median(&header, &timestamps).map_err(|e| invalid(e.to_string()))?;
+
+        let target = self.target_difficulty(&header)?;
+        let achieved_target =
+            check_target_difficulty(&header, target, &self.randomx_factory).map_err(|e| invalid(e.to_string()))?;
+        let accumulated_data = BlockHeaderAccumulatedData::builder(tip.accumulated_data())
+            .with_hash(header.hash())
+            .with_achieved_target_difficulty(achieved_target)
+            .with_total_kernel_offset(header.total_kernel_offset.clone())
+            .build()
+            .map_err(|e| invalid(e.to_string()))?;
+        let header = ChainHeader::try_construct(header, accumulated_data)
+            .ok_or_else(|| invalid("Accumulated data does not match the header".to_string()))?;
+
+        self.headers.push_back(header);
+        if self.headers.len() > self.max_headers {
+            self.headers.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Calculates the target difficulty of `header` from the target difficulties of the preceding headers mined with
+    /// the same algorithm
+    fn target_difficulty(&self, header: &BlockHeader) -> Result<Difficulty, HeaderSyncError> {
+        let pow_algo = header.pow_algo();
+        let mut window = self.rules.new_target_difficulty(pow_algo, header.height);
+        for h in self.headers.iter().rev() {
+            if window.is_full() {
+                break;
+            }
+            if h.header().pow_algo() == pow_algo {
+                window.add_front(h.header().timestamp, h.accumulated_data().target_difficulty);
+            }
+        }
+        // A partial window is only correct if it includes every header back to the genesis block
+        if !window.is_full() && self.headers.front().map(|h| h.height()) != Some(0) {
+            return Err(HeaderSyncError::InsufficientHistory(header.height));
+        }
+        let constants = self.rules.consensus_constants(header.height);
+        Ok(window.calculate(
+            constants.min_pow_difficulty(pow_algo),
+            constants.max_pow_difficulty(pow_algo),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+
+    use super::*;
+
+    fn create_chain(max_headers: usize) -> HeaderChain {
+        HeaderChain::new(
+            ConsensusManager::builder(Network::LocalNet).build(),
+            RandomXFactory::default(),
+            max_headers,
+        )
+    }
+
+    #[test]
+    fn it_rejects_headers_that_do_not_connect() {
+        let mut chain = create_chain(10);
+        let mut header = BlockHeader::from_previous(chain.tip().header());
+        header.prev_hash = HashOutput::zero();
+        assert!(matches!(
+            chain.add_header(header),
+            Err(HeaderSyncError::DoesNotConnect {
+                height: 1,
+                tip_height: 0
+            })
+        ));
+
+        let mut header = BlockHeader::from_previous(chain.tip().header());
+        header.height = 2;
+        assert!(matches!(
+            chain.add_header(header),
+            Err(HeaderSyncError::DoesNotConnect { height: 2, .. })
+        ));
+        assert_eq!(chain.tip().height(), 0);
+    }
+
+    #[test]
+    fn it_keeps_the_genesis_header() {
+        let mut chain = create_chain(10);
+        let genesis_hash = *chain.tip().hash();
+        assert_eq!(chain.block_hashes(5), vec![genesis_hash]);
+        assert!(chain.get_header(0).is_some());
+        assert!(chain.get_header(1).is_none());
+        assert!(matches!(chain.rewind(1), Err(HeaderSyncError::ChainSplitNotFound)));
+        assert!(chain.rewind(0).is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! A proof of work verified copy of the base node's header chain, so that what the base node reports can be checked
+//! against the accumulated work of the chain instead of being trusted blindly. Verifying headers requires the
+//! `header_sync` feature.
+
+pub mod config;
+#[cfg(feature = "header_sync")]
+pub mod error;
+#[cfg(feature = "header_sync")]
+pub mod handle;
+#[cfg(feature = "header_sync")]
+pub mod header_chain;
+#[cfg(feature = "header_sync")]
+pub mod service;
+
+#[cfg(feature = "header_sync")]
+pub use initializer::HeaderSyncServiceInitializer;
+
+#[cfg(feature = "header_sync")]
+mod initializer {
+    use log::*;
+    use tari_core::{consensus::ConsensusManager, proof_of_work::randomx_factory::RandomXFactory};
+    use tari_service_framework::{
+        async_trait,
+        reply_channel,
+        ServiceInitializationError,
+        ServiceInitializer,
+        ServiceInitializerContext,
+    };
+    use tokio::sync::broadcast;
+
+    use crate::{
+        base_node_service::handle::BaseNodeServiceHandle,
+        connectivity_service::WalletConnectivityHandle,
+        header_sync::{
+            config::HeaderSyncServiceConfig,
+            handle::HeaderSyncHandle,
+            header_chain::HeaderChain,
+            service::HeaderSyncService,
+        },
+    };
+
+    const LOG_TARGET: &str = "wallet::header_sync";
+
+    pub struct HeaderSyncServiceInitializer {
+        config: HeaderSyncServiceConfig,
+        consensus_manager: ConsensusManager,
+    }
+
+    impl HeaderSyncServiceInitializer {
+        pub fn new(config: HeaderSyncServiceConfig, consensus_manager: ConsensusManager) -> Self {
+            Self {
+                config,
+                consensus_manager,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ServiceInitializer for HeaderSyncServiceInitializer {
+        async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
+            info!(target: LOG_TARGET, "Wallet header sync service initializing.");
+
+            let (sender, request_stream) = reply_channel::unbounded();
+            let (event_publisher, _) = broadcast::channel(self.config.event_channel_size);
+
+            // Register handle before waiting for handles to be ready
+            context.register_handle(HeaderSyncHandle::new(sender, event_publisher.clone()));
+
+            let config = self.config.clone();
+            let chain = HeaderChain::new(
+                self.consensus_manager.clone(),
+                RandomXFactory::default(),
+                config.max_retained_headers,
+            );
+
+            context.spawn_when_ready(move |handles| async move {
+                let wallet_connectivity = handles.expect_handle::<WalletConnectivityHandle>();
+                let base_node_service = handles.expect_handle::<BaseNodeServiceHandle>();
+
+                let result = HeaderSyncService::new(
+                    config,
+                    request_stream,
+                    chain,
+                    wallet_connectivity,
+                    base_node_service,
+                    event_publisher,
+                    handles.get_shutdown_signal(),
+                )
+                .start()
+                .await;
+
+                info!(
+                    target: LOG_TARGET,
+                    "Wallet Header Sync Service shutdown with result {:?}", result
+                );
+            });
+
+            Ok(())
+        }
+    }
+}
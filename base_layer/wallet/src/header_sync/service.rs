@@ -0,0 +1,280 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Arc,
+};
+
+use futures::{future, StreamExt};
+use log::*;
+use tari_common_types::types::BlockHash;
+use tari_comms::protocol::rpc::RpcError;
+use tari_core::{
+    blocks::{BlockHeader, BlockInclusionProof},
+    proto::base_node::{BlockInclusionProofRequest, FindChainSplitRequest},
+};
+use tari_service_framework::reply_channel::Receiver;
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    sync::{broadcast::error::RecvError, RwLock},
+    task,
+};
+
+use super::{
+    config::HeaderSyncServiceConfig,
+    error::HeaderSyncError,
+    handle::{HeaderSyncEvent, HeaderSyncEventSender, HeaderSyncRequest, HeaderSyncResponse},
+    header_chain::HeaderChain,
+};
+use crate::{
+    base_node_service::handle::BaseNodeServiceHandle,
+    connectivity_service::{WalletConnectivityHandle, WalletConnectivityInterface},
+};
+
+const LOG_TARGET: &str = "wallet::header_sync::service";
+
+/// The maximum number of block hashes the base node accepts when finding a chain split
+const MAX_CHAIN_SPLIT_HASHES: usize = 1000;
+
+/// The header sync service keeps a proof of work verified copy of the base node's header chain, so that other
+/// services can check what the base node reports against the accumulated work of the chain.
+pub struct HeaderSyncService {
+    config: HeaderSyncServiceConfig,
+    request_stream: Option<Receiver<HeaderSyncRequest, Result<HeaderSyncResponse, HeaderSyncError>>>,
+    wallet_connectivity: WalletConnectivityHandle,
+    base_node_service: BaseNodeServiceHandle,
+    event_publisher: HeaderSyncEventSender,
+    shutdown_signal: ShutdownSignal,
+    chain: Arc<RwLock<HeaderChain>>,
+}
+
+impl HeaderSyncService {
+    pub fn new(
+        config: HeaderSyncServiceConfig,
+        request_stream: Receiver<HeaderSyncRequest, Result<HeaderSyncResponse, HeaderSyncError>>,
+        chain: HeaderChain,
+        wallet_connectivity: WalletConnectivityHandle,
+        base_node_service: BaseNodeServiceHandle,
+        event_publisher: HeaderSyncEventSender,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            request_stream: Some(request_stream),
+            wallet_connectivity,
+            base_node_service,
+            event_publisher,
+            shutdown_signal,
+            chain: Arc::new(RwLock::new(chain)),
+        }
+    }
+
+    /// Starts the service.
+    pub async fn start(mut self) -> Result<(), HeaderSyncError> {
+        if self.config.enabled {
+            self.spawn_synchronizer();
+        } else {
+            debug!(target: LOG_TARGET, "Wallet header sync is disabled");
+        }
+
+        let mut request_stream = self
+            .request_stream
+            .take()
+            .expect("Wallet Header Sync Service initialized without request_stream")
+            .take_until(self.shutdown_signal.clone());
+
+        debug!(target: LOG_TARGET, "Wallet Header Sync Service started");
+        while let Some(request_context) = request_stream.next().await {
+            let (request, reply_tx) = request_context.split();
+            let response = self.handle_request(request).await;
+            let _result = reply_tx.send(response).map_err(|e| {
+                warn!(target: LOG_TARGET, "Failed to send reply");
+                e
+            });
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Wallet Header Sync Service shutting down because the shutdown signal was received"
+        );
+        Ok(())
+    }
+
+    fn spawn_synchronizer(&self) {
+        let synchronizer = HeaderSynchronizer {
+            headers_per_request: self.config.headers_per_request,
+            chain: self.chain.clone(),
+            wallet_connectivity: self.wallet_connectivity.clone(),
+            base_node_service: self.base_node_service.clone(),
+            event_publisher: self.event_publisher.clone(),
+        };
+
+        let shutdown_signal = self.shutdown_signal.clone();
+        tokio::spawn(async move {
+            let synchronizer_fut = synchronizer.run();
+            futures::pin_mut!(synchronizer_fut);
+            future::select(shutdown_signal, synchronizer_fut).await;
+        });
+    }
+
+    async fn handle_request(&mut self, request: HeaderSyncRequest) -> Result<HeaderSyncResponse, HeaderSyncError> {
+        trace!(target: LOG_TARGET, "Handling Wallet Header Sync Request: {:?}", request);
+        match request {
+            HeaderSyncRequest::GetVerifiedHeader(height) => Ok(HeaderSyncResponse::VerifiedHeader(
+                self.chain.read().await.get_header(height).cloned().map(Box::new),
+            )),
+            HeaderSyncRequest::GetVerifiedTip => Ok(HeaderSyncResponse::VerifiedTip(Box::new(
+                self.chain.read().await.tip().clone(),
+            ))),
+            HeaderSyncRequest::GetVerifiedInclusionProof { height, block_hash } => self
+                .get_verified_inclusion_proof(height, block_hash)
+                .await
+                .map(|proof| HeaderSyncResponse::VerifiedInclusionProof(Box::new(proof))),
+        }
+    }
+
+    async fn get_verified_inclusion_proof(
+        &mut self,
+        height: u64,
+        block_hash: BlockHash,
+    ) -> Result<BlockInclusionProof, HeaderSyncError> {
+        let header = self
+            .chain
+            .read()
+            .await
+            .get_header(height)
+            .map(|h| h.header().clone())
+            .ok_or(HeaderSyncError::HeaderNotVerified(height))?;
+        if header.hash() != block_hash {
+            return Err(HeaderSyncError::BlockNotVerified(height));
+        }
+
+        let mut client = self
+            .wallet_connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or(HeaderSyncError::NodeShuttingDown)?;
+        let proof = client
+            .get_block_inclusion_proof(BlockInclusionProofRequest {
+                block_hash: block_hash.to_vec(),
+            })
+            .await?;
+        let proof = BlockInclusionProof::try_from(proof).map_err(HeaderSyncError::InvalidBaseNodeResponse)?;
+        proof.verify(&header)?;
+        Ok(proof)
+    }
+}
+
+/// Follows the base node's chain, verifying every new header before it is added to the shared header chain
+struct HeaderSynchronizer {
+    headers_per_request: u64,
+    chain: Arc<RwLock<HeaderChain>>,
+    wallet_connectivity: WalletConnectivityHandle,
+    base_node_service: BaseNodeServiceHandle,
+    event_publisher: HeaderSyncEventSender,
+}
+
+impl HeaderSynchronizer {
+    async fn run(mut self) {
+        let mut chain_events = self.base_node_service.subscribe_chain_events();
+        loop {
+            match self.synchronize().await {
+                Ok(()) => {},
+                Err(HeaderSyncError::NodeShuttingDown) => break,
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Header sync failed: {}", e);
+                    self.publish_event(HeaderSyncEvent::SyncFailed(e.to_string()));
+                },
+            }
+            // Wait for the base node to report a new block or reorg before syncing again
+            match chain_events.recv().await {
+                Ok(_) | Err(RecvError::Lagged(_)) => {},
+                Err(RecvError::Closed) => break,
+            }
+        }
+        debug!(target: LOG_TARGET, "Wallet header synchronizer stopped");
+    }
+
+    async fn synchronize(&mut self) -> Result<(), HeaderSyncError> {
+        let mut client = self
+            .wallet_connectivity
+            .obtain_base_node_sync_rpc_client()
+            .await
+            .ok_or(HeaderSyncError::NodeShuttingDown)?;
+
+        loop {
+            let block_hashes = self.chain.read().await.block_hashes(MAX_CHAIN_SPLIT_HASHES);
+            let request = FindChainSplitRequest {
+                block_hashes: block_hashes.iter().map(|h| h.to_vec()).collect(),
+                header_count: self.headers_per_request,
+            };
+            let response = match client.find_chain_split(request).await {
+                Ok(response) => response,
+                Err(RpcError::RequestFailed(status)) if status.as_status_code().is_not_found() => {
+                    return Err(HeaderSyncError::ChainSplitNotFound);
+                },
+                Err(e) => return Err(e.into()),
+            };
+            let fork_hash_index: usize = response
+                .fork_hash_index
+                .try_into()
+                .map_err(|_| HeaderSyncError::InvalidBaseNodeResponse("Fork hash index is too large".to_string()))?;
+            let headers = response
+                .headers
+                .into_iter()
+                .map(BlockHeader::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(HeaderSyncError::InvalidBaseNodeResponse)?;
+            let num_headers = headers.len();
+
+            if fork_hash_index > 0 || num_headers > 0 {
+                // Verification is CPU intensive, so it is done on a copy of the chain off the async runtime
+                let mut candidate = self.chain.read().await.clone();
+                let candidate = task::spawn_blocking(move || -> Result<HeaderChain, HeaderSyncError> {
+                    candidate.rewind(fork_hash_index)?;
+                    for header in headers {
+                        candidate.add_header(header)?;
+                    }
+                    Ok(candidate)
+                })
+                .await??;
+
+                let mut chain = self.chain.write().await;
+                if fork_hash_index > 0 {
+                    // Only follow a reorg to a chain with more accumulated work than the verified chain
+                    let current_difficulty = chain.tip().accumulated_data().total_accumulated_difficulty;
+                    if candidate.tip().accumulated_data().total_accumulated_difficulty <= current_difficulty {
+                        return Err(HeaderSyncError::WeakerChain);
+                    }
+                    info!(
+                        target: LOG_TARGET,
+                        "Verified header chain reorged {} header(s) back from height {}",
+                        fork_hash_index,
+                        chain.tip().height()
+                    );
+                }
+                *chain = candidate;
+            }
+
+            let chain = self.chain.read().await;
+            let tip = chain.tip();
+            if num_headers == 0 || tip.height() >= response.tip_height {
+                debug!(
+                    target: LOG_TARGET,
+                    "Verified header chain synced to height {}",
+                    tip.height()
+                );
+                self.publish_event(HeaderSyncEvent::Synced {
+                    height: tip.height(),
+                    accumulated_difficulty: tip.accumulated_data().total_accumulated_difficulty,
+                });
+                return Ok(());
+            }
+        }
+    }
+
+    fn publish_event(&self, event: HeaderSyncEvent) {
+        let _size = self.event_publisher.send(Arc::new(event));
+    }
+}
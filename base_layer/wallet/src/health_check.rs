@@ -0,0 +1,127 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Liveness and readiness of the wallet's services, as reported by [Wallet::health_check](crate::Wallet::health_check).
+
+use std::{
+    fmt::Display,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use tokio::time;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceStatus {
+    /// The service replied within the lag threshold
+    Responsive,
+    /// The service replied, but took longer than the lag threshold
+    Lagging,
+    /// The service returned an error or did not reply before the timeout
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ServiceHealth {
+    pub service: &'static str,
+    pub status: ServiceStatus,
+    /// How long the service took to reply, if it replied
+    pub response_time: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    pub services: Vec<ServiceHealth>,
+}
+
+impl HealthReport {
+    /// Returns true if no service has failed, i.e. the wallet does not need to be restarted
+    pub fn is_live(&self) -> bool {
+        self.services
+            .iter()
+            .all(|s| !matches!(s.status, ServiceStatus::Failed(_)))
+    }
+
+    /// Returns true if every service is responsive, i.e. the wallet can serve requests without delay
+    pub fn is_ready(&self) -> bool {
+        self.services.iter().all(|s| s.status == ServiceStatus::Responsive)
+    }
+
+    pub fn get(&self, service: &str) -> Option<&ServiceHealth> {
+        self.services.iter().find(|s| s.service == service)
+    }
+}
+
+/// Pings a service and classifies how it replied
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HealthProbe {
+    pub lag_threshold: Duration,
+    pub timeout: Duration,
+}
+
+impl HealthProbe {
+    pub async fn check<F, T, E>(&self, service: &'static str, ping: F) -> ServiceHealth
+    where
+        F: Future<Output = Result<T, E>>,
+        E: Display,
+    {
+        let start = Instant::now();
+        let result = time::timeout(self.timeout, ping).await;
+        let elapsed = start.elapsed();
+        let (status, response_time) = match result {
+            Ok(Ok(_)) if elapsed < self.lag_threshold => (ServiceStatus::Responsive, Some(elapsed)),
+            Ok(Ok(_)) => (ServiceStatus::Lagging, Some(elapsed)),
+            Ok(Err(e)) => (ServiceStatus::Failed(e.to_string()), Some(elapsed)),
+            Err(_) => (
+                ServiceStatus::Failed(format!("No reply within {:.0?}", self.timeout)),
+                None,
+            ),
+        };
+        ServiceHealth {
+            service,
+            status,
+            response_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::future;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_classifies_service_replies() {
+        let probe = HealthProbe {
+            lag_threshold: Duration::from_secs(60),
+            timeout: Duration::from_millis(10),
+        };
+        let responsive = probe.check("responsive", future::ok::<_, String>(())).await;
+        assert_eq!(responsive.status, ServiceStatus::Responsive);
+        let failed = probe.check("failed", future::err::<(), _>("boom")).await;
+        assert_eq!(failed.status, ServiceStatus::Failed("boom".to_string()));
+        let timed_out = probe.check("timed_out", future::pending::<Result<(), String>>()).await;
+        assert!(matches!(timed_out.status, ServiceStatus::Failed(_)));
+        assert!(timed_out.response_time.is_none());
+
+        let lagging = HealthProbe {
+            lag_threshold: Duration::ZERO,
+            ..probe
+        }
+        .check("lagging", future::ok::<_, String>(()))
+        .await;
+        assert_eq!(lagging.status, ServiceStatus::Lagging);
+
+        let report = HealthReport {
+            services: vec![responsive, lagging],
+        };
+        assert!(report.is_live());
+        assert!(!report.is_ready());
+        assert_eq!(report.get("lagging").unwrap().status, ServiceStatus::Lagging);
+        let report = HealthReport {
+            services: vec![failed, timed_out],
+        };
+        assert!(!report.is_live());
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InheritanceServiceConfig {
+    /// How often the inheritance transaction is signed again with a later lock height while the wallet is running
+    #[serde(with = "serializers::seconds")]
+    pub refresh_interval: Duration,
+    /// The transaction is also refreshed early when the chain comes within this many blocks of its lock height
+    pub refresh_margin: u64,
+    /// This is the size of the event channel used to communicate inheritance events to the wallet
+    pub event_channel_size: usize,
+}
+
+impl Default for InheritanceServiceConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval: Duration::from_secs(24 * 60 * 60),
+            refresh_margin: 720,
+            event_channel_size: 10,
+        }
+    }
+}
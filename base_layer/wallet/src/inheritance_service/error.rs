@@ -0,0 +1,36 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use tari_service_framework::reply_channel::TransportChannelError;
+use tari_utilities::message_format::MessageFormatError;
+use thiserror::Error;
+
+use crate::{
+    base_node_service::error::BaseNodeServiceError,
+    error::WalletStorageError,
+    output_manager_service::error::OutputManagerError,
+};
+
+#[derive(Debug, Error)]
+pub enum InheritanceServiceError {
+    #[error("Output manager service error: `{0}`")]
+    OutputManagerError(#[from] OutputManagerError),
+    #[error("Base node service error: `{0}`")]
+    BaseNodeServiceError(#[from] BaseNodeServiceError),
+    #[error("Wallet storage error: `{0}`")]
+    WalletStorageError(#[from] WalletStorageError),
+    #[error("Transport channel error: `{0}`")]
+    TransportChannelError(#[from] TransportChannelError),
+    #[error("Message format error: `{0}`")]
+    MessageFormatError(#[from] MessageFormatError),
+    #[error("Invalid inheritance plan: {0}")]
+    InvalidPlan(String),
+    #[error("No inheritance plan has been configured")]
+    NoPlan,
+    #[error("The inheritance transaction has not been signed yet")]
+    NoSignedTransaction,
+    #[error("The height of the chain tip is not known yet")]
+    ChainTipUnknown,
+    #[error("Unexpected API response")]
+    UnexpectedApiResponse,
+}
@@ -0,0 +1,185 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{fmt, fmt::Formatter, sync::Arc};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::{tari_amount::MicroTari, transaction_components::Transaction};
+use tari_service_framework::reply_channel::SenderService;
+use tari_utilities::message_format::MessageFormat;
+use tokio::sync::broadcast;
+use tower::Service;
+
+use super::error::InheritanceServiceError;
+
+pub type InheritanceEventSender = broadcast::Sender<Arc<InheritanceEvent>>;
+pub type InheritanceEventReceiver = broadcast::Receiver<Arc<InheritanceEvent>>;
+
+#[derive(Debug)]
+pub enum InheritanceServiceRequest {
+    SetPlan {
+        beneficiaries: Vec<Beneficiary>,
+        inactivity_period: u64,
+        fee_per_gram: MicroTari,
+    },
+    GetPlan,
+    ClearPlan,
+    RefreshTransaction,
+    ExportTransaction,
+}
+
+#[derive(Debug)]
+pub enum InheritanceServiceResponse {
+    Plan(Box<Option<InheritancePlan>>),
+    PlanCleared,
+    Transaction(Box<InheritanceTransaction>),
+    ExportedTransaction(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InheritanceEvent {
+    TransactionRefreshed {
+        lock_height: u64,
+        amount: MicroTari,
+    },
+    /// The transaction could not be signed again, so heirs holding an older export rely on its earlier lock height
+    RefreshFailed(String),
+}
+
+impl fmt::Display for InheritanceEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InheritanceEvent::TransactionRefreshed { lock_height, amount } => {
+                write!(
+                    f,
+                    "TransactionRefreshed: {} locked until height {}",
+                    amount, lock_height
+                )
+            },
+            InheritanceEvent::RefreshFailed(reason) => write!(f, "RefreshFailed: {}", reason),
+        }
+    }
+}
+
+/// An heir that receives part of the wallet's funds
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Beneficiary {
+    /// The wallet public key of the heir, which is paid with a one-sided output
+    pub public_key: CommsPublicKey,
+    /// The share of the funds, relative to the shares of the other beneficiaries
+    pub share: u64,
+    pub label: String,
+}
+
+/// The beneficiaries of the wallet and the latest signed transaction that pays them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InheritancePlan {
+    pub beneficiaries: Vec<Beneficiary>,
+    /// The number of blocks after the last refresh from which the heirs can broadcast the transaction
+    pub inactivity_period: u64,
+    pub fee_per_gram: MicroTari,
+    pub transaction: Option<InheritanceTransaction>,
+}
+
+/// A signed transaction that pays the funds of the wallet to the beneficiaries once the chain reaches `lock_height`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InheritanceTransaction {
+    pub transaction: Transaction,
+    pub lock_height: u64,
+    /// The total amount paid to the beneficiaries
+    pub amount: MicroTari,
+    pub signed_at: NaiveDateTime,
+}
+
+impl InheritanceTransaction {
+    /// Encodes the transaction as a blob that is handed to the heirs
+    pub fn export(&self) -> Result<String, InheritanceServiceError> {
+        Ok(self.to_base64()?)
+    }
+
+    /// Decodes a blob created with [InheritanceTransaction::export]
+    pub fn import(blob: &str) -> Result<Self, InheritanceServiceError> {
+        Ok(Self::from_base64(blob.trim())?)
+    }
+}
+
+#[derive(Clone)]
+pub struct InheritanceServiceHandle {
+    handle: SenderService<InheritanceServiceRequest, Result<InheritanceServiceResponse, InheritanceServiceError>>,
+    event_stream_sender: InheritanceEventSender,
+}
+
+impl InheritanceServiceHandle {
+    pub fn new(
+        handle: SenderService<InheritanceServiceRequest, Result<InheritanceServiceResponse, InheritanceServiceError>>,
+        event_stream_sender: InheritanceEventSender,
+    ) -> Self {
+        Self {
+            handle,
+            event_stream_sender,
+        }
+    }
+
+    pub fn get_event_stream(&self) -> InheritanceEventReceiver {
+        self.event_stream_sender.subscribe()
+    }
+
+    /// Replaces the beneficiaries of the wallet and signs a new inheritance transaction
+    pub async fn set_plan(
+        &mut self,
+        beneficiaries: Vec<Beneficiary>,
+        inactivity_period: u64,
+        fee_per_gram: MicroTari,
+    ) -> Result<InheritancePlan, InheritanceServiceError> {
+        match self
+            .handle
+            .call(InheritanceServiceRequest::SetPlan {
+                beneficiaries,
+                inactivity_period,
+                fee_per_gram,
+            })
+            .await??
+        {
+            InheritanceServiceResponse::Plan(plan) => (*plan).ok_or(InheritanceServiceError::NoPlan),
+            _ => Err(InheritanceServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_plan(&mut self) -> Result<Option<InheritancePlan>, InheritanceServiceError> {
+        match self.handle.call(InheritanceServiceRequest::GetPlan).await?? {
+            InheritanceServiceResponse::Plan(plan) => Ok(*plan),
+            _ => Err(InheritanceServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Removes the plan and its transaction from the wallet. Transactions that were already exported stay valid until
+    /// one of their inputs is spent.
+    pub async fn clear_plan(&mut self) -> Result<(), InheritanceServiceError> {
+        match self.handle.call(InheritanceServiceRequest::ClearPlan).await?? {
+            InheritanceServiceResponse::PlanCleared => Ok(()),
+            _ => Err(InheritanceServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Signs the inheritance transaction again, with a lock height `inactivity_period` blocks after the current tip
+    pub async fn refresh_transaction(&mut self) -> Result<InheritanceTransaction, InheritanceServiceError> {
+        match self
+            .handle
+            .call(InheritanceServiceRequest::RefreshTransaction)
+            .await??
+        {
+            InheritanceServiceResponse::Transaction(tx) => Ok(*tx),
+            _ => Err(InheritanceServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the latest signed inheritance transaction as a blob for the heirs
+    pub async fn export_transaction(&mut self) -> Result<String, InheritanceServiceError> {
+        match self.handle.call(InheritanceServiceRequest::ExportTransaction).await?? {
+            InheritanceServiceResponse::ExportedTransaction(blob) => Ok(blob),
+            _ => Err(InheritanceServiceError::UnexpectedApiResponse),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+pub mod config;
+pub mod error;
+pub mod handle;
+pub mod service;
+
+use log::*;
+use tari_service_framework::{
+    async_trait,
+    reply_channel,
+    ServiceInitializationError,
+    ServiceInitializer,
+    ServiceInitializerContext,
+};
+use tokio::sync::broadcast;
+
+use crate::{
+    base_node_service::handle::BaseNodeServiceHandle,
+    inheritance_service::{
+        config::InheritanceServiceConfig,
+        handle::InheritanceServiceHandle,
+        service::InheritanceService,
+    },
+    output_manager_service::handle::OutputManagerHandle,
+    storage::database::{WalletBackend, WalletDatabase},
+};
+
+const LOG_TARGET: &str = "wallet::inheritance_service";
+
+pub struct InheritanceServiceInitializer<T>
+where T: WalletBackend + 'static
+{
+    config: InheritanceServiceConfig,
+    db: WalletDatabase<T>,
+}
+
+impl<T> InheritanceServiceInitializer<T>
+where T: WalletBackend + 'static
+{
+    pub fn new(config: InheritanceServiceConfig, db: WalletDatabase<T>) -> Self {
+        Self { config, db }
+    }
+}
+
+#[async_trait]
+impl<T> ServiceInitializer for InheritanceServiceInitializer<T>
+where T: WalletBackend + 'static
+{
+    async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
+        info!(target: LOG_TARGET, "Wallet inheritance service initializing.");
+
+        let (sender, request_stream) = reply_channel::unbounded();
+
+        let (event_publisher, _) = broadcast::channel(self.config.event_channel_size);
+
+        let inheritance_service_handle = InheritanceServiceHandle::new(sender, event_publisher.clone());
+
+        // Register handle before waiting for handles to be ready
+        context.register_handle(inheritance_service_handle);
+
+        let config = self.config.clone();
+        let db = self.db.clone();
+
+        context.spawn_when_ready(move |handles| async move {
+            let output_manager_service = handles.expect_handle::<OutputManagerHandle>();
+            let base_node_service = handles.expect_handle::<BaseNodeServiceHandle>();
+
+            let result = InheritanceService::new(
+                config,
+                request_stream,
+                db,
+                output_manager_service,
+                base_node_service,
+                event_publisher,
+                handles.get_shutdown_signal(),
+            )
+            .start()
+            .await;
+
+            info!(
+                target: LOG_TARGET,
+                "Wallet Inheritance Service shutdown with result {:?}", result
+            );
+        });
+
+        Ok(())
+    }
+}
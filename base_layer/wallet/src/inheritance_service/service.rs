@@ -0,0 +1,347 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use futures::StreamExt;
+use log::*;
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_service_framework::reply_channel::Receiver;
+use tari_shutdown::ShutdownSignal;
+use tokio::time::{self, Instant, MissedTickBehavior};
+
+use super::{
+    config::InheritanceServiceConfig,
+    error::InheritanceServiceError,
+    handle::{
+        Beneficiary,
+        InheritanceEvent,
+        InheritanceEventSender,
+        InheritancePlan,
+        InheritanceServiceRequest,
+        InheritanceServiceResponse,
+        InheritanceTransaction,
+    },
+};
+use crate::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    output_manager_service::handle::OutputManagerHandle,
+    storage::database::{WalletBackend, WalletDatabase},
+};
+
+const LOG_TARGET: &str = "wallet::inheritance_service::service";
+
+/// The number of blocks to wait before retrying a refresh that failed when it was triggered by a new block
+const REFRESH_RETRY_BLOCKS: u64 = 30;
+
+/// Keeps a signed transaction that pays the funds of the wallet to its beneficiaries once the chain passes a lock
+/// height. While the wallet is running, the transaction is signed again with a lock height `inactivity_period` blocks
+/// after the tip, so the heirs can only broadcast it once the owner has stopped using the wallet for that long.
+pub struct InheritanceService<T>
+where T: WalletBackend + 'static
+{
+    config: InheritanceServiceConfig,
+    request_stream:
+        Option<Receiver<InheritanceServiceRequest, Result<InheritanceServiceResponse, InheritanceServiceError>>>,
+    db: WalletDatabase<T>,
+    output_manager_service: OutputManagerHandle,
+    base_node_service: BaseNodeServiceHandle,
+    event_publisher: InheritanceEventSender,
+    shutdown_signal: ShutdownSignal,
+    next_block_refresh_height: u64,
+}
+
+impl<T> InheritanceService<T>
+where T: WalletBackend + 'static
+{
+    pub fn new(
+        config: InheritanceServiceConfig,
+        request_stream: Receiver<
+            InheritanceServiceRequest,
+            Result<InheritanceServiceResponse, InheritanceServiceError>,
+        >,
+        db: WalletDatabase<T>,
+        output_manager_service: OutputManagerHandle,
+        base_node_service: BaseNodeServiceHandle,
+        event_publisher: InheritanceEventSender,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            request_stream: Some(request_stream),
+            db,
+            output_manager_service,
+            base_node_service,
+            event_publisher,
+            shutdown_signal,
+            next_block_refresh_height: 0,
+        }
+    }
+
+    /// Starts the service.
+    pub async fn start(mut self) -> Result<(), InheritanceServiceError> {
+        let mut request_stream = self
+            .request_stream
+            .take()
+            .expect("Wallet Inheritance Service initialized without request_stream");
+        let mut shutdown = self.shutdown_signal.clone();
+        let mut base_node_events = self.base_node_service.get_event_stream();
+
+        let interval = self.config.refresh_interval;
+        let mut refresh_interval = time::interval_at(Instant::now() + interval, interval);
+        refresh_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        debug!(target: LOG_TARGET, "Wallet Inheritance Service started");
+        loop {
+            tokio::select! {
+                Some(request_context) = request_stream.next() => {
+                    let (request, reply_tx) = request_context.split();
+                    let response = self.handle_request(request).await.map_err(|e| {
+                        error!(target: LOG_TARGET, "Error handling request: {:?}", e);
+                        e
+                    });
+                    let _result = reply_tx.send(response).map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send reply");
+                        e
+                    });
+                },
+                Ok(event) = base_node_events.recv() => {
+                    if let BaseNodeEvent::NewBlockDetected(height) = *event {
+                        self.handle_new_block(height).await;
+                    }
+                },
+                _ = refresh_interval.tick() => {
+                    let _refreshed = self.refresh_in_background().await;
+                },
+                _ = shutdown.wait() => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Wallet Inheritance Service shutting down because the shutdown signal was received"
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_request(
+        &mut self,
+        request: InheritanceServiceRequest,
+    ) -> Result<InheritanceServiceResponse, InheritanceServiceError> {
+        debug!(target: LOG_TARGET, "Handling Service Request: {:?}", request);
+        match request {
+            InheritanceServiceRequest::SetPlan {
+                beneficiaries,
+                inactivity_period,
+                fee_per_gram,
+            } => {
+                self.set_plan(beneficiaries, inactivity_period, fee_per_gram).await?;
+                Ok(InheritanceServiceResponse::Plan(Box::new(
+                    self.db.get_inheritance_plan()?,
+                )))
+            },
+            InheritanceServiceRequest::GetPlan => Ok(InheritanceServiceResponse::Plan(Box::new(
+                self.db.get_inheritance_plan()?,
+            ))),
+            InheritanceServiceRequest::ClearPlan => {
+                self.db.clear_inheritance_plan()?;
+                Ok(InheritanceServiceResponse::PlanCleared)
+            },
+            InheritanceServiceRequest::RefreshTransaction => {
+                let transaction = self.refresh().await?;
+                Ok(InheritanceServiceResponse::Transaction(Box::new(transaction)))
+            },
+            InheritanceServiceRequest::ExportTransaction => {
+                let blob = self
+                    .db
+                    .get_inheritance_plan()?
+                    .ok_or(InheritanceServiceError::NoPlan)?
+                    .transaction
+                    .ok_or(InheritanceServiceError::NoSignedTransaction)?
+                    .export()?;
+                Ok(InheritanceServiceResponse::ExportedTransaction(blob))
+            },
+        }
+    }
+
+    /// Stores the new plan and signs its transaction. The plan is kept if the transaction cannot be signed yet, in
+    /// which case it is signed by a later refresh.
+    async fn set_plan(
+        &mut self,
+        beneficiaries: Vec<Beneficiary>,
+        inactivity_period: u64,
+        fee_per_gram: MicroTari,
+    ) -> Result<(), InheritanceServiceError> {
+        validate_plan(&beneficiaries, inactivity_period, self.config.refresh_margin)?;
+        self.db.set_inheritance_plan(InheritancePlan {
+            beneficiaries,
+            inactivity_period,
+            fee_per_gram,
+            transaction: None,
+        })?;
+        self.next_block_refresh_height = 0;
+        let _refreshed = self.refresh_in_background().await;
+        Ok(())
+    }
+
+    async fn handle_new_block(&mut self, height: u64) {
+        if height < self.next_block_refresh_height {
+            return;
+        }
+        let plan = match self.db.get_inheritance_plan() {
+            Ok(Some(plan)) => plan,
+            Ok(None) => return,
+            Err(e) => {
+                error!(target: LOG_TARGET, "Could not read the inheritance plan: {}", e);
+                return;
+            },
+        };
+        if needs_refresh(&plan, height, self.config.refresh_margin) && !self.refresh_in_background().await {
+            self.next_block_refresh_height = height.saturating_add(REFRESH_RETRY_BLOCKS);
+        }
+    }
+
+    /// Refreshes the transaction if a plan is configured, publishing an event if that fails. Returns false if the
+    /// refresh failed.
+    async fn refresh_in_background(&mut self) -> bool {
+        match self.refresh().await {
+            Ok(_) | Err(InheritanceServiceError::NoPlan) => true,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not refresh the inheritance transaction: {}", e
+                );
+                let _size = self
+                    .event_publisher
+                    .send(Arc::new(InheritanceEvent::RefreshFailed(e.to_string())));
+                false
+            },
+        }
+    }
+
+    async fn refresh(&mut self) -> Result<InheritanceTransaction, InheritanceServiceError> {
+        let mut plan = self.db.get_inheritance_plan()?.ok_or(InheritanceServiceError::NoPlan)?;
+        let tip_height = self
+            .base_node_service
+            .get_chain_metadata()
+            .await?
+            .map(|metadata| metadata.height_of_longest_chain())
+            .ok_or(InheritanceServiceError::ChainTipUnknown)?;
+        let lock_height = tip_height.saturating_add(plan.inactivity_period);
+        let beneficiaries = plan
+            .beneficiaries
+            .iter()
+            .map(|b| (b.public_key.clone(), b.share))
+            .collect();
+        let (transaction, amount) = self
+            .output_manager_service
+            .create_inheritance_transaction(beneficiaries, lock_height, plan.fee_per_gram)
+            .await?;
+        let transaction = InheritanceTransaction {
+            transaction,
+            lock_height,
+            amount,
+            signed_at: Utc::now().naive_utc(),
+        };
+        plan.transaction = Some(transaction.clone());
+        self.db.set_inheritance_plan(plan)?;
+
+        info!(
+            target: LOG_TARGET,
+            "Inheritance transaction of {} signed with lock height {}", amount, lock_height
+        );
+        let _size = self
+            .event_publisher
+            .send(Arc::new(InheritanceEvent::TransactionRefreshed { lock_height, amount }));
+        Ok(transaction)
+    }
+}
+
+fn validate_plan(
+    beneficiaries: &[Beneficiary],
+    inactivity_period: u64,
+    refresh_margin: u64,
+) -> Result<(), InheritanceServiceError> {
+    if beneficiaries.is_empty() {
+        return Err(InheritanceServiceError::InvalidPlan(
+            "At least one beneficiary is required".to_string(),
+        ));
+    }
+    if let Some(b) = beneficiaries.iter().find(|b| b.share == 0) {
+        return Err(InheritanceServiceError::InvalidPlan(format!(
+            "The share of beneficiary '{}' must be greater than zero",
+            b.label
+        )));
+    }
+    // Otherwise the transaction would be refreshed on every block
+    if inactivity_period <= refresh_margin {
+        return Err(InheritanceServiceError::InvalidPlan(format!(
+            "The inactivity period must be more than {} blocks",
+            refresh_margin
+        )));
+    }
+    Ok(())
+}
+
+/// The transaction is refreshed when there is none yet, or when the chain is about to reach its lock height
+fn needs_refresh(plan: &InheritancePlan, height: u64, refresh_margin: u64) -> bool {
+    plan.transaction
+        .as_ref()
+        .map_or(true, |tx| tx.lock_height <= height.saturating_add(refresh_margin))
+}
+
+#[cfg(test)]
+mod test {
+    use tari_comms::types::CommsPublicKey;
+    use tari_core::transactions::transaction_components::Transaction;
+
+    use super::*;
+
+    fn beneficiary(share: u64) -> Beneficiary {
+        Beneficiary {
+            public_key: CommsPublicKey::default(),
+            share,
+            label: "heir".to_string(),
+        }
+    }
+
+    #[test]
+    fn it_validates_the_plan() {
+        assert!(validate_plan(&[beneficiary(1), beneficiary(3)], 1000, 720).is_ok());
+        assert!(matches!(
+            validate_plan(&[], 1000, 720),
+            Err(InheritanceServiceError::InvalidPlan(_))
+        ));
+        assert!(matches!(
+            validate_plan(&[beneficiary(1), beneficiary(0)], 1000, 720),
+            Err(InheritanceServiceError::InvalidPlan(_))
+        ));
+        assert!(matches!(
+            validate_plan(&[beneficiary(1)], 720, 720),
+            Err(InheritanceServiceError::InvalidPlan(_))
+        ));
+    }
+
+    #[test]
+    fn it_refreshes_near_the_lock_height() {
+        let mut plan = InheritancePlan {
+            beneficiaries: vec![beneficiary(1)],
+            inactivity_period: 1000,
+            fee_per_gram: MicroTari::from(5),
+            transaction: None,
+        };
+        assert!(needs_refresh(&plan, 100, 10));
+
+        plan.transaction = Some(InheritanceTransaction {
+            transaction: Transaction::new(vec![], vec![], vec![], Default::default(), Default::default()),
+            lock_height: 1100,
+            amount: MicroTari::from(1000),
+            signed_at: Utc::now().naive_utc(),
+        });
+        assert!(!needs_refresh(&plan, 100, 10));
+        assert!(!needs_refresh(&plan, 1089, 10));
+        assert!(needs_refresh(&plan, 1090, 10));
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Consistency checks across the databases of the wallet's services, run when the wallet starts and by
+//! [Wallet::check_integrity](crate::Wallet::check_integrity). Records that reference a record that does not exist, e.g.
+//! after a crash between the writes of two services, are repaired where that cannot lose funds and are reported
+//! otherwise.
+
+use std::collections::{HashMap, HashSet};
+
+use log::*;
+use tari_common_types::{transaction::TxId, types::Commitment};
+use tari_utilities::hex::Hex;
+
+use crate::{
+    error::WalletError,
+    output_manager_service::{
+        error::OutputManagerError,
+        handle::RestoredKeyIndex,
+        storage::{
+            database::{OutputManagerBackend, OutputManagerDatabase},
+            OutputStatus,
+        },
+    },
+    transaction_service::{
+        error::TransactionServiceError,
+        storage::database::{TransactionBackend, TransactionDatabase},
+    },
+};
+
+const LOG_TARGET: &str = "wallet::integrity";
+
+/// How many consecutive unused keys are probed past the last used key when the key manager indices are checked
+pub const KEY_INDEX_GAP_LIMIT: u64 = 100;
+
+/// What is done about an output that references a transaction that the wallet does not have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrphanRepair {
+    /// The output was encumbered to be spent in the transaction and is released, so that it can be spent again
+    Release,
+    /// The output was encumbered to be received in the transaction and is cancelled
+    Cancel,
+    /// The output is left as is, because its state depends on the chain and not only on the transaction
+    None,
+}
+
+/// An output that references a transaction that the wallet does not have
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrphanedOutput {
+    pub commitment: Commitment,
+    pub status: OutputStatus,
+    /// The transaction that does not exist
+    pub tx_id: TxId,
+    pub repair: OrphanRepair,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    /// Whether the repairs were applied or only detected
+    pub repaired: bool,
+    pub orphaned_outputs: Vec<OrphanedOutput>,
+    /// Completed transactions that no output was received or spent in. These are only reported.
+    pub transactions_without_outputs: Vec<TxId>,
+    /// The key manager indices after they were moved past the keys of the outputs in the database. The indices are
+    /// only checked when repairing.
+    pub restored_key_indices: Vec<RestoredKeyIndex>,
+}
+
+impl IntegrityReport {
+    /// Returns true if no orphaned records were found
+    pub fn is_consistent(&self) -> bool {
+        self.orphaned_outputs.is_empty() && self.transactions_without_outputs.is_empty()
+    }
+}
+
+/// Cross-checks the outputs of the output manager against the transactions of the transaction service
+pub(crate) struct IntegrityChecker<U, V> {
+    transaction_db: TransactionDatabase<U>,
+    output_db: OutputManagerDatabase<V>,
+}
+
+impl<U, V> IntegrityChecker<U, V>
+where
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+{
+    pub fn new(transaction_db: TransactionDatabase<U>, output_db: OutputManagerDatabase<V>) -> Self {
+        Self {
+            transaction_db,
+            output_db,
+        }
+    }
+
+    /// Finds the orphaned records and, if `repair` is true, releases or cancels the outputs that are encumbered to a
+    /// transaction that does not exist. Short term encumbrances are skipped, because their transaction is still being
+    /// negotiated and is only stored once it is complete.
+    pub fn check(&self, repair: bool) -> Result<IntegrityReport, WalletError> {
+        let references = self
+            .output_db
+            .fetch_output_tx_references()
+            .map_err(OutputManagerError::from)?;
+
+        let mut report = IntegrityReport {
+            repaired: repair,
+            ..Default::default()
+        };
+        // Whether each transaction that an output references exists
+        let mut exists = HashMap::new();
+        let mut to_cancel = HashSet::new();
+        for reference in references {
+            let tx_ids = [
+                (reference.received_in_tx_id, OutputStatus::EncumberedToBeReceived),
+                (reference.spent_in_tx_id, OutputStatus::EncumberedToBeSpent),
+            ];
+            for (tx_id, encumbered) in tx_ids {
+                let tx_id = match tx_id {
+                    Some(tx_id) => tx_id,
+                    None => continue,
+                };
+                let tx_exists = match exists.get(&tx_id) {
+                    Some(tx_exists) => *tx_exists,
+                    None => {
+                        let tx_exists = self.transaction_exists(tx_id)?;
+                        exists.insert(tx_id, tx_exists);
+                        tx_exists
+                    },
+                };
+                if tx_exists {
+                    continue;
+                }
+                let repair = match reference.status {
+                    OutputStatus::ShortTermEncumberedToBeReceived | OutputStatus::ShortTermEncumberedToBeSpent => {
+                        continue
+                    },
+                    status if status != encumbered => OrphanRepair::None,
+                    OutputStatus::EncumberedToBeSpent => OrphanRepair::Release,
+                    _ => OrphanRepair::Cancel,
+                };
+                if repair != OrphanRepair::None {
+                    to_cancel.insert(tx_id);
+                }
+                warn!(
+                    target: LOG_TARGET,
+                    "Output {} ({}) references transaction {} which does not exist ({:?})",
+                    reference.commitment.to_hex(),
+                    reference.status,
+                    tx_id,
+                    repair
+                );
+                report.orphaned_outputs.push(OrphanedOutput {
+                    commitment: reference.commitment.clone(),
+                    status: reference.status,
+                    tx_id,
+                    repair,
+                });
+            }
+        }
+
+        if repair {
+            for tx_id in to_cancel {
+                // Releases the outputs that the transaction spends and cancels the outputs that it receives
+                self.output_db
+                    .cancel_pending_transaction_outputs(tx_id)
+                    .map_err(OutputManagerError::from)?;
+            }
+        }
+
+        let completed = self
+            .transaction_db
+            .get_completed_transactions()
+            .map_err(TransactionServiceError::from)?;
+        let mut without_outputs = completed
+            .into_keys()
+            .filter(|tx_id| !exists.contains_key(tx_id))
+            .collect::<Vec<_>>();
+        without_outputs.sort_by_key(|tx_id| tx_id.as_u64());
+        for tx_id in &without_outputs {
+            warn!(
+                target: LOG_TARGET,
+                "Completed transaction {} has no outputs in the output manager", tx_id
+            );
+        }
+        report.transactions_without_outputs = without_outputs;
+
+        Ok(report)
+    }
+
+    fn transaction_exists(&self, tx_id: TxId) -> Result<bool, WalletError> {
+        let tx = self
+            .transaction_db
+            .get_any_transaction(tx_id)
+            .map_err(TransactionServiceError::from)?;
+        Ok(tx.is_some())
+    }
+}
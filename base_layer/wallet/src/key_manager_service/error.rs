@@ -25,7 +25,7 @@ use tari_key_manager::error::KeyManagerError as KMError;
 use tari_script::ScriptError;
 use tari_utilities::{hex::HexError, ByteArrayError};
 
-use crate::error::WalletStorageError;
+use crate::error::{diesel_error_class, ClassifyError, ErrorClass, ErrorKind, SuggestedAction, WalletStorageError};
 /// Error enum for the [KeyManagerService]
 #[derive(Debug, thiserror::Error)]
 pub enum KeyManagerServiceError {
@@ -35,6 +35,8 @@ pub enum KeyManagerServiceError {
     MasterSeedMismatch,
     #[error("Could not find key in key manager")]
     KeyNotFoundInKeyChain,
+    #[error("Key epoch {0} is compromised, no further keys can be derived from it")]
+    KeyEpochCompromised(u64),
     #[error("Storage error: `{0}`")]
     KeyManagerStorageError(#[from] KeyManagerStorageError),
     #[error("Byte array error: `{0}`")]
@@ -82,3 +84,34 @@ pub enum KeyManagerStorageError {
     #[error("Tari Key Manager error: `{0}`")]
     TariKeyManagerError(#[from] KMError),
 }
+
+impl ClassifyError for KeyManagerServiceError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            KeyManagerServiceError::KeyManagerStorageError(e) => e.class(),
+            KeyManagerServiceError::UnknownKeyBranch | KeyManagerServiceError::KeyNotFoundInKeyChain => {
+                ErrorClass::NOT_FOUND
+            },
+            KeyManagerServiceError::MasterSeedMismatch => {
+                ErrorClass::new(ErrorKind::Unauthorized, SuggestedAction::FixRequest)
+            },
+            KeyManagerServiceError::KeyEpochCompromised(_) => ErrorClass::CONFLICT,
+            KeyManagerServiceError::ByteArrayError(_) | KeyManagerServiceError::TariKeyManagerError(_) => {
+                ErrorClass::INVALID_ARGUMENT
+            },
+        }
+    }
+}
+
+impl ClassifyError for KeyManagerStorageError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            KeyManagerStorageError::WalletStorageError(e) => e.class(),
+            KeyManagerStorageError::DieselError(e) => diesel_error_class(e),
+            KeyManagerStorageError::ValueNotFound | KeyManagerStorageError::ValuesNotFound => ErrorClass::NOT_FOUND,
+            KeyManagerStorageError::AlreadyEncrypted => ErrorClass::CONFLICT,
+            KeyManagerStorageError::ValueEncrypted | KeyManagerStorageError::AeadError(_) => ErrorClass::UNAUTHORIZED,
+            _ => ErrorClass::STORAGE,
+        }
+    }
+}
@@ -20,11 +20,17 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use chacha20poly1305::XChaCha20Poly1305;
 use tari_common_types::types::PrivateKey;
-use tari_key_manager::cipher_seed::CipherSeed;
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    mnemonic::{word_completions, MnemonicLanguage},
+};
 use tokio::sync::RwLock;
 
 use crate::key_manager_service::{
@@ -34,6 +40,8 @@ use crate::key_manager_service::{
     AddResult,
     KeyManagerInner,
     KeyManagerInterface,
+    KeyUsage,
+    KeyUsageCallback,
 };
 /// The key manager provides a hierarchical key derivation function (KDF) that derives uniformly random secret keys from
 /// a single seed key for arbitrary branches, using an implementation of `KeyManagerBackend` to store the current index
@@ -43,6 +51,7 @@ use crate::key_manager_service::{
 #[derive(Clone)]
 pub struct KeyManagerHandle<TBackend> {
     key_manager_inner: Arc<RwLock<KeyManagerInner<TBackend>>>,
+    key_usage_callbacks: Arc<HashMap<String, KeyUsageCallback>>,
 }
 
 impl<TBackend> KeyManagerHandle<TBackend>
@@ -54,8 +63,32 @@ where TBackend: KeyManagerBackend + 'static
     pub fn new(master_seed: CipherSeed, db: KeyManagerDatabase<TBackend>) -> Self {
         KeyManagerHandle {
             key_manager_inner: Arc::new(RwLock::new(KeyManagerInner::new(master_seed, db))),
+            key_usage_callbacks: Arc::new(HashMap::new()),
         }
     }
+
+    /// Sets the callbacks that are called with every key derived from their branch with `get_next_key`. The callbacks
+    /// are called after the key manager is unlocked, so they may use the key manager themselves.
+    pub fn with_key_usage_callbacks(mut self, key_usage_callbacks: HashMap<String, KeyUsageCallback>) -> Self {
+        self.key_usage_callbacks = Arc::new(key_usage_callbacks);
+        self
+    }
+
+    /// Returns the seed words of `language` that start with `prefix`, for completing words typed into a recovery UI
+    pub fn seed_word_completions(&self, prefix: &str, language: MnemonicLanguage) -> Vec<String> {
+        word_completions(prefix, language)
+    }
+
+    /// Returns the languages whose word lists contain all of the seed words entered so far
+    pub fn detect_seed_words_language(&self, seed_words: &[String]) -> Vec<MnemonicLanguage> {
+        MnemonicLanguage::detect_candidates(seed_words)
+    }
+
+    /// Checks the words and checksum of a seed phrase before it is used for recovery, returning its language. A wrong
+    /// passphrase is only detected once the wallet is created from the seed phrase.
+    pub fn validate_seed_words(&self, seed_words: &[String]) -> Result<MnemonicLanguage, KeyManagerServiceError> {
+        Ok(CipherSeed::validate_mnemonic(seed_words)?)
+    }
 }
 
 #[async_trait::async_trait]
@@ -77,8 +110,29 @@ where TBackend: KeyManagerBackend + 'static
         (*self.key_manager_inner).write().await.remove_encryption()
     }
 
+    async fn get_key_epoch(&self) -> Result<u64, KeyManagerServiceError> {
+        (*self.key_manager_inner).read().await.get_key_epoch()
+    }
+
+    async fn rotate_key_epoch(&self) -> Result<u64, KeyManagerServiceError> {
+        (*self.key_manager_inner).write().await.rotate_key_epoch()
+    }
+
     async fn get_next_key<T: Into<String> + Send>(&self, branch: T) -> Result<NextKeyResult, KeyManagerServiceError> {
-        (*self.key_manager_inner).read().await.get_next_key(branch.into()).await
+        let branch = branch.into();
+        let result = (*self.key_manager_inner)
+            .read()
+            .await
+            .get_next_key(branch.clone())
+            .await?;
+        if let Some(callback) = self.key_usage_callbacks.get(&branch) {
+            callback(&KeyUsage {
+                branch,
+                index: result.index,
+                public_key: result.to_public_key(),
+            });
+        }
+        Ok(result)
     }
 
     async fn get_key_at_index<T: Into<String> + Send>(
@@ -105,6 +159,19 @@ where TBackend: KeyManagerBackend + 'static
             .await
     }
 
+    async fn find_last_used_key_index<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        used_keys: &HashSet<PrivateKey>,
+        gap_limit: u64,
+    ) -> Result<Option<u64>, KeyManagerServiceError> {
+        (*self.key_manager_inner)
+            .read()
+            .await
+            .find_last_used_key_index(branch.into(), used_keys, gap_limit)
+            .await
+    }
+
     async fn update_current_key_index_if_higher<T: Into<String> + Send>(
         &self,
         branch: T,
@@ -28,12 +28,15 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::collections::HashMap;
+
 use tari_key_manager::cipher_seed::CipherSeed;
 use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
 
 use crate::key_manager_service::{
     storage::database::{KeyManagerBackend, KeyManagerDatabase},
     KeyManagerHandle,
+    KeyUsageCallback,
 };
 
 /// Initializes the key manager service by implementing the [ServiceInitializer] trait.
@@ -42,6 +45,7 @@ where T: KeyManagerBackend
 {
     backend: Option<T>,
     master_seed: CipherSeed,
+    key_usage_callbacks: HashMap<String, KeyUsageCallback>,
 }
 
 impl<T> KeyManagerInitializer<T>
@@ -52,8 +56,15 @@ where T: KeyManagerBackend + 'static
         Self {
             backend: Some(backend),
             master_seed,
+            key_usage_callbacks: HashMap::new(),
         }
     }
+
+    /// Registers callbacks that are called with every key derived from their branch
+    pub fn with_key_usage_callbacks(mut self, key_usage_callbacks: HashMap<String, KeyUsageCallback>) -> Self {
+        self.key_usage_callbacks = key_usage_callbacks;
+        self
+    }
 }
 
 #[async_trait]
@@ -66,7 +77,8 @@ where T: KeyManagerBackend + 'static
             .take()
             .expect("Cannot start Key Manager Service without setting a storage backend");
 
-        let key_manager = KeyManagerHandle::new(self.master_seed.clone(), KeyManagerDatabase::new(backend));
+        let key_manager = KeyManagerHandle::new(self.master_seed.clone(), KeyManagerDatabase::new(backend))
+            .with_key_usage_callbacks(std::mem::take(&mut self.key_usage_callbacks));
         context.register_handle(key_manager);
 
         Ok(())
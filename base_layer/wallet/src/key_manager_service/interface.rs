@@ -20,6 +20,8 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::{collections::HashSet, sync::Arc};
+
 use chacha20poly1305::XChaCha20Poly1305;
 use tari_common_types::types::{PrivateKey, PublicKey};
 use tari_crypto::keys::PublicKey as PublicKeyTrait;
@@ -45,6 +47,17 @@ impl NextKeyResult {
     }
 }
 
+/// A key that was derived from a branch with [KeyManagerInterface::get_next_key]
+#[derive(Debug, Clone)]
+pub struct KeyUsage {
+    pub branch: String,
+    pub index: u64,
+    pub public_key: PublicKey,
+}
+
+/// A callback that is called with every key derived from the branch it is registered for
+pub type KeyUsageCallback = Arc<dyn Fn(&KeyUsage) + Send + Sync>;
+
 /// Behaviour required for the Key manager service
 #[async_trait::async_trait]
 pub trait KeyManagerInterface: Clone + Send + Sync + 'static {
@@ -62,6 +75,14 @@ pub trait KeyManagerInterface: Clone + Send + Sync + 'static {
     /// Decrypts the key manager state using the provided cipher. An error is returned if the state is not encrypted.
     async fn remove_encryption(&self) -> Result<(), KeyManagerServiceError>;
 
+    /// Returns the key epoch that keys are currently derived from. It is 0 until the keys are rotated.
+    async fn get_key_epoch(&self) -> Result<u64, KeyManagerServiceError>;
+
+    /// Marks the current key epoch as compromised and starts a new epoch, which is returned. Every branch is
+    /// restarted at index 0 with keys derived from the new epoch, and no further keys are derived from the
+    /// compromised epoch.
+    async fn rotate_key_epoch(&self) -> Result<u64, KeyManagerServiceError>;
+
     /// Gets the next key from the branch. This will auto-increment the branch key index by 1
     async fn get_next_key<T: Into<String> + Send>(&self, branch: T) -> Result<NextKeyResult, KeyManagerServiceError>;
 
@@ -79,6 +100,16 @@ pub trait KeyManagerInterface: Clone + Send + Sync + 'static {
         key: &PrivateKey,
     ) -> Result<u64, KeyManagerServiceError>;
 
+    /// Walks the branch key chain forward from index 0 and returns the highest index whose key is one of `used_keys`.
+    /// The walk stops once `gap_limit` consecutive keys have been derived without finding a used key, or once all
+    /// the used keys have been found. Returns `None` if none of the used keys belong to the searched range.
+    async fn find_last_used_key_index<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        used_keys: &HashSet<PrivateKey>,
+        gap_limit: u64,
+    ) -> Result<Option<u64>, KeyManagerServiceError>;
+
     /// Will update the index of the branch if the index given is higher than the current saved index
     async fn update_current_key_index_if_higher<T: Into<String> + Send>(
         &self,
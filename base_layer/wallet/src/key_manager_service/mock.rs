@@ -27,13 +27,16 @@ use tari_key_manager::{cipher_seed::CipherSeed, key_manager::KeyManager};
 use tokio::sync::RwLock;
 
 use crate::{
-    key_manager_service::{interface::NextKeyResult, AddResult, KeyManagerInterface},
+    key_manager_service::{epoch_branch_seed, interface::NextKeyResult, AddResult, KeyManagerInterface},
     types::KeyDigest,
 };
 
 const LOG_TARGET: &str = "wallet::Key_manager_mock";
 const KEY_MANAGER_MAX_SEARCH_DEPTH: u64 = 1_000_000;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use crate::key_manager_service::{error::KeyManagerServiceError, storage::database::KeyManagerState};
 
@@ -43,6 +46,7 @@ use crate::key_manager_service::{error::KeyManagerServiceError, storage::databas
 pub struct KeyManagerMock {
     key_managers: Arc<RwLock<HashMap<String, KeyManager<PrivateKey, KeyDigest>>>>,
     master_seed: CipherSeed,
+    epoch: Arc<RwLock<u64>>,
 }
 
 impl KeyManagerMock {
@@ -51,6 +55,7 @@ impl KeyManagerMock {
         KeyManagerMock {
             key_managers: Arc::new(RwLock::new(HashMap::new())),
             master_seed,
+            epoch: Arc::new(RwLock::new(0)),
         }
     }
 }
@@ -64,7 +69,7 @@ impl KeyManagerMock {
             AddResult::NewEntry
         };
         let state = KeyManagerState {
-            branch_seed: branch.to_string(),
+            branch_seed: epoch_branch_seed(&branch, *self.epoch.read().await),
             primary_key_index: 0,
         };
 
@@ -79,6 +84,20 @@ impl KeyManagerMock {
         Ok(result)
     }
 
+    /// Starts a new key epoch and restarts every branch at index 0 in it
+    pub async fn rotate_key_epoch_mock(&self) -> Result<u64, KeyManagerServiceError> {
+        let epoch = {
+            let mut lock = self.epoch.write().await;
+            *lock += 1;
+            *lock
+        };
+        let branches = self.key_managers.read().await.keys().cloned().collect::<Vec<_>>();
+        for branch in branches {
+            self.add_key_manager_mock(branch).await?;
+        }
+        Ok(epoch)
+    }
+
     /// Gets the next key in the branch and increments the index
     pub async fn get_next_key_mock(&self, branch: String) -> Result<NextKeyResult, KeyManagerServiceError> {
         let mut lock = self.key_managers.write().await;
@@ -119,6 +138,35 @@ impl KeyManagerMock {
         Err(KeyManagerServiceError::KeyNotFoundInKeyChain)
     }
 
+    /// Walk the specified branch key manager key chain to find the highest index of a used key, see
+    /// [KeyManagerInterface::find_last_used_key_index].
+    pub async fn find_last_used_key_index_mock(
+        &self,
+        branch: String,
+        used_keys: &HashSet<PrivateKey>,
+        gap_limit: u64,
+    ) -> Result<Option<u64>, KeyManagerServiceError> {
+        let lock = self.key_managers.read().await;
+        let km = lock.get(&branch).ok_or(KeyManagerServiceError::UnknownKeyBranch)?;
+
+        let mut last_used_index = None;
+        let mut num_found = 0usize;
+        let mut gap = 0u64;
+        let mut index = 0u64;
+        while gap < gap_limit && num_found < used_keys.len() {
+            if used_keys.contains(&km.derive_key(index)?.k) {
+                last_used_index = Some(index);
+                num_found += 1;
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+            index += 1;
+        }
+
+        Ok(last_used_index)
+    }
+
     /// If the supplied index is higher than the current UTXO key chain indices then they will be updated.
     pub async fn update_current_key_index_if_higher_mock(
         &self,
@@ -142,6 +190,14 @@ impl KeyManagerInterface for KeyManagerMock {
         self.add_key_manager_mock(branch.into()).await
     }
 
+    async fn get_key_epoch(&self) -> Result<u64, KeyManagerServiceError> {
+        Ok(*self.epoch.read().await)
+    }
+
+    async fn rotate_key_epoch(&self) -> Result<u64, KeyManagerServiceError> {
+        self.rotate_key_epoch_mock().await
+    }
+
     async fn get_next_key<T: Into<String> + Send>(&self, branch: T) -> Result<NextKeyResult, KeyManagerServiceError> {
         self.get_next_key_mock(branch.into()).await
     }
@@ -170,6 +226,16 @@ impl KeyManagerInterface for KeyManagerMock {
         self.find_key_index_mock(branch.into(), key).await
     }
 
+    async fn find_last_used_key_index<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        used_keys: &HashSet<PrivateKey>,
+        gap_limit: u64,
+    ) -> Result<Option<u64>, KeyManagerServiceError> {
+        self.find_last_used_key_index_mock(branch.into(), used_keys, gap_limit)
+            .await
+    }
+
     async fn update_current_key_index_if_higher<T: Into<String> + Send>(
         &self,
         branch: T,
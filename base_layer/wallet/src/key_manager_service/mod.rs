@@ -30,7 +30,7 @@ mod initializer;
 pub use initializer::KeyManagerInitializer;
 
 mod service;
-pub use service::KeyManagerInner;
+pub use service::{epoch_branch_seed, KeyManagerInner};
 
 mod mock;
 pub use mock::KeyManagerMock;
@@ -40,4 +40,4 @@ mod test;
 mod interface;
 pub mod storage;
 
-pub use interface::{AddResult, KeyManagerInterface, NextKeyResult};
+pub use interface::{AddResult, KeyManagerInterface, KeyUsage, KeyUsageCallback, NextKeyResult};
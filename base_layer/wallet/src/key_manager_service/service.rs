@@ -30,7 +30,7 @@ use crate::types::KeyDigest;
 const LOG_TARGET: &str = "wallet::key_manager";
 const KEY_MANAGER_MAX_SEARCH_DEPTH: u64 = 1_000_000;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::key_manager_service::{
     error::KeyManagerServiceError,
@@ -39,10 +39,21 @@ use crate::key_manager_service::{
     AddResult,
 };
 
+/// Returns the branch seed that the keys of `branch` are derived from in a key epoch. Epoch 0 uses the branch itself,
+/// so the keys of a wallet whose keys were never rotated are unchanged.
+pub fn epoch_branch_seed(branch: &str, epoch: u64) -> String {
+    if epoch == 0 {
+        branch.to_string()
+    } else {
+        format!("{}#epoch{}", branch, epoch)
+    }
+}
+
 pub struct KeyManagerInner<TBackend> {
     key_managers: HashMap<String, Mutex<KeyManager<PrivateKey, KeyDigest>>>,
     db: KeyManagerDatabase<TBackend>,
     master_seed: CipherSeed,
+    epoch: u64,
 }
 
 impl<TBackend> KeyManagerInner<TBackend>
@@ -53,6 +64,7 @@ where TBackend: KeyManagerBackend + 'static
             key_managers: HashMap::new(),
             db,
             master_seed,
+            epoch: 0,
         }
     }
 
@@ -62,10 +74,12 @@ where TBackend: KeyManagerBackend + 'static
         } else {
             AddResult::NewEntry
         };
-        let state = match self.db.get_key_manager_state(branch.clone())? {
+        self.epoch = self.db.get_key_epoch()?;
+        let branch_seed = epoch_branch_seed(&branch, self.epoch);
+        let state = match self.db.get_key_manager_state(branch_seed.clone())? {
             None => {
                 let starting_state = KeyManagerState {
-                    branch_seed: branch.to_string(),
+                    branch_seed,
                     primary_key_index: 0,
                 };
                 self.db.set_key_manager_state(starting_state.clone())?;
@@ -85,6 +99,10 @@ where TBackend: KeyManagerBackend + 'static
     }
 
     pub async fn get_next_key(&self, branch: String) -> Result<NextKeyResult, KeyManagerServiceError> {
+        // Another instance sharing the database could have rotated the keys since the branches were loaded
+        if self.db.is_key_epoch_compromised(self.epoch)? {
+            return Err(KeyManagerServiceError::KeyEpochCompromised(self.epoch));
+        }
         let mut km = self
             .key_managers
             .get(&branch)
@@ -92,7 +110,7 @@ where TBackend: KeyManagerBackend + 'static
             .lock()
             .await;
         let key = km.next_key()?;
-        self.db.increment_key_index(branch)?;
+        self.db.increment_key_index(epoch_branch_seed(&branch, self.epoch))?;
         Ok(NextKeyResult {
             key: key.k,
             index: km.key_index(),
@@ -110,6 +128,22 @@ where TBackend: KeyManagerBackend + 'static
         Ok(key.k)
     }
 
+    pub fn get_key_epoch(&self) -> Result<u64, KeyManagerServiceError> {
+        Ok(self.db.get_key_epoch()?)
+    }
+
+    /// Marks the current key epoch as compromised and reloads every tracked branch in a new epoch, starting at index 0.
+    /// Keys of the compromised epoch can no longer be derived through the key manager.
+    pub fn rotate_key_epoch(&mut self) -> Result<u64, KeyManagerServiceError> {
+        let epoch = self.db.rotate_key_epoch()?;
+        let branches = self.key_managers.drain().map(|(branch, _)| branch).collect::<Vec<_>>();
+        for branch in branches {
+            self.add_key_manager_branch(branch)?;
+        }
+        info!(target: LOG_TARGET, "Rotated the key manager to key epoch {}", epoch);
+        Ok(epoch)
+    }
+
     pub fn apply_encryption(&self, cipher: XChaCha20Poly1305) -> Result<(), KeyManagerServiceError> {
         self.db.apply_encryption(cipher)?;
         Ok(())
@@ -141,6 +175,46 @@ where TBackend: KeyManagerBackend + 'static
         Err(KeyManagerServiceError::KeyNotFoundInKeyChain)
     }
 
+    /// Walk the specified branch key manager key chain forward from index 0 and return the highest index whose key is
+    /// one of the `used_keys`. The walk ends once `gap_limit` consecutive unused keys have been derived or all of the
+    /// used keys have been found.
+    pub async fn find_last_used_key_index(
+        &self,
+        branch: String,
+        used_keys: &HashSet<PrivateKey>,
+        gap_limit: u64,
+    ) -> Result<Option<u64>, KeyManagerServiceError> {
+        let km = self
+            .key_managers
+            .get(&branch)
+            .ok_or(KeyManagerServiceError::UnknownKeyBranch)?
+            .lock()
+            .await;
+
+        let mut last_used_index = None;
+        let mut num_found = 0usize;
+        let mut gap = 0u64;
+        let mut index = 0u64;
+        while gap < gap_limit && num_found < used_keys.len() {
+            if used_keys.contains(&km.derive_key(index)?.k) {
+                trace!(
+                    target: LOG_TARGET,
+                    "Used key found in {} Key Chain at index {}",
+                    branch,
+                    index
+                );
+                last_used_index = Some(index);
+                num_found += 1;
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+            index += 1;
+        }
+
+        Ok(last_used_index)
+    }
+
     /// If the supplied index is higher than the current UTXO key chain indices then they will be updated.
     pub async fn update_current_key_index_if_higher(
         &self,
@@ -156,7 +230,7 @@ where TBackend: KeyManagerBackend + 'static
         let current_index = km.key_index();
         if index > current_index {
             km.update_key_index(index);
-            self.db.set_key_index(branch, index)?;
+            self.db.set_key_index(epoch_branch_seed(&branch, self.epoch), index)?;
             trace!(target: LOG_TARGET, "Updated UTXO Key Index to {}", index);
         }
         Ok(())
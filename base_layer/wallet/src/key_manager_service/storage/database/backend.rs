@@ -34,6 +34,12 @@ pub trait KeyManagerBackend: Send + Sync + Clone {
     fn increment_key_index(&self, branch: String) -> Result<(), KeyManagerStorageError>;
     /// This method will set the currently stored key index for the key manager.
     fn set_key_index(&self, branch: String, index: u64) -> Result<(), KeyManagerStorageError>;
+    /// Returns the latest key epoch, the epoch that keys are currently derived from.
+    fn get_key_epoch(&self) -> Result<u64, KeyManagerStorageError>;
+    /// Marks the latest key epoch as compromised and starts a new epoch, which is returned.
+    fn rotate_key_epoch(&self) -> Result<u64, KeyManagerStorageError>;
+    /// Returns true if the key epoch was marked as compromised by a rotation.
+    fn is_key_epoch_compromised(&self, epoch: u64) -> Result<bool, KeyManagerStorageError>;
     /// Apply encryption to the backend.
     fn apply_encryption(&self, cipher: XChaCha20Poly1305) -> Result<(), KeyManagerStorageError>;
     /// Remove encryption from the backend.
@@ -73,6 +73,21 @@ where T: KeyManagerBackend + 'static
         self.db.set_key_index(branch, index)
     }
 
+    /// Returns the key epoch that the keys of all branches are currently derived from.
+    pub fn get_key_epoch(&self) -> Result<u64, KeyManagerStorageError> {
+        self.db.get_key_epoch()
+    }
+
+    /// Marks the current key epoch as compromised and starts the next one.
+    pub fn rotate_key_epoch(&self) -> Result<u64, KeyManagerStorageError> {
+        self.db.rotate_key_epoch()
+    }
+
+    /// Returns true if the keys were rotated away from the provided epoch.
+    pub fn is_key_epoch_compromised(&self, epoch: u64) -> Result<bool, KeyManagerStorageError> {
+        self.db.is_key_epoch_compromised(epoch)
+    }
+
     /// Encrypts the entire key manager with all branches.
     /// This will only encrypt the index used, as the master seed phrase is not directly stored with the key manager.
     pub fn apply_encryption(&self, cipher: XChaCha20Poly1305) -> Result<(), KeyManagerStorageError> {
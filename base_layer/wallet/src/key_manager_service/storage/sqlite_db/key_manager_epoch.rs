@@ -0,0 +1,70 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::{prelude::*, SqliteConnection};
+
+use crate::{key_manager_service::error::KeyManagerStorageError, schema::key_manager_epochs};
+
+/// Represents a row in the key_manager_epochs table.
+#[derive(Clone, Debug, Queryable, Insertable, Identifiable)]
+#[table_name = "key_manager_epochs"]
+#[primary_key(epoch)]
+pub struct KeyManagerEpochSql {
+    pub epoch: i64,
+    pub created_at: NaiveDateTime,
+    pub compromised_at: Option<NaiveDateTime>,
+}
+
+impl KeyManagerEpochSql {
+    /// Returns the latest key epoch, which is 0 for a wallet whose keys were never rotated
+    pub fn current(conn: &SqliteConnection) -> Result<u64, KeyManagerStorageError> {
+        let epoch = key_manager_epochs::table
+            .select(diesel::dsl::max(key_manager_epochs::epoch))
+            .first::<Option<i64>>(conn)?
+            .unwrap_or(0);
+        Ok(epoch as u64)
+    }
+
+    /// Marks the latest key epoch as compromised and starts the next one, returning the new epoch
+    pub fn rotate(conn: &SqliteConnection) -> Result<u64, KeyManagerStorageError> {
+        conn.transaction::<_, KeyManagerStorageError, _>(|| {
+            let now = Utc::now().naive_utc();
+            let current = Self::current(conn)?;
+            let updated =
+                diesel::update(key_manager_epochs::table.filter(key_manager_epochs::epoch.eq(current as i64)))
+                    .set(key_manager_epochs::compromised_at.eq(Some(now)))
+                    .execute(conn)?;
+            // Epoch 0 only gets a row once it is rotated away from
+            if updated == 0 {
+                diesel::insert_into(key_manager_epochs::table)
+                    .values(KeyManagerEpochSql {
+                        epoch: current as i64,
+                        created_at: now,
+                        compromised_at: Some(now),
+                    })
+                    .execute(conn)?;
+            }
+
+            let next = current + 1;
+            diesel::insert_into(key_manager_epochs::table)
+                .values(KeyManagerEpochSql {
+                    epoch: next as i64,
+                    created_at: now,
+                    compromised_at: None,
+                })
+                .execute(conn)?;
+            Ok(next)
+        })
+    }
+
+    /// Returns true if the keys were rotated away from the epoch
+    pub fn is_compromised(epoch: u64, conn: &SqliteConnection) -> Result<bool, KeyManagerStorageError> {
+        let compromised_at = key_manager_epochs::table
+            .filter(key_manager_epochs::epoch.eq(epoch as i64))
+            .select(key_manager_epochs::compromised_at)
+            .first::<Option<NaiveDateTime>>(conn)
+            .optional()?;
+        Ok(matches!(compromised_at, Some(Some(_))))
+    }
+}
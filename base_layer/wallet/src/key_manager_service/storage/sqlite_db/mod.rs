@@ -26,6 +26,7 @@ use std::{
 };
 
 use chacha20poly1305::XChaCha20Poly1305;
+pub use key_manager_epoch::KeyManagerEpochSql;
 pub use key_manager_state::{KeyManagerStateSql, NewKeyManagerStateSql};
 use log::*;
 use tokio::time::Instant;
@@ -39,6 +40,7 @@ use crate::{
     util::encryption::Encryptable,
 };
 
+mod key_manager_epoch;
 mod key_manager_state;
 
 const LOG_TARGET: &str = "wallet::key_manager_service::database::wallet";
@@ -178,6 +180,34 @@ impl KeyManagerBackend for KeyManagerSqliteDatabase {
         Ok(())
     }
 
+    fn get_key_epoch(&self) -> Result<u64, KeyManagerStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        KeyManagerEpochSql::current(&conn)
+    }
+
+    fn rotate_key_epoch(&self) -> Result<u64, KeyManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let epoch = KeyManagerEpochSql::rotate(&conn)?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - rotate_key_epoch: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+
+        Ok(epoch)
+    }
+
+    fn is_key_epoch_compromised(&self, epoch: u64) -> Result<bool, KeyManagerStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        KeyManagerEpochSql::is_compromised(epoch, &conn)
+    }
+
     fn apply_encryption(&self, cipher: XChaCha20Poly1305) -> Result<(), KeyManagerStorageError> {
         let mut current_cipher = acquire_write_lock!(self.cipher);
 
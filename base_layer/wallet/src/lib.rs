@@ -10,17 +10,38 @@
 #[macro_use]
 mod macros;
 pub mod base_node_service;
+pub mod cloud_sync;
+pub mod command_executor;
 pub mod connectivity_service;
 pub mod contacts_service;
+pub mod decoy_service;
+pub mod diagnostics;
+pub mod digest_service;
 pub mod error;
+pub mod extensions;
+pub mod header_sync;
+pub mod health_check;
+pub mod inheritance_service;
+pub mod integrity;
+pub mod network_state;
 mod operation_id;
 pub mod output_manager_service;
+pub mod platform_keystore;
+pub mod portable_dump;
+pub mod proof_of_reserves;
+pub mod provisioning;
+pub mod read_only;
+pub mod remote_signer;
+pub mod search;
+#[cfg(feature = "simulation")]
+pub mod simulation;
 pub mod storage;
 pub mod test_utils;
 pub mod transaction_service;
 pub mod types;
 pub mod util;
 pub mod wallet;
+pub mod wallet_manager;
 
 pub use operation_id::OperationId;
 use tari_crypto::{hash::blake2::Blake256, hash_domain, hashing::DomainSeparatedHasher};
@@ -36,9 +57,12 @@ pub mod schema;
 pub mod utxo_scanner_service;
 
 pub use config::{TransactionStage, WalletConfig};
-pub use wallet::Wallet;
+pub use extensions::WalletExtensions;
+pub use wallet::{ConfigReload, ConsensusParameters, ServiceKind, Wallet};
+pub use wallet_manager::WalletManager;
 
 use crate::{
+    command_executor::CommandExecutor,
     contacts_service::storage::sqlite_db::ContactsServiceSqliteDatabase,
     key_manager_service::storage::sqlite_db::KeyManagerSqliteDatabase,
     output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
@@ -54,6 +78,22 @@ pub type WalletSqlite = Wallet<
     KeyManagerSqliteDatabase,
 >;
 
+pub type CommandExecutorSqlite = CommandExecutor<
+    WalletSqliteDatabase,
+    TransactionServiceSqliteDatabase,
+    OutputManagerSqliteDatabase,
+    ContactsServiceSqliteDatabase,
+    KeyManagerSqliteDatabase,
+>;
+
+pub type WalletManagerSqlite = WalletManager<
+    WalletSqliteDatabase,
+    TransactionServiceSqliteDatabase,
+    OutputManagerSqliteDatabase,
+    ContactsServiceSqliteDatabase,
+    KeyManagerSqliteDatabase,
+>;
+
 hash_domain!(
     WalletSecretKeysDomain,
     "com.tari.tari_project.base_layer.wallet.secret_keys",
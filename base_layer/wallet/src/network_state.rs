@@ -0,0 +1,95 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The network state of a wallet, as exported by [Wallet::export_network_state](crate::Wallet::export_network_state).
+//! Importing it on another device lets the wallet reuse its identity and known peers instead of rediscovering the
+//! network from scratch.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use tari_comms::{
+    multiaddr::Multiaddr,
+    peer_manager::{IdentitySignature, Peer, PeerFeatures},
+    tor::TorIdentity,
+    types::CommsPublicKey,
+};
+
+use crate::error::WalletError;
+
+/// The version of the network state file format
+const NETWORK_STATE_VERSION: u32 = 1;
+
+/// The node identity, Tor identity and known peers of a wallet. The comms secret key is not included because it is
+/// derived from the wallet's master seed, but the Tor identity contains the private key of the onion service, so the
+/// exported file must be kept private.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkState {
+    version: u32,
+    pub public_key: CommsPublicKey,
+    pub public_address: Multiaddr,
+    pub features: PeerFeatures,
+    pub identity_signature: Option<IdentitySignature>,
+    pub tor_identity: Option<TorIdentity>,
+    pub peers: Vec<Peer>,
+}
+
+impl NetworkState {
+    pub fn new(
+        public_key: CommsPublicKey,
+        public_address: Multiaddr,
+        features: PeerFeatures,
+        identity_signature: Option<IdentitySignature>,
+        tor_identity: Option<TorIdentity>,
+        peers: Vec<Peer>,
+    ) -> Self {
+        Self {
+            version: NETWORK_STATE_VERSION,
+            public_key,
+            public_address,
+            features,
+            identity_signature,
+            tor_identity,
+            peers,
+        }
+    }
+
+    /// Writes the network state to `path` as JSON. On unix, the file is only readable by its owner.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletError> {
+        let json = serde_json::to_vec_pretty(self).map_err(|e| WalletError::NetworkStateError(e.to_string()))?;
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(path.as_ref(), json).map_err(io_error)?;
+        set_owner_only_permissions(path.as_ref()).map_err(io_error)?;
+        Ok(())
+    }
+
+    /// Reads a network state written by [NetworkState::write_to_file]
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, WalletError> {
+        let json = fs::read(path.as_ref()).map_err(io_error)?;
+        let state = serde_json::from_slice::<Self>(&json).map_err(|e| WalletError::NetworkStateError(e.to_string()))?;
+        if state.version != NETWORK_STATE_VERSION {
+            return Err(WalletError::NetworkStateError(format!(
+                "Unsupported network state version {}",
+                state.version
+            )));
+        }
+        Ok(state)
+    }
+}
+
+fn io_error(err: io::Error) -> WalletError {
+    WalletError::NetworkStateError(err.to_string())
+}
+
+#[cfg(target_family = "unix")]
+pub(crate) fn set_owner_only_permissions(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(target_family = "unix"))]
+pub(crate) fn set_owner_only_permissions(_: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -20,7 +20,13 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+use tari_common_types::types::PublicKey;
+
+use crate::util::retry::{Backoff, RetryPolicy};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -41,6 +47,29 @@ pub struct OutputManagerServiceConfig {
     /// If set to `true`, then outputs received via simple one-sided transactions, won't be automatically selected as
     /// inputs for further transactions, but can still be selected individually as specific outputs.
     pub autoignore_onesided_utxos: bool,
+    /// If set, the change of outgoing transactions is sent to a new stealth address derived from this public key of a
+    /// cold storage wallet, instead of being kept by this wallet. Such change outputs are tracked but never selected
+    /// as inputs or counted in the balance.
+    pub cold_storage_public_key: Option<PublicKey>,
+    /// The maximum number of inputs in each transaction of a sweep. Sweeps of more outputs are split into several
+    /// transactions. The limit is also capped so that each transaction fits in a block. An inheritance transaction is
+    /// not created when the wallet has more spendable outputs than this.
+    pub max_inputs_per_sweep_transaction: usize,
+    /// The subscribers of balance changes are notified at most once per interval, so that a burst of changes results
+    /// in a single update
+    #[serde(with = "serializers::seconds")]
+    pub balance_refresh_interval: Duration,
+    /// The balance is read from running totals that are updated as the outputs change. They are recounted over all the
+    /// outputs once per interval, and corrected if they drifted.
+    #[serde(with = "serializers::seconds")]
+    pub balance_recount_interval: Duration,
+    /// The number of blocks the chain has to grow past the height of a coinbase that is not in the chain before the
+    /// coinbase is marked as abandoned, because its block was never mined or was reorged out
+    pub coinbase_abandon_delay: u64,
+    /// How a TXO validation that failed because the base node could not be reached is retried
+    pub validation_retry: RetryPolicy,
+    /// How the requests of the output manager to the base node, e.g. for the output of an atomic swap, are retried
+    pub base_node_rpc_retry: RetryPolicy,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -51,6 +80,25 @@ impl Default for OutputManagerServiceConfig {
             num_confirmations_required: 3,
             tx_validator_batch_size: 100,
             autoignore_onesided_utxos: false,
+            cold_storage_public_key: None,
+            max_inputs_per_sweep_transaction: 500,
+            balance_refresh_interval: Duration::from_secs(1),
+            balance_recount_interval: Duration::from_secs(60 * 60),
+            coinbase_abandon_delay: 3,
+            validation_retry: RetryPolicy::new(
+                Some(3),
+                Duration::from_secs(10),
+                Backoff::Exponential,
+                Duration::from_secs(120),
+            )
+            .with_jitter(0.2),
+            base_node_rpc_retry: RetryPolicy::new(
+                Some(3),
+                Duration::from_secs(1),
+                Backoff::Exponential,
+                Duration::from_secs(10),
+            )
+            .with_jitter(0.2),
         }
     }
 }
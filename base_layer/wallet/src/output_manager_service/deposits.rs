@@ -0,0 +1,76 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Receive-only deposit identities for custodial wallets, e.g. one for each user of an exchange. Every identity is a
+//! key derived from the seed of the wallet, so thousands of them cost no more than a row in the database each. Payments
+//! are made to an identity as simple one-sided payments to its public key. The outputs that they create are pooled
+//! with the rest of the outputs of the wallet, so they are spent like any other output, but each is attributed to the
+//! identity that received it and published as a [Deposit].
+
+use chrono::NaiveDateTime;
+use tari_common_types::{
+    transaction::TxId,
+    types::{Commitment, PublicKey},
+};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_script::{Opcode, TariScript};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::output_manager_service::handle::{OutputManagerEvent, OutputManagerEventReceiver};
+
+/// A receive-only identity that the wallet derived for a user of a custodian
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositIdentity {
+    /// The index of the key of the identity in the deposit identity branch of the key manager
+    pub key_index: u64,
+    /// The user or account that the identity belongs to, unique in the wallet
+    pub label: String,
+    /// The public key that payments to the identity are sent to
+    pub public_key: PublicKey,
+    pub created_at: NaiveDateTime,
+}
+
+/// An output received by a deposit identity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deposit {
+    /// The key index of the identity that received the output
+    pub key_index: u64,
+    pub commitment: Commitment,
+    pub value: MicroTari,
+    /// The transaction that the output was imported with
+    pub tx_id: TxId,
+    pub received_at: NaiveDateTime,
+}
+
+/// Returns the public key that a simple one-sided payment was made to, which is the public key of the deposit
+/// identity that receives it
+pub(crate) fn one_sided_payment_key(script: &TariScript) -> Option<&PublicKey> {
+    match script.as_slice() {
+        [Opcode::PushPubKey(public_key)] => Some(public_key.as_ref()),
+        _ => None,
+    }
+}
+
+/// The deposits of one identity, filtered from the event stream of the output manager
+pub struct DepositEventReceiver {
+    key_index: u64,
+    receiver: OutputManagerEventReceiver,
+}
+
+impl DepositEventReceiver {
+    pub(crate) fn new(key_index: u64, receiver: OutputManagerEventReceiver) -> Self {
+        Self { key_index, receiver }
+    }
+
+    /// Waits for the next deposit to the identity. Fails with [RecvError::Lagged] if the receiver fell behind and
+    /// missed events, in which case the deposits stored for the identity have to be fetched to catch up.
+    pub async fn recv(&mut self) -> Result<Deposit, RecvError> {
+        loop {
+            if let OutputManagerEvent::DepositReceived(deposit) = &*self.receiver.recv().await? {
+                if deposit.key_index == self.key_index {
+                    return Ok(deposit.clone());
+                }
+            }
+        }
+    }
+}
@@ -22,9 +22,11 @@
 
 use diesel::result::Error as DieselError;
 use tari_common::exit_codes::{ExitCode, ExitError};
+use tari_common_types::types::FixedHash;
 use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
+    tari_amount::MicroTari,
     transaction_components::{EncryptionError, TransactionError},
     transaction_protocol::TransactionProtocolError,
     CoinbaseBuildError,
@@ -37,7 +39,7 @@ use thiserror::Error;
 
 use crate::{
     base_node_service::error::BaseNodeServiceError,
-    error::WalletStorageError,
+    error::{diesel_error_class, ClassifyError, ErrorClass, ErrorKind, SuggestedAction, WalletStorageError},
     key_manager_service::KeyManagerServiceError,
     output_manager_service::UtxoSelectionCriteria,
 };
@@ -100,6 +102,8 @@ pub enum OutputManagerError {
     CoinbaseBuildError(#[from] CoinbaseBuildError),
     #[error("TXO Validation protocol cancelled")]
     Cancellation,
+    #[error("The preparation of the transaction was cancelled")]
+    TransactionPreparationCancelled,
     #[error("Base NodeService Error: `{0}`")]
     BaseNodeServiceError(#[from] BaseNodeServiceError),
     #[error("Shutdown Signal Received")]
@@ -133,6 +137,23 @@ pub enum OutputManagerError {
     NoCommitmentsProvided,
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("The output is not a vault output")]
+    NotAVaultOutput,
+    #[error("The vault can only be recovered from height {recovery_height}")]
+    VaultNotYetRecoverable { recovery_height: u64 },
+    #[error("The recovery key does not match the vault")]
+    InvalidVaultRecoveryKey,
+    #[error("The wallet does not hold the unique asset {0}")]
+    UniqueAssetNotFound(FixedHash),
+    #[error(
+        "A transaction can spend at most {max_inputs} outputs, which leaves {uncovered} in {excluded_outputs} more \
+         output(s) uncovered"
+    )]
+    TooManyInputs {
+        max_inputs: usize,
+        excluded_outputs: usize,
+        uncovered: MicroTari,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -194,6 +215,78 @@ impl From<OutputManagerError> for ExitError {
     }
 }
 
+impl ClassifyError for OutputManagerError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            OutputManagerError::OutputManagerStorageError(e) => e.class(),
+            OutputManagerError::BaseNodeServiceError(e) => e.class(),
+            OutputManagerError::KeyManagerServiceError(e) => e.class(),
+            OutputManagerError::NotEnoughFunds | OutputManagerError::NoUtxosSelected { .. } => {
+                ErrorClass::new(ErrorKind::InsufficientFunds, SuggestedAction::FixRequest)
+            },
+            OutputManagerError::FundsPending => {
+                ErrorClass::new(ErrorKind::InsufficientFunds, SuggestedAction::WaitForFunds)
+            },
+            OutputManagerError::DuplicateOutput => ErrorClass::CONFLICT,
+            OutputManagerError::UniqueAssetNotFound(_) | OutputManagerError::KeyNotFoundInKeyChain => {
+                ErrorClass::NOT_FOUND
+            },
+            OutputManagerError::BuildError(_) |
+            OutputManagerError::ConversionError(_) |
+            OutputManagerError::InvalidArgument(_) |
+            OutputManagerError::InvalidConfig |
+            OutputManagerError::InvalidScriptHash |
+            OutputManagerError::NoCommitmentsProvided |
+            OutputManagerError::NotAVaultOutput |
+            OutputManagerError::InvalidVaultRecoveryKey => ErrorClass::INVALID_ARGUMENT,
+            OutputManagerError::TransactionPreparationCancelled | OutputManagerError::TooManyInputs { .. } => {
+                ErrorClass::new(ErrorKind::Rejected, SuggestedAction::FixRequest)
+            },
+            OutputManagerError::VaultNotYetRecoverable { .. } => {
+                ErrorClass::new(ErrorKind::Rejected, SuggestedAction::Retry)
+            },
+            OutputManagerError::MasterSeedMismatch => {
+                ErrorClass::new(ErrorKind::Unauthorized, SuggestedAction::FixRequest)
+            },
+            OutputManagerError::NoBaseNodeKeysProvided => {
+                ErrorClass::new(ErrorKind::Connectivity, SuggestedAction::FixRequest)
+            },
+            OutputManagerError::BaseNodeNotSynced |
+            OutputManagerError::RpcError(_) |
+            OutputManagerError::ConnectivityError { .. } |
+            OutputManagerError::DhtOutboundError(_) |
+            OutputManagerError::MaximumAttemptsExceeded => ErrorClass::CONNECTIVITY,
+            OutputManagerError::TransportChannelError(_) |
+            OutputManagerError::ApiSendFailed |
+            OutputManagerError::ApiReceiveFailed |
+            OutputManagerError::Cancellation => ErrorClass::UNAVAILABLE,
+            OutputManagerError::Shutdown => ErrorClass::SHUTDOWN,
+            _ => ErrorClass::INTERNAL,
+        }
+    }
+}
+
+impl ClassifyError for OutputManagerStorageError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            OutputManagerStorageError::DieselR2d2Error(e) => e.class(),
+            OutputManagerStorageError::DieselError(e) => diesel_error_class(e),
+            OutputManagerStorageError::KeyManagerServiceError(e) => e.class(),
+            OutputManagerStorageError::ValueNotFound |
+            OutputManagerStorageError::ValuesNotFound |
+            OutputManagerStorageError::PendingTransactionNotFound => ErrorClass::NOT_FOUND,
+            OutputManagerStorageError::DuplicateOutput |
+            OutputManagerStorageError::DuplicateTransaction |
+            OutputManagerStorageError::DuplicateScript |
+            OutputManagerStorageError::OutputAlreadySpent |
+            OutputManagerStorageError::OutputAlreadyEncumbered |
+            OutputManagerStorageError::AlreadyEncrypted => ErrorClass::CONFLICT,
+            OutputManagerStorageError::AeadError(_) => ErrorClass::UNAUTHORIZED,
+            _ => ErrorClass::STORAGE,
+        }
+    }
+}
+
 /// This error type is used to return OutputManagerError from inside a Output Manager Service protocol but also
 /// include the ID of the protocol
 #[derive(Debug)]
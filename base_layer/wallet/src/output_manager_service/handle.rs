@@ -25,7 +25,7 @@ use std::{fmt, fmt::Formatter, sync::Arc};
 use chacha20poly1305::XChaCha20Poly1305;
 use tari_common_types::{
     transaction::TxId,
-    types::{Commitment, HashOutput, PublicKey},
+    types::{Commitment, FixedHash, HashOutput, PrivateKey, PublicKey},
 };
 use tari_core::{
     covenants::Covenant,
@@ -33,6 +33,7 @@ use tari_core::{
         tari_amount::MicroTari,
         transaction_components::{
             OutputFeatures,
+            RewoundOutput,
             Transaction,
             TransactionOutput,
             UnblindedOutput,
@@ -45,18 +46,36 @@ use tari_core::{
 };
 use tari_script::TariScript;
 use tari_service_framework::reply_channel::SenderService;
-use tari_utilities::hex::Hex;
-use tokio::sync::broadcast;
+use tari_utilities::hex::{to_hex, Hex};
+use tokio::sync::{broadcast, watch};
 use tower::Service;
 
-use crate::output_manager_service::{
-    error::OutputManagerError,
-    service::{Balance, OutputStatusesByTxId},
-    storage::{
-        database::OutputBackendQuery,
-        models::{KnownOneSidedPaymentScript, SpendingPriority},
+use crate::{
+    output_manager_service::{
+        config::OutputManagerServiceConfig,
+        error::OutputManagerError,
+        service::{Balance, CoinbaseStats, OutputStatusesByTxId},
+        storage::{
+            database::OutputBackendQuery,
+            models::{KnownOneSidedPaymentScript, SpendingPriority},
+            OutputSource,
+        },
+        Deposit,
+        DepositEventReceiver,
+        DepositIdentity,
+        ListedOutput,
+        OwnedAsset,
+        ScriptOffsetAudit,
+        TokenBalance,
+        TransactionScriptKeys,
+        UtxoSelectionCriteria,
+        VaultOutput,
+    },
+    util::{
+        cancellation::CancellationToken,
+        scheduler::{PrioritizedRequest, RequestPriority, RequestScheduler, ScheduledService},
+        watch::Watch,
     },
-    UtxoSelectionCriteria,
 };
 
 /// API Request enum
@@ -73,7 +92,7 @@ pub enum OutputManagerRequest {
     AddUnvalidatedOutput((TxId, Box<UnblindedOutput>, Option<SpendingPriority>)),
     UpdateOutputMetadataSignature(Box<TransactionOutput>),
     GetRecipientTransaction(TransactionSenderMessage),
-    GetCoinbaseTransaction((TxId, MicroTari, MicroTari, u64)),
+    GetCoinbaseTransaction((TxId, MicroTari, MicroTari, u64, Vec<u8>)),
     ConfirmPendingTransaction(TxId),
     PrepareToSendTransaction {
         tx_id: TxId,
@@ -86,6 +105,7 @@ pub enum OutputManagerRequest {
         script: TariScript,
         covenant: Covenant,
         minimum_value_promise: MicroTari,
+        cancellation: CancellationToken,
     },
     CreatePayToSelfTransaction {
         tx_id: TxId,
@@ -104,6 +124,7 @@ pub enum OutputManagerRequest {
     CancelTransaction(TxId),
     GetSpentOutputs,
     GetUnspentOutputs,
+    ListOutputs,
     GetOutputsBy(OutputBackendQuery),
     GetInvalidOutputs,
     ValidateUtxos,
@@ -112,12 +133,18 @@ pub enum OutputManagerRequest {
     CreateCoinSplitEven((Vec<Commitment>, usize, MicroTari)),
     PreviewCoinJoin((Vec<Commitment>, MicroTari)),
     PreviewCoinSplitEven((Vec<Commitment>, usize, MicroTari)),
+    PreviewSweepAll {
+        fee_per_gram: MicroTari,
+        output_features: Box<OutputFeatures>,
+        script: TariScript,
+    },
     CreateCoinJoin {
         commitments: Vec<Commitment>,
         fee_per_gram: MicroTari,
     },
     ApplyEncryption(Box<XChaCha20Poly1305>),
     RemoveEncryption,
+    RotateKeyEpoch,
     FeeEstimate {
         amount: MicroTari,
         fee_per_gram: MicroTari,
@@ -126,6 +153,10 @@ pub enum OutputManagerRequest {
     },
 
     ScanForRecoverableOutputs(Vec<TransactionOutput>),
+    RewindOutputs(Vec<TransactionOutput>),
+    RestoreKeyIndices {
+        gap_limit: u64,
+    },
     ScanOutputs(Vec<TransactionOutput>),
     AddKnownOneSidedPaymentScript(KnownOneSidedPaymentScript),
     CreateOutputWithFeatures {
@@ -135,9 +166,68 @@ pub enum OutputManagerRequest {
 
     ReinstateCancelledInboundTx(TxId),
     SetCoinbaseAbandoned(TxId, bool),
+    GetCoinbaseStats,
     CreateClaimShaAtomicSwapTransaction(HashOutput, PublicKey, MicroTari),
     CreateHtlcRefundTransaction(HashOutput, MicroTari),
     GetOutputStatusesByTxId(TxId),
+    CreateVaultTransaction {
+        tx_id: TxId,
+        amount: MicroTari,
+        recovery_key: Box<PublicKey>,
+        recovery_delay: u64,
+        unlock_height: u64,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    GetVaultOutputs,
+    CreateVaultRecoveryTransaction(HashOutput, Box<PrivateKey>, MicroTari),
+    CreateInheritanceTransaction {
+        beneficiaries: Vec<(PublicKey, u64)>,
+        lock_height: u64,
+        fee_per_gram: MicroTari,
+    },
+    CreateTokenRegistrationTransaction {
+        tx_id: TxId,
+        amount: MicroTari,
+        symbol: String,
+        total_supply: u64,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    CreateUniqueAssetMintTransaction {
+        tx_id: TxId,
+        amount: MicroTari,
+        unique_id: Vec<u8>,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    GetTokenBalances,
+    GetOwnedAssets,
+    PreviewUniqueAssetTransfer {
+        asset_id: FixedHash,
+        fee_per_gram: MicroTari,
+        recipient_script: TariScript,
+    },
+    SetConfig(Box<OutputManagerServiceConfig>),
+    CreateDepositIdentity(String),
+    GetDepositIdentities,
+    GetDeposits(u64),
+    GetScriptKeys,
+    AuditScriptOffset(Box<Transaction>),
+}
+
+impl PrioritizedRequest for OutputManagerRequest {
+    fn priority(&self) -> RequestPriority {
+        match self {
+            Self::ValidateUtxos |
+            Self::RevalidateTxos |
+            Self::ScanForRecoverableOutputs(_) |
+            Self::RewindOutputs(_) |
+            Self::RestoreKeyIndices { .. } |
+            Self::ScanOutputs(_) => RequestPriority::Background,
+            _ => RequestPriority::Interactive,
+        }
+    }
 }
 
 impl fmt::Display for OutputManagerRequest {
@@ -168,6 +258,7 @@ impl fmt::Display for OutputManagerRequest {
             CancelTransaction(v) => write!(f, "CancelTransaction ({})", v),
             GetSpentOutputs => write!(f, "GetSpentOutputs"),
             GetUnspentOutputs => write!(f, "GetUnspentOutputs"),
+            ListOutputs => write!(f, "ListOutputs"),
             GetOutputsBy(q) => write!(f, "GetOutputs({:#?})", q),
             GetInvalidOutputs => write!(f, "GetInvalidOutputs"),
             ValidateUtxos => write!(f, "ValidateUtxos"),
@@ -182,6 +273,7 @@ impl fmt::Display for OutputManagerRequest {
                 "PreviewCoinSplitEven(commitments={:#?}, number_of_splits={}, fee_per_gram={})",
                 commitments, number_of_splits, fee_per_gram
             ),
+            PreviewSweepAll { fee_per_gram, .. } => write!(f, "PreviewSweepAll(fee_per_gram={})", fee_per_gram),
             CreateCoinSplit(v) => write!(f, "CreateCoinSplit ({:?})", v.0),
             CreateCoinSplitEven(v) => write!(f, "CreateCoinSplitEven ({:?})", v.0),
             CreateCoinJoin {
@@ -194,6 +286,7 @@ impl fmt::Display for OutputManagerRequest {
             ),
             ApplyEncryption(_) => write!(f, "ApplyEncryption"),
             RemoveEncryption => write!(f, "RemoveEncryption"),
+            RotateKeyEpoch => write!(f, "RotateKeyEpoch"),
             GetCoinbaseTransaction(_) => write!(f, "GetCoinbaseTransaction"),
             FeeEstimate {
                 amount,
@@ -206,6 +299,8 @@ impl fmt::Display for OutputManagerRequest {
                 amount, fee_per_gram, num_kernels, num_outputs
             ),
             ScanForRecoverableOutputs(_) => write!(f, "ScanForRecoverableOutputs"),
+            RewindOutputs(outputs) => write!(f, "RewindOutputs({} output(s))", outputs.len()),
+            RestoreKeyIndices { gap_limit } => write!(f, "RestoreKeyIndices(gap limit: {})", gap_limit),
             ScanOutputs(_) => write!(f, "ScanOutputs"),
             AddKnownOneSidedPaymentScript(_) => write!(f, "AddKnownOneSidedPaymentScript"),
             CreateOutputWithFeatures { value, features } => {
@@ -214,6 +309,7 @@ impl fmt::Display for OutputManagerRequest {
             CreatePayToSelfWithOutputs { .. } => write!(f, "CreatePayToSelfWithOutputs"),
             ReinstateCancelledInboundTx(_) => write!(f, "ReinstateCancelledInboundTx"),
             SetCoinbaseAbandoned(_, _) => write!(f, "SetCoinbaseAbandoned"),
+            GetCoinbaseStats => write!(f, "GetCoinbaseStats"),
             CreateClaimShaAtomicSwapTransaction(output, pre_image, fee_per_gram) => write!(
                 f,
                 "ClaimShaAtomicSwap(output hash: {}, pre_image: {}, fee_per_gram: {} )",
@@ -229,6 +325,65 @@ impl fmt::Display for OutputManagerRequest {
             ),
 
             GetOutputStatusesByTxId(t) => write!(f, "GetOutputStatusesByTxId: {}", t),
+            CreateVaultTransaction {
+                amount,
+                recovery_delay,
+                unlock_height,
+                ..
+            } => write!(
+                f,
+                "CreateVaultTransaction(amount: {}, recovery_delay: {}, unlock_height: {})",
+                amount, recovery_delay, unlock_height
+            ),
+            GetVaultOutputs => write!(f, "GetVaultOutputs"),
+            CreateVaultRecoveryTransaction(output, _, fee_per_gram) => write!(
+                f,
+                "CreateVaultRecoveryTransaction(output hash: {}, fee_per_gram: {})",
+                output.to_hex(),
+                fee_per_gram,
+            ),
+            CreateInheritanceTransaction {
+                beneficiaries,
+                lock_height,
+                fee_per_gram,
+            } => write!(
+                f,
+                "CreateInheritanceTransaction(beneficiaries: {}, lock_height: {}, fee_per_gram: {})",
+                beneficiaries.len(),
+                lock_height,
+                fee_per_gram
+            ),
+            CreateTokenRegistrationTransaction {
+                amount,
+                symbol,
+                total_supply,
+                ..
+            } => write!(
+                f,
+                "CreateTokenRegistrationTransaction(amount: {}, symbol: {}, total_supply: {})",
+                amount, symbol, total_supply
+            ),
+            CreateUniqueAssetMintTransaction { amount, unique_id, .. } => write!(
+                f,
+                "CreateUniqueAssetMintTransaction(amount: {}, unique_id: {})",
+                amount,
+                to_hex(unique_id)
+            ),
+            GetTokenBalances => write!(f, "GetTokenBalances"),
+            GetOwnedAssets => write!(f, "GetOwnedAssets"),
+            PreviewUniqueAssetTransfer {
+                asset_id, fee_per_gram, ..
+            } => write!(
+                f,
+                "PreviewUniqueAssetTransfer(asset_id: {}, fee_per_gram: {})",
+                asset_id, fee_per_gram
+            ),
+            SetConfig(_) => write!(f, "SetConfig"),
+            CreateDepositIdentity(label) => write!(f, "CreateDepositIdentity({})", label),
+            GetDepositIdentities => write!(f, "GetDepositIdentities"),
+            GetDeposits(key_index) => write!(f, "GetDeposits({})", key_index),
+            GetScriptKeys => write!(f, "GetScriptKeys"),
+            AuditScriptOffset(_) => write!(f, "AuditScriptOffset"),
         }
     }
 }
@@ -249,6 +404,7 @@ pub enum OutputManagerResponse {
     TransactionCancelled,
     SpentOutputs(Vec<UnblindedOutput>),
     UnspentOutputs(Vec<UnblindedOutput>),
+    ListedOutputs(Vec<ListedOutput>),
     Outputs(Vec<UnblindedOutput>),
     InvalidOutputs(Vec<UnblindedOutput>),
     BaseNodePublicKeySet,
@@ -256,19 +412,36 @@ pub enum OutputManagerResponse {
     Transaction((TxId, Transaction, MicroTari)),
     EncryptionApplied,
     EncryptionRemoved,
+    KeyEpochRotated(u64),
     PublicRewindKeys(Box<PublicRewindKeys>),
     RecoveryByte(u8),
     FeeEstimate(MicroTari),
     RewoundOutputs(Vec<RecoveredOutput>),
+    OutputsRewound(Vec<(TransactionOutput, RewoundOutput)>),
+    KeyIndicesRestored(Vec<RestoredKeyIndex>),
     ScanOutputs(Vec<RecoveredOutput>),
     AddKnownOneSidedPaymentScript,
     CreateOutputWithFeatures { output: Box<UnblindedOutputBuilder> },
     CreatePayToSelfWithOutputs { transaction: Box<Transaction>, tx_id: TxId },
     ReinstatedCancelledInboundTx,
     CoinbaseAbandonedSet,
+    CoinbaseStats(CoinbaseStats),
     ClaimHtlcTransaction((TxId, MicroTari, MicroTari, Transaction)),
     OutputStatusesByTxId(OutputStatusesByTxId),
     CoinPreview((Vec<MicroTari>, MicroTari)),
+    SweepPreview(Vec<(Vec<Commitment>, MicroTari)>),
+    VaultOutputs(Vec<VaultOutput>),
+    VaultRecoveryTransaction((TxId, MicroTari, MicroTari, Transaction)),
+    InheritanceTransaction(Box<(Transaction, MicroTari)>),
+    TokenBalances(Vec<TokenBalance>),
+    OwnedAssets(Vec<OwnedAsset>),
+    UniqueAssetTransferPreview(Box<(OwnedAsset, MicroTari)>),
+    ConfigSet,
+    DepositIdentityCreated(DepositIdentity),
+    DepositIdentities(Vec<DepositIdentity>),
+    Deposits(Vec<Deposit>),
+    ScriptKeys(Vec<TransactionScriptKeys>),
+    ScriptOffsetAudit(ScriptOffsetAudit),
 }
 
 pub type OutputManagerEventSender = broadcast::Sender<Arc<OutputManagerEvent>>;
@@ -279,6 +452,8 @@ pub type OutputManagerEventReceiver = broadcast::Receiver<Arc<OutputManagerEvent
 pub enum OutputManagerEvent {
     TxoValidationSuccess(u64),
     TxoValidationFailure(u64),
+    /// A deposit identity received an output, see [OutputManagerHandle::get_deposit_event_receiver]
+    DepositReceived(Deposit),
     Error(String),
 }
 
@@ -291,6 +466,13 @@ impl fmt::Display for OutputManagerEvent {
             OutputManagerEvent::TxoValidationFailure(tx) => {
                 write!(f, "TxoValidationFailure for {}", tx)
             },
+            OutputManagerEvent::DepositReceived(deposit) => {
+                write!(
+                    f,
+                    "DepositReceived of {} for identity {}",
+                    deposit.value, deposit.key_index
+                )
+            },
             OutputManagerEvent::Error(error) => {
                 write!(f, "Error {}", error)
             },
@@ -307,22 +489,44 @@ pub struct PublicRewindKeys {
 pub struct RecoveredOutput {
     pub tx_id: TxId,
     pub output: UnblindedOutput,
+    pub source: OutputSource,
+    /// The memo the sender of a one-sided payment encrypted in the output
+    pub memo: Option<String>,
+}
+
+/// A key manager branch index that was restored after recovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoredKeyIndex {
+    pub branch: String,
+    pub index: u64,
 }
 
 #[derive(Clone)]
 pub struct OutputManagerHandle {
-    handle: SenderService<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
+    handle: ScheduledService<SenderService<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>>,
     event_stream_sender: OutputManagerEventSender,
+    balance_watch: Watch<Option<Balance>>,
 }
 
 impl OutputManagerHandle {
     pub fn new(
         handle: SenderService<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
         event_stream_sender: OutputManagerEventSender,
+        balance_watch: Watch<Option<Balance>>,
     ) -> Self {
         OutputManagerHandle {
-            handle,
+            handle: ScheduledService::new(handle),
             event_stream_sender,
+            balance_watch,
+        }
+    }
+
+    /// Returns a handle whose requests count towards the limit of `caller` on the requests in flight
+    pub fn for_caller(&self, scheduler: &RequestScheduler, caller: &str) -> Self {
+        Self {
+            handle: self.handle.for_caller(scheduler, caller),
+            event_stream_sender: self.event_stream_sender.clone(),
+            balance_watch: self.balance_watch.clone(),
         }
     }
 
@@ -330,6 +534,17 @@ impl OutputManagerHandle {
         self.event_stream_sender.subscribe()
     }
 
+    /// Returns a stream of the deposits received by the deposit identity with the given key index from now on
+    pub fn get_deposit_event_receiver(&self, key_index: u64) -> DepositEventReceiver {
+        DepositEventReceiver::new(key_index, self.event_stream_sender.subscribe())
+    }
+
+    /// Subscribes to the balance of the wallet, which is only published when it changes. Bursts of changes, e.g. while
+    /// outputs are validated, are coalesced into a single update. The balance is `None` until it is first computed.
+    pub fn subscribe_balance_changes(&self) -> watch::Receiver<Option<Balance>> {
+        self.balance_watch.get_receiver()
+    }
+
     pub async fn add_output(
         &mut self,
         output: UnblindedOutput,
@@ -505,12 +720,15 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Builds the coinbase transaction for a block. The `extra` data, e.g. a pool tag, is added to the coinbase output
+    /// and may not exceed the maximum size allowed by consensus.
     pub async fn get_coinbase_transaction(
         &mut self,
         tx_id: TxId,
         reward: MicroTari,
         fees: MicroTari,
         block_height: u64,
+        extra: Vec<u8>,
     ) -> Result<Transaction, OutputManagerError> {
         match self
             .handle
@@ -519,6 +737,7 @@ impl OutputManagerHandle {
                 reward,
                 fees,
                 block_height,
+                extra,
             )))
             .await??
         {
@@ -539,6 +758,38 @@ impl OutputManagerHandle {
         script: TariScript,
         covenant: Covenant,
         minimum_value_promise: MicroTari,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        self.prepare_transaction_to_send_with_cancellation(
+            tx_id,
+            amount,
+            utxo_selection,
+            output_features,
+            fee_per_gram,
+            tx_meta,
+            message,
+            script,
+            covenant,
+            minimum_value_promise,
+            CancellationToken::new(),
+        )
+        .await
+    }
+
+    /// Prepares a transaction to send like [prepare_transaction_to_send](Self::prepare_transaction_to_send), unless
+    /// `cancellation` is cancelled before the inputs are encumbered
+    pub async fn prepare_transaction_to_send_with_cancellation(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        utxo_selection: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        tx_meta: TransactionMetadata,
+        message: String,
+        script: TariScript,
+        covenant: Covenant,
+        minimum_value_promise: MicroTari,
+        cancellation: CancellationToken,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         match self
             .handle
@@ -553,6 +804,7 @@ impl OutputManagerHandle {
                 script,
                 covenant,
                 minimum_value_promise,
+                cancellation,
             })
             .await??
         {
@@ -622,6 +874,15 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns the unspent outputs of the wallet along with the height and estimated time from which each of them can
+    /// be spent, sorted from lowest value to highest
+    pub async fn list_outputs(&mut self) -> Result<Vec<ListedOutput>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::ListOutputs).await?? {
+            OutputManagerResponse::ListedOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     // ToDo: This API method call could probably be removed by expanding test utils if only needed for testing
     pub async fn get_invalid_outputs(&mut self) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::GetInvalidOutputs).await?? {
@@ -672,6 +933,29 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Groups all the spendable outputs into batches for sweeping the wallet to a recipient output with these features
+    /// and script. Returns the commitments of the outputs in each batch and the amount that the batch can send after
+    /// fees, without a change output.
+    pub async fn preview_sweep_all(
+        &mut self,
+        fee_per_gram: MicroTari,
+        output_features: OutputFeatures,
+        script: TariScript,
+    ) -> Result<Vec<(Vec<Commitment>, MicroTari)>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PreviewSweepAll {
+                fee_per_gram,
+                output_features: Box::new(output_features),
+                script,
+            })
+            .await??
+        {
+            OutputManagerResponse::SweepPreview(batches) => Ok(batches),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     /// Create a coin split transaction.
     /// Returns (tx_id, tx, utxos_total_value).
     pub async fn create_coin_split(
@@ -749,6 +1033,253 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Creates a pay-to-self transaction with a vault output of `amount`. The vault can be spent with the recovery
+    /// key `recovery_delay` blocks from now, or with the wallet's key alone from `unlock_height`. Returns the fee
+    /// and the transaction, which still needs to be submitted.
+    pub async fn create_vault_transaction(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        recovery_key: PublicKey,
+        recovery_delay: u64,
+        unlock_height: u64,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateVaultTransaction {
+                tx_id,
+                amount,
+                recovery_key: Box::new(recovery_key),
+                recovery_delay,
+                unlock_height,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            OutputManagerResponse::PayToSelfTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the unspent vault outputs of the wallet
+    pub async fn get_vault_outputs(&mut self) -> Result<Vec<VaultOutput>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetVaultOutputs).await?? {
+            OutputManagerResponse::VaultOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a transaction that spends a vault output back to the wallet using the recovery key, before the vault
+    /// unlocks
+    pub async fn create_vault_recovery_transaction(
+        &mut self,
+        output: HashOutput,
+        recovery_key: PrivateKey,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, MicroTari, MicroTari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateVaultRecoveryTransaction(
+                output,
+                Box::new(recovery_key),
+                fee_per_gram,
+            ))
+            .await??
+        {
+            OutputManagerResponse::VaultRecoveryTransaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a transaction that spends all the spendable outputs of the wallet to one-sided outputs for the
+    /// beneficiaries, split according to their shares, which can only be mined from `lock_height`. The inputs are not
+    /// encumbered, so the transaction stays valid only as long as none of them are spent. Returns the transaction and
+    /// the total amount paid to the beneficiaries.
+    pub async fn create_inheritance_transaction(
+        &mut self,
+        beneficiaries: Vec<(PublicKey, u64)>,
+        lock_height: u64,
+        fee_per_gram: MicroTari,
+    ) -> Result<(Transaction, MicroTari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateInheritanceTransaction {
+                beneficiaries,
+                lock_height,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::InheritanceTransaction(tx) => Ok(*tx),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a transaction that issues `total_supply` units of a new fungible token to the wallet, by locking
+    /// `amount` in a registration output signed with the issuer key of the wallet. Returns the fee and the transaction,
+    /// which still needs to be submitted.
+    pub async fn create_token_registration_transaction(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        symbol: String,
+        total_supply: u64,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateTokenRegistrationTransaction {
+                tx_id,
+                amount,
+                symbol,
+                total_supply,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            OutputManagerResponse::PayToSelfTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a transaction that mints the unique asset `unique_id` to the wallet in an output of `amount`, with the
+    /// issuer key of the wallet as the parent of the asset. Returns the fee and the transaction, which still needs to
+    /// be submitted.
+    pub async fn create_unique_asset_mint_transaction(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        unique_id: Vec<u8>,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateUniqueAssetMintTransaction {
+                tx_id,
+                amount,
+                unique_id,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            OutputManagerResponse::PayToSelfTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the balances of the fungible tokens held in the unspent registration outputs of the wallet
+    pub async fn get_token_balances(&mut self) -> Result<Vec<TokenBalance>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetTokenBalances).await?? {
+            OutputManagerResponse::TokenBalances(balances) => Ok(balances),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the unique assets held in the unspent outputs of the wallet
+    pub async fn get_owned_assets(&mut self) -> Result<Vec<OwnedAsset>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetOwnedAssets).await?? {
+            OutputManagerResponse::OwnedAssets(assets) => Ok(assets),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the output that holds the asset and the amount it transfers to a recipient output with the given
+    /// script once the fee is deducted. The asset output is the only input of the transfer, so it pays the fee and no
+    /// change output is needed.
+    pub async fn preview_unique_asset_transfer(
+        &mut self,
+        asset_id: FixedHash,
+        fee_per_gram: MicroTari,
+        recipient_script: TariScript,
+    ) -> Result<(OwnedAsset, MicroTari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PreviewUniqueAssetTransfer {
+                asset_id,
+                fee_per_gram,
+                recipient_script,
+            })
+            .await??
+        {
+            OutputManagerResponse::UniqueAssetTransferPreview(preview) => Ok(*preview),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Replaces the config of the running service, e.g. to change the retry policies without a restart. Validations
+    /// that are already running keep the config they were started with.
+    pub async fn set_config(&mut self, config: OutputManagerServiceConfig) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetConfig(Box::new(config)))
+            .await??
+        {
+            OutputManagerResponse::ConfigSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Derives a new receive-only deposit identity for the user or account `label`, which must be unique in the
+    /// wallet. Simple one-sided payments to the public key of the identity are spent with the other outputs of the
+    /// wallet, and attributed to the identity as deposits.
+    pub async fn create_deposit_identity(&mut self, label: String) -> Result<DepositIdentity, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateDepositIdentity(label))
+            .await??
+        {
+            OutputManagerResponse::DepositIdentityCreated(identity) => Ok(identity),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_deposit_identities(&mut self) -> Result<Vec<DepositIdentity>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetDepositIdentities).await?? {
+            OutputManagerResponse::DepositIdentities(identities) => Ok(identities),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the deposits received by the deposit identity with the given key index, oldest first
+    pub async fn get_deposits(&mut self, key_index: u64) -> Result<Vec<Deposit>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetDeposits(key_index)).await?? {
+            OutputManagerResponse::Deposits(deposits) => Ok(deposits),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the script private keys of the outputs that each transaction of the wallet spent, with the part of the
+    /// script offset of the transaction that they contribute
+    pub async fn get_script_keys(&mut self) -> Result<Vec<TransactionScriptKeys>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetScriptKeys).await?? {
+            OutputManagerResponse::ScriptKeys(records) => Ok(records),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Checks the script offset of a transaction, e.g. one that was rebuilt from a backup, against the script keys and
+    /// sender offset public keys of the outputs of the wallet
+    pub async fn audit_script_offset(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<ScriptOffsetAudit, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::AuditScriptOffset(Box::new(transaction)))
+            .await??
+        {
+            OutputManagerResponse::ScriptOffsetAudit(audit) => Ok(audit),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_claim_sha_atomic_swap_transaction(
         &mut self,
         output: HashOutput,
@@ -787,6 +1318,15 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Marks the current key epoch as compromised and derives all new keys, including the rewind keys, from a new
+    /// epoch, which is returned. Existing outputs are not touched and still have to be swept to keys of the new epoch.
+    pub async fn rotate_key_epoch(&mut self) -> Result<u64, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::RotateKeyEpoch).await?? {
+            OutputManagerResponse::KeyEpochRotated(epoch) => Ok(epoch),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn scan_for_recoverable_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
@@ -801,6 +1341,32 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Rewinds the outputs with the rewind keys of the wallet and returns the ones that belong to it together with
+    /// their value and blinding factor. Unlike [scan_for_recoverable_outputs](Self::scan_for_recoverable_outputs), the
+    /// outputs are not added to the wallet.
+    pub async fn rewind_outputs(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<(TransactionOutput, RewoundOutput)>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::RewindOutputs(outputs)).await?? {
+            OutputManagerResponse::OutputsRewound(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Walk the key manager branches forward, up to `gap_limit` unused keys past the last match, to restore the key
+    /// indices of outputs that were recovered.
+    pub async fn restore_key_indices(&mut self, gap_limit: u64) -> Result<Vec<RestoredKeyIndex>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::RestoreKeyIndices { gap_limit })
+            .await??
+        {
+            OutputManagerResponse::KeyIndicesRestored(indices) => Ok(indices),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn scan_outputs_for_one_sided_payments(
         &mut self,
         outputs: Vec<TransactionOutput>,
@@ -895,6 +1461,14 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns how many of the coinbases generated by the wallet are pending, mined or abandoned
+    pub async fn get_coinbase_stats(&mut self) -> Result<CoinbaseStats, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetCoinbaseStats).await?? {
+            OutputManagerResponse::CoinbaseStats(stats) => Ok(stats),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_output_statuses_by_tx_id(
         &mut self,
         tx_id: TxId,
@@ -21,6 +21,10 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod config;
+
+mod deposits;
+pub use deposits::{Deposit, DepositEventReceiver, DepositIdentity};
+
 pub mod error;
 pub mod handle;
 
@@ -29,10 +33,24 @@ pub use input_selection::{UtxoSelectionCriteria, UtxoSelectionFilter, UtxoSelect
 
 mod recovery;
 pub mod resources;
+
+mod script_offsets;
+pub use script_offsets::{ScriptKeyRecord, ScriptOffsetAudit, TransactionScriptKeys};
+
 pub mod service;
+mod spendability;
+pub use spendability::{ListedOutput, OutputSpendability};
+
 pub mod storage;
 mod tasks;
 
+mod tokens;
+pub use tokens::TokenBalance;
+
+mod unique_assets;
+pub use unique_assets::OwnedAsset;
+
+mod vault;
 use std::{marker::PhantomData, sync::Arc};
 
 use futures::future;
@@ -47,6 +65,7 @@ use tari_service_framework::{
     ServiceInitializerContext,
 };
 use tokio::sync::broadcast;
+pub use vault::{ExpiringVaultScript, VaultOutput, VaultScript};
 
 use crate::{
     base_node_service::handle::BaseNodeServiceHandle,
@@ -58,6 +77,7 @@ use crate::{
         service::OutputManagerService,
         storage::database::{OutputManagerBackend, OutputManagerDatabase},
     },
+    util::watch::Watch,
 };
 
 const LOG_TARGET: &str = "wallet::output_manager_service::initializer";
@@ -105,7 +125,8 @@ where
         let (publisher, _) = broadcast::channel(self.config.event_channel_size);
 
         // Register handle before waiting for handles to be ready
-        let oms_handle = OutputManagerHandle::new(sender, publisher.clone());
+        let balance_watch = Watch::new(None);
+        let oms_handle = OutputManagerHandle::new(sender, publisher.clone(), balance_watch.clone());
         context.register_handle(oms_handle);
 
         let backend = self
@@ -126,6 +147,7 @@ where
                 receiver,
                 OutputManagerDatabase::new(backend),
                 publisher,
+                balance_watch,
                 factories,
                 constants,
                 handles.get_shutdown_signal(),
@@ -0,0 +1,147 @@
+// Copyright 2022. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashSet, sync::Arc, time::Instant};
+
+use log::*;
+use tari_common_types::types::PrivateKey;
+use tokio::task::JoinHandle;
+
+use crate::{
+    key_manager_service::KeyManagerInterface,
+    output_manager_service::{
+        error::OutputManagerError,
+        handle::RestoredKeyIndex,
+        resources::OutputManagerKeyManagerBranch,
+        storage::database::{OutputBackendQuery, OutputManagerBackend, OutputManagerDatabase},
+    },
+};
+
+const LOG_TARGET: &str = "wallet::output_manager_service::recovery::key_index";
+
+/// Restores the key manager index counters after a wallet recovery. Every output held in the database is matched
+/// against the spending and script key branches, walking each branch forward until `gap_limit` consecutive keys are
+/// found to be unused, so that keys derived after recovery do not collide with keys that were used before.
+pub(crate) struct KeyIndexRecoverer<TBackend: OutputManagerBackend + 'static, TKeyManagerInterface> {
+    master_key_manager: TKeyManagerInterface,
+    db: OutputManagerDatabase<TBackend>,
+}
+
+impl<TBackend, TKeyManagerInterface> KeyIndexRecoverer<TBackend, TKeyManagerInterface>
+where
+    TBackend: OutputManagerBackend + 'static,
+    TKeyManagerInterface: KeyManagerInterface,
+{
+    pub fn new(master_key_manager: TKeyManagerInterface, db: OutputManagerDatabase<TBackend>) -> Self {
+        Self { master_key_manager, db }
+    }
+
+    /// Probe the key manager branches concurrently and update their indices to the highest used index found. The
+    /// spend and script branches are derived in lockstep, so each pair is advanced to the highest index found in
+    /// either branch of the pair.
+    pub async fn restore_key_indices(&self, gap_limit: u64) -> Result<Vec<RestoredKeyIndex>, OutputManagerError> {
+        let start = Instant::now();
+        let outputs = self.db.fetch_outputs_by(OutputBackendQuery {
+            status: vec![],
+            ..Default::default()
+        })?;
+        let spending_keys = Arc::new(
+            outputs
+                .iter()
+                .map(|o| o.unblinded_output.spending_key.clone())
+                .collect::<HashSet<PrivateKey>>(),
+        );
+        let script_keys = Arc::new(
+            outputs
+                .iter()
+                .map(|o| o.unblinded_output.script_private_key.clone())
+                .collect::<HashSet<PrivateKey>>(),
+        );
+
+        let spend = self.spawn_probe(OutputManagerKeyManagerBranch::Spend, spending_keys.clone(), gap_limit);
+        let spend_script = self.spawn_probe(
+            OutputManagerKeyManagerBranch::SpendScript,
+            script_keys.clone(),
+            gap_limit,
+        );
+        let coinbase = self.spawn_probe(OutputManagerKeyManagerBranch::Coinbase, spending_keys, gap_limit);
+        let coinbase_script = self.spawn_probe(OutputManagerKeyManagerBranch::CoinbaseScript, script_keys, gap_limit);
+
+        let (spend, spend_script, coinbase, coinbase_script) =
+            tokio::try_join!(spend, spend_script, coinbase, coinbase_script)
+                .map_err(|e| OutputManagerError::ServiceError(e.to_string()))?;
+
+        let mut restored = Vec::new();
+        for (branches, found) in [
+            (
+                [
+                    OutputManagerKeyManagerBranch::Spend,
+                    OutputManagerKeyManagerBranch::SpendScript,
+                ],
+                spend?.max(spend_script?),
+            ),
+            (
+                [
+                    OutputManagerKeyManagerBranch::Coinbase,
+                    OutputManagerKeyManagerBranch::CoinbaseScript,
+                ],
+                coinbase?.max(coinbase_script?),
+            ),
+        ] {
+            if let Some(index) = found {
+                for branch in branches {
+                    self.master_key_manager
+                        .update_current_key_index_if_higher(branch.get_branch_key(), index)
+                        .await?;
+                    restored.push(RestoredKeyIndex {
+                        branch: branch.get_branch_key(),
+                        index,
+                    });
+                }
+            }
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Restored {} key manager indices from {} outputs in {:.2?}",
+            restored.len(),
+            outputs.len(),
+            start.elapsed()
+        );
+        Ok(restored)
+    }
+
+    fn spawn_probe(
+        &self,
+        branch: OutputManagerKeyManagerBranch,
+        used_keys: Arc<HashSet<PrivateKey>>,
+        gap_limit: u64,
+    ) -> JoinHandle<Result<Option<u64>, OutputManagerError>> {
+        let key_manager = self.master_key_manager.clone();
+        tokio::spawn(async move {
+            let found = key_manager
+                .find_last_used_key_index(branch.get_branch_key(), &used_keys, gap_limit)
+                .await?;
+            Ok(found)
+        })
+    }
+}
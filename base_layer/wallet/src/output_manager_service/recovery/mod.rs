@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod key_index_recoverer;
 mod standard_outputs_recoverer;
 
-pub(crate) use standard_outputs_recoverer::StandardUtxoRecoverer;
+pub(crate) use key_index_recoverer::KeyIndexRecoverer;
+pub(crate) use standard_outputs_recoverer::{rewind_outputs, StandardUtxoRecoverer};
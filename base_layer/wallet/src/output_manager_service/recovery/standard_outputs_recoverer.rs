@@ -20,8 +20,9 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::time::Instant;
+use std::{thread, time::Instant};
 
+use futures::future;
 use log::*;
 use rand::rngs::OsRng;
 use tari_common_types::{
@@ -29,7 +30,7 @@ use tari_common_types::{
     types::{BulletRangeProof, PrivateKey, PublicKey},
 };
 use tari_core::transactions::{
-    transaction_components::{EncryptedValue, TransactionOutput, UnblindedOutput},
+    transaction_components::{RewoundOutput, TransactionOutput, UnblindedOutput},
     transaction_protocol::RewindData,
     CryptoFactories,
 };
@@ -38,6 +39,7 @@ use tari_crypto::{
     tari_utilities::hex::Hex,
 };
 use tari_script::{inputs, script, Opcode};
+use tokio::task;
 
 use crate::{
     key_manager_service::KeyManagerInterface,
@@ -55,6 +57,46 @@ use crate::{
 
 const LOG_TARGET: &str = "wallet::output_manager_service::recovery";
 
+/// Rewinds the outputs with the rewind keys of the wallet and returns the ones that belong to it together with their
+/// value and blinding factor, in the order they were given. Rewinding is CPU bound, so the outputs are split into
+/// batches that are rewound in parallel on the blocking thread pool.
+pub(crate) async fn rewind_outputs(
+    outputs: Vec<TransactionOutput>,
+    rewind_data: &RewindData,
+    factories: &CryptoFactories,
+) -> Result<Vec<(TransactionOutput, RewoundOutput)>, OutputManagerError> {
+    if outputs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let concurrency = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let batch_size = (outputs.len() + concurrency - 1) / concurrency;
+    let mut outputs = outputs.into_iter().peekable();
+    let mut tasks = Vec::with_capacity(concurrency);
+    while outputs.peek().is_some() {
+        let batch = outputs.by_ref().take(batch_size).collect::<Vec<_>>();
+        let rewind_data = rewind_data.clone();
+        let range_proof = factories.range_proof.clone();
+        tasks.push(task::spawn_blocking(move || {
+            let mut rewound = Vec::new();
+            for output in batch {
+                if let Some(rewound_output) = output.rewind(&range_proof, &rewind_data)? {
+                    rewound.push((output, rewound_output));
+                }
+            }
+            Ok::<_, OutputManagerError>(rewound)
+        }));
+    }
+
+    let mut rewound = Vec::new();
+    for batch in future::try_join_all(tasks)
+        .await
+        .map_err(|e| OutputManagerError::ServiceError(format!("Rewinding outputs failed: {}", e)))?
+    {
+        rewound.extend(batch?);
+    }
+    Ok(rewound)
+}
+
 pub(crate) struct StandardUtxoRecoverer<TBackend: OutputManagerBackend + 'static, TKeyManagerInterface> {
     master_key_manager: TKeyManagerInterface,
     rewind_data: RewindData,
@@ -92,48 +134,35 @@ where
 
         let known_scripts = self.db.get_all_known_one_sided_payment_scripts()?;
 
+        let outputs = outputs
+            .into_iter()
+            .filter(|output| output.script == script!(Nop) || known_scripts.iter().any(|s| s.script == output.script))
+            .collect();
         let mut rewound_outputs: Vec<(UnblindedOutput, BulletRangeProof)> = Vec::new();
-        for output in outputs {
-            let known_script_index = known_scripts.iter().position(|s| s.script == output.script);
-            if output.script != script!(Nop) && known_script_index.is_none() {
-                continue;
-            }
-            let committed_value = EncryptedValue::decrypt_value(
-                &self.rewind_data.encryption_key,
-                &output.commitment,
-                &output.encrypted_value,
+        for (output, rewound) in rewind_outputs(outputs, &self.rewind_data, &self.factories).await? {
+            let (input_data, script_key) =
+                if let Some(known_script) = known_scripts.iter().find(|s| s.script == output.script) {
+                    (known_script.input.clone(), known_script.private_key.clone())
+                } else {
+                    let key = PrivateKey::random(&mut OsRng);
+                    (inputs!(PublicKey::from_secret_key(&key)), key)
+                };
+            let uo = UnblindedOutput::new(
+                output.version,
+                rewound.value,
+                rewound.blinding_factor,
+                output.features,
+                output.script,
+                input_data,
+                script_key,
+                output.sender_offset_public_key,
+                output.metadata_signature,
+                0,
+                output.covenant,
+                output.encrypted_value,
+                output.minimum_value_promise,
             );
-            if let Ok(committed_value) = committed_value {
-                let blinding_factor =
-                    output.recover_mask(&self.factories.range_proof, &self.rewind_data.rewind_blinding_key)?;
-                if output.verify_mask(&self.factories.range_proof, &blinding_factor, committed_value.into())? {
-                    let (input_data, script_key) = if let Some(index) = known_script_index {
-                        (
-                            known_scripts[index].input.clone(),
-                            known_scripts[index].private_key.clone(),
-                        )
-                    } else {
-                        let key = PrivateKey::random(&mut OsRng);
-                        (inputs!(PublicKey::from_secret_key(&key)), key)
-                    };
-                    let uo = UnblindedOutput::new(
-                        output.version,
-                        committed_value,
-                        blinding_factor,
-                        output.features,
-                        output.script,
-                        input_data,
-                        script_key,
-                        output.sender_offset_public_key,
-                        output.metadata_signature,
-                        0,
-                        output.covenant,
-                        output.encrypted_value,
-                        output.minimum_value_promise,
-                    );
-                    rewound_outputs.push((uo, output.proof));
-                }
-            }
+            rewound_outputs.push((uo, output.proof));
         }
 
         let rewind_time = start.elapsed();
@@ -176,6 +205,8 @@ where
             rewound_outputs_with_tx_id.push(RecoveredOutput {
                 output: output.clone(),
                 tx_id,
+                source: output_source,
+                memo: None,
             });
             self.update_outputs_script_private_key_and_update_key_manager_index(output)
                 .await?;
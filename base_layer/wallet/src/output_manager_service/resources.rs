@@ -56,6 +56,7 @@ pub enum OutputManagerKeyManagerBranch {
     RecoveryBlinding,
     ContractIssuer,
     ValueEncryption,
+    DepositIdentity,
 }
 
 impl OutputManagerKeyManagerBranch {
@@ -70,6 +71,7 @@ impl OutputManagerKeyManagerBranch {
             OutputManagerKeyManagerBranch::RecoveryBlinding => "recovery_blinding".to_string(),
             OutputManagerKeyManagerBranch::ContractIssuer => "contract_issuer".to_string(),
             OutputManagerKeyManagerBranch::ValueEncryption => "value_encryption".to_string(),
+            OutputManagerKeyManagerBranch::DepositIdentity => "deposit_identity".to_string(),
         }
     }
 }
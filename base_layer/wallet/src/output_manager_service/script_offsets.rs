@@ -0,0 +1,232 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Auditing of the script offsets of the transactions of the wallet, e.g. to check a transaction that is rebuilt after
+//! part of the database was lost. The script offset of a transaction is the sum of the script private keys of its
+//! inputs less the sum of the sender offset private keys of its outputs. The wallet keeps the script private key of
+//! every output it owns, but the sender offset private keys are random and discarded once the outputs are signed, so
+//! only the part of the offset that the inputs contribute can be computed from the records, and a transaction is
+//! checked against them with public keys.
+
+use std::collections::BTreeMap;
+
+use tari_common_types::{
+    transaction::TxId,
+    types::{Commitment, PrivateKey, PublicKey},
+};
+use tari_core::transactions::transaction_components::Transaction;
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+use crate::output_manager_service::{
+    error::{OutputManagerError, OutputManagerStorageError},
+    storage::models::DbUnblindedOutput,
+};
+
+/// The script key of an output of the wallet that was spent in a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptKeyRecord {
+    pub commitment: Commitment,
+    pub script_private_key: PrivateKey,
+}
+
+/// The script keys of the outputs of the wallet that a transaction spent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionScriptKeys {
+    pub tx_id: TxId,
+    pub inputs: Vec<ScriptKeyRecord>,
+    /// The sum of the script private keys of the inputs. The script offset of the transaction is this less the sum of
+    /// the sender offset private keys of its outputs.
+    pub input_script_offset: PrivateKey,
+}
+
+/// The result of checking the script offset of a transaction against the records of the wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptOffsetAudit {
+    /// The inputs of the transaction that spend outputs of the wallet
+    pub known_inputs: Vec<Commitment>,
+    /// The known inputs whose script does not yield the script key on record
+    pub mismatched_inputs: Vec<Commitment>,
+    /// The outputs of the wallet in the transaction whose sender offset public key is not the one on record
+    pub mismatched_outputs: Vec<Commitment>,
+    /// True if the script offset of the transaction is the sum of the script keys of its inputs, taken from the
+    /// records for the known inputs, less the sum of the sender offset public keys of its outputs
+    pub is_balanced: bool,
+}
+
+impl ScriptOffsetAudit {
+    pub fn is_valid(&self) -> bool {
+        self.is_balanced && self.mismatched_inputs.is_empty() && self.mismatched_outputs.is_empty()
+    }
+}
+
+/// Groups the spent outputs by the transaction that spent them, ordered by transaction id
+pub fn transaction_script_keys<I>(spent_outputs: I) -> Vec<TransactionScriptKeys>
+where I: IntoIterator<Item = (TxId, DbUnblindedOutput)> {
+    let mut transactions = BTreeMap::<u64, TransactionScriptKeys>::new();
+    for (tx_id, output) in spent_outputs {
+        let transaction = transactions
+            .entry(tx_id.as_u64())
+            .or_insert_with(|| TransactionScriptKeys {
+                tx_id,
+                inputs: Vec::new(),
+                input_script_offset: PrivateKey::default(),
+            });
+        let script_private_key = output.unblinded_output.script_private_key;
+        transaction.input_script_offset = transaction.input_script_offset.clone() + script_private_key.clone();
+        transaction.inputs.push(ScriptKeyRecord {
+            commitment: output.commitment,
+            script_private_key,
+        });
+    }
+    transactions.into_values().collect()
+}
+
+/// Checks the script offset of `transaction`, using `find_output` to look up the outputs of the wallet by commitment.
+/// The script keys of the inputs that the wallet does not know are obtained by running their scripts, which fails for
+/// a script that needs the context of a block.
+pub fn audit_script_offset<F>(
+    transaction: &Transaction,
+    mut find_output: F,
+) -> Result<ScriptOffsetAudit, OutputManagerError>
+where
+    F: FnMut(&Commitment) -> Result<Option<DbUnblindedOutput>, OutputManagerStorageError>,
+{
+    let mut known_inputs = Vec::new();
+    let mut mismatched_inputs = Vec::new();
+    let mut input_keys = PublicKey::default();
+    for input in transaction.body.inputs() {
+        let commitment = input.commitment()?;
+        match find_output(commitment)? {
+            Some(output) => {
+                let script_public_key = PublicKey::from_secret_key(&output.unblinded_output.script_private_key);
+                if input.run_script(None).ok().as_ref() != Some(&script_public_key) {
+                    mismatched_inputs.push(commitment.clone());
+                }
+                known_inputs.push(commitment.clone());
+                input_keys = input_keys + script_public_key;
+            },
+            None => input_keys = input_keys + input.run_script(None)?,
+        }
+    }
+
+    let mut mismatched_outputs = Vec::new();
+    let mut output_keys = PublicKey::default();
+    for output in transaction.body.outputs() {
+        // Coinbase outputs do not count towards the script offset
+        if output.is_coinbase() {
+            continue;
+        }
+        if let Some(record) = find_output(&output.commitment)? {
+            if record.unblinded_output.sender_offset_public_key != output.sender_offset_public_key {
+                mismatched_outputs.push(output.commitment.clone());
+            }
+        }
+        output_keys = output_keys + output.sender_offset_public_key.clone();
+    }
+
+    Ok(ScriptOffsetAudit {
+        known_inputs,
+        mismatched_inputs,
+        mismatched_outputs,
+        is_balanced: input_keys - output_keys == PublicKey::from_secret_key(&transaction.script_offset),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use tari_core::transactions::{
+        test_helpers::TestParams,
+        transaction_components::{TransactionInput, TransactionOutput},
+        CryptoFactories,
+    };
+
+    use super::*;
+    use crate::output_manager_service::storage::OutputSource;
+
+    struct Party {
+        params: TestParams,
+        output: DbUnblindedOutput,
+    }
+
+    fn party(factories: &CryptoFactories) -> Party {
+        let params = TestParams::new();
+        let output = params.create_unblinded_output(Default::default());
+        let output = DbUnblindedOutput::from_unblinded_output(output, factories, None, OutputSource::Standard).unwrap();
+        Party { params, output }
+    }
+
+    fn input(party: &Party, factories: &CryptoFactories) -> TransactionInput {
+        party
+            .output
+            .unblinded_output
+            .as_transaction_input(&factories.commitment)
+            .unwrap()
+    }
+
+    fn output(party: &Party, factories: &CryptoFactories) -> TransactionOutput {
+        party.output.unblinded_output.as_transaction_output(factories).unwrap()
+    }
+
+    #[test]
+    fn it_sums_the_script_keys_of_each_transaction() {
+        let factories = CryptoFactories::default();
+        let (a, b, c) = (party(&factories), party(&factories), party(&factories));
+        let records = transaction_script_keys(vec![
+            (TxId::from(2u64), b.output.clone()),
+            (TxId::from(1u64), a.output.clone()),
+            (TxId::from(2u64), c.output.clone()),
+        ]);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].tx_id, TxId::from(1u64));
+        assert_eq!(records[0].input_script_offset, a.params.script_private_key);
+        assert_eq!(records[1].tx_id, TxId::from(2u64));
+        assert_eq!(records[1].inputs.len(), 2);
+        assert_eq!(records[1].inputs[0].commitment, b.output.commitment);
+        assert_eq!(
+            records[1].input_script_offset,
+            b.params.script_private_key.clone() + c.params.script_private_key.clone()
+        );
+    }
+
+    #[test]
+    fn it_audits_the_script_offset_of_a_transaction() {
+        let factories = CryptoFactories::default();
+        // The wallet spends an output of its own and receives change, while the recipient output is not known to it
+        let (spent, change, recipient) = (party(&factories), party(&factories), party(&factories));
+        let script_offset = spent.params.script_private_key.clone() -
+            change.params.sender_offset_private_key.clone() -
+            recipient.params.sender_offset_private_key.clone();
+        let mut transaction = Transaction::new(
+            vec![input(&spent, &factories)],
+            vec![output(&change, &factories), output(&recipient, &factories)],
+            vec![],
+            PrivateKey::default(),
+            script_offset,
+        );
+        let mut records = HashMap::new();
+        records.insert(spent.output.commitment.clone(), spent.output.clone());
+        records.insert(change.output.commitment.clone(), change.output.clone());
+
+        let audit = audit_script_offset(&transaction, |commitment| Ok(records.get(commitment).cloned())).unwrap();
+        assert!(audit.is_valid());
+        assert_eq!(audit.known_inputs, vec![spent.output.commitment.clone()]);
+
+        // A record with another script key no longer matches the input
+        let mut corrupted = spent.output.clone();
+        corrupted.unblinded_output.script_private_key = change.params.script_private_key.clone();
+        records.insert(spent.output.commitment.clone(), corrupted);
+        let audit = audit_script_offset(&transaction, |commitment| Ok(records.get(commitment).cloned())).unwrap();
+        assert_eq!(audit.mismatched_inputs, vec![spent.output.commitment.clone()]);
+        assert!(!audit.is_balanced);
+        records.insert(spent.output.commitment.clone(), spent.output.clone());
+
+        transaction.script_offset = PrivateKey::default();
+        let audit = audit_script_offset(&transaction, |commitment| Ok(records.get(commitment).cloned())).unwrap();
+        assert!(audit.mismatched_inputs.is_empty());
+        assert!(!audit.is_balanced);
+        assert!(!audit.is_valid());
+    }
+}
@@ -20,17 +20,17 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryInto, fmt, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, fmt, sync::Arc};
 
+use chrono::Utc;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use futures::{pin_mut, StreamExt};
 use itertools::Itertools;
-use log::*;
 use rand::{rngs::OsRng, RngCore};
 use strum::IntoEnumIterator;
 use tari_common_types::{
     transaction::TxId,
-    types::{BlockHash, Commitment, HashOutput, PrivateKey, PublicKey},
+    types::{BlockHash, Commitment, FixedHash, HashOutput, PrivateKey, PublicKey},
 };
 use tari_comms::{types::CommsPublicKey, NodeIdentity};
 use tari_core::{
@@ -41,9 +41,12 @@ use tari_core::{
         fee::Fee,
         tari_amount::MicroTari,
         transaction_components::{
+            EncryptedMemo,
             EncryptedValue,
             KernelFeatures,
             OutputFeatures,
+            OutputType,
+            TokenRegistration,
             Transaction,
             TransactionError,
             TransactionInput,
@@ -51,8 +54,15 @@ use tari_core::{
             TransactionOutputVersion,
             UnblindedOutput,
             UnblindedOutputBuilder,
+            UniqueAsset,
+        },
+        transaction_protocol::{
+            sanitizer,
+            sender::TransactionSenderMessage,
+            transaction_initializer::SenderTransactionInitializer,
+            RewindData,
+            TransactionMetadata,
         },
-        transaction_protocol::{sender::TransactionSenderMessage, RewindData, TransactionMetadata},
         CoinbaseBuilder,
         CryptoFactories,
         ReceiverTransactionProtocol,
@@ -62,20 +72,25 @@ use tari_core::{
 use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
     errors::RangeProofError,
+    hash::blake2::Blake256,
     keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait, SecretKey},
     ristretto::RistrettoSecretKey,
 };
-use tari_script::{inputs, script, Opcode, TariScript};
+use tari_script::{inputs, script, ExecutionStack, Opcode, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::{hex::Hex, ByteArray};
+use tokio::time::{self, MissedTickBehavior};
+use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::{
-    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle, ChainEvent},
     connectivity_service::WalletConnectivityInterface,
+    error::ClassifyError,
     key_manager_service::KeyManagerInterface,
     output_manager_service::{
         config::OutputManagerServiceConfig,
+        deposits::{one_sided_payment_key, Deposit, DepositIdentity},
         error::{OutputManagerError, OutputManagerProtocolError, OutputManagerStorageError},
         handle::{
             OutputManagerEvent,
@@ -86,8 +101,10 @@ use crate::{
             RecoveredOutput,
         },
         input_selection::UtxoSelectionCriteria,
-        recovery::StandardUtxoRecoverer,
+        recovery::{rewind_outputs, KeyIndexRecoverer, StandardUtxoRecoverer},
         resources::{OutputManagerKeyManagerBranch, OutputManagerResources},
+        script_offsets::{audit_script_offset, transaction_script_keys, ScriptOffsetAudit, TransactionScriptKeys},
+        spendability::{ListedOutput, OutputSpendability},
         storage::{
             database::{OutputBackendQuery, OutputManagerBackend, OutputManagerDatabase},
             models::{DbUnblindedOutput, KnownOneSidedPaymentScript, SpendingPriority},
@@ -95,8 +112,12 @@ use crate::{
             OutputStatus,
         },
         tasks::TxoValidationTask,
+        tokens::{token_balances, TokenBalance},
+        unique_assets::OwnedAsset,
+        vault::{ExpiringVaultScript, VaultOutput, VaultScript},
     },
     types::WalletHasher,
+    util::{cancellation::CancellationToken, scheduler::PrioritizedRequests, watch::Watch},
     WalletSecretKeysDomainHasher,
 };
 
@@ -113,6 +134,8 @@ pub struct OutputManagerService<TBackend, TWalletConnectivity, TKeyManagerInterf
     base_node_service: BaseNodeServiceHandle,
     last_seen_tip_height: Option<u64>,
     node_identity: Arc<NodeIdentity>,
+    balance_watch: Watch<Option<Balance>>,
+    balance_stale: bool,
 }
 
 impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
@@ -130,6 +153,7 @@ where
         >,
         db: OutputManagerDatabase<TBackend>,
         event_publisher: OutputManagerEventSender,
+        balance_watch: Watch<Option<Balance>>,
         factories: CryptoFactories,
         consensus_constants: ConsensusConstants,
         shutdown_signal: ShutdownSignal,
@@ -139,16 +163,7 @@ where
         key_manager: TKeyManagerInterface,
     ) -> Result<Self, OutputManagerError> {
         Self::initialise_key_manager(&key_manager).await?;
-        let rewind_blinding_key = key_manager
-            .get_key_at_index(OutputManagerKeyManagerBranch::RecoveryBlinding.get_branch_key(), 0)
-            .await?;
-        let encryption_key = key_manager
-            .get_key_at_index(OutputManagerKeyManagerBranch::ValueEncryption.get_branch_key(), 0)
-            .await?;
-        let rewind_data = RewindData {
-            rewind_blinding_key,
-            encryption_key,
-        };
+        let rewind_data = Self::derive_rewind_data(&key_manager).await?;
 
         let resources = OutputManagerResources {
             config,
@@ -168,6 +183,8 @@ where
             base_node_service,
             last_seen_tip_height: None,
             node_identity,
+            balance_watch,
+            balance_stale: true,
         })
     }
 
@@ -178,6 +195,28 @@ where
         Ok(())
     }
 
+    async fn derive_rewind_data(key_manager: &TKeyManagerInterface) -> Result<RewindData, OutputManagerError> {
+        let rewind_blinding_key = key_manager
+            .get_key_at_index(OutputManagerKeyManagerBranch::RecoveryBlinding.get_branch_key(), 0)
+            .await?;
+        let encryption_key = key_manager
+            .get_key_at_index(OutputManagerKeyManagerBranch::ValueEncryption.get_branch_key(), 0)
+            .await?;
+        Ok(RewindData {
+            rewind_blinding_key,
+            encryption_key,
+        })
+    }
+
+    /// Rotates the key manager to a new key epoch and switches to the rewind keys of that epoch, so that outputs
+    /// created from now on cannot be rewound with the keys of the compromised epoch
+    async fn rotate_key_epoch(&mut self) -> Result<u64, OutputManagerError> {
+        let epoch = self.resources.master_key_manager.rotate_key_epoch().await?;
+        self.resources.rewind_data = Self::derive_rewind_data(&self.resources.master_key_manager).await?;
+        info!(target: LOG_TARGET, "Output manager switched to key epoch {}", epoch);
+        Ok(epoch)
+    }
+
     /// Return the public rewind keys
     pub fn get_rewind_public_keys(&self) -> PublicRewindKeys {
         PublicRewindKeys {
@@ -192,23 +231,55 @@ where
             .expect("OutputManagerService initialized without request_stream")
             .fuse();
         pin_mut!(request_stream);
+        // Requests that the user is waiting for are handled before queued background work
+        let mut request_stream = PrioritizedRequests::new(request_stream);
 
         let mut shutdown = self.resources.shutdown_signal.clone();
 
+        if let Err(e) = self.resources.db.warm_up_cache() {
+            warn!(
+                target: LOG_TARGET,
+                "Could not load the unspent outputs into the cache: {:?}", e
+            );
+        }
+
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
+        let mut chain_event_stream = self.base_node_service.subscribe_chain_events();
+        // Validation tasks update the outputs outside of the request handler, they report back with events
+        let mut output_manager_event_stream = self.resources.event_publisher.subscribe();
+        let mut balance_refresh = time::interval(self.resources.config.balance_refresh_interval);
+        balance_refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut balance_recount = time::interval(self.resources.config.balance_recount_interval);
+        balance_recount.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
         debug!(target: LOG_TARGET, "Output Manager Service started");
         loop {
             tokio::select! {
+                _ = balance_refresh.tick(), if self.balance_stale => self.publish_balance().await,
+                _ = balance_recount.tick() => self.recount_balance_totals(),
+                event = output_manager_event_stream.recv() => {
+                    if let Err(e) = event {
+                        debug!(target: LOG_TARGET, "Lagging read on output manager event broadcast channel: {}", e);
+                    }
+                    self.balance_stale = true;
+                },
                 event = base_node_service_event_stream.recv() => {
                     match event {
                         Ok(msg) => self.handle_base_node_service_event(msg),
                         Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
                     }
                 },
+                event = chain_event_stream.recv() => {
+                    match event {
+                        Ok(msg) => self.handle_chain_event(msg),
+                        Err(e) => debug!(target: LOG_TARGET, "Lagging read on chain event broadcast channel: {}", e),
+                    }
+                },
                 Some(request_context) = request_stream.next() => {
                 trace!(target: LOG_TARGET, "Handling Service API Request");
                     let (request, reply_tx) = request_context.split();
+                    // Any request other than a balance query may change the outputs
+                    self.balance_stale |= !matches!(request, OutputManagerRequest::GetBalance);
                     let response = self.handle_request(request).await.map_err(|e| {
                         warn!(target: LOG_TARGET, "Error handling request: {:?}", e);
                         e
@@ -230,6 +301,12 @@ where
 
     /// This handler is called when the Service executor loops receives an API request
     #[allow(clippy::too_many_lines)]
+    #[instrument(
+        level = "debug",
+        name = "output_manager_service::request",
+        skip_all,
+        fields(service = "output_manager_service", request = %request)
+    )]
     async fn handle_request(
         &mut self,
         request: OutputManagerRequest,
@@ -272,8 +349,8 @@ where
                 .get_recipient_transaction(tsm)
                 .await
                 .map(OutputManagerResponse::RecipientTransactionGenerated),
-            OutputManagerRequest::GetCoinbaseTransaction((tx_id, reward, fees, block_height)) => self
-                .get_coinbase_transaction(tx_id, reward, fees, block_height)
+            OutputManagerRequest::GetCoinbaseTransaction((tx_id, reward, fees, block_height, extra)) => self
+                .get_coinbase_transaction(tx_id, reward, fees, block_height, extra)
                 .await
                 .map(OutputManagerResponse::CoinbaseTransaction),
             OutputManagerRequest::PrepareToSendTransaction {
@@ -287,6 +364,7 @@ where
                 script,
                 covenant,
                 minimum_value_promise,
+                cancellation,
             } => self
                 .prepare_transaction_to_send(
                     tx_id,
@@ -299,6 +377,7 @@ where
                     script,
                     covenant,
                     minimum_value_promise,
+                    &cancellation,
                 )
                 .await
                 .map(OutputManagerResponse::TransactionToSend),
@@ -345,6 +424,7 @@ where
                 let outputs = self.fetch_unspent_outputs()?.into_iter().map(|v| v.into()).collect();
                 Ok(OutputManagerResponse::UnspentOutputs(outputs))
             },
+            OutputManagerRequest::ListOutputs => Ok(OutputManagerResponse::ListedOutputs(self.list_outputs().await?)),
             OutputManagerRequest::GetOutputsBy(q) => {
                 let outputs = self.fetch_outputs_by(q)?.into_iter().map(|v| v.into()).collect();
                 Ok(OutputManagerResponse::Outputs(outputs))
@@ -365,6 +445,13 @@ where
                         .await?,
                 ))
             },
+            OutputManagerRequest::PreviewSweepAll {
+                fee_per_gram,
+                output_features,
+                script,
+            } => Ok(OutputManagerResponse::SweepPreview(
+                self.preview_sweep_all(fee_per_gram, *output_features, script).await?,
+            )),
             OutputManagerRequest::PreviewCoinSplitEven((commitments, number_of_splits, fee_per_gram)) => {
                 Ok(OutputManagerResponse::CoinPreview(
                     self.preview_coin_split_with_commitments_no_amount(commitments, number_of_splits, fee_per_gram)
@@ -417,6 +504,10 @@ where
                 .remove_encryption()
                 .map(|_| OutputManagerResponse::EncryptionRemoved)
                 .map_err(OutputManagerError::OutputManagerStorageError),
+            OutputManagerRequest::RotateKeyEpoch => self
+                .rotate_key_epoch()
+                .await
+                .map(OutputManagerResponse::KeyEpochRotated),
 
             OutputManagerRequest::ScanForRecoverableOutputs(outputs) => StandardUtxoRecoverer::new(
                 self.resources.master_key_manager.clone(),
@@ -427,6 +518,17 @@ where
             .scan_and_recover_outputs(outputs)
             .await
             .map(OutputManagerResponse::RewoundOutputs),
+            OutputManagerRequest::RewindOutputs(outputs) => {
+                rewind_outputs(outputs, &self.resources.rewind_data, &self.resources.factories)
+                    .await
+                    .map(OutputManagerResponse::OutputsRewound)
+            },
+            OutputManagerRequest::RestoreKeyIndices { gap_limit } => {
+                KeyIndexRecoverer::new(self.resources.master_key_manager.clone(), self.resources.db.clone())
+                    .restore_key_indices(gap_limit)
+                    .await
+                    .map(OutputManagerResponse::KeyIndicesRestored)
+            },
             OutputManagerRequest::ScanOutputs(outputs) => self
                 .scan_outputs_for_one_sided_payments(outputs)
                 .map(OutputManagerResponse::ScanOutputs),
@@ -458,6 +560,9 @@ where
             OutputManagerRequest::SetCoinbaseAbandoned(tx_id, abandoned) => self
                 .set_coinbase_abandoned(tx_id, abandoned)
                 .map(|_| OutputManagerResponse::CoinbaseAbandonedSet),
+            OutputManagerRequest::GetCoinbaseStats => Ok(OutputManagerResponse::CoinbaseStats(
+                self.resources.db.get_coinbase_stats()?,
+            )),
             OutputManagerRequest::CreateClaimShaAtomicSwapTransaction(output_hash, pre_image, fee_per_gram) => {
                 self.claim_sha_atomic_swap_with_hash(output_hash, pre_image, fee_per_gram)
                     .await
@@ -470,6 +575,95 @@ where
                 let output_statuses_by_tx_id = self.get_output_status_by_tx_id(tx_id)?;
                 Ok(OutputManagerResponse::OutputStatusesByTxId(output_statuses_by_tx_id))
             },
+            OutputManagerRequest::CreateVaultTransaction {
+                tx_id,
+                amount,
+                recovery_key,
+                recovery_delay,
+                unlock_height,
+                fee_per_gram,
+                message,
+            } => self
+                .create_vault_transaction(
+                    tx_id,
+                    amount,
+                    *recovery_key,
+                    recovery_delay,
+                    unlock_height,
+                    fee_per_gram,
+                    message,
+                )
+                .await
+                .map(OutputManagerResponse::PayToSelfTransaction),
+            OutputManagerRequest::GetVaultOutputs => self.get_vault_outputs().map(OutputManagerResponse::VaultOutputs),
+            OutputManagerRequest::CreateVaultRecoveryTransaction(output, recovery_key, fee_per_gram) => self
+                .create_vault_recovery_transaction(output, *recovery_key, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::VaultRecoveryTransaction),
+            OutputManagerRequest::CreateInheritanceTransaction {
+                beneficiaries,
+                lock_height,
+                fee_per_gram,
+            } => self
+                .create_inheritance_transaction(beneficiaries, lock_height, fee_per_gram)
+                .await
+                .map(|tx| OutputManagerResponse::InheritanceTransaction(Box::new(tx))),
+            OutputManagerRequest::CreateTokenRegistrationTransaction {
+                tx_id,
+                amount,
+                symbol,
+                total_supply,
+                fee_per_gram,
+                message,
+            } => self
+                .create_token_registration_transaction(tx_id, amount, symbol, total_supply, fee_per_gram, message)
+                .await
+                .map(OutputManagerResponse::PayToSelfTransaction),
+            OutputManagerRequest::CreateUniqueAssetMintTransaction {
+                tx_id,
+                amount,
+                unique_id,
+                fee_per_gram,
+                message,
+            } => self
+                .create_unique_asset_mint_transaction(tx_id, amount, unique_id, fee_per_gram, message)
+                .await
+                .map(OutputManagerResponse::PayToSelfTransaction),
+            OutputManagerRequest::GetTokenBalances => {
+                self.get_token_balances().map(OutputManagerResponse::TokenBalances)
+            },
+            OutputManagerRequest::GetOwnedAssets => self.get_owned_assets().map(OutputManagerResponse::OwnedAssets),
+            OutputManagerRequest::CreateDepositIdentity(label) => self
+                .create_deposit_identity(label)
+                .await
+                .map(OutputManagerResponse::DepositIdentityCreated),
+            OutputManagerRequest::GetDepositIdentities => self
+                .resources
+                .db
+                .fetch_deposit_identities()
+                .map(OutputManagerResponse::DepositIdentities)
+                .map_err(OutputManagerError::from),
+            OutputManagerRequest::GetDeposits(key_index) => self
+                .resources
+                .db
+                .fetch_deposits(key_index)
+                .map(OutputManagerResponse::Deposits)
+                .map_err(OutputManagerError::from),
+            OutputManagerRequest::GetScriptKeys => self.get_script_keys().map(OutputManagerResponse::ScriptKeys),
+            OutputManagerRequest::AuditScriptOffset(transaction) => self
+                .audit_script_offset(&transaction)
+                .map(OutputManagerResponse::ScriptOffsetAudit),
+            OutputManagerRequest::SetConfig(config) => {
+                self.resources.config = *config;
+                Ok(OutputManagerResponse::ConfigSet)
+            },
+            OutputManagerRequest::PreviewUniqueAssetTransfer {
+                asset_id,
+                fee_per_gram,
+                recipient_script,
+            } => self
+                .preview_unique_asset_transfer(asset_id, fee_per_gram, &recipient_script)
+                .map(|preview| OutputManagerResponse::UniqueAssetTransferPreview(Box::new(preview))),
         }
     }
 
@@ -527,8 +721,29 @@ where
                     });
                 }
                 self.last_seen_tip_height = state.chain_metadata.map(|cm| cm.height_of_longest_chain());
+                // The time-locked balance depends on the tip
+                self.balance_stale = true;
             },
-            BaseNodeEvent::NewBlockDetected(_) => {},
+            BaseNodeEvent::NewBlockDetected(_) |
+            BaseNodeEvent::BaseNodeIdentityChanged(_) |
+            BaseNodeEvent::ChainSplitSuspected(_) |
+            BaseNodeEvent::ChainSplitResolved |
+            BaseNodeEvent::ClockSkewDetected(_) |
+            BaseNodeEvent::ClockSkewResolved => {},
+        }
+    }
+
+    /// A reorg does not always change the tip height, so outputs are revalidated as soon as one is observed
+    fn handle_chain_event(&mut self, event: Arc<ChainEvent>) {
+        if let ChainEvent::Reorged { from, to } = *event {
+            debug!(
+                target: LOG_TARGET,
+                "Revalidating outputs after a reorg from height {} to {}", from, to
+            );
+            let _id = self.validate_outputs().map_err(|e| {
+                warn!(target: LOG_TARGET, "Error validating  txos: {:?}", e);
+                e
+            });
         }
     }
 
@@ -683,6 +898,61 @@ where
         Ok(())
     }
 
+    /// Sets up the change output of a transaction that only has outputs for other recipients. The change is kept by
+    /// this wallet, or sent to a one-sided stealth address of the cold storage wallet if one is configured so that the
+    /// cold wallet finds it when scanning for one-sided payments. Returns the rewind data and source of the change
+    /// output.
+    async fn add_change_output_to_builder(
+        &mut self,
+        builder: &mut SenderTransactionInitializer,
+    ) -> Result<(RewindData, OutputSource), OutputManagerError> {
+        let cold_storage_public_key = match self.resources.config.cold_storage_public_key.as_ref() {
+            Some(public_key) => public_key,
+            None => {
+                let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
+                builder.with_change_secret(spending_key);
+                builder.with_rewindable_outputs(self.resources.rewind_data.clone());
+                builder.with_change_script(
+                    script!(Nop),
+                    inputs!(PublicKey::from_secret_key(&script_private_key)),
+                    script_private_key,
+                );
+                return Ok((self.resources.rewind_data.clone(), OutputSource::default()));
+            },
+        };
+
+        // Stealth address `Ks = c⋅G + K` with `c = H(r⋅K)`, which only the owner of the cold storage key can spend
+        let (nonce_private_key, nonce_public_key) = PublicKey::random_keypair(&mut OsRng);
+        let c = WalletHasher::new_with_label("stealth_address")
+            .chain(PublicKey::shared_secret(&nonce_private_key, cold_storage_public_key).as_bytes())
+            .finalize();
+        let script_spending_key =
+            PublicKey::from_secret_key(&PrivateKey::from_bytes(c.as_ref())?) + cold_storage_public_key.clone();
+
+        // The cold wallet derives the rewind keys from the Diffie-Hellman shared secret with the sender offset key
+        let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+        let shared_secret_key = PrivateKey::from_bytes(
+            CommsPublicKey::shared_secret(&sender_offset_private_key, cold_storage_public_key).as_bytes(),
+        )?;
+        let rewind_blinding_key = PrivateKey::from_bytes(&hash_secret_key(&shared_secret_key))?;
+        let encryption_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_blinding_key))?;
+        let rewind_data = RewindData {
+            rewind_blinding_key,
+            encryption_key,
+        };
+
+        builder.with_change_secret(PrivateKey::random(&mut OsRng));
+        builder.with_change_sender_offset_private_key(sender_offset_private_key);
+        builder.with_rewindable_outputs(rewind_data.clone());
+        // The script private key is only known to the cold wallet
+        builder.with_change_script(
+            script!(PushPubKey(Box::new(nonce_public_key)) Drop PushPubKey(Box::new(script_spending_key))),
+            ExecutionStack::default(),
+            PrivateKey::default(),
+        );
+        Ok((rewind_data, OutputSource::ColdStorageChange))
+    }
+
     async fn get_spend_and_script_keys(&self) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
         let result = self
             .resources
@@ -723,6 +993,47 @@ where
         Ok(balance)
     }
 
+    /// Verifies the running totals that the balance is read from against a recount over all the outputs, and has the
+    /// corrected balance published if any of them drifted
+    fn recount_balance_totals(&mut self) {
+        match self.resources.db.recount_balance_totals() {
+            Ok(drifts) if drifts.is_empty() => trace!(target: LOG_TARGET, "The balance totals are consistent"),
+            Ok(drifts) => {
+                for drift in drifts {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Corrected the balance total of {:?} outputs from source {:?} from {} to {}",
+                        drift.status,
+                        drift.source,
+                        drift.recorded,
+                        drift.recounted
+                    );
+                }
+                self.balance_stale = true;
+            },
+            Err(e) => warn!(target: LOG_TARGET, "Could not recount the balance totals: {:?}", e),
+        }
+    }
+
+    /// Publishes the balance to the subscribers of balance changes if it differs from the last published balance
+    async fn publish_balance(&mut self) {
+        self.balance_stale = false;
+        let current_tip_for_time_lock_calculation = match self.base_node_service.get_chain_metadata().await {
+            Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
+            Err(_) => None,
+        };
+        match self.get_balance(current_tip_for_time_lock_calculation) {
+            Ok(balance) => {
+                let changed = self.balance_watch.borrow().as_ref() != Some(&balance);
+                if changed {
+                    debug!(target: LOG_TARGET, "Balance changed: {:?}", balance);
+                    self.balance_watch.send(Some(balance));
+                }
+            },
+            Err(e) => warn!(target: LOG_TARGET, "Could not compute the balance: {:?}", e),
+        }
+    }
+
     /// Request a receiver transaction be generated from the supplied Sender Message
     async fn get_recipient_transaction(
         &mut self,
@@ -854,6 +1165,7 @@ where
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
     /// will be produced.
     #[allow(clippy::too_many_lines)]
+    #[instrument(skip_all, fields(service = "output_manager_service", tx_id = %tx_id))]
     pub async fn prepare_transaction_to_send(
         &mut self,
         tx_id: TxId,
@@ -866,6 +1178,7 @@ where
         recipient_script: TariScript,
         recipient_covenant: Covenant,
         recipient_minimum_value_promise: MicroTari,
+        cancellation: &CancellationToken,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         debug!(
             target: LOG_TARGET,
@@ -926,16 +1239,11 @@ where
             input_selection.num_selected()
         );
 
-        if input_selection.requires_change_output() {
-            let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
-            builder.with_change_secret(spending_key);
-            builder.with_rewindable_outputs(self.resources.rewind_data.clone());
-            builder.with_change_script(
-                script!(Nop),
-                inputs!(PublicKey::from_secret_key(&script_private_key)),
-                script_private_key,
-            );
-        }
+        let change_rewind_data = if input_selection.requires_change_output() {
+            Some(self.add_change_output_to_builder(&mut builder).await?)
+        } else {
+            None
+        };
 
         let stp = builder
             .build(
@@ -947,7 +1255,7 @@ where
 
         // If a change output was created add it to the pending_outputs list.
         let mut change_output = Vec::<DbUnblindedOutput>::new();
-        if input_selection.requires_change_output() {
+        if let Some((rewind_data, source)) = change_rewind_data {
             let unblinded_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
                 OutputManagerError::BuildError(
                     "There should be a change output metadata signature available".to_string(),
@@ -956,13 +1264,22 @@ where
             change_output.push(DbUnblindedOutput::rewindable_from_unblinded_output(
                 unblinded_output,
                 &self.resources.factories,
-                &self.resources.rewind_data.clone(),
+                &rewind_data,
                 None,
                 None,
-                OutputSource::default(),
+                source,
             )?);
         }
 
+        // A send that was cancelled while it was being built never encumbers its inputs
+        if cancellation.is_cancelled() {
+            debug!(
+                target: LOG_TARGET,
+                "Preparation of transaction (TxId: {}) cancelled", tx_id
+            );
+            return Err(OutputManagerError::TransactionPreparationCancelled);
+        }
+
         // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
         // store them until the transaction times out OR is confirmed
         self.resources
@@ -978,12 +1295,14 @@ where
     /// the corresponding output hash will be cancelled.
     /// The key will be derived from the coinbase specific keychain using the blockheight as an index. The coinbase
     /// keychain is based on the wallets master_key and the "coinbase" branch.
+    #[instrument(skip_all, fields(service = "output_manager_service", tx_id = %tx_id))]
     async fn get_coinbase_transaction(
         &mut self,
         tx_id: TxId,
         reward: MicroTari,
         fees: MicroTari,
         block_height: u64,
+        extra: Vec<u8>,
     ) -> Result<Transaction, OutputManagerError> {
         debug!(
             target: LOG_TARGET,
@@ -1013,6 +1332,7 @@ where
             .with_script(script!(Nop))
             .with_nonce(nonce)
             .with_rewind_data(self.resources.rewind_data.clone())
+            .with_extra(extra)
             .build_with_reward(&self.resources.consensus_constants, reward)?;
 
         let output = DbUnblindedOutput::rewindable_from_unblinded_output(
@@ -1204,6 +1524,7 @@ where
     }
 
     #[allow(clippy::too_many_lines)]
+    #[instrument(skip_all, fields(service = "output_manager_service", tx_id = %tx_id))]
     async fn create_pay_to_self_transaction(
         &mut self,
         tx_id: TxId,
@@ -1358,6 +1679,7 @@ where
 
     /// Confirm that a transaction has finished being negotiated between parties so the short-term encumberance can be
     /// made official
+    #[instrument(skip_all, fields(service = "output_manager_service", tx_id = %tx_id))]
     fn confirm_encumberance(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
         self.resources.db.confirm_encumbered_outputs(tx_id)?;
 
@@ -1365,6 +1687,7 @@ where
     }
 
     /// Cancel a pending transaction and place the encumbered outputs back into the unspent pool
+    #[instrument(skip_all, fields(service = "output_manager_service", tx_id = %tx_id))]
     pub fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
         debug!(
             target: LOG_TARGET,
@@ -1375,6 +1698,7 @@ where
 
     /// Restore the pending transaction encumberance and output for an inbound transaction that was previously
     /// cancelled.
+    #[instrument(skip_all, fields(service = "output_manager_service", tx_id = %tx_id))]
     fn reinstate_cancelled_inbound_transaction_outputs(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
         self.resources.db.reinstate_cancelled_inbound_output(tx_id)?;
 
@@ -1461,13 +1785,14 @@ where
             if utxos_total_value == amount + fee_without_change {
                 break;
             }
-            fee_with_change = fee_calc.calculate(
+            // The sender protocol rounds up the fee of a transaction with change
+            fee_with_change = sanitizer::canonical_fee(fee_calc.calculate(
                 fee_per_gram,
                 1,
                 utxos.len(),
                 num_outputs + 1,
                 total_output_metadata_byte_size + default_metadata_size,
-            );
+            ));
 
             trace!(target: LOG_TARGET, "-- amt+fee = {:?} {}", amount, fee_with_change);
             if utxos_total_value > amount + fee_with_change {
@@ -1507,6 +1832,19 @@ where
         Ok(self.resources.db.fetch_all_unspent_outputs()?)
     }
 
+    async fn list_outputs(&mut self) -> Result<Vec<ListedOutput>, OutputManagerError> {
+        let tip = self.base_node_service.get_chain_metadata().await?;
+        let target_block_interval = self.resources.consensus_constants.get_target_block_interval();
+        Ok(self
+            .fetch_unspent_outputs()?
+            .into_iter()
+            .map(|output| ListedOutput {
+                spendability: OutputSpendability::new(&output.unblinded_output, tip.as_ref(), target_block_interval),
+                output,
+            })
+            .collect())
+    }
+
     pub fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
         Ok(self.resources.db.fetch_outputs_by(q)?)
     }
@@ -1601,6 +1939,80 @@ where
         Ok((expected_outputs, fee))
     }
 
+    /// Groups all the spendable outputs into batches of at most `max_inputs_per_sweep_transaction` inputs that each fit
+    /// in a block, and calculates the amount that each batch can send to a recipient output with these features and
+    /// script after fees, without a change output. Outputs worth less than the fee to spend them are left out, as
+    /// are batches that cannot cover their own fee.
+    pub async fn preview_sweep_all(
+        &mut self,
+        fee_per_gram: MicroTari,
+        recipient_output_features: OutputFeatures,
+        recipient_script: TariScript,
+    ) -> Result<Vec<(Vec<Commitment>, MicroTari)>, OutputManagerError> {
+        let fee_calc = self.get_fee_calc();
+        // This matches the metadata size that `prepare_transaction_to_send` uses for input selection, so that each
+        // batch is selected without a change output
+        let metadata_byte_size = fee_calc.weighting().round_up_metadata_size(
+            recipient_output_features.consensus_encode_exact_size() +
+                recipient_script.consensus_encode_exact_size() +
+                Covenant::default().consensus_encode_exact_size(),
+        );
+
+        let max_block_weight = self
+            .resources
+            .consensus_constants
+            .get_max_block_weight_excluding_coinbase();
+        let max_inputs_in_block: usize =
+            (max_block_weight.saturating_sub(fee_calc.weighting().calculate(1, 0, 1, metadata_byte_size)) /
+                fee_calc.weighting().params().input_weight)
+                .try_into()
+                .unwrap_or(usize::MAX);
+        let max_inputs = max_inputs_in_block
+            .min(self.resources.config.max_inputs_per_sweep_transaction)
+            .max(1);
+
+        let chain_metadata = self.base_node_service.get_chain_metadata().await?;
+        let tip_height = chain_metadata.as_ref().map(|m| m.height_of_longest_chain());
+        let mut selection_criteria = UtxoSelectionCriteria::largest_first();
+        selection_criteria.excluding_onesided = self.resources.config.autoignore_onesided_utxos;
+        let input_fee = fee_calc.calculate(fee_per_gram, 0, 1, 0, 0);
+        let outputs = self
+            .resources
+            .db
+            .fetch_unspent_outputs_for_spending(&selection_criteria, MicroTari::zero(), tip_height)?
+            .into_iter()
+            .filter(|o| o.unblinded_output.value > input_fee)
+            .collect::<Vec<_>>();
+
+        let mut batches = Vec::new();
+        for batch in outputs.chunks(max_inputs) {
+            let total_value = batch.iter().map(|o| o.unblinded_output.value).sum::<MicroTari>();
+            let fee = fee_calc.calculate(fee_per_gram, 1, batch.len(), 1, metadata_byte_size);
+            let minimum_amount = if self.resources.config.prevent_fee_gt_amount {
+                fee
+            } else {
+                MicroTari::zero()
+            };
+            match total_value.checked_sub(fee) {
+                Some(amount) if amount > minimum_amount => {
+                    batches.push((batch.iter().map(|o| o.commitment.clone()).collect(), amount));
+                },
+                _ => debug!(
+                    target: LOG_TARGET,
+                    "Leaving {} output(s) worth {} out of the sweep, they do not cover the fee of {}",
+                    batch.len(),
+                    total_value,
+                    fee
+                ),
+            }
+        }
+
+        if batches.is_empty() {
+            return Err(OutputManagerError::NotEnoughFunds);
+        }
+        Ok(batches)
+    }
+
     async fn create_coin_split_with_commitments(
         &mut self,
         commitments: Vec<Commitment>,
@@ -2266,16 +2678,35 @@ where
         let req = FetchMatchingUtxos {
             output_hashes: hashes.iter().map(|v| v.to_vec()).collect(),
         };
-        let results: Vec<TransactionOutput> = self
-            .resources
-            .connectivity
-            .obtain_base_node_wallet_rpc_client()
-            .await
-            .ok_or_else(|| {
-                OutputManagerError::InvalidResponseError("Could not connect to base node rpc client".to_string())
-            })?
-            .fetch_matching_utxos(req)
-            .await?
+        let policy = self.resources.config.base_node_rpc_retry.clone();
+        let mut failed_attempts = 0;
+        let response = loop {
+            let result = match self.resources.connectivity.obtain_base_node_wallet_rpc_client().await {
+                Some(mut client) => client
+                    .fetch_matching_utxos(req.clone())
+                    .await
+                    .map_err(OutputManagerError::from),
+                None => Err(OutputManagerError::InvalidResponseError(
+                    "Could not connect to base node rpc client".to_string(),
+                )),
+            };
+            match result {
+                Err(e) if e.is_transient() => {
+                    failed_attempts += 1;
+                    if !policy.should_retry(failed_attempts) {
+                        return Err(e);
+                    }
+                    let delay = policy.delay(failed_attempts);
+                    warn!(
+                        target: LOG_TARGET,
+                        "Could not fetch outputs from the base node: {}. Retrying in {:.1?}", e, delay
+                    );
+                    time::sleep(delay).await;
+                },
+                result => break result?,
+            }
+        };
+        let results: Vec<TransactionOutput> = response
             .outputs
             .into_iter()
             .filter_map(|o| match o.try_into() {
@@ -2480,50 +2911,779 @@ where
         Ok((tx_id, fee, amount - fee, tx))
     }
 
-    /// Persist a one-sided payment script for a Comms Public/Private key. These are the scripts that this wallet knows
-    /// to look for when scanning for one-sided payments
-    fn add_known_script(&mut self, known_script: KnownOneSidedPaymentScript) -> Result<(), OutputManagerError> {
-        debug!(target: LOG_TARGET, "Adding new script to output manager service");
-        // It is not a problem if the script has already been persisted
-        match self.resources.db.add_known_script(known_script) {
-            Ok(_) => (),
-            Err(OutputManagerStorageError::DieselError(DieselError::DatabaseError(
-                DatabaseErrorKind::UniqueViolation,
-                _,
-            ))) => {
-                trace!(target: LOG_TARGET, "Duplicate script not added");
-            },
-            Err(OutputManagerStorageError::DuplicateScript) => {
-                trace!(target: LOG_TARGET, "Duplicate script not added");
-            },
-            Err(e) => return Err(e.into()),
-        }
-        Ok(())
-    }
-
-    // Scanning outputs addressed to this wallet
-    fn scan_outputs_for_one_sided_payments(
+    /// Creates a pay-to-self transaction with a vault output. The output can be spent with the recovery key
+    /// `recovery_delay` blocks after the current tip, or with the script key of the wallet alone from `unlock_height`.
+    #[allow(clippy::too_many_lines)]
+    async fn create_vault_transaction(
         &mut self,
-        outputs: Vec<TransactionOutput>,
-    ) -> Result<Vec<RecoveredOutput>, OutputManagerError> {
-        // TODO: use MultiKey
-        // NOTE: known keys is a list consisting of an actual and deprecated wallet keys
-        let known_keys = self.resources.db.get_all_known_one_sided_payment_scripts()?;
+        tx_id: TxId,
+        amount: MicroTari,
+        recovery_key: PublicKey,
+        recovery_delay: u64,
+        unlock_height: u64,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        if recovery_delay == 0 {
+            return Err(OutputManagerError::InvalidArgument(
+                "The recovery delay must be at least one block".to_string(),
+            ));
+        }
+        let recovery_height = self.last_seen_tip_height.unwrap_or(0).saturating_add(recovery_delay);
+        if unlock_height <= recovery_height {
+            return Err(OutputManagerError::InvalidArgument(format!(
+                "The unlock height must be after the recovery height {}",
+                recovery_height
+            )));
+        }
 
-        let wallet_sk = self.node_identity.secret_key().clone();
-        let wallet_pk = self.node_identity.public_key();
+        let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
+        let vault = VaultScript::new(
+            PublicKey::from_secret_key(&script_private_key),
+            &recovery_key,
+            recovery_height,
+            unlock_height,
+        );
+        let script = vault.to_script();
+        let output_features = OutputFeatures::default();
+        let covenant = Covenant::default();
+        let metadata_byte_size = self
+            .resources
+            .consensus_constants
+            .transaction_weight()
+            .round_up_metadata_size(
+                output_features.consensus_encode_exact_size() +
+                    script.consensus_encode_exact_size() +
+                    covenant.consensus_encode_exact_size(),
+            );
 
-        let mut scanned_outputs = vec![];
+        let input_selection = self
+            .select_utxos(
+                amount,
+                fee_per_gram,
+                1,
+                metadata_byte_size,
+                UtxoSelectionCriteria::default(),
+            )
+            .await?;
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+
+        // Create builder with no recipients (other than ourselves)
+        let mut builder = SenderTransactionProtocol::builder(0, self.resources.consensus_constants.clone());
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset.clone())
+            .with_private_nonce(nonce.clone())
+            .with_message(message)
+            .with_rewindable_outputs(self.resources.rewind_data.clone())
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_tx_id(tx_id);
+
+        for uo in input_selection.iter() {
+            builder.with_input(
+                uo.unblinded_output
+                    .as_transaction_input(&self.resources.factories.commitment)?,
+                uo.unblinded_output.clone(),
+            );
+        }
+
+        let commitment = self
+            .resources
+            .factories
+            .commitment
+            .commit_value(&spending_key, amount.into());
+        let encrypted_value =
+            EncryptedValue::encrypt_value(&self.resources.rewind_data.encryption_key, &commitment, amount)?;
+        let minimum_amount_promise = MicroTari::zero();
+        let metadata_signature = TransactionOutput::create_final_metadata_signature(
+            TransactionOutputVersion::get_current_version(),
+            amount,
+            &spending_key,
+            &script,
+            &output_features,
+            &sender_offset_private_key,
+            &covenant,
+            &encrypted_value,
+            minimum_amount_promise,
+        )?;
+        // The script lock height keeps the vault out of normal UTXO selection until it unlocks
+        let utxo = DbUnblindedOutput::rewindable_from_unblinded_output(
+            UnblindedOutput::new_current_version(
+                amount,
+                spending_key,
+                output_features,
+                script,
+                ExecutionStack::default(),
+                script_private_key,
+                PublicKey::from_secret_key(&sender_offset_private_key),
+                metadata_signature,
+                unlock_height,
+                covenant,
+                encrypted_value,
+                minimum_amount_promise,
+            ),
+            &self.resources.factories,
+            &self.resources.rewind_data,
+            None,
+            None,
+            OutputSource::Vault,
+        )?;
+        builder
+            .with_output(utxo.unblinded_output.clone(), sender_offset_private_key)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let mut outputs = vec![utxo];
+
+        if input_selection.requires_change_output() {
+            let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
+            builder.with_change_secret(spending_key);
+            builder.with_rewindable_outputs(self.resources.rewind_data.clone());
+            builder.with_change_script(
+                script!(Nop),
+                inputs!(PublicKey::from_secret_key(&script_private_key)),
+                script_private_key,
+            );
+        }
+
+        let factories = CryptoFactories::default();
+        let mut stp = builder
+            .build(
+                &self.resources.factories,
+                None,
+                self.last_seen_tip_height.unwrap_or(u64::MAX),
+            )
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        if input_selection.requires_change_output() {
+            let unblinded_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
+                OutputManagerError::BuildError(
+                    "There should be a change output metadata signature available".to_string(),
+                )
+            })?;
+            let change_output = DbUnblindedOutput::rewindable_from_unblinded_output(
+                unblinded_output,
+                &self.resources.factories,
+                &self.resources.rewind_data,
+                None,
+                None,
+                OutputSource::default(),
+            )?;
+            outputs.push(change_output);
+        }
+
+        trace!(target: LOG_TARGET, "Encumber vault transaction ({}) outputs.", tx_id);
+        self.resources
+            .db
+            .encumber_outputs(tx_id, input_selection.into_selected(), outputs)?;
+        self.confirm_encumberance(tx_id)?;
+        let fee = stp.get_fee_amount()?;
+        trace!(target: LOG_TARGET, "Finalize vault transaction ({}).", tx_id);
+        stp.finalize(&factories, None, self.last_seen_tip_height.unwrap_or(u64::MAX))?;
+        let tx = stp.take_transaction()?;
+
+        Ok((fee, tx))
+    }
+
+    fn get_vault_outputs(&self) -> Result<Vec<VaultOutput>, OutputManagerError> {
+        let outputs = self
+            .resources
+            .db
+            .fetch_all_unspent_outputs()?
+            .into_iter()
+            .filter(|output| output.source == OutputSource::Vault)
+            .filter_map(|output| {
+                VaultScript::from_script(&output.unblinded_output.script).map(|vault| VaultOutput { output, vault })
+            })
+            .collect();
+        Ok(outputs)
+    }
+
+    /// Spends a vault output back to the wallet on the recovery path. The recovery key is combined with the script key
+    /// of the vault, so the transaction can only be created by someone who holds both keys.
+    pub async fn create_vault_recovery_transaction(
+        &mut self,
+        output_hash: HashOutput,
+        recovery_key: PrivateKey,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, MicroTari, MicroTari, Transaction), OutputManagerError> {
+        let db_output = self.resources.db.get_unspent_output(output_hash)?;
+        if db_output.source != OutputSource::Vault {
+            return Err(OutputManagerError::NotAVaultOutput);
+        }
+        let vault =
+            VaultScript::from_script(&db_output.unblinded_output.script).ok_or(OutputManagerError::NotAVaultOutput)?;
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        if tip_height < vault.recovery_height {
+            return Err(OutputManagerError::VaultNotYetRecoverable {
+                recovery_height: vault.recovery_height,
+            });
+        }
+
+        let mut output = db_output.unblinded_output.clone();
+        let recovery_path_secret = &output.script_private_key + &recovery_key;
+        if PublicKey::from_secret_key(&recovery_path_secret) != vault.recovery_path_key {
+            return Err(OutputManagerError::InvalidVaultRecoveryKey);
+        }
+        // Once the vault has unlocked, the script selects the primary key instead
+        if tip_height < vault.unlock_height {
+            output.script_private_key = recovery_path_secret;
+        }
+
+        let amount = output.value;
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let message = "Vault recovery".to_string();
+
+        // Create builder with no recipients (other than ourselves)
+        let mut builder = SenderTransactionProtocol::builder(0, self.resources.consensus_constants.clone());
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset.clone())
+            .with_private_nonce(nonce.clone())
+            .with_message(message)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_input(
+                output.as_transaction_input(&self.resources.factories.commitment)?,
+                output,
+            );
+
+        let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
+        builder.with_change_secret(spending_key);
+        builder.with_rewindable_outputs(self.resources.rewind_data.clone());
+        builder.with_change_script(
+            script!(Nop),
+            inputs!(PublicKey::from_secret_key(&script_private_key)),
+            script_private_key,
+        );
+
+        let factories = CryptoFactories::default();
+        let mut stp = builder
+            .build(&self.resources.factories, None, tip_height)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let tx_id = stp.get_tx_id()?;
+
+        let unblinded_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
+            OutputManagerError::BuildError("There should be a change output metadata signature available".to_string())
+        })?;
+
+        let change_output = DbUnblindedOutput::rewindable_from_unblinded_output(
+            unblinded_output,
+            &self.resources.factories,
+            &self.resources.rewind_data,
+            None,
+            None,
+            OutputSource::default(),
+        )?;
+
+        trace!(target: LOG_TARGET, "Recovering vault with transaction ({}).", tx_id);
+
+        let fee = stp.get_fee_amount()?;
+
+        stp.finalize(&factories, None, tip_height)?;
+
+        let tx = stp.take_transaction()?;
+
+        self.resources
+            .db
+            .encumber_outputs(tx_id, vec![db_output], vec![change_output])?;
+        self.confirm_encumberance(tx_id)?;
+        Ok((tx_id, fee, amount - fee, tx))
+    }
+
+    /// Creates a transaction with a kernel lock height that spends all the spendable outputs of the wallet to
+    /// one-sided outputs for the beneficiaries, split according to their shares. Nothing is written to the database:
+    /// the inputs stay spendable by the wallet, which invalidates the transaction once any of them is spent. The
+    /// transaction fails with the balance it would leave uncovered if the wallet has more than
+    /// `max_inputs_per_sweep_transaction` spendable outputs, which have to be consolidated first.
+    #[allow(clippy::too_many_lines)]
+    async fn create_inheritance_transaction(
+        &mut self,
+        beneficiaries: Vec<(PublicKey, u64)>,
+        lock_height: u64,
+        fee_per_gram: MicroTari,
+    ) -> Result<(Transaction, MicroTari), OutputManagerError> {
+        if beneficiaries.is_empty() {
+            return Err(OutputManagerError::InvalidArgument(
+                "At least one beneficiary is required".to_string(),
+            ));
+        }
+        if beneficiaries.iter().any(|(_, share)| *share == 0) {
+            return Err(OutputManagerError::InvalidArgument(
+                "Every beneficiary must have a share greater than zero".to_string(),
+            ));
+        }
+
+        let output_features = OutputFeatures::default();
+        let covenant = Covenant::default();
+        let fee_calc = self.get_fee_calc();
+        let metadata_byte_size = beneficiaries
+            .iter()
+            .map(|(public_key, _)| {
+                fee_calc.weighting().round_up_metadata_size(
+                    output_features.consensus_encode_exact_size() +
+                        script!(PushPubKey(Box::new(public_key.clone()))).consensus_encode_exact_size() +
+                        covenant.consensus_encode_exact_size(),
+                )
+            })
+            .sum::<usize>();
+
+        let mut selection_criteria = UtxoSelectionCriteria::largest_first();
+        selection_criteria.excluding_onesided = self.resources.config.autoignore_onesided_utxos;
+        let mut inputs = self.resources.db.fetch_unspent_outputs_for_spending(
+            &selection_criteria,
+            MicroTari::zero(),
+            self.last_seen_tip_height,
+        )?;
+        let max_inputs = self.resources.config.max_inputs_per_sweep_transaction.max(1);
+        if inputs.len() > max_inputs {
+            let excluded = inputs.split_off(max_inputs);
+            return Err(OutputManagerError::TooManyInputs {
+                max_inputs,
+                excluded_outputs: excluded.len(),
+                uncovered: excluded.iter().map(|o| o.unblinded_output.value).sum(),
+            });
+        }
+
+        let total_value = inputs.iter().map(|o| o.unblinded_output.value).sum::<MicroTari>();
+        let fee = fee_calc.calculate(fee_per_gram, 1, inputs.len(), beneficiaries.len(), metadata_byte_size);
+        let amount = total_value.checked_sub(fee).unwrap_or_default();
+        let shares = beneficiaries.iter().map(|(_, share)| *share).collect::<Vec<_>>();
+        let amounts = split_by_shares(amount, &shares);
+        if amounts.iter().any(|a| *a == MicroTari::zero()) {
+            return Err(OutputManagerError::NotEnoughFunds);
+        }
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let mut builder = SenderTransactionProtocol::builder(0, self.resources.consensus_constants.clone());
+        builder
+            .with_lock_height(lock_height)
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce)
+            .with_message("Inheritance".to_string())
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_kernel_features(KernelFeatures::empty());
+
+        for uo in &inputs {
+            builder.with_input(
+                uo.unblinded_output
+                    .as_transaction_input(&self.resources.factories.commitment)?,
+                uo.unblinded_output.clone(),
+            );
+        }
+
+        // Each beneficiary is paid with a one-sided output that their wallet finds when scanning the chain
+        for ((public_key, _), amount) in beneficiaries.iter().zip(amounts) {
+            let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+            let spending_key = PrivateKey::from_bytes(
+                CommsPublicKey::shared_secret(&sender_offset_private_key, public_key).as_bytes(),
+            )?;
+            let rewind_blinding_key = PrivateKey::from_bytes(&hash_secret_key(&spending_key))?;
+            let encryption_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_blinding_key))?;
+            let commitment = self
+                .resources
+                .factories
+                .commitment
+                .commit_value(&spending_key, amount.into());
+            let encrypted_value = EncryptedValue::encrypt_value(&encryption_key, &commitment, amount)?;
+            let script = script!(PushPubKey(Box::new(public_key.clone())));
+            let minimum_value_promise = MicroTari::zero();
+            let metadata_signature = TransactionOutput::create_final_metadata_signature(
+                TransactionOutputVersion::get_current_version(),
+                amount,
+                &spending_key,
+                &script,
+                &output_features,
+                &sender_offset_private_key,
+                &covenant,
+                &encrypted_value,
+                minimum_value_promise,
+            )?;
+            let output = UnblindedOutput::new_current_version(
+                amount,
+                spending_key,
+                output_features.clone(),
+                script,
+                ExecutionStack::default(),
+                // Only the beneficiary can provide the script key when the output is spent
+                PrivateKey::default(),
+                PublicKey::from_secret_key(&sender_offset_private_key),
+                metadata_signature,
+                0,
+                covenant.clone(),
+                encrypted_value,
+                minimum_value_promise,
+            );
+            builder
+                .with_output(output, sender_offset_private_key)
+                .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        }
+
+        let mut stp = builder
+            .build(&self.resources.factories, None, u64::MAX)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        stp.finalize(&self.resources.factories, None, u64::MAX)?;
+        debug!(
+            target: LOG_TARGET,
+            "Created inheritance transaction of {} to {} beneficiaries with lock height {}",
+            amount,
+            beneficiaries.len(),
+            lock_height
+        );
+
+        Ok((stp.take_transaction()?, amount))
+    }
+
+    /// Issues a fungible token to the wallet in a registration output of `amount`. The issuer key of the wallet signs
+    /// the registration for the commitment of the output, so it can not be copied to another output.
+    async fn create_token_registration_transaction(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        symbol: String,
+        total_supply: u64,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        self.create_issuer_signed_output_transaction(tx_id, amount, fee_per_gram, message, |issuer_key, commitment| {
+            Ok(TokenRegistration::new(symbol, total_supply, issuer_key, commitment)?.to_features())
+        })
+        .await
+    }
+
+    /// Mints the unique asset `unique_id` to the wallet in an output of `amount`. The issuer key of the wallet is the
+    /// parent of the asset and signs the mint for the commitment of the output, so it can not be copied to another
+    /// output.
+    async fn create_unique_asset_mint_transaction(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        unique_id: Vec<u8>,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        self.create_issuer_signed_output_transaction(tx_id, amount, fee_per_gram, message, |parent_key, commitment| {
+            Ok(UniqueAsset::new(unique_id, parent_key, commitment)?.to_features())
+        })
+        .await
+    }
+
+    /// Creates a transaction that pays `amount` to the wallet in an output with the features returned by
+    /// `create_features`, which is given the issuer key of the wallet and the commitment of the output to sign.
+    async fn create_issuer_signed_output_transaction<F>(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        create_features: F,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError>
+    where
+        F: FnOnce(&PrivateKey, &Commitment) -> Result<OutputFeatures, OutputManagerError>,
+    {
+        let issuer_private_key = self
+            .resources
+            .master_key_manager
+            .get_key_at_index(OutputManagerKeyManagerBranch::ContractIssuer.get_branch_key(), 0)
+            .await?;
+        let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
+        let commitment = self
+            .resources
+            .factories
+            .commitment
+            .commit_value(&spending_key, amount.into());
+        let output_features = create_features(&issuer_private_key, &commitment)?;
+        let script = script!(Nop);
+        let covenant = Covenant::default();
+        let metadata_byte_size = self
+            .resources
+            .consensus_constants
+            .transaction_weight()
+            .round_up_metadata_size(
+                output_features.consensus_encode_exact_size() +
+                    script.consensus_encode_exact_size() +
+                    covenant.consensus_encode_exact_size(),
+            );
+
+        let input_selection = self
+            .select_utxos(
+                amount,
+                fee_per_gram,
+                1,
+                metadata_byte_size,
+                UtxoSelectionCriteria::default(),
+            )
+            .await?;
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+
+        // Create builder with no recipients (other than ourselves)
+        let mut builder = SenderTransactionProtocol::builder(0, self.resources.consensus_constants.clone());
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset.clone())
+            .with_private_nonce(nonce.clone())
+            .with_message(message)
+            .with_rewindable_outputs(self.resources.rewind_data.clone())
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_tx_id(tx_id);
+
+        for uo in input_selection.iter() {
+            builder.with_input(
+                uo.unblinded_output
+                    .as_transaction_input(&self.resources.factories.commitment)?,
+                uo.unblinded_output.clone(),
+            );
+        }
+
+        let encrypted_value =
+            EncryptedValue::encrypt_value(&self.resources.rewind_data.encryption_key, &commitment, amount)?;
+        let minimum_amount_promise = MicroTari::zero();
+        let metadata_signature = TransactionOutput::create_final_metadata_signature(
+            TransactionOutputVersion::get_current_version(),
+            amount,
+            &spending_key,
+            &script,
+            &output_features,
+            &sender_offset_private_key,
+            &covenant,
+            &encrypted_value,
+            minimum_amount_promise,
+        )?;
+        // Registration and unique asset outputs are not standard outputs, which keeps them out of normal UTXO selection
+        let utxo = DbUnblindedOutput::rewindable_from_unblinded_output(
+            UnblindedOutput::new_current_version(
+                amount,
+                spending_key,
+                output_features,
+                script,
+                inputs!(PublicKey::from_secret_key(&script_private_key)),
+                script_private_key,
+                PublicKey::from_secret_key(&sender_offset_private_key),
+                metadata_signature,
+                0,
+                covenant,
+                encrypted_value,
+                minimum_amount_promise,
+            ),
+            &self.resources.factories,
+            &self.resources.rewind_data,
+            None,
+            None,
+            OutputSource::default(),
+        )?;
+        builder
+            .with_output(utxo.unblinded_output.clone(), sender_offset_private_key)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let mut outputs = vec![utxo];
+
+        if input_selection.requires_change_output() {
+            let (spending_key, script_private_key) = self.get_spend_and_script_keys().await?;
+            builder.with_change_secret(spending_key);
+            builder.with_rewindable_outputs(self.resources.rewind_data.clone());
+            builder.with_change_script(
+                script!(Nop),
+                inputs!(PublicKey::from_secret_key(&script_private_key)),
+                script_private_key,
+            );
+        }
+
+        let mut stp = builder
+            .build(
+                &self.resources.factories,
+                None,
+                self.last_seen_tip_height.unwrap_or(u64::MAX),
+            )
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        if input_selection.requires_change_output() {
+            let unblinded_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
+                OutputManagerError::BuildError(
+                    "There should be a change output metadata signature available".to_string(),
+                )
+            })?;
+            let change_output = DbUnblindedOutput::rewindable_from_unblinded_output(
+                unblinded_output,
+                &self.resources.factories,
+                &self.resources.rewind_data,
+                None,
+                None,
+                OutputSource::default(),
+            )?;
+            outputs.push(change_output);
+        }
+
+        trace!(
+            target: LOG_TARGET,
+            "Encumber issuer signed output transaction ({}) outputs.",
+            tx_id
+        );
+        self.resources
+            .db
+            .encumber_outputs(tx_id, input_selection.into_selected(), outputs)?;
+        self.confirm_encumberance(tx_id)?;
+        let fee = stp.get_fee_amount()?;
+        trace!(
+            target: LOG_TARGET,
+            "Finalize issuer signed output transaction ({}).",
+            tx_id
+        );
+        stp.finalize(
+            &self.resources.factories,
+            None,
+            self.last_seen_tip_height.unwrap_or(u64::MAX),
+        )?;
+        let tx = stp.take_transaction()?;
+
+        Ok((fee, tx))
+    }
+
+    fn get_token_balances(&self) -> Result<Vec<TokenBalance>, OutputManagerError> {
+        let outputs = self.resources.db.fetch_with_features(OutputType::TokenRegistration)?;
+        Ok(token_balances(
+            outputs.iter().filter(|output| output.status == OutputStatus::Unspent),
+        )?)
+    }
+
+    fn get_owned_assets(&self) -> Result<Vec<OwnedAsset>, OutputManagerError> {
+        let mut assets = Vec::new();
+        for output in self.resources.db.fetch_with_features(OutputType::UniqueAsset)? {
+            if output.status != OutputStatus::Unspent {
+                continue;
+            }
+            if let Some(asset) = UniqueAsset::from_features(&output.unblinded_output.features)? {
+                assets.push(OwnedAsset { output, asset });
+            }
+        }
+        Ok(assets)
+    }
+
+    fn get_script_keys(&self) -> Result<Vec<TransactionScriptKeys>, OutputManagerError> {
+        let mut spent_commitments = HashMap::<u64, Vec<Commitment>>::new();
+        for reference in self.resources.db.fetch_output_tx_references()? {
+            if let Some(tx_id) = reference.spent_in_tx_id {
+                spent_commitments
+                    .entry(tx_id.as_u64())
+                    .or_default()
+                    .push(reference.commitment);
+            }
+        }
+        let mut spent_outputs = Vec::new();
+        for (tx_id, commitments) in spent_commitments {
+            // The outputs of a transaction include the ones it received, which are not part of its input script offset
+            let tx_id = TxId::from(tx_id);
+            for output in self.resources.db.fetch_outputs_by_tx_id(tx_id)? {
+                if commitments.contains(&output.commitment) {
+                    spent_outputs.push((tx_id, output));
+                }
+            }
+        }
+        Ok(transaction_script_keys(spent_outputs))
+    }
+
+    fn audit_script_offset(&self, transaction: &Transaction) -> Result<ScriptOffsetAudit, OutputManagerError> {
+        audit_script_offset(transaction, |commitment| {
+            Ok(self
+                .resources
+                .db
+                .fetch_by_commitment(commitment.clone())?
+                .into_iter()
+                .next())
+        })
+    }
+
+    fn preview_unique_asset_transfer(
+        &self,
+        asset_id: FixedHash,
+        fee_per_gram: MicroTari,
+        recipient_script: &TariScript,
+    ) -> Result<(OwnedAsset, MicroTari), OutputManagerError> {
+        let owned = self
+            .get_owned_assets()?
+            .into_iter()
+            .find(|owned| owned.asset.asset_id() == asset_id)
+            .ok_or(OutputManagerError::UniqueAssetNotFound(asset_id))?;
+        let fee_calc = self.get_fee_calc();
+        // This matches the metadata size that `prepare_transaction_to_send` uses for input selection, so that the
+        // transfer is selected without a change output
+        let metadata_byte_size = fee_calc.weighting().round_up_metadata_size(
+            owned.output.unblinded_output.features.consensus_encode_exact_size() +
+                recipient_script.consensus_encode_exact_size() +
+                Covenant::default().consensus_encode_exact_size(),
+        );
+        let fee = fee_calc.calculate(fee_per_gram, 1, 1, 1, metadata_byte_size);
+        let minimum_amount = if self.resources.config.prevent_fee_gt_amount {
+            fee
+        } else {
+            MicroTari::zero()
+        };
+        match owned.output.unblinded_output.value.checked_sub(fee) {
+            Some(amount) if amount > minimum_amount => Ok((owned, amount)),
+            _ => Err(OutputManagerError::NotEnoughFunds),
+        }
+    }
+
+    /// Persist a one-sided payment script for a Comms Public/Private key. These are the scripts that this wallet knows
+    /// to look for when scanning for one-sided payments
+    fn add_known_script(&mut self, known_script: KnownOneSidedPaymentScript) -> Result<(), OutputManagerError> {
+        debug!(target: LOG_TARGET, "Adding new script to output manager service");
+        // It is not a problem if the script has already been persisted
+        match self.resources.db.add_known_script(known_script) {
+            Ok(_) => (),
+            Err(OutputManagerStorageError::DieselError(DieselError::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _,
+            ))) => {
+                trace!(target: LOG_TARGET, "Duplicate script not added");
+            },
+            Err(OutputManagerStorageError::DuplicateScript) => {
+                trace!(target: LOG_TARGET, "Duplicate script not added");
+            },
+            Err(e) => return Err(e.into()),
+        }
+        Ok(())
+    }
+
+    // Scanning outputs addressed to this wallet
+    fn scan_outputs_for_one_sided_payments(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<RecoveredOutput>, OutputManagerError> {
+        // TODO: use MultiKey
+        // NOTE: known keys is a list consisting of an actual and deprecated wallet keys
+        let known_keys = self.resources.db.get_all_known_one_sided_payment_scripts()?;
+        // Deposit identities add a known key each, so the public keys are derived once rather than for every output
+        let known_keys = known_keys
+            .iter()
+            .map(|known_key| (PublicKey::from_secret_key(&known_key.private_key), known_key))
+            .collect::<HashMap<_, _>>();
+
+        let wallet_sk = self.node_identity.secret_key().clone();
+        let wallet_pk = self.node_identity.public_key();
+
+        let mut scanned_outputs = vec![];
 
         for output in outputs {
             match output.script.as_slice() {
                 // ----------------------------------------------------------------------------
                 // simple one-sided address
                 [Opcode::PushPubKey(scanned_pk)] => {
-                    match known_keys
-                        .iter()
-                        .find(|x| &PublicKey::from_secret_key(&x.private_key) == scanned_pk.as_ref())
-                    {
+                    match known_keys.get(scanned_pk.as_ref()) {
                         // none of the keys match, skipping
                         None => continue,
 
@@ -2593,11 +3753,151 @@ where
                     }
                 },
 
+                // ----------------------------------------------------------------------------
+                // expiring vault payment, which can be claimed with a known key until it expires
+                [Opcode::CheckHeight(_), ..] => {
+                    let vault = match ExpiringVaultScript::from_script(&output.script) {
+                        Some(vault) => vault,
+                        None => continue,
+                    };
+                    let matched_key = match known_keys.get(&vault.recipient_key) {
+                        Some(matched_key) => matched_key,
+                        None => continue,
+                    };
+                    match PrivateKey::from_bytes(
+                        CommsPublicKey::shared_secret(&matched_key.private_key, &output.sender_offset_public_key)
+                            .as_bytes(),
+                    ) {
+                        Ok(spending_sk) => scanned_outputs.push((
+                            output.clone(),
+                            OutputSource::ExpiringVault,
+                            matched_key.private_key.clone(),
+                            spending_sk,
+                        )),
+                        Err(e) => {
+                            error!(
+                                target: LOG_TARGET,
+                                "failed to derive private key from DH shared secret (expiring vault): {:?}", e
+                            );
+                            continue;
+                        },
+                    }
+                },
+
                 _ => {},
             }
         }
 
-        self.import_onesided_outputs(scanned_outputs)
+        let recovered_outputs = self.import_onesided_outputs(scanned_outputs)?;
+        self.attribute_deposits(&recovered_outputs)?;
+        Ok(recovered_outputs)
+    }
+
+    /// Derives a new receive-only identity for the user or account `label`. One-sided payments to its public key are
+    /// found by the scan for one-sided payments and attributed to it.
+    async fn create_deposit_identity(&mut self, label: String) -> Result<DepositIdentity, OutputManagerError> {
+        let label = label.trim().to_string();
+        if label.is_empty() {
+            return Err(OutputManagerError::InvalidArgument(
+                "The label of a deposit identity cannot be empty".to_string(),
+            ));
+        }
+        if self.resources.db.fetch_deposit_identity(&label)?.is_some() {
+            return Err(OutputManagerError::InvalidArgument(format!(
+                "A deposit identity labelled `{}` already exists",
+                label
+            )));
+        }
+
+        let result = self
+            .resources
+            .master_key_manager
+            .get_next_key(OutputManagerKeyManagerBranch::DepositIdentity.get_branch_key())
+            .await?;
+        let public_key = PublicKey::from_secret_key(&result.key);
+        let script = script!(PushPubKey(Box::new(public_key.clone())));
+        self.add_known_script(KnownOneSidedPaymentScript {
+            script_hash: script.as_hash::<Blake256>()?.to_vec(),
+            private_key: result.key,
+            script,
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        })?;
+
+        let identity = DepositIdentity {
+            key_index: result.index,
+            label,
+            public_key,
+            created_at: Utc::now().naive_utc(),
+        };
+        self.resources.db.add_deposit_identity(identity.clone())?;
+        debug!(
+            target: LOG_TARGET,
+            "Created deposit identity {} for `{}`", identity.key_index, identity.label
+        );
+        Ok(identity)
+    }
+
+    /// Records the simple one-sided payments received by deposit identities as deposits of the identities and
+    /// publishes them
+    fn attribute_deposits(&self, recovered_outputs: &[RecoveredOutput]) -> Result<(), OutputManagerError> {
+        if recovered_outputs
+            .iter()
+            .all(|recovered| recovered.source != OutputSource::OneSided)
+        {
+            return Ok(());
+        }
+        let identities = self
+            .resources
+            .db
+            .fetch_deposit_identities()?
+            .into_iter()
+            .map(|identity| (identity.public_key, identity.key_index))
+            .collect::<HashMap<_, _>>();
+        let received_at = Utc::now().naive_utc();
+        let deposits = recovered_outputs
+            .iter()
+            .filter(|recovered| recovered.source == OutputSource::OneSided)
+            .filter_map(|recovered| {
+                let key_index = *identities.get(one_sided_payment_key(&recovered.output.script)?)?;
+                Some(Deposit {
+                    key_index,
+                    commitment: self
+                        .resources
+                        .factories
+                        .commitment
+                        .commit_value(&recovered.output.spending_key, recovered.output.value.as_u64()),
+                    value: recovered.output.value,
+                    tx_id: recovered.tx_id,
+                    received_at,
+                })
+            })
+            .collect::<Vec<_>>();
+        if deposits.is_empty() {
+            return Ok(());
+        }
+
+        self.resources.db.add_deposits(&deposits)?;
+        for deposit in deposits {
+            debug!(
+                target: LOG_TARGET,
+                "Deposit of {} received by deposit identity {} (TxId: {})",
+                deposit.value,
+                deposit.key_index,
+                deposit.tx_id
+            );
+            if let Err(e) = self
+                .resources
+                .event_publisher
+                .send(Arc::new(OutputManagerEvent::DepositReceived(deposit)))
+            {
+                debug!(
+                    target: LOG_TARGET,
+                    "Error sending event because there are no subscribers: {:?}", e
+                );
+            }
+        }
+        Ok(())
     }
 
     // Imports scanned outputs into the wallet
@@ -2610,74 +3910,78 @@ where
         for (output, output_source, script_private_key, spending_sk) in scanned_outputs {
             let rewind_blinding_key = PrivateKey::from_bytes(&hash_secret_key(&spending_sk))?;
             let encryption_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_blinding_key))?;
-            let committed_value =
-                EncryptedValue::decrypt_value(&encryption_key, &output.commitment, &output.encrypted_value);
-
-            if let Ok(committed_value) = committed_value {
-                let blinding_factor =
-                    output.recover_mask(&self.resources.factories.range_proof, &rewind_blinding_key)?;
-
-                if output.verify_mask(
-                    &self.resources.factories.range_proof,
-                    &blinding_factor,
-                    committed_value.into(),
-                )? {
-                    let rewound_output = UnblindedOutput::new(
-                        output.version,
-                        committed_value,
-                        blinding_factor.clone(),
-                        output.features,
-                        output.script,
-                        tari_script::ExecutionStack::new(vec![]),
-                        script_private_key,
-                        output.sender_offset_public_key,
-                        output.metadata_signature,
-                        0,
-                        output.covenant,
-                        output.encrypted_value,
-                        output.minimum_value_promise,
-                    );
+            let rewind_data = RewindData {
+                rewind_blinding_key,
+                encryption_key,
+            };
 
-                    let db_output = DbUnblindedOutput::rewindable_from_unblinded_output(
-                        rewound_output.clone(),
-                        &self.resources.factories,
-                        &RewindData {
-                            rewind_blinding_key,
-                            encryption_key,
-                        },
-                        None,
-                        Some(&output.proof),
-                        output_source,
-                    )?;
+            if let Some(rewound) = output.rewind(&self.resources.factories.range_proof, &rewind_data)? {
+                // Metadata that is not a memo for this wallet fails to decrypt and is ignored
+                let memo = if output.features.metadata.is_empty() {
+                    None
+                } else {
+                    EncryptedMemo::decrypt_memo(&rewind_data.encryption_key, &output.features.metadata).ok()
+                };
+                let rewound_output = UnblindedOutput::new(
+                    output.version,
+                    rewound.value,
+                    rewound.blinding_factor,
+                    output.features,
+                    output.script,
+                    tari_script::ExecutionStack::new(vec![]),
+                    script_private_key,
+                    output.sender_offset_public_key,
+                    output.metadata_signature,
+                    0,
+                    output.covenant,
+                    output.encrypted_value,
+                    output.minimum_value_promise,
+                );
 
-                    let output_hex = output.commitment.to_hex();
-                    let tx_id = TxId::new_random();
+                // An expiring vault payment is claimed as soon as possible, as it is refunded to the sender once it
+                // expires
+                let spending_priority = if output_source == OutputSource::ExpiringVault {
+                    Some(SpendingPriority::HtlcSpendAsap)
+                } else {
+                    None
+                };
+                let db_output = DbUnblindedOutput::rewindable_from_unblinded_output(
+                    rewound_output.clone(),
+                    &self.resources.factories,
+                    &rewind_data,
+                    spending_priority,
+                    Some(&output.proof),
+                    output_source,
+                )?;
 
-                    match self.resources.db.add_unspent_output_with_tx_id(tx_id, db_output) {
-                        Ok(_) => {
-                            trace!(
-                                target: LOG_TARGET,
-                                "One-sided payment Output {} with value {} recovered",
-                                output_hex,
-                                committed_value,
-                            );
+                let output_hex = output.commitment.to_hex();
+                let tx_id = TxId::new_random();
 
-                            rewound_outputs.push(RecoveredOutput {
-                                output: rewound_output,
-                                tx_id,
-                            })
-                        },
-                        Err(OutputManagerStorageError::DuplicateOutput) => {
-                            warn!(
-                                target: LOG_TARGET,
-                                "Attempt to add scanned output {} that already exists. Ignoring the output.",
-                                output_hex
-                            );
-                        },
-                        Err(err) => {
-                            return Err(err.into());
-                        },
-                    }
+                match self.resources.db.add_unspent_output_with_tx_id(tx_id, db_output) {
+                    Ok(_) => {
+                        trace!(
+                            target: LOG_TARGET,
+                            "One-sided payment Output {} with value {} recovered",
+                            output_hex,
+                            rewound.value,
+                        );
+
+                        rewound_outputs.push(RecoveredOutput {
+                            output: rewound_output,
+                            tx_id,
+                            source: output_source,
+                            memo,
+                        })
+                    },
+                    Err(OutputManagerStorageError::DuplicateOutput) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Attempt to add scanned output {} that already exists. Ignoring the output.", output_hex
+                        );
+                    },
+                    Err(err) => {
+                        return Err(err.into());
+                    },
                 }
             }
         }
@@ -2726,6 +4030,55 @@ impl fmt::Display for Balance {
     }
 }
 
+/// Statistics of the coinbases the wallet generated for mined blocks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoinbaseStats {
+    /// The number of coinbases whose blocks have not been mined yet
+    pub pending: usize,
+    pub pending_value: MicroTari,
+    /// The number of coinbases in blocks that are part of the chain
+    pub mined: usize,
+    pub mined_value: MicroTari,
+    /// The number of coinbases whose blocks were never mined or were reorged out
+    pub abandoned: usize,
+    pub abandoned_value: MicroTari,
+    /// The height of the most recent block that was mined with a coinbase of the wallet
+    pub last_mined_height: Option<u64>,
+}
+
+impl fmt::Display for CoinbaseStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pending coinbases: {} ({})", self.pending, self.pending_value)?;
+        writeln!(f, "Mined coinbases: {} ({})", self.mined, self.mined_value)?;
+        writeln!(f, "Abandoned coinbases: {} ({})", self.abandoned, self.abandoned_value)?;
+        if let Some(height) = self.last_mined_height {
+            writeln!(f, "Last mined block: #{}", height)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits `amount` in proportion to `shares`. Rounding leftovers go to the last share.
+fn split_by_shares(amount: MicroTari, shares: &[u64]) -> Vec<MicroTari> {
+    let total_shares = shares.iter().map(|s| u128::from(*s)).sum::<u128>();
+    if total_shares == 0 {
+        return vec![MicroTari::zero(); shares.len()];
+    }
+    let mut amounts = shares
+        .iter()
+        .map(|share| {
+            let value = u128::from(amount.as_u64()) * u128::from(*share) / total_shares;
+            // The result is at most `amount`, so it fits in a u64
+            MicroTari::from(value as u64)
+        })
+        .collect::<Vec<_>>();
+    let allocated = amounts.iter().copied().sum::<MicroTari>();
+    if let Some(last) = amounts.last_mut() {
+        *last += amount - allocated;
+    }
+    amounts
+}
+
 fn hash_secret_key(key: &PrivateKey) -> Vec<u8> {
     WalletSecretKeysDomainHasher::new()
         .chain(key.as_bytes())
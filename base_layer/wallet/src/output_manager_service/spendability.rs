@@ -0,0 +1,83 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::TryFrom;
+
+use chrono::NaiveDateTime;
+use tari_common_types::chain_metadata::ChainMetadata;
+use tari_core::transactions::transaction_components::UnblindedOutput;
+
+use crate::output_manager_service::storage::models::DbUnblindedOutput;
+
+/// When an output of the wallet can be spent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputSpendability {
+    /// The lowest height of a block that can spend the output, see [UnblindedOutput::spendable_height]
+    pub spendable_height: u64,
+    /// The estimated time at which the block at `spendable_height` is mined. This is `None` if the output can be spent
+    /// in the next block or the tip of the chain is not known.
+    pub estimated_spendable_at: Option<NaiveDateTime>,
+}
+
+impl OutputSpendability {
+    /// Estimates when the output can be spent from the tip of the chain, assuming blocks are mined every
+    /// `target_block_interval` seconds from then on
+    pub fn new(output: &UnblindedOutput, tip: Option<&ChainMetadata>, target_block_interval: u64) -> Self {
+        let spendable_height = output.spendable_height();
+        let estimated_spendable_at = tip.and_then(|tip| {
+            let tip_height = tip.height_of_longest_chain();
+            if spendable_height <= tip_height.saturating_add(1) {
+                return None;
+            }
+            let seconds = (spendable_height - tip_height).saturating_mul(target_block_interval);
+            let timestamp = tip.timestamp().saturating_add(seconds);
+            NaiveDateTime::from_timestamp_opt(i64::try_from(timestamp).ok()?, 0)
+        });
+        Self {
+            spendable_height,
+            estimated_spendable_at,
+        }
+    }
+
+    /// Returns true if the output can be spent in the block after the given tip height
+    pub fn is_spendable_after(&self, tip_height: u64) -> bool {
+        self.spendable_height <= tip_height.saturating_add(1)
+    }
+}
+
+/// An unspent output of the wallet together with when it can be spent
+#[derive(Debug, Clone)]
+pub struct ListedOutput {
+    pub output: DbUnblindedOutput,
+    pub spendability: OutputSpendability,
+}
+
+#[cfg(test)]
+mod test {
+    use tari_core::transactions::test_helpers::TestParams;
+
+    use super::*;
+
+    #[test]
+    fn it_estimates_when_the_output_is_spendable() {
+        let mut output = TestParams::new().create_unblinded_output(Default::default());
+        output.features.maturity = 110;
+        let tip = ChainMetadata::new(100, Default::default(), 0, 0, 0, 1_000_000);
+
+        let spendability = OutputSpendability::new(&output, Some(&tip), 120);
+        assert_eq!(spendability.spendable_height, 110);
+        assert_eq!(
+            spendability.estimated_spendable_at,
+            NaiveDateTime::from_timestamp_opt(1_000_000 + 10 * 120, 0)
+        );
+        assert!(!spendability.is_spendable_after(100));
+        assert!(spendability.is_spendable_after(109));
+
+        assert_eq!(OutputSpendability::new(&output, None, 120).estimated_spendable_at, None);
+        output.features.maturity = 101;
+        assert_eq!(
+            OutputSpendability::new(&output, Some(&tip), 120).estimated_spendable_at,
+            None
+        );
+    }
+}
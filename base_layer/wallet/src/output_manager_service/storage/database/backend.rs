@@ -2,6 +2,10 @@
 // SPDX-License-Identifier: BSD-3-Clause
 
 use chacha20poly1305::XChaCha20Poly1305;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
 use tari_common_types::{
     transaction::TxId,
     types::{Commitment, FixedHash},
@@ -11,11 +15,23 @@ use tari_core::transactions::transaction_components::{OutputType, TransactionOut
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
     input_selection::UtxoSelectionCriteria,
-    service::Balance,
+    service::{Balance, CoinbaseStats},
     storage::{
-        database::{DbKey, DbValue, OutputBackendQuery, WriteOperation},
+        database::{
+            BalanceTotalDrift,
+            DbKey,
+            DbValue,
+            OutputBackendQuery,
+            OutputPage,
+            OutputSet,
+            OutputStatusUpdate,
+            OutputTxReference,
+            WriteOperation,
+        },
         models::DbUnblindedOutput,
     },
+    Deposit,
+    DepositIdentity,
 };
 
 /// This trait defines the required behaviour that a storage backend must provide for the Output Manager service.
@@ -59,6 +75,8 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     ) -> Result<(), OutputManagerStorageError>;
 
     fn mark_output_as_unspent(&self, hash: FixedHash) -> Result<(), OutputManagerStorageError>;
+    /// Applies a batch of mined and spent state changes in a single database transaction
+    fn apply_status_updates(&self, updates: Vec<OutputStatusUpdate>) -> Result<(), OutputManagerStorageError>;
     /// This method encumbers the specified outputs into a `PendingTransactionOutputs` record. This is a short term
     /// encumberance in case the app is closed or crashes before transaction neogtiation is complete. These will be
     /// cleared on startup of the service.
@@ -93,12 +111,31 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn get_last_spent_output(&self) -> Result<Option<DbUnblindedOutput>, OutputManagerStorageError>;
     /// Set if a coinbase output is abandoned or not
     fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError>;
+    /// Mark the coinbases for blocks up to `max_block_height` that are not in the chain as abandoned, returning the
+    /// transaction ids of the abandoned coinbases
+    fn abandon_unmined_coinbases(&self, max_block_height: u64) -> Result<Vec<TxId>, OutputManagerStorageError>;
+    /// Return statistics of the coinbases generated by the wallet
+    fn get_coinbase_stats(&self) -> Result<CoinbaseStats, OutputManagerStorageError>;
     /// Reinstate a cancelled inbound output
     fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     /// Return the available, time locked, pending incoming and pending outgoing balance
     fn get_balance(&self, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
+    /// Recount the running totals of the output values per status and source over all the outputs, correct the totals
+    /// that do not match and return them
+    fn recount_balance_totals(&self) -> Result<Vec<BalanceTotalDrift>, OutputManagerStorageError>;
     /// Import unvalidated output
     fn add_unvalidated_output(&self, output: DbUnblindedOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
+    /// Load the caches of the backend, so that the first queries that use them are not slowed down by it
+    fn warm_up_cache(&self) -> Result<(), OutputManagerStorageError>;
+    /// Store a new deposit identity
+    fn add_deposit_identity(&self, identity: DepositIdentity) -> Result<(), OutputManagerStorageError>;
+    /// Fetch all the deposit identities, ordered by key index
+    fn fetch_deposit_identities(&self) -> Result<Vec<DepositIdentity>, OutputManagerStorageError>;
+    fn fetch_deposit_identity(&self, label: &str) -> Result<Option<DepositIdentity>, OutputManagerStorageError>;
+    /// Store the deposits, ignoring the ones that are already stored
+    fn add_deposits(&self, deposits: &[Deposit]) -> Result<(), OutputManagerStorageError>;
+    /// Fetch the deposits received by the identity with the key index, in the order they were received
+    fn fetch_deposits(&self, key_index: u64) -> Result<Vec<Deposit>, OutputManagerStorageError>;
     fn fetch_unspent_outputs_for_spending(
         &self,
         selection_criteria: &UtxoSelectionCriteria,
@@ -107,4 +144,48 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     ) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>;
     fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>;
     fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>;
+    /// Fetch at most `limit` outputs in the set, ordered by id and starting after the output with id `after_id`
+    fn fetch_outputs_page(
+        &self,
+        set: OutputSet,
+        after_id: Option<i32>,
+        limit: usize,
+    ) -> Result<OutputPage, OutputManagerStorageError>;
+    /// Fetch the transactions that every output was received and spent in
+    fn fetch_output_tx_references(&self) -> Result<Vec<OutputTxReference>, OutputManagerStorageError>;
+
+    /// Streams the outputs in the set in pages of at most `page_size` outputs, so that a large set of outputs is never
+    /// held in memory at once. Pages are fetched as the stream is polled, and an output that enters or leaves the set
+    /// while it is streamed may or may not be yielded.
+    fn fetch_outputs_stream(
+        &self,
+        set: OutputSet,
+        page_size: usize,
+    ) -> BoxStream<'static, Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>>
+    where
+        Self: Sized + 'static,
+    {
+        let db = self.clone();
+        // `None` once the last page was yielded, otherwise the cursor of the next page
+        stream::try_unfold(Some(None), move |cursor| {
+            let db = db.clone();
+            async move {
+                let after_id = match cursor {
+                    Some(after_id) => after_id,
+                    None => return Ok(None),
+                };
+                let page = db.fetch_outputs_page(set, after_id, page_size)?;
+                if page.outputs.is_empty() {
+                    return Ok(None);
+                }
+                let next = if page.outputs.len() < page_size {
+                    None
+                } else {
+                    Some(page.last_id)
+                };
+                Ok(Some((page.outputs, next)))
+            }
+        })
+        .boxed()
+    }
 }
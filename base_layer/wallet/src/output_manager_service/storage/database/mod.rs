@@ -28,6 +28,7 @@ use std::{
 
 pub use backend::OutputManagerBackend;
 use chacha20poly1305::XChaCha20Poly1305;
+use futures::stream::BoxStream;
 use log::*;
 use tari_common_types::{
     transaction::TxId,
@@ -42,11 +43,14 @@ use tari_utilities::hex::Hex;
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
     input_selection::UtxoSelectionCriteria,
-    service::Balance,
+    service::{Balance, CoinbaseStats},
     storage::{
         models::{DbUnblindedOutput, KnownOneSidedPaymentScript},
+        OutputSource,
         OutputStatus,
     },
+    Deposit,
+    DepositIdentity,
 };
 
 const LOG_TARGET: &str = "wallet::output_manager_service::database";
@@ -82,6 +86,45 @@ impl Default for OutputBackendQuery {
     }
 }
 
+/// A set of outputs that can be streamed with [OutputManagerBackend::fetch_outputs_stream]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputSet {
+    All,
+    Unspent,
+    /// Outputs that have not been found or confirmed in the block chain yet
+    Unconfirmed,
+    /// Outputs that have been mined but not spent yet
+    MinedUnspent,
+}
+
+/// A page of outputs, ordered by id
+#[derive(Debug, Clone, Default)]
+pub struct OutputPage {
+    pub outputs: Vec<DbUnblindedOutput>,
+    /// The id of the last output in the page, which the next page starts after
+    pub last_id: Option<i32>,
+}
+
+/// A running total of the output values with a status and source that did not match the sum over the outputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceTotalDrift {
+    pub status: OutputStatus,
+    pub source: OutputSource,
+    /// The running total before it was corrected
+    pub recorded: i64,
+    /// The sum over the outputs that the running total was corrected to
+    pub recounted: i64,
+}
+
+/// The transactions that an output was received and spent in
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTxReference {
+    pub commitment: Commitment,
+    pub status: OutputStatus,
+    pub received_in_tx_id: Option<TxId>,
+    pub spent_in_tx_id: Option<TxId>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DbKey {
     SpentOutput(BlindingFactor),
@@ -120,6 +163,28 @@ pub enum WriteOperation {
     Remove(DbKey),
 }
 
+/// A change to the mined or spent state of an output that the validation protocol found on chain. A batch of updates
+/// is applied atomically with [OutputManagerBackend::apply_status_updates].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputStatusUpdate {
+    Mined {
+        hash: HashOutput,
+        mined_height: u64,
+        mined_in_block: HashOutput,
+        mmr_position: u64,
+        confirmed: bool,
+        mined_timestamp: u64,
+    },
+    Unmined(HashOutput),
+    Spent {
+        hash: HashOutput,
+        deleted_height: u64,
+        deleted_in_block: HashOutput,
+        confirmed: bool,
+    },
+    Unspent(HashOutput),
+}
+
 /// This structure holds an inner type that implements the `OutputManagerBackend` trait and contains the more complex
 /// data access logic required by the module built onto the functionality defined by the trait
 #[derive(Clone)]
@@ -189,6 +254,37 @@ where T: OutputManagerBackend + 'static
         self.db.get_balance(current_tip_for_time_lock_calculation)
     }
 
+    /// Loads the caches of the backend, e.g. of the unspent outputs that coin selection uses
+    pub fn warm_up_cache(&self) -> Result<(), OutputManagerStorageError> {
+        self.db.warm_up_cache()
+    }
+
+    pub fn add_deposit_identity(&self, identity: DepositIdentity) -> Result<(), OutputManagerStorageError> {
+        self.db.add_deposit_identity(identity)
+    }
+
+    pub fn fetch_deposit_identities(&self) -> Result<Vec<DepositIdentity>, OutputManagerStorageError> {
+        self.db.fetch_deposit_identities()
+    }
+
+    pub fn fetch_deposit_identity(&self, label: &str) -> Result<Option<DepositIdentity>, OutputManagerStorageError> {
+        self.db.fetch_deposit_identity(label)
+    }
+
+    pub fn add_deposits(&self, deposits: &[Deposit]) -> Result<(), OutputManagerStorageError> {
+        self.db.add_deposits(deposits)
+    }
+
+    pub fn fetch_deposits(&self, key_index: u64) -> Result<Vec<Deposit>, OutputManagerStorageError> {
+        self.db.fetch_deposits(key_index)
+    }
+
+    /// Recounts the running totals that the balance is read from over all the outputs and corrects the totals that
+    /// drifted, returning them
+    pub fn recount_balance_totals(&self) -> Result<Vec<BalanceTotalDrift>, OutputManagerStorageError> {
+        self.db.recount_balance_totals()
+    }
+
     /// This method is called when a transaction is built to be sent. It will encumber unspent outputs against a pending
     /// transaction in the short term.
     pub fn encumber_outputs(
@@ -446,12 +542,28 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    /// Applies the updates in a single database transaction, so either all or none of them are applied
+    pub fn apply_status_updates(&self, updates: Vec<OutputStatusUpdate>) -> Result<(), OutputManagerStorageError> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+        self.db.apply_status_updates(updates)
+    }
+
     pub fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError> {
         let db = self.db.clone();
         db.set_coinbase_abandoned(tx_id, abandoned)?;
         Ok(())
     }
 
+    pub fn abandon_unmined_coinbases(&self, max_block_height: u64) -> Result<Vec<TxId>, OutputManagerStorageError> {
+        self.db.abandon_unmined_coinbases(max_block_height)
+    }
+
+    pub fn get_coinbase_stats(&self) -> Result<CoinbaseStats, OutputManagerStorageError> {
+        self.db.get_coinbase_stats()
+    }
+
     pub fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
         let outputs = self.db.fetch_outputs_by_tx_id(tx_id)?;
         Ok(outputs)
@@ -460,6 +572,20 @@ where T: OutputManagerBackend + 'static
     pub fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
         self.db.fetch_outputs_by(q)
     }
+
+    /// Streams the outputs in the set in pages of at most `page_size` outputs
+    pub fn fetch_outputs_stream(
+        &self,
+        set: OutputSet,
+        page_size: usize,
+    ) -> BoxStream<'static, Result<Vec<DbUnblindedOutput>, OutputManagerStorageError>> {
+        self.db.fetch_outputs_stream(set, page_size)
+    }
+
+    /// Fetches the transactions that every output in the database was received and spent in
+    pub fn fetch_output_tx_references(&self) -> Result<Vec<OutputTxReference>, OutputManagerStorageError> {
+        self.db.fetch_output_tx_references()
+    }
 }
 
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, OutputManagerStorageError> {
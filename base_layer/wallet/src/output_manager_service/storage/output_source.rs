@@ -43,6 +43,13 @@ pub enum OutputSource {
     StealthOneSided,
     Refund,
     AtomicSwap,
+    /// Change sent to a cold storage address, which this wallet can not spend
+    ColdStorageChange,
+    /// A time-locked vault output, see [VaultScript](crate::output_manager_service::VaultScript)
+    Vault,
+    /// An expiring vault payment from another wallet, see
+    /// [ExpiringVaultScript](crate::output_manager_service::ExpiringVaultScript)
+    ExpiringVault,
 }
 
 impl TryFrom<i32> for OutputSource {
@@ -58,9 +65,12 @@ impl TryFrom<i32> for OutputSource {
             5 => OutputSource::StealthOneSided,
             6 => OutputSource::Refund,
             7 => OutputSource::AtomicSwap,
+            8 => OutputSource::ColdStorageChange,
+            9 => OutputSource::Vault,
+            10 => OutputSource::ExpiringVault,
             _ => {
                 return Err(OutputManagerStorageError::ConversionError {
-                    reason: "Was expecting value between 0 and 7 for OutputSource".to_string(),
+                    reason: "Was expecting value between 0 and 10 for OutputSource".to_string(),
                 })
             },
         })
@@ -0,0 +1,132 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::TryFrom;
+
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, SqliteConnection};
+use tari_common_types::{
+    transaction::TxId,
+    types::{Commitment, PublicKey},
+};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_utilities::ByteArray;
+
+use crate::{
+    output_manager_service::{error::OutputManagerStorageError, Deposit, DepositIdentity},
+    schema::{deposit_identities, deposits},
+};
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "deposit_identities"]
+pub struct DepositIdentitySql {
+    key_index: i64,
+    label: String,
+    public_key: Vec<u8>,
+    created_at: NaiveDateTime,
+}
+
+impl DepositIdentitySql {
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_into(deposit_identities::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<DepositIdentitySql>, OutputManagerStorageError> {
+        Ok(deposit_identities::table
+            .order_by(deposit_identities::key_index)
+            .load::<DepositIdentitySql>(conn)?)
+    }
+
+    pub fn find_by_label(
+        label: &str,
+        conn: &SqliteConnection,
+    ) -> Result<Option<DepositIdentitySql>, OutputManagerStorageError> {
+        Ok(deposit_identities::table
+            .filter(deposit_identities::label.eq(label))
+            .first::<DepositIdentitySql>(conn)
+            .optional()?)
+    }
+}
+
+impl From<DepositIdentity> for DepositIdentitySql {
+    fn from(identity: DepositIdentity) -> Self {
+        Self {
+            key_index: identity.key_index as i64,
+            label: identity.label,
+            public_key: identity.public_key.to_vec(),
+            created_at: identity.created_at,
+        }
+    }
+}
+
+impl TryFrom<DepositIdentitySql> for DepositIdentity {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(identity: DepositIdentitySql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key_index: identity.key_index as u64,
+            label: identity.label,
+            public_key: PublicKey::from_bytes(&identity.public_key)?,
+            created_at: identity.created_at,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "deposits"]
+pub struct DepositSql {
+    commitment: Vec<u8>,
+    key_index: i64,
+    value: i64,
+    tx_id: i64,
+    received_at: NaiveDateTime,
+}
+
+impl DepositSql {
+    /// Inserts the deposit, ignoring a deposit of the same output that was recorded before, e.g. by an earlier scan
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_or_ignore_into(deposits::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index_by_key_index(
+        key_index: u64,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<DepositSql>, OutputManagerStorageError> {
+        Ok(deposits::table
+            .filter(deposits::key_index.eq(key_index as i64))
+            .order_by(deposits::received_at)
+            .load::<DepositSql>(conn)?)
+    }
+}
+
+impl From<&Deposit> for DepositSql {
+    fn from(deposit: &Deposit) -> Self {
+        Self {
+            commitment: deposit.commitment.to_vec(),
+            key_index: deposit.key_index as i64,
+            value: deposit.value.as_u64() as i64,
+            tx_id: deposit.tx_id.as_u64() as i64,
+            received_at: deposit.received_at,
+        }
+    }
+}
+
+impl TryFrom<DepositSql> for Deposit {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(deposit: DepositSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key_index: deposit.key_index as u64,
+            commitment: Commitment::from_bytes(&deposit.commitment)?,
+            value: MicroTari::from(deposit.value as u64),
+            tx_id: TxId::from(deposit.tx_id as u64),
+            received_at: deposit.received_at,
+        })
+    }
+}
@@ -22,11 +22,12 @@
 
 use std::{
     convert::{TryFrom, TryInto},
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
 };
 
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::NaiveDateTime;
+use deposit_sql::{DepositIdentitySql, DepositSql};
 use derivative::Derivative;
 use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
 use log::*;
@@ -40,34 +41,53 @@ use tari_core::transactions::transaction_components::{OutputType, TransactionOut
 use tari_crypto::tari_utilities::{hex::Hex, ByteArray};
 use tari_script::{ExecutionStack, TariScript};
 use tokio::time::Instant;
+use unspent_output_cache::UnspentOutputCache;
 
 use crate::{
     output_manager_service::{
         error::OutputManagerStorageError,
-        service::Balance,
+        service::{Balance, CoinbaseStats},
         storage::{
-            database::{DbKey, DbKeyValuePair, DbValue, OutputBackendQuery, OutputManagerBackend, WriteOperation},
+            database::{
+                BalanceTotalDrift,
+                DbKey,
+                DbKeyValuePair,
+                DbValue,
+                OutputBackendQuery,
+                OutputManagerBackend,
+                OutputPage,
+                OutputSet,
+                OutputStatusUpdate,
+                OutputTxReference,
+                WriteOperation,
+            },
             models::{DbUnblindedOutput, KnownOneSidedPaymentScript},
             OutputStatus,
         },
+        Deposit,
+        DepositIdentity,
         UtxoSelectionCriteria,
     },
-    schema::{known_one_sided_payment_scripts, outputs},
+    schema::{known_one_sided_payment_scripts, outputs, outputs_version},
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
     util::{
         diesel_ext::ExpectedRowsExtension,
         encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
     },
 };
+mod deposit_sql;
 mod new_output_sql;
 mod output_sql;
+mod unspent_output_cache;
 const LOG_TARGET: &str = "wallet::output_manager_service::database::wallet";
 
 /// A Sqlite backend for the Output Manager Service. The Backend is accessed via a connection pool to the Sqlite file.
+/// The decrypted unspent outputs are cached in memory for coin selection, see [UnspentOutputCache].
 #[derive(Clone)]
 pub struct OutputManagerSqliteDatabase {
     database_connection: WalletDbConnection,
     cipher: Arc<RwLock<Option<XChaCha20Poly1305>>>,
+    unspent_cache: Arc<Mutex<UnspentOutputCache>>,
 }
 
 impl OutputManagerSqliteDatabase {
@@ -75,7 +95,43 @@ impl OutputManagerSqliteDatabase {
         Self {
             database_connection,
             cipher: Arc::new(RwLock::new(cipher)),
+            unspent_cache: Arc::new(Mutex::new(UnspentOutputCache::default())),
+        }
+    }
+
+    fn outputs_version(conn: &SqliteConnection) -> Result<i64, OutputManagerStorageError> {
+        Ok(outputs_version::table.select(outputs_version::version).first(conn)?)
+    }
+
+    /// Runs `f` with the cache of the unspent outputs, after reloading the cache if the outputs changed since it was
+    /// loaded
+    fn with_unspent_cache<T, F: FnOnce(&UnspentOutputCache) -> T>(
+        &self,
+        conn: &SqliteConnection,
+        f: F,
+    ) -> Result<T, OutputManagerStorageError> {
+        let mut cache = acquire_lock!(self.unspent_cache, lock);
+        if !cache.is_current(Self::outputs_version(conn)?) {
+            // The version and the outputs are read in one transaction, so that they agree
+            let (version, outputs) = conn.transaction::<_, OutputManagerStorageError, _>(|| {
+                Ok((
+                    Self::outputs_version(conn)?,
+                    OutputSql::index_status(OutputStatus::Unspent, conn)?,
+                ))
+            })?;
+            let num_outputs = outputs.len();
+            let mut unspent = Vec::with_capacity(num_outputs);
+            for mut o in outputs {
+                self.decrypt_if_necessary(&mut o)?;
+                unspent.push((o.id, DbUnblindedOutput::try_from(o)?));
+            }
+            cache.load(version, unspent);
+            debug!(
+                target: LOG_TARGET,
+                "Loaded {} unspent outputs into the cache at version {}", num_outputs, version
+            );
         }
+        Ok(f(&cache))
     }
 
     fn decrypt_if_necessary<T: Encryptable<XChaCha20Poly1305>>(
@@ -145,6 +201,114 @@ impl OutputManagerSqliteDatabase {
         }
         Ok(())
     }
+
+    fn apply_status_update(
+        update: OutputStatusUpdate,
+        conn: &SqliteConnection,
+    ) -> Result<(), OutputManagerStorageError> {
+        match update {
+            OutputStatusUpdate::Mined {
+                hash,
+                mined_height,
+                mined_in_block,
+                mmr_position,
+                confirmed,
+                mined_timestamp,
+            } => {
+                let status = if confirmed {
+                    OutputStatus::Unspent as i32
+                } else {
+                    OutputStatus::UnspentMinedUnconfirmed as i32
+                };
+                debug!(
+                    target: LOG_TARGET,
+                    "`set_received_output_mined_height` status: {}", status
+                );
+                let hash = hash.to_vec();
+                let mined_in_block = mined_in_block.to_vec();
+                // Only allow updating of non-deleted utxos
+                diesel::update(
+                    outputs::table.filter(outputs::hash.eq(hash).and(outputs::marked_deleted_at_height.is_null())),
+                )
+                .set((
+                    outputs::mined_height.eq(mined_height as i64),
+                    outputs::mined_in_block.eq(mined_in_block),
+                    outputs::mined_mmr_position.eq(mmr_position as i64),
+                    outputs::status.eq(status),
+                    outputs::mined_timestamp.eq(NaiveDateTime::from_timestamp(mined_timestamp as i64, 0)),
+                ))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+            },
+            OutputStatusUpdate::Unmined(hash) => {
+                // Only allow updating of non-deleted utxos
+                let hash = hash.to_vec();
+                diesel::update(
+                    outputs::table.filter(outputs::hash.eq(hash).and(outputs::marked_deleted_at_height.is_null())),
+                )
+                .set((
+                    outputs::mined_height.eq::<Option<i64>>(None),
+                    outputs::mined_in_block.eq::<Option<Vec<u8>>>(None),
+                    outputs::mined_mmr_position.eq::<Option<i64>>(None),
+                    outputs::status.eq(OutputStatus::Invalid as i32),
+                    outputs::mined_timestamp.eq::<Option<NaiveDateTime>>(None),
+                ))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+            },
+            OutputStatusUpdate::Spent {
+                hash,
+                deleted_height,
+                deleted_in_block,
+                confirmed,
+            } => {
+                let hash = hash.to_vec();
+                let deleted_in_block = deleted_in_block.to_vec();
+                let status = if confirmed {
+                    OutputStatus::Spent as i32
+                } else {
+                    OutputStatus::SpentMinedUnconfirmed as i32
+                };
+                // Only allow updating of non-deleted utxos
+                diesel::update(
+                    outputs::table.filter(
+                        outputs::hash.eq(hash).and(
+                            outputs::marked_deleted_in_block
+                                .is_null()
+                                .or(outputs::status.eq(OutputStatus::SpentMinedUnconfirmed as i32)),
+                        ),
+                    ),
+                )
+                .set((
+                    outputs::marked_deleted_at_height.eq(deleted_height as i64),
+                    outputs::marked_deleted_in_block.eq(deleted_in_block),
+                    outputs::status.eq(status),
+                ))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+            },
+            OutputStatusUpdate::Unspent(hash) => {
+                let hash = hash.to_vec();
+                debug!(target: LOG_TARGET, "mark_output_as_unspent({})", hash.to_hex());
+                diesel::update(
+                    outputs::table.filter(
+                        outputs::hash
+                            .eq(hash)
+                            .and(outputs::marked_deleted_at_height.is_not_null())
+                            .and(outputs::mined_height.is_not_null()),
+                    ),
+                )
+                .set((
+                    outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+                    outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+                    outputs::status.eq(OutputStatus::Unspent as i32),
+                ))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+            },
+        }
+        Ok(())
+    }
 }
 
 impl OutputManagerBackend for OutputManagerSqliteDatabase {
@@ -479,28 +643,17 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let status = if confirmed {
-            OutputStatus::Unspent as i32
-        } else {
-            OutputStatus::UnspentMinedUnconfirmed as i32
-        };
-        debug!(
-            target: LOG_TARGET,
-            "`set_received_output_mined_height` status: {}", status
-        );
-        let hash = hash.to_vec();
-        let mined_in_block = mined_in_block.to_vec();
-        // Only allow updating of non-deleted utxos
-        diesel::update(outputs::table.filter(outputs::hash.eq(hash).and(outputs::marked_deleted_at_height.is_null())))
-            .set((
-                outputs::mined_height.eq(mined_height as i64),
-                outputs::mined_in_block.eq(mined_in_block),
-                outputs::mined_mmr_position.eq(mmr_position as i64),
-                outputs::status.eq(status),
-                outputs::mined_timestamp.eq(NaiveDateTime::from_timestamp(mined_timestamp as i64, 0)),
-            ))
-            .execute(&conn)
-            .num_rows_affected_or_not_found(1)?;
+        Self::apply_status_update(
+            OutputStatusUpdate::Mined {
+                hash,
+                mined_height,
+                mined_in_block,
+                mmr_position,
+                confirmed,
+                mined_timestamp,
+            },
+            &conn,
+        )?;
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -518,18 +671,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        // Only allow updating of non-deleted utxos
-        let hash = hash.to_vec();
-        diesel::update(outputs::table.filter(outputs::hash.eq(hash).and(outputs::marked_deleted_at_height.is_null())))
-            .set((
-                outputs::mined_height.eq::<Option<i64>>(None),
-                outputs::mined_in_block.eq::<Option<Vec<u8>>>(None),
-                outputs::mined_mmr_position.eq::<Option<i64>>(None),
-                outputs::status.eq(OutputStatus::Invalid as i32),
-                outputs::mined_timestamp.eq::<Option<NaiveDateTime>>(None),
-            ))
-            .execute(&conn)
-            .num_rows_affected_or_not_found(1)?;
+        Self::apply_status_update(OutputStatusUpdate::Unmined(hash), &conn)?;
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -581,30 +723,15 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let hash = hash.to_vec();
-        let mark_deleted_in_block = mark_deleted_in_block.to_vec();
-        let status = if confirmed {
-            OutputStatus::Spent as i32
-        } else {
-            OutputStatus::SpentMinedUnconfirmed as i32
-        };
-        // Only allow updating of non-deleted utxos
-        diesel::update(
-            outputs::table.filter(
-                outputs::hash.eq(hash).and(
-                    outputs::marked_deleted_in_block
-                        .is_null()
-                        .or(outputs::status.eq(OutputStatus::SpentMinedUnconfirmed as i32)),
-                ),
-            ),
-        )
-        .set((
-            outputs::marked_deleted_at_height.eq(mark_deleted_at_height as i64),
-            outputs::marked_deleted_in_block.eq(mark_deleted_in_block),
-            outputs::status.eq(status),
-        ))
-        .execute(&conn)
-        .num_rows_affected_or_not_found(1)?;
+        Self::apply_status_update(
+            OutputStatusUpdate::Spent {
+                hash,
+                deleted_height: mark_deleted_at_height,
+                deleted_in_block: mark_deleted_in_block,
+                confirmed,
+            },
+            &conn,
+        )?;
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -622,23 +749,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let hash = hash.to_vec();
-        debug!(target: LOG_TARGET, "mark_output_as_unspent({})", hash.to_hex());
-        diesel::update(
-            outputs::table.filter(
-                outputs::hash
-                    .eq(hash)
-                    .and(outputs::marked_deleted_at_height.is_not_null())
-                    .and(outputs::mined_height.is_not_null()),
-            ),
-        )
-        .set((
-            outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
-            outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
-            outputs::status.eq(OutputStatus::Unspent as i32),
-        ))
-        .execute(&conn)
-        .num_rows_affected_or_not_found(1)?;
+        Self::apply_status_update(OutputStatusUpdate::Unspent(hash), &conn)?;
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -652,6 +763,31 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn apply_status_updates(&self, updates: Vec<OutputStatusUpdate>) -> Result<(), OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let num_updates = updates.len();
+        conn.transaction::<_, OutputManagerStorageError, _>(|| {
+            for update in updates {
+                Self::apply_status_update(update, &conn)?;
+            }
+            Ok(())
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - apply_status_updates ({} updates): lock {} + db_op {} = {} ms",
+                num_updates,
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+
+        Ok(())
+    }
+
     fn short_term_encumber_outputs(
         &self,
         tx_id: TxId,
@@ -662,39 +798,48 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
 
-        let mut outputs_to_be_spent = Vec::with_capacity(outputs_to_send.len());
-        for i in outputs_to_send {
-            let output = OutputSql::find_by_commitment_and_cancelled(i.commitment.as_bytes(), false, &conn)?;
-            if output.status != (OutputStatus::Unspent as i32) {
-                return Err(OutputManagerStorageError::OutputAlreadySpent);
-            }
-            if output.status == (OutputStatus::EncumberedToBeSpent as i32) {
-                return Err(OutputManagerStorageError::OutputAlreadyEncumbered);
+        // The spent outputs are removed from the cache of unspent outputs as they are encumbered, so that the cache
+        // does not have to be reloaded for every transaction that is sent
+        let mut cache = acquire_lock!(self.unspent_cache, lock);
+        let (version, new_version) = conn.transaction::<_, OutputManagerStorageError, _>(|| {
+            let version = Self::outputs_version(&conn)?;
+            let mut outputs_to_be_spent = Vec::with_capacity(outputs_to_send.len());
+            for i in outputs_to_send {
+                let output = OutputSql::find_by_commitment_and_cancelled(i.commitment.as_bytes(), false, &conn)?;
+                if output.status != (OutputStatus::Unspent as i32) {
+                    return Err(OutputManagerStorageError::OutputAlreadySpent);
+                }
+                if output.status == (OutputStatus::EncumberedToBeSpent as i32) {
+                    return Err(OutputManagerStorageError::OutputAlreadyEncumbered);
+                }
+                outputs_to_be_spent.push(output);
             }
-            outputs_to_be_spent.push(output);
-        }
 
-        for o in outputs_to_be_spent {
-            o.update(
-                UpdateOutput {
-                    status: Some(OutputStatus::ShortTermEncumberedToBeSpent),
-                    spent_in_tx_id: Some(Some(tx_id)),
-                    ..Default::default()
-                },
-                &conn,
-            )?;
-        }
+            for o in outputs_to_be_spent {
+                o.update(
+                    UpdateOutput {
+                        status: Some(OutputStatus::ShortTermEncumberedToBeSpent),
+                        spent_in_tx_id: Some(Some(tx_id)),
+                        ..Default::default()
+                    },
+                    &conn,
+                )?;
+            }
 
-        for co in outputs_to_receive {
-            let mut new_output = NewOutputSql::new(
-                co.clone(),
-                OutputStatus::ShortTermEncumberedToBeReceived,
-                Some(tx_id),
-                None,
-            )?;
-            self.encrypt_if_necessary(&mut new_output)?;
-            new_output.commit(&conn)?;
-        }
+            for co in outputs_to_receive {
+                let mut new_output = NewOutputSql::new(
+                    co.clone(),
+                    OutputStatus::ShortTermEncumberedToBeReceived,
+                    Some(tx_id),
+                    None,
+                )?;
+                self.encrypt_if_necessary(&mut new_output)?;
+                new_output.commit(&conn)?;
+            }
+            Ok((version, Self::outputs_version(&conn)?))
+        })?;
+        let spent = outputs_to_send.iter().map(|o| o.commitment.clone()).collect::<Vec<_>>();
+        cache.remove(version, new_version, &spent);
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -846,7 +991,11 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
 
-        let result = OutputSql::get_balance(current_tip_for_time_lock_calculation, &conn);
+        // The time locked balance is summed over the cached unspent outputs, the other balances are running totals
+        let mut result = OutputSql::get_balance(None, &conn);
+        if let (Ok(balance), Some(tip)) = (result.as_mut(), current_tip_for_time_lock_calculation) {
+            balance.time_locked_balance = Some(self.with_unspent_cache(&conn, |cache| cache.time_locked_balance(tip))?);
+        }
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -859,6 +1008,25 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         result
     }
 
+    fn recount_balance_totals(&self) -> Result<Vec<BalanceTotalDrift>, OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let drifts =
+            conn.transaction::<_, OutputManagerStorageError, _>(|| OutputSql::recount_balance_totals(&conn))?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - recount_balance_totals: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(drifts)
+    }
+
     fn cancel_pending_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
@@ -1116,6 +1284,54 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn abandon_unmined_coinbases(&self, max_block_height: u64) -> Result<Vec<TxId>, OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let coinbases = OutputSql::index_unmined_coinbases(max_block_height, &conn)?;
+
+        let mut tx_ids = Vec::with_capacity(coinbases.len());
+        for o in coinbases {
+            o.update(
+                UpdateOutput {
+                    status: Some(OutputStatus::AbandonedCoinbase),
+                    ..Default::default()
+                },
+                &conn,
+            )?;
+            if let Some(tx_id) = o.received_in_tx_id {
+                tx_ids.push((tx_id as u64).into());
+            }
+        }
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - abandon_unmined_coinbases: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(tx_ids)
+    }
+
+    fn get_coinbase_stats(&self) -> Result<CoinbaseStats, OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let stats = OutputSql::get_coinbase_stats(&conn)?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - get_coinbase_stats: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(stats)
+    }
+
     fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
@@ -1177,10 +1393,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        let mut outputs = OutputSql::fetch_unspent_outputs_for_spending(selection_criteria, amount, tip_height, &conn)?;
-        for o in &mut outputs {
-            self.decrypt_if_necessary(o)?;
-        }
+        let outputs = self.with_unspent_cache(&conn, |cache| cache.select(selection_criteria, amount, tip_height))?;
         trace!(
             target: LOG_TARGET,
             "sqlite profile - fetch_unspent_outputs_for_spending: lock {} + db_op {} = {} ms",
@@ -1188,10 +1401,63 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             (start.elapsed() - acquire_lock).as_millis(),
             start.elapsed().as_millis()
         );
-        outputs
-            .iter()
-            .map(|o| DbUnblindedOutput::try_from(o.clone()))
-            .collect::<Result<Vec<_>, _>>()
+        Ok(outputs)
+    }
+
+    fn warm_up_cache(&self) -> Result<(), OutputManagerStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        self.with_unspent_cache(&conn, |_| ())
+    }
+
+    fn add_deposit_identity(&self, identity: DepositIdentity) -> Result<(), OutputManagerStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        DepositIdentitySql::from(identity).commit(&conn)
+    }
+
+    fn fetch_deposit_identities(&self) -> Result<Vec<DepositIdentity>, OutputManagerStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        DepositIdentitySql::index(&conn)?
+            .into_iter()
+            .map(DepositIdentity::try_from)
+            .collect()
+    }
+
+    fn fetch_deposit_identity(&self, label: &str) -> Result<Option<DepositIdentity>, OutputManagerStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        DepositIdentitySql::find_by_label(label, &conn)?
+            .map(DepositIdentity::try_from)
+            .transpose()
+    }
+
+    fn add_deposits(&self, deposits: &[Deposit]) -> Result<(), OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        conn.transaction::<_, OutputManagerStorageError, _>(|| {
+            for deposit in deposits {
+                DepositSql::from(deposit).commit(&conn)?;
+            }
+            Ok(())
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - add_deposits: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
+    fn fetch_deposits(&self, key_index: u64) -> Result<Vec<Deposit>, OutputManagerStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        DepositSql::index_by_key_index(key_index, &conn)?
+            .into_iter()
+            .map(Deposit::try_from)
+            .collect()
     }
 
     fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbUnblindedOutput>, OutputManagerStorageError> {
@@ -1228,6 +1494,70 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             })
             .collect())
     }
+
+    fn fetch_outputs_page(
+        &self,
+        set: OutputSet,
+        after_id: Option<i32>,
+        limit: usize,
+    ) -> Result<OutputPage, OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let limit = i64::try_from(limit).map_err(|_| OutputManagerStorageError::ConversionError {
+            reason: "Page size is too large".to_string(),
+        })?;
+        let page = OutputSql::fetch_page(set, after_id, limit, &conn)?;
+        let last_id = page.last().map(|o| o.id);
+        let mut outputs = Vec::with_capacity(page.len());
+        for mut o in page {
+            self.decrypt_if_necessary(&mut o)?;
+            outputs.push(DbUnblindedOutput::try_from(o)?);
+        }
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - fetch_outputs_page: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+
+        Ok(OutputPage { outputs, last_id })
+    }
+
+    fn fetch_output_tx_references(&self) -> Result<Vec<OutputTxReference>, OutputManagerStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let outputs = OutputSql::index(&conn)?;
+        let mut references = Vec::with_capacity(outputs.len());
+        for mut o in outputs {
+            let received_in_tx_id = o.received_in_tx_id.map(|tx_id| TxId::from(tx_id as u64));
+            let spent_in_tx_id = o.spent_in_tx_id.map(|tx_id| TxId::from(tx_id as u64));
+            self.decrypt_if_necessary(&mut o)?;
+            let output = DbUnblindedOutput::try_from(o)?;
+            references.push(OutputTxReference {
+                commitment: output.commitment,
+                status: output.status,
+                received_in_tx_id,
+                spent_in_tx_id,
+            });
+        }
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - fetch_output_tx_references: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+
+        Ok(references)
+    }
 }
 
 /// These are the fields that can be updated for an Output
@@ -1455,33 +1785,50 @@ mod test {
     use std::{mem::size_of, time::Duration};
 
     use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
-    use diesel::{Connection, SqliteConnection};
+    use chrono::Utc;
+    use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+    use futures::TryStreamExt;
     use rand::{rngs::OsRng, RngCore};
     use tari_common_sqlite::sqlite_connection_pool::SqliteConnectionPool;
-    use tari_common_types::types::CommitmentFactory;
+    use tari_common_types::{
+        transaction::TxId,
+        types::{CommitmentFactory, PrivateKey, PublicKey},
+    };
     use tari_core::transactions::{
         tari_amount::MicroTari,
         test_helpers::{create_unblinded_output, TestParams as TestParamsHelpers},
         transaction_components::{OutputFeatures, TransactionInput, UnblindedOutput},
         CryptoFactories,
     };
+    use tari_crypto::{
+        commitment::HomomorphicCommitmentFactory,
+        keys::{PublicKey as PublicKeyTrait, SecretKey},
+        tari_utilities::ByteArray,
+    };
     use tari_script::script;
     use tari_test_utils::random;
     use tempfile::tempdir;
 
     use crate::{
-        output_manager_service::storage::{
-            database::{DbKey, OutputManagerBackend},
-            models::DbUnblindedOutput,
-            sqlite_db::{
-                new_output_sql::NewOutputSql,
-                output_sql::OutputSql,
-                OutputManagerSqliteDatabase,
-                OutputStatus,
-                UpdateOutput,
+        output_manager_service::{
+            input_selection::UtxoSelectionCriteria,
+            service::CoinbaseStats,
+            storage::{
+                database::{BalanceTotalDrift, DbKey, OutputManagerBackend, OutputSet, OutputStatusUpdate},
+                models::DbUnblindedOutput,
+                sqlite_db::{
+                    new_output_sql::NewOutputSql,
+                    output_sql::OutputSql,
+                    OutputManagerSqliteDatabase,
+                    OutputStatus,
+                    UpdateOutput,
+                },
+                OutputSource,
             },
-            OutputSource,
+            Deposit,
+            DepositIdentity,
         },
+        schema::{output_balances, outputs},
         storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
         util::encryption::Encryptable,
     };
@@ -1612,6 +1959,442 @@ mod test {
         assert_eq!(result[0].spending_key, outputs[1].spending_key);
     }
 
+    #[test]
+    fn test_balance_totals() {
+        let db_name = format!("{}.sqlite3", random::string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let conn = SqliteConnection::establish(&db_path).unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+
+        embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+
+        let factories = CryptoFactories::default();
+        let mut outputs = Vec::new();
+        for (value, status, source) in [
+            (100, OutputStatus::Unspent, OutputSource::Standard),
+            (200, OutputStatus::Unspent, OutputSource::OneSided),
+            (400, OutputStatus::Unspent, OutputSource::ColdStorageChange),
+            (800, OutputStatus::EncumberedToBeReceived, OutputSource::Standard),
+            (1600, OutputStatus::Spent, OutputSource::Standard),
+        ] {
+            let (_, uo) = make_input(MicroTari::from(value));
+            let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories, None, source).unwrap();
+            let o = NewOutputSql::new(uo, status, None, None).unwrap();
+            o.commit(&conn).unwrap();
+            outputs.push(o);
+        }
+
+        let balance = OutputSql::get_balance(Some(1), &conn).unwrap();
+        assert_eq!(balance.available_balance, MicroTari::from(300));
+        assert_eq!(balance.time_locked_balance, Some(MicroTari::from(0)));
+        assert_eq!(balance.pending_incoming_balance, MicroTari::from(800));
+        assert_eq!(balance.pending_outgoing_balance, MicroTari::from(0));
+
+        let _updated = OutputSql::find(&outputs[0].spending_key, &conn)
+            .unwrap()
+            .update(
+                UpdateOutput {
+                    status: Some(OutputStatus::EncumberedToBeSpent),
+                    ..Default::default()
+                },
+                &conn,
+            )
+            .unwrap();
+        OutputSql::find(&outputs[3].spending_key, &conn)
+            .unwrap()
+            .delete(&conn)
+            .unwrap();
+
+        let balance = OutputSql::get_balance(None, &conn).unwrap();
+        assert_eq!(balance.available_balance, MicroTari::from(200));
+        assert_eq!(balance.time_locked_balance, None);
+        assert_eq!(balance.pending_incoming_balance, MicroTari::from(0));
+        assert_eq!(balance.pending_outgoing_balance, MicroTari::from(100));
+        assert!(OutputSql::recount_balance_totals(&conn).unwrap().is_empty());
+
+        // A change that bypasses the triggers is found and corrected by the recount
+        diesel::delete(output_balances::table)
+            .filter(output_balances::source.eq(OutputSource::OneSided as i32))
+            .execute(&conn)
+            .unwrap();
+        assert_eq!(
+            OutputSql::get_balance(None, &conn).unwrap().available_balance,
+            MicroTari::from(0)
+        );
+        let drifts = OutputSql::recount_balance_totals(&conn).unwrap();
+        assert_eq!(drifts, vec![BalanceTotalDrift {
+            status: OutputStatus::Unspent,
+            source: OutputSource::OneSided,
+            recorded: 0,
+            recounted: 200,
+        }]);
+        assert_eq!(
+            OutputSql::get_balance(None, &conn).unwrap().available_balance,
+            MicroTari::from(200)
+        );
+        assert!(OutputSql::recount_balance_totals(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_abandon_unmined_coinbases() {
+        let db_name = format!("{}.sqlite3", random::string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let conn = SqliteConnection::establish(&db_path).unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+
+        embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+
+        let factories = CryptoFactories::default();
+        let mut outputs = Vec::new();
+        for (tx_id, height, status) in [
+            (1u64, 10, OutputStatus::EncumberedToBeReceived),
+            (2, 10, OutputStatus::Invalid),
+            (3, 11, OutputStatus::Unspent),
+            (4, 12, OutputStatus::EncumberedToBeReceived),
+        ] {
+            let (_, uo) = make_input(MicroTari::from(100 * tx_id));
+            let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories, None, OutputSource::Coinbase).unwrap();
+            let o = NewOutputSql::new(uo, status, Some(tx_id.into()), Some(height)).unwrap();
+            o.commit(&conn).unwrap();
+            outputs.push(o);
+        }
+        let _mined = diesel::update(outputs::table.filter(outputs::spending_key.eq(&outputs[2].spending_key)))
+            .set((
+                outputs::mined_height.eq(11i64),
+                outputs::mined_in_block.eq(vec![0u8; 32]),
+            ))
+            .execute(&conn)
+            .unwrap();
+
+        // The coinbase of the block that is mined is left alone, as are coinbases above the height
+        let coinbases = OutputSql::index_unmined_coinbases(11, &conn).unwrap();
+        assert_eq!(coinbases.iter().map(|o| o.received_in_tx_id).collect::<Vec<_>>(), vec![
+            Some(1),
+            Some(2)
+        ]);
+        for o in coinbases {
+            let _abandoned = o
+                .update(
+                    UpdateOutput {
+                        status: Some(OutputStatus::AbandonedCoinbase),
+                        ..Default::default()
+                    },
+                    &conn,
+                )
+                .unwrap();
+        }
+        assert!(OutputSql::index_unmined_coinbases(11, &conn).unwrap().is_empty());
+
+        let stats = OutputSql::get_coinbase_stats(&conn).unwrap();
+        assert_eq!(stats, CoinbaseStats {
+            pending: 1,
+            pending_value: MicroTari::from(400),
+            mined: 1,
+            mined_value: MicroTari::from(300),
+            abandoned: 2,
+            abandoned_value: MicroTari::from(300),
+            last_mined_height: Some(11),
+        });
+    }
+
+    #[test]
+    fn test_apply_status_updates() {
+        let db_name = format!("{}.sqlite3", random::string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        let connection = WalletDbConnection::new(pool, None);
+
+        let factories = CryptoFactories::default();
+        let mut outputs = Vec::new();
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+            for _ in 0..2 {
+                let (_, uo) = make_input(MicroTari::from(100 + OsRng.next_u64() % 1000));
+                let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories, None, OutputSource::Unknown).unwrap();
+                outputs.push((uo.hash, uo.commitment.clone()));
+                let output = NewOutputSql::new(uo, OutputStatus::EncumberedToBeReceived, None, None).unwrap();
+                output.commit(&conn).unwrap();
+            }
+        }
+        let status_of = |i: usize| {
+            let conn = connection.get_pooled_connection().unwrap();
+            OutputSql::find_by_commitment(outputs[i].1.as_bytes(), &conn)
+                .unwrap()
+                .status
+        };
+
+        let db = OutputManagerSqliteDatabase::new(connection.clone(), None);
+        let block = [1u8; 32].into();
+        db.apply_status_updates(vec![
+            OutputStatusUpdate::Mined {
+                hash: outputs[0].0,
+                mined_height: 10,
+                mined_in_block: block,
+                mmr_position: 1,
+                confirmed: true,
+                mined_timestamp: 0,
+            },
+            OutputStatusUpdate::Mined {
+                hash: outputs[1].0,
+                mined_height: 12,
+                mined_in_block: block,
+                mmr_position: 2,
+                confirmed: false,
+                mined_timestamp: 0,
+            },
+        ])
+        .unwrap();
+        assert_eq!(status_of(0), OutputStatus::Unspent as i32);
+        assert_eq!(status_of(1), OutputStatus::UnspentMinedUnconfirmed as i32);
+
+        // The second output was never spent, so it cannot be marked as unspent and the whole batch is rolled back
+        let result = db.apply_status_updates(vec![
+            OutputStatusUpdate::Spent {
+                hash: outputs[0].0,
+                deleted_height: 13,
+                deleted_in_block: block,
+                confirmed: false,
+            },
+            OutputStatusUpdate::Unspent(outputs[1].0),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(status_of(0), OutputStatus::Unspent as i32);
+
+        db.apply_status_updates(vec![
+            OutputStatusUpdate::Spent {
+                hash: outputs[0].0,
+                deleted_height: 13,
+                deleted_in_block: block,
+                confirmed: false,
+            },
+            OutputStatusUpdate::Unmined(outputs[1].0),
+        ])
+        .unwrap();
+        assert_eq!(status_of(0), OutputStatus::SpentMinedUnconfirmed as i32);
+        assert_eq!(status_of(1), OutputStatus::Invalid as i32);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_outputs_stream() {
+        let db_name = format!("{}.sqlite3", random::string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        let connection = WalletDbConnection::new(pool, None);
+
+        let factories = CryptoFactories::default();
+        let mut unspent = Vec::new();
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+            for i in 0..5 {
+                let (_, uo) = make_input(MicroTari::from(100 + OsRng.next_u64() % 1000));
+                let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories, None, OutputSource::Unknown).unwrap();
+                let status = if i % 2 == 0 {
+                    unspent.push(uo.commitment.clone());
+                    OutputStatus::Unspent
+                } else {
+                    OutputStatus::Spent
+                };
+                NewOutputSql::new(uo, status, None, None)
+                    .unwrap()
+                    .commit(&conn)
+                    .unwrap();
+            }
+        }
+
+        let db = OutputManagerSqliteDatabase::new(connection, None);
+        let pages = db
+            .fetch_outputs_stream(OutputSet::All, 2)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(pages.iter().map(|p| p.len()).collect::<Vec<_>>(), vec![2, 2, 1]);
+
+        let pages = db
+            .fetch_outputs_stream(OutputSet::Unspent, 2)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 2);
+        let commitments = pages.into_iter().flatten().map(|o| o.commitment).collect::<Vec<_>>();
+        assert_eq!(commitments, unspent);
+
+        // A set that fills the last page exactly ends without an empty page
+        let pages = db
+            .fetch_outputs_stream(OutputSet::Unspent, 3)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 1);
+        assert!(db
+            .fetch_outputs_stream(OutputSet::MinedUnspent, 3)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_unspent_output_cache() {
+        let db_name = format!("{}.sqlite3", random::string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        let connection = WalletDbConnection::new(pool, None);
+
+        let factories = CryptoFactories::default();
+        let mut outputs = Vec::new();
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+            for (value, source) in [
+                (100, OutputSource::Standard),
+                (400, OutputSource::Standard),
+                (200, OutputSource::OneSided),
+                (300, OutputSource::Standard),
+                (1000, OutputSource::ColdStorageChange),
+            ] {
+                let (_, uo) = make_input(MicroTari::from(value));
+                let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories, None, source).unwrap();
+                NewOutputSql::new(uo.clone(), OutputStatus::Unspent, None, None)
+                    .unwrap()
+                    .commit(&conn)
+                    .unwrap();
+                outputs.push(uo);
+            }
+        }
+
+        let db = OutputManagerSqliteDatabase::new(connection.clone(), None);
+        db.warm_up_cache().unwrap();
+        let values = |criteria: &UtxoSelectionCriteria, amount: u64| {
+            let cached = db
+                .fetch_unspent_outputs_for_spending(criteria, amount, Some(10))
+                .unwrap()
+                .into_iter()
+                .map(|o| o.unblinded_output.value.as_u64())
+                .collect::<Vec<_>>();
+            // The cache selects the same outputs in the same order as the query it replaces
+            let conn = connection.get_pooled_connection().unwrap();
+            let queried = OutputSql::fetch_unspent_outputs_for_spending(criteria, amount, Some(10), &conn)
+                .unwrap()
+                .into_iter()
+                .map(|o| o.value as u64)
+                .collect::<Vec<_>>();
+            assert_eq!(cached, queried);
+            cached
+        };
+        assert_eq!(values(&UtxoSelectionCriteria::smallest_first(), 0), vec![
+            100, 200, 300, 400
+        ]);
+        assert_eq!(values(&UtxoSelectionCriteria::largest_first(), 0), vec![
+            400, 300, 200, 100
+        ]);
+        assert_eq!(values(&UtxoSelectionCriteria::default(), 500), vec![400, 300, 200, 100]);
+        assert_eq!(values(&UtxoSelectionCriteria::default(), 50), vec![100, 200, 300, 400]);
+        let criteria = UtxoSelectionCriteria {
+            excluding_onesided: true,
+            excluding: vec![outputs[3].commitment.clone()],
+            ..UtxoSelectionCriteria::smallest_first()
+        };
+        assert_eq!(values(&criteria, 0), vec![100, 400]);
+
+        // Encumbered outputs are removed from the cache as they are written
+        db.short_term_encumber_outputs(TxId::from(1u64), &outputs[..1], &[])
+            .unwrap();
+        assert_eq!(values(&UtxoSelectionCriteria::smallest_first(), 0), vec![200, 300, 400]);
+
+        // Changes made outside of the backend are picked up
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            diesel::update(outputs::table.filter(outputs::value.eq(400)))
+                .set(outputs::status.eq(OutputStatus::Spent as i32))
+                .execute(&conn)
+                .unwrap();
+        }
+        assert_eq!(values(&UtxoSelectionCriteria::smallest_first(), 0), vec![200, 300]);
+    }
+
+    #[test]
+    fn test_deposit_identities() {
+        let db_name = format!("{}.sqlite3", random::string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        let connection = WalletDbConnection::new(pool, None);
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+        }
+        let db = OutputManagerSqliteDatabase::new(connection, None);
+
+        let created_at = Utc::now().naive_utc();
+        let identities = ["alice", "bob"]
+            .iter()
+            .enumerate()
+            .map(|(i, label)| DepositIdentity {
+                key_index: i as u64,
+                label: label.to_string(),
+                public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+                created_at,
+            })
+            .collect::<Vec<_>>();
+        for identity in &identities {
+            db.add_deposit_identity(identity.clone()).unwrap();
+        }
+        assert!(db.add_deposit_identity(identities[0].clone()).is_err());
+        assert_eq!(db.fetch_deposit_identities().unwrap(), identities);
+        assert_eq!(db.fetch_deposit_identity("bob").unwrap(), Some(identities[1].clone()));
+        assert_eq!(db.fetch_deposit_identity("carol").unwrap(), None);
+
+        let factory = CommitmentFactory::default();
+        let deposits = [(0, 100), (1, 200), (1, 300)]
+            .iter()
+            .enumerate()
+            .map(|(i, (key_index, value))| Deposit {
+                key_index: *key_index,
+                commitment: factory.commit_value(&PrivateKey::random(&mut OsRng), *value),
+                value: MicroTari::from(*value),
+                tx_id: TxId::from(i as u64),
+                received_at: created_at,
+            })
+            .collect::<Vec<_>>();
+        db.add_deposits(&deposits).unwrap();
+        // Deposits that are found again by a later scan are not recorded twice
+        db.add_deposits(&deposits[1..2]).unwrap();
+        assert_eq!(db.fetch_deposits(0).unwrap(), deposits[..1]);
+        assert_eq!(db.fetch_deposits(1).unwrap(), deposits[1..]);
+        assert!(db.fetch_deposits(2).unwrap().is_empty());
+    }
+
     #[test]
     fn test_output_encryption() {
         let db_name = format!("{}.sqlite3", random::string(8).as_str());
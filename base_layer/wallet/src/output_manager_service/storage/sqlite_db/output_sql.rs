@@ -20,7 +20,10 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::convert::{TryFrom, TryInto};
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+};
 
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::NaiveDateTime;
@@ -46,9 +49,9 @@ use crate::{
     output_manager_service::{
         error::OutputManagerStorageError,
         input_selection::UtxoSelectionCriteria,
-        service::Balance,
+        service::{Balance, CoinbaseStats},
         storage::{
-            database::{OutputBackendQuery, SortDirection},
+            database::{BalanceTotalDrift, OutputBackendQuery, OutputSet, SortDirection},
             models::DbUnblindedOutput,
             sqlite_db::{UpdateOutput, UpdateOutputSql},
             OutputSource,
@@ -57,7 +60,7 @@ use crate::{
         UtxoSelectionFilter,
         UtxoSelectionOrdering,
     },
-    schema::outputs,
+    schema::{output_balances, outputs},
     util::{
         diesel_ext::ExpectedRowsExtension,
         encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
@@ -200,6 +203,8 @@ impl OutputSql {
         let mut query = outputs::table
             .into_boxed()
             .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+            // Change sent to cold storage can only be spent by the cold wallet
+            .filter(outputs::source.ne(OutputSource::ColdStorageChange as i32))
             .order_by(outputs::spending_priority.desc());
 
         match &selection_criteria.filter {
@@ -241,6 +246,7 @@ impl OutputSql {
                 // lets get the max value for all utxos
                 let max: Option<i64> = outputs::table
                     .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+                    .filter(outputs::source.ne(OutputSource::ColdStorageChange as i32))
                     .filter(outputs::script_lock_height.le(i64_tip_height))
                     .filter(outputs::maturity.le(i64_tip_height))
                     .order(outputs::value.desc())
@@ -309,7 +315,7 @@ impl OutputSql {
         output_type: OutputType,
         conn: &SqliteConnection,
     ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
-        let res = diesel::sql_query("SELECT * FROM outputs where output_type & $1 = $1 ORDER BY id;")
+        let res = diesel::sql_query("SELECT * FROM outputs where output_type = $1 ORDER BY id;")
             .bind::<diesel::sql_types::Integer, _>(i32::from(output_type.as_byte()))
             .load(conn)?;
         Ok(res)
@@ -334,6 +340,36 @@ impl OutputSql {
             .load(conn)?)
     }
 
+    /// Returns at most `limit` outputs in the set, ordered by id and starting after the output with id `after_id`
+    pub fn fetch_page(
+        set: OutputSet,
+        after_id: Option<i32>,
+        limit: i64,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
+        let mut query = outputs::table.into_boxed().order(outputs::id.asc()).limit(limit);
+        if let Some(after_id) = after_id {
+            query = query.filter(outputs::id.gt(after_id));
+        }
+        query = match set {
+            OutputSet::All => query,
+            OutputSet::Unspent => query.filter(outputs::status.eq(OutputStatus::Unspent as i32)),
+            OutputSet::Unconfirmed => query.filter(
+                outputs::status
+                    .eq(OutputStatus::UnspentMinedUnconfirmed as i32)
+                    .or(outputs::mined_in_block.is_null()),
+            ),
+            OutputSet::MinedUnspent => query
+                .filter(
+                    outputs::marked_deleted_in_block
+                        .is_null()
+                        .or(outputs::status.eq(OutputStatus::SpentMinedUnconfirmed as i32)),
+                )
+                .filter(outputs::mined_in_block.is_not_null()),
+        };
+        Ok(query.load(conn)?)
+    }
+
     pub fn first_by_mined_height_desc(conn: &SqliteConnection) -> Result<Option<OutputSql>, OutputManagerStorageError> {
         Ok(outputs::table
             .filter(outputs::mined_height.is_not_null())
@@ -369,7 +405,9 @@ impl OutputSql {
             .load(conn)?)
     }
 
-    /// Return the available, time locked, pending incoming and pending outgoing balance
+    /// Return the available, time locked, pending incoming and pending outgoing balance. All but the time locked
+    /// balance are read from the running totals in `output_balances`, so their cost does not grow with the number of
+    /// outputs. The time locked balance depends on the tip and is summed over the locked outputs only.
     #[allow(clippy::cast_possible_wrap)]
     pub fn get_balance(
         current_tip_for_time_lock_calculation: Option<u64>,
@@ -384,25 +422,28 @@ impl OutputSql {
         }
         let balance_query_result = if let Some(current_tip) = current_tip_for_time_lock_calculation {
             let balance_query = sql_query(
-                "SELECT coalesce(sum(value), 0) as amount, 'available_balance' as category \
-                 FROM outputs WHERE status = ? \
+                "SELECT coalesce(sum(amount), 0) as amount, 'available_balance' as category \
+                 FROM output_balances WHERE source != ? AND status = ? \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'time_locked_balance' as category \
-                 FROM outputs WHERE status = ? AND maturity > ? OR script_lock_height > ? \
+                 FROM outputs WHERE source != ? AND (status = ? AND maturity > ? OR script_lock_height > ?) \
                  UNION ALL \
-                 SELECT coalesce(sum(value), 0) as amount, 'pending_incoming_balance' as category \
-                 FROM outputs WHERE status = ? OR status = ? OR status = ? \
+                 SELECT coalesce(sum(amount), 0) as amount, 'pending_incoming_balance' as category \
+                 FROM output_balances WHERE source != ? AND (status = ? OR status = ? OR status = ?) \
                  UNION ALL \
-                 SELECT coalesce(sum(value), 0) as amount, 'pending_outgoing_balance' as category \
-                 FROM outputs WHERE status = ? OR status = ? OR status = ?",
+                 SELECT coalesce(sum(amount), 0) as amount, 'pending_outgoing_balance' as category \
+                 FROM output_balances WHERE status = ? OR status = ? OR status = ?",
             )
                 // available_balance
+                .bind::<diesel::sql_types::Integer, _>(OutputSource::ColdStorageChange as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
                 // time_locked_balance
+                .bind::<diesel::sql_types::Integer, _>(OutputSource::ColdStorageChange as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
                 .bind::<diesel::sql_types::BigInt, _>(current_tip as i64)
                 .bind::<diesel::sql_types::BigInt, _>(current_tip as i64)
                 // pending_incoming_balance
+                .bind::<diesel::sql_types::Integer, _>(OutputSource::ColdStorageChange as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::EncumberedToBeReceived as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::ShortTermEncumberedToBeReceived as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::UnspentMinedUnconfirmed as i32)
@@ -413,18 +454,20 @@ impl OutputSql {
             balance_query.load::<BalanceQueryResult>(conn)?
         } else {
             let balance_query = sql_query(
-                "SELECT coalesce(sum(value), 0) as amount, 'available_balance' as category \
-                 FROM outputs WHERE status = ? \
+                "SELECT coalesce(sum(amount), 0) as amount, 'available_balance' as category \
+                 FROM output_balances WHERE source != ? AND status = ? \
                  UNION ALL \
-                 SELECT coalesce(sum(value), 0) as amount, 'pending_incoming_balance' as category \
-                 FROM outputs WHERE status = ? OR status = ? OR status = ? \
+                 SELECT coalesce(sum(amount), 0) as amount, 'pending_incoming_balance' as category \
+                 FROM output_balances WHERE source != ? AND (status = ? OR status = ? OR status = ?) \
                  UNION ALL \
-                 SELECT coalesce(sum(value), 0) as amount, 'pending_outgoing_balance' as category \
-                 FROM outputs WHERE status = ? OR status = ? OR status = ?",
+                 SELECT coalesce(sum(amount), 0) as amount, 'pending_outgoing_balance' as category \
+                 FROM output_balances WHERE status = ? OR status = ? OR status = ?",
             )
                 // available_balance
+                .bind::<diesel::sql_types::Integer, _>(OutputSource::ColdStorageChange as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
                 // pending_incoming_balance
+                .bind::<diesel::sql_types::Integer, _>(OutputSource::ColdStorageChange as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::EncumberedToBeReceived as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::ShortTermEncumberedToBeReceived as i32)
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::UnspentMinedUnconfirmed as i32)
@@ -472,6 +515,57 @@ impl OutputSql {
         })
     }
 
+    /// Recount the running totals in `output_balances` over all the outputs and correct the ones that drifted, e.g.
+    /// because the outputs were changed by a statement that the triggers do not cover. Should be run in a database
+    /// transaction.
+    pub fn recount_balance_totals(
+        conn: &SqliteConnection,
+    ) -> Result<Vec<BalanceTotalDrift>, OutputManagerStorageError> {
+        #[derive(QueryableByName, Clone)]
+        struct BalanceTotal {
+            #[sql_type = "diesel::sql_types::Integer"]
+            status: i32,
+            #[sql_type = "diesel::sql_types::Integer"]
+            source: i32,
+            #[sql_type = "diesel::sql_types::BigInt"]
+            amount: i64,
+        }
+        let recounted =
+            sql_query("SELECT status, source, coalesce(sum(value), 0) as amount FROM outputs GROUP BY status, source")
+                .load::<BalanceTotal>(conn)?;
+        let recorded = output_balances::table.load::<(i32, i32, i64)>(conn)?;
+
+        // Each total as (recorded, recounted), a total that is missing on either side is zero
+        let mut totals = HashMap::<(i32, i32), (i64, i64)>::new();
+        for (status, source, amount) in recorded {
+            totals.entry((status, source)).or_default().0 = amount;
+        }
+        for total in recounted {
+            totals.entry((total.status, total.source)).or_default().1 = total.amount;
+        }
+
+        let mut drifts = Vec::new();
+        for ((status, source), (recorded, recounted)) in totals {
+            if recorded == recounted {
+                continue;
+            }
+            diesel::replace_into(output_balances::table)
+                .values((
+                    output_balances::status.eq(status),
+                    output_balances::source.eq(source),
+                    output_balances::amount.eq(recounted),
+                ))
+                .execute(conn)?;
+            drifts.push(BalanceTotalDrift {
+                status: OutputStatus::try_from(status)?,
+                source: OutputSource::try_from(source)?,
+                recorded,
+                recounted,
+            });
+        }
+        Ok(drifts)
+    }
+
     pub fn find_by_commitment(
         commitment: &[u8],
         conn: &SqliteConnection,
@@ -569,6 +663,60 @@ impl OutputSql {
             .first::<OutputSql>(conn)?)
     }
 
+    /// Return the coinbases for blocks up to `max_block_height` that are not in the chain, i.e. the coinbases of blocks
+    /// that were never mined or were reorged out
+    pub fn index_unmined_coinbases(
+        max_block_height: u64,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
+        Ok(outputs::table
+            .filter(outputs::coinbase_block_height.le(max_block_height as i64))
+            .filter(outputs::mined_in_block.is_null())
+            .filter(outputs::status.eq_any::<Vec<i32>>(vec![
+                OutputStatus::EncumberedToBeReceived as i32,
+                OutputStatus::Invalid as i32,
+            ]))
+            .order(outputs::id.asc())
+            .load(conn)?)
+    }
+
+    /// Tallies the coinbases the wallet generated by their status
+    pub fn get_coinbase_stats(conn: &SqliteConnection) -> Result<CoinbaseStats, OutputManagerStorageError> {
+        let coinbases = outputs::table
+            .filter(outputs::coinbase_block_height.is_not_null())
+            .load::<OutputSql>(conn)?;
+
+        let mut stats = CoinbaseStats::default();
+        for coinbase in coinbases {
+            let value = MicroTari::from(coinbase.value as u64);
+            match OutputStatus::try_from(coinbase.status)? {
+                OutputStatus::EncumberedToBeReceived | OutputStatus::Invalid => {
+                    stats.pending += 1;
+                    stats.pending_value += value;
+                },
+                OutputStatus::AbandonedCoinbase => {
+                    stats.abandoned += 1;
+                    stats.abandoned_value += value;
+                },
+                OutputStatus::Unspent |
+                OutputStatus::UnspentMinedUnconfirmed |
+                OutputStatus::EncumberedToBeSpent |
+                OutputStatus::ShortTermEncumberedToBeSpent |
+                OutputStatus::SpentMinedUnconfirmed |
+                OutputStatus::Spent => {
+                    stats.mined += 1;
+                    stats.mined_value += value;
+                    let mined_height = coinbase.mined_height.map(|h| h as u64);
+                    stats.last_mined_height = stats.last_mined_height.max(mined_height);
+                },
+                OutputStatus::CancelledInbound |
+                OutputStatus::ShortTermEncumberedToBeReceived |
+                OutputStatus::NotStored => {},
+            }
+        }
+        Ok(stats)
+    }
+
     pub fn delete(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
         let num_deleted =
             diesel::delete(outputs::table.filter(outputs::spending_key.eq(&self.spending_key))).execute(conn)?;
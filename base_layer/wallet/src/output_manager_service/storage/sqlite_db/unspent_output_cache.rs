@@ -0,0 +1,136 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! An in-memory cache of the decrypted unspent outputs, so that coin selection and the time locked balance do not
+//! query and decrypt every unspent output from the database. The cache is tagged with the `outputs_version` counter
+//! that triggers increment on every change to the outputs. It is only used while the counter still has that value, so
+//! it is reloaded after any change that it was not updated for, including changes made outside of this backend.
+
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use tari_common_types::types::Commitment;
+use tari_core::transactions::{tari_amount::MicroTari, transaction_components::OutputType};
+
+use crate::output_manager_service::{
+    input_selection::{UtxoSelectionCriteria, UtxoSelectionFilter, UtxoSelectionOrdering},
+    storage::{models::DbUnblindedOutput, OutputSource},
+};
+
+#[derive(Default)]
+pub(super) struct UnspentOutputCache {
+    /// The `outputs_version` that the cache reflects, `None` if it is not loaded
+    version: Option<i64>,
+    /// The unspent outputs, indexed by value and then by id
+    outputs: BTreeMap<(MicroTari, i32), DbUnblindedOutput>,
+}
+
+impl UnspentOutputCache {
+    /// Returns true if the cache is loaded and reflects the outputs at `version`
+    pub fn is_current(&self, version: i64) -> bool {
+        self.version == Some(version)
+    }
+
+    /// Replaces the cached outputs with the unspent outputs at `version`, given with their ids
+    pub fn load<I: IntoIterator<Item = (i32, DbUnblindedOutput)>>(&mut self, version: i64, outputs: I) {
+        self.outputs = outputs
+            .into_iter()
+            .map(|(id, output)| ((output.unblinded_output.value, id), output))
+            .collect();
+        self.version = Some(version);
+    }
+
+    pub fn invalidate(&mut self) {
+        self.version = None;
+        self.outputs.clear();
+    }
+
+    /// Removes outputs that are no longer unspent after a change that moved the outputs from `version` to
+    /// `new_version`. The cache is invalidated if it did not reflect `version`, as it would miss other changes.
+    pub fn remove(&mut self, version: i64, new_version: i64, commitments: &[Commitment]) {
+        if !self.is_current(version) {
+            self.invalidate();
+            return;
+        }
+        self.outputs
+            .retain(|_, output| !commitments.iter().any(|c| *c == output.commitment));
+        self.version = Some(new_version);
+    }
+
+    /// Selects the unspent outputs that may be spent, in the order that coin selection should consider them. This
+    /// matches the selection of `OutputSql::fetch_unspent_outputs_for_spending`.
+    pub fn select(&self, criteria: &UtxoSelectionCriteria, amount: u64, tip: Option<u64>) -> Vec<DbUnblindedOutput> {
+        let spendable = self
+            .outputs
+            .iter()
+            // Change sent to cold storage can only be spent by the cold wallet
+            .filter(|(_, output)| output.source != OutputSource::ColdStorageChange);
+        let unlocked_by = |tip: u64, output: &DbUnblindedOutput| {
+            output.unblinded_output.script_lock_height <= tip && output.unblinded_output.features.maturity <= tip
+        };
+
+        let largest_first = match criteria.ordering {
+            UtxoSelectionOrdering::SmallestFirst => false,
+            UtxoSelectionOrdering::LargestFirst => true,
+            UtxoSelectionOrdering::Default => {
+                // Use fewer inputs to reduce the fee if no single output covers the amount, otherwise use the smaller
+                // outputs to make up the amount
+                let tip = tip.unwrap_or(u64::MAX);
+                let max = spendable
+                    .clone()
+                    .filter(|(_, output)| unlocked_by(tip, output))
+                    .map(|((value, _), _)| *value)
+                    .max();
+                matches!(max, Some(max) if amount > max.as_u64())
+            },
+        };
+
+        let mut selected = spendable
+            .filter(|(_, output)| match &criteria.filter {
+                UtxoSelectionFilter::Standard => {
+                    matches!(
+                        output.unblinded_output.features.output_type,
+                        OutputType::Standard | OutputType::Coinbase
+                    ) && !(criteria.excluding_onesided && output.source == OutputSource::OneSided)
+                },
+                UtxoSelectionFilter::SpecificOutputs { commitments } => {
+                    commitments.is_empty() || commitments.contains(&output.commitment)
+                },
+            })
+            .filter(|(_, output)| !criteria.excluding.contains(&output.commitment))
+            .filter(|(_, output)| tip.map_or(true, |tip| unlocked_by(tip, output)))
+            .collect::<Vec<_>>();
+        selected.sort_by_key(|((value, id), output)| {
+            let value = if largest_first {
+                u64::MAX - value.as_u64()
+            } else {
+                value.as_u64()
+            };
+            // If the tip is not known, prefer the outputs that mature first to reduce the chances of a locked output
+            // being used
+            let maturity = if tip.is_none() {
+                output.unblinded_output.features.maturity
+            } else {
+                0
+            };
+            (
+                Reverse(u32::from(output.spending_priority.clone())),
+                value,
+                maturity,
+                *id,
+            )
+        });
+        selected.into_iter().map(|(_, output)| output.clone()).collect()
+    }
+
+    /// The value of the unspent outputs that are still locked at `tip`, excluding the change sent to cold storage
+    pub fn time_locked_balance(&self, tip: u64) -> MicroTari {
+        self.outputs
+            .iter()
+            .filter(|(_, output)| output.source != OutputSource::ColdStorageChange)
+            .filter(|(_, output)| {
+                output.unblinded_output.features.maturity > tip || output.unblinded_output.script_lock_height > tip
+            })
+            .map(|((value, _), _)| *value)
+            .sum()
+    }
+}
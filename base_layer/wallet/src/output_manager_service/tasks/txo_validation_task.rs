@@ -25,6 +25,7 @@ use std::{
     sync::Arc,
 };
 
+use futures::TryStreamExt;
 use log::*;
 use tari_common_types::types::{BlockHash, FixedHash};
 use tari_comms::protocol::rpc::RpcError::RequestFailed;
@@ -35,15 +36,17 @@ use tari_core::{
 };
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::hex::Hex;
+use tokio::time::sleep;
 
 use crate::{
     connectivity_service::WalletConnectivityInterface,
+    error::ClassifyError,
     output_manager_service::{
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerProtocolError, OutputManagerProtocolErrorExt},
         handle::{OutputManagerEvent, OutputManagerEventSender},
         storage::{
-            database::{OutputManagerBackend, OutputManagerDatabase},
+            database::{OutputManagerBackend, OutputManagerDatabase, OutputSet, OutputStatusUpdate},
             models::DbUnblindedOutput,
         },
     },
@@ -80,7 +83,41 @@ where
         }
     }
 
-    pub async fn execute(mut self, _shutdown: ShutdownSignal) -> Result<u64, OutputManagerProtocolError> {
+    /// Runs the validation, and retries it as per the `validation_retry` config setting if it fails because the base
+    /// node could not be reached
+    pub async fn execute(mut self, mut shutdown: ShutdownSignal) -> Result<u64, OutputManagerProtocolError> {
+        let mut failed_attempts = 0;
+        loop {
+            match self.validate().await {
+                Err(e) if e.error.is_transient() => {
+                    failed_attempts += 1;
+                    if !self.config.validation_retry.should_retry(failed_attempts) {
+                        return Err(e);
+                    }
+                    let delay = self.config.validation_retry.delay(failed_attempts);
+                    warn!(
+                        target: LOG_TARGET,
+                        "TXO validation protocol failed (Id: {}): {}. Retrying in {:.1?}",
+                        self.operation_id,
+                        e.error,
+                        delay
+                    );
+                    tokio::select! {
+                        _ = sleep(delay) => {},
+                        _ = shutdown.wait() => {
+                            return Err(OutputManagerProtocolError::new(
+                                self.operation_id,
+                                OutputManagerError::Shutdown,
+                            ));
+                        },
+                    }
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn validate(&mut self) -> Result<u64, OutputManagerProtocolError> {
         let mut base_node_client = self
             .connectivity
             .obtain_base_node_wallet_rpc_client()
@@ -112,13 +149,10 @@ where
         wallet_client: &mut BaseNodeWalletRpcClient,
         last_mined_header_hash: Option<BlockHash>,
     ) -> Result<(), OutputManagerProtocolError> {
-        let mined_outputs = self.db.fetch_mined_unspent_outputs().for_protocol(self.operation_id)?;
-
-        if mined_outputs.is_empty() {
-            return Ok(());
-        }
-
-        for batch in mined_outputs.chunks(self.config.tx_validator_batch_size) {
+        let mut mined_outputs = self
+            .db
+            .fetch_outputs_stream(OutputSet::MinedUnspent, self.config.tx_validator_batch_size);
+        while let Some(batch) = mined_outputs.try_next().await.for_protocol(self.operation_id)? {
             debug!(
                 target: LOG_TARGET,
                 "Asking base node for status of {} mmr_positions (Operation ID: {})",
@@ -138,7 +172,8 @@ where
                 .await
                 .for_protocol(self.operation_id)?;
 
-            for output in batch {
+            let mut updates = Vec::new();
+            for output in &batch {
                 let mined_mmr_position = output
                     .mined_mmr_position
                     .ok_or(OutputManagerError::InconsistentDataError(
@@ -181,9 +216,12 @@ where
                     let confirmed = (deleted_bitmap_response.height_of_longest_chain - deleted_height) >=
                         self.config.num_confirmations_required;
 
-                    self.db
-                        .mark_output_as_spent(output.hash, deleted_height, deleted_block, confirmed)
-                        .for_protocol(self.operation_id)?;
+                    updates.push(OutputStatusUpdate::Spent {
+                        hash: output.hash,
+                        deleted_height,
+                        deleted_in_block: deleted_block,
+                        confirmed,
+                    });
                     info!(
                         target: LOG_TARGET,
                         "Updating output comm:{}: hash {} as spent at tip height {} (Operation ID: {})",
@@ -203,9 +241,7 @@ where
                         .for_protocol(self.operation_id)?,
                 ) && output.marked_deleted_at_height.is_some()
                 {
-                    self.db
-                        .mark_output_as_unspent(output.hash)
-                        .for_protocol(self.operation_id)?;
+                    updates.push(OutputStatusUpdate::Unspent(output.hash));
                     info!(
                         target: LOG_TARGET,
                         "Updating output comm:{}: hash {} as unspent at tip height {} (Operation ID: {})",
@@ -216,6 +252,7 @@ where
                     );
                 }
             }
+            self.db.apply_status_updates(updates).for_protocol(self.operation_id)?;
         }
         Ok(())
     }
@@ -224,9 +261,12 @@ where
         &self,
         wallet_client: &mut BaseNodeWalletRpcClient,
     ) -> Result<(), OutputManagerProtocolError> {
-        let unconfirmed_outputs = self.db.fetch_unconfirmed_outputs().for_protocol(self.operation_id)?;
+        let mut unconfirmed_outputs = self
+            .db
+            .fetch_outputs_stream(OutputSet::Unconfirmed, self.config.tx_validator_batch_size);
 
-        for batch in unconfirmed_outputs.chunks(self.config.tx_validator_batch_size) {
+        let mut last_tip_height = None;
+        while let Some(batch) = unconfirmed_outputs.try_next().await.for_protocol(self.operation_id)? {
             debug!(
                 target: LOG_TARGET,
                 "Asking base node for location of {} unconfirmed outputs by hash (Operation ID: {})",
@@ -234,7 +274,7 @@ where
                 self.operation_id
             );
             let (mined, unmined, tip_height) = self
-                .query_base_node_for_outputs(batch, wallet_client)
+                .query_base_node_for_outputs(&batch, wallet_client)
                 .await
                 .for_protocol(self.operation_id)?;
             debug!(
@@ -244,6 +284,7 @@ where
                 unmined.len(),
                 self.operation_id
             );
+            let mut updates = Vec::with_capacity(mined.len());
             for (output, mined_height, mined_in_block, mmr_position, mined_timestamp) in &mined {
                 info!(
                     target: LOG_TARGET,
@@ -254,21 +295,51 @@ where
                     tip_height,
                     self.operation_id
                 );
-                self.update_output_as_mined(
-                    output,
-                    mined_in_block,
-                    *mined_height,
-                    *mmr_position,
-                    tip_height,
-                    *mined_timestamp,
-                )
-                .await?;
+                updates.push(OutputStatusUpdate::Mined {
+                    hash: output.hash,
+                    mined_height: *mined_height,
+                    mined_in_block: *mined_in_block,
+                    mmr_position: *mmr_position,
+                    confirmed: (tip_height - mined_height) >= self.config.num_confirmations_required,
+                    mined_timestamp: *mined_timestamp,
+                });
             }
+            self.db.apply_status_updates(updates).for_protocol(self.operation_id)?;
+            last_tip_height = Some(tip_height);
+        }
+
+        if let Some(tip_height) = last_tip_height {
+            self.abandon_unmined_coinbases(tip_height)?;
         }
 
         Ok(())
     }
 
+    /// Coinbases are generated for blocks that may never be mined, and a mined coinbase can be reorged out. The
+    /// outputs of these coinbases will never be found on chain, so once the chain has grown past their height they
+    /// are marked as abandoned instead of being counted as pending forever.
+    fn abandon_unmined_coinbases(&self, tip_height: u64) -> Result<(), OutputManagerProtocolError> {
+        let max_block_height = match tip_height.checked_sub(self.config.coinbase_abandon_delay) {
+            Some(height) => height,
+            None => return Ok(()),
+        };
+        let abandoned = self
+            .db
+            .abandon_unmined_coinbases(max_block_height)
+            .for_protocol(self.operation_id)?;
+        if !abandoned.is_empty() {
+            info!(
+                target: LOG_TARGET,
+                "Marked {} coinbase(s) up to height {} that are not in the chain as abandoned: {:?} (Operation ID: {})",
+                abandoned.len(),
+                max_block_height,
+                abandoned,
+                self.operation_id
+            );
+        }
+        Ok(())
+    }
+
     // returns the last header found still in the chain
     async fn check_for_reorgs(
         &mut self,
@@ -456,32 +527,6 @@ where
         Ok((mined, unmined, batch_response.height_of_longest_chain))
     }
 
-    #[allow(clippy::ptr_arg)]
-    async fn update_output_as_mined(
-        &self,
-        tx: &DbUnblindedOutput,
-        mined_in_block: &BlockHash,
-        mined_height: u64,
-        mmr_position: u64,
-        tip_height: u64,
-        mined_timestamp: u64,
-    ) -> Result<(), OutputManagerProtocolError> {
-        let confirmed = (tip_height - mined_height) >= self.config.num_confirmations_required;
-
-        self.db
-            .set_received_output_mined_height(
-                tx.hash,
-                mined_height,
-                *mined_in_block,
-                mmr_position,
-                confirmed,
-                mined_timestamp,
-            )
-            .for_protocol(self.operation_id)?;
-
-        Ok(())
-    }
-
     fn publish_event(&self, event: OutputManagerEvent) {
         if let Err(e) = self.event_publisher.send(Arc::new(event)) {
             debug!(
@@ -0,0 +1,43 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::BTreeMap;
+
+use tari_common_types::types::{FixedHash, PublicKey};
+use tari_core::transactions::transaction_components::{TokenRegistration, TransactionError};
+
+use crate::output_manager_service::storage::models::DbUnblindedOutput;
+
+/// The balance of a fungible token held by the wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenBalance {
+    /// Identifies the token, see [TokenRegistration::token_id]
+    pub token_id: FixedHash,
+    pub symbol: String,
+    pub issuer_public_key: PublicKey,
+    /// The sum of the supplies of the registration outputs of the token that the wallet owns
+    pub balance: u64,
+}
+
+/// Sums the token balances of the registration outputs, ordered by token id. Outputs that are not registrations are
+/// ignored.
+pub fn token_balances<'a, I>(outputs: I) -> Result<Vec<TokenBalance>, TransactionError>
+where I: IntoIterator<Item = &'a DbUnblindedOutput> {
+    let mut balances = BTreeMap::<FixedHash, TokenBalance>::new();
+    for output in outputs {
+        let registration = match TokenRegistration::from_features(&output.unblinded_output.features)? {
+            Some(registration) => registration,
+            None => continue,
+        };
+        let token_id = registration.token_id();
+        let supply = registration.total_supply;
+        let token = balances.entry(token_id).or_insert_with(|| TokenBalance {
+            token_id,
+            symbol: registration.symbol,
+            issuer_public_key: registration.issuer_public_key,
+            balance: 0,
+        });
+        token.balance = token.balance.saturating_add(supply);
+    }
+    Ok(balances.into_values().collect())
+}
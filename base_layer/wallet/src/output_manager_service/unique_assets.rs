@@ -0,0 +1,13 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use tari_core::transactions::transaction_components::UniqueAsset;
+
+use crate::output_manager_service::storage::models::DbUnblindedOutput;
+
+/// A unique asset held by the wallet along with the output that holds it
+#[derive(Debug, Clone)]
+pub struct OwnedAsset {
+    pub output: DbUnblindedOutput,
+    pub asset: UniqueAsset,
+}
@@ -0,0 +1,202 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use tari_common_types::types::PublicKey;
+use tari_script::{script, Opcode, TariScript};
+
+use crate::output_manager_service::storage::models::DbUnblindedOutput;
+
+/// The spending conditions of a vault output.
+///
+/// Once the chain reaches `unlock_height`, the output can be spent with the primary key alone, like any other output
+/// of the wallet. Before that, the output can only be spent from `recovery_height` onwards with the sum of the primary
+/// key and a recovery key that is kept outside of the wallet. A thief that obtains the wallet therefore has to wait
+/// until `unlock_height`, while the owner can use the recovery key to move the funds to safety well before then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultScript {
+    /// The script key of the wallet
+    pub primary_key: PublicKey,
+    /// The sum of the primary key and the recovery key, which spends the output on the recovery path
+    pub recovery_path_key: PublicKey,
+    /// The height from which the output can be spent on the recovery path
+    pub recovery_height: u64,
+    /// The height from which the output can be spent with the primary key alone
+    pub unlock_height: u64,
+}
+
+impl VaultScript {
+    pub fn new(primary_key: PublicKey, recovery_key: &PublicKey, recovery_height: u64, unlock_height: u64) -> Self {
+        let recovery_path_key = &primary_key + recovery_key;
+        Self {
+            primary_key,
+            recovery_path_key,
+            recovery_height,
+            unlock_height,
+        }
+    }
+
+    /// The script that enforces the spending conditions. The script takes no input data and leaves the key of the
+    /// spending path on the stack.
+    pub fn to_script(&self) -> TariScript {
+        script!(
+            CheckHeight(self.unlock_height) GeZero IfThen
+                PushPubKey(Box::new(self.primary_key.clone()))
+            Else
+                CheckHeightVerify(self.recovery_height) PushPubKey(Box::new(self.recovery_path_key.clone()))
+            EndIf
+        )
+    }
+
+    /// Returns the spending conditions if the script is a vault script
+    pub fn from_script(script: &TariScript) -> Option<Self> {
+        use Opcode::{CheckHeight, CheckHeightVerify, PushPubKey};
+        let vault = match script.as_slice() {
+            [CheckHeight(unlock), _, _, PushPubKey(primary), _, CheckHeightVerify(recovery), PushPubKey(key), _] => {
+                Self {
+                    primary_key: (**primary).clone(),
+                    recovery_path_key: (**key).clone(),
+                    recovery_height: *recovery,
+                    unlock_height: *unlock,
+                }
+            },
+            _ => return None,
+        };
+        // The remaining opcodes are checked by rebuilding the script
+        if vault.to_script() == *script {
+            Some(vault)
+        } else {
+            None
+        }
+    }
+}
+
+/// The spending conditions of an expiring vault output, which pays a recipient that may be offline.
+///
+/// Until the chain reaches `expiry_height`, the output can only be spent with the key of the recipient. From then on
+/// it can only be spent with the key of the sender, who reclaims the funds if the recipient did not claim them in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiringVaultScript {
+    /// The key of the recipient, which claims the output before it expires
+    pub recipient_key: PublicKey,
+    /// The key of the sender, which reclaims the output once it has expired
+    pub refund_key: PublicKey,
+    /// The height from which the output can no longer be claimed by the recipient
+    pub expiry_height: u64,
+}
+
+impl ExpiringVaultScript {
+    pub fn new(recipient_key: PublicKey, refund_key: PublicKey, expiry_height: u64) -> Self {
+        Self {
+            recipient_key,
+            refund_key,
+            expiry_height,
+        }
+    }
+
+    /// The script that enforces the spending conditions. The script takes no input data and leaves the key of the
+    /// spending path on the stack.
+    pub fn to_script(&self) -> TariScript {
+        script!(
+            CheckHeight(self.expiry_height) LtZero IfThen
+                PushPubKey(Box::new(self.recipient_key.clone()))
+            Else
+                PushPubKey(Box::new(self.refund_key.clone()))
+            EndIf
+        )
+    }
+
+    /// Returns the spending conditions if the script is an expiring vault script
+    pub fn from_script(script: &TariScript) -> Option<Self> {
+        use Opcode::{CheckHeight, PushPubKey};
+        let vault = match script.as_slice() {
+            [CheckHeight(expiry), _, _, PushPubKey(recipient), _, PushPubKey(refund), _] => Self {
+                recipient_key: (**recipient).clone(),
+                refund_key: (**refund).clone(),
+                expiry_height: *expiry,
+            },
+            _ => return None,
+        };
+        // The remaining opcodes are checked by rebuilding the script
+        if vault.to_script() == *script {
+            Some(vault)
+        } else {
+            None
+        }
+    }
+}
+
+/// A vault output of the wallet along with its spending conditions
+#[derive(Debug, Clone)]
+pub struct VaultOutput {
+    pub output: DbUnblindedOutput,
+    pub vault: VaultScript,
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::{Commitment, PrivateKey};
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+    use tari_script::{ExecutionStack, ScriptContext, StackItem};
+
+    use super::*;
+
+    fn execute_at_height(script: &TariScript, height: u64) -> Option<PublicKey> {
+        let context = ScriptContext::new(height, &[0u8; 32], &Commitment::default());
+        match script.execute_with_context(&ExecutionStack::default(), &context) {
+            Ok(StackItem::PublicKey(key)) => Some(key),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn it_enforces_the_spending_paths() {
+        let primary_secret = PrivateKey::random(&mut OsRng);
+        let recovery_secret = PrivateKey::random(&mut OsRng);
+        let vault = VaultScript::new(
+            PublicKey::from_secret_key(&primary_secret),
+            &PublicKey::from_secret_key(&recovery_secret),
+            100,
+            1000,
+        );
+        assert_eq!(
+            vault.recovery_path_key,
+            PublicKey::from_secret_key(&(&primary_secret + &recovery_secret))
+        );
+
+        let script = vault.to_script();
+        assert!(execute_at_height(&script, 99).is_none());
+        assert_eq!(execute_at_height(&script, 100).unwrap(), vault.recovery_path_key);
+        assert_eq!(execute_at_height(&script, 999).unwrap(), vault.recovery_path_key);
+        assert_eq!(execute_at_height(&script, 1000).unwrap(), vault.primary_key);
+    }
+
+    #[test]
+    fn it_recognises_vault_scripts() {
+        let vault = VaultScript::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            &PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            100,
+            1000,
+        );
+        assert_eq!(VaultScript::from_script(&vault.to_script()).unwrap(), vault);
+        assert!(VaultScript::from_script(&script!(Nop)).is_none());
+    }
+
+    #[test]
+    fn it_expires_the_claim_of_the_recipient() {
+        let recipient_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let refund_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let vault = ExpiringVaultScript::new(recipient_key.clone(), refund_key.clone(), 100);
+
+        let script = vault.to_script();
+        assert_eq!(execute_at_height(&script, 0).unwrap(), recipient_key);
+        assert_eq!(execute_at_height(&script, 99).unwrap(), recipient_key);
+        assert_eq!(execute_at_height(&script, 100).unwrap(), refund_key);
+
+        assert_eq!(ExpiringVaultScript::from_script(&script).unwrap(), vault);
+        let pay_to_self = VaultScript::new(recipient_key, &refund_key, 100, 1000);
+        assert!(ExpiringVaultScript::from_script(&pay_to_self.to_script()).is_none());
+        assert!(VaultScript::from_script(&script).is_none());
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Integration points for wrapping the wallet database encryption key with a key held by the platform, such as the
+//! Android Keystore, the iOS Secure Enclave or Windows DPAPI, instead of deriving it from a passphrase typed by the
+//! user.
+//!
+//! The wallet generates a random passphrase, asks the [PlatformKeystore] to wrap it and stores the wrapped blob in
+//! the wallet database. The blob is useless without the platform key, so it is stored in the clear. Every time the
+//! wallet is opened the keystore unwraps the passphrase, which is then used exactly like a passphrase typed by the
+//! user, see [sqlite_utilities::read_or_create_keystore_passphrase](crate::storage::sqlite_utilities).
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use tari_utilities::{hex::Hex, SafePassword};
+use thiserror::Error;
+
+/// The number of random bytes in a passphrase generated for a platform keystore
+pub const KEYSTORE_PASSPHRASE_BYTES: usize = 32;
+
+/// Where the key that encrypts the wallet database comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptionKeySource {
+    /// The key is derived from a passphrase supplied by the user
+    Passphrase,
+    /// The key is derived from a random passphrase that is wrapped by a [PlatformKeystore]
+    PlatformKeystore,
+}
+
+impl Default for EncryptionKeySource {
+    fn default() -> Self {
+        EncryptionKeySource::Passphrase
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PlatformKeystoreError {
+    #[error("The platform keystore is not available: `{0}`")]
+    Unavailable(String),
+    #[error("The platform keystore could not wrap the key: `{0}`")]
+    WrapFailed(String),
+    #[error("The platform keystore could not unwrap the key: `{0}`")]
+    UnwrapFailed(String),
+}
+
+/// A key held by the platform that wraps the wallet database passphrase. Implementations must return the original
+/// bytes from `unwrap_key` for every blob returned by `wrap_key`, for as long as the wallet exists. If the platform
+/// key is lost, the wallet can only be restored from its seed words.
+pub trait PlatformKeystore: Send + Sync {
+    /// Encrypts `key` with the platform key
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, PlatformKeystoreError>;
+
+    /// Decrypts a blob returned by [PlatformKeystore::wrap_key]
+    fn unwrap_key(&self, wrapped_key: &[u8]) -> Result<Vec<u8>, PlatformKeystoreError>;
+}
+
+/// Generates the random key that is wrapped by the keystore
+pub(crate) fn generate_keystore_key() -> Vec<u8> {
+    let mut key = vec![0u8; KEYSTORE_PASSPHRASE_BYTES];
+    OsRng.fill_bytes(&mut key);
+    key
+}
+
+/// Turns an unwrapped key into the passphrase that encrypts the wallet database
+pub(crate) fn keystore_passphrase(key: &[u8]) -> Result<SafePassword, PlatformKeystoreError> {
+    if key.len() != KEYSTORE_PASSPHRASE_BYTES {
+        return Err(PlatformKeystoreError::UnwrapFailed(format!(
+            "Expected a {} byte key but got {} bytes",
+            KEYSTORE_PASSPHRASE_BYTES,
+            key.len()
+        )));
+    }
+    Ok(SafePassword::from(key.to_hex()))
+}
+
+#[cfg(test)]
+mod test {
+    use tari_key_manager::cipher_seed::CipherSeed;
+    use tari_test_utils::random;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        error::WalletStorageError,
+        storage::{
+            database::WalletDatabase,
+            sqlite_utilities::{initialize_sqlite_database_backends, read_or_create_keystore_passphrase},
+        },
+    };
+
+    /// A keystore that xors the key with a fixed platform key
+    struct XorKeystore(u8);
+
+    impl PlatformKeystore for XorKeystore {
+        fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, PlatformKeystoreError> {
+            Ok(key.iter().map(|b| b ^ self.0).collect())
+        }
+
+        fn unwrap_key(&self, wrapped_key: &[u8]) -> Result<Vec<u8>, PlatformKeystoreError> {
+            self.wrap_key(wrapped_key)
+        }
+    }
+
+    #[test]
+    fn it_wraps_the_passphrase_with_the_keystore() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join(format!("{}.sqlite3", random::string(8)));
+
+        let passphrase = read_or_create_keystore_passphrase(&db_path, &XorKeystore(0x5a)).unwrap();
+        assert_eq!(passphrase.reveal().len(), KEYSTORE_PASSPHRASE_BYTES * 2);
+        let again = read_or_create_keystore_passphrase(&db_path, &XorKeystore(0x5a)).unwrap();
+        assert_eq!(passphrase.reveal(), again.reveal());
+        let other = read_or_create_keystore_passphrase(&db_path, &XorKeystore(0xa5)).unwrap();
+        assert_ne!(passphrase.reveal(), other.reveal());
+
+        // A wallet encrypted with a user passphrase does not get a keystore key
+        let db_path = temp_dir.path().join(format!("{}.sqlite3", random::string(8)));
+        let (wallet_backend, ..) = initialize_sqlite_database_backends(&db_path, None, 1).unwrap();
+        let db = WalletDatabase::new(wallet_backend);
+        db.set_master_seed(CipherSeed::new()).unwrap();
+        db.apply_encryption("user passphrase".to_string().into()).unwrap();
+        drop(db);
+        assert!(matches!(
+            read_or_create_keystore_passphrase(&db_path, &XorKeystore(0x5a)),
+            Err(WalletStorageError::AlreadyEncrypted)
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! A backend-agnostic dump of the wallet history, as exported by [Wallet::export_data](crate::Wallet::export_data).
+//! It holds the contacts, transactions and transaction tags of a wallet in a versioned JSON layout, so they can be
+//! moved to a wallet with another storage backend or inspected with external tooling.
+//!
+//! Outputs and keys are not part of the dump. They are derived from the master seed, so the wallet that imports a dump
+//! recovers them by scanning the chain.
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tari_comms::types::CommsPublicKey;
+use tari_utilities::ByteArray;
+
+use crate::{
+    contacts_service::storage::database::Contact,
+    error::WalletError,
+    network_state::set_owner_only_permissions,
+    transaction_service::storage::models::{CompletedTransaction, InboundTransaction, OutboundTransaction},
+};
+
+/// The version of the portable dump format
+const PORTABLE_DUMP_VERSION: u32 = 1;
+
+/// A contact without its liveness data, which is only meaningful to the wallet that measured it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortableContact {
+    pub alias: String,
+    pub public_key: CommsPublicKey,
+    pub last_seen: Option<NaiveDateTime>,
+}
+
+impl From<Contact> for PortableContact {
+    fn from(contact: Contact) -> Self {
+        Self {
+            alias: contact.alias,
+            public_key: contact.public_key,
+            last_seen: contact.last_seen,
+        }
+    }
+}
+
+impl From<PortableContact> for Contact {
+    fn from(contact: PortableContact) -> Self {
+        Contact::new(contact.alias, contact.public_key, contact.last_seen, None)
+    }
+}
+
+/// The contacts, transactions and transaction tags of a wallet. Every list is sorted, so exporting the same wallet
+/// twice produces the same file. Pending transactions contain the state of their transaction protocol, including
+/// private keys, so the exported file must be kept private.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableWalletDump {
+    version: u32,
+    pub contacts: Vec<PortableContact>,
+    pub completed_transactions: Vec<CompletedTransaction>,
+    pub inbound_transactions: Vec<InboundTransaction>,
+    pub outbound_transactions: Vec<OutboundTransaction>,
+    /// The tags of every tagged transaction, keyed by transaction id
+    pub transaction_tags: BTreeMap<u64, Vec<String>>,
+}
+
+impl PortableWalletDump {
+    pub fn new(
+        mut contacts: Vec<PortableContact>,
+        mut completed_transactions: Vec<CompletedTransaction>,
+        mut inbound_transactions: Vec<InboundTransaction>,
+        mut outbound_transactions: Vec<OutboundTransaction>,
+        transaction_tags: BTreeMap<u64, Vec<String>>,
+    ) -> Self {
+        contacts.sort_by(|a, b| a.public_key.as_bytes().cmp(b.public_key.as_bytes()));
+        completed_transactions.sort_by_key(|tx| tx.tx_id.as_u64());
+        inbound_transactions.sort_by_key(|tx| tx.tx_id.as_u64());
+        outbound_transactions.sort_by_key(|tx| tx.tx_id.as_u64());
+        let transaction_tags = transaction_tags
+            .into_iter()
+            .map(|(tx_id, mut tags)| {
+                tags.sort();
+                (tx_id, tags)
+            })
+            .collect();
+        Self {
+            version: PORTABLE_DUMP_VERSION,
+            contacts,
+            completed_transactions,
+            inbound_transactions,
+            outbound_transactions,
+            transaction_tags,
+        }
+    }
+
+    pub fn num_transactions(&self) -> usize {
+        self.completed_transactions.len() + self.inbound_transactions.len() + self.outbound_transactions.len()
+    }
+
+    pub fn to_json(&self) -> Result<String, WalletError> {
+        serde_json::to_string_pretty(self).map_err(|e| WalletError::PortableDumpError(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, WalletError> {
+        let dump = serde_json::from_str::<Self>(json).map_err(|e| WalletError::PortableDumpError(e.to_string()))?;
+        if dump.version != PORTABLE_DUMP_VERSION {
+            return Err(WalletError::PortableDumpError(format!(
+                "Unsupported dump version {}",
+                dump.version
+            )));
+        }
+        Ok(dump)
+    }
+
+    /// Writes the dump to `path` as JSON. On unix, the file is only readable by its owner.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletError> {
+        let json = self.to_json()?;
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(path.as_ref(), json).map_err(io_error)?;
+        set_owner_only_permissions(path.as_ref()).map_err(io_error)?;
+        Ok(())
+    }
+
+    /// Reads a dump written by [PortableWalletDump::write_to_file]
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, WalletError> {
+        let json = fs::read_to_string(path.as_ref()).map_err(io_error)?;
+        Self::from_json(&json)
+    }
+}
+
+/// The number of records that [Wallet::import_data](crate::Wallet::import_data) added to the wallet. Records that
+/// already existed are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PortableImportSummary {
+    pub contacts: usize,
+    pub transactions: usize,
+    pub transaction_tags: usize,
+}
+
+fn io_error(err: io::Error) -> WalletError {
+    WalletError::PortableDumpError(err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::transaction::TxId;
+    use tari_crypto::keys::PublicKey;
+
+    use super::*;
+
+    fn contact(alias: &str) -> PortableContact {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        PortableContact {
+            alias: alias.to_string(),
+            public_key,
+            last_seen: None,
+        }
+    }
+
+    #[test]
+    fn it_is_deterministic() {
+        let contacts = vec![contact("alice"), contact("bob"), contact("carol")];
+        let mut tags = BTreeMap::new();
+        tags.insert(TxId::from(7u64).as_u64(), vec!["rent".to_string(), "home".to_string()]);
+
+        let dump = PortableWalletDump::new(contacts.clone(), vec![], vec![], vec![], tags.clone());
+        let reversed = PortableWalletDump::new(contacts.into_iter().rev().collect(), vec![], vec![], vec![], tags);
+        assert_eq!(dump.to_json().unwrap(), reversed.to_json().unwrap());
+        assert_eq!(dump.transaction_tags[&7], vec!["home".to_string(), "rent".to_string()]);
+
+        let restored = PortableWalletDump::from_json(&dump.to_json().unwrap()).unwrap();
+        assert_eq!(restored.contacts, dump.contacts);
+        assert_eq!(restored.transaction_tags, dump.transaction_tags);
+    }
+
+    #[test]
+    fn it_rejects_unknown_versions() {
+        let mut dump = PortableWalletDump::new(vec![contact("alice")], vec![], vec![], vec![], BTreeMap::new());
+        dump.version = PORTABLE_DUMP_VERSION + 1;
+        assert!(matches!(
+            PortableWalletDump::from_json(&dump.to_json().unwrap()),
+            Err(WalletError::PortableDumpError(_))
+        ));
+    }
+}
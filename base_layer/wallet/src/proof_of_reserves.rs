@@ -0,0 +1,302 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Proof-of-reserves attestations, as produced by
+//! [Wallet::generate_reserve_attestation](crate::Wallet::generate_reserve_attestation). An attestation states the total
+//! value of the unspent outputs that the wallet controls at a block height, in answer to a challenge chosen by the
+//! auditor so that it cannot be prepared in advance.
+//!
+//! Every output comes with an ownership proof: a signature made with the blinding factor `k` of its commitment
+//! `C = k⋅G + v⋅H`, which verifies under the public key `C - v⋅H` and so proves knowledge of `k` and the value `v`
+//! without revealing `k`. The statement as a whole is signed with the identity key of the wallet.
+//!
+//! [ReserveAttestation::verify] checks the proofs and the total. A verifier must also check that every commitment is
+//! in the UTXO set of the chain at the attested height, which needs a base node and is left to the verifier.
+
+use digest::Digest;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_common_types::types::{BlockHash, Commitment, CommitmentFactory, PrivateKey, PublicKey, Signature};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+};
+use tari_utilities::ByteArray;
+use thiserror::Error;
+
+use crate::types::WalletHasher;
+
+/// Proves that the wallet knows the blinding factor of an unspent output of the given value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputOwnershipProof {
+    pub commitment: Commitment,
+    pub value: MicroTari,
+    /// Made with the blinding factor over the output challenge of the attestation, verifies under `commitment - v⋅H`
+    pub signature: Signature,
+}
+
+/// A signed statement of the total value that a wallet controls at a block height
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReserveAttestation {
+    pub network: Network,
+    /// The challenge of the auditor that the attestation answers
+    pub challenge: String,
+    pub height: u64,
+    pub block_hash: BlockHash,
+    /// The sum of the values of the outputs
+    pub total: MicroTari,
+    pub outputs: Vec<OutputOwnershipProof>,
+    /// The identity public key of the wallet that signed the statement
+    pub public_key: PublicKey,
+    /// The signature of the statement challenge with the identity key
+    pub signature: Signature,
+}
+
+/// The fields of an attestation that are signed, before the signatures are made
+pub(crate) struct ReserveStatement {
+    pub network: Network,
+    pub challenge: String,
+    pub height: u64,
+    pub block_hash: BlockHash,
+}
+
+impl ReserveStatement {
+    /// Signs the statement with the identity key of the wallet, and each output with its blinding factor
+    pub fn sign(
+        self,
+        identity_secret_key: PrivateKey,
+        outputs: Vec<(Commitment, MicroTari, PrivateKey)>,
+    ) -> Result<ReserveAttestation, ReserveAttestationError> {
+        let mut attestation = ReserveAttestation {
+            network: self.network,
+            challenge: self.challenge,
+            height: self.height,
+            block_hash: self.block_hash,
+            total: MicroTari::from(0),
+            outputs: Vec::with_capacity(outputs.len()),
+            public_key: PublicKey::from_secret_key(&identity_secret_key),
+            signature: Signature::default(),
+        };
+        for (commitment, value, blinding_factor) in outputs {
+            let (nonce, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+            let blinding_public_key = PublicKey::from_secret_key(&blinding_factor);
+            let challenge = attestation.output_challenge(&commitment, value, &blinding_public_key, &public_nonce);
+            let signature = Signature::sign(blinding_factor, nonce, &challenge)
+                .map_err(|e| ReserveAttestationError::SigningError(e.to_string()))?;
+            attestation.total += value;
+            attestation.outputs.push(OutputOwnershipProof {
+                commitment,
+                value,
+                signature,
+            });
+        }
+        let (nonce, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+        let challenge = attestation.statement_challenge(&public_nonce);
+        attestation.signature = Signature::sign(identity_secret_key, nonce, &challenge)
+            .map_err(|e| ReserveAttestationError::SigningError(e.to_string()))?;
+        Ok(attestation)
+    }
+}
+
+impl ReserveAttestation {
+    /// Checks the ownership proof of every output, the total and the signature of the statement. This does not check
+    /// that the outputs exist on chain.
+    pub fn verify(&self, factory: &CommitmentFactory) -> Result<(), ReserveAttestationError> {
+        let mut total = MicroTari::from(0);
+        for output in &self.outputs {
+            let value_commitment = factory.commit_value(&PrivateKey::default(), output.value.as_u64());
+            let blinding_public_key = (&output.commitment - &value_commitment).as_public_key().clone();
+            let challenge = self.output_challenge(
+                &output.commitment,
+                output.value,
+                &blinding_public_key,
+                output.signature.get_public_nonce(),
+            );
+            if !output.signature.verify_challenge(&blinding_public_key, &challenge) {
+                return Err(ReserveAttestationError::InvalidOwnershipProof(
+                    output.commitment.clone(),
+                ));
+            }
+            total += output.value;
+        }
+        if total != self.total {
+            return Err(ReserveAttestationError::TotalMismatch {
+                stated: self.total,
+                actual: total,
+            });
+        }
+        if !self.signature.verify_challenge(
+            &self.public_key,
+            &self.statement_challenge(self.signature.get_public_nonce()),
+        ) {
+            return Err(ReserveAttestationError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// The challenge that the blinding factor of an output signs. It commits to the public key `C - v⋅H` and the public
+    /// nonce of the signature, so that a signature cannot be solved for without the key, and to the challenge of the
+    /// auditor and the height, so that a proof cannot be reused in another attestation.
+    fn output_challenge(
+        &self,
+        commitment: &Commitment,
+        value: MicroTari,
+        blinding_public_key: &PublicKey,
+        public_nonce: &PublicKey,
+    ) -> Vec<u8> {
+        WalletHasher::new_with_label("reserve_output")
+            .chain(self.network.as_key_str().as_bytes())
+            .chain((self.challenge.len() as u64).to_le_bytes())
+            .chain(self.challenge.as_bytes())
+            .chain(self.height.to_le_bytes())
+            .chain(self.block_hash.as_slice())
+            .chain(commitment.as_bytes())
+            .chain(value.as_u64().to_le_bytes())
+            .chain(blinding_public_key.as_bytes())
+            .chain(public_nonce.as_bytes())
+            .finalize()
+            .as_ref()
+            .to_vec()
+    }
+
+    /// The challenge that the identity key signs, over everything else in the attestation and the public nonce of the
+    /// signature
+    fn statement_challenge(&self, public_nonce: &PublicKey) -> Vec<u8> {
+        let mut hasher = WalletHasher::new_with_label("reserve_attestation")
+            .chain(self.network.as_key_str().as_bytes())
+            .chain((self.challenge.len() as u64).to_le_bytes())
+            .chain(self.challenge.as_bytes())
+            .chain(self.height.to_le_bytes())
+            .chain(self.block_hash.as_slice())
+            .chain(self.total.as_u64().to_le_bytes())
+            .chain((self.outputs.len() as u64).to_le_bytes());
+        for output in &self.outputs {
+            hasher = hasher
+                .chain(output.commitment.as_bytes())
+                .chain(output.value.as_u64().to_le_bytes())
+                .chain(output.signature.get_public_nonce().as_bytes())
+                .chain(output.signature.get_signature().as_bytes());
+        }
+        hasher
+            .chain(self.public_key.as_bytes())
+            .chain(public_nonce.as_bytes())
+            .finalize()
+            .as_ref()
+            .to_vec()
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ReserveAttestationError {
+    #[error("Could not sign the attestation: {0}")]
+    SigningError(String),
+    #[error("The ownership proof of output {0:?} is invalid")]
+    InvalidOwnershipProof(Commitment),
+    #[error("The attestation states a total of {stated}, but its outputs add up to {actual}")]
+    TotalMismatch { stated: MicroTari, actual: MicroTari },
+    #[error("The signature of the attestation is invalid")]
+    InvalidSignature,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn attestation(factory: &CommitmentFactory) -> ReserveAttestation {
+        let outputs = [1_000u64, 250_000]
+            .iter()
+            .map(|value| {
+                let blinding_factor = PrivateKey::random(&mut OsRng);
+                let commitment = factory.commit_value(&blinding_factor, *value);
+                (commitment, MicroTari::from(*value), blinding_factor)
+            })
+            .collect();
+        let statement = ReserveStatement {
+            network: Network::LocalNet,
+            challenge: "audit 2022-Q4".to_string(),
+            height: 1000,
+            block_hash: BlockHash::from([1u8; 32]),
+        };
+        statement.sign(PrivateKey::random(&mut OsRng), outputs).unwrap()
+    }
+
+    #[test]
+    fn it_verifies_an_attestation() {
+        let factory = CommitmentFactory::default();
+        let attestation = attestation(&factory);
+        assert_eq!(attestation.total, MicroTari::from(251_000));
+        assert_eq!(attestation.verify(&factory), Ok(()));
+
+        let json = serde_json::to_string(&attestation).unwrap();
+        let parsed = serde_json::from_str::<ReserveAttestation>(&json).unwrap();
+        assert_eq!(parsed.verify(&factory), Ok(()));
+    }
+
+    #[test]
+    fn it_rejects_altered_attestations() {
+        let factory = CommitmentFactory::default();
+        let attestation = attestation(&factory);
+
+        let mut altered = attestation.clone();
+        altered.outputs[0].value = MicroTari::from(2_000);
+        altered.total = MicroTari::from(252_000);
+        assert!(matches!(
+            altered.verify(&factory),
+            Err(ReserveAttestationError::InvalidOwnershipProof(_))
+        ));
+
+        let mut altered = attestation.clone();
+        altered.total = MicroTari::from(1_000_000);
+        assert!(matches!(
+            altered.verify(&factory),
+            Err(ReserveAttestationError::TotalMismatch { .. })
+        ));
+
+        let mut altered = attestation.clone();
+        altered.challenge = "audit 2022-Q3".to_string();
+        assert!(matches!(
+            altered.verify(&factory),
+            Err(ReserveAttestationError::InvalidOwnershipProof(_))
+        ));
+
+        let mut altered = attestation;
+        altered.outputs.pop();
+        altered.total = MicroTari::from(1_000);
+        assert_eq!(altered.verify(&factory), Err(ReserveAttestationError::InvalidSignature));
+    }
+
+    #[test]
+    fn it_rejects_signatures_with_a_made_up_nonce() {
+        let factory = CommitmentFactory::default();
+        let attestation = attestation(&factory);
+        // Anyone can pick `s` and solve `s⋅G = R + e⋅P` for `R`, but `R` then differs from the nonce that went into `e`
+        let forge = |public_key: &PublicKey, challenge: Vec<u8>| {
+            let s = PrivateKey::random(&mut OsRng);
+            let e = PrivateKey::from_bytes(&challenge).unwrap();
+            Signature::new(PublicKey::from_secret_key(&s) - &e * public_key, s)
+        };
+        let made_up_nonce = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+
+        let mut forged = attestation.clone();
+        let value = MicroTari::from(1_000_000);
+        let commitment = forged.outputs[0].commitment.clone();
+        let value_commitment = factory.commit_value(&PrivateKey::default(), value.as_u64());
+        let blinding_public_key = (&commitment - &value_commitment).as_public_key().clone();
+        let challenge = forged.output_challenge(&commitment, value, &blinding_public_key, &made_up_nonce);
+        forged.outputs[0].value = value;
+        forged.outputs[0].signature = forge(&blinding_public_key, challenge);
+        forged.total = value + forged.outputs[1].value;
+        assert_eq!(
+            forged.verify(&factory),
+            Err(ReserveAttestationError::InvalidOwnershipProof(commitment))
+        );
+
+        let mut forged = attestation;
+        forged.public_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let challenge = forged.statement_challenge(&made_up_nonce);
+        forged.signature = forge(&forged.public_key, challenge);
+        assert_eq!(forged.verify(&factory), Err(ReserveAttestationError::InvalidSignature));
+    }
+}
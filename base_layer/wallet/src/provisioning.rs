@@ -0,0 +1,102 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Creates the database of a new wallet in a single call, without starting any of the wallet services or the
+//! network, e.g. to provision many wallets for tests or for the accounts of a custodian. The wallet is then started as
+//! usual with the database, and derives the same node identity from its seed.
+
+use std::{fs, path::Path, sync::Arc};
+
+use log::*;
+use tari_common::configuration::Network;
+use tari_comms::{multiaddr::Multiaddr, peer_manager::PeerFeatures, NodeIdentity};
+use tari_key_manager::mnemonic::{Mnemonic, MnemonicLanguage};
+use tari_utilities::SafePassword;
+
+use crate::{
+    error::{WalletError, WalletStorageError},
+    key_manager_service::{storage::database::KeyManagerDatabase, KeyManagerServiceError},
+    output_manager_service::{error::OutputManagerError, storage::database::OutputManagerDatabase},
+    storage::{database::WalletDatabase, sqlite_utilities::initialize_sqlite_database_backends},
+    transaction_service::{error::TransactionServiceError, storage::database::TransactionDatabase},
+    wallet::{check_database_network, derive_comms_secret_key, read_or_create_master_seed},
+    WalletSqlite,
+};
+
+const LOG_TARGET: &str = "wallet::provisioning";
+
+/// The database is only used by one thread while it is created
+const PROVISIONING_POOL_SIZE: usize = 1;
+
+/// What the owner of a new wallet needs to keep, as created by [Wallet::initialize_new](crate::Wallet::initialize_new)
+pub struct NewWallet {
+    /// The English seed words, from which the wallet can be recovered
+    pub seed_words: Vec<String>,
+    /// The node identity that the wallet derives from its seed, without a public address
+    pub node_identity: Arc<NodeIdentity>,
+    /// The birthday of the seed, in days since the epoch of the cipher seed
+    pub birthday: u16,
+}
+
+impl WalletSqlite {
+    /// Creates the database of a new wallet for `network` at `db_path`: generates the seed, records its birthday and
+    /// the node identity, and encrypts the database if a passphrase is given. Fails if a database already exists at
+    /// the path rather than reusing its seed.
+    pub fn initialize_new<P: AsRef<Path>>(
+        db_path: P,
+        passphrase: Option<SafePassword>,
+        network: Network,
+    ) -> Result<NewWallet, WalletError> {
+        let db_path = db_path.as_ref();
+        if db_path.exists() {
+            return Err(WalletError::ArgumentError {
+                argument: "db_path".to_string(),
+                value: db_path.to_string_lossy().to_string(),
+                message: "A wallet database already exists at the path".to_string(),
+            });
+        }
+        let parent = db_path.parent().ok_or(WalletStorageError::DatabasePathIsRootPath)?;
+        fs::create_dir_all(parent).map_err(WalletStorageError::from)?;
+
+        let (wallet_backend, transaction_backend, output_manager_backend, _contacts_backend, key_manager_backend) =
+            initialize_sqlite_database_backends(db_path, None, PROVISIONING_POOL_SIZE)?;
+        let wallet_db = WalletDatabase::new(wallet_backend);
+        check_database_network(&wallet_db, network)?;
+        let master_seed = read_or_create_master_seed(None, &wallet_db)?;
+
+        let node_identity = Arc::new(NodeIdentity::new(
+            derive_comms_secret_key(&master_seed)?,
+            Multiaddr::empty(),
+            PeerFeatures::COMMUNICATION_CLIENT,
+        ));
+        if let Some(signature) = node_identity.identity_signature_read().as_ref() {
+            wallet_db.set_comms_identity_signature(signature.clone())?;
+        }
+
+        if let Some(passphrase) = passphrase {
+            let cipher = wallet_db.apply_encryption(passphrase)?;
+            OutputManagerDatabase::new(output_manager_backend)
+                .apply_encryption(cipher.clone())
+                .map_err(OutputManagerError::from)?;
+            TransactionDatabase::new(transaction_backend)
+                .apply_encryption(cipher.clone())
+                .map_err(TransactionServiceError::from)?;
+            KeyManagerDatabase::new(key_manager_backend)
+                .apply_encryption(cipher)
+                .map_err(KeyManagerServiceError::from)?;
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Created a new {} wallet with node id {} at {}",
+            network,
+            node_identity.node_id(),
+            db_path.display()
+        );
+        Ok(NewWallet {
+            seed_words: master_seed.to_mnemonic(MnemonicLanguage::English, None)?,
+            node_identity,
+            birthday: master_seed.birthday(),
+        })
+    }
+}
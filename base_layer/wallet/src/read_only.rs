@@ -0,0 +1,144 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Read-only access to the databases of a wallet, e.g. for reporting jobs that query the history and balance of a
+//! wallet that is running in another process. The databases are opened with connections that refuse all writes, and
+//! none of the wallet services are started, so a [ReadOnlyWallet] can neither sign nor talk to the network.
+
+use std::{collections::HashMap, path::Path};
+
+use tari_common::configuration::Network;
+use tari_common_types::{chain_metadata::ChainMetadata, transaction::TxId};
+use tari_core::transactions::transaction_components::UnblindedOutput;
+use tari_utilities::SafePassword;
+
+use crate::{
+    contacts_service::{
+        error::ContactsServiceError,
+        storage::{
+            database::{Contact, ContactsDatabase},
+            sqlite_db::ContactsServiceSqliteDatabase,
+        },
+    },
+    error::WalletError,
+    output_manager_service::{
+        error::OutputManagerError,
+        service::Balance,
+        storage::{database::OutputManagerDatabase, sqlite_db::OutputManagerSqliteDatabase},
+    },
+    storage::{
+        database::WalletDatabase,
+        sqlite_db::wallet::WalletSqliteDatabase,
+        sqlite_utilities::create_read_only_sqlite_connection,
+    },
+    transaction_service::{
+        error::TransactionServiceError,
+        storage::{
+            database::TransactionDatabase,
+            models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
+            sqlite_db::TransactionServiceSqliteDatabase,
+        },
+    },
+    WalletSqlite,
+};
+
+/// The number of connections to the database of a read-only wallet
+const READ_ONLY_POOL_SIZE: usize = 4;
+
+impl WalletSqlite {
+    /// Opens the database of a wallet for reading, without starting any of the wallet services. The database is not
+    /// locked, so it can be opened while the wallet that owns it is running. The passphrase of an encrypted database
+    /// is needed to read its encrypted fields.
+    pub fn open_read_only<P: AsRef<Path>>(
+        db_path: P,
+        passphrase: Option<SafePassword>,
+    ) -> Result<ReadOnlyWallet, WalletError> {
+        let connection = create_read_only_sqlite_connection(db_path, READ_ONLY_POOL_SIZE)?;
+        let wallet_backend = WalletSqliteDatabase::new(connection.clone(), passphrase)?;
+        let cipher = wallet_backend.cipher();
+        Ok(ReadOnlyWallet {
+            db: WalletDatabase::new(wallet_backend),
+            transaction_db: TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+                connection.clone(),
+                cipher.clone(),
+            )),
+            output_db: OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection.clone(), cipher)),
+            contacts_db: ContactsDatabase::new(ContactsServiceSqliteDatabase::new(connection)),
+        })
+    }
+}
+
+/// The databases of a wallet, opened with [Wallet::open_read_only](crate::Wallet::open_read_only)
+pub struct ReadOnlyWallet {
+    db: WalletDatabase<WalletSqliteDatabase>,
+    transaction_db: TransactionDatabase<TransactionServiceSqliteDatabase>,
+    output_db: OutputManagerDatabase<OutputManagerSqliteDatabase>,
+    contacts_db: ContactsDatabase<ContactsServiceSqliteDatabase>,
+}
+
+impl ReadOnlyWallet {
+    /// The network that the wallet database belongs to
+    pub fn network(&self) -> Result<Option<Network>, WalletError> {
+        Ok(self.db.get_network()?)
+    }
+
+    /// The chain metadata of the base node that the wallet last synced with
+    pub fn chain_metadata(&self) -> Result<Option<ChainMetadata>, WalletError> {
+        Ok(self.db.get_chain_metadata()?)
+    }
+
+    /// The balance of the wallet, with time locks evaluated at the tip height that the wallet last saw
+    pub fn get_balance(&self) -> Result<Balance, WalletError> {
+        let tip = self.chain_metadata()?.map(|m| m.height_of_longest_chain());
+        let balance = self.output_db.get_balance(tip).map_err(OutputManagerError::from)?;
+        Ok(balance)
+    }
+
+    pub fn get_unspent_outputs(&self) -> Result<Vec<UnblindedOutput>, WalletError> {
+        let outputs = self
+            .output_db
+            .fetch_sorted_unspent_outputs()
+            .map_err(OutputManagerError::from)?;
+        Ok(outputs.into_iter().map(|o| o.unblinded_output).collect())
+    }
+
+    pub fn get_completed_transactions(&self) -> Result<HashMap<TxId, CompletedTransaction>, WalletError> {
+        Ok(self
+            .transaction_db
+            .get_completed_transactions()
+            .map_err(TransactionServiceError::from)?)
+    }
+
+    pub fn get_pending_inbound_transactions(&self) -> Result<HashMap<TxId, InboundTransaction>, WalletError> {
+        Ok(self
+            .transaction_db
+            .get_pending_inbound_transactions()
+            .map_err(TransactionServiceError::from)?)
+    }
+
+    pub fn get_pending_outbound_transactions(&self) -> Result<HashMap<TxId, OutboundTransaction>, WalletError> {
+        Ok(self
+            .transaction_db
+            .get_pending_outbound_transactions()
+            .map_err(TransactionServiceError::from)?)
+    }
+
+    /// Looks up a transaction in any state
+    pub fn get_any_transaction(&self, tx_id: TxId) -> Result<Option<WalletTransaction>, WalletError> {
+        Ok(self
+            .transaction_db
+            .get_any_transaction(tx_id)
+            .map_err(TransactionServiceError::from)?)
+    }
+
+    pub fn get_all_transaction_tags(&self) -> Result<HashMap<TxId, Vec<String>>, WalletError> {
+        Ok(self
+            .transaction_db
+            .get_all_transaction_tags()
+            .map_err(TransactionServiceError::from)?)
+    }
+
+    pub fn get_contacts(&self) -> Result<Vec<Contact>, WalletError> {
+        Ok(self.contacts_db.get_contacts().map_err(ContactsServiceError::from)?)
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use chacha20poly1305::XChaCha20Poly1305;
+use tari_common_types::types::{ComSignature, PublicKey, Signature};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_script::{ExecutionStack, TariScript};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use super::{
+    error::RemoteSignerError,
+    protocol::{
+        cipher_from_shared_key,
+        read_frame,
+        read_sealed,
+        write_sealed,
+        Envelope,
+        KeyId,
+        SignerHello,
+        SignerRequest,
+        SignerResponse,
+        REMOTE_SIGNER_PROTOCOL_VERSION,
+        REQUEST_DOMAIN,
+        RESPONSE_DOMAIN,
+        SESSION_ID_BYTES,
+    },
+};
+
+/// The front-end side of the remote signer protocol. Requests are sent one at a time and each call waits for the
+/// response to its request.
+pub struct RemoteSignerClient<S> {
+    stream: S,
+    cipher: XChaCha20Poly1305,
+    session_id: [u8; SESSION_ID_BYTES],
+    next_request_id: u64,
+}
+
+impl RemoteSignerClient<TcpStream> {
+    pub async fn connect<A: ToSocketAddrs>(address: A, shared_key: &[u8]) -> Result<Self, RemoteSignerError> {
+        let stream = TcpStream::connect(address).await?;
+        Self::new(stream, shared_key).await
+    }
+}
+
+impl<S> RemoteSignerClient<S>
+where S: AsyncRead + AsyncWrite + Unpin
+{
+    /// Starts a session over an established connection by reading the greeting of the signer
+    pub async fn new(mut stream: S, shared_key: &[u8]) -> Result<Self, RemoteSignerError> {
+        let cipher = cipher_from_shared_key(shared_key)?;
+        let hello: SignerHello = bincode::deserialize(&read_frame(&mut stream).await?)?;
+        if hello.version != REMOTE_SIGNER_PROTOCOL_VERSION {
+            return Err(RemoteSignerError::UnsupportedVersion(hello.version));
+        }
+        Ok(Self {
+            stream,
+            cipher,
+            session_id: hello.session_id,
+            next_request_id: 1,
+        })
+    }
+
+    /// Sends a request and returns the response of the signer. A response that the signer marked as an error is
+    /// returned as [RemoteSignerError::Refused].
+    pub async fn call(&mut self, request: SignerRequest) -> Result<SignerResponse, RemoteSignerError> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let envelope = Envelope {
+            session_id: self.session_id,
+            request_id,
+            body: request,
+        };
+        write_sealed(&mut self.stream, &self.cipher, REQUEST_DOMAIN, &envelope).await?;
+
+        let response: Envelope<SignerResponse> = read_sealed(&mut self.stream, &self.cipher, RESPONSE_DOMAIN).await?;
+        if response.session_id != self.session_id || response.request_id != request_id {
+            return Err(RemoteSignerError::UnexpectedFrame);
+        }
+        match response.body {
+            SignerResponse::Error(e) => Err(RemoteSignerError::Refused(e)),
+            body => Ok(body),
+        }
+    }
+
+    pub async fn get_public_key(&mut self, key: KeyId) -> Result<PublicKey, RemoteSignerError> {
+        match self.call(SignerRequest::GetPublicKey(key)).await? {
+            SignerResponse::PublicKey(public_key) => Ok(public_key),
+            _ => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+
+    /// Returns the id and public nonce of a new single use kernel nonce
+    pub async fn create_nonce(&mut self) -> Result<(u64, PublicKey), RemoteSignerError> {
+        match self.call(SignerRequest::CreateNonce).await? {
+            SignerResponse::Nonce { nonce_id, public_nonce } => Ok((nonce_id, public_nonce)),
+            _ => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+
+    pub async fn sign_kernel(
+        &mut self,
+        excess_key: KeyId,
+        nonce_id: u64,
+        challenge: Vec<u8>,
+    ) -> Result<Signature, RemoteSignerError> {
+        let request = SignerRequest::SignKernel {
+            excess_key,
+            nonce_id,
+            challenge,
+        };
+        match self.call(request).await? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            _ => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+
+    pub async fn sign_script(
+        &mut self,
+        spending_key: KeyId,
+        script_key: KeyId,
+        value: MicroTari,
+        script: TariScript,
+        input_data: ExecutionStack,
+    ) -> Result<ComSignature, RemoteSignerError> {
+        let request = SignerRequest::SignScript {
+            spending_key,
+            script_key,
+            value,
+            script,
+            input_data,
+        };
+        match self.call(request).await? {
+            SignerResponse::ScriptSignature(signature) => Ok(signature),
+            _ => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+
+    pub async fn sign_message(&mut self, key: KeyId, message: String) -> Result<Signature, RemoteSignerError> {
+        match self.call(SignerRequest::SignMessage { key, message }).await? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            _ => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use digest::Digest;
+    use tari_crypto::hash::blake2::Blake256;
+    use tari_key_manager::cipher_seed::CipherSeed;
+    use tokio::io::{duplex, DuplexStream};
+
+    use super::*;
+    use crate::remote_signer::signer::RemoteSigner;
+
+    const SHARED_KEY: [u8; 32] = [7u8; 32];
+
+    async fn connect(
+        signer: &Arc<RemoteSigner>,
+        shared_key: &[u8],
+    ) -> Result<RemoteSignerClient<DuplexStream>, RemoteSignerError> {
+        let (client_stream, signer_stream) = duplex(4096);
+        let signer = signer.clone();
+        tokio::spawn(async move {
+            let _ = signer.serve_connection(signer_stream).await;
+        });
+        RemoteSignerClient::new(client_stream, shared_key).await
+    }
+
+    #[tokio::test]
+    async fn it_signs_messages_and_kernels() {
+        let signer = Arc::new(RemoteSigner::new(CipherSeed::new(), &SHARED_KEY).unwrap());
+        let mut client = connect(&signer, &SHARED_KEY).await.unwrap();
+        let key = KeyId::new("", 3);
+        let public_key = client.get_public_key(key.clone()).await.unwrap();
+
+        let message = "Remote signing".to_string();
+        let signature = client.sign_message(key.clone(), message.clone()).await.unwrap();
+        assert!(signature.verify_challenge(&public_key, Blake256::digest(message.as_bytes()).as_slice()));
+
+        let (nonce_id, public_nonce) = client.create_nonce().await.unwrap();
+        let challenge = vec![1u8; 32];
+        let signature = client
+            .sign_kernel(key.clone(), nonce_id, challenge.clone())
+            .await
+            .unwrap();
+        assert_eq!(signature.get_public_nonce(), &public_nonce);
+        assert!(signature.verify_challenge(&public_key, &challenge));
+
+        // A nonce can only be used once
+        let err = client.sign_kernel(key, nonce_id, challenge).await.unwrap_err();
+        assert!(matches!(err, RemoteSignerError::Refused(_)));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_a_wrong_shared_key() {
+        let signer = Arc::new(RemoteSigner::new(CipherSeed::new(), &SHARED_KEY).unwrap());
+        let mut client = connect(&signer, &[8u8; 32]).await.unwrap();
+        assert!(client.get_public_key(KeyId::new("", 0)).await.is_err());
+
+        assert!(matches!(
+            RemoteSigner::new(CipherSeed::new(), &[1u8; 16]),
+            Err(RemoteSignerError::InvalidSharedKey(32))
+        ));
+    }
+}
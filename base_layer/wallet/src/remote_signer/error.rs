@@ -0,0 +1,28 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RemoteSignerError {
+    #[error("IO error: `{0}`")]
+    IoError(#[from] std::io::Error),
+    #[error("Serialization error: `{0}`")]
+    SerializationError(#[from] bincode::Error),
+    #[error("The shared key must be {0} bytes long")]
+    InvalidSharedKey(usize),
+    #[error("Encryption error: `{0}`")]
+    EncryptionError(String),
+    #[error("A frame could not be authenticated with the shared key")]
+    AuthenticationFailed,
+    #[error("A frame of {0} bytes exceeds the maximum frame size")]
+    FrameTooLarge(usize),
+    #[error("Unsupported remote signer protocol version {0}")]
+    UnsupportedVersion(u8),
+    #[error("A frame does not belong to the current session or request")]
+    UnexpectedFrame,
+    #[error("Unexpected response from the remote signer")]
+    UnexpectedResponse,
+    #[error("The remote signer refused the request: `{0}`")]
+    Refused(String),
+}
@@ -0,0 +1,19 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! A protocol that splits the wallet into a network facing front-end and a signer that holds the master seed, so the
+//! two can run on separate hardened machines.
+//!
+//! The front-end connects to the signer with a [RemoteSignerClient](client::RemoteSignerClient) and refers to keys
+//! by their key manager branch and index. The signer derives the keys from its master seed and only ever returns
+//! public keys and signatures. Kernel nonces are created by the signer and can only be used for one signature, so a
+//! compromised front-end cannot extract a key by asking for two signatures with the same nonce.
+//!
+//! Every frame is length prefixed and, after the signer's greeting, encrypted and authenticated with XChaCha20-Poly1305
+//! under a key shared by both machines. Frames are bound to a random session id chosen by the signer and to an
+//! increasing request id, so recorded frames cannot be replayed.
+
+pub mod client;
+pub mod error;
+pub mod protocol;
+pub mod signer;
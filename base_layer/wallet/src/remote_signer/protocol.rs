@@ -0,0 +1,157 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::TryFrom;
+
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tari_common_types::types::{ComSignature, PublicKey, Signature};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_script::{ExecutionStack, TariScript};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::error::RemoteSignerError;
+use crate::util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce};
+
+/// The version of the remote signer protocol
+pub const REMOTE_SIGNER_PROTOCOL_VERSION: u8 = 1;
+/// The maximum size of a frame, which is far larger than any request
+pub const MAX_FRAME_SIZE: usize = 64 * 1024;
+/// The length of the key shared by the front-end and the signer
+pub const SHARED_KEY_BYTES: usize = 32;
+/// The number of random bytes in a session id
+pub const SESSION_ID_BYTES: usize = 32;
+
+pub(crate) const REQUEST_DOMAIN: &[u8] = b"REMOTE_SIGNER_REQUEST";
+pub(crate) const RESPONSE_DOMAIN: &[u8] = b"REMOTE_SIGNER_RESPONSE";
+
+/// A key of the signer, derived from its master seed in the same way as the keys of the key manager
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeyId {
+    pub branch: String,
+    pub index: u64,
+}
+
+impl KeyId {
+    pub fn new<T: Into<String>>(branch: T, index: u64) -> Self {
+        Self {
+            branch: branch.into(),
+            index,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SignerRequest {
+    /// Returns the public key of a key, so the front-end can build transactions without the private key
+    GetPublicKey(KeyId),
+    /// Creates a nonce for a kernel signature and returns its id and public nonce
+    CreateNonce,
+    /// Signs a kernel challenge with an excess key. The nonce is discarded by the signer, whether signing succeeds or
+    /// not.
+    SignKernel {
+        excess_key: KeyId,
+        nonce_id: u64,
+        challenge: Vec<u8>,
+    },
+    /// Signs the script of an input that spends the output committed to by `spending_key` and `value`. The signer
+    /// builds the script challenge itself.
+    SignScript {
+        spending_key: KeyId,
+        script_key: KeyId,
+        value: MicroTari,
+        script: TariScript,
+        input_data: ExecutionStack,
+    },
+    /// Signs a message in the same way as [Wallet::sign_message](crate::wallet::Wallet::sign_message)
+    SignMessage { key: KeyId, message: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SignerResponse {
+    PublicKey(PublicKey),
+    Nonce {
+        nonce_id: u64,
+        public_nonce: PublicKey,
+    },
+    Signature(Signature),
+    ScriptSignature(ComSignature),
+    /// The signer refused or failed to handle the request
+    Error(String),
+}
+
+/// Sent in the clear by the signer when a connection is opened
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignerHello {
+    pub version: u8,
+    pub session_id: [u8; SESSION_ID_BYTES],
+}
+
+/// The authenticated content of a frame
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Envelope<T> {
+    pub session_id: [u8; SESSION_ID_BYTES],
+    pub request_id: u64,
+    pub body: T,
+}
+
+pub(crate) fn cipher_from_shared_key(shared_key: &[u8]) -> Result<XChaCha20Poly1305, RemoteSignerError> {
+    if shared_key.len() != SHARED_KEY_BYTES {
+        return Err(RemoteSignerError::InvalidSharedKey(SHARED_KEY_BYTES));
+    }
+    Ok(XChaCha20Poly1305::new(Key::from_slice(shared_key)))
+}
+
+pub(crate) async fn write_frame<W>(writer: &mut W, bytes: &[u8]) -> Result<(), RemoteSignerError>
+where W: AsyncWrite + Unpin {
+    if bytes.len() > MAX_FRAME_SIZE {
+        return Err(RemoteSignerError::FrameTooLarge(bytes.len()));
+    }
+    let len = u32::try_from(bytes.len()).map_err(|_| RemoteSignerError::FrameTooLarge(bytes.len()))?;
+    writer.write_u32(len).await?;
+    writer.write_all(bytes).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+pub(crate) async fn read_frame<R>(reader: &mut R) -> Result<Vec<u8>, RemoteSignerError>
+where R: AsyncRead + Unpin {
+    let len = reader.read_u32().await? as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(RemoteSignerError::FrameTooLarge(len));
+    }
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes).await?;
+    Ok(bytes)
+}
+
+pub(crate) async fn write_sealed<W, T>(
+    writer: &mut W,
+    cipher: &XChaCha20Poly1305,
+    domain: &[u8],
+    envelope: &Envelope<T>,
+) -> Result<(), RemoteSignerError>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let bytes = bincode::serialize(envelope)?;
+    let sealed =
+        encrypt_bytes_integral_nonce(cipher, domain.to_vec(), bytes).map_err(RemoteSignerError::EncryptionError)?;
+    write_frame(writer, &sealed).await
+}
+
+pub(crate) async fn read_sealed<R, T>(
+    reader: &mut R,
+    cipher: &XChaCha20Poly1305,
+    domain: &[u8],
+) -> Result<Envelope<T>, RemoteSignerError>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let sealed = read_frame(reader).await?;
+    let bytes = decrypt_bytes_integral_nonce(cipher, domain.to_vec(), sealed)
+        .map_err(|_| RemoteSignerError::AuthenticationFailed)?;
+    Ok(bincode::deserialize(&bytes)?)
+}
@@ -0,0 +1,237 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+    },
+};
+
+use chacha20poly1305::XChaCha20Poly1305;
+use digest::Digest;
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use tari_common_types::types::{ComSignature, CommitmentFactory, PrivateKey, PublicKey, Signature};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction_components::{TransactionInput, TransactionInputVersion},
+};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    hash::blake2::Blake256,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+};
+use tari_key_manager::{cipher_seed::CipherSeed, key_manager::KeyManager};
+use tari_script::{ExecutionStack, TariScript};
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+};
+
+use super::{
+    error::RemoteSignerError,
+    protocol::{
+        cipher_from_shared_key,
+        read_sealed,
+        write_frame,
+        write_sealed,
+        Envelope,
+        KeyId,
+        SignerHello,
+        SignerRequest,
+        SignerResponse,
+        REMOTE_SIGNER_PROTOCOL_VERSION,
+        REQUEST_DOMAIN,
+        RESPONSE_DOMAIN,
+        SESSION_ID_BYTES,
+    },
+};
+use crate::types::KeyDigest;
+
+const LOG_TARGET: &str = "wallet::remote_signer::signer";
+
+/// The key holding side of the remote signer protocol. It derives every key from the master seed of the wallet it
+/// signs for and keeps the nonces it created in memory until they are used, so a restart discards unused nonces.
+pub struct RemoteSigner {
+    master_seed: CipherSeed,
+    cipher: XChaCha20Poly1305,
+    factory: CommitmentFactory,
+    nonces: Mutex<HashMap<u64, PrivateKey>>,
+    next_nonce_id: AtomicU64,
+}
+
+impl RemoteSigner {
+    pub fn new(master_seed: CipherSeed, shared_key: &[u8]) -> Result<Self, RemoteSignerError> {
+        Ok(Self {
+            master_seed,
+            cipher: cipher_from_shared_key(shared_key)?,
+            factory: CommitmentFactory::default(),
+            nonces: Mutex::new(HashMap::new()),
+            next_nonce_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Serves every connection accepted by `listener` until the shutdown signal is triggered
+    pub async fn serve(self: Arc<Self>, listener: TcpListener, mut shutdown: ShutdownSignal) {
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, address)) => {
+                        debug!(target: LOG_TARGET, "Remote signer connection from {}", address);
+                        let signer = self.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = signer.serve_connection(stream).await {
+                                warn!(target: LOG_TARGET, "Remote signer connection from {} failed: {}", address, e);
+                            }
+                        });
+                    },
+                    Err(e) => warn!(target: LOG_TARGET, "Could not accept a remote signer connection: {}", e),
+                },
+                _ = shutdown.wait() => {
+                    info!(target: LOG_TARGET, "Remote signer shutting down because the shutdown signal was received");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Runs a session over an established connection until the front-end closes it. The session ends with an error
+    /// on the first frame that cannot be authenticated or does not belong to the session.
+    pub async fn serve_connection<S>(&self, mut stream: S) -> Result<(), RemoteSignerError>
+    where S: AsyncRead + AsyncWrite + Unpin {
+        let mut session_id = [0u8; SESSION_ID_BYTES];
+        OsRng.fill_bytes(&mut session_id);
+        let hello = SignerHello {
+            version: REMOTE_SIGNER_PROTOCOL_VERSION,
+            session_id,
+        };
+        write_frame(&mut stream, &bincode::serialize(&hello)?).await?;
+
+        let mut last_request_id = 0;
+        loop {
+            let request = match read_sealed::<_, SignerRequest>(&mut stream, &self.cipher, REQUEST_DOMAIN).await {
+                Ok(request) => request,
+                Err(RemoteSignerError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            if request.session_id != session_id || request.request_id <= last_request_id {
+                return Err(RemoteSignerError::UnexpectedFrame);
+            }
+            last_request_id = request.request_id;
+
+            let response = Envelope {
+                session_id,
+                request_id: request.request_id,
+                body: self.handle_request(request.body),
+            };
+            write_sealed(&mut stream, &self.cipher, RESPONSE_DOMAIN, &response).await?;
+        }
+    }
+
+    pub fn handle_request(&self, request: SignerRequest) -> SignerResponse {
+        let result = match request {
+            SignerRequest::GetPublicKey(key) => self
+                .derive_key(&key)
+                .map(|k| SignerResponse::PublicKey(PublicKey::from_secret_key(&k))),
+            SignerRequest::CreateNonce => Ok(self.create_nonce()),
+            SignerRequest::SignKernel {
+                excess_key,
+                nonce_id,
+                challenge,
+            } => self.sign_kernel(&excess_key, nonce_id, &challenge),
+            SignerRequest::SignScript {
+                spending_key,
+                script_key,
+                value,
+                script,
+                input_data,
+            } => self.sign_script(&spending_key, &script_key, value, &script, &input_data),
+            SignerRequest::SignMessage { key, message } => self.sign_message(&key, &message),
+        };
+        result.unwrap_or_else(|e| {
+            warn!(target: LOG_TARGET, "Remote signer refused a request: {}", e);
+            SignerResponse::Error(e)
+        })
+    }
+
+    fn derive_key(&self, key: &KeyId) -> Result<PrivateKey, String> {
+        KeyManager::<PrivateKey, KeyDigest>::from(self.master_seed.clone(), key.branch.clone(), 0)
+            .derive_key(key.index)
+            .map(|derived| derived.k)
+            .map_err(|e| format!("Could not derive key {:?}: {}", key, e))
+    }
+
+    fn create_nonce(&self) -> SignerResponse {
+        let nonce = PrivateKey::random(&mut OsRng);
+        let public_nonce = PublicKey::from_secret_key(&nonce);
+        let nonce_id = self.next_nonce_id.fetch_add(1, Ordering::SeqCst);
+        self.nonces
+            .lock()
+            .expect("The remote signer nonce lock is poisoned")
+            .insert(nonce_id, nonce);
+        SignerResponse::Nonce { nonce_id, public_nonce }
+    }
+
+    fn sign_kernel(&self, excess_key: &KeyId, nonce_id: u64, challenge: &[u8]) -> Result<SignerResponse, String> {
+        // The nonce is removed first, so it can never be used for a second signature
+        let nonce = self
+            .nonces
+            .lock()
+            .expect("The remote signer nonce lock is poisoned")
+            .remove(&nonce_id)
+            .ok_or_else(|| format!("Nonce {} does not exist or was already used", nonce_id))?;
+        let excess = self.derive_key(excess_key)?;
+        Signature::sign(excess, nonce, challenge)
+            .map(SignerResponse::Signature)
+            .map_err(|e| e.to_string())
+    }
+
+    fn sign_script(
+        &self,
+        spending_key: &KeyId,
+        script_key: &KeyId,
+        value: MicroTari,
+        script: &TariScript,
+        input_data: &ExecutionStack,
+    ) -> Result<SignerResponse, String> {
+        let spending_key = self.derive_key(spending_key)?;
+        let script_key = self.derive_key(script_key)?;
+        let commitment = self.factory.commit(&spending_key, &value.into());
+        let script_nonce_a = PrivateKey::random(&mut OsRng);
+        let script_nonce_b = PrivateKey::random(&mut OsRng);
+        let nonce_commitment = self.factory.commit(&script_nonce_b, &script_nonce_a);
+
+        let challenge = TransactionInput::build_script_challenge(
+            TransactionInputVersion::get_current_version(),
+            &nonce_commitment,
+            script,
+            input_data,
+            &PublicKey::from_secret_key(&script_key),
+            &commitment,
+        );
+        ComSignature::sign(
+            &value.into(),
+            &(&script_key + &spending_key),
+            &script_nonce_a,
+            &script_nonce_b,
+            &challenge,
+            &self.factory,
+        )
+        .map(SignerResponse::ScriptSignature)
+        .map_err(|e| e.to_string())
+    }
+
+    fn sign_message(&self, key: &KeyId, message: &str) -> Result<SignerResponse, String> {
+        let secret = self.derive_key(key)?;
+        let nonce = PrivateKey::random(&mut OsRng);
+        let challenge = Blake256::digest(message.as_bytes());
+        Signature::sign(secret, nonce, &challenge)
+            .map(SignerResponse::Signature)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -20,6 +20,26 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+table! {
+    aggregated_sender_sessions (public_key) {
+        public_key -> Binary,
+        session -> Text,
+        last_updated -> Timestamp,
+    }
+}
+
+table! {
+    chat_messages (message_id) {
+        message_id -> Binary,
+        address -> Binary,
+        body -> Text,
+        direction -> Integer,
+        sent_at -> Timestamp,
+        stored_at -> Timestamp,
+        delivery_confirmed_at -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     client_key_values (key) {
         key -> Text,
@@ -49,6 +69,16 @@ table! {
         mined_timestamp -> Nullable<Timestamp>,
         transaction_signature_nonce -> Binary,
         transaction_signature_key -> Binary,
+        is_decoy -> Integer,
+    }
+}
+
+table! {
+    contact_groups (aggregate_public_key) {
+        aggregate_public_key -> Binary,
+        alias -> Text,
+        threshold -> Integer,
+        members -> Binary,
     }
 }
 
@@ -62,6 +92,25 @@ table! {
     }
 }
 
+table! {
+    deposit_identities (key_index) {
+        key_index -> BigInt,
+        label -> Text,
+        public_key -> Binary,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    deposits (commitment) {
+        commitment -> Binary,
+        key_index -> BigInt,
+        value -> BigInt,
+        tx_id -> BigInt,
+        received_at -> Timestamp,
+    }
+}
+
 table! {
     inbound_transactions (tx_id) {
         tx_id -> BigInt,
@@ -74,6 +123,15 @@ table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        expires_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    key_manager_epochs (epoch) {
+        epoch -> BigInt,
+        created_at -> Timestamp,
+        compromised_at -> Nullable<Timestamp>,
     }
 }
 
@@ -119,6 +177,15 @@ table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        expires_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    output_balances (status, source) {
+        status -> Integer,
+        source -> Integer,
+        amount -> BigInt,
     }
 }
 
@@ -159,6 +226,29 @@ table! {
     }
 }
 
+table! {
+    outputs_version (id) {
+        id -> Integer,
+        version -> BigInt,
+    }
+}
+
+table! {
+    pending_approval_transactions (tx_id) {
+        tx_id -> BigInt,
+        payment_type -> Integer,
+        destination_public_key -> Binary,
+        amount -> BigInt,
+        fee_per_gram -> BigInt,
+        output_features -> Text,
+        message -> Text,
+        timestamp -> Timestamp,
+        expiry_timestamp -> Timestamp,
+        memo -> Nullable<Text>,
+        expires_at -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     scanned_blocks (header_hash) {
         header_hash -> Binary,
@@ -169,6 +259,43 @@ table! {
     }
 }
 
+table! {
+    transaction_attachments (tx_id, name) {
+        tx_id -> BigInt,
+        name -> Text,
+        content_type -> Text,
+        data -> Binary,
+        timestamp -> Timestamp,
+    }
+}
+
+table! {
+    transaction_idempotency_keys (idempotency_key) {
+        idempotency_key -> Text,
+        tx_id -> BigInt,
+        destination_public_key -> Binary,
+        amount -> BigInt,
+        timestamp -> Timestamp,
+    }
+}
+
+table! {
+    transaction_protocol_states (tx_id) {
+        tx_id -> BigInt,
+        stage -> Integer,
+        counterparty_public_key -> Binary,
+        protocol_state -> Text,
+        last_updated -> Timestamp,
+    }
+}
+
+table! {
+    transaction_tags (tx_id, tag) {
+        tx_id -> BigInt,
+        tag -> Text,
+    }
+}
+
 table! {
     wallet_settings (key) {
         key -> Text,
@@ -177,15 +304,28 @@ table! {
 }
 
 allow_tables_to_appear_in_same_query!(
+    aggregated_sender_sessions,
+    chat_messages,
     client_key_values,
     completed_transactions,
+    contact_groups,
     contacts,
+    deposit_identities,
+    deposits,
     inbound_transactions,
+    key_manager_epochs,
     key_manager_states,
     key_manager_states_old,
     known_one_sided_payment_scripts,
     outbound_transactions,
+    output_balances,
     outputs,
+    outputs_version,
+    pending_approval_transactions,
     scanned_blocks,
+    transaction_attachments,
+    transaction_idempotency_keys,
+    transaction_protocol_states,
+    transaction_tags,
     wallet_settings,
 );
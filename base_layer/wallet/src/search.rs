@@ -0,0 +1,59 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Full-text search over the transaction messages, contact aliases and transaction tags of a wallet, as returned by
+//! [Wallet::search](crate::Wallet::search).
+
+use tari_common_types::transaction::TxId;
+use tari_comms::types::CommsPublicKey;
+
+use crate::{contacts_service::storage::database::Contact, transaction_service::storage::models::WalletTransaction};
+
+/// The maximum number of index entries that [Wallet::search](crate::Wallet::search) resolves
+pub const MAX_SEARCH_RESULTS: usize = 100;
+
+/// An entry of the search index that matched a query. A transaction matches when its message or one of its tags
+/// matches.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SearchMatch {
+    Transaction(TxId),
+    Contact(CommsPublicKey),
+}
+
+/// A search result, ordered by relevance
+#[derive(Debug, Clone)]
+pub enum SearchResult {
+    Transaction(Box<WalletTransaction>),
+    Contact(Contact),
+}
+
+/// Turns the words of a user query into an FTS5 query that matches entries containing words starting with every one
+/// of them. Each word is quoted, so the FTS5 query syntax cannot be used from the search box. Returns None if the
+/// query has no words.
+pub fn to_fts_query(query: &str) -> Option<String> {
+    let terms = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_builds_prefix_queries() {
+        assert_eq!(to_fts_query("  "), None);
+        assert_eq!(to_fts_query("coffee"), Some("\"coffee\"*".to_string()));
+        assert_eq!(to_fts_query(" rent  june "), Some("\"rent\"* \"june\"*".to_string()));
+        assert_eq!(
+            to_fts_query("say \"hi\" OR NEAR("),
+            Some("\"say\"* \"\"\"hi\"\"\"* \"OR\"* \"NEAR(\"*".to_string())
+        );
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::{Arc, Mutex},
+};
+
+use tari_common_types::types::{FixedHash, Signature};
+use tari_comms::{
+    multiaddr::Multiaddr,
+    peer_manager::{NodeIdentity, PeerFeatures},
+    protocol::rpc::{mock::MockRpcServer, NamedProtocolService, Request, Response, RpcError, RpcStatus, Streaming},
+};
+use tari_core::{
+    base_node::{
+        proto::wallet_rpc::FeeFloor,
+        rpc::{BaseNodeWalletRpcClient, BaseNodeWalletRpcServer, BaseNodeWalletService},
+    },
+    proto,
+    proto::{
+        base_node::{
+            BlockInclusionProof,
+            BlockInclusionProofRequest,
+            FeeFloorResponse,
+            FetchMatchingUtxos,
+            FetchUtxosResponse,
+            GetMempoolFeePerGramStatsRequest,
+            GetMempoolFeePerGramStatsResponse,
+            NetworkHashRateRequest,
+            NetworkHashRateResponse,
+            QueryDeletedRequest,
+            QueryDeletedResponse,
+            Signatures as SignaturesProto,
+            SyncUtxosByBlockRequest,
+            SyncUtxosByBlockResponse,
+            TipInfoResponse,
+            TransactionPackage,
+            TxQueryBatchResponse as TxQueryBatchResponseProto,
+            TxQueryBatchResponses as TxQueryBatchResponsesProto,
+            TxQueryResponse as TxQueryResponseProto,
+            TxSubmissionResponse as TxSubmissionResponseProto,
+            UtxoQueryRequest,
+            UtxoQueryResponse,
+            UtxoQueryResponses,
+        },
+        types::{Signature as SignatureProto, Transaction as TransactionProto},
+    },
+    transactions::transaction_components::Transaction,
+};
+use tokio::{sync::mpsc, task};
+
+use crate::{connectivity_service::WalletConnectivityMock, simulation::SimulatedChain};
+
+/// The mock RPC server that serves a [SimulatedBaseNode] to a wallet. The wallet is disconnected when it is dropped.
+pub type SimulatedBaseNodeServer = MockRpcServer<BaseNodeWalletRpcServer<SimulatedBaseNode>>;
+
+/// A base node that answers the wallet's RPC requests from a [SimulatedChain]. Clones share the same chain, so a test
+/// can keep a clone to mine, reorg and change the mempool behaviour while the wallet is connected.
+#[derive(Clone)]
+pub struct SimulatedBaseNode {
+    chain: Arc<Mutex<SimulatedChain>>,
+    node_identity: Arc<NodeIdentity>,
+}
+
+impl SimulatedBaseNode {
+    pub fn new(chain: SimulatedChain) -> Self {
+        Self {
+            chain: Arc::new(Mutex::new(chain)),
+            node_identity: Arc::new(NodeIdentity::random(
+                &mut rand::rngs::OsRng,
+                Multiaddr::empty(),
+                PeerFeatures::COMMUNICATION_NODE,
+            )),
+        }
+    }
+
+    pub fn node_identity(&self) -> Arc<NodeIdentity> {
+        self.node_identity.clone()
+    }
+
+    /// Changes or inspects the chain, e.g. `base_node.with_chain(|chain| chain.mine_blocks(3))`
+    pub fn with_chain<F, R>(&self, f: F) -> R
+    where F: FnOnce(&mut SimulatedChain) -> R {
+        let mut chain = acquire_lock!(self.chain);
+        f(&mut chain)
+    }
+
+    /// Serves this base node and sets it as the base node of the wallet services that use `connectivity`. The returned
+    /// server must be kept for as long as the wallet should stay connected.
+    pub async fn connect(&self, connectivity: &WalletConnectivityMock) -> Result<SimulatedBaseNodeServer, RpcError> {
+        let server = BaseNodeWalletRpcServer::new(self.clone());
+        let protocol_name = server.as_protocol_name();
+        let mut rpc_server = MockRpcServer::new(server, self.node_identity.clone());
+        rpc_server.serve();
+        let mut connection = rpc_server
+            .create_connection(self.node_identity.to_peer(), protocol_name.into())
+            .await;
+        let client = connection.connect_rpc::<BaseNodeWalletRpcClient>().await?;
+        connectivity.notify_base_node_set(self.node_identity.to_peer());
+        connectivity.set_base_node_wallet_rpc_client(client);
+        Ok(rpc_server)
+    }
+}
+
+fn parse_hash(hash: Vec<u8>) -> Result<FixedHash, RpcStatus> {
+    hash.try_into()
+        .map_err(|_| RpcStatus::bad_request("Malformed hash received"))
+}
+
+#[tari_comms::async_trait]
+impl BaseNodeWalletService for SimulatedBaseNode {
+    async fn submit_transaction(
+        &self,
+        request: Request<TransactionProto>,
+    ) -> Result<Response<TxSubmissionResponseProto>, RpcStatus> {
+        let transaction = Transaction::try_from(request.into_message())
+            .map_err(|_| RpcStatus::bad_request("Transaction was invalid"))?;
+        let response = self.with_chain(|chain| chain.submit_transaction(transaction));
+        Ok(Response::new(response.into()))
+    }
+
+    async fn transaction_query(
+        &self,
+        request: Request<SignatureProto>,
+    ) -> Result<Response<TxQueryResponseProto>, RpcStatus> {
+        let signature =
+            Signature::try_from(request.into_message()).map_err(|_| RpcStatus::bad_request("Signature was invalid"))?;
+        let response = self.with_chain(|chain| chain.query_transaction(&signature));
+        Ok(Response::new(response.into()))
+    }
+
+    async fn transaction_batch_query(
+        &self,
+        request: Request<SignaturesProto>,
+    ) -> Result<Response<TxQueryBatchResponsesProto>, RpcStatus> {
+        let signatures = request
+            .into_message()
+            .sigs
+            .into_iter()
+            .map(|s| Signature::try_from(s).map_err(|_| RpcStatus::bad_request("Signature was invalid")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let chain = acquire_lock!(self.chain);
+        let responses = signatures
+            .into_iter()
+            .map(|signature| {
+                let response = TxQueryResponseProto::from(chain.query_transaction(&signature));
+                TxQueryBatchResponseProto {
+                    signature: Some(signature.into()),
+                    location: response.location,
+                    block_hash: response.block_hash,
+                    confirmations: response.confirmations,
+                    block_height: response.height_of_longest_chain - response.confirmations,
+                    mined_timestamp: response.mined_timestamp,
+                }
+            })
+            .collect();
+        let tip = chain.tip();
+        Ok(Response::new(TxQueryBatchResponsesProto {
+            responses,
+            is_synced: chain.is_synced(),
+            tip_hash: Some(tip.hash().to_vec()),
+            height_of_longest_chain: tip.height(),
+            tip_mined_timestamp: Some(tip.timestamp()),
+        }))
+    }
+
+    async fn fetch_matching_utxos(
+        &self,
+        request: Request<FetchMatchingUtxos>,
+    ) -> Result<Response<FetchUtxosResponse>, RpcStatus> {
+        let hashes = request.into_message().output_hashes;
+        let chain = acquire_lock!(self.chain);
+        let outputs = hashes
+            .iter()
+            .filter(|hash| chain.find_spend(hash).is_none())
+            .filter_map(|hash| chain.find_output(hash))
+            .map(|mined| mined.output.clone().into())
+            .collect();
+        Ok(Response::new(FetchUtxosResponse {
+            outputs,
+            is_synced: chain.is_synced(),
+        }))
+    }
+
+    async fn get_tip_info(&self, _request: Request<()>) -> Result<Response<TipInfoResponse>, RpcStatus> {
+        let chain = acquire_lock!(self.chain);
+        Ok(Response::new(TipInfoResponse {
+            metadata: Some(chain.chain_metadata().into()),
+            is_synced: chain.is_synced(),
+        }))
+    }
+
+    async fn get_header(&self, request: Request<u64>) -> Result<Response<proto::core::BlockHeader>, RpcStatus> {
+        self.get_header_by_height(request).await
+    }
+
+    async fn utxo_query(&self, request: Request<UtxoQueryRequest>) -> Result<Response<UtxoQueryResponses>, RpcStatus> {
+        let hashes = request.into_message().output_hashes;
+        if hashes.is_empty() {
+            return Err(RpcStatus::bad_request("Empty output hashes"));
+        }
+        let chain = acquire_lock!(self.chain);
+        let responses = hashes
+            .iter()
+            .filter_map(|hash| chain.find_output(hash))
+            .map(|mined| UtxoQueryResponse {
+                output: Some(mined.output.clone().into()),
+                mmr_position: mined.mmr_position,
+                mined_height: mined.block.height(),
+                mined_in_block: mined.block.hash().to_vec(),
+                output_hash: mined.output.hash().to_vec(),
+                mined_timestamp: mined.block.timestamp(),
+            })
+            .collect();
+        let tip = chain.tip();
+        Ok(Response::new(UtxoQueryResponses {
+            responses,
+            best_block: tip.hash().to_vec(),
+            height_of_longest_chain: tip.height(),
+        }))
+    }
+
+    async fn query_deleted(
+        &self,
+        request: Request<QueryDeletedRequest>,
+    ) -> Result<Response<QueryDeletedResponse>, RpcStatus> {
+        let message = request.into_message();
+        let chain = acquire_lock!(self.chain);
+        if let Some(hash) = message.chain_must_include_header {
+            if chain.block_by_hash(&hash).is_none() {
+                return Err(RpcStatus::not_found(
+                    "Chain does not include header. It might have been reorged out",
+                ));
+            }
+        }
+
+        let mut response = QueryDeletedResponse::default();
+        for position in message.mmr_positions {
+            match chain.find_spend_by_mmr_position(position) {
+                Some(block) => {
+                    response.deleted_positions.push(position);
+                    if message.include_deleted_block_data {
+                        response.heights_deleted_at.push(block.height());
+                        response.blocks_deleted_in.push(block.hash().to_vec());
+                    }
+                },
+                None => response.not_deleted_positions.push(position),
+            }
+        }
+        let tip = chain.tip();
+        response.best_block = tip.hash().to_vec();
+        response.height_of_longest_chain = tip.height();
+        Ok(Response::new(response))
+    }
+
+    async fn get_header_by_height(
+        &self,
+        request: Request<u64>,
+    ) -> Result<Response<proto::core::BlockHeader>, RpcStatus> {
+        let height = request.into_message();
+        let header = self
+            .with_chain(|chain| chain.block_at_height(height).map(|b| b.header().clone()))
+            .ok_or_else(|| RpcStatus::not_found(&format!("Header not found at height {}", height)))?;
+        Ok(Response::new(header.into()))
+    }
+
+    async fn get_height_at_time(&self, request: Request<u64>) -> Result<Response<u64>, RpcStatus> {
+        let timestamp = request.into_message();
+        Ok(Response::new(self.with_chain(|chain| chain.height_at_time(timestamp))))
+    }
+
+    async fn sync_utxos_by_block(
+        &self,
+        request: Request<SyncUtxosByBlockRequest>,
+    ) -> Result<Streaming<SyncUtxosByBlockResponse>, RpcStatus> {
+        let SyncUtxosByBlockRequest {
+            start_header_hash,
+            end_header_hash,
+        } = request.into_message();
+        let start_header_hash = parse_hash(start_header_hash)?;
+        let end_header_hash = parse_hash(end_header_hash)?;
+
+        let items = {
+            let chain = acquire_lock!(self.chain);
+            let (start, end) = match (
+                chain.block_by_hash(start_header_hash.as_slice()),
+                chain.block_by_hash(end_header_hash.as_slice()),
+            ) {
+                (Some(start), Some(end)) => (start.height(), end.height()),
+                _ => return Err(RpcStatus::not_found("Headers not found")),
+            };
+            (start..=end)
+                .filter_map(|height| chain.block_at_height(height))
+                .map(|block| SyncUtxosByBlockResponse {
+                    outputs: block
+                        .outputs()
+                        .iter()
+                        .filter(|o| chain.find_spend(o.hash().as_slice()).is_none())
+                        .map(|o| o.clone().into())
+                        .collect(),
+                    height: block.height(),
+                    header_hash: block.hash().to_vec(),
+                    mined_timestamp: block.timestamp(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let (tx, rx) = mpsc::channel(items.len().max(1));
+        task::spawn(async move {
+            for item in items {
+                if tx.send(Ok(item)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Streaming::new(rx))
+    }
+
+    async fn get_mempool_fee_per_gram_stats(
+        &self,
+        _request: Request<GetMempoolFeePerGramStatsRequest>,
+    ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus> {
+        Ok(Response::new(GetMempoolFeePerGramStatsResponse::default()))
+    }
+
+    async fn get_block_inclusion_proof(
+        &self,
+        _request: Request<BlockInclusionProofRequest>,
+    ) -> Result<Response<BlockInclusionProof>, RpcStatus> {
+        Err(RpcStatus::not_implemented(
+            "The simulated base node does not provide block inclusion proofs",
+        ))
+    }
+
+    async fn submit_transaction_package(
+        &self,
+        request: Request<TransactionPackage>,
+    ) -> Result<Response<TxSubmissionResponseProto>, RpcStatus> {
+        let transactions = request
+            .into_message()
+            .transactions
+            .into_iter()
+            .map(Transaction::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| RpcStatus::bad_request("Transaction was invalid"))?;
+        let response = self.with_chain(|chain| chain.submit_transaction_package(transactions));
+        Ok(Response::new(response.into()))
+    }
+
+    /// The simulated blocks are not mined with a difficulty, so only the tip height is reported
+    async fn get_network_hash_rate(
+        &self,
+        _request: Request<NetworkHashRateRequest>,
+    ) -> Result<Response<NetworkHashRateResponse>, RpcStatus> {
+        let chain = acquire_lock!(self.chain);
+        Ok(Response::new(NetworkHashRateResponse {
+            tip_height: chain.height(),
+            ..Default::default()
+        }))
+    }
+
+    async fn get_fee_floor(&self, _request: Request<()>) -> Result<Response<FeeFloorResponse>, RpcStatus> {
+        Ok(Response::new(FeeFloor::consensus().into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::connectivity_service::{create_wallet_connectivity_mock, WalletConnectivityInterface};
+
+    #[tokio::test]
+    async fn it_serves_the_simulated_chain() {
+        let base_node = SimulatedBaseNode::new(SimulatedChain::new());
+        let mut connectivity = create_wallet_connectivity_mock();
+        let _server = base_node.connect(&connectivity).await.unwrap();
+        assert_eq!(
+            connectivity.get_current_base_node_id(),
+            Some(base_node.node_identity().node_id().clone())
+        );
+
+        let mut client = connectivity.obtain_base_node_wallet_rpc_client().await.unwrap();
+        let old_tip = base_node.with_chain(|chain| chain.mine_blocks(3));
+        let metadata = client.get_tip_info().await.unwrap().metadata.unwrap();
+        assert_eq!(metadata.height_of_longest_chain, Some(3));
+        assert_eq!(metadata.best_block, Some(old_tip.to_vec()));
+
+        let new_tip = base_node.with_chain(|chain| chain.reorg(2, 2));
+        let header = client.get_header_by_height(3).await.unwrap();
+        assert_eq!(header.height, 3);
+        assert_ne!(new_tip, old_tip);
+        let err = client
+            .query_deleted(QueryDeletedRequest {
+                chain_must_include_header: Some(old_tip.to_vec()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RpcError::RequestFailed(status) if status.is_not_found()));
+    }
+}
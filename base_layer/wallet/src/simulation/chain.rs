@@ -0,0 +1,490 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::TryFrom;
+
+use tari_common_types::{
+    chain_metadata::ChainMetadata,
+    types::{BlockHash, HashOutput, Signature},
+};
+use tari_core::{
+    base_node::proto::wallet_rpc::{TxLocation, TxQueryResponse, TxSubmissionRejectionReason, TxSubmissionResponse},
+    blocks::BlockHeader,
+    transactions::transaction_components::{Transaction, TransactionOutput},
+};
+use tari_utilities::epoch_time::EpochTime;
+
+/// The timestamp of the genesis block, 2022-01-01 00:00:00 UTC
+const GENESIS_TIMESTAMP: u64 = 1_640_995_200;
+/// The number of seconds between the timestamps of consecutive blocks
+const BLOCK_TIME_SECS: u64 = 120;
+
+/// How the simulated mempool treats submitted transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolBehavior {
+    /// Transactions are validated against the chain and kept until they are mined
+    Accept,
+    /// Every transaction is rejected with the given reason
+    Reject(TxSubmissionRejectionReason),
+    /// Transactions are reported as accepted but are discarded, as if they had been evicted from the mempool
+    Discard,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatedBlock {
+    header: BlockHeader,
+    hash: BlockHash,
+    transactions: Vec<Transaction>,
+    /// The outputs added in this block, in MMR order
+    outputs: Vec<TransactionOutput>,
+    /// The hashes of the outputs spent in this block
+    spent: Vec<HashOutput>,
+}
+
+impl SimulatedBlock {
+    pub fn header(&self) -> &BlockHeader {
+        &self.header
+    }
+
+    pub fn hash(&self) -> BlockHash {
+        self.hash
+    }
+
+    pub fn height(&self) -> u64 {
+        self.header.height
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.header.timestamp.as_u64()
+    }
+
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    pub fn outputs(&self) -> &[TransactionOutput] {
+        &self.outputs
+    }
+
+    /// The MMR position of the first output added in this block
+    fn first_mmr_position(&self) -> u64 {
+        self.header.output_mmr_size - self.outputs.len() as u64
+    }
+
+    fn contains_kernel(&self, excess_sig: &Signature) -> bool {
+        self.transactions
+            .iter()
+            .any(|tx| tx.body.kernels().iter().any(|k| &k.excess_sig == excess_sig))
+    }
+}
+
+/// An output found on the simulated chain
+#[derive(Debug, Clone)]
+pub struct MinedOutput<'a> {
+    pub output: &'a TransactionOutput,
+    pub mmr_position: u64,
+    pub block: &'a SimulatedBlock,
+}
+
+/// A chain that is extended, reorged and queried by the test instead of by consensus. Block timestamps are derived from
+/// the height and block hashes from the height and the number of reorgs, so a chain built by the same sequence of calls
+/// is identical across test runs.
+#[derive(Debug, Clone)]
+pub struct SimulatedChain {
+    blocks: Vec<SimulatedBlock>,
+    mempool: Vec<Transaction>,
+    mempool_behavior: MempoolBehavior,
+    is_synced: bool,
+    num_reorgs: u64,
+}
+
+impl SimulatedChain {
+    /// Creates a chain containing only an empty genesis block
+    pub fn new() -> Self {
+        let mut header = BlockHeader::new(0);
+        header.timestamp = EpochTime::from(GENESIS_TIMESTAMP);
+        Self {
+            blocks: vec![SimulatedBlock {
+                hash: header.hash(),
+                header,
+                transactions: vec![],
+                outputs: vec![],
+                spent: vec![],
+            }],
+            mempool: vec![],
+            mempool_behavior: MempoolBehavior::Accept,
+            is_synced: true,
+            num_reorgs: 0,
+        }
+    }
+
+    pub fn tip(&self) -> &SimulatedBlock {
+        self.blocks.last().expect("The chain always contains the genesis block")
+    }
+
+    pub fn height(&self) -> u64 {
+        self.tip().height()
+    }
+
+    pub fn block_at_height(&self, height: u64) -> Option<&SimulatedBlock> {
+        self.blocks.get(usize::try_from(height).ok()?)
+    }
+
+    pub fn block_by_hash(&self, hash: &[u8]) -> Option<&SimulatedBlock> {
+        self.blocks.iter().find(|b| b.hash == *hash)
+    }
+
+    /// The height of the last block with a timestamp at or before `timestamp`
+    pub fn height_at_time(&self, timestamp: u64) -> u64 {
+        self.blocks
+            .iter()
+            .rev()
+            .find(|b| b.timestamp() <= timestamp)
+            .map(|b| b.height())
+            .unwrap_or(0)
+    }
+
+    pub fn chain_metadata(&self) -> ChainMetadata {
+        let tip = self.tip();
+        ChainMetadata::new(tip.height(), tip.hash, 0, 0, u128::from(tip.height()), tip.timestamp())
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.is_synced
+    }
+
+    /// Sets whether the base node reports itself as synced, which the wallet uses to decide whether to trust replies
+    pub fn set_synced(&mut self, is_synced: bool) {
+        self.is_synced = is_synced;
+    }
+
+    pub fn set_mempool_behavior(&mut self, behavior: MempoolBehavior) {
+        self.mempool_behavior = behavior;
+    }
+
+    pub fn mempool(&self) -> &[Transaction] {
+        &self.mempool
+    }
+
+    /// Evicts every transaction from the mempool without mining it
+    pub fn clear_mempool(&mut self) {
+        self.mempool.clear();
+    }
+
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> TxSubmissionResponse {
+        let result = match &self.mempool_behavior {
+            MempoolBehavior::Accept => self.validate(&transaction),
+            MempoolBehavior::Reject(reason) => Err(reason.clone()),
+            MempoolBehavior::Discard => return self.submission_response(Ok(())),
+        };
+        if result.is_ok() && !self.mempool.contains(&transaction) {
+            self.mempool.push(transaction);
+        }
+        self.submission_response(result)
+    }
+
+    /// Submits a package of dependent transactions, parents first. Either all of them are added to the mempool or none
+    /// are. Transactions of the package that were already mined are skipped.
+    pub fn submit_transaction_package(&mut self, transactions: Vec<Transaction>) -> TxSubmissionResponse {
+        let mempool_len = self.mempool.len();
+        let mut response = self.submission_response(Err(TxSubmissionRejectionReason::AlreadyMined));
+        for transaction in transactions {
+            let tx_response = self.submit_transaction(transaction);
+            match tx_response.rejection_reason {
+                TxSubmissionRejectionReason::AlreadyMined => {},
+                TxSubmissionRejectionReason::None => response = tx_response,
+                _ => {
+                    self.mempool.truncate(mempool_len);
+                    return tx_response;
+                },
+            }
+        }
+        response
+    }
+
+    fn submission_response(&self, result: Result<(), TxSubmissionRejectionReason>) -> TxSubmissionResponse {
+        TxSubmissionResponse {
+            accepted: result.is_ok(),
+            rejection_reason: result.err().unwrap_or(TxSubmissionRejectionReason::None),
+            is_synced: self.is_synced,
+        }
+    }
+
+    /// Checks the transaction against the chain and the other transactions in the mempool. Signatures and range proofs
+    /// are not checked.
+    fn validate(&self, transaction: &Transaction) -> Result<(), TxSubmissionRejectionReason> {
+        if self.mempool.contains(transaction) {
+            return Ok(());
+        }
+        if transaction
+            .body
+            .kernels()
+            .iter()
+            .any(|k| self.find_kernel(&k.excess_sig).is_some())
+        {
+            return Err(TxSubmissionRejectionReason::AlreadyMined);
+        }
+        if transaction.max_kernel_timelock() > self.height() + 1 {
+            return Err(TxSubmissionRejectionReason::TimeLocked);
+        }
+        for input in transaction.body.inputs() {
+            let hash = input.output_hash();
+            let spent_in_mempool = self
+                .mempool
+                .iter()
+                .any(|tx| tx.body.inputs().iter().any(|i| i.output_hash() == hash));
+            if spent_in_mempool || self.find_spend(hash.as_slice()).is_some() {
+                return Err(TxSubmissionRejectionReason::DoubleSpend);
+            }
+            let created_in_mempool = self
+                .mempool
+                .iter()
+                .any(|tx| tx.body.outputs().iter().any(|o| o.hash() == hash));
+            if !created_in_mempool && self.find_output(hash.as_slice()).is_none() {
+                return Err(TxSubmissionRejectionReason::Orphan);
+            }
+        }
+        Ok(())
+    }
+
+    /// Mines a block containing every transaction in the mempool and returns its hash
+    pub fn mine_block(&mut self) -> BlockHash {
+        self.mine_block_with_outputs(vec![])
+    }
+
+    /// Mines `num_blocks` blocks, the first of which contains every transaction in the mempool, and returns the hash of
+    /// the new tip
+    pub fn mine_blocks(&mut self, num_blocks: u64) -> BlockHash {
+        for _ in 0..num_blocks {
+            self.mine_block();
+        }
+        self.tip().hash
+    }
+
+    /// Mines a block containing every transaction in the mempool as well as `outputs`, which do not need a transaction.
+    /// This is how funds are given to a wallet under test.
+    pub fn mine_block_with_outputs(&mut self, outputs: Vec<TransactionOutput>) -> BlockHash {
+        let transactions = self.mempool.drain(..).collect::<Vec<_>>();
+        self.append_block(transactions, outputs)
+    }
+
+    fn append_block(&mut self, transactions: Vec<Transaction>, extra_outputs: Vec<TransactionOutput>) -> BlockHash {
+        let prev = self.tip().header.clone();
+        let mut outputs = extra_outputs;
+        let mut spent = vec![];
+        for tx in &transactions {
+            outputs.extend(tx.body.outputs().iter().cloned());
+            spent.extend(tx.body.inputs().iter().map(|i| i.output_hash()));
+        }
+
+        let mut header = BlockHeader::from_previous(&prev);
+        header.timestamp = EpochTime::from(GENESIS_TIMESTAMP + header.height * BLOCK_TIME_SECS);
+        header.output_mmr_size = prev.output_mmr_size + outputs.len() as u64;
+        header.kernel_mmr_size = prev.kernel_mmr_size +
+            transactions
+                .iter()
+                .map(|tx| tx.body.kernels().len() as u64)
+                .sum::<u64>();
+        // Blocks at the same height on different forks must have different hashes
+        header.nonce = self.num_reorgs;
+        let hash = header.hash();
+        self.blocks.push(SimulatedBlock {
+            header,
+            hash,
+            transactions,
+            outputs,
+            spent,
+        });
+        hash
+    }
+
+    /// Replaces the top `depth` blocks with `num_new_blocks` empty blocks and returns the hash of the new tip. The
+    /// transactions in the removed blocks are returned to the mempool if they are still valid on the new chain, and
+    /// outputs that were added without a transaction are lost. The genesis block cannot be reorged out.
+    pub fn reorg(&mut self, depth: u64, num_new_blocks: u64) -> BlockHash {
+        let depth = depth.min(self.height());
+        let keep = self.blocks.len() - depth as usize;
+        let removed = self.blocks.split_off(keep);
+        self.num_reorgs += 1;
+
+        let pending = self.mempool.drain(..).collect::<Vec<_>>();
+        for tx in removed.into_iter().flat_map(|b| b.transactions).chain(pending) {
+            if self.validate(&tx).is_ok() {
+                self.mempool.push(tx);
+            }
+        }
+        for _ in 0..num_new_blocks {
+            self.append_block(vec![], vec![]);
+        }
+        self.tip().hash
+    }
+
+    /// The number of blocks mined on top of the block at `height`
+    pub fn confirmations(&self, height: u64) -> u64 {
+        self.height().saturating_sub(height)
+    }
+
+    pub fn find_kernel(&self, excess_sig: &Signature) -> Option<&SimulatedBlock> {
+        self.blocks.iter().find(|b| b.contains_kernel(excess_sig))
+    }
+
+    pub fn find_output(&self, hash: &[u8]) -> Option<MinedOutput<'_>> {
+        self.blocks.iter().find_map(|block| {
+            let index = block.outputs.iter().position(|o| o.hash() == *hash)?;
+            Some(MinedOutput {
+                output: &block.outputs[index],
+                mmr_position: block.first_mmr_position() + index as u64,
+                block,
+            })
+        })
+    }
+
+    /// Returns the block in which the output with the given hash was spent
+    pub fn find_spend(&self, hash: &[u8]) -> Option<&SimulatedBlock> {
+        self.blocks.iter().find(|b| b.spent.iter().any(|s| *s == *hash))
+    }
+
+    /// Returns the block in which the output at the given MMR position was spent
+    pub fn find_spend_by_mmr_position(&self, mmr_position: u64) -> Option<&SimulatedBlock> {
+        let block = self
+            .blocks
+            .iter()
+            .find(|b| mmr_position >= b.first_mmr_position() && mmr_position < b.header.output_mmr_size)?;
+        let output = &block.outputs[(mmr_position - block.first_mmr_position()) as usize];
+        self.find_spend(output.hash().as_slice())
+    }
+
+    pub fn query_transaction(&self, excess_sig: &Signature) -> TxQueryResponse {
+        let (location, block) = match self.find_kernel(excess_sig) {
+            Some(block) => (TxLocation::Mined, Some(block)),
+            None if self
+                .mempool
+                .iter()
+                .any(|tx| tx.body.kernels().iter().any(|k| &k.excess_sig == excess_sig)) =>
+            {
+                (TxLocation::InMempool, None)
+            },
+            None => (TxLocation::NotStored, None),
+        };
+        TxQueryResponse {
+            location,
+            block_hash: block.map(|b| b.hash),
+            confirmations: block.map(|b| self.confirmations(b.height())).unwrap_or(0),
+            is_synced: self.is_synced,
+            height_of_longest_chain: self.height(),
+            mined_timestamp: block.map(|b| b.timestamp()),
+        }
+    }
+}
+
+impl Default for SimulatedChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_core::transactions::{
+        tari_amount::{uT, T},
+        test_helpers::create_tx,
+        transaction_components::OutputFeatures,
+    };
+
+    use super::*;
+
+    fn funded_tx(chain: &mut SimulatedChain) -> Transaction {
+        let (tx, inputs, _) = create_tx(T, 5 * uT, 0, 1, 0, 2, OutputFeatures::default());
+        let funding = inputs
+            .iter()
+            .map(|i| i.as_transaction_output(&Default::default()).unwrap())
+            .collect();
+        chain.mine_block_with_outputs(funding);
+        tx
+    }
+
+    #[test]
+    fn it_mines_and_confirms_transactions() {
+        let mut chain = SimulatedChain::new();
+        let tx = funded_tx(&mut chain);
+        let excess_sig = tx.first_kernel_excess_sig().unwrap().clone();
+        assert_eq!(chain.query_transaction(&excess_sig).location, TxLocation::NotStored);
+
+        assert!(chain.submit_transaction(tx.clone()).accepted);
+        assert_eq!(chain.query_transaction(&excess_sig).location, TxLocation::InMempool);
+
+        let block_hash = chain.mine_block();
+        chain.mine_blocks(2);
+        let response = chain.query_transaction(&excess_sig);
+        assert_eq!(response.location, TxLocation::Mined);
+        assert_eq!(response.block_hash, Some(block_hash));
+        assert_eq!(response.confirmations, 2);
+        assert_eq!(chain.block_at_height(2).unwrap().timestamp(), GENESIS_TIMESTAMP + 240);
+
+        let spent = tx.body.inputs()[0].output_hash();
+        assert_eq!(chain.find_spend(spent.as_slice()).unwrap().hash(), block_hash);
+        let response = chain.submit_transaction(tx);
+        assert_eq!(response.rejection_reason, TxSubmissionRejectionReason::AlreadyMined);
+    }
+
+    #[test]
+    fn it_rejects_invalid_transactions() {
+        let mut chain = SimulatedChain::new();
+        let (orphan, _, _) = create_tx(T, 5 * uT, 0, 1, 0, 2, OutputFeatures::default());
+        assert_eq!(
+            chain.submit_transaction(orphan).rejection_reason,
+            TxSubmissionRejectionReason::Orphan
+        );
+
+        let tx = funded_tx(&mut chain);
+        let mut double_spend = tx.clone();
+        double_spend.body.kernels_mut()[0].excess_sig = Signature::default();
+        assert!(chain.submit_transaction(tx).accepted);
+        assert_eq!(
+            chain.submit_transaction(double_spend).rejection_reason,
+            TxSubmissionRejectionReason::DoubleSpend
+        );
+
+        chain.set_mempool_behavior(MempoolBehavior::Discard);
+        let tx = funded_tx(&mut chain);
+        assert!(chain.submit_transaction(tx).accepted);
+        assert!(chain.mempool().is_empty());
+    }
+
+    #[test]
+    fn it_accepts_or_rejects_packages_as_a_whole() {
+        let mut chain = SimulatedChain::new();
+        let tx = funded_tx(&mut chain);
+        let (orphan, _, _) = create_tx(T, 5 * uT, 0, 1, 0, 2, OutputFeatures::default());
+        let response = chain.submit_transaction_package(vec![tx.clone(), orphan]);
+        assert_eq!(response.rejection_reason, TxSubmissionRejectionReason::Orphan);
+        assert!(chain.mempool().is_empty());
+
+        assert!(chain.submit_transaction_package(vec![tx.clone()]).accepted);
+        chain.mine_block();
+        let response = chain.submit_transaction_package(vec![tx]);
+        assert_eq!(response.rejection_reason, TxSubmissionRejectionReason::AlreadyMined);
+    }
+
+    #[test]
+    fn it_returns_reorged_transactions_to_the_mempool() {
+        let mut chain = SimulatedChain::new();
+        let tx = funded_tx(&mut chain);
+        let excess_sig = tx.first_kernel_excess_sig().unwrap().clone();
+        chain.submit_transaction(tx);
+        let mined_in = chain.mine_block();
+        let old_tip = chain.mine_block();
+
+        let new_tip = chain.reorg(2, 3);
+        assert_ne!(new_tip, old_tip);
+        assert_eq!(chain.height(), 4);
+        assert!(chain.block_by_hash(mined_in.as_slice()).is_none());
+        assert_eq!(chain.query_transaction(&excess_sig).location, TxLocation::InMempool);
+
+        // Reorging out the block that funded the transaction makes it an orphan
+        chain.reorg(4, 1);
+        assert_eq!(chain.query_transaction(&excess_sig).location, TxLocation::NotStored);
+        assert_eq!(chain.height_at_time(GENESIS_TIMESTAMP + 119), 0);
+        assert_eq!(chain.height_at_time(GENESIS_TIMESTAMP + 120), 1);
+    }
+}
@@ -0,0 +1,27 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! A simulated base node for deterministic end-to-end tests of the wallet services, enabled with the `simulation`
+//! feature.
+//!
+//! The test owns a [SimulatedChain] and decides when blocks are mined, when the chain reorgs and how the mempool treats
+//! submitted transactions. A [SimulatedBaseNode] serves the chain over the base node wallet RPC protocol, and
+//! [SimulatedBaseNode::connect] sets it as the base node of the services built with a
+//! [WalletConnectivityMock](crate::connectivity_service::WalletConnectivityMock), so no network is needed:
+//!
+//! ```ignore
+//! let base_node = SimulatedBaseNode::new(SimulatedChain::new());
+//! let connectivity = create_wallet_connectivity_mock();
+//! let _server = base_node.connect(&connectivity).await?;
+//! // ... start the services under test with `connectivity` and submit a transaction ...
+//! base_node.with_chain(|chain| chain.mine_blocks(3));
+//! ```
+//!
+//! Block inclusion proofs are not provided, so transactions cannot be confirmed when the `header_sync` feature is
+//! enabled.
+
+mod base_node;
+mod chain;
+
+pub use base_node::{SimulatedBaseNode, SimulatedBaseNodeServer};
+pub use chain::{MempoolBehavior, MinedOutput, SimulatedBlock, SimulatedChain};
@@ -27,16 +27,24 @@ use std::{
 
 use chacha20poly1305::XChaCha20Poly1305;
 use log::*;
+use tari_common::configuration::Network;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::{
+    i2p::I2pIdentity,
     multiaddr::Multiaddr,
     peer_manager::{IdentitySignature, PeerFeatures},
     tor::TorIdentity,
+    types::CommsPublicKey,
 };
 use tari_key_manager::cipher_seed::CipherSeed;
 use tari_utilities::SafePassword;
 
-use crate::{error::WalletStorageError, utxo_scanner_service::service::ScannedBlock};
+use crate::{
+    error::WalletStorageError,
+    inheritance_service::handle::InheritancePlan,
+    search::SearchMatch,
+    utxo_scanner_service::service::ScannedBlock,
+};
 
 const LOG_TARGET: &str = "wallet::database";
 
@@ -63,6 +71,8 @@ pub trait WalletBackend: Send + Sync + Clone {
         height: u64,
         exclude_recovered: bool,
     ) -> Result<(), WalletStorageError>;
+    /// Returns up to `limit` search index entries matching every word of `query`, most relevant first
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchMatch>, WalletStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -71,12 +81,17 @@ pub enum DbKey {
     CommsFeatures,
     CommsIdentitySignature,
     TorId,
+    I2pId,
+    InheritancePlan,
     BaseNodeChainMetadata,
     ClientKey(String),
     MasterSeed,
     PassphraseHash,
     EncryptionSalt,
+    WrappedEncryptionKey,
     WalletBirthday,
+    Network,
+    BaseNodeIdentity(Multiaddr),
 }
 
 pub enum DbValue {
@@ -84,24 +99,33 @@ pub enum DbValue {
     CommsFeatures(PeerFeatures),
     CommsIdentitySignature(Box<IdentitySignature>),
     TorId(TorIdentity),
+    I2pId(I2pIdentity),
+    InheritancePlan(Box<InheritancePlan>),
     ClientValue(String),
     ValueCleared,
     BaseNodeChainMetadata(ChainMetadata),
     MasterSeed(CipherSeed),
     PassphraseHash(String),
     EncryptionSalt(String),
+    WrappedEncryptionKey(String),
     WalletBirthday(String),
+    Network(String),
+    BaseNodeIdentity(CommsPublicKey),
 }
 
 #[derive(Clone)]
 pub enum DbKeyValuePair {
     ClientKeyValue(String, String),
     TorId(TorIdentity),
+    I2pId(I2pIdentity),
+    InheritancePlan(Box<InheritancePlan>),
     BaseNodeChainMetadata(ChainMetadata),
     MasterSeed(CipherSeed),
     CommsAddress(Multiaddr),
     CommsFeatures(PeerFeatures),
     CommsIdentitySignature(Box<IdentitySignature>),
+    Network(Network),
+    BaseNodeIdentity(Multiaddr, CommsPublicKey),
 }
 
 pub enum WriteOperation {
@@ -157,6 +181,42 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    pub fn get_i2p_id(&self) -> Result<Option<I2pIdentity>, WalletStorageError> {
+        let c = match self.db.fetch(&DbKey::I2pId) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::I2pId(k))) => Ok(Some(k)),
+            Ok(Some(other)) => unexpected_result(DbKey::I2pId, other),
+            Err(e) => log_error(DbKey::I2pId, e),
+        }?;
+        Ok(c)
+    }
+
+    pub fn set_i2p_identity(&self, id: I2pIdentity) -> Result<(), WalletStorageError> {
+        self.db.write(WriteOperation::Insert(DbKeyValuePair::I2pId(id)))?;
+        Ok(())
+    }
+
+    pub fn get_inheritance_plan(&self) -> Result<Option<InheritancePlan>, WalletStorageError> {
+        let c = match self.db.fetch(&DbKey::InheritancePlan) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::InheritancePlan(p))) => Ok(Some(*p)),
+            Ok(Some(other)) => unexpected_result(DbKey::InheritancePlan, other),
+            Err(e) => log_error(DbKey::InheritancePlan, e),
+        }?;
+        Ok(c)
+    }
+
+    pub fn set_inheritance_plan(&self, plan: InheritancePlan) -> Result<(), WalletStorageError> {
+        self.db
+            .write(WriteOperation::Insert(DbKeyValuePair::InheritancePlan(Box::new(plan))))?;
+        Ok(())
+    }
+
+    pub fn clear_inheritance_plan(&self) -> Result<(), WalletStorageError> {
+        self.db.write(WriteOperation::Remove(DbKey::InheritancePlan))?;
+        Ok(())
+    }
+
     pub fn get_node_address(&self) -> Result<Option<Multiaddr>, WalletStorageError> {
         let c = match self.db.fetch(&DbKey::CommsAddress) {
             Ok(None) => Ok(None),
@@ -290,6 +350,53 @@ where T: WalletBackend + 'static
         Ok(result)
     }
 
+    /// Returns the network the database was created for, or `None` for a database created before it was recorded
+    pub fn get_network(&self) -> Result<Option<Network>, WalletStorageError> {
+        let result = match self.db.fetch(&DbKey::Network) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::Network(n))) => {
+                Ok(Some(n.parse::<Network>().map_err(|_| {
+                    WalletStorageError::ConversionError(format!("Could not parse network '{}'", n))
+                })?))
+            },
+            Ok(Some(other)) => unexpected_result(DbKey::Network, other),
+            Err(e) => log_error(DbKey::Network, e),
+        }?;
+        Ok(result)
+    }
+
+    /// Records the network the database belongs to. The wallet refuses to start with a database of another network,
+    /// so this is also the way to deliberately move a database to another network.
+    pub fn set_network(&self, network: Network) -> Result<(), WalletStorageError> {
+        self.db
+            .write(WriteOperation::Insert(DbKeyValuePair::Network(network)))?;
+        Ok(())
+    }
+
+    /// Returns the public key that was pinned for the base node at `address`
+    pub fn get_base_node_identity(&self, address: &Multiaddr) -> Result<Option<CommsPublicKey>, WalletStorageError> {
+        let key = DbKey::BaseNodeIdentity(address.clone());
+        let result = match self.db.fetch(&key) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::BaseNodeIdentity(public_key))) => Ok(Some(public_key)),
+            Ok(Some(other)) => unexpected_result(key, other),
+            Err(e) => log_error(key, e),
+        }?;
+        Ok(result)
+    }
+
+    /// Pins the public key of the base node at `address`, replacing any key that was pinned before
+    pub fn set_base_node_identity(
+        &self,
+        address: Multiaddr,
+        public_key: CommsPublicKey,
+    ) -> Result<(), WalletStorageError> {
+        self.db.write(WriteOperation::Insert(DbKeyValuePair::BaseNodeIdentity(
+            address, public_key,
+        )))?;
+        Ok(())
+    }
+
     pub fn get_scanned_blocks(&self) -> Result<Vec<ScannedBlock>, WalletStorageError> {
         let result = self.db.get_scanned_blocks()?;
         Ok(result)
@@ -318,6 +425,11 @@ where T: WalletBackend + 'static
         self.db.clear_scanned_blocks_before_height(height, exclude_recovered)?;
         Ok(())
     }
+
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchMatch>, WalletStorageError> {
+        let result = self.db.search(query, limit)?;
+        Ok(result)
+    }
 }
 
 impl Display for DbKey {
@@ -327,12 +439,17 @@ impl Display for DbKey {
             DbKey::CommsAddress => f.write_str("CommsAddress"),
             DbKey::CommsFeatures => f.write_str("Nod features"),
             DbKey::TorId => f.write_str("TorId"),
+            DbKey::I2pId => f.write_str("I2pId"),
+            DbKey::InheritancePlan => f.write_str("InheritancePlan"),
             DbKey::ClientKey(k) => f.write_str(&format!("ClientKey: {:?}", k)),
             DbKey::BaseNodeChainMetadata => f.write_str("Last seen Chain metadata from basw node"),
             DbKey::PassphraseHash => f.write_str("PassphraseHash"),
             DbKey::EncryptionSalt => f.write_str("EncryptionSalt"),
+            DbKey::WrappedEncryptionKey => f.write_str("WrappedEncryptionKey"),
             DbKey::WalletBirthday => f.write_str("WalletBirthday"),
             DbKey::CommsIdentitySignature => f.write_str("CommsIdentitySignature"),
+            DbKey::Network => f.write_str("Network"),
+            DbKey::BaseNodeIdentity(a) => f.write_str(&format!("BaseNodeIdentity: {}", a)),
         }
     }
 }
@@ -346,11 +463,19 @@ impl Display for DbValue {
             DbValue::CommsFeatures(_) => f.write_str("Node features"),
             DbValue::CommsAddress(_) => f.write_str("Comms Address"),
             DbValue::TorId(v) => f.write_str(&format!("Tor ID: {}", v)),
+            DbValue::I2pId(v) => f.write_str(&format!("I2P ID: {}", v)),
+            DbValue::InheritancePlan(p) => f.write_str(&format!(
+                "Inheritance plan with {} beneficiaries",
+                p.beneficiaries.len()
+            )),
             DbValue::BaseNodeChainMetadata(v) => f.write_str(&format!("Last seen Chain metadata from base node:{}", v)),
             DbValue::PassphraseHash(h) => f.write_str(&format!("PassphraseHash: {}", h)),
             DbValue::EncryptionSalt(s) => f.write_str(&format!("EncryptionSalt: {}", s)),
+            DbValue::WrappedEncryptionKey(k) => f.write_str(&format!("WrappedEncryptionKey: {}", k)),
             DbValue::WalletBirthday(b) => f.write_str(&format!("WalletBirthday: {}", b)),
             DbValue::CommsIdentitySignature(_) => f.write_str("CommsIdentitySignature"),
+            DbValue::Network(n) => f.write_str(&format!("Network: {}", n)),
+            DbValue::BaseNodeIdentity(pk) => f.write_str(&format!("BaseNodeIdentity: {}", pk)),
         }
     }
 }
@@ -373,6 +498,9 @@ fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, WalletStorageErro
 
 #[cfg(test)]
 mod test {
+    use rand::rngs::OsRng;
+    use tari_comms::{multiaddr::Multiaddr, types::CommsPublicKey};
+    use tari_crypto::keys::PublicKey;
     use tari_key_manager::cipher_seed::CipherSeed;
     use tari_test_utils::random::string;
     use tempfile::tempdir;
@@ -427,5 +555,13 @@ mod test {
         assert!(db.clear_client_value(client_key_values[0].0.clone()).unwrap());
 
         assert!(!db.clear_client_value(client_key_values[0].0.clone()).unwrap());
+
+        let address: Multiaddr = "/dns4/base-node.example.com/tcp/18189".parse().unwrap();
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        assert!(db.get_base_node_identity(&address).unwrap().is_none());
+        db.set_base_node_identity(address.clone(), public_key.clone()).unwrap();
+        assert_eq!(db.get_base_node_identity(&address).unwrap(), Some(public_key));
+        let other_address: Multiaddr = "/ip4/127.0.0.1/tcp/18189".parse().unwrap();
+        assert!(db.get_base_node_identity(&other_address).unwrap().is_none());
     }
 }
@@ -0,0 +1,61 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::TryFrom;
+
+use diesel::{sql_query, RunQueryDsl, SqliteConnection};
+use tari_common_types::transaction::TxId;
+use tari_comms::types::CommsPublicKey;
+use tari_utilities::hex::Hex;
+
+use crate::{
+    error::WalletStorageError,
+    search::{to_fts_query, SearchMatch},
+};
+
+/// A row of the `search_index` FTS5 table. The table is maintained by triggers on the transaction, contact and tag
+/// tables, so it is only ever read from here. Contacts are stored with their hex encoded public key, everything else
+/// with its transaction id.
+#[derive(Clone, QueryableByName)]
+pub struct SearchIndexSql {
+    #[sql_type = "diesel::sql_types::Text"]
+    kind: String,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::BigInt>"]
+    tx_id: Option<i64>,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Text>"]
+    public_key: Option<String>,
+}
+
+impl SearchIndexSql {
+    /// Returns the entries matching every word of `query`, most relevant first
+    pub fn search(query: &str, limit: usize, conn: &SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        let fts_query = match to_fts_query(query) {
+            Some(q) => q,
+            None => return Ok(Vec::new()),
+        };
+        Ok(sql_query(
+            "SELECT kind, tx_id, public_key FROM search_index WHERE search_index MATCH ? ORDER BY rank LIMIT ?",
+        )
+        .bind::<diesel::sql_types::Text, _>(fts_query)
+        .bind::<diesel::sql_types::BigInt, _>(limit as i64)
+        .load::<SearchIndexSql>(conn)?)
+    }
+}
+
+impl TryFrom<SearchIndexSql> for SearchMatch {
+    type Error = WalletStorageError;
+
+    fn try_from(row: SearchIndexSql) -> Result<Self, Self::Error> {
+        if row.kind == "contact" {
+            let public_key = row.public_key.ok_or_else(|| {
+                WalletStorageError::ConversionError("Contact search entry without a public key".to_string())
+            })?;
+            Ok(SearchMatch::Contact(CommsPublicKey::from_hex(&public_key)?))
+        } else {
+            let tx_id = row.tx_id.ok_or_else(|| {
+                WalletStorageError::ConversionError(format!("'{}' search entry without a tx_id", row.kind))
+            })?;
+            Ok(SearchMatch::Transaction(TxId::from(tx_id as u64)))
+        }
+    }
+}
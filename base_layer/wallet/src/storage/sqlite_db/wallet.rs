@@ -36,9 +36,11 @@ use diesel::{prelude::*, SqliteConnection};
 use log::*;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::{
+    i2p::I2pIdentity,
     multiaddr::Multiaddr,
     peer_manager::{IdentitySignature, PeerFeatures},
     tor::TorIdentity,
+    types::CommsPublicKey,
 };
 use tari_key_manager::cipher_seed::CipherSeed;
 use tari_utilities::{
@@ -50,10 +52,12 @@ use tokio::time::Instant;
 
 use crate::{
     error::WalletStorageError,
+    inheritance_service::handle::InheritancePlan,
     schema::{client_key_values, wallet_settings},
+    search::SearchMatch,
     storage::{
         database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
-        sqlite_db::scanned_blocks::ScannedBlockSql,
+        sqlite_db::{scanned_blocks::ScannedBlockSql, search_index::SearchIndexSql},
         sqlite_utilities::wallet_db_connection::WalletDbConnection,
     },
     util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
@@ -208,6 +212,98 @@ impl WalletSqliteDatabase {
         }
     }
 
+    fn set_i2p_id(&self, i2p: I2pIdentity, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        let i2p_string = i2p
+            .to_json()
+            .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+        match cipher.as_ref() {
+            None => {
+                WalletSettingSql::new(DbKey::I2pId.to_string(), i2p_string).set(conn)?;
+            },
+            Some(cipher) => {
+                let bytes = bincode::serialize(&i2p).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+                let ciphertext_integral_nonce =
+                    encrypt_bytes_integral_nonce(cipher, b"wallet_setting_i2p_id".to_vec(), bytes)
+                        .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+
+                WalletSettingSql::new(DbKey::I2pId.to_string(), ciphertext_integral_nonce.to_hex()).set(conn)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn get_i2p_id(&self, conn: &SqliteConnection) -> Result<Option<DbValue>, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(key_str) = WalletSettingSql::get(DbKey::I2pId.to_string(), conn)? {
+            let id = match cipher.as_ref() {
+                None => {
+                    I2pIdentity::from_json(&key_str).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?
+                },
+                Some(cipher) => {
+                    let decrypted_key_bytes =
+                        decrypt_bytes_integral_nonce(cipher, b"wallet_setting_i2p_id".to_vec(), from_hex(&key_str)?)
+                            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?;
+
+                    bincode::deserialize(&decrypted_key_bytes)
+                        .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?
+                },
+            };
+            Ok(Some(DbValue::I2pId(id)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn set_inheritance_plan(&self, plan: InheritancePlan, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        match cipher.as_ref() {
+            None => {
+                let plan_string = plan
+                    .to_json()
+                    .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+                WalletSettingSql::new(DbKey::InheritancePlan.to_string(), plan_string).set(conn)?;
+            },
+            Some(cipher) => {
+                let bytes =
+                    bincode::serialize(&plan).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+                let ciphertext_integral_nonce =
+                    encrypt_bytes_integral_nonce(cipher, b"wallet_setting_inheritance_plan".to_vec(), bytes)
+                        .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+
+                WalletSettingSql::new(DbKey::InheritancePlan.to_string(), ciphertext_integral_nonce.to_hex())
+                    .set(conn)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn get_inheritance_plan(&self, conn: &SqliteConnection) -> Result<Option<DbValue>, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(plan_str) = WalletSettingSql::get(DbKey::InheritancePlan.to_string(), conn)? {
+            let plan = match cipher.as_ref() {
+                None => InheritancePlan::from_json(&plan_str)
+                    .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+                Some(cipher) => {
+                    let decrypted_bytes = decrypt_bytes_integral_nonce(
+                        cipher,
+                        b"wallet_setting_inheritance_plan".to_vec(),
+                        from_hex(&plan_str)?,
+                    )
+                    .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?;
+
+                    bincode::deserialize(&decrypted_bytes)
+                        .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?
+                },
+            };
+            Ok(Some(DbValue::InheritancePlan(Box::new(plan))))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn set_chain_metadata(&self, chain: ChainMetadata, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
         let bytes = bincode::serialize(&chain).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
         WalletSettingSql::new(DbKey::BaseNodeChainMetadata.to_string(), bytes.to_hex()).set(conn)?;
@@ -238,6 +334,14 @@ impl WalletSqliteDatabase {
                 kvp_text = "TorId";
                 self.set_tor_id(node_id, &(*conn))?;
             },
+            DbKeyValuePair::I2pId(i2p_id) => {
+                kvp_text = "I2pId";
+                self.set_i2p_id(i2p_id, &(*conn))?;
+            },
+            DbKeyValuePair::InheritancePlan(plan) => {
+                kvp_text = "InheritancePlan";
+                self.set_inheritance_plan(*plan, &(*conn))?;
+            },
             DbKeyValuePair::BaseNodeChainMetadata(metadata) => {
                 kvp_text = "BaseNodeChainMetadata";
                 self.set_chain_metadata(metadata, &(*conn))?;
@@ -283,6 +387,14 @@ impl WalletSqliteDatabase {
                 )
                 .set(&conn)?;
             },
+            DbKeyValuePair::Network(network) => {
+                kvp_text = "Network";
+                WalletSettingSql::new(DbKey::Network.to_string(), network.as_key_str().to_string()).set(&conn)?;
+            },
+            DbKeyValuePair::BaseNodeIdentity(address, public_key) => {
+                kvp_text = "BaseNodeIdentity";
+                WalletSettingSql::new(DbKey::BaseNodeIdentity(address).to_string(), public_key.to_hex()).set(&conn)?;
+            },
         }
         if start.elapsed().as_millis() > 0 {
             trace!(
@@ -313,13 +425,24 @@ impl WalletSqliteDatabase {
             DbKey::TorId => {
                 let _ = WalletSettingSql::clear(DbKey::TorId.to_string(), &conn)?;
             },
+            DbKey::I2pId => {
+                let _ = WalletSettingSql::clear(DbKey::I2pId.to_string(), &conn)?;
+            },
+            DbKey::InheritancePlan => {
+                let _ = WalletSettingSql::clear(DbKey::InheritancePlan.to_string(), &conn)?;
+            },
+            DbKey::BaseNodeIdentity(_) => {
+                let _ = WalletSettingSql::clear(k.to_string(), &conn)?;
+            },
             DbKey::CommsFeatures |
             DbKey::CommsAddress |
             DbKey::BaseNodeChainMetadata |
             DbKey::PassphraseHash |
             DbKey::EncryptionSalt |
+            DbKey::WrappedEncryptionKey |
             DbKey::WalletBirthday |
-            DbKey::CommsIdentitySignature => {
+            DbKey::CommsIdentitySignature |
+            DbKey::Network => {
                 return Err(WalletStorageError::OperationNotSupported);
             },
         };
@@ -359,11 +482,23 @@ impl WalletBackend for WalletSqliteDatabase {
             },
             DbKey::CommsAddress => self.get_comms_address(&conn)?.map(DbValue::CommsAddress),
             DbKey::TorId => self.get_tor_id(&conn)?,
+            DbKey::I2pId => self.get_i2p_id(&conn)?,
+            DbKey::InheritancePlan => self.get_inheritance_plan(&conn)?,
             DbKey::CommsFeatures => self.get_comms_features(&conn)?.map(DbValue::CommsFeatures),
             DbKey::BaseNodeChainMetadata => self.get_chain_metadata(&conn)?.map(DbValue::BaseNodeChainMetadata),
             DbKey::PassphraseHash => WalletSettingSql::get(key.to_string(), &conn)?.map(DbValue::PassphraseHash),
             DbKey::EncryptionSalt => WalletSettingSql::get(key.to_string(), &conn)?.map(DbValue::EncryptionSalt),
+            DbKey::WrappedEncryptionKey => {
+                WalletSettingSql::get(key.to_string(), &conn)?.map(DbValue::WrappedEncryptionKey)
+            },
             DbKey::WalletBirthday => WalletSettingSql::get(key.to_string(), &conn)?.map(DbValue::WalletBirthday),
+            DbKey::Network => WalletSettingSql::get(key.to_string(), &conn)?.map(DbValue::Network),
+            DbKey::BaseNodeIdentity(_) => match WalletSettingSql::get(key.to_string(), &conn)? {
+                Some(hex) => Some(DbValue::BaseNodeIdentity(
+                    CommsPublicKey::from_hex(&hex).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+                )),
+                None => None,
+            },
             DbKey::CommsIdentitySignature => WalletSettingSql::get(key.to_string(), &conn)?
                 .and_then(|s| from_hex(&s).ok())
                 .and_then(|bytes| IdentitySignature::from_bytes(&bytes).ok())
@@ -462,6 +597,29 @@ impl WalletBackend for WalletSqliteDatabase {
             WalletSettingSql::new(DbKey::TorId.to_string(), ciphertext_integral_nonce.to_hex()).set(&conn)?;
         }
 
+        // Encrypt i2p_id if present
+        let i2p_id = WalletSettingSql::get(DbKey::I2pId.to_string(), &conn)?;
+        if let Some(v) = i2p_id {
+            let i2p = I2pIdentity::from_json(&v).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            let bytes = bincode::serialize(&i2p).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            let ciphertext_integral_nonce =
+                encrypt_bytes_integral_nonce(&cipher, b"wallet_setting_i2p_id".to_vec(), bytes)
+                    .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+            WalletSettingSql::new(DbKey::I2pId.to_string(), ciphertext_integral_nonce.to_hex()).set(&conn)?;
+        }
+
+        // Encrypt the inheritance plan if present
+        let plan_str = WalletSettingSql::get(DbKey::InheritancePlan.to_string(), &conn)?;
+        if let Some(v) = plan_str {
+            let plan =
+                InheritancePlan::from_json(&v).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            let bytes = bincode::serialize(&plan).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            let ciphertext_integral_nonce =
+                encrypt_bytes_integral_nonce(&cipher, b"wallet_setting_inheritance_plan".to_vec(), bytes)
+                    .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+            WalletSettingSql::new(DbKey::InheritancePlan.to_string(), ciphertext_integral_nonce.to_hex()).set(&conn)?;
+        }
+
         (*current_cipher) = Some(cipher.clone());
         if start.elapsed().as_millis() > 0 {
             trace!(
@@ -529,6 +687,41 @@ impl WalletBackend for WalletSqliteDatabase {
             WalletSettingSql::new(DbKey::TorId.to_string(), tor_string).set(&conn)?;
         }
 
+        // remove i2p id encryption if present
+        let key_str = WalletSettingSql::get(DbKey::I2pId.to_string(), &conn)?;
+        if let Some(v) = key_str {
+            let decrypted_key_bytes =
+                decrypt_bytes_integral_nonce(&cipher, b"wallet_setting_i2p_id".to_vec(), from_hex(v.as_str())?)
+                    .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?;
+
+            let i2p_id: I2pIdentity = bincode::deserialize(&decrypted_key_bytes)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+
+            let i2p_string = i2p_id
+                .to_json()
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            WalletSettingSql::new(DbKey::I2pId.to_string(), i2p_string).set(&conn)?;
+        }
+
+        // remove inheritance plan encryption if present
+        let plan_str = WalletSettingSql::get(DbKey::InheritancePlan.to_string(), &conn)?;
+        if let Some(v) = plan_str {
+            let decrypted_bytes = decrypt_bytes_integral_nonce(
+                &cipher,
+                b"wallet_setting_inheritance_plan".to_vec(),
+                from_hex(v.as_str())?,
+            )
+            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?;
+
+            let plan: InheritancePlan = bincode::deserialize(&decrypted_bytes)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+
+            let plan_string = plan
+                .to_json()
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            WalletSettingSql::new(DbKey::InheritancePlan.to_string(), plan_string).set(&conn)?;
+        }
+
         // Now that all the decryption has been completed we can safely remove the cipher fully
         std::mem::drop((*current_cipher).take());
         if start.elapsed().as_millis() > 0 {
@@ -577,6 +770,26 @@ impl WalletBackend for WalletSqliteDatabase {
         let conn = self.database_connection.get_pooled_connection()?;
         ScannedBlockSql::clear_before_height(height, exclude_recovered, &conn)
     }
+
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchMatch>, WalletStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let matches = SearchIndexSql::search(query, limit, &conn)?
+            .into_iter()
+            .map(SearchMatch::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - search: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(matches)
+    }
 }
 
 /// Confirm if database is encrypted or not and if a cipher is provided confirm the cipher is correct.
@@ -808,15 +1021,23 @@ impl Encryptable<XChaCha20Poly1305> for ClientKeyValueSql {
 
 #[cfg(test)]
 mod test {
+    use diesel::{sql_query, RunQueryDsl};
+    use tari_common_types::transaction::TxId;
+    use tari_comms::{i2p::I2pIdentity, types::CommsPublicKey};
+    use tari_core::transactions::tari_amount::MicroTari;
     use tari_key_manager::cipher_seed::CipherSeed;
     use tari_test_utils::random::string;
-    use tari_utilities::{hex::Hex, SafePassword};
+    use tari_utilities::{hex::Hex, ByteArray, SafePassword};
     use tempfile::tempdir;
 
-    use crate::storage::{
-        database::{DbKey, DbValue, WalletBackend},
-        sqlite_db::wallet::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
-        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    use crate::{
+        inheritance_service::handle::{Beneficiary, InheritancePlan},
+        search::SearchMatch,
+        storage::{
+            database::{DbKey, DbValue, WalletBackend, WriteOperation},
+            sqlite_db::wallet::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
+            sqlite_utilities::run_migration_and_create_sqlite_connection,
+        },
     };
 
     #[test]
@@ -974,6 +1195,94 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_i2p_id_with_encryption() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(&format!("{}/{}", db_folder, db_name), 16).unwrap();
+
+        let db = WalletSqliteDatabase::new(connection.clone(), None).unwrap();
+        let i2p_id = I2pIdentity {
+            destination: "destination".to_string(),
+            private_key: "private_key".to_string(),
+        };
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            db.set_master_seed(&CipherSeed::new(), &conn).unwrap();
+            db.set_i2p_id(i2p_id.clone(), &conn).unwrap();
+        }
+
+        let assert_i2p_id = |db: &WalletSqliteDatabase| match db.fetch(&DbKey::I2pId).unwrap().unwrap() {
+            DbValue::I2pId(id) => {
+                assert_eq!(id.destination, i2p_id.destination);
+                assert_eq!(id.private_key, i2p_id.private_key);
+            },
+            _ => panic!("Should be an I2P identity"),
+        };
+        assert_i2p_id(&db);
+
+        db.apply_encryption("an example very very secret key.".to_string().into())
+            .unwrap();
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            let stored = WalletSettingSql::get(DbKey::I2pId.to_string(), &conn).unwrap().unwrap();
+            assert!(!stored.contains("private_key"));
+        }
+        assert_i2p_id(&db);
+
+        db.remove_encryption().unwrap();
+        assert_i2p_id(&db);
+    }
+
+    #[test]
+    fn test_inheritance_plan_with_encryption() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(&format!("{}/{}", db_folder, db_name), 16).unwrap();
+
+        let db = WalletSqliteDatabase::new(connection.clone(), None).unwrap();
+        let plan = InheritancePlan {
+            beneficiaries: vec![Beneficiary {
+                public_key: CommsPublicKey::default(),
+                share: 1,
+                label: "beneficiary_label".to_string(),
+            }],
+            inactivity_period: 1000,
+            fee_per_gram: MicroTari::from(5),
+            transaction: None,
+        };
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            db.set_master_seed(&CipherSeed::new(), &conn).unwrap();
+            db.set_inheritance_plan(plan.clone(), &conn).unwrap();
+        }
+
+        let assert_plan = |db: &WalletSqliteDatabase| match db.fetch(&DbKey::InheritancePlan).unwrap().unwrap() {
+            DbValue::InheritancePlan(p) => assert_eq!(*p, plan),
+            _ => panic!("Should be an inheritance plan"),
+        };
+        assert_plan(&db);
+
+        db.apply_encryption("an example very very secret key.".to_string().into())
+            .unwrap();
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            let stored = WalletSettingSql::get(DbKey::InheritancePlan.to_string(), &conn)
+                .unwrap()
+                .unwrap();
+            assert!(!stored.contains("beneficiary_label"));
+        }
+        assert_plan(&db);
+
+        db.remove_encryption().unwrap();
+        assert_plan(&db);
+
+        db.write(WriteOperation::Remove(DbKey::InheritancePlan)).unwrap();
+        assert!(db.fetch(&DbKey::InheritancePlan).unwrap().is_none());
+    }
+
     #[test]
     fn test_client_key_value_store() {
         let db_name = format!("{}.sqlite3", string(8).as_str());
@@ -1013,4 +1322,49 @@ mod test {
             panic!("Should find value2");
         }
     }
+
+    #[test]
+    fn test_search_index() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(&format!("{}{}", db_folder, db_name), 16).unwrap();
+        let conn = connection.get_pooled_connection().unwrap();
+        let db = WalletSqliteDatabase::new(connection, None).unwrap();
+
+        let public_key = CommsPublicKey::default();
+        sql_query("INSERT INTO contacts (public_key, node_id, alias) VALUES (?, x'01', 'Zoë the Baker')")
+            .bind::<diesel::sql_types::Binary, _>(public_key.to_vec())
+            .execute(&conn)
+            .unwrap();
+        sql_query("INSERT INTO transaction_tags (tx_id, tag) VALUES (7, 'bakery'), (8, 'rent')")
+            .execute(&conn)
+            .unwrap();
+
+        assert!(db.search("  ", 10).unwrap().is_empty());
+        assert_eq!(db.search("zoe", 10).unwrap(), vec![SearchMatch::Contact(
+            public_key.clone()
+        )]);
+        assert_eq!(db.search("rent", 10).unwrap(), vec![SearchMatch::Transaction(
+            TxId::from(8u64)
+        )]);
+        let matches = db.search("bak", 10).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert!(matches.contains(&SearchMatch::Transaction(TxId::from(7u64))));
+        assert_eq!(db.search("bak", 1).unwrap().len(), 1);
+        // Every word has to match
+        assert!(db.search("zoe rent", 10).unwrap().is_empty());
+        // The FTS5 query syntax is treated as plain words
+        assert!(db.search("\"rent OR", 10).unwrap().is_empty());
+
+        sql_query("UPDATE contacts SET alias = 'Zoë' WHERE public_key = ?")
+            .bind::<diesel::sql_types::Binary, _>(public_key.to_vec())
+            .execute(&conn)
+            .unwrap();
+        sql_query("DELETE FROM transaction_tags WHERE tx_id = 7")
+            .execute(&conn)
+            .unwrap();
+        assert!(db.search("bak", 10).unwrap().is_empty());
+        assert_eq!(db.search("zoe", 10).unwrap(), vec![SearchMatch::Contact(public_key)]);
+    }
 }
@@ -25,7 +25,10 @@ use std::{fs::File, path::Path, time::Duration};
 use fs2::FileExt;
 use log::*;
 use tari_common_sqlite::sqlite_connection_pool::SqliteConnectionPool;
-use tari_utilities::SafePassword;
+use tari_utilities::{
+    hex::{from_hex, Hex},
+    SafePassword,
+};
 pub use wallet_db_connection::WalletDbConnection;
 
 use crate::{
@@ -33,7 +36,11 @@ use crate::{
     error::WalletStorageError,
     key_manager_service::storage::sqlite_db::KeyManagerSqliteDatabase,
     output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
-    storage::{database::WalletDatabase, sqlite_db::wallet::WalletSqliteDatabase},
+    platform_keystore::{generate_keystore_key, keystore_passphrase, PlatformKeystore},
+    storage::{
+        database::{DbKey, WalletDatabase},
+        sqlite_db::wallet::{WalletSettingSql, WalletSqliteDatabase},
+    },
     transaction_service::storage::sqlite_db::TransactionServiceSqliteDatabase,
 };
 
@@ -69,6 +76,27 @@ pub fn run_migration_and_create_sqlite_connection<P: AsRef<Path>>(
     Ok(WalletDbConnection::new(pool, Some(file_lock)))
 }
 
+/// Opens connections to an existing wallet database that refuse all writes. The database is neither locked nor
+/// migrated, so it can be opened while the wallet that owns it is running, as long as that wallet has migrated it.
+pub fn create_read_only_sqlite_connection<P: AsRef<Path>>(
+    db_path: P,
+    sqlite_pool_size: usize,
+) -> Result<WalletDbConnection, WalletStorageError> {
+    if !db_path.as_ref().exists() {
+        return Err(WalletStorageError::DbPathDoesNotExist);
+    }
+    let path_str = db_path
+        .as_ref()
+        .to_str()
+        .ok_or(WalletStorageError::InvalidUnicodePath)?;
+
+    let mut pool =
+        SqliteConnectionPool::new_read_only(String::from(path_str), sqlite_pool_size, Duration::from_secs(60));
+    pool.create_pool()?;
+
+    Ok(WalletDbConnection::new(pool, None))
+}
+
 /// This function will copy a wallet database to the provided path and then clear the Master Private Key from the
 /// database.
 pub fn partial_wallet_backup<P: AsRef<Path>>(current_db: P, backup_path: P) -> Result<(), WalletStorageError> {
@@ -163,3 +191,33 @@ pub fn initialize_sqlite_database_backends<P: AsRef<Path>>(
         key_manager_backend,
     ))
 }
+
+/// Returns the passphrase of a wallet database whose encryption key is wrapped by a platform keystore. The first time,
+/// a random passphrase is generated and stored in the database wrapped by the keystore, and the caller must encrypt
+/// the wallet with it, just like with a new passphrase from the user. A wallet that is already encrypted with a user
+/// passphrase must have that encryption removed first.
+pub fn read_or_create_keystore_passphrase<P: AsRef<Path>>(
+    db_path: P,
+    keystore: &dyn PlatformKeystore,
+) -> Result<SafePassword, WalletStorageError> {
+    let connection = run_migration_and_create_sqlite_connection(db_path, 1)?;
+    let conn = connection.get_pooled_connection()?;
+
+    let key = match WalletSettingSql::get(DbKey::WrappedEncryptionKey.to_string(), &conn)? {
+        Some(wrapped_key) => keystore.unwrap_key(&from_hex(&wrapped_key)?)?,
+        None => {
+            if WalletSettingSql::get(DbKey::PassphraseHash.to_string(), &conn)?.is_some() {
+                return Err(WalletStorageError::AlreadyEncrypted);
+            }
+            let key = generate_keystore_key();
+            let wrapped_key = keystore.wrap_key(&key)?;
+            WalletSettingSql::new(DbKey::WrappedEncryptionKey.to_string(), wrapped_key.to_hex()).set(&conn)?;
+            debug!(
+                target: LOG_TARGET,
+                "Stored a new wallet encryption key wrapped by the platform keystore"
+            );
+            key
+        },
+    };
+    Ok(keystore_passphrase(&key)?)
+}
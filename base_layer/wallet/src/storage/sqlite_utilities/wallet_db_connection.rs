@@ -30,6 +30,9 @@ use tari_common_sqlite::sqlite_connection_pool::SqliteConnectionPool;
 
 use crate::error::WalletStorageError;
 
+/// The pool of connections to the wallet database. Clones share the pool, which is how all the service backends of a
+/// wallet share the same connections. Every connection keeps a cache of the statements it prepared for as long as the
+/// pool is open.
 #[derive(Clone)]
 pub struct WalletDbConnection {
     pool: SqliteConnectionPool,
@@ -0,0 +1,279 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::time::Duration;
+
+use hmac::{Hmac, Mac, NewMac};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use tari_common::configuration::serializers;
+use tari_common_types::{
+    transaction::TxId,
+    types::{PrivateKey, PublicKey, Signature},
+};
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_utilities::{hex::Hex, ByteArray};
+use thiserror::Error;
+
+use crate::types::WalletHasher;
+
+/// The number of seconds that a TOTP code is valid for
+const TOTP_STEP_SECONDS: u64 = 30;
+/// The number of steps before and after the current one for which a TOTP code is still accepted, to allow for clock
+/// drift between the wallet and the authenticator
+const TOTP_ALLOWED_DRIFT_STEPS: u64 = 1;
+
+/// Second factor approval of large outgoing transactions. Transactions of at least `threshold` are held in a pending
+/// approval queue until `approve_transaction` is called with a valid approval token.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionApprovalConfig {
+    /// Outgoing transactions of at least this value must be approved before they are sent (default = disabled)
+    pub threshold: Option<MicroTari>,
+    /// Hex encoded secret used to verify 6 digit time based one-time passwords (RFC 6238, HMAC-SHA256, 30s steps)
+    pub totp_secret: Option<String>,
+    /// A secondary key that approves transactions by signing the approval challenge of the transaction
+    pub approval_public_key: Option<CommsPublicKey>,
+    /// Transactions that have not been approved within this period are discarded
+    #[serde(with = "serializers::seconds")]
+    pub expiry: Duration,
+}
+
+impl Default for TransactionApprovalConfig {
+    fn default() -> Self {
+        Self {
+            threshold: None,
+            totp_secret: None,
+            approval_public_key: None,
+            expiry: Duration::from_secs(86_400),
+        }
+    }
+}
+
+impl TransactionApprovalConfig {
+    /// Returns true if an outgoing transaction of this value must be approved before it is sent
+    pub fn requires_approval(&self, amount: MicroTari) -> bool {
+        matches!(self.threshold, Some(threshold) if amount >= threshold)
+    }
+
+    /// Verifies an approval token for a transaction. The token is either a current TOTP code or the hex encoded
+    /// signature of the transaction's approval challenge (see `approval_challenge`) made with the secondary key.
+    ///
+    /// A TOTP code is only accepted for a step after `last_totp_step`, the step of the last code that was accepted, so
+    /// that a code cannot be used twice within the drift window. Returns the step of the code if the token is one.
+    pub fn verify_token(
+        &self,
+        tx_id: TxId,
+        approval_token: &str,
+        unix_timestamp: u64,
+        last_totp_step: Option<u64>,
+    ) -> Result<Option<u64>, TransactionApprovalError> {
+        if self.totp_secret.is_none() && self.approval_public_key.is_none() {
+            return Err(TransactionApprovalError::NoApprovalMethodConfigured);
+        }
+        if let Some(secret) = self.totp_secret.as_ref() {
+            let secret = Vec::<u8>::from_hex(secret).map_err(|_| TransactionApprovalError::InvalidTotpSecret)?;
+            let step = unix_timestamp / TOTP_STEP_SECONDS;
+            let first_step = step
+                .saturating_sub(TOTP_ALLOWED_DRIFT_STEPS)
+                .max(last_totp_step.map_or(0, |last_step| last_step + 1));
+            // Every step of the window is compared, so that the time taken does not depend on which one matches
+            let matching_step = (first_step..=step + TOTP_ALLOWED_DRIFT_STEPS)
+                .filter(|counter| {
+                    let code = format!("{:06}", totp_code(&secret, *counter));
+                    bool::from(code.as_bytes().ct_eq(approval_token.as_bytes()))
+                })
+                .last();
+            if matching_step.is_some() {
+                return Ok(matching_step);
+            }
+        }
+        if let Some(public_key) = self.approval_public_key.as_ref() {
+            if let Some(signature) = parse_signature(approval_token) {
+                let challenge = approval_challenge(tx_id, public_key, signature.get_public_nonce());
+                if signature.verify_challenge(public_key, &challenge) {
+                    return Ok(None);
+                }
+            }
+        }
+        Err(TransactionApprovalError::InvalidApprovalToken(tx_id))
+    }
+}
+
+/// The challenge that the secondary approval key signs to approve a transaction. It commits to the approval public key
+/// and the public nonce of the signature, so that a signature cannot be solved for without the secret key.
+pub fn approval_challenge(tx_id: TxId, public_key: &PublicKey, public_nonce: &PublicKey) -> Vec<u8> {
+    WalletHasher::new_with_label("transaction_approval")
+        .chain(tx_id.as_u64().to_le_bytes())
+        .chain(public_key.as_bytes())
+        .chain(public_nonce.as_bytes())
+        .finalize()
+        .as_ref()
+        .to_vec()
+}
+
+/// Creates an approval token for a transaction by signing its approval challenge with the secondary approval key
+pub fn sign_approval(secret_key: PrivateKey, tx_id: TxId) -> Result<String, TransactionApprovalError> {
+    let public_key = PublicKey::from_secret_key(&secret_key);
+    let (nonce, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+    let challenge = approval_challenge(tx_id, &public_key, &public_nonce);
+    let signature = Signature::sign(secret_key, nonce, &challenge)
+        .map_err(|e| TransactionApprovalError::SigningError(e.to_string()))?;
+    Ok(format!(
+        "{}{}",
+        signature.get_public_nonce().to_hex(),
+        signature.get_signature().to_hex()
+    ))
+}
+
+/// Parses a token made of the hex encoded public nonce followed by the hex encoded signature scalar
+fn parse_signature(approval_token: &str) -> Option<Signature> {
+    let bytes = Vec::<u8>::from_hex(approval_token).ok()?;
+    if bytes.len() != 64 {
+        return None;
+    }
+    let public_nonce = PublicKey::from_bytes(&bytes[..32]).ok()?;
+    let signature = PrivateKey::from_bytes(&bytes[32..]).ok()?;
+    Some(Signature::new(public_nonce, signature))
+}
+
+/// RFC 4226 HOTP value for the given counter, using HMAC-SHA256 as allowed by RFC 6238
+fn totp_code(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC can take a key of any size");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset], hash[offset + 1], hash[offset + 2], hash[offset + 3]]) & 0x7fff_ffff;
+    binary % 1_000_000
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TransactionApprovalError {
+    #[error("No TOTP secret or approval public key is configured to approve transactions")]
+    NoApprovalMethodConfigured,
+    #[error("The configured TOTP secret is not valid hex")]
+    InvalidTotpSecret,
+    #[error("The approval token for TxId `{0}` is not valid")]
+    InvalidApprovalToken(TxId),
+    #[error("Transaction with TxId `{0}` is not pending approval")]
+    NotPendingApproval(TxId),
+    #[error("Transaction with TxId `{0}` was not approved in time and has expired")]
+    ApprovalExpired(TxId),
+    #[error("Sweeping {0} in a single transaction requires approval, which is not supported for sweeps")]
+    SweepRequiresApproval(MicroTari),
+    #[error("Transferring a unique asset worth {0} requires approval, which is not supported for asset transfers")]
+    UniqueAssetTransferRequiresApproval(MicroTari),
+    #[error("Could not sign the approval challenge: {0}")]
+    SigningError(String),
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn it_matches_the_rfc_6238_sha256_test_vectors() {
+        // The SHA256 seed from RFC 6238 appendix B, using the last 6 of the 8 digit reference values
+        let secret = b"12345678901234567890123456789012";
+        let vectors = [
+            (59, 46_119_246),
+            (1_111_111_109, 68_084_774),
+            (1_111_111_111, 67_062_674),
+            (1_234_567_890, 91_819_424),
+            (2_000_000_000, 90_698_825),
+            (20_000_000_000, 77_737_706),
+        ];
+        for (unix_timestamp, code) in vectors {
+            assert_eq!(totp_code(secret, unix_timestamp / TOTP_STEP_SECONDS), code % 1_000_000);
+        }
+    }
+
+    #[test]
+    fn it_verifies_totp_tokens() {
+        let config = TransactionApprovalConfig {
+            threshold: Some(MicroTari::from(1_000)),
+            totp_secret: Some(b"12345678901234567890123456789012".to_vec().to_hex()),
+            ..Default::default()
+        };
+        let tx_id = TxId::new_random();
+        assert_eq!(config.verify_token(tx_id, "119246", 59, None), Ok(Some(1)));
+        // The previous step is still accepted, but not older ones
+        assert_eq!(
+            config.verify_token(tx_id, "119246", 59 + TOTP_STEP_SECONDS, None),
+            Ok(Some(1))
+        );
+        assert_eq!(
+            config.verify_token(tx_id, "119246", 59 + 2 * TOTP_STEP_SECONDS, None),
+            Err(TransactionApprovalError::InvalidApprovalToken(tx_id))
+        );
+        assert!(config.verify_token(tx_id, "000000", 59, None).is_err());
+        assert!(config.verify_token(tx_id, "1192461", 59, None).is_err());
+    }
+
+    #[test]
+    fn it_rejects_a_totp_code_that_was_already_used() {
+        let config = TransactionApprovalConfig {
+            threshold: Some(MicroTari::from(1_000)),
+            totp_secret: Some(b"12345678901234567890123456789012".to_vec().to_hex()),
+            ..Default::default()
+        };
+        let tx_id = TxId::new_random();
+        let last_step = config.verify_token(tx_id, "119246", 59, None).unwrap();
+        assert_eq!(
+            config.verify_token(tx_id, "119246", 59 + TOTP_STEP_SECONDS, last_step),
+            Err(TransactionApprovalError::InvalidApprovalToken(tx_id))
+        );
+        // The code of the next step is still accepted within the drift window
+        let code = format!("{:06}", totp_code(b"12345678901234567890123456789012", 2));
+        assert_eq!(config.verify_token(tx_id, &code, 59, last_step), Ok(Some(2)));
+    }
+
+    #[test]
+    fn it_verifies_signed_tokens() {
+        let (secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let config = TransactionApprovalConfig {
+            threshold: Some(MicroTari::from(1_000)),
+            approval_public_key: Some(public_key),
+            ..Default::default()
+        };
+        let tx_id = TxId::new_random();
+        let token = sign_approval(secret_key.clone(), tx_id).unwrap();
+        assert_eq!(config.verify_token(tx_id, &token, 0, None), Ok(None));
+        // A token is only valid for the transaction it was made for
+        let other_tx_id = TxId::new_random();
+        assert!(config.verify_token(other_tx_id, &token, 0, None).is_err());
+        assert_eq!(
+            TransactionApprovalConfig::default().verify_token(tx_id, &token, 0, None),
+            Err(TransactionApprovalError::NoApprovalMethodConfigured)
+        );
+        assert!(config.requires_approval(MicroTari::from(1_000)));
+        assert!(!config.requires_approval(MicroTari::from(999)));
+    }
+    #[test]
+    fn it_rejects_signed_tokens_with_a_made_up_nonce() {
+        let public_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let config = TransactionApprovalConfig {
+            threshold: Some(MicroTari::from(1_000)),
+            approval_public_key: Some(public_key.clone()),
+            ..Default::default()
+        };
+        let tx_id = TxId::new_random();
+        // Without the secret key, anyone can pick `s` and solve `s⋅G = R + e⋅P` for `R`, but `R` then differs from the
+        // nonce that went into `e`
+        let made_up_nonce = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let s = PrivateKey::random(&mut OsRng);
+        let e = PrivateKey::from_bytes(&approval_challenge(tx_id, &public_key, &made_up_nonce)).unwrap();
+        let public_nonce = PublicKey::from_secret_key(&s) - &e * &public_key;
+        let token = format!("{}{}", public_nonce.to_hex(), s.to_hex());
+        assert_eq!(
+            config.verify_token(tx_id, &token, 0, None),
+            Err(TransactionApprovalError::InvalidApprovalToken(tx_id))
+        );
+    }
+}
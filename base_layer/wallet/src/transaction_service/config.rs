@@ -20,11 +20,17 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fmt, time::Duration};
+use std::{fmt, path::PathBuf, time::Duration};
 
 use log::*;
 use serde::{Deserialize, Serialize};
 use tari_common::configuration::serializers;
+use tari_core::transactions::tari_amount::MicroTari;
+
+use crate::{
+    transaction_service::{approval::TransactionApprovalConfig, spending_policy::SpendingPolicy},
+    util::retry::{Backoff, RetryPolicy},
+};
 
 const LOG_TARGET: &str = "wallet::transaction_service::config";
 
@@ -55,21 +61,60 @@ pub struct TransactionServiceConfig {
     /// This is the timeout period that will be used to expire pending transactions
     #[serde(with = "serializers::seconds")]
     pub pending_transaction_cancellation_timeout: Duration,
+    /// If the finalized transaction for a pending inbound transaction has not arrived this long after our reply was
+    /// sent, the sender is asked to send it again. This should be longer than the sender's `resend_response_cooldown`.
+    #[serde(with = "serializers::seconds")]
+    pub finalization_request_timeout: Duration,
     /// This is the number of block confirmations required for a transaction to be considered completely mined and
-    /// confirmed
+    /// confirmed, if its amount is not below any of the tiers in `confirmation_schedule`
     pub num_confirmations_required: u64,
+    /// The number of confirmations required for transactions below a given amount, so that smaller transactions can
+    /// be confirmed sooner than larger ones
+    #[serde(default)]
+    pub confirmation_schedule: Vec<ConfirmationTier>,
     /// The number of batches the unconfirmed transactions will be divided into before being queried from the base node
     // TODO: Fix this logic; it should more directly determine the msg size not the number of batches
     pub max_tx_query_batch_size: usize,
     /// This option specifies the transaction routing mechanism as being directly between wallets, making use of store
     /// and forward or using any combination of these.
     pub transaction_routing_mechanism: TransactionRoutingMechanism,
+    /// If true, store-and-forward messages are only sent to the closest peers the wallet is already connected to, e.g.
+    /// its base node, instead of connecting to the neighbours of the recipient
+    pub store_and_forward_connected_peers_only: bool,
     /// This is the size of the event channel used to communicate transaction status events to the wallet's UI. A busy
     /// console wallet doing thousands of bulk payments or used for stress testing needs a fairly big size.
     pub transaction_event_channel_size: usize,
     /// This is the timeout period that will be used to re-submit transactions not found in the mempool
     #[serde(with = "serializers::seconds")]
     pub transaction_mempool_resubmission_window: Duration,
+    /// A broadcast transaction that is no longer in the mempool of the base node is rebroadcast after this period. The
+    /// period doubles every time the same transaction is evicted again.
+    #[serde(with = "serializers::seconds")]
+    pub mempool_eviction_backoff: Duration,
+    /// The longest period that an evicted transaction waits before it is rebroadcast
+    #[serde(with = "serializers::seconds")]
+    pub max_mempool_eviction_backoff: Duration,
+    /// The limits that are enforced on outgoing transactions
+    pub spending_policy: SpendingPolicy,
+    /// The second factor approval required for large outgoing transactions
+    pub approval: TransactionApprovalConfig,
+    /// Record every transaction negotiation message that is sent and received to this file, encrypted with a key
+    /// derived from the wallet's secret key, so that failed negotiations can be replayed offline. Not recorded if not
+    /// set.
+    pub protocol_recording_file: Option<PathBuf>,
+    /// The number of blocks for which the recipient of an expiring vault payment can claim it. Once it expires, the
+    /// payment is refunded to the sender.
+    pub expiring_vault_lifetime: u64,
+    /// How a broadcast protocol retries when the base node cannot be reached. The transaction is broadcast again when
+    /// the next validation completes if the protocol gives up.
+    pub broadcast_retry: RetryPolicy,
+    /// How a transaction validation that failed because the base node could not be reached is retried
+    pub validation_retry: RetryPolicy,
+    /// How a transaction message that could not be sent to the neighbours of the recipient for Store-and-forward is
+    /// resent
+    pub saf_resend_retry: RetryPolicy,
+    /// The largest file, in bytes, that can be attached to a transaction. Attachments are kept in the wallet database.
+    pub max_attachment_size: usize,
 }
 
 impl Default for TransactionServiceConfig {
@@ -83,15 +128,69 @@ impl Default for TransactionServiceConfig {
             transaction_resend_period: Duration::from_secs(600),
             resend_response_cooldown: Duration::from_secs(300),
             pending_transaction_cancellation_timeout: Duration::from_secs(259_200), // 3 Days
+            finalization_request_timeout: Duration::from_secs(360),
             num_confirmations_required: 3,
+            confirmation_schedule: vec![],
             max_tx_query_batch_size: 20,
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
+            store_and_forward_connected_peers_only: false,
             transaction_event_channel_size: 1000,
             transaction_mempool_resubmission_window: Duration::from_secs(600),
+            mempool_eviction_backoff: Duration::from_secs(60),
+            max_mempool_eviction_backoff: Duration::from_secs(3600),
+            spending_policy: SpendingPolicy::default(),
+            approval: TransactionApprovalConfig::default(),
+            protocol_recording_file: None,
+            expiring_vault_lifetime: 3 * 24 * 30, // 3 days
+            broadcast_retry: RetryPolicy::new(
+                None,
+                Duration::from_secs(5),
+                Backoff::Exponential,
+                Duration::from_secs(300),
+            )
+            .with_jitter(0.2),
+            validation_retry: RetryPolicy::new(
+                Some(3),
+                Duration::from_secs(10),
+                Backoff::Exponential,
+                Duration::from_secs(120),
+            )
+            .with_jitter(0.2),
+            // Pending transactions are resent after `transaction_resend_period` anyway
+            saf_resend_retry: RetryPolicy::new(
+                Some(1),
+                Duration::from_secs(5),
+                Backoff::Constant,
+                Duration::from_secs(5),
+            ),
+            max_attachment_size: 1024 * 1024,
         }
     }
 }
 
+impl TransactionServiceConfig {
+    /// The number of confirmations required for a transaction of this amount to be considered confirmed. This is the
+    /// lowest tier of the confirmation schedule that the amount is below, or `num_confirmations_required` otherwise.
+    pub fn confirmations_required(&self, amount: MicroTari) -> u64 {
+        self.confirmation_schedule
+            .iter()
+            .filter(|tier| amount < tier.below)
+            .min_by_key(|tier| tier.below)
+            .map(|tier| tier.confirmations)
+            .unwrap_or(self.num_confirmations_required)
+    }
+}
+
+/// A tier of the confirmation schedule
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfirmationTier {
+    /// Transactions of less than this amount use this tier, unless a lower tier also applies
+    pub below: MicroTari,
+    /// The number of confirmations required for transactions in this tier
+    pub confirmations: u64,
+}
+
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum TransactionRoutingMechanism {
     DirectOnly,
@@ -132,3 +231,34 @@ impl Default for TransactionRoutingMechanism {
         Self::DirectAndStoreAndForward
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_applies_the_confirmation_schedule() {
+        let config = TransactionServiceConfig {
+            num_confirmations_required: 10,
+            confirmation_schedule: vec![
+                ConfirmationTier {
+                    below: MicroTari::from(1_000_000_000),
+                    confirmations: 3,
+                },
+                ConfirmationTier {
+                    below: MicroTari::from(10_000_000),
+                    confirmations: 1,
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(config.confirmations_required(MicroTari::from(5_000_000)), 1);
+        assert_eq!(config.confirmations_required(MicroTari::from(10_000_000)), 3);
+        assert_eq!(config.confirmations_required(MicroTari::from(999_999_999)), 3);
+        assert_eq!(config.confirmations_required(MicroTari::from(1_000_000_000)), 10);
+        assert_eq!(
+            TransactionServiceConfig::default().confirmations_required(MicroTari::from(1)),
+            3
+        );
+    }
+}
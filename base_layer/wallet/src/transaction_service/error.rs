@@ -25,11 +25,12 @@ use futures::channel::oneshot::Canceled;
 use serde_json::Error as SerdeJsonError;
 use tari_common_types::{
     transaction::{TransactionConversionError, TransactionDirectionError, TxId},
-    types::FixedHashSizeError,
+    types::{FixedHashSizeError, PublicKey},
 };
 use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
+    tari_amount::MicroTari,
     transaction_components::{EncryptionError, TransactionError},
     transaction_protocol::TransactionProtocolError,
 };
@@ -39,10 +40,14 @@ use tari_utilities::ByteArrayError;
 use thiserror::Error;
 use tokio::sync::broadcast::error::RecvError;
 
+#[cfg(feature = "header_sync")]
+use crate::header_sync::error::HeaderSyncError;
 use crate::{
-    error::WalletStorageError,
+    error::{diesel_error_class, ClassifyError, ErrorClass, ErrorKind, SuggestedAction, WalletStorageError},
     output_manager_service::error::OutputManagerError,
     transaction_service::{
+        approval::TransactionApprovalError,
+        spending_policy::SpendingPolicyViolation,
         storage::{database::DbKey, sqlite_db::CompletedTransactionConversionError},
         utc::NegativeDurationError,
     },
@@ -136,6 +141,8 @@ pub enum TransactionServiceError {
     LivenessError(#[from] LivenessError),
     #[error("Pending Transaction Timed out")]
     Timeout,
+    #[error("The transaction expired before it was accepted")]
+    TransactionExpired,
     #[error("Shutdown Signal Received")]
     Shutdown,
     #[error("Transaction detected as rejected by mempool due to containing time-locked input")]
@@ -146,6 +153,10 @@ pub enum TransactionServiceError {
     MempoolRejectionDoubleSpend,
     #[error("Transaction detected as rejected by mempool due to invalid transaction")]
     MempoolRejectionInvalidTransaction,
+    #[error("Transaction detected as rejected by mempool due to a fee below the minimum relay fee")]
+    MempoolRejectionFeeTooLow,
+    #[error("The fee per gram of {fee_per_gram} is below the minimum of {floor} that the base node accepts")]
+    FeeBelowFloor { fee_per_gram: MicroTari, floor: MicroTari },
     #[error("Transaction is malformed")]
     InvalidTransaction,
     #[error("RpcError: `{0}`")]
@@ -171,6 +182,28 @@ pub enum TransactionServiceError {
     EncryptionError(#[from] EncryptionError),
     #[error("FixedHash size error: `{0}`")]
     FixedHashSizeError(#[from] FixedHashSizeError),
+    #[error("Transaction approval error: {0}")]
+    TransactionApprovalError(#[from] TransactionApprovalError),
+    #[error("Spending policy violation: {0}")]
+    SpendingPolicyViolation(#[from] SpendingPolicyViolation),
+    #[error("Idempotency key `{0}` was already used for a transaction with a different recipient or amount")]
+    IdempotencyKeyConflict(String),
+    #[error("Invalid out-of-band transaction message: {0}")]
+    InvalidOutOfBandMessage(String),
+    #[error("Invalid transaction tag: {0}")]
+    InvalidTransactionTag(String),
+    #[error("Invalid transaction attachment: {0}")]
+    InvalidTransactionAttachment(String),
+    #[error("Protocol recording error: {0}")]
+    ProtocolRecordingError(String),
+    #[error("Aggregated sender session `{0}` not found")]
+    AggregatedSenderSessionNotFound(PublicKey),
+    #[cfg(feature = "header_sync")]
+    #[error("Header sync error: {0}")]
+    HeaderSyncError(#[from] HeaderSyncError),
+    #[cfg(feature = "header_sync")]
+    #[error("Cannot generate spend proof: {0}")]
+    SpendProofError(String),
 }
 
 #[derive(Debug, Error)]
@@ -183,6 +216,8 @@ pub enum TransactionKeyError {
     SignatureNonce(ByteArrayError),
     #[error("Invalid transaction signature key")]
     SignatureKey(ByteArrayError),
+    #[error("Invalid counterparty PublicKey")]
+    Counterparty(ByteArrayError),
 }
 
 #[derive(Debug, Error)]
@@ -233,6 +268,102 @@ pub enum TransactionStorageError {
     NotCoinbase,
 }
 
+impl ClassifyError for TransactionServiceError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            TransactionServiceError::OutputManagerError(e) => e.class(),
+            TransactionServiceError::TransactionStorageError(e) => e.class(),
+            TransactionServiceError::WalletStorageError(e) => e.class(),
+            TransactionServiceError::TransactionDoesNotExistError |
+            TransactionServiceError::AggregatedSenderSessionNotFound(_) => ErrorClass::NOT_FOUND,
+            TransactionServiceError::InvalidStateError |
+            TransactionServiceError::RepeatedMessageError |
+            TransactionServiceError::IdempotencyKeyConflict(_) => ErrorClass::CONFLICT,
+            TransactionServiceError::OneSidedTransactionError(_) |
+            TransactionServiceError::InvalidMessageTypeError |
+            TransactionServiceError::InvalidSourcePublicKey |
+            TransactionServiceError::ReceiverOutputNotFound |
+            TransactionServiceError::InvalidCompletedTransaction |
+            TransactionServiceError::AttemptedToBroadcastCoinbaseTransaction(_) |
+            TransactionServiceError::InvalidMessageError(_) |
+            TransactionServiceError::InvalidTransaction |
+            TransactionServiceError::ConversionError(_) |
+            TransactionServiceError::DurationOutOfRange(_) |
+            TransactionServiceError::ProtobufConversionError(_) |
+            TransactionServiceError::ByteArrayError(_) |
+            TransactionServiceError::FixedHashSizeError(_) |
+            TransactionServiceError::InvalidOutOfBandMessage(_) |
+            TransactionServiceError::InvalidTransactionTag(_) |
+            TransactionServiceError::InvalidTransactionAttachment(_) => ErrorClass::INVALID_ARGUMENT,
+            // The transaction was stored and is sent once the recipient is discovered, so retrying would send it twice
+            TransactionServiceError::OutboundSendDiscoveryInProgress(_) => {
+                ErrorClass::new(ErrorKind::Connectivity, SuggestedAction::AwaitEvents)
+            },
+            TransactionServiceError::NoBaseNodeKeysProvided => {
+                ErrorClass::new(ErrorKind::Connectivity, SuggestedAction::FixRequest)
+            },
+            TransactionServiceError::DiscoveryProcessFailed(_) |
+            TransactionServiceError::OutboundSendFailure |
+            TransactionServiceError::DhtOutboundError(_) |
+            TransactionServiceError::LivenessError(_) |
+            TransactionServiceError::RpcError(_) |
+            TransactionServiceError::ConnectivityError { .. } |
+            TransactionServiceError::BaseNodeNotSynced |
+            TransactionServiceError::MaximumAttemptsExceeded |
+            TransactionServiceError::Timeout => ErrorClass::CONNECTIVITY,
+            TransactionServiceError::MempoolRejectionTimeLocked | TransactionServiceError::MempoolRejectionOrphan => {
+                ErrorClass::new(ErrorKind::Rejected, SuggestedAction::Retry)
+            },
+            TransactionServiceError::MempoolRejection |
+            TransactionServiceError::MempoolRejectionDoubleSpend |
+            TransactionServiceError::MempoolRejectionInvalidTransaction |
+            TransactionServiceError::MempoolRejectionFeeTooLow |
+            TransactionServiceError::FeeBelowFloor { .. } |
+            TransactionServiceError::TransactionCancelled |
+            TransactionServiceError::TransactionExpired |
+            TransactionServiceError::ChainTipHigherThanCoinbaseHeight |
+            TransactionServiceError::TransactionApprovalError(_) |
+            TransactionServiceError::SpendingPolicyViolation(_) => {
+                ErrorClass::new(ErrorKind::Rejected, SuggestedAction::FixRequest)
+            },
+            TransactionServiceError::TransportChannelError(_) |
+            TransactionServiceError::ApiSendFailed |
+            TransactionServiceError::ApiReceiveFailed |
+            TransactionServiceError::ProtocolChannelError |
+            TransactionServiceError::EventStreamError |
+            TransactionServiceError::BroadcastRecvError(_) |
+            TransactionServiceError::OneshotCancelled(_) |
+            TransactionServiceError::WalletRecoveryInProgress => ErrorClass::UNAVAILABLE,
+            TransactionServiceError::Shutdown => ErrorClass::SHUTDOWN,
+            _ => ErrorClass::INTERNAL,
+        }
+    }
+}
+
+impl ClassifyError for TransactionStorageError {
+    fn class(&self) -> ErrorClass {
+        match self {
+            TransactionStorageError::DieselR2d2Error(e) => e.class(),
+            TransactionStorageError::DieselError(e) => diesel_error_class(e),
+            TransactionStorageError::BlockingTaskSpawnError(_) => {
+                ErrorClass::new(ErrorKind::Storage, SuggestedAction::Retry)
+            },
+            TransactionStorageError::ValueNotFound(_) | TransactionStorageError::ValuesNotFound => {
+                ErrorClass::NOT_FOUND
+            },
+            TransactionStorageError::TransactionNotMined(_) => {
+                ErrorClass::new(ErrorKind::NotFound, SuggestedAction::Retry)
+            },
+            TransactionStorageError::DuplicateOutput |
+            TransactionStorageError::TransactionAlreadyExists |
+            TransactionStorageError::AlreadyEncrypted => ErrorClass::CONFLICT,
+            TransactionStorageError::NotCoinbase => ErrorClass::INVALID_ARGUMENT,
+            TransactionStorageError::AeadError(_) => ErrorClass::UNAUTHORIZED,
+            _ => ErrorClass::STORAGE,
+        }
+    }
+}
+
 /// This error type is used to return TransactionServiceErrors from inside a Transaction Service protocol but also
 /// include the ID of the protocol
 #[derive(Debug)]
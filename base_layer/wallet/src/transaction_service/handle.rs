@@ -24,22 +24,30 @@ use std::{
     collections::HashMap,
     fmt,
     fmt::{Display, Formatter},
+    path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
 
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::NaiveDateTime;
+#[cfg(feature = "header_sync")]
+use tari_common_types::types::Commitment;
 use tari_common_types::{
-    transaction::{ImportStatus, TxId},
-    types::PublicKey,
+    transaction::{ImportStatus, TransactionStatus, TxId},
+    types::{FixedHash, PublicKey},
 };
 use tari_comms::types::CommsPublicKey;
+#[cfg(feature = "header_sync")]
+use tari_core::blocks::SpendProof;
 use tari_core::{
     mempool::FeePerGramStat,
     proto,
     transactions::{
+        fee::Fee,
         tari_amount::MicroTari,
         transaction_components::{OutputFeatures, Transaction, TransactionOutput},
+        transaction_protocol::aggregated_sender::{NonceCommitment, NonceReveal, PartialMetadataSignature},
     },
 };
 use tari_service_framework::reply_channel::SenderService;
@@ -49,15 +57,26 @@ use tower::Service;
 
 use crate::{
     transaction_service::{
+        config::TransactionServiceConfig,
         error::TransactionServiceError,
+        recorder::ReplaySummary,
+        spending_policy::SpendingPolicyViolation,
         storage::models::{
             CompletedTransaction,
+            CounterpartyStats,
             InboundTransaction,
             OutboundTransaction,
+            PendingApprovalTransaction,
+            SpendingSummary,
+            TransactionAttachment,
             TxCancellationReason,
             WalletTransaction,
         },
     },
+    util::{
+        cancellation::CancellationToken,
+        scheduler::{PrioritizedRequest, RequestPriority, RequestScheduler, ScheduledService},
+    },
     OperationId,
 };
 
@@ -79,6 +98,9 @@ pub enum TransactionServiceRequest {
         output_features: Box<OutputFeatures>,
         fee_per_gram: MicroTari,
         message: String,
+        idempotency_key: Option<String>,
+        ttl: Option<Duration>,
+        cancellation: CancellationToken,
     },
     BurnTari {
         amount: MicroTari,
@@ -91,6 +113,8 @@ pub enum TransactionServiceRequest {
         output_features: Box<OutputFeatures>,
         fee_per_gram: MicroTari,
         message: String,
+        /// An encrypted message for the recipient, recovered when it scans the output
+        memo: Option<String>,
     },
     SendOneSidedToStealthAddressTransaction {
         dest_pubkey: CommsPublicKey,
@@ -98,9 +122,42 @@ pub enum TransactionServiceRequest {
         output_features: Box<OutputFeatures>,
         fee_per_gram: MicroTari,
         message: String,
+        memo: Option<String>,
+    },
+    SweepAll {
+        dest_pubkey: CommsPublicKey,
+        fee_per_gram: MicroTari,
+    },
+    TransferUniqueAsset {
+        dest_pubkey: CommsPublicKey,
+        asset_id: FixedHash,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
+    SendDecoyTransaction {
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+    },
+    RotateKeys {
+        fee_per_gram: MicroTari,
     },
     SendShaAtomicSwapTransaction(CommsPublicKey, MicroTari, MicroTari, String),
+    SendExpiringVaultTransaction {
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    },
     CancelTransaction(TxId),
+    RequestResend(TxId),
+    ExportTransactionMessage(TxId),
+    ImportTransactionMessage(Vec<u8>),
+    ReplayProtocolRecording(PathBuf),
+    GetPendingApprovalTransactions,
+    ApproveTransaction {
+        tx_id: TxId,
+        approval_token: String,
+    },
     ImportUtxoWithStatus {
         amount: MicroTari,
         source_public_key: CommsPublicKey,
@@ -116,17 +173,94 @@ pub enum TransactionServiceRequest {
     SetNormalPowerMode,
     ApplyEncryption(Box<XChaCha20Poly1305>),
     RemoveEncryption,
-    GenerateCoinbaseTransaction(MicroTari, MicroTari, u64),
+    GenerateCoinbaseTransaction(MicroTari, MicroTari, u64, Vec<u8>),
     RestartTransactionProtocols,
     RestartBroadcastProtocols,
     GetNumConfirmationsRequired,
     SetNumConfirmationsRequired(u64),
+    SetConfig(Box<TransactionServiceConfig>),
     ValidateTransactions,
     ReValidateTransactions,
     /// Returns the fee per gram estimates for the next {count} blocks.
     GetFeePerGramStatsPerBlock {
         count: usize,
     },
+    GetCounterpartyStats(CommsPublicKey),
+    /// Returns the transactions of the last {period} grouped by counterparty.
+    GetSpendingSummary(Duration),
+    TagTransaction {
+        tx_id: TxId,
+        tag: String,
+    },
+    UntagTransaction {
+        tx_id: TxId,
+        tag: String,
+    },
+    GetTransactionTags(TxId),
+    GetAllTransactionTags,
+    GetTransactionsByTag(String),
+    AddTransactionAttachment(Box<TransactionAttachment>),
+    GetTransactionAttachments(TxId),
+    RemoveTransactionAttachment {
+        tx_id: TxId,
+        name: String,
+    },
+    /// Stores transactions exported from another wallet database. Transactions that already exist are skipped.
+    ImportTransactions {
+        completed: Vec<CompletedTransaction>,
+        inbound: Vec<InboundTransaction>,
+        outbound: Vec<OutboundTransaction>,
+    },
+    /// Proves that the output with the given commitment was spent by a mined transaction of the wallet
+    #[cfg(feature = "header_sync")]
+    GenerateSpendProof(Commitment),
+    /// Starts a signing session as a party to an aggregated sender key, for a one-sided payment to the recipient
+    StartAggregatedSenderSession(CommsPublicKey),
+    AddAggregatedSenderPartyKeys {
+        session: PublicKey,
+        party_keys: Vec<PublicKey>,
+    },
+    AddAggregatedSenderNonceCommitments {
+        session: PublicKey,
+        commitments: Vec<NonceCommitment>,
+    },
+    SignAggregatedSenderOutput {
+        session: PublicKey,
+        reveals: Vec<NonceReveal>,
+        output: Box<TransactionOutput>,
+    },
+}
+
+impl TransactionServiceRequest {
+    /// The fee per gram of a request that sends a transaction to the network
+    pub(crate) fn fee_per_gram(&self) -> Option<MicroTari> {
+        match self {
+            Self::SendTransaction { fee_per_gram, .. } |
+            Self::BurnTari { fee_per_gram, .. } |
+            Self::SendOneSidedTransaction { fee_per_gram, .. } |
+            Self::SendOneSidedToStealthAddressTransaction { fee_per_gram, .. } |
+            Self::SweepAll { fee_per_gram, .. } |
+            Self::TransferUniqueAsset { fee_per_gram, .. } |
+            Self::SendDecoyTransaction { fee_per_gram, .. } |
+            Self::RotateKeys { fee_per_gram } |
+            Self::SendShaAtomicSwapTransaction(_, _, fee_per_gram, _) |
+            Self::SendExpiringVaultTransaction { fee_per_gram, .. } => Some(*fee_per_gram),
+            _ => None,
+        }
+    }
+}
+
+impl PrioritizedRequest for TransactionServiceRequest {
+    fn priority(&self) -> RequestPriority {
+        match self {
+            Self::ImportUtxoWithStatus { .. } |
+            Self::RestartTransactionProtocols |
+            Self::RestartBroadcastProtocols |
+            Self::ValidateTransactions |
+            Self::ReValidateTransactions => RequestPriority::Background,
+            _ => RequestPriority::Interactive,
+        }
+    }
 }
 
 impl fmt::Display for TransactionServiceRequest {
@@ -173,10 +307,48 @@ impl fmt::Display for TransactionServiceRequest {
                 amount,
                 message
             )),
+            Self::SweepAll {
+                dest_pubkey,
+                fee_per_gram,
+            } => f.write_str(&format!("SweepAll (to {}, {})", dest_pubkey.to_hex(), fee_per_gram)),
+            Self::TransferUniqueAsset {
+                dest_pubkey,
+                asset_id,
+                message,
+                ..
+            } => f.write_str(&format!(
+                "TransferUniqueAsset (to {}, {}, {})",
+                dest_pubkey.to_hex(),
+                asset_id,
+                message
+            )),
+            Self::RotateKeys { fee_per_gram } => write!(f, "RotateKeys ({})", fee_per_gram),
+            Self::SendDecoyTransaction { amount, fee_per_gram } => {
+                f.write_str(&format!("SendDecoyTransaction ({}, {})", amount, fee_per_gram))
+            },
             Self::SendShaAtomicSwapTransaction(k, v, _, msg) => {
                 f.write_str(&format!("SendShaAtomicSwapTransaction (to {}, {}, {})", k, v, msg))
             },
+            Self::SendExpiringVaultTransaction {
+                dest_pubkey,
+                amount,
+                message,
+                ..
+            } => f.write_str(&format!(
+                "SendExpiringVaultTransaction (to {}, {}, {})",
+                dest_pubkey.to_hex(),
+                amount,
+                message
+            )),
             Self::CancelTransaction(t) => f.write_str(&format!("CancelTransaction ({})", t)),
+            Self::RequestResend(t) => f.write_str(&format!("RequestResend ({})", t)),
+            Self::ExportTransactionMessage(t) => f.write_str(&format!("ExportTransactionMessage ({})", t)),
+            Self::ImportTransactionMessage(_) => f.write_str("ImportTransactionMessage"),
+            Self::ReplayProtocolRecording(path) => {
+                f.write_str(&format!("ReplayProtocolRecording ({})", path.display()))
+            },
+            Self::GetPendingApprovalTransactions => f.write_str("GetPendingApprovalTransactions"),
+            Self::ApproveTransaction { tx_id, .. } => f.write_str(&format!("ApproveTransaction ({})", tx_id)),
             Self::ImportUtxoWithStatus {
                 amount,
                 source_public_key,
@@ -202,19 +374,66 @@ impl fmt::Display for TransactionServiceRequest {
             Self::SetNormalPowerMode => f.write_str("SetNormalPowerMode"),
             Self::ApplyEncryption(_) => f.write_str("ApplyEncryption"),
             Self::RemoveEncryption => f.write_str("RemoveEncryption"),
-            Self::GenerateCoinbaseTransaction(_, _, bh) => {
+            Self::GenerateCoinbaseTransaction(_, _, bh, _) => {
                 f.write_str(&format!("GenerateCoinbaseTransaction (Blockheight {})", bh))
             },
             Self::RestartTransactionProtocols => f.write_str("RestartTransactionProtocols"),
             Self::RestartBroadcastProtocols => f.write_str("RestartBroadcastProtocols"),
             Self::GetNumConfirmationsRequired => f.write_str("GetNumConfirmationsRequired"),
             Self::SetNumConfirmationsRequired(_) => f.write_str("SetNumConfirmationsRequired"),
+            Self::SetConfig(_) => f.write_str("SetConfig"),
             Self::GetAnyTransaction(t) => f.write_str(&format!("GetAnyTransaction({})", t)),
             Self::ValidateTransactions => f.write_str("ValidateTransactions"),
             Self::ReValidateTransactions => f.write_str("ReValidateTransactions"),
             Self::GetFeePerGramStatsPerBlock { count } => {
                 write!(f, "GetFeePerGramEstimatesPerBlock(count: {})", count,)
             },
+            Self::GetCounterpartyStats(pk) => write!(f, "GetCounterpartyStats({})", pk.to_hex()),
+            Self::GetSpendingSummary(period) => write!(f, "GetSpendingSummary({:.0?})", period),
+            Self::TagTransaction { tx_id, tag } => write!(f, "TagTransaction ({}, {})", tx_id, tag),
+            Self::UntagTransaction { tx_id, tag } => write!(f, "UntagTransaction ({}, {})", tx_id, tag),
+            Self::GetTransactionTags(tx_id) => write!(f, "GetTransactionTags ({})", tx_id),
+            Self::GetAllTransactionTags => f.write_str("GetAllTransactionTags"),
+            Self::GetTransactionsByTag(tag) => write!(f, "GetTransactionsByTag ({})", tag),
+            Self::AddTransactionAttachment(attachment) => write!(
+                f,
+                "AddTransactionAttachment ({}, {}, {} bytes)",
+                attachment.tx_id,
+                attachment.name,
+                attachment.data.len()
+            ),
+            Self::GetTransactionAttachments(tx_id) => write!(f, "GetTransactionAttachments ({})", tx_id),
+            Self::RemoveTransactionAttachment { tx_id, name } => {
+                write!(f, "RemoveTransactionAttachment ({}, {})", tx_id, name)
+            },
+            Self::ImportTransactions {
+                completed,
+                inbound,
+                outbound,
+            } => write!(
+                f,
+                "ImportTransactions ({} completed, {} inbound, {} outbound)",
+                completed.len(),
+                inbound.len(),
+                outbound.len()
+            ),
+            #[cfg(feature = "header_sync")]
+            Self::GenerateSpendProof(commitment) => write!(f, "GenerateSpendProof ({})", commitment.to_hex()),
+            Self::StartAggregatedSenderSession(recipient) => {
+                write!(f, "StartAggregatedSenderSession ({})", recipient.to_hex())
+            },
+            Self::AddAggregatedSenderPartyKeys { session, party_keys } => write!(
+                f,
+                "AddAggregatedSenderPartyKeys ({}, {} parties)",
+                session.to_hex(),
+                party_keys.len()
+            ),
+            Self::AddAggregatedSenderNonceCommitments { session, .. } => {
+                write!(f, "AddAggregatedSenderNonceCommitments ({})", session.to_hex())
+            },
+            Self::SignAggregatedSenderOutput { session, .. } => {
+                write!(f, "SignAggregatedSenderOutput ({})", session.to_hex())
+            },
         }
     }
 }
@@ -223,7 +442,18 @@ impl fmt::Display for TransactionServiceRequest {
 #[derive(Debug)]
 pub enum TransactionServiceResponse {
     TransactionSent(TxId),
+    /// A transaction was already created with the idempotency key of the request
+    TransactionAlreadySent(TxId, TransactionStatus),
+    TransactionsSent(Vec<TxId>),
+    KeysRotated {
+        epoch: u64,
+        tx_ids: Vec<TxId>,
+    },
     TransactionCancelled,
+    ResendRequested,
+    TransactionMessageExported(Vec<u8>),
+    TransactionMessageImported(TxId),
+    ProtocolRecordingReplayed(ReplaySummary),
     PendingInboundTransactions(HashMap<TxId, InboundTransaction>),
     PendingOutboundTransactions(HashMap<TxId, OutboundTransaction>),
     CompletedTransactions(HashMap<TxId, CompletedTransaction>),
@@ -240,10 +470,29 @@ pub enum TransactionServiceResponse {
     AnyTransaction(Box<Option<WalletTransaction>>),
     NumConfirmationsRequired(u64),
     NumConfirmationsSet,
+    ConfigSet,
     ValidationStarted(OperationId),
     CompletedTransactionValidityChanged,
     ShaAtomicSwapTransactionSent(Box<(TxId, PublicKey, TransactionOutput)>),
     FeePerGramStatsPerBlock(FeePerGramStatsResponse),
+    PendingApprovalTransactions(Vec<PendingApprovalTransaction>),
+    CounterpartyStats(Box<CounterpartyStats>),
+    SpendingSummary(Box<SpendingSummary>),
+    TransactionTagged,
+    TransactionUntagged,
+    TransactionTags(Vec<String>),
+    AllTransactionTags(HashMap<TxId, Vec<String>>),
+    TaggedTransactions(Vec<WalletTransaction>),
+    TransactionAttachmentAdded,
+    TransactionAttachments(Vec<TransactionAttachment>),
+    TransactionAttachmentRemoved,
+    TransactionsImported(usize),
+    #[cfg(feature = "header_sync")]
+    SpendProof(Box<SpendProof>),
+    AggregatedSenderSessionStarted(PublicKey),
+    AggregatedSenderNonceCommitment(NonceCommitment),
+    AggregatedSenderNonceReveal(NonceReveal),
+    AggregatedSenderPartialSignature(Box<PartialMetadataSignature>),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
@@ -286,19 +535,63 @@ pub enum TransactionEvent {
         tx_id: TxId,
         is_valid: bool,
     },
+    /// The transaction has the number of confirmations required for its amount by the confirmation schedule
     TransactionMined {
         tx_id: TxId,
         is_valid: bool,
     },
+    /// The transaction was confirmed on the word of the base node alone, as the wallet has no verified header chain to
+    /// prove that it was mined. It is published after the [TransactionMined](Self::TransactionMined) event.
+    TransactionConfirmedUnverified(TxId),
     TransactionMinedRequestTimedOut(TxId),
+    /// The broadcast transaction is no longer in the mempool of the base node, e.g. because its fee was too low or it
+    /// expired. It is rebroadcast after a backoff that grows with `num_evictions`.
+    TransactionEvictedFromMempool {
+        tx_id: TxId,
+        num_evictions: u32,
+    },
     TransactionMinedUnconfirmed {
         tx_id: TxId,
         num_confirmations: u64,
+        /// The number of confirmations after which the transaction is confirmed, according to the confirmation
+        /// schedule
+        confirmations_required: u64,
         is_valid: bool,
     },
+    /// The block that the transaction was mined in at `mined_height` was reorged out of the chain. The transaction is
+    /// broadcast again, unless the base node finds it mined in another block.
+    TransactionReorged {
+        tx_id: TxId,
+        mined_height: u64,
+    },
     TransactionValidationStateChanged(OperationId),
     TransactionValidationCompleted(OperationId),
     TransactionValidationFailed(OperationId),
+    SpendingPolicyViolation(SpendingPolicyViolation),
+    /// An input of an incoming transaction was spent on-chain by a different transaction, so it will never be mined
+    TransactionDoubleSpent(TxId),
+    TransactionPendingApproval(TxId),
+    TransactionApprovalExpired(TxId),
+    /// The keys were rotated to a new key epoch and the outputs will be swept to it in `num_batches` transactions
+    KeyRotationStarted {
+        epoch: u64,
+        num_batches: usize,
+    },
+    /// Sweep transaction `batch` of `num_batches` of a key rotation was sent
+    KeyRotationProgress {
+        epoch: u64,
+        tx_id: TxId,
+        batch: usize,
+        num_batches: usize,
+    },
+    KeyRotationCompleted {
+        epoch: u64,
+    },
+    /// The keys were rotated to the epoch, but not all of the outputs could be swept to it
+    KeyRotationFailed {
+        epoch: u64,
+        error: String,
+    },
     Error(String),
 }
 
@@ -352,20 +645,34 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::TransactionMined { tx_id, is_valid } => {
                 write!(f, "TransactionMined for {}. is_valid: {}", tx_id, is_valid)
             },
+            TransactionEvent::TransactionConfirmedUnverified(tx) => {
+                write!(f, "TransactionConfirmedUnverified for {}", tx)
+            },
             TransactionEvent::TransactionMinedRequestTimedOut(tx) => {
                 write!(f, "TransactionMinedRequestTimedOut for {}", tx)
             },
+            TransactionEvent::TransactionEvictedFromMempool { tx_id, num_evictions } => {
+                write!(
+                    f,
+                    "TransactionEvictedFromMempool for {} after {} eviction(s)",
+                    tx_id, num_evictions
+                )
+            },
             TransactionEvent::TransactionMinedUnconfirmed {
                 tx_id,
                 num_confirmations,
+                confirmations_required,
                 is_valid,
             } => {
                 write!(
                     f,
-                    "TransactionMinedUnconfirmed for {} with num confirmations: {}. is_valid: {}",
-                    tx_id, num_confirmations, is_valid
+                    "TransactionMinedUnconfirmed for {} with num confirmations: {}/{}. is_valid: {}",
+                    tx_id, num_confirmations, confirmations_required, is_valid
                 )
             },
+            TransactionEvent::TransactionReorged { tx_id, mined_height } => {
+                write!(f, "TransactionReorged for {} mined at height {}", tx_id, mined_height)
+            },
             TransactionEvent::Error(error) => {
                 write!(f, "Error:{}", error)
             },
@@ -381,6 +688,39 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::NewBlockMined(tx_id) => {
                 write!(f, "New block mined {}", tx_id)
             },
+            TransactionEvent::SpendingPolicyViolation(violation) => {
+                write!(f, "Spending policy violation: {}", violation)
+            },
+            TransactionEvent::TransactionDoubleSpent(tx_id) => {
+                write!(f, "TransactionDoubleSpent for {}", tx_id)
+            },
+            TransactionEvent::TransactionPendingApproval(tx_id) => {
+                write!(f, "TransactionPendingApproval for {}", tx_id)
+            },
+            TransactionEvent::TransactionApprovalExpired(tx_id) => {
+                write!(f, "TransactionApprovalExpired for {}", tx_id)
+            },
+            TransactionEvent::KeyRotationStarted { epoch, num_batches } => {
+                write!(
+                    f,
+                    "KeyRotationStarted to epoch {} with {} batch(es)",
+                    epoch, num_batches
+                )
+            },
+            TransactionEvent::KeyRotationProgress {
+                epoch,
+                tx_id,
+                batch,
+                num_batches,
+            } => write!(
+                f,
+                "KeyRotationProgress to epoch {}: batch {} of {} sent in {}",
+                epoch, batch, num_batches, tx_id
+            ),
+            TransactionEvent::KeyRotationCompleted { epoch } => write!(f, "KeyRotationCompleted to epoch {}", epoch),
+            TransactionEvent::KeyRotationFailed { epoch, error } => {
+                write!(f, "KeyRotationFailed to epoch {}: {}", epoch, error)
+            },
         }
     }
 }
@@ -391,12 +731,15 @@ pub type TransactionEventReceiver = broadcast::Receiver<Arc<TransactionEvent>>;
 #[derive(Debug, Clone, Default)]
 pub struct FeePerGramStatsResponse {
     pub stats: Vec<FeePerGramStat>,
+    /// The lowest fee per gram that the base node accepts, below which a transaction cannot be broadcast
+    pub fee_floor: MicroTari,
 }
 
 impl From<proto::base_node::GetMempoolFeePerGramStatsResponse> for FeePerGramStatsResponse {
     fn from(value: proto::base_node::GetMempoolFeePerGramStatsResponse) -> Self {
         Self {
             stats: value.stats.into_iter().map(Into::into).collect(),
+            fee_floor: Fee::MINIMUM_FEE_PER_GRAM,
         }
     }
 }
@@ -405,7 +748,9 @@ impl From<proto::base_node::GetMempoolFeePerGramStatsResponse> for FeePerGramSta
 /// Transaction Service
 #[derive(Clone)]
 pub struct TransactionServiceHandle {
-    handle: SenderService<TransactionServiceRequest, Result<TransactionServiceResponse, TransactionServiceError>>,
+    handle: ScheduledService<
+        SenderService<TransactionServiceRequest, Result<TransactionServiceResponse, TransactionServiceError>>,
+    >,
     event_stream_sender: TransactionEventSender,
 }
 
@@ -415,15 +760,27 @@ impl TransactionServiceHandle {
         event_stream_sender: TransactionEventSender,
     ) -> Self {
         Self {
-            handle,
+            handle: ScheduledService::new(handle),
             event_stream_sender,
         }
     }
 
+    /// Returns a handle whose requests count towards the limit of `caller` on the requests in flight
+    pub fn for_caller(&self, scheduler: &RequestScheduler, caller: &str) -> Self {
+        Self {
+            handle: self.handle.for_caller(scheduler, caller),
+            event_stream_sender: self.event_stream_sender.clone(),
+        }
+    }
+
     pub fn get_event_stream(&self) -> TransactionEventReceiver {
         self.event_stream_sender.subscribe()
     }
 
+    /// Send a transaction to `dest_pubkey`. If an `idempotency_key` is supplied and a transaction was already created
+    /// with the same key, no new transaction is created and the TxId of the original transaction is returned instead,
+    /// so that a call that timed out can be safely retried. Use `send_idempotent_transaction` to also learn whether the
+    /// transaction is the original one and what its status is.
     pub async fn send_transaction(
         &mut self,
         dest_pubkey: CommsPublicKey,
@@ -431,6 +788,103 @@ impl TransactionServiceHandle {
         output_features: OutputFeatures,
         fee_per_gram: MicroTari,
         message: String,
+        idempotency_key: Option<String>,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransaction {
+                dest_pubkey,
+                amount,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+                idempotency_key,
+                ttl: None,
+                cancellation: CancellationToken::new(),
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            TransactionServiceResponse::TransactionAlreadySent(tx_id, _) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Send a transaction to `dest_pubkey` that is created at most once for `idempotency_key`. If a transaction was
+    /// already created with the same key, the TxId of the original transaction is returned along with its current
+    /// status; the status is `None` when a new transaction was created.
+    pub async fn send_idempotent_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        idempotency_key: String,
+    ) -> Result<(TxId, Option<TransactionStatus>), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransaction {
+                dest_pubkey,
+                amount,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+                idempotency_key: Some(idempotency_key),
+                ttl: None,
+                cancellation: CancellationToken::new(),
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok((tx_id, None)),
+            TransactionServiceResponse::TransactionAlreadySent(tx_id, status) => Ok((tx_id, Some(status))),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends a transaction that both parties cancel, unlocking its inputs, if it is not finalized within `ttl`
+    pub async fn send_transaction_with_ttl(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        ttl: Duration,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransaction {
+                dest_pubkey,
+                amount,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+                idempotency_key: None,
+                ttl: Some(ttl),
+                cancellation: CancellationToken::new(),
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends a transaction that is aborted, releasing its inputs, if `cancellation` is cancelled or its deadline is
+    /// reached before the transaction has been sent to the recipient. Fails with
+    /// [TransactionCancelled](TransactionServiceError::TransactionCancelled) or
+    /// [Timeout](TransactionServiceError::Timeout) if that happens before the inputs are selected, otherwise a
+    /// [TransactionCancelled](TransactionEvent::TransactionCancelled) event is published. Once sent, cancelling the
+    /// token cancels the transaction like [cancel_transaction](Self::cancel_transaction) does.
+    pub async fn send_transaction_with_cancellation(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        cancellation: CancellationToken,
     ) -> Result<TxId, TransactionServiceError> {
         match self
             .handle
@@ -440,6 +894,9 @@ impl TransactionServiceHandle {
                 output_features: Box::new(output_features),
                 fee_per_gram,
                 message,
+                idempotency_key: None,
+                ttl: None,
+                cancellation,
             })
             .await??
         {
@@ -464,6 +921,37 @@ impl TransactionServiceHandle {
                 output_features: Box::new(output_features),
                 fee_per_gram,
                 message,
+                memo: None,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends a one-sided payment with a memo of at most
+    /// [MAX_MEMO_SIZE](tari_core::transactions::transaction_components::MAX_MEMO_SIZE) bytes. The memo is
+    /// encrypted in the output, so only the recipient can read it, and uses the output features metadata, which must
+    /// be empty.
+    pub async fn send_one_sided_transaction_with_memo(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        memo: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedTransaction {
+                dest_pubkey,
+                amount,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+                memo: Some(memo),
             })
             .await??
         {
@@ -509,6 +997,82 @@ impl TransactionServiceHandle {
                 output_features: Box::new(output_features),
                 fee_per_gram,
                 message,
+                memo: None,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends a one-sided stealth address payment with a memo of at most
+    /// [MAX_MEMO_SIZE](tari_core::transactions::transaction_components::MAX_MEMO_SIZE) bytes. The memo is
+    /// encrypted in the output, so only the recipient can read it, and uses the output features metadata, which must
+    /// be empty.
+    pub async fn send_one_sided_to_stealth_address_transaction_with_memo(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        memo: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedToStealthAddressTransaction {
+                dest_pubkey,
+                amount,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+                memo: Some(memo),
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends the entire spendable balance of the wallet to `dest_pubkey` as one-sided stealth address payments without
+    /// change outputs. Outputs that are worth less than the fee to spend them are left behind. If the outputs do not
+    /// fit in a single transaction they are swept in batches, and the TxIds of all the transactions are returned.
+    pub async fn sweep_all(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        fee_per_gram: MicroTari,
+    ) -> Result<Vec<TxId>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SweepAll {
+                dest_pubkey,
+                fee_per_gram,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionsSent(tx_ids) => Ok(tx_ids),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Transfers the unique asset with the given asset id to `dest_pubkey` as a one-sided stealth address payment. The
+    /// output holding the asset is the only input and pays the fee, so the recipient receives its value less the fee.
+    pub async fn transfer_unique_asset(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        asset_id: FixedHash,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::TransferUniqueAsset {
+                dest_pubkey,
+                asset_id,
+                fee_per_gram,
+                message,
             })
             .await??
         {
@@ -517,6 +1081,37 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Rotates the keys of the wallet to a new key epoch after a compromise and sweeps every spendable output to keys
+    /// of the new epoch in batched pay-to-self transactions, publishing key rotation events along the way. Returns
+    /// the new epoch and the TxIds of the sweep transactions.
+    pub async fn rotate_keys(&mut self, fee_per_gram: MicroTari) -> Result<(u64, Vec<TxId>), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::RotateKeys { fee_per_gram })
+            .await??
+        {
+            TransactionServiceResponse::KeysRotated { epoch, tx_ids } => Ok((epoch, tx_ids)),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a decoy self-spend of `amount`. Decoy transactions are flagged with `is_decoy` and are left out of the
+    /// transaction history that the wallet presents, as they are not real payments.
+    pub async fn send_decoy_transaction(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendDecoyTransaction { amount, fee_per_gram })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         match self
             .handle
@@ -528,6 +1123,91 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Asks the sender of a pending inbound transaction to send the finalized transaction again, by resending our
+    /// reply to them
+    pub async fn request_resend(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::RequestResend(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::ResendRequested => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Exports the message the counterparty needs next to advance the negotiation of a transaction, so that it can be
+    /// delivered out-of-band (e.g. as a file or QR code). For a pending outbound transaction this is the sender's
+    /// first round message, for a pending inbound transaction it is our reply and for a completed outbound
+    /// transaction it is the finalized transaction.
+    pub async fn export_transaction_message(&mut self, tx_id: TxId) -> Result<Vec<u8>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ExportTransactionMessage(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::TransactionMessageExported(bytes) => Ok(bytes),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Imports a transaction negotiation message that was exported by the counterparty and delivered out-of-band. The
+    /// message is handled as if it was received over the network.
+    pub async fn import_transaction_message(&mut self, bytes: Vec<u8>) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportTransactionMessage(bytes))
+            .await??
+        {
+            TransactionServiceResponse::TransactionMessageImported(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Replays a recording of the transaction negotiation messages made by this wallet, see
+    /// [TransactionServiceConfig::protocol_recording_file]. The received messages are handed to the protocols again in
+    /// the order in which they arrived, so a failed negotiation can be reproduced with a copy of the wallet database.
+    pub async fn replay_protocol_recording(&mut self, path: PathBuf) -> Result<ReplaySummary, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ReplayProtocolRecording(path))
+            .await??
+        {
+            TransactionServiceResponse::ProtocolRecordingReplayed(summary) => Ok(summary),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Approve an outgoing transaction that is waiting for second factor approval. The `approval_token` is either the
+    /// current TOTP code or a signature of the transaction's approval challenge made with the secondary approval key.
+    pub async fn approve_transaction(
+        &mut self,
+        tx_id: TxId,
+        approval_token: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ApproveTransaction { tx_id, approval_token })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_pending_approval_transactions(
+        &mut self,
+    ) -> Result<Vec<PendingApprovalTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetPendingApprovalTransactions)
+            .await??
+        {
+            TransactionServiceResponse::PendingApprovalTransactions(transactions) => Ok(transactions),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_pending_inbound_transactions(
         &mut self,
     ) -> Result<HashMap<TxId, InboundTransaction>, TransactionServiceError> {
@@ -753,11 +1433,36 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Replaces the config of the running service, e.g. to change the broadcast intervals without a restart
+    pub async fn set_config(&mut self, config: TransactionServiceConfig) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SetConfig(Box::new(config)))
+            .await??
+        {
+            TransactionServiceResponse::ConfigSet => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn generate_coinbase_transaction(
         &mut self,
         rewards: MicroTari,
         fees: MicroTari,
         block_height: u64,
+    ) -> Result<Transaction, TransactionServiceError> {
+        self.generate_coinbase_transaction_with_extra(rewards, fees, block_height, Vec::new())
+            .await
+    }
+
+    /// Generates a coinbase transaction with extra data, e.g. a pool tag or merge mining data, in the coinbase output.
+    /// The extra data may not exceed the maximum size allowed by consensus.
+    pub async fn generate_coinbase_transaction_with_extra(
+        &mut self,
+        rewards: MicroTari,
+        fees: MicroTari,
+        block_height: u64,
+        extra: Vec<u8>,
     ) -> Result<Transaction, TransactionServiceError> {
         match self
             .handle
@@ -765,6 +1470,7 @@ impl TransactionServiceHandle {
                 rewards,
                 fees,
                 block_height,
+                extra,
             ))
             .await??
         {
@@ -831,6 +1537,32 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Pays a recipient that may be offline with an expiring vault output, which the recipient can claim for
+    /// `expiring_vault_lifetime` blocks, after which it is refunded to this wallet. The transaction is broadcast
+    /// immediately and sent to the recipient directly or via store-and-forward, so that it can be claimed when the
+    /// recipient comes online.
+    pub async fn send_expiring_vault_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendExpiringVaultTransaction {
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     /// Query the base node for the fee per gram stats of the next {count} blocks.
     pub async fn get_fee_per_gram_stats_per_block(
         &mut self,
@@ -845,4 +1577,257 @@ impl TransactionServiceHandle {
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns the totals of the completed transactions exchanged with a counterparty
+    pub async fn get_counterparty_stats(
+        &mut self,
+        counterparty: CommsPublicKey,
+    ) -> Result<CounterpartyStats, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCounterpartyStats(counterparty))
+            .await??
+        {
+            TransactionServiceResponse::CounterpartyStats(stats) => Ok(*stats),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the completed transactions of the last `period` grouped by counterparty. A period that reaches back
+    /// further than the wallet's history covers all of it.
+    pub async fn get_spending_summary(&mut self, period: Duration) -> Result<SpendingSummary, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetSpendingSummary(period))
+            .await??
+        {
+            TransactionServiceResponse::SpendingSummary(summary) => Ok(*summary),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Adds a user defined tag to a transaction, e.g. to categorize it for expense tracking. Surrounding whitespace is
+    /// removed from the tag.
+    pub async fn tag_transaction(&mut self, tx_id: TxId, tag: String) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::TagTransaction { tx_id, tag })
+            .await??
+        {
+            TransactionServiceResponse::TransactionTagged => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn untag_transaction(&mut self, tx_id: TxId, tag: String) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::UntagTransaction { tx_id, tag })
+            .await??
+        {
+            TransactionServiceResponse::TransactionUntagged => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_transaction_tags(&mut self, tx_id: TxId) -> Result<Vec<String>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionTags(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::TransactionTags(tags) => Ok(tags),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the tags of every transaction that has at least one tag
+    pub async fn get_all_transaction_tags(&mut self) -> Result<HashMap<TxId, Vec<String>>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetAllTransactionTags)
+            .await??
+        {
+            TransactionServiceResponse::AllTransactionTags(tags) => Ok(tags),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the transactions with a tag, including cancelled ones
+    pub async fn get_transactions_by_tag(
+        &mut self,
+        tag: String,
+    ) -> Result<Vec<WalletTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionsByTag(tag))
+            .await??
+        {
+            TransactionServiceResponse::TaggedTransactions(transactions) => Ok(transactions),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Attaches a file, e.g. the receipt or the invoice of a payment, to a transaction. An attachment of the
+    /// transaction with the same name is replaced. The size of the file is limited by `max_attachment_size`.
+    pub async fn add_transaction_attachment(
+        &mut self,
+        tx_id: TxId,
+        name: String,
+        content_type: String,
+        data: Vec<u8>,
+    ) -> Result<(), TransactionServiceError> {
+        let attachment = Box::new(TransactionAttachment::new(tx_id, name, content_type, data));
+        match self
+            .handle
+            .call(TransactionServiceRequest::AddTransactionAttachment(attachment))
+            .await??
+        {
+            TransactionServiceResponse::TransactionAttachmentAdded => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the attachments of a transaction ordered by name
+    pub async fn get_transaction_attachments(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<Vec<TransactionAttachment>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionAttachments(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::TransactionAttachments(attachments) => Ok(attachments),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn remove_transaction_attachment(
+        &mut self,
+        tx_id: TxId,
+        name: String,
+    ) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::RemoveTransactionAttachment { tx_id, name })
+            .await??
+        {
+            TransactionServiceResponse::TransactionAttachmentRemoved => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Stores transactions exported from another wallet database, skipping the ones that already exist. Returns the
+    /// number of transactions that were imported.
+    pub async fn import_transactions(
+        &mut self,
+        completed: Vec<CompletedTransaction>,
+        inbound: Vec<InboundTransaction>,
+        outbound: Vec<OutboundTransaction>,
+    ) -> Result<usize, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportTransactions {
+                completed,
+                inbound,
+                outbound,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionsImported(num_imported) => Ok(num_imported),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Proves to a third party that the output with the given commitment was spent by a mined transaction of this
+    /// wallet. The proof contains the header of the block the transaction was mined in, the spending input, a kernel
+    /// of the transaction and the inclusion proof of the block, and can be checked with [SpendProof::verify]. The block
+    /// must be part of the verified header chain.
+    #[cfg(feature = "header_sync")]
+    pub async fn generate_spend_proof(
+        &mut self,
+        commitment: Commitment,
+    ) -> Result<SpendProof, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GenerateSpendProof(commitment))
+            .await??
+        {
+            TransactionServiceResponse::SpendProof(proof) => Ok(*proof),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Starts a signing session as a party to an aggregated sender key, for a one-sided payment to the recipient, e.g.
+    /// to release an escrow. Returns the public key of this party, which identifies the session and is sent to the
+    /// other parties. The session is persisted between the rounds, see
+    /// `tari_core::transactions::transaction_protocol::aggregated_sender`.
+    pub async fn start_aggregated_sender_session(
+        &mut self,
+        recipient: CommsPublicKey,
+    ) -> Result<PublicKey, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::StartAggregatedSenderSession(recipient))
+            .await??
+        {
+            TransactionServiceResponse::AggregatedSenderSessionStarted(public_key) => Ok(public_key),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Adds the public keys of all the parties to the session and returns the nonce commitment of this party
+    pub async fn add_aggregated_sender_party_keys(
+        &mut self,
+        session: PublicKey,
+        party_keys: Vec<PublicKey>,
+    ) -> Result<NonceCommitment, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::AddAggregatedSenderPartyKeys { session, party_keys })
+            .await??
+        {
+            TransactionServiceResponse::AggregatedSenderNonceCommitment(commitment) => Ok(commitment),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Adds the nonce commitments of all the parties to the session and returns the nonce of this party
+    pub async fn add_aggregated_sender_nonce_commitments(
+        &mut self,
+        session: PublicKey,
+        commitments: Vec<NonceCommitment>,
+    ) -> Result<NonceReveal, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::AddAggregatedSenderNonceCommitments { session, commitments })
+            .await??
+        {
+            TransactionServiceResponse::AggregatedSenderNonceReveal(reveal) => Ok(reveal),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Signs the output that the coordinator built with the nonces of all the parties. The output must be a one-sided
+    /// payment to the recipient of the session. The session ends once it has signed.
+    pub async fn sign_aggregated_sender_output(
+        &mut self,
+        session: PublicKey,
+        reveals: Vec<NonceReveal>,
+        output: TransactionOutput,
+    ) -> Result<PartialMetadataSignature, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SignAggregatedSenderOutput {
+                session,
+                reveals,
+                output: Box::new(output),
+            })
+            .await??
+        {
+            TransactionServiceResponse::AggregatedSenderPartialSignature(signature) => Ok(*signature),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
 }
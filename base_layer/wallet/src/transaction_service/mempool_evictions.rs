@@ -0,0 +1,122 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Broadcast transactions that the base node no longer has in its mempool, e.g. because their fee was too low or they
+//! expired, are rebroadcast with an exponential backoff instead of being resubmitted after every block.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tari_common_types::transaction::TxId;
+
+use crate::transaction_service::config::TransactionServiceConfig;
+
+#[derive(Clone, Copy)]
+struct Eviction {
+    count: u32,
+    evicted_at: Instant,
+}
+
+/// The evictions of transactions from the mempool of the base node, shared by the service and its protocols
+#[derive(Clone)]
+pub struct MempoolEvictions {
+    evictions: Arc<Mutex<HashMap<TxId, Eviction>>>,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl MempoolEvictions {
+    pub fn new(config: &TransactionServiceConfig) -> Self {
+        Self {
+            evictions: Arc::new(Mutex::new(HashMap::new())),
+            backoff: config.mempool_eviction_backoff,
+            max_backoff: config.max_mempool_eviction_backoff,
+        }
+    }
+
+    /// Records that the transaction was not found in the mempool and returns the number of times it has been evicted.
+    /// Returns `None` if the transaction is still waiting out the backoff of its last eviction, as it has not been
+    /// rebroadcast since.
+    pub fn record(&self, tx_id: TxId) -> Option<u32> {
+        let mut evictions = self.evictions.lock().expect("The mempool evictions lock is poisoned");
+        let count = match evictions.get(&tx_id) {
+            Some(eviction) if eviction.evicted_at.elapsed() < self.backoff_for(eviction.count) => return None,
+            Some(eviction) => eviction.count.saturating_add(1),
+            None => 1,
+        };
+        evictions.insert(tx_id, Eviction {
+            count,
+            evicted_at: Instant::now(),
+        });
+        Some(count)
+    }
+
+    /// The remaining time before an evicted transaction may be rebroadcast
+    pub fn rebroadcast_delay(&self, tx_id: TxId) -> Option<Duration> {
+        let evictions = self.evictions.lock().expect("The mempool evictions lock is poisoned");
+        let eviction = evictions.get(&tx_id)?;
+        self.backoff_for(eviction.count)
+            .checked_sub(eviction.evicted_at.elapsed())
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Forgets a transaction that was mined or will not be broadcast again
+    pub fn remove(&self, tx_id: TxId) {
+        let _eviction = self
+            .evictions
+            .lock()
+            .expect("The mempool evictions lock is poisoned")
+            .remove(&tx_id);
+    }
+
+    /// The backoff doubles with every eviction, up to the maximum
+    fn backoff_for(&self, count: u32) -> Duration {
+        let factor = 1u32 << count.saturating_sub(1).min(16);
+        self.backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_evictions(backoff: Duration, max_backoff: Duration) -> MempoolEvictions {
+        MempoolEvictions::new(&TransactionServiceConfig {
+            mempool_eviction_backoff: backoff,
+            max_mempool_eviction_backoff: max_backoff,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn it_backs_off_exponentially() {
+        let evictions = new_evictions(Duration::from_secs(10), Duration::from_secs(35));
+        assert_eq!(evictions.backoff_for(1), Duration::from_secs(10));
+        assert_eq!(evictions.backoff_for(2), Duration::from_secs(20));
+        assert_eq!(evictions.backoff_for(3), Duration::from_secs(35));
+        assert_eq!(evictions.backoff_for(u32::MAX), Duration::from_secs(35));
+    }
+
+    #[test]
+    fn it_only_counts_evictions_after_the_backoff() {
+        let tx_id = TxId::from(1u64);
+        let evictions = new_evictions(Duration::from_secs(60), Duration::from_secs(600));
+        assert!(evictions.rebroadcast_delay(tx_id).is_none());
+        assert_eq!(evictions.record(tx_id), Some(1));
+        assert!(evictions.rebroadcast_delay(tx_id).unwrap() > Duration::from_secs(50));
+        assert_eq!(evictions.record(tx_id), None);
+        evictions.remove(tx_id);
+        assert!(evictions.rebroadcast_delay(tx_id).is_none());
+
+        let evictions = new_evictions(Duration::ZERO, Duration::ZERO);
+        assert_eq!(evictions.record(tx_id), Some(1));
+        assert_eq!(evictions.record(tx_id), Some(2));
+        assert!(evictions.rebroadcast_delay(tx_id).is_none());
+    }
+}
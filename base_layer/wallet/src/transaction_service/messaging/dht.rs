@@ -0,0 +1,414 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::*;
+use tari_common_types::transaction::TxId;
+use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams, SendMessageResponse},
+};
+use tari_core::transactions::{
+    transaction_components::Transaction,
+    transaction_protocol::{
+        proto::protocol as proto,
+        recipient::RecipientSignedMessage,
+        sender::SingleRoundSenderData,
+    },
+};
+use tari_p2p::tari_message::TariMessageType;
+use tokio::time::sleep;
+
+use crate::transaction_service::{
+    config::{TransactionRoutingMechanism, TransactionServiceConfig},
+    error::TransactionServiceError,
+    handle::{TransactionEvent, TransactionEventSender},
+    messaging::{MessageSendResult, TransactionMessagingBackend},
+    tasks::wait_on_dial::wait_on_dial,
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::messaging::dht";
+
+/// Sends the transaction messages over the DHT, directly to the counterparty and/or via Store-and-forward as per the
+/// `transaction_routing_mechanism` config setting.
+#[derive(Clone)]
+pub struct DhtMessagingBackend {
+    outbound_message_service: OutboundMessageRequester,
+    event_publisher: TransactionEventSender,
+}
+
+impl DhtMessagingBackend {
+    pub fn new(outbound_message_service: OutboundMessageRequester, event_publisher: TransactionEventSender) -> Self {
+        Self {
+            outbound_message_service,
+            event_publisher,
+        }
+    }
+
+    /// Sends the message as per the configured routing mechanism. When sending directly, a Store-and-forward message
+    /// is sent regardless of the outcome of the direct send if `DirectAndStoreAndForward` is configured.
+    #[allow(clippy::too_many_lines)]
+    async fn send_message<T>(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: T,
+        details: MessageDetails,
+        config: &TransactionServiceConfig,
+    ) -> MessageSendResult
+    where
+        T: prost::Message + Clone,
+    {
+        let mut result = MessageSendResult::default();
+        let routing = config.transaction_routing_mechanism;
+        if routing == TransactionRoutingMechanism::StoreAndForwardOnly {
+            result.store_and_forward_send_result = self
+                .send_store_and_forward(tx_id, destination, message, &details, config)
+                .await;
+            return result;
+        }
+        let store_and_forward = routing == TransactionRoutingMechanism::DirectAndStoreAndForward;
+
+        let mut outbound_message_service = self.outbound_message_service.clone();
+        match outbound_message_service
+            .send_direct(
+                destination.clone(),
+                OutboundDomainMessage::new(&details.message_type, message.clone()),
+            )
+            .await
+        {
+            Ok(SendMessageResponse::Queued(send_states)) => {
+                result.direct_send_result = wait_on_dial(
+                    send_states,
+                    tx_id,
+                    destination.clone(),
+                    details.label,
+                    config.direct_send_timeout,
+                )
+                .await;
+                // Send a Store and Forward (SAF) regardless. Empirical testing determined that in some cases a direct
+                // send would be reported as true, even though the wallet was offline. Possibly due to the Tor
+                // connection remaining active for a few minutes after wallet shutdown.
+                info!(
+                    target: LOG_TARGET,
+                    "Direct Send {} result was {}. Sending SAF for TxId: {} to recipient with Public Key: {}",
+                    details.label,
+                    result.direct_send_result,
+                    tx_id,
+                    destination,
+                );
+                if store_and_forward {
+                    result.store_and_forward_send_result = self
+                        .send_store_and_forward(tx_id, destination, message, &details, config)
+                        .await;
+                }
+            },
+            Ok(SendMessageResponse::Failed(err)) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "{} Send Direct for TxID {} failed: {}", details.label, tx_id, err
+                );
+                if store_and_forward {
+                    result.store_and_forward_send_result = self
+                        .send_store_and_forward(tx_id, destination, message, &details, config)
+                        .await;
+                }
+            },
+            Ok(SendMessageResponse::PendingDiscovery(rx)) => {
+                if details.notify_discovery {
+                    let _size = self
+                        .event_publisher
+                        .send(Arc::new(TransactionEvent::TransactionDiscoveryInProgress(tx_id)));
+                }
+                if store_and_forward {
+                    result.store_and_forward_send_result = self
+                        .send_store_and_forward(tx_id, destination.clone(), message, &details, config)
+                        .await;
+                }
+                // now wait for discovery to complete
+                match rx.await {
+                    Ok(SendMessageResponse::Queued(send_states)) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Discovery of {} completed for TxID: {}", destination, tx_id
+                        );
+                        result.direct_send_result = wait_on_dial(
+                            send_states,
+                            tx_id,
+                            destination,
+                            details.label,
+                            config.direct_send_timeout,
+                        )
+                        .await;
+                    },
+                    Ok(SendMessageResponse::Failed(e)) => warn!(
+                        target: LOG_TARGET,
+                        "Failed to send message ({}) Discovery failed for TxId: {}", e, tx_id
+                    ),
+                    Ok(SendMessageResponse::PendingDiscovery(_)) => unreachable!(),
+                    Err(e) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Error waiting for Discovery while sending message to TxId: {} {:?}", tx_id, e
+                        );
+                    },
+                }
+            },
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Direct {} Send (TxId: {}) failed: {:?}", details.label, tx_id, e
+                );
+            },
+        }
+
+        result
+    }
+
+    /// Sends the message to the neighbours of the destination for Store-and-forward, and resends it as per the
+    /// `saf_resend_retry` config setting if it could not be sent
+    async fn send_store_and_forward<T>(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: T,
+        details: &MessageDetails,
+        config: &TransactionServiceConfig,
+    ) -> bool
+    where
+        T: prost::Message + Clone,
+    {
+        let policy = &config.saf_resend_retry;
+        let mut failed_attempts = 0;
+        loop {
+            if self
+                .try_send_store_and_forward(tx_id, destination.clone(), message.clone(), details, config)
+                .await
+            {
+                return true;
+            }
+            failed_attempts += 1;
+            if !policy.should_retry(failed_attempts) {
+                return false;
+            }
+            let delay = policy.delay(failed_attempts);
+            debug!(
+                target: LOG_TARGET,
+                "Resending {} (TxId: {}) to Neighbours for Store and Forward in {:.1?}", details.label, tx_id, delay
+            );
+            sleep(delay).await;
+        }
+    }
+
+    async fn try_send_store_and_forward<T>(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: T,
+        details: &MessageDetails,
+        config: &TransactionServiceConfig,
+    ) -> bool
+    where
+        T: prost::Message,
+    {
+        let mut outbound_message_service = self.outbound_message_service.clone();
+        let send_result = if config.store_and_forward_connected_peers_only {
+            match outbound_message_service
+                .send_message(
+                    SendMessageParams::new()
+                        .closest_connected(NodeId::from_public_key(&destination), vec![])
+                        .with_encryption(OutboundEncryption::encrypt_for(destination.clone()))
+                        .with_destination(destination.into())
+                        .finish(),
+                    OutboundDomainMessage::new(&details.message_type, message),
+                )
+                .await
+            {
+                Ok(response) => response.resolve().await.map_err(Into::into),
+                Err(e) => Err(e),
+            }
+        } else {
+            outbound_message_service
+                .closest_broadcast(
+                    destination.clone(),
+                    OutboundEncryption::encrypt_for(destination),
+                    vec![],
+                    OutboundDomainMessage::new(&details.message_type, message),
+                )
+                .await
+        };
+        match send_result {
+            Ok(send_states) if !details.wait_for_store_and_forward => {
+                info!(
+                    target: LOG_TARGET,
+                    "Sending {} (TxId: {}) to Neighbours for Store and Forward successful with Message Tags: {:?}",
+                    details.label,
+                    tx_id,
+                    send_states.to_tags(),
+                );
+                true
+            },
+            Ok(send_states) if !send_states.is_empty() => {
+                let (successful_sends, failed_sends) =
+                    send_states.wait_n_timeout(config.broadcast_send_timeout, 1).await;
+                if !successful_sends.is_empty() {
+                    info!(
+                        target: LOG_TARGET,
+                        "{} (TxId: {}) Send to Neighbours for Store and Forward successful with Message Tags: {:?}",
+                        details.label,
+                        tx_id,
+                        successful_sends[0],
+                    );
+                    true
+                } else if !failed_sends.is_empty() {
+                    warn!(
+                        target: LOG_TARGET,
+                        "{} Send to Neighbours for Store and Forward for TX_ID: {} was unsuccessful and no messages \
+                         were sent",
+                        details.label,
+                        tx_id
+                    );
+                    false
+                } else {
+                    warn!(
+                        target: LOG_TARGET,
+                        "{} Send to Neighbours for Store and Forward for TX_ID: {} timed out and was unsuccessful. \
+                         Some message might still be sent.",
+                        details.label,
+                        tx_id
+                    );
+                    false
+                }
+            },
+            Ok(_) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "{} Send to Neighbours for Store and Forward for TX_ID: {} was unsuccessful and no messages were \
+                     sent",
+                    details.label,
+                    tx_id
+                );
+                false
+            },
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Sending {} (TxId: {}) to neighbours for Store and Forward failed: {:?}", details.label, tx_id, e
+                );
+                false
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionMessagingBackend for DhtMessagingBackend {
+    async fn send_transaction(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: SingleRoundSenderData,
+        config: &TransactionServiceConfig,
+    ) -> Result<MessageSendResult, TransactionServiceError> {
+        info!(
+            target: LOG_TARGET,
+            "Attempting to Send Transaction (TxId: {}) to recipient with Public Key: {}", tx_id, destination,
+        );
+        let proto_message = proto::TransactionSenderMessage::single(message.into());
+        let details = MessageDetails {
+            message_type: TariMessageType::SenderPartialTransaction,
+            label: "Transaction",
+            wait_for_store_and_forward: true,
+            notify_discovery: true,
+        };
+        Ok(self
+            .send_message(tx_id, destination, proto_message, details, config)
+            .await)
+    }
+
+    async fn send_transaction_reply(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: RecipientSignedMessage,
+        config: &TransactionServiceConfig,
+    ) -> Result<bool, TransactionServiceError> {
+        let proto_message: proto::RecipientSignedMessage = message.into();
+        let details = MessageDetails {
+            message_type: TariMessageType::ReceiverPartialTransactionReply,
+            label: "Transaction Reply",
+            wait_for_store_and_forward: false,
+            notify_discovery: false,
+        };
+        let result = self
+            .send_message(tx_id, destination, proto_message, details, config)
+            .await;
+        Ok(result.is_sent())
+    }
+
+    async fn send_finalized_transaction(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        transaction: Transaction,
+        config: &TransactionServiceConfig,
+    ) -> Result<(), TransactionServiceError> {
+        let proto_message = proto::TransactionFinalizedMessage::new(tx_id, transaction)
+            .map_err(TransactionServiceError::InvalidMessageError)?;
+        let details = MessageDetails {
+            message_type: TariMessageType::TransactionFinalized,
+            label: "Finalized Transaction",
+            wait_for_store_and_forward: false,
+            notify_discovery: false,
+        };
+        let result = self
+            .send_message(tx_id, destination, proto_message, details, config)
+            .await;
+        if !result.is_sent() {
+            return Err(TransactionServiceError::OutboundSendFailure);
+        }
+        Ok(())
+    }
+
+    async fn send_transaction_cancelled(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+    ) -> Result<(), TransactionServiceError> {
+        let proto_message = proto::TransactionCancelledMessage { tx_id: tx_id.into() };
+        let mut outbound_message_service = self.outbound_message_service.clone();
+
+        // Send both direct and SAF we are not going to monitor the progress on these messages for potential resend as
+        // they are just courtesy messages
+        let _send_message_response = outbound_message_service
+            .send_direct(
+                destination.clone(),
+                OutboundDomainMessage::new(&TariMessageType::TransactionCancelled, proto_message.clone()),
+            )
+            .await?;
+
+        let _message_send_state = outbound_message_service
+            .closest_broadcast(
+                destination.clone(),
+                OutboundEncryption::encrypt_for(destination),
+                vec![],
+                OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// How a particular kind of transaction message is sent
+struct MessageDetails {
+    message_type: TariMessageType,
+    /// Used to identify the message in the logs
+    label: &'static str,
+    /// Wait for the Store-and-forward message to be sent to at least one neighbour before reporting it as sent
+    wait_for_store_and_forward: bool,
+    /// Publish a `TransactionDiscoveryInProgress` event if the destination first has to be discovered
+    notify_discovery: bool,
+}
@@ -0,0 +1,78 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The transport over which the transaction negotiation messages are delivered to the counterparty. The protocols
+//! only talk to a [TransactionMessagingBackend], so alternative transports (e.g. a relay server or a manual file
+//! exchange for air-gapped wallets) can be plugged in without changing the protocol logic. Replies, finalized
+//! transactions and cancellations that arrive over an alternative transport must still be handed to the
+//! Transaction Service.
+
+mod dht;
+mod out_of_band;
+
+use async_trait::async_trait;
+pub use dht::DhtMessagingBackend;
+pub(crate) use out_of_band::encode_envelope;
+pub use out_of_band::{OutOfBandMessage, OutOfBandMessagingBackend};
+use tari_common_types::transaction::TxId;
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::{
+    transaction_components::Transaction,
+    transaction_protocol::{recipient::RecipientSignedMessage, sender::SingleRoundSenderData},
+};
+
+use crate::transaction_service::{config::TransactionServiceConfig, error::TransactionServiceError};
+
+/// Delivers the transaction negotiation messages to the counterparty of a transaction.
+#[async_trait]
+pub trait TransactionMessagingBackend: Send + Sync + 'static {
+    /// Send the sender's first round message to the recipient.
+    async fn send_transaction(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: SingleRoundSenderData,
+        config: &TransactionServiceConfig,
+    ) -> Result<MessageSendResult, TransactionServiceError>;
+
+    /// Send the recipient's signed reply back to the sender. Returns true if the reply was handed to the transport.
+    async fn send_transaction_reply(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: RecipientSignedMessage,
+        config: &TransactionServiceConfig,
+    ) -> Result<bool, TransactionServiceError>;
+
+    /// Send the finalized transaction to the recipient.
+    async fn send_finalized_transaction(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        transaction: Transaction,
+        config: &TransactionServiceConfig,
+    ) -> Result<(), TransactionServiceError>;
+
+    /// Let the counterparty know that the transaction was cancelled. This is a courtesy message, so delivery is not
+    /// monitored.
+    async fn send_transaction_cancelled(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+    ) -> Result<(), TransactionServiceError>;
+}
+
+/// The outcome of sending a transaction to its recipient
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageSendResult {
+    /// The message was delivered directly to the recipient
+    pub direct_send_result: bool,
+    /// The message was stored with peers of the recipient for later delivery
+    pub store_and_forward_send_result: bool,
+}
+
+impl MessageSendResult {
+    pub fn is_sent(&self) -> bool {
+        self.direct_send_result || self.store_and_forward_send_result
+    }
+}
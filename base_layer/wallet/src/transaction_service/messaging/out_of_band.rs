@@ -0,0 +1,239 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::convert::{TryFrom, TryInto};
+
+use async_trait::async_trait;
+use log::*;
+use prost::Message;
+use tari_common_types::transaction::TxId;
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::{
+    transaction_components::Transaction,
+    transaction_protocol::{
+        proto::protocol as proto,
+        recipient::RecipientSignedMessage,
+        sender::{SingleRoundSenderData, TransactionSenderMessage},
+    },
+};
+use tari_p2p::tari_message::TariMessageType;
+use tari_utilities::ByteArray;
+
+use crate::transaction_service::{
+    config::TransactionServiceConfig,
+    error::TransactionServiceError,
+    messaging::{MessageSendResult, TransactionMessagingBackend},
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::messaging::out_of_band";
+
+/// The version of the out-of-band message encoding
+const OUT_OF_BAND_MESSAGE_VERSION: u32 = 1;
+
+/// A messaging backend for wallets that never connect to their counterparties, e.g. air-gapped wallets. Nothing is
+/// sent; instead each message is exported with `TransactionServiceHandle::export_transaction_message`, carried to the
+/// counterparty as a file or QR code and imported there with `TransactionServiceHandle::import_transaction_message`.
+/// Messages are reported as sent so that the protocols advance to waiting for the counterparty.
+#[derive(Debug, Clone, Default)]
+pub struct OutOfBandMessagingBackend;
+
+#[async_trait]
+impl TransactionMessagingBackend for OutOfBandMessagingBackend {
+    async fn send_transaction(
+        &self,
+        tx_id: TxId,
+        _destination: CommsPublicKey,
+        _message: SingleRoundSenderData,
+        _config: &TransactionServiceConfig,
+    ) -> Result<MessageSendResult, TransactionServiceError> {
+        debug!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) is ready to be exported", tx_id
+        );
+        Ok(MessageSendResult {
+            direct_send_result: true,
+            store_and_forward_send_result: false,
+        })
+    }
+
+    async fn send_transaction_reply(
+        &self,
+        tx_id: TxId,
+        _destination: CommsPublicKey,
+        _message: RecipientSignedMessage,
+        _config: &TransactionServiceConfig,
+    ) -> Result<bool, TransactionServiceError> {
+        debug!(
+            target: LOG_TARGET,
+            "Transaction Reply (TxId: {}) is ready to be exported", tx_id
+        );
+        Ok(true)
+    }
+
+    async fn send_finalized_transaction(
+        &self,
+        tx_id: TxId,
+        _destination: CommsPublicKey,
+        _transaction: Transaction,
+        _config: &TransactionServiceConfig,
+    ) -> Result<(), TransactionServiceError> {
+        debug!(
+            target: LOG_TARGET,
+            "Finalized Transaction (TxId: {}) is ready to be exported", tx_id
+        );
+        Ok(())
+    }
+
+    async fn send_transaction_cancelled(
+        &self,
+        _tx_id: TxId,
+        _destination: CommsPublicKey,
+    ) -> Result<(), TransactionServiceError> {
+        Ok(())
+    }
+}
+
+/// A transaction negotiation message that is exchanged out-of-band
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutOfBandMessage {
+    /// The sender's first round message
+    Transaction(SingleRoundSenderData),
+    /// The recipient's signed reply
+    Reply(RecipientSignedMessage),
+    /// The finalized transaction for the recipient
+    Finalized { tx_id: TxId, transaction: Transaction },
+    /// The cancellation of the transaction by the counterparty
+    Cancelled { tx_id: TxId },
+}
+
+impl OutOfBandMessage {
+    pub fn tx_id(&self) -> TxId {
+        match self {
+            OutOfBandMessage::Transaction(data) => data.tx_id,
+            OutOfBandMessage::Reply(reply) => reply.tx_id,
+            OutOfBandMessage::Finalized { tx_id, .. } | OutOfBandMessage::Cancelled { tx_id } => *tx_id,
+        }
+    }
+
+    /// Encodes the message, along with the public key of the wallet that created it, as a compact blob that can be
+    /// written to a file or a QR code.
+    pub fn to_bytes(&self, source_public_key: &CommsPublicKey) -> Result<Vec<u8>, TransactionServiceError> {
+        let (message_type, body) = self.encode()?;
+        Ok(encode_envelope(source_public_key, message_type, body))
+    }
+
+    /// The message type and protobuf encoding of the message, as it is sent over the DHT
+    pub(crate) fn encode(&self) -> Result<(TariMessageType, Vec<u8>), TransactionServiceError> {
+        let encoded = match self.clone() {
+            OutOfBandMessage::Transaction(data) => (
+                TariMessageType::SenderPartialTransaction,
+                proto::TransactionSenderMessage::single(data.into()).encode_to_vec(),
+            ),
+            OutOfBandMessage::Reply(reply) => (
+                TariMessageType::ReceiverPartialTransactionReply,
+                proto::RecipientSignedMessage::from(reply).encode_to_vec(),
+            ),
+            OutOfBandMessage::Finalized { tx_id, transaction } => (
+                TariMessageType::TransactionFinalized,
+                proto::TransactionFinalizedMessage::new(tx_id, transaction)
+                    .map_err(TransactionServiceError::InvalidMessageError)?
+                    .encode_to_vec(),
+            ),
+            OutOfBandMessage::Cancelled { tx_id } => (
+                TariMessageType::TransactionCancelled,
+                proto::TransactionCancelledMessage { tx_id: tx_id.into() }.encode_to_vec(),
+            ),
+        };
+        Ok(encoded)
+    }
+
+    /// Decodes a blob created by [OutOfBandMessage::to_bytes], returning the public key of the wallet that created it
+    /// and the message.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(CommsPublicKey, Self), TransactionServiceError> {
+        let envelope = OutOfBandEnvelope::decode(bytes)
+            .map_err(|e| TransactionServiceError::InvalidOutOfBandMessage(e.to_string()))?;
+        if envelope.version != OUT_OF_BAND_MESSAGE_VERSION {
+            return Err(TransactionServiceError::InvalidOutOfBandMessage(format!(
+                "Unsupported message version {}",
+                envelope.version
+            )));
+        }
+        let source_public_key = CommsPublicKey::from_bytes(&envelope.source_public_key)
+            .map_err(|e| TransactionServiceError::InvalidOutOfBandMessage(e.to_string()))?;
+        let body = envelope.body.as_slice();
+        let decode_error = |e: prost::DecodeError| TransactionServiceError::InvalidOutOfBandMessage(e.to_string());
+
+        let message = match TariMessageType::from_i32(envelope.message_type) {
+            Some(TariMessageType::SenderPartialTransaction) => {
+                let message = proto::TransactionSenderMessage::decode(body).map_err(decode_error)?;
+                match TransactionSenderMessage::try_from(message)
+                    .map_err(TransactionServiceError::InvalidMessageError)?
+                {
+                    TransactionSenderMessage::Single(data) => OutOfBandMessage::Transaction(*data),
+                    _ => {
+                        return Err(TransactionServiceError::InvalidOutOfBandMessage(
+                            "Only single round transactions are supported".to_string(),
+                        ))
+                    },
+                }
+            },
+            Some(TariMessageType::ReceiverPartialTransactionReply) => {
+                let message = proto::RecipientSignedMessage::decode(body).map_err(decode_error)?;
+                OutOfBandMessage::Reply(
+                    message
+                        .try_into()
+                        .map_err(TransactionServiceError::InvalidMessageError)?,
+                )
+            },
+            Some(TariMessageType::TransactionFinalized) => {
+                let message = proto::TransactionFinalizedMessage::decode(body).map_err(decode_error)?;
+                let (tx_id, transaction) = message
+                    .into_transaction()
+                    .map_err(TransactionServiceError::InvalidMessageError)?;
+                OutOfBandMessage::Finalized { tx_id, transaction }
+            },
+            Some(TariMessageType::TransactionCancelled) => {
+                let message = proto::TransactionCancelledMessage::decode(body).map_err(decode_error)?;
+                OutOfBandMessage::Cancelled {
+                    tx_id: message.tx_id.into(),
+                }
+            },
+            _ => {
+                return Err(TransactionServiceError::InvalidOutOfBandMessage(format!(
+                    "Unexpected message type {}",
+                    envelope.message_type
+                )))
+            },
+        };
+
+        Ok((source_public_key, message))
+    }
+}
+
+/// Wraps an encoded message, along with the public key of the wallet that created it, in the blob decoded by
+/// [OutOfBandMessage::from_bytes]
+pub(crate) fn encode_envelope(
+    source_public_key: &CommsPublicKey,
+    message_type: TariMessageType,
+    body: Vec<u8>,
+) -> Vec<u8> {
+    OutOfBandEnvelope {
+        version: OUT_OF_BAND_MESSAGE_VERSION,
+        source_public_key: source_public_key.to_vec(),
+        message_type: message_type as i32,
+        body,
+    }
+    .encode_to_vec()
+}
+
+#[derive(Clone, prost::Message)]
+struct OutOfBandEnvelope {
+    #[prost(uint32, tag = "1")]
+    version: u32,
+    #[prost(bytes, tag = "2")]
+    source_public_key: Vec<u8>,
+    #[prost(int32, tag = "3")]
+    message_type: i32,
+    #[prost(bytes, tag = "4")]
+    body: Vec<u8>,
+}
@@ -45,6 +45,8 @@ use tari_service_framework::{
 };
 use tokio::sync::broadcast;
 
+#[cfg(feature = "header_sync")]
+use crate::header_sync::handle::HeaderSyncHandle;
 use crate::{
     base_node_service::handle::BaseNodeServiceHandle,
     connectivity_service::WalletConnectivityHandle,
@@ -53,16 +55,24 @@ use crate::{
     transaction_service::{
         config::TransactionServiceConfig,
         handle::TransactionServiceHandle,
+        messaging::{DhtMessagingBackend, TransactionMessagingBackend},
+        recorder::{recording_cipher, ProtocolRecorder, RecordingMessagingBackend},
         service::TransactionService,
         storage::database::{TransactionBackend, TransactionDatabase},
     },
+    util::supervisor::{run_supervised, RequestRelay, RestartReceiver},
 };
 
+pub mod approval;
 pub mod config;
 pub mod error;
 pub mod handle;
+pub mod mempool_evictions;
+pub mod messaging;
 pub mod protocols;
+pub mod recorder;
 pub mod service;
+pub mod spending_policy;
 pub mod storage;
 pub mod tasks;
 mod utc;
@@ -81,6 +91,10 @@ where
     node_identity: Arc<NodeIdentity>,
     factories: CryptoFactories,
     wallet_database: Option<WalletDatabase<W>>,
+    restart_receiver: Option<RestartReceiver>,
+    messaging: Option<Arc<dyn TransactionMessagingBackend>>,
+    #[cfg(feature = "header_sync")]
+    verify_with_header_sync: bool,
 }
 
 impl<T, W> TransactionServiceInitializer<T, W>
@@ -103,74 +117,127 @@ where
             node_identity,
             factories,
             wallet_database: Some(wallet_database),
+            restart_receiver: None,
+            messaging: None,
+            #[cfg(feature = "header_sync")]
+            verify_with_header_sync: false,
         }
     }
 
-    /// Get a stream of inbound Text messages
-    fn transaction_stream(&self) -> impl Stream<Item = DomainMessage<proto::TransactionSenderMessage>> {
-        trace!(
-            target: LOG_TARGET,
-            "Subscription '{}' for topic '{:?}' created.",
-            SUBSCRIPTION_LABEL,
-            TariMessageType::SenderPartialTransaction
-        );
-        self.subscription_factory
-            .get_subscription(TariMessageType::SenderPartialTransaction, SUBSCRIPTION_LABEL)
-            .map(map_decode::<proto::TransactionSenderMessage>)
-            .filter_map(ok_or_skip_result)
+    /// Allows the service to be restarted through the given receiver's requester
+    pub fn with_restart_receiver(mut self, restart_receiver: RestartReceiver) -> Self {
+        self.restart_receiver = Some(restart_receiver);
+        self
     }
 
-    fn transaction_reply_stream(&self) -> impl Stream<Item = DomainMessage<proto::RecipientSignedMessage>> {
-        trace!(
-            target: LOG_TARGET,
-            "Subscription '{}' for topic '{:?}' created.",
-            SUBSCRIPTION_LABEL,
-            TariMessageType::ReceiverPartialTransactionReply
-        );
-        self.subscription_factory
-            .get_subscription(TariMessageType::ReceiverPartialTransactionReply, SUBSCRIPTION_LABEL)
-            .map(map_decode::<proto::RecipientSignedMessage>)
-            .filter_map(ok_or_skip_result)
+    /// Deliver the transaction negotiation messages through the given backend instead of the DHT
+    pub fn with_messaging_backend(mut self, messaging: Arc<dyn TransactionMessagingBackend>) -> Self {
+        self.messaging = Some(messaging);
+        self
     }
 
-    fn transaction_finalized_stream(&self) -> impl Stream<Item = DomainMessage<proto::TransactionFinalizedMessage>> {
-        trace!(
-            target: LOG_TARGET,
-            "Subscription '{}' for topic '{:?}' created.",
-            SUBSCRIPTION_LABEL,
-            TariMessageType::TransactionFinalized
-        );
-        self.subscription_factory
-            .get_subscription(TariMessageType::TransactionFinalized, SUBSCRIPTION_LABEL)
-            .map(map_decode::<proto::TransactionFinalizedMessage>)
-            .filter_map(ok_or_skip_result)
+    /// Verify mined transactions against the header chain of the header sync service before marking them as
+    /// confirmed
+    #[cfg(feature = "header_sync")]
+    pub fn with_header_sync_verification(mut self) -> Self {
+        self.verify_with_header_sync = true;
+        self
     }
+}
 
-    fn base_node_response_stream(&self) -> impl Stream<Item = DomainMessage<base_node_proto::BaseNodeServiceResponse>> {
-        trace!(
-            target: LOG_TARGET,
-            "Subscription '{}' for topic '{:?}' created.",
-            SUBSCRIPTION_LABEL,
-            TariMessageType::BaseNodeResponse
-        );
-        self.subscription_factory
-            .get_subscription(TariMessageType::BaseNodeResponse, SUBSCRIPTION_LABEL)
-            .map(map_decode::<base_node_proto::BaseNodeServiceResponse>)
-            .filter_map(ok_or_skip_result)
-    }
+/// Subscribes to every message type handled by the transaction service
+fn message_streams(
+    subscription_factory: &SubscriptionFactory,
+) -> (
+    impl Stream<Item = DomainMessage<proto::TransactionSenderMessage>>,
+    impl Stream<Item = DomainMessage<proto::RecipientSignedMessage>>,
+    impl Stream<Item = DomainMessage<proto::TransactionFinalizedMessage>>,
+    impl Stream<Item = DomainMessage<base_node_proto::BaseNodeServiceResponse>>,
+    impl Stream<Item = DomainMessage<proto::TransactionCancelledMessage>>,
+) {
+    (
+        transaction_stream(subscription_factory),
+        transaction_reply_stream(subscription_factory),
+        transaction_finalized_stream(subscription_factory),
+        base_node_response_stream(subscription_factory),
+        transaction_cancelled_stream(subscription_factory),
+    )
+}
 
-    fn transaction_cancelled_stream(&self) -> impl Stream<Item = DomainMessage<proto::TransactionCancelledMessage>> {
-        trace!(
-            target: LOG_TARGET,
-            "Subscription '{}' for topic '{:?}' created.",
-            SUBSCRIPTION_LABEL,
-            TariMessageType::TransactionCancelled
-        );
-        self.subscription_factory
-            .get_subscription(TariMessageType::TransactionCancelled, SUBSCRIPTION_LABEL)
-            .map(map_decode::<proto::TransactionCancelledMessage>)
-            .filter_map(ok_or_skip_result)
-    }
+/// Get a stream of inbound Text messages
+fn transaction_stream(
+    subscription_factory: &SubscriptionFactory,
+) -> impl Stream<Item = DomainMessage<proto::TransactionSenderMessage>> {
+    trace!(
+        target: LOG_TARGET,
+        "Subscription '{}' for topic '{:?}' created.",
+        SUBSCRIPTION_LABEL,
+        TariMessageType::SenderPartialTransaction
+    );
+    subscription_factory
+        .get_subscription(TariMessageType::SenderPartialTransaction, SUBSCRIPTION_LABEL)
+        .map(map_decode::<proto::TransactionSenderMessage>)
+        .filter_map(ok_or_skip_result)
+}
+
+fn transaction_reply_stream(
+    subscription_factory: &SubscriptionFactory,
+) -> impl Stream<Item = DomainMessage<proto::RecipientSignedMessage>> {
+    trace!(
+        target: LOG_TARGET,
+        "Subscription '{}' for topic '{:?}' created.",
+        SUBSCRIPTION_LABEL,
+        TariMessageType::ReceiverPartialTransactionReply
+    );
+    subscription_factory
+        .get_subscription(TariMessageType::ReceiverPartialTransactionReply, SUBSCRIPTION_LABEL)
+        .map(map_decode::<proto::RecipientSignedMessage>)
+        .filter_map(ok_or_skip_result)
+}
+
+fn transaction_finalized_stream(
+    subscription_factory: &SubscriptionFactory,
+) -> impl Stream<Item = DomainMessage<proto::TransactionFinalizedMessage>> {
+    trace!(
+        target: LOG_TARGET,
+        "Subscription '{}' for topic '{:?}' created.",
+        SUBSCRIPTION_LABEL,
+        TariMessageType::TransactionFinalized
+    );
+    subscription_factory
+        .get_subscription(TariMessageType::TransactionFinalized, SUBSCRIPTION_LABEL)
+        .map(map_decode::<proto::TransactionFinalizedMessage>)
+        .filter_map(ok_or_skip_result)
+}
+
+fn base_node_response_stream(
+    subscription_factory: &SubscriptionFactory,
+) -> impl Stream<Item = DomainMessage<base_node_proto::BaseNodeServiceResponse>> {
+    trace!(
+        target: LOG_TARGET,
+        "Subscription '{}' for topic '{:?}' created.",
+        SUBSCRIPTION_LABEL,
+        TariMessageType::BaseNodeResponse
+    );
+    subscription_factory
+        .get_subscription(TariMessageType::BaseNodeResponse, SUBSCRIPTION_LABEL)
+        .map(map_decode::<base_node_proto::BaseNodeServiceResponse>)
+        .filter_map(ok_or_skip_result)
+}
+
+fn transaction_cancelled_stream(
+    subscription_factory: &SubscriptionFactory,
+) -> impl Stream<Item = DomainMessage<proto::TransactionCancelledMessage>> {
+    trace!(
+        target: LOG_TARGET,
+        "Subscription '{}' for topic '{:?}' created.",
+        SUBSCRIPTION_LABEL,
+        TariMessageType::TransactionCancelled
+    );
+    subscription_factory
+        .get_subscription(TariMessageType::TransactionCancelled, SUBSCRIPTION_LABEL)
+        .map(map_decode::<proto::TransactionCancelledMessage>)
+        .filter_map(ok_or_skip_result)
 }
 
 #[async_trait]
@@ -181,11 +248,8 @@ where
 {
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         let (sender, receiver) = reply_channel::unbounded();
-        let transaction_stream = self.transaction_stream();
-        let transaction_reply_stream = self.transaction_reply_stream();
-        let transaction_finalized_stream = self.transaction_finalized_stream();
-        let base_node_response_stream = self.base_node_response_stream();
-        let transaction_cancelled_stream = self.transaction_cancelled_stream();
+        // Subscribe before comms is started so that no messages are missed. Restarted services subscribe again.
+        let mut initial_streams = Some(message_streams(&self.subscription_factory));
 
         let (publisher, _) = broadcast::channel(self.config.transaction_event_channel_size);
 
@@ -207,38 +271,105 @@ where
         let node_identity = self.node_identity.clone();
         let factories = self.factories.clone();
         let config = self.config.clone();
+        let subscription_factory = self.subscription_factory.clone();
+        let restart_receiver = self.restart_receiver.take();
+        let messaging = self.messaging.take();
+        #[cfg(feature = "header_sync")]
+        let verify_with_header_sync = self.verify_with_header_sync;
 
         context.spawn_when_ready(move |handles| async move {
-            let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
+            let messaging = messaging.unwrap_or_else(|| {
+                let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
+                Arc::new(DhtMessagingBackend::new(outbound_message_service, publisher.clone()))
+            });
+            let protocol_recorder =
+                config.protocol_recording_file.as_ref().and_then(|path| {
+                    match ProtocolRecorder::open(path, recording_cipher(node_identity.secret_key())) {
+                        Ok(recorder) => {
+                            info!(
+                                target: LOG_TARGET,
+                                "Recording transaction protocol messages to '{}'",
+                                path.display()
+                            );
+                            Some(recorder)
+                        },
+                        Err(e) => {
+                            error!(
+                                target: LOG_TARGET,
+                                "Transaction protocol messages are not recorded: {}", e
+                            );
+                            None
+                        },
+                    }
+                });
+            let messaging: Arc<dyn TransactionMessagingBackend> = match protocol_recorder.clone() {
+                Some(recorder) => Arc::new(RecordingMessagingBackend::new(messaging, recorder)),
+                None => messaging,
+            };
             let output_manager_service = handles.expect_handle::<OutputManagerHandle>();
             let connectivity = handles.expect_handle::<WalletConnectivityHandle>();
             let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
 
-            let result = TransactionService::new(
-                config,
-                TransactionDatabase::new(tx_backend),
-                wallet_database,
-                receiver,
-                transaction_stream,
-                transaction_reply_stream,
-                transaction_finalized_stream,
-                base_node_response_stream,
-                transaction_cancelled_stream,
-                output_manager_service,
-                outbound_message_service,
-                connectivity,
-                publisher,
-                node_identity,
-                factories,
-                handles.get_shutdown_signal(),
-                base_node_service_handle,
+            #[cfg(feature = "header_sync")]
+            let header_sync = if verify_with_header_sync {
+                Some(handles.expect_handle::<HeaderSyncHandle>())
+            } else {
+                None
+            };
+
+            // Handles keep sending requests through the relay, which follows the service across restarts
+            let request_relay = RequestRelay::spawn(receiver);
+            let shutdown_signal = handles.get_shutdown_signal();
+            run_supervised(
+                "Transaction Service",
+                restart_receiver,
+                shutdown_signal,
+                |shutdown_signal| {
+                    let (
+                        transaction_stream,
+                        transaction_reply_stream,
+                        transaction_finalized_stream,
+                        base_node_response_stream,
+                        transaction_cancelled_stream,
+                    ) = initial_streams
+                        .take()
+                        .unwrap_or_else(|| message_streams(&subscription_factory));
+                    let service = TransactionService::new(
+                        config.clone(),
+                        TransactionDatabase::new(tx_backend.clone()),
+                        wallet_database.clone(),
+                        request_relay.next_receiver(),
+                        transaction_stream,
+                        transaction_reply_stream,
+                        transaction_finalized_stream,
+                        base_node_response_stream,
+                        transaction_cancelled_stream,
+                        output_manager_service.clone(),
+                        messaging.clone(),
+                        connectivity.clone(),
+                        publisher.clone(),
+                        node_identity.clone(),
+                        factories.clone(),
+                        shutdown_signal,
+                        base_node_service_handle.clone(),
+                    );
+                    let service = match protocol_recorder.clone() {
+                        Some(recorder) => service.with_protocol_recorder(recorder),
+                        None => service,
+                    };
+                    #[cfg(feature = "header_sync")]
+                    let service = match header_sync.clone() {
+                        Some(header_sync) => service.with_header_sync(header_sync),
+                        None => service,
+                    };
+                    async move {
+                        if let Err(e) = service.start().await {
+                            error!(target: LOG_TARGET, "Transaction Service error: {}", e);
+                        }
+                    }
+                },
             )
-            .start()
             .await;
-
-            if let Err(e) = result {
-                error!(target: LOG_TARGET, "Transaction Service error: {}", e);
-            }
             info!(target: LOG_TARGET, "Transaction Service shutdown");
         });
 
@@ -27,7 +27,6 @@ use std::{
 };
 
 use futures::FutureExt;
-use log::*;
 use tari_common_types::{
     transaction::{TransactionStatus, TxId},
     types::Signature,
@@ -37,10 +36,13 @@ use tari_core::{
         proto::wallet_rpc::{TxLocation, TxQueryResponse, TxSubmissionRejectionReason, TxSubmissionResponse},
         rpc::BaseNodeWalletRpcClient,
     },
-    transactions::transaction_components::Transaction,
+    mempool::MAX_TRANSACTION_PACKAGE_SIZE,
+    proto::base_node::TransactionPackage,
+    transactions::{tari_amount::MicroTari, transaction_components::Transaction},
 };
 use tari_utilities::hex::Hex;
 use tokio::{sync::watch, time::sleep};
+use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::{
     connectivity_service::WalletConnectivityInterface,
@@ -63,6 +65,8 @@ pub struct TransactionBroadcastProtocol<TBackend, TWalletConnectivity> {
     resources: TransactionServiceResources<TBackend, TWalletConnectivity>,
     timeout_update_receiver: watch::Receiver<Duration>,
     last_rejection: Option<Instant>,
+    /// The number of RPC calls to the base node that failed in a row
+    failed_attempts: u32,
 }
 
 impl<TBackend, TWalletConnectivity> TransactionBroadcastProtocol<TBackend, TWalletConnectivity>
@@ -81,10 +85,16 @@ where
             resources,
             timeout_update_receiver,
             last_rejection: None,
+            failed_attempts: 0,
         }
     }
 
     /// The task that defines the execution of the protocol.
+    #[instrument(
+        name = "transaction_service::broadcast_protocol",
+        skip_all,
+        fields(service = "transaction_service", tx_id = %self.tx_id)
+    )]
     pub async fn execute(mut self) -> Result<TxId, TransactionServiceProtocolError<TxId>> {
         let mut shutdown = self.resources.shutdown_signal.clone();
         let mut current_base_node_watcher = self.resources.connectivity.get_current_base_node_watcher();
@@ -154,7 +164,25 @@ where
                         }
                         // Wait out the remainder of the delay before proceeding with next loop
                         drop(client);
-                        let delay = *timeout_update_receiver.borrow();
+                        let delay = if self.failed_attempts > 0 {
+                            let policy = &self.resources.config.broadcast_retry;
+                            if !policy.should_retry(self.failed_attempts) {
+                                warn!(
+                                    target: LOG_TARGET,
+                                    "Transaction Broadcast protocol (TxId: {}) gave up after {} failed calls to the base \
+                                     node",
+                                    self.tx_id,
+                                    self.failed_attempts
+                                );
+                                return Err(TransactionServiceProtocolError::new(
+                                    self.tx_id,
+                                    TransactionServiceError::MaximumAttemptsExceeded,
+                                ));
+                            }
+                            policy.delay(self.failed_attempts)
+                        } else {
+                            *timeout_update_receiver.borrow()
+                        };
                         sleep(delay).await;
                         break;
                     },
@@ -180,33 +208,57 @@ where
     /// `Ok(false)` => There was a problem with the RPC call and this should be retried
     /// `Err(_)` => The transaction was rejected by the base node and the protocol should end.
     #[allow(clippy::too_many_lines)]
+    #[instrument(level = "debug", skip_all, fields(stage = "submit_transaction"))]
     async fn submit_transaction(
         &mut self,
         tx: Transaction,
         client: &mut BaseNodeWalletRpcClient,
     ) -> Result<bool, TransactionServiceProtocolError<TxId>> {
-        let response = match client
-            .submit_transaction(tx.try_into().map_err(|e| {
+        let mut response = match client
+            .submit_transaction(tx.clone().try_into().map_err(|e| {
                 TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::InvalidMessageError(e))
             })?)
             .await
         {
-            Ok(r) => match TxSubmissionResponse::try_from(r) {
-                Ok(r) => r,
-                Err(_) => {
-                    trace!(target: LOG_TARGET, "Could not convert proto TxSubmission Response");
-                    return Ok(false);
-                },
+            Ok(r) => {
+                self.failed_attempts = 0;
+                match TxSubmissionResponse::try_from(r) {
+                    Ok(r) => r,
+                    Err(_) => {
+                        trace!(target: LOG_TARGET, "Could not convert proto TxSubmission Response");
+                        return Ok(false);
+                    },
+                }
             },
             Err(e) => {
                 info!(
                     target: LOG_TARGET,
                     "Submit Transaction RPC Call to Base Node failed: {}", e
                 );
+                self.failed_attempts += 1;
                 return Ok(false);
             },
         };
 
+        // A transaction that spends the change of unmined transactions is an orphan to a base node that has not seen
+        // them, so they are submitted together as a package
+        if response.rejection_reason == TxSubmissionRejectionReason::Orphan {
+            let parents = self.unmined_parents(&tx);
+            if !parents.is_empty() {
+                info!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) spends the outputs of {} unmined transaction(s), submitting them as a \
+                     package",
+                    self.tx_id,
+                    parents.len()
+                );
+                response = match self.submit_transaction_package(parents, tx, client).await? {
+                    Some(response) => response,
+                    None => return Ok(false),
+                };
+            }
+        }
+
         if !response.is_synced {
             info!(
                 target: LOG_TARGET,
@@ -238,6 +290,10 @@ where
                     TransactionServiceError::MempoolRejectionTimeLocked,
                     TxCancellationReason::TimeLocked,
                 ),
+                TxSubmissionRejectionReason::FeeTooLow => (
+                    TransactionServiceError::MempoolRejectionFeeTooLow,
+                    TxCancellationReason::InvalidTransaction,
+                ),
                 _ => (
                     TransactionServiceError::UnexpectedBaseNodeResponse,
                     TxCancellationReason::Unknown,
@@ -293,37 +349,122 @@ where
         Ok(true)
     }
 
+    /// Submits the transaction together with the unmined transactions whose outputs it spends. Returns None if the RPC
+    /// call failed and the submission should be retried.
+    #[instrument(level = "debug", skip_all, fields(stage = "submit_transaction_package"))]
+    async fn submit_transaction_package(
+        &self,
+        parents: Vec<Transaction>,
+        tx: Transaction,
+        client: &mut BaseNodeWalletRpcClient,
+    ) -> Result<Option<TxSubmissionResponse>, TransactionServiceProtocolError<TxId>> {
+        let transactions = parents
+            .into_iter()
+            .chain(Some(tx))
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::InvalidMessageError(e))
+            })?;
+        match client
+            .submit_transaction_package(TransactionPackage { transactions })
+            .await
+        {
+            Ok(r) => match TxSubmissionResponse::try_from(r) {
+                Ok(r) => Ok(Some(r)),
+                Err(_) => {
+                    trace!(target: LOG_TARGET, "Could not convert proto TxSubmission Response");
+                    Ok(None)
+                },
+            },
+            Err(e) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Submit Transaction Package RPC Call to Base Node failed: {}", e
+                );
+                Ok(None)
+            },
+        }
+    }
+
+    /// The unmined transactions of the wallet whose outputs `tx` spends, directly or through other unmined
+    /// transactions, ancestors first. At most enough ancestors to fill a transaction package are returned.
+    fn unmined_parents(&self, tx: &Transaction) -> Vec<Transaction> {
+        let unmined = match self.resources.db.get_completed_transactions() {
+            Ok(txs) => txs
+                .into_values()
+                .filter(|t| {
+                    t.tx_id != self.tx_id &&
+                        matches!(t.status, TransactionStatus::Completed | TransactionStatus::Broadcast)
+                })
+                .map(|t| t.transaction)
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not fetch the completed transactions to find the parents of TxId: {}: {}", self.tx_id, e
+                );
+                return vec![];
+            },
+        };
+
+        let mut parents = Vec::new();
+        let mut spent_outputs = tx.body.inputs().iter().map(|i| i.output_hash()).collect::<Vec<_>>();
+        while let Some(hash) = spent_outputs.pop() {
+            if parents.len() + 1 >= MAX_TRANSACTION_PACKAGE_SIZE {
+                break;
+            }
+            let parent = unmined
+                .iter()
+                .find(|t| t.body.outputs().iter().any(|o| o.hash() == hash));
+            if let Some(parent) = parent {
+                if !parents.contains(parent) {
+                    spent_outputs.extend(parent.body.inputs().iter().map(|i| i.output_hash()));
+                    parents.push(parent.clone());
+                }
+            }
+        }
+        parents.reverse();
+        parents
+    }
+
     /// Attempt to query the location of the transaction from the base node via RPC.
     /// # Returns:
     /// `Ok(true)` => Transaction was successfully mined and confirmed
     /// `Ok(false)` => There was a problem with the RPC call or the transaction is not mined but still in the mempool
     /// and this should be retried `Err(_)` => The transaction was rejected by the base node and the protocol should
     /// end.
+    #[instrument(level = "debug", skip_all, fields(stage = "transaction_query"))]
     async fn transaction_query(
         &mut self,
         signature: Signature,
+        amount: MicroTari,
         client: &mut BaseNodeWalletRpcClient,
     ) -> Result<bool, TransactionServiceProtocolError<TxId>> {
         let response = match client.transaction_query(signature.into()).await {
-            Ok(r) => match TxQueryResponse::try_from(r) {
-                Ok(r) => r,
-                Err(_) => {
-                    trace!(target: LOG_TARGET, "Could not convert proto TxQueryResponse");
-                    return Ok(false);
-                },
+            Ok(r) => {
+                self.failed_attempts = 0;
+                match TxQueryResponse::try_from(r) {
+                    Ok(r) => r,
+                    Err(_) => {
+                        trace!(target: LOG_TARGET, "Could not convert proto TxQueryResponse");
+                        return Ok(false);
+                    },
+                }
             },
             Err(e) => {
                 info!(
                     target: LOG_TARGET,
                     "Transaction Query RPC Call to Base Node failed: {}", e
                 );
+                self.failed_attempts += 1;
                 return Ok(false);
             },
         };
 
         if !(response.is_synced ||
             (response.location == TxLocation::Mined &&
-                response.confirmations >= self.resources.config.num_confirmations_required as u64))
+                response.confirmations >= self.resources.config.confirmations_required(amount)))
         {
             info!(
                 target: LOG_TARGET,
@@ -413,10 +554,12 @@ where
                 target: LOG_TARGET,
                 "Querying Transaction (TxId: {}) status on Base Node", self.tx_id
             );
-            self.transaction_query(signature.clone(), client).await
+            self.transaction_query(signature.clone(), completed_transaction.amount, client)
+                .await
         }
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "cancel_transaction"))]
     async fn cancel_transaction(&mut self, reason: TxCancellationReason) {
         if let Err(e) = self
             .resources
@@ -20,11 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use futures::future::FutureExt;
-use log::*;
 use tari_common_types::{
     transaction::{TransactionDirection, TransactionStatus, TxId},
     types::HashOutput,
@@ -38,6 +37,7 @@ use tokio::{
     sync::{mpsc, oneshot},
     time::sleep,
 };
+use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::{
     connectivity_service::WalletConnectivityInterface,
@@ -47,10 +47,17 @@ use crate::{
         service::TransactionServiceResources,
         storage::{
             database::TransactionBackend,
-            models::{CompletedTransaction, InboundTransaction, TxCancellationReason},
+            models::{
+                CompletedTransaction,
+                InboundTransaction,
+                TransactionNegotiationStage,
+                TransactionProtocolSnapshot,
+                TransactionProtocolState,
+                TxCancellationReason,
+            },
         },
         tasks::send_transaction_reply::send_transaction_reply,
-        utc::utc_duration_since,
+        utc::{utc_duration_since, utc_duration_until},
     },
 };
 
@@ -59,6 +66,7 @@ const LOG_TARGET: &str = "wallet::transaction_service::protocols::receive_protoc
 #[derive(Debug, PartialEq)]
 pub enum TransactionReceiveProtocolStage {
     Initial,
+    SendReply,
     WaitForFinalize,
 }
 
@@ -103,6 +111,11 @@ where
         }
     }
 
+    #[instrument(
+        name = "transaction_service::receive_protocol",
+        skip_all,
+        fields(service = "transaction_service", tx_id = %self.id, initial_stage = ?self.stage)
+    )]
     pub async fn execute(mut self) -> Result<TxId, TransactionServiceProtocolError<TxId>> {
         info!(
             target: LOG_TARGET,
@@ -114,6 +127,15 @@ where
                 self.accept_transaction().await?;
                 self.wait_for_finalization().await?;
             },
+            TransactionReceiveProtocolStage::SendReply => {
+                let inbound_transaction = self
+                    .resources
+                    .db
+                    .get_pending_inbound_transaction(self.id)
+                    .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+                self.send_reply(inbound_transaction).await?;
+                self.wait_for_finalization().await?;
+            },
             TransactionReceiveProtocolStage::WaitForFinalize => {
                 self.wait_for_finalization().await?;
             },
@@ -122,6 +144,7 @@ where
         Ok(self.id)
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "accept_transaction"))]
     async fn accept_transaction(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         // Currently we will only reply to a Single sender transaction protocol
         if let TransactionSenderMessage::Single(data) = self.sender_message.clone() {
@@ -144,6 +167,25 @@ where
                 ));
             }
 
+            // A transaction that expired in transit is not accepted, so no outputs are created for it
+            #[allow(clippy::cast_possible_wrap)]
+            let expires_at = match data.expires_at {
+                Some(expires_at) => match NaiveDateTime::from_timestamp_opt(expires_at as i64, 0) {
+                    Some(expires_at) if utc_duration_until(&expires_at).is_some() => Some(expires_at),
+                    _ => {
+                        info!(
+                            target: LOG_TARGET,
+                            "Received Transaction (TxId: {}) expired before it was accepted", data.tx_id
+                        );
+                        return Err(TransactionServiceProtocolError::new(
+                            self.id,
+                            TransactionServiceError::TransactionExpired,
+                        ));
+                    },
+                },
+                None => None,
+            };
+
             let amount = data.amount;
 
             let rtp = self
@@ -161,40 +203,16 @@ where
                 TransactionStatus::Pending,
                 data.message.clone(),
                 Utc::now().naive_utc(),
-            );
-
-            self.resources
-                .db
-                .add_pending_inbound_transaction(inbound_transaction.tx_id, inbound_transaction.clone())
-                .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
-
-            let send_result = send_transaction_reply(
-                inbound_transaction,
-                self.resources.outbound_message_service.clone(),
-                self.resources.config.direct_send_timeout,
-                self.resources.config.transaction_routing_mechanism,
             )
-            .await
-            .map_err(|e| TransactionServiceProtocolError::new(self.id, e))?;
+            .with_expiry(expires_at);
 
             self.resources
                 .db
-                .increment_send_count(self.id)
+                .add_pending_inbound_transaction(inbound_transaction.tx_id, inbound_transaction.clone())
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+            self.save_protocol_state(TransactionNegotiationStage::ReceiverReplyPending, &inbound_transaction)?;
 
-            if send_result {
-                info!(
-                    target: LOG_TARGET,
-                    "Transaction with TX_ID = {} received from {}. Reply Sent", data.tx_id, self.source_pubkey,
-                );
-            } else {
-                error!(
-                    target: LOG_TARGET,
-                    "Transaction with TX_ID = {} received from {}. Reply could not be sent!",
-                    data.tx_id,
-                    self.source_pubkey,
-                );
-            }
+            self.send_reply(inbound_transaction).await?;
 
             trace!(
                 target: LOG_TARGET,
@@ -221,7 +239,62 @@ where
         }
     }
 
+    /// Send the reply for the accepted transaction to the sender. Once the reply has been sent the protocol state is
+    /// advanced so that a restart will only wait for the finalized transaction.
+    #[instrument(level = "debug", skip_all, fields(stage = "send_reply"))]
+    async fn send_reply(
+        &mut self,
+        inbound_transaction: InboundTransaction,
+    ) -> Result<(), TransactionServiceProtocolError<TxId>> {
+        let send_result = send_transaction_reply(
+            inbound_transaction.clone(),
+            self.resources.messaging.clone(),
+            self.resources.config.clone(),
+        )
+        .await
+        .map_err(|e| TransactionServiceProtocolError::new(self.id, e))?;
+
+        self.resources
+            .db
+            .increment_send_count(self.id)
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
+        if send_result {
+            self.save_protocol_state(
+                TransactionNegotiationStage::ReceiverWaitForFinalize,
+                &inbound_transaction,
+            )?;
+            info!(
+                target: LOG_TARGET,
+                "Transaction with TX_ID = {} received from {}. Reply Sent", self.id, self.source_pubkey,
+            );
+        } else {
+            error!(
+                target: LOG_TARGET,
+                "Transaction with TX_ID = {} received from {}. Reply could not be sent!", self.id, self.source_pubkey,
+            );
+        }
+        Ok(())
+    }
+
+    fn save_protocol_state(
+        &self,
+        stage: TransactionNegotiationStage,
+        inbound_transaction: &InboundTransaction,
+    ) -> Result<(), TransactionServiceProtocolError<TxId>> {
+        self.resources
+            .db
+            .save_transaction_protocol_state(TransactionProtocolState::new(
+                self.id,
+                stage,
+                self.source_pubkey.clone(),
+                TransactionProtocolSnapshot::Receiver(Box::new(inbound_transaction.receiver_protocol.clone())),
+            ))
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))
+    }
+
     #[allow(clippy::too_many_lines)]
+    #[instrument(level = "debug", skip_all, fields(stage = "wait_for_finalization"))]
     async fn wait_for_finalization(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         let mut receiver = self
             .transaction_finalize_receiver
@@ -250,12 +323,16 @@ where
         let elapsed_time = utc_duration_since(&inbound_tx.timestamp)
             .map_err(|e| TransactionServiceProtocolError::new(self.id, e.into()))?;
 
+        // A transaction that expires before the timeout is cancelled when it expires
         let timeout_duration = match self
             .resources
             .config
             .pending_transaction_cancellation_timeout
             .checked_sub(elapsed_time)
-        {
+            .and_then(|timeout| match inbound_tx.expires_at {
+                Some(expires_at) => utc_duration_until(&expires_at).map(|remaining| remaining.min(timeout)),
+                None => Some(timeout),
+            }) {
             None => {
                 // This will cancel the transaction and exit this protocol
                 return self.timeout_transaction().await;
@@ -278,9 +355,8 @@ where
         if resend {
             if let Err(e) = send_transaction_reply(
                 inbound_tx.clone(),
-                self.resources.outbound_message_service.clone(),
-                self.resources.config.direct_send_timeout,
-                self.resources.config.transaction_routing_mechanism,
+                self.resources.messaging.clone(),
+                self.resources.config.clone(),
             )
             .await
             {
@@ -295,6 +371,24 @@ where
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
         }
 
+        // Ask the sender for the finalized transaction again if it has not arrived in time, rather than waiting for
+        // the next resend
+        let since_last_send = match inbound_tx.last_send_timestamp {
+            Some(timestamp) if !resend => {
+                utc_duration_since(&timestamp).map_err(|e| TransactionServiceProtocolError::new(self.id, e.into()))?
+            },
+            _ => Duration::ZERO,
+        };
+        let finalization_request_delay = sleep(
+            self.resources
+                .config
+                .finalization_request_timeout
+                .saturating_sub(since_last_send),
+        )
+        .fuse();
+        tokio::pin!(finalization_request_delay);
+        let mut finalization_requested = false;
+
         let mut shutdown = self.resources.shutdown_signal.clone();
 
         #[allow(unused_assignments)]
@@ -327,9 +421,8 @@ where
                     _ = resend_timeout => {
                         match send_transaction_reply(
                             inbound_tx.clone(),
-                            self.resources.outbound_message_service.clone(),
-                            self.resources.config.direct_send_timeout,
-                            self.resources.config.transaction_routing_mechanism,
+                            self.resources.messaging.clone(),
+                            self.resources.config.clone(),
                         )
                         .await {
                             Ok(_) => self.resources
@@ -342,6 +435,28 @@ where
                                         ),
                         }
                     },
+                    _ = &mut finalization_request_delay, if !finalization_requested => {
+                        finalization_requested = true;
+                        info!(
+                            target: LOG_TARGET,
+                            "Finalized Transaction (TxId: {}) has not been received, asking the sender to resend it", self.id
+                        );
+                        match send_transaction_reply(
+                            inbound_tx.clone(),
+                            self.resources.messaging.clone(),
+                            self.resources.config.clone(),
+                        )
+                        .await {
+                            Ok(_) => self.resources
+                                        .db
+                                        .increment_send_count(self.id)
+                                        .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?,
+                            Err(e) => warn!(
+                                            target: LOG_TARGET,
+                                            "Error requesting Finalized Transaction (TxId: {}): {:?}", self.id, e
+                                        ),
+                        }
+                    },
                     _ = &mut timeout_delay => {
                         return self.timeout_transaction().await;
                     }
@@ -474,6 +589,7 @@ where
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "timeout_transaction"))]
     async fn timeout_transaction(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         info!(
             target: LOG_TARGET,
@@ -22,58 +22,55 @@
 
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use futures::FutureExt;
-use log::*;
 use tari_common_types::{
     transaction::{TransactionDirection, TransactionStatus, TxId},
     types::HashOutput,
 };
 use tari_comms::types::CommsPublicKey;
-use tari_comms_dht::{
-    domain_message::OutboundDomainMessage,
-    outbound::{OutboundEncryption, SendMessageResponse},
-};
 use tari_core::{
     covenants::Covenant,
     transactions::{
         tari_amount::MicroTari,
         transaction_components::OutputFeatures,
-        transaction_protocol::{
-            proto::protocol as proto,
-            recipient::RecipientSignedMessage,
-            sender::SingleRoundSenderData,
-            TransactionMetadata,
-        },
+        transaction_protocol::{recipient::RecipientSignedMessage, sender::SingleRoundSenderData, TransactionMetadata},
         SenderTransactionProtocol,
     },
 };
-use tari_p2p::tari_message::TariMessageType;
 use tari_script::script;
 use tokio::{
     sync::{mpsc::Receiver, oneshot},
     time::sleep,
 };
+use tracing::{error, info, instrument, trace, warn};
 
 use crate::{
     connectivity_service::WalletConnectivityInterface,
-    output_manager_service::UtxoSelectionCriteria,
+    output_manager_service::{error::OutputManagerError, UtxoSelectionCriteria},
     transaction_service::{
-        config::TransactionRoutingMechanism,
         error::{TransactionServiceError, TransactionServiceProtocolError},
         handle::{TransactionEvent, TransactionSendStatus, TransactionServiceResponse},
+        messaging::MessageSendResult,
         service::{TransactionSendResult, TransactionServiceResources},
         storage::{
             database::TransactionBackend,
-            models::{CompletedTransaction, OutboundTransaction, TxCancellationReason},
+            models::{
+                CompletedTransaction,
+                OutboundTransaction,
+                TransactionNegotiationStage,
+                TransactionProtocolSnapshot,
+                TransactionProtocolState,
+                TxCancellationReason,
+            },
         },
         tasks::{
             send_finalized_transaction::send_finalized_transaction_message,
             send_transaction_cancelled::send_transaction_cancelled_message,
-            wait_on_dial::wait_on_dial,
         },
-        utc::utc_duration_since,
+        utc::{utc_duration_since, utc_duration_until},
     },
+    util::cancellation::CancellationToken,
 };
 
 const LOG_TARGET: &str = "wallet::transaction_service::protocols::send_protocol";
@@ -95,11 +92,12 @@ pub struct TransactionSendProtocol<TBackend, TWalletConnectivity> {
     stage: TransactionSendProtocolStage,
     resources: TransactionServiceResources<TBackend, TWalletConnectivity>,
     transaction_reply_receiver: Option<Receiver<(CommsPublicKey, RecipientSignedMessage)>>,
-    cancellation_receiver: Option<oneshot::Receiver<()>>,
+    cancellation: CancellationToken,
     prev_header: Option<HashOutput>,
     height: Option<u64>,
     tx_meta: TransactionMetadata,
     sender_protocol: Option<SenderTransactionProtocol>,
+    expires_at: Option<NaiveDateTime>,
 }
 
 impl<TBackend, TWalletConnectivity> TransactionSendProtocol<TBackend, TWalletConnectivity>
@@ -111,7 +109,7 @@ where
         id: TxId,
         resources: TransactionServiceResources<TBackend, TWalletConnectivity>,
         transaction_reply_receiver: Receiver<(CommsPublicKey, RecipientSignedMessage)>,
-        cancellation_receiver: oneshot::Receiver<()>,
+        cancellation: CancellationToken,
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
         fee_per_gram: MicroTari,
@@ -124,12 +122,13 @@ where
         prev_header: Option<HashOutput>,
         height: Option<u64>,
         sender_protocol: Option<SenderTransactionProtocol>,
+        expires_at: Option<NaiveDateTime>,
     ) -> Self {
         Self {
             id,
             resources,
             transaction_reply_receiver: Some(transaction_reply_receiver),
-            cancellation_receiver: Some(cancellation_receiver),
+            cancellation,
             dest_pubkey,
             amount,
             fee_per_gram,
@@ -140,10 +139,16 @@ where
             height,
             tx_meta,
             sender_protocol,
+            expires_at,
         }
     }
 
     /// Execute the Transaction Send Protocol as an async task.
+    #[instrument(
+        name = "transaction_service::send_protocol",
+        skip_all,
+        fields(service = "transaction_service", tx_id = %self.id, initial_stage = ?self.stage)
+    )]
     pub async fn execute(
         mut self,
     ) -> Result<crate::transaction_service::service::TransactionSendResult, TransactionServiceProtocolError<TxId>> {
@@ -154,14 +159,40 @@ where
 
         let transaction_status = match self.stage {
             TransactionSendProtocolStage::Initial => {
-                let sender_protocol = self.prepare_transaction().await?;
-                let status = self.initial_send_transaction(sender_protocol).await?;
+                // The send is aborted if it is cancelled, or its deadline is reached, before the transaction has been
+                // sent to the recipient
+                let cancellation = self.cancellation.clone();
+                let status = tokio::select! {
+                    status = self.prepare_and_send_transaction() => status,
+                    () = cancellation.cancelled() => Err(TransactionServiceProtocolError::new(
+                        self.id,
+                        TransactionServiceError::TransactionCancelled,
+                    )),
+                };
+                let status = match status {
+                    Err(TransactionServiceProtocolError {
+                        error: TransactionServiceError::TransactionCancelled,
+                        ..
+                    }) => return Err(self.abort_send().await),
+                    status => status?,
+                };
                 if status == TransactionStatus::Pending {
                     self.wait_for_reply().await?;
                 }
                 status
             },
             TransactionSendProtocolStage::Queued => {
+                // A transaction that expired while it was queued is cancelled without being sent
+                if self
+                    .expires_at
+                    .map_or(false, |expires_at| utc_duration_until(&expires_at).is_none())
+                {
+                    self.timeout_transaction().await?;
+                    return Ok(TransactionSendResult {
+                        tx_id: self.id,
+                        transaction_status: TransactionStatus::Queued,
+                    });
+                }
                 if let Some(mut sender_protocol) = self.sender_protocol.clone() {
                     if sender_protocol.is_collecting_single_signature() {
                         sender_protocol
@@ -198,28 +229,33 @@ where
         })
     }
 
+    async fn prepare_and_send_transaction(
+        &mut self,
+    ) -> Result<TransactionStatus, TransactionServiceProtocolError<TxId>> {
+        let sender_protocol = self.prepare_transaction().await?;
+        self.initial_send_transaction(sender_protocol).await
+    }
+
     // Prepare transaction to send and encumber the unspent outputs to use as inputs
+    #[instrument(level = "debug", skip_all, fields(stage = "prepare_transaction"))]
     async fn prepare_transaction(
         &mut self,
     ) -> Result<SenderTransactionProtocol, TransactionServiceProtocolError<TxId>> {
-        let service_reply_channel = match self.service_request_reply_channel.take() {
-            Some(src) => src,
-            None => {
-                error!(
-                    target: LOG_TARGET,
-                    "Service Reply Channel not provided for new Send Transaction Protocol"
-                );
-                return Err(TransactionServiceProtocolError::new(
-                    self.id,
-                    TransactionServiceError::ProtocolChannelError,
-                ));
-            },
-        };
+        if self.service_request_reply_channel.is_none() {
+            error!(
+                target: LOG_TARGET,
+                "Service Reply Channel not provided for new Send Transaction Protocol"
+            );
+            return Err(TransactionServiceProtocolError::new(
+                self.id,
+                TransactionServiceError::ProtocolChannelError,
+            ));
+        }
 
-        match self
+        let result = self
             .resources
             .output_manager_service
-            .prepare_transaction_to_send(
+            .prepare_transaction_to_send_with_cancellation(
                 self.id,
                 self.amount,
                 UtxoSelectionCriteria::default(),
@@ -230,9 +266,19 @@ where
                 script!(Nop),
                 Covenant::default(),
                 MicroTari::zero(),
+                self.cancellation.clone(),
             )
-            .await
-        {
+            .await;
+        // The reply is sent when the send is aborted if it was cancelled while the transaction was being prepared
+        if let Err(OutputManagerError::TransactionPreparationCancelled) = result {
+            return Err(TransactionServiceProtocolError::new(
+                self.id,
+                TransactionServiceError::TransactionCancelled,
+            ));
+        }
+        let service_reply_channel = self.service_request_reply_channel.take().expect("Cannot be missing");
+
+        match result {
             Ok(sp) => {
                 let _result = service_reply_channel
                     .send(Ok(TransactionServiceResponse::TransactionSent(self.id)))
@@ -259,6 +305,7 @@ where
     }
 
     #[allow(clippy::too_many_lines)]
+    #[instrument(level = "debug", skip_all, fields(stage = "initial_send_transaction"))]
     async fn initial_send_transaction(
         &mut self,
         mut sender_protocol: SenderTransactionProtocol,
@@ -332,7 +379,8 @@ where
                 self.message.clone(),
                 Utc::now().naive_utc(),
                 direct_send_result,
-            );
+            )
+            .with_expiry(self.expires_at);
             self.resources
                 .db
                 .add_pending_outbound_transaction(outbound_tx.tx_id, outbound_tx)
@@ -345,6 +393,22 @@ where
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
         }
 
+        // Record how far the negotiation got so that a restart resumes from this point
+        let stage = if transaction_status == TransactionStatus::Pending {
+            TransactionNegotiationStage::SenderWaitForReply
+        } else {
+            TransactionNegotiationStage::SenderQueued
+        };
+        self.resources
+            .db
+            .save_transaction_protocol_state(TransactionProtocolState::new(
+                self.id,
+                stage,
+                self.dest_pubkey.clone(),
+                TransactionProtocolSnapshot::Sender(Box::new(sender_protocol)),
+            ))
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
         // Notify subscribers
         let _size = self
             .resources
@@ -373,6 +437,7 @@ where
     }
 
     #[allow(clippy::too_many_lines)]
+    #[instrument(level = "debug", skip_all, fields(stage = "wait_for_reply"))]
     async fn wait_for_reply(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         // Waiting  for Transaction Reply
         let tx_id = self.id;
@@ -381,11 +446,10 @@ where
             .take()
             .ok_or_else(|| TransactionServiceProtocolError::new(self.id, TransactionServiceError::InvalidStateError))?;
 
-        let mut cancellation_receiver = self
-            .cancellation_receiver
-            .take()
-            .ok_or_else(|| TransactionServiceProtocolError::new(self.id, TransactionServiceError::InvalidStateError))?
-            .fuse();
+        // The deadline of the cancellation only applies until the transaction has been sent
+        let cancellation = self.cancellation.without_deadline();
+        let cancelled = cancellation.cancelled();
+        tokio::pin!(cancelled);
 
         let mut outbound_tx = self
             .resources
@@ -408,12 +472,16 @@ where
         let elapsed_time = utc_duration_since(&outbound_tx.timestamp)
             .map_err(|e| TransactionServiceProtocolError::new(self.id, e.into()))?;
 
+        // A transaction that expires before the timeout is cancelled when it expires
         let timeout_duration = match self
             .resources
             .config
             .pending_transaction_cancellation_timeout
             .checked_sub(elapsed_time)
-        {
+            .and_then(|timeout| match outbound_tx.expires_at {
+                Some(expires_at) => utc_duration_until(&expires_at).map(|remaining| remaining.min(timeout)),
+                None => Some(timeout),
+            }) {
             None => {
                 // This will cancel the transaction and exit this protocol
                 return self.timeout_transaction().await;
@@ -479,29 +547,27 @@ where
                         break;
                     }
                 },
-                result = &mut cancellation_receiver => {
-                    if result.is_ok() {
-                        info!(target: LOG_TARGET, "Cancelling Transaction Send Protocol (TxId: {})", self.id);
-                        let _ = send_transaction_cancelled_message(
-                            self.id,self.dest_pubkey.clone(),
-                            self.resources.outbound_message_service.clone(), )
-                        .await.map_err(|e| {
-                            warn!(
-                                target: LOG_TARGET,
-                                "Error sending Transaction Cancelled (TxId: {}) message: {:?}", self.id, e
-                            )
-                        });
-                        self.resources
-                            .db
-                            .increment_send_count(self.id)
-                            .map_err(|e| TransactionServiceProtocolError::new(
-                                self.id, TransactionServiceError::from(e))
-                            )?;
-                        return Err(TransactionServiceProtocolError::new(
-                            self.id,
-                            TransactionServiceError::TransactionCancelled,
-                        ));
-                    }
+                () = &mut cancelled => {
+                    info!(target: LOG_TARGET, "Cancelling Transaction Send Protocol (TxId: {})", self.id);
+                    let _ = send_transaction_cancelled_message(
+                        self.id,self.dest_pubkey.clone(),
+                        self.resources.messaging.clone(), )
+                    .await.map_err(|e| {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Error sending Transaction Cancelled (TxId: {}) message: {:?}", self.id, e
+                        )
+                    });
+                    self.resources
+                        .db
+                        .increment_send_count(self.id)
+                        .map_err(|e| TransactionServiceProtocolError::new(
+                            self.id, TransactionServiceError::from(e))
+                        )?;
+                    return Err(TransactionServiceProtocolError::new(
+                        self.id,
+                        TransactionServiceError::TransactionCancelled,
+                    ));
                 },
                 () = resend_timeout => {
                     match self.send_transaction(
@@ -597,9 +663,8 @@ where
             tx_id,
             tx.clone(),
             self.dest_pubkey.clone(),
-            self.resources.outbound_message_service.clone(),
-            self.resources.config.direct_send_timeout,
-            self.resources.config.transaction_routing_mechanism,
+            self.resources.messaging.clone(),
+            self.resources.config.clone(),
         )
         .await
         .map_err(|e| TransactionServiceProtocolError::new(self.id, e))?;
@@ -625,176 +690,30 @@ where
         Ok(())
     }
 
-    /// Attempt to send the transaction to the recipient either directly, via Store-and-forward or both as per config
-    /// setting. If the selected sending mechanism fail to send the transaction will be cancelled.
-    /// # Argumentswallet_sync_with_base_node
+    /// Attempt to send the transaction to the recipient through the messaging backend. If the message could not be
+    /// sent the transaction stays queued and will be resent later.
+    /// # Arguments
     /// `msg`: The transaction data message to be sent
+    #[allow(clippy::cast_sign_loss)]
     async fn send_transaction(
         &mut self,
-        msg: SingleRoundSenderData,
+        mut msg: SingleRoundSenderData,
     ) -> Result<SendResult, TransactionServiceProtocolError<TxId>> {
-        let mut result = SendResult {
-            direct_send_result: false,
-            store_and_forward_send_result: false,
-            transaction_status: TransactionStatus::Queued,
-        };
-
-        match self.resources.config.transaction_routing_mechanism {
-            TransactionRoutingMechanism::DirectOnly | TransactionRoutingMechanism::DirectAndStoreAndForward => {
-                result = self.send_transaction_direct(msg.clone()).await?;
-            },
-            TransactionRoutingMechanism::StoreAndForwardOnly => {
-                result.store_and_forward_send_result = self.send_transaction_store_and_forward(msg.clone()).await?;
-                if result.store_and_forward_send_result {
-                    result.transaction_status = TransactionStatus::Pending;
-                }
-            },
-        };
-
-        Ok(result)
-    }
-
-    /// Attempt to send the transaction to the recipient both directly and via Store-and-forward. If both fail to send
-    /// the transaction will be cancelled.
-    /// # Argumentswallet_sync_with_base_node
-    /// `msg`: The transaction data message to be sent
-    #[allow(clippy::too_many_lines)]
-    async fn send_transaction_direct(
-        &mut self,
-        msg: SingleRoundSenderData,
-    ) -> Result<SendResult, TransactionServiceProtocolError<TxId>> {
-        let proto_message = proto::TransactionSenderMessage::single(msg.clone().into());
-        let mut store_and_forward_send_result = false;
-        let mut direct_send_result = false;
-        let mut transaction_status = TransactionStatus::Queued;
-
-        info!(
-            target: LOG_TARGET,
-            "Attempting to Send Transaction (TxId: {}) to recipient with Public Key: {}", self.id, self.dest_pubkey,
-        );
-
-        match self
+        msg.expires_at = self.expires_at.map(|expires_at| expires_at.timestamp() as u64);
+        let MessageSendResult {
+            direct_send_result,
+            store_and_forward_send_result,
+        } = self
             .resources
-            .outbound_message_service
-            .send_direct(
-                self.dest_pubkey.clone(),
-                OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message.clone()),
-            )
+            .messaging
+            .send_transaction(self.id, self.dest_pubkey.clone(), msg, &self.resources.config)
             .await
-        {
-            Ok(result) => match result {
-                SendMessageResponse::Queued(send_states) => {
-                    if wait_on_dial(
-                        send_states,
-                        self.id,
-                        self.dest_pubkey.clone(),
-                        "Transaction",
-                        self.resources.config.direct_send_timeout,
-                    )
-                    .await
-                    {
-                        direct_send_result = true;
-                        transaction_status = TransactionStatus::Pending;
-                    }
-                    // Send a Store and Forward (SAF) regardless. Empirical testing determined
-                    // that in some cases a direct send would be reported as true, even though the wallet
-                    // was offline. Possibly due to the Tor connection remaining active for a few
-                    // minutes after wallet shutdown.
-                    info!(
-                        target: LOG_TARGET,
-                        "Direct Send result was {}. Sending SAF for TxId: {} to recipient with Public Key: {}",
-                        direct_send_result,
-                        self.id,
-                        self.dest_pubkey,
-                    );
-                    match self.send_transaction_store_and_forward(msg.clone()).await {
-                        Ok(res) => {
-                            store_and_forward_send_result = res;
-                            if store_and_forward_send_result {
-                                transaction_status = TransactionStatus::Pending
-                            };
-                        },
-                        // Sending SAF is the secondary concern here, so do not propagate the error
-                        Err(e) => warn!(target: LOG_TARGET, "Sending SAF for TxId {} failed ({:?})", self.id, e),
-                    }
-                },
-                SendMessageResponse::Failed(err) => {
-                    warn!(
-                        target: LOG_TARGET,
-                        "Transaction Send Direct for TxID {} failed: {}", self.id, err
-                    );
-                    match self.send_transaction_store_and_forward(msg.clone()).await {
-                        Ok(res) => {
-                            store_and_forward_send_result = res;
-                            if store_and_forward_send_result {
-                                transaction_status = TransactionStatus::Pending
-                            };
-                        },
-                        // Sending SAF is the secondary concern here, so do not propagate the error
-                        Err(e) => warn!(target: LOG_TARGET, "Sending SAF for TxId {} failed ({:?})", self.id, e),
-                    }
-                },
-                SendMessageResponse::PendingDiscovery(rx) => {
-                    let _size = self
-                        .resources
-                        .event_publisher
-                        .send(Arc::new(TransactionEvent::TransactionDiscoveryInProgress(self.id)));
-                    match self.send_transaction_store_and_forward(msg.clone()).await {
-                        Ok(res) => {
-                            store_and_forward_send_result = res;
-                            if store_and_forward_send_result {
-                                transaction_status = TransactionStatus::Pending
-                            };
-                        },
-                        // Sending SAF is the secondary concern here, so do not propagate the error
-                        Err(e) => warn!(
-                            target: LOG_TARGET,
-                            "Sending SAF for TxId {} failed; we will still wait for discovery of {} to complete ({:?})",
-                            self.id,
-                            self.dest_pubkey,
-                            e
-                        ),
-                    }
-                    // now wait for discovery to complete
-                    match rx.await {
-                        Ok(SendMessageResponse::Queued(send_states)) => {
-                            debug!(
-                                target: LOG_TARGET,
-                                "Discovery of {} completed for TxID: {}", self.dest_pubkey, self.id
-                            );
-                            direct_send_result = wait_on_dial(
-                                send_states,
-                                self.id,
-                                self.dest_pubkey.clone(),
-                                "Transaction",
-                                self.resources.config.direct_send_timeout,
-                            )
-                            .await;
-                            if direct_send_result {
-                                transaction_status = TransactionStatus::Pending
-                            };
-                        },
-                        Ok(SendMessageResponse::Failed(e)) => warn!(
-                            target: LOG_TARGET,
-                            "Failed to send message ({}) for TxId: {}", e, self.id
-                        ),
-                        Ok(SendMessageResponse::PendingDiscovery(_)) => unreachable!(),
-                        Err(e) => {
-                            warn!(
-                                target: LOG_TARGET,
-                                "Error waiting for Discovery while sending message (TxId: {}) {:?}", self.id, e
-                            );
-                        },
-                    }
-                },
-            },
-            Err(e) => {
-                warn!(
-                    target: LOG_TARGET,
-                    "Direct Transaction Send (TxId: {}) failed: {:?}", self.id, e
-                );
-            },
-        }
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, e))?;
+        let transaction_status = if direct_send_result || store_and_forward_send_result {
+            TransactionStatus::Pending
+        } else {
+            TransactionStatus::Queued
+        };
 
         Ok(SendResult {
             direct_send_result,
@@ -803,96 +722,20 @@ where
         })
     }
 
-    /// Contains all the logic to send the transaction to the recipient via store and forward
-    /// # Arguments
-    /// `msg`: The transaction data message to be sent
-    /// 'send_events': A bool indicating whether we should send events during the operation or not.
-    async fn send_transaction_store_and_forward(
-        &mut self,
-        msg: SingleRoundSenderData,
-    ) -> Result<bool, TransactionServiceProtocolError<TxId>> {
-        if self.resources.config.transaction_routing_mechanism == TransactionRoutingMechanism::DirectOnly {
-            return Ok(false);
-        }
-        let proto_message = proto::TransactionSenderMessage::single(msg.into());
-        match self
-            .resources
-            .outbound_message_service
-            .closest_broadcast(
-                self.dest_pubkey.clone(),
-                OutboundEncryption::encrypt_for(self.dest_pubkey.clone()),
-                vec![],
-                OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message),
-            )
-            .await
-        {
-            Ok(send_states) if !send_states.is_empty() => {
-                let (successful_sends, failed_sends) = send_states
-                    .wait_n_timeout(self.resources.config.broadcast_send_timeout, 1)
-                    .await;
-                if !successful_sends.is_empty() {
-                    info!(
-                        target: LOG_TARGET,
-                        "Transaction (TxId: {}) Send to Neighbours for Store and Forward successful with Message \
-                         Tags: {:?}",
-                        self.id,
-                        successful_sends[0],
-                    );
-                    Ok(true)
-                } else if !failed_sends.is_empty() {
-                    warn!(
-                        target: LOG_TARGET,
-                        "Transaction Send to Neighbours for Store and Forward for TX_ID: {} was unsuccessful and no \
-                         messages were sent",
-                        self.id
-                    );
-                    Ok(false)
-                } else {
-                    warn!(
-                        target: LOG_TARGET,
-                        "Transaction Send to Neighbours for Store and Forward for TX_ID: {} timed out and was \
-                         unsuccessful. Some message might still be sent.",
-                        self.id
-                    );
-                    Ok(false)
-                }
-            },
-            Ok(_) => {
-                warn!(
-                    target: LOG_TARGET,
-                    "Transaction Send to Neighbours for Store and Forward for TX_ID: {} was unsuccessful and no \
-                     messages were sent",
-                    self.id
-                );
-                Ok(false)
-            },
-            Err(e) => {
-                warn!(
-                    target: LOG_TARGET,
-                    "Transaction Send (TxId: {}) to neighbours for Store and Forward failed: {:?}", self.id, e
-                );
-                Ok(false)
-            },
-        }
-    }
-
+    #[instrument(level = "debug", skip_all, fields(stage = "timeout_transaction"))]
     async fn timeout_transaction(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         info!(
             target: LOG_TARGET,
             "Cancelling Transaction Send Protocol (TxId: {}) due to timeout after no counterparty response", self.id
         );
-        let _ = send_transaction_cancelled_message(
-            self.id,
-            self.dest_pubkey.clone(),
-            self.resources.outbound_message_service.clone(),
-        )
-        .await
-        .map_err(|e| {
-            warn!(
-                target: LOG_TARGET,
-                "Error sending Transaction Cancelled (TxId: {}) message: {:?}", self.id, e
-            )
-        });
+        let _ = send_transaction_cancelled_message(self.id, self.dest_pubkey.clone(), self.resources.messaging.clone())
+            .await
+            .map_err(|e| {
+                warn!(
+                    target: LOG_TARGET,
+                    "Error sending Transaction Cancelled (TxId: {}) message: {:?}", self.id, e
+                )
+            });
         self.resources
             .db
             .increment_send_count(self.id)
@@ -941,6 +784,95 @@ where
             TransactionServiceError::Timeout,
         ))
     }
+
+    /// Aborts a send that was cancelled, or that reached the deadline of its cancellation, before the transaction was
+    /// sent to the recipient. The inputs are released whichever stage the send was aborted in.
+    #[instrument(level = "debug", skip_all, fields(stage = "abort_send"))]
+    async fn abort_send(&mut self) -> TransactionServiceProtocolError<TxId> {
+        let timed_out = self.cancellation.is_past_deadline();
+        let error = || {
+            if timed_out {
+                TransactionServiceError::Timeout
+            } else {
+                TransactionServiceError::TransactionCancelled
+            }
+        };
+        let reason = if timed_out {
+            TxCancellationReason::Timeout
+        } else {
+            TxCancellationReason::UserCancelled
+        };
+        info!(
+            target: LOG_TARGET,
+            "Aborting Transaction Send Protocol (TxId: {}) before the transaction was sent: {}", self.id, reason
+        );
+
+        // A send that is aborted before its inputs are selected fails the request, as the caller has no TxId yet
+        let caller_notified = match self.service_request_reply_channel.take() {
+            Some(service_reply_channel) => {
+                let _result = service_reply_channel.send(Err(error())).map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+                true
+            },
+            None => false,
+        };
+
+        // The output manager handles requests in order, so this releases the inputs even if it is still preparing the
+        // transaction
+        if let Err(e) = self.resources.output_manager_service.cancel_transaction(self.id).await {
+            info!(
+                target: LOG_TARGET,
+                "No outputs to release for aborted Transaction (TxId: {}): {}", self.id, e
+            );
+        }
+
+        match self.resources.db.transaction_exists(self.id) {
+            Ok(true) => {
+                let _ = send_transaction_cancelled_message(
+                    self.id,
+                    self.dest_pubkey.clone(),
+                    self.resources.messaging.clone(),
+                )
+                .await
+                .map_err(|e| {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error sending Transaction Cancelled (TxId: {}) message: {:?}", self.id, e
+                    )
+                });
+                if let Err(e) = self.resources.db.cancel_pending_transaction(self.id) {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Aborted Transaction (TxId: {}) could not be cancelled: {:?}", self.id, e
+                    );
+                }
+            },
+            Ok(false) => {},
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Could not check if aborted Transaction (TxId: {}) exists: {:?}", self.id, e
+            ),
+        }
+
+        if !caller_notified {
+            let _size = self
+                .resources
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionCancelled(self.id, reason)))
+                .map_err(|e| {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Error sending event because there are no subscribers: {:?}",
+                        e
+                    );
+                    e
+                });
+        }
+
+        TransactionServiceProtocolError::new(self.id, error())
+    }
 }
 
 struct SendResult {
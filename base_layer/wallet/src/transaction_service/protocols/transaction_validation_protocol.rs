@@ -21,36 +21,41 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     convert::{TryFrom, TryInto},
     sync::Arc,
 };
 
-use log::*;
 use tari_common_types::{
-    transaction::{TransactionStatus, TxId},
+    transaction::{TransactionDirection, TransactionStatus, TxId},
     types::BlockHash,
 };
 use tari_comms::protocol::rpc::{RpcError::RequestFailed, RpcStatusCode::NotFound};
 use tari_core::{
     base_node::{
-        proto::wallet_rpc::{TxLocation, TxQueryBatchResponse},
+        proto::wallet_rpc::{QueryDeletedRequest, TxLocation, TxQueryBatchResponse, UtxoQueryRequest},
         rpc::BaseNodeWalletRpcClient,
     },
     blocks::BlockHeader,
     proto::{base_node::Signatures as SignaturesProto, types::Signature as SignatureProto},
 };
 use tari_utilities::hex::Hex;
+use tokio::time::sleep;
+use tracing::{debug, info, instrument, warn};
 
+#[cfg(feature = "header_sync")]
+use crate::header_sync::handle::HeaderSyncHandle;
 use crate::{
     connectivity_service::WalletConnectivityInterface,
+    error::ClassifyError,
     output_manager_service::handle::OutputManagerHandle,
     transaction_service::{
         config::TransactionServiceConfig,
         error::{TransactionServiceError, TransactionServiceProtocolError, TransactionServiceProtocolErrorExt},
         handle::{TransactionEvent, TransactionEventSender},
+        mempool_evictions::MempoolEvictions,
         storage::{
-            database::{TransactionBackend, TransactionDatabase},
+            database::{TransactionBackend, TransactionDatabase, TransactionStatusUpdate},
             sqlite_db::UnconfirmedTransactionInfo,
         },
     },
@@ -59,6 +64,17 @@ use crate::{
 
 const LOG_TARGET: &str = "wallet::transaction_service::protocols::validation_protocol";
 
+/// The outcome of checking that a mined transaction is in the block that the base node reported it in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InclusionVerification {
+    /// The transaction is proven to be in a block of the verified header chain
+    Verified,
+    /// The transaction is not proven to be in the block yet, or the block does not contain it
+    NotVerified,
+    /// The wallet has no verified header chain, so only the base node vouches for the transaction being mined
+    Unavailable,
+}
+
 pub struct TransactionValidationProtocol<TTransactionBackend, TWalletConnectivity> {
     operation_id: OperationId,
     db: TransactionDatabase<TTransactionBackend>,
@@ -66,6 +82,9 @@ pub struct TransactionValidationProtocol<TTransactionBackend, TWalletConnectivit
     config: TransactionServiceConfig,
     event_publisher: TransactionEventSender,
     output_manager_handle: OutputManagerHandle,
+    mempool_evictions: MempoolEvictions,
+    #[cfg(feature = "header_sync")]
+    header_sync: Option<HeaderSyncHandle>,
 }
 use tari_common_types::types::Signature;
 
@@ -89,13 +108,59 @@ where
             operation_id,
             db,
             connectivity,
+            mempool_evictions: MempoolEvictions::new(&config),
             config,
             event_publisher,
             output_manager_handle,
+            #[cfg(feature = "header_sync")]
+            header_sync: None,
         }
     }
 
+    /// Share the mempool evictions with the service, so that evicted transactions are rebroadcast with a backoff
+    pub fn with_mempool_evictions(mut self, mempool_evictions: MempoolEvictions) -> Self {
+        self.mempool_evictions = mempool_evictions;
+        self
+    }
+
+    /// Only confirm mined transactions once their kernels and outputs are proven to be in a block of the verified
+    /// header chain, instead of trusting the base node's response
+    #[cfg(feature = "header_sync")]
+    pub fn with_header_sync(mut self, header_sync: HeaderSyncHandle) -> Self {
+        self.header_sync = Some(header_sync);
+        self
+    }
+
+    #[instrument(
+        name = "transaction_service::validation_protocol",
+        skip_all,
+        fields(service = "transaction_service", operation_id = %self.operation_id)
+    )]
     pub async fn execute(mut self) -> Result<OperationId, TransactionServiceProtocolError<OperationId>> {
+        let mut failed_attempts = 0;
+        loop {
+            match self.validate().await {
+                Err(e) if e.error.is_transient() => {
+                    failed_attempts += 1;
+                    if !self.config.validation_retry.should_retry(failed_attempts) {
+                        return Err(e);
+                    }
+                    let delay = self.config.validation_retry.delay(failed_attempts);
+                    warn!(
+                        target: LOG_TARGET,
+                        "Transaction validation failed (Operation ID: {}): {}. Retrying in {:.1?}",
+                        self.operation_id,
+                        e.error,
+                        delay
+                    );
+                    sleep(delay).await;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn validate(&mut self) -> Result<OperationId, TransactionServiceProtocolError<OperationId>> {
         let mut base_node_wallet_client = self
             .connectivity
             .obtain_base_node_wallet_rpc_client()
@@ -128,25 +193,49 @@ where
                 unmined.len(),
                 self.operation_id
             );
+            let mut updates = Vec::with_capacity(mined.len() + unmined.len());
+            let mut mined_txs = Vec::with_capacity(mined.len());
             for (mined_tx, mined_height, mined_in_block, num_confirmations, mined_timestamp) in &mined {
+                let confirmations_required = self.config.confirmations_required(mined_tx.amount);
+                let verification = if *num_confirmations >= confirmations_required {
+                    Some(
+                        self.verify_inclusion(mined_tx.tx_id, *mined_height, mined_in_block)
+                            .await?,
+                    )
+                } else {
+                    None
+                };
+                let is_confirmed = matches!(
+                    verification,
+                    Some(InclusionVerification::Verified | InclusionVerification::Unavailable)
+                );
+                let is_unverified = verification == Some(InclusionVerification::Unavailable);
                 debug!(
                     target: LOG_TARGET,
                     "Updating transaction {} as mined and confirmed '{}' (Operation ID: {})",
                     mined_tx.tx_id,
-                    *num_confirmations >= self.config.num_confirmations_required,
+                    is_confirmed,
                     self.operation_id
                 );
-                self.update_transaction_as_mined(
-                    mined_tx.tx_id,
-                    &mined_tx.status,
-                    mined_in_block,
-                    *mined_height,
+                updates.push(TransactionStatusUpdate::Mined {
+                    tx_id: mined_tx.tx_id,
+                    mined_height: *mined_height,
+                    mined_in_block: *mined_in_block,
+                    mined_timestamp: *mined_timestamp,
+                    num_confirmations: *num_confirmations,
+                    is_confirmed,
+                    is_faux: mined_tx.status.is_faux(),
+                });
+                mined_txs.push((
+                    mined_tx,
                     *num_confirmations,
-                    *mined_timestamp,
-                )
-                .await?;
-                state_changed = true;
+                    confirmations_required,
+                    is_confirmed,
+                    is_unverified,
+                ));
             }
+            let mut abandoned_coinbases = Vec::new();
+            let mut unmined_txs = Vec::new();
             if let Some((tip_height, tip_block, tip_mined_timestamp)) = tip_info {
                 for unmined_tx in &unmined {
                     // Treat coinbases separately
@@ -154,19 +243,24 @@ where
                         if unmined_tx.coinbase_block_height.unwrap_or_default() <= tip_height {
                             debug!(
                                 target: LOG_TARGET,
-                                "Updated coinbase {} as abandoned (Operation ID: {})",
+                                "Updating coinbase {} as abandoned (Operation ID: {})",
                                 unmined_tx.tx_id,
                                 self.operation_id
                             );
-                            self.update_coinbase_as_abandoned(
-                                unmined_tx.tx_id,
-                                &tip_block,
-                                tip_height,
-                                tip_mined_timestamp,
-                                tip_height.saturating_sub(unmined_tx.coinbase_block_height.unwrap_or_default()),
-                            )
-                            .await?;
-                            state_changed = true;
+                            let num_confirmations =
+                                tip_height.saturating_sub(unmined_tx.coinbase_block_height.unwrap_or_default());
+                            self.abandon_coinbase_output(unmined_tx.tx_id).await?;
+                            updates.push(TransactionStatusUpdate::Mined {
+                                tx_id: unmined_tx.tx_id,
+                                mined_height: tip_height,
+                                mined_in_block: tip_block,
+                                mined_timestamp: tip_mined_timestamp,
+                                num_confirmations,
+                                is_confirmed: num_confirmations >= self.config.num_confirmations_required,
+                                is_faux: false,
+                            });
+                            updates.push(TransactionStatusUpdate::AbandonedCoinbase(unmined_tx.tx_id));
+                            abandoned_coinbases.push(unmined_tx.tx_id);
                         } else {
                             debug!(
                                 target: LOG_TARGET,
@@ -180,14 +274,53 @@ where
                     } else {
                         debug!(
                             target: LOG_TARGET,
-                            "Updated transaction {} as unmined (Operation ID: {})", unmined_tx.tx_id, self.operation_id
+                            "Updating transaction {} as unmined (Operation ID: {})",
+                            unmined_tx.tx_id,
+                            self.operation_id
                         );
-                        self.update_transaction_as_unmined(unmined_tx.tx_id, &unmined_tx.status)
-                            .await?;
-                        self.publish_event(TransactionEvent::NewBlockMined(unmined_tx.tx_id));
+                        updates.push(TransactionStatusUpdate::Unmined(unmined_tx.tx_id));
+                        unmined_txs.push(unmined_tx);
                     }
                 }
             }
+
+            // The state changes of the batch are written in one database transaction, and the events are only published
+            // once they are committed
+            self.db.apply_status_updates(updates).for_protocol(self.operation_id)?;
+            for (mined_tx, num_confirmations, confirmations_required, is_confirmed, is_unverified) in mined_txs {
+                self.handle_transaction_mined(
+                    mined_tx.tx_id,
+                    &mined_tx.status,
+                    num_confirmations,
+                    confirmations_required,
+                    is_confirmed,
+                )
+                .await;
+                if is_unverified {
+                    self.publish_event(TransactionEvent::TransactionConfirmedUnverified(mined_tx.tx_id));
+                }
+                state_changed = true;
+            }
+            for tx_id in abandoned_coinbases {
+                self.publish_event(TransactionEvent::TransactionCancelled(
+                    tx_id,
+                    TxCancellationReason::AbandonedCoinbase,
+                ));
+                state_changed = true;
+            }
+            for unmined_tx in unmined_txs {
+                self.handle_transaction_unmined(unmined_tx.tx_id, &unmined_tx.status)
+                    .await;
+                self.publish_event(TransactionEvent::NewBlockMined(unmined_tx.tx_id));
+            }
+            if let Some((tip_height, _, _)) = tip_info {
+                if self
+                    .check_for_double_spends(&unmined, tip_height, &mut *base_node_wallet_client)
+                    .await?
+                {
+                    state_changed = true;
+                }
+            }
         }
         if state_changed {
             self.publish_event(TransactionEvent::TransactionValidationStateChanged(self.operation_id));
@@ -205,6 +338,120 @@ where
         }
     }
 
+    /// Checks whether an input of an unmined incoming transaction has been spent on-chain by a different transaction.
+    /// The sender controls these inputs, so such a transaction will never be mined and is marked as double spent.
+    #[instrument(level = "debug", skip_all, fields(stage = "check_for_double_spends"))]
+    async fn check_for_double_spends(
+        &mut self,
+        unmined: &[UnconfirmedTransactionInfo],
+        tip_height: u64,
+        client: &mut BaseNodeWalletRpcClient,
+    ) -> Result<bool, TransactionServiceProtocolError<OperationId>> {
+        const MAX_UTXO_QUERY_SIZE: usize = 512;
+
+        let mut inputs_by_tx = Vec::new();
+        for unmined_tx in unmined.iter().filter(|tx| !tx.is_coinbase()) {
+            let tx = self
+                .db
+                .get_completed_transaction(unmined_tx.tx_id)
+                .for_protocol(self.operation_id)?;
+            if tx.direction != TransactionDirection::Inbound || tx.transaction.body.inputs().is_empty() {
+                continue;
+            }
+            let input_hashes = tx
+                .transaction
+                .body
+                .inputs()
+                .iter()
+                .map(|input| input.output_hash().to_vec())
+                .collect::<Vec<_>>();
+            inputs_by_tx.push((tx.tx_id, input_hashes));
+        }
+        if inputs_by_tx.is_empty() {
+            return Ok(false);
+        }
+
+        let output_hashes = inputs_by_tx
+            .iter()
+            .flat_map(|(_, hashes)| hashes.iter().cloned())
+            .collect::<Vec<_>>();
+        let mut mmr_positions = HashMap::new();
+        for chunk in output_hashes.chunks(MAX_UTXO_QUERY_SIZE) {
+            let response = client
+                .utxo_query(UtxoQueryRequest {
+                    output_hashes: chunk.to_vec(),
+                })
+                .await
+                .for_protocol(self.operation_id)?;
+            mmr_positions.extend(response.responses.into_iter().map(|r| (r.output_hash, r.mmr_position)));
+        }
+        if mmr_positions.is_empty() {
+            return Ok(false);
+        }
+
+        let deleted = client
+            .query_deleted(QueryDeletedRequest {
+                mmr_positions: mmr_positions.values().copied().collect(),
+                chain_must_include_header: None,
+                include_deleted_block_data: true,
+            })
+            .await
+            .for_protocol(self.operation_id)?;
+        // Ignore spends above the tip at which the kernels were queried, in case our own transaction was mined since
+        let spent_positions = deleted
+            .deleted_positions
+            .iter()
+            .zip(&deleted.heights_deleted_at)
+            .filter(|(_, height)| **height <= tip_height)
+            .map(|(position, _)| *position)
+            .collect::<HashSet<_>>();
+
+        let double_spent = inputs_by_tx
+            .into_iter()
+            .filter(|(_, input_hashes)| {
+                input_hashes
+                    .iter()
+                    .filter_map(|hash| mmr_positions.get(hash))
+                    .any(|position| spent_positions.contains(position))
+            })
+            .map(|(tx_id, _)| tx_id)
+            .collect::<Vec<_>>();
+        for tx_id in &double_spent {
+            warn!(
+                target: LOG_TARGET,
+                "An input of incoming transaction {} was spent by another transaction, marking it as double spent \
+                 (Operation ID: {})",
+                tx_id,
+                self.operation_id
+            );
+        }
+        self.db
+            .apply_status_updates(
+                double_spent
+                    .iter()
+                    .map(|tx_id| TransactionStatusUpdate::DoubleSpent(*tx_id))
+                    .collect(),
+            )
+            .for_protocol(self.operation_id)?;
+
+        let mut state_changed = false;
+        for tx_id in double_spent {
+            if let Err(e) = self.output_manager_handle.cancel_transaction(tx_id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not cancel the outputs of double spent transaction {}: {} (Operation ID: {})",
+                    tx_id,
+                    e,
+                    self.operation_id
+                );
+            }
+            self.publish_event(TransactionEvent::TransactionDoubleSpent(tx_id));
+            state_changed = true;
+        }
+        Ok(state_changed)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(stage = "check_for_reorgs"))]
     async fn check_for_reorgs(
         &mut self,
         client: &mut BaseNodeWalletRpcClient,
@@ -255,8 +502,13 @@ where
                         .unwrap_or_else(|| "{No Kernel found}".to_string()),
                     self.operation_id
                 );
+                // The transaction is queried again below, which moves it back to mined if it is in another block
                 self.update_transaction_as_unmined(last_mined_transaction.tx_id, &last_mined_transaction.status)
                     .await?;
+                self.publish_event(TransactionEvent::TransactionReorged {
+                    tx_id: last_mined_transaction.tx_id,
+                    mined_height,
+                });
                 self.publish_event(TransactionEvent::TransactionValidationStateChanged(op_id));
             } else {
                 debug!(
@@ -270,6 +522,7 @@ where
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "query_base_node_for_transactions"))]
     async fn query_base_node_for_transactions(
         &self,
         batch: &[UnconfirmedTransactionInfo],
@@ -317,6 +570,7 @@ where
             })
             .await?;
 
+        let is_synced = batch_response.is_synced;
         for response_proto in batch_response.responses {
             let response = TxQueryBatchResponse::try_from(response_proto)
                 .map_err(TransactionServiceError::ProtobufConversionError)?;
@@ -335,10 +589,17 @@ where
                         target: LOG_TARGET,
                         "Marking transaction {} as unmined and confirmed '{}' with block '{}' (Operation ID: {})",
                         &unconfirmed_tx.tx_id,
-                        response.confirmations >= self.config.num_confirmations_required,
+                        response.confirmations >= self.config.confirmations_required(unconfirmed_tx.amount),
                         response.block_hash.is_some(),
                         self.operation_id,
                     );
+                    // The base node can only tell that a transaction was evicted from its mempool once it is synced
+                    if is_synced &&
+                        response.location == TxLocation::NotStored &&
+                        unconfirmed_tx.status == TransactionStatus::Broadcast
+                    {
+                        self.handle_mempool_eviction(unconfirmed_tx.tx_id);
+                    }
                     unmined.push((*unconfirmed_tx).clone());
                 }
             }
@@ -360,6 +621,20 @@ where
         ))
     }
 
+    fn handle_mempool_eviction(&self, tx_id: TxId) {
+        if let Some(num_evictions) = self.mempool_evictions.record(tx_id) {
+            warn!(
+                target: LOG_TARGET,
+                "Broadcast transaction {} is no longer in the mempool of the base node and will be rebroadcast \
+                 (evictions: {}, Operation ID: {})",
+                tx_id,
+                num_evictions,
+                self.operation_id
+            );
+            self.publish_event(TransactionEvent::TransactionEvictedFromMempool { tx_id, num_evictions });
+        }
+    }
+
     async fn get_base_node_block_at_height(
         &mut self,
         height: u64,
@@ -393,29 +668,98 @@ where
         Ok(Some(block_header.hash()))
     }
 
-    #[allow(clippy::ptr_arg)]
-    async fn update_transaction_as_mined(
+    /// Checks the kernels and outputs of the transaction against the base node's inclusion proof for the block it was
+    /// mined in. A transaction that cannot be verified yet, e.g. because the header chain has not caught up, is left
+    /// unconfirmed and checked again in the next validation round.
+    #[cfg(feature = "header_sync")]
+    async fn verify_inclusion(
         &mut self,
         tx_id: TxId,
-        status: &TransactionStatus,
-        mined_in_block: &BlockHash,
         mined_height: u64,
-        num_confirmations: u64,
-        mined_timestamp: u64,
-    ) -> Result<(), TransactionServiceProtocolError<OperationId>> {
-        self.db
-            .set_transaction_mined_height(
+        mined_in_block: &BlockHash,
+    ) -> Result<InclusionVerification, TransactionServiceProtocolError<OperationId>> {
+        let header_sync = match self.header_sync.as_mut() {
+            Some(header_sync) => header_sync,
+            None => return Ok(self.inclusion_unavailable(tx_id, mined_height)),
+        };
+        let proof = match header_sync
+            .get_verified_inclusion_proof(mined_height, *mined_in_block)
+            .await
+        {
+            Ok(proof) => proof,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not verify that transaction {} was mined in block #{}: {} (Operation ID: {})",
+                    tx_id,
+                    mined_height,
+                    e,
+                    self.operation_id
+                );
+                return Ok(InclusionVerification::NotVerified);
+            },
+        };
+
+        let completed_tx = self
+            .db
+            .get_completed_transaction(tx_id)
+            .for_protocol(self.operation_id)?;
+        let body = &completed_tx.transaction.body;
+        let is_included = body.kernels().iter().all(|k| proof.contains_kernel(&k.hash())) &&
+            body.outputs()
+                .iter()
+                .all(|o| proof.contains_output_witness(&o.witness_hash()));
+        if !is_included {
+            warn!(
+                target: LOG_TARGET,
+                "The base node reported transaction {} as mined in block #{}, but the block does not contain it \
+                 (Operation ID: {})",
                 tx_id,
                 mined_height,
-                *mined_in_block,
-                mined_timestamp,
-                num_confirmations,
-                num_confirmations >= self.config.num_confirmations_required,
-                status.is_faux(),
-            )
-            .for_protocol(self.operation_id)?;
+                self.operation_id
+            );
+            return Ok(InclusionVerification::NotVerified);
+        }
+        Ok(InclusionVerification::Verified)
+    }
+
+    #[cfg(not(feature = "header_sync"))]
+    async fn verify_inclusion(
+        &mut self,
+        tx_id: TxId,
+        mined_height: u64,
+        _mined_in_block: &BlockHash,
+    ) -> Result<InclusionVerification, TransactionServiceProtocolError<OperationId>> {
+        Ok(self.inclusion_unavailable(tx_id, mined_height))
+    }
+
+    /// Without a verified header chain the transaction is confirmed on the word of the base node alone, which is logged
+    /// and published as a [TransactionConfirmedUnverified](TransactionEvent::TransactionConfirmedUnverified) event
+    fn inclusion_unavailable(&self, tx_id: TxId, mined_height: u64) -> InclusionVerification {
+        warn!(
+            target: LOG_TARGET,
+            "Transaction {} is confirmed without verifying that it was mined in block #{}, as there is no verified \
+             header chain (Operation ID: {})",
+            tx_id,
+            mined_height,
+            self.operation_id
+        );
+        InclusionVerification::Unavailable
+    }
 
-        if num_confirmations >= self.config.num_confirmations_required {
+    /// Publishes the events of a transaction that was updated as mined
+    #[instrument(level = "debug", skip_all, fields(stage = "handle_transaction_mined", tx_id = %tx_id))]
+    async fn handle_transaction_mined(
+        &mut self,
+        tx_id: TxId,
+        status: &TransactionStatus,
+        num_confirmations: u64,
+        confirmations_required: u64,
+        is_confirmed: bool,
+    ) {
+        self.mempool_evictions.remove(tx_id);
+
+        if is_confirmed {
             if status.is_faux() {
                 self.publish_event(TransactionEvent::FauxTransactionConfirmed { tx_id, is_valid: true })
             } else {
@@ -431,6 +775,7 @@ where
             self.publish_event(TransactionEvent::TransactionMinedUnconfirmed {
                 tx_id,
                 num_confirmations,
+                confirmations_required,
                 is_valid: true,
             })
         }
@@ -446,18 +791,12 @@ where
                 );
             };
         }
-
-        Ok(())
     }
 
-    #[allow(clippy::ptr_arg)]
-    async fn update_coinbase_as_abandoned(
+    #[instrument(level = "debug", skip_all, fields(stage = "abandon_coinbase_output", tx_id = %tx_id))]
+    async fn abandon_coinbase_output(
         &mut self,
         tx_id: TxId,
-        mined_in_block: &BlockHash,
-        mined_height: u64,
-        mined_timestamp: u64,
-        num_confirmations: u64,
     ) -> Result<(), TransactionServiceProtocolError<OperationId>> {
         // This updates the OMS first before we update the TMS. If we update the TMS first and operation fail inside of
         // the OMS, we have two databases that are out of sync, as the TMS would have been updated and OMS will be stuck
@@ -476,29 +815,10 @@ where
                 e
             })
             .for_protocol(self.operation_id)?;
-        self.db
-            .set_transaction_mined_height(
-                tx_id,
-                mined_height,
-                *mined_in_block,
-                mined_timestamp,
-                num_confirmations,
-                num_confirmations >= self.config.num_confirmations_required,
-                false,
-            )
-            .for_protocol(self.operation_id)?;
-
-        self.db
-            .abandon_coinbase_transaction(tx_id)
-            .for_protocol(self.operation_id)?;
-
-        self.publish_event(TransactionEvent::TransactionCancelled(
-            tx_id,
-            TxCancellationReason::AbandonedCoinbase,
-        ));
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "update_transaction_as_unmined", tx_id = %tx_id))]
     async fn update_transaction_as_unmined(
         &mut self,
         tx_id: TxId,
@@ -507,7 +827,12 @@ where
         self.db
             .set_transaction_as_unmined(tx_id)
             .for_protocol(self.operation_id)?;
+        self.handle_transaction_unmined(tx_id, status).await;
+        Ok(())
+    }
 
+    /// Publishes the events of a transaction that was updated as unmined
+    async fn handle_transaction_unmined(&mut self, tx_id: TxId, status: &TransactionStatus) {
         if *status == TransactionStatus::Coinbase {
             if let Err(e) = self.output_manager_handle.set_coinbase_abandoned(tx_id, false).await {
                 warn!(
@@ -521,6 +846,5 @@ where
         }
 
         self.publish_event(TransactionEvent::TransactionBroadcast(tx_id));
-        Ok(())
     }
 }
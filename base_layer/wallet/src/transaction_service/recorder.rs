@@ -0,0 +1,392 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! An opt-in recording of every transaction negotiation message that the wallet sends and receives, so that
+//! negotiation failures can be reproduced offline. The recording is encrypted with a key derived from the secret key
+//! of the wallet, so only the wallet that made it can read it back. Replaying a recording with
+//! `TransactionServiceHandle::replay_protocol_recording` hands the received messages to the protocols again, one at a
+//! time and in the order in which they arrived.
+
+use std::{
+    convert::TryFrom,
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use chrono::Utc;
+use log::*;
+use prost::Message;
+use tari_common_types::transaction::TxId;
+use tari_comms::types::{CommsPublicKey, CommsSecretKey};
+use tari_core::transactions::{
+    transaction_components::Transaction,
+    transaction_protocol::{recipient::RecipientSignedMessage, sender::SingleRoundSenderData},
+};
+use tari_p2p::tari_message::TariMessageType;
+use tari_utilities::ByteArray;
+
+use crate::{
+    network_state::set_owner_only_permissions,
+    transaction_service::{
+        config::TransactionServiceConfig,
+        error::TransactionServiceError,
+        messaging::{encode_envelope, MessageSendResult, OutOfBandMessage, TransactionMessagingBackend},
+    },
+    util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce},
+    WalletSecretKeysDomainHasher,
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::recorder";
+
+/// The version of the recording entry encoding
+const RECORDING_VERSION: u32 = 1;
+/// The domain that the entries of a recording are encrypted under
+const RECORDING_DOMAIN: &[u8] = b"TRANSACTION_PROTOCOL_RECORDING";
+/// Entries larger than this are not read back, as the length prefix must be corrupt
+const MAX_ENTRY_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDirection {
+    Inbound,
+    Outbound,
+}
+
+/// A transaction negotiation message in a recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedMessage {
+    /// Milliseconds since the unix epoch
+    pub recorded_at: u64,
+    pub direction: MessageDirection,
+    /// The counterparty that sent or received the message
+    pub peer: CommsPublicKey,
+    pub message_type: TariMessageType,
+    /// The message as it was encoded over the wire
+    pub body: Vec<u8>,
+}
+
+impl RecordedMessage {
+    /// Decodes the message in the same way as a message that was imported out-of-band, so a message that the wallet
+    /// failed to decode when it arrived fails in the same way when it is replayed
+    pub fn to_out_of_band_message(&self) -> Result<OutOfBandMessage, TransactionServiceError> {
+        let bytes = encode_envelope(&self.peer, self.message_type, self.body.clone());
+        OutOfBandMessage::from_bytes(&bytes).map(|(_, message)| message)
+    }
+}
+
+/// The outcome of replaying a recording
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// The number of received messages that were handed to the protocols
+    pub replayed: usize,
+    /// The number of sent messages, which are not replayed as the protocols send them again
+    pub skipped: usize,
+    /// The errors of the received messages that were rejected
+    pub failures: Vec<String>,
+}
+
+/// Appends the messages that the wallet sends and receives to an encrypted recording
+#[derive(Clone)]
+pub struct ProtocolRecorder {
+    file: Arc<Mutex<File>>,
+    cipher: XChaCha20Poly1305,
+}
+
+impl ProtocolRecorder {
+    /// Opens the recording at `path`, appending to it if it already exists. On unix, the file is only readable by its
+    /// owner.
+    pub fn open<P: AsRef<Path>>(path: P, cipher: XChaCha20Poly1305) -> Result<Self, TransactionServiceError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(recording_error)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(recording_error)?;
+        set_owner_only_permissions(path).map_err(recording_error)?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            cipher,
+        })
+    }
+
+    /// Records a message. A message that cannot be recorded is logged, as the recording must never interfere with the
+    /// negotiation itself.
+    pub fn record(
+        &self,
+        direction: MessageDirection,
+        peer: &CommsPublicKey,
+        message_type: TariMessageType,
+        body: Vec<u8>,
+    ) {
+        if let Err(e) = self.append(direction, peer, message_type, body) {
+            warn!(
+                target: LOG_TARGET,
+                "Could not record a transaction protocol message: {}", e
+            );
+        }
+    }
+
+    /// Records an out-of-band message, which is how the protocols hand their messages to the messaging backend
+    pub fn record_message(&self, direction: MessageDirection, peer: &CommsPublicKey, message: &OutOfBandMessage) {
+        match message.encode() {
+            Ok((message_type, body)) => self.record(direction, peer, message_type, body),
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Could not record a message for Transaction (TxId: {}): {}",
+                message.tx_id(),
+                e
+            ),
+        }
+    }
+
+    fn append(
+        &self,
+        direction: MessageDirection,
+        peer: &CommsPublicKey,
+        message_type: TariMessageType,
+        body: Vec<u8>,
+    ) -> Result<(), TransactionServiceError> {
+        let entry = RecordingEntry {
+            version: RECORDING_VERSION,
+            recorded_at: u64::try_from(Utc::now().timestamp_millis()).unwrap_or_default(),
+            outbound: direction == MessageDirection::Outbound,
+            peer: peer.to_vec(),
+            message_type: message_type as i32,
+            body,
+        };
+        let ciphertext = encrypt_bytes_integral_nonce(&self.cipher, RECORDING_DOMAIN.to_vec(), entry.encode_to_vec())
+            .map_err(TransactionServiceError::ProtocolRecordingError)?;
+        let len = u32::try_from(ciphertext.len())
+            .map_err(|_| TransactionServiceError::ProtocolRecordingError("The message is too large".to_string()))?;
+
+        let mut file = self.file.lock().map_err(|_| {
+            TransactionServiceError::ProtocolRecordingError("The recording lock is poisoned".to_string())
+        })?;
+        file.write_all(&len.to_le_bytes()).map_err(recording_error)?;
+        file.write_all(&ciphertext).map_err(recording_error)?;
+        file.flush().map_err(recording_error)?;
+        Ok(())
+    }
+}
+
+/// The cipher that the recordings of the wallet with this secret key are encrypted with
+pub fn recording_cipher(secret_key: &CommsSecretKey) -> XChaCha20Poly1305 {
+    let key = WalletSecretKeysDomainHasher::new_with_label("protocol_recording")
+        .chain(secret_key.as_bytes())
+        .finalize();
+    XChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+}
+
+/// Reads all the messages of a recording in the order in which they were recorded
+pub fn read_recording<P: AsRef<Path>>(
+    path: P,
+    cipher: &XChaCha20Poly1305,
+) -> Result<Vec<RecordedMessage>, TransactionServiceError> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .map_err(recording_error)?;
+
+    let mut messages = Vec::new();
+    let mut remaining = bytes.as_slice();
+    while !remaining.is_empty() {
+        if remaining.len() < 4 {
+            return Err(TransactionServiceError::ProtocolRecordingError(
+                "The recording is truncated".to_string(),
+            ));
+        }
+        let (len, rest) = remaining.split_at(4);
+        let len = usize::try_from(u32::from_le_bytes([len[0], len[1], len[2], len[3]])).unwrap_or(usize::MAX);
+        if len > MAX_ENTRY_SIZE || len > rest.len() {
+            return Err(TransactionServiceError::ProtocolRecordingError(
+                "The recording is truncated".to_string(),
+            ));
+        }
+        let (ciphertext, rest) = rest.split_at(len);
+        remaining = rest;
+
+        let plaintext = decrypt_bytes_integral_nonce(cipher, RECORDING_DOMAIN.to_vec(), ciphertext.to_vec())
+            .map_err(TransactionServiceError::ProtocolRecordingError)?;
+        let entry = RecordingEntry::decode(plaintext.as_slice())
+            .map_err(|e| TransactionServiceError::ProtocolRecordingError(e.to_string()))?;
+        if entry.version != RECORDING_VERSION {
+            return Err(TransactionServiceError::ProtocolRecordingError(format!(
+                "Unsupported recording version {}",
+                entry.version
+            )));
+        }
+        let message_type = TariMessageType::from_i32(entry.message_type).ok_or_else(|| {
+            TransactionServiceError::ProtocolRecordingError(format!("Unknown message type {}", entry.message_type))
+        })?;
+        messages.push(RecordedMessage {
+            recorded_at: entry.recorded_at,
+            direction: if entry.outbound {
+                MessageDirection::Outbound
+            } else {
+                MessageDirection::Inbound
+            },
+            peer: CommsPublicKey::from_bytes(&entry.peer)
+                .map_err(|e| TransactionServiceError::ProtocolRecordingError(e.to_string()))?,
+            message_type,
+            body: entry.body,
+        });
+    }
+    Ok(messages)
+}
+
+/// Records the messages that the protocols send before handing them to the wrapped backend
+pub struct RecordingMessagingBackend {
+    inner: Arc<dyn TransactionMessagingBackend>,
+    recorder: ProtocolRecorder,
+}
+
+impl RecordingMessagingBackend {
+    pub fn new(inner: Arc<dyn TransactionMessagingBackend>, recorder: ProtocolRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl TransactionMessagingBackend for RecordingMessagingBackend {
+    async fn send_transaction(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: SingleRoundSenderData,
+        config: &TransactionServiceConfig,
+    ) -> Result<MessageSendResult, TransactionServiceError> {
+        self.recorder.record_message(
+            MessageDirection::Outbound,
+            &destination,
+            &OutOfBandMessage::Transaction(message.clone()),
+        );
+        self.inner.send_transaction(tx_id, destination, message, config).await
+    }
+
+    async fn send_transaction_reply(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        message: RecipientSignedMessage,
+        config: &TransactionServiceConfig,
+    ) -> Result<bool, TransactionServiceError> {
+        self.recorder.record_message(
+            MessageDirection::Outbound,
+            &destination,
+            &OutOfBandMessage::Reply(message.clone()),
+        );
+        self.inner
+            .send_transaction_reply(tx_id, destination, message, config)
+            .await
+    }
+
+    async fn send_finalized_transaction(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        transaction: Transaction,
+        config: &TransactionServiceConfig,
+    ) -> Result<(), TransactionServiceError> {
+        self.recorder
+            .record_message(MessageDirection::Outbound, &destination, &OutOfBandMessage::Finalized {
+                tx_id,
+                transaction: transaction.clone(),
+            });
+        self.inner
+            .send_finalized_transaction(tx_id, destination, transaction, config)
+            .await
+    }
+
+    async fn send_transaction_cancelled(
+        &self,
+        tx_id: TxId,
+        destination: CommsPublicKey,
+    ) -> Result<(), TransactionServiceError> {
+        self.recorder
+            .record_message(MessageDirection::Outbound, &destination, &OutOfBandMessage::Cancelled {
+                tx_id,
+            });
+        self.inner.send_transaction_cancelled(tx_id, destination).await
+    }
+}
+
+#[derive(Clone, prost::Message)]
+struct RecordingEntry {
+    #[prost(uint32, tag = "1")]
+    version: u32,
+    #[prost(uint64, tag = "2")]
+    recorded_at: u64,
+    #[prost(bool, tag = "3")]
+    outbound: bool,
+    #[prost(bytes, tag = "4")]
+    peer: Vec<u8>,
+    #[prost(int32, tag = "5")]
+    message_type: i32,
+    #[prost(bytes, tag = "6")]
+    body: Vec<u8>,
+}
+
+fn recording_error(err: std::io::Error) -> TransactionServiceError {
+    TransactionServiceError::ProtocolRecordingError(err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::{PublicKey, SecretKey};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn it_reads_back_the_recorded_messages() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("recordings").join("protocol.rec");
+        let secret_key = CommsSecretKey::random(&mut OsRng);
+        let peer = CommsPublicKey::from_secret_key(&CommsSecretKey::random(&mut OsRng));
+
+        let recorder = ProtocolRecorder::open(&path, recording_cipher(&secret_key)).unwrap();
+        recorder.record_message(MessageDirection::Outbound, &peer, &OutOfBandMessage::Cancelled {
+            tx_id: TxId::from(1u64),
+        });
+        // A message that fails to decode is recorded as it arrived
+        recorder.record(
+            MessageDirection::Inbound,
+            &peer,
+            TariMessageType::ReceiverPartialTransactionReply,
+            vec![0xff; 8],
+        );
+        // Reopening the recording appends to it
+        let recorder = ProtocolRecorder::open(&path, recording_cipher(&secret_key)).unwrap();
+        recorder.record_message(MessageDirection::Inbound, &peer, &OutOfBandMessage::Cancelled {
+            tx_id: TxId::from(2u64),
+        });
+
+        let messages = read_recording(&path, &recording_cipher(&secret_key)).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].direction, MessageDirection::Outbound);
+        assert_eq!(messages[0].peer, peer);
+        assert_eq!(
+            messages[0].to_out_of_band_message().unwrap(),
+            OutOfBandMessage::Cancelled {
+                tx_id: TxId::from(1u64)
+            }
+        );
+        assert_eq!(messages[1].direction, MessageDirection::Inbound);
+        assert!(matches!(
+            messages[1].to_out_of_band_message(),
+            Err(TransactionServiceError::InvalidOutOfBandMessage(_))
+        ));
+        assert_eq!(messages[2].to_out_of_band_message().unwrap().tx_id(), TxId::from(2u64));
+
+        // Only the wallet that made the recording can read it
+        let other_key = CommsSecretKey::random(&mut OsRng);
+        assert!(read_recording(&path, &recording_cipher(&other_key)).is_err());
+    }
+}
@@ -23,6 +23,7 @@
 use std::{
     collections::{HashMap, HashSet},
     convert::TryInto,
+    path::Path,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -30,30 +31,36 @@ use std::{
 use chrono::{NaiveDateTime, Utc};
 use digest::Digest;
 use futures::{pin_mut, stream::FuturesUnordered, Stream, StreamExt};
-use log::*;
 use rand::rngs::OsRng;
 use sha2::Sha256;
+#[cfg(feature = "header_sync")]
+use tari_common_types::types::Commitment;
 use tari_common_types::{
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
-    types::{PrivateKey, PublicKey},
+    types::{FixedHash, PrivateKey, PublicKey},
 };
 use tari_comms::{peer_manager::NodeIdentity, types::CommsPublicKey};
-use tari_comms_dht::outbound::OutboundMessageRequester;
+#[cfg(feature = "header_sync")]
+use tari_core::blocks::SpendProof;
 use tari_core::{
+    base_node::proto::wallet_rpc::FeeFloor,
     covenants::Covenant,
     mempool::FeePerGramStat,
     proto::base_node as base_node_proto,
     transactions::{
         tari_amount::MicroTari,
         transaction_components::{
+            EncryptedMemo,
             EncryptedValue,
             KernelFeatures,
             OutputFeatures,
             Transaction,
             TransactionOutput,
             UnblindedOutput,
+            MAX_MEMO_SIZE,
         },
         transaction_protocol::{
+            aggregated_sender::{NonceCommitment, NonceReveal, PartialMetadataSignature, SenderSigningSession},
             proto::protocol as proto,
             recipient::RecipientSignedMessage,
             sender::TransactionSenderMessage,
@@ -69,25 +76,34 @@ use tari_crypto::{
     keys::{DiffieHellmanSharedSecret, PublicKey as PKtrait, SecretKey},
     tari_utilities::ByteArray,
 };
-use tari_p2p::domain_message::DomainMessage;
-use tari_script::{inputs, script, TariScript};
+use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
+use tari_script::{inputs, script, ExecutionStack, TariScript};
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
 use tari_shutdown::ShutdownSignal;
+#[cfg(feature = "header_sync")]
+use tari_utilities::hex::Hex;
 use tokio::{
     sync::{mpsc, mpsc::Sender, oneshot},
     task::JoinHandle,
+    time::sleep,
 };
+use tracing::{debug, error, field, info, instrument, trace, warn, Span};
 
+#[cfg(feature = "header_sync")]
+use crate::header_sync::handle::HeaderSyncHandle;
 use crate::{
-    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle, ChainEvent},
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{
+        error::OutputManagerError,
         handle::{OutputManagerEvent, OutputManagerHandle},
         storage::models::SpendingPriority,
+        ExpiringVaultScript,
         UtxoSelectionCriteria,
     },
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
+        approval::TransactionApprovalError,
         config::TransactionServiceConfig,
         error::{TransactionServiceError, TransactionServiceProtocolError},
         handle::{
@@ -97,15 +113,32 @@ use crate::{
             TransactionServiceRequest,
             TransactionServiceResponse,
         },
+        mempool_evictions::MempoolEvictions,
+        messaging::{OutOfBandMessage, TransactionMessagingBackend},
         protocols::{
             transaction_broadcast_protocol::TransactionBroadcastProtocol,
             transaction_receive_protocol::{TransactionReceiveProtocol, TransactionReceiveProtocolStage},
             transaction_send_protocol::{TransactionSendProtocol, TransactionSendProtocolStage},
             transaction_validation_protocol::TransactionValidationProtocol,
         },
+        recorder::{read_recording, recording_cipher, MessageDirection, ProtocolRecorder, ReplaySummary},
         storage::{
             database::{TransactionBackend, TransactionDatabase},
-            models::{CompletedTransaction, TxCancellationReason},
+            models::{
+                ApprovalPaymentType,
+                CompletedTransaction,
+                IdempotencyKeyRecord,
+                InboundTransaction,
+                OutboundTransaction,
+                PendingApprovalTransaction,
+                SpendingSummary,
+                TransactionAttachment,
+                TransactionNegotiationStage,
+                TransactionProtocolSnapshot,
+                TransactionProtocolState,
+                TxCancellationReason,
+                WalletTransaction,
+            },
         },
         tasks::{
             check_faux_transaction_status::check_faux_transactions,
@@ -113,10 +146,10 @@ use crate::{
             send_transaction_cancelled::send_transaction_cancelled_message,
             send_transaction_reply::send_transaction_reply,
         },
-        utc::utc_duration_since,
+        utc::{utc_after, utc_duration_since},
     },
     types::WalletHasher,
-    util::watch::Watch,
+    util::{cancellation::CancellationToken, clock::corrected_utc_now, scheduler::PrioritizedRequests, watch::Watch},
     utxo_scanner_service::RECOVERY_KEY,
     OperationId,
     WalletSecretKeysDomainHasher,
@@ -124,6 +157,11 @@ use crate::{
 
 const LOG_TARGET: &str = "wallet::transaction_service::service";
 
+/// The maximum number of characters in a transaction tag
+const MAX_TRANSACTION_TAG_LENGTH: usize = 64;
+const MAX_ATTACHMENT_NAME_LENGTH: usize = 255;
+const DEFAULT_ATTACHMENT_CONTENT_TYPE: &str = "application/octet-stream";
+
 /// TransactionService allows for the management of multiple inbound and outbound transaction protocols
 /// which are uniquely identified by a tx_id. The TransactionService generates and accepts the various protocol
 /// messages and applies them to the appropriate protocol instances based on the tx_id.
@@ -163,14 +201,22 @@ pub struct TransactionService<
     resources: TransactionServiceResources<TBackend, TWalletConnectivity>,
     pending_transaction_reply_senders: HashMap<TxId, Sender<(CommsPublicKey, RecipientSignedMessage)>>,
     base_node_response_senders: HashMap<TxId, (TxId, Sender<base_node_proto::BaseNodeServiceResponse>)>,
-    send_transaction_cancellation_senders: HashMap<TxId, oneshot::Sender<()>>,
+    send_transaction_cancellation_tokens: HashMap<TxId, CancellationToken>,
     finalized_transaction_senders: HashMap<TxId, Sender<(CommsPublicKey, TxId, Transaction)>>,
     receiver_transaction_cancellation_senders: HashMap<TxId, oneshot::Sender<()>>,
     active_transaction_broadcast_protocols: HashSet<TxId>,
+    mempool_evictions: MempoolEvictions,
+    power_mode: PowerMode,
     timeout_update_watch: Watch<Duration>,
     wallet_db: WalletDatabase<TWalletBackend>,
     base_node_service: BaseNodeServiceHandle,
     last_seen_tip_height: Option<u64>,
+    chain_split_suspected: bool,
+    protocol_recorder: Option<ProtocolRecorder>,
+    /// The TOTP step of the last approval code that was accepted, so that a code cannot be used again
+    last_totp_step: Option<u64>,
+    #[cfg(feature = "header_sync")]
+    header_sync: Option<HeaderSyncHandle>,
 }
 
 impl<
@@ -217,7 +263,7 @@ where
         base_node_response_stream: BNResponseStream,
         transaction_cancelled_stream: TTxCancelledStream,
         output_manager_service: OutputManagerHandle,
-        outbound_message_service: OutboundMessageRequester,
+        messaging: Arc<dyn TransactionMessagingBackend>,
         connectivity: TWalletConnectivity,
         event_publisher: TransactionEventSender,
         node_identity: Arc<NodeIdentity>,
@@ -230,7 +276,7 @@ where
         let resources = TransactionServiceResources {
             db: db.clone(),
             output_manager_service: output_manager_service.clone(),
-            outbound_message_service,
+            messaging,
             connectivity,
             event_publisher: event_publisher.clone(),
             node_identity: node_identity.clone(),
@@ -261,17 +307,40 @@ where
             resources,
             pending_transaction_reply_senders: HashMap::new(),
             base_node_response_senders: HashMap::new(),
-            send_transaction_cancellation_senders: HashMap::new(),
+            send_transaction_cancellation_tokens: HashMap::new(),
             finalized_transaction_senders: HashMap::new(),
             receiver_transaction_cancellation_senders: HashMap::new(),
             active_transaction_broadcast_protocols: HashSet::new(),
+            mempool_evictions: MempoolEvictions::new(&config),
+            power_mode,
             timeout_update_watch,
             base_node_service,
             wallet_db,
             last_seen_tip_height: None,
+            chain_split_suspected: false,
+            protocol_recorder: None,
+            last_totp_step: None,
+            #[cfg(feature = "header_sync")]
+            header_sync: None,
         }
     }
 
+    /// Record the transaction negotiation messages that are received. The messages that are sent are recorded by
+    /// wrapping the messaging backend in a
+    /// [RecordingMessagingBackend](crate::transaction_service::recorder::RecordingMessagingBackend) with the same
+    /// recorder.
+    pub fn with_protocol_recorder(mut self, protocol_recorder: ProtocolRecorder) -> Self {
+        self.protocol_recorder = Some(protocol_recorder);
+        self
+    }
+
+    /// Verify mined transactions against the verified header chain before marking them as confirmed
+    #[cfg(feature = "header_sync")]
+    pub fn with_header_sync(mut self, header_sync: HeaderSyncHandle) -> Self {
+        self.header_sync = Some(header_sync);
+        self
+    }
+
     #[allow(clippy::too_many_lines)]
     pub async fn start(mut self) -> Result<(), TransactionServiceError> {
         let request_stream = self
@@ -280,6 +349,8 @@ where
             .expect("Transaction Service initialized without request_stream")
             .fuse();
         pin_mut!(request_stream);
+        // Requests that the user is waiting for are handled before queued background work
+        let mut request_stream = PrioritizedRequests::new(request_stream);
         let transaction_stream = self
             .transaction_stream
             .take()
@@ -330,6 +401,7 @@ where
         > = FuturesUnordered::new();
 
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
+        let mut chain_event_stream = self.base_node_service.subscribe_chain_events();
         let mut output_manager_event_stream = self.output_manager_service.get_event_stream();
 
         debug!(target: LOG_TARGET, "Transaction Service started");
@@ -344,10 +416,20 @@ where
                 // Base Node Monitoring Service event
                 event = base_node_service_event_stream.recv() => {
                     match event {
-                        Ok(msg) => self.handle_base_node_service_event(msg, &mut transaction_validation_protocol_handles).await,
+                        Ok(msg) => self.handle_base_node_service_event(
+                            msg,
+                            &mut transaction_validation_protocol_handles,
+                            &mut transaction_broadcast_protocol_handles,
+                        ).await,
                         Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
                     };
                 },
+                event = chain_event_stream.recv() => {
+                    match event {
+                        Ok(msg) => self.handle_chain_event(msg, &mut transaction_validation_protocol_handles).await,
+                        Err(e) => debug!(target: LOG_TARGET, "Lagging read on chain event broadcast channel: {}", e),
+                    };
+                },
                 //Incoming request
                 Some(request_context) = request_stream.next() => {
                     // TODO: Remove time measurements; this is to aid in system testing only #LOGGED
@@ -377,6 +459,7 @@ where
                     let start = Instant::now();
                     let (origin_public_key, inner_msg) = msg.clone().into_origin_and_inner();
                     trace!(target: LOG_TARGET, "Handling Transaction Message, Trace: {}", msg.dht_header.message_tag);
+                    self.record_inbound(&origin_public_key, TariMessageType::SenderPartialTransaction, &inner_msg);
 
                     let result  = self.accept_transaction(origin_public_key, inner_msg,
                         msg.dht_header.message_tag.as_value(), &mut receive_transaction_protocol_handles);
@@ -406,6 +489,11 @@ where
                     let start = Instant::now();
                     let (origin_public_key, inner_msg) = msg.clone().into_origin_and_inner();
                     trace!(target: LOG_TARGET, "Handling Transaction Reply Message, Trace: {}", msg.dht_header.message_tag);
+                    self.record_inbound(
+                        &origin_public_key,
+                        TariMessageType::ReceiverPartialTransactionReply,
+                        &inner_msg,
+                    );
                     let result = self.accept_recipient_reply(origin_public_key, inner_msg).await;
 
                     match result {
@@ -439,6 +527,7 @@ where
                         "Handling Transaction Finalized Message, Trace: {}",
                         msg.dht_header.message_tag.as_value()
                     );
+                    self.record_inbound(&origin_public_key, TariMessageType::TransactionFinalized, &inner_msg);
                     let result = self.accept_finalized_transaction(
                         origin_public_key,
                         inner_msg,
@@ -491,6 +580,7 @@ where
                     let start = Instant::now();
                     let (origin_public_key, inner_msg) = msg.clone().into_origin_and_inner();
                     trace!(target: LOG_TARGET, "Handling Transaction Cancelled message, Trace: {}", msg.dht_header.message_tag);
+                    self.record_inbound(&origin_public_key, TariMessageType::TransactionCancelled, &inner_msg);
                     if let Err(e) = self.handle_transaction_cancelled_message(origin_public_key, inner_msg, ).await {
                         warn!(target: LOG_TARGET, "Error handing Transaction Cancelled Message: {:?}", e);
                     }
@@ -549,6 +639,12 @@ where
 
     /// This handler is called when requests arrive from the various streams
     #[allow(clippy::too_many_lines)]
+    #[instrument(
+        level = "debug",
+        name = "transaction_service::request",
+        skip_all,
+        fields(service = "transaction_service", request = %request)
+    )]
     async fn handle_request(
         &mut self,
         request: TransactionServiceRequest,
@@ -569,6 +665,20 @@ where
         let mut reply_channel = Some(reply_channel);
 
         trace!(target: LOG_TARGET, "Handling Service Request: {}", request);
+        // A transaction that pays less than the base node accepts would only be rejected when it is broadcast
+        if let Some(fee_per_gram) = request.fee_per_gram() {
+            let floor = fetch_fee_floor(self.base_node_service.clone()).await.fee_per_gram();
+            if fee_per_gram < floor {
+                let rp = reply_channel.take().expect("Cannot be missing");
+                let _result = rp
+                    .send(Err(TransactionServiceError::FeeBelowFloor { fee_per_gram, floor }))
+                    .map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send reply");
+                        e
+                    });
+                return Ok(());
+            }
+        }
         let response = match request {
             TransactionServiceRequest::SendTransaction {
                 dest_pubkey,
@@ -576,6 +686,9 @@ where
                 output_features,
                 fee_per_gram,
                 message,
+                idempotency_key,
+                ttl,
+                cancellation,
             } => {
                 let rp = reply_channel.take().expect("Cannot be missing");
                 self.send_transaction(
@@ -584,6 +697,9 @@ where
                     *output_features,
                     fee_per_gram,
                     message,
+                    idempotency_key,
+                    ttl,
+                    cancellation,
                     TransactionMetadata::default(),
                     send_transaction_join_handles,
                     transaction_broadcast_join_handles,
@@ -598,6 +714,7 @@ where
                 output_features,
                 fee_per_gram,
                 message,
+                memo,
             } => self
                 .send_one_sided_transaction(
                     dest_pubkey,
@@ -605,6 +722,7 @@ where
                     *output_features,
                     fee_per_gram,
                     message,
+                    memo,
                     transaction_broadcast_join_handles,
                 )
                 .await
@@ -615,6 +733,7 @@ where
                 output_features,
                 fee_per_gram,
                 message,
+                memo,
             } => self
                 .send_one_sided_to_stealth_address_transaction(
                     dest_pubkey,
@@ -622,10 +741,41 @@ where
                     *output_features,
                     fee_per_gram,
                     message,
+                    memo,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SweepAll {
+                dest_pubkey,
+                fee_per_gram,
+            } => self
+                .sweep_all(dest_pubkey, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionsSent),
+            TransactionServiceRequest::TransferUniqueAsset {
+                dest_pubkey,
+                asset_id,
+                fee_per_gram,
+                message,
+            } => self
+                .transfer_unique_asset(
+                    dest_pubkey,
+                    asset_id,
+                    fee_per_gram,
+                    message,
                     transaction_broadcast_join_handles,
                 )
                 .await
                 .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::RotateKeys { fee_per_gram } => self
+                .rotate_keys(fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(|(epoch, tx_ids)| TransactionServiceResponse::KeysRotated { epoch, tx_ids }),
+            TransactionServiceRequest::SendDecoyTransaction { amount, fee_per_gram } => self
+                .send_decoy_transaction(amount, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::BurnTari {
                 amount,
                 fee_per_gram,
@@ -646,10 +796,39 @@ where
                     .await?,
                 ))
             },
+            TransactionServiceRequest::SendExpiringVaultTransaction {
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            } => self
+                .send_expiring_vault_transaction(
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::CancelTransaction(tx_id) => self
                 .cancel_pending_transaction(tx_id)
                 .await
                 .map(|_| TransactionServiceResponse::TransactionCancelled),
+            TransactionServiceRequest::RequestResend(tx_id) => self
+                .request_resend(tx_id)
+                .map(|_| TransactionServiceResponse::ResendRequested),
+            TransactionServiceRequest::ExportTransactionMessage(tx_id) => self
+                .export_transaction_message(tx_id)
+                .map(TransactionServiceResponse::TransactionMessageExported),
+            TransactionServiceRequest::ImportTransactionMessage(bytes) => self
+                .import_transaction_message(&bytes, receive_transaction_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionMessageImported),
+            TransactionServiceRequest::ReplayProtocolRecording(path) => self
+                .replay_protocol_recording(&path, receive_transaction_join_handles)
+                .await
+                .map(TransactionServiceResponse::ProtocolRecordingReplayed),
             TransactionServiceRequest::GetPendingInboundTransactions => Ok(
                 TransactionServiceResponse::PendingInboundTransactions(self.db.get_pending_inbound_transactions()?),
             ),
@@ -703,8 +882,8 @@ where
             TransactionServiceRequest::SubmitTransactionToSelf(tx_id, tx, fee, amount, message) => self
                 .submit_transaction_to_self(transaction_broadcast_join_handles, tx_id, tx, fee, amount, message)
                 .map(|_| TransactionServiceResponse::TransactionSubmitted),
-            TransactionServiceRequest::GenerateCoinbaseTransaction(reward, fees, block_height) => self
-                .generate_coinbase_transaction(reward, fees, block_height)
+            TransactionServiceRequest::GenerateCoinbaseTransaction(reward, fees, block_height, extra) => self
+                .generate_coinbase_transaction(reward, fees, block_height, extra)
                 .await
                 .map(|tx| TransactionServiceResponse::CoinbaseTransactionGenerated(Box::new(tx))),
             TransactionServiceRequest::SetLowPowerMode => {
@@ -741,6 +920,10 @@ where
                 self.resources.config.num_confirmations_required = number;
                 Ok(TransactionServiceResponse::NumConfirmationsSet)
             },
+            TransactionServiceRequest::SetConfig(config) => {
+                self.set_config(*config).await?;
+                Ok(TransactionServiceResponse::ConfigSet)
+            },
             TransactionServiceRequest::ValidateTransactions => self
                 .start_transaction_validation_protocol(transaction_validation_join_handles)
                 .await
@@ -754,6 +937,102 @@ where
                 self.handle_get_fee_per_gram_stats_per_block_request(count, reply_channel);
                 return Ok(());
             },
+            TransactionServiceRequest::GetCounterpartyStats(counterparty) => Ok(
+                TransactionServiceResponse::CounterpartyStats(Box::new(self.db.get_counterparty_stats(&counterparty)?)),
+            ),
+            TransactionServiceRequest::GetSpendingSummary(period) => Ok(TransactionServiceResponse::SpendingSummary(
+                Box::new(self.get_spending_summary(period)?),
+            )),
+            TransactionServiceRequest::TagTransaction { tx_id, tag } => {
+                let tag = normalize_transaction_tag(&tag)?;
+                if self.db.get_any_transaction(tx_id)?.is_none() {
+                    return Err(TransactionServiceError::TransactionDoesNotExistError);
+                }
+                self.db.add_transaction_tag(tx_id, &tag)?;
+                Ok(TransactionServiceResponse::TransactionTagged)
+            },
+            TransactionServiceRequest::UntagTransaction { tx_id, tag } => {
+                self.db.remove_transaction_tag(tx_id, tag.trim())?;
+                Ok(TransactionServiceResponse::TransactionUntagged)
+            },
+            TransactionServiceRequest::GetTransactionTags(tx_id) => Ok(TransactionServiceResponse::TransactionTags(
+                self.db.get_transaction_tags(tx_id)?,
+            )),
+            TransactionServiceRequest::GetAllTransactionTags => Ok(TransactionServiceResponse::AllTransactionTags(
+                self.db.get_all_transaction_tags()?,
+            )),
+            TransactionServiceRequest::GetTransactionsByTag(tag) => {
+                let mut transactions = Vec::new();
+                for tx_id in self.db.get_tagged_transaction_ids(tag.trim())? {
+                    if let Some(tx) = self.db.get_any_transaction(tx_id)? {
+                        transactions.push(tx);
+                    }
+                }
+                Ok(TransactionServiceResponse::TaggedTransactions(transactions))
+            },
+            TransactionServiceRequest::AddTransactionAttachment(attachment) => {
+                let attachment =
+                    normalize_transaction_attachment(*attachment, self.resources.config.max_attachment_size)?;
+                if self.db.get_any_transaction(attachment.tx_id)?.is_none() {
+                    return Err(TransactionServiceError::TransactionDoesNotExistError);
+                }
+                self.db.save_transaction_attachment(attachment)?;
+                Ok(TransactionServiceResponse::TransactionAttachmentAdded)
+            },
+            TransactionServiceRequest::GetTransactionAttachments(tx_id) => Ok(
+                TransactionServiceResponse::TransactionAttachments(self.db.get_transaction_attachments(tx_id)?),
+            ),
+            TransactionServiceRequest::RemoveTransactionAttachment { tx_id, name } => {
+                self.db.remove_transaction_attachment(tx_id, name.trim())?;
+                Ok(TransactionServiceResponse::TransactionAttachmentRemoved)
+            },
+            TransactionServiceRequest::ImportTransactions {
+                completed,
+                inbound,
+                outbound,
+            } => Ok(TransactionServiceResponse::TransactionsImported(
+                self.import_transactions(completed, inbound, outbound)?,
+            )),
+            TransactionServiceRequest::StartAggregatedSenderSession(recipient) => self
+                .start_aggregated_sender_session(recipient)
+                .map(TransactionServiceResponse::AggregatedSenderSessionStarted),
+            TransactionServiceRequest::AddAggregatedSenderPartyKeys { session, party_keys } => self
+                .add_aggregated_sender_party_keys(&session, party_keys)
+                .map(TransactionServiceResponse::AggregatedSenderNonceCommitment),
+            TransactionServiceRequest::AddAggregatedSenderNonceCommitments { session, commitments } => self
+                .add_aggregated_sender_nonce_commitments(&session, commitments)
+                .map(TransactionServiceResponse::AggregatedSenderNonceReveal),
+            TransactionServiceRequest::SignAggregatedSenderOutput {
+                session,
+                reveals,
+                output,
+            } => self
+                .sign_aggregated_sender_output(&session, &reveals, &output)
+                .map(|signature| TransactionServiceResponse::AggregatedSenderPartialSignature(Box::new(signature))),
+            #[cfg(feature = "header_sync")]
+            TransactionServiceRequest::GenerateSpendProof(commitment) => {
+                let reply_channel = reply_channel.take().expect("reply_channel is Some");
+                self.handle_generate_spend_proof_request(commitment, reply_channel);
+                return Ok(());
+            },
+            TransactionServiceRequest::GetPendingApprovalTransactions => {
+                self.expire_pending_approvals()?;
+                Ok(TransactionServiceResponse::PendingApprovalTransactions(
+                    self.db.get_pending_approval_transactions()?,
+                ))
+            },
+            TransactionServiceRequest::ApproveTransaction { tx_id, approval_token } => {
+                let rp = reply_channel.take().expect("Cannot be missing");
+                self.approve_transaction(
+                    tx_id,
+                    approval_token,
+                    send_transaction_join_handles,
+                    transaction_broadcast_join_handles,
+                    rp,
+                )
+                .await?;
+                return Ok(());
+            },
         };
 
         // If the individual handlers did not already send the API response then do it here.
@@ -772,6 +1051,7 @@ where
         reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
     ) {
         let mut connectivity = self.resources.connectivity.clone();
+        let base_node_service = self.base_node_service.clone();
 
         let query_base_node_fut = async move {
             let mut client = connectivity
@@ -785,13 +1065,14 @@ where
                 })
                 .await?;
             let mut resp = FeePerGramStatsResponse::from(resp);
-            // If there are no transactions in the mempool, populate with a minimal fee per gram.
+            resp.fee_floor = fetch_fee_floor(base_node_service).await.fee_per_gram();
+            // If there are no transactions in the mempool, populate with the minimal fee per gram.
             if resp.stats.is_empty() {
                 resp.stats = vec![FeePerGramStat {
                     order: 0,
-                    min_fee_per_gram: 1.into(),
-                    avg_fee_per_gram: 1.into(),
-                    max_fee_per_gram: 1.into(),
+                    min_fee_per_gram: resp.fee_floor,
+                    avg_fee_per_gram: resp.fee_floor,
+                    max_fee_per_gram: resp.fee_floor,
                 }]
             }
             Ok(TransactionServiceResponse::FeePerGramStatsPerBlock(resp))
@@ -808,12 +1089,87 @@ where
         });
     }
 
+    /// Proves that the output with the given commitment was spent by a mined transaction of the wallet, using the
+    /// verified inclusion proof of the block the transaction was mined in
+    #[cfg(feature = "header_sync")]
+    fn handle_generate_spend_proof_request(
+        &self,
+        commitment: Commitment,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) {
+        let db = self.db.clone();
+        let header_sync = self.header_sync.clone();
+
+        let generate_proof_fut = async move {
+            let mut header_sync = header_sync
+                .ok_or_else(|| TransactionServiceError::SpendProofError("Header sync is not enabled".to_string()))?;
+            let (mined_height, mined_in_block, input, kernel) = db
+                .get_completed_transactions()?
+                .into_values()
+                .find_map(|tx| {
+                    let body = &tx.transaction.body;
+                    let input = body
+                        .inputs()
+                        .iter()
+                        .find(|i| i.commitment().map(|c| *c == commitment).unwrap_or(false))?;
+                    Some((
+                        tx.mined_height?,
+                        tx.mined_in_block?,
+                        input.clone(),
+                        body.kernels().first()?.clone(),
+                    ))
+                })
+                .ok_or_else(|| {
+                    TransactionServiceError::SpendProofError(format!(
+                        "Output {} was not spent by a mined transaction",
+                        commitment.to_hex()
+                    ))
+                })?;
+
+            let header = header_sync
+                .get_verified_header(mined_height)
+                .await?
+                .ok_or_else(|| {
+                    TransactionServiceError::SpendProofError(format!("Header #{} is not verified", mined_height))
+                })?
+                .header()
+                .clone();
+            let inclusion_proof = header_sync
+                .get_verified_inclusion_proof(mined_height, mined_in_block)
+                .await?;
+            let proof = SpendProof {
+                header,
+                input,
+                kernel,
+                inclusion_proof,
+            };
+            // Fails if the base node does not include the input hashes in the inclusion proof
+            proof
+                .verify()
+                .map_err(|e| TransactionServiceError::SpendProofError(e.to_string()))?;
+            Ok(TransactionServiceResponse::SpendProof(Box::new(proof)))
+        };
+
+        tokio::spawn(async move {
+            let resp = generate_proof_fut.await;
+            if reply_channel.send(resp).is_err() {
+                warn!(
+                    target: LOG_TARGET,
+                    "handle_generate_spend_proof_request: service reply cancelled"
+                );
+            }
+        });
+    }
+
     async fn handle_base_node_service_event(
         &mut self,
         event: Arc<BaseNodeEvent>,
         transaction_validation_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<OperationId, TransactionServiceProtocolError<OperationId>>>,
         >,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
     ) {
         match (*event).clone() {
             BaseNodeEvent::BaseNodeStateChanged(state) => {
@@ -831,10 +1187,62 @@ where
                             warn!(target: LOG_TARGET, "Error validating  txos: {:?}", e);
                             e
                         });
+                    if let Err(e) = self.expire_pending_approvals() {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Error expiring transactions pending approval: {:?}", e
+                        );
+                    }
                 }
                 self.last_seen_tip_height = state.chain_metadata.map(|cm| cm.height_of_longest_chain());
             },
-            BaseNodeEvent::NewBlockDetected(_) => {},
+            // New transactions are not broadcast while the base nodes disagree on the tip, as they could be mined
+            // on a chain that is reorged out
+            BaseNodeEvent::ChainSplitSuspected(_) => {
+                self.chain_split_suspected = true;
+            },
+            BaseNodeEvent::ChainSplitResolved => {
+                self.chain_split_suspected = false;
+                if let Err(e) = self.restart_broadcast_protocols(transaction_broadcast_join_handles) {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error restarting transaction broadcast protocols after a chain split: {:?}", e
+                    );
+                }
+            },
+            // The expiries are computed with the corrected time, which changes with the clock skew
+            BaseNodeEvent::ClockSkewDetected(_) | BaseNodeEvent::ClockSkewResolved => {
+                if let Err(e) = self.expire_pending_approvals() {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error expiring transactions pending approval: {:?}", e
+                    );
+                }
+            },
+            BaseNodeEvent::NewBlockDetected(_) | BaseNodeEvent::BaseNodeIdentityChanged(_) => {},
+        }
+    }
+
+    /// A reorg does not always change the tip height, so transactions are revalidated as soon as one is observed
+    async fn handle_chain_event(
+        &mut self,
+        event: Arc<ChainEvent>,
+        transaction_validation_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<OperationId, TransactionServiceProtocolError<OperationId>>>,
+        >,
+    ) {
+        if let ChainEvent::Reorged { from, to } = *event {
+            debug!(
+                target: LOG_TARGET,
+                "Revalidating transactions after a reorg from height {} to {}", from, to
+            );
+            let _operation_id = self
+                .start_transaction_validation_protocol(transaction_validation_join_handles)
+                .await
+                .map_err(|e| {
+                    warn!(target: LOG_TARGET, "Error validating  txos: {:?}", e);
+                    e
+                });
         }
     }
 
@@ -865,6 +1273,8 @@ where
     /// 'dest_pubkey': The Comms pubkey of the recipient node
     /// 'amount': The amount of Tari to send to the recipient
     /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    /// 'ttl': The time after which both parties cancel the transaction if it has not been finalized
+    /// 'cancellation': Aborts the send if it is cancelled, or its deadline is reached, before the transaction is sent
     pub async fn send_transaction(
         &mut self,
         dest_pubkey: CommsPublicKey,
@@ -872,6 +1282,9 @@ where
         output_features: OutputFeatures,
         fee_per_gram: MicroTari,
         message: String,
+        idempotency_key: Option<String>,
+        ttl: Option<Duration>,
+        cancellation: CancellationToken,
         tx_meta: TransactionMetadata,
         join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TransactionSendResult, TransactionServiceProtocolError<TxId>>>,
@@ -881,8 +1294,115 @@ where
         >,
         reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
     ) -> Result<(), TransactionServiceError> {
+        if let Some(key) = idempotency_key.as_deref() {
+            let response = match self.check_idempotency_key(key, &dest_pubkey, amount) {
+                Ok(None) => None,
+                Ok(Some((tx_id, status))) => {
+                    Some(Ok(TransactionServiceResponse::TransactionAlreadySent(tx_id, status)))
+                },
+                Err(e) => Some(Err(e)),
+            };
+            if let Some(response) = response {
+                let _result = reply_channel.send(response).map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+                return Ok(());
+            }
+        }
+
+        if self.node_identity.public_key() != &dest_pubkey {
+            if let Err(e) = self.check_spending_policy(Some(&dest_pubkey), amount) {
+                let _result = reply_channel.send(Err(e)).map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+                return Ok(());
+            }
+        }
+
+        let expires_at = match ttl.map(utc_after) {
+            Some(None) => {
+                let _result = reply_channel
+                    .send(Err(TransactionServiceError::ServiceError(
+                        "The transaction time-to-live is out of range".to_string(),
+                    )))
+                    .map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send service reply");
+                        e
+                    });
+                return Ok(());
+            },
+            expires_at => expires_at.flatten(),
+        };
+
         let tx_id = TxId::new_random();
+        if let Some(key) = idempotency_key {
+            self.db
+                .save_idempotency_key(IdempotencyKeyRecord::new(key, tx_id, dest_pubkey.clone(), amount))?;
+        }
+
+        if self.node_identity.public_key() != &dest_pubkey {
+            let result = self.queue_for_approval_if_required(
+                tx_id,
+                ApprovalPaymentType::Standard,
+                &dest_pubkey,
+                amount,
+                fee_per_gram,
+                &output_features,
+                &message,
+                None,
+                expires_at,
+            );
+            if !matches!(result, Ok(false)) {
+                let _result = reply_channel
+                    .send(result.map(|_| TransactionServiceResponse::TransactionSent(tx_id)))
+                    .map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send service reply");
+                        e
+                    });
+                return Ok(());
+            }
+        }
+
+        self.start_send_transaction(
+            tx_id,
+            dest_pubkey,
+            amount,
+            output_features,
+            fee_per_gram,
+            message,
+            tx_meta,
+            expires_at,
+            cancellation,
+            join_handles,
+            transaction_broadcast_join_handles,
+            reply_channel,
+        )
+        .await
+    }
 
+    /// Starts the send protocol for a transaction, or completes it immediately if it is a spend-to-self
+    #[instrument(skip_all, fields(service = "transaction_service", tx_id = %tx_id))]
+    async fn start_send_transaction(
+        &mut self,
+        tx_id: TxId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        tx_meta: TransactionMetadata,
+        expires_at: Option<NaiveDateTime>,
+        cancellation: CancellationToken,
+        join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TransactionSendResult, TransactionServiceProtocolError<TxId>>>,
+        >,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) -> Result<(), TransactionServiceError> {
         // If we're paying ourselves, let's complete and submit the transaction immediately
         if self.node_identity.public_key() == &dest_pubkey {
             debug!(
@@ -939,16 +1459,15 @@ where
         }
 
         let (tx_reply_sender, tx_reply_receiver) = mpsc::channel(100);
-        let (cancellation_sender, cancellation_receiver) = oneshot::channel();
         self.pending_transaction_reply_senders.insert(tx_id, tx_reply_sender);
-        self.send_transaction_cancellation_senders
-            .insert(tx_id, cancellation_sender);
+        self.send_transaction_cancellation_tokens
+            .insert(tx_id, cancellation.without_deadline());
 
         let protocol = TransactionSendProtocol::new(
             tx_id,
             self.resources.clone(),
             tx_reply_receiver,
-            cancellation_receiver,
+            cancellation,
             dest_pubkey,
             amount,
             fee_per_gram,
@@ -959,6 +1478,7 @@ where
             None,
             self.last_seen_tip_height,
             None,
+            expires_at,
         );
         let join_handle = tokio::spawn(protocol.execute());
         join_handles.push(join_handle);
@@ -966,44 +1486,399 @@ where
         Ok(())
     }
 
-    /// broadcasts a SHA-XTR atomic swap transaction
-    /// # Arguments
-    /// 'dest_pubkey': The Comms pubkey of the recipient node
-    /// 'amount': The amount of Tari to send to the recipient
-    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
-    #[allow(clippy::too_many_lines)]
-    pub async fn send_sha_atomic_swap_transaction(
-        &mut self,
-        dest_pubkey: CommsPublicKey,
+    /// Returns the TxId and status of the transaction previously created with this idempotency key, if any. A
+    /// transaction that is awaiting approval or has not been recorded yet is reported as queued. A key whose original
+    /// send failed before the transaction was recorded is released so that it can be used again.
+    fn check_idempotency_key(
+        &self,
+        idempotency_key: &str,
+        dest_pubkey: &CommsPublicKey,
         amount: MicroTari,
-        fee_per_gram: MicroTari,
-        message: String,
-        transaction_broadcast_join_handles: &mut FuturesUnordered<
-            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
-        >,
-    ) -> Result<Box<(TxId, PublicKey, TransactionOutput)>, TransactionServiceError> {
-        let tx_id = TxId::new_random();
-        // this can be anything, so lets generate a random private key
-        let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
-        let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
+    ) -> Result<Option<(TxId, TransactionStatus)>, TransactionServiceError> {
+        let record = match self.db.get_idempotency_key(idempotency_key)? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        if &record.destination_public_key != dest_pubkey || record.amount != amount {
+            return Err(TransactionServiceError::IdempotencyKeyConflict(
+                idempotency_key.to_string(),
+            ));
+        }
+        let status = match self.db.get_any_transaction(record.tx_id)? {
+            Some(WalletTransaction::PendingInbound(tx)) => Some(tx.status),
+            Some(WalletTransaction::PendingOutbound(tx)) => Some(tx.status),
+            Some(WalletTransaction::Completed(tx)) => Some(tx.status),
+            None if self.pending_transaction_reply_senders.contains_key(&record.tx_id) ||
+                self.db.get_pending_approval_transaction(record.tx_id)?.is_some() =>
+            {
+                Some(TransactionStatus::Queued)
+            },
+            None => None,
+        };
+        if let Some(status) = status {
+            info!(
+                target: LOG_TARGET,
+                "Idempotency key `{}` was already used for TxId: {} ({}), not creating a new transaction",
+                idempotency_key,
+                record.tx_id,
+                status
+            );
+            return Ok(Some((record.tx_id, status)));
+        }
+        self.db.remove_idempotency_key(idempotency_key)?;
+        Ok(None)
+    }
 
-        // lets make the unlock height a day from now, 2 min blocks which gives us 30 blocks per hour * 24 hours
-        let height = self.last_seen_tip_height.unwrap_or(0) + (24 * 30);
+    /// Checks an outgoing transaction against the configured spending policy, publishing an event if it is rejected
+    fn check_spending_policy(
+        &self,
+        destination: Option<&CommsPublicKey>,
+        amount: MicroTari,
+    ) -> Result<(), TransactionServiceError> {
+        let policy = &self.resources.config.spending_policy;
+        let spent_in_last_day = if policy.daily_spend_limit.is_some() {
+            self.get_amount_sent_in_last_day()?
+        } else {
+            MicroTari::zero()
+        };
+        if let Err(violation) = policy.check(destination, amount, spent_in_last_day) {
+            warn!(target: LOG_TARGET, "Outgoing transaction rejected: {}", violation);
+            let _size = self
+                .event_publisher
+                .send(Arc::new(TransactionEvent::SpendingPolicyViolation(violation.clone())));
+            return Err(violation.into());
+        }
+        Ok(())
+    }
 
-        // lets create the HTLC script
-        let script = script!(
-            HashSha256 PushHash(Box::new(hash)) Equal IfThen
-                PushPubKey(Box::new(dest_pubkey.clone()))
-            Else
-                CheckHeightVerify(height) PushPubKey(Box::new(self.node_identity.public_key().clone()))
-            EndIf
-        );
+    /// The total value of the outgoing transactions created in the last 24 hours that have not been cancelled
+    fn get_amount_sent_in_last_day(&self) -> Result<MicroTari, TransactionServiceError> {
+        let since = Utc::now().naive_utc() - chrono::Duration::days(1);
+        let completed_txs = self.db.get_completed_transactions()?;
+        let pending_txs = self.db.get_pending_outbound_transactions()?;
+        let amount = completed_txs
+            .values()
+            .filter(|tx| tx.direction == TransactionDirection::Outbound && tx.timestamp >= since)
+            .map(|tx| tx.amount)
+            .chain(
+                pending_txs
+                    .values()
+                    .filter(|tx| tx.timestamp >= since)
+                    .map(|tx| tx.amount),
+            )
+            .fold(MicroTari::zero(), |total, amount| {
+                total.checked_add(amount).unwrap_or_else(|| u64::MAX.into())
+            });
+        Ok(amount)
+    }
 
-        // Empty covenant
-        let covenant = Covenant::default();
+    /// Groups the completed transactions of the last `period` by counterparty
+    fn get_spending_summary(&self, period: Duration) -> Result<SpendingSummary, TransactionServiceError> {
+        let since = chrono::Duration::from_std(period)
+            .ok()
+            .and_then(|period| Utc::now().naive_utc().checked_sub_signed(period));
+        let counterparties = self.db.get_counterparty_stats_since(since)?;
+        Ok(SpendingSummary::new(since, counterparties))
+    }
 
-        // Default range proof
-        let minimum_value_promise = MicroTari::zero();
+    fn start_aggregated_sender_session(&self, recipient: CommsPublicKey) -> Result<PublicKey, TransactionServiceError> {
+        // The key is only used for this payment
+        let session = SenderSigningSession::new(PrivateKey::random(&mut OsRng), recipient);
+        let public_key = session.public_key().clone();
+        self.db.save_aggregated_sender_session(session)?;
+        Ok(public_key)
+    }
+
+    fn get_aggregated_sender_session(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<SenderSigningSession, TransactionServiceError> {
+        self.db
+            .get_aggregated_sender_session(public_key)?
+            .ok_or_else(|| TransactionServiceError::AggregatedSenderSessionNotFound(public_key.clone()))
+    }
+
+    fn add_aggregated_sender_party_keys(
+        &self,
+        public_key: &PublicKey,
+        party_keys: Vec<PublicKey>,
+    ) -> Result<NonceCommitment, TransactionServiceError> {
+        let mut session = self.get_aggregated_sender_session(public_key)?;
+        let commitment = session.add_party_keys(party_keys)?;
+        self.db.save_aggregated_sender_session(session)?;
+        Ok(commitment)
+    }
+
+    fn add_aggregated_sender_nonce_commitments(
+        &self,
+        public_key: &PublicKey,
+        commitments: Vec<NonceCommitment>,
+    ) -> Result<NonceReveal, TransactionServiceError> {
+        let mut session = self.get_aggregated_sender_session(public_key)?;
+        let reveal = session.add_nonce_commitments(commitments)?;
+        self.db.save_aggregated_sender_session(session)?;
+        Ok(reveal)
+    }
+
+    fn sign_aggregated_sender_output(
+        &self,
+        public_key: &PublicKey,
+        reveals: &[NonceReveal],
+        output: &TransactionOutput,
+    ) -> Result<PartialMetadataSignature, TransactionServiceError> {
+        let mut session = self.get_aggregated_sender_session(public_key)?;
+        if output.script != one_sided_script(session.recipient()) {
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "The output is not a one-sided payment to the recipient of the session".to_string(),
+            ));
+        }
+        let signature = session.sign(reveals, output)?;
+        // The nonce may never sign again, so the session is removed before the signature is released
+        self.db.remove_aggregated_sender_session(public_key)?;
+        Ok(signature)
+    }
+
+    /// Stores transactions exported from another wallet database as they are, so cancelled transactions stay cancelled.
+    /// Transactions whose id is already in use are skipped.
+    fn import_transactions(
+        &self,
+        completed: Vec<CompletedTransaction>,
+        inbound: Vec<InboundTransaction>,
+        outbound: Vec<OutboundTransaction>,
+    ) -> Result<usize, TransactionServiceError> {
+        let mut num_imported = 0;
+        for tx in completed {
+            if self.db.get_any_transaction(tx.tx_id)?.is_none() {
+                self.db.insert_completed_transaction(tx.tx_id, tx)?;
+                num_imported += 1;
+            }
+        }
+        for tx in inbound {
+            if self.db.get_any_transaction(tx.tx_id)?.is_none() {
+                self.db.add_pending_inbound_transaction(tx.tx_id, tx)?;
+                num_imported += 1;
+            }
+        }
+        for tx in outbound {
+            if self.db.get_any_transaction(tx.tx_id)?.is_none() {
+                self.db.add_pending_outbound_transaction(tx.tx_id, tx)?;
+                num_imported += 1;
+            }
+        }
+        info!(target: LOG_TARGET, "Imported {} transaction(s)", num_imported);
+        Ok(num_imported)
+    }
+
+    /// Holds an outgoing transaction back until it is approved if its value is above the approval threshold. Returns
+    /// true if the transaction was queued.
+    fn queue_for_approval_if_required(
+        &self,
+        tx_id: TxId,
+        payment_type: ApprovalPaymentType,
+        dest_pubkey: &CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        output_features: &OutputFeatures,
+        message: &str,
+        memo: Option<&str>,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<bool, TransactionServiceError> {
+        let config = &self.resources.config.approval;
+        if !config.requires_approval(amount) {
+            return Ok(false);
+        }
+        let timestamp = Utc::now().naive_utc();
+        let expiry = chrono::Duration::from_std(config.expiry)
+            .map_err(|e| TransactionServiceError::ServiceError(e.to_string()))?;
+        let approval_expiry = corrected_utc_now() + expiry;
+        self.db.add_pending_approval_transaction(PendingApprovalTransaction {
+            tx_id,
+            payment_type,
+            destination_public_key: dest_pubkey.clone(),
+            amount,
+            fee_per_gram,
+            output_features: output_features.clone(),
+            message: message.to_string(),
+            memo: memo.map(ToString::to_string),
+            timestamp,
+            // A transaction cannot be approved after it has expired
+            expiry_timestamp: expires_at.map_or(approval_expiry, |expires_at| expires_at.min(approval_expiry)),
+            expires_at,
+        })?;
+        info!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) of {} is waiting for approval", tx_id, amount
+        );
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionPendingApproval(tx_id)));
+        Ok(true)
+    }
+
+    /// Approves a transaction that is pending approval and sends it. The reply is sent once the transaction has been
+    /// handed to the send protocol, or immediately if the approval fails.
+    async fn approve_transaction(
+        &mut self,
+        tx_id: TxId,
+        approval_token: String,
+        join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TransactionSendResult, TransactionServiceProtocolError<TxId>>>,
+        >,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) -> Result<(), TransactionServiceError> {
+        let pending_tx = match self.verify_approval(tx_id, &approval_token) {
+            Ok(pending_tx) => pending_tx,
+            Err(e) => {
+                let _result = reply_channel.send(Err(e)).map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+                return Ok(());
+            },
+        };
+        info!(target: LOG_TARGET, "Transaction (TxId: {}) approved", tx_id);
+
+        let script = match pending_tx.payment_type {
+            ApprovalPaymentType::Standard => {
+                return self
+                    .start_send_transaction(
+                        tx_id,
+                        pending_tx.destination_public_key,
+                        pending_tx.amount,
+                        pending_tx.output_features,
+                        pending_tx.fee_per_gram,
+                        pending_tx.message,
+                        TransactionMetadata::default(),
+                        pending_tx.expires_at,
+                        CancellationToken::new(),
+                        join_handles,
+                        transaction_broadcast_join_handles,
+                        reply_channel,
+                    )
+                    .await;
+            },
+            ApprovalPaymentType::OneSided => one_sided_script(&pending_tx.destination_public_key),
+            ApprovalPaymentType::OneSidedToStealthAddress => {
+                one_sided_to_stealth_address_script(&pending_tx.destination_public_key)
+            },
+        };
+        let result = self
+            .send_one_sided_or_stealth(
+                tx_id,
+                pending_tx.destination_public_key,
+                pending_tx.amount,
+                UtxoSelectionCriteria::default(),
+                pending_tx.output_features,
+                pending_tx.fee_per_gram,
+                pending_tx.message,
+                pending_tx.memo,
+                transaction_broadcast_join_handles,
+                script,
+            )
+            .await;
+        let _result = reply_channel
+            .send(result.map(TransactionServiceResponse::TransactionSent))
+            .map_err(|e| {
+                warn!(target: LOG_TARGET, "Failed to send service reply");
+                e
+            });
+        Ok(())
+    }
+
+    /// Checks the approval token of a transaction that is pending approval and removes it from the approval queue. The
+    /// spending policy is checked again first, as other transactions may have been sent since this one was requested;
+    /// a transaction that no longer complies with it stays in the queue.
+    fn verify_approval(
+        &mut self,
+        tx_id: TxId,
+        approval_token: &str,
+    ) -> Result<PendingApprovalTransaction, TransactionServiceError> {
+        let pending_tx = self
+            .db
+            .get_pending_approval_transaction(tx_id)?
+            .ok_or(TransactionApprovalError::NotPendingApproval(tx_id))?;
+        if pending_tx.is_expired() {
+            self.expire_pending_approval(tx_id)?;
+            return Err(TransactionApprovalError::ApprovalExpired(tx_id).into());
+        }
+        let unix_timestamp = Utc::now().timestamp().try_into().unwrap_or_default();
+        let totp_step =
+            self.resources
+                .config
+                .approval
+                .verify_token(tx_id, approval_token, unix_timestamp, self.last_totp_step)?;
+        self.check_spending_policy(Some(&pending_tx.destination_public_key), pending_tx.amount)?;
+        self.db.remove_pending_approval_transaction(tx_id)?;
+        if totp_step.is_some() {
+            self.last_totp_step = totp_step;
+        }
+        Ok(pending_tx)
+    }
+
+    /// Discards the transactions that were not approved before their expiry time
+    fn expire_pending_approvals(&self) -> Result<(), TransactionServiceError> {
+        for pending_tx in self.db.get_pending_approval_transactions()? {
+            if pending_tx.is_expired() {
+                self.expire_pending_approval(pending_tx.tx_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn expire_pending_approval(&self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        self.db.remove_pending_approval_transaction(tx_id)?;
+        info!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) was not approved in time and has been discarded", tx_id
+        );
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionApprovalExpired(tx_id)));
+        Ok(())
+    }
+
+    /// broadcasts a SHA-XTR atomic swap transaction
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    #[allow(clippy::too_many_lines)]
+    pub async fn send_sha_atomic_swap_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<Box<(TxId, PublicKey, TransactionOutput)>, TransactionServiceError> {
+        self.check_spending_policy(Some(&dest_pubkey), amount)?;
+        let tx_id = TxId::new_random();
+        // this can be anything, so lets generate a random private key
+        let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
+
+        // lets make the unlock height a day from now, 2 min blocks which gives us 30 blocks per hour * 24 hours
+        let height = self.last_seen_tip_height.unwrap_or(0) + (24 * 30);
+
+        // lets create the HTLC script
+        let script = script!(
+            HashSha256 PushHash(Box::new(hash)) Equal IfThen
+                PushPubKey(Box::new(dest_pubkey.clone()))
+            Else
+                CheckHeightVerify(height) PushPubKey(Box::new(self.node_identity.public_key().clone()))
+            EndIf
+        );
+
+        // Empty covenant
+        let covenant = Covenant::default();
+
+        // Default range proof
+        let minimum_value_promise = MicroTari::zero();
 
         // Prepare sender part of the transaction
         let mut stp = self
@@ -1152,19 +2027,50 @@ where
         Ok(Box::new((tx_id, pre_image, output)))
     }
 
-    async fn send_one_sided_or_stealth(
+    /// Pays a recipient that may be offline with an expiring vault output. The recipient can claim the output until
+    /// `expiring_vault_lifetime` blocks from now, after which only this wallet can spend it. The transaction is
+    /// broadcast straight away and the finalized transaction is sent to the recipient, directly or via
+    /// store-and-forward, so that it can claim the output when it comes online. A recipient that misses the message
+    /// still finds the output when it scans the chain.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    #[allow(clippy::too_many_lines)]
+    pub async fn send_expiring_vault_transaction(
         &mut self,
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
-        output_features: OutputFeatures,
         fee_per_gram: MicroTari,
         message: String,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
-        script: TariScript,
     ) -> Result<TxId, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "Expiring vault payments to self are not supported".to_string(),
+            ));
+        }
+        if self.resources.config.expiring_vault_lifetime == 0 {
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "The expiring vault lifetime must be at least one block".to_string(),
+            ));
+        }
+        self.check_spending_policy(Some(&dest_pubkey), amount)?;
         let tx_id = TxId::new_random();
+        let expiry_height = self
+            .last_seen_tip_height
+            .unwrap_or(0)
+            .saturating_add(self.resources.config.expiring_vault_lifetime);
+        let script = ExpiringVaultScript::new(
+            dest_pubkey.clone(),
+            self.node_identity.public_key().clone(),
+            expiry_height,
+        )
+        .to_script();
+        let covenant = Covenant::default();
+        let minimum_value_promise = MicroTari::zero();
 
         // Prepare sender part of the transaction
         let mut stp = self
@@ -1173,6 +2079,177 @@ where
                 tx_id,
                 amount,
                 UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                fee_per_gram,
+                TransactionMetadata::default(),
+                message.clone(),
+                script.clone(),
+                covenant.clone(),
+                minimum_value_promise,
+            )
+            .await?;
+
+        // This call is needed to advance the state from `SingleRoundMessageReady` to `SingleRoundMessageReady`,
+        // but the returned value is not used
+        let _single_round_sender_data = stp
+            .build_single_round_message()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        self.output_manager_service
+            .confirm_pending_transaction(tx_id)
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        // Prepare receiver part of the transaction
+
+        // The spending key is the Diffie-Hellman shared secret with the recipient, as for a one-sided payment, so the
+        // recipient can claim the output from the transaction alone
+        let sender_offset_private_key = stp
+            .get_recipient_sender_offset_private_key(0)
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        let spend_key =
+            PrivateKey::from_bytes(CommsPublicKey::shared_secret(&sender_offset_private_key, &dest_pubkey).as_bytes())
+                .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        let sender_message = TransactionSenderMessage::new_single_round_message(stp.get_single_round_message()?);
+        let rewind_blinding_key = PrivateKey::from_bytes(&hash_secret_key(&spend_key))?;
+        let encryption_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_blinding_key))?;
+        let rewind_data = RewindData {
+            rewind_blinding_key,
+            encryption_key,
+        };
+
+        let rtp = ReceiverTransactionProtocol::new_with_rewindable_output(
+            sender_message,
+            PrivateKey::random(&mut OsRng),
+            spend_key.clone(),
+            &self.resources.factories,
+            &rewind_data,
+        );
+
+        let recipient_reply = rtp.get_signed_data()?.clone();
+        let output = recipient_reply.output.clone();
+        let commitment = self
+            .resources
+            .factories
+            .commitment
+            .commit_value(&spend_key, amount.into());
+        let encrypted_value = EncryptedValue::encrypt_value(&rewind_data.encryption_key, &commitment, amount)?;
+        // The refund can only be spent by this wallet once the vault has expired
+        let refund_output = UnblindedOutput::new_current_version(
+            amount,
+            spend_key,
+            output.features.clone(),
+            script,
+            ExecutionStack::default(),
+            self.node_identity.secret_key().clone(),
+            output.sender_offset_public_key.clone(),
+            output.metadata_signature.clone(),
+            expiry_height,
+            covenant,
+            encrypted_value,
+            minimum_value_promise,
+        );
+
+        // Start finalizing
+
+        stp.add_single_recipient_info(recipient_reply, &self.resources.factories.range_proof)
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        // Finalize
+
+        stp.finalize(
+            &self.resources.factories,
+            None,
+            self.last_seen_tip_height.unwrap_or(u64::MAX),
+        )
+        .map_err(|e| {
+            error!(
+                target: LOG_TARGET,
+                "Transaction (TxId: {}) could not be finalized. Failure error: {:?}", tx_id, e,
+            );
+            TransactionServiceProtocolError::new(tx_id, e.into())
+        })?;
+        info!(
+            target: LOG_TARGET,
+            "Finalized expiring vault transaction TxId: {}, expiring at height {}", tx_id, expiry_height
+        );
+
+        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
+        // there are no subscribers.
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        let tx = stp
+            .get_transaction()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?
+            .clone();
+        let fee = stp
+            .get_fee_amount()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        self.output_manager_service
+            .add_rewindable_output_with_tx_id(tx_id, refund_output, None, Some(rewind_data))
+            .await?;
+        self.submit_transaction(
+            transaction_broadcast_join_handles,
+            CompletedTransaction::new(
+                tx_id,
+                self.resources.node_identity.public_key().clone(),
+                dest_pubkey.clone(),
+                amount,
+                fee,
+                tx.clone(),
+                TransactionStatus::Completed,
+                message,
+                Utc::now().naive_utc(),
+                TransactionDirection::Outbound,
+                None,
+                None,
+                None,
+            ),
+        )?;
+
+        // Deliver the claim to the recipient, which picks it up from store-and-forward if it is offline
+        tokio::spawn(send_finalized_transaction_message(
+            tx_id,
+            tx,
+            dest_pubkey,
+            self.resources.messaging.clone(),
+            self.resources.config.clone(),
+        ));
+
+        Ok(tx_id)
+    }
+
+    async fn send_one_sided_or_stealth(
+        &mut self,
+        tx_id: TxId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        utxo_selection: UtxoSelectionCriteria,
+        mut output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        memo: Option<String>,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+        script: TariScript,
+    ) -> Result<TxId, TransactionServiceError> {
+        // The memo can only be encrypted once the spending key of the recipient output is known, so space for it is
+        // reserved in the output features metadata to include it in the fee
+        if let Some(memo) = memo.as_ref() {
+            output_features.metadata = vec![0u8; EncryptedMemo::encrypted_size(memo)];
+        }
+
+        // Prepare sender part of the transaction
+        let mut stp = self
+            .output_manager_service
+            .prepare_transaction_to_send(
+                tx_id,
+                amount,
+                utxo_selection,
                 output_features,
                 fee_per_gram,
                 TransactionMetadata::default(),
@@ -1206,9 +2283,13 @@ where
         )
         .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
 
-        let sender_message = TransactionSenderMessage::new_single_round_message(stp.get_single_round_message()?);
         let rewind_blinding_key = PrivateKey::from_bytes(&hash_secret_key(&spend_key))?;
         let encryption_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_blinding_key))?;
+        let mut single_round_message = stp.get_single_round_message()?;
+        if let Some(memo) = memo {
+            single_round_message.features.metadata = EncryptedMemo::encrypt_memo(&encryption_key, &memo)?;
+        }
+        let sender_message = TransactionSenderMessage::new_single_round_message(single_round_message);
         let rewind_data = RewindData {
             rewind_blinding_key,
             encryption_key,
@@ -1293,6 +2374,7 @@ where
         output_features: OutputFeatures,
         fee_per_gram: MicroTari,
         message: String,
+        memo: Option<String>,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
@@ -1303,14 +2385,35 @@ where
                 "One-sided spend-to-self transactions not supported".to_string(),
             ));
         }
+        self.check_spending_policy(Some(&dest_pubkey), amount)?;
+        check_memo(memo.as_deref(), &output_features)?;
+        let tx_id = TxId::new_random();
+        if self.queue_for_approval_if_required(
+            tx_id,
+            ApprovalPaymentType::OneSided,
+            &dest_pubkey,
+            amount,
+            fee_per_gram,
+            &output_features,
+            &message,
+            memo.as_deref(),
+            None,
+        )? {
+            return Ok(tx_id);
+        }
+
+        let script = one_sided_script(&dest_pubkey);
         self.send_one_sided_or_stealth(
-            dest_pubkey.clone(),
+            tx_id,
+            dest_pubkey,
             amount,
+            UtxoSelectionCriteria::default(),
             output_features,
             fee_per_gram,
             message,
+            memo,
             transaction_broadcast_join_handles,
-            script!(PushPubKey(Box::new(dest_pubkey))),
+            script,
         )
         .await
     }
@@ -1328,6 +2431,7 @@ where
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
     ) -> Result<TxId, TransactionServiceError> {
+        self.check_spending_policy(None, amount)?;
         let tx_id = TxId::new_random();
         let output_features = OutputFeatures::create_burn_output();
         // Prepare sender part of the transaction
@@ -1412,68 +2516,369 @@ where
                 CommsPublicKey::default(),
                 amount,
                 fee,
-                tx.clone(),
-                TransactionStatus::Completed,
-                message.clone(),
-                Utc::now().naive_utc(),
-                TransactionDirection::Outbound,
-                None,
-                None,
-                None,
-            ),
-        )?;
+                tx.clone(),
+                TransactionStatus::Completed,
+                message.clone(),
+                Utc::now().naive_utc(),
+                TransactionDirection::Outbound,
+                None,
+                None,
+                None,
+            ),
+        )?;
+
+        Ok(tx_id)
+    }
+
+    /// Sends a one side payment transaction to a recipient
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    pub async fn send_one_sided_to_stealth_address_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroTari,
+        message: String,
+        memo: Option<String>,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided-to-stealth-address spend-to-self transactions not supported".to_string(),
+            ));
+        }
+        self.check_spending_policy(Some(&dest_pubkey), amount)?;
+        check_memo(memo.as_deref(), &output_features)?;
+        let tx_id = TxId::new_random();
+        if self.queue_for_approval_if_required(
+            tx_id,
+            ApprovalPaymentType::OneSidedToStealthAddress,
+            &dest_pubkey,
+            amount,
+            fee_per_gram,
+            &output_features,
+            &message,
+            memo.as_deref(),
+            None,
+        )? {
+            return Ok(tx_id);
+        }
+
+        let script = one_sided_to_stealth_address_script(&dest_pubkey);
+        self.send_one_sided_or_stealth(
+            tx_id,
+            dest_pubkey,
+            amount,
+            UtxoSelectionCriteria::default(),
+            output_features,
+            fee_per_gram,
+            message,
+            memo,
+            transaction_broadcast_join_handles,
+            script,
+        )
+        .await
+    }
+
+    /// Sends the entire spendable balance to a recipient as one-sided stealth address payments without change outputs.
+    /// One transaction is created for every batch of outputs that fits in a transaction. The spending policy is
+    /// checked against the total swept value before anything is sent, and sweeps that would need second factor
+    /// approval are rejected.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in each transaction
+    pub async fn sweep_all(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<Vec<TxId>, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            warn!(target: LOG_TARGET, "Sweeping to self is not supported");
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "Sweep to self transactions not supported".to_string(),
+            ));
+        }
+        let output_features = OutputFeatures::default();
+        // The stealth address scripts of all the batches are the same size, so any one of them can be used to
+        // calculate the fees
+        let batches = self
+            .output_manager_service
+            .preview_sweep_all(
+                fee_per_gram,
+                output_features.clone(),
+                one_sided_to_stealth_address_script(&dest_pubkey),
+            )
+            .await?;
+        let total = batches.iter().map(|(_, amount)| *amount).sum::<MicroTari>();
+        self.check_spending_policy(Some(&dest_pubkey), total)?;
+        if let Some((_, amount)) = batches
+            .iter()
+            .find(|(_, amount)| self.resources.config.approval.requires_approval(*amount))
+        {
+            return Err(TransactionApprovalError::SweepRequiresApproval(*amount).into());
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Sweeping {} to {} in {} transaction(s)",
+            total,
+            dest_pubkey,
+            batches.len()
+        );
+        let num_batches = batches.len();
+        let mut tx_ids = Vec::with_capacity(num_batches);
+        for (i, (commitments, amount)) in batches.into_iter().enumerate() {
+            let tx_id = TxId::new_random();
+            let script = one_sided_to_stealth_address_script(&dest_pubkey);
+            self.send_one_sided_or_stealth(
+                tx_id,
+                dest_pubkey.clone(),
+                amount,
+                UtxoSelectionCriteria::specific(commitments),
+                output_features.clone(),
+                fee_per_gram,
+                format!("Sweep {} of {}", i + 1, num_batches),
+                None,
+                transaction_broadcast_join_handles,
+                script,
+            )
+            .await
+            .map_err(|e| {
+                error!(
+                    target: LOG_TARGET,
+                    "Sweep transaction {} of {} failed, {} transaction(s) were already sent: {}",
+                    i + 1,
+                    num_batches,
+                    tx_ids.len(),
+                    e
+                );
+                e
+            })?;
+            tx_ids.push(tx_id);
+        }
+
+        Ok(tx_ids)
+    }
+
+    /// Transfers a unique asset held by the wallet to a recipient as a one-sided stealth address payment. The output
+    /// holding the asset is spent on its own and the asset is sent with the same features, less the fee, so no change
+    /// output is created. Transfers that would need second factor approval are rejected, as the approval queue does
+    /// not keep the input selection.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'asset_id': The id of the asset, see `UniqueAsset::asset_id`
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in the transaction
+    pub async fn transfer_unique_asset(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        asset_id: FixedHash,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            warn!(
+                target: LOG_TARGET,
+                "Transferring a unique asset to self is not supported"
+            );
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "Unique asset transfers to self are not supported".to_string(),
+            ));
+        }
+        let script = one_sided_to_stealth_address_script(&dest_pubkey);
+        let (owned, amount) = self
+            .output_manager_service
+            .preview_unique_asset_transfer(asset_id, fee_per_gram, script.clone())
+            .await?;
+        self.check_spending_policy(Some(&dest_pubkey), amount)?;
+        if self.resources.config.approval.requires_approval(amount) {
+            return Err(TransactionApprovalError::UniqueAssetTransferRequiresApproval(amount).into());
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Transferring unique asset {} to {}", asset_id, dest_pubkey
+        );
+        self.send_one_sided_or_stealth(
+            TxId::new_random(),
+            dest_pubkey,
+            amount,
+            UtxoSelectionCriteria::specific(vec![owned.output.commitment]),
+            owned.output.unblinded_output.features,
+            fee_per_gram,
+            message,
+            None,
+            transaction_broadcast_join_handles,
+            script,
+        )
+        .await
+    }
+
+    /// Responds to compromised keys by rotating the key manager to a new key epoch and sweeping every spendable output
+    /// to keys of the new epoch in batched pay-to-self transactions. The old epoch is marked as compromised before
+    /// anything is swept, so no further keys are derived from it even if the sweep fails part of the way, in which case
+    /// the remaining outputs can be swept by rotating again. Progress is published as key rotation events.
+    /// Returns the new key epoch and the TxIds of the sweep transactions.
+    pub async fn rotate_keys(
+        &mut self,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<(u64, Vec<TxId>), TransactionServiceError> {
+        let epoch = self.output_manager_service.rotate_key_epoch().await?;
+        match self
+            .sweep_to_key_epoch(epoch, fee_per_gram, transaction_broadcast_join_handles)
+            .await
+        {
+            Ok(tx_ids) => {
+                let _size = self
+                    .event_publisher
+                    .send(Arc::new(TransactionEvent::KeyRotationCompleted { epoch }));
+                Ok((epoch, tx_ids))
+            },
+            Err(e) => {
+                error!(
+                    target: LOG_TARGET,
+                    "Sweeping the outputs to key epoch {} failed: {}", epoch, e
+                );
+                let _size = self.event_publisher.send(Arc::new(TransactionEvent::KeyRotationFailed {
+                    epoch,
+                    error: e.to_string(),
+                }));
+                Err(e)
+            },
+        }
+    }
+
+    async fn sweep_to_key_epoch(
+        &mut self,
+        epoch: u64,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<Vec<TxId>, TransactionServiceError> {
+        let output_features = OutputFeatures::default();
+        // The batches are sized for the pay-to-self outputs that they are swept to
+        let batches = match self
+            .output_manager_service
+            .preview_sweep_all(fee_per_gram, output_features.clone(), script!(Nop))
+            .await
+        {
+            Ok(batches) => batches,
+            Err(OutputManagerError::NotEnoughFunds) => {
+                info!(target: LOG_TARGET, "No outputs worth sweeping to key epoch {}", epoch);
+                Vec::new()
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        let num_batches = batches.len();
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::KeyRotationStarted { epoch, num_batches }));
+        let mut tx_ids = Vec::with_capacity(num_batches);
+        for (i, (commitments, amount)) in batches.into_iter().enumerate() {
+            let tx_id = TxId::new_random();
+            let message = format!("Key rotation {} of {}", i + 1, num_batches);
+            let (fee, transaction) = self
+                .output_manager_service
+                .create_pay_to_self_transaction(
+                    tx_id,
+                    amount,
+                    UtxoSelectionCriteria::specific(commitments),
+                    output_features.clone(),
+                    fee_per_gram,
+                    None,
+                    message.clone(),
+                )
+                .await?;
+            self.submit_transaction_to_self(
+                transaction_broadcast_join_handles,
+                tx_id,
+                transaction,
+                fee,
+                amount,
+                message,
+            )?;
+            tx_ids.push(tx_id);
+            let _size = self
+                .event_publisher
+                .send(Arc::new(TransactionEvent::KeyRotationProgress {
+                    epoch,
+                    tx_id,
+                    batch: i + 1,
+                    num_batches,
+                }));
+        }
 
-        Ok(tx_id)
+        Ok(tx_ids)
     }
 
-    /// Sends a one side payment transaction to a recipient
-    /// # Arguments
-    /// 'dest_pubkey': The Comms pubkey of the recipient node
-    /// 'amount': The amount of Tari to send to the recipient
-    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
-    pub async fn send_one_sided_to_stealth_address_transaction(
+    /// Creates a self-spend of `amount` that is broadcast like any other transaction but recorded with the `is_decoy`
+    /// flag, so that it is never presented as a real payment. No transaction event is published for it.
+    pub async fn send_decoy_transaction(
         &mut self,
-        dest_pubkey: CommsPublicKey,
         amount: MicroTari,
-        output_features: OutputFeatures,
         fee_per_gram: MicroTari,
-        message: String,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
     ) -> Result<TxId, TransactionServiceError> {
-        if self.node_identity.public_key() == &dest_pubkey {
-            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
-            return Err(TransactionServiceError::OneSidedTransactionError(
-                "One-sided-to-stealth-address spend-to-self transactions not supported".to_string(),
-            ));
-        }
-
-        let (nonce_private_key, nonce_public_key) = PublicKey::random_keypair(&mut OsRng);
-
-        let c = WalletHasher::new_with_label("stealth_address")
-            .chain((dest_pubkey.clone() * nonce_private_key).as_bytes())
-            .finalize();
-
-        let script_spending_key =
-            PublicKey::from_secret_key(&PrivateKey::from_bytes(c.as_ref()).unwrap()) + dest_pubkey.clone();
+        let tx_id = TxId::new_random();
+        let message = "Decoy".to_string();
+        let (fee, transaction) = self
+            .output_manager_service
+            .create_pay_to_self_transaction(
+                tx_id,
+                amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                fee_per_gram,
+                None,
+                message.clone(),
+            )
+            .await?;
 
-        self.send_one_sided_or_stealth(
-            dest_pubkey,
+        let mut completed_tx = CompletedTransaction::new(
+            tx_id,
+            self.node_identity.public_key().clone(),
+            self.node_identity.public_key().clone(),
             amount,
-            output_features,
-            fee_per_gram,
+            fee,
+            transaction,
+            TransactionStatus::Completed,
             message,
-            transaction_broadcast_join_handles,
-            script!(PushPubKey(Box::new(nonce_public_key)) Drop PushPubKey(Box::new(script_spending_key))),
-        )
-        .await
+            Utc::now().naive_utc(),
+            TransactionDirection::Inbound,
+            None,
+            None,
+            None,
+        );
+        completed_tx.is_decoy = true;
+        self.submit_transaction(transaction_broadcast_join_handles, completed_tx)?;
+        debug!(target: LOG_TARGET, "Decoy transaction (TxId: {}) created", tx_id);
+
+        Ok(tx_id)
     }
 
     /// Accept the public reply from a recipient and apply the reply to the relevant transaction protocol
     /// # Arguments
     /// 'recipient_reply' - The public response from a recipient with data required to complete the transaction
     #[allow(clippy::too_many_lines)]
+    #[instrument(skip_all, fields(service = "transaction_service", tx_id = recipient_reply.tx_id))]
     pub async fn accept_recipient_reply(
         &mut self,
         source_pubkey: CommsPublicKey,
@@ -1531,7 +2936,7 @@ where
                 tokio::spawn(send_transaction_cancelled_message(
                     tx_id,
                     source_pubkey,
-                    self.resources.outbound_message_service.clone(),
+                    self.resources.messaging.clone(),
                 ));
             } else {
                 // Resend the reply
@@ -1543,9 +2948,8 @@ where
                     tx_id,
                     ctx.transaction,
                     source_pubkey,
-                    self.resources.outbound_message_service.clone(),
-                    self.resources.config.direct_send_timeout,
-                    self.resources.config.transaction_routing_mechanism,
+                    self.resources.messaging.clone(),
+                    self.resources.config.clone(),
                 ));
             }
 
@@ -1577,7 +2981,7 @@ where
             tokio::spawn(send_transaction_cancelled_message(
                 tx_id,
                 source_pubkey,
-                self.resources.outbound_message_service.clone(),
+                self.resources.messaging.clone(),
             ));
 
             if let Err(e) = self.resources.db.increment_send_count(tx_id) {
@@ -1615,7 +3019,8 @@ where
             Ok(val) => {
                 if val.transaction_status != TransactionStatus::Queued {
                     let _sender = self.pending_transaction_reply_senders.remove(&val.tx_id);
-                    let _sender = self.send_transaction_cancellation_senders.remove(&val.tx_id);
+                    let _token = self.send_transaction_cancellation_tokens.remove(&val.tx_id);
+                    self.remove_transaction_protocol_state(val.tx_id);
                     let completed_tx = match self.db.get_completed_transaction(val.tx_id) {
                         Ok(v) => v,
                         Err(e) => {
@@ -1647,10 +3052,12 @@ where
             },
             Err(TransactionServiceProtocolError { id, error }) => {
                 let _public_key = self.pending_transaction_reply_senders.remove(&id);
-                let _result = self.send_transaction_cancellation_senders.remove(&id);
+                let _token = self.send_transaction_cancellation_tokens.remove(&id);
+                // The protocol state is kept on shutdown so that the negotiation can be resumed on restart
                 if let TransactionServiceError::Shutdown = error {
                     return;
                 }
+                self.remove_transaction_protocol_state(id);
                 warn!(
                     target: LOG_TARGET,
                     "Error completing Send Transaction Protocol (Id: {}): {:?}", id, error
@@ -1663,7 +3070,36 @@ where
     }
 
     /// Cancel a pending transaction
+    #[instrument(skip_all, fields(service = "transaction_service", tx_id = %tx_id))]
     async fn cancel_pending_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        if self.db.get_pending_approval_transaction(tx_id)?.is_some() {
+            self.db.remove_pending_approval_transaction(tx_id)?;
+            let _size = self
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionCancelled(
+                    tx_id,
+                    TxCancellationReason::UserCancelled,
+                )));
+            info!(
+                target: LOG_TARGET,
+                "Transaction (TxId: {}) pending approval cancelled", tx_id
+            );
+            return Ok(());
+        }
+
+        // A transaction that is still being prepared or sent is not stored yet, its send protocol releases the inputs
+        // and publishes the cancellation once it has been aborted
+        if !self.db.transaction_exists(tx_id)? {
+            if let Some(cancellation) = self.send_transaction_cancellation_tokens.get(&tx_id) {
+                cancellation.cancel();
+                info!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) cancelled while it was being sent", tx_id
+                );
+                return Ok(());
+            }
+        }
+
         self.db.cancel_pending_transaction(tx_id).map_err(|e| {
             warn!(
                 target: LOG_TARGET,
@@ -1673,9 +3109,10 @@ where
         })?;
 
         self.output_manager_service.cancel_transaction(tx_id).await?;
+        self.remove_transaction_protocol_state(tx_id);
 
-        if let Some(cancellation_sender) = self.send_transaction_cancellation_senders.remove(&tx_id) {
-            let _result = cancellation_sender.send(());
+        if let Some(cancellation) = self.send_transaction_cancellation_tokens.remove(&tx_id) {
+            cancellation.cancel();
         }
         let _public_key = self.pending_transaction_reply_senders.remove(&tx_id);
 
@@ -1704,6 +3141,151 @@ where
         Ok(())
     }
 
+    /// Resend our reply for a pending inbound transaction, which prompts the sender to send the finalized transaction
+    /// again
+    fn request_resend(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        let inbound_tx = self.db.get_pending_inbound_transaction(tx_id)?;
+        debug!(
+            target: LOG_TARGET,
+            "Requesting the finalized transaction (TxId: {}) from the sender again", tx_id
+        );
+        tokio::spawn(send_transaction_reply(
+            inbound_tx,
+            self.resources.messaging.clone(),
+            self.resources.config.clone(),
+        ));
+        self.resources.db.increment_send_count(tx_id)?;
+        Ok(())
+    }
+
+    /// Export the message that the counterparty of the transaction needs next, for delivery out-of-band
+    fn export_transaction_message(&self, tx_id: TxId) -> Result<Vec<u8>, TransactionServiceError> {
+        let message = if let Ok(outbound_tx) = self.db.get_pending_outbound_transaction(tx_id) {
+            let mut sender_message = outbound_tx.sender_protocol.get_single_round_message()?;
+            #[allow(clippy::cast_sign_loss)]
+            let expires_at = outbound_tx.expires_at.map(|expires_at| expires_at.timestamp() as u64);
+            sender_message.expires_at = expires_at;
+            OutOfBandMessage::Transaction(sender_message)
+        } else if let Ok(inbound_tx) = self.db.get_pending_inbound_transaction(tx_id) {
+            OutOfBandMessage::Reply(inbound_tx.receiver_protocol.get_signed_data()?.clone())
+        } else {
+            let completed_tx = self.db.get_completed_transaction(tx_id)?;
+            if completed_tx.direction != TransactionDirection::Outbound {
+                return Err(TransactionServiceError::InvalidOutOfBandMessage(format!(
+                    "Transaction {} was received, so there is nothing left to send",
+                    tx_id
+                )));
+            }
+            OutOfBandMessage::Finalized {
+                tx_id,
+                transaction: completed_tx.transaction,
+            }
+        };
+        message.to_bytes(self.node_identity.public_key())
+    }
+
+    /// Import a transaction negotiation message that was exported by the counterparty and delivered out-of-band
+    async fn import_transaction_message(
+        &mut self,
+        bytes: &[u8],
+        join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
+    ) -> Result<TxId, TransactionServiceError> {
+        let (source_pubkey, message) = OutOfBandMessage::from_bytes(bytes)?;
+        if let Some(recorder) = self.protocol_recorder.as_ref() {
+            recorder.record_message(MessageDirection::Inbound, &source_pubkey, &message);
+        }
+        self.accept_out_of_band_message(source_pubkey, message, join_handles)
+            .await
+    }
+
+    /// Hand a transaction negotiation message that did not arrive over the DHT to the protocols
+    async fn accept_out_of_band_message(
+        &mut self,
+        source_pubkey: CommsPublicKey,
+        message: OutOfBandMessage,
+        join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
+    ) -> Result<TxId, TransactionServiceError> {
+        let tx_id = message.tx_id();
+        debug!(
+            target: LOG_TARGET,
+            "Importing out-of-band message for Transaction (TxId: {}) from {}", tx_id, source_pubkey
+        );
+        match message {
+            OutOfBandMessage::Transaction(data) => {
+                self.accept_transaction(
+                    source_pubkey,
+                    proto::TransactionSenderMessage::single(data.into()),
+                    0,
+                    join_handles,
+                )?;
+            },
+            OutOfBandMessage::Reply(reply) => {
+                self.accept_recipient_reply(source_pubkey, reply.into()).await?;
+            },
+            OutOfBandMessage::Finalized { tx_id, transaction } => {
+                let finalized_transaction = proto::TransactionFinalizedMessage::new(tx_id, transaction)
+                    .map_err(TransactionServiceError::InvalidMessageError)?;
+                self.accept_finalized_transaction(source_pubkey, finalized_transaction, join_handles)
+                    .await?;
+            },
+            OutOfBandMessage::Cancelled { tx_id } => {
+                self.handle_transaction_cancelled_message(source_pubkey, proto::TransactionCancelledMessage {
+                    tx_id: tx_id.into(),
+                })
+                .await?;
+            },
+        }
+        Ok(tx_id)
+    }
+
+    /// Hand the received messages of a recording made by this wallet to the protocols again, in the order in which
+    /// they arrived. The messages that were sent are skipped, as the protocols send them again. A recording is meant to
+    /// be replayed into a copy of the wallet database that it was recorded with.
+    async fn replay_protocol_recording(
+        &mut self,
+        path: &Path,
+        join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
+    ) -> Result<ReplaySummary, TransactionServiceError> {
+        let messages = read_recording(path, &recording_cipher(self.node_identity.secret_key()))?;
+        info!(
+            target: LOG_TARGET,
+            "Replaying {} transaction protocol messages from '{}'",
+            messages.len(),
+            path.display()
+        );
+        let mut summary = ReplaySummary::default();
+        for message in messages {
+            if message.direction == MessageDirection::Outbound {
+                summary.skipped += 1;
+                continue;
+            }
+            let result = match message.to_out_of_band_message() {
+                Ok(oob_message) => {
+                    self.accept_out_of_band_message(message.peer.clone(), oob_message, join_handles)
+                        .await
+                },
+                Err(e) => Err(e),
+            };
+            summary.replayed += 1;
+            if let Err(e) = result {
+                warn!(
+                    target: LOG_TARGET,
+                    "Replayed {:?} message from {} was rejected: {}", message.message_type, message.peer, e
+                );
+                summary
+                    .failures
+                    .push(format!("{:?} from {}: {}", message.message_type, message.peer, e));
+            }
+        }
+        Ok(summary)
+    }
+
+    fn record_inbound<M: prost::Message>(&self, source: &CommsPublicKey, message_type: TariMessageType, message: &M) {
+        if let Some(recorder) = self.protocol_recorder.as_ref() {
+            recorder.record(MessageDirection::Inbound, source, message_type, message.encode_to_vec());
+        }
+    }
+
     /// Handle a Transaction Cancelled message received from the Comms layer
     pub async fn handle_transaction_cancelled_message(
         &mut self,
@@ -1737,11 +3319,21 @@ where
         >,
     ) -> Result<(), TransactionServiceError> {
         let outbound_txs = self.db.get_pending_outbound_transactions()?;
+        let mut protocol_states = self.fetch_transaction_protocol_states()?;
         for (tx_id, tx) in outbound_txs {
-            let (sender_protocol, stage) = if tx.send_count > 0 {
-                (None, TransactionSendProtocolStage::WaitForReply)
-            } else {
-                (Some(tx.sender_protocol), TransactionSendProtocolStage::Queued)
+            // Resume from the persisted negotiation stage, falling back to the send count when no state was saved
+            let (sender_protocol, stage) = match protocol_states.remove(&tx_id) {
+                Some(TransactionProtocolState {
+                    stage: TransactionNegotiationStage::SenderWaitForReply,
+                    ..
+                }) => (None, TransactionSendProtocolStage::WaitForReply),
+                Some(TransactionProtocolState {
+                    stage: TransactionNegotiationStage::SenderQueued,
+                    snapshot: TransactionProtocolSnapshot::Sender(sender_protocol),
+                    ..
+                }) => (Some(*sender_protocol), TransactionSendProtocolStage::Queued),
+                _ if tx.send_count > 0 => (None, TransactionSendProtocolStage::WaitForReply),
+                _ => (Some(tx.sender_protocol), TransactionSendProtocolStage::Queued),
             };
             let (not_yet_pending, queued) = (
                 !self.pending_transaction_reply_senders.contains_key(&tx_id),
@@ -1759,22 +3351,22 @@ where
                     "Retry sending queued Pending Outbound Transaction TxId: {}", tx_id
                 );
                 let _sender = self.pending_transaction_reply_senders.remove(&tx_id);
-                let _sender = self.send_transaction_cancellation_senders.remove(&tx_id);
+                let _token = self.send_transaction_cancellation_tokens.remove(&tx_id);
             } else {
             }
 
             if not_yet_pending || queued {
                 let (tx_reply_sender, tx_reply_receiver) = mpsc::channel(100);
-                let (cancellation_sender, cancellation_receiver) = oneshot::channel();
+                let cancellation = CancellationToken::new();
                 self.pending_transaction_reply_senders.insert(tx_id, tx_reply_sender);
-                self.send_transaction_cancellation_senders
-                    .insert(tx_id, cancellation_sender);
+                self.send_transaction_cancellation_tokens
+                    .insert(tx_id, cancellation.clone());
 
                 let protocol = TransactionSendProtocol::new(
                     tx_id,
                     self.resources.clone(),
                     tx_reply_receiver,
-                    cancellation_receiver,
+                    cancellation,
                     tx.destination_public_key,
                     tx.amount,
                     tx.fee,
@@ -1785,6 +3377,7 @@ where
                     None,
                     self.last_seen_tip_height,
                     sender_protocol,
+                    tx.expires_at,
                 );
 
                 let join_handle = tokio::spawn(protocol.execute());
@@ -1800,6 +3393,7 @@ where
     /// 'source_pubkey' - The pubkey from which the message was sent and to which the reply will be sent.
     /// 'sender_message' - Message from a sender containing the setup of the transaction being sent to you
     #[allow(clippy::too_many_lines)]
+    #[instrument(skip_all, fields(service = "transaction_service", tx_id = field::Empty))]
     pub fn accept_transaction(
         &mut self,
         source_pubkey: CommsPublicKey,
@@ -1816,6 +3410,7 @@ where
 
         // Currently we will only reply to a Single sender transaction protocol
         if let TransactionSenderMessage::Single(data) = sender_message.clone() {
+            Span::current().record("tx_id", &data.tx_id.as_u64());
             trace!(
                 target: LOG_TARGET,
                 "Transaction (TxId: {}) received from {}, Trace: {}",
@@ -1839,7 +3434,7 @@ where
                 tokio::spawn(send_transaction_cancelled_message(
                     tx.tx_id,
                     source_pubkey,
-                    self.resources.outbound_message_service.clone(),
+                    self.resources.messaging.clone(),
                 ));
 
                 return Ok(());
@@ -1872,9 +3467,8 @@ where
                 // Ok we will resend the reply
                 tokio::spawn(send_transaction_reply(
                     inbound_tx,
-                    self.resources.outbound_message_service.clone(),
-                    self.resources.config.direct_send_timeout,
-                    self.resources.config.transaction_routing_mechanism,
+                    self.resources.messaging.clone(),
+                    self.resources.config.clone(),
                 ));
                 if let Err(e) = self.resources.db.increment_send_count(tx_id) {
                     warn!(
@@ -1929,6 +3523,7 @@ where
     /// Accept the public reply from a recipient and apply the reply to the relevant transaction protocol
     /// # Arguments
     /// 'recipient_reply' - The public response from a recipient with data required to complete the transaction
+    #[instrument(skip_all, fields(service = "transaction_service", tx_id = finalized_transaction.tx_id))]
     pub async fn accept_finalized_transaction(
         &mut self,
         source_pubkey: CommsPublicKey,
@@ -1938,20 +3533,9 @@ where
         // Check if a wallet recovery is in progress, if it is we will ignore this request
         self.check_recovery_status()?;
 
-        let tx_id = finalized_transaction.tx_id.into();
-        let transaction: Transaction = finalized_transaction
-            .transaction
-            .ok_or_else(|| {
-                TransactionServiceError::InvalidMessageError(
-                    "Finalized Transaction missing Transaction field".to_string(),
-                )
-            })?
-            .try_into()
-            .map_err(|_| {
-                TransactionServiceError::InvalidMessageError(
-                    "Cannot convert Transaction field from TransactionFinalized message".to_string(),
-                )
-            })?;
+        let (tx_id, transaction) = finalized_transaction
+            .into_transaction()
+            .map_err(TransactionServiceError::InvalidMessageError)?;
 
         let sender = match self.finalized_transaction_senders.get_mut(&tx_id) {
             None => {
@@ -1978,13 +3562,22 @@ where
                             .reinstate_cancelled_inbound_transaction_outputs(tx_id)
                             .await?;
 
-                        self.restart_receive_transaction_protocol(tx_id, source_pubkey.clone(), join_handles);
+                        self.restart_receive_transaction_protocol(
+                            tx_id,
+                            source_pubkey.clone(),
+                            TransactionReceiveProtocolStage::WaitForFinalize,
+                            join_handles,
+                        );
                         match self.finalized_transaction_senders.get_mut(&tx_id) {
                             None => return Err(TransactionServiceError::TransactionDoesNotExistError),
                             Some(s) => s,
                         }
                     },
-                    Err(_) => return Err(TransactionServiceError::TransactionDoesNotExistError),
+                    Err(_) => {
+                        return self
+                            .accept_expiring_vault_transaction(source_pubkey, tx_id, transaction)
+                            .await
+                    },
                 }
             },
             Some(s) => s,
@@ -1998,6 +3591,59 @@ where
         Ok(())
     }
 
+    /// Claims the expiring vault outputs that are addressed to this wallet in a finalized transaction that was not
+    /// negotiated with this wallet. Outputs that were already claimed, e.g. because the transaction was received both
+    /// directly and via store-and-forward, are ignored.
+    async fn accept_expiring_vault_transaction(
+        &mut self,
+        source_pubkey: CommsPublicKey,
+        tx_id: TxId,
+        transaction: Transaction,
+    ) -> Result<(), TransactionServiceError> {
+        let vault_outputs = transaction
+            .body
+            .outputs()
+            .iter()
+            .filter(|output| ExpiringVaultScript::from_script(&output.script).is_some())
+            .cloned()
+            .collect::<Vec<_>>();
+        if vault_outputs.is_empty() {
+            return Err(TransactionServiceError::TransactionDoesNotExistError);
+        }
+
+        let recovered = self
+            .output_manager_service
+            .scan_outputs_for_one_sided_payments(vault_outputs)
+            .await?;
+        if recovered.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "Finalized Transaction (TxId: {}) contains no new expiring vault outputs for this wallet", tx_id
+            );
+        }
+        for output in recovered {
+            let message = output.memo.unwrap_or_else(|| "Expiring vault payment".to_string());
+            let import_tx_id = self.add_utxo_import_transaction_with_status(
+                output.output.value,
+                source_pubkey.clone(),
+                message,
+                Some(output.output.features.maturity),
+                ImportStatus::FauxUnconfirmed,
+                Some(output.tx_id),
+                None,
+                None,
+            )?;
+            info!(
+                target: LOG_TARGET,
+                "Expiring vault payment of {} from Finalized Transaction (TxId: {}) imported as TxId: {}",
+                output.output.value,
+                tx_id,
+                import_tx_id
+            );
+        }
+        Ok(())
+    }
+
     /// Handle the final clean up after a Send Transaction protocol completes
     fn complete_receive_transaction_protocol(
         &mut self,
@@ -2010,6 +3656,7 @@ where
             Ok(id) => {
                 let _public_key = self.finalized_transaction_senders.remove(&id);
                 let _result = self.receiver_transaction_cancellation_senders.remove(&id);
+                self.remove_transaction_protocol_state(id);
 
                 let completed_tx = match self.db.get_completed_transaction(id) {
                     Ok(v) => v,
@@ -2050,10 +3697,13 @@ where
                     TransactionServiceError::Shutdown => {
                         return;
                     },
-                    _ => warn!(
-                        target: LOG_TARGET,
-                        "Error completing Receive Transaction Protocol (Id: {}): {}", id, error
-                    ),
+                    _ => {
+                        self.remove_transaction_protocol_state(id);
+                        warn!(
+                            target: LOG_TARGET,
+                            "Error completing Receive Transaction Protocol (Id: {}): {}", id, error
+                        )
+                    },
                 }
 
                 let _size = self
@@ -2068,8 +3718,17 @@ where
         join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
     ) -> Result<(), TransactionServiceError> {
         let inbound_txs = self.db.get_pending_inbound_transaction_sender_info()?;
+        let protocol_states = self.fetch_transaction_protocol_states()?;
         for txn in inbound_txs {
-            self.restart_receive_transaction_protocol(txn.tx_id, txn.source_public_key, join_handles);
+            // A reply that was never sent is sent before waiting for the finalized transaction
+            let stage = match protocol_states.get(&txn.tx_id) {
+                Some(TransactionProtocolState {
+                    stage: TransactionNegotiationStage::ReceiverReplyPending,
+                    ..
+                }) => TransactionReceiveProtocolStage::SendReply,
+                _ => TransactionReceiveProtocolStage::WaitForFinalize,
+            };
+            self.restart_receive_transaction_protocol(txn.tx_id, txn.source_public_key, stage, join_handles);
         }
 
         Ok(())
@@ -2079,12 +3738,15 @@ where
         &mut self,
         tx_id: TxId,
         source_public_key: CommsPublicKey,
+        stage: TransactionReceiveProtocolStage,
         join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
     ) {
         if !self.pending_transaction_reply_senders.contains_key(&tx_id) {
             debug!(
                 target: LOG_TARGET,
-                "Restarting listening for Transaction Finalize for Pending Inbound Transaction TxId: {}", tx_id
+                "Restarting Receive Transaction Protocol for Pending Inbound Transaction TxId: {} at Stage {:?}",
+                tx_id,
+                stage
             );
             let (tx_finalized_sender, tx_finalized_receiver) = mpsc::channel(100);
             let (cancellation_sender, cancellation_receiver) = oneshot::channel();
@@ -2095,7 +3757,7 @@ where
                 tx_id,
                 source_public_key,
                 TransactionSenderMessage::None,
-                TransactionReceiveProtocolStage::WaitForFinalize,
+                stage,
                 self.resources.clone(),
                 tx_finalized_receiver,
                 cancellation_receiver,
@@ -2136,9 +3798,43 @@ where
                 resp
             })?;
 
+        // Any protocol state that was not resumed belongs to a transaction that is no longer being negotiated
+        for tx_id in self.fetch_transaction_protocol_states()?.into_keys() {
+            if !self.pending_transaction_reply_senders.contains_key(&tx_id) &&
+                !self.finalized_transaction_senders.contains_key(&tx_id)
+            {
+                debug!(
+                    target: LOG_TARGET,
+                    "Removing stale Transaction Protocol State for TxId: {}", tx_id
+                );
+                self.remove_transaction_protocol_state(tx_id);
+            }
+        }
+
         Ok(())
     }
 
+    fn fetch_transaction_protocol_states(
+        &self,
+    ) -> Result<HashMap<TxId, TransactionProtocolState>, TransactionServiceError> {
+        Ok(self
+            .db
+            .get_transaction_protocol_states()?
+            .into_iter()
+            .map(|state| (state.tx_id, state))
+            .collect())
+    }
+
+    /// Remove the persisted negotiation state of a transaction protocol that will not be resumed
+    fn remove_transaction_protocol_state(&self, tx_id: TxId) {
+        if let Err(e) = self.db.remove_transaction_protocol_state(tx_id) {
+            warn!(
+                target: LOG_TARGET,
+                "Could not remove Transaction Protocol State for TxId: {}: {}", tx_id, e
+            );
+        }
+    }
+
     async fn start_transaction_revalidation(
         &mut self,
         join_handles: &mut FuturesUnordered<
@@ -2168,7 +3864,13 @@ where
             self.resources.config.clone(),
             self.event_publisher.clone(),
             self.resources.output_manager_service.clone(),
-        );
+        )
+        .with_mempool_evictions(self.mempool_evictions.clone());
+        #[cfg(feature = "header_sync")]
+        let protocol = match self.header_sync.clone() {
+            Some(header_sync) => protocol.with_header_sync(header_sync),
+            None => protocol,
+        };
 
         let join_handle = tokio::spawn(protocol.execute());
         join_handles.push(join_handle);
@@ -2234,6 +3936,7 @@ where
     }
 
     /// Start to protocol to Broadcast the specified Completed Transaction to the Base Node.
+    #[instrument(skip_all, fields(service = "transaction_service", tx_id = %completed_tx.tx_id))]
     fn broadcast_completed_transaction(
         &mut self,
         completed_tx: CompletedTransaction,
@@ -2257,6 +3960,15 @@ where
             return Err(TransactionServiceError::NoBaseNodeKeysProvided);
         }
 
+        // Transactions that were already broadcast are still monitored, they are rebroadcast if they are reorged out
+        if self.chain_split_suspected && completed_tx.status == TransactionStatus::Completed {
+            info!(
+                target: LOG_TARGET,
+                "Not broadcasting transaction (TxId: {}) while a chain split is suspected", tx_id
+            );
+            return Ok(());
+        }
+
         // Check if the protocol has already been started
         if self.active_transaction_broadcast_protocols.insert(tx_id) {
             let protocol = TransactionBroadcastProtocol::new(
@@ -2264,7 +3976,34 @@ where
                 self.resources.clone(),
                 self.timeout_update_watch.get_receiver(),
             );
-            let join_handle = tokio::spawn(protocol.execute());
+            let delay = match self.get_large_send_broadcast_delay(&completed_tx) {
+                Some(delay) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Delaying the broadcast of large transaction (TxId: {}) by {:.0?} as required by the spending \
+                         policy",
+                        tx_id,
+                        delay
+                    );
+                    Some(delay)
+                },
+                None => self.mempool_evictions.rebroadcast_delay(tx_id).map(|delay| {
+                    info!(
+                        target: LOG_TARGET,
+                        "Delaying the rebroadcast of transaction (TxId: {}) evicted from the mempool by {:.0?}",
+                        tx_id,
+                        delay
+                    );
+                    delay
+                }),
+            };
+            let join_handle = match delay {
+                Some(delay) => tokio::spawn(async move {
+                    sleep(delay).await;
+                    protocol.execute().await
+                }),
+                None => tokio::spawn(protocol.execute()),
+            };
             join_handles.push(join_handle);
         } else {
             trace!(
@@ -2277,6 +4016,25 @@ where
         Ok(())
     }
 
+    /// The remaining time that the spending policy requires a large outgoing transaction to be held back before it is
+    /// broadcast, counted from when the transaction was created
+    fn get_large_send_broadcast_delay(&self, completed_tx: &CompletedTransaction) -> Option<Duration> {
+        if completed_tx.direction != TransactionDirection::Outbound ||
+            completed_tx.status != TransactionStatus::Completed
+        {
+            return None;
+        }
+        let delay = self
+            .resources
+            .config
+            .spending_policy
+            .broadcast_delay(completed_tx.amount)?;
+        let elapsed = (Utc::now().naive_utc() - completed_tx.timestamp)
+            .to_std()
+            .unwrap_or_default();
+        delay.checked_sub(elapsed).filter(|remaining| !remaining.is_zero())
+    }
+
     /// Broadcast all valid and not cancelled completed transactions with status 'Completed' and 'Broadcast' to the base
     /// node.
     fn broadcast_completed_and_broadcast_transactions(
@@ -2311,6 +4069,7 @@ where
             },
             Err(TransactionServiceProtocolError { id, error }) => {
                 let _ = self.active_transaction_broadcast_protocols.remove(&id);
+                self.mempool_evictions.remove(id);
 
                 if let TransactionServiceError::Shutdown = error {
                     return;
@@ -2355,11 +4114,20 @@ where
             PowerMode::Low => self.config.low_power_polling_timeout,
             PowerMode::Normal => self.config.broadcast_monitoring_timeout,
         };
+        self.power_mode = mode;
         self.timeout_update_watch.send(timeout);
 
         Ok(())
     }
 
+    /// Replaces the service config. Protocols that are already running pick up the new broadcast monitoring interval,
+    /// and other settings apply to protocols started from now on.
+    async fn set_config(&mut self, config: TransactionServiceConfig) -> Result<(), TransactionServiceError> {
+        self.config = config.clone();
+        self.resources.config = config;
+        self.set_power_mode(self.power_mode).await
+    }
+
     /// Add a completed transaction to the Transaction Manager to record directly importing a spendable UTXO.
     pub fn add_utxo_import_transaction_with_status(
         &mut self,
@@ -2471,13 +4239,15 @@ where
         reward: MicroTari,
         fees: MicroTari,
         block_height: u64,
+        extra: Vec<u8>,
     ) -> Result<Transaction, TransactionServiceError> {
         let amount = reward + fees;
 
-        // first check if we already have a coinbase tx for this height and amount
+        // first check if we already have a coinbase tx for this height, amount and extra data
         let find_result = self
             .db
-            .find_coinbase_transaction_at_block_height(block_height, amount)?;
+            .find_coinbase_transaction_at_block_height(block_height, amount)?
+            .filter(|tx| tx.coinbase_extra().unwrap_or_default() == extra.as_slice());
 
         let completed_transaction = match find_result {
             Some(completed_tx) => {
@@ -2496,7 +4266,7 @@ where
                 let tx_id = TxId::new_random();
                 let tx = self
                     .output_manager_service
-                    .get_coinbase_transaction(tx_id, reward, fees, block_height)
+                    .get_coinbase_transaction(tx_id, reward, fees, block_height, extra)
                     .await?;
                 self.db.insert_completed_transaction(
                     tx_id,
@@ -2561,7 +4331,7 @@ where
 pub struct TransactionServiceResources<TBackend, TWalletConnectivity> {
     pub db: TransactionDatabase<TBackend>,
     pub output_manager_service: OutputManagerHandle,
-    pub outbound_message_service: OutboundMessageRequester,
+    pub messaging: Arc<dyn TransactionMessagingBackend>,
     pub connectivity: TWalletConnectivity,
     pub event_publisher: TransactionEventSender,
     pub node_identity: Arc<NodeIdentity>,
@@ -2597,6 +4367,120 @@ fn hash_secret_key(key: &PrivateKey) -> Vec<u8> {
         .to_vec()
 }
 
+/// The lowest fees that the connected base node accepts, or the floor of the consensus rules until it reported them
+async fn fetch_fee_floor(mut base_node_service: BaseNodeServiceHandle) -> FeeFloor {
+    match base_node_service.get_fee_floor().await {
+        Ok(fee_floor) => fee_floor.unwrap_or_else(FeeFloor::consensus),
+        Err(e) => {
+            warn!(
+                target: LOG_TARGET,
+                "Could not get the fee floor of the base node: {}", e
+            );
+            FeeFloor::consensus()
+        },
+    }
+}
+
+/// Checks that a memo fits in the output of a one-sided payment. The memo is carried in the output features metadata,
+/// so it cannot be combined with other metadata.
+fn check_memo(memo: Option<&str>, output_features: &OutputFeatures) -> Result<(), TransactionServiceError> {
+    match memo {
+        Some(memo) if memo.len() > MAX_MEMO_SIZE => Err(TransactionServiceError::OneSidedTransactionError(format!(
+            "The memo cannot be longer than {} bytes",
+            MAX_MEMO_SIZE
+        ))),
+        Some(_) if !output_features.metadata.is_empty() => Err(TransactionServiceError::OneSidedTransactionError(
+            "A memo cannot be combined with output features metadata".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn one_sided_script(dest_pubkey: &CommsPublicKey) -> TariScript {
+    script!(PushPubKey(Box::new(dest_pubkey.clone())))
+}
+
+/// Builds a one-sided script that pays to a stealth address `Ks=c⋅G+B` derived from a fresh nonce `R`, where
+/// `c=H(r⋅B)`
+fn one_sided_to_stealth_address_script(dest_pubkey: &CommsPublicKey) -> TariScript {
+    let (nonce_private_key, nonce_public_key) = PublicKey::random_keypair(&mut OsRng);
+
+    let c = WalletHasher::new_with_label("stealth_address")
+        .chain((dest_pubkey.clone() * nonce_private_key).as_bytes())
+        .finalize();
+
+    let script_spending_key =
+        PublicKey::from_secret_key(&PrivateKey::from_bytes(c.as_ref()).unwrap()) + dest_pubkey.clone();
+
+    script!(PushPubKey(Box::new(nonce_public_key)) Drop PushPubKey(Box::new(script_spending_key)))
+}
+
+/// Trims a transaction tag and checks that it is not empty, not too long and can be listed in a CSV column
+fn normalize_transaction_tag(tag: &str) -> Result<String, TransactionServiceError> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(TransactionServiceError::InvalidTransactionTag(
+            "Tags cannot be empty".to_string(),
+        ));
+    }
+    if tag.chars().count() > MAX_TRANSACTION_TAG_LENGTH {
+        return Err(TransactionServiceError::InvalidTransactionTag(format!(
+            "Tags cannot be longer than {} characters",
+            MAX_TRANSACTION_TAG_LENGTH
+        )));
+    }
+    if tag.chars().any(|c| c == ';' || c == '"' || c.is_control()) {
+        return Err(TransactionServiceError::InvalidTransactionTag(format!(
+            "Tag `{}` contains a `;`, a `\"` or a control character",
+            tag.escape_debug()
+        )));
+    }
+    Ok(tag.to_string())
+}
+
+/// Trims the name and the content type of an attachment and checks that the name is usable as a file name and that the
+/// data is not empty and not larger than `max_size`
+fn normalize_transaction_attachment(
+    mut attachment: TransactionAttachment,
+    max_size: usize,
+) -> Result<TransactionAttachment, TransactionServiceError> {
+    attachment.name = attachment.name.trim().to_string();
+    if attachment.name.is_empty() {
+        return Err(TransactionServiceError::InvalidTransactionAttachment(
+            "Attachments must have a name".to_string(),
+        ));
+    }
+    if attachment.name.chars().count() > MAX_ATTACHMENT_NAME_LENGTH {
+        return Err(TransactionServiceError::InvalidTransactionAttachment(format!(
+            "Attachment names cannot be longer than {} characters",
+            MAX_ATTACHMENT_NAME_LENGTH
+        )));
+    }
+    if attachment.name.chars().any(|c| c == '/' || c == '\\' || c.is_control()) {
+        return Err(TransactionServiceError::InvalidTransactionAttachment(format!(
+            "Attachment name `{}` contains a path separator or a control character",
+            attachment.name.escape_debug()
+        )));
+    }
+    if attachment.data.is_empty() {
+        return Err(TransactionServiceError::InvalidTransactionAttachment(
+            "Attachments cannot be empty".to_string(),
+        ));
+    }
+    if attachment.data.len() > max_size {
+        return Err(TransactionServiceError::InvalidTransactionAttachment(format!(
+            "Attachment of {} bytes is larger than the limit of {} bytes",
+            attachment.data.len(),
+            max_size
+        )));
+    }
+    attachment.content_type = attachment.content_type.trim().to_string();
+    if attachment.content_type.is_empty() {
+        attachment.content_type = DEFAULT_ATTACHMENT_CONTENT_TYPE.to_string();
+    }
+    Ok(attachment)
+}
+
 /// Contains the generated TxId and TransactionStatus transaction send result
 #[derive(Debug)]
 pub struct TransactionSendResult {
@@ -2657,4 +4541,34 @@ mod tests {
             assert_eq!(scanning_key, receiver_spending_key);
         }
     }
+
+    #[test]
+    fn test_normalize_transaction_tag() {
+        assert_eq!(normalize_transaction_tag("  groceries ").unwrap(), "groceries");
+        assert_eq!(normalize_transaction_tag("Office supplies").unwrap(), "Office supplies");
+        assert!(normalize_transaction_tag("   ").is_err());
+        assert!(normalize_transaction_tag("rent;housing").is_err());
+        assert!(normalize_transaction_tag("line\nbreak").is_err());
+        assert!(normalize_transaction_tag(&"a".repeat(MAX_TRANSACTION_TAG_LENGTH)).is_ok());
+        assert!(normalize_transaction_tag(&"a".repeat(MAX_TRANSACTION_TAG_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_transaction_attachment() {
+        let attachment = |name: &str, content_type: &str, size: usize| {
+            let data = vec![0; size];
+            TransactionAttachment::new(TxId::from(1u64), name.to_string(), content_type.to_string(), data)
+        };
+        let normalized = normalize_transaction_attachment(attachment(" receipt.png ", "image/png", 100), 100).unwrap();
+        assert_eq!(normalized.name, "receipt.png");
+        assert_eq!(normalized.content_type, "image/png");
+        let normalized = normalize_transaction_attachment(attachment("notes", " ", 1), 100).unwrap();
+        assert_eq!(normalized.content_type, DEFAULT_ATTACHMENT_CONTENT_TYPE);
+        assert!(normalize_transaction_attachment(attachment("receipt.png", "image/png", 101), 100).is_err());
+        assert!(normalize_transaction_attachment(attachment("receipt.png", "image/png", 0), 100).is_err());
+        assert!(normalize_transaction_attachment(attachment("  ", "image/png", 1), 100).is_err());
+        assert!(normalize_transaction_attachment(attachment("../receipt.png", "image/png", 1), 100).is_err());
+        let long_name = "a".repeat(MAX_ATTACHMENT_NAME_LENGTH + 1);
+        assert!(normalize_transaction_attachment(attachment(&long_name, "", 1), 100).is_err());
+    }
 }
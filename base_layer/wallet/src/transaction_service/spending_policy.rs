@@ -0,0 +1,159 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::tari_amount::MicroTari;
+use thiserror::Error;
+
+/// Guardrails that the transaction service enforces on every outgoing transaction before it is constructed. All the
+/// limits are disabled by default.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpendingPolicy {
+    /// The maximum total value that may be sent in any rolling 24 hour period
+    #[serde(default)]
+    pub daily_spend_limit: Option<MicroTari>,
+    /// The maximum value of a single outgoing transaction
+    #[serde(default)]
+    pub max_transaction_amount: Option<MicroTari>,
+    /// If not empty, transactions may only be sent to these public keys
+    #[serde(default)]
+    pub recipient_allowlist: Vec<CommsPublicKey>,
+    /// Transactions may never be sent to these public keys
+    #[serde(default)]
+    pub recipient_denylist: Vec<CommsPublicKey>,
+    /// Outgoing transactions of at least this value are held back for `large_send_delay` before being broadcast
+    #[serde(default)]
+    pub large_send_threshold: Option<MicroTari>,
+    /// The mandatory delay between creating a large transaction and broadcasting it to the network
+    #[serde(default, with = "serializers::seconds")]
+    pub large_send_delay: Duration,
+}
+
+impl SpendingPolicy {
+    /// Checks whether sending `amount` to `destination` is permitted, given the value already sent in the last 24
+    /// hours. Burn transactions have no destination and are only subject to the value limits.
+    pub fn check(
+        &self,
+        destination: Option<&CommsPublicKey>,
+        amount: MicroTari,
+        spent_in_last_day: MicroTari,
+    ) -> Result<(), SpendingPolicyViolation> {
+        if let Some(destination) = destination {
+            if self.recipient_denylist.contains(destination) {
+                return Err(SpendingPolicyViolation::RecipientDenied(destination.clone()));
+            }
+            if !self.recipient_allowlist.is_empty() && !self.recipient_allowlist.contains(destination) {
+                return Err(SpendingPolicyViolation::RecipientNotAllowed(destination.clone()));
+            }
+        }
+        if let Some(maximum) = self.max_transaction_amount {
+            if amount > maximum {
+                return Err(SpendingPolicyViolation::TransactionMaximumExceeded { amount, maximum });
+            }
+        }
+        if let Some(limit) = self.daily_spend_limit {
+            // A total that does not fit in a MicroTari exceeds any limit
+            if spent_in_last_day
+                .checked_add(amount)
+                .map_or(true, |total| total > limit)
+            {
+                return Err(SpendingPolicyViolation::DailyLimitExceeded {
+                    amount,
+                    spent: spent_in_last_day,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The delay that must elapse between creating a transaction of this value and broadcasting it, if any
+    pub fn broadcast_delay(&self, amount: MicroTari) -> Option<Duration> {
+        match self.large_send_threshold {
+            Some(threshold) if amount >= threshold && !self.large_send_delay.is_zero() => Some(self.large_send_delay),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Error)]
+pub enum SpendingPolicyViolation {
+    #[error("Recipient `{0}` is on the denylist")]
+    RecipientDenied(CommsPublicKey),
+    #[error("Recipient `{0}` is not on the allowlist")]
+    RecipientNotAllowed(CommsPublicKey),
+    #[error("Amount {amount} exceeds the maximum of {maximum} per transaction")]
+    TransactionMaximumExceeded { amount: MicroTari, maximum: MicroTari },
+    #[error("Amount {amount} exceeds the daily spend limit of {limit} ({spent} already sent in the last 24 hours)")]
+    DailyLimitExceeded {
+        amount: MicroTari,
+        spent: MicroTari,
+        limit: MicroTari,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey;
+
+    use super::*;
+
+    #[test]
+    fn it_enforces_limits() {
+        let (_, alice) = CommsPublicKey::random_keypair(&mut OsRng);
+        let (_, bob) = CommsPublicKey::random_keypair(&mut OsRng);
+        let policy = SpendingPolicy {
+            daily_spend_limit: Some(MicroTari::from(1_000)),
+            max_transaction_amount: Some(MicroTari::from(600)),
+            recipient_denylist: vec![bob.clone()],
+            ..Default::default()
+        };
+
+        assert!(policy.check(Some(&alice), 500.into(), 0.into()).is_ok());
+        assert!(policy.check(None, 500.into(), 0.into()).is_ok());
+        assert_eq!(
+            policy.check(Some(&bob), 500.into(), 0.into()),
+            Err(SpendingPolicyViolation::RecipientDenied(bob.clone()))
+        );
+        assert!(matches!(
+            policy.check(Some(&alice), 601.into(), 0.into()),
+            Err(SpendingPolicyViolation::TransactionMaximumExceeded { .. })
+        ));
+        assert!(matches!(
+            policy.check(Some(&alice), 500.into(), 501.into()),
+            Err(SpendingPolicyViolation::DailyLimitExceeded { .. })
+        ));
+        assert!(matches!(
+            policy.check(Some(&alice), 500.into(), u64::MAX.into()),
+            Err(SpendingPolicyViolation::DailyLimitExceeded { .. })
+        ));
+
+        let policy = SpendingPolicy {
+            recipient_allowlist: vec![alice.clone()],
+            ..Default::default()
+        };
+        assert!(policy.check(Some(&alice), 500.into(), 0.into()).is_ok());
+        assert_eq!(
+            policy.check(Some(&bob), 500.into(), 0.into()),
+            Err(SpendingPolicyViolation::RecipientNotAllowed(bob))
+        );
+    }
+
+    #[test]
+    fn it_delays_large_sends() {
+        let policy = SpendingPolicy {
+            large_send_threshold: Some(MicroTari::from(1_000)),
+            large_send_delay: Duration::from_secs(60),
+            ..Default::default()
+        };
+        assert_eq!(policy.broadcast_delay(999.into()), None);
+        assert_eq!(policy.broadcast_delay(1_000.into()), Some(Duration::from_secs(60)));
+        assert_eq!(SpendingPolicy::default().broadcast_delay(1_000.into()), None);
+    }
+}
@@ -33,18 +33,27 @@ use chrono::{NaiveDateTime, Utc};
 use log::*;
 use tari_common_types::{
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
-    types::{BlindingFactor, BlockHash},
+    types::{BlindingFactor, BlockHash, PublicKey},
 };
 use tari_comms::types::CommsPublicKey;
-use tari_core::transactions::{tari_amount::MicroTari, transaction_components::Transaction};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction_components::Transaction,
+    transaction_protocol::aggregated_sender::SenderSigningSession,
+};
 
 use crate::transaction_service::{
     error::TransactionStorageError,
     storage::{
         models::{
             CompletedTransaction,
+            CounterpartyStats,
+            IdempotencyKeyRecord,
             InboundTransaction,
             OutboundTransaction,
+            PendingApprovalTransaction,
+            TransactionAttachment,
+            TransactionProtocolState,
             TxCancellationReason,
             WalletTransaction,
         },
@@ -103,6 +112,8 @@ pub trait TransactionBackend: Send + Sync + Clone {
         tx_id: TxId,
         reason: TxCancellationReason,
     ) -> Result<(), TransactionStorageError>;
+    /// Marks a completed transaction as double spent and cancels it
+    fn mark_completed_transaction_as_double_spent(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
     /// Set cancellation on Pending transaction, this will update the transaction status
     fn set_pending_transaction_cancellation_status(
         &self,
@@ -160,6 +171,65 @@ pub trait TransactionBackend: Send + Sync + Clone {
         height: u64,
     ) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
     fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Applies a batch of mined state changes in a single database transaction
+    fn apply_status_updates(&self, updates: Vec<TransactionStatusUpdate>) -> Result<(), TransactionStorageError>;
+    /// Insert or replace the persisted state of an in-flight transaction protocol
+    fn save_transaction_protocol_state(&self, state: TransactionProtocolState) -> Result<(), TransactionStorageError>;
+    /// Fetch the persisted states of all the in-flight transaction protocols
+    fn fetch_transaction_protocol_states(&self) -> Result<Vec<TransactionProtocolState>, TransactionStorageError>;
+    /// Remove the persisted state of a transaction protocol once it has run to completion
+    fn remove_transaction_protocol_state(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Insert or replace a signing session of this wallet as a party to an aggregated sender key
+    fn save_aggregated_sender_session(&self, session: SenderSigningSession) -> Result<(), TransactionStorageError>;
+    /// Fetch the signing session in which this wallet is the party with the given public key
+    fn fetch_aggregated_sender_session(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<SenderSigningSession>, TransactionStorageError>;
+    /// Remove a signing session once it has signed or was abandoned
+    fn remove_aggregated_sender_session(&self, public_key: &PublicKey) -> Result<(), TransactionStorageError>;
+    /// Record the transaction that was created for a client supplied idempotency key
+    fn save_idempotency_key(&self, record: IdempotencyKeyRecord) -> Result<(), TransactionStorageError>;
+    /// Fetch the transaction record associated with an idempotency key, if the key has been used before
+    fn fetch_idempotency_key(
+        &self,
+        idempotency_key: &str,
+    ) -> Result<Option<IdempotencyKeyRecord>, TransactionStorageError>;
+    /// Remove an idempotency key so that it can be used for a new transaction
+    fn remove_idempotency_key(&self, idempotency_key: &str) -> Result<(), TransactionStorageError>;
+    /// Add an outgoing transaction to the queue of transactions waiting to be approved
+    fn insert_pending_approval_transaction(
+        &self,
+        transaction: PendingApprovalTransaction,
+    ) -> Result<(), TransactionStorageError>;
+    /// Fetch all the outgoing transactions waiting to be approved
+    fn fetch_pending_approval_transactions(&self) -> Result<Vec<PendingApprovalTransaction>, TransactionStorageError>;
+    /// Remove a transaction from the approval queue once it has been approved, cancelled or has expired
+    fn remove_pending_approval_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Aggregate the completed transactions by counterparty, optionally limited to a single counterparty and to the
+    /// transactions created since a point in time
+    fn fetch_counterparty_stats(
+        &self,
+        counterparty: Option<&CommsPublicKey>,
+        since: Option<NaiveDateTime>,
+    ) -> Result<Vec<CounterpartyStats>, TransactionStorageError>;
+    /// Add a user defined tag to a transaction. Adding a tag that the transaction already has is a no-op.
+    fn add_transaction_tag(&self, tx_id: TxId, tag: &str) -> Result<(), TransactionStorageError>;
+    /// Remove a tag from a transaction
+    fn remove_transaction_tag(&self, tx_id: TxId, tag: &str) -> Result<(), TransactionStorageError>;
+    /// Fetch the tags of a transaction in alphabetical order
+    fn fetch_transaction_tags(&self, tx_id: TxId) -> Result<Vec<String>, TransactionStorageError>;
+    /// Fetch the tags of all the transactions that have at least one tag
+    fn fetch_all_transaction_tags(&self) -> Result<HashMap<TxId, Vec<String>>, TransactionStorageError>;
+    /// Fetch the ids of the transactions that have a tag
+    fn fetch_tagged_transaction_ids(&self, tag: &str) -> Result<Vec<TxId>, TransactionStorageError>;
+    /// Attach a file to a transaction, replacing the attachment of the transaction with the same name
+    fn save_transaction_attachment(&self, attachment: TransactionAttachment) -> Result<(), TransactionStorageError>;
+    /// Fetch the attachments of a transaction ordered by name
+    fn fetch_transaction_attachments(&self, tx_id: TxId)
+        -> Result<Vec<TransactionAttachment>, TransactionStorageError>;
+    /// Remove an attachment from a transaction
+    fn remove_transaction_attachment(&self, tx_id: TxId, name: &str) -> Result<(), TransactionStorageError>;
 }
 
 #[derive(Clone, PartialEq)]
@@ -266,6 +336,24 @@ pub enum WriteOperation {
     Remove(DbKey),
 }
 
+/// A change to the mined state of a completed transaction that the validation protocol found on chain. A batch of
+/// updates is applied atomically with [TransactionBackend::apply_status_updates].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatusUpdate {
+    Mined {
+        tx_id: TxId,
+        mined_height: u64,
+        mined_in_block: BlockHash,
+        mined_timestamp: u64,
+        num_confirmations: u64,
+        is_confirmed: bool,
+        is_faux: bool,
+    },
+    Unmined(TxId),
+    DoubleSpent(TxId),
+    AbandonedCoinbase(TxId),
+}
+
 /// This structure holds an inner type that implements the `TransactionBackend` trait and contains the more complex
 /// data access logic required by the module built onto the functionality defined by the trait
 #[derive(Clone)]
@@ -629,6 +717,10 @@ where T: TransactionBackend + 'static
         self.db.reject_completed_transaction(tx_id, reason)
     }
 
+    pub fn mark_completed_transaction_as_double_spent(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        self.db.mark_completed_transaction_as_double_spent(tx_id)
+    }
+
     pub fn cancel_pending_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         self.db.set_pending_transaction_cancellation_status(tx_id, true)
     }
@@ -758,6 +850,144 @@ where T: TransactionBackend + 'static
     pub fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         self.db.abandon_coinbase_transaction(tx_id)
     }
+
+    /// Applies the updates in a single database transaction, so either all or none of them are applied
+    pub fn apply_status_updates(&self, updates: Vec<TransactionStatusUpdate>) -> Result<(), TransactionStorageError> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+        self.db.apply_status_updates(updates)
+    }
+
+    pub fn save_transaction_protocol_state(
+        &self,
+        state: TransactionProtocolState,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.save_transaction_protocol_state(state)
+    }
+
+    pub fn get_transaction_protocol_states(&self) -> Result<Vec<TransactionProtocolState>, TransactionStorageError> {
+        self.db.fetch_transaction_protocol_states()
+    }
+
+    pub fn remove_transaction_protocol_state(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        self.db.remove_transaction_protocol_state(tx_id)
+    }
+
+    pub fn save_aggregated_sender_session(&self, session: SenderSigningSession) -> Result<(), TransactionStorageError> {
+        self.db.save_aggregated_sender_session(session)
+    }
+
+    pub fn get_aggregated_sender_session(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<SenderSigningSession>, TransactionStorageError> {
+        self.db.fetch_aggregated_sender_session(public_key)
+    }
+
+    pub fn remove_aggregated_sender_session(&self, public_key: &PublicKey) -> Result<(), TransactionStorageError> {
+        self.db.remove_aggregated_sender_session(public_key)
+    }
+
+    pub fn save_idempotency_key(&self, record: IdempotencyKeyRecord) -> Result<(), TransactionStorageError> {
+        self.db.save_idempotency_key(record)
+    }
+
+    pub fn get_idempotency_key(
+        &self,
+        idempotency_key: &str,
+    ) -> Result<Option<IdempotencyKeyRecord>, TransactionStorageError> {
+        self.db.fetch_idempotency_key(idempotency_key)
+    }
+
+    pub fn remove_idempotency_key(&self, idempotency_key: &str) -> Result<(), TransactionStorageError> {
+        self.db.remove_idempotency_key(idempotency_key)
+    }
+
+    pub fn add_pending_approval_transaction(
+        &self,
+        transaction: PendingApprovalTransaction,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.insert_pending_approval_transaction(transaction)
+    }
+
+    pub fn get_pending_approval_transactions(
+        &self,
+    ) -> Result<Vec<PendingApprovalTransaction>, TransactionStorageError> {
+        self.db.fetch_pending_approval_transactions()
+    }
+
+    pub fn get_pending_approval_transaction(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Option<PendingApprovalTransaction>, TransactionStorageError> {
+        Ok(self
+            .db
+            .fetch_pending_approval_transactions()?
+            .into_iter()
+            .find(|tx| tx.tx_id == tx_id))
+    }
+
+    pub fn remove_pending_approval_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        self.db.remove_pending_approval_transaction(tx_id)
+    }
+
+    pub fn get_counterparty_stats(
+        &self,
+        counterparty: &CommsPublicKey,
+    ) -> Result<CounterpartyStats, TransactionStorageError> {
+        Ok(self
+            .db
+            .fetch_counterparty_stats(Some(counterparty), None)?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| CounterpartyStats::empty(counterparty.clone())))
+    }
+
+    pub fn get_counterparty_stats_since(
+        &self,
+        since: Option<NaiveDateTime>,
+    ) -> Result<Vec<CounterpartyStats>, TransactionStorageError> {
+        self.db.fetch_counterparty_stats(None, since)
+    }
+
+    pub fn add_transaction_tag(&self, tx_id: TxId, tag: &str) -> Result<(), TransactionStorageError> {
+        self.db.add_transaction_tag(tx_id, tag)
+    }
+
+    pub fn remove_transaction_tag(&self, tx_id: TxId, tag: &str) -> Result<(), TransactionStorageError> {
+        self.db.remove_transaction_tag(tx_id, tag)
+    }
+
+    pub fn get_transaction_tags(&self, tx_id: TxId) -> Result<Vec<String>, TransactionStorageError> {
+        self.db.fetch_transaction_tags(tx_id)
+    }
+
+    pub fn get_all_transaction_tags(&self) -> Result<HashMap<TxId, Vec<String>>, TransactionStorageError> {
+        self.db.fetch_all_transaction_tags()
+    }
+
+    pub fn get_tagged_transaction_ids(&self, tag: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        self.db.fetch_tagged_transaction_ids(tag)
+    }
+
+    pub fn save_transaction_attachment(
+        &self,
+        attachment: TransactionAttachment,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.save_transaction_attachment(attachment)
+    }
+
+    pub fn get_transaction_attachments(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Vec<TransactionAttachment>, TransactionStorageError> {
+        self.db.fetch_transaction_attachments(tx_id)
+    }
+
+    pub fn remove_transaction_attachment(&self, tx_id: TxId, name: &str) -> Result<(), TransactionStorageError> {
+        self.db.remove_transaction_attachment(tx_id, name)
+    }
 }
 
 impl Display for DbKey {
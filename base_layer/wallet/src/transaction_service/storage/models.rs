@@ -25,7 +25,7 @@ use std::{
     fmt::{Display, Error, Formatter},
 };
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tari_common_types::{
     transaction::{TransactionConversionError, TransactionDirection, TransactionStatus, TxId},
@@ -34,11 +34,13 @@ use tari_common_types::{
 use tari_comms::types::CommsPublicKey;
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction_components::Transaction,
+    transaction_components::{OutputFeatures, Transaction},
     ReceiverTransactionProtocol,
     SenderTransactionProtocol,
 };
 
+use crate::util::clock::corrected_utc_now;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct InboundTransaction {
     pub tx_id: TxId,
@@ -52,6 +54,8 @@ pub struct InboundTransaction {
     pub direct_send_success: bool,
     pub send_count: u32,
     pub last_send_timestamp: Option<NaiveDateTime>,
+    /// The time set by the sender after which the transaction is cancelled if it has not been finalized
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 impl InboundTransaction {
@@ -76,8 +80,15 @@ impl InboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         }
     }
+
+    /// Sets the time after which the transaction is cancelled if it has not been finalized
+    pub fn with_expiry(mut self, expires_at: Option<NaiveDateTime>) -> Self {
+        self.expires_at = expires_at;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -94,6 +105,8 @@ pub struct OutboundTransaction {
     pub direct_send_success: bool,
     pub send_count: u32,
     pub last_send_timestamp: Option<NaiveDateTime>,
+    /// The time after which the transaction is cancelled if it has not been finalized
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 impl OutboundTransaction {
@@ -121,8 +134,15 @@ impl OutboundTransaction {
             direct_send_success,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         }
     }
+
+    /// Sets the time after which the transaction is cancelled if it has not been finalized
+    pub fn with_expiry(mut self, expires_at: Option<NaiveDateTime>) -> Self {
+        self.expires_at = expires_at;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -146,6 +166,9 @@ pub struct CompletedTransaction {
     pub mined_height: Option<u64>,
     pub mined_in_block: Option<BlockHash>,
     pub mined_timestamp: Option<NaiveDateTime>,
+    /// Decoy transactions are self-spends created by the decoy service to obscure the wallet's spending pattern. They
+    /// are not real payments.
+    pub is_decoy: bool,
 }
 
 impl CompletedTransaction {
@@ -189,6 +212,7 @@ impl CompletedTransaction {
             mined_height,
             mined_in_block: None,
             mined_timestamp,
+            is_decoy: false,
         }
     }
 
@@ -199,6 +223,18 @@ impl CompletedTransaction {
             false
         }
     }
+
+    /// The extra data, e.g. a pool tag, that the miner added to the coinbase output of a coinbase transaction
+    pub fn coinbase_extra(&self) -> Option<&[u8]> {
+        if !self.is_coinbase() {
+            return None;
+        }
+        self.transaction
+            .body
+            .outputs()
+            .iter()
+            .find_map(|o| o.features.coinbase_extra())
+    }
 }
 
 impl From<CompletedTransaction> for InboundTransaction {
@@ -215,6 +251,7 @@ impl From<CompletedTransaction> for InboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         }
     }
 }
@@ -234,6 +271,7 @@ impl From<CompletedTransaction> for OutboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         }
     }
 }
@@ -277,6 +315,7 @@ impl From<OutboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         }
     }
 }
@@ -307,6 +346,7 @@ impl From<InboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         }
     }
 }
@@ -376,3 +416,232 @@ impl Display for TxCancellationReason {
         fmt.write_str(response)
     }
 }
+
+/// The negotiation round that an in-flight transaction protocol has reached
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionNegotiationStage {
+    /// The sender has built the transaction but has not yet sent the initial message to the recipient
+    SenderQueued, // 0
+    /// The initial message was sent and the sender is waiting for the recipient's reply
+    SenderWaitForReply, // 1
+    /// The recipient has accepted the transaction but has not yet sent its reply
+    ReceiverReplyPending, // 2
+    /// The reply was sent and the recipient is waiting for the finalized transaction
+    ReceiverWaitForFinalize, // 3
+}
+
+impl TryFrom<i32> for TransactionNegotiationStage {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TransactionNegotiationStage::SenderQueued),
+            1 => Ok(TransactionNegotiationStage::SenderWaitForReply),
+            2 => Ok(TransactionNegotiationStage::ReceiverReplyPending),
+            3 => Ok(TransactionNegotiationStage::ReceiverWaitForFinalize),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<TransactionNegotiationStage> for i32 {
+    fn from(stage: TransactionNegotiationStage) -> Self {
+        match stage {
+            TransactionNegotiationStage::SenderQueued => 0,
+            TransactionNegotiationStage::SenderWaitForReply => 1,
+            TransactionNegotiationStage::ReceiverReplyPending => 2,
+            TransactionNegotiationStage::ReceiverWaitForFinalize => 3,
+        }
+    }
+}
+
+/// A snapshot of the sender or receiver side of a transaction negotiation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TransactionProtocolSnapshot {
+    Sender(Box<SenderTransactionProtocol>),
+    Receiver(Box<ReceiverTransactionProtocol>),
+}
+
+/// The persisted state of an in-flight transaction protocol, used to resume the negotiation after a restart
+#[derive(Debug, Clone)]
+pub struct TransactionProtocolState {
+    pub tx_id: TxId,
+    pub stage: TransactionNegotiationStage,
+    pub counterparty_public_key: CommsPublicKey,
+    pub snapshot: TransactionProtocolSnapshot,
+    pub last_updated: NaiveDateTime,
+}
+
+impl TransactionProtocolState {
+    pub fn new(
+        tx_id: TxId,
+        stage: TransactionNegotiationStage,
+        counterparty_public_key: CommsPublicKey,
+        snapshot: TransactionProtocolSnapshot,
+    ) -> Self {
+        Self {
+            tx_id,
+            stage,
+            counterparty_public_key,
+            snapshot,
+            last_updated: Utc::now().naive_utc(),
+        }
+    }
+}
+
+/// Associates a client supplied idempotency key with the transaction that was created the first time it was used
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotencyKeyRecord {
+    pub idempotency_key: String,
+    pub tx_id: TxId,
+    pub destination_public_key: CommsPublicKey,
+    pub amount: MicroTari,
+    pub timestamp: NaiveDateTime,
+}
+
+impl IdempotencyKeyRecord {
+    pub fn new(
+        idempotency_key: String,
+        tx_id: TxId,
+        destination_public_key: CommsPublicKey,
+        amount: MicroTari,
+    ) -> Self {
+        Self {
+            idempotency_key,
+            tx_id,
+            destination_public_key,
+            amount,
+            timestamp: Utc::now().naive_utc(),
+        }
+    }
+}
+
+/// The kind of payment that is held back until it has been approved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApprovalPaymentType {
+    Standard,                 // 0
+    OneSided,                 // 1
+    OneSidedToStealthAddress, // 2
+}
+
+impl TryFrom<i32> for ApprovalPaymentType {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ApprovalPaymentType::Standard),
+            1 => Ok(ApprovalPaymentType::OneSided),
+            2 => Ok(ApprovalPaymentType::OneSidedToStealthAddress),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<ApprovalPaymentType> for i32 {
+    fn from(payment_type: ApprovalPaymentType) -> Self {
+        match payment_type {
+            ApprovalPaymentType::Standard => 0,
+            ApprovalPaymentType::OneSided => 1,
+            ApprovalPaymentType::OneSidedToStealthAddress => 2,
+        }
+    }
+}
+
+/// An outgoing transaction that has been requested but will only be constructed and sent once it is approved
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingApprovalTransaction {
+    pub tx_id: TxId,
+    pub payment_type: ApprovalPaymentType,
+    pub destination_public_key: CommsPublicKey,
+    pub amount: MicroTari,
+    pub fee_per_gram: MicroTari,
+    pub output_features: OutputFeatures,
+    pub message: String,
+    /// The memo of a one-sided payment
+    pub memo: Option<String>,
+    pub timestamp: NaiveDateTime,
+    pub expiry_timestamp: NaiveDateTime,
+    /// The time after which the transaction is cancelled if it has not been finalized, once it is approved
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl PendingApprovalTransaction {
+    pub fn is_expired(&self) -> bool {
+        corrected_utc_now() >= self.expiry_timestamp
+    }
+}
+
+/// The totals of the completed, non-cancelled transactions exchanged with a single counterparty. Coinbase transactions
+/// are not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CounterpartyStats {
+    pub counterparty: CommsPublicKey,
+    pub total_sent: MicroTari,
+    pub total_received: MicroTari,
+    /// The fees paid on the transactions sent to the counterparty
+    pub fees_paid: MicroTari,
+    pub transaction_count: u64,
+    pub last_payment: Option<NaiveDateTime>,
+}
+
+impl CounterpartyStats {
+    /// The stats of a counterparty that no transactions have been exchanged with
+    pub fn empty(counterparty: CommsPublicKey) -> Self {
+        Self {
+            counterparty,
+            total_sent: MicroTari::zero(),
+            total_received: MicroTari::zero(),
+            fees_paid: MicroTari::zero(),
+            transaction_count: 0,
+            last_payment: None,
+        }
+    }
+}
+
+/// The transactions of a period grouped by counterparty, ordered by the total value exchanged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendingSummary {
+    /// The start of the period, or None if the summary covers the whole history of the wallet
+    pub since: Option<NaiveDateTime>,
+    pub total_sent: MicroTari,
+    pub total_received: MicroTari,
+    pub fees_paid: MicroTari,
+    pub counterparties: Vec<CounterpartyStats>,
+}
+
+impl SpendingSummary {
+    pub fn new(since: Option<NaiveDateTime>, counterparties: Vec<CounterpartyStats>) -> Self {
+        Self {
+            since,
+            total_sent: counterparties.iter().map(|stats| stats.total_sent).sum(),
+            total_received: counterparties.iter().map(|stats| stats.total_received).sum(),
+            fees_paid: counterparties.iter().map(|stats| stats.fees_paid).sum(),
+            counterparties,
+        }
+    }
+}
+
+/// A small file, e.g. the receipt or the invoice of a payment, attached to a transaction. Attachments are stored, and
+/// encrypted, with the transactions in the wallet database, so they are part of its backups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionAttachment {
+    pub tx_id: TxId,
+    /// The name of the attachment, unique among the attachments of the transaction, e.g. its file name
+    pub name: String,
+    /// The MIME type of the data, e.g. `application/pdf`
+    pub content_type: String,
+    pub data: Vec<u8>,
+    pub timestamp: NaiveDateTime,
+}
+
+impl TransactionAttachment {
+    pub fn new(tx_id: TxId, name: String, content_type: String, data: Vec<u8>) -> Self {
+        Self {
+            tx_id,
+            name,
+            content_type,
+            data,
+            timestamp: Utc::now().naive_utc(),
+        }
+    }
+}
@@ -29,7 +29,7 @@ use std::{
 
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::{NaiveDateTime, Utc};
-use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
+use diesel::{prelude::*, result::Error as DieselError, sql_query, SqliteConnection};
 use log::*;
 use tari_common_types::{
     transaction::{
@@ -42,7 +42,7 @@ use tari_common_types::{
     types::{BlockHash, PrivateKey, PublicKey, Signature},
 };
 use tari_comms::types::CommsPublicKey;
-use tari_core::transactions::tari_amount::MicroTari;
+use tari_core::transactions::{tari_amount::MicroTari, transaction_protocol::aggregated_sender::SenderSigningSession};
 use tari_utilities::{
     hex::{from_hex, Hex},
     ByteArray,
@@ -51,16 +51,31 @@ use thiserror::Error;
 use tokio::time::Instant;
 
 use crate::{
-    schema::{completed_transactions, inbound_transactions, outbound_transactions},
+    schema::{
+        aggregated_sender_sessions,
+        completed_transactions,
+        inbound_transactions,
+        outbound_transactions,
+        pending_approval_transactions,
+        transaction_attachments,
+        transaction_idempotency_keys,
+        transaction_protocol_states,
+        transaction_tags,
+    },
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
     transaction_service::{
         error::{TransactionKeyError, TransactionStorageError},
         storage::{
-            database::{DbKey, DbKeyValuePair, DbValue, TransactionBackend, WriteOperation},
+            database::{DbKey, DbKeyValuePair, DbValue, TransactionBackend, TransactionStatusUpdate, WriteOperation},
             models::{
                 CompletedTransaction,
+                CounterpartyStats,
+                IdempotencyKeyRecord,
                 InboundTransaction,
                 OutboundTransaction,
+                PendingApprovalTransaction,
+                TransactionAttachment,
+                TransactionProtocolState,
                 TxCancellationReason,
                 WalletTransaction,
             },
@@ -225,6 +240,54 @@ impl TransactionServiceSqliteDatabase {
         }
         Ok(())
     }
+
+    fn apply_status_update(
+        update: TransactionStatusUpdate,
+        conn: &SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        let not_found = |tx_id: TxId| {
+            move |e: TransactionStorageError| match e {
+                TransactionStorageError::DieselError(DieselError::NotFound) => {
+                    TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(tx_id))
+                },
+                e => e,
+            }
+        };
+        match update {
+            TransactionStatusUpdate::Mined {
+                tx_id,
+                mined_height,
+                mined_in_block,
+                mined_timestamp,
+                num_confirmations,
+                is_confirmed,
+                is_faux,
+            } => CompletedTransactionSql::find(tx_id, conn)
+                .map_err(not_found(tx_id))?
+                .update_mined_height(
+                    mined_height,
+                    mined_in_block,
+                    mined_timestamp,
+                    num_confirmations,
+                    is_confirmed,
+                    conn,
+                    is_faux,
+                ),
+            TransactionStatusUpdate::Unmined(tx_id) => CompletedTransactionSql::find(tx_id, conn)
+                .map_err(not_found(tx_id))?
+                .set_as_unmined(conn),
+            TransactionStatusUpdate::DoubleSpent(tx_id) => {
+                CompletedTransactionSql::find_by_cancelled(tx_id, false, conn)
+                    .map_err(not_found(tx_id))?
+                    .mark_double_spent(conn)
+            },
+            TransactionStatusUpdate::AbandonedCoinbase(tx_id) => {
+                CompletedTransactionSql::find_by_cancelled(tx_id, false, conn)
+                    .map_err(not_found(tx_id))?
+                    .abandon_coinbase(conn)
+            },
+        }
+    }
 }
 
 impl TransactionBackend for TransactionServiceSqliteDatabase {
@@ -711,6 +774,23 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         Ok(())
     }
 
+    fn mark_completed_transaction_as_double_spent(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        Self::apply_status_update(TransactionStatusUpdate::DoubleSpent(tx_id), &conn)?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - mark_completed_transaction_as_double_spent: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
     fn set_pending_transaction_cancellation_status(
         &self,
         tx_id: TxId,
@@ -856,6 +936,46 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
             tx.update_encryption(&conn)?;
         }
 
+        let mut protocol_states = TransactionProtocolStateSql::index(&conn)?;
+        for state in &mut protocol_states {
+            // Test if this protocol state is encrypted or not to avoid a double encryption.
+            let _protocol_state = TransactionProtocolState::try_from(state.clone()).map_err(|_| {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not convert Transaction Protocol State from database version, it might already be encrypted"
+                );
+                TransactionStorageError::AlreadyEncrypted
+            })?;
+            state
+                .encrypt(&cipher)
+                .map_err(|_| TransactionStorageError::AeadError("Encryption Error".to_string()))?;
+            state.update_encryption(&conn)?;
+        }
+
+        let mut sessions = AggregatedSenderSessionSql::index(&conn)?;
+        for session in &mut sessions {
+            // Test if this session is encrypted or not to avoid a double encryption.
+            let _session = SenderSigningSession::try_from(session.clone()).map_err(|_| {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not convert Aggregated Sender Session from database version, it might already be encrypted"
+                );
+                TransactionStorageError::AlreadyEncrypted
+            })?;
+            session
+                .encrypt(&cipher)
+                .map_err(|_| TransactionStorageError::AeadError("Encryption Error".to_string()))?;
+            session.update_encryption(&conn)?;
+        }
+
+        let mut attachments = TransactionAttachmentSql::index(&conn)?;
+        for attachment in &mut attachments {
+            attachment
+                .encrypt(&cipher)
+                .map_err(|_| TransactionStorageError::AeadError("Encryption Error".to_string()))?;
+            attachment.update_encryption(&conn)?;
+        }
+
         (*current_cipher) = Some(cipher);
         if start.elapsed().as_millis() > 0 {
             trace!(
@@ -905,6 +1025,30 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
             tx.update_encryption(&conn)?;
         }
 
+        let mut protocol_states = TransactionProtocolStateSql::index(&conn)?;
+        for state in &mut protocol_states {
+            state
+                .decrypt(&cipher)
+                .map_err(|_| TransactionStorageError::AeadError("Decryption Error".to_string()))?;
+            state.update_encryption(&conn)?;
+        }
+
+        let mut sessions = AggregatedSenderSessionSql::index(&conn)?;
+        for session in &mut sessions {
+            session
+                .decrypt(&cipher)
+                .map_err(|_| TransactionStorageError::AeadError("Decryption Error".to_string()))?;
+            session.update_encryption(&conn)?;
+        }
+
+        let mut attachments = TransactionAttachmentSql::index(&conn)?;
+        for attachment in &mut attachments {
+            attachment
+                .decrypt(&cipher)
+                .map_err(|_| TransactionStorageError::AeadError("Decryption Error".to_string()))?;
+            attachment.update_encryption(&conn)?;
+        }
+
         // Now that all the decryption has been completed we can safely remove the cipher fully
         std::mem::drop((*current_cipher).take());
         if start.elapsed().as_millis() > 0 {
@@ -1031,25 +1175,18 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        match CompletedTransactionSql::find(tx_id, &conn) {
-            Ok(v) => {
-                v.update_mined_height(
-                    mined_height,
-                    mined_in_block,
-                    mined_timestamp,
-                    num_confirmations,
-                    is_confirmed,
-                    &conn,
-                    is_faux,
-                )?;
-            },
-            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
-                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
-                    tx_id,
-                )));
+        Self::apply_status_update(
+            TransactionStatusUpdate::Mined {
+                tx_id,
+                mined_height,
+                mined_in_block,
+                mined_timestamp,
+                num_confirmations,
+                is_confirmed,
+                is_faux,
             },
-            Err(e) => return Err(e),
-        };
+            &conn,
+        )?;
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -1186,17 +1323,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         let start = Instant::now();
         let conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
-        match CompletedTransactionSql::find(tx_id, &conn) {
-            Ok(v) => {
-                v.set_as_unmined(&conn)?;
-            },
-            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
-                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
-                    tx_id,
-                )));
-            },
-            Err(e) => return Err(e),
-        };
+        Self::apply_status_update(TransactionStatusUpdate::Unmined(tx_id), &conn)?;
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -1285,20 +1412,236 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
     fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         let conn = self.database_connection.get_pooled_connection()?;
-        match CompletedTransactionSql::find_by_cancelled(tx_id, false, &conn) {
-            Ok(tx) => {
-                tx.abandon_coinbase(&conn)?;
-            },
-            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
-                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
-                    tx_id,
-                )));
-            },
-            Err(e) => return Err(e),
-        };
+        Self::apply_status_update(TransactionStatusUpdate::AbandonedCoinbase(tx_id), &conn)
+    }
+
+    fn apply_status_updates(&self, updates: Vec<TransactionStatusUpdate>) -> Result<(), TransactionStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let num_updates = updates.len();
+        conn.transaction::<_, TransactionStorageError, _>(|| {
+            for update in updates {
+                Self::apply_status_update(update, &conn)?;
+            }
+            Ok(())
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - apply_status_updates ({} updates): lock {} + db_op {} = {} ms",
+                num_updates,
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
+    fn save_transaction_protocol_state(&self, state: TransactionProtocolState) -> Result<(), TransactionStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let mut state_sql = TransactionProtocolStateSql::try_from(state)?;
+        self.encrypt_if_necessary(&mut state_sql)?;
+        state_sql.commit(&conn)?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - save_transaction_protocol_state: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
 
         Ok(())
     }
+
+    fn fetch_transaction_protocol_states(&self) -> Result<Vec<TransactionProtocolState>, TransactionStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let mut result = Vec::new();
+        for mut state_sql in TransactionProtocolStateSql::index(&conn)? {
+            self.decrypt_if_necessary(&mut state_sql)?;
+            result.push(TransactionProtocolState::try_from(state_sql)?);
+        }
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - fetch_transaction_protocol_states: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+
+        Ok(result)
+    }
+
+    fn remove_transaction_protocol_state(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        TransactionProtocolStateSql::delete(tx_id, &conn)
+    }
+
+    fn save_aggregated_sender_session(&self, session: SenderSigningSession) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        let mut session_sql = AggregatedSenderSessionSql::try_from(session)?;
+        self.encrypt_if_necessary(&mut session_sql)?;
+        session_sql.commit(&conn)
+    }
+
+    fn fetch_aggregated_sender_session(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<SenderSigningSession>, TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        match AggregatedSenderSessionSql::find(public_key, &conn) {
+            Ok(mut session_sql) => {
+                self.decrypt_if_necessary(&mut session_sql)?;
+                Ok(Some(SenderSigningSession::try_from(session_sql)?))
+            },
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove_aggregated_sender_session(&self, public_key: &PublicKey) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        AggregatedSenderSessionSql::delete(public_key, &conn)
+    }
+
+    fn save_idempotency_key(&self, record: IdempotencyKeyRecord) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        IdempotencyKeySql::from(record).commit(&conn)
+    }
+
+    fn fetch_idempotency_key(
+        &self,
+        idempotency_key: &str,
+    ) -> Result<Option<IdempotencyKeyRecord>, TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        match IdempotencyKeySql::find(idempotency_key, &conn) {
+            Ok(record) => Ok(Some(IdempotencyKeyRecord::try_from(record)?)),
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove_idempotency_key(&self, idempotency_key: &str) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        IdempotencyKeySql::delete(idempotency_key, &conn)
+    }
+
+    fn insert_pending_approval_transaction(
+        &self,
+        transaction: PendingApprovalTransaction,
+    ) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        PendingApprovalTransactionSql::try_from(transaction)?.commit(&conn)
+    }
+
+    fn fetch_pending_approval_transactions(&self) -> Result<Vec<PendingApprovalTransaction>, TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        PendingApprovalTransactionSql::index(&conn)?
+            .into_iter()
+            .map(PendingApprovalTransaction::try_from)
+            .collect()
+    }
+
+    fn remove_pending_approval_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        PendingApprovalTransactionSql::delete(tx_id, &conn)
+    }
+
+    fn fetch_counterparty_stats(
+        &self,
+        counterparty: Option<&CommsPublicKey>,
+        since: Option<NaiveDateTime>,
+    ) -> Result<Vec<CounterpartyStats>, TransactionStorageError> {
+        let start = Instant::now();
+        let conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let result = CounterpartyStatsSql::fetch(counterparty, since, &conn)?
+            .into_iter()
+            .map(CounterpartyStats::try_from)
+            .collect();
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - fetch_counterparty_stats: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        result
+    }
+
+    fn add_transaction_tag(&self, tx_id: TxId, tag: &str) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        TransactionTagSql::new(tx_id, tag).commit(&conn)
+    }
+
+    fn remove_transaction_tag(&self, tx_id: TxId, tag: &str) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        TransactionTagSql::new(tx_id, tag).delete(&conn)
+    }
+
+    fn fetch_transaction_tags(&self, tx_id: TxId) -> Result<Vec<String>, TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        Ok(TransactionTagSql::index_by_tx_id(tx_id, &conn)?
+            .into_iter()
+            .map(|t| t.tag)
+            .collect())
+    }
+
+    fn fetch_all_transaction_tags(&self) -> Result<HashMap<TxId, Vec<String>>, TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        let mut tags: HashMap<TxId, Vec<String>> = HashMap::new();
+        for t in TransactionTagSql::index(&conn)? {
+            tags.entry(TxId::from(t.tx_id as u64)).or_default().push(t.tag);
+        }
+        Ok(tags)
+    }
+
+    fn fetch_tagged_transaction_ids(&self, tag: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        Ok(TransactionTagSql::index_by_tag(tag, &conn)?
+            .into_iter()
+            .map(|t| TxId::from(t.tx_id as u64))
+            .collect())
+    }
+
+    fn save_transaction_attachment(&self, attachment: TransactionAttachment) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        let mut attachment_sql = TransactionAttachmentSql::from(attachment);
+        self.encrypt_if_necessary(&mut attachment_sql)?;
+        attachment_sql.commit(&conn)
+    }
+
+    fn fetch_transaction_attachments(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Vec<TransactionAttachment>, TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        TransactionAttachmentSql::index_by_tx_id(tx_id, &conn)?
+            .into_iter()
+            .map(|mut attachment_sql| {
+                self.decrypt_if_necessary(&mut attachment_sql)?;
+                Ok(TransactionAttachment::from(attachment_sql))
+            })
+            .collect()
+    }
+
+    fn remove_transaction_attachment(&self, tx_id: TxId, name: &str) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.get_pooled_connection()?;
+        TransactionAttachmentSql::delete(tx_id, name, &conn)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -1350,6 +1693,7 @@ struct InboundTransactionSql {
     direct_send_success: i32,
     send_count: i32,
     last_send_timestamp: Option<NaiveDateTime>,
+    expires_at: Option<NaiveDateTime>,
 }
 
 impl InboundTransactionSql {
@@ -1500,6 +1844,7 @@ impl TryFrom<InboundTransaction> for InboundTransactionSql {
             direct_send_success: i32::from(i.direct_send_success),
             send_count: i.send_count as i32,
             last_send_timestamp: i.last_send_timestamp,
+            expires_at: i.expires_at,
         })
     }
 }
@@ -1520,6 +1865,7 @@ impl TryFrom<InboundTransactionSql> for InboundTransaction {
             direct_send_success: i.direct_send_success != 0,
             send_count: i.send_count as u32,
             last_send_timestamp: i.last_send_timestamp,
+            expires_at: i.expires_at,
         })
     }
 }
@@ -1549,6 +1895,7 @@ struct OutboundTransactionSql {
     direct_send_success: i32,
     send_count: i32,
     last_send_timestamp: Option<NaiveDateTime>,
+    expires_at: Option<NaiveDateTime>,
 }
 
 impl OutboundTransactionSql {
@@ -1689,6 +2036,7 @@ impl TryFrom<OutboundTransaction> for OutboundTransactionSql {
             direct_send_success: i32::from(o.direct_send_success),
             send_count: o.send_count as i32,
             last_send_timestamp: o.last_send_timestamp,
+            expires_at: o.expires_at,
         })
     }
 }
@@ -1711,6 +2059,7 @@ impl TryFrom<OutboundTransactionSql> for OutboundTransaction {
             direct_send_success: o.direct_send_success != 0,
             send_count: o.send_count as u32,
             last_send_timestamp: o.last_send_timestamp,
+            expires_at: o.expires_at,
         })
     }
 }
@@ -1749,6 +2098,7 @@ struct CompletedTransactionSql {
     mined_timestamp: Option<NaiveDateTime>,
     transaction_signature_nonce: Vec<u8>,
     transaction_signature_key: Vec<u8>,
+    is_decoy: i32,
 }
 
 impl CompletedTransactionSql {
@@ -1884,6 +2234,19 @@ impl CompletedTransactionSql {
         Ok(())
     }
 
+    pub fn mark_double_spent(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        self.update(
+            UpdateCompletedTransactionSql {
+                cancelled: Some(Some(TxCancellationReason::DoubleSpend as i32)),
+                status: Some(TransactionStatus::DoubleSpent as i32),
+                ..Default::default()
+            },
+            conn,
+        )?;
+
+        Ok(())
+    }
+
     pub fn abandon_coinbase(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
         if self.coinbase_block_height.is_none() {
             return Err(TransactionStorageError::NotCoinbase);
@@ -1905,7 +2268,11 @@ impl CompletedTransactionSql {
             Some(TransactionStatus::Coinbase as i32)
         } else if self.status == TransactionStatus::FauxConfirmed as i32 {
             Some(TransactionStatus::FauxUnconfirmed as i32)
-        } else if self.status == TransactionStatus::Broadcast as i32 {
+        } else if self.status == TransactionStatus::Broadcast as i32 ||
+            self.status == TransactionStatus::MinedUnconfirmed as i32 ||
+            self.status == TransactionStatus::MinedConfirmed as i32
+        {
+            // A transaction that was mined has been broadcast, and will be rebroadcast if it is no longer mined
             Some(TransactionStatus::Broadcast as i32)
         } else {
             Some(TransactionStatus::Completed as i32)
@@ -2044,6 +2411,7 @@ impl TryFrom<CompletedTransaction> for CompletedTransactionSql {
             mined_timestamp: c.mined_timestamp,
             transaction_signature_nonce: c.transaction_signature.get_public_nonce().to_vec(),
             transaction_signature_key: c.transaction_signature.get_signature().to_vec(),
+            is_decoy: i32::from(c.is_decoy),
         })
     }
 }
@@ -2101,6 +2469,7 @@ impl TryFrom<CompletedTransactionSql> for CompletedTransaction {
             mined_height: c.mined_height.map(|ic| ic as u64),
             mined_in_block,
             mined_timestamp: c.mined_timestamp,
+            is_decoy: c.is_decoy != 0,
         })
     }
 }
@@ -2128,6 +2497,7 @@ pub struct UnconfirmedTransactionInfo {
     pub tx_id: TxId,
     pub signature: Signature,
     pub status: TransactionStatus,
+    pub amount: MicroTari,
     pub coinbase_block_height: Option<u64>,
 }
 
@@ -2152,6 +2522,7 @@ impl TryFrom<UnconfirmedTransactionInfoSql> for UnconfirmedTransactionInfo {
                 PrivateKey::from_vec(&i.transaction_signature_key)?,
             ),
             status: TransactionStatus::try_from(i.status)?,
+            amount: MicroTari::from(i.amount as u64),
             coinbase_block_height: i.coinbase_block_height.map(|b| b as u64),
         })
     }
@@ -2163,6 +2534,7 @@ pub struct UnconfirmedTransactionInfoSql {
     pub status: i32,
     pub transaction_signature_nonce: Vec<u8>,
     pub transaction_signature_key: Vec<u8>,
+    pub amount: i64,
     pub coinbase_block_height: Option<i64>,
 }
 
@@ -2178,6 +2550,7 @@ impl UnconfirmedTransactionInfoSql {
                 completed_transactions::status,
                 completed_transactions::transaction_signature_nonce,
                 completed_transactions::transaction_signature_key,
+                completed_transactions::amount,
                 completed_transactions::coinbase_block_height,
             ))
             .filter(
@@ -2198,23 +2571,613 @@ impl UnconfirmedTransactionInfoSql {
     }
 }
 
-#[cfg(test)]
-mod test {
-    use std::{convert::TryFrom, mem::size_of, time::Duration};
+#[derive(Clone, QueryableByName)]
+struct CounterpartyStatsSql {
+    #[sql_type = "diesel::sql_types::Binary"]
+    counterparty: Vec<u8>,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    total_sent: i64,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    total_received: i64,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    fees_paid: i64,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    transaction_count: i64,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Timestamp>"]
+    last_payment: Option<NaiveDateTime>,
+}
 
-    use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
-    use chrono::Utc;
-    use diesel::{Connection, SqliteConnection};
-    use rand::{rngs::OsRng, RngCore};
-    use tari_common_sqlite::sqlite_connection_pool::SqliteConnectionPool;
-    use tari_common_types::{
-        transaction::{TransactionDirection, TransactionStatus, TxId},
-        types::{PrivateKey, PublicKey, Signature},
-    };
-    use tari_core::{
-        covenants::Covenant,
-        transactions::{
-            tari_amount::MicroTari,
+impl CounterpartyStatsSql {
+    /// Sums the completed transactions per counterparty. Cancelled, coinbase and decoy transactions are left out, and
+    /// the counterparty of a transaction is its destination if it was sent and its source otherwise.
+    pub fn fetch(
+        counterparty: Option<&CommsPublicKey>,
+        since: Option<NaiveDateTime>,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<CounterpartyStatsSql>, TransactionStorageError> {
+        let counterparty = counterparty.map(|pk| pk.to_vec());
+        let query = sql_query(
+            "SELECT counterparty, \
+             coalesce(sum(CASE WHEN direction = ? THEN amount ELSE 0 END), 0) AS total_sent, \
+             coalesce(sum(CASE WHEN direction = ? THEN amount ELSE 0 END), 0) AS total_received, \
+             coalesce(sum(CASE WHEN direction = ? THEN fee ELSE 0 END), 0) AS fees_paid, \
+             count(*) AS transaction_count, \
+             max(timestamp) AS last_payment \
+             FROM (SELECT CASE WHEN direction = ? THEN destination_public_key ELSE source_public_key END \
+             AS counterparty, direction, amount, fee, timestamp \
+             FROM completed_transactions \
+             WHERE cancelled IS NULL AND is_decoy = 0 AND status != ? AND (direction = ? OR direction = ?) \
+             AND (? IS NULL OR timestamp >= ?)) \
+             WHERE ? IS NULL OR counterparty = ? \
+             GROUP BY counterparty \
+             ORDER BY total_sent + total_received DESC",
+        )
+        // sums
+        .bind::<diesel::sql_types::Integer, _>(TransactionDirection::Outbound as i32)
+        .bind::<diesel::sql_types::Integer, _>(TransactionDirection::Inbound as i32)
+        .bind::<diesel::sql_types::Integer, _>(TransactionDirection::Outbound as i32)
+        // counterparty
+        .bind::<diesel::sql_types::Integer, _>(TransactionDirection::Outbound as i32)
+        // filters
+        .bind::<diesel::sql_types::Integer, _>(TransactionStatus::Coinbase as i32)
+        .bind::<diesel::sql_types::Integer, _>(TransactionDirection::Inbound as i32)
+        .bind::<diesel::sql_types::Integer, _>(TransactionDirection::Outbound as i32)
+        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Timestamp>, _>(since)
+        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Timestamp>, _>(since)
+        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Binary>, _>(counterparty.clone())
+        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Binary>, _>(counterparty);
+        Ok(query.load::<CounterpartyStatsSql>(conn)?)
+    }
+}
+
+impl TryFrom<CounterpartyStatsSql> for CounterpartyStats {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: CounterpartyStatsSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            counterparty: CommsPublicKey::from_vec(&s.counterparty)?,
+            total_sent: MicroTari::from(s.total_sent as u64),
+            total_received: MicroTari::from(s.total_received as u64),
+            fees_paid: MicroTari::from(s.fees_paid as u64),
+            transaction_count: s.transaction_count as u64,
+            last_payment: s.last_payment,
+        })
+    }
+}
+
+/// A structure to represent a Sql compatible version of the TransactionProtocolState struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_protocol_states"]
+struct TransactionProtocolStateSql {
+    tx_id: i64,
+    stage: i32,
+    counterparty_public_key: Vec<u8>,
+    protocol_state: String,
+    last_updated: NaiveDateTime,
+}
+
+impl TransactionProtocolStateSql {
+    /// Insert the protocol state, replacing any state previously saved for this transaction
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(transaction_protocol_states::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<TransactionProtocolStateSql>, TransactionStorageError> {
+        Ok(transaction_protocol_states::table.load::<TransactionProtocolStateSql>(conn)?)
+    }
+
+    pub fn delete(tx_id: TxId, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            transaction_protocol_states::table.filter(transaction_protocol_states::tx_id.eq(tx_id.as_u64() as i64)),
+        )
+        .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(transaction_protocol_states::table.filter(transaction_protocol_states::tx_id.eq(&self.tx_id)))
+            .set(transaction_protocol_states::protocol_state.eq(&self.protocol_state))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for TransactionProtocolStateSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::TRANSACTION_PROTOCOL_STATE,
+            self.tx_id.to_le_bytes().as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(&mut self, cipher: &XChaCha20Poly1305) -> Result<(), String> {
+        self.protocol_state = encrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("protocol_state"),
+            self.protocol_state.as_bytes().to_vec(),
+        )?
+        .to_hex();
+
+        Ok(())
+    }
+
+    fn decrypt(&mut self, cipher: &XChaCha20Poly1305) -> Result<(), String> {
+        let decrypted_state = decrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("protocol_state"),
+            from_hex(self.protocol_state.as_str()).map_err(|e| e.to_string())?,
+        )?;
+
+        self.protocol_state = from_utf8(decrypted_state.as_slice())
+            .map_err(|e| e.to_string())?
+            .to_string();
+
+        Ok(())
+    }
+}
+
+impl TryFrom<TransactionProtocolState> for TransactionProtocolStateSql {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: TransactionProtocolState) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: s.tx_id.as_u64() as i64,
+            stage: i32::from(s.stage),
+            counterparty_public_key: s.counterparty_public_key.to_vec(),
+            protocol_state: serde_json::to_string(&s.snapshot)?,
+            last_updated: s.last_updated,
+        })
+    }
+}
+
+impl TryFrom<TransactionProtocolStateSql> for TransactionProtocolState {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: TransactionProtocolStateSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: (s.tx_id as u64).into(),
+            stage: s.stage.try_into()?,
+            counterparty_public_key: PublicKey::from_vec(&s.counterparty_public_key)
+                .map_err(TransactionKeyError::Counterparty)?,
+            snapshot: serde_json::from_str(&s.protocol_state)?,
+            last_updated: s.last_updated,
+        })
+    }
+}
+
+/// A user defined tag on a transaction. Tags are stored in plain text so that transactions can be queried by tag.
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_tags"]
+struct TransactionTagSql {
+    tx_id: i64,
+    tag: String,
+}
+
+impl TransactionTagSql {
+    pub fn new(tx_id: TxId, tag: &str) -> Self {
+        Self {
+            tx_id: tx_id.as_u64() as i64,
+            tag: tag.to_string(),
+        }
+    }
+
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(transaction_tags::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn delete(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            transaction_tags::table
+                .filter(transaction_tags::tx_id.eq(self.tx_id))
+                .filter(transaction_tags::tag.eq(&self.tag)),
+        )
+        .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<TransactionTagSql>, TransactionStorageError> {
+        Ok(transaction_tags::table
+            .order_by((transaction_tags::tx_id, transaction_tags::tag))
+            .load::<TransactionTagSql>(conn)?)
+    }
+
+    pub fn index_by_tx_id(
+        tx_id: TxId,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<TransactionTagSql>, TransactionStorageError> {
+        Ok(transaction_tags::table
+            .filter(transaction_tags::tx_id.eq(tx_id.as_u64() as i64))
+            .order_by(transaction_tags::tag)
+            .load::<TransactionTagSql>(conn)?)
+    }
+
+    pub fn index_by_tag(tag: &str, conn: &SqliteConnection) -> Result<Vec<TransactionTagSql>, TransactionStorageError> {
+        Ok(transaction_tags::table
+            .filter(transaction_tags::tag.eq(tag))
+            .order_by(transaction_tags::tx_id)
+            .load::<TransactionTagSql>(conn)?)
+    }
+}
+
+/// A structure to represent a Sql compatible version of the TransactionAttachment struct. The name is stored in plain
+/// text so that attachments can be replaced and removed by name.
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_attachments"]
+struct TransactionAttachmentSql {
+    tx_id: i64,
+    name: String,
+    content_type: String,
+    data: Vec<u8>,
+    timestamp: NaiveDateTime,
+}
+
+impl TransactionAttachmentSql {
+    /// Insert the attachment, replacing the attachment of the transaction with the same name
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(transaction_attachments::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<TransactionAttachmentSql>, TransactionStorageError> {
+        Ok(transaction_attachments::table.load::<TransactionAttachmentSql>(conn)?)
+    }
+
+    pub fn index_by_tx_id(
+        tx_id: TxId,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<TransactionAttachmentSql>, TransactionStorageError> {
+        Ok(transaction_attachments::table
+            .filter(transaction_attachments::tx_id.eq(tx_id.as_u64() as i64))
+            .order_by(transaction_attachments::name)
+            .load::<TransactionAttachmentSql>(conn)?)
+    }
+
+    pub fn delete(tx_id: TxId, name: &str, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            transaction_attachments::table
+                .filter(transaction_attachments::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(transaction_attachments::name.eq(name)),
+        )
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            transaction_attachments::table
+                .filter(transaction_attachments::tx_id.eq(self.tx_id))
+                .filter(transaction_attachments::name.eq(&self.name)),
+        )
+        .set(transaction_attachments::data.eq(&self.data))
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for TransactionAttachmentSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::TRANSACTION_ATTACHMENT,
+            self.tx_id.to_le_bytes().as_slice(),
+            self.name.as_bytes(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(&mut self, cipher: &XChaCha20Poly1305) -> Result<(), String> {
+        self.data = encrypt_bytes_integral_nonce(cipher, self.domain("data"), self.data.clone())?;
+
+        Ok(())
+    }
+
+    fn decrypt(&mut self, cipher: &XChaCha20Poly1305) -> Result<(), String> {
+        self.data = decrypt_bytes_integral_nonce(cipher, self.domain("data"), self.data.clone())?;
+
+        Ok(())
+    }
+}
+
+impl From<TransactionAttachment> for TransactionAttachmentSql {
+    fn from(a: TransactionAttachment) -> Self {
+        Self {
+            tx_id: a.tx_id.as_u64() as i64,
+            name: a.name,
+            content_type: a.content_type,
+            data: a.data,
+            timestamp: a.timestamp,
+        }
+    }
+}
+
+impl From<TransactionAttachmentSql> for TransactionAttachment {
+    fn from(a: TransactionAttachmentSql) -> Self {
+        Self {
+            tx_id: TxId::from(a.tx_id as u64),
+            name: a.name,
+            content_type: a.content_type,
+            data: a.data,
+            timestamp: a.timestamp,
+        }
+    }
+}
+
+/// A structure to represent a Sql compatible version of the SenderSigningSession struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "aggregated_sender_sessions"]
+struct AggregatedSenderSessionSql {
+    public_key: Vec<u8>,
+    session: String,
+    last_updated: NaiveDateTime,
+}
+
+impl AggregatedSenderSessionSql {
+    /// Insert the session, replacing the state previously saved for it
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(aggregated_sender_sessions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<AggregatedSenderSessionSql>, TransactionStorageError> {
+        Ok(aggregated_sender_sessions::table.load::<AggregatedSenderSessionSql>(conn)?)
+    }
+
+    pub fn find(
+        public_key: &PublicKey,
+        conn: &SqliteConnection,
+    ) -> Result<AggregatedSenderSessionSql, TransactionStorageError> {
+        Ok(aggregated_sender_sessions::table
+            .filter(aggregated_sender_sessions::public_key.eq(public_key.as_bytes()))
+            .first::<AggregatedSenderSessionSql>(conn)?)
+    }
+
+    pub fn delete(public_key: &PublicKey, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            aggregated_sender_sessions::table.filter(aggregated_sender_sessions::public_key.eq(public_key.as_bytes())),
+        )
+        .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            aggregated_sender_sessions::table.filter(aggregated_sender_sessions::public_key.eq(&self.public_key)),
+        )
+        .set(aggregated_sender_sessions::session.eq(&self.session))
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for AggregatedSenderSessionSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::AGGREGATED_SENDER_SESSION,
+            self.public_key.as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(&mut self, cipher: &XChaCha20Poly1305) -> Result<(), String> {
+        self.session =
+            encrypt_bytes_integral_nonce(cipher, self.domain("session"), self.session.as_bytes().to_vec())?.to_hex();
+
+        Ok(())
+    }
+
+    fn decrypt(&mut self, cipher: &XChaCha20Poly1305) -> Result<(), String> {
+        let decrypted_session = decrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("session"),
+            from_hex(self.session.as_str()).map_err(|e| e.to_string())?,
+        )?;
+
+        self.session = from_utf8(decrypted_session.as_slice())
+            .map_err(|e| e.to_string())?
+            .to_string();
+
+        Ok(())
+    }
+}
+
+impl TryFrom<SenderSigningSession> for AggregatedSenderSessionSql {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: SenderSigningSession) -> Result<Self, Self::Error> {
+        Ok(Self {
+            public_key: s.public_key().to_vec(),
+            session: serde_json::to_string(&s)?,
+            last_updated: Utc::now().naive_utc(),
+        })
+    }
+}
+
+impl TryFrom<AggregatedSenderSessionSql> for SenderSigningSession {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: AggregatedSenderSessionSql) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(&s.session)?)
+    }
+}
+
+/// A structure to represent a Sql compatible version of the IdempotencyKeyRecord struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_idempotency_keys"]
+struct IdempotencyKeySql {
+    idempotency_key: String,
+    tx_id: i64,
+    destination_public_key: Vec<u8>,
+    amount: i64,
+    timestamp: NaiveDateTime,
+}
+
+impl IdempotencyKeySql {
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(transaction_idempotency_keys::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn find(idempotency_key: &str, conn: &SqliteConnection) -> Result<IdempotencyKeySql, TransactionStorageError> {
+        Ok(transaction_idempotency_keys::table
+            .filter(transaction_idempotency_keys::idempotency_key.eq(idempotency_key))
+            .first::<IdempotencyKeySql>(conn)?)
+    }
+
+    pub fn delete(idempotency_key: &str, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            transaction_idempotency_keys::table
+                .filter(transaction_idempotency_keys::idempotency_key.eq(idempotency_key)),
+        )
+        .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl From<IdempotencyKeyRecord> for IdempotencyKeySql {
+    fn from(r: IdempotencyKeyRecord) -> Self {
+        Self {
+            idempotency_key: r.idempotency_key,
+            tx_id: r.tx_id.as_u64() as i64,
+            destination_public_key: r.destination_public_key.to_vec(),
+            amount: u64::from(r.amount) as i64,
+            timestamp: r.timestamp,
+        }
+    }
+}
+
+impl TryFrom<IdempotencyKeySql> for IdempotencyKeyRecord {
+    type Error = TransactionStorageError;
+
+    fn try_from(r: IdempotencyKeySql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            idempotency_key: r.idempotency_key,
+            tx_id: (r.tx_id as u64).into(),
+            destination_public_key: PublicKey::from_vec(&r.destination_public_key)
+                .map_err(TransactionKeyError::Destination)?,
+            amount: MicroTari::from(r.amount as u64),
+            timestamp: r.timestamp,
+        })
+    }
+}
+
+/// A structure to represent a Sql compatible version of the PendingApprovalTransaction struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "pending_approval_transactions"]
+struct PendingApprovalTransactionSql {
+    tx_id: i64,
+    payment_type: i32,
+    destination_public_key: Vec<u8>,
+    amount: i64,
+    fee_per_gram: i64,
+    output_features: String,
+    message: String,
+    timestamp: NaiveDateTime,
+    expiry_timestamp: NaiveDateTime,
+    memo: Option<String>,
+    expires_at: Option<NaiveDateTime>,
+}
+
+impl PendingApprovalTransactionSql {
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(pending_approval_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<PendingApprovalTransactionSql>, TransactionStorageError> {
+        Ok(pending_approval_transactions::table.load::<PendingApprovalTransactionSql>(conn)?)
+    }
+
+    pub fn delete(tx_id: TxId, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            pending_approval_transactions::table.filter(pending_approval_transactions::tx_id.eq(tx_id.as_u64() as i64)),
+        )
+        .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl TryFrom<PendingApprovalTransaction> for PendingApprovalTransactionSql {
+    type Error = TransactionStorageError;
+
+    fn try_from(t: PendingApprovalTransaction) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: t.tx_id.as_u64() as i64,
+            payment_type: i32::from(t.payment_type),
+            destination_public_key: t.destination_public_key.to_vec(),
+            amount: u64::from(t.amount) as i64,
+            fee_per_gram: u64::from(t.fee_per_gram) as i64,
+            output_features: serde_json::to_string(&t.output_features)?,
+            message: t.message,
+            timestamp: t.timestamp,
+            expiry_timestamp: t.expiry_timestamp,
+            memo: t.memo,
+            expires_at: t.expires_at,
+        })
+    }
+}
+
+impl TryFrom<PendingApprovalTransactionSql> for PendingApprovalTransaction {
+    type Error = TransactionStorageError;
+
+    fn try_from(t: PendingApprovalTransactionSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: (t.tx_id as u64).into(),
+            payment_type: t.payment_type.try_into()?,
+            destination_public_key: PublicKey::from_vec(&t.destination_public_key)
+                .map_err(TransactionKeyError::Destination)?,
+            amount: MicroTari::from(t.amount as u64),
+            fee_per_gram: MicroTari::from(t.fee_per_gram as u64),
+            output_features: serde_json::from_str(&t.output_features)?,
+            message: t.message,
+            memo: t.memo,
+            timestamp: t.timestamp,
+            expiry_timestamp: t.expiry_timestamp,
+            expires_at: t.expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{convert::TryFrom, mem::size_of, time::Duration};
+
+    use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+    use chrono::Utc;
+    use diesel::{Connection, SqliteConnection};
+    use rand::{rngs::OsRng, RngCore};
+    use tari_common_sqlite::sqlite_connection_pool::SqliteConnectionPool;
+    use tari_common_types::{
+        transaction::{TransactionDirection, TransactionStatus, TxId},
+        types::{PrivateKey, PublicKey, Signature},
+    };
+    use tari_core::{
+        covenants::Covenant,
+        transactions::{
+            tari_amount::MicroTari,
             test_helpers::{create_unblinded_output, TestParams},
             transaction_components::{OutputFeatures, Transaction},
             transaction_protocol::sender::TransactionSenderMessage,
@@ -2231,15 +3194,24 @@ mod test {
     use crate::{
         storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
         test_utils::create_consensus_constants,
-        transaction_service::storage::{
-            database::{DbKey, TransactionBackend},
-            models::{CompletedTransaction, InboundTransaction, OutboundTransaction, TxCancellationReason},
-            sqlite_db::{
-                CompletedTransactionSql,
-                InboundTransactionSenderInfo,
-                InboundTransactionSql,
-                OutboundTransactionSql,
-                TransactionServiceSqliteDatabase,
+        transaction_service::{
+            error::TransactionStorageError,
+            storage::{
+                database::{DbKey, TransactionBackend, TransactionStatusUpdate},
+                models::{
+                    CompletedTransaction,
+                    InboundTransaction,
+                    OutboundTransaction,
+                    TransactionAttachment,
+                    TxCancellationReason,
+                },
+                sqlite_db::{
+                    CompletedTransactionSql,
+                    InboundTransactionSenderInfo,
+                    InboundTransactionSql,
+                    OutboundTransactionSql,
+                    TransactionServiceSqliteDatabase,
+                },
             },
         },
         util::encryption::Encryptable,
@@ -2311,6 +3283,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         };
 
         let outbound_tx2 = OutboundTransactionSql::try_from(OutboundTransaction {
@@ -2326,6 +3299,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         })
         .unwrap();
 
@@ -2366,6 +3340,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         };
         let inbound_tx2 = InboundTransaction {
             tx_id: 3u64.into(),
@@ -2379,6 +3354,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         };
 
         InboundTransactionSql::try_from(inbound_tx1.clone())
@@ -2429,6 +3405,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         };
         let completed_tx2 = CompletedTransaction {
             tx_id: 3u64.into(),
@@ -2450,6 +3427,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         };
 
         CompletedTransactionSql::try_from(completed_tx1.clone())
@@ -2581,6 +3559,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         };
 
         let coinbase_tx2 = CompletedTransaction {
@@ -2603,6 +3582,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         };
 
         let coinbase_tx3 = CompletedTransaction {
@@ -2625,6 +3605,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         };
 
         CompletedTransactionSql::try_from(coinbase_tx1)
@@ -2679,6 +3660,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         };
         let mut inbound_tx_sql = InboundTransactionSql::try_from(inbound_tx.clone()).unwrap();
         inbound_tx_sql.commit(&conn).unwrap();
@@ -2702,6 +3684,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         };
 
         let mut outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx.clone()).unwrap();
@@ -2739,6 +3722,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         };
 
         let mut completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone()).unwrap();
@@ -2783,6 +3767,7 @@ mod test {
                 direct_send_success: false,
                 send_count: 0,
                 last_send_timestamp: None,
+                expires_at: None,
             };
             let inbound_tx_sql = InboundTransactionSql::try_from(inbound_tx).unwrap();
             inbound_tx_sql.commit(&conn).unwrap();
@@ -2800,6 +3785,7 @@ mod test {
                 direct_send_success: false,
                 send_count: 0,
                 last_send_timestamp: None,
+                expires_at: None,
             };
             let outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx).unwrap();
             outbound_tx_sql.commit(&conn).unwrap();
@@ -2830,6 +3816,7 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                is_decoy: false,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx).unwrap();
             completed_tx_sql.commit(&conn).unwrap();
@@ -2958,6 +3945,7 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                is_decoy: false,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone()).unwrap();
             completed_tx_sql.commit(&conn).unwrap();
@@ -2989,4 +3977,303 @@ mod test {
         assert_eq!(info_list.len(), 941);
         assert_eq!(info_list, info_list_reference);
     }
+
+    #[test]
+    fn test_counterparty_stats() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        {
+            let conn = pool
+                .get_pooled_connection()
+                .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+        }
+        let connection = WalletDbConnection::new(pool, None);
+        let db = TransactionServiceSqliteDatabase::new(connection.clone(), None);
+
+        let own_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let alice = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let bob = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let now = Utc::now().naive_utc();
+        let last_week = now - chrono::Duration::days(7);
+
+        let new_tx = |tx_id: u64, counterparty: &PublicKey, direction: TransactionDirection, amount: u64| {
+            let (source_public_key, destination_public_key) = match direction {
+                TransactionDirection::Outbound => (own_key.clone(), counterparty.clone()),
+                _ => (counterparty.clone(), own_key.clone()),
+            };
+            CompletedTransaction {
+                tx_id: TxId::from(tx_id),
+                source_public_key,
+                destination_public_key,
+                amount: MicroTari::from(amount),
+                fee: MicroTari::from(10),
+                transaction: Transaction::new(vec![], vec![], vec![], PrivateKey::default(), PrivateKey::default()),
+                status: TransactionStatus::MinedConfirmed,
+                message: "Yo!".to_string(),
+                timestamp: now,
+                cancelled: None,
+                direction,
+                coinbase_block_height: None,
+                send_count: 0,
+                last_send_timestamp: None,
+                transaction_signature: Signature::default(),
+                confirmations: None,
+                mined_height: None,
+                mined_in_block: None,
+                mined_timestamp: None,
+                is_decoy: false,
+            }
+        };
+        let mut old_tx = new_tx(1, &alice, TransactionDirection::Outbound, 500);
+        old_tx.timestamp = last_week;
+        let mut cancelled_tx = new_tx(4, &bob, TransactionDirection::Outbound, 5000);
+        cancelled_tx.cancelled = Some(TxCancellationReason::UserCancelled);
+        let mut decoy_tx = new_tx(5, &bob, TransactionDirection::Outbound, 700);
+        decoy_tx.is_decoy = true;
+        let mut coinbase_tx = new_tx(6, &own_key, TransactionDirection::Inbound, 9000);
+        coinbase_tx.status = TransactionStatus::Coinbase;
+        let transactions = vec![
+            new_tx(0, &alice, TransactionDirection::Outbound, 1000),
+            old_tx,
+            new_tx(2, &alice, TransactionDirection::Inbound, 300),
+            new_tx(3, &bob, TransactionDirection::Inbound, 200),
+            cancelled_tx,
+            decoy_tx,
+            coinbase_tx,
+        ];
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            for tx in transactions {
+                CompletedTransactionSql::try_from(tx).unwrap().commit(&conn).unwrap();
+            }
+        }
+
+        let stats = db.fetch_counterparty_stats(None, None).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].counterparty, alice);
+        assert_eq!(stats[0].total_sent, MicroTari::from(1500));
+        assert_eq!(stats[0].total_received, MicroTari::from(300));
+        assert_eq!(stats[0].fees_paid, MicroTari::from(20));
+        assert_eq!(stats[0].transaction_count, 3);
+        assert_eq!(stats[0].last_payment, Some(now));
+        assert_eq!(stats[1].counterparty, bob);
+        assert_eq!(stats[1].total_sent, MicroTari::from(0));
+        assert_eq!(stats[1].total_received, MicroTari::from(200));
+        assert_eq!(stats[1].fees_paid, MicroTari::from(0));
+        assert_eq!(stats[1].transaction_count, 1);
+
+        let stats = db.fetch_counterparty_stats(Some(&alice), None).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].transaction_count, 3);
+
+        let stats = db
+            .fetch_counterparty_stats(Some(&alice), Some(now - chrono::Duration::days(1)))
+            .unwrap();
+        assert_eq!(stats[0].total_sent, MicroTari::from(1000));
+        assert_eq!(stats[0].transaction_count, 2);
+
+        let stranger = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        assert!(db.fetch_counterparty_stats(Some(&stranger), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_transaction_tags() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        {
+            let conn = pool
+                .get_pooled_connection()
+                .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+        }
+        let db = TransactionServiceSqliteDatabase::new(WalletDbConnection::new(pool, None), None);
+
+        db.add_transaction_tag(TxId::from(1u64), "rent").unwrap();
+        db.add_transaction_tag(TxId::from(1u64), "housing").unwrap();
+        db.add_transaction_tag(TxId::from(1u64), "rent").unwrap();
+        db.add_transaction_tag(TxId::from(2u64), "rent").unwrap();
+        db.add_transaction_tag(TxId::from(3u64), "groceries").unwrap();
+
+        assert_eq!(db.fetch_transaction_tags(TxId::from(1u64)).unwrap(), vec![
+            "housing".to_string(),
+            "rent".to_string()
+        ]);
+        assert_eq!(db.fetch_tagged_transaction_ids("rent").unwrap(), vec![
+            TxId::from(1u64),
+            TxId::from(2u64)
+        ]);
+        assert!(db.fetch_tagged_transaction_ids("travel").unwrap().is_empty());
+        assert_eq!(db.fetch_all_transaction_tags().unwrap().len(), 3);
+
+        db.remove_transaction_tag(TxId::from(1u64), "rent").unwrap();
+        db.remove_transaction_tag(TxId::from(3u64), "groceries").unwrap();
+        assert_eq!(db.fetch_transaction_tags(TxId::from(1u64)).unwrap(), vec![
+            "housing".to_string()
+        ]);
+        assert_eq!(db.fetch_tagged_transaction_ids("rent").unwrap(), vec![TxId::from(2u64)]);
+        let all_tags = db.fetch_all_transaction_tags().unwrap();
+        assert_eq!(all_tags.len(), 2);
+        assert!(!all_tags.contains_key(&TxId::from(3u64)));
+    }
+
+    #[test]
+    fn test_transaction_attachments() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        {
+            let conn = pool
+                .get_pooled_connection()
+                .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+        }
+        let connection = WalletDbConnection::new(pool, None);
+        let db = TransactionServiceSqliteDatabase::new(connection.clone(), None);
+
+        let tx_id = TxId::from(1u64);
+        let attachment = |name: &str, content_type: &str, data: Vec<u8>| {
+            TransactionAttachment::new(tx_id, name.to_string(), content_type.to_string(), data)
+        };
+        let receipt = attachment("receipt.png", "image/png", vec![1; 64]);
+        let invoice = attachment("invoice.pdf", "application/pdf", vec![2; 128]);
+        db.save_transaction_attachment(receipt.clone()).unwrap();
+        db.save_transaction_attachment(invoice).unwrap();
+        let invoice = attachment("invoice.pdf", "application/pdf", vec![3; 32]);
+        db.save_transaction_attachment(invoice.clone()).unwrap();
+        assert_eq!(db.fetch_transaction_attachments(tx_id).unwrap(), vec![
+            invoice.clone(),
+            receipt.clone()
+        ]);
+        assert!(db.fetch_transaction_attachments(TxId::from(2u64)).unwrap().is_empty());
+
+        let mut key = [0u8; size_of::<Key>()];
+        OsRng.fill_bytes(&mut key);
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+        db.apply_encryption(cipher).unwrap();
+        assert_eq!(db.fetch_transaction_attachments(tx_id).unwrap(), vec![
+            invoice.clone(),
+            receipt.clone()
+        ]);
+        let unencrypted_db = TransactionServiceSqliteDatabase::new(connection, None);
+        let stored = unencrypted_db.fetch_transaction_attachments(tx_id).unwrap();
+        assert_ne!(stored[1].data, receipt.data);
+        db.remove_encryption().unwrap();
+        assert_eq!(unencrypted_db.fetch_transaction_attachments(tx_id).unwrap(), vec![
+            invoice,
+            receipt.clone()
+        ]);
+
+        db.remove_transaction_attachment(tx_id, "invoice.pdf").unwrap();
+        assert!(db.remove_transaction_attachment(tx_id, "invoice.pdf").is_err());
+        assert_eq!(db.fetch_transaction_attachments(tx_id).unwrap(), vec![receipt]);
+    }
+
+    #[test]
+    fn test_apply_status_updates() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let mut pool = SqliteConnectionPool::new(db_path.clone(), 1, true, true, Duration::from_secs(60));
+        pool.create_pool()
+            .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+        {
+            let conn = pool
+                .get_pooled_connection()
+                .unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+            embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+        }
+        let connection = WalletDbConnection::new(pool, None);
+        let db = TransactionServiceSqliteDatabase::new(connection.clone(), None);
+
+        let public_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        {
+            let conn = connection.get_pooled_connection().unwrap();
+            for tx_id in 1..=2u64 {
+                let tx = CompletedTransaction {
+                    tx_id: TxId::from(tx_id),
+                    source_public_key: public_key.clone(),
+                    destination_public_key: public_key.clone(),
+                    amount: MicroTari::from(100),
+                    fee: MicroTari::from(10),
+                    transaction: Transaction::new(vec![], vec![], vec![], PrivateKey::default(), PrivateKey::default()),
+                    status: TransactionStatus::Broadcast,
+                    message: "Yo!".to_string(),
+                    timestamp: Utc::now().naive_utc(),
+                    cancelled: None,
+                    direction: TransactionDirection::Inbound,
+                    coinbase_block_height: None,
+                    send_count: 0,
+                    last_send_timestamp: None,
+                    transaction_signature: Signature::default(),
+                    confirmations: None,
+                    mined_height: None,
+                    mined_in_block: None,
+                    mined_timestamp: None,
+                    is_decoy: false,
+                };
+                CompletedTransactionSql::try_from(tx).unwrap().commit(&conn).unwrap();
+            }
+        }
+        let find = |tx_id: u64| {
+            let conn = connection.get_pooled_connection().unwrap();
+            CompletedTransactionSql::find(TxId::from(tx_id), &conn).unwrap()
+        };
+        let mined = |tx_id: u64, is_confirmed: bool| TransactionStatusUpdate::Mined {
+            tx_id: TxId::from(tx_id),
+            mined_height: 10,
+            mined_in_block: [1u8; 32].into(),
+            mined_timestamp: 0,
+            num_confirmations: 3,
+            is_confirmed,
+            is_faux: false,
+        };
+
+        db.apply_status_updates(vec![mined(1, true), mined(2, false)]).unwrap();
+        assert_eq!(find(1).status, TransactionStatus::MinedConfirmed as i32);
+        assert_eq!(find(2).status, TransactionStatus::MinedUnconfirmed as i32);
+        assert_eq!(find(2).mined_height, Some(10));
+
+        // The unknown transaction fails the batch, so the first update is rolled back
+        let result = db.apply_status_updates(vec![
+            TransactionStatusUpdate::Unmined(TxId::from(1u64)),
+            TransactionStatusUpdate::DoubleSpent(TxId::from(3u64)),
+        ]);
+        assert!(matches!(
+            result,
+            Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(_)))
+        ));
+        assert_eq!(find(1).mined_height, Some(10));
+
+        db.apply_status_updates(vec![
+            TransactionStatusUpdate::Unmined(TxId::from(1u64)),
+            TransactionStatusUpdate::DoubleSpent(TxId::from(2u64)),
+        ])
+        .unwrap();
+        assert_eq!(find(1).mined_height, None);
+        assert_eq!(find(2).status, TransactionStatus::DoubleSpent as i32);
+    }
 }
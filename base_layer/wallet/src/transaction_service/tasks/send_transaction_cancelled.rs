@@ -19,40 +19,19 @@
 // SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::sync::Arc;
+
 use tari_common_types::transaction::TxId;
 use tari_comms::types::CommsPublicKey;
-use tari_comms_dht::{
-    domain_message::OutboundDomainMessage,
-    outbound::{OutboundEncryption, OutboundMessageRequester},
-};
-use tari_core::transactions::transaction_protocol::proto::protocol as proto;
-use tari_p2p::tari_message::TariMessageType;
 
-use crate::transaction_service::error::TransactionServiceError;
+use crate::transaction_service::{error::TransactionServiceError, messaging::TransactionMessagingBackend};
 
 pub async fn send_transaction_cancelled_message(
     tx_id: TxId,
     destination_public_key: CommsPublicKey,
-    mut outbound_message_service: OutboundMessageRequester,
+    messaging: Arc<dyn TransactionMessagingBackend>,
 ) -> Result<(), TransactionServiceError> {
-    let proto_message = proto::TransactionCancelledMessage { tx_id: tx_id.into() };
-
-    // Send both direct and SAF we are not going to monitor the progress on these messages for potential resend as
-    // they are just courtesy messages
-    let _send_message_response = outbound_message_service
-        .send_direct(
-            destination_public_key.clone(),
-            OutboundDomainMessage::new(&TariMessageType::TransactionCancelled, proto_message.clone()),
-        )
-        .await?;
-
-    let _message_send_state = outbound_message_service
-        .closest_broadcast(
-            destination_public_key.clone(),
-            OutboundEncryption::encrypt_for(destination_public_key),
-            vec![],
-            OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message),
-        )
-        .await?;
-    Ok(())
+    messaging
+        .send_transaction_cancelled(tx_id, destination_public_key)
+        .await
 }
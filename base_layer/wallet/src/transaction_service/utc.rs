@@ -5,6 +5,9 @@ use std::{convert::TryFrom, time::Duration};
 
 use chrono::{NaiveDateTime, Utc};
 use thiserror::Error;
+
+use crate::util::clock::corrected_utc_now;
+
 /// The error happens when a duration is negative.
 #[derive(Debug, Error)]
 #[error("Diration is negative: {ms} ms")]
@@ -31,3 +34,16 @@ pub fn utc_duration_since(since: &NaiveDateTime) -> Result<Duration, NegativeDur
         Err(NegativeDurationError { ms })
     }
 }
+
+/// The time remaining until the expiry `until`, or `None` if it has passed. Expiries are corrected for the skew of the
+/// local clock.
+pub fn utc_duration_until(until: &NaiveDateTime) -> Option<Duration> {
+    let ms = until.timestamp_millis() - corrected_utc_now().timestamp_millis();
+    u64::try_from(ms).ok().filter(|ms| *ms > 0).map(Duration::from_millis)
+}
+
+/// The expiry `duration` from now, or `None` if that is out of range
+pub fn utc_after(duration: Duration) -> Option<NaiveDateTime> {
+    let duration = chrono::Duration::from_std(duration).ok()?;
+    corrected_utc_now().checked_add_signed(duration)
+}
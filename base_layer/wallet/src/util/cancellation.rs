@@ -0,0 +1,106 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::time::Instant;
+
+use tokio::time;
+
+use crate::util::watch::Watch;
+
+/// Lets the caller of a long running operation abort it, e.g. a user who presses cancel while a transaction is still
+/// being sent over a slow connection. Clones share the cancellation, so the caller keeps one and hands one to the
+/// operation. An optional deadline cancels the operation when it is reached.
+#[derive(Clone)]
+pub struct CancellationToken {
+    cancelled: Watch<bool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self {
+            cancelled: Watch::new(false),
+            deadline: None,
+        }
+    }
+
+    /// Returns a token that is also cancelled once `deadline` is reached
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns a token that shares the cancellation, but not the deadline, of this token
+    pub fn without_deadline(&self) -> Self {
+        Self {
+            cancelled: self.cancelled.clone(),
+            deadline: None,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.send(true);
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn is_past_deadline(&self) -> bool {
+        self.deadline.map_or(false, |deadline| Instant::now() >= deadline)
+    }
+
+    /// Returns true if the token was cancelled or its deadline was reached
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow() || self.is_past_deadline()
+    }
+
+    /// Waits until the token is cancelled or its deadline is reached
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.clone();
+        let wait_for_cancel = async move {
+            while !*cancelled.borrow() {
+                cancelled.changed().await;
+            }
+        };
+        match self.deadline {
+            Some(deadline) => {
+                let _result = time::timeout_at(deadline.into(), wait_for_cancel).await;
+            },
+            None => wait_for_cancel.await,
+        }
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_is_cancelled_by_a_clone() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        tokio::spawn(async move { clone.cancel() });
+        token.cancelled().await;
+        assert!(token.is_cancelled());
+        assert!(token.without_deadline().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn it_is_cancelled_at_the_deadline() {
+        let token = CancellationToken::new().with_deadline(Instant::now() + Duration::from_millis(10));
+        token.cancelled().await;
+        assert!(token.is_cancelled());
+        assert!(token.is_past_deadline());
+        assert!(!token.without_deadline().is_cancelled());
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The time that the wallet computes expiries with. A device clock that is off, e.g. on a phone whose time was set by
+//! hand, would otherwise make transactions expire too early or too late. The base node service estimates the skew of
+//! the local clock from the timestamps of the chain and sets the correction once the skew exceeds the configured
+//! threshold. The clock of the device is shared by all the wallets in the process, and so is the correction.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::{Duration, NaiveDateTime, Utc};
+
+static CLOCK_CORRECTION_MS: AtomicI64 = AtomicI64::new(0);
+
+/// The current UTC time, corrected for the skew of the local clock
+pub fn corrected_utc_now() -> NaiveDateTime {
+    Utc::now().naive_utc() + clock_correction()
+}
+
+/// What is added to the local time to correct it, i.e. how far the local clock is behind the network. This is zero
+/// unless a skew above the threshold was detected.
+pub fn clock_correction() -> Duration {
+    Duration::milliseconds(CLOCK_CORRECTION_MS.load(Ordering::Relaxed))
+}
+
+pub(crate) fn set_clock_correction(correction: Duration) {
+    CLOCK_CORRECTION_MS.store(correction.num_milliseconds(), Ordering::Relaxed);
+}
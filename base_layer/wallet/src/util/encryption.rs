@@ -39,6 +39,9 @@ pub trait Encryptable<C> {
     const INBOUND_TRANSACTION: &'static [u8] = b"INBOUND_TRANSACTION";
     const OUTBOUND_TRANSACTION: &'static [u8] = b"OUTBOUND_TRANSACTION";
     const COMPLETED_TRANSACTION: &'static [u8] = b"COMPLETED_TRANSACTION";
+    const TRANSACTION_PROTOCOL_STATE: &'static [u8] = b"TRANSACTION_PROTOCOL_STATE";
+    const AGGREGATED_SENDER_SESSION: &'static [u8] = b"AGGREGATED_SENDER_SESSION";
+    const TRANSACTION_ATTACHMENT: &'static [u8] = b"TRANSACTION_ATTACHMENT";
     const KNOWN_ONESIDED_PAYMENT_SCRIPT: &'static [u8] = b"KNOWN_ONESIDED_PAYMENT_SCRIPT";
     const CLIENT_KEY_VALUE: &'static [u8] = b"CLIENT_KEY_VALUE";
 
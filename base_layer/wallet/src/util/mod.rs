@@ -20,6 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod cancellation;
+pub mod clock;
 pub mod diesel_ext;
 pub mod encryption;
+pub mod output_payload;
+pub mod retry;
+pub mod scheduler;
+pub mod supervisor;
 pub mod watch;
@@ -0,0 +1,134 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The fields a wallet can send along with an output in the encrypted payload of its
+//! [EncryptedValue](tari_core::transactions::transaction_components::EncryptedValue). Fields are encoded as
+//! type-length-value records, so wallets skip the fields they do not know yet.
+
+use std::convert::TryFrom;
+
+use tari_common_types::types::{Commitment, PrivateKey};
+use tari_core::transactions::transaction_components::{EncryptedValue, EncryptionError};
+
+const MEMO: u8 = 0;
+const PAYMENT_ID: u8 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputPayload {
+    /// A short message for the recipient
+    pub memo: Option<String>,
+    /// An identifier the recipient can match the payment with, e.g. an invoice number
+    pub payment_id: Option<Vec<u8>>,
+}
+
+impl OutputPayload {
+    pub fn is_empty(&self) -> bool {
+        self.memo.is_none() && self.payment_id.is_none()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncryptionError> {
+        let mut bytes = Vec::new();
+        if let Some(memo) = &self.memo {
+            write_record(&mut bytes, MEMO, memo.as_bytes())?;
+        }
+        if let Some(payment_id) = &self.payment_id {
+            write_record(&mut bytes, PAYMENT_ID, payment_id)?;
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, EncryptionError> {
+        let mut payload = Self::default();
+        while let Some((&field, rest)) = bytes.split_first() {
+            if rest.len() < 2 {
+                return Err(EncryptionError::InvalidPayload);
+            }
+            let len = usize::from(u16::from_le_bytes([rest[0], rest[1]]));
+            let value = rest.get(2..2 + len).ok_or(EncryptionError::InvalidPayload)?;
+            match field {
+                MEMO => {
+                    payload.memo = Some(String::from_utf8(value.to_vec()).map_err(|_| EncryptionError::InvalidPayload)?)
+                },
+                PAYMENT_ID => payload.payment_id = Some(value.to_vec()),
+                _ => {},
+            }
+            bytes = &rest[2 + len..];
+        }
+        Ok(payload)
+    }
+
+    /// Encrypts the payload with the encryption key of the output and adds it to its encrypted value
+    pub fn encrypt(
+        &self,
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        encrypted_value: EncryptedValue,
+    ) -> Result<EncryptedValue, EncryptionError> {
+        encrypted_value.with_encrypted_payload(encryption_key, commitment, &self.to_bytes()?)
+    }
+
+    /// Decrypts the payload of an output, returns `None` if the output has no payload
+    pub fn decrypt(
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        encrypted_value: &EncryptedValue,
+    ) -> Result<Option<Self>, EncryptionError> {
+        if !encrypted_value.has_payload() {
+            return Ok(None);
+        }
+        let bytes = EncryptedValue::decrypt_payload(encryption_key, commitment, encrypted_value)?;
+        Self::from_bytes(&bytes).map(Some)
+    }
+}
+
+fn write_record(bytes: &mut Vec<u8>, field: u8, value: &[u8]) -> Result<(), EncryptionError> {
+    let len = u16::try_from(value.len()).map_err(|_| EncryptionError::PayloadTooLong(usize::from(u16::MAX)))?;
+    bytes.push(field);
+    bytes.extend_from_slice(&len.to_le_bytes());
+    bytes.extend_from_slice(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    use super::*;
+
+    #[test]
+    fn it_round_trips_through_the_encrypted_value() {
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let commitment = Commitment::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)));
+        let encrypted_value = EncryptedValue::encrypt_value(&encryption_key, &commitment, 100.into()).unwrap();
+        assert_eq!(
+            OutputPayload::decrypt(&encryption_key, &commitment, &encrypted_value).unwrap(),
+            None
+        );
+
+        let payload = OutputPayload {
+            memo: Some("Invoice 42".to_string()),
+            payment_id: Some(vec![4, 2]),
+        };
+        let encrypted_value = payload.encrypt(&encryption_key, &commitment, encrypted_value).unwrap();
+        assert_eq!(
+            OutputPayload::decrypt(&encryption_key, &commitment, &encrypted_value).unwrap(),
+            Some(payload)
+        );
+        assert!(OutputPayload::decrypt(&PrivateKey::random(&mut OsRng), &commitment, &encrypted_value).is_err());
+    }
+
+    #[test]
+    fn it_skips_unknown_fields() {
+        let mut bytes = OutputPayload {
+            memo: None,
+            payment_id: Some(vec![1]),
+        }
+        .to_bytes()
+        .unwrap();
+        write_record(&mut bytes, 200, b"from the future").unwrap();
+        assert_eq!(OutputPayload::from_bytes(&bytes).unwrap().payment_id, Some(vec![1]));
+        assert!(OutputPayload::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Retry policies for the protocols of the wallet that talk to the base node or to other wallets, so that the number of
+//! attempts and the delays between them are set in the service configs.
+
+use std::time::Duration;
+
+use rand::{rngs::OsRng, Rng};
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+
+/// How the delay before a retry grows with the number of attempts that failed in a row
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backoff {
+    /// Every retry waits `initial_delay`
+    Constant,
+    /// The nth retry waits n times `initial_delay`
+    Linear,
+    /// The delay doubles with every retry
+    Exponential,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// The number of attempts, including the first, after which the operation is given up. Attempts are made until
+    /// one succeeds if not set.
+    pub max_attempts: Option<u32>,
+    /// The delay before the first retry
+    #[serde(with = "serializers::seconds")]
+    pub initial_delay: Duration,
+    pub backoff: Backoff,
+    /// The longest delay before a retry, before the jitter is applied
+    #[serde(with = "serializers::seconds")]
+    pub max_delay: Duration,
+    /// Every delay is varied at random by up to this fraction of it, so that operations that failed together are not
+    /// all retried at the same time
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: Option<u32>, initial_delay: Duration, backoff: Backoff, max_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_delay,
+            backoff,
+            max_delay,
+            jitter: 0.0,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns true if the operation may be attempted again after `failed_attempts` attempts failed
+    pub fn should_retry(&self, failed_attempts: u32) -> bool {
+        self.max_attempts.map_or(true, |max| failed_attempts < max)
+    }
+
+    /// The delay before the next attempt once `failed_attempts` attempts failed in a row, without the jitter
+    pub fn base_delay(&self, failed_attempts: u32) -> Duration {
+        let retry = failed_attempts.max(1);
+        let delay = match self.backoff {
+            Backoff::Constant => self.initial_delay,
+            Backoff::Linear => self.initial_delay.saturating_mul(retry),
+            Backoff::Exponential => self
+                .initial_delay
+                .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1))),
+        };
+        delay.min(self.max_delay)
+    }
+
+    /// The delay before the next attempt once `failed_attempts` attempts failed in a row
+    pub fn delay(&self, failed_attempts: u32) -> Duration {
+        let delay = self.base_delay(failed_attempts);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter <= 0.0 {
+            return delay;
+        }
+        delay.mul_f64(OsRng.gen_range(1.0 - jitter..=1.0 + jitter))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_backs_off() {
+        let policy = RetryPolicy::new(
+            Some(4),
+            Duration::from_secs(2),
+            Backoff::Exponential,
+            Duration::from_secs(10),
+        );
+        let delays = (1..=5).map(|n| policy.base_delay(n).as_secs()).collect::<Vec<_>>();
+        assert_eq!(delays, vec![2, 4, 8, 10, 10]);
+        assert!(policy.should_retry(3));
+        assert!(!policy.should_retry(4));
+
+        let policy = RetryPolicy {
+            backoff: Backoff::Linear,
+            max_attempts: None,
+            ..policy
+        };
+        let delays = (1..=5).map(|n| policy.base_delay(n).as_secs()).collect::<Vec<_>>();
+        assert_eq!(delays, vec![2, 4, 6, 8, 10]);
+        assert!(policy.should_retry(u32::MAX));
+
+        let policy = RetryPolicy {
+            backoff: Backoff::Constant,
+            ..policy
+        };
+        assert_eq!(policy.base_delay(100), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn it_applies_the_jitter() {
+        let policy = RetryPolicy::new(
+            None,
+            Duration::from_secs(100),
+            Backoff::Constant,
+            Duration::from_secs(100),
+        )
+        .with_jitter(0.1);
+        for _ in 0..100 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_secs(90) && delay <= Duration::from_secs(110));
+        }
+    }
+}
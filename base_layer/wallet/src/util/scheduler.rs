@@ -0,0 +1,236 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Scheduling of the requests that the wallet services handle. A service handles its requests one at a time, so a
+//! service busy with background work, e.g. a rescan that imports thousands of outputs, used to make the requests of
+//! the user wait behind all of it. [PrioritizedRequests] has a service handle interactive requests, e.g. sends and
+//! balance queries, before the background requests that are queued, and a [RequestScheduler] limits the number of
+//! requests that each caller has in flight, so that no single caller can flood a service.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
+use tari_service_framework::reply_channel::RequestContext;
+use tokio::sync::Semaphore;
+use tower::Service;
+
+/// The number of interactive requests that are handled in a row while background requests are queued, so that the
+/// background work still progresses while the wallet is busy with the user
+pub const MAX_CONSECUTIVE_INTERACTIVE_REQUESTS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestPriority {
+    /// A request that the user is waiting for
+    Interactive,
+    /// Work that the wallet does on its own, which may be delayed, e.g. validation and rescans
+    Background,
+}
+
+pub trait PrioritizedRequest {
+    fn priority(&self) -> RequestPriority;
+}
+
+impl<TReq: PrioritizedRequest, TResp> PrioritizedRequest for RequestContext<TReq, TResp> {
+    fn priority(&self) -> RequestPriority {
+        self.request()
+            .map_or(RequestPriority::Interactive, PrioritizedRequest::priority)
+    }
+}
+
+/// The request stream of a service, which yields the queued interactive requests before the queued background
+/// requests
+pub struct PrioritizedRequests<S: Stream> {
+    stream: S,
+    interactive: VecDeque<S::Item>,
+    background: VecDeque<S::Item>,
+    consecutive_interactive: usize,
+}
+
+impl<S> PrioritizedRequests<S>
+where
+    S: Stream + Unpin,
+    S::Item: PrioritizedRequest,
+{
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            interactive: VecDeque::new(),
+            background: VecDeque::new(),
+            consecutive_interactive: 0,
+        }
+    }
+
+    /// Returns the next request to handle. This is cancel safe, so it can be used in `tokio::select!`.
+    pub async fn next(&mut self) -> Option<S::Item> {
+        // The requests that were sent while the last request was handled are all considered
+        while let Some(Some(request)) = self.stream.next().now_or_never() {
+            self.push(request);
+        }
+        if let Some(request) = self.pop() {
+            return Some(request);
+        }
+        let request = self.stream.next().await?;
+        self.push(request);
+        self.pop()
+    }
+
+    fn push(&mut self, request: S::Item) {
+        match request.priority() {
+            RequestPriority::Interactive => self.interactive.push_back(request),
+            RequestPriority::Background => self.background.push_back(request),
+        }
+    }
+
+    fn pop(&mut self) -> Option<S::Item> {
+        if self.consecutive_interactive >= MAX_CONSECUTIVE_INTERACTIVE_REQUESTS || self.interactive.is_empty() {
+            if let Some(request) = self.background.pop_front() {
+                self.consecutive_interactive = 0;
+                return Some(request);
+            }
+        }
+        let request = self.interactive.pop_front()?;
+        self.consecutive_interactive += 1;
+        Some(request)
+    }
+}
+
+/// Hands out the limits on the requests that each caller of the wallet services has in flight. Callers are named,
+/// e.g. `grpc` or `ffi`, and the handles of a caller are obtained with the `for_caller` method of the service handle.
+#[derive(Clone)]
+pub struct RequestScheduler {
+    max_requests_per_caller: usize,
+    callers: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl RequestScheduler {
+    pub fn new(max_requests_per_caller: usize) -> Self {
+        Self {
+            max_requests_per_caller: max_requests_per_caller.max(1),
+            callers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the limit of `caller`, which all the handles of the caller share
+    fn caller_limit(&self, caller: &str) -> Arc<Semaphore> {
+        let mut callers = self.callers.lock().expect("request scheduler lock poisoned");
+        callers
+            .entry(caller.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_requests_per_caller)))
+            .clone()
+    }
+}
+
+/// The service of a handle, which waits until its caller has fewer than the maximum number of requests in flight
+/// before it makes a request. The handles that the wallet services use to call each other have no limit, as a limited
+/// caller could otherwise hold all of its slots while the services wait for each other.
+#[derive(Clone)]
+pub struct ScheduledService<S> {
+    inner: S,
+    limit: Option<Arc<Semaphore>>,
+}
+
+impl<S> ScheduledService<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner, limit: None }
+    }
+
+    /// Returns the service limited to the requests of `caller` that `scheduler` allows
+    pub fn for_caller(&self, scheduler: &RequestScheduler, caller: &str) -> Self
+    where S: Clone {
+        Self {
+            inner: self.inner.clone(),
+            limit: Some(scheduler.caller_limit(caller)),
+        }
+    }
+}
+
+impl<S, TReq> Service<TReq> for ScheduledService<S>
+where
+    S: Service<TReq> + Clone + Send + 'static,
+    S::Future: Send,
+    TReq: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: TReq) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let limit = self.limit.clone();
+        Box::pin(async move {
+            // The semaphore is never closed
+            let _permit = match limit {
+                Some(limit) => limit.acquire_owned().await.ok(),
+                None => None,
+            };
+            inner.call(request).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::stream;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Request(RequestPriority, usize);
+
+    impl PrioritizedRequest for Request {
+        fn priority(&self) -> RequestPriority {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn it_handles_interactive_requests_first() {
+        use RequestPriority::{Background, Interactive};
+        let requests = vec![
+            Request(Background, 0),
+            Request(Background, 1),
+            Request(Interactive, 2),
+            Request(Interactive, 3),
+        ];
+        let mut requests = PrioritizedRequests::new(stream::iter(requests));
+        let mut order = Vec::new();
+        while let Some(Request(_, i)) = requests.next().await {
+            order.push(i);
+        }
+        assert_eq!(order, vec![2, 3, 0, 1]);
+    }
+
+    #[tokio::test]
+    async fn it_does_not_starve_background_requests() {
+        use RequestPriority::{Background, Interactive};
+        let requests = (0..=MAX_CONSECUTIVE_INTERACTIVE_REQUESTS + 1)
+            .map(|i| Request(if i == 0 { Background } else { Interactive }, i));
+        let mut requests = PrioritizedRequests::new(stream::iter(requests));
+        for i in 1..=MAX_CONSECUTIVE_INTERACTIVE_REQUESTS {
+            assert_eq!(requests.next().await, Some(Request(Interactive, i)));
+        }
+        assert_eq!(requests.next().await, Some(Request(Background, 0)));
+        assert_eq!(
+            requests.next().await,
+            Some(Request(Interactive, MAX_CONSECUTIVE_INTERACTIVE_REQUESTS + 1))
+        );
+    }
+
+    #[tokio::test]
+    async fn it_limits_the_requests_of_a_caller() {
+        let scheduler = RequestScheduler::new(1);
+        let permit = scheduler.caller_limit("ffi").try_acquire_owned().unwrap();
+        assert!(scheduler.caller_limit("ffi").try_acquire_owned().is_err());
+        assert!(scheduler.caller_limit("grpc").try_acquire_owned().is_ok());
+        drop(permit);
+        assert!(scheduler.caller_limit("ffi").try_acquire_owned().is_ok());
+    }
+}
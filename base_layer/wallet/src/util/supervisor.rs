@@ -0,0 +1,213 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Runs a service so that it can be stopped and started again without restarting the rest of the wallet.
+
+use std::{future::Future, time::Duration};
+
+use futures::StreamExt;
+use log::*;
+use tari_service_framework::reply_channel::Receiver;
+use tari_shutdown::{Shutdown, ShutdownSignal};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot, watch};
+
+const LOG_TARGET: &str = "wallet::util::supervisor";
+
+/// How long a service is given to stop once its shutdown signal has been triggered before it is dropped
+const SERVICE_STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum RestartError {
+    #[error("The service is not running")]
+    ServiceNotRunning,
+}
+
+/// Creates a channel used to request a supervised service to restart
+pub fn restart_channel() -> (RestartRequester, RestartReceiver) {
+    let (sender, receiver) = mpsc::channel(1);
+    (RestartRequester { sender }, RestartReceiver { receiver })
+}
+
+#[derive(Clone)]
+pub struct RestartRequester {
+    sender: mpsc::Sender<oneshot::Sender<()>>,
+}
+
+impl RestartRequester {
+    /// Requests the service to restart and waits until the new instance has been started
+    pub async fn restart(&self) -> Result<(), RestartError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(reply_tx)
+            .await
+            .map_err(|_| RestartError::ServiceNotRunning)?;
+        reply_rx.await.map_err(|_| RestartError::ServiceNotRunning)
+    }
+}
+
+pub struct RestartReceiver {
+    receiver: mpsc::Receiver<oneshot::Sender<()>>,
+}
+
+/// Runs the service returned by `start_service` until `shutdown_signal` is triggered or the service exits by itself.
+/// When a restart is requested the service's own shutdown signal is triggered, and once it has stopped a new instance
+/// is started. Without a restart receiver the service is run once.
+pub async fn run_supervised<F, Fut>(
+    name: &'static str,
+    restart_receiver: Option<RestartReceiver>,
+    mut shutdown_signal: ShutdownSignal,
+    mut start_service: F,
+) where
+    F: FnMut(ShutdownSignal) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut restart_receiver = restart_receiver.map(|r| r.receiver);
+    let mut restarted_reply = None;
+    loop {
+        let mut service_shutdown = Shutdown::new();
+        let service = start_service(service_shutdown.to_signal());
+        futures::pin_mut!(service);
+        if let Some(reply) = restarted_reply.take() {
+            info!(target: LOG_TARGET, "{} restarted", name);
+            let _result = reply.send(());
+        }
+
+        let restart_request = async {
+            match restart_receiver.as_mut() {
+                Some(receiver) => receiver.recv().await,
+                None => futures::future::pending().await,
+            }
+        };
+        tokio::select! {
+            // Shutting down takes priority over a restart requested at the same time
+            biased;
+            _ = shutdown_signal.wait() => {
+                service_shutdown.trigger();
+                stop_service(name, service).await;
+                break;
+            },
+            _ = &mut service => {
+                debug!(target: LOG_TARGET, "{} exited", name);
+                break;
+            },
+            Some(reply) = restart_request => {
+                info!(target: LOG_TARGET, "Restarting {}", name);
+                service_shutdown.trigger();
+                stop_service(name, service).await;
+                restarted_reply = Some(reply);
+            },
+        }
+    }
+}
+
+async fn stop_service<Fut: Future<Output = ()>>(name: &str, service: Fut) {
+    if tokio::time::timeout(SERVICE_STOP_TIMEOUT, service).await.is_err() {
+        warn!(
+            target: LOG_TARGET,
+            "{} did not stop within {:.0?} and was dropped", name, SERVICE_STOP_TIMEOUT
+        );
+    }
+}
+
+type RequestSender<TReq, TResp> = mpsc::UnboundedSender<(TReq, oneshot::Sender<TResp>)>;
+
+/// Relays requests from a service's handles to the current instance of a supervised service, so that handles keep
+/// working when the service is restarted. Requests that are in flight when the service stops are dropped.
+pub struct RequestRelay<TReq, TResp> {
+    target: watch::Sender<Option<RequestSender<TReq, TResp>>>,
+}
+
+impl<TReq, TResp> RequestRelay<TReq, TResp>
+where
+    TReq: Send + 'static,
+    TResp: Send + 'static,
+{
+    /// Spawns a task relaying the requests received on `requests`. Requests are held while no instance of the service
+    /// is running, and dropped once the relay itself has been dropped.
+    pub fn spawn(mut requests: Receiver<TReq, TResp>) -> Self {
+        let (target, mut target_rx) = watch::channel(None::<RequestSender<TReq, TResp>>);
+        tokio::spawn(async move {
+            while let Some(request_context) = requests.next().await {
+                // Wait for a running instance, which may be starting or restarting
+                let sender = loop {
+                    let current = target_rx.borrow().clone();
+                    match current {
+                        Some(sender) if !sender.is_closed() => break Some(sender),
+                        _ => {
+                            if target_rx.changed().await.is_err() {
+                                break None;
+                            }
+                        },
+                    }
+                };
+                let sent = sender.map(|sender| sender.send(request_context.split()).is_ok());
+                if sent != Some(true) {
+                    debug!(target: LOG_TARGET, "Request dropped because the service is not running");
+                }
+            }
+        });
+        Self { target }
+    }
+
+    /// Returns the request stream for a new instance of the service. Requests are relayed to it from now on.
+    pub fn next_receiver(&self) -> Receiver<TReq, TResp> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let _result = self.target.send(Some(sender));
+        Receiver::new(receiver)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use tari_service_framework::reply_channel;
+    use tower::Service;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_restarts_the_service_and_relays_requests() {
+        let (mut sender, receiver) = reply_channel::unbounded::<usize, usize>();
+        let relay = RequestRelay::spawn(receiver);
+        let (restart_requester, restart_receiver) = restart_channel();
+        let mut shutdown = Shutdown::new();
+        let instances = Arc::new(AtomicUsize::new(0));
+
+        let supervisor = tokio::spawn({
+            let instances = instances.clone();
+            run_supervised(
+                "Test Service",
+                Some(restart_receiver),
+                shutdown.to_signal(),
+                move |signal| {
+                    let instance = instances.fetch_add(1, Ordering::SeqCst) + 1;
+                    let requests = relay.next_receiver().take_until(signal);
+                    async move {
+                        futures::pin_mut!(requests);
+                        while let Some(request) = requests.next().await {
+                            let (n, reply) = request.split();
+                            let _result = reply.send(n * instance);
+                        }
+                    }
+                },
+            )
+        });
+
+        assert_eq!(sender.call(2).await.unwrap(), 2);
+        restart_requester.restart().await.unwrap();
+        assert_eq!(instances.load(Ordering::SeqCst), 2);
+        assert_eq!(sender.call(2).await.unwrap(), 4);
+
+        shutdown.trigger();
+        supervisor.await.unwrap();
+        assert!(matches!(
+            restart_requester.restart().await,
+            Err(RestartError::ServiceNotRunning)
+        ));
+    }
+}
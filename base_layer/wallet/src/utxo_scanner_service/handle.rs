@@ -23,10 +23,89 @@
 use std::time::Duration;
 
 use tari_comms::peer_manager::NodeId;
-use tari_core::transactions::tari_amount::MicroTari;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction_components::{OutputFeatures, UniqueAsset},
+};
 use tokio::sync::{broadcast, watch};
 
-use crate::util::watch::Watch;
+use crate::{output_manager_service::storage::OutputSource, util::watch::Watch};
+
+/// The number and total value of the recovered outputs of a single output type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecoveredOutputStats {
+    pub num_recovered: u64,
+    pub value_recovered: MicroTari,
+}
+
+impl RecoveredOutputStats {
+    fn add(&mut self, value: MicroTari) {
+        self.num_recovered = self.num_recovered.saturating_add(1);
+        self.value_recovered += value;
+    }
+}
+
+/// Statistics, per output type, of the outputs recovered during a scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryStatistics {
+    /// Standard Mimblewimble outputs recovered by rewinding
+    pub standard: RecoveredOutputStats,
+    /// Coinbase outputs recovered by rewinding
+    pub coinbase: RecoveredOutputStats,
+    /// One-sided payments made to a script key known to this wallet
+    pub one_sided: RecoveredOutputStats,
+    /// One-sided payments made to this wallet's stealth address
+    pub stealth_one_sided: RecoveredOutputStats,
+    /// Outputs that could be rewound but whose script was not recognised
+    pub unrecognized: RecoveredOutputStats,
+    /// Outputs whose source was not recorded when they were recovered
+    pub unknown: RecoveredOutputStats,
+    /// Outputs that hold a unique asset. These are also counted under the way they were recovered.
+    pub unique_assets: RecoveredOutputStats,
+}
+
+impl RecoveryStatistics {
+    pub(crate) fn record(&mut self, source: OutputSource, features: &OutputFeatures, value: MicroTari) {
+        if matches!(UniqueAsset::from_features(features), Ok(Some(_))) {
+            self.unique_assets.add(value);
+        }
+        let stats = if features.is_coinbase() {
+            &mut self.coinbase
+        } else {
+            match source {
+                OutputSource::Coinbase => &mut self.coinbase,
+                OutputSource::OneSided | OutputSource::ExpiringVault => &mut self.one_sided,
+                OutputSource::StealthOneSided => &mut self.stealth_one_sided,
+                OutputSource::RecoveredButUnrecognized => &mut self.unrecognized,
+                OutputSource::Unknown => &mut self.unknown,
+                OutputSource::Standard |
+                OutputSource::Refund |
+                OutputSource::AtomicSwap |
+                OutputSource::ColdStorageChange |
+                OutputSource::Vault => &mut self.standard,
+            }
+        };
+        stats.add(value);
+    }
+
+    /// Returns the number and value of all the recovered outputs. Unique asset outputs are only counted once, under
+    /// the way they were recovered.
+    pub fn total(&self) -> RecoveredOutputStats {
+        [
+            self.standard,
+            self.coinbase,
+            self.one_sided,
+            self.stealth_one_sided,
+            self.unrecognized,
+            self.unknown,
+        ]
+        .iter()
+        .fold(RecoveredOutputStats::default(), |total, stats| RecoveredOutputStats {
+            num_recovered: total.num_recovered.saturating_add(stats.num_recovered),
+            value_recovered: total.value_recovered + stats.value_recovered,
+        })
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum UtxoScannerEvent {
@@ -48,12 +127,14 @@ pub enum UtxoScannerEvent {
         current_height: u64,
         tip_height: u64,
     },
-    /// Completed Recovery (Number scanned, Num of Recovered outputs, Value of recovered outputs, Time taken)
+    /// Completed Recovery (Number scanned, Num of Recovered outputs, Value of recovered outputs, Time taken,
+    /// Per output type statistics of the outputs recovered in this scan)
     Completed {
         final_height: u64,
         num_recovered: u64,
         value_recovered: MicroTari,
         time_taken: Duration,
+        statistics: RecoveryStatistics,
     },
     /// Scanning process has failed and scanning process has exited
     ScanningFailed,
@@ -35,7 +35,10 @@ use crate::{
     output_manager_service::handle::OutputManagerHandle,
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::handle::TransactionServiceHandle,
-    util::watch::Watch,
+    util::{
+        supervisor::{run_supervised, RestartReceiver},
+        watch::Watch,
+    },
     utxo_scanner_service::{
         handle::UtxoScannerHandle,
         service::UtxoScannerService,
@@ -49,6 +52,7 @@ pub struct UtxoScannerServiceInitializer<T> {
     backend: Option<WalletDatabase<T>>,
     factories: CryptoFactories,
     node_identity: Arc<NodeIdentity>,
+    restart_receiver: Option<RestartReceiver>,
 }
 
 impl<T> UtxoScannerServiceInitializer<T>
@@ -59,8 +63,15 @@ where T: WalletBackend + 'static
             backend: Some(backend),
             factories,
             node_identity,
+            restart_receiver: None,
         }
     }
+
+    /// Allows the service to be restarted through the given receiver's requester
+    pub fn with_restart_receiver(mut self, restart_receiver: RestartReceiver) -> Self {
+        self.restart_receiver = Some(restart_receiver);
+        self
+    }
 }
 
 #[async_trait]
@@ -89,6 +100,7 @@ where T: WalletBackend + 'static
             .expect("Cannot start Utxo scanner service without setting a storage backend");
         let factories = self.factories.clone();
         let node_identity = self.node_identity.clone();
+        let restart_receiver = self.restart_receiver.take();
 
         context.spawn_when_ready(move |handles| async move {
             let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
@@ -97,28 +109,38 @@ where T: WalletBackend + 'static
             let wallet_connectivity = handles.expect_handle::<WalletConnectivityHandle>();
             let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
 
-            let scanning_service = UtxoScannerService::<T, WalletConnectivityHandle>::builder()
-                .with_peers(vec![])
-                .with_retry_limit(2)
-                .with_mode(UtxoScannerMode::Scanning)
-                .build_with_resources(
-                    backend,
-                    comms_connectivity,
-                    wallet_connectivity.clone(),
-                    output_manager_service,
-                    transaction_service,
-                    node_identity,
-                    factories,
-                    handles.get_shutdown_signal(),
-                    event_sender,
-                    base_node_service_handle,
-                    one_sided_message_watch_receiver,
-                    recovery_message_watch_receiver,
-                )
-                .run();
-
-            futures::pin_mut!(scanning_service);
-            future::select(scanning_service, handles.get_shutdown_signal()).await;
+            let shutdown_signal = handles.get_shutdown_signal();
+            run_supervised(
+                "Utxo scanner service",
+                restart_receiver,
+                shutdown_signal,
+                |shutdown_signal| {
+                    let scanning_service = UtxoScannerService::<T, WalletConnectivityHandle>::builder()
+                        .with_peers(vec![])
+                        .with_retry_limit(2)
+                        .with_mode(UtxoScannerMode::Scanning)
+                        .build_with_resources(
+                            backend.clone(),
+                            comms_connectivity.clone(),
+                            wallet_connectivity.clone(),
+                            output_manager_service.clone(),
+                            transaction_service.clone(),
+                            node_identity.clone(),
+                            factories.clone(),
+                            shutdown_signal.clone(),
+                            event_sender.clone(),
+                            base_node_service_handle.clone(),
+                            one_sided_message_watch_receiver.clone(),
+                            recovery_message_watch_receiver.clone(),
+                        )
+                        .run();
+                    async move {
+                        futures::pin_mut!(scanning_service);
+                        future::select(scanning_service, shutdown_signal).await;
+                    }
+                },
+            )
+            .await;
             info!(target: LOG_TARGET, "Utxo scanner service shutdown");
         });
         Ok(())
@@ -24,7 +24,6 @@ use std::sync::Arc;
 
 use chrono::NaiveDateTime;
 use futures::FutureExt;
-use log::*;
 use tari_common_types::types::HashOutput;
 use tari_comms::{connectivity::ConnectivityRequester, peer_manager::Peer, types::CommsPublicKey, NodeIdentity};
 use tari_core::transactions::{tari_amount::MicroTari, CryptoFactories};
@@ -33,16 +32,17 @@ use tokio::{
     sync::{broadcast, watch},
     task,
 };
+use tracing::{debug, error, info};
 
 use crate::{
-    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    base_node_service::handle::{BaseNodeServiceHandle, ChainEvent},
     connectivity_service::WalletConnectivityInterface,
     error::WalletError,
     output_manager_service::handle::OutputManagerHandle,
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::handle::TransactionServiceHandle,
     utxo_scanner_service::{
-        handle::UtxoScannerEvent,
+        handle::{RecoveryStatistics, UtxoScannerEvent},
         utxo_scanner_task::UtxoScannerTask,
         uxto_scanner_service_builder::{UtxoScannerMode, UtxoScannerServiceBuilder},
     },
@@ -59,6 +59,7 @@ pub const SCANNED_BLOCK_CACHE_SIZE: u64 = 720;
 pub struct UtxoScannerService<TBackend, TWalletConnectivity> {
     pub(crate) resources: UtxoScannerResources<TBackend, TWalletConnectivity>,
     pub(crate) retry_limit: usize,
+    pub(crate) key_index_gap_limit: u64,
     pub(crate) peer_seeds: Vec<CommsPublicKey>,
     pub(crate) mode: UtxoScannerMode,
     pub(crate) shutdown_signal: ShutdownSignal,
@@ -76,6 +77,7 @@ where
     pub fn new(
         peer_seeds: Vec<CommsPublicKey>,
         retry_limit: usize,
+        key_index_gap_limit: u64,
         mode: UtxoScannerMode,
         resources: UtxoScannerResources<TBackend, TWalletConnectivity>,
         shutdown_signal: ShutdownSignal,
@@ -88,6 +90,7 @@ where
             resources,
             peer_seeds,
             retry_limit,
+            key_index_gap_limit,
             mode,
             shutdown_signal,
             event_sender,
@@ -103,8 +106,10 @@ where
             peer_seeds: self.peer_seeds.clone(),
             event_sender: self.event_sender.clone(),
             retry_limit: self.retry_limit,
+            key_index_gap_limit: self.key_index_gap_limit,
             peer_index: 0,
             num_retries: 1,
+            statistics: RecoveryStatistics::default(),
             mode: self.mode.clone(),
             shutdown_signal,
         }
@@ -132,7 +137,7 @@ where
         }
 
         let mut main_shutdown = self.shutdown_signal.clone();
-        let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
+        let mut chain_event_stream = self.base_node_service.subscribe_chain_events();
 
         loop {
             let mut local_shutdown = Shutdown::new();
@@ -146,18 +151,25 @@ where
 
             loop {
                 tokio::select! {
-                    event = base_node_service_event_stream.recv() => {
+                    event = chain_event_stream.recv() => {
                         match event {
-                            Ok(e) => {
-                                if let BaseNodeEvent::NewBlockDetected(h) = (*e).clone() {
-                                        debug!(target: LOG_TARGET, "New block event received: {}", h);
-                                        if local_shutdown.is_triggered() {
-                                            debug!(target: LOG_TARGET, "Starting new round of UTXO scanning");
-                                            break;
-                                        }
-                                }
+                            Ok(e) => match *e {
+                                ChainEvent::NewBlock(h, _) => {
+                                    debug!(target: LOG_TARGET, "New block event received: {}", h);
+                                    if local_shutdown.is_triggered() {
+                                        debug!(target: LOG_TARGET, "Starting new round of UTXO scanning");
+                                        break;
+                                    }
+                                },
+                                ChainEvent::Reorged { from, to } => {
+                                    // Stop the running round as it may be scanning blocks that are no longer part of the
+                                    // chain, the new block event that follows the reorg starts a new one
+                                    debug!(target: LOG_TARGET, "Reorg from height {} to {} detected", from, to);
+                                    local_shutdown.trigger();
+                                },
+                                ChainEvent::SyncStateChanged(_) => {},
                             },
-                            Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
+                            Err(e) => debug!(target: LOG_TARGET, "Lagging read on chain event broadcast channel: {}", e),
                         };
                     },
                     _ = &mut task_join_handle => {
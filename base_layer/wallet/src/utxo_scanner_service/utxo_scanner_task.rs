@@ -27,7 +27,6 @@ use std::{
 
 use chrono::{NaiveDateTime, Utc};
 use futures::StreamExt;
-use log::*;
 use tari_common_types::{
     transaction::{ImportStatus, TxId},
     types::HashOutput,
@@ -51,15 +50,17 @@ use tari_core::{
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::hex::Hex;
 use tokio::sync::broadcast;
+use tracing::{debug, info, instrument, trace, warn};
 
 use crate::{
     connectivity_service::WalletConnectivityInterface,
     error::WalletError,
+    output_manager_service::storage::OutputSource,
     storage::database::WalletBackend,
     transaction_service::error::{TransactionServiceError, TransactionStorageError},
     utxo_scanner_service::{
         error::UtxoScannerError,
-        handle::UtxoScannerEvent,
+        handle::{RecoveryStatistics, UtxoScannerEvent},
         service::{ScannedBlock, UtxoScannerResources, SCANNED_BLOCK_CACHE_SIZE},
         uxto_scanner_service_builder::UtxoScannerMode,
         RECOVERY_KEY,
@@ -72,17 +73,24 @@ pub struct UtxoScannerTask<TBackend, TWalletConnectivity> {
     pub(crate) resources: UtxoScannerResources<TBackend, TWalletConnectivity>,
     pub(crate) event_sender: broadcast::Sender<UtxoScannerEvent>,
     pub(crate) retry_limit: usize,
+    pub(crate) key_index_gap_limit: u64,
     pub(crate) num_retries: usize,
     pub(crate) peer_seeds: Vec<CommsPublicKey>,
     pub(crate) peer_index: usize,
     pub(crate) mode: UtxoScannerMode,
     pub(crate) shutdown_signal: ShutdownSignal,
+    pub(crate) statistics: RecoveryStatistics,
 }
 impl<TBackend, TWalletConnectivity> UtxoScannerTask<TBackend, TWalletConnectivity>
 where
     TBackend: WalletBackend + 'static,
     TWalletConnectivity: WalletConnectivityInterface,
 {
+    #[instrument(
+        name = "utxo_scanner_service::scan",
+        skip_all,
+        fields(service = "utxo_scanner_service", mode = ?self.mode)
+    )]
     pub async fn run(mut self) -> Result<(), UtxoScannerError> {
         if self.mode == UtxoScannerMode::Recovery {
             self.set_recovery_mode()?;
@@ -105,6 +113,9 @@ where
                 Some(peer) => match self.attempt_sync(peer.clone()).await {
                     Ok((num_outputs_recovered, final_height, final_amount, elapsed)) => {
                         debug!(target: LOG_TARGET, "Scanned to height #{}", final_height);
+                        if self.mode == UtxoScannerMode::Recovery {
+                            self.restore_key_indices().await?;
+                        }
                         self.finalize(num_outputs_recovered, final_height, final_amount, elapsed)?;
                         return Ok(());
                     },
@@ -160,6 +171,7 @@ where
             num_recovered: num_outputs_recovered,
             value_recovered: total_value,
             time_taken: elapsed,
+            statistics: self.statistics.clone(),
         });
 
         // Presence of scanning keys are used to determine if a wallet is busy with recovery or not.
@@ -195,6 +207,7 @@ where
         }
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "attempt_sync", peer = %peer))]
     async fn attempt_sync(&mut self, peer: NodeId) -> Result<(u64, u64, MicroTari, Duration), UtxoScannerError> {
         self.publish_event(UtxoScannerEvent::ConnectingToBaseNode(peer.clone()));
         let selected_peer = self.resources.wallet_connectivity.get_current_base_node_id();
@@ -426,6 +439,7 @@ where
         }
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "scan_utxos", tip_height = tip_height))]
     async fn scan_utxos(
         &mut self,
         client: &mut BaseNodeWalletRpcClient,
@@ -531,8 +545,8 @@ where
     async fn scan_for_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
-    ) -> Result<Vec<(UnblindedOutput, String, ImportStatus, TxId)>, UtxoScannerError> {
-        let mut found_outputs: Vec<(UnblindedOutput, String, ImportStatus, TxId)> = Vec::new();
+    ) -> Result<Vec<(UnblindedOutput, String, ImportStatus, TxId, OutputSource)>, UtxoScannerError> {
+        let mut found_outputs: Vec<(UnblindedOutput, String, ImportStatus, TxId, OutputSource)> = Vec::new();
         found_outputs.append(
             &mut self
                 .resources
@@ -546,7 +560,13 @@ where
                     } else {
                         ImportStatus::Imported
                     };
-                    (ro.output, self.resources.recovery_message.clone(), status, ro.tx_id)
+                    (
+                        ro.output,
+                        self.resources.recovery_message.clone(),
+                        status,
+                        ro.tx_id,
+                        ro.source,
+                    )
                 })
                 .collect(),
         );
@@ -561,9 +581,11 @@ where
                 .map(|ro| {
                     (
                         ro.output,
-                        self.resources.one_sided_payment_message.clone(),
+                        ro.memo
+                            .unwrap_or_else(|| self.resources.one_sided_payment_message.clone()),
                         ImportStatus::FauxUnconfirmed,
                         ro.tx_id,
+                        ro.source,
                     )
                 })
                 .collect(),
@@ -571,9 +593,10 @@ where
         Ok(found_outputs)
     }
 
+    #[instrument(level = "debug", skip_all, fields(stage = "import_utxos"))]
     async fn import_utxos_to_transaction_service(
         &mut self,
-        utxos: Vec<(UnblindedOutput, String, ImportStatus, TxId)>,
+        utxos: Vec<(UnblindedOutput, String, ImportStatus, TxId, OutputSource)>,
         current_height: u64,
         mined_timestamp: NaiveDateTime,
     ) -> Result<(u64, MicroTari), UtxoScannerError> {
@@ -582,7 +605,7 @@ where
         let default_key = CommsPublicKey::default();
         let self_key = self.resources.node_identity.public_key().clone();
 
-        for (uo, message, import_status, tx_id) in utxos {
+        for (uo, message, import_status, tx_id, source) in utxos {
             let source_public_key = if uo.features.is_coinbase() {
                 // its a coinbase, so we know we mined it and it comes from us.
                 &self_key
@@ -606,6 +629,7 @@ where
                 Ok(_) => {
                     num_recovered = num_recovered.saturating_add(1);
                     total_amount += uo.value;
+                    self.statistics.record(source, &uo.features, uo.value);
                 },
                 Err(WalletError::TransactionServiceError(TransactionServiceError::TransactionStorageError(
                     TransactionStorageError::DuplicateOutput,
@@ -623,6 +647,23 @@ where
         Ok((num_recovered, total_amount))
     }
 
+    /// Probe the key manager branches past the recovered outputs so that the key index counters continue after the
+    /// highest key used by this wallet before recovery.
+    async fn restore_key_indices(&mut self) -> Result<(), UtxoScannerError> {
+        let restored = self
+            .resources
+            .output_manager_service
+            .restore_key_indices(self.key_index_gap_limit)
+            .await?;
+        for key_index in restored {
+            info!(
+                target: LOG_TARGET,
+                "Key manager branch '{}' index restored to {}", key_index.branch, key_index.index
+            );
+        }
+        Ok(())
+    }
+
     fn set_recovery_mode(&self) -> Result<(), UtxoScannerError> {
         self.resources
             .db
@@ -649,6 +690,7 @@ where
 
     /// A faux incoming transaction will be created to provide a record of the event of importing a scanned UTXO. The
     /// TxId of the generated transaction is returned.
+    #[instrument(skip_all, fields(service = "utxo_scanner_service", tx_id = %tx_id))]
     pub async fn import_unblinded_utxo_to_transaction_service(
         &mut self,
         unblinded_output: UnblindedOutput,
@@ -55,9 +55,13 @@ impl Default for UtxoScannerMode {
     }
 }
 
+/// The default number of consecutive unused keys probed past the last used key when restoring key manager indices
+pub const DEFAULT_KEY_INDEX_GAP_LIMIT: u64 = 1_000;
+
 #[derive(Debug, Clone)]
 pub struct UtxoScannerServiceBuilder {
     retry_limit: usize,
+    key_index_gap_limit: u64,
     peers: Vec<CommsPublicKey>,
     mode: Option<UtxoScannerMode>,
     one_sided_message: String,
@@ -68,6 +72,7 @@ impl Default for UtxoScannerServiceBuilder {
     fn default() -> Self {
         Self {
             retry_limit: 0,
+            key_index_gap_limit: DEFAULT_KEY_INDEX_GAP_LIMIT,
             peers: vec![],
             mode: None,
             one_sided_message: "Detected one-sided payment on blockchain".to_string(),
@@ -84,6 +89,13 @@ impl UtxoScannerServiceBuilder {
         self
     }
 
+    /// Set the number of consecutive unused keys that are probed past the last used key of each key manager branch
+    /// when restoring the key indices at the end of a recovery.
+    pub fn with_key_index_gap_limit(&mut self, gap_limit: u64) -> &mut Self {
+        self.key_index_gap_limit = gap_limit;
+        self
+    }
+
     pub fn with_peers(&mut self, peer_public_keys: Vec<CommsPublicKey>) -> &mut Self {
         self.peers = peer_public_keys;
         self
@@ -127,6 +139,7 @@ impl UtxoScannerServiceBuilder {
         UtxoScannerService::new(
             self.peers.drain(..).collect(),
             self.retry_limit,
+            self.key_index_gap_limit,
             self.mode.clone().unwrap_or_default(),
             resources,
             shutdown_signal,
@@ -168,6 +181,7 @@ impl UtxoScannerServiceBuilder {
         UtxoScannerService::new(
             self.peers.drain(..).collect(),
             self.retry_limit,
+            self.key_index_gap_limit,
             self.mode.clone().unwrap_or_default(),
             resources,
             shutdown_signal,
@@ -20,30 +20,51 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{cmp, marker::PhantomData, sync::Arc};
+use std::{
+    cmp,
+    collections::{BTreeSet, HashSet},
+    fmt,
+    fs,
+    marker::PhantomData,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use digest::Digest;
 use log::*;
-use tari_common::configuration::bootstrap::ApplicationType;
+use tari_common::configuration::{bootstrap::ApplicationType, Network};
 use tari_common_types::{
     transaction::{ImportStatus, TxId},
-    types::{ComSignature, Commitment, PrivateKey, PublicKey},
+    types::{ComSignature, Commitment, FixedHash, HashOutput, PrivateKey, PublicKey},
 };
 use tari_comms::{
+    connectivity::ConnectivityEvent,
     multiaddr::Multiaddr,
-    peer_manager::{NodeId, Peer, PeerFeatures, PeerFlags},
+    peer_manager::{NodeId, Peer, PeerFeatures, PeerFlags, PeerQuery},
     types::{CommsPublicKey, CommsSecretKey},
     CommsNode,
     NodeIdentity,
     UnspawnedCommsNode,
 };
-use tari_comms_dht::{store_forward::StoreAndForwardRequester, Dht};
+use tari_comms_dht::{
+    store_forward::{SafStatistics, StoreAndForwardRequester},
+    Dht,
+};
 use tari_core::{
-    consensus::NetworkConsensus,
+    consensus::{ConsensusManager, NetworkConsensus},
     covenants::Covenant,
     transactions::{
+        fee::Fee,
         tari_amount::MicroTari,
-        transaction_components::{EncryptedValue, OutputFeatures, UnblindedOutput},
+        transaction_components::{
+            EncryptedValue,
+            OutputFeatures,
+            OutputFeaturesBuilder,
+            SideChainFeatures,
+            UnblindedOutput,
+        },
         CryptoFactories,
     },
 };
@@ -56,33 +77,50 @@ use tari_crypto::{
 use tari_key_manager::{
     cipher_seed::CipherSeed,
     key_manager::KeyManager,
-    mnemonic::{Mnemonic, MnemonicLanguage},
+    mnemonic::{self, Mnemonic, MnemonicLanguage},
 };
 use tari_p2p::{
     auto_update::{AutoUpdateConfig, SoftwareUpdaterHandle, SoftwareUpdaterService},
     comms_connector::pubsub_connector,
     initialization,
     initialization::P2pInitializer,
+    peer_seeds::SeedPeer,
     services::liveness::{config::LivenessConfig, LivenessInitializer},
     PeerSeedsConfig,
 };
-use tari_script::{script, ExecutionStack, TariScript};
+use tari_script::{script, ExecutionStack, OpcodeVersion, TariScript};
 use tari_service_framework::StackBuilder;
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::{ByteArray, SafePassword};
 
+#[cfg(feature = "header_sync")]
+use crate::header_sync::{handle::HeaderSyncHandle, HeaderSyncServiceInitializer};
 use crate::{
-    base_node_service::{handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
+    base_node_service::{error::BaseNodeServiceError, handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
+    cloud_sync::{CloudSyncEngine, SyncBlobStore, SyncKey, SyncSnapshot, SyncSummary},
     config::{WalletConfig, KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY},
     connectivity_service::{WalletConnectivityHandle, WalletConnectivityInitializer, WalletConnectivityInterface},
-    contacts_service::{handle::ContactsServiceHandle, storage::database::ContactsBackend, ContactsServiceInitializer},
+    contacts_service::{
+        error::{ContactsServiceError, ContactsServiceStorageError},
+        handle::ContactsServiceHandle,
+        storage::database::ContactsBackend,
+        ContactsServiceInitializer,
+    },
+    decoy_service::{handle::DecoyServiceHandle, DecoyServiceInitializer},
+    diagnostics::{ConnectivityDiagnostics, DatabaseStatistics, DiagnosticsBundle, ServiceDiagnostics},
+    digest_service::{handle::DigestServiceHandle, DigestServiceInitializer},
     error::{WalletError, WalletStorageError},
+    extensions::WalletExtensions,
+    health_check::{HealthProbe, HealthReport},
+    inheritance_service::{handle::InheritanceServiceHandle, InheritanceServiceInitializer},
+    integrity::{IntegrityChecker, IntegrityReport, KEY_INDEX_GAP_LIMIT},
     key_manager_service::{
         storage::database::KeyManagerBackend,
         KeyManagerHandle,
         KeyManagerInitializer,
         KeyManagerInterface,
     },
+    network_state::NetworkState,
     output_manager_service::{
         error::OutputManagerError,
         handle::OutputManagerHandle,
@@ -91,20 +129,88 @@ use crate::{
             models::KnownOneSidedPaymentScript,
         },
         OutputManagerServiceInitializer,
+        OwnedAsset,
+        TokenBalance,
+        VaultOutput,
     },
+    portable_dump::{PortableImportSummary, PortableWalletDump},
+    proof_of_reserves::{ReserveAttestation, ReserveStatement},
+    search::{SearchMatch, SearchResult, MAX_SEARCH_RESULTS},
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
+        error::TransactionServiceError,
         handle::TransactionServiceHandle,
-        storage::database::TransactionBackend,
+        storage::database::{TransactionBackend, TransactionDatabase},
         TransactionServiceInitializer,
     },
     types::KeyDigest,
+    util::{
+        scheduler::RequestScheduler,
+        supervisor::{restart_channel, RestartRequester},
+    },
     utxo_scanner_service::{handle::UtxoScannerHandle, initializer::UtxoScannerServiceInitializer, RECOVERY_KEY},
 };
 
 const LOG_TARGET: &str = "wallet";
+/// The client key-value key that the ids of the transactions that the owner has seen are stored under
+const READ_TRANSACTIONS_KEY: &str = "read_transactions";
 /// The minimum buffer size for the wallet pubsub_connector channel
 const WALLET_BUFFER_MIN_SIZE: usize = 300;
+/// The top-level config settings, as named in the config file, that [Wallet::reload_config] applies to the running
+/// wallet. Changes to any other setting only take effect after the wallet is restarted.
+const LIVE_CONFIG_SETTINGS: &[&str] = &[
+    "transactions",
+    "outputs",
+    "fee_per_gram",
+    "num_required_confirmations",
+    "command_send_wait_timeout",
+    "command_send_wait_stage",
+    "health_check_lag_threshold",
+    "health_check_timeout",
+    "log_dir",
+];
+
+/// A service that can be restarted on its own with [Wallet::restart_service]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceKind {
+    TransactionService,
+    UtxoScanner,
+}
+
+impl fmt::Display for ServiceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceKind::TransactionService => write!(f, "transaction service"),
+            ServiceKind::UtxoScanner => write!(f, "UTXO scanner"),
+        }
+    }
+}
+
+/// The outcome of [Wallet::reload_config]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigReload {
+    /// The settings that changed but cannot be applied to the running wallet
+    pub requires_restart: Vec<String>,
+}
+
+/// The consensus parameters of the network of the wallet that apply at a given height, see
+/// [Wallet::get_consensus_constants]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsensusParameters {
+    pub network: Network,
+    /// The height from which these parameters apply
+    pub effective_from_height: u64,
+    pub blockchain_version: u16,
+    pub min_fee_per_gram: MicroTari,
+    pub min_transaction_fee: MicroTari,
+    /// The number of blocks before a coinbase output can be spent
+    pub coinbase_lock_height: u64,
+    pub max_block_transaction_weight: u64,
+    pub max_script_byte_size: usize,
+    pub max_script_execution_cost: u64,
+    /// The latest version of TariScript opcodes that outputs may use
+    pub max_opcode_version: OpcodeVersion,
+}
 
 /// A structure containing the config and services that a Wallet application will require. This struct will start up all
 /// the services and provide the APIs that applications will use to interact with the services
@@ -120,11 +226,24 @@ pub struct Wallet<T, U, V, W, X> {
     pub wallet_connectivity: WalletConnectivityHandle,
     pub contacts_service: ContactsServiceHandle,
     pub base_node_service: BaseNodeServiceHandle,
+    pub digest_service: DigestServiceHandle,
+    pub decoy_service: DecoyServiceHandle,
+    pub inheritance_service: InheritanceServiceHandle,
+    #[cfg(feature = "header_sync")]
+    pub header_sync_service: HeaderSyncHandle,
     pub utxo_scanner_service: UtxoScannerHandle,
     pub updater_service: Option<SoftwareUpdaterHandle>,
     pub db: WalletDatabase<T>,
     pub output_db: OutputManagerDatabase<V>,
     pub factories: CryptoFactories,
+    /// Limits the requests that each caller has in flight, see [OutputManagerHandle::for_caller] and
+    /// [TransactionServiceHandle::for_caller]
+    pub request_scheduler: RequestScheduler,
+    transaction_db: TransactionDatabase<U>,
+    consensus_manager: ConsensusManager,
+    transaction_service_restarter: RestartRequester,
+    utxo_scanner_restarter: RestartRequester,
+    config: Arc<RwLock<WalletConfig>>,
     _u: PhantomData<U>,
     _v: PhantomData<V>,
     _w: PhantomData<W>,
@@ -140,7 +259,7 @@ where
 {
     #[allow(clippy::too_many_lines)]
     pub async fn start(
-        config: WalletConfig,
+        mut config: WalletConfig,
         peer_seeds: PeerSeedsConfig,
         auto_update: AutoUpdateConfig,
         node_identity: Arc<NodeIdentity>,
@@ -153,8 +272,28 @@ where
         key_manager_backend: X,
         shutdown_signal: ShutdownSignal,
         master_seed: CipherSeed,
+        extensions: WalletExtensions,
     ) -> Result<Self, WalletError> {
+        extensions.validate()?;
+        check_database_network(&wallet_database, config.network)?;
+        let monitored_base_nodes = parse_base_node_service_peers(&config.base_node_service_peers)?;
+        if config.light_mode {
+            if node_identity.has_peer_features(PeerFeatures::DHT_STORE_FORWARD) {
+                return Err(WalletError::ArgumentError {
+                    argument: "node_identity".to_string(),
+                    value: format!("{:?}", node_identity.features()),
+                    message: "A light mode wallet does not store messages for other peers".to_string(),
+                });
+            }
+            config.apply_light_mode();
+            info!(
+                target: LOG_TARGET,
+                "Light mode is enabled, the wallet will not join the DHT"
+            );
+        }
         let buf_size = cmp::max(WALLET_BUFFER_MIN_SIZE, config.buffer_size);
+        let request_scheduler = RequestScheduler::new(config.max_requests_per_caller);
+        let shared_config = Arc::new(RwLock::new(config.clone()));
         let (publisher, subscription_factory) = pubsub_connector(buf_size, config.buffer_rate_limit);
         let peer_message_subscription_factory = Arc::new(subscription_factory);
 
@@ -172,6 +311,31 @@ where
             config.buffer_size,
             config.buffer_rate_limit
         );
+        // Repair orphaned records before the services start to act on them
+        let transaction_db = TransactionDatabase::new(transaction_backend.clone());
+        let mut integrity_report =
+            IntegrityChecker::new(transaction_db.clone(), output_manager_database.clone()).check(true)?;
+
+        let consensus_manager = ConsensusManager::builder(config.network).build();
+        let (transaction_service_restarter, transaction_service_restart_receiver) = restart_channel();
+        let (utxo_scanner_restarter, utxo_scanner_restart_receiver) = restart_channel();
+        let transaction_service_initializer = TransactionServiceInitializer::new(
+            config.transaction_service_config,
+            peer_message_subscription_factory.clone(),
+            transaction_backend,
+            node_identity.clone(),
+            factories.clone(),
+            wallet_database.clone(),
+        )
+        .with_restart_receiver(transaction_service_restart_receiver);
+        // Mined transactions can only be verified when the wallet keeps its own header chain
+        #[cfg(feature = "header_sync")]
+        let transaction_service_initializer = if config.header_sync_service_config.enabled {
+            transaction_service_initializer.with_header_sync_verification()
+        } else {
+            transaction_service_initializer
+        };
+
         let stack = StackBuilder::new(shutdown_signal)
             .add_initializer(P2pInitializer::new(
                 config.p2p.clone(),
@@ -187,15 +351,11 @@ where
                 config.network.into(),
                 node_identity.clone(),
             ))
-            .add_initializer(KeyManagerInitializer::new(key_manager_backend, master_seed))
-            .add_initializer(TransactionServiceInitializer::new(
-                config.transaction_service_config,
-                peer_message_subscription_factory.clone(),
-                transaction_backend,
-                node_identity.clone(),
-                factories.clone(),
-                wallet_database.clone(),
-            ))
+            .add_initializer(
+                KeyManagerInitializer::new(key_manager_backend, master_seed)
+                    .with_key_usage_callbacks(extensions.key_usage_callbacks()),
+            )
+            .add_initializer(transaction_service_initializer)
             .add_initializer(LivenessInitializer::new(
                 LivenessConfig {
                     auto_ping_interval: Some(config.contacts_auto_ping_interval),
@@ -203,24 +363,36 @@ where
                     max_allowed_ping_failures: 0, // Peer with failed ping-pong will never be removed
                     ..Default::default()
                 },
-                peer_message_subscription_factory,
+                peer_message_subscription_factory.clone(),
             ))
             .add_initializer(ContactsServiceInitializer::new(
                 contacts_backend,
+                peer_message_subscription_factory,
                 config.contacts_auto_ping_interval,
                 config.contacts_online_ping_window,
             ))
-            .add_initializer(BaseNodeServiceInitializer::new(
-                config.base_node_service_config.clone(),
-                wallet_database.clone(),
-            ))
+            .add_initializer(
+                BaseNodeServiceInitializer::new(config.base_node_service_config.clone(), wallet_database.clone())
+                    .with_monitored_base_nodes(monitored_base_nodes),
+            )
             .add_initializer(WalletConnectivityInitializer::new(config.base_node_service_config))
-            .add_initializer(UtxoScannerServiceInitializer::new(
+            .add_initializer(
+                UtxoScannerServiceInitializer::new(wallet_database.clone(), factories.clone(), node_identity.clone())
+                    .with_restart_receiver(utxo_scanner_restart_receiver),
+            )
+            .add_initializer(DigestServiceInitializer::new(config.digest_service_config))
+            .add_initializer(DecoyServiceInitializer::new(config.decoy_service_config))
+            .add_initializer(InheritanceServiceInitializer::new(
+                config.inheritance_service_config,
                 wallet_database.clone(),
-                factories.clone(),
-                node_identity.clone(),
             ));
 
+        #[cfg(feature = "header_sync")]
+        let stack = stack.add_initializer(HeaderSyncServiceInitializer::new(
+            config.header_sync_service_config,
+            consensus_manager.clone(),
+        ));
+
         // Check if we have update config. FFI wallets don't do this, the update on mobile is done differently.
         let stack = if auto_update.is_update_enabled() {
             stack.add_initializer(SoftwareUpdaterService::new(
@@ -251,6 +423,11 @@ where
 
         let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
         let utxo_scanner_service_handle = handles.expect_handle::<UtxoScannerHandle>();
+        let digest_service_handle = handles.expect_handle::<DigestServiceHandle>();
+        let decoy_service_handle = handles.expect_handle::<DecoyServiceHandle>();
+        let inheritance_service_handle = handles.expect_handle::<InheritanceServiceHandle>();
+        #[cfg(feature = "header_sync")]
+        let header_sync_service_handle = handles.expect_handle::<HeaderSyncHandle>();
         let wallet_connectivity = handles.expect_handle::<WalletConnectivityHandle>();
         let updater_handle = if auto_update.is_update_enabled() {
             Some(handles.expect_handle::<SoftwareUpdaterHandle>())
@@ -258,6 +435,10 @@ where
             None
         };
 
+        for branch in extensions.key_branches() {
+            key_manager_handle.add_new_branch(branch.clone()).await?;
+        }
+
         persist_one_sided_payment_script_for_node_identity(&mut output_manager_handle, comms.node_identity())
             .await
             .map_err(|e| {
@@ -265,6 +446,16 @@ where
                 e
             })?;
 
+        integrity_report.restored_key_indices = output_manager_handle.restore_key_indices(KEY_INDEX_GAP_LIMIT).await?;
+        if !integrity_report.is_consistent() {
+            warn!(
+                target: LOG_TARGET,
+                "Repaired the wallet database on startup: {} orphaned output(s), {} transaction(s) without outputs",
+                integrity_report.orphaned_outputs.len(),
+                integrity_report.transactions_without_outputs.len()
+            );
+        }
+
         // Persist the comms node address and features after it has been spawned to capture any modifications made
         // during comms startup. In the case of a Tor Transport the public address could have been generated
         wallet_database.set_node_address(comms.node_identity().public_address())?;
@@ -274,6 +465,20 @@ where
             wallet_database.set_comms_identity_signature(identity_sig)?;
         }
 
+        let allow_list = parse_allow_listed_peers(&config.allow_listed_peers)?;
+        let mut connectivity = comms.connectivity();
+        for node_id in &allow_list {
+            connectivity.add_peer_to_allow_list(node_id.clone()).await?;
+        }
+        if config.allow_list_only {
+            info!(
+                target: LOG_TARGET,
+                "Allow list only mode is enabled, {} peer(s) are allow listed",
+                allow_list.len()
+            );
+            spawn_allow_list_enforcer(&comms, wallet_connectivity.clone(), allow_list);
+        }
+
         Ok(Self {
             network: config.network.into(),
             comms,
@@ -284,14 +489,23 @@ where
             transaction_service: transaction_service_handle,
             contacts_service: contacts_handle,
             base_node_service: base_node_service_handle,
+            digest_service: digest_service_handle,
+            decoy_service: decoy_service_handle,
+            inheritance_service: inheritance_service_handle,
+            #[cfg(feature = "header_sync")]
+            header_sync_service: header_sync_service_handle,
             utxo_scanner_service: utxo_scanner_service_handle,
             updater_service: updater_handle,
             wallet_connectivity,
             db: wallet_database,
             output_db: output_manager_database,
             factories,
-            #[cfg(feature = "test_harness")]
-            transaction_backend: transaction_backend_handle,
+            request_scheduler,
+            transaction_db,
+            consensus_manager,
+            transaction_service_restarter,
+            utxo_scanner_restarter,
+            config: shared_config,
             _u: PhantomData,
             _v: PhantomData,
             _w: PhantomData,
@@ -304,6 +518,314 @@ where
         self.comms.to_owned().wait_until_shutdown().await;
     }
 
+    /// Stops the given service and starts it again, without restarting comms or any other service. Existing handles to
+    /// the service keep working, but requests that are in progress while it restarts fail.
+    pub async fn restart_service(&self, service: ServiceKind) -> Result<(), WalletError> {
+        let restarter = match service {
+            ServiceKind::TransactionService => &self.transaction_service_restarter,
+            ServiceKind::UtxoScanner => &self.utxo_scanner_restarter,
+        };
+        info!(target: LOG_TARGET, "Restarting the {}", service);
+        restarter
+            .restart()
+            .await
+            .map_err(|e| WalletError::ServiceRestartFailed(service, e))?;
+        if service == ServiceKind::TransactionService {
+            // The restarted service is built from the config the wallet was started with
+            let config = self.config().transaction_service_config;
+            self.transaction_service.clone().set_config(config).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the consensus parameters of the network of the wallet at `height`, so that applications do not have to
+    /// hardcode values that differ between networks and change with upgrades
+    pub fn get_consensus_constants(&self, height: u64) -> ConsensusParameters {
+        let constants = self.consensus_manager.consensus_constants(height);
+        ConsensusParameters {
+            network: self.network.as_network(),
+            effective_from_height: constants.effective_from_height(),
+            blockchain_version: constants.blockchain_version(),
+            min_fee_per_gram: Fee::MINIMUM_FEE_PER_GRAM,
+            min_transaction_fee: Fee::MINIMUM_TRANSACTION_FEE,
+            coinbase_lock_height: constants.coinbase_lock_height(),
+            max_block_transaction_weight: constants.get_max_block_transaction_weight(),
+            max_script_byte_size: constants.get_max_script_byte_size(),
+            max_script_execution_cost: constants.max_script_execution_cost(),
+            max_opcode_version: constants.max_opcode_version(),
+        }
+    }
+
+    /// Returns the config that the wallet is currently running with, including any changes made by
+    /// [Wallet::reload_config]
+    pub fn config(&self) -> WalletConfig {
+        acquire_read_lock!(self.config).clone()
+    }
+
+    /// Applies a new config to the running wallet. The transaction and output manager service settings (e.g. the
+    /// broadcast intervals, timeouts and retry policies), fee and confirmation defaults and health check thresholds
+    /// take effect immediately. Any other setting that changed is listed in the returned [ConfigReload] and only takes
+    /// effect after the wallet is restarted.
+    pub async fn reload_config(&self, new_config: WalletConfig) -> Result<ConfigReload, WalletError> {
+        reload_config(
+            &self.config,
+            self.transaction_service.clone(),
+            self.output_manager_service.clone(),
+            new_config,
+        )
+        .await
+    }
+
+    /// Reloads the wallet config with `load_config` every time the process receives a SIGHUP, until the wallet shuts
+    /// down. The current config is kept if `load_config` fails.
+    #[cfg(unix)]
+    pub fn reload_config_on_sighup<F, E>(&self, load_config: F) -> Result<(), WalletError>
+    where
+        F: Fn() -> Result<WalletConfig, E> + Send + 'static,
+        E: fmt::Display,
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = signal(SignalKind::hangup()).map_err(|e| WalletError::ConfigReloadError(e.to_string()))?;
+        let mut shutdown_signal = self.comms.shutdown_signal();
+        let config = self.config.clone();
+        let transaction_service = self.transaction_service.clone();
+        let output_manager_service = self.output_manager_service.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    received = hangup.recv() => {
+                        if received.is_none() {
+                            break;
+                        }
+                        info!(target: LOG_TARGET, "SIGHUP received, reloading the wallet config");
+                        match load_config() {
+                            Ok(new_config) => {
+                                let result = reload_config(
+                                    &config,
+                                    transaction_service.clone(),
+                                    output_manager_service.clone(),
+                                    new_config,
+                                )
+                                .await;
+                                if let Err(e) = result {
+                                    error!(target: LOG_TARGET, "Could not reload the wallet config: {}", e);
+                                }
+                            },
+                            Err(e) => error!(target: LOG_TARGET, "Could not load the wallet config: {}", e),
+                        }
+                    },
+                    _ = shutdown_signal.wait() => break,
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Checks that the records of the output manager and transaction service are consistent with each other, see
+    /// [integrity](crate::integrity). The same check repairs the database when the wallet starts. If `repair` is
+    /// true, outputs that are encumbered to a transaction that does not exist are released or cancelled, and the key
+    /// manager indices are moved past the keys of the outputs in the database.
+    pub async fn check_integrity(&mut self, repair: bool) -> Result<IntegrityReport, WalletError> {
+        let mut report = IntegrityChecker::new(self.transaction_db.clone(), self.output_db.clone()).check(repair)?;
+        if repair {
+            report.restored_key_indices = self
+                .output_manager_service
+                .restore_key_indices(KEY_INDEX_GAP_LIMIT)
+                .await?;
+        }
+        Ok(report)
+    }
+
+    /// Pings comms and each service that accepts requests, and reports whether it replied in time. The UTXO scanner
+    /// and decoy service do not accept requests and are not checked.
+    pub async fn health_check(&self) -> HealthReport {
+        let probe = {
+            let config = acquire_read_lock!(self.config);
+            HealthProbe {
+                lag_threshold: config.health_check_lag_threshold,
+                timeout: config.health_check_timeout,
+            }
+        };
+        let mut connectivity = self.comms.connectivity();
+        let mut transaction_service = self.transaction_service.clone();
+        let mut output_manager_service = self.output_manager_service.clone();
+        let mut contacts_service = self.contacts_service.clone();
+        let mut base_node_service = self.base_node_service.clone();
+        let (comms, transactions, outputs, contacts, base_node) = futures::join!(
+            probe.check("comms", connectivity.get_connectivity_status()),
+            probe.check(
+                "transaction_service",
+                transaction_service.get_num_confirmations_required()
+            ),
+            probe.check("output_manager_service", output_manager_service.get_balance()),
+            probe.check("contacts_service", contacts_service.get_contacts()),
+            probe.check("base_node_service", base_node_service.get_chain_metadata()),
+        );
+        #[allow(unused_mut)]
+        let mut services = vec![comms, transactions, outputs, contacts, base_node];
+        #[cfg(feature = "header_sync")]
+        {
+            let mut header_sync_service = self.header_sync_service.clone();
+            services.push(
+                probe
+                    .check("header_sync_service", header_sync_service.get_verified_tip())
+                    .await,
+            );
+        }
+        HealthReport { services }
+    }
+
+    /// Produces a signed statement of the total value of the unspent outputs that the wallet controls at the tip that
+    /// the base node reported last, with an ownership proof for each output, in answer to the `challenge` of an
+    /// auditor. Outputs that were mined above the tip are left out. See [proof_of_reserves](crate::proof_of_reserves)
+    /// for what a verifier checks.
+    pub async fn generate_reserve_attestation(&self, challenge: &str) -> Result<ReserveAttestation, WalletError> {
+        let tip = self
+            .base_node_service
+            .clone()
+            .get_chain_metadata()
+            .await?
+            .ok_or(BaseNodeServiceError::NoChainMetadata)?;
+        let height = tip.height_of_longest_chain();
+        let outputs = self
+            .output_db
+            .fetch_all_unspent_outputs()
+            .map_err(OutputManagerError::from)?
+            .into_iter()
+            .filter(|output| output.mined_height.map_or(false, |mined_height| mined_height <= height))
+            .map(|output| {
+                (
+                    output.commitment,
+                    output.unblinded_output.value,
+                    output.unblinded_output.spending_key,
+                )
+            })
+            .collect();
+        let statement = ReserveStatement {
+            network: self.network.as_network(),
+            challenge: challenge.to_string(),
+            height,
+            block_hash: *tip.best_block(),
+        };
+        let attestation = statement.sign(self.comms.node_identity().secret_key().clone(), outputs)?;
+        info!(
+            target: LOG_TARGET,
+            "Attested reserves of {} in {} outputs at height {}",
+            attestation.total,
+            attestation.outputs.len(),
+            height
+        );
+        Ok(attestation)
+    }
+
+    /// Writes a diagnostics bundle to `path` for users to attach to bug reports. It holds the config, service health,
+    /// database statistics, peer connectivity and the most recent logs and errors, see [DiagnosticsBundle]. Secret
+    /// settings and key material are redacted, and the bundle is not written if any of the secrets of the wallet can
+    /// still be found in it.
+    pub async fn generate_diagnostics<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletError> {
+        let config = self.config();
+        let secrets = self.diagnostics_secrets(&config);
+        let config_json = serde_json::to_value(&config).map_err(|e| WalletError::DiagnosticsError(e.to_string()))?;
+        let mut bundle = DiagnosticsBundle::new(
+            env!("CARGO_PKG_VERSION").to_string(),
+            config.network.to_string(),
+            config_json,
+        );
+
+        bundle.services = self
+            .health_check()
+            .await
+            .services
+            .into_iter()
+            .map(|health| ServiceDiagnostics {
+                service: health.service.to_string(),
+                status: format!("{:?}", health.status),
+                response_time_ms: health.response_time.map(|t| t.as_millis()),
+            })
+            .collect();
+
+        let mut output_manager_service = self.output_manager_service.clone();
+        let mut transaction_service = self.transaction_service.clone();
+        let mut contacts_service = self.contacts_service.clone();
+        bundle.database = DatabaseStatistics {
+            db_file_size: fs::metadata(&config.db_file).ok().map(|m| m.len()),
+            unspent_outputs: output_manager_service.get_unspent_outputs().await.ok().map(|o| o.len()),
+            spent_outputs: output_manager_service.get_spent_outputs().await.ok().map(|o| o.len()),
+            completed_transactions: transaction_service
+                .get_completed_transactions()
+                .await
+                .ok()
+                .map(|t| t.len()),
+            cancelled_transactions: transaction_service
+                .get_cancelled_completed_transactions()
+                .await
+                .ok()
+                .map(|t| t.len()),
+            pending_inbound_transactions: transaction_service
+                .get_pending_inbound_transactions()
+                .await
+                .ok()
+                .map(|t| t.len()),
+            pending_outbound_transactions: transaction_service
+                .get_pending_outbound_transactions()
+                .await
+                .ok()
+                .map(|t| t.len()),
+            contacts: contacts_service.get_contacts().await.ok().map(|c| c.len()),
+        };
+
+        let mut connectivity = self.comms.connectivity();
+        let mut wallet_connectivity = self.wallet_connectivity.clone();
+        bundle.connectivity = ConnectivityDiagnostics {
+            comms_status: match connectivity.get_connectivity_status().await {
+                Ok(status) => format!("{:?}", status),
+                Err(e) => e.to_string(),
+            },
+            base_node_status: format!("{:?}", wallet_connectivity.get_connectivity_status()),
+            base_node: wallet_connectivity.get_current_base_node_id().map(|id| id.to_string()),
+            known_peers: self.comms.peer_manager().count().await,
+            connected_peers: connectivity
+                .get_active_connections()
+                .await
+                .map(|conns| conns.iter().map(|conn| conn.peer_node_id().to_string()).collect())
+                .unwrap_or_default(),
+        };
+
+        if let Some(log_dir) = config.log_dir.as_ref() {
+            if let Err(e) = bundle.add_logs(log_dir, &secrets) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not add the logs to the diagnostics bundle: {}", e
+                );
+            }
+        }
+
+        bundle.write_to_file(path.as_ref(), &secrets)?;
+        info!(
+            target: LOG_TARGET,
+            "Wrote a diagnostics bundle to '{}'",
+            path.as_ref().display()
+        );
+        Ok(())
+    }
+
+    /// The secrets of the wallet that must never appear in a diagnostics bundle
+    fn diagnostics_secrets(&self, config: &WalletConfig) -> Vec<String> {
+        let mut secrets = vec![self.comms.node_identity().secret_key().to_hex()];
+        if let Ok(seed_words) = self.get_seed_words(&MnemonicLanguage::English) {
+            secrets.push(seed_words.join(" "));
+        }
+        let passwords = config.password.iter().chain(
+            config
+                .grpc_authentication
+                .username_password()
+                .map(|(_, password)| password),
+        );
+        secrets.extend(passwords.map(|password| String::from_utf8_lossy(password.reveal()).to_string()));
+        secrets
+    }
+
     /// This function will set the base node that the wallet uses to broadcast transactions, monitor outputs, and
     /// monitor the base node state.
     pub async fn set_base_node_peer(
@@ -316,6 +838,25 @@ where
             public_key, address
         );
 
+        match self.db.get_base_node_identity(&address)? {
+            Some(pinned_public_key) if pinned_public_key != public_key => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Base node at {} presented public key {}, but {} is pinned for it",
+                    address,
+                    public_key,
+                    pinned_public_key
+                );
+                return Err(WalletError::BaseNodeIdentityChanged {
+                    address,
+                    pinned_public_key,
+                    presented_public_key: public_key,
+                });
+            },
+            Some(_) => {},
+            None => self.db.set_base_node_identity(address.clone(), public_key.clone())?,
+        }
+
         if let Some(current_node) = self.wallet_connectivity.get_current_base_node_id() {
             self.comms
                 .connectivity()
@@ -361,6 +902,324 @@ where
         Ok(())
     }
 
+    /// Pins `public_key` as the identity of the base node at `address` and sets it as the base node peer. This is how
+    /// a user accepts a base node that presents another identity than the one that was pinned for its address, e.g.
+    /// after a `BaseNodeIdentityChanged` event.
+    pub async fn trust_new_base_node_identity(
+        &mut self,
+        public_key: CommsPublicKey,
+        address: Multiaddr,
+    ) -> Result<(), WalletError> {
+        warn!(
+            target: LOG_TARGET,
+            "Trusting public key {} as the new identity of the base node at {}", public_key, address
+        );
+        self.db.set_base_node_identity(address.clone(), public_key.clone())?;
+        self.set_base_node_peer(public_key, address).await
+    }
+
+    /// Bans the peer for the given duration and disconnects it. The `reason` is persisted in the peer database. Allow
+    /// listed peers are not banned.
+    pub async fn ban_peer(&self, node_id: NodeId, duration: Duration, reason: String) -> Result<(), WalletError> {
+        self.comms
+            .connectivity()
+            .ban_peer_until(node_id, duration, reason)
+            .await?;
+        Ok(())
+    }
+
+    /// Lifts the ban on the peer, if it is banned
+    pub async fn unban_peer(&self, node_id: &NodeId) -> Result<(), WalletError> {
+        self.comms.peer_manager().unban_peer(node_id).await?;
+        Ok(())
+    }
+
+    /// Returns the peers that are currently banned
+    pub async fn list_banned_peers(&self) -> Result<Vec<Peer>, WalletError> {
+        let query = PeerQuery::new().select_where(|p| p.is_banned());
+        let peers = self.comms.peer_manager().perform_query(query).await?;
+        Ok(peers)
+    }
+
+    /// Writes the node identity, Tor identity and known peers of the wallet to `path`, so that the wallet can be moved
+    /// to another device without rediscovering the network. See [NetworkState].
+    pub async fn export_network_state<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletError> {
+        let node_identity = self.comms.node_identity();
+        let peers = self.comms.peer_manager().perform_query(PeerQuery::new()).await?;
+        let num_peers = peers.len();
+        let state = NetworkState::new(
+            node_identity.public_key().clone(),
+            node_identity.public_address(),
+            node_identity.features(),
+            node_identity.identity_signature_read().as_ref().cloned(),
+            self.db.get_tor_id()?,
+            peers,
+        );
+        state.write_to_file(path.as_ref())?;
+        info!(
+            target: LOG_TARGET,
+            "Exported the network state with {} peer(s) to '{}'",
+            num_peers,
+            path.as_ref().display()
+        );
+        Ok(())
+    }
+
+    /// Imports a network state written by [Wallet::export_network_state] on a wallet with the same comms key, i.e. one
+    /// restored from the same seed. The peers that are not yet known are added to the peer database straight away. The
+    /// node address, features and Tor identity are stored in the wallet database and are used the next time the wallet
+    /// starts. Returns the number of peers that were added.
+    pub async fn import_network_state<P: AsRef<Path>>(&self, path: P) -> Result<usize, WalletError> {
+        let state = NetworkState::read_from_file(path.as_ref())?;
+        let node_identity = self.comms.node_identity();
+        if state.public_key != *node_identity.public_key() {
+            return Err(WalletError::NetworkStateError(format!(
+                "The network state belongs to node '{}', not to this wallet",
+                state.public_key
+            )));
+        }
+
+        self.db.set_node_address(state.public_address)?;
+        self.db.set_node_features(state.features)?;
+        if let Some(identity_signature) = state.identity_signature {
+            self.db.set_comms_identity_signature(identity_signature)?;
+        }
+        if let Some(tor_identity) = state.tor_identity {
+            self.db.set_tor_identity(tor_identity)?;
+        }
+
+        let peer_manager = self.comms.peer_manager();
+        let mut num_added = 0;
+        for peer in state.peers {
+            if peer.node_id == *node_identity.node_id() || peer_manager.exists_node_id(&peer.node_id).await {
+                continue;
+            }
+            peer_manager.add_peer(peer).await?;
+            num_added += 1;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Imported the network state from '{}', {} peer(s) added",
+            path.as_ref().display(),
+            num_added
+        );
+        Ok(num_added)
+    }
+
+    /// Exports the contacts, transactions and transaction tags of the wallet into a [PortableWalletDump], which can be
+    /// imported into a wallet with any storage backend with [Wallet::import_data].
+    pub async fn export_data(&mut self) -> Result<PortableWalletDump, WalletError> {
+        let contacts = self.contacts_service.get_contacts().await?;
+        let mut completed = self.transaction_service.get_completed_transactions().await?;
+        completed.extend(self.transaction_service.get_cancelled_completed_transactions().await?);
+        let mut inbound = self.transaction_service.get_pending_inbound_transactions().await?;
+        inbound.extend(
+            self.transaction_service
+                .get_cancelled_pending_inbound_transactions()
+                .await?,
+        );
+        let mut outbound = self.transaction_service.get_pending_outbound_transactions().await?;
+        outbound.extend(
+            self.transaction_service
+                .get_cancelled_pending_outbound_transactions()
+                .await?,
+        );
+        let tags = self.transaction_service.get_all_transaction_tags().await?;
+
+        let dump = PortableWalletDump::new(
+            contacts.into_iter().map(Into::into).collect(),
+            completed.into_values().collect(),
+            inbound.into_values().collect(),
+            outbound.into_values().collect(),
+            tags.into_iter().map(|(tx_id, tags)| (tx_id.as_u64(), tags)).collect(),
+        );
+        info!(
+            target: LOG_TARGET,
+            "Exported {} contact(s) and {} transaction(s)",
+            dump.contacts.len(),
+            dump.num_transactions()
+        );
+        Ok(dump)
+    }
+
+    /// Imports a dump created with [Wallet::export_data]. Contacts are added or updated, while transactions that
+    /// already exist in this wallet are left as they are.
+    pub async fn import_data(&mut self, dump: PortableWalletDump) -> Result<PortableImportSummary, WalletError> {
+        let mut summary = PortableImportSummary::default();
+        let existing_contacts = self
+            .contacts_service
+            .get_contacts()
+            .await?
+            .into_iter()
+            .map(|c| c.public_key)
+            .collect::<HashSet<_>>();
+        for contact in dump.contacts {
+            if !existing_contacts.contains(&contact.public_key) {
+                summary.contacts += 1;
+            }
+            self.contacts_service.upsert_contact(contact.into()).await?;
+        }
+        summary.transactions = self
+            .transaction_service
+            .import_transactions(
+                dump.completed_transactions,
+                dump.inbound_transactions,
+                dump.outbound_transactions,
+            )
+            .await?;
+        let existing_tags = self.transaction_service.get_all_transaction_tags().await?;
+        for (tx_id, tags) in dump.transaction_tags {
+            let tx_id = TxId::from(tx_id);
+            for tag in tags {
+                if existing_tags.get(&tx_id).map_or(false, |t| t.contains(&tag)) {
+                    continue;
+                }
+                // Tags can outlive the transaction they were added to
+                match self.transaction_service.tag_transaction(tx_id, tag).await {
+                    Ok(()) => summary.transaction_tags += 1,
+                    Err(TransactionServiceError::TransactionDoesNotExistError) => {},
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        info!(target: LOG_TARGET, "Imported wallet data: {:?}", summary);
+        Ok(summary)
+    }
+
+    /// Searches the transaction messages, transaction tags and contact aliases of the wallet for entries containing
+    /// words that start with every word of `query`. Results are ordered by relevance, and a transaction that matches
+    /// on both its message and a tag is only returned once.
+    pub async fn search(&mut self, query: &str) -> Result<Vec<SearchResult>, WalletError> {
+        let matches = self.db.search(query, MAX_SEARCH_RESULTS)?;
+        let mut seen = HashSet::with_capacity(matches.len());
+        let mut results = Vec::with_capacity(matches.len());
+        for m in matches {
+            if !seen.insert(m.clone()) {
+                continue;
+            }
+            match m {
+                SearchMatch::Transaction(tx_id) => {
+                    // Tags can outlive the transaction they were added to
+                    if let Some(tx) = self.transaction_service.get_any_transaction(tx_id).await? {
+                        results.push(SearchResult::Transaction(Box::new(tx)));
+                    }
+                },
+                SearchMatch::Contact(public_key) => match self.contacts_service.get_contact(public_key).await {
+                    Ok(contact) => results.push(SearchResult::Contact(contact)),
+                    Err(ContactsServiceError::ContactsServiceStorageError(
+                        ContactsServiceStorageError::ValueNotFound(_),
+                    )) => {},
+                    Err(e) => return Err(e.into()),
+                },
+            }
+        }
+        Ok(results)
+    }
+
+    /// Marks transactions as seen by the owner of the wallet. The read state is synced between devices by
+    /// [Wallet::cloud_sync].
+    pub fn mark_transactions_read(&self, tx_ids: &[TxId]) -> Result<(), WalletError> {
+        let mut read = self
+            .get_read_transactions()?
+            .into_iter()
+            .map(|tx_id| tx_id.as_u64())
+            .collect::<BTreeSet<_>>();
+        read.extend(tx_ids.iter().map(|tx_id| tx_id.as_u64()));
+        let value = serde_json::to_string(&read).map_err(WalletStorageError::from)?;
+        self.db.set_client_key_value(READ_TRANSACTIONS_KEY.to_string(), value)?;
+        Ok(())
+    }
+
+    /// The transactions that the owner of the wallet has seen
+    pub fn get_read_transactions(&self) -> Result<HashSet<TxId>, WalletError> {
+        match self.db.get_client_key_value(READ_TRANSACTIONS_KEY.to_string())? {
+            Some(value) => {
+                let read = serde_json::from_str::<Vec<u64>>(&value).map_err(WalletStorageError::from)?;
+                Ok(read.into_iter().map(TxId::from).collect())
+            },
+            None => Ok(HashSet::new()),
+        }
+    }
+
+    /// The key that the [cloud sync](crate::cloud_sync) snapshots of this wallet are encrypted with. Every wallet that
+    /// was created from the same seed has the same key.
+    pub fn cloud_sync_key(&self) -> Result<SyncKey, WalletError> {
+        let master_seed = self
+            .db
+            .get_master_seed()?
+            .ok_or_else(|| WalletError::CloudSyncError("Cipher Seed not found".to_string()))?;
+        Ok(SyncKey::from_seed(&master_seed))
+    }
+
+    /// Merges the contacts, transaction tags and read state of this wallet with the snapshot that the other devices
+    /// of the owner uploaded, adds the entries that this wallet is missing and uploads the merged snapshot. Tags of
+    /// transactions that this wallet does not have (yet) are kept in the snapshot, so they are added by a later sync.
+    pub async fn cloud_sync<S: SyncBlobStore>(
+        &mut self,
+        engine: &CloudSyncEngine<S>,
+    ) -> Result<SyncSummary, WalletError> {
+        let contacts = self.contacts_service.get_contacts().await?;
+        let tags = self.transaction_service.get_all_transaction_tags().await?;
+        let read = self.get_read_transactions()?;
+        let local = SyncSnapshot::new(
+            contacts.into_iter().map(Into::into).collect(),
+            tags.into_iter()
+                .map(|(tx_id, tags)| (tx_id.as_u64(), tags.into_iter().collect()))
+                .collect(),
+            read.into_iter().map(|tx_id| tx_id.as_u64()).collect(),
+        );
+
+        let remote = engine.pull().await?;
+        let mut merged = local.clone();
+        let mut summary = SyncSummary::default();
+        if let Some(remote) = &remote {
+            merged.merge(remote);
+
+            let local_contacts = local.contacts.iter().map(|c| &c.public_key).collect::<HashSet<_>>();
+            for contact in &merged.contacts {
+                if !local_contacts.contains(&contact.public_key) {
+                    self.contacts_service.upsert_contact(contact.clone().into()).await?;
+                    summary.contacts += 1;
+                }
+            }
+            for (tx_id, tags) in &merged.transaction_tags {
+                for tag in tags {
+                    if local.transaction_tags.get(tx_id).map_or(false, |t| t.contains(tag)) {
+                        continue;
+                    }
+                    match self
+                        .transaction_service
+                        .tag_transaction(TxId::from(*tx_id), tag.clone())
+                        .await
+                    {
+                        Ok(()) => summary.transaction_tags += 1,
+                        Err(TransactionServiceError::TransactionDoesNotExistError) => {},
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+            let new_read = merged
+                .read_transactions
+                .difference(&local.read_transactions)
+                .map(|tx_id| TxId::from(*tx_id))
+                .collect::<Vec<_>>();
+            if !new_read.is_empty() {
+                self.mark_transactions_read(&new_read)?;
+                summary.read_transactions = new_read.len();
+            }
+        }
+
+        if remote.as_ref() != Some(&merged) {
+            engine.push(&merged).await?;
+            summary.uploaded = true;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Synced the wallet state with the cloud: {:?}", summary
+        );
+        Ok(summary)
+    }
+
     pub async fn get_base_node_peer(&mut self) -> Option<Peer> {
         self.wallet_connectivity.get_current_base_node_peer()
     }
@@ -401,6 +1260,45 @@ where
         self.updater_service.as_ref().cloned()
     }
 
+    /// Returns the number of store-and-forward messages this wallet is holding for other peers
+    pub async fn saf_stored_message_count(&self) -> Result<usize, WalletError> {
+        let count = self
+            .store_and_forward_requester
+            .clone()
+            .get_stored_message_count()
+            .await?;
+        Ok(count)
+    }
+
+    /// Removes the expired store-and-forward messages now, returning the number of messages removed
+    pub async fn purge_expired_saf_messages(&self) -> Result<usize, WalletError> {
+        let num_removed = self
+            .store_and_forward_requester
+            .clone()
+            .remove_expired_messages()
+            .await?;
+        Ok(num_removed)
+    }
+
+    /// Limits the number of store-and-forward messages held for the given peer. Passing `None` removes the limit.
+    pub async fn set_saf_peer_quota(
+        &self,
+        public_key: CommsPublicKey,
+        quota: Option<usize>,
+    ) -> Result<(), WalletError> {
+        self.store_and_forward_requester
+            .clone()
+            .set_peer_quota(public_key, quota)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the store-and-forward delivery statistics since the wallet was started
+    pub async fn saf_statistics(&self) -> Result<SafStatistics, WalletError> {
+        let statistics = self.store_and_forward_requester.clone().get_statistics().await?;
+        Ok(statistics)
+    }
+
     /// Import an external spendable UTXO into the wallet as a non-rewindable/non-recoverable UTXO. The output will be
     /// added to the Output Manager and made EncumberedToBeReceived. A faux incoming transaction will be created to
     /// provide a record of the event. The TxId of the generated transaction is returned.
@@ -670,6 +1568,151 @@ where
         }
     }
 
+    /// Pays `amount` to this wallet in an output that belongs to a side chain. The side chain features are checked
+    /// against the consensus limits before the transaction is created.
+    pub async fn create_sidechain_output(
+        &mut self,
+        sidechain_features: SideChainFeatures,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        let output_features = OutputFeaturesBuilder::new()
+            .with_sidechain_features(sidechain_features)
+            .build()?;
+        let tx_id = self
+            .transaction_service
+            .send_transaction(
+                self.comms.node_identity().public_key().clone(),
+                amount,
+                output_features,
+                fee_per_gram,
+                message,
+                None,
+            )
+            .await?;
+        Ok(tx_id)
+    }
+
+    /// Locks `amount` in a vault output. The vault can be recovered with the private key of `recovery_key`
+    /// `recovery_delay` blocks from now, or spent by this wallet alone once the chain reaches `unlock_height`.
+    pub async fn create_vault(
+        &mut self,
+        amount: MicroTari,
+        recovery_key: PublicKey,
+        recovery_delay: u64,
+        unlock_height: u64,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        let tx_id = TxId::new_random();
+        let (_fee, transaction) = self
+            .output_manager_service
+            .create_vault_transaction(
+                tx_id,
+                amount,
+                recovery_key,
+                recovery_delay,
+                unlock_height,
+                fee_per_gram,
+                message.clone(),
+            )
+            .await?;
+        self.transaction_service
+            .submit_transaction(tx_id, transaction, amount, message)
+            .await?;
+        Ok(tx_id)
+    }
+
+    /// Returns the unspent vault outputs of this wallet
+    pub async fn get_vault_outputs(&mut self) -> Result<Vec<VaultOutput>, WalletError> {
+        Ok(self.output_manager_service.get_vault_outputs().await?)
+    }
+
+    /// Moves the funds of a vault back to this wallet using the recovery key, before the vault unlocks
+    pub async fn recover_vault(
+        &mut self,
+        output_hash: HashOutput,
+        recovery_key: PrivateKey,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        let (tx_id, _fee, amount, transaction) = self
+            .output_manager_service
+            .create_vault_recovery_transaction(output_hash, recovery_key, fee_per_gram)
+            .await?;
+        self.transaction_service
+            .submit_transaction(tx_id, transaction, amount, message)
+            .await?;
+        Ok(tx_id)
+    }
+
+    /// Issues `total_supply` units of a new fungible token with the given symbol to this wallet. The registration
+    /// output locks `amount` and is signed with the issuer key of the wallet.
+    pub async fn issue_token(
+        &mut self,
+        symbol: String,
+        total_supply: u64,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        let tx_id = TxId::new_random();
+        let (_fee, transaction) = self
+            .output_manager_service
+            .create_token_registration_transaction(tx_id, amount, symbol, total_supply, fee_per_gram, message.clone())
+            .await?;
+        self.transaction_service
+            .submit_transaction(tx_id, transaction, amount, message)
+            .await?;
+        Ok(tx_id)
+    }
+
+    /// Returns the balances of the fungible tokens held by this wallet
+    pub async fn get_token_balances(&mut self) -> Result<Vec<TokenBalance>, WalletError> {
+        Ok(self.output_manager_service.get_token_balances().await?)
+    }
+
+    /// Mints the unique asset `unique_id` to this wallet in an output worth `amount`. The issuer key of this wallet is
+    /// the parent of the asset and signs the mint.
+    pub async fn mint_unique_asset(
+        &mut self,
+        unique_id: Vec<u8>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        let tx_id = TxId::new_random();
+        let (_fee, transaction) = self
+            .output_manager_service
+            .create_unique_asset_mint_transaction(tx_id, amount, unique_id, fee_per_gram, message.clone())
+            .await?;
+        self.transaction_service
+            .submit_transaction(tx_id, transaction, amount, message)
+            .await?;
+        Ok(tx_id)
+    }
+
+    /// Transfers a unique asset held by this wallet to `dest_pubkey`. The fee is paid from the value of the asset
+    /// output.
+    pub async fn transfer_unique_asset(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        asset_id: FixedHash,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        Ok(self
+            .transaction_service
+            .transfer_unique_asset(dest_pubkey, asset_id, fee_per_gram, message)
+            .await?)
+    }
+
+    /// Returns the unique assets held by this wallet, including assets received in one-sided payments
+    pub async fn get_owned_assets(&mut self) -> Result<Vec<OwnedAsset>, WalletError> {
+        Ok(self.output_manager_service.get_owned_assets().await?)
+    }
+
     /// Apply encryption to all the Wallet db backends. The Wallet backend will test if the db's are already encrypted
     /// in which case this will fail.
     pub async fn apply_encryption(&mut self, passphrase: SafePassword) -> Result<(), WalletError> {
@@ -707,6 +1750,41 @@ where
         let seed_words = master_seed.to_mnemonic(*language, None)?;
         Ok(seed_words)
     }
+
+    /// Checks the words that the user wrote down against the seed words at the given positions, counted from zero, so
+    /// that a backup can be verified without passing the seed words to the caller. The words may be in any of the
+    /// mnemonic languages and are compared ignoring case and diacritics.
+    pub fn verify_seed_backup(&self, indices: &[usize], words: &[String]) -> Result<bool, WalletError> {
+        if indices.is_empty() || indices.len() != words.len() {
+            return Err(WalletError::ArgumentError {
+                argument: "words".to_string(),
+                value: words.len().to_string(),
+                message: format!("Expected a word for each of the {} positions", indices.len()),
+            });
+        }
+        let seed_length = self.get_seed_words(&MnemonicLanguage::English)?.len();
+        if let Some(index) = indices.iter().find(|index| **index >= seed_length) {
+            return Err(WalletError::ArgumentError {
+                argument: "indices".to_string(),
+                value: index.to_string(),
+                message: format!("The seed has {} words", seed_length),
+            });
+        }
+        let words = words.iter().map(|word| word.trim().to_string()).collect::<Vec<_>>();
+        // A few words may be in the word lists of several languages, so they are checked in each of them
+        for language in MnemonicLanguage::detect_candidates(&words) {
+            let seed_words = self.get_seed_words(&language)?;
+            let matches = indices.iter().zip(&words).all(|(index, word)| {
+                // The words are compared by their index in the word list, which normalizes case and diacritics
+                mnemonic::to_bytes_with_language(&[word.clone()], &language).ok() ==
+                    mnemonic::to_bytes_with_language(&[seed_words[*index].clone()], &language).ok()
+            });
+            if matches {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
 }
 
 pub fn read_or_create_master_seed<T: WalletBackend + 'static>(
@@ -742,6 +1820,30 @@ pub fn read_or_create_master_seed<T: WalletBackend + 'static>(
     Ok(master_seed)
 }
 
+/// Tags a database that has no network yet with the network of the wallet, and refuses to open the database of
+/// another network, as its keys and transactions do not belong to this one. A database is moved to another network
+/// explicitly with [WalletDatabase::set_network].
+pub(crate) fn check_database_network<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+    network: Network,
+) -> Result<(), WalletError> {
+    match db.get_network()? {
+        Some(database) if database != network => Err(WalletError::NetworkMismatch {
+            database,
+            configured: network,
+        }),
+        Some(_) => Ok(()),
+        None => {
+            info!(
+                target: LOG_TARGET,
+                "Tagging the wallet database with the {} network", network
+            );
+            db.set_network(network)?;
+            Ok(())
+        },
+    }
+}
+
 pub fn derive_comms_secret_key(master_seed: &CipherSeed) -> Result<CommsSecretKey, WalletError> {
     let comms_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
         master_seed.clone(),
@@ -773,3 +1875,115 @@ async fn persist_one_sided_payment_script_for_node_identity(
     output_manager_service.add_known_script(known_script).await?;
     Ok(())
 }
+
+/// Parses the hex public keys of the allow listed peers into their node ids
+fn parse_allow_listed_peers(peers: &[String]) -> Result<Vec<NodeId>, WalletError> {
+    peers
+        .iter()
+        .map(|peer| {
+            let public_key = CommsPublicKey::from_hex(peer).map_err(|e| WalletError::ArgumentError {
+                argument: "allow_listed_peers".to_string(),
+                value: peer.clone(),
+                message: e.to_string(),
+            })?;
+            Ok(NodeId::from_public_key(&public_key))
+        })
+        .collect()
+}
+
+/// Parses the configured base node peers, given as `<public key>::<address>`
+fn parse_base_node_service_peers(peers: &[String]) -> Result<Vec<Peer>, WalletError> {
+    peers
+        .iter()
+        .map(|peer| {
+            let seed = SeedPeer::from_str(peer).map_err(|e| WalletError::ArgumentError {
+                argument: "base_node_service_peers".to_string(),
+                value: peer.clone(),
+                message: e.to_string(),
+            })?;
+            Ok(Peer::from(seed))
+        })
+        .collect()
+}
+
+/// Disconnects every peer that connects to the wallet, other than the allow listed peers and the current base node,
+/// until comms shuts down
+fn spawn_allow_list_enforcer(
+    comms: &CommsNode,
+    wallet_connectivity: WalletConnectivityHandle,
+    allow_list: Vec<NodeId>,
+) {
+    let mut connectivity_events = comms.connectivity().get_event_subscription();
+    let mut shutdown_signal = comms.shutdown_signal();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                event = connectivity_events.recv() => match event {
+                    Ok(ConnectivityEvent::PeerConnected(mut conn)) => {
+                        let node_id = conn.peer_node_id().clone();
+                        let is_base_node = wallet_connectivity.get_current_base_node_id().as_ref() == Some(&node_id);
+                        if is_base_node || allow_list.contains(&node_id) {
+                            continue;
+                        }
+                        debug!(
+                            target: LOG_TARGET,
+                            "Disconnecting peer '{}' because it is not allow listed", node_id
+                        );
+                        if let Err(e) = conn.disconnect().await {
+                            warn!(target: LOG_TARGET, "Could not disconnect peer '{}': {}", node_id, e);
+                        }
+                    },
+                    Ok(_) => {},
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Allow list enforcer missed {} connectivity event(s)", n);
+                    },
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                _ = shutdown_signal.wait() => break,
+            }
+        }
+    });
+}
+
+/// Applies `new_config` to the running services and stores it as the current config
+async fn reload_config(
+    config: &RwLock<WalletConfig>,
+    mut transaction_service: TransactionServiceHandle,
+    mut output_manager_service: OutputManagerHandle,
+    mut new_config: WalletConfig,
+) -> Result<ConfigReload, WalletError> {
+    // The running config has the light mode overrides applied, so they are applied to the new config before comparing
+    new_config.apply_light_mode();
+    let old = serde_json::to_value(&*acquire_read_lock!(config))
+        .map_err(|e| WalletError::ConfigReloadError(e.to_string()))?;
+    let new = serde_json::to_value(&new_config).map_err(|e| WalletError::ConfigReloadError(e.to_string()))?;
+    let requires_restart = match (old, new) {
+        (serde_json::Value::Object(old), serde_json::Value::Object(new)) => new
+            .into_iter()
+            .filter(|(key, value)| !LIVE_CONFIG_SETTINGS.contains(&key.as_str()) && old.get(key) != Some(value))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>(),
+        _ => {
+            return Err(WalletError::ConfigReloadError(
+                "The wallet config is not a table".to_string(),
+            ))
+        },
+    };
+
+    transaction_service
+        .set_config(new_config.transaction_service_config.clone())
+        .await?;
+    output_manager_service
+        .set_config(new_config.output_manager_service_config.clone())
+        .await?;
+    *acquire_write_lock!(config) = new_config;
+
+    for key in &requires_restart {
+        warn!(
+            target: LOG_TARGET,
+            "Config setting `{}` changed but will only be applied when the wallet is restarted", key
+        );
+    }
+    info!(target: LOG_TARGET, "Wallet config reloaded");
+    Ok(ConfigReload { requires_restart })
+}
@@ -0,0 +1,287 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Runs several wallets in one process. Each wallet is started on its own tokio runtime with its own comms stack and
+//! shutdown signal, so a busy wallet does not hold up the others and wallets can be started and stopped independently.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use log::*;
+use tari_comms::{
+    multiaddr::{Multiaddr, Protocol},
+    NodeIdentity,
+};
+use tari_core::transactions::CryptoFactories;
+use tari_key_manager::cipher_seed::CipherSeed;
+use tari_p2p::{auto_update::AutoUpdateConfig, P2pConfig, PeerSeedsConfig, TransportType};
+use tari_shutdown::Shutdown;
+use tokio::runtime::{self, Runtime};
+
+use crate::{
+    contacts_service::storage::database::ContactsBackend,
+    error::WalletError,
+    extensions::WalletExtensions,
+    key_manager_service::storage::database::KeyManagerBackend,
+    output_manager_service::storage::database::{OutputManagerBackend, OutputManagerDatabase},
+    storage::database::{WalletBackend, WalletDatabase},
+    transaction_service::storage::database::TransactionBackend,
+    Wallet,
+    WalletConfig,
+};
+
+const LOG_TARGET: &str = "wallet::wallet_manager";
+
+/// The number of runtime worker threads given to each wallet by default
+const DEFAULT_WORKER_THREADS: usize = 2;
+
+/// Everything [Wallet::start] needs to start a wallet, apart from the shutdown signal which the manager owns
+pub struct WalletParams<T, U, V, W, X> {
+    pub config: WalletConfig,
+    pub peer_seeds: PeerSeedsConfig,
+    pub auto_update: AutoUpdateConfig,
+    pub node_identity: Arc<NodeIdentity>,
+    pub factories: CryptoFactories,
+    pub wallet_database: WalletDatabase<T>,
+    pub output_manager_database: OutputManagerDatabase<V>,
+    pub transaction_backend: U,
+    pub output_manager_backend: V,
+    pub contacts_backend: W,
+    pub key_manager_backend: X,
+    pub master_seed: CipherSeed,
+    pub extensions: WalletExtensions,
+}
+
+struct ManagedWallet<T, U, V, W, X> {
+    wallet: Wallet<T, U, V, W, X>,
+    shutdown: Shutdown,
+    runtime: Runtime,
+    peer_database_path: PathBuf,
+    listener_addresses: Vec<Multiaddr>,
+}
+
+/// Starts, tracks and stops named wallets running in the same process
+pub struct WalletManager<T, U, V, W, X> {
+    worker_threads: usize,
+    wallets: HashMap<String, ManagedWallet<T, U, V, W, X>>,
+}
+
+impl<T, U, V, W, X> WalletManager<T, U, V, W, X>
+where
+    T: WalletBackend + 'static,
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+    W: ContactsBackend + 'static,
+    X: KeyManagerBackend + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            worker_threads: DEFAULT_WORKER_THREADS,
+            wallets: HashMap::new(),
+        }
+    }
+
+    /// Sets the number of worker threads of the runtime created for each wallet started after this call
+    pub fn with_worker_threads(mut self, worker_threads: usize) -> Self {
+        self.worker_threads = worker_threads.max(1);
+        self
+    }
+
+    /// Starts a wallet on a new runtime and returns a handle to it. Fails if a wallet with the same name is running, or
+    /// if the wallet would share a node identity, peer database or listener address with a running wallet.
+    pub async fn start_wallet(
+        &mut self,
+        name: &str,
+        params: WalletParams<T, U, V, W, X>,
+    ) -> Result<Wallet<T, U, V, W, X>, WalletError> {
+        if self.wallets.contains_key(name) {
+            return Err(WalletError::WalletAlreadyRunning(name.to_string()));
+        }
+        let peer_database_path = peer_database_path(&params.config.p2p);
+        let listener_addresses = listener_addresses(&params.config.p2p);
+        for (other, managed) in &self.wallets {
+            let conflict = |reason: &str| WalletError::ConflictingWallet {
+                name: name.to_string(),
+                other: other.clone(),
+                reason: reason.to_string(),
+            };
+            if managed.wallet.comms.node_identity().public_key() == params.node_identity.public_key() {
+                return Err(conflict("both use the same node identity"));
+            }
+            if managed.peer_database_path == peer_database_path {
+                return Err(conflict("both use the same peer database"));
+            }
+            if listener_addresses
+                .iter()
+                .any(|a| managed.listener_addresses.contains(a))
+            {
+                return Err(conflict("both listen on the same address"));
+            }
+        }
+
+        let runtime = runtime::Builder::new_multi_thread()
+            .worker_threads(self.worker_threads)
+            .thread_name(format!("wallet-{}", name))
+            .enable_all()
+            .build()
+            .map_err(|e| WalletError::RuntimeError(e.to_string()))?;
+        let shutdown = Shutdown::new();
+        let shutdown_signal = shutdown.to_signal();
+
+        // Services spawn their tasks on the runtime that starts the wallet, so it is started on its own runtime
+        let result = runtime
+            .spawn(async move {
+                Wallet::start(
+                    params.config,
+                    params.peer_seeds,
+                    params.auto_update,
+                    params.node_identity,
+                    params.factories,
+                    params.wallet_database,
+                    params.output_manager_database,
+                    params.transaction_backend,
+                    params.output_manager_backend,
+                    params.contacts_backend,
+                    params.key_manager_backend,
+                    shutdown_signal,
+                    params.master_seed,
+                    params.extensions,
+                )
+                .await
+            })
+            .await
+            .map_err(|e| WalletError::RuntimeError(e.to_string()));
+        let wallet = match result {
+            Ok(Ok(wallet)) => wallet,
+            Ok(Err(e)) | Err(e) => {
+                // A runtime cannot be dropped from async code, so it is shut down without blocking
+                runtime.shutdown_background();
+                return Err(e);
+            },
+        };
+
+        info!(
+            target: LOG_TARGET,
+            "Started wallet `{}` with node ID {}",
+            name,
+            wallet.comms.node_identity().node_id()
+        );
+        self.wallets.insert(name.to_string(), ManagedWallet {
+            wallet: wallet.clone(),
+            shutdown,
+            runtime,
+            peer_database_path,
+            listener_addresses,
+        });
+        Ok(wallet)
+    }
+
+    pub fn get_wallet(&self, name: &str) -> Option<&Wallet<T, U, V, W, X>> {
+        self.wallets.get(name).map(|managed| &managed.wallet)
+    }
+
+    pub fn get_wallet_mut(&mut self, name: &str) -> Option<&mut Wallet<T, U, V, W, X>> {
+        self.wallets.get_mut(name).map(|managed| &mut managed.wallet)
+    }
+
+    pub fn wallet_names(&self) -> impl Iterator<Item = &str> {
+        self.wallets.keys().map(|name| name.as_str())
+    }
+
+    /// Shuts down the wallet's services and comms stack, and then its runtime
+    pub async fn stop_wallet(&mut self, name: &str) -> Result<(), WalletError> {
+        let ManagedWallet {
+            wallet,
+            mut shutdown,
+            runtime,
+            ..
+        } = self
+            .wallets
+            .remove(name)
+            .ok_or_else(|| WalletError::WalletNotRunning(name.to_string()))?;
+        shutdown.trigger();
+        wallet.wait_until_shutdown().await;
+        runtime.shutdown_background();
+        info!(target: LOG_TARGET, "Stopped wallet `{}`", name);
+        Ok(())
+    }
+
+    pub async fn stop_all(&mut self) {
+        let names = self.wallets.keys().cloned().collect::<Vec<_>>();
+        for name in names {
+            let _result = self.stop_wallet(&name).await;
+        }
+    }
+}
+
+impl<T, U, V, W, X> Default for WalletManager<T, U, V, W, X>
+where
+    T: WalletBackend + 'static,
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+    W: ContactsBackend + 'static,
+    X: KeyManagerBackend + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U, V, W, X> Drop for WalletManager<T, U, V, W, X> {
+    fn drop(&mut self) {
+        for (_, mut managed) in self.wallets.drain() {
+            managed.shutdown.trigger();
+            managed.runtime.shutdown_background();
+        }
+    }
+}
+
+fn peer_database_path(config: &P2pConfig) -> PathBuf {
+    config.datastore_path.join(&config.peer_database_name)
+}
+
+/// The local addresses the wallet's comms stack listens on. Addresses with port 0 are assigned by the OS and cannot
+/// collide.
+fn listener_addresses(config: &P2pConfig) -> Vec<Multiaddr> {
+    let transport = &config.transport;
+    let mut addresses = match transport.transport_type {
+        TransportType::Memory => vec![transport.memory.listener_address.clone()],
+        TransportType::Tcp => vec![transport.tcp.listener_address.clone()],
+        TransportType::Tor => transport.tor.forward_address.iter().cloned().collect(),
+        TransportType::Socks5 | TransportType::I2p => vec![],
+    };
+    addresses.extend(config.auxiliary_tcp_listener_address.iter().cloned());
+    addresses.retain(|address| {
+        !address
+            .iter()
+            .any(|p| matches!(p, Protocol::Tcp(0) | Protocol::Memory(0)))
+    });
+    addresses
+}
+
+#[cfg(test)]
+mod test {
+    use tari_p2p::{transport::MemoryTransportConfig, TcpTransportConfig, TransportConfig};
+
+    use super::*;
+
+    #[test]
+    fn it_ignores_os_assigned_listener_ports() {
+        let mut config = P2pConfig {
+            transport: TransportConfig::new_memory(MemoryTransportConfig {
+                listener_address: "/memory/0".parse().unwrap(),
+            }),
+            ..Default::default()
+        };
+        assert!(listener_addresses(&config).is_empty());
+
+        config.transport.transport_type = TransportType::Tcp;
+        config.transport.tcp = TcpTransportConfig {
+            listener_address: "/ip4/127.0.0.1/tcp/18189".parse().unwrap(),
+            ..Default::default()
+        };
+        config.auxiliary_tcp_listener_address = Some("/ip4/127.0.0.1/tcp/0".parse().unwrap());
+        assert_eq!(listener_addresses(&config), vec!["/ip4/127.0.0.1/tcp/18189"
+            .parse::<Multiaddr>()
+            .unwrap()]);
+    }
+}
@@ -117,9 +117,14 @@ pub fn setup_contacts_service<T: ContactsBackend + 'static>(
                 max_allowed_ping_failures: 0, // Peer with failed ping-pong will never be removed
                 ..Default::default()
             },
+            peer_message_subscription_factory.clone(),
+        ))
+        .add_initializer(ContactsServiceInitializer::new(
+            backend,
             peer_message_subscription_factory,
+            Duration::from_secs(5),
+            2,
         ))
-        .add_initializer(ContactsServiceInitializer::new(backend, Duration::from_secs(5), 2))
         .build();
 
     let handles = runtime.block_on(fut).expect("Service initialization failed");
@@ -196,6 +201,13 @@ pub fn test_contacts_service() {
 
     assert_eq!(new_contact.alias, updated_contact.alias);
 
+    // The transaction service is not part of this stack
+    let result = runtime.block_on(contacts_service.get_contact_stats(contacts[0].public_key.clone()));
+    assert!(matches!(
+        result,
+        Err(ContactsServiceError::TransactionServiceUnavailable)
+    ));
+
     #[allow(clippy::match_wild_err_arm)]
     match liveness_event_stream.try_recv() {
         Ok(_) => panic!("Should not receive any event here"),
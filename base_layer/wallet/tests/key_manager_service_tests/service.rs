@@ -20,7 +20,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::mem::size_of;
+use std::{
+    collections::{HashMap, HashSet},
+    mem::size_of,
+    sync::{Arc, Mutex},
+};
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use rand::{rngs::OsRng, RngCore};
@@ -30,6 +34,8 @@ use tari_wallet::key_manager_service::{
     AddResult,
     KeyManagerHandle,
     KeyManagerInterface,
+    KeyManagerServiceError,
+    KeyUsageCallback,
 };
 
 use crate::support::data::get_temp_sqlite_database_connection;
@@ -205,3 +211,118 @@ async fn key_manager_test_index() {
         key_manager.find_key_index("branch2", &key_2).await.unwrap()
     );
 }
+
+#[tokio::test]
+async fn find_last_used_key_index_respects_gap_limit() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let cipher = CipherSeed::new();
+    let key_manager = KeyManagerHandle::new(
+        cipher,
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::new(connection, None).unwrap()),
+    );
+    key_manager.add_new_branch("branch1").await.unwrap();
+
+    let mut used_keys = HashSet::new();
+    used_keys.insert(key_manager.get_key_at_index("branch1", 3).await.unwrap());
+    used_keys.insert(key_manager.get_key_at_index("branch1", 12).await.unwrap());
+    used_keys.insert(key_manager.get_key_at_index("branch1", 30).await.unwrap());
+
+    // The gap between index 12 and 30 is too large to be bridged
+    let found = key_manager
+        .find_last_used_key_index("branch1", &used_keys, 10)
+        .await
+        .unwrap();
+    assert_eq!(found, Some(12));
+
+    let found = key_manager
+        .find_last_used_key_index("branch1", &used_keys, 20)
+        .await
+        .unwrap();
+    assert_eq!(found, Some(30));
+
+    let found = key_manager
+        .find_last_used_key_index("branch1", &HashSet::new(), 20)
+        .await
+        .unwrap();
+    assert_eq!(found, None);
+
+    assert!(key_manager
+        .find_last_used_key_index("unknown", &used_keys, 20)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn rotate_key_epoch_test() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let cipher = CipherSeed::new();
+    let key_manager = KeyManagerHandle::new(
+        cipher.clone(),
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::new(connection.clone(), None).unwrap()),
+    );
+    key_manager.add_new_branch("branch1").await.unwrap();
+    assert_eq!(key_manager.get_key_epoch().await.unwrap(), 0);
+    let epoch_0_key_1 = key_manager.get_next_key("branch1").await.unwrap();
+    key_manager.get_next_key("branch1").await.unwrap();
+
+    // A second instance that loaded its branches before the rotation
+    let stale_key_manager = KeyManagerHandle::new(
+        cipher.clone(),
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::new(connection.clone(), None).unwrap()),
+    );
+    stale_key_manager.add_new_branch("branch1").await.unwrap();
+
+    assert_eq!(key_manager.rotate_key_epoch().await.unwrap(), 1);
+    assert_eq!(key_manager.get_key_epoch().await.unwrap(), 1);
+    let epoch_1_key_1 = key_manager.get_next_key("branch1").await.unwrap();
+    assert_eq!(epoch_1_key_1.index, 1);
+    assert_ne!(epoch_1_key_1.key, epoch_0_key_1.key);
+    assert_eq!(
+        key_manager.get_key_at_index("branch1", 1).await.unwrap(),
+        epoch_1_key_1.key
+    );
+
+    assert!(matches!(
+        stale_key_manager.get_next_key("branch1").await,
+        Err(KeyManagerServiceError::KeyEpochCompromised(0))
+    ));
+
+    // The epoch and the index in it are restored when the wallet is reopened
+    let key_manager = KeyManagerHandle::new(
+        cipher,
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::new(connection, None).unwrap()),
+    );
+    key_manager.add_new_branch("branch1").await.unwrap();
+    assert_eq!(key_manager.get_key_epoch().await.unwrap(), 1);
+    let epoch_1_key_2 = key_manager.get_next_key("branch1").await.unwrap();
+    assert_eq!(epoch_1_key_2.index, 2);
+}
+
+#[tokio::test]
+async fn key_usage_callbacks_are_called_for_their_branch() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let used = Arc::new(Mutex::new(Vec::new()));
+    let used_clone = used.clone();
+    let callback: KeyUsageCallback = Arc::new(move |usage| {
+        used_clone
+            .lock()
+            .unwrap()
+            .push((usage.branch.clone(), usage.index, usage.public_key.clone()));
+    });
+    let key_manager = KeyManagerHandle::new(
+        CipherSeed::new(),
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::new(connection, None).unwrap()),
+    )
+    .with_key_usage_callbacks(HashMap::from([("app".to_string(), callback)]));
+    key_manager.add_new_branch("app").await.unwrap();
+    key_manager.add_new_branch("other").await.unwrap();
+
+    let key_1 = key_manager.get_next_key("app").await.unwrap();
+    key_manager.get_next_key("other").await.unwrap();
+    let key_2 = key_manager.get_next_key("app").await.unwrap();
+
+    assert_eq!(*used.lock().unwrap(), vec![
+        ("app".to_string(), key_1.index, key_1.to_public_key()),
+        ("app".to_string(), key_2.index, key_2.to_public_key()),
+    ]);
+}
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
+use digest::Digest;
 use rand::{rngs::OsRng, RngCore};
 use tari_common_types::{
     transaction::TxId,
@@ -41,8 +42,17 @@ use tari_core::{
         fee::Fee,
         tari_amount::{uT, MicroTari},
         test_helpers::{create_unblinded_output, TestParams as TestParamsHelpers},
-        transaction_components::{EncryptedValue, OutputFeatures, OutputType, TransactionOutput, UnblindedOutput},
-        transaction_protocol::{sender::TransactionSenderMessage, RewindData, TransactionMetadata},
+        transaction_components::{
+            EncryptedValue,
+            OutputFeatures,
+            OutputType,
+            TokenRegistration,
+            TransactionError,
+            TransactionOutput,
+            UnblindedOutput,
+            UniqueAsset,
+        },
+        transaction_protocol::{sanitizer, sender::TransactionSenderMessage, RewindData, TransactionMetadata},
         weight::TransactionWeight,
         CryptoFactories,
         SenderTransactionProtocol,
@@ -50,12 +60,13 @@ use tari_core::{
 };
 use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
-    keys::{PublicKey as PublicKeyTrait, SecretKey},
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait, SecretKey},
 };
 use tari_key_manager::{cipher_seed::CipherSeed, mnemonic::Mnemonic};
-use tari_script::{inputs, script, TariScript};
+use tari_script::{inputs, script, Opcode, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::Shutdown;
+use tari_utilities::ByteArray;
 use tari_wallet::{
     base_node_service::{
         handle::{BaseNodeEvent, BaseNodeServiceHandle},
@@ -84,9 +95,12 @@ use tari_wallet::{
             OutputStatus,
         },
         UtxoSelectionCriteria,
+        VaultScript,
     },
     test_utils::create_consensus_constants,
     transaction_service::handle::TransactionServiceHandle,
+    types::WalletHasher,
+    util::watch::Watch,
 };
 use tokio::{
     sync::{broadcast, broadcast::channel},
@@ -126,6 +140,17 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static, U: KeyM
     backend: T,
     ks_backend: U,
     with_connection: bool,
+) -> TestOmsService<U> {
+    setup_output_manager_service_with_config(backend, ks_backend, with_connection, Default::default()).await
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_lines)]
+async fn setup_output_manager_service_with_config<T: OutputManagerBackend + 'static, U: KeyManagerBackend + 'static>(
+    backend: T,
+    ks_backend: U,
+    with_connection: bool,
+    config: OutputManagerServiceConfig,
 ) -> TestOmsService<U> {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -141,7 +166,8 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static, U: KeyM
 
     let (sender, receiver_bns) = reply_channel::unbounded();
     let (event_publisher_bns, _) = broadcast::channel(100);
-    let basenode_service_handle = BaseNodeServiceHandle::new(sender, event_publisher_bns.clone());
+    let basenode_service_handle =
+        BaseNodeServiceHandle::new(sender, event_publisher_bns.clone(), broadcast::channel(100).0);
     let mut mock_base_node_service = MockBaseNodeService::new(receiver_bns, shutdown.to_signal());
     mock_base_node_service.set_default_base_node_state();
     task::spawn(mock_base_node_service.run());
@@ -191,11 +217,13 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static, U: KeyM
     .unwrap();
     let key_manager = KeyManagerHandle::new(cipher_seed.clone(), KeyManagerDatabase::new(ks_backend));
 
+    let balance_watch = Watch::new(None);
     let output_manager_service = OutputManagerService::new(
-        OutputManagerServiceConfig { ..Default::default() },
+        config,
         oms_request_receiver,
         OutputManagerDatabase::new(backend),
         oms_event_publisher.clone(),
+        balance_watch.clone(),
         factories,
         constants,
         shutdown.to_signal(),
@@ -206,7 +234,8 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static, U: KeyM
     )
     .await
     .unwrap();
-    let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
+    let output_manager_service_handle =
+        OutputManagerHandle::new(oms_request_sender, oms_event_publisher, balance_watch);
 
     let rewind_blinding_key = key_manager
         .get_key_at_index(OutputManagerKeyManagerBranch::RecoveryBlinding.get_branch_key(), 0)
@@ -263,18 +292,21 @@ pub async fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
     let (sender, receiver_bns) = reply_channel::unbounded();
     let (event_publisher_bns, _) = broadcast::channel(100);
 
-    let base_node_service_handle = BaseNodeServiceHandle::new(sender, event_publisher_bns.clone());
+    let base_node_service_handle =
+        BaseNodeServiceHandle::new(sender, event_publisher_bns.clone(), broadcast::channel(100).0);
     let mut mock_base_node_service = MockBaseNodeService::new(receiver_bns, shutdown.to_signal());
     mock_base_node_service.set_base_node_state(height);
     task::spawn(mock_base_node_service.run());
     let connectivity = create_wallet_connectivity_mock();
     let cipher = CipherSeed::new();
     let key_manager = KeyManagerMock::new(cipher.clone());
+    let balance_watch = Watch::new(None);
     let output_manager_service = OutputManagerService::new(
         OutputManagerServiceConfig { ..Default::default() },
         oms_request_receiver,
         OutputManagerDatabase::new(backend),
         oms_event_publisher.clone(),
+        balance_watch.clone(),
         factories,
         constants,
         shutdown.to_signal(),
@@ -285,7 +317,8 @@ pub async fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
     )
     .await
     .unwrap();
-    let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
+    let output_manager_service_handle =
+        OutputManagerHandle::new(oms_request_sender, oms_event_publisher, balance_watch);
 
     task::spawn(async move { output_manager_service.start().await.unwrap() });
 
@@ -358,7 +391,7 @@ async fn fee_estimate() {
         .unwrap();
     assert_eq!(
         fee,
-        fee_calc.calculate(fee_per_gram, 1, 1, 2, 2 * default_metadata_byte_size())
+        sanitizer::canonical_fee(fee_calc.calculate(fee_per_gram, 1, 1, 2, 2 * default_metadata_byte_size()))
     );
 
     let fee_per_gram = MicroTari::from(5);
@@ -371,13 +404,13 @@ async fn fee_estimate() {
 
         assert_eq!(
             fee,
-            fee_calc.calculate(
+            sanitizer::canonical_fee(fee_calc.calculate(
                 fee_per_gram,
                 1,
                 1,
                 outputs + 1,
                 default_metadata_byte_size() * (outputs + 1)
-            )
+            ))
         );
     }
 
@@ -469,7 +502,8 @@ async fn test_utxo_selection_no_chain_metadata() {
 
     // test that we can get a fee estimate with no chain metadata
     let fee = oms.fee_estimate(amount, fee_per_gram, 1, 2).await.unwrap();
-    let expected_fee = fee_calc.calculate(fee_per_gram, 1, 1, 3, default_metadata_byte_size() * 3);
+    let expected_fee =
+        sanitizer::canonical_fee(fee_calc.calculate(fee_per_gram, 1, 1, 3, default_metadata_byte_size() * 3));
     assert_eq!(fee, expected_fee);
 
     // test if a fee estimate would be possible with pending funds included
@@ -560,7 +594,8 @@ async fn test_utxo_selection_with_chain_metadata() {
 
     // test fee estimates
     let fee = oms.fee_estimate(amount, fee_per_gram, 1, 2).await.unwrap();
-    let expected_fee = fee_calc.calculate(fee_per_gram, 1, 2, 3, default_metadata_byte_size() * 3);
+    let expected_fee =
+        sanitizer::canonical_fee(fee_calc.calculate(fee_per_gram, 1, 2, 3, default_metadata_byte_size() * 3));
     assert_eq!(fee, expected_fee);
 
     // test fee estimates are maturity aware
@@ -1024,6 +1059,355 @@ async fn test_get_balance() {
     assert_eq!(output_val, balance.pending_outgoing_balance);
 }
 
+#[tokio::test]
+async fn test_balance_changes_are_published() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+    let mut balance_changes = oms.output_manager_handle.subscribe_balance_changes();
+
+    let output_val = MicroTari::from(2000);
+    for _ in 0..2 {
+        let (_ti, uo) = make_input(&mut OsRng.clone(), output_val, &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(output_val + output_val, balance.available_balance);
+
+    // The burst of added outputs ends with the balance that includes both of them
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while balance_changes.borrow().as_ref() != Some(&balance) {
+            balance_changes.changed().await.unwrap();
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn send_change_to_cold_storage() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+
+    let (cold_storage_secret_key, cold_storage_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, OutputManagerServiceConfig {
+        cold_storage_public_key: Some(cold_storage_public_key.clone()),
+        ..Default::default()
+    })
+    .await;
+
+    let output_val = MicroTari::from(2000);
+    let (_ti, uo) = make_input(&mut OsRng.clone(), output_val, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let stp = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroTari::from(1000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroTari::from(4),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .await
+        .unwrap();
+    let change = stp.get_change_unblinded_output().unwrap().unwrap();
+
+    // The change is paid to a stealth address that only the cold storage key can spend
+    match change.script.as_slice() {
+        [Opcode::PushPubKey(nonce), Opcode::Drop, Opcode::PushPubKey(spending_key)] => {
+            let c = WalletHasher::new_with_label("stealth_address")
+                .chain(PublicKey::shared_secret(&cold_storage_secret_key, nonce.as_ref()).as_bytes())
+                .finalize();
+            let expected_spending_key =
+                PublicKey::from_secret_key(&PrivateKey::from_bytes(c.as_ref()).unwrap()) + cold_storage_public_key;
+            assert_eq!(spending_key.as_ref(), &expected_spending_key);
+        },
+        _ => panic!("Change output should have a stealth address script"),
+    }
+    assert_eq!(change.script_private_key, PrivateKey::default());
+
+    // The change is not counted as incoming funds of this wallet
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_outgoing_balance, output_val);
+}
+
+#[tokio::test]
+async fn create_vault() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(20_000), &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let (_recovery_secret, recovery_key) = PublicKey::random_keypair(&mut OsRng);
+    let amount = MicroTari::from(5_000);
+
+    // The vault must unlock after it becomes recoverable
+    let err = oms
+        .output_manager_handle
+        .create_vault_transaction(
+            TxId::new_random(),
+            amount,
+            recovery_key.clone(),
+            10,
+            10,
+            MicroTari::from(4),
+            "".to_string(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::InvalidArgument(_)));
+
+    let (_fee, tx) = oms
+        .output_manager_handle
+        .create_vault_transaction(
+            TxId::new_random(),
+            amount,
+            recovery_key,
+            10,
+            100,
+            MicroTari::from(4),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+    let vault = tx
+        .body
+        .outputs()
+        .iter()
+        .find_map(|output| VaultScript::from_script(&output.script))
+        .unwrap();
+    assert_eq!(vault.recovery_height, 10);
+    assert_eq!(vault.unlock_height, 100);
+
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert!(balance.pending_incoming_balance >= amount);
+}
+
+#[tokio::test]
+async fn create_inheritance_transaction() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let value = MicroTari::from(20_000);
+    let (_ti, uo) = make_input(&mut OsRng.clone(), value, &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let (_, heir_1) = PublicKey::random_keypair(&mut OsRng);
+    let (_, heir_2) = PublicKey::random_keypair(&mut OsRng);
+    let (tx, amount) = oms
+        .output_manager_handle
+        .create_inheritance_transaction(vec![(heir_1.clone(), 1), (heir_2.clone(), 3)], 1000, MicroTari::from(4))
+        .await
+        .unwrap();
+
+    assert_eq!(tx.body.kernels()[0].lock_height, 1000);
+    assert_eq!(amount + tx.body.get_total_fee(), value);
+    assert_eq!(tx.body.outputs().len(), 2);
+    for heir in [heir_1, heir_2] {
+        assert!(tx
+            .body
+            .outputs()
+            .iter()
+            .any(|o| o.script == script!(PushPubKey(Box::new(heir.clone())))));
+    }
+
+    // The inputs are not encumbered, so the wallet can keep spending them
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, value);
+
+    let err = oms
+        .output_manager_handle
+        .create_inheritance_transaction(vec![], 1000, MicroTari::from(4))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::InvalidArgument(_)));
+}
+
+#[tokio::test]
+async fn create_inheritance_transaction_with_too_many_inputs() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+    let mut oms = setup_output_manager_service_with_config(backend, ks_backend, true, OutputManagerServiceConfig {
+        max_inputs_per_sweep_transaction: 2,
+        ..Default::default()
+    })
+    .await;
+
+    for value in [30_000, 20_000, 2_000, 1_000] {
+        let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(value), &factories.commitment).await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+
+    let (_, heir) = PublicKey::random_keypair(&mut OsRng);
+    let err = oms
+        .output_manager_handle
+        .create_inheritance_transaction(vec![(heir, 1)], 1000, MicroTari::from(4))
+        .await
+        .unwrap_err();
+    // The largest outputs fit in the transaction, the rest of the balance would not be passed on
+    match err {
+        OutputManagerError::TooManyInputs {
+            max_inputs,
+            excluded_outputs,
+            uncovered,
+        } => {
+            assert_eq!(max_inputs, 2);
+            assert_eq!(excluded_outputs, 2);
+            assert_eq!(uncovered, MicroTari::from(3_000));
+        },
+        e => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[tokio::test]
+async fn create_token_registration_transaction() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(20_000), &factories.commitment).await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let err = oms
+        .output_manager_handle
+        .create_token_registration_transaction(
+            TxId::new_random(),
+            MicroTari::from(1_000),
+            "tkn".to_string(),
+            1_000_000,
+            MicroTari::from(4),
+            "".to_string(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        OutputManagerError::TransactionError(TransactionError::InvalidTokenRegistration(_))
+    ));
+
+    let (_fee, tx) = oms
+        .output_manager_handle
+        .create_token_registration_transaction(
+            TxId::new_random(),
+            MicroTari::from(1_000),
+            "TKN".to_string(),
+            1_000_000,
+            MicroTari::from(4),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+    let output = tx
+        .body
+        .outputs()
+        .iter()
+        .find(|o| o.features.output_type == OutputType::TokenRegistration)
+        .unwrap();
+    output.verify_token_registration().unwrap();
+    let registration = TokenRegistration::from_features(&output.features).unwrap().unwrap();
+    assert_eq!(registration.total_supply, 1_000_000);
+
+    // The registration is only counted once it is mined
+    assert!(oms.output_manager_handle.get_token_balances().await.unwrap().is_empty());
+
+    let test_params = TestParamsHelpers::new();
+    let value = MicroTari::from(1_000);
+    let commitment = factories
+        .commitment
+        .commit_value(&test_params.spend_key, value.as_u64());
+    let registration =
+        TokenRegistration::new("TKN".to_string(), 500, &PrivateKey::random(&mut OsRng), &commitment).unwrap();
+    let uo = create_unblinded_output(script!(Nop), registration.to_features(), &test_params, value);
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let balances = oms.output_manager_handle.get_token_balances().await.unwrap();
+    assert_eq!(balances.len(), 1);
+    assert_eq!(balances[0].token_id, registration.token_id());
+    assert_eq!(balances[0].symbol, "TKN");
+    assert_eq!(balances[0].balance, 500);
+}
+
+#[tokio::test]
+async fn preview_unique_asset_transfer() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let test_params = TestParamsHelpers::new();
+    let value = MicroTari::from(10_000);
+    let commitment = factories
+        .commitment
+        .commit_value(&test_params.spend_key, value.as_u64());
+    let asset = UniqueAsset::new(b"sword #1".to_vec(), &PrivateKey::random(&mut OsRng), &commitment).unwrap();
+    let recipient_script = script!(Nop);
+    let fee_per_gram = MicroTari::from(4);
+    let err = oms
+        .output_manager_handle
+        .preview_unique_asset_transfer(asset.asset_id(), fee_per_gram, recipient_script.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::UniqueAssetNotFound(id) if id == asset.asset_id()));
+
+    let uo = create_unblinded_output(script!(Nop), asset.to_features(), &test_params, value);
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let assets = oms.output_manager_handle.get_owned_assets().await.unwrap();
+    assert_eq!(assets.len(), 1);
+    assert_eq!(assets[0].asset, asset);
+
+    let fee_calc = Fee::new(*create_consensus_constants(0).transaction_weight());
+    let metadata_byte_size = fee_calc.weighting().round_up_metadata_size(
+        asset.to_features().consensus_encode_exact_size() +
+            recipient_script.consensus_encode_exact_size() +
+            Covenant::default().consensus_encode_exact_size(),
+    );
+    let fee = fee_calc.calculate(fee_per_gram, 1, 1, 1, metadata_byte_size);
+    let (owned, amount) = oms
+        .output_manager_handle
+        .preview_unique_asset_transfer(asset.asset_id(), fee_per_gram, recipient_script.clone())
+        .await
+        .unwrap();
+    assert_eq!(owned.output.commitment, assets[0].output.commitment);
+    assert_eq!(amount, value - fee);
+
+    // The asset output cannot pay the fee
+    let err = oms
+        .output_manager_handle
+        .preview_unique_asset_transfer(asset.asset_id(), MicroTari::from(1_000), recipient_script)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughFunds));
+}
+
 #[tokio::test]
 async fn sending_transaction_persisted_while_offline() {
     let factories = CryptoFactories::default();
@@ -1210,7 +1594,7 @@ async fn handle_coinbase_with_bulletproofs_rewinding() {
 
     let _transaction = oms
         .output_manager_handle
-        .get_coinbase_transaction(1u64.into(), reward1, fees1, 1)
+        .get_coinbase_transaction(1u64.into(), reward1, fees1, 1, vec![])
         .await
         .unwrap();
     assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
@@ -1225,7 +1609,7 @@ async fn handle_coinbase_with_bulletproofs_rewinding() {
 
     let _tx2 = oms
         .output_manager_handle
-        .get_coinbase_transaction(2u64.into(), reward2, fees2, 1)
+        .get_coinbase_transaction(2u64.into(), reward2, fees2, 1, vec![])
         .await
         .unwrap();
     assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
@@ -1239,7 +1623,7 @@ async fn handle_coinbase_with_bulletproofs_rewinding() {
     );
     let tx3 = oms
         .output_manager_handle
-        .get_coinbase_transaction(3u64.into(), reward3, fees3, 2)
+        .get_coinbase_transaction(3u64.into(), reward3, fees3, 2, vec![])
         .await
         .unwrap();
     assert_eq!(oms.output_manager_handle.get_unspent_outputs().await.unwrap().len(), 0);
@@ -1263,6 +1647,31 @@ async fn handle_coinbase_with_bulletproofs_rewinding() {
     assert_eq!(decrypted, value3);
 }
 
+#[tokio::test]
+async fn handle_coinbase_with_extra() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone(), None);
+    let ks_backend = KeyManagerSqliteDatabase::new(connection, None).unwrap();
+    let mut oms = setup_output_manager_service(backend, ks_backend, true).await;
+
+    let tx = oms
+        .output_manager_handle
+        .get_coinbase_transaction(1u64.into(), 1000.into(), 500.into(), 1, b"pool-tag".to_vec())
+        .await
+        .unwrap();
+    let output = &tx.body.outputs()[0];
+    assert_eq!(output.features.output_type, OutputType::Coinbase);
+    assert_eq!(output.features.coinbase_extra(), Some(&b"pool-tag"[..]));
+
+    let max = create_consensus_constants(0).coinbase_extra_max_length();
+    let err = oms
+        .output_manager_handle
+        .get_coinbase_transaction(2u64.into(), 1000.into(), 500.into(), 2, vec![0u8; max + 1])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::CoinbaseBuildError(_)));
+}
+
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn test_txo_validation() {
@@ -1407,7 +1816,13 @@ async fn test_txo_validation() {
         .unwrap();
 
     oms.output_manager_handle
-        .get_coinbase_transaction(6u64.into(), MicroTari::from(15_000_000), MicroTari::from(1_000_000), 2)
+        .get_coinbase_transaction(
+            6u64.into(),
+            MicroTari::from(15_000_000),
+            MicroTari::from(1_000_000),
+            2,
+            vec![],
+        )
         .await
         .unwrap();
 
@@ -2111,6 +2526,28 @@ async fn scan_for_recovery_test() {
         })
         .collect();
 
+    // Rewinding alone reveals the values and masks of all the owned outputs, in order
+    let rewound_outputs = oms
+        .output_manager_handle
+        .rewind_outputs(
+            non_rewindable_outputs
+                .clone()
+                .into_iter()
+                .chain(rewindable_outputs.clone().into_iter())
+                .collect(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rewound_outputs.len(), NUM_REWINDABLE);
+    for ((output, rewound), uo) in rewound_outputs.iter().zip(&rewindable_unblinded_outputs) {
+        assert_eq!(
+            output.commitment,
+            factories.commitment.commit_value(&uo.spending_key, uo.value.as_u64())
+        );
+        assert_eq!(rewound.value, uo.value);
+        assert_eq!(rewound.blinding_factor, uo.spending_key);
+    }
+
     oms.output_manager_handle
         .add_rewindable_output(rewindable_unblinded_outputs[0].clone(), None, None)
         .await
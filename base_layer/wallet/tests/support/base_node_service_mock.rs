@@ -90,6 +90,7 @@ impl MockBaseNodeService {
             is_synced,
             updated: None,
             latency: None,
+            fee_floor: None,
         }
     }
 
@@ -100,6 +101,7 @@ impl MockBaseNodeService {
             is_synced: Some(true),
             updated: None,
             latency: None,
+            fee_floor: None,
         }
     }
 
@@ -113,6 +115,11 @@ impl MockBaseNodeService {
                 self.state.chain_metadata.clone(),
             )),
             BaseNodeServiceRequest::GetBaseNodeLatency => Ok(BaseNodeServiceResponse::Latency(None)),
+            BaseNodeServiceRequest::GetFeeFloor => Ok(BaseNodeServiceResponse::FeeFloor(self.state.fee_floor)),
+            BaseNodeServiceRequest::GetNetworkHashRate(_) => {
+                Ok(BaseNodeServiceResponse::NetworkHashRate(Default::default()))
+            },
+            BaseNodeServiceRequest::GetClockSkew => Ok(BaseNodeServiceResponse::ClockSkew(None)),
         }
     }
 }
@@ -38,24 +38,30 @@ use tari_comms::{
 };
 use tari_core::{
     base_node::{
-        proto::wallet_rpc::{TxLocation, TxQueryResponse, TxSubmissionRejectionReason, TxSubmissionResponse},
+        proto::wallet_rpc::{FeeFloor, TxLocation, TxQueryResponse, TxSubmissionRejectionReason, TxSubmissionResponse},
         rpc::BaseNodeWalletService,
     },
     blocks::BlockHeader,
     proto,
     proto::{
         base_node::{
+            BlockInclusionProof,
+            BlockInclusionProofRequest,
             ChainMetadata as ChainMetadataProto,
+            FeeFloorResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
             GetMempoolFeePerGramStatsResponse,
+            NetworkHashRateRequest,
+            NetworkHashRateResponse,
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures as SignaturesProto,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
+            TransactionPackage,
             TxQueryBatchResponses as TxQueryBatchResponsesProto,
             TxQueryResponse as TxQueryResponseProto,
             TxSubmissionResponse as TxSubmissionResponseProto,
@@ -89,6 +95,7 @@ where T: From<RpcClient> + NamedProtocolService {
 #[derive(Clone, Debug)]
 pub struct BaseNodeWalletRpcMockState {
     submit_transaction_calls: Arc<Mutex<Vec<Transaction>>>,
+    submit_transaction_package_calls: Arc<Mutex<Vec<Vec<Transaction>>>>,
     transaction_query_calls: Arc<Mutex<Vec<Signature>>>,
     transaction_batch_query_calls: Arc<Mutex<Vec<Vec<Signature>>>>,
     utxo_query_calls: Arc<Mutex<Vec<Vec<Vec<u8>>>>>,
@@ -97,6 +104,7 @@ pub struct BaseNodeWalletRpcMockState {
     get_height_at_time_calls: Arc<Mutex<Vec<u64>>>,
     sync_utxo_by_block_calls: Arc<Mutex<Vec<(HashOutput, HashOutput)>>>,
     submit_transaction_response: Arc<Mutex<TxSubmissionResponse>>,
+    submit_transaction_package_response: Arc<Mutex<TxSubmissionResponse>>,
     transaction_query_response: Arc<Mutex<TxQueryResponse>>,
     transaction_query_batch_response: Arc<Mutex<TxQueryBatchResponsesProto>>,
     tip_info_response: Arc<Mutex<TipInfoResponse>>,
@@ -110,6 +118,9 @@ pub struct BaseNodeWalletRpcMockState {
     utxos: Arc<Mutex<Vec<TransactionOutput>>>,
     blocks: Arc<Mutex<HashMap<u64, BlockHeader>>>,
     get_mempool_fee_per_gram_stats: Arc<Mutex<GetMempoolFeePerGramStatsResponse>>,
+    network_hash_rate_response: Arc<Mutex<NetworkHashRateResponse>>,
+    fee_floor: Arc<Mutex<FeeFloor>>,
+    block_inclusion_proof_response: Arc<Mutex<Option<BlockInclusionProof>>>,
     utxos_by_block: Arc<Mutex<Vec<UtxosByBlock>>>,
     sync_utxos_by_block_trigger_channel: Arc<Mutex<Option<mpsc::Receiver<usize>>>>,
 }
@@ -119,6 +130,7 @@ impl BaseNodeWalletRpcMockState {
     pub fn new() -> Self {
         Self {
             submit_transaction_calls: Arc::new(Mutex::new(Vec::new())),
+            submit_transaction_package_calls: Arc::new(Mutex::new(Vec::new())),
             transaction_query_calls: Arc::new(Mutex::new(Vec::new())),
             transaction_batch_query_calls: Arc::new(Mutex::new(Vec::new())),
             utxo_query_calls: Arc::new(Mutex::new(vec![])),
@@ -131,6 +143,11 @@ impl BaseNodeWalletRpcMockState {
                 rejection_reason: TxSubmissionRejectionReason::None,
                 is_synced: true,
             })),
+            submit_transaction_package_response: Arc::new(Mutex::new(TxSubmissionResponse {
+                accepted: true,
+                rejection_reason: TxSubmissionRejectionReason::None,
+                is_synced: true,
+            })),
             transaction_query_response: Arc::new(Mutex::new(TxQueryResponse {
                 location: TxLocation::InMempool,
                 block_hash: None,
@@ -177,6 +194,9 @@ impl BaseNodeWalletRpcMockState {
             utxos: Arc::new(Mutex::new(Vec::new())),
             blocks: Arc::new(Mutex::new(Default::default())),
             get_mempool_fee_per_gram_stats: Default::default(),
+            network_hash_rate_response: Default::default(),
+            fee_floor: Arc::new(Mutex::new(FeeFloor::consensus())),
+            block_inclusion_proof_response: Arc::new(Mutex::new(None)),
 
             utxos_by_block: Arc::new(Mutex::new(vec![])),
             sync_utxos_by_block_trigger_channel: Arc::new(Mutex::new(None)),
@@ -193,6 +213,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = response;
     }
 
+    pub fn set_submit_transaction_package_response(&self, response: TxSubmissionResponse) {
+        let mut lock = acquire_lock!(self.submit_transaction_package_response);
+        *lock = response;
+    }
+
     pub fn set_transaction_query_response(&self, response: TxQueryResponse) {
         let mut lock = acquire_lock!(self.transaction_query_response);
         *lock = response;
@@ -245,6 +270,21 @@ impl BaseNodeWalletRpcMockState {
         *lock = resp;
     }
 
+    pub fn set_network_hash_rate_response(&self, response: NetworkHashRateResponse) {
+        let mut lock = acquire_lock!(self.network_hash_rate_response);
+        *lock = response;
+    }
+
+    pub fn set_fee_floor(&self, fee_floor: FeeFloor) {
+        let mut lock = acquire_lock!(self.fee_floor);
+        *lock = fee_floor;
+    }
+
+    pub fn set_block_inclusion_proof_response(&self, response: Option<BlockInclusionProof>) {
+        let mut lock = acquire_lock!(self.block_inclusion_proof_response);
+        *lock = response;
+    }
+
     pub fn set_utxos_by_block(&self, utxos_by_block: Vec<UtxosByBlock>) {
         let mut lock = acquire_lock!(self.utxos_by_block);
         *lock = utxos_by_block;
@@ -276,6 +316,10 @@ impl BaseNodeWalletRpcMockState {
         acquire_lock!(self.submit_transaction_calls).drain(..).collect()
     }
 
+    pub fn take_submit_transaction_package_calls(&self) -> Vec<Vec<Transaction>> {
+        acquire_lock!(self.submit_transaction_package_calls).drain(..).collect()
+    }
+
     pub fn pop_submit_transaction_call(&self) -> Option<Transaction> {
         acquire_lock!(self.submit_transaction_calls).pop()
     }
@@ -852,6 +896,60 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
             acquire_lock!(self.state.get_mempool_fee_per_gram_stats).clone(),
         ))
     }
+
+    async fn get_block_inclusion_proof(
+        &self,
+        _request: Request<BlockInclusionProofRequest>,
+    ) -> Result<Response<BlockInclusionProof>, RpcStatus> {
+        acquire_lock!(self.state.block_inclusion_proof_response)
+            .clone()
+            .map(Response::new)
+            .ok_or_else(|| RpcStatus::not_found("Block inclusion proof not found"))
+    }
+
+    async fn submit_transaction_package(
+        &self,
+        request: Request<TransactionPackage>,
+    ) -> Result<Response<TxSubmissionResponseProto>, RpcStatus> {
+        let transactions = request
+            .into_message()
+            .transactions
+            .into_iter()
+            .map(Transaction::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| RpcStatus::bad_request("Transaction was invalid"))?;
+        log::info!(
+            "Submit Transaction Package call received: {} transaction(s)",
+            transactions.len()
+        );
+        acquire_lock!(self.state.submit_transaction_package_calls).push(transactions);
+
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        let response_lock = acquire_lock!(self.state.submit_transaction_package_response);
+        Ok(Response::new(response_lock.clone().into()))
+    }
+
+    async fn get_network_hash_rate(
+        &self,
+        _request: Request<NetworkHashRateRequest>,
+    ) -> Result<Response<NetworkHashRateResponse>, RpcStatus> {
+        Ok(Response::new(
+            acquire_lock!(self.state.network_hash_rate_response).clone(),
+        ))
+    }
+
+    async fn get_fee_floor(&self, _request: Request<()>) -> Result<Response<FeeFloorResponse>, RpcStatus> {
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        Ok(Response::new((*acquire_lock!(self.state.fee_floor)).into()))
+    }
 }
 
 #[derive(Clone, Debug)]
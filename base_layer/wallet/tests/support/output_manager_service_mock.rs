@@ -27,10 +27,19 @@ use log::*;
 use tari_common_types::transaction::TxId;
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
 use tari_shutdown::ShutdownSignal;
-use tari_wallet::output_manager_service::{
-    error::OutputManagerError,
-    handle::{OutputManagerEvent, OutputManagerHandle, OutputManagerRequest, OutputManagerResponse, RecoveredOutput},
-    storage::models::DbUnblindedOutput,
+use tari_wallet::{
+    output_manager_service::{
+        error::OutputManagerError,
+        handle::{
+            OutputManagerEvent,
+            OutputManagerHandle,
+            OutputManagerRequest,
+            OutputManagerResponse,
+            RecoveredOutput,
+        },
+        storage::models::DbUnblindedOutput,
+    },
+    util::watch::Watch,
 };
 use tokio::sync::{broadcast, broadcast::Sender, oneshot};
 
@@ -41,7 +50,7 @@ pub fn make_output_manager_service_mock(
 ) -> (OutputManagerServiceMock, OutputManagerHandle) {
     let (sender, receiver) = reply_channel::unbounded();
     let (publisher, _) = broadcast::channel(100);
-    let output_manager_handle = OutputManagerHandle::new(sender, publisher.clone());
+    let output_manager_handle = OutputManagerHandle::new(sender, publisher.clone(), Watch::new(None));
     let mock = OutputManagerServiceMock::new(publisher, receiver, shutdown_signal);
     (mock, output_manager_handle)
 }
@@ -107,6 +116,8 @@ impl OutputManagerServiceMock {
                             Some(RecoveredOutput {
                                 output: dbuo.unblinded_output,
                                 tx_id: TxId::new_random(),
+                                source: dbuo.source,
+                                memo: None,
                             })
                         } else {
                             None
@@ -131,6 +142,8 @@ impl OutputManagerServiceMock {
                             Some(RecoveredOutput {
                                 output: dbuo.unblinded_output,
                                 tx_id: TxId::new_random(),
+                                source: dbuo.source,
+                                memo: None,
                             })
                         } else {
                             None
@@ -144,6 +157,14 @@ impl OutputManagerServiceMock {
                         e
                     });
             },
+            OutputManagerRequest::RestoreKeyIndices { .. } => {
+                let _result = reply_tx
+                    .send(Ok(OutputManagerResponse::KeyIndicesRestored(vec![])))
+                    .map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send reply");
+                        e
+                    });
+            },
             _ => panic!("Output Manager Service Mock does not support this call"),
         }
     }
@@ -25,7 +25,7 @@ use std::{
     convert::{TryFrom, TryInto},
     path::Path,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use chrono::{Duration as ChronoDuration, Utc};
@@ -111,12 +111,14 @@ use tari_wallet::{
     key_manager_service::{storage::sqlite_db::KeyManagerSqliteDatabase, KeyManagerInitializer, KeyManagerMock},
     output_manager_service::{
         config::OutputManagerServiceConfig,
+        error::OutputManagerError,
         handle::{OutputManagerEvent, OutputManagerHandle},
         service::{Balance, OutputManagerService},
         storage::{
             database::OutputManagerDatabase,
             models::KnownOneSidedPaymentScript,
             sqlite_db::OutputManagerSqliteDatabase,
+            OutputSource,
         },
         OutputManagerServiceInitializer,
         UtxoSelectionCriteria,
@@ -128,17 +130,27 @@ use tari_wallet::{
     },
     test_utils::{create_consensus_constants, make_wallet_database_connection},
     transaction_service::{
+        approval::{sign_approval, TransactionApprovalConfig, TransactionApprovalError},
         config::TransactionServiceConfig,
         error::TransactionServiceError,
         handle::{TransactionEvent, TransactionSendStatus, TransactionServiceHandle},
+        messaging::DhtMessagingBackend,
         service::TransactionService,
+        spending_policy::{SpendingPolicy, SpendingPolicyViolation},
         storage::{
             database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
-            models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
+            models::{
+                CompletedTransaction,
+                InboundTransaction,
+                OutboundTransaction,
+                TxCancellationReason,
+                WalletTransaction,
+            },
             sqlite_db::TransactionServiceSqliteDatabase,
         },
         TransactionServiceInitializer,
     },
+    util::{cancellation::CancellationToken, watch::Watch},
 };
 use tempfile::tempdir;
 use tokio::{
@@ -308,7 +320,8 @@ async fn setup_transaction_service_no_comms(
     let (sender, receiver_bns) = reply_channel::unbounded();
     let (base_node_service_event_publisher, _) = broadcast::channel(100);
 
-    let base_node_service_handle = BaseNodeServiceHandle::new(sender, base_node_service_event_publisher);
+    let base_node_service_handle =
+        BaseNodeServiceHandle::new(sender, base_node_service_event_publisher, broadcast::channel(100).0);
     let mut mock_base_node_service = MockBaseNodeService::new(receiver_bns, shutdown.to_signal());
     mock_base_node_service.set_default_base_node_state();
     task::spawn(mock_base_node_service.run());
@@ -320,11 +333,13 @@ async fn setup_transaction_service_no_comms(
     let cipher = CipherSeed::new();
     let key_manager = KeyManagerMock::new(cipher);
     let oms_db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(db_connection, None));
+    let balance_watch = Watch::new(None);
     let output_manager_service = OutputManagerService::new(
         OutputManagerServiceConfig::default(),
         oms_request_receiver,
         oms_db,
         output_manager_service_event_publisher.clone(),
+        balance_watch.clone(),
         factories.clone(),
         constants,
         shutdown.to_signal(),
@@ -336,8 +351,11 @@ async fn setup_transaction_service_no_comms(
     .await
     .unwrap();
 
-    let output_manager_service_handle =
-        OutputManagerHandle::new(oms_request_sender, output_manager_service_event_publisher.clone());
+    let output_manager_service_handle = OutputManagerHandle::new(
+        oms_request_sender,
+        output_manager_service_event_publisher.clone(),
+        balance_watch,
+    );
 
     let test_config = config.unwrap_or(TransactionServiceConfig {
         broadcast_monitoring_timeout: Duration::from_secs(5),
@@ -364,7 +382,10 @@ async fn setup_transaction_service_no_comms(
         base_node_response_receiver,
         tx_cancelled_receiver,
         output_manager_service_handle.clone(),
-        outbound_message_requester,
+        Arc::new(DhtMessagingBackend::new(
+            outbound_message_requester,
+            event_publisher.clone(),
+        )),
         wallet_connectivity_service_mock.clone(),
         event_publisher,
         base_node_identity.clone(),
@@ -524,7 +545,8 @@ async fn manage_single_transaction() {
             value,
             OutputFeatures::default(),
             MicroTari::from(4),
-            "".to_string()
+            "".to_string(),
+            None
         )
         .await
         .is_err());
@@ -538,6 +560,7 @@ async fn manage_single_transaction() {
             OutputFeatures::default(),
             MicroTari::from(4),
             message,
+            None,
         )
         .await
         .expect("Alice sending tx");
@@ -644,6 +667,7 @@ async fn single_transaction_to_self() {
             OutputFeatures::default(),
             20.into(),
             message.clone(),
+            None,
         )
         .await
         .expect("Alice sending tx");
@@ -875,6 +899,112 @@ async fn recover_one_sided_transaction() {
     assert!(unblinded.is_empty());
 }
 
+#[tokio::test]
+async fn recover_expiring_vault_transaction() {
+    let factories = CryptoFactories::default();
+    let alice_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+    let bob_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+    let base_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let temp_dir = tempdir().unwrap();
+    let temp_dir2 = tempdir().unwrap();
+    let database_path = temp_dir.path().to_str().unwrap().to_string();
+    let database_path2 = temp_dir2.path().to_str().unwrap().to_string();
+
+    let (alice_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
+    let (bob_connection, _tempdir) = make_wallet_database_connection(Some(database_path2.clone()));
+
+    let shutdown = Shutdown::new();
+    let (mut alice_ts, mut alice_oms, _alice_comms, mut alice_connectivity) = setup_transaction_service(
+        alice_node_identity.clone(),
+        vec![],
+        factories.clone(),
+        alice_connection,
+        database_path,
+        Duration::from_secs(0),
+        shutdown.to_signal(),
+    )
+    .await;
+
+    let (_bob_ts, mut bob_oms, _bob_comms, _bob_connectivity) = setup_transaction_service(
+        bob_node_identity.clone(),
+        vec![],
+        factories.clone(),
+        bob_connection,
+        database_path2,
+        Duration::from_secs(0),
+        shutdown.to_signal(),
+    )
+    .await;
+    let script = script!(PushPubKey(Box::new(bob_node_identity.public_key().clone())));
+    let known_script = KnownOneSidedPaymentScript {
+        script_hash: script.as_hash::<Blake256>().unwrap().to_vec(),
+        private_key: bob_node_identity.secret_key().clone(),
+        script,
+        input: ExecutionStack::default(),
+        script_lock_height: 0,
+    };
+    bob_oms.add_known_script(known_script).await.unwrap();
+
+    alice_connectivity.set_base_node(base_node_identity.to_peer());
+
+    let (_utxo, uo1) = make_input(&mut OsRng, 25000.into(), &factories.commitment).await;
+    alice_oms.add_rewindable_output(uo1, None, None).await.unwrap();
+
+    let value = 10000.into();
+    let tx_id = alice_ts
+        .send_expiring_vault_transaction(
+            bob_node_identity.public_key().clone(),
+            value,
+            20.into(),
+            "expiring vault".to_string(),
+        )
+        .await
+        .unwrap();
+
+    let completed_tx = alice_ts.get_completed_transaction(tx_id).await.unwrap();
+    let outputs = completed_tx.transaction.body.outputs().clone();
+
+    // Alice keeps the vault output so that it can be refunded once it expires
+    let vault_output = outputs
+        .iter()
+        .find(|output| output.script.as_slice().len() > 1)
+        .unwrap();
+    let refund = alice_oms
+        .get_unspent_outputs()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|output| output.script == vault_output.script)
+        .unwrap();
+    assert_eq!(refund.value, value);
+    assert!(refund.script_lock_height > 0);
+
+    // Bob claims it like a one-sided payment
+    let unblinded = bob_oms
+        .scan_outputs_for_one_sided_payments(outputs.clone())
+        .await
+        .unwrap();
+    assert_eq!(1, unblinded.len());
+    assert_eq!(value, unblinded[0].output.value);
+    assert_eq!(unblinded[0].source, OutputSource::ExpiringVault);
+
+    let unblinded = bob_oms.scan_outputs_for_one_sided_payments(outputs).await.unwrap();
+    assert!(unblinded.is_empty());
+}
+
 #[tokio::test]
 async fn test_htlc_send_and_claim() {
     let factories = CryptoFactories::default();
@@ -1190,6 +1320,7 @@ async fn manage_multiple_transactions() {
             OutputFeatures::default(),
             MicroTari::from(20),
             "a to b 1".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -1202,6 +1333,7 @@ async fn manage_multiple_transactions() {
             OutputFeatures::default(),
             MicroTari::from(20),
             "a to c 1".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -1216,6 +1348,7 @@ async fn manage_multiple_transactions() {
             OutputFeatures::default(),
             MicroTari::from(20),
             "b to a 1".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -1226,6 +1359,7 @@ async fn manage_multiple_transactions() {
             OutputFeatures::default(),
             MicroTari::from(20),
             "a to b 2".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -1352,6 +1486,7 @@ async fn test_accepting_unknown_tx_id_and_malformed_reply() {
             OutputFeatures::default(),
             MicroTari::from(20),
             "".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -1493,10 +1628,8 @@ async fn finalize_tx_with_incorrect_pubkey() {
     stp.finalize(&factories, None, u64::MAX).unwrap();
     let tx = stp.get_transaction().unwrap();
 
-    let finalized_transaction_message = proto::TransactionFinalizedMessage {
-        tx_id: recipient_reply.tx_id.as_u64(),
-        transaction: Some(tx.clone().try_into().unwrap()),
-    };
+    let finalized_transaction_message =
+        proto::TransactionFinalizedMessage::new(recipient_reply.tx_id, tx.clone()).unwrap();
 
     alice_ts_interface
         .transaction_finalize_message_channel
@@ -1607,20 +1740,17 @@ async fn finalize_tx_with_missing_output() {
         .unwrap();
     stp.finalize(&factories, None, u64::MAX).unwrap();
 
-    let finalized_transaction_message = proto::TransactionFinalizedMessage {
-        tx_id: recipient_reply.tx_id.as_u64(),
-        transaction: Some(
-            Transaction::new(
-                vec![],
-                vec![],
-                vec![],
-                PrivateKey::random(&mut OsRng),
-                PrivateKey::random(&mut OsRng),
-            )
-            .try_into()
-            .unwrap(),
+    let finalized_transaction_message = proto::TransactionFinalizedMessage::new(
+        recipient_reply.tx_id,
+        Transaction::new(
+            vec![],
+            vec![],
+            vec![],
+            PrivateKey::random(&mut OsRng),
+            PrivateKey::random(&mut OsRng),
         ),
-    };
+    )
+    .unwrap();
 
     alice_ts_interface
         .transaction_finalize_message_channel
@@ -1735,6 +1865,7 @@ async fn discovery_async_return_test() {
             OutputFeatures::default(),
             MicroTari::from(20),
             "Discovery Tx!".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -1771,6 +1902,7 @@ async fn discovery_async_return_test() {
             OutputFeatures::default(),
             MicroTari::from(20),
             "Discovery Tx2!".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -1861,6 +1993,7 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        is_decoy: false,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -1883,6 +2016,7 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        is_decoy: false,
     };
 
     tx_backend
@@ -2040,6 +2174,7 @@ async fn test_transaction_cancellation() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -2361,6 +2496,7 @@ async fn test_direct_vs_saf_send_of_tx_reply_and_finalize() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -2545,6 +2681,7 @@ async fn test_direct_vs_saf_send_of_tx_reply_and_finalize() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -2677,6 +2814,7 @@ async fn test_tx_direct_send_behaviour() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message1".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -2720,6 +2858,7 @@ async fn test_tx_direct_send_behaviour() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message2".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -2768,6 +2907,7 @@ async fn test_tx_direct_send_behaviour() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message3".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -2816,6 +2956,7 @@ async fn test_tx_direct_send_behaviour() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message4".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -2850,96 +2991,605 @@ async fn test_tx_direct_send_behaviour() {
 }
 
 #[tokio::test]
-async fn test_restarting_transaction_protocols() {
+async fn test_send_transaction_with_idempotency_key() {
     let factories = CryptoFactories::default();
-    let (alice_connection, _temp_dir) = make_wallet_database_connection(None);
-    let alice_backend = TransactionServiceSqliteDatabase::new(alice_connection.clone(), None);
 
-    let (bob_connection, _temp_dir2) = make_wallet_database_connection(None);
-    let bob_backend = TransactionServiceSqliteDatabase::new(bob_connection.clone(), None);
-
-    let base_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
 
-    let alice_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
 
-    let bob_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
+    for _ in 0..2 {
+        let (_utxo, uo) = make_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+        alice_ts_interface
+            .output_manager_service_handle
+            .add_output(uo, None)
+            .await
+            .unwrap();
+    }
 
-    // Bob is going to send a transaction to Alice
-    let alice = TestParams::new(&mut OsRng);
-    let bob = TestParams::new(&mut OsRng);
-    let (utxo, input) = make_input(&mut OsRng, MicroTari(2000), &factories.commitment).await;
-    let constants = create_consensus_constants(0);
-    let fee_calc = Fee::new(*constants.transaction_weight());
-    let mut builder = SenderTransactionProtocol::builder(1, constants);
-    let fee = fee_calc.calculate(MicroTari(4), 1, 1, 1, 0);
-    let script_private_key = PrivateKey::random(&mut OsRng);
-    builder
-        .with_lock_height(0)
-        .with_fee_per_gram(MicroTari(4))
-        .with_offset(bob.offset.clone())
-        .with_private_nonce(bob.nonce)
-        .with_input(utxo, input)
-        .with_amount(0, MicroTari(2000) - fee - MicroTari(10))
-        .with_recipient_data(
-            0,
-            script!(Nop),
-            PrivateKey::random(&mut OsRng),
-            Default::default(),
-            PrivateKey::random(&mut OsRng),
-            Covenant::default(),
-            MicroTari::zero(),
+    let amount_sent = 100000 * uT;
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            amount_sent,
+            OutputFeatures::default(),
+            100 * uT,
+            "Idempotent payment".to_string(),
+            Some("payment-1".to_string()),
         )
-        .with_change_script(
-            script!(Nop),
-            inputs!(PublicKey::from_secret_key(&script_private_key)),
-            script_private_key,
-        );
-    let mut bob_stp = builder.build(&factories, None, u64::MAX).unwrap();
-    let msg = bob_stp.build_single_round_message().unwrap();
-    let bob_pre_finalize = bob_stp.clone();
+        .await
+        .unwrap();
 
-    let tx_id = msg.tx_id;
+    // Retrying with the same key must not create a second transaction
+    let repeated_tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            amount_sent,
+            OutputFeatures::default(),
+            100 * uT,
+            "Idempotent payment".to_string(),
+            Some("payment-1".to_string()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(tx_id, repeated_tx_id);
 
-    let sender_info = TransactionSenderMessage::Single(Box::new(msg.clone()));
-    let receiver_protocol =
-        ReceiverTransactionProtocol::new(sender_info, alice.nonce.clone(), alice.spend_key, &factories);
+    // Once the original transaction is recorded, a retry also reports its status
+    let mut original_status = None;
+    for _ in 0..50 {
+        if let Some(WalletTransaction::PendingOutbound(tx)) = alice_ts_interface
+            .transaction_service_handle
+            .get_any_transaction(tx_id)
+            .await
+            .unwrap()
+        {
+            original_status = Some(tx.status);
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert!(original_status.is_some(), "The original transaction should be recorded");
+    let (repeated_tx_id, status) = alice_ts_interface
+        .transaction_service_handle
+        .send_idempotent_transaction(
+            bob_node_identity.public_key().clone(),
+            amount_sent,
+            OutputFeatures::default(),
+            100 * uT,
+            "Idempotent payment".to_string(),
+            "payment-1".to_string(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(tx_id, repeated_tx_id);
+    assert_eq!(status, original_status);
 
-    let alice_reply = receiver_protocol.get_signed_data().unwrap().clone();
+    // Reusing the key for a different payment is rejected
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            amount_sent + uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Idempotent payment".to_string(),
+            Some("payment-1".to_string()),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::IdempotencyKeyConflict(_))
+    ));
 
-    bob_stp
-        .add_single_recipient_info(alice_reply.clone(), &factories.range_proof)
+    let (new_tx_id, status) = alice_ts_interface
+        .transaction_service_handle
+        .send_idempotent_transaction(
+            bob_node_identity.public_key().clone(),
+            amount_sent,
+            OutputFeatures::default(),
+            100 * uT,
+            "Idempotent payment".to_string(),
+            "payment-2".to_string(),
+        )
+        .await
         .unwrap();
+    assert_ne!(tx_id, new_tx_id);
+    assert_eq!(status, None);
+}
 
-    match bob_stp.finalize(&factories, None, u64::MAX) {
-        Ok(_) => (),
-        Err(e) => panic!("Should be able to finalize tx: {}", e),
-    };
-    let tx = bob_stp.get_transaction().unwrap().clone();
+#[tokio::test]
+async fn test_spending_policy_rejects_transactions() {
+    let factories = CryptoFactories::default();
 
-    let inbound_tx = InboundTransaction {
-        tx_id,
-        source_public_key: bob_identity.public_key().clone(),
-        amount: msg.amount,
-        receiver_protocol,
-        status: TransactionStatus::Pending,
-        message: msg.message.clone(),
-        timestamp: Utc::now().naive_utc(),
-        cancelled: false,
-        direct_send_success: false,
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let carol_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
+        factories.clone(),
+        connection,
+        Some(TransactionServiceConfig {
+            spending_policy: SpendingPolicy {
+                max_transaction_amount: Some(200000 * uT),
+                recipient_denylist: vec![carol_node_identity.public_key().clone()],
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+    )
+    .await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let (_utxo, uo) = make_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            300000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Too large".to_string(),
+            None,
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::SpendingPolicyViolation(
+            SpendingPolicyViolation::TransactionMaximumExceeded { .. }
+        ))
+    ));
+
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .send_one_sided_transaction(
+            carol_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Denied recipient".to_string(),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::SpendingPolicyViolation(
+            SpendingPolicyViolation::RecipientDenied(_)
+        ))
+    ));
+
+    let mut violations = 0;
+    let delay = sleep(Duration::from_secs(10));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::SpendingPolicyViolation(_) = &*event.unwrap() {
+                    violations += 1;
+                    if violations == 2 {
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert_eq!(violations, 2);
+
+    alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Within policy".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_transactions_above_threshold_require_approval() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (approval_secret_key, approval_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
+        factories.clone(),
+        connection,
+        Some(TransactionServiceConfig {
+            approval: TransactionApprovalConfig {
+                threshold: Some(200000 * uT),
+                approval_public_key: Some(approval_public_key),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+    )
+    .await;
+
+    let (_utxo, uo) = make_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            300000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Needs approval".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // The transaction is held back until it is approved
+    let pending_approval = alice_ts_interface
+        .transaction_service_handle
+        .get_pending_approval_transactions()
+        .await
+        .unwrap();
+    assert_eq!(pending_approval.len(), 1);
+    assert_eq!(pending_approval[0].tx_id, tx_id);
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_pending_outbound_transactions()
+        .await
+        .unwrap()
+        .is_empty());
+
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .approve_transaction(tx_id, "123456".to_string())
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::TransactionApprovalError(
+            TransactionApprovalError::InvalidApprovalToken(_)
+        ))
+    ));
+
+    let approval_token = sign_approval(approval_secret_key, tx_id).unwrap();
+    let approved_tx_id = alice_ts_interface
+        .transaction_service_handle
+        .approve_transaction(tx_id, approval_token)
+        .await
+        .unwrap();
+    assert_eq!(approved_tx_id, tx_id);
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_pending_approval_transactions()
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Transactions below the threshold are sent straight away
+    alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Below threshold".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_pending_approval_transactions()
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_approval_rechecks_the_spending_policy() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (approval_secret_key, approval_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(
+        factories.clone(),
+        connection,
+        Some(TransactionServiceConfig {
+            approval: TransactionApprovalConfig {
+                threshold: Some(200000 * uT),
+                approval_public_key: Some(approval_public_key),
+                ..Default::default()
+            },
+            spending_policy: SpendingPolicy {
+                daily_spend_limit: Some(500000 * uT),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+    )
+    .await;
+
+    for _ in 0..2 {
+        let (_utxo, uo) = make_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+        alice_ts_interface
+            .output_manager_service_handle
+            .add_output(uo, None)
+            .await
+            .unwrap();
+    }
+
+    // Both transactions are within the daily limit on their own, so both are queued for approval
+    let mut tx_ids = Vec::new();
+    for _ in 0..2 {
+        let tx_id = alice_ts_interface
+            .transaction_service_handle
+            .send_transaction(
+                bob_node_identity.public_key().clone(),
+                300000 * uT,
+                OutputFeatures::default(),
+                100 * uT,
+                "Needs approval".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        tx_ids.push(tx_id);
+    }
+
+    let approval_token = sign_approval(approval_secret_key.clone(), tx_ids[0]).unwrap();
+    alice_ts_interface
+        .transaction_service_handle
+        .approve_transaction(tx_ids[0], approval_token)
+        .await
+        .unwrap();
+    let mut is_recorded = false;
+    for _ in 0..50 {
+        is_recorded = alice_ts_interface
+            .transaction_service_handle
+            .get_pending_outbound_transactions()
+            .await
+            .unwrap()
+            .contains_key(&tx_ids[0]);
+        if is_recorded {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert!(is_recorded, "The approved transaction should have been sent");
+
+    // Together they exceed the daily limit, so the second one can no longer be approved but stays in the queue
+    let approval_token = sign_approval(approval_secret_key, tx_ids[1]).unwrap();
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .approve_transaction(tx_ids[1], approval_token)
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::SpendingPolicyViolation(
+            SpendingPolicyViolation::DailyLimitExceeded { .. }
+        ))
+    ));
+    let pending_approval = alice_ts_interface
+        .transaction_service_handle
+        .get_pending_approval_transactions()
+        .await
+        .unwrap();
+    assert_eq!(pending_approval.len(), 1);
+    assert_eq!(pending_approval[0].tx_id, tx_ids[1]);
+}
+
+#[tokio::test]
+async fn test_sweep_all() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+
+    let mut total_value = MicroTari::zero();
+    for value in [100000 * uT, 200000 * uT, 300000 * uT] {
+        let (_utxo, uo) = make_input(&mut OsRng, value, &factories.commitment).await;
+        alice_ts_interface
+            .output_manager_service_handle
+            .add_output(uo, None)
+            .await
+            .unwrap();
+        total_value += value;
+    }
+    // This output is worth less than the fee to spend it, so it is left behind
+    let dust_value = 50 * uT;
+    let (_utxo, uo) = make_input(&mut OsRng, dust_value, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let tx_ids = alice_ts_interface
+        .transaction_service_handle
+        .sweep_all(bob_node_identity.public_key().clone(), 20 * uT)
+        .await
+        .unwrap();
+    assert_eq!(tx_ids.len(), 1);
+
+    let completed_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_ids[0])
+        .await
+        .unwrap();
+    assert_eq!(completed_tx.amount + completed_tx.fee, total_value);
+    assert_eq!(completed_tx.transaction.body.inputs().len(), 3);
+    assert_eq!(completed_tx.transaction.body.outputs().len(), 1);
+
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, dust_value);
+    assert_eq!(balance.pending_incoming_balance, MicroTari::zero());
+
+    // Only dust is left, so there is nothing more to sweep
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .sweep_all(bob_node_identity.public_key().clone(), 20 * uT)
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::OutputManagerError(
+            OutputManagerError::NotEnoughFunds
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_decoy_transactions_are_flagged() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+
+    let (_utxo, uo) = make_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_decoy_transaction(5000 * uT, 5 * uT)
+        .await
+        .unwrap();
+
+    let completed_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    assert!(completed_tx.is_decoy);
+    assert_eq!(completed_tx.amount, 5000 * uT);
+    assert_eq!(completed_tx.source_public_key, completed_tx.destination_public_key);
+}
+
+#[tokio::test]
+async fn test_restarting_transaction_protocols() {
+    let factories = CryptoFactories::default();
+    let (alice_connection, _temp_dir) = make_wallet_database_connection(None);
+    let alice_backend = TransactionServiceSqliteDatabase::new(alice_connection.clone(), None);
+
+    let (bob_connection, _temp_dir2) = make_wallet_database_connection(None);
+    let bob_backend = TransactionServiceSqliteDatabase::new(bob_connection.clone(), None);
+
+    let base_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let alice_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let bob_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    // Bob is going to send a transaction to Alice
+    let alice = TestParams::new(&mut OsRng);
+    let bob = TestParams::new(&mut OsRng);
+    let (utxo, input) = make_input(&mut OsRng, MicroTari(2000), &factories.commitment).await;
+    let constants = create_consensus_constants(0);
+    let fee_calc = Fee::new(*constants.transaction_weight());
+    let mut builder = SenderTransactionProtocol::builder(1, constants);
+    let fee = fee_calc.calculate(MicroTari(4), 1, 1, 1, 0);
+    let script_private_key = PrivateKey::random(&mut OsRng);
+    builder
+        .with_lock_height(0)
+        .with_fee_per_gram(MicroTari(4))
+        .with_offset(bob.offset.clone())
+        .with_private_nonce(bob.nonce)
+        .with_input(utxo, input)
+        .with_amount(0, MicroTari(2000) - fee - MicroTari(10))
+        .with_recipient_data(
+            0,
+            script!(Nop),
+            PrivateKey::random(&mut OsRng),
+            Default::default(),
+            PrivateKey::random(&mut OsRng),
+            Covenant::default(),
+            MicroTari::zero(),
+        )
+        .with_change_script(
+            script!(Nop),
+            inputs!(PublicKey::from_secret_key(&script_private_key)),
+            script_private_key,
+        );
+    let mut bob_stp = builder.build(&factories, None, u64::MAX).unwrap();
+    let msg = bob_stp.build_single_round_message().unwrap();
+    let bob_pre_finalize = bob_stp.clone();
+
+    let tx_id = msg.tx_id;
+
+    let sender_info = TransactionSenderMessage::Single(Box::new(msg.clone()));
+    let receiver_protocol =
+        ReceiverTransactionProtocol::new(sender_info, alice.nonce.clone(), alice.spend_key, &factories);
+
+    let alice_reply = receiver_protocol.get_signed_data().unwrap().clone();
+
+    bob_stp
+        .add_single_recipient_info(alice_reply.clone(), &factories.range_proof)
+        .unwrap();
+
+    match bob_stp.finalize(&factories, None, u64::MAX) {
+        Ok(_) => (),
+        Err(e) => panic!("Should be able to finalize tx: {}", e),
+    };
+    let tx = bob_stp.get_transaction().unwrap().clone();
+
+    let inbound_tx = InboundTransaction {
+        tx_id,
+        source_public_key: bob_identity.public_key().clone(),
+        amount: msg.amount,
+        receiver_protocol,
+        status: TransactionStatus::Pending,
+        message: msg.message.clone(),
+        timestamp: Utc::now().naive_utc(),
+        cancelled: false,
+        direct_send_success: false,
         send_count: 0,
         last_send_timestamp: None,
+        expires_at: None,
     };
 
     alice_backend
@@ -2962,6 +3612,7 @@ async fn test_restarting_transaction_protocols() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: None,
+        expires_at: None,
     };
     bob_backend
         .write(WriteOperation::Insert(DbKeyValuePair::PendingOutboundTransaction(
@@ -3022,10 +3673,7 @@ async fn test_restarting_transaction_protocols() {
         .await
         .is_ok());
 
-    let finalized_transaction_message = proto::TransactionFinalizedMessage {
-        tx_id: tx_id.as_u64(),
-        transaction: Some(tx.try_into().unwrap()),
-    };
+    let finalized_transaction_message = proto::TransactionFinalizedMessage::new(tx_id, tx).unwrap();
 
     alice_ts_interface
         .transaction_finalize_message_channel
@@ -3679,7 +4327,7 @@ async fn test_coinbase_abandoned() {
                             completed = true;
                         }
                     },
-                    TransactionEvent::TransactionMinedUnconfirmed{tx_id, num_confirmations:_, is_valid: _} => {
+                    TransactionEvent::TransactionMinedUnconfirmed{tx_id, num_confirmations:_, confirmations_required: _, is_valid: _} => {
                          if tx_id == &tx_id2  {
                             mined_unconfirmed = true;
                         }
@@ -4016,6 +4664,51 @@ async fn test_coinbase_transaction_reused_for_same_height() {
     );
 }
 
+#[tokio::test]
+async fn test_coinbase_transaction_with_extra() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+
+    let block_height = 10;
+    let fees = 2000 * uT;
+    let reward = 1_000_000 * uT;
+
+    let tx1 = ts_interface
+        .transaction_service_handle
+        .generate_coinbase_transaction_with_extra(reward, fees, block_height, b"pool-a".to_vec())
+        .await
+        .unwrap();
+    // The same coinbase with other extra data is a new transaction
+    let tx2 = ts_interface
+        .transaction_service_handle
+        .generate_coinbase_transaction_with_extra(reward, fees, block_height, b"pool-b".to_vec())
+        .await
+        .unwrap();
+    assert_ne!(tx1, tx2);
+
+    let transactions = ts_interface
+        .transaction_service_handle
+        .get_completed_transactions()
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 2);
+    let mut extras = transactions
+        .values()
+        .map(|tx| tx.coinbase_extra().unwrap().to_vec())
+        .collect::<Vec<_>>();
+    extras.sort();
+    assert_eq!(extras, vec![b"pool-a".to_vec(), b"pool-b".to_vec()]);
+
+    let err = ts_interface
+        .transaction_service_handle
+        .generate_coinbase_transaction_with_extra(reward, fees, block_height + 1, vec![0u8; 1024])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TransactionServiceError::OutputManagerError(_)));
+}
+
 #[tokio::test]
 async fn test_transaction_resending() {
     let factories = CryptoFactories::default();
@@ -4057,6 +4750,7 @@ async fn test_transaction_resending() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -4177,43 +4871,286 @@ async fn test_transaction_resending() {
     let alice_finalize_message = try_decode_finalized_transaction_message(call.1.to_vec()).unwrap();
     assert_eq!(alice_finalize_message.tx_id, tx_id.as_u64());
 
-    // See if sending a second message before cooldown and see if it is ignored
-    alice_ts_interface
-        .transaction_ack_message_channel
-        .send(create_dummy_message(
-            bob_reply_message.clone().into(),
-            bob_node_identity.public_key(),
-        ))
+    // See if sending a second message before cooldown and see if it is ignored
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            bob_reply_message.clone().into(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    assert!(alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(8))
+        .await
+        .is_err());
+
+    // Wait for the cooldown to expire but before the resend period has elapsed see if a repeat illicts a response.
+    sleep(Duration::from_secs(6)).await;
+
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            bob_reply_message.into(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(30))
+        .await
+        .expect("Alice call wait 3");
+
+    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let alice_finalize_message = try_decode_finalized_transaction_message(call.1.to_vec()).unwrap();
+    assert_eq!(alice_finalize_message.tx_id, tx_id);
+}
+
+#[tokio::test]
+async fn test_request_resend() {
+    let factories = CryptoFactories::default();
+
+    let alice_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _tempdir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+
+    let (_utxo, uo) = make_input(&mut OsRng, 250000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Testing Message".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .expect("Alice call wait 1");
+    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let alice_sender_message = try_decode_sender_message(call.1.to_vec()).unwrap();
+
+    let (connection, _tempdir) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(
+        factories,
+        connection,
+        Some(TransactionServiceConfig {
+            finalization_request_timeout: Duration::from_secs(5),
+            ..Default::default()
+        }),
+    )
+    .await;
+    bob_ts_interface
+        .transaction_send_message_channel
+        .send(create_dummy_message(
+            alice_sender_message.into(),
+            alice_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+    bob_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .expect("Bob call wait 1");
+    sleep(Duration::from_secs(1)).await;
+    let _result = bob_ts_interface.outbound_service_mock_state.take_calls().await;
+
+    // Alice never sends the finalized transaction, so Bob asks for it on request
+    bob_ts_interface
+        .transaction_service_handle
+        .request_resend(tx_id)
+        .await
+        .unwrap();
+    bob_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(2))
+        .await
+        .expect("Bob call wait 2");
+    let call = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let bob_reply_message = try_decode_transaction_reply_message(call.1.to_vec()).unwrap();
+    assert_eq!(bob_reply_message.tx_id, tx_id);
+    let _result = bob_ts_interface.outbound_service_mock_state.take_calls().await;
+
+    // and again automatically once the finalization request timeout has elapsed
+    bob_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(10))
+        .await
+        .expect("Bob call wait 3");
+    let call = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let bob_reply_message = try_decode_transaction_reply_message(call.1.to_vec()).unwrap();
+    assert_eq!(bob_reply_message.tx_id, tx_id);
+
+    assert!(bob_ts_interface
+        .transaction_service_handle
+        .request_resend(TxId::new_random())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_out_of_band_transaction_negotiation() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let (connection, _tempdir) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+    let mut bob_event_stream = bob_ts_interface.transaction_service_handle.get_event_stream();
+
+    let (_utxo, uo) = make_input(&mut OsRng, 250000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_ts_interface.base_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Testing Message".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .expect("Alice call wait 1");
+
+    // The sender's message is carried to Bob out-of-band
+    let sender_message = alice_ts_interface
+        .transaction_service_handle
+        .export_transaction_message(tx_id)
+        .await
+        .unwrap();
+    let imported_tx_id = bob_ts_interface
+        .transaction_service_handle
+        .import_transaction_message(sender_message)
+        .await
+        .unwrap();
+    assert_eq!(imported_tx_id, tx_id);
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = bob_event_stream.recv() => {
+                if let TransactionEvent::ReceivedTransaction(id) = &*event.unwrap() {
+                    assert_eq!(*id, tx_id);
+                    break;
+                }
+            },
+            () = &mut delay => {
+                panic!("Timeout while waiting for Bob to receive the transaction");
+            },
+        }
+    }
+
+    // Bob's reply is carried back to Alice
+    let reply_message = bob_ts_interface
+        .transaction_service_handle
+        .export_transaction_message(tx_id)
+        .await
+        .unwrap();
+    alice_ts_interface
+        .transaction_service_handle
+        .import_transaction_message(reply_message)
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::ReceivedTransactionReply(id) = &*event.unwrap() {
+                    assert_eq!(*id, tx_id);
+                    break;
+                }
+            },
+            () = &mut delay => {
+                panic!("Timeout while waiting for Alice to receive the reply");
+            },
+        }
+    }
+
+    // And the finalized transaction is carried to Bob
+    let finalized_message = alice_ts_interface
+        .transaction_service_handle
+        .export_transaction_message(tx_id)
         .await
         .unwrap();
-
-    assert!(alice_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(1, Duration::from_secs(8))
+    bob_ts_interface
+        .transaction_service_handle
+        .import_transaction_message(finalized_message)
         .await
-        .is_err());
+        .unwrap();
 
-    // Wait for the cooldown to expire but before the resend period has elapsed see if a repeat illicts a response.
-    sleep(Duration::from_secs(6)).await;
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = bob_event_stream.recv() => {
+                if let TransactionEvent::ReceivedFinalizedTransaction(id) = &*event.unwrap() {
+                    assert_eq!(*id, tx_id);
+                    break;
+                }
+            },
+            () = &mut delay => {
+                panic!("Timeout while waiting for Bob to receive the finalized transaction");
+            },
+        }
+    }
 
-    alice_ts_interface
-        .transaction_ack_message_channel
-        .send(create_dummy_message(
-            bob_reply_message.into(),
-            bob_node_identity.public_key(),
-        ))
+    let bob_completed_tx = bob_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
         .await
         .unwrap();
-
-    alice_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(1, Duration::from_secs(30))
+    let alice_completed_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
         .await
-        .expect("Alice call wait 3");
+        .unwrap();
+    assert_eq!(bob_completed_tx.transaction, alice_completed_tx.transaction);
 
-    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-    let alice_finalize_message = try_decode_finalized_transaction_message(call.1.to_vec()).unwrap();
-    assert_eq!(alice_finalize_message.tx_id, tx_id);
+    // The recipient has nothing left to export and garbage is rejected
+    assert!(bob_ts_interface
+        .transaction_service_handle
+        .export_transaction_message(tx_id)
+        .await
+        .is_err());
+    assert!(matches!(
+        bob_ts_interface
+            .transaction_service_handle
+            .import_transaction_message(vec![0xff; 8])
+            .await,
+        Err(TransactionServiceError::InvalidOutOfBandMessage(_))
+    ));
 }
 
 #[tokio::test]
@@ -4277,6 +5214,7 @@ async fn test_resend_on_startup() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        expires_at: None,
     };
     let (connection, _temp_dir) = make_wallet_database_connection(None);
     let alice_backend = TransactionServiceSqliteDatabase::new(connection.clone(), None);
@@ -4403,6 +5341,7 @@ async fn test_resend_on_startup() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        expires_at: None,
     };
     let (bob_connection, _temp_dir) = make_wallet_database_connection(None);
     let bob_backend = TransactionServiceSqliteDatabase::new(bob_connection.clone(), None);
@@ -4544,6 +5483,7 @@ async fn test_replying_to_cancelled_tx() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -4666,6 +5606,7 @@ async fn test_transaction_timeout_cancellation() {
             OutputFeatures::default(),
             20 * uT,
             "Testing Message".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -4757,6 +5698,7 @@ async fn test_transaction_timeout_cancellation() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        expires_at: None,
     };
     let (bob_connection, _temp_dir) = make_wallet_database_connection(None);
     let bob_backend = TransactionServiceSqliteDatabase::new(bob_connection.clone(), None);
@@ -4870,6 +5812,85 @@ async fn test_transaction_timeout_cancellation() {
     assert!(transaction_cancelled, "Transaction must be cancelled");
 }
 
+#[tokio::test]
+async fn test_transaction_expiry_cancellation() {
+    let factories = CryptoFactories::default();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (alice_connection, _tempdir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), alice_connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let alice_total_available = 250000 * uT;
+    let (_utxo, uo) = make_input(&mut OsRng, alice_total_available, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    // The default cancellation timeout is days away, so only the expiry can cancel the transaction
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction_with_ttl(
+            bob_node_identity.public_key().clone(),
+            10000 * uT,
+            OutputFeatures::default(),
+            20 * uT,
+            "Testing Message".to_string(),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(3, Duration::from_secs(60))
+        .await
+        .expect("Alice call wait 1");
+    let calls = alice_ts_interface.outbound_service_mock_state.take_calls().await;
+
+    // The expiry is communicated to the recipient
+    if let TransactionSenderMessage::Single(data) = try_decode_sender_message(calls[0].1.to_vec()).unwrap() {
+        assert_eq!(data.tx_id, tx_id);
+        assert!(data.expires_at.is_some());
+    } else {
+        panic!("Should be a Single Transaction Sender Message")
+    }
+    let alice_cancelled_message = try_decode_transaction_cancelled_message(calls[2].1.to_vec()).unwrap();
+    assert_eq!(alice_cancelled_message.tx_id, tx_id.as_u64());
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    let mut transaction_cancelled = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                 if let TransactionEvent::TransactionCancelled(t, _) = &*event.unwrap() {
+                    if t == &tx_id {
+                        transaction_cancelled = true;
+                        break;
+                    }
+                 }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(transaction_cancelled, "Transaction must be cancelled");
+
+    // The inputs are unlocked again
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, alice_total_available);
+}
+
 /// This test will check that the Transaction Service starts the tx broadcast protocol correctly and reacts correctly
 /// to a tx being broadcast and to a tx being rejected.
 #[tokio::test]
@@ -4920,6 +5941,7 @@ async fn transaction_service_tx_broadcast() {
             OutputFeatures::default(),
             100 * uT,
             "Testing Message".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -4980,6 +6002,7 @@ async fn transaction_service_tx_broadcast() {
             OutputFeatures::default(),
             20 * uT,
             "Testing Message2".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -5260,6 +6283,7 @@ async fn broadcast_all_completed_transactions_on_startup() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        is_decoy: false,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -5491,6 +6515,60 @@ async fn test_update_faux_tx_on_oms_validation() {
     );
 }
 
+#[tokio::test]
+async fn test_transaction_tags() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+
+    let mut tx_ids = Vec::new();
+    for amount in [10000, 20000] {
+        let tx_id = alice_ts_interface
+            .transaction_service_handle
+            .import_utxo_with_status(
+                MicroTari::from(amount),
+                alice_ts_interface.base_node_identity.public_key().clone(),
+                "Payment".to_string(),
+                None,
+                ImportStatus::Imported,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        tx_ids.push(tx_id);
+    }
+
+    let ts = &mut alice_ts_interface.transaction_service_handle;
+    ts.tag_transaction(tx_ids[0], " invoices ".to_string()).await.unwrap();
+    ts.tag_transaction(tx_ids[0], "2022".to_string()).await.unwrap();
+    ts.tag_transaction(tx_ids[1], "invoices".to_string()).await.unwrap();
+
+    assert_eq!(ts.get_transaction_tags(tx_ids[0]).await.unwrap(), vec![
+        "2022".to_string(),
+        "invoices".to_string()
+    ]);
+    let tagged = ts.get_transactions_by_tag("invoices".to_string()).await.unwrap();
+    assert_eq!(tagged.len(), 2);
+
+    assert!(matches!(
+        ts.tag_transaction(tx_ids[0], "a;b".to_string()).await,
+        Err(TransactionServiceError::InvalidTransactionTag(_))
+    ));
+    assert!(matches!(
+        ts.tag_transaction(TxId::from(123u64), "invoices".to_string()).await,
+        Err(TransactionServiceError::TransactionDoesNotExistError)
+    ));
+
+    ts.untag_transaction(tx_ids[1], "invoices".to_string()).await.unwrap();
+    let tagged = ts.get_transactions_by_tag("invoices".to_string()).await.unwrap();
+    assert_eq!(tagged.len(), 1);
+    let all_tags = ts.get_all_transaction_tags().await.unwrap();
+    assert_eq!(all_tags.len(), 1);
+    assert_eq!(all_tags[&tx_ids[0]].len(), 2);
+}
+
 #[tokio::test]
 async fn test_get_fee_per_gram_per_block_basic() {
     let factories = CryptoFactories::default();
@@ -5512,5 +6590,151 @@ async fn test_get_fee_per_gram_per_block_basic() {
         .await
         .unwrap();
     assert_eq!(estimates.stats, stats.into_iter().map(Into::into).collect::<Vec<_>>());
-    assert_eq!(estimates.stats.len(), 1)
+    assert_eq!(estimates.stats.len(), 1);
+    assert_eq!(estimates.fee_floor, Fee::MINIMUM_FEE_PER_GRAM);
+}
+
+#[tokio::test]
+async fn test_sends_below_the_fee_floor_are_rejected() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let (_utxo, uo) = make_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .send_one_sided_transaction(
+            bob_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            0 * uT,
+            "Below the floor".to_string(),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionServiceError::FeeBelowFloor { fee_per_gram, floor })
+            if fee_per_gram == 0 * uT && floor == Fee::MINIMUM_FEE_PER_GRAM
+    ));
+    // The transaction was rejected before any of the outputs were encumbered
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, 1000000 * uT);
+
+    alice_ts_interface
+        .transaction_service_handle
+        .send_one_sided_transaction(
+            bob_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            Fee::MINIMUM_FEE_PER_GRAM,
+            "At the floor".to_string(),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_cancelling_a_send_that_is_being_sent_releases_its_inputs() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+    let (_utxo, uo) = make_input(&mut OsRng, 1000000 * uT, &factories.commitment).await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+
+    // A send whose deadline has passed fails before any of the outputs are encumbered
+    let result = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction_with_cancellation(
+            bob_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Past the deadline".to_string(),
+            CancellationToken::new().with_deadline(Instant::now()),
+        )
+        .await;
+    assert!(matches!(result, Err(TransactionServiceError::Timeout)));
+
+    // The direct send to Bob stalls, as it does while the recipient is being discovered over a slow connection
+    alice_ts_interface
+        .outbound_service_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::QueuedSuccessDelay(Duration::from_secs(30)),
+            broadcast: ResponseType::Queued,
+        })
+        .await;
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction_with_cancellation(
+            bob_node_identity.public_key().clone(),
+            100000 * uT,
+            OutputFeatures::default(),
+            100 * uT,
+            "Cancelled while sending".to_string(),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, 0 * uT);
+
+    alice_ts_interface
+        .transaction_service_handle
+        .cancel_transaction(tx_id)
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut reason = None;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionCancelled(id, r) = &*event.unwrap() {
+                    if id == &tx_id {
+                        reason = Some(*r);
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert_eq!(reason, Some(TxCancellationReason::UserCancelled));
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, 1000000 * uT);
+    assert_eq!(balance.pending_outgoing_balance, 0 * uT);
 }
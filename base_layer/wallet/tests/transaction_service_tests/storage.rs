@@ -35,7 +35,10 @@ use tari_core::{
         tari_amount::{uT, MicroTari},
         test_helpers::{create_unblinded_output, TestParams},
         transaction_components::{OutputFeatures, Transaction},
-        transaction_protocol::sender::TransactionSenderMessage,
+        transaction_protocol::{
+            aggregated_sender::{SenderSigningRound, SenderSigningSession},
+            sender::TransactionSenderMessage,
+        },
         CryptoFactories,
         ReceiverTransactionProtocol,
         SenderTransactionProtocol,
@@ -53,6 +56,9 @@ use tari_wallet::{
             CompletedTransaction,
             InboundTransaction,
             OutboundTransaction,
+            TransactionNegotiationStage,
+            TransactionProtocolSnapshot,
+            TransactionProtocolState,
             TxCancellationReason,
             WalletTransaction,
         },
@@ -120,6 +126,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         });
         assert!(!db.transaction_exists(tx_id).unwrap(), "TxId should not exist");
 
@@ -129,6 +136,52 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
         assert!(db.transaction_exists(tx_id).unwrap(), "TxId should exist");
     }
 
+    for stage in [
+        TransactionNegotiationStage::SenderQueued,
+        TransactionNegotiationStage::SenderWaitForReply,
+    ] {
+        db.save_transaction_protocol_state(TransactionProtocolState::new(
+            outbound_txs[0].tx_id,
+            stage,
+            outbound_txs[0].destination_public_key.clone(),
+            TransactionProtocolSnapshot::Sender(Box::new(stp.clone())),
+        ))
+        .unwrap();
+    }
+    let protocol_states = db.get_transaction_protocol_states().unwrap();
+    assert_eq!(protocol_states.len(), 1);
+    assert_eq!(protocol_states[0].tx_id, outbound_txs[0].tx_id);
+    assert_eq!(
+        protocol_states[0].stage,
+        TransactionNegotiationStage::SenderWaitForReply
+    );
+    assert_eq!(
+        protocol_states[0].counterparty_public_key,
+        outbound_txs[0].destination_public_key
+    );
+    assert!(matches!(
+        protocol_states[0].snapshot,
+        TransactionProtocolSnapshot::Sender(_)
+    ));
+    db.remove_transaction_protocol_state(outbound_txs[0].tx_id).unwrap();
+    assert!(db.get_transaction_protocol_states().unwrap().is_empty());
+
+    let recipient = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    let mut session = SenderSigningSession::new(PrivateKey::random(&mut OsRng), recipient);
+    let session_key = session.public_key().clone();
+    db.save_aggregated_sender_session(session.clone()).unwrap();
+    let party_keys = vec![
+        session_key.clone(),
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+    ];
+    let commitment = session.add_party_keys(party_keys).unwrap();
+    db.save_aggregated_sender_session(session).unwrap();
+    let session = db.get_aggregated_sender_session(&session_key).unwrap().unwrap();
+    assert_eq!(session.round(), SenderSigningRound::NonceCommitments);
+    assert_eq!(session.nonce_commitment(), commitment);
+    db.remove_aggregated_sender_session(&session_key).unwrap();
+    assert!(db.get_aggregated_sender_session(&session_key).unwrap().is_none());
+
     let retrieved_outbound_txs = db.get_pending_outbound_transactions().unwrap();
     assert_eq!(outbound_txs.len(), messages.len());
     for i in outbound_txs.iter().take(messages.len()) {
@@ -175,6 +228,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            expires_at: None,
         });
         assert!(!db.transaction_exists(tx_id).unwrap(), "TxId should not exist");
         db.add_pending_inbound_transaction(tx_id, inbound_txs[i].clone())
@@ -252,6 +306,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            is_decoy: false,
         });
         db.complete_outbound_transaction(outbound_txs[i].tx_id, completed_txs[i].clone())
             .unwrap();
@@ -42,9 +42,12 @@ use tari_core::{
     blocks::BlockHeader,
     proto::{
         base_node::{
+            QueryDeletedResponse,
             TxLocation as TxLocationProto,
             TxQueryBatchResponse as TxQueryBatchResponseProto,
             TxQueryBatchResponses as TxQueryBatchResponsesProto,
+            UtxoQueryResponse,
+            UtxoQueryResponses,
         },
         types::Signature as SignatureProto,
     },
@@ -69,6 +72,8 @@ use tari_wallet::{
         config::TransactionServiceConfig,
         error::TransactionServiceError,
         handle::{TransactionEvent, TransactionEventReceiver, TransactionEventSender},
+        mempool_evictions::MempoolEvictions,
+        messaging::DhtMessagingBackend,
         protocols::{
             transaction_broadcast_protocol::TransactionBroadcastProtocol,
             transaction_validation_protocol::TransactionValidationProtocol,
@@ -132,7 +137,8 @@ pub async fn setup() -> (
     task::spawn(oms_reply_channel_task(oms_request_receiver));
 
     let (oms_event_publisher, _) = broadcast::channel(200);
-    let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
+    let output_manager_service_handle =
+        OutputManagerHandle::new(oms_request_sender, oms_event_publisher, Watch::new(None));
 
     let (outbound_message_requester, mock_outbound_service) = create_outbound_service_mock(100);
     let outbound_mock_state = mock_outbound_service.get_state();
@@ -146,7 +152,10 @@ pub async fn setup() -> (
     let resources = TransactionServiceResources {
         db,
         output_manager_service: output_manager_service_handle,
-        outbound_message_service: outbound_message_requester,
+        messaging: Arc::new(DhtMessagingBackend::new(
+            outbound_message_requester,
+            ts_event_publisher.clone(),
+        )),
         connectivity: wallet_connectivity.clone(),
         event_publisher: ts_event_publisher,
         node_identity: client_node_identity,
@@ -375,6 +384,96 @@ async fn tx_broadcast_protocol_submit_rejection() {
     assert!(cancelled, "Should have cancelled transaction");
 }
 
+/// Test submitting a transaction that spends the change of an unmined transaction, which the base node rejects as an
+/// orphan until both are submitted as a package
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn tx_broadcast_protocol_submits_orphan_with_unmined_parent() {
+    let (
+        resources,
+        _outbound_mock_state,
+        mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        _transaction_event_receiver,
+        wallet_connectivity,
+    ) = setup().await;
+    let mut event_stream = resources.event_publisher.subscribe();
+
+    let factories = CryptoFactories::default();
+    let (_utxo, uo0) = make_input(&mut OsRng, 10 * T, &factories.commitment).await;
+    let (parent_txs, parent_outputs) = schema_to_transaction(&[txn_schema!(from: vec![uo0], to: vec![2 * T])]);
+    let (child_txs, _child_outputs) =
+        schema_to_transaction(&[txn_schema!(from: vec![parent_outputs[0].clone()], to: vec![1 * T])]);
+    let parent = (*parent_txs[0]).clone();
+    let child = (*child_txs[0]).clone();
+    for (tx_id, tx) in [(1u64, parent.clone()), (2u64, child.clone())] {
+        let completed_tx = CompletedTransaction::new(
+            tx_id.into(),
+            CommsPublicKey::default(),
+            CommsPublicKey::default(),
+            1 * T,
+            200 * uT,
+            tx,
+            TransactionStatus::Completed,
+            "Test".to_string(),
+            Utc::now().naive_local(),
+            TransactionDirection::Outbound,
+            None,
+            None,
+            None,
+        );
+        resources
+            .db
+            .insert_completed_transaction(tx_id.into(), completed_tx)
+            .unwrap();
+    }
+
+    let timeout_update_watch = Watch::new(Duration::from_secs(1));
+    wallet_connectivity.notify_base_node_set(server_node_identity.to_peer());
+    let mut connection = mock_rpc_server
+        .create_connection(server_node_identity.to_peer(), "t/bnwallet/1".into())
+        .await;
+    wallet_connectivity.set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+
+    rpc_service_state.set_submit_transaction_response(TxSubmissionResponse {
+        accepted: false,
+        rejection_reason: TxSubmissionRejectionReason::Orphan,
+        is_synced: true,
+    });
+
+    let protocol =
+        TransactionBroadcastProtocol::new(2u64.into(), resources.clone(), timeout_update_watch.get_receiver());
+    task::spawn(protocol.execute());
+
+    let delay = sleep(Duration::from_secs(5));
+    tokio::pin!(delay);
+    let mut broadcast = false;
+    loop {
+        tokio::select! {
+            event = event_stream.recv() => {
+                if let TransactionEvent::TransactionBroadcast(tx_id) = &*event.unwrap() {
+                    assert_eq!(*tx_id, TxId::from(2u64));
+                    broadcast = true;
+                    break;
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(broadcast, "Should have received a broadcast event");
+
+    assert_eq!(rpc_service_state.take_submit_transaction_package_calls(), vec![vec![
+        parent, child
+    ]]);
+    let db_completed_tx = resources.db.get_completed_transaction(2u64.into()).unwrap();
+    assert_eq!(db_completed_tx.status, TransactionStatus::Broadcast);
+}
+
 /// Test restarting a protocol which means the first step is a query not a submission, detecting the Tx is not in the
 /// mempool, resubmit the tx and then have it mined
 #[tokio::test]
@@ -736,7 +835,7 @@ async fn tx_validation_protocol_tx_becomes_mined_unconfirmed_then_confirmed() {
         rpc_service_state,
         _shutdown,
         _temp_dir,
-        _transaction_event_receiver,
+        mut transaction_event_receiver,
         wallet_connectivity,
     ) = setup().await;
     // Now we add the connection
@@ -878,6 +977,218 @@ async fn tx_validation_protocol_tx_becomes_mined_unconfirmed_then_confirmed() {
         TransactionStatus::MinedConfirmed
     );
     assert_eq!(completed_txs.get(&2u64.into()).unwrap().confirmations.unwrap(), 4);
+
+    // Without a verified header chain the confirmation is only on the base node's word, which is published
+    let mut unverified = vec![];
+    while let Ok(event) = transaction_event_receiver.try_recv() {
+        if let TransactionEvent::TransactionConfirmedUnverified(tx_id) = &*event {
+            unverified.push(*tx_id);
+        }
+    }
+    assert_eq!(unverified, vec![TxId::from(2u64)]);
+}
+
+/// Test that an incoming transaction whose input was spent by another transaction is marked as double spent
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn tx_validation_protocol_detects_double_spent_incoming_tx() {
+    let (
+        resources,
+        _outbound_mock_state,
+        mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        wallet_connectivity,
+    ) = setup().await;
+    let mut connection = mock_rpc_server
+        .create_connection(server_node_identity.to_peer(), "t/bnwallet/1".into())
+        .await;
+    wallet_connectivity.set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+    add_transaction_to_database(
+        1u64.into(),
+        1 * T,
+        Some(TransactionStatus::Broadcast),
+        None,
+        resources.db.clone(),
+    )
+    .await;
+    let factories = CryptoFactories::default();
+    let (_utxo, uo) = make_input(&mut OsRng, 10 * T, &factories.commitment).await;
+    let (txs, _) = schema_to_transaction(&[txn_schema!(from: vec![uo], to: vec![1 * T])]);
+    let inbound_tx = CompletedTransaction::new(
+        2u64.into(),
+        CommsPublicKey::default(),
+        CommsPublicKey::default(),
+        1 * T,
+        200 * uT,
+        (*txs[0]).clone(),
+        TransactionStatus::Broadcast,
+        "Test".to_string(),
+        Utc::now().naive_local(),
+        TransactionDirection::Inbound,
+        None,
+        None,
+        None,
+    );
+    let input_hash = inbound_tx.transaction.body.inputs()[0].output_hash();
+    resources
+        .db
+        .insert_completed_transaction(2u64.into(), inbound_tx)
+        .unwrap();
+
+    // Neither transaction is mined, but the input of the inbound one was spent at height 4
+    let not_stored = |tx_id: TxId| {
+        let tx = resources.db.get_completed_transaction(tx_id).unwrap();
+        TxQueryBatchResponseProto {
+            signature: Some(SignatureProto::from(
+                tx.transaction.first_kernel_excess_sig().unwrap().clone(),
+            )),
+            location: TxLocationProto::from(TxLocation::NotStored) as i32,
+            block_hash: None,
+            confirmations: 0,
+            block_height: 0,
+            mined_timestamp: None,
+        }
+    };
+    rpc_service_state.set_transaction_query_batch_responses(TxQueryBatchResponsesProto {
+        responses: vec![not_stored(1u64.into()), not_stored(2u64.into())],
+        is_synced: true,
+        tip_hash: Some([5u8; 32].to_vec()),
+        height_of_longest_chain: 5,
+        tip_mined_timestamp: Some(0),
+    });
+    rpc_service_state.set_utxo_query_response(UtxoQueryResponses {
+        responses: vec![UtxoQueryResponse {
+            output: None,
+            mmr_position: 7,
+            mined_height: 2,
+            mined_in_block: [2u8; 32].to_vec(),
+            output_hash: input_hash.to_vec(),
+            mined_timestamp: 0,
+        }],
+        best_block: [5u8; 32].to_vec(),
+        height_of_longest_chain: 5,
+    });
+    rpc_service_state.set_query_deleted_response(QueryDeletedResponse {
+        deleted_positions: vec![7],
+        not_deleted_positions: vec![],
+        best_block: [5u8; 32].to_vec(),
+        height_of_longest_chain: 5,
+        blocks_deleted_in: vec![[4u8; 32].to_vec()],
+        heights_deleted_at: vec![4],
+    });
+
+    let protocol = TransactionValidationProtocol::new(
+        1.into(),
+        resources.db.clone(),
+        wallet_connectivity.clone(),
+        resources.config.clone(),
+        resources.event_publisher.clone(),
+        resources.output_manager_service.clone(),
+    );
+    let result = task::spawn(protocol.execute()).await.unwrap();
+    assert!(result.is_ok());
+
+    // Only the inbound transaction is checked
+    let utxo_query_calls = rpc_service_state.take_utxo_query_calls();
+    assert_eq!(utxo_query_calls, vec![vec![input_hash.to_vec()]]);
+    assert_eq!(
+        resources.db.get_completed_transaction(1u64.into()).unwrap().status,
+        TransactionStatus::Broadcast
+    );
+    let double_spent = resources
+        .db
+        .get_completed_transaction_cancelled_or_not(2u64.into())
+        .unwrap();
+    assert_eq!(double_spent.status, TransactionStatus::DoubleSpent);
+    assert_eq!(double_spent.cancelled, Some(TxCancellationReason::DoubleSpend));
+
+    let mut double_spent_event = false;
+    while let Ok(event) = transaction_event_receiver.try_recv() {
+        if let TransactionEvent::TransactionDoubleSpent(tx_id) = &*event {
+            assert_eq!(*tx_id, 2u64.into());
+            double_spent_event = true;
+        }
+    }
+    assert!(double_spent_event, "Expected a TransactionDoubleSpent event");
+}
+
+/// Test that a broadcast transaction that is no longer in the mempool is reported as evicted once per backoff period
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn tx_validation_protocol_detects_mempool_eviction() {
+    let (
+        resources,
+        _outbound_mock_state,
+        mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        wallet_connectivity,
+    ) = setup().await;
+    let mut connection = mock_rpc_server
+        .create_connection(server_node_identity.to_peer(), "t/bnwallet/1".into())
+        .await;
+    wallet_connectivity.set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+    add_transaction_to_database(
+        1u64.into(),
+        1 * T,
+        Some(TransactionStatus::Broadcast),
+        None,
+        resources.db.clone(),
+    )
+    .await;
+    let tx1 = resources.db.get_completed_transaction(1u64.into()).unwrap();
+    rpc_service_state.set_transaction_query_batch_responses(TxQueryBatchResponsesProto {
+        responses: vec![TxQueryBatchResponseProto {
+            signature: Some(SignatureProto::from(
+                tx1.transaction.first_kernel_excess_sig().unwrap().clone(),
+            )),
+            location: TxLocationProto::from(TxLocation::NotStored) as i32,
+            block_hash: None,
+            confirmations: 0,
+            block_height: 0,
+            mined_timestamp: None,
+        }],
+        is_synced: true,
+        tip_hash: Some([5u8; 32].to_vec()),
+        height_of_longest_chain: 5,
+        tip_mined_timestamp: Some(0),
+    });
+
+    let mempool_evictions = MempoolEvictions::new(&resources.config);
+    for operation_id in 1..=2u64 {
+        let protocol = TransactionValidationProtocol::new(
+            operation_id.into(),
+            resources.db.clone(),
+            wallet_connectivity.clone(),
+            resources.config.clone(),
+            resources.event_publisher.clone(),
+            resources.output_manager_service.clone(),
+        )
+        .with_mempool_evictions(mempool_evictions.clone());
+        let result = task::spawn(protocol.execute()).await.unwrap();
+        assert!(result.is_ok());
+    }
+
+    // The second validation falls within the backoff of the first eviction
+    let mut evictions = vec![];
+    while let Ok(event) = transaction_event_receiver.try_recv() {
+        if let TransactionEvent::TransactionEvictedFromMempool { tx_id, num_evictions } = &*event {
+            evictions.push((*tx_id, *num_evictions));
+        }
+    }
+    assert_eq!(evictions, vec![(TxId::from(1u64), 1)]);
+    assert!(mempool_evictions.rebroadcast_delay(1u64.into()).is_some());
+    assert_eq!(
+        resources.db.get_completed_transaction(1u64.into()).unwrap().status,
+        TransactionStatus::Broadcast
+    );
 }
 
 /// Test that revalidation clears the correct db fields and calls for validation of is said transactions
@@ -1027,7 +1338,7 @@ async fn tx_validation_protocol_reorg() {
         rpc_service_state,
         _shutdown,
         _temp_dir,
-        _transaction_event_receiver,
+        mut transaction_event_receiver,
         wallet_connectivity,
     ) = setup().await;
     // Now we add the connection
@@ -1299,7 +1610,7 @@ async fn tx_validation_protocol_reorg() {
     let completed_txs = resources.db.get_completed_transactions().unwrap();
     assert_eq!(
         completed_txs.get(&4u64.into()).unwrap().status,
-        TransactionStatus::Completed
+        TransactionStatus::Broadcast
     );
     assert_eq!(
         completed_txs.get(&5u64.into()).unwrap().status,
@@ -1323,4 +1634,15 @@ async fn tx_validation_protocol_reorg() {
         cancelled_completed_txs.get(&6u64.into()).unwrap().cancelled,
         Some(TxCancellationReason::AbandonedCoinbase)
     ));
+
+    let mut reorged_event = false;
+    while let Ok(event) = transaction_event_receiver.try_recv() {
+        if let TransactionEvent::TransactionReorged { tx_id, mined_height } = &*event {
+            if *tx_id == 4u64.into() {
+                assert_eq!(*mined_height, 8);
+                reorged_event = true;
+            }
+        }
+    }
+    assert!(reorged_event, "Expected a TransactionReorged event");
 }
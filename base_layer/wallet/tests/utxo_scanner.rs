@@ -44,7 +44,7 @@ use tari_shutdown::Shutdown;
 use tari_test_utils::random;
 use tari_utilities::{epoch_time::EpochTime, ByteArray};
 use tari_wallet::{
-    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    base_node_service::handle::{BaseNodeServiceHandle, ChainEvent},
     connectivity_service::{create_wallet_connectivity_mock, WalletConnectivityMock},
     output_manager_service::storage::models::DbUnblindedOutput,
     storage::{
@@ -79,7 +79,7 @@ use tari_wallet::{
     output_manager_service::storage::OutputSource,
     transaction_service::handle::TransactionServiceRequest,
     util::watch::Watch,
-    utxo_scanner_service::handle::UtxoScannerHandle,
+    utxo_scanner_service::handle::{RecoveredOutputStats, UtxoScannerHandle},
 };
 
 use crate::support::transaction_service_mock::TransactionServiceMockState;
@@ -88,7 +88,7 @@ pub struct UtxoScannerTestInterface {
     scanner_service: Option<UtxoScannerService<WalletSqliteDatabase, WalletConnectivityMock>>,
     scanner_handle: UtxoScannerHandle,
     wallet_db: WalletDatabase<WalletSqliteDatabase>,
-    base_node_service_event_publisher: broadcast::Sender<Arc<BaseNodeEvent>>,
+    chain_event_publisher: broadcast::Sender<Arc<ChainEvent>>,
     rpc_service_state: BaseNodeWalletRpcMockState,
     _rpc_mock_server: MockRpcServer<BaseNodeWalletRpcServer<BaseNodeWalletRpcMockService>>,
     _comms_connectivity_mock_state: ConnectivityManagerMockState,
@@ -110,7 +110,9 @@ async fn setup(
     // Base Node Service Mock
     let (sender, receiver_bns) = reply_channel::unbounded();
     let (event_publisher_bns, _) = broadcast::channel(100);
-    let base_node_service_handle = BaseNodeServiceHandle::new(sender, event_publisher_bns.clone());
+    let (chain_event_publisher, _) = broadcast::channel(100);
+    let base_node_service_handle =
+        BaseNodeServiceHandle::new(sender, event_publisher_bns, chain_event_publisher.clone());
     let mut mock_base_node_service = MockBaseNodeService::new(receiver_bns, shutdown.to_signal());
     mock_base_node_service.set_default_base_node_state();
     task::spawn(mock_base_node_service.run());
@@ -207,7 +209,7 @@ async fn setup(
         scanner_service: Some(scanner_service),
         scanner_handle,
         wallet_db,
-        base_node_service_event_publisher: event_publisher_bns,
+        chain_event_publisher,
         rpc_service_state,
         _rpc_mock_server: mock_server,
         _comms_connectivity_mock_state: comms_connectivity_mock_state,
@@ -352,6 +354,7 @@ async fn test_utxo_scanner_recovery() {
                     num_recovered,
                     value_recovered,
                     time_taken: _,
+                    statistics: _,
                 } = event.unwrap() {
                     assert_eq!(final_height, NUM_BLOCKS - 1);
                     assert_eq!(num_recovered, total_outputs_to_recover);
@@ -362,6 +365,101 @@ async fn test_utxo_scanner_recovery() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_utxo_scanner_recovery_statistics() {
+    let factories = CryptoFactories::default();
+    let mut test_interface = setup(UtxoScannerMode::Recovery, None, None, None).await;
+
+    let cipher_seed = CipherSeed::new();
+    let birthday_epoch_time = u64::from(cipher_seed.birthday() - 2) * 60 * 60 * 24;
+    test_interface.wallet_db.set_master_seed(cipher_seed).unwrap();
+
+    const NUM_BLOCKS: u64 = 11;
+    const BIRTHDAY_OFFSET: u64 = 5;
+
+    let TestBlockData {
+        block_headers,
+        unblinded_outputs,
+        utxos_by_block,
+    } = generate_block_headers_and_utxos(0, NUM_BLOCKS, birthday_epoch_time, BIRTHDAY_OFFSET, false).await;
+
+    test_interface
+        .rpc_service_state
+        .set_utxos_by_block(utxos_by_block.clone());
+    test_interface.rpc_service_state.set_blocks(block_headers.clone());
+
+    let chain_metadata = ChainMetadata {
+        height_of_longest_chain: Some(NUM_BLOCKS - 1),
+        best_block: Some(block_headers.get(&(NUM_BLOCKS - 1)).unwrap().clone().hash().to_vec()),
+        accumulated_difficulty: Vec::new(),
+        pruned_height: 0,
+        timestamp: Some(0),
+    };
+    test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
+        metadata: Some(chain_metadata),
+        is_synced: true,
+    });
+
+    // Half of the recoverable outputs are standard outputs and the other half have no recorded source
+    let mut db_unblinded_outputs = Vec::new();
+    let mut standard = RecoveredOutputStats::default();
+    let mut unknown = RecoveredOutputStats::default();
+    for (h, outputs) in &unblinded_outputs {
+        for (i, output) in outputs.iter().enumerate() {
+            let source = if i % 2 == 0 {
+                OutputSource::Standard
+            } else {
+                OutputSource::Unknown
+            };
+            let dbo = DbUnblindedOutput::from_unblinded_output(output.clone(), &factories, None, source).unwrap();
+            // Only the outputs in blocks after the birthday are recovered
+            if *h >= NUM_BLOCKS.saturating_sub(BIRTHDAY_OFFSET).saturating_sub(2) {
+                let stats = if source == OutputSource::Standard {
+                    &mut standard
+                } else {
+                    &mut unknown
+                };
+                stats.num_recovered += 1;
+                stats.value_recovered += dbo.unblinded_output.value;
+            }
+            db_unblinded_outputs.push(dbo);
+        }
+    }
+    test_interface
+        .oms_mock_state
+        .set_recoverable_outputs(db_unblinded_outputs);
+
+    let mut scanner_event_stream = test_interface.scanner_handle.get_event_receiver();
+
+    tokio::spawn(test_interface.scanner_service.take().unwrap().run());
+
+    let delay = time::sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            _ = &mut delay => {
+                panic!("Completed event should have arrived by now.");
+            }
+            event = scanner_event_stream.recv() => {
+                if let UtxoScannerEvent::Completed {
+                    num_recovered,
+                    value_recovered,
+                    statistics,
+                    ..
+                } = event.unwrap() {
+                    assert_eq!(statistics.standard, standard);
+                    assert_eq!(statistics.unknown, unknown);
+                    assert_eq!(statistics.unrecognized, RecoveredOutputStats::default());
+                    assert_eq!(statistics.one_sided, RecoveredOutputStats::default());
+                    assert_eq!(statistics.total().num_recovered, num_recovered);
+                    assert_eq!(statistics.total().value_recovered, value_recovered);
+                    break;
+                }
+            }
+        }
+    }
+}
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn test_utxo_scanner_recovery_with_restart() {
@@ -496,7 +594,8 @@ async fn test_utxo_scanner_recovery_with_restart() {
                     final_height,
                     num_recovered,
                     value_recovered,
-                    time_taken: _,} = event.unwrap() {
+                    time_taken: _,
+                    statistics: _,} = event.unwrap() {
                     assert_eq!(final_height, NUM_BLOCKS-1);
                     assert_eq!(num_recovered, total_outputs_to_recover);
                     assert_eq!(value_recovered, total_amount_to_recover);
@@ -666,7 +765,8 @@ async fn test_utxo_scanner_recovery_with_restart_and_reorg() {
                     final_height,
                     num_recovered,
                     value_recovered,
-                    time_taken: _,} = event.unwrap() {
+                    time_taken: _,
+                    statistics: _,} = event.unwrap() {
                     assert_eq!(final_height, 9);
                     assert_eq!(num_recovered, total_outputs_to_recover);
                     assert_eq!(value_recovered, total_amount_to_recover);
@@ -766,7 +866,8 @@ async fn test_utxo_scanner_scanned_block_cache_clearing() {
                     final_height:_,
                     num_recovered:_,
                     value_recovered:_,
-                    time_taken: _,} = event.unwrap(){
+                    time_taken: _,
+                    statistics: _,} = event.unwrap(){
                     break;
                 }
             }
@@ -869,7 +970,8 @@ async fn test_utxo_scanner_one_sided_payments() {
                     final_height,
                     num_recovered,
                     value_recovered,
-                    time_taken: _,} = event.unwrap() {
+                    time_taken: _,
+                    statistics: _,} = event.unwrap() {
                     assert_eq!(final_height, NUM_BLOCKS-1);
                     assert_eq!(num_recovered, total_outputs_to_recover);
                     assert_eq!(value_recovered, total_amount_to_recover);
@@ -897,7 +999,7 @@ async fn test_utxo_scanner_one_sided_payments() {
         }
     }
 
-    // Now we add a new block and emit a NewBlockDetected event to trigger another round of scan and
+    // Now we add a new block and emit a NewBlock event to trigger another round of scan and
     // see if the updated message appears in the newly found Faux tx
     let mut block_header11 = BlockHeader::new(0);
     block_header11.height = 11;
@@ -941,8 +1043,11 @@ async fn test_utxo_scanner_one_sided_payments() {
     time::sleep(Duration::from_secs(5)).await;
 
     test_interface
-        .base_node_service_event_publisher
-        .send(Arc::new(BaseNodeEvent::NewBlockDetected(11)))
+        .chain_event_publisher
+        .send(Arc::new(ChainEvent::NewBlock(
+            NUM_BLOCKS,
+            block_headers.get(&NUM_BLOCKS).unwrap().hash(),
+        )))
         .unwrap();
 
     let delay = time::sleep(Duration::from_secs(60));
@@ -957,7 +1062,8 @@ async fn test_utxo_scanner_one_sided_payments() {
                     final_height,
                     num_recovered: _,
                     value_recovered: _,
-                    time_taken: _,} = event.unwrap() {
+                    time_taken: _,
+                    statistics: _,} = event.unwrap() {
                     assert_eq!(final_height, NUM_BLOCKS);
 
                     break;
@@ -27,7 +27,7 @@ use support::{comms_and_services::get_next_memory_address, utils::make_input};
 use tari_common::configuration::StringList;
 use tari_common_types::{
     chain_metadata::ChainMetadata,
-    transaction::TransactionStatus,
+    transaction::{TransactionStatus, TxId},
     types::{FixedHash, PrivateKey, PublicKey},
 };
 use tari_comms::{
@@ -37,16 +37,20 @@ use tari_comms::{
 };
 use tari_comms_dht::{store_forward::SafConfig, DhtConfig};
 use tari_core::{
+    consensus::ConsensusManager,
     covenants::Covenant,
     transactions::{
         tari_amount::{uT, MicroTari},
         test_helpers::{create_unblinded_output, TestParams},
-        transaction_components::OutputFeatures,
+        transaction_components::{OutputFeatures, SideChainFeatures, TransactionError, MAX_COMMITTEE_SIGNATURE_SIZE},
         CryptoFactories,
     },
 };
 use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
-use tari_key_manager::{cipher_seed::CipherSeed, mnemonic::Mnemonic};
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    mnemonic::{Mnemonic, MnemonicLanguage},
+};
 use tari_p2p::{
     auto_update::AutoUpdateConfig,
     comms_connector::InboundDomainConnector,
@@ -60,21 +64,32 @@ use tari_p2p::{
 };
 use tari_script::{inputs, script};
 use tari_shutdown::{Shutdown, ShutdownSignal};
-use tari_test_utils::{collect_recv, random};
+use tari_test_utils::{async_assert_eventually, collect_recv, random};
 use tari_utilities::SafePassword;
 use tari_wallet::{
+    cloud_sync::{CloudSyncEngine, MemoryBlobStore, SyncKey, SyncSummary},
+    connectivity_service::WalletConnectivityInterface,
     contacts_service::{
         handle::ContactsLivenessEvent,
         service::ContactMessageType,
         storage::{database::Contact, sqlite_db::ContactsServiceSqliteDatabase},
     },
     error::{WalletError, WalletStorageError},
+    health_check::ServiceStatus,
+    integrity::OrphanRepair,
     key_manager_service::storage::sqlite_db::KeyManagerSqliteDatabase,
-    output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
+    output_manager_service::storage::{
+        models::DbUnblindedOutput,
+        sqlite_db::OutputManagerSqliteDatabase,
+        OutputSource,
+        OutputStatus,
+    },
+    portable_dump::{PortableImportSummary, PortableWalletDump},
     storage::{
         database::{DbKeyValuePair, WalletBackend, WalletDatabase, WriteOperation},
         sqlite_db::wallet::WalletSqliteDatabase,
         sqlite_utilities::{
+            create_read_only_sqlite_connection,
             initialize_sqlite_database_backends,
             partial_wallet_backup,
             run_migration_and_create_sqlite_connection,
@@ -84,11 +99,14 @@ use tari_wallet::{
     transaction_service::{
         config::TransactionServiceConfig,
         handle::TransactionEvent,
-        storage::sqlite_db::TransactionServiceSqliteDatabase,
+        storage::{database::TransactionDatabase, sqlite_db::TransactionServiceSqliteDatabase},
     },
-    wallet::read_or_create_master_seed,
+    wallet::{derive_comms_secret_key, read_or_create_master_seed},
+    wallet_manager::{WalletManager, WalletParams},
+    ServiceKind,
     Wallet,
     WalletConfig,
+    WalletExtensions,
     WalletSqlite,
 };
 use tempfile::tempdir;
@@ -109,14 +127,21 @@ fn create_peer(public_key: CommsPublicKey, net_address: Multiaddr) -> Peer {
     )
 }
 
-async fn create_wallet(
+type WalletSqliteParams = WalletParams<
+    WalletSqliteDatabase,
+    TransactionServiceSqliteDatabase,
+    OutputManagerSqliteDatabase,
+    ContactsServiceSqliteDatabase,
+    KeyManagerSqliteDatabase,
+>;
+
+fn create_wallet_params(
     data_path: &Path,
     database_name: &str,
     factories: CryptoFactories,
-    shutdown_signal: ShutdownSignal,
     passphrase: Option<SafePassword>,
     recovery_seed: Option<CipherSeed>,
-) -> Result<WalletSqlite, WalletError> {
+) -> Result<WalletSqliteParams, WalletError> {
     const NETWORK: Network = Network::LocalNet;
     let node_identity = NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
     let comms_config = P2pConfig {
@@ -179,20 +204,54 @@ async fn create_wallet(
 
     let output_db = OutputManagerDatabase::new(output_manager_backend.clone());
 
-    Wallet::start(
+    Ok(WalletParams {
         config,
-        PeerSeedsConfig::default(),
-        AutoUpdateConfig::default(),
-        Arc::new(node_identity.clone()),
+        peer_seeds: PeerSeedsConfig::default(),
+        auto_update: AutoUpdateConfig::default(),
+        node_identity: Arc::new(node_identity),
         factories,
-        wallet_db,
-        output_db,
+        wallet_database: wallet_db,
+        output_manager_database: output_db,
         transaction_backend,
         output_manager_backend,
         contacts_backend,
         key_manager_backend,
-        shutdown_signal,
         master_seed,
+        extensions: WalletExtensions::default(),
+    })
+}
+
+async fn create_wallet(
+    data_path: &Path,
+    database_name: &str,
+    factories: CryptoFactories,
+    shutdown_signal: ShutdownSignal,
+    passphrase: Option<SafePassword>,
+    recovery_seed: Option<CipherSeed>,
+) -> Result<WalletSqlite, WalletError> {
+    let params = create_wallet_params(data_path, database_name, factories, passphrase, recovery_seed)?;
+    start_wallet(params, shutdown_signal).await
+}
+
+async fn start_wallet(
+    params: WalletSqliteParams,
+    shutdown_signal: ShutdownSignal,
+) -> Result<WalletSqlite, WalletError> {
+    Wallet::start(
+        params.config,
+        params.peer_seeds,
+        params.auto_update,
+        params.node_identity,
+        params.factories,
+        params.wallet_database,
+        params.output_manager_database,
+        params.transaction_backend,
+        params.output_manager_backend,
+        params.contacts_backend,
+        params.key_manager_backend,
+        shutdown_signal,
+        params.master_seed,
+        params.extensions,
     )
     .await
 }
@@ -269,7 +328,7 @@ async fn test_wallet() {
 
     alice_wallet.output_manager_service.add_output(uo1, None).await.unwrap();
 
-    alice_wallet
+    let tx_id = alice_wallet
         .transaction_service
         .send_transaction(
             bob_identity.public_key().clone(),
@@ -277,6 +336,17 @@ async fn test_wallet() {
             OutputFeatures::default(),
             MicroTari::from(5),
             "".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    alice_wallet
+        .transaction_service
+        .add_transaction_attachment(
+            tx_id,
+            "receipt.txt".to_string(),
+            "text/plain".to_string(),
+            b"One turtle".to_vec(),
         )
         .await
         .unwrap();
@@ -421,6 +491,11 @@ async fn test_wallet() {
     let backup_wallet_db = WalletDatabase::new(WalletSqliteDatabase::new(connection.clone(), None).unwrap());
     let master_seed = backup_wallet_db.get_master_seed().unwrap();
     assert!(master_seed.is_none());
+    // The attachments of the transactions are part of the backup
+    let backup_transaction_db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(connection, None));
+    let attachments = backup_transaction_db.get_transaction_attachments(tx_id).unwrap();
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].data, b"One turtle".to_vec());
 
     shutdown_b.trigger();
 
@@ -479,6 +554,140 @@ async fn test_do_not_overwrite_master_key() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn test_refuse_database_of_another_network() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let params = create_wallet_params(dir.path(), "wallet_db", factories.clone(), None, None).unwrap();
+    let configured = params.config.network;
+    let wallet = start_wallet(params, shutdown.to_signal()).await.unwrap();
+    assert_eq!(wallet.db.get_network().unwrap(), Some(configured));
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+
+    let other = if configured == Network::Esmeralda {
+        Network::Igor
+    } else {
+        Network::Esmeralda
+    };
+    let mut shutdown = Shutdown::new();
+    let mut params = create_wallet_params(dir.path(), "wallet_db", factories.clone(), None, None).unwrap();
+    params.config.network = other;
+    let result = start_wallet(params, shutdown.to_signal()).await;
+    assert!(matches!(
+        result,
+        Err(WalletError::NetworkMismatch { database, configured: c }) if database == configured && c == other
+    ));
+
+    // The database can be moved to the other network explicitly
+    let mut params = create_wallet_params(dir.path(), "wallet_db", factories, None, None).unwrap();
+    params.config.network = other;
+    params.wallet_database.set_network(other).unwrap();
+    let wallet = start_wallet(params, shutdown.to_signal()).await.unwrap();
+    assert_eq!(wallet.db.get_network().unwrap(), Some(other));
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_light_mode() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let mut params = create_wallet_params(dir.path(), "wallet_db", factories.clone(), None, None).unwrap();
+    params.config.light_mode = true;
+    let result = start_wallet(params, shutdown.to_signal()).await;
+    assert!(matches!(result, Err(WalletError::ArgumentError { .. })));
+
+    let mut params = create_wallet_params(dir.path(), "wallet_db", factories, None, None).unwrap();
+    params.config.light_mode = true;
+    params.node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_CLIENT,
+    ));
+    let wallet = start_wallet(params, shutdown.to_signal()).await.unwrap();
+    let config = wallet.config();
+    assert!(!config.p2p.dht.auto_join);
+    assert!(!config.p2p.dht.network_discovery.enabled);
+    assert_eq!(config.p2p.dht.num_random_nodes, 0);
+    assert!(config.transaction_service_config.store_and_forward_connected_peers_only);
+
+    // Reloading the config as it was given keeps the light mode settings
+    let mut config = config;
+    config.p2p.dht.auto_join = true;
+    config.transaction_service_config.store_and_forward_connected_peers_only = false;
+    let reload = wallet.reload_config(config).await.unwrap();
+    assert!(reload.requires_restart.is_empty());
+    assert!(
+        wallet
+            .config()
+            .transaction_service_config
+            .store_and_forward_connected_peers_only
+    );
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_base_node_identity_pinning() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let mut wallet = create_wallet(dir.path(), "wallet_db", factories, shutdown.to_signal(), None, None)
+        .await
+        .unwrap();
+
+    let address = get_next_memory_address();
+    let (_, public_key) = PublicKey::random_keypair(&mut OsRng);
+    let (_, impostor_public_key) = PublicKey::random_keypair(&mut OsRng);
+    wallet
+        .set_base_node_peer(public_key.clone(), address.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        wallet.db.get_base_node_identity(&address).unwrap(),
+        Some(public_key.clone())
+    );
+    // Setting the same base node again is fine
+    wallet
+        .set_base_node_peer(public_key.clone(), address.clone())
+        .await
+        .unwrap();
+
+    let result = wallet
+        .set_base_node_peer(impostor_public_key.clone(), address.clone())
+        .await;
+    assert!(matches!(
+        result,
+        Err(WalletError::BaseNodeIdentityChanged { pinned_public_key, presented_public_key, .. })
+            if pinned_public_key == public_key && presented_public_key == impostor_public_key
+    ));
+    assert_eq!(
+        wallet.wallet_connectivity.get_current_base_node_peer_public_key(),
+        Some(public_key)
+    );
+
+    wallet
+        .trust_new_base_node_identity(impostor_public_key.clone(), address.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        wallet.db.get_base_node_identity(&address).unwrap(),
+        Some(impostor_public_key.clone())
+    );
+    assert_eq!(
+        wallet.wallet_connectivity.get_current_base_node_peer_public_key(),
+        Some(impostor_public_key)
+    );
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
 #[tokio::test]
 async fn test_sign_message() {
     let factories = CryptoFactories::default();
@@ -505,6 +714,714 @@ async fn test_sign_message() {
     assert!(wallet.verify_message_signature(public_key, public_nonce, signature, message.into()));
 }
 
+#[tokio::test]
+async fn test_verify_seed_backup() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let shutdown = Shutdown::new();
+    let wallet = create_wallet(dir.path(), "wallet_db", factories, shutdown.to_signal(), None, None)
+        .await
+        .unwrap();
+
+    let seed_words = wallet.get_seed_words(&MnemonicLanguage::English).unwrap();
+    let indices = [2, 11, 23];
+    let words = indices
+        .iter()
+        .map(|i| seed_words[*i].to_uppercase())
+        .collect::<Vec<_>>();
+    assert!(wallet.verify_seed_backup(&indices, &words).unwrap());
+
+    let seed_words = wallet.get_seed_words(&MnemonicLanguage::Spanish).unwrap();
+    let words = indices.iter().map(|i| seed_words[*i].clone()).collect::<Vec<_>>();
+    assert!(wallet.verify_seed_backup(&indices, &words).unwrap());
+
+    let mut words = words;
+    words.swap(0, 1);
+    assert!(!wallet.verify_seed_backup(&indices, &words).unwrap());
+    words[0] = "not a seed word".to_string();
+    assert!(!wallet.verify_seed_backup(&indices, &words).unwrap());
+
+    assert!(matches!(
+        wallet.verify_seed_backup(&[2, 24, 11], &words),
+        Err(WalletError::ArgumentError { .. })
+    ));
+    assert!(matches!(
+        wallet.verify_seed_backup(&indices[..2], &words),
+        Err(WalletError::ArgumentError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_get_consensus_constants() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let shutdown = Shutdown::new();
+    let wallet = create_wallet(dir.path(), "wallet_db", factories, shutdown.to_signal(), None, None)
+        .await
+        .unwrap();
+
+    let rules = ConsensusManager::builder(Network::LocalNet).build();
+    let constants = rules.consensus_constants(1000);
+    let parameters = wallet.get_consensus_constants(1000);
+    assert_eq!(parameters.network, Network::LocalNet);
+    assert_eq!(parameters.effective_from_height, constants.effective_from_height());
+    assert_eq!(parameters.coinbase_lock_height, constants.coinbase_lock_height());
+    assert_eq!(
+        parameters.max_block_transaction_weight,
+        constants.get_max_block_transaction_weight()
+    );
+    assert_eq!(parameters.max_script_byte_size, constants.get_max_script_byte_size());
+    assert_eq!(parameters.max_opcode_version, constants.max_opcode_version());
+    assert!(parameters.min_fee_per_gram > MicroTari::from(0));
+    assert!(parameters.min_transaction_fee >= parameters.min_fee_per_gram);
+}
+
+#[tokio::test]
+async fn test_restart_service() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let wallet = create_wallet(dir.path(), "wallet_db", factories, shutdown.to_signal(), None, None)
+        .await
+        .unwrap();
+
+    assert!(wallet
+        .transaction_service
+        .get_completed_transactions()
+        .await
+        .unwrap()
+        .is_empty());
+    wallet.restart_service(ServiceKind::TransactionService).await.unwrap();
+    // The existing handle is served by the restarted service
+    assert!(wallet
+        .transaction_service
+        .get_completed_transactions()
+        .await
+        .unwrap()
+        .is_empty());
+    wallet.restart_service(ServiceKind::UtxoScanner).await.unwrap();
+
+    shutdown.trigger();
+    wallet.clone().wait_until_shutdown().await;
+    assert!(matches!(
+        wallet.restart_service(ServiceKind::TransactionService).await,
+        Err(WalletError::ServiceRestartFailed(ServiceKind::TransactionService, _))
+    ));
+}
+
+#[tokio::test]
+async fn test_health_check() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let wallet = create_wallet(dir.path(), "wallet_db", factories, shutdown.to_signal(), None, None)
+        .await
+        .unwrap();
+
+    let report = wallet.health_check().await;
+    assert!(report.is_live());
+    for service in [
+        "comms",
+        "transaction_service",
+        "output_manager_service",
+        "contacts_service",
+    ] {
+        assert!(report.get(service).unwrap().response_time.is_some());
+    }
+
+    shutdown.trigger();
+    wallet.clone().wait_until_shutdown().await;
+    let report = wallet.health_check().await;
+    assert!(!report.is_live());
+    assert!(matches!(
+        report.get("transaction_service").unwrap().status,
+        ServiceStatus::Failed(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_reload_config() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let wallet = create_wallet(dir.path(), "wallet_db", factories, shutdown.to_signal(), None, None)
+        .await
+        .unwrap();
+
+    let mut config = wallet.config();
+    let reload = wallet.reload_config(config.clone()).await.unwrap();
+    assert!(reload.requires_restart.is_empty());
+
+    config.fee_per_gram += 1;
+    config.transaction_service_config.num_confirmations_required += 1;
+    config.buffer_size += 1;
+    let reload = wallet.reload_config(config.clone()).await.unwrap();
+    assert_eq!(reload.requires_restart, vec!["buffer_size".to_string()]);
+    assert_eq!(wallet.config().fee_per_gram, config.fee_per_gram);
+    assert_eq!(
+        wallet
+            .transaction_service
+            .clone()
+            .get_num_confirmations_required()
+            .await
+            .unwrap(),
+        config.transaction_service_config.num_confirmations_required
+    );
+
+    // The reloaded config survives a restart of the transaction service
+    wallet.restart_service(ServiceKind::TransactionService).await.unwrap();
+    assert_eq!(
+        wallet
+            .transaction_service
+            .clone()
+            .get_num_confirmations_required()
+            .await
+            .unwrap(),
+        config.transaction_service_config.num_confirmations_required
+    );
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_ban_peer() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let wallet = create_wallet(dir.path(), "wallet_db", factories, shutdown.to_signal(), None, None)
+        .await
+        .unwrap();
+
+    let peer_identity = NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    wallet
+        .comms
+        .peer_manager()
+        .add_peer(peer_identity.to_peer())
+        .await
+        .unwrap();
+    assert!(wallet.list_banned_peers().await.unwrap().is_empty());
+
+    wallet
+        .ban_peer(
+            peer_identity.node_id().clone(),
+            Duration::from_secs(60 * 60),
+            "Sent invalid messages".to_string(),
+        )
+        .await
+        .unwrap();
+    // The ban is applied by the connectivity manager in the background
+    async_assert_eventually!(
+        wallet.list_banned_peers().await.unwrap().len(),
+        expect = 1,
+        max_attempts = 20,
+        interval = Duration::from_millis(100),
+    );
+    let banned = wallet.list_banned_peers().await.unwrap();
+    assert_eq!(banned[0].node_id, *peer_identity.node_id());
+    assert_eq!(banned[0].reason_banned(), "Sent invalid messages");
+
+    wallet.unban_peer(peer_identity.node_id()).await.unwrap();
+    assert!(wallet.list_banned_peers().await.unwrap().is_empty());
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_create_sidechain_output() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+    let mut shutdown = Shutdown::new();
+    let mut wallet = create_wallet(
+        dir.path(),
+        "wallet_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let (_utxo, uo1) = make_input(&mut OsRng, MicroTari(20_000), &factories.commitment).await;
+    wallet.output_manager_service.add_output(uo1, None).await.unwrap();
+
+    let oversized =
+        SideChainFeatures::new(FixedHash::zero()).with_committee_signature(vec![1; MAX_COMMITTEE_SIGNATURE_SIZE + 1]);
+    let err = wallet
+        .create_sidechain_output(oversized, MicroTari(5_000), MicroTari(5), "".to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        WalletError::TransactionError(TransactionError::InvalidSideChainFeatures(_))
+    ));
+
+    let sidechain_features = SideChainFeatures::new(FixedHash::from([1u8; 32])).with_committee_signature(vec![2; 64]);
+    let tx_id = wallet
+        .create_sidechain_output(
+            sidechain_features.clone(),
+            MicroTari(5_000),
+            MicroTari(5),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+    let completed_tx = wallet
+        .transaction_service
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    assert!(completed_tx
+        .transaction
+        .body
+        .outputs()
+        .iter()
+        .any(|output| output.features.sidechain_features.as_deref() == Some(&sidechain_features)));
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_wallets_on_assigned_memory_addresses() {
+    let factories = CryptoFactories::default();
+    let alice_dir = tempdir().unwrap();
+    let bob_dir = tempdir().unwrap();
+    let mut shutdown = Shutdown::new();
+
+    let mut wallets = Vec::new();
+    for (dir, name) in [(&alice_dir, "alice_db"), (&bob_dir, "bob_db")] {
+        let mut params = create_wallet_params(dir.path(), name, factories.clone(), None, None).unwrap();
+        params.config.set_memory_transport();
+        params.node_identity = Arc::new(NodeIdentity::random(
+            &mut OsRng,
+            "/memory/0".parse().unwrap(),
+            PeerFeatures::COMMUNICATION_NODE,
+        ));
+        wallets.push(start_wallet(params, shutdown.to_signal()).await.unwrap());
+    }
+    let bob_wallet = wallets.pop().unwrap();
+    let alice_wallet = wallets.pop().unwrap();
+
+    let unassigned = "/memory/0".parse::<Multiaddr>().unwrap();
+    let bob_identity = bob_wallet.comms.node_identity();
+    assert_ne!(bob_identity.public_address(), unassigned);
+    assert_eq!(bob_identity.public_address(), *bob_wallet.comms.listening_address());
+    assert_ne!(
+        alice_wallet.comms.node_identity().public_address(),
+        bob_identity.public_address()
+    );
+
+    alice_wallet
+        .comms
+        .peer_manager()
+        .add_peer(bob_identity.to_peer())
+        .await
+        .unwrap();
+    let conn = alice_wallet
+        .comms
+        .connectivity()
+        .dial_peer(bob_identity.node_id().clone())
+        .await
+        .unwrap();
+    assert_eq!(conn.peer_node_id(), bob_identity.node_id());
+
+    shutdown.trigger();
+    alice_wallet.wait_until_shutdown().await;
+    bob_wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_export_import_network_state() {
+    let factories = CryptoFactories::default();
+    let alice_db_tempdir = tempdir().unwrap();
+    let bob_db_tempdir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let wallet = create_wallet(
+        alice_db_tempdir.path(),
+        "alice_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let other_wallet = create_wallet(
+        bob_db_tempdir.path(),
+        "bob_db",
+        factories,
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let peer_identity = NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let peer_manager = wallet.comms.peer_manager();
+    peer_manager.add_peer(peer_identity.to_peer()).await.unwrap();
+
+    let state_file = alice_db_tempdir.path().join("network_state.json");
+    wallet.export_network_state(&state_file).await.unwrap();
+
+    // Peers that are already known are not imported again
+    assert_eq!(wallet.import_network_state(&state_file).await.unwrap(), 0);
+    peer_manager.delete_peer(peer_identity.node_id()).await.unwrap();
+    assert_eq!(wallet.import_network_state(&state_file).await.unwrap(), 1);
+    assert!(peer_manager.exists_node_id(peer_identity.node_id()).await);
+
+    // The network state of one wallet cannot be imported into another
+    assert!(matches!(
+        other_wallet.import_network_state(&state_file).await,
+        Err(WalletError::NetworkStateError(_))
+    ));
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+    other_wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_export_import_data() {
+    let factories = CryptoFactories::default();
+    let alice_db_tempdir = tempdir().unwrap();
+    let bob_db_tempdir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let mut alice_wallet = create_wallet(
+        alice_db_tempdir.path(),
+        "alice_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let mut bob_wallet = create_wallet(
+        bob_db_tempdir.path(),
+        "bob_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let (_, carol_public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+    let carol = Contact::new("Carol".to_string(), carol_public_key, None, None);
+    alice_wallet
+        .contacts_service
+        .upsert_contact(carol.clone())
+        .await
+        .unwrap();
+
+    let p = TestParams::new();
+    let utxo = create_unblinded_output(script!(Nop), OutputFeatures::default(), &p, 20000 * uT);
+    let output = utxo.as_transaction_output(&factories).unwrap();
+    let tx_id = alice_wallet
+        .import_external_utxo_as_non_rewindable(
+            utxo.value,
+            &utxo.spending_key,
+            script!(Nop),
+            inputs!(PublicKey::from_secret_key(&p.spend_key)),
+            &carol.public_key,
+            utxo.features.clone(),
+            "Rent for June".to_string(),
+            utxo.metadata_signature.clone(),
+            &p.script_private_key,
+            &p.sender_offset_public_key,
+            0,
+            Covenant::default(),
+            output.encrypted_value,
+            utxo.minimum_value_promise,
+        )
+        .await
+        .unwrap();
+    alice_wallet
+        .transaction_service
+        .tag_transaction(tx_id, "rent".to_string())
+        .await
+        .unwrap();
+
+    let dump_file = alice_db_tempdir.path().join("wallet_dump.json");
+    alice_wallet
+        .export_data()
+        .await
+        .unwrap()
+        .write_to_file(&dump_file)
+        .unwrap();
+    let dump = PortableWalletDump::read_from_file(&dump_file).unwrap();
+    assert_eq!(dump.contacts.len(), 1);
+    assert_eq!(dump.num_transactions(), 1);
+
+    let summary = bob_wallet.import_data(dump.clone()).await.unwrap();
+    assert_eq!(summary, PortableImportSummary {
+        contacts: 1,
+        transactions: 1,
+        transaction_tags: 1,
+    });
+    let contact = bob_wallet
+        .contacts_service
+        .get_contact(carol.public_key.clone())
+        .await
+        .unwrap();
+    assert_eq!(contact.alias, "Carol");
+    let completed_tx = bob_wallet
+        .transaction_service
+        .get_completed_transactions()
+        .await
+        .unwrap()
+        .remove(&tx_id)
+        .expect("Tx should be imported");
+    assert_eq!(completed_tx.message, "Rent for June");
+    assert_eq!(
+        bob_wallet
+            .transaction_service
+            .get_transaction_tags(tx_id)
+            .await
+            .unwrap(),
+        vec!["rent".to_string()]
+    );
+
+    // Importing the same dump again adds nothing
+    assert_eq!(
+        bob_wallet.import_data(dump).await.unwrap(),
+        PortableImportSummary::default()
+    );
+
+    shutdown.trigger();
+    alice_wallet.wait_until_shutdown().await;
+    bob_wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_open_read_only() {
+    let factories = CryptoFactories::default();
+    let db_tempdir = tempdir().unwrap();
+    let db_path = db_tempdir.path().join("alice_db.sqlite3");
+
+    let mut shutdown = Shutdown::new();
+    let mut wallet = create_wallet(
+        db_tempdir.path(),
+        "alice_db",
+        factories,
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let (_, carol_public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+    let carol = Contact::new("Carol".to_string(), carol_public_key, None, None);
+    wallet.contacts_service.upsert_contact(carol.clone()).await.unwrap();
+
+    // The database can be read while the wallet is running
+    let replica = WalletSqlite::open_read_only(&db_path, None).unwrap();
+    let contacts = replica.get_contacts().unwrap();
+    assert_eq!(contacts.len(), 1);
+    assert_eq!(contacts[0].public_key, carol.public_key);
+    assert_eq!(replica.get_balance().unwrap().available_balance, MicroTari::from(0));
+    assert!(replica.get_completed_transactions().unwrap().is_empty());
+
+    // But it cannot be written to
+    let connection = create_read_only_sqlite_connection(&db_path, 1).unwrap();
+    let read_only_db = WalletDatabase::new(WalletSqliteDatabase::new(connection, None).unwrap());
+    assert!(read_only_db
+        .set_client_key_value("report".to_string(), "done".to_string())
+        .is_err());
+
+    assert!(matches!(
+        WalletSqlite::open_read_only(db_tempdir.path().join("missing.sqlite3"), None),
+        Err(WalletError::WalletStorageError(WalletStorageError::DbPathDoesNotExist))
+    ));
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[test]
+fn test_initialize_new() {
+    let db_tempdir = tempdir().unwrap();
+    let db_path = db_tempdir.path().join("accounts").join("alice_db.sqlite3");
+    let passphrase = || SafePassword::from("provisioned".to_string());
+
+    let new_wallet = WalletSqlite::initialize_new(&db_path, Some(passphrase()), Network::LocalNet).unwrap();
+    assert_eq!(new_wallet.seed_words.len(), 24);
+
+    // The seed words recover the same wallet
+    let seed = CipherSeed::from_mnemonic(&new_wallet.seed_words, None).unwrap();
+    assert_eq!(seed.birthday(), new_wallet.birthday);
+    let comms_secret_key = derive_comms_secret_key(&seed).unwrap();
+    assert_eq!(
+        &CommsPublicKey::from_secret_key(&comms_secret_key),
+        new_wallet.node_identity.public_key()
+    );
+
+    // The database is encrypted and belongs to the network
+    assert!(WalletSqlite::open_read_only(&db_path, None).is_err());
+    let replica = WalletSqlite::open_read_only(&db_path, Some(passphrase())).unwrap();
+    assert_eq!(replica.network().unwrap(), Some(Network::LocalNet));
+
+    assert!(matches!(
+        WalletSqlite::initialize_new(&db_path, Some(passphrase()), Network::LocalNet),
+        Err(WalletError::ArgumentError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_check_integrity() {
+    let factories = CryptoFactories::default();
+    let db_tempdir = tempdir().unwrap();
+
+    let mut shutdown = Shutdown::new();
+    let mut wallet = create_wallet(
+        db_tempdir.path(),
+        "alice_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(wallet.check_integrity(false).await.unwrap().is_consistent());
+
+    // An output that is encumbered to be spent in a transaction that was never stored
+    let (_utxo, uo) = make_input(&mut OsRng, MicroTari(2500), &factories.commitment).await;
+    let output = DbUnblindedOutput::from_unblinded_output(uo, &factories, None, OutputSource::Unknown).unwrap();
+    let tx_id = TxId::from(99u64);
+    wallet.output_db.add_unspent_output(output.clone()).unwrap();
+    wallet
+        .output_db
+        .encumber_outputs(tx_id, vec![output.clone()], vec![])
+        .unwrap();
+    wallet.output_db.confirm_encumbered_outputs(tx_id).unwrap();
+
+    let report = wallet.check_integrity(false).await.unwrap();
+    assert!(!report.repaired);
+    assert_eq!(report.orphaned_outputs.len(), 1);
+    assert_eq!(report.orphaned_outputs[0].commitment, output.commitment);
+    assert_eq!(report.orphaned_outputs[0].status, OutputStatus::EncumberedToBeSpent);
+    assert_eq!(report.orphaned_outputs[0].tx_id, tx_id);
+    assert_eq!(report.orphaned_outputs[0].repair, OrphanRepair::Release);
+    assert!(report.transactions_without_outputs.is_empty());
+    assert!(wallet.output_db.fetch_sorted_unspent_outputs().unwrap().is_empty());
+
+    let report = wallet.check_integrity(true).await.unwrap();
+    assert!(report.repaired);
+    assert_eq!(report.orphaned_outputs.len(), 1);
+    let unspent = wallet.output_db.fetch_sorted_unspent_outputs().unwrap();
+    assert_eq!(unspent.len(), 1);
+    assert_eq!(unspent[0].commitment, output.commitment);
+    assert!(wallet.check_integrity(false).await.unwrap().is_consistent());
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio::test]
+async fn test_cloud_sync() {
+    let factories = CryptoFactories::default();
+    let laptop_db_tempdir = tempdir().unwrap();
+    let phone_db_tempdir = tempdir().unwrap();
+    let seed = CipherSeed::new();
+
+    let mut shutdown = Shutdown::new();
+    let mut laptop_wallet = create_wallet(
+        laptop_db_tempdir.path(),
+        "laptop_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        Some(seed.clone()),
+    )
+    .await
+    .unwrap();
+    let mut phone_wallet = create_wallet(
+        phone_db_tempdir.path(),
+        "phone_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        Some(seed),
+    )
+    .await
+    .unwrap();
+
+    let store = MemoryBlobStore::default();
+    let laptop_engine = CloudSyncEngine::new(store.clone(), laptop_wallet.cloud_sync_key().unwrap());
+    let phone_engine = CloudSyncEngine::new(store.clone(), phone_wallet.cloud_sync_key().unwrap());
+
+    let (_, carol_public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+    let carol = Contact::new("Carol".to_string(), carol_public_key, None, None);
+    laptop_wallet
+        .contacts_service
+        .upsert_contact(carol.clone())
+        .await
+        .unwrap();
+    laptop_wallet.mark_transactions_read(&[TxId::from(42u64)]).unwrap();
+
+    let summary = laptop_wallet.cloud_sync(&laptop_engine).await.unwrap();
+    assert_eq!(summary, SyncSummary {
+        uploaded: true,
+        ..Default::default()
+    });
+
+    let summary = phone_wallet.cloud_sync(&phone_engine).await.unwrap();
+    assert_eq!(summary, SyncSummary {
+        contacts: 1,
+        transaction_tags: 0,
+        read_transactions: 1,
+        uploaded: false,
+    });
+    let contact = phone_wallet
+        .contacts_service
+        .get_contact(carol.public_key.clone())
+        .await
+        .unwrap();
+    assert_eq!(contact.alias, "Carol");
+    assert!(phone_wallet
+        .get_read_transactions()
+        .unwrap()
+        .contains(&TxId::from(42u64)));
+
+    // Changes made on the phone reach the laptop, and syncing again changes nothing
+    phone_wallet.mark_transactions_read(&[TxId::from(43u64)]).unwrap();
+    assert!(phone_wallet.cloud_sync(&phone_engine).await.unwrap().uploaded);
+    assert_eq!(
+        laptop_wallet
+            .cloud_sync(&laptop_engine)
+            .await
+            .unwrap()
+            .read_transactions,
+        1
+    );
+    assert_eq!(
+        laptop_wallet.cloud_sync(&laptop_engine).await.unwrap(),
+        SyncSummary::default()
+    );
+
+    // A wallet with another seed does not find the snapshot
+    let other_engine = CloudSyncEngine::new(store, SyncKey::from_seed(&CipherSeed::new()));
+    assert!(other_engine.pull().await.unwrap().is_none());
+
+    shutdown.trigger();
+    laptop_wallet.wait_until_shutdown().await;
+    phone_wallet.wait_until_shutdown().await;
+}
+
 #[test]
 fn test_many_iterations_store_and_forward_send_tx() {
     for _n in 1..=10 {
@@ -592,6 +1509,7 @@ async fn test_store_and_forward_send_tx() {
             OutputFeatures::default(),
             MicroTari::from(3),
             "Store and Forward!".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -702,6 +1620,7 @@ async fn test_import_utxo() {
         KeyManagerSqliteDatabase::new(connection.clone(), None).unwrap(),
         shutdown.to_signal(),
         CipherSeed::new(),
+        WalletExtensions::default(),
     )
     .await
     .unwrap();
@@ -940,3 +1859,50 @@ async fn test_contacts_service_liveness() {
     alice_wallet.wait_until_shutdown().await;
     bob_wallet.wait_until_shutdown().await;
 }
+
+#[tokio::test]
+async fn test_wallet_manager() {
+    let factories = CryptoFactories::default();
+    let alice_db_tempdir = tempdir().unwrap();
+    let bob_db_tempdir = tempdir().unwrap();
+    let carol_db_tempdir = tempdir().unwrap();
+    let mut manager = WalletManager::new().with_worker_threads(1);
+
+    let alice_params =
+        create_wallet_params(alice_db_tempdir.path(), "alice_db", factories.clone(), None, None).unwrap();
+    let alice_identity = alice_params.node_identity.clone();
+    let alice_wallet = manager.start_wallet("alice", alice_params).await.unwrap();
+    let bob_params = create_wallet_params(bob_db_tempdir.path(), "bob_db", factories.clone(), None, None).unwrap();
+    let bob_wallet = manager.start_wallet("bob", bob_params).await.unwrap();
+    assert_ne!(
+        alice_wallet.comms.node_identity().node_id(),
+        bob_wallet.comms.node_identity().node_id()
+    );
+    let mut names = manager.wallet_names().collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(names, vec!["alice", "bob"]);
+
+    let carol_params =
+        create_wallet_params(carol_db_tempdir.path(), "carol_db", factories.clone(), None, None).unwrap();
+    assert!(matches!(
+        manager.start_wallet("alice", carol_params).await,
+        Err(WalletError::WalletAlreadyRunning(_))
+    ));
+    let mut carol_params =
+        create_wallet_params(carol_db_tempdir.path(), "carol_db_2", factories.clone(), None, None).unwrap();
+    carol_params.node_identity = alice_identity;
+    assert!(matches!(
+        manager.start_wallet("carol", carol_params).await,
+        Err(WalletError::ConflictingWallet { .. })
+    ));
+
+    manager.stop_wallet("alice").await.unwrap();
+    assert!(manager.get_wallet("alice").is_none());
+    assert!(manager.get_wallet("bob").is_some());
+    assert!(matches!(
+        manager.stop_wallet("alice").await,
+        Err(WalletError::WalletNotRunning(_))
+    ));
+    manager.stop_all().await;
+    assert_eq!(manager.wallet_names().count(), 0);
+}
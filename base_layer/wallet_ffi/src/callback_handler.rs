@@ -83,11 +83,10 @@ where TBackend: TransactionBackend + 'static
     db: TransactionDatabase<TBackend>,
     transaction_service_event_stream: TransactionEventReceiver,
     output_manager_service_event_stream: OutputManagerEventReceiver,
-    output_manager_service: OutputManagerHandle,
+    balance_updates: watch::Receiver<Option<Balance>>,
     dht_event_stream: DhtEventReceiver,
     shutdown_signal: Option<ShutdownSignal>,
     comms_public_key: CommsPublicKey,
-    balance_cache: Balance,
     connectivity_status_watch: watch::Receiver<OnlineStatus>,
     contacts_liveness_events: broadcast::Receiver<Arc<ContactsLivenessEvent>>,
 }
@@ -208,11 +207,10 @@ where TBackend: TransactionBackend + 'static
             db,
             transaction_service_event_stream,
             output_manager_service_event_stream,
-            output_manager_service,
+            balance_updates: output_manager_service.subscribe_balance_changes(),
             dht_event_stream,
             shutdown_signal: Some(shutdown_signal),
             comms_public_key,
-            balance_cache: Balance::zero(),
             connectivity_status_watch,
             contacts_liveness_events,
         }
@@ -236,46 +234,33 @@ where TBackend: TransactionBackend + 'static
                             match (*msg).clone() {
                                 TransactionEvent::ReceivedTransaction(tx_id) => {
                                     self.receive_transaction_event(tx_id);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::ReceivedTransactionReply(tx_id) => {
                                     self.receive_transaction_reply_event(tx_id);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::ReceivedFinalizedTransaction(tx_id) => {
                                     self.receive_finalized_transaction_event(tx_id);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::TransactionSendResult(tx_id, status) => {
                                     self.receive_transaction_send_result(tx_id, status);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::TransactionCancelled(tx_id, reason) => {
                                     self.receive_transaction_cancellation(tx_id, reason as u64);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::TransactionBroadcast(tx_id) => {
                                     self.receive_transaction_broadcast_event(tx_id);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::TransactionMined{tx_id, is_valid: _} => {
                                     self.receive_transaction_mined_event(tx_id);
-                                    self.trigger_balance_refresh().await;
                                 },
-                                TransactionEvent::TransactionMinedUnconfirmed{tx_id, num_confirmations, is_valid: _} => {
+                                TransactionEvent::TransactionMinedUnconfirmed{tx_id, num_confirmations, confirmations_required: _, is_valid: _} => {
                                     self.receive_transaction_mined_unconfirmed_event(tx_id, num_confirmations);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::FauxTransactionConfirmed{tx_id, is_valid: _} => {
                                     self.receive_faux_transaction_confirmed_event(tx_id);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::FauxTransactionUnconfirmed{tx_id, num_confirmations, is_valid: _} => {
                                     self.receive_faux_transaction_unconfirmed_event(tx_id, num_confirmations);
-                                    self.trigger_balance_refresh().await;
-                                },
-                                TransactionEvent::TransactionValidationStateChanged(_request_key)  => {
-                                    self.trigger_balance_refresh().await;
                                 },
                                 TransactionEvent::TransactionValidationCompleted(request_key)  => {
                                     self.transaction_validation_complete_event(request_key.as_u64(), true);
@@ -283,12 +268,6 @@ where TBackend: TransactionBackend + 'static
                                 TransactionEvent::TransactionValidationFailed(request_key)  => {
                                     self.transaction_validation_complete_event(request_key.as_u64(), false);
                                 },
-                                TransactionEvent::TransactionMinedRequestTimedOut(_tx_id) |
-                                TransactionEvent::TransactionImported(_tx_id)|
-                                TransactionEvent::TransactionCompletedImmediately(_tx_id)
-                                => {
-                                    self.trigger_balance_refresh().await;
-                                },
                                 // Only the above variants are mapped to callbacks
                                 _ => (),
                             }
@@ -303,7 +282,6 @@ where TBackend: TransactionBackend + 'static
                             match (*msg).clone() {
                                 OutputManagerEvent::TxoValidationSuccess(request_key) => {
                                     self.output_validation_complete_event(request_key,  true);
-                                    self.trigger_balance_refresh().await;
                                 },
                                 OutputManagerEvent::TxoValidationFailure(request_key) => {
                                     self.output_validation_complete_event(request_key,  false);
@@ -326,6 +304,12 @@ where TBackend: TransactionBackend + 'static
                         Err(_e) => error!(target: LOG_TARGET, "Error reading from DHT event broadcast channel"),
                     }
                 }
+                Ok(_) = self.balance_updates.changed() => {
+                    let balance = self.balance_updates.borrow().clone();
+                    if let Some(balance) = balance {
+                        self.balance_updated_event(balance);
+                    }
+                },
                 Ok(_) = self.connectivity_status_watch.changed() => {
                     let status  = *self.connectivity_status_watch.borrow();
                     trace!(target: LOG_TARGET, "Connectivity status change detected: {:?}", status);
@@ -409,29 +393,18 @@ where TBackend: TransactionBackend + 'static
         }
     }
 
-    async fn trigger_balance_refresh(&mut self) {
-        match self.output_manager_service.get_balance().await {
-            Ok(balance) => {
-                if balance != self.balance_cache {
-                    self.balance_cache = balance.clone();
-                    debug!(
-                        target: LOG_TARGET,
-                        "Calling Update Balance callback function: available {}, time locked {:?}, incoming {}, \
-                         outgoing {}",
-                        balance.available_balance,
-                        balance.time_locked_balance,
-                        balance.pending_incoming_balance,
-                        balance.pending_outgoing_balance
-                    );
-                    let boxing = Box::into_raw(Box::new(balance));
-                    unsafe {
-                        (self.callback_balance_updated)(boxing);
-                    }
-                }
-            },
-            Err(e) => {
-                error!(target: LOG_TARGET, "Could not obtain balance ({:?})", e);
-            },
+    fn balance_updated_event(&mut self, balance: Balance) {
+        debug!(
+            target: LOG_TARGET,
+            "Calling Update Balance callback function: available {}, time locked {:?}, incoming {}, outgoing {}",
+            balance.available_balance,
+            balance.time_locked_balance,
+            balance.pending_incoming_balance,
+            balance.pending_outgoing_balance
+        );
+        let boxing = Box::into_raw(Box::new(balance));
+        unsafe {
+            (self.callback_balance_updated)(boxing);
         }
     }
 
@@ -45,6 +45,7 @@ mod test {
                 sqlite_db::TransactionServiceSqliteDatabase,
             },
         },
+        util::watch::Watch,
     };
     use tokio::{
         runtime::Runtime,
@@ -360,11 +361,13 @@ mod test {
         let (dht_event_sender, dht_event_receiver) = broadcast::channel(20);
 
         let (oms_request_sender, oms_request_receiver) = reply_channel::unbounded();
-        let mut oms_handle = OutputManagerHandle::new(oms_request_sender, oms_event_sender.clone());
+        let balance_watch = Watch::new(None);
+        let mut oms_handle =
+            OutputManagerHandle::new(oms_request_sender, oms_event_sender.clone(), balance_watch.clone());
 
         let shutdown_signal = Shutdown::new();
         let mut mock_output_manager_service =
-            MockOutputManagerService::new(oms_request_receiver, shutdown_signal.to_signal());
+            MockOutputManagerService::new(oms_request_receiver, shutdown_signal.to_signal(), balance_watch);
         let mut balance = Balance {
             available_balance: completed_tx.amount +
                 completed_tx.fee +
@@ -414,9 +417,9 @@ mod test {
         runtime.spawn(callback_handler.start());
         let mut callback_balance_updated = 0;
 
-        // The balance updated callback is bundled with other callbacks and will only fire if the balance actually
-        // changed from an initial zero balance.
-        // Balance updated should be detected with following event, total = 1 times
+        // The balance updated callback fires whenever the output manager publishes a new balance, starting with the
+        // balance that was published before the handler started.
+        // Balance updated should be detected, total = 1 times
         transaction_event_sender
             .send(Arc::new(TransactionEvent::ReceivedTransaction(1u64.into())))
             .unwrap();
@@ -435,7 +438,7 @@ mod test {
 
         balance.time_locked_balance = Some(completed_tx_cancelled.amount);
         mock_output_manager_service_state.set_balance(balance.clone());
-        // Balance updated should be detected with following event, total = 2 times
+        // Balance updated should be detected, total = 2 times
         transaction_event_sender
             .send(Arc::new(TransactionEvent::ReceivedTransactionReply(2u64.into())))
             .unwrap();
@@ -454,7 +457,7 @@ mod test {
 
         balance.pending_incoming_balance += inbound_tx.amount;
         mock_output_manager_service_state.set_balance(balance.clone());
-        // Balance updated should be detected with following event, total = 3 times
+        // Balance updated should be detected, total = 3 times
         transaction_event_sender
             .send(Arc::new(TransactionEvent::ReceivedFinalizedTransaction(2u64.into())))
             .unwrap();
@@ -486,6 +489,7 @@ mod test {
             .send(Arc::new(TransactionEvent::TransactionMinedUnconfirmed {
                 tx_id: 2u64.into(),
                 num_confirmations: 22u64,
+                confirmations_required: 3,
                 is_valid: true,
             }))
             .unwrap();
@@ -525,7 +529,7 @@ mod test {
 
         balance.pending_outgoing_balance += outbound_tx.amount;
         mock_output_manager_service_state.set_balance(balance.clone());
-        // Balance updated should be detected with following event, total = 4 times
+        // Balance updated should be detected, total = 4 times
         transaction_event_sender
             .send(Arc::new(TransactionEvent::TransactionCancelled(
                 3u64.into(),
@@ -569,7 +573,7 @@ mod test {
 
         balance.available_balance -= completed_tx_cancelled.amount;
         mock_output_manager_service_state.set_balance(balance.clone());
-        // Balance updated should be detected with following event, total = 5 times
+        // Balance updated should be detected, total = 5 times
         oms_event_sender
             .send(Arc::new(OutputManagerEvent::TxoValidationSuccess(1u64)))
             .unwrap();
@@ -612,7 +616,7 @@ mod test {
 
         balance.pending_incoming_balance += faux_unconfirmed_tx.amount;
         mock_output_manager_service_state.set_balance(balance.clone());
-        // Balance updated should be detected with following event, total = 6 times
+        // Balance updated should be detected, total = 6 times
         transaction_event_sender
             .send(Arc::new(TransactionEvent::FauxTransactionUnconfirmed {
                 tx_id: 6u64.into(),
@@ -635,7 +639,7 @@ mod test {
 
         balance.available_balance += faux_confirmed_tx.amount;
         mock_output_manager_service_state.set_balance(balance.clone());
-        // Balance updated should be detected with following event, total = 7 times
+        // Balance updated should be detected, total = 7 times
         transaction_event_sender
             .send(Arc::new(TransactionEvent::FauxTransactionConfirmed {
                 tx_id: 7u64.into(),
@@ -19,6 +19,8 @@
 // SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::cell::Cell;
+
 use log::*;
 use tari_comms::multiaddr;
 use tari_comms_dht::store_forward::StoreAndForwardError;
@@ -29,7 +31,7 @@ use tari_crypto::{
 use tari_key_manager::error::{KeyManagerError, MnemonicError};
 use tari_wallet::{
     contacts_service::error::{ContactsServiceError, ContactsServiceStorageError},
-    error::{WalletError, WalletStorageError},
+    error::{ClassifyError, ErrorClass, WalletError, WalletStorageError},
     output_manager_service::error::{OutputManagerError, OutputManagerStorageError},
     transaction_service::error::{TransactionServiceError, TransactionStorageError},
 };
@@ -37,6 +39,11 @@ use thiserror::Error;
 
 const LOG_TARGET: &str = "wallet_ffi::error";
 
+thread_local! {
+    /// The classification of the last error that was converted to a [LibWalletError] on this thread
+    static LAST_ERROR_CLASS: Cell<Option<ErrorClass>> = Cell::new(None);
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum InterfaceError {
     #[error("An error has occurred due to one of the parameters being null: `{0}`")]
@@ -65,9 +72,25 @@ pub struct LibWalletError {
     pub message: String,
 }
 
+impl LibWalletError {
+    /// The classification of the last error that was reported to the client on this thread, which the client can read
+    /// after a function has set its error code
+    pub fn last_error_class() -> Option<ErrorClass> {
+        LAST_ERROR_CLASS.with(Cell::get)
+    }
+
+    fn record_class(class: ErrorClass) {
+        LAST_ERROR_CLASS.with(|c| c.set(Some(class)));
+    }
+}
+
 impl From<InterfaceError> for LibWalletError {
     fn from(v: InterfaceError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", v));
+        Self::record_class(match v {
+            InterfaceError::TokioError(_) | InterfaceError::BalanceError => ErrorClass::INTERNAL,
+            _ => ErrorClass::INVALID_ARGUMENT,
+        });
         match v {
             InterfaceError::NullError(_) => Self {
                 code: 1,
@@ -115,6 +138,7 @@ impl From<WalletError> for LibWalletError {
     #[allow(clippy::too_many_lines)]
     fn from(w: WalletError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", w));
+        Self::record_class(w.class());
         match w {
             // Output Manager Service Errors
             WalletError::OutputManagerError(OutputManagerError::NotEnoughFunds) => Self {
@@ -313,6 +337,18 @@ impl From<WalletError> for LibWalletError {
                 code: 432,
                 message: format!("{:?}", w),
             },
+            WalletError::WalletStorageError(WalletStorageError::PlatformKeystoreError(_)) => Self {
+                code: 433,
+                message: format!("{:?}", w),
+            },
+            WalletError::NetworkMismatch { .. } => Self {
+                code: 434,
+                message: format!("{:?}", w),
+            },
+            WalletError::BaseNodeIdentityChanged { .. } => Self {
+                code: 435,
+                message: format!("{:?}", w),
+            },
             // This is the catch all error code. Any error that is not explicitly mapped above will be given this code
             _ => Self {
                 code: 999,
@@ -327,6 +363,7 @@ impl From<WalletError> for LibWalletError {
 impl From<HexError> for LibWalletError {
     fn from(h: HexError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", h));
+        Self::record_class(ErrorClass::INVALID_ARGUMENT);
         match h {
             HexError::HexConversionError => Self {
                 code: 404,
@@ -349,6 +386,7 @@ impl From<HexError> for LibWalletError {
 impl From<ByteArrayError> for LibWalletError {
     fn from(b: ByteArrayError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", b));
+        Self::record_class(ErrorClass::INVALID_ARGUMENT);
         match b {
             ByteArrayError::ConversionError(_) => Self {
                 code: 404,
@@ -365,6 +403,7 @@ impl From<ByteArrayError> for LibWalletError {
 impl From<multiaddr::Error> for LibWalletError {
     fn from(err: multiaddr::Error) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", err));
+        Self::record_class(ErrorClass::INVALID_ARGUMENT);
         match err {
             multiaddr::Error::ParsingError(_) => Self {
                 code: 801,
@@ -401,6 +440,7 @@ impl From<multiaddr::Error> for LibWalletError {
 impl From<SchnorrSignatureError> for LibWalletError {
     fn from(err: SchnorrSignatureError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", err));
+        Self::record_class(ErrorClass::INVALID_ARGUMENT);
         match err {
             SchnorrSignatureError::InvalidChallenge => Self {
                 code: 901,
@@ -413,6 +453,7 @@ impl From<SchnorrSignatureError> for LibWalletError {
 impl From<StoreAndForwardError> for LibWalletError {
     fn from(err: StoreAndForwardError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", err));
+        Self::record_class(ErrorClass::CONNECTIVITY);
         Self {
             code: 902,
             message: format!("{:?}", err),
@@ -432,6 +473,7 @@ pub enum TransactionError {
 impl From<TransactionError> for LibWalletError {
     fn from(v: TransactionError) -> Self {
         error!(target: LOG_TARGET, "{}", v);
+        Self::record_class(ErrorClass::CONFLICT);
         match v {
             TransactionError::StatusError(_) => Self {
                 code: 640,
@@ -448,6 +490,7 @@ impl From<TransactionError> for LibWalletError {
 impl From<MnemonicError> for LibWalletError {
     fn from(err: MnemonicError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", err));
+        Self::record_class(ErrorClass::INVALID_ARGUMENT);
         Self {
             code: 910,
             message: format!("{:?}", err),
@@ -88,6 +88,7 @@ use rand::rngs::OsRng;
 use tari_common::configuration::StringList;
 use tari_common_types::{
     emoji::{emoji_set, EmojiId, EmojiIdError},
+    qr_payload::QrErrorCorrection,
     transaction::{TransactionDirection, TransactionStatus, TxId},
     types::{Commitment, PublicKey},
 };
@@ -139,7 +140,11 @@ use tari_wallet::{
     storage::{
         database::WalletDatabase,
         sqlite_db::wallet::WalletSqliteDatabase,
-        sqlite_utilities::{initialize_sqlite_database_backends, partial_wallet_backup},
+        sqlite_utilities::{
+            initialize_sqlite_database_backends,
+            partial_wallet_backup,
+            read_or_create_keystore_passphrase,
+        },
     },
     transaction_service::{
         config::TransactionServiceConfig,
@@ -153,6 +158,7 @@ use tari_wallet::{
     wallet::{derive_comms_secret_key, read_or_create_master_seed},
     Wallet,
     WalletConfig,
+    WalletExtensions,
     WalletSqlite,
 };
 use tokio::runtime::Runtime;
@@ -161,6 +167,7 @@ use crate::{
     callback_handler::CallbackHandler,
     enums::SeedWordPushResult,
     error::{InterfaceError, TransactionError},
+    platform_keystore::{FfiPlatformKeystore, PlatformKeystoreCallback},
     tasks::recovery_event_monitoring,
 };
 
@@ -171,12 +178,14 @@ mod enums;
 mod error;
 #[cfg(test)]
 mod output_manager_service_mock;
+mod platform_keystore;
 mod tasks;
 
 const LOG_TARGET: &str = "wallet_ffi";
 
 pub type TariTransportConfig = tari_p2p::TransportConfig;
 pub type TariPublicKey = tari_common_types::types::PublicKey;
+pub type TariQrPayload = tari_common_types::qr_payload::QrPayload;
 pub type TariNodeId = tari_comms::peer_manager::NodeId;
 pub type TariPrivateKey = tari_common_types::types::PrivateKey;
 pub type TariOutputFeatures = tari_core::transactions::transaction_components::OutputFeatures;
@@ -1074,6 +1083,307 @@ pub unsafe extern "C" fn emoji_id_to_public_key(emoji: *const c_char, error_out:
 
 /// -------------------------------------------------------------------------------------------- ///
 
+/// -------------------------------- QR Payload ------------------------------------------------ ///
+
+/// Creates the payload of a QR code that requests a payment to a public key
+///
+/// ## Arguments
+/// `network` - The name of the network, e.g. "dibbler"
+/// `public_key` - The pointer to the TariPublicKey to be paid
+/// `amount` - The amount requested in µT, or 0 for no amount
+/// `memo` - The memo of the payment, may be null
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariQrPayload` - Returns a pointer to a TariQrPayload. Note that it returns null on error.
+///
+/// # Safety
+/// The ```qr_payload_destroy``` method must be called when finished with a TariQrPayload to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_create(
+    network: *const c_char,
+    public_key: *mut TariPublicKey,
+    amount: c_ulonglong,
+    memo: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariQrPayload {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if network.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("network".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let network = match CStr::from_ptr(network).to_str() {
+        Ok(v) => v,
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("network".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    let mut payload = TariQrPayload::new(network, (*public_key).clone());
+    if amount > 0 {
+        payload = payload.with_amount(amount);
+    }
+    if !memo.is_null() {
+        match CStr::from_ptr(memo).to_str() {
+            Ok(v) if !v.is_empty() => payload = payload.with_memo(v),
+            Ok(_) => {},
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("memo".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    }
+    Box::into_raw(Box::new(payload))
+}
+
+/// Parses the payload of a scanned QR code
+///
+/// ## Arguments
+/// `payload` - The text of the QR code, a `tari://` URI
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariQrPayload` - Returns a pointer to a TariQrPayload. Note that it returns null if the payload is invalid.
+///
+/// # Safety
+/// The ```qr_payload_destroy``` method must be called when finished with a TariQrPayload to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_from_string(payload: *const c_char, error_out: *mut c_int) -> *mut TariQrPayload {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if payload.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("payload".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let payload = match CStr::from_ptr(payload).to_str() {
+        Ok(v) => v,
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("payload".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    match payload.parse::<TariQrPayload>() {
+        Ok(payload) => Box::into_raw(Box::new(payload)),
+        Err(e) => {
+            error = LibWalletError::from(InterfaceError::InvalidArgument(e.to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Gets the text to encode in the QR code
+///
+/// ## Arguments
+/// `payload` - The pointer to a TariQrPayload
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if payload is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_to_string(payload: *mut TariQrPayload, error_out: *mut c_int) -> *mut c_char {
+    let mut error = 0;
+    let mut result = CString::new("").expect("Blank CString will not fail.");
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if payload.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("payload".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return CString::into_raw(result);
+    }
+    match CString::new((*payload).to_uri()) {
+        Ok(v) => result = v,
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("payload".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        },
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the network that a TariQrPayload was created for, which the wallet should check against its own
+///
+/// ## Arguments
+/// `payload` - The pointer to a TariQrPayload
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if payload is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_get_network(payload: *mut TariQrPayload, error_out: *mut c_int) -> *mut c_char {
+    let mut error = 0;
+    let mut result = CString::new("").expect("Blank CString will not fail.");
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if payload.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("payload".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return CString::into_raw(result);
+    }
+    match CString::new((*payload).network.as_str()) {
+        Ok(v) => result = v,
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("network".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        },
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the public key to be paid from a TariQrPayload
+///
+/// ## Arguments
+/// `payload` - The pointer to a TariQrPayload
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariPublicKey` - Returns a pointer to a TariPublicKey. Note that it returns null if payload is null
+///
+/// # Safety
+/// The ```public_key_destroy``` method must be called when finished with a TariPublicKey to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_get_public_key(
+    payload: *mut TariQrPayload,
+    error_out: *mut c_int,
+) -> *mut TariPublicKey {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if payload.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("payload".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*payload).public_key.clone()))
+}
+
+/// Gets the amount requested in a TariQrPayload
+///
+/// ## Arguments
+/// `payload` - The pointer to a TariQrPayload
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the amount in µT, or 0 if the payload does not request an amount or payload is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_get_amount(payload: *mut TariQrPayload, error_out: *mut c_int) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if payload.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("payload".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*payload).amount.unwrap_or_default()
+}
+
+/// Gets the memo of a TariQrPayload
+///
+/// ## Arguments
+/// `payload` - The pointer to a TariQrPayload
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if the payload has no
+/// memo or payload is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_get_memo(payload: *mut TariQrPayload, error_out: *mut c_int) -> *mut c_char {
+    let mut error = 0;
+    let mut result = CString::new("").expect("Blank CString will not fail.");
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if payload.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("payload".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return CString::into_raw(result);
+    }
+    if let Some(memo) = (*payload).memo.as_ref() {
+        match CString::new(memo.as_str()) {
+            Ok(v) => result = v,
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("memo".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+            },
+        }
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the error correction level to render a TariQrPayload with. Long payloads get a lower level so that the QR
+/// code stays small enough to scan.
+///
+/// ## Arguments
+/// `payload` - The pointer to a TariQrPayload
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_uint` - Returns the level: 0 = Low (L), 1 = Medium (M), 2 = Quartile (Q), 3 = High (H)
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_get_error_correction(payload: *mut TariQrPayload, error_out: *mut c_int) -> c_uint {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if payload.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("payload".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    match (*payload).error_correction() {
+        QrErrorCorrection::Low => 0,
+        QrErrorCorrection::Medium => 1,
+        QrErrorCorrection::Quartile => 2,
+        QrErrorCorrection::High => 3,
+    }
+}
+
+/// Frees memory for a TariQrPayload
+///
+/// ## Arguments
+/// `payload` - The pointer to a TariQrPayload
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn qr_payload_destroy(payload: *mut TariQrPayload) {
+    if !payload.is_null() {
+        Box::from_raw(payload);
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
 /// -------------------------------- Private Key ----------------------------------------------- ///
 
 /// Creates a TariPrivateKey from a ByteVector
@@ -1849,6 +2159,139 @@ pub unsafe extern "C" fn seed_words_push_word(
     }
 }
 
+/// Create a TariSeedWords instance containing the words of the requested language that start with a prefix, for
+/// completing partially typed seed words. Case and, for latin scripts, diacritics are ignored.
+///
+/// ## Arguments
+/// `prefix` - The partially typed word
+/// `language` - The required language as a string
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `TariSeedWords` - Returns the TariSeedWords instance containing the matching words in word list order, which is
+/// empty if the prefix is empty or no word matches.
+///
+/// # Safety
+/// The `seed_words_destroy` method must be called when finished with a TariSeedWords instance from rust to prevent a
+/// memory leak
+#[no_mangle]
+pub unsafe extern "C" fn seed_words_get_completions(
+    prefix: *const c_char,
+    language: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariSeedWords {
+    use tari_key_manager::mnemonic::word_completions;
+
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    let mut completions = Vec::new();
+    if prefix.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("prefix".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else if language.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("language".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        match (
+            CStr::from_ptr(prefix).to_str(),
+            CStr::from_ptr(language)
+                .to_str()
+                .ok()
+                .and_then(|l| TariMnemonicLanguage::from_str(l).ok()),
+        ) {
+            (Ok(prefix), Some(language)) => {
+                completions = word_completions(prefix, language);
+            },
+            (Err(_), _) => {
+                error = LibWalletError::from(InterfaceError::PointerError("prefix".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+            },
+            (_, None) => {
+                error = LibWalletError::from(InterfaceError::InvalidArgument(
+                    "seed word completions - language not supported".to_string(),
+                ))
+                .code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+            },
+        }
+    }
+
+    Box::into_raw(Box::new(TariSeedWords(completions)))
+}
+
+/// Detects the language of the words in a TariSeedWords instance, which can hold a partial seed phrase
+///
+/// ## Arguments
+/// `seed_words` - The pointer to a TariSeedWords
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array with the name of the language, as accepted by
+/// `seed_words_get_mnemonic_word_list_for_language`. Note that it returns an empty char array if no language contains
+/// all of the words
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn seed_words_detect_language(
+    seed_words: *const TariSeedWords,
+    error_out: *mut c_int,
+) -> *mut c_char {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut language = CString::new("").expect("Blank CString will not fail.");
+    if seed_words.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("seed words".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        match MnemonicLanguage::detect_language(&(*seed_words).0) {
+            Ok(l) => {
+                language = CString::new(l.to_string()).expect("Language names do not contain null bytes");
+            },
+            Err(e) => {
+                error = LibWalletError::from(WalletError::KeyManagerError(e.into())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+            },
+        }
+    }
+    CString::into_raw(language)
+}
+
+/// Checks that a TariSeedWords instance holds a complete seed phrase with a valid checksum. The passphrase is not
+/// needed, so a recovery UI can report typos before the wallet is created.
+///
+/// ## Arguments
+/// `seed_words` - The pointer to a TariSeedWords
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter. It holds the reason when the seed phrase is invalid.
+///
+/// ## Returns
+/// `bool` - Returns true if the seed phrase is valid
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn seed_words_validate(seed_words: *const TariSeedWords, error_out: *mut c_int) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if seed_words.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("seed words".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    match CipherSeed::validate_mnemonic(&(*seed_words).0) {
+        Ok(_) => true,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::KeyManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
 /// Frees memory for a TariSeedWords
 ///
 /// ## Arguments
@@ -4287,6 +4730,9 @@ pub unsafe extern "C" fn wallet_create(
     if let TransportType::Tor = comms_config.transport.transport_type {
         comms_config.transport.tor.identity = wallet_database.get_tor_id().ok().flatten();
     }
+    if let TransportType::I2p = comms_config.transport.transport_type {
+        comms_config.transport.i2p.identity = wallet_database.get_i2p_id().ok().flatten();
+    }
 
     let result = runtime.block_on(async {
         let master_seed = read_or_create_master_seed(recovery_seed, &wallet_database)
@@ -4378,6 +4824,7 @@ pub unsafe extern "C" fn wallet_create(
         key_manager_backend,
         shutdown.to_signal(),
         master_seed,
+        WalletExtensions::default(),
     ));
 
     match w {
@@ -4388,6 +4835,11 @@ pub unsafe extern "C" fn wallet_create(
                     warn!(target: LOG_TARGET, "Could not save tor identity to db: {:?}", e);
                 }
             }
+            if let Some(session) = w.comms.i2p_session() {
+                if let Err(e) = w.db.set_i2p_identity(session.identity().clone()) {
+                    warn!(target: LOG_TARGET, "Could not save I2P identity to db: {:?}", e);
+                }
+            }
             // Start Callback Handler
             let callback_handler = CallbackHandler::new(
                 TransactionDatabase::new(transaction_backend),
@@ -5067,42 +5519,115 @@ pub unsafe extern "C" fn wallet_verify_message_signature(
         return result;
     }
 
-    if let Some(key1) = hex_keys.get(0) {
-        if let Some(key2) = hex_keys.get(1) {
-            let secret = TariPrivateKey::from_hex(key1);
-            match secret {
-                Ok(p) => {
-                    let public_nonce = TariPublicKey::from_hex(key2);
-                    match public_nonce {
-                        Ok(pn) => {
-                            result = (*wallet)
-                                .wallet
-                                .verify_message_signature((*public_key).clone(), pn, p, message)
-                        },
-                        Err(e) => {
-                            error = LibWalletError::from(e).code;
-                            ptr::swap(error_out, &mut error as *mut c_int);
-                        },
-                    }
-                },
-                Err(e) => {
-                    error = LibWalletError::from(e).code;
-                    ptr::swap(error_out, &mut error as *mut c_int);
-                },
-            }
-        } else {
-            error = LibWalletError::from(InterfaceError::InvalidArgument("hex_sig_nonce".to_string())).code;
-            ptr::swap(error_out, &mut error as *mut c_int);
-        }
-    } else {
-        error = LibWalletError::from(InterfaceError::InvalidArgument("hex_sig_nonce".to_string())).code;
+    if let Some(key1) = hex_keys.get(0) {
+        if let Some(key2) = hex_keys.get(1) {
+            let secret = TariPrivateKey::from_hex(key1);
+            match secret {
+                Ok(p) => {
+                    let public_nonce = TariPublicKey::from_hex(key2);
+                    match public_nonce {
+                        Ok(pn) => {
+                            result = (*wallet)
+                                .wallet
+                                .verify_message_signature((*public_key).clone(), pn, p, message)
+                        },
+                        Err(e) => {
+                            error = LibWalletError::from(e).code;
+                            ptr::swap(error_out, &mut error as *mut c_int);
+                        },
+                    }
+                },
+                Err(e) => {
+                    error = LibWalletError::from(e).code;
+                    ptr::swap(error_out, &mut error as *mut c_int);
+                },
+            }
+        } else {
+            error = LibWalletError::from(InterfaceError::InvalidArgument("hex_sig_nonce".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        }
+    } else {
+        error = LibWalletError::from(InterfaceError::InvalidArgument("hex_sig_nonce".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    }
+
+    result
+}
+
+/// Adds a base node peer to the TariWallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `public_key` - The TariPublicKey pointer
+/// `address` - The pointer to a char array
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if successful or not. Fails with error code 435 if another public key is pinned for the address.
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_add_base_node_peer(
+    wallet: *mut TariWallet,
+    public_key: *mut TariPublicKey,
+    address: *const c_char,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    if public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    let parsed_addr;
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    } else {
+        match CStr::from_ptr(address).to_str() {
+            Ok(v) => {
+                parsed_addr = match Multiaddr::from_str(v) {
+                    Ok(v) => v,
+                    Err(_) => {
+                        error = LibWalletError::from(InterfaceError::InvalidArgument("address is invalid".to_string()))
+                            .code;
+                        ptr::swap(error_out, &mut error as *mut c_int);
+                        return false;
+                    },
+                }
+            },
+            _ => {
+                error = LibWalletError::from(InterfaceError::PointerError("address".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return false;
+            },
+        }
+    }
+
+    if let Err(e) = (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.set_base_node_peer((*public_key).clone(), parsed_addr))
+    {
+        error = LibWalletError::from(e).code;
         ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
     }
-
-    result
+    true
 }
 
-/// Adds a base node peer to the TariWallet
+/// Pins the public key as the identity of the base node at the address and sets it as the base node peer of the
+/// TariWallet. This accepts a base node that presents another identity than the one that was pinned for its address.
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
@@ -5117,7 +5642,7 @@ pub unsafe extern "C" fn wallet_verify_message_signature(
 /// # Safety
 /// None
 #[no_mangle]
-pub unsafe extern "C" fn wallet_add_base_node_peer(
+pub unsafe extern "C" fn wallet_trust_new_base_node_identity(
     wallet: *mut TariWallet,
     public_key: *mut TariPublicKey,
     address: *const c_char,
@@ -5163,10 +5688,11 @@ pub unsafe extern "C" fn wallet_add_base_node_peer(
         }
     }
 
-    if let Err(e) = (*wallet)
-        .runtime
-        .block_on((*wallet).wallet.set_base_node_peer((*public_key).clone(), parsed_addr))
-    {
+    if let Err(e) = (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .trust_new_base_node_identity((*public_key).clone(), parsed_addr),
+    ) {
         error = LibWalletError::from(e).code;
         ptr::swap(error_out, &mut error as *mut c_int);
         return false;
@@ -5484,6 +6010,7 @@ pub unsafe extern "C" fn wallet_send_transaction(
                 OutputFeatures::default(),
                 MicroTari::from(fee_per_gram),
                 message_string,
+                None,
             )) {
             Ok(tx_id) => tx_id.as_u64(),
             Err(e) => {
@@ -5697,9 +6224,11 @@ pub unsafe extern "C" fn wallet_get_completed_transactions(
             // The frontend specification calls for completed transactions that have not yet been mined to be
             // classified as Pending Transactions. In order to support this logic without impacting the practical
             // definitions and storage of a MimbleWimble CompletedTransaction we will remove CompletedTransactions with
-            // the Completed and Broadcast states from the list returned by this FFI function
+            // the Completed and Broadcast states from the list returned by this FFI function. Decoy self-spends are
+            // never presented as payments.
             for tx in completed_transactions
                 .values()
+                .filter(|ct| !ct.is_decoy)
                 .filter(|ct| ct.status != TransactionStatus::Completed)
                 .filter(|ct| ct.status != TransactionStatus::Broadcast)
                 .filter(|ct| ct.status != TransactionStatus::Imported)
@@ -5766,6 +6295,7 @@ pub unsafe extern "C" fn wallet_get_pending_inbound_transactions(
                 // list here in the FFI interface
                 for ct in completed_txs
                     .values()
+                    .filter(|ct| !ct.is_decoy)
                     .filter(|ct| {
                         ct.status == TransactionStatus::Completed ||
                             ct.status == TransactionStatus::Broadcast ||
@@ -5835,6 +6365,7 @@ pub unsafe extern "C" fn wallet_get_pending_outbound_transactions(
                 // list here in the FFI interface
                 for ct in completed_txs
                     .values()
+                    .filter(|ct| !ct.is_decoy)
                     .filter(|ct| ct.status == TransactionStatus::Completed || ct.status == TransactionStatus::Broadcast)
                     .filter(|ct| ct.direction == TransactionDirection::Outbound)
                 {
@@ -5921,7 +6452,7 @@ pub unsafe extern "C" fn wallet_get_cancelled_transactions(
     };
 
     let mut completed = Vec::new();
-    for tx in completed_transactions.values() {
+    for tx in completed_transactions.values().filter(|tx| !tx.is_decoy) {
         completed.push(tx.clone());
     }
     for tx in inbound_transactions.values() {
@@ -5974,7 +6505,10 @@ pub unsafe extern "C" fn wallet_get_completed_transaction_by_id(
     match completed_transactions {
         Ok(completed_transactions) => {
             if let Some(tx) = completed_transactions.get(&TxId::from(transaction_id)) {
-                if tx.status != TransactionStatus::Completed && tx.status != TransactionStatus::Broadcast {
+                if !tx.is_decoy &&
+                    tx.status != TransactionStatus::Completed &&
+                    tx.status != TransactionStatus::Broadcast
+                {
                     let completed = tx.clone();
                     return Box::into_raw(Box::new(completed));
                 }
@@ -6032,7 +6566,8 @@ pub unsafe extern "C" fn wallet_get_pending_inbound_transaction_by_id(
     match completed_transactions {
         Ok(completed_transactions) => {
             if let Some(tx) = completed_transactions.get(&transaction_id) {
-                if (tx.status == TransactionStatus::Broadcast || tx.status == TransactionStatus::Completed) &&
+                if !tx.is_decoy &&
+                    (tx.status == TransactionStatus::Broadcast || tx.status == TransactionStatus::Completed) &&
                     tx.direction == TransactionDirection::Inbound
                 {
                     let completed = tx.clone();
@@ -6106,7 +6641,8 @@ pub unsafe extern "C" fn wallet_get_pending_outbound_transaction_by_id(
     match completed_transactions {
         Ok(completed_transactions) => {
             if let Some(tx) = completed_transactions.get(&transaction_id) {
-                if (tx.status == TransactionStatus::Broadcast || tx.status == TransactionStatus::Completed) &&
+                if !tx.is_decoy &&
+                    (tx.status == TransactionStatus::Broadcast || tx.status == TransactionStatus::Completed) &&
                     tx.direction == TransactionDirection::Outbound
                 {
                     let completed = tx.clone();
@@ -6186,7 +6722,7 @@ pub unsafe extern "C" fn wallet_get_cancelled_transaction_by_id(
         },
     };
 
-    if let Some(tx) = completed_transactions.remove(&transaction_id) {
+    if let Some(tx) = completed_transactions.remove(&transaction_id).filter(|tx| !tx.is_decoy) {
         transaction = Some(tx);
     } else {
         let mut outbound_transactions = match (*wallet).runtime.block_on(
@@ -6663,6 +7199,62 @@ pub unsafe extern "C" fn wallet_get_seed_words(wallet: *mut TariWallet, error_ou
     }
 }
 
+/// Checks the seed words that the user wrote down against the seed words of the provided `TariWallet` at the given
+/// positions, so that a backup can be verified without the seed words leaving the library
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `indices` - An array of the positions of the words in the seed, counted from zero, with as many elements as there
+/// are words in `words`
+/// `words` - The pointer to a TariSeedWords holding the words entered by the user, in the order of `indices`
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if all of the words match the seed, false if any does not or if an error occurs
+///
+/// # Safety
+/// `indices` must point to at least as many elements as there are words in `words`
+#[no_mangle]
+pub unsafe extern "C" fn wallet_verify_seed_backup(
+    wallet: *mut TariWallet,
+    indices: *const c_uint,
+    words: *const TariSeedWords,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if indices.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("indices".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if words.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("words".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    let words = &(*words).0;
+    let indices = slice::from_raw_parts(indices, words.len())
+        .iter()
+        .map(|index| *index as usize)
+        .collect::<Vec<_>>();
+    match (*wallet).wallet.verify_seed_backup(&indices, words) {
+        Ok(matches) => matches,
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
 /// Set the power mode of the wallet to Low Power mode which will reduce the amount of network operations the wallet
 /// performs to conserve power
 ///
@@ -6785,6 +7377,67 @@ pub unsafe extern "C" fn wallet_remove_encryption(wallet: *mut TariWallet, error
     }
 }
 
+/// Gets the passphrase of a wallet whose database encryption key is wrapped by a platform keystore, such as the
+/// Android Keystore or the iOS Secure Enclave, instead of being derived from a passphrase typed by the user. The first
+/// time, a random passphrase is generated and stored in the database wrapped by the keystore.
+///
+/// Pass the passphrase to `wallet_create` to open an encrypted wallet. A wallet database that is not encrypted yet,
+/// like that of a new wallet, is opened with a null passphrase and then encrypted with `wallet_apply_encryption`
+/// using this passphrase. A wallet that is encrypted with a user passphrase must have that encryption removed first.
+///
+/// ## Arguments
+/// `config` - The TariCommsConfig pointer of the wallet
+/// `wrap_key_callback` - The callback function pointer that wraps a key with the platform key
+/// `unwrap_key_callback` - The callback function pointer that unwraps a key wrapped by `wrap_key_callback`
+/// Both callbacks receive a ByteVector that is only valid during the call, and must return a ByteVector created with
+/// `byte_vector_create`, which the library takes ownership of, or null if the keystore failed.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array with the passphrase. Note that it returns ptr::null_mut() if
+/// config is null or the keystore failed
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_keystore_passphrase(
+    config: *mut TariCommsConfig,
+    wrap_key_callback: PlatformKeystoreCallback,
+    unwrap_key_callback: PlatformKeystoreCallback,
+    error_out: *mut c_int,
+) -> *mut c_char {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if config.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("config".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let sql_database_path = (*config)
+        .datastore_path
+        .join((*config).peer_database_name.clone())
+        .with_extension("sqlite3");
+    let keystore = FfiPlatformKeystore::new(wrap_key_callback, unwrap_key_callback);
+    match read_or_create_keystore_passphrase(sql_database_path, &keystore) {
+        Ok(passphrase) => match CString::new(passphrase.reveal().as_str()) {
+            Ok(v) => CString::into_raw(v),
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("passphrase".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                ptr::null_mut()
+            },
+        },
+        Err(e) => {
+            error!(target: LOG_TARGET, "Could not get the keystore passphrase: {}", e);
+            error = LibWalletError::from(WalletError::WalletStorageError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Set a Key Value in the Wallet storage used for Client Key Value store
 ///
 /// ## Arguments
@@ -7671,6 +8324,58 @@ pub unsafe extern "C" fn fee_per_gram_stat_destroy(fee_per_gram_stat: *mut TariF
     }
 }
 
+/// Gets the kind of the last error that a function reported through its `error_out` on this thread, so that the client
+/// can decide how to handle it without matching on error codes
+///
+/// ## Arguments
+/// None
+///
+/// ## Returns
+/// `c_int` - Returns the kind of the error, 0 if no error has been reported. The kinds are:
+/// 1 - InvalidArgument, 2 - NotFound, 3 - Conflict, 4 - InsufficientFunds, 5 - Connectivity, 6 - Storage,
+/// 7 - Unauthorized, 8 - Unavailable, 9 - Rejected, 10 - Internal
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_last_error_kind() -> c_int {
+    LibWalletError::last_error_class().map_or(0, |class| class.kind as c_int)
+}
+
+/// Gets what the client should do about the last error that a function reported through its `error_out` on this
+/// thread
+///
+/// ## Arguments
+/// None
+///
+/// ## Returns
+/// `c_int` - Returns the suggested action, 0 if no error has been reported. The actions are:
+/// 1 - Retry, 2 - RetryWhenConnected, 3 - WaitForFunds, 4 - AwaitEvents, 5 - FixRequest, 6 - ProvidePassphrase,
+/// 7 - Restart, 8 - Report
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_last_error_suggested_action() -> c_int {
+    LibWalletError::last_error_class().map_or(0, |class| class.action as c_int)
+}
+
+/// Gets whether the last error that a function reported through its `error_out` on this thread is transient, i.e.
+/// whether the same call may succeed if it is retried later
+///
+/// ## Arguments
+/// None
+///
+/// ## Returns
+/// `bool` - Returns true if the error is transient, false if it is permanent or no error has been reported
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_last_error_is_transient() -> bool {
+    LibWalletError::last_error_class().map_or(false, |class| class.is_transient())
+}
+
 /// ------------------------------------------------------------------------------------------ ///
 #[cfg(test)]
 mod test {
@@ -7688,9 +8393,13 @@ mod test {
         transactions::test_helpers::{create_test_input, create_unblinded_output, TestParams},
     };
     use tari_crypto::ristretto::pedersen::extended_commitment_factory::ExtendedPedersenCommitmentFactory;
-    use tari_key_manager::{mnemonic::MnemonicLanguage, mnemonic_wordlists};
+    use tari_key_manager::{
+        mnemonic::{Mnemonic, MnemonicLanguage},
+        mnemonic_wordlists,
+    };
     use tari_test_utils::random;
     use tari_wallet::{
+        error::{ErrorKind, SuggestedAction},
         storage::sqlite_utilities::run_migration_and_create_sqlite_connection,
         transaction_service::handle::TransactionSendStatus,
     };
@@ -8140,6 +8849,48 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_qr_payload() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+            let private_key = private_key_generate();
+            let public_key = public_key_from_private_key(private_key, error_ptr);
+            let network = CString::new("dibbler").unwrap();
+            let memo = CString::new("Coffee & cake").unwrap();
+
+            let payload = qr_payload_create(network.as_ptr(), public_key, 1_000_000, memo.as_ptr(), error_ptr);
+            assert_eq!(error, 0);
+            let uri = qr_payload_to_string(payload, error_ptr);
+            assert_eq!(error, 0);
+            let parsed = qr_payload_from_string(uri, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(*parsed, *payload);
+            assert_eq!(qr_payload_get_amount(parsed, error_ptr), 1_000_000);
+            let parsed_memo = qr_payload_get_memo(parsed, error_ptr);
+            assert_eq!(CStr::from_ptr(parsed_memo).to_str().unwrap(), "Coffee & cake");
+            let parsed_network = qr_payload_get_network(parsed, error_ptr);
+            assert_eq!(CStr::from_ptr(parsed_network).to_str().unwrap(), "dibbler");
+            let parsed_key = qr_payload_get_public_key(parsed, error_ptr);
+            assert_eq!(*parsed_key, *public_key);
+            assert_eq!(qr_payload_get_error_correction(parsed, error_ptr), 2);
+
+            let invalid = CString::new("https://example.com").unwrap();
+            let invalid_payload = qr_payload_from_string(invalid.as_ptr(), error_ptr);
+            assert!(invalid_payload.is_null());
+            assert_ne!(error, 0);
+
+            string_destroy(uri);
+            string_destroy(parsed_memo);
+            string_destroy(parsed_network);
+            public_key_destroy(parsed_key);
+            qr_payload_destroy(parsed);
+            qr_payload_destroy(payload);
+            public_key_destroy(public_key);
+            private_key_destroy(private_key);
+        }
+    }
+
     #[test]
     fn test_keys() {
         unsafe {
@@ -9028,6 +9779,116 @@ mod test {
         }
     }
 
+    #[test]
+    pub fn test_seed_word_completion_and_validation() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+
+            let prefix_str = CString::into_raw(CString::new("Zo").unwrap()) as *const c_char;
+            let language_str = CString::into_raw(CString::new("English").unwrap()) as *const c_char;
+            let completions = seed_words_get_completions(prefix_str, language_str, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!((*completions).0, vec!["zone".to_string(), "zoo".to_string()]);
+            seed_words_destroy(completions);
+
+            let unknown_language_str = CString::into_raw(CString::new("Klingon").unwrap()) as *const c_char;
+            let completions = seed_words_get_completions(prefix_str, unknown_language_str, error_ptr);
+            assert_ne!(error, 0);
+            assert_eq!(seed_words_get_length(completions, error_ptr), 0);
+            seed_words_destroy(completions);
+
+            let seed_words = seed_words_create();
+            let seed = CipherSeed::new();
+            let mnemonic = seed.to_mnemonic(MnemonicLanguage::Italian, None).unwrap();
+            for w in &mnemonic {
+                let w_str = CString::into_raw(CString::new(w.as_str()).unwrap()) as *const c_char;
+                seed_words_push_word(seed_words, w_str, error_ptr);
+                string_destroy(w_str as *mut c_char);
+            }
+            let language = CString::from_raw(seed_words_detect_language(seed_words, error_ptr));
+            assert_eq!(error, 0);
+            assert_eq!(language.to_str().unwrap(), MnemonicLanguage::Italian.to_string());
+            assert!(seed_words_validate(seed_words, error_ptr));
+            assert_eq!(error, 0);
+
+            // A partial seed phrase has a language but is not valid
+            (*seed_words).0.truncate(12);
+            let language = CString::from_raw(seed_words_detect_language(seed_words, error_ptr));
+            assert_eq!(language.to_str().unwrap(), MnemonicLanguage::Italian.to_string());
+            assert!(!seed_words_validate(seed_words, error_ptr));
+            assert_ne!(error, 0);
+
+            string_destroy(prefix_str as *mut c_char);
+            string_destroy(language_str as *mut c_char);
+            string_destroy(unknown_language_str as *mut c_char);
+            seed_words_destroy(seed_words);
+        }
+    }
+
+    unsafe extern "C" fn xor_keystore_callback(bytes: *const ByteVector) -> *mut ByteVector {
+        Box::into_raw(Box::new(ByteVector((*bytes).0.iter().map(|b| b ^ 0x5a).collect())))
+    }
+
+    unsafe extern "C" fn failing_keystore_callback(_bytes: *const ByteVector) -> *mut ByteVector {
+        ptr::null_mut()
+    }
+
+    #[test]
+    pub fn test_keystore_passphrase() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+
+            let db_name = CString::new(random::string(8).as_str()).unwrap();
+            let db_name_str: *const c_char = CString::into_raw(db_name) as *const c_char;
+            let temp_dir = tempdir().unwrap();
+            let db_path = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+            let db_path_str: *const c_char = CString::into_raw(db_path) as *const c_char;
+            let transport_type = transport_memory_create();
+            let address = transport_memory_get_address(transport_type, error_ptr);
+            let address_str = CStr::from_ptr(address).to_str().unwrap().to_owned();
+            let address_str = CString::new(address_str).unwrap().into_raw() as *const c_char;
+            let config = comms_config_create(
+                address_str,
+                transport_type,
+                db_name_str,
+                db_path_str,
+                20,
+                10800,
+                error_ptr,
+            );
+
+            // The keystore cannot wrap a new key
+            let passphrase =
+                wallet_get_keystore_passphrase(config, failing_keystore_callback, xor_keystore_callback, error_ptr);
+            assert!(passphrase.is_null());
+            assert_ne!(error, 0);
+
+            let passphrase =
+                wallet_get_keystore_passphrase(config, xor_keystore_callback, xor_keystore_callback, error_ptr);
+            assert_eq!(error, 0);
+            assert!(!passphrase.is_null());
+            let again = wallet_get_keystore_passphrase(config, xor_keystore_callback, xor_keystore_callback, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(CStr::from_ptr(passphrase), CStr::from_ptr(again));
+
+            // The keystore cannot unwrap the stored key
+            let failed =
+                wallet_get_keystore_passphrase(config, xor_keystore_callback, failing_keystore_callback, error_ptr);
+            assert!(failed.is_null());
+            assert_ne!(error, 0);
+
+            string_destroy(passphrase);
+            string_destroy(again);
+            string_destroy(db_name_str as *mut c_char);
+            string_destroy(db_path_str as *mut c_char);
+            string_destroy(address_str as *mut c_char);
+            transport_config_destroy(transport_type);
+            comms_config_destroy(config);
+        }
+    }
+
     fn get_next_memory_address() -> Multiaddr {
         let port = MemoryTransport::acquire_next_memsocket_port();
         format!("/memory/{}", port).parse().unwrap()
@@ -9269,6 +10130,13 @@ mod test {
 
             let seed_words = wallet_get_seed_words(wallet, error_ptr);
             assert_eq!(error, 0);
+            let indices: [c_uint; 2] = [0, 23];
+            let backup = TariSeedWords(vec![(*seed_words).0[0].clone(), (*seed_words).0[23].clone()]);
+            assert!(wallet_verify_seed_backup(wallet, indices.as_ptr(), &backup, error_ptr));
+            assert_eq!(error, 0);
+            let backup = TariSeedWords(vec![(*seed_words).0[23].clone(), (*seed_words).0[0].clone()]);
+            assert!(!wallet_verify_seed_backup(wallet, indices.as_ptr(), &backup, error_ptr));
+            assert_eq!(error, 0);
             let public_key = wallet_get_public_key(wallet, error_ptr);
             assert_eq!(error, 0);
 
@@ -10016,4 +10884,26 @@ mod test {
             destroy_tari_vector(tv);
         }
     }
+
+    #[test]
+    fn test_last_error_class() {
+        unsafe {
+            let error = LibWalletError::from(WalletError::OutputManagerError(OutputManagerError::FundsPending));
+            assert_eq!(error.code, 115);
+            assert_eq!(wallet_last_error_kind(), ErrorKind::InsufficientFunds as c_int);
+            assert_eq!(
+                wallet_last_error_suggested_action(),
+                SuggestedAction::WaitForFunds as c_int
+            );
+            assert!(wallet_last_error_is_transient());
+
+            let _error = LibWalletError::from(InterfaceError::NullError("wallet".to_string()));
+            assert_eq!(wallet_last_error_kind(), ErrorKind::InvalidArgument as c_int);
+            assert_eq!(
+                wallet_last_error_suggested_action(),
+                SuggestedAction::FixRequest as c_int
+            );
+            assert!(!wallet_last_error_is_transient());
+        }
+    }
 }
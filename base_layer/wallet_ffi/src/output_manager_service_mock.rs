@@ -25,10 +25,13 @@ use std::sync::{Arc, Mutex};
 use futures::StreamExt;
 use tari_service_framework::reply_channel::Receiver;
 use tari_shutdown::ShutdownSignal;
-use tari_wallet::output_manager_service::{
-    error::OutputManagerError,
-    handle::{OutputManagerRequest, OutputManagerResponse},
-    service::Balance,
+use tari_wallet::{
+    output_manager_service::{
+        error::OutputManagerError,
+        handle::{OutputManagerRequest, OutputManagerResponse},
+        service::Balance,
+    },
+    util::watch::Watch,
 };
 
 /// This macro unlocks a Mutex or RwLock. If the lock is poisoned (i.e. panic while unlocked) the last value
@@ -48,22 +51,25 @@ macro_rules! acquire_lock {
         };
     }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct ResponseState {
     balance: Arc<Mutex<Balance>>,
+    balance_watch: Watch<Option<Balance>>,
 }
 
 impl ResponseState {
-    pub fn new() -> Self {
+    pub fn new(balance_watch: Watch<Option<Balance>>) -> Self {
         Self {
             balance: Arc::new(Mutex::new(Balance::zero())),
+            balance_watch,
         }
     }
 
-    /// Set the mock server balance response
+    /// Set the mock server balance response and publish it to the subscribers of balance changes
     pub fn set_balance(&mut self, balance: Balance) {
         let mut lock = acquire_lock!(self.balance);
-        *lock = balance;
+        *lock = balance.clone();
+        self.balance_watch.send(Some(balance));
     }
 
     /// Get the mock server balance value
@@ -83,10 +89,11 @@ impl MockOutputManagerService {
     pub fn new(
         request_stream: Receiver<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
         shutdown_signal: ShutdownSignal,
+        balance_watch: Watch<Option<Balance>>,
     ) -> Self {
         Self {
             request_stream: Some(request_stream),
-            state: ResponseState::new(),
+            state: ResponseState::new(balance_watch),
             shutdown_signal: Some(shutdown_signal),
         }
     }
@@ -0,0 +1,50 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use tari_wallet::platform_keystore::{PlatformKeystore, PlatformKeystoreError};
+
+use crate::ByteVector;
+
+/// A callback that wraps or unwraps a key. It must return a ByteVector created with `byte_vector_create`, which the
+/// library takes ownership of, or null if the keystore failed. The argument is only valid during the call.
+pub type PlatformKeystoreCallback = unsafe extern "C" fn(*const ByteVector) -> *mut ByteVector;
+
+/// A platform keystore implemented by the client application, e.g. with the Android Keystore or the iOS Secure Enclave
+pub struct FfiPlatformKeystore {
+    wrap_key_callback: PlatformKeystoreCallback,
+    unwrap_key_callback: PlatformKeystoreCallback,
+}
+
+impl FfiPlatformKeystore {
+    pub fn new(wrap_key_callback: PlatformKeystoreCallback, unwrap_key_callback: PlatformKeystoreCallback) -> Self {
+        Self {
+            wrap_key_callback,
+            unwrap_key_callback,
+        }
+    }
+}
+
+impl PlatformKeystore for FfiPlatformKeystore {
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, PlatformKeystoreError> {
+        call_keystore(self.wrap_key_callback, key)
+            .ok_or_else(|| PlatformKeystoreError::WrapFailed("The wrap callback returned null".to_string()))
+    }
+
+    fn unwrap_key(&self, wrapped_key: &[u8]) -> Result<Vec<u8>, PlatformKeystoreError> {
+        call_keystore(self.unwrap_key_callback, wrapped_key)
+            .ok_or_else(|| PlatformKeystoreError::UnwrapFailed("The unwrap callback returned null".to_string()))
+    }
+}
+
+fn call_keystore(callback: PlatformKeystoreCallback, bytes: &[u8]) -> Option<Vec<u8>> {
+    let input = ByteVector(bytes.to_vec());
+    // Safety: the callback only borrows the input and hands over ownership of the boxed output
+    unsafe {
+        let output = callback(&input);
+        if output.is_null() {
+            None
+        } else {
+            Some(Box::from_raw(output).0)
+        }
+    }
+}
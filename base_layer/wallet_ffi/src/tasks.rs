@@ -101,6 +101,7 @@ pub async fn recovery_event_monitoring(
                 num_recovered,
                 value_recovered,
                 time_taken: elapsed,
+                statistics,
             }) => {
                 let rate = (final_height as f32) * 1000f32 / (elapsed.as_millis() as f32);
                 info!(
@@ -112,6 +113,10 @@ pub async fn recovery_event_monitoring(
                     num_recovered,
                     value_recovered
                 );
+                debug!(
+                    target: LOG_TARGET,
+                    "Recovery statistics per output type: {:?}", statistics
+                );
                 unsafe {
                     (recovery_progress_callback)(
                         RecoveryEvent::Completed as u8,
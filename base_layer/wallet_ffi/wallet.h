@@ -73,6 +73,11 @@ struct OutboundTransaction;
  */
 struct P2pConfig;
 
+/**
+ * A request to be paid, as encoded in a QR code
+ */
+struct QrPayload;
+
 /**
  * The [PublicKey](trait.PublicKey.html) implementation for `ristretto255` is a thin wrapper around the dalek
  * library's [RistrettoPoint](struct.RistrettoPoint.html).
@@ -173,6 +178,8 @@ typedef struct RistrettoSecretKey PrivateKey;
 
 typedef PrivateKey TariPrivateKey;
 
+typedef struct QrPayload TariQrPayload;
+
 /**
  * # A Commitment signature implementation on Ristretto
  *
@@ -289,6 +296,12 @@ typedef struct FeePerGramStatsResponse TariFeePerGramStats;
 
 typedef FeePerGramStat TariFeePerGramStat;
 
+/**
+ * A callback that wraps or unwraps a key. It must return a ByteVector created with `byte_vector_create`, which the
+ * library takes ownership of, or null if the keystore failed. The argument is only valid during the call.
+ */
+typedef struct ByteVector *(*PlatformKeystoreCallback)(const struct ByteVector*);
+
 struct TariUtxo {
   const char *commitment;
   uint64_t value;
@@ -646,6 +659,166 @@ char *public_key_to_emoji_id(TariPublicKey *pk,
 TariPublicKey *emoji_id_to_public_key(const char *emoji,
                                       int *error_out);
 
+/**
+ * -------------------------------------------------------------------------------------------- ///
+ * -------------------------------- QR Payload ------------------------------------------------ ///
+ * Creates the payload of a QR code that requests a payment to a public key
+ *
+ * ## Arguments
+ * `network` - The name of the network, e.g. "dibbler"
+ * `public_key` - The pointer to the TariPublicKey to be paid
+ * `amount` - The amount requested in µT, or 0 for no amount
+ * `memo` - The memo of the payment, may be null
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariQrPayload` - Returns a pointer to a TariQrPayload. Note that it returns null on error.
+ *
+ * # Safety
+ * The ```qr_payload_destroy``` method must be called when finished with a TariQrPayload to prevent a memory leak
+ */
+TariQrPayload *qr_payload_create(const char *network,
+                                 TariPublicKey *public_key,
+                                 unsigned long long amount,
+                                 const char *memo,
+                                 int *error_out);
+
+/**
+ * Parses the payload of a scanned QR code
+ *
+ * ## Arguments
+ * `payload` - The text of the QR code, a `tari://` URI
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariQrPayload` - Returns a pointer to a TariQrPayload. Note that it returns null if the payload is invalid.
+ *
+ * # Safety
+ * The ```qr_payload_destroy``` method must be called when finished with a TariQrPayload to prevent a memory leak
+ */
+TariQrPayload *qr_payload_from_string(const char *payload,
+                                      int *error_out);
+
+/**
+ * Gets the text to encode in the QR code
+ *
+ * ## Arguments
+ * `payload` - The pointer to a TariQrPayload
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if payload is null
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *qr_payload_to_string(TariQrPayload *payload,
+                           int *error_out);
+
+/**
+ * Gets the network that a TariQrPayload was created for, which the wallet should check against its own
+ *
+ * ## Arguments
+ * `payload` - The pointer to a TariQrPayload
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if payload is null
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *qr_payload_get_network(TariQrPayload *payload,
+                             int *error_out);
+
+/**
+ * Gets the public key to be paid from a TariQrPayload
+ *
+ * ## Arguments
+ * `payload` - The pointer to a TariQrPayload
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariPublicKey` - Returns a pointer to a TariPublicKey. Note that it returns null if payload is null
+ *
+ * # Safety
+ * The ```public_key_destroy``` method must be called when finished with a TariPublicKey to prevent a memory leak
+ */
+TariPublicKey *qr_payload_get_public_key(TariQrPayload *payload,
+                                         int *error_out);
+
+/**
+ * Gets the amount requested in a TariQrPayload
+ *
+ * ## Arguments
+ * `payload` - The pointer to a TariQrPayload
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_ulonglong` - Returns the amount in µT, or 0 if the payload does not request an amount or payload is null
+ *
+ * # Safety
+ * None
+ */
+unsigned long long qr_payload_get_amount(TariQrPayload *payload,
+                                         int *error_out);
+
+/**
+ * Gets the memo of a TariQrPayload
+ *
+ * ## Arguments
+ * `payload` - The pointer to a TariQrPayload
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if the payload has no
+ * memo or payload is null
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *qr_payload_get_memo(TariQrPayload *payload,
+                          int *error_out);
+
+/**
+ * Gets the error correction level to render a TariQrPayload with. Long payloads get a lower level so that the QR
+ * code stays small enough to scan.
+ *
+ * ## Arguments
+ * `payload` - The pointer to a TariQrPayload
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_uint` - Returns the level: 0 = Low (L), 1 = Medium (M), 2 = Quartile (Q), 3 = High (H)
+ *
+ * # Safety
+ * None
+ */
+unsigned int qr_payload_get_error_correction(TariQrPayload *payload,
+                                             int *error_out);
+
+/**
+ * Frees memory for a TariQrPayload
+ *
+ * ## Arguments
+ * `payload` - The pointer to a TariQrPayload
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void qr_payload_destroy(TariQrPayload *payload);
+
 /**
  * -------------------------------------------------------------------------------------------- ///
  * -------------------------------- Private Key ----------------------------------------------- ///
@@ -992,6 +1165,65 @@ unsigned char seed_words_push_word(struct TariSeedWords *seed_words,
                                    const char *word,
                                    int *error_out);
 
+/**
+ * Create a TariSeedWords instance containing the words of the requested language that start with a prefix, for
+ * completing partially typed seed words. Case and, for latin scripts, diacritics are ignored.
+ *
+ * ## Arguments
+ * `prefix` - The partially typed word
+ * `language` - The required language as a string
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `TariSeedWords` - Returns the TariSeedWords instance containing the matching words in word list order, which is
+ * empty if the prefix is empty or no word matches.
+ *
+ * # Safety
+ * The `seed_words_destroy` method must be called when finished with a TariSeedWords instance from rust to prevent a
+ * memory leak
+ */
+struct TariSeedWords *seed_words_get_completions(const char *prefix,
+                                                 const char *language,
+                                                 int *error_out);
+
+/**
+ * Detects the language of the words in a TariSeedWords instance, which can hold a partial seed phrase
+ *
+ * ## Arguments
+ * `seed_words` - The pointer to a TariSeedWords
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array with the name of the language, as accepted by
+ * `seed_words_get_mnemonic_word_list_for_language`. Note that it returns an empty char array if no language contains
+ * all of the words
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *seed_words_detect_language(const struct TariSeedWords *seed_words,
+                                 int *error_out);
+
+/**
+ * Checks that a TariSeedWords instance holds a complete seed phrase with a valid checksum. The passphrase is not
+ * needed, so a recovery UI can report typos before the wallet is created.
+ *
+ * ## Arguments
+ * `seed_words` - The pointer to a TariSeedWords
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter. It holds the reason when the seed phrase is invalid.
+ *
+ * ## Returns
+ * `bool` - Returns true if the seed phrase is valid
+ *
+ * # Safety
+ * None
+ */
+bool seed_words_validate(const struct TariSeedWords *seed_words,
+                         int *error_out);
+
 /**
  * Frees memory for a TariSeedWords
  *
@@ -2486,7 +2718,7 @@ bool wallet_verify_message_signature(struct TariWallet *wallet,
  * as an out parameter.
  *
  * ## Returns
- * `bool` - Returns if successful or not
+ * `bool` - Returns if successful or not. Fails with error code 435 if another public key is pinned for the address.
  *
  * # Safety
  * None
@@ -2496,6 +2728,28 @@ bool wallet_add_base_node_peer(struct TariWallet *wallet,
                                const char *address,
                                int *error_out);
 
+/**
+ * Pins the public key as the identity of the base node at the address and sets it as the base node peer of the
+ * TariWallet. This accepts a base node that presents another identity than the one that was pinned for its address.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `public_key` - The TariPublicKey pointer
+ * `address` - The pointer to a char array
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns if successful or not
+ *
+ * # Safety
+ * None
+ */
+bool wallet_trust_new_base_node_identity(struct TariWallet *wallet,
+                                         TariPublicKey *public_key,
+                                         const char *address,
+                                         int *error_out);
+
 /**
  * Upserts a TariContact to the TariWallet. If the contact does not exist it will be Inserted. If it does exist the
  * Alias will be updated.
@@ -3039,6 +3293,29 @@ bool wallet_restart_transaction_broadcast(struct TariWallet *wallet,
 struct TariSeedWords *wallet_get_seed_words(struct TariWallet *wallet,
                                             int *error_out);
 
+/**
+ * Checks the seed words that the user wrote down against the seed words of the provided `TariWallet` at the given
+ * positions, so that a backup can be verified without the seed words leaving the library
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `indices` - An array of the positions of the words in the seed, counted from zero, with as many elements as there
+ * are words in `words`
+ * `words` - The pointer to a TariSeedWords holding the words entered by the user, in the order of `indices`
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if all of the words match the seed, false if any does not or if an error occurs
+ *
+ * # Safety
+ * `indices` must point to at least as many elements as there are words in `words`
+ */
+bool wallet_verify_seed_backup(struct TariWallet *wallet,
+                               const unsigned int *indices,
+                               const struct TariSeedWords *words,
+                               int *error_out);
+
 /**
  * Set the power mode of the wallet to Low Power mode which will reduce the amount of network operations the wallet
  * performs to conserve power
@@ -3097,6 +3374,36 @@ void wallet_apply_encryption(struct TariWallet *wallet,
 void wallet_remove_encryption(struct TariWallet *wallet,
                               int *error_out);
 
+/**
+ * Gets the passphrase of a wallet whose database encryption key is wrapped by a platform keystore, such as the
+ * Android Keystore or the iOS Secure Enclave, instead of being derived from a passphrase typed by the user. The first
+ * time, a random passphrase is generated and stored in the database wrapped by the keystore.
+ *
+ * Pass the passphrase to `wallet_create` to open an encrypted wallet. A wallet database that is not encrypted yet,
+ * like that of a new wallet, is opened with a null passphrase and then encrypted with `wallet_apply_encryption`
+ * using this passphrase. A wallet that is encrypted with a user passphrase must have that encryption removed first.
+ *
+ * ## Arguments
+ * `config` - The TariCommsConfig pointer of the wallet
+ * `wrap_key_callback` - The callback function pointer that wraps a key with the platform key
+ * `unwrap_key_callback` - The callback function pointer that unwraps a key wrapped by `wrap_key_callback`
+ * Both callbacks receive a ByteVector that is only valid during the call, and must return a ByteVector created with
+ * `byte_vector_create`, which the library takes ownership of, or null if the keystore failed.
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array with the passphrase. Note that it returns ptr::null_mut() if
+ * config is null or the keystore failed
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *wallet_get_keystore_passphrase(TariCommsConfig *config,
+                                     PlatformKeystoreCallback wrap_key_callback,
+                                     PlatformKeystoreCallback unwrap_key_callback,
+                                     int *error_out);
+
 /**
  * Set a Key Value in the Wallet storage used for Client Key Value store
  *
@@ -3530,6 +3837,55 @@ unsigned long long fee_per_gram_stat_get_max_fee_per_gram(TariFeePerGramStat *fe
  */
 void fee_per_gram_stat_destroy(TariFeePerGramStat *fee_per_gram_stat);
 
+/**
+ * Gets the kind of the last error that a function reported through its `error_out` on this thread, so that the client
+ * can decide how to handle it without matching on error codes
+ *
+ * ## Arguments
+ * None
+ *
+ * ## Returns
+ * `c_int` - Returns the kind of the error, 0 if no error has been reported. The kinds are:
+ * 1 - InvalidArgument, 2 - NotFound, 3 - Conflict, 4 - InsufficientFunds, 5 - Connectivity, 6 - Storage,
+ * 7 - Unauthorized, 8 - Unavailable, 9 - Rejected, 10 - Internal
+ *
+ * # Safety
+ * None
+ */
+int wallet_last_error_kind(void);
+
+/**
+ * Gets what the client should do about the last error that a function reported through its `error_out` on this
+ * thread
+ *
+ * ## Arguments
+ * None
+ *
+ * ## Returns
+ * `c_int` - Returns the suggested action, 0 if no error has been reported. The actions are:
+ * 1 - Retry, 2 - RetryWhenConnected, 3 - WaitForFunds, 4 - AwaitEvents, 5 - FixRequest, 6 - ProvidePassphrase,
+ * 7 - Restart, 8 - Report
+ *
+ * # Safety
+ * None
+ */
+int wallet_last_error_suggested_action(void);
+
+/**
+ * Gets whether the last error that a function reported through its `error_out` on this thread is transient, i.e.
+ * whether the same call may succeed if it is retried later
+ *
+ * ## Arguments
+ * None
+ *
+ * ## Returns
+ * `bool` - Returns true if the error is transient, false if it is permanent or no error has been reported
+ *
+ * # Safety
+ * None
+ */
+bool wallet_last_error_is_transient(void);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
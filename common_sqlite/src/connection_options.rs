@@ -33,6 +33,7 @@ pub struct ConnectionOptions {
     enable_wal: bool,
     enable_foreign_keys: bool,
     busy_timeout: Option<Duration>,
+    read_only: bool,
 }
 
 impl ConnectionOptions {
@@ -41,6 +42,18 @@ impl ConnectionOptions {
             enable_wal,
             enable_foreign_keys,
             busy_timeout: Some(busy_timeout),
+            read_only: false,
+        }
+    }
+
+    /// Options for connections that refuse to write to the database. The journal mode is left as it is, as changing
+    /// it is a write.
+    pub fn read_only(busy_timeout: Duration) -> Self {
+        Self {
+            enable_wal: false,
+            enable_foreign_keys: false,
+            busy_timeout: Some(busy_timeout),
+            read_only: true,
         }
     }
 }
@@ -57,6 +70,9 @@ impl diesel::r2d2::CustomizeConnection<SqliteConnection, diesel::r2d2::Error> fo
             if let Some(d) = self.busy_timeout {
                 conn.batch_execute(&format!("PRAGMA busy_timeout = {};", d.as_millis()))?;
             }
+            if self.read_only {
+                conn.batch_execute("PRAGMA query_only = ON;")?;
+            }
             Ok(())
         })()
         .map_err(diesel::r2d2::Error::QueryError)
@@ -64,11 +64,25 @@ impl SqliteConnectionPool {
         }
     }
 
-    /// Create an sqlite connection pool managed by the pool connection manager
+    /// A pool of connections that refuse to write to the database. Any statement that would modify the database fails.
+    pub fn new_read_only(db_path: String, pool_size: usize, busy_timeout: Duration) -> Self {
+        Self {
+            pool: None,
+            db_path,
+            pool_size,
+            connection_options: ConnectionOptions::read_only(busy_timeout),
+        }
+    }
+
+    /// Create an sqlite connection pool managed by the pool connection manager. Connections are kept open for the
+    /// lifetime of the pool, so the statements that a connection prepared stay in its statement cache and are reused
+    /// by every caller that gets the connection from the pool.
     pub fn create_pool(&mut self) -> Result<(), SqliteStorageError> {
         if self.pool.is_none() {
             let pool = Pool::builder()
                 .max_size(u32::try_from(self.pool_size)?)
+                .idle_timeout(None)
+                .max_lifetime(None)
                 .connection_customizer(Box::new(self.connection_options.clone()))
                 .build(ConnectionManager::<SqliteConnection>::new(self.db_path.as_str()))
                 .map_err(|e| SqliteStorageError::DieselR2d2Error(e.to_string()));
@@ -39,6 +39,7 @@ use crate::{
         ListenerInfo,
     },
     connectivity::{ConnectivityEventRx, ConnectivityManager, ConnectivityRequest, ConnectivityRequester},
+    i2p,
     multiaddr::Multiaddr,
     noise::NoiseConfig,
     peer_manager::{NodeIdentity, PeerManager},
@@ -125,6 +126,13 @@ impl UnspawnedCommsNode {
         self
     }
 
+    /// Set the I2P session to associate with this comms instance. The session is kept open for as long as comms is
+    /// running.
+    pub fn with_i2p_session(mut self, i2p_session: i2p::I2pSession) -> Self {
+        self.builder.i2p_session = Some(i2p_session);
+        self
+    }
+
     /// Spawn a new node using the specified [Transport](crate::transports::Transport).
     pub async fn spawn_with_transport<TTransport>(self, transport: TTransport) -> Result<CommsNode, CommsBuilderError>
     where
@@ -147,6 +155,7 @@ impl UnspawnedCommsNode {
         let CommsBuilder {
             dial_backoff,
             hidden_service_ctl,
+            i2p_session,
             connection_manager_config,
             connectivity_config,
             ..
@@ -223,6 +232,11 @@ impl UnspawnedCommsNode {
             }
             hidden_service = Some(hs);
         }
+        if let Some(session) = i2p_session.as_ref() {
+            if node_identity.public_address() != *session.public_address() {
+                node_identity.set_public_address(session.public_address().clone());
+            }
+        }
         info!(
             target: LOG_TARGET,
             "Your node's public address is '{}'",
@@ -237,6 +251,7 @@ impl UnspawnedCommsNode {
             node_identity,
             peer_manager,
             hidden_service,
+            i2p_session,
             complete_signals: ext_context.drain_complete_signals(),
         })
     }
@@ -282,6 +297,8 @@ pub struct CommsNode {
     listening_info: ListenerInfo,
     /// `Some` if the comms node is configured to run via a hidden service, otherwise `None`
     hidden_service: Option<tor::HiddenService>,
+    /// `Some` if the comms node is configured to run over I2P, otherwise `None`
+    i2p_session: Option<i2p::I2pSession>,
     /// The 'reciprocal' shutdown signals for each comms service
     complete_signals: Vec<ShutdownSignal>,
 }
@@ -322,6 +339,11 @@ impl CommsNode {
         self.hidden_service.as_ref()
     }
 
+    /// Return the I2P session, if the comms node is running over I2P
+    pub fn i2p_session(&self) -> Option<&i2p::I2pSession> {
+        self.i2p_session.as_ref()
+    }
+
     /// Return a handle that is used to call the connectivity service.
     pub fn connectivity(&self) -> ConnectivityRequester {
         self.connectivity_requester.clone()
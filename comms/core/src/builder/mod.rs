@@ -44,6 +44,7 @@ use crate::{
     backoff::{Backoff, BoxedBackoff, ConstantBackoff},
     connection_manager::{ConnectionManagerConfig, ConnectionManagerRequester},
     connectivity::{ConnectivityConfig, ConnectivityRequester},
+    i2p,
     multiaddr::Multiaddr,
     peer_manager::{NodeIdentity, PeerManager},
     protocol::{NodeNetworkInfo, ProtocolExtensions},
@@ -122,6 +123,7 @@ pub struct CommsBuilder {
     node_identity: Option<Arc<NodeIdentity>>,
     dial_backoff: BoxedBackoff,
     hidden_service_ctl: Option<tor::HiddenServiceController>,
+    i2p_session: Option<i2p::I2pSession>,
     connection_manager_config: ConnectionManagerConfig,
     connectivity_config: ConnectivityConfig,
 
@@ -136,6 +138,7 @@ impl Default for CommsBuilder {
             node_identity: None,
             dial_backoff: Box::new(ConstantBackoff::new(Duration::from_millis(500))),
             hidden_service_ctl: None,
+            i2p_session: None,
             connection_manager_config: ConnectionManagerConfig::default(),
             connectivity_config: ConnectivityConfig::default(),
             shutdown_signal: None,
@@ -0,0 +1,26 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::io;
+
+use thiserror::Error;
+
+use crate::multiaddr::Multiaddr;
+
+#[derive(Debug, Error)]
+pub enum I2pError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("The SAM bridge closed the connection")]
+    ConnectionClosed,
+    #[error("The SAM bridge replied with {result}: {message}")]
+    SamError { result: String, message: String },
+    #[error("Unexpected reply from the SAM bridge: {0}")]
+    UnexpectedReply(String),
+    #[error("Invalid I2P destination: {0}")]
+    InvalidDestination(String),
+    #[error("Address '{0}' is not supported by the I2P transport")]
+    UnsupportedAddress(Multiaddr),
+    #[error("The SAM bridge address was not provided. Use `with_sam_address` to set it.")]
+    SamAddressNotProvided,
+}
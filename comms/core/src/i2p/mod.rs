@@ -0,0 +1,21 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! # I2P
+//!
+//! Support for running comms over [I2P](https://geti2p.net) using the SAM v3 bridge of a local I2P router.
+//!
+//! An [I2pSession] registers the node's destination with the router and keeps it alive while comms is running. The
+//! [I2pTransport] dials peers and accepts inbound connections through that session. I2P peers are addressed as
+//! `/dns4/<hash>.b32.i2p/tcp/<port>`. The port only exists to satisfy the multiaddr format and is ignored by I2P.
+
+mod error;
+pub use error::I2pError;
+
+mod sam_client;
+
+mod session;
+pub use session::{I2pIdentity, I2pSession, I2pSessionBuilder};
+
+mod transport;
+pub use transport::I2pTransport;
@@ -0,0 +1,233 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use log::*;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use super::I2pError;
+
+const LOG_TARGET: &str = "comms::i2p::sam_client";
+
+/// The SAM protocol version spoken by the client
+const SAM_VERSION: &str = "3.1";
+/// The signature type of generated destinations
+const SIGNATURE_TYPE: &str = "EdDSA_SHA512_Ed25519";
+/// Upper bound on the length of a reply line, which protects against a misbehaving bridge
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// A connection to the SAM bridge. Every SAM connection is used for a single purpose: either as the control connection
+/// of a session, or for a single stream, which is why the stream commands consume the client.
+pub(super) struct SamClient {
+    stream: TcpStream,
+}
+
+impl SamClient {
+    /// Connects to the SAM bridge and completes the version handshake
+    pub async fn connect(address: SocketAddr) -> Result<Self, I2pError> {
+        let stream = TcpStream::connect(address).await?;
+        let mut client = Self { stream };
+        client
+            .request(&format!("HELLO VERSION MIN={} MAX={}", SAM_VERSION, SAM_VERSION))
+            .await?
+            .expect("HELLO", "REPLY")?;
+        Ok(client)
+    }
+
+    /// Generates a new destination, returning the public destination and the private key
+    pub async fn generate_destination(&mut self) -> Result<(String, String), I2pError> {
+        let mut reply = self
+            .request(&format!("DEST GENERATE SIGNATURE_TYPE={}", SIGNATURE_TYPE))
+            .await?
+            .expect("DEST", "REPLY")?;
+        Ok((reply.take("PUB")?, reply.take("PRIV")?))
+    }
+
+    /// Creates a streaming session for the destination with the given private key. The session lasts until this
+    /// connection is closed.
+    pub async fn create_session(&mut self, session_id: &str, private_key: &str) -> Result<(), I2pError> {
+        self.request(&format!(
+            "SESSION CREATE STYLE=STREAM ID={} DESTINATION={}",
+            session_id, private_key
+        ))
+        .await?
+        .expect("SESSION", "STATUS")?;
+        Ok(())
+    }
+
+    /// Opens a stream to `destination`, which can be a full destination or a `.i2p` hostname
+    pub async fn stream_connect(mut self, session_id: &str, destination: &str) -> Result<TcpStream, I2pError> {
+        self.request(&format!(
+            "STREAM CONNECT ID={} DESTINATION={} SILENT=false",
+            session_id, destination
+        ))
+        .await?
+        .expect("STREAM", "STATUS")?;
+        Ok(self.stream)
+    }
+
+    /// Waits for an inbound stream, returning the stream and the destination of the remote peer
+    pub async fn stream_accept(mut self, session_id: &str) -> Result<(TcpStream, String), I2pError> {
+        self.request(&format!("STREAM ACCEPT ID={} SILENT=false", session_id))
+            .await?
+            .expect("STREAM", "STATUS")?;
+        // Once a peer connects, the bridge sends the peer's destination, optionally followed by its ports
+        let line = self.read_line().await?;
+        let destination = line
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| I2pError::UnexpectedReply(line.clone()))?
+            .to_string();
+        Ok((self.stream, destination))
+    }
+
+    async fn request(&mut self, command: &str) -> Result<SamReply, I2pError> {
+        trace!(
+            target: LOG_TARGET,
+            "Sending '{}'",
+            command.split_whitespace().take(2).collect::<Vec<_>>().join(" ")
+        );
+        self.stream.write_all(command.as_bytes()).await?;
+        self.stream.write_all(b"\n").await?;
+        let line = self.read_line().await?;
+        SamReply::parse(&line)
+    }
+
+    /// Reads a reply line a byte at a time, so that no bytes that follow it on the stream are consumed
+    async fn read_line(&mut self) -> Result<String, I2pError> {
+        let mut line = Vec::new();
+        loop {
+            let byte = match self.stream.read_u8().await {
+                Ok(byte) => byte,
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Err(I2pError::ConnectionClosed),
+                Err(err) => return Err(err.into()),
+            };
+            if byte == b'\n' {
+                break;
+            }
+            if line.len() >= MAX_LINE_LENGTH {
+                return Err(I2pError::UnexpectedReply("Reply line is too long".to_string()));
+            }
+            line.push(byte);
+        }
+        String::from_utf8(line).map_err(|err| I2pError::UnexpectedReply(err.to_string()))
+    }
+}
+
+/// A reply from the SAM bridge in the form `TOPIC COMMAND KEY=VALUE...`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SamReply {
+    topic: String,
+    command: String,
+    values: HashMap<String, String>,
+}
+
+impl SamReply {
+    fn parse(line: &str) -> Result<Self, I2pError> {
+        let mut tokens = tokenize(line).into_iter();
+        let topic = tokens
+            .next()
+            .ok_or_else(|| I2pError::UnexpectedReply(line.to_string()))?;
+        let command = tokens
+            .next()
+            .ok_or_else(|| I2pError::UnexpectedReply(line.to_string()))?;
+        let values = tokens
+            .map(|token| match token.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (token, String::new()),
+            })
+            .collect();
+        Ok(Self { topic, command, values })
+    }
+
+    /// Checks that this is a successful reply to the expected command
+    fn expect(mut self, topic: &str, command: &str) -> Result<Self, I2pError> {
+        if self.topic != topic || self.command != command {
+            return Err(I2pError::UnexpectedReply(format!(
+                "Expected {} {} but got {} {}",
+                topic, command, self.topic, self.command
+            )));
+        }
+        match self.values.remove("RESULT") {
+            Some(result) if result != "OK" => Err(I2pError::SamError {
+                result,
+                message: self.values.remove("MESSAGE").unwrap_or_default(),
+            }),
+            _ => Ok(self),
+        }
+    }
+
+    fn take(&mut self, key: &str) -> Result<String, I2pError> {
+        self.values
+            .remove(key)
+            .ok_or_else(|| I2pError::UnexpectedReply(format!("Reply does not contain {}", key)))
+    }
+}
+
+/// Splits a reply line on whitespace, keeping quoted values (e.g. `MESSAGE="Invalid key"`) together
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut is_quoted = false;
+    for c in line.trim().chars() {
+        match c {
+            '"' => is_quoted = !is_quoted,
+            c if c.is_whitespace() && !is_quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            },
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_reply() {
+        let reply = SamReply::parse("HELLO REPLY RESULT=OK VERSION=3.1").unwrap();
+        assert_eq!(reply.topic, "HELLO");
+        assert_eq!(reply.command, "REPLY");
+        assert_eq!(reply.values.get("VERSION").unwrap(), "3.1");
+        let reply = reply.expect("HELLO", "REPLY").unwrap();
+        assert!(reply.values.get("RESULT").is_none());
+    }
+
+    #[test]
+    fn parse_error_reply() {
+        let reply = SamReply::parse(r#"SESSION STATUS RESULT=INVALID_KEY MESSAGE="Invalid destination key""#).unwrap();
+        match reply.expect("SESSION", "STATUS").unwrap_err() {
+            I2pError::SamError { result, message } => {
+                assert_eq!(result, "INVALID_KEY");
+                assert_eq!(message, "Invalid destination key");
+            },
+            err => panic!("Unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn parse_unexpected_reply() {
+        let reply = SamReply::parse("STREAM STATUS RESULT=OK").unwrap();
+        assert!(matches!(
+            reply.expect("SESSION", "STATUS"),
+            Err(I2pError::UnexpectedReply(_))
+        ));
+        assert!(matches!(SamReply::parse(" "), Err(I2pError::UnexpectedReply(_))));
+    }
+
+    #[test]
+    fn tokenize_quoted_values() {
+        assert_eq!(tokenize(r#"A B C="d e"  F"#), vec!["A", "B", "C=d e", "F"]);
+    }
+}
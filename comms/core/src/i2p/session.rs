@@ -0,0 +1,204 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{fmt, net::SocketAddr, sync::Arc};
+
+use data_encoding::{Encoding, Specification, BASE32_NOPAD};
+use derivative::Derivative;
+use log::*;
+use once_cell::sync::Lazy;
+use rand::{rngs::OsRng, RngCore};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{sam_client::SamClient, I2pError};
+use crate::{
+    multiaddr::{Multiaddr, Protocol},
+    utils::multiaddr::multiaddr_to_socketaddr,
+};
+
+const LOG_TARGET: &str = "comms::i2p::session";
+
+/// The default port included in the I2P address of the node
+const DEFAULT_PORT: u16 = 18141;
+
+/// I2P encodes destinations in base64 using `-` and `~` instead of `+` and `/`
+static I2P_BASE64: Lazy<Encoding> = Lazy::new(|| {
+    let mut spec = Specification::new();
+    spec.symbols
+        .push_str("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-~");
+    spec.padding = Some('=');
+    spec.encoding().expect("I2P base64 specification is valid")
+});
+
+/// The keys of an I2P destination. Persisting the identity lets the node keep the same I2P address across restarts.
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug)]
+pub struct I2pIdentity {
+    /// The public destination, in I2P base64
+    pub destination: String,
+    /// The private key of the destination, in I2P base64
+    #[derivative(Debug = "ignore")]
+    pub private_key: String,
+}
+
+impl I2pIdentity {
+    /// Returns the `<hash>.b32.i2p` hostname of the destination
+    pub fn b32_address(&self) -> Result<String, I2pError> {
+        b32_address(&self.destination)
+    }
+
+    /// Returns the multiaddr used to reach the destination, in the form `/dns4/<hash>.b32.i2p/tcp/<port>`
+    pub fn to_multiaddr(&self, port: u16) -> Result<Multiaddr, I2pError> {
+        to_multiaddr(&self.destination, port)
+    }
+}
+
+impl fmt::Display for I2pIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.b32_address() {
+            Ok(address) => writeln!(f, "Address: {}", address),
+            Err(_) => writeln!(f, "Address: <invalid destination>"),
+        }
+    }
+}
+
+/// Returns the `<hash>.b32.i2p` hostname of an I2P base64 destination
+pub(super) fn b32_address(destination: &str) -> Result<String, I2pError> {
+    let bytes = I2P_BASE64
+        .decode(destination.as_bytes())
+        .map_err(|err| I2pError::InvalidDestination(err.to_string()))?;
+    let hash = Sha256::digest(&bytes);
+    Ok(format!("{}.b32.i2p", BASE32_NOPAD.encode(&hash).to_lowercase()))
+}
+
+pub(super) fn to_multiaddr(destination: &str, port: u16) -> Result<Multiaddr, I2pError> {
+    let mut addr = Multiaddr::empty();
+    addr.push(Protocol::Dns4(b32_address(destination)?.into()));
+    addr.push(Protocol::Tcp(port));
+    Ok(addr)
+}
+
+/// Builds an [I2pSession]. A new I2P identity is generated unless one is provided.
+#[derive(Default)]
+pub struct I2pSessionBuilder {
+    sam_address: Option<Multiaddr>,
+    identity: Option<I2pIdentity>,
+    port: Option<u16>,
+}
+
+impl I2pSessionBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The address of the SAM bridge of the I2P router
+    pub fn with_sam_address(mut self, address: Multiaddr) -> Self {
+        self.sam_address = Some(address);
+        self
+    }
+
+    /// The identity to create the session with. If not set, a new identity is generated.
+    pub fn with_identity(mut self, identity: I2pIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// The port included in the I2P address of the node
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub async fn build(self) -> Result<I2pSession, I2pError> {
+        let sam_address = self.sam_address.ok_or(I2pError::SamAddressNotProvided)?;
+        let sam_address = multiaddr_to_socketaddr(&sam_address)?;
+        let mut control_connection = SamClient::connect(sam_address).await?;
+
+        let identity = match self.identity {
+            Some(identity) => identity,
+            None => {
+                debug!(target: LOG_TARGET, "Generating a new I2P identity");
+                let (destination, private_key) = control_connection.generate_destination().await?;
+                I2pIdentity {
+                    destination,
+                    private_key,
+                }
+            },
+        };
+        let public_address = identity.to_multiaddr(self.port.unwrap_or(DEFAULT_PORT))?;
+
+        let session_id = format!("tari_{:016x}", OsRng.next_u64());
+        control_connection
+            .create_session(&session_id, &identity.private_key)
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "I2P session '{}' created for address '{}'", session_id, public_address
+        );
+
+        Ok(I2pSession {
+            identity,
+            public_address,
+            session_id,
+            sam_address,
+            _control_connection: Arc::new(control_connection),
+        })
+    }
+}
+
+/// A streaming session registered with the SAM bridge. The bridge closes the session when its control connection is
+/// closed, which happens when the last clone of the session is dropped.
+#[derive(Clone)]
+pub struct I2pSession {
+    identity: I2pIdentity,
+    public_address: Multiaddr,
+    session_id: String,
+    sam_address: SocketAddr,
+    _control_connection: Arc<SamClient>,
+}
+
+impl I2pSession {
+    /// The identity of the session, which should be persisted to keep the same address across restarts
+    pub fn identity(&self) -> &I2pIdentity {
+        &self.identity
+    }
+
+    /// The address at which other I2P nodes can reach this node
+    pub fn public_address(&self) -> &Multiaddr {
+        &self.public_address
+    }
+
+    pub(super) fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub(super) fn sam_address(&self) -> SocketAddr {
+        self.sam_address
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn b32_address_of_destination() {
+        let destination = I2P_BASE64.encode(&[0xab; 391]);
+        let address = b32_address(&destination).unwrap();
+        assert!(address.ends_with(".b32.i2p"));
+        assert_eq!(address.len(), 52 + ".b32.i2p".len());
+        assert_eq!(address, address.to_lowercase());
+
+        let addr = to_multiaddr(&destination, 18141).unwrap();
+        assert_eq!(addr.to_string(), format!("/dns4/{}/tcp/18141", address));
+    }
+
+    #[test]
+    fn b32_address_rejects_invalid_destination() {
+        assert!(matches!(
+            b32_address("not+i2p/base64"),
+            Err(I2pError::InvalidDestination(_))
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2022 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{net::SocketAddr, time::Duration};
+
+use log::*;
+use tokio::{net::TcpStream, sync::mpsc, time};
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{sam_client::SamClient, session, I2pError, I2pSession};
+use crate::{
+    multiaddr::{Multiaddr, Protocol},
+    transports::{TcpTransport, Transport},
+};
+
+const LOG_TARGET: &str = "comms::i2p::transport";
+
+/// How long to wait before accepting again after the SAM bridge failed to accept a stream
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Transport that dials and accepts connections through an [I2pSession]
+#[derive(Clone)]
+pub struct I2pTransport {
+    sam_address: SocketAddr,
+    session_id: String,
+    port: u16,
+    tcp_transport: Option<TcpTransport>,
+}
+
+impl I2pTransport {
+    pub fn new(session: &I2pSession) -> Self {
+        let port = session
+            .public_address()
+            .iter()
+            .find_map(|protocol| match protocol {
+                Protocol::Tcp(port) => Some(port),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Self {
+            sam_address: session.sam_address(),
+            session_id: session.session_id().to_string(),
+            port,
+            tcp_transport: None,
+        }
+    }
+
+    /// Dial addresses that are not I2P addresses directly over TCP, instead of rejecting them. This reveals the IP
+    /// address of the node to those peers.
+    pub fn with_tcp_fallback(mut self) -> Self {
+        self.tcp_transport = Some(TcpTransport::new());
+        self
+    }
+
+    /// Returns the `.i2p` hostname of the address, if it is an I2P address
+    fn i2p_hostname(addr: &Multiaddr) -> Option<String> {
+        match addr.iter().next()? {
+            Protocol::Dns(host) | Protocol::Dns4(host) | Protocol::Dns6(host) if host.ends_with(".i2p") => {
+                Some(host.to_string())
+            },
+            _ => None,
+        }
+    }
+}
+
+#[crate::async_trait]
+impl Transport for I2pTransport {
+    type Error = I2pError;
+    type Listener = ReceiverStream<Result<(TcpStream, Multiaddr), I2pError>>;
+    type Output = TcpStream;
+
+    async fn listen(&self, addr: Multiaddr) -> Result<(Self::Listener, Multiaddr), Self::Error> {
+        let (tx, rx) = mpsc::channel(1);
+        let sam_address = self.sam_address;
+        let session_id = self.session_id.clone();
+        let port = self.port;
+        tokio::spawn(async move {
+            // Each inbound stream is accepted on a new SAM connection
+            loop {
+                let accept = async {
+                    let client = SamClient::connect(sam_address).await?;
+                    let (stream, destination) = client.stream_accept(&session_id).await?;
+                    let remote_addr = session::to_multiaddr(&destination, port)?;
+                    Ok::<_, I2pError>((stream, remote_addr))
+                };
+                let result = tokio::select! {
+                    result = accept => result,
+                    _ = tx.closed() => break,
+                };
+                let is_err = result.is_err();
+                if tx.send(result).await.is_err() {
+                    break;
+                }
+                if is_err {
+                    time::sleep(ACCEPT_RETRY_DELAY).await;
+                }
+            }
+            debug!(target: LOG_TARGET, "I2P listener closed");
+        });
+        Ok((ReceiverStream::new(rx), addr))
+    }
+
+    async fn dial(&self, addr: Multiaddr) -> Result<Self::Output, Self::Error> {
+        match Self::i2p_hostname(&addr) {
+            Some(hostname) => {
+                debug!(target: LOG_TARGET, "Dialing '{}' over I2P", hostname);
+                let client = SamClient::connect(self.sam_address).await?;
+                client.stream_connect(&self.session_id, &hostname).await
+            },
+            None => match self.tcp_transport.as_ref() {
+                Some(tcp_transport) => Ok(tcp_transport.dial(addr).await?),
+                None => Err(I2pError::UnsupportedAddress(addr)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn i2p_hostname() {
+        let addr = "/dns4/ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkdq.b32.i2p/tcp/18141"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            I2pTransport::i2p_hostname(&addr).unwrap(),
+            "ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkdq.b32.i2p"
+        );
+        let addr = "/dns4/example.com/tcp/18141".parse().unwrap();
+        assert!(I2pTransport::i2p_hostname(&addr).is_none());
+        let addr = "/ip4/127.0.0.1/tcp/18141".parse().unwrap();
+        assert!(I2pTransport::i2p_hostname(&addr).is_none());
+    }
+}
@@ -38,6 +38,7 @@ mod stream_id;
 
 pub mod backoff;
 pub mod bounded_executor;
+pub mod i2p;
 pub mod memsocket;
 pub mod protocol;
 pub mod runtime;
@@ -172,6 +172,18 @@ impl ConnectivityManagerMockState {
         .await
     }
 
+    /// Fails the dials of a peer that were deferred with `set_pending_connection`
+    pub async fn fail_pending_connection(&self, peer: &NodeId, err: ConnectionManagerError) {
+        self.with_state(|state| {
+            if let Some(replies) = state.pending_conns.remove(peer) {
+                replies.into_iter().for_each(|reply| {
+                    let _result = reply.send(Err(err.clone()));
+                });
+            }
+        })
+        .await
+    }
+
     pub fn publish_event(&self, event: ConnectivityEvent) {
         self.event_tx.send(event).unwrap();
     }
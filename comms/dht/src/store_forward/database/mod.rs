@@ -171,6 +171,33 @@ impl StoreAndForwardDatabase {
             .map_err(Into::into)
     }
 
+    pub(crate) fn count_messages(&self) -> Result<usize, StorageError> {
+        let conn = self.connection.get_pooled_connection()?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let count = stored_messages::table
+            .select(dsl::count(stored_messages::id))
+            .first::<i64>(&conn)? as usize;
+        Ok(count)
+    }
+
+    pub(crate) fn count_messages_for_peer(
+        &self,
+        public_key: &CommsPublicKey,
+        node_id: &NodeId,
+    ) -> Result<usize, StorageError> {
+        let conn = self.connection.get_pooled_connection()?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let count = stored_messages::table
+            .select(dsl::count(stored_messages::id))
+            .filter(
+                stored_messages::destination_pubkey
+                    .eq(public_key.to_hex())
+                    .or(stored_messages::destination_node_id.eq(node_id.to_hex())),
+            )
+            .first::<i64>(&conn)? as usize;
+        Ok(count)
+    }
+
     #[cfg(test)]
     pub(crate) fn get_all_messages(&self) -> Result<Vec<StoredMessage>, StorageError> {
         let conn = self.connection.get_pooled_connection()?;
@@ -303,4 +330,26 @@ mod test {
         assert_eq!(messages[0].body_hash, msg3.body_hash);
         assert_eq!(messages[1].body_hash, msg4.body_hash);
     }
+
+    #[runtime::test]
+    async fn count_messages_for_peer() {
+        let conn = DbConnection::connect_memory(random::string(8)).unwrap();
+        conn.migrate().unwrap();
+        let db = StoreAndForwardDatabase::new(conn);
+        let public_key = CommsPublicKey::default();
+        let node_id = NodeId::from_public_key(&public_key);
+        let mut msg1 = NewStoredMessage::default();
+        msg1.body_hash.push('1');
+        msg1.destination_pubkey = Some(public_key.to_hex());
+        let mut msg2 = NewStoredMessage::default();
+        msg2.body_hash.push('2');
+        msg2.destination_node_id = Some(node_id.to_hex());
+        let mut msg3 = NewStoredMessage::default();
+        msg3.body_hash.push('3');
+        db.insert_message_if_unique(msg1).unwrap();
+        db.insert_message_if_unique(msg2).unwrap();
+        db.insert_message_if_unique(msg3).unwrap();
+        assert_eq!(db.count_messages().unwrap(), 3);
+        assert_eq!(db.count_messages_for_peer(&public_key, &node_id).unwrap(), 2);
+    }
 }
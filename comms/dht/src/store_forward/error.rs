@@ -91,4 +91,6 @@ pub enum StoreAndForwardError {
     SafMessagesReceivedAfterDeadline { peer: NodeId, message_age: Duration },
     #[error("Invalid SAF request: `stored_at` cannot be in the future")]
     StoredAtWasInFuture,
+    #[error("The store and forward quota for the destination peer has been reached")]
+    PeerQuotaExceeded,
 }
@@ -25,7 +25,7 @@
 type SafResult<T> = Result<T, StoreAndForwardError>;
 
 mod service;
-pub use service::{StoreAndForwardRequest, StoreAndForwardRequester, StoreAndForwardService};
+pub use service::{SafStatistics, StoreAndForwardRequest, StoreAndForwardRequester, StoreAndForwardService};
 
 mod database;
 pub use database::StoredMessage;
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryFrom, sync::Arc, time::Duration};
+use std::{collections::HashMap, convert::TryFrom, sync::Arc, time::Duration};
 
 use chrono::{DateTime, NaiveDateTime, Utc};
 use log::*;
@@ -31,6 +31,7 @@ use tari_comms::{
     PeerManager,
 };
 use tari_shutdown::ShutdownSignal;
+use tari_utilities::hex::Hex;
 use tokio::{
     sync::{mpsc, oneshot},
     task,
@@ -98,6 +99,19 @@ impl FetchStoredMessageQuery {
     }
 }
 
+/// Counters of the messages handled by the SAF actor since it was started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SafStatistics {
+    /// The number of messages stored for other peers
+    pub messages_stored: u64,
+    /// The number of stored messages returned to the peers they were stored for
+    pub messages_delivered: u64,
+    /// The number of messages that were not stored because the destination peer's quota was reached
+    pub messages_rejected: u64,
+    /// The number of stored messages removed because they expired or the storage capacity was exceeded
+    pub messages_expired: u64,
+}
+
 /// Request types for the SAF actor.
 #[derive(Debug)]
 pub enum StoreAndForwardRequest {
@@ -105,9 +119,13 @@ pub enum StoreAndForwardRequest {
     InsertMessage(NewStoredMessage, oneshot::Sender<SafResult<bool>>),
     RemoveMessages(Vec<i32>),
     RemoveMessagesOlderThan(DateTime<Utc>),
+    RemoveExpiredMessages(oneshot::Sender<SafResult<usize>>),
     SendStoreForwardRequestToPeer(NodeId),
     SendStoreForwardRequestNeighbours,
     MarkSafResponseReceived(NodeId, oneshot::Sender<Option<Duration>>),
+    GetStoredMessageCount(oneshot::Sender<SafResult<usize>>),
+    SetPeerQuota(Box<CommsPublicKey>, Option<usize>),
+    GetStatistics(oneshot::Sender<SafStatistics>),
 }
 
 /// Store and forward actor handle.
@@ -159,6 +177,47 @@ impl StoreAndForwardRequester {
         Ok(())
     }
 
+    /// Remove the stored messages whose time-to-live has expired and enforce the storage capacity now, instead of
+    /// waiting for the periodic cleanup. Returns the number of messages that were removed.
+    pub async fn remove_expired_messages(&mut self) -> SafResult<usize> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(StoreAndForwardRequest::RemoveExpiredMessages(reply_tx))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        reply_rx.await.map_err(|_| StoreAndForwardError::RequestCancelled)?
+    }
+
+    /// Returns the number of messages that this node is storing for other peers.
+    pub async fn get_stored_message_count(&mut self) -> SafResult<usize> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(StoreAndForwardRequest::GetStoredMessageCount(reply_tx))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        reply_rx.await.map_err(|_| StoreAndForwardError::RequestCancelled)?
+    }
+
+    /// Limit the number of messages stored for the given peer. Messages for the peer are not stored once the quota is
+    /// reached. `None` removes the quota.
+    pub async fn set_peer_quota(&mut self, public_key: CommsPublicKey, quota: Option<usize>) -> SafResult<()> {
+        self.sender
+            .send(StoreAndForwardRequest::SetPeerQuota(Box::new(public_key), quota))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        Ok(())
+    }
+
+    /// Returns the message counters of the SAF actor.
+    pub async fn get_statistics(&mut self) -> SafResult<SafStatistics> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(StoreAndForwardRequest::GetStatistics(reply_tx))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        reply_rx.await.map_err(|_| StoreAndForwardError::RequestCancelled)
+    }
+
     /// Send a request for SAF messages from the given peer.
     pub async fn request_saf_messages_from_peer(&mut self, node_id: NodeId) -> SafResult<()> {
         self.sender
@@ -203,6 +262,8 @@ pub struct StoreAndForwardService {
     saf_response_signal_rx: mpsc::Receiver<()>,
     event_publisher: DhtEventSender,
     local_state: SafLocalState,
+    peer_quotas: HashMap<CommsPublicKey, usize>,
+    statistics: SafStatistics,
 }
 
 impl StoreAndForwardService {
@@ -233,6 +294,8 @@ impl StoreAndForwardService {
             saf_response_signal_rx,
             event_publisher,
             local_state: Default::default(),
+            peer_quotas: HashMap::new(),
+            statistics: SafStatistics::default(),
         }
     }
 
@@ -298,6 +361,11 @@ impl StoreAndForwardService {
                 },
             },
             InsertMessage(msg, reply_tx) => {
+                if let Err(err) = self.check_peer_quota(&msg) {
+                    self.statistics.messages_rejected += 1;
+                    let _result = reply_tx.send(Err(err));
+                    return;
+                }
                 let public_key = msg.destination_pubkey.clone();
                 let node_id = msg.destination_node_id.clone();
                 match self.database.insert_message_if_unique(msg) {
@@ -309,6 +377,7 @@ impl StoreAndForwardService {
                         if existed {
                             info!(target: LOG_TARGET, "SAF message for {} already stored", pub_key);
                         } else {
+                            self.statistics.messages_stored += 1;
                             info!(target: LOG_TARGET, "Stored message for {}", pub_key);
                         }
                         let _result = reply_tx.send(Ok(existed));
@@ -342,9 +411,32 @@ impl StoreAndForwardService {
                     Err(err) => error!(target: LOG_TARGET, "RemoveMessage failed because '{:?}'", err),
                 }
             },
+            RemoveExpiredMessages(reply_tx) => {
+                let _result = reply_tx.send(self.cleanup());
+            },
             MarkSafResponseReceived(peer, reply) => {
                 let _ = reply.send(self.local_state.mark_infight_response_received(peer));
             },
+            GetStoredMessageCount(reply_tx) => {
+                let _result = reply_tx.send(self.database.count_messages().map_err(Into::into));
+            },
+            SetPeerQuota(public_key, quota) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Setting SAF quota for '{}' to {:?}", public_key, quota
+                );
+                match quota {
+                    Some(quota) => {
+                        self.peer_quotas.insert(*public_key, quota);
+                    },
+                    None => {
+                        self.peer_quotas.remove(&*public_key);
+                    },
+                }
+            },
+            GetStatistics(reply_tx) => {
+                let _result = reply_tx.send(self.statistics);
+            },
         }
     }
 
@@ -471,7 +563,7 @@ impl StoreAndForwardService {
         }
     }
 
-    fn handle_fetch_message_query(&self, query: &FetchStoredMessageQuery) -> SafResult<Vec<StoredMessage>> {
+    fn handle_fetch_message_query(&mut self, query: &FetchStoredMessageQuery) -> SafResult<Vec<StoredMessage>> {
         use SafResponseType::{Anonymous, Discovery, ForMe, Join};
         let limit = i64::try_from(self.config.max_returned_messages)
             .ok()
@@ -485,35 +577,73 @@ impl StoreAndForwardService {
             },
             Anonymous => db.find_anonymous_messages(query.since, limit)?,
         };
+        if query.response_type == ForMe {
+            self.statistics.messages_delivered += messages.len() as u64;
+        }
 
         Ok(messages)
     }
 
-    fn cleanup(&mut self) -> SafResult<()> {
+    /// Checks that storing the message would not exceed the quota of the destination peer, if it has one
+    fn check_peer_quota(&self, message: &NewStoredMessage) -> SafResult<()> {
+        let public_key = match message
+            .destination_pubkey
+            .as_ref()
+            .and_then(|p| CommsPublicKey::from_hex(p).ok())
+        {
+            Some(public_key) => public_key,
+            None => return Ok(()),
+        };
+        let quota = match self.peer_quotas.get(&public_key) {
+            Some(quota) => *quota,
+            None => return Ok(()),
+        };
+        let num_stored = self
+            .database
+            .count_messages_for_peer(&public_key, &NodeId::from_public_key(&public_key))?;
+        if num_stored >= quota {
+            debug!(
+                target: LOG_TARGET,
+                "Not storing message for '{}' because its quota of {} message(s) has been reached", public_key, quota
+            );
+            return Err(StoreAndForwardError::PeerQuotaExceeded);
+        }
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> SafResult<usize> {
         self.local_state
             .garbage_collect(self.config.max_inflight_request_age * 2);
 
-        let num_removed = self.database.delete_messages_with_priority_older_than(
+        let num_removed_low = self.database.delete_messages_with_priority_older_than(
             StoredMessagePriority::Low,
             since(self.config.low_priority_msg_storage_ttl),
         )?;
-        debug!(target: LOG_TARGET, "Cleaned {} old low priority messages", num_removed);
+        debug!(
+            target: LOG_TARGET,
+            "Cleaned {} old low priority messages", num_removed_low
+        );
 
-        let num_removed = self.database.delete_messages_with_priority_older_than(
+        let num_removed_high = self.database.delete_messages_with_priority_older_than(
             StoredMessagePriority::High,
             since(self.config.high_priority_msg_storage_ttl),
         )?;
-        debug!(target: LOG_TARGET, "Cleaned {} old high priority messages", num_removed);
+        debug!(
+            target: LOG_TARGET,
+            "Cleaned {} old high priority messages", num_removed_high
+        );
 
-        let num_removed = self.database.truncate_messages(self.config.msg_storage_capacity)?;
-        if num_removed > 0 {
+        let num_truncated = self.database.truncate_messages(self.config.msg_storage_capacity)?;
+        if num_truncated > 0 {
             debug!(
                 target: LOG_TARGET,
-                "Storage limits exceeded, removing {} oldest messages", num_removed
+                "Storage limits exceeded, removing {} oldest messages", num_truncated
             );
         }
 
-        Ok(())
+        let num_removed = num_removed_low + num_removed_high + num_truncated;
+        self.statistics.messages_expired += num_removed as u64;
+        Ok(num_removed)
     }
 
     fn publish_event(&mut self, event: DhtEvent) {
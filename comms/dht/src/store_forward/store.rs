@@ -207,8 +207,12 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Se
         message.set_saf_stored(false);
         if let Some(priority) = self.get_storage_priority(&message).await? {
             message.set_saf_stored(true);
-            let existing = self.store(priority, message.clone()).await?;
-            message.set_already_forwarded(existing);
+            match self.store(priority, message.clone()).await {
+                Ok(existing) => message.set_already_forwarded(existing),
+                // The message is not stored for the peer, but should still be processed by the rest of the pipeline
+                Err(StoreAndForwardError::PeerQuotaExceeded) => message.set_saf_stored(false),
+                Err(err) => return Err(err.into()),
+            }
         }
 
         trace!(
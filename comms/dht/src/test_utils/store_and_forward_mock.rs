@@ -36,7 +36,7 @@ use tokio::{
     sync::{mpsc, RwLock},
 };
 
-use crate::store_forward::{StoreAndForwardRequest, StoreAndForwardRequester, StoredMessage};
+use crate::store_forward::{SafStatistics, StoreAndForwardRequest, StoreAndForwardRequester, StoredMessage};
 
 const LOG_TARGET: &str = "comms::dht::discovery_mock";
 
@@ -171,6 +171,16 @@ impl StoreAndForwardMock {
             MarkSafResponseReceived(_, reply) => {
                 let _ = reply.send(*self.state.inflight_request.read().await);
             },
+            RemoveExpiredMessages(reply_tx) => {
+                let _result = reply_tx.send(Ok(0));
+            },
+            GetStoredMessageCount(reply_tx) => {
+                let _result = reply_tx.send(Ok(self.state.stored_messages.read().await.len()));
+            },
+            SetPeerQuota(_, _) => {},
+            GetStatistics(reply_tx) => {
+                let _result = reply_tx.send(SafStatistics::default());
+            },
         }
     }
 }
@@ -116,6 +116,7 @@ pub const OP_CHECK_MULTI_SIG_VERIFY: u8 = 0xaf;
 pub const OP_HASH_BLAKE256: u8 = 0xb0;
 pub const OP_HASH_SHA256: u8 = 0xb1;
 pub const OP_HASH_SHA3: u8 = 0xb2;
+pub const OP_CHECK_MULTI_SIG_VERIFY_AGGREGATE_PUB_KEY: u8 = 0xb3;
 
 // Opcode constants: Miscellaneous
 pub const OP_RETURN: u8 = 0x60;
@@ -123,6 +124,20 @@ pub const OP_IF_THEN: u8 = 0x61;
 pub const OP_ELSE: u8 = 0x62;
 pub const OP_END_IF: u8 = 0x63;
 
+/// The execution cost of an opcode that does not verify signatures
+pub const OPCODE_BASE_COST: u64 = 1;
+/// The execution cost of verifying a single signature
+pub const SIGNATURE_VERIFICATION_COST: u64 = 100;
+
+/// The consensus version that introduced an opcode. Nodes only accept opcodes of the versions that are active at the
+/// height of the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OpcodeVersion {
+    V0 = 0,
+    /// Adds CheckMultiSigVerifyAggregatePubKey
+    V1 = 1,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Opcode {
     // Block Height Checks
@@ -232,6 +247,10 @@ pub enum Opcode {
     /// Identical to CheckMultiSig, except that nothing is pushed to the stack if the m signatures are valid, and the
     /// operation fails with VERIFY_FAILED if any of the signatures are invalid.
     CheckMultiSigVerify(u8, u8, Vec<RistrettoPublicKey>, Box<Message>),
+    /// Identical to CheckMultiSigVerify, except that the sum of the public keys of the m signers is pushed to the
+    /// stack if the m signatures are valid. A script that ends with this opcode yields the aggregate key of the
+    /// signers as its script key, without having to push and add the keys separately.
+    CheckMultiSigVerifyAggregatePubKey(u8, u8, Vec<RistrettoPublicKey>, Box<Message>),
 
     // Miscellaneous
     /// Always fails with VERIFY_FAILED.
@@ -350,6 +369,10 @@ impl Opcode {
                 let (m, n, keys, msg, end) = Opcode::read_multisig_args(bytes)?;
                 Ok((CheckMultiSigVerify(m, n, keys, msg), &bytes[end..]))
             },
+            OP_CHECK_MULTI_SIG_VERIFY_AGGREGATE_PUB_KEY => {
+                let (m, n, keys, msg, end) = Opcode::read_multisig_args(bytes)?;
+                Ok((CheckMultiSigVerifyAggregatePubKey(m, n, keys, msg), &bytes[end..]))
+            },
             OP_RETURN => Ok((Return, &bytes[1..])),
             OP_IF_THEN => Ok((IfThen, &bytes[1..])),
             OP_ELSE => Ok((Else, &bytes[1..])),
@@ -458,6 +481,13 @@ impl Opcode {
                 }
                 array.extend_from_slice(msg.deref());
             },
+            CheckMultiSigVerifyAggregatePubKey(m, n, public_keys, msg) => {
+                array.extend_from_slice(&[OP_CHECK_MULTI_SIG_VERIFY_AGGREGATE_PUB_KEY, *m, *n]);
+                for public_key in public_keys {
+                    array.extend(public_key.to_vec());
+                }
+                array.extend_from_slice(msg.deref());
+            },
             Return => array.push(OP_RETURN),
             IfThen => array.push(OP_IF_THEN),
             Else => array.push(OP_ELSE),
@@ -466,6 +496,28 @@ impl Opcode {
 
         &array[n..]
     }
+
+    /// The consensus version that introduced the opcode
+    pub fn version(&self) -> OpcodeVersion {
+        match self {
+            Opcode::CheckMultiSigVerifyAggregatePubKey(..) => OpcodeVersion::V1,
+            _ => OpcodeVersion::V0,
+        }
+    }
+
+    /// The worst case cost of executing the opcode, which is dominated by the number of signatures it verifies. A
+    /// multisig opcode tries each of its m signatures against each of its n public keys.
+    pub fn execution_cost(&self) -> u64 {
+        #[allow(clippy::enum_glob_use)]
+        use Opcode::*;
+        match self {
+            CheckSig(_) | CheckSigVerify(_) => SIGNATURE_VERIFICATION_COST,
+            CheckMultiSig(m, n, ..) | CheckMultiSigVerify(m, n, ..) | CheckMultiSigVerifyAggregatePubKey(m, n, ..) => {
+                u64::from(*m) * u64::from(*n) * SIGNATURE_VERIFICATION_COST
+            },
+            _ => OPCODE_BASE_COST,
+        }
+    }
 }
 
 impl fmt::Display for Opcode {
@@ -521,6 +573,16 @@ impl fmt::Display for Opcode {
                     (*msg).to_hex()
                 ))
             },
+            CheckMultiSigVerifyAggregatePubKey(m, n, public_keys, msg) => {
+                let keys: Vec<String> = public_keys.iter().map(|p| p.to_hex()).collect();
+                fmt.write_str(&format!(
+                    "CheckMultiSigVerifyAggregatePubKey({}, {}, [{}], {})",
+                    *m,
+                    *n,
+                    keys.join(", "),
+                    (*msg).to_hex()
+                ))
+            },
             Return => fmt.write_str("Return"),
             IfThen => fmt.write_str("IfThen"),
             Else => fmt.write_str("Else"),
@@ -761,12 +823,37 @@ mod test {
              6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401)",
         );
         test_checkmultisig(
-            &Opcode::CheckMultiSigVerify(1, 2, keys, Box::new(*msg)),
+            &Opcode::CheckMultiSigVerify(1, 2, keys.clone(), Box::new(*msg)),
             OP_CHECK_MULTI_SIG_VERIFY,
             "CheckMultiSigVerify(1, 2, [9c8bc5f90d221191748e8dd7686f09e1114b4bada4c367ed58ae199c51eb100b, \
              56e9f018b138ba843521b3243a29d81730c3a4c25108b108b1ca47c2132db569], \
              6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401)",
         );
+        test_checkmultisig(
+            &Opcode::CheckMultiSigVerifyAggregatePubKey(1, 2, keys, Box::new(*msg)),
+            OP_CHECK_MULTI_SIG_VERIFY_AGGREGATE_PUB_KEY,
+            "CheckMultiSigVerifyAggregatePubKey(1, 2, \
+             [9c8bc5f90d221191748e8dd7686f09e1114b4bada4c367ed58ae199c51eb100b, \
+             56e9f018b138ba843521b3243a29d81730c3a4c25108b108b1ca47c2132db569], \
+             6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401)",
+        );
+    }
+
+    #[test]
+    fn versions_and_costs() {
+        let msg = Box::new([0u8; 32]);
+        assert_eq!(Opcode::Nop.version(), OpcodeVersion::V0);
+        assert_eq!(Opcode::Nop.execution_cost(), OPCODE_BASE_COST);
+        assert_eq!(
+            Opcode::CheckSigVerify(msg.clone()).execution_cost(),
+            SIGNATURE_VERIFICATION_COST
+        );
+        let multisig = Opcode::CheckMultiSigVerify(2, 3, vec![], msg.clone());
+        assert_eq!(multisig.version(), OpcodeVersion::V0);
+        assert_eq!(multisig.execution_cost(), 6 * SIGNATURE_VERIFICATION_COST);
+        let aggregate = Opcode::CheckMultiSigVerifyAggregatePubKey(2, 3, vec![], msg);
+        assert_eq!(aggregate.version(), OpcodeVersion::V1);
+        assert_eq!(aggregate.execution_cost(), multisig.execution_cost());
     }
 
     #[test]
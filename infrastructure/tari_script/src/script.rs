@@ -31,7 +31,7 @@ use tari_utilities::{
 };
 
 use crate::{
-    op_codes::Message,
+    op_codes::{Message, OpcodeVersion},
     slice_to_hash,
     ExecutionStack,
     HashValue,
@@ -108,6 +108,40 @@ impl TariScript {
         self.script.len()
     }
 
+    /// Returns the worst case cost of executing the script, i.e. the sum of the costs of all its opcodes regardless
+    /// of the branches taken
+    pub fn execution_cost(&self) -> u64 {
+        self.script
+            .iter()
+            .fold(0u64, |cost, opcode| cost.saturating_add(opcode.execution_cost()))
+    }
+
+    /// Returns the latest consensus version of the opcodes in the script
+    pub fn opcode_version(&self) -> OpcodeVersion {
+        self.script
+            .iter()
+            .map(Opcode::version)
+            .max()
+            .unwrap_or(OpcodeVersion::V0)
+    }
+
+    /// Returns the lowest block height at which the script can pass, as enforced by the `CheckHeightVerify` opcodes
+    /// that are executed on every path through the script. Height checks inside conditional branches are ignored, so
+    /// the script may still fail at or above this height.
+    pub fn lock_height(&self) -> u64 {
+        let mut depth = 0usize;
+        let mut lock_height = 0;
+        for opcode in &self.script {
+            match opcode {
+                Opcode::IfThen => depth += 1,
+                Opcode::EndIf => depth = depth.saturating_sub(1),
+                Opcode::CheckHeightVerify(height) if depth == 0 => lock_height = lock_height.max(*height),
+                _ => {},
+            }
+        }
+        lock_height
+    }
+
     fn should_execute(&self, opcode: &Opcode, state: &ExecutionState) -> Result<bool, ScriptError> {
         use Opcode::{Else, EndIf, IfThen};
         match opcode {
@@ -247,19 +281,30 @@ impl TariScript {
                 }
             },
             CheckMultiSig(m, n, public_keys, msg) => {
-                if self.check_multisig(stack, *m, *n, public_keys, *msg.deref())? {
+                if self.check_multisig(stack, *m, *n, public_keys, *msg.deref())?.is_some() {
                     stack.push(Number(1))
                 } else {
                     stack.push(Number(0))
                 }
             },
             CheckMultiSigVerify(m, n, public_keys, msg) => {
-                if self.check_multisig(stack, *m, *n, public_keys, *msg.deref())? {
+                if self.check_multisig(stack, *m, *n, public_keys, *msg.deref())?.is_some() {
                     Ok(())
                 } else {
                     Err(ScriptError::VerifyFailed)
                 }
             },
+            CheckMultiSigVerifyAggregatePubKey(m, n, public_keys, msg) => {
+                match self.check_multisig(stack, *m, *n, public_keys, *msg.deref())? {
+                    Some(signers) => {
+                        let aggregate = signers
+                            .iter()
+                            .fold(RistrettoPublicKey::default(), |aggregate, signer| &aggregate + *signer);
+                        stack.push(PublicKey(aggregate))
+                    },
+                    None => Err(ScriptError::VerifyFailed),
+                }
+            },
             Return => Err(ScriptError::Return),
             IfThen => TariScript::handle_if_then(stack, state),
             Else => TariScript::handle_else(state),
@@ -496,14 +541,15 @@ impl TariScript {
         }
     }
 
-    fn check_multisig(
+    /// Returns the public keys of the m signers if the m signatures on the stack are valid
+    fn check_multisig<'a>(
         &self,
         stack: &mut ExecutionStack,
         m: u8,
         n: u8,
-        public_keys: &[RistrettoPublicKey],
+        public_keys: &'a [RistrettoPublicKey],
         message: Message,
-    ) -> Result<bool, ScriptError> {
+    ) -> Result<Option<Vec<&'a RistrettoPublicKey>>, ScriptError> {
         if m == 0 || n == 0 || m > n || n > MAX_MULTISIG_LIMIT {
             return Err(ScriptError::InvalidData);
         }
@@ -531,11 +577,20 @@ impl TariScript {
                 }
             }
             if !sig_set.contains(s) {
-                return Ok(false);
+                return Ok(None);
             }
         }
 
-        Ok(sig_set.len() == m)
+        if sig_set.len() != m {
+            return Ok(None);
+        }
+        let signers = public_keys
+            .iter()
+            .zip(key_signed)
+            .filter(|(_, signed)| *signed)
+            .map(|(pk, _)| pk)
+            .collect();
+        Ok(Some(signers))
     }
 }
 
@@ -608,7 +663,7 @@ mod test {
     use crate::{
         error::ScriptError,
         inputs,
-        op_codes::{slice_to_boxed_hash, slice_to_boxed_message, HashValue, Message},
+        op_codes::{slice_to_boxed_hash, slice_to_boxed_message, HashValue, Message, OpcodeVersion},
         ExecutionStack,
         ScriptContext,
         StackItem,
@@ -1352,6 +1407,62 @@ mod test {
         let result = script.execute(&inputs).unwrap();
         assert_eq!(result, Number(1));
     }
+    #[test]
+    fn check_multisig_verify_aggregate_pub_key() {
+        use crate::{op_codes::Opcode::CheckMultiSigVerifyAggregatePubKey, StackItem::PublicKey};
+        let (msg, data) = multisig_data(3);
+        let keys = data.iter().map(|(_, p, _)| p.clone()).collect::<Vec<_>>();
+
+        // 2 of 3, signed by the first and the last
+        let script = script!(CheckMultiSigVerifyAggregatePubKey(2, 3, keys.clone(), msg.clone()));
+        let inputs = inputs!(data[0].2.clone(), data[2].2.clone());
+        let result = script.execute(&inputs).unwrap();
+        assert_eq!(result, PublicKey(&data[0].1 + &data[2].1));
+
+        // the same signature twice
+        let inputs = inputs!(data[0].2.clone(), data[0].2.clone());
+        let err = script.execute(&inputs).unwrap_err();
+        assert_eq!(err, ScriptError::VerifyFailed);
+
+        // a signature by a key that is not in the script
+        let (_, other) = multisig_data(1);
+        let inputs = inputs!(data[0].2.clone(), other[0].2.clone());
+        let err = script.execute(&inputs).unwrap_err();
+        assert_eq!(err, ScriptError::VerifyFailed);
+
+        // m > n
+        let script = script!(CheckMultiSigVerifyAggregatePubKey(4, 3, keys, msg));
+        let inputs = inputs!(data[0].2.clone());
+        let err = script.execute(&inputs).unwrap_err();
+        assert_eq!(err, ScriptError::InvalidData);
+    }
+
+    #[test]
+    fn execution_cost_and_opcode_version() {
+        use crate::op_codes::{OPCODE_BASE_COST, SIGNATURE_VERIFICATION_COST};
+        let (msg, data) = multisig_data(2);
+        let keys = data.into_iter().map(|(_, p, _)| p).collect::<Vec<_>>();
+        let script = script!(Dup CheckMultiSigVerify(1, 2, keys.clone(), msg.clone()));
+        assert_eq!(
+            script.execution_cost(),
+            OPCODE_BASE_COST + 2 * SIGNATURE_VERIFICATION_COST
+        );
+        assert_eq!(script.opcode_version(), OpcodeVersion::V0);
+        let script = script!(CheckMultiSigVerifyAggregatePubKey(1, 2, keys, msg));
+        assert_eq!(script.execution_cost(), 2 * SIGNATURE_VERIFICATION_COST);
+        assert_eq!(script.opcode_version(), OpcodeVersion::V1);
+    }
+
+    #[test]
+    fn lock_height() {
+        assert_eq!(script!(Nop).lock_height(), 0);
+        let script = script!(CheckHeightVerify(5) CheckHeightVerify(10) CheckHeight(20) Drop);
+        assert_eq!(script.lock_height(), 10);
+        // Only one of the branches is locked, so the script as a whole is not
+        let script = script!(PushOne IfThen CheckHeightVerify(100) Else Nop EndIf CheckHeightVerify(7));
+        assert_eq!(script.lock_height(), 7);
+    }
+
     #[test]
     fn add_partial_signatures() {
         use crate::StackItem::Number;